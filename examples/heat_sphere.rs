@@ -0,0 +1,102 @@
+use eyre::eyre;
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{
+    Density, ElementLaplaceBeltramiAssembler, ElementSurfaceMassAssembler, UniformQuadratureTable,
+};
+use fenris::connectivity::Tri6d3Connectivity;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::mesh::{Mesh3d, Tet10Mesh, Tet4Mesh};
+use fenris::nalgebra::{DMatrix, DVector, Point3, Vector3};
+use fenris::quadrature;
+
+/// Radius of the sphere.
+const RADIUS: f64 = 1.0;
+
+/// Solves the heat equation du/dt = Δ_Γ u on the unit sphere and compares the solution with the
+/// exact solution.
+///
+/// Spherical harmonics of degree l are eigenfunctions of the Laplace-Beltrami operator with
+/// eigenvalue -l (l + 1) / R^2. Starting from a combination of harmonics of degree 1 and 2,
+/// each component therefore decays exponentially at its own rate.
+fn main() -> eyre::Result<()> {
+    let mesh = create_sphere_mesh(RADIUS, 4);
+    let (stiffness, mass) = assemble_matrices(&mesh)?;
+
+    let initial_condition = |x: &Point3<f64>| x.z + x.x * x.y;
+    let exact_solution = |x: &Point3<f64>, t: f64| {
+        let r2 = RADIUS * RADIUS;
+        (-2.0 * t / r2).exp() * x.z + (-6.0 * t / r2).exp() * x.x * x.y
+    };
+
+    // Crank-Nicolson time stepping: (M + dt/2 A) u^{n+1} = (M - dt/2 A) u^n
+    let dt = 0.01;
+    let num_steps = 50;
+    let lhs = &mass + 0.5 * dt * &stiffness;
+    let rhs_matrix = &mass - 0.5 * dt * &stiffness;
+    // The system matrix is symmetric positive definite, so we can use a Cholesky factorization
+    let cholesky = lhs
+        .cholesky()
+        .ok_or_else(|| eyre!("Failed to factorize system matrix"))?;
+
+    let mut u = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(initial_condition));
+    for _ in 0..num_steps {
+        u = cholesky.solve(&(&rhs_matrix * &u));
+    }
+
+    // Measure the error in the (discrete) L2 norm induced by the mass matrix
+    let t = dt * num_steps as f64;
+    let u_exact = DVector::from_iterator(
+        mesh.vertices().len(),
+        mesh.vertices().iter().map(|x| exact_solution(x, t)),
+    );
+    let error = &u - &u_exact;
+    let l2_norm = |v: &DVector<f64>| v.dot(&(&mass * v)).sqrt();
+    println!(
+        "Relative L2 error at t = {t}: {:.3e}",
+        l2_norm(&error) / l2_norm(&u_exact)
+    );
+
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_title("Heat equation on a sphere")
+        .with_point_scalar_attributes("u", 1, u.as_slice())
+        .with_point_scalar_attributes("u_exact", 1, u_exact.as_slice())
+        .try_export("heat_sphere.vtu")?;
+
+    Ok(())
+}
+
+/// Creates a quadratic triangle mesh of the sphere by projecting the boundary of a tetrahedral
+/// box mesh onto the sphere.
+fn create_sphere_mesh(radius: f64, resolution: usize) -> Mesh3d<f64, Tri6d3Connectivity> {
+    let box_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(resolution);
+    let mut mesh = Tet10Mesh::from(&box_mesh)
+        .extract_boundary_mesh()
+        .into_mesh();
+    for v in mesh.vertices_mut() {
+        let x = v.coords - Vector3::repeat(0.5);
+        *v = Point3::from(x.normalize() * radius);
+    }
+    mesh
+}
+
+fn assemble_matrices(mesh: &Mesh3d<f64, Tri6d3Connectivity>) -> eyre::Result<(DMatrix<f64>, DMatrix<f64>)> {
+    // Quadrature rules for surface elements are defined on the reference domain of the elements,
+    // which for triangles in 3D is the 2D reference triangle
+    let quadrature = quadrature::total_order::triangle(4)?;
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature.clone());
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature, Density(1.0));
+
+    let stiffness_assembler = ElementLaplaceBeltramiAssembler::with_solution_dim(1)
+        .with_space(mesh)
+        .with_quadrature_table(&stiffness_qtable);
+    let mass_assembler = ElementSurfaceMassAssembler::with_solution_dim(1)
+        .with_space(mesh)
+        .with_quadrature_table(&mass_qtable);
+
+    // TODO: Use sparse solver
+    let matrix_assembler = CsrAssembler::default();
+    let stiffness = DMatrix::from(&matrix_assembler.assemble(&stiffness_assembler)?);
+    let mass = DMatrix::from(&matrix_assembler.assemble(&mass_assembler)?);
+    Ok((stiffness, mass))
+}
@@ -32,6 +32,10 @@ where
     pub fn from_vertices(vertices: [Point3<T>; 8]) -> Self {
         Self { vertices }
     }
+
+    pub fn vertices(&self) -> &[Point3<T>; 8] {
+        &self.vertices
+    }
 }
 
 impl<T> Hexahedron<T>
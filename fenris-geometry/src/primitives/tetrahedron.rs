@@ -28,6 +28,10 @@ where
     pub fn from_vertices(vertices: [Point3<T>; 4]) -> Self {
         Self { vertices }
     }
+
+    pub fn vertices(&self) -> &[Point3<T>; 4] {
+        &self.vertices
+    }
 }
 
 impl<T> Tetrahedron<T>
@@ -1,6 +1,5 @@
 use crate::calculus::{DifferentiableVectorFunction, VectorFunction};
 use fenris_traits::Real;
-use itertools::iterate;
 use log::debug;
 use nalgebra::{DVector, DVectorView, DVectorViewMut, Scalar};
 use numeric_literals::replace_float_literals;
@@ -20,7 +19,38 @@ where
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct NewtonSettings<T> {
     pub max_iterations: Option<usize>,
+    /// Absolute tolerance for the norm of the residual.
     pub tolerance: T,
+    /// Tolerance for the norm of the residual relative to the norm of the initial residual.
+    pub relative_tolerance: T,
+    /// Optional tolerance for the norm of the increment taken in a single iteration.
+    pub increment_tolerance: Option<T>,
+}
+
+/// The criterion by which the Newton iterations were deemed converged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConvergenceReason {
+    ResidualNorm,
+    IncrementNorm,
+}
+
+/// Diagnostics for a single Newton iteration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NewtonIteration<T> {
+    /// The norm of the residual after the iteration.
+    pub residual_norm: T,
+    /// The norm of the increment taken in the iteration.
+    pub increment_norm: T,
+    /// The step length accepted by the line search.
+    pub step_length: T,
+}
+
+/// Summary of a converged Newton solve.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NewtonConvergence<T> {
+    pub initial_residual_norm: T,
+    pub iterations: usize,
+    pub converged_by: ConvergenceReason,
 }
 
 #[derive(Debug)]
@@ -54,7 +84,9 @@ impl Error for NewtonError {}
 /// Attempts to solve the non-linear equation F(u) = 0.
 ///
 /// No heap allocation is performed. The solution is said to have converged if
-/// ```|F(u)|_2 <= tolerance```.
+/// ```|F(u)|_2 <= tolerance + relative_tolerance * |F(u_0)|_2```,
+/// or, if an increment tolerance is given, if the norm of the increment taken in an iteration
+/// is below the increment tolerance.
 ///
 /// If successful, returns the number of iterations performed.
 #[replace_float_literals(T::from_f64(literal).unwrap())]
@@ -73,15 +105,33 @@ where
 }
 
 /// Same as `newton`, but allows specifying a line search.
-#[replace_float_literals(T::from_f64(literal).unwrap())]
 pub fn newton_line_search<'a, T, F>(
-    mut function: F,
+    function: F,
     x: impl Into<DVectorViewMut<'a, T>>,
     f: impl Into<DVectorViewMut<'a, T>>,
     dx: impl Into<DVectorViewMut<'a, T>>,
     settings: NewtonSettings<T>,
     line_search: &mut impl LineSearch<T, F>,
 ) -> Result<usize, NewtonError>
+where
+    T: Real,
+    F: DifferentiableVectorFunction<T>,
+{
+    newton_with_callback(function, x, f, dx, settings, line_search, |_| {}).map(|convergence| convergence.iterations)
+}
+
+/// Same as `newton_line_search`, but calls `callback` after each iteration and returns
+/// a summary of the solve.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn newton_with_callback<'a, T, F>(
+    mut function: F,
+    x: impl Into<DVectorViewMut<'a, T>>,
+    f: impl Into<DVectorViewMut<'a, T>>,
+    dx: impl Into<DVectorViewMut<'a, T>>,
+    settings: NewtonSettings<T>,
+    line_search: &mut impl LineSearch<T, F>,
+    mut callback: impl FnMut(&NewtonIteration<T>),
+) -> Result<NewtonConvergence<T>, NewtonError>
 where
     T: Real,
     F: DifferentiableVectorFunction<T>,
@@ -95,9 +145,13 @@ where
 
     function.eval_into(&mut f, &DVectorView::from(&x));
 
+    let initial_residual_norm = f.norm();
+    let tolerance = settings.tolerance + settings.relative_tolerance * initial_residual_norm;
+    let mut residual_norm = initial_residual_norm;
+    let mut converged_by = ConvergenceReason::ResidualNorm;
     let mut iter = 0;
 
-    while f.norm() > settings.tolerance {
+    while residual_norm > tolerance {
         if settings
             .max_iterations
             .map(|max_iter| iter == max_iter)
@@ -126,9 +180,30 @@ where
             .map_err(|err| NewtonError::LineSearchError(err))?;
         debug!("Newton step length at iter {}: {}", iter, step_length);
         iter += 1;
+
+        residual_norm = f.norm();
+        let iteration = NewtonIteration {
+            residual_norm,
+            increment_norm: step_length * dx.norm(),
+            step_length,
+        };
+        callback(&iteration);
+
+        if residual_norm > tolerance
+            && settings
+                .increment_tolerance
+                .is_some_and(|tol| iteration.increment_norm <= tol)
+        {
+            converged_by = ConvergenceReason::IncrementNorm;
+            break;
+        }
     }
 
-    Ok(iter)
+    Ok(NewtonConvergence {
+        initial_residual_norm,
+        iterations: iter,
+        converged_by,
+    })
 }
 
 pub trait LineSearch<T: Scalar, F: VectorFunction<T>> {
@@ -167,11 +242,33 @@ where
 
 /// Standard backtracking line search using the Armijo condition.
 ///
+/// The step length $\alpha$ starts at $1$ and is multiplied by the contraction factor until
+/// <div>$$
+/// \\| F(x + \alpha p) \\|^2 \leq (1 - 2 c \alpha) \\| F(x) \\|^2,
+/// $$</div>
+/// which is the Armijo condition for the merit function $\frac{1}{2} \\| F \\|^2$ with
+/// sufficient decrease parameter $c$. The line search fails if the step length drops below
+/// the minimum step length.
+///
 /// See Jorge & Nocedal (2006), Numerical Optimization, Chapter 3.1.
-/// #[derive(Clone, Debug)]
-pub struct BacktrackingLineSearch;
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BacktrackingLineSearch<T> {
+    pub sufficient_decrease: T,
+    pub contraction_factor: T,
+    pub min_step_length: T,
+}
+
+impl<T: Real> Default for BacktrackingLineSearch<T> {
+    fn default() -> Self {
+        Self {
+            sufficient_decrease: T::from_f64(1e-4).unwrap(),
+            contraction_factor: T::from_f64(0.5).unwrap(),
+            min_step_length: T::from_f64(1e-8).unwrap(),
+        }
+    }
+}
 
-impl<T, F> LineSearch<T, F> for BacktrackingLineSearch
+impl<T, F> LineSearch<T, F> for BacktrackingLineSearch<T>
 where
     T: Real,
     F: VectorFunction<T>,
@@ -189,61 +286,45 @@ where
         // by minimizing
         //  g(x) = (1/2) || F(x) ||^2
         // We have that
-        //  grad g = (grad F) * F,
+        //  grad g = (grad F)^T * F,
         // and the sufficient decrease condition becomes
         //  g(x_k + alpha * p_k) <= g(x_k) + c * alpha * (grad g)^T * p_k
-        //                       ~= g(x_k) - c * alpha * g(x_k)
-        //                        = (1 - c * alpha) * g(x_k)
+        //                       ~= g(x_k) - 2 * c * alpha * g(x_k)
+        //                        = (1 - 2 * c * alpha) * g(x_k)
         // where p_k is the step direction, c is a parameter in (0, 1)
         // and we have assumed that
-        //  grad F^T p_k ~= - F(x_k)
+        //  grad F p_k ~= - F(x_k)
         // (which it would satisfy if p_k is the exact solution of the Newton step equation).
-
-        // TODO: Is this an OK parameter? Should anyway make it configurable
-        let c = 1e-4;
-        let alpha_min = 1e-6;
-
+        let c = self.sufficient_decrease;
         let p = direction;
         let g_initial = 0.5 * f.magnitude_squared();
 
-        // Start out with some alphas that don't decrease too quickly, then
-        // start decreasing them much faster if the first few iterations don't let us
-        // take a step.
-        let initial_alphas = [0.0, 1.0, 0.75, 0.5];
-        let mut alpha_iter = initial_alphas
-            .iter()
-            .copied()
-            .chain(iterate(0.25, |alpha_i| 0.25 * *alpha_i));
-
-        let mut alpha_prev = alpha_iter.next().unwrap();
-        let mut alpha = alpha_iter.next().unwrap();
+        let mut alpha_prev = 0.0;
+        let mut alpha = 1.0;
 
         loop {
-            let delta_alpha = alpha - alpha_prev;
-
             // We have that x^{k + 1} = x^0 + alpha^k * p,
             // where x^{k+1} is the value of x after taking the step based on the current alpha
             // parameter. It is straightforward to show that this implies that
             //  x^{k + 1} = x^k + (alpha^k - alpha^{k - 1}) * p,
             // which is far more amenable to computation
-            x.axpy(delta_alpha, &p, T::one());
+            x.axpy(alpha - alpha_prev, &p, T::one());
             function.eval_into(&mut f, &DVectorView::from(&x));
 
             let g = 0.5 * f.magnitude_squared();
-            if g <= (1.0 - c * alpha) * g_initial {
-                break;
-            } else if alpha < alpha_min {
+            if g <= (1.0 - 2.0 * c * alpha) * g_initial {
+                return Ok(alpha);
+            }
+
+            alpha_prev = alpha;
+            alpha *= self.contraction_factor;
+            if alpha < self.min_step_length {
                 return Err(Box::from(format!(
-                    "Failed to produce valid step direction.\
+                    "Failed to produce valid step direction. \
                     Alpha {} is smaller than minimum allowed alpha {}.",
-                    alpha, alpha_min
+                    alpha, self.min_step_length
                 )));
-            } else {
-                alpha_prev = alpha;
-                alpha = alpha_iter.next().unwrap();
             }
         }
-
-        Ok(alpha)
     }
 }
@@ -1,4 +1,4 @@
-use fenris_optimize::calculus::{DifferentiableVectorFunction, VectorFunction};
+use fenris_optimize::calculus::{DifferentiableVectorFunction, VectorFunction, VectorFunctionBuilder};
 use fenris_optimize::newton::*;
 use nalgebra::{DVector, DVectorView, DVectorViewMut, Matrix3, Vector3};
 use numeric_literals::replace_numeric_literals;
@@ -44,6 +44,8 @@ fn newton_converges_in_single_iteration_for_linear_system() {
     let settings = NewtonSettings {
         max_iterations: Some(2),
         tolerance: Vector3::new(1.0, 2.0, 3.0).norm() * 1e-6,
+        relative_tolerance: 0.0,
+        increment_tolerance: None,
     };
 
     let mut f = DVector::zeros(3);
@@ -56,3 +58,44 @@ fn newton_converges_in_single_iteration_for_linear_system() {
     assert!(diff.norm() < 1e-6);
     assert_eq!(iterations, 1);
 }
+
+#[test]
+fn newton_backtracking_line_search_globalizes_convergence() {
+    // Newton's method diverges for atan(x) = 0 without globalization for this initial guess
+    let function = VectorFunctionBuilder::with_dimension(1)
+        .with_function(|f: &mut DVectorViewMut<f64>, x: &DVectorView<f64>| f[0] = x[0].atan())
+        .with_jacobian_solver(
+            |sol: &mut DVectorViewMut<f64>, x: &DVectorView<f64>, rhs: &DVectorView<f64>| {
+                sol[0] = rhs[0] * (1.0 + x[0] * x[0]);
+                Ok(())
+            },
+        );
+    let settings = NewtonSettings {
+        max_iterations: Some(20),
+        tolerance: 1e-12,
+        relative_tolerance: 0.0,
+        increment_tolerance: None,
+    };
+
+    let mut f = DVector::zeros(1);
+    let mut x = DVector::from_element(1, 3.0);
+    let mut dx = DVector::zeros(1);
+    let mut iterations = Vec::new();
+    let convergence = newton_with_callback(
+        function,
+        &mut x,
+        &mut f,
+        &mut dx,
+        settings,
+        &mut BacktrackingLineSearch::default(),
+        |iteration| iterations.push(*iteration),
+    )
+    .expect("Newton iterations must succeed");
+
+    assert!(x[0].abs() <= 1e-12);
+    assert_eq!(convergence.converged_by, ConvergenceReason::ResidualNorm);
+    assert_eq!(convergence.initial_residual_norm, 3.0f64.atan());
+    assert_eq!(convergence.iterations, iterations.len());
+    assert!(iterations[0].step_length < 1.0);
+    assert_eq!(iterations.last().unwrap().step_length, 1.0);
+}
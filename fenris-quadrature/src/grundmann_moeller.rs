@@ -0,0 +1,97 @@
+//! Grundmann–Möller quadrature rules for simplices, computed at runtime.
+//!
+//! This module contains the rules published in the paper
+//!
+//! ```text
+//! Grundmann, Axel, and H. M. Möller.
+//! "Invariant integration formulas for the n-simplex by combinatorial methods."
+//! SIAM Journal on Numerical Analysis 15, no. 2 (1978): 282-290.
+//! ```
+//!
+//! The rules are available for arbitrary strength and dimension, since they are given by an
+//! explicit formula. However, they contain negative weights for strength 2 and higher, and they
+//! generally require more points than the rules in [`polyquad`](crate::polyquad). The magnitude
+//! of the negative weights grows with the strength, which can lead to loss of precision for
+//! high strengths. The rules are therefore mostly useful when the strength required
+//! exceeds what is available from tabulated rules.
+//!
+//! The points are symmetric with respect to permutations of the barycentric coordinates.
+
+use crate::Rule;
+
+/// Creates a Grundmann–Möller rule for the reference triangle with the provided strength.
+///
+/// The returned rule is the smallest rule of the family with sufficient accuracy. Since the
+/// rules have odd strength, the strength of the returned rule may exceed the requested strength
+/// by one.
+pub fn triangle(strength: usize) -> Rule<2> {
+    simplex(strength)
+}
+
+/// Creates a Grundmann–Möller rule for the reference tetrahedron with the provided strength.
+///
+/// The returned rule is the smallest rule of the family with sufficient accuracy. Since the
+/// rules have odd strength, the strength of the returned rule may exceed the requested strength
+/// by one.
+pub fn tetrahedron(strength: usize) -> Rule<3> {
+    simplex(strength)
+}
+
+/// Creates a Grundmann–Möller rule for the `D`-dimensional reference simplex with the provided
+/// strength.
+///
+/// The reference simplex is comprised of the vertices $(-1, \dots, -1)$ and
+/// $-\vec 1 + 2 \vec e_k$ for $k = 1, \dots, D$, which coincides with the reference
+/// segment, triangle and tetrahedron for $D = 1, 2, 3$.
+///
+/// # Panics
+///
+/// Panics if `D` is zero.
+pub fn simplex<const D: usize>(strength: usize) -> Rule<D> {
+    assert!(D > 0, "Dimension must be positive");
+    // The rule with index s has strength 2 s + 1
+    let s = strength / 2;
+    let d = 2 * s + 1;
+    let n = D;
+
+    // The formula is stated for the unit simplex, which is mapped to the reference simplex
+    // by x = 2 lambda - 1 with Jacobian determinant 2^n
+    let scale = 2.0f64.powi(n as i32) / 2.0f64.powi(2 * s as i32);
+    let mut weights = Vec::new();
+    let mut points = Vec::new();
+    let mut beta = vec![0; n + 1];
+    for i in 0..=s {
+        let denominator = (d + n - 2 * i) as f64;
+        let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+        let weight = sign * scale * denominator.powi(d as i32) / (factorial(i) * factorial(d + n - i));
+        for_each_composition(&mut beta, 0, s - i, &mut |beta| {
+            let mut point = [0.0; D];
+            for (x_k, &beta_k) in point.iter_mut().zip(&beta[1..]) {
+                let lambda_k = (2 * beta_k + 1) as f64 / denominator;
+                *x_k = 2.0 * lambda_k - 1.0;
+            }
+            weights.push(weight);
+            points.push(point);
+        });
+    }
+
+    (weights, points)
+}
+
+fn factorial(n: usize) -> f64 {
+    (1..=n).map(|k| k as f64).product()
+}
+
+/// Calls `f` with every assignment of non-negative integers to `entries[index..]`
+/// that sums to `total`.
+fn for_each_composition(entries: &mut [usize], index: usize, total: usize, f: &mut impl FnMut(&[usize])) {
+    if index + 1 == entries.len() {
+        entries[index] = total;
+        f(entries);
+    } else {
+        for value in 0..=total {
+            entries[index] = value;
+            for_each_composition(entries, index + 1, total - value, f);
+        }
+    }
+}
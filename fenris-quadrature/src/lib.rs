@@ -70,9 +70,11 @@ doc = ::embed_doc_image::embed_image!("ref_prism", "assets/reference_prism.svg")
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+pub mod grundmann_moeller;
 pub mod polyquad;
 pub mod tensor;
 pub mod univariate;
+pub mod verification;
 
 /// Library-wide error type.
 #[derive(Debug, Clone, PartialEq)]
@@ -94,6 +96,68 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
+/// A family of quadrature rules for the reference triangle and tetrahedron.
+///
+/// The families offer different trade-offs between the number of points, the positivity of
+/// the weights and the maximum available strength.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use fenris_quadrature::SimplexRuleFamily;
+///
+/// // Polyquad rules are only tabulated up to a certain strength
+/// assert!(SimplexRuleFamily::Polyquad.triangle(25).is_err());
+/// let (weights, _) = SimplexRuleFamily::GrundmannMoeller.triangle(25)?;
+/// assert!(weights.iter().any(|&w| w < 0.0));
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum SimplexRuleFamily {
+    /// Tabulated symmetric rules with positive weights, see [`polyquad`].
+    #[default]
+    Polyquad,
+    /// Rules with negative weights that are available for any strength, see [`grundmann_moeller`].
+    GrundmannMoeller,
+}
+
+impl SimplexRuleFamily {
+    /// Returns the smallest rule of the family for the reference triangle with sufficient strength.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the family has no rule with sufficient strength.
+    pub fn triangle(&self, strength: usize) -> Result<Rule<2>, Error> {
+        match self {
+            Self::Polyquad => polyquad::triangle(strength),
+            Self::GrundmannMoeller => Ok(grundmann_moeller::triangle(strength)),
+        }
+    }
+
+    /// Returns the smallest rule of the family for the reference tetrahedron with sufficient
+    /// strength.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the family has no rule with sufficient strength.
+    pub fn tetrahedron(&self, strength: usize) -> Result<Rule<3>, Error> {
+        match self {
+            Self::Polyquad => polyquad::tetrahedron(strength),
+            Self::GrundmannMoeller => Ok(grundmann_moeller::tetrahedron(strength)),
+        }
+    }
+
+    /// Returns whether all rules of the family have positive weights.
+    pub fn has_positive_weights(&self) -> bool {
+        match self {
+            Self::Polyquad => true,
+            Self::GrundmannMoeller => false,
+        }
+    }
+}
+
 /// A D-dimensional point.
 pub type Point<const D: usize> = [f64; D];
 
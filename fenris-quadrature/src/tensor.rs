@@ -3,7 +3,7 @@
 //! For quadrilaterals and hexahedra, quadrature rules can be constructed as tensor products
 //! of 1D rules. This module provides rules constructed in this fashion.
 
-use crate::univariate::gauss;
+use crate::univariate::{gauss, try_gauss_lobatto};
 use crate::Rule;
 
 /// A Gauss quadrature rule for the reference quadrilateral.
@@ -11,8 +11,43 @@ use crate::Rule;
 /// The rule is constructed as a tensor product from 1D rules, with the provided number of
 /// points per dimension.
 pub fn quadrilateral_gauss(num_points_per_dim: usize) -> Rule<2> {
-    let n = num_points_per_dim;
-    let (weights1d, points1d) = gauss(n);
+    tensor_product_2d(gauss(num_points_per_dim))
+}
+
+/// A Gauss quadrature rule for the reference hexahedron.
+///
+/// The rule is constructed as a tensor product from 1D rules, with the provided number of
+/// points per dimension.
+pub fn hexahedron_gauss(num_points_per_dim: usize) -> Rule<3> {
+    tensor_product_3d(gauss(num_points_per_dim))
+}
+
+/// A Gauss-Lobatto quadrature rule for the reference quadrilateral.
+///
+/// The rule is constructed as a tensor product from 1D
+/// [Gauss-Lobatto rules](crate::univariate::try_gauss_lobatto), with the provided number of
+/// points per dimension. With `n` points per dimension, the points coincide with the nodes of
+/// a tensor-product Lagrange element of order `n - 1` with Gauss-Lobatto nodes, such as the
+/// vertices of a bilinear quadrilateral for `n = 2` or the nodes of a biquadratic quadrilateral
+/// for `n = 3`. Using the rule for the mass matrix of such an element therefore gives a diagonal
+/// mass matrix, as in spectral element methods.
+///
+/// Returns `None` if `n < 2` or a rule with that size is not available.
+pub fn try_quadrilateral_gauss_lobatto(num_points_per_dim: usize) -> Option<Rule<2>> {
+    try_gauss_lobatto(num_points_per_dim).map(tensor_product_2d)
+}
+
+/// A Gauss-Lobatto quadrature rule for the reference hexahedron.
+///
+/// See [`try_quadrilateral_gauss_lobatto`] for more information.
+///
+/// Returns `None` if `n < 2` or a rule with that size is not available.
+pub fn try_hexahedron_gauss_lobatto(num_points_per_dim: usize) -> Option<Rule<3>> {
+    try_gauss_lobatto(num_points_per_dim).map(tensor_product_3d)
+}
+
+fn tensor_product_2d((weights1d, points1d): Rule<1>) -> Rule<2> {
+    let n = weights1d.len();
     let mut weights2d = Vec::with_capacity(n * n);
     let mut points2d = Vec::with_capacity(n * n);
 
@@ -29,13 +64,8 @@ pub fn quadrilateral_gauss(num_points_per_dim: usize) -> Rule<2> {
     (weights2d, points2d)
 }
 
-/// A Gauss quadrature rule for the reference hexahedron.
-///
-/// The rule is constructed as a tensor product from 1D rules, with the provided number of
-/// points per dimension.
-pub fn hexahedron_gauss(num_points_per_dim: usize) -> Rule<3> {
-    let n = num_points_per_dim;
-    let (weights1d, points1d) = gauss(n);
+fn tensor_product_3d((weights1d, points1d): Rule<1>) -> Rule<3> {
+    let n = weights1d.len();
     let mut weights3d = Vec::with_capacity(n * n * n);
     let mut points3d = Vec::with_capacity(n * n * n);
 
@@ -0,0 +1,272 @@
+//! Verification of quadrature rules against exact monomial integrals.
+//!
+//! A quadrature rule of strength $p$ on a reference domain integrates all monomials
+//! $x^\alpha y^\beta z^\gamma$ with total degree $\alpha + \beta + \gamma \leq p$ exactly.
+//! The functions in this module check this property for arbitrary rules, which catches
+//! tabulation and transcription errors when adding new sources of quadrature rules.
+//!
+//! The exact monomial integrals are computed with collapsed tensor-product Gauss rules
+//! (see [`ReferenceDomain::integrate_monomial`]), which are exact for polynomials and
+//! independent of any tabulated data.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use fenris_quadrature::polyquad;
+//! use fenris_quadrature::verification::{verify_rule_family, ReferenceDomain};
+//!
+//! // Verify every available polyquad rule for the reference triangle
+//! let num_rules = verify_rule_family(ReferenceDomain::Triangle, polyquad::triangle, 1e-13).unwrap();
+//! assert_eq!(num_rules, 21);
+//! ```
+use crate::univariate::gauss;
+use crate::{integrate, Error, Point, Rule};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The reference domains for which quadrature rules are available.
+///
+/// See the [crate-level documentation](crate) for the definition of each domain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceDomain {
+    Segment,
+    Triangle,
+    Quadrilateral,
+    Tetrahedron,
+    Hexahedron,
+    Prism,
+    Pyramid,
+}
+
+impl ReferenceDomain {
+    /// Returns the dimension of the domain.
+    pub fn dim(&self) -> usize {
+        match self {
+            Self::Segment => 1,
+            Self::Triangle | Self::Quadrilateral => 2,
+            Self::Tetrahedron | Self::Hexahedron | Self::Prism | Self::Pyramid => 3,
+        }
+    }
+
+    /// Computes the exact integral of the monomial with the given exponents over the domain.
+    ///
+    /// For example, the exponents `[1, 2]` correspond to the monomial $x y^2$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of exponents does not match the dimension of the domain.
+    pub fn integrate_monomial<const D: usize>(&self, exponents: [usize; D]) -> f64 {
+        let degree = exponents.iter().sum();
+        let rule = self.exact_rule::<D>(degree);
+        integrate(&rule, |x| evaluate_monomial(&exponents, x))
+    }
+
+    /// Constructs a rule with positive weights that integrates polynomials of the given total
+    /// degree exactly.
+    ///
+    /// Simplices and pyramids are mapped onto hypercubes by collapsing coordinates, and the
+    /// resulting polynomial integrands (including the Jacobian determinant of the collapse)
+    /// are integrated with tensor-product Gauss rules.
+    fn exact_rule<const D: usize>(&self, degree: usize) -> Rule<D> {
+        assert_eq!(D, self.dim(), "Number of exponents must match dimension of domain");
+        // The collapse increases the degree by up to 2, so that n Gauss points with
+        // 2 n - 1 >= degree + 2 suffice
+        let (weights1d, points1d) = gauss(degree / 2 + 2);
+        let rule1d = || weights1d.iter().copied().zip(points1d.iter().map(|&[x]| x));
+
+        let mut weights = Vec::new();
+        let mut points = Vec::new();
+        let mut push = |w: f64, coords: &[f64]| {
+            let mut point = [0.0; D];
+            point.copy_from_slice(coords);
+            weights.push(w);
+            points.push(point);
+        };
+
+        // Maps (a, b) in [-1, 1]^2 to the reference triangle, returning the point and the
+        // Jacobian determinant of the map
+        let collapse_triangle = |a: f64, b: f64| ([(1.0 + a) * (1.0 - b) / 2.0 - 1.0, b], (1.0 - b) / 2.0);
+
+        match self {
+            Self::Segment => {
+                for (w, x) in rule1d() {
+                    push(w, &[x]);
+                }
+            }
+            Self::Quadrilateral => {
+                for ((wx, x), (wy, y)) in cartesian(rule1d, rule1d) {
+                    push(wx * wy, &[x, y]);
+                }
+            }
+            Self::Triangle => {
+                for ((wa, a), (wb, b)) in cartesian(rule1d, rule1d) {
+                    let ([x, y], det) = collapse_triangle(a, b);
+                    push(wa * wb * det, &[x, y]);
+                }
+            }
+            Self::Hexahedron => {
+                for (((wx, x), (wy, y)), (wz, z)) in cartesian(|| cartesian(rule1d, rule1d), rule1d) {
+                    push(wx * wy * wz, &[x, y, z]);
+                }
+            }
+            Self::Prism => {
+                for (((wa, a), (wb, b)), (wz, z)) in cartesian(|| cartesian(rule1d, rule1d), rule1d) {
+                    let ([x, y], det) = collapse_triangle(a, b);
+                    push(wa * wb * det * wz, &[x, y, z]);
+                }
+            }
+            Self::Tetrahedron => {
+                // The cross section at height z is the reference triangle scaled by s = (1 - z) / 2
+                // towards the vertex (-1, -1)
+                for (((wa, a), (wb, b)), (wz, z)) in cartesian(|| cartesian(rule1d, rule1d), rule1d) {
+                    let ([x, y], det) = collapse_triangle(a, b);
+                    let s = (1.0 - z) / 2.0;
+                    push(
+                        wa * wb * det * wz * s * s,
+                        &[s * (x + 1.0) - 1.0, s * (y + 1.0) - 1.0, z],
+                    );
+                }
+            }
+            Self::Pyramid => {
+                // The cross section at height z is the square [-s, s]^2 with s = (1 - z) / 2
+                for (((wx, x), (wy, y)), (wz, z)) in cartesian(|| cartesian(rule1d, rule1d), rule1d) {
+                    let s = (1.0 - z) / 2.0;
+                    push(wx * wy * wz * s * s, &[s * x, s * y, z]);
+                }
+            }
+        }
+
+        (weights, points)
+    }
+}
+
+/// Returns all pairs of items from two (repeatable) iterators.
+fn cartesian<I, J>(first: impl Fn() -> I, second: impl Fn() -> J) -> impl Iterator<Item = (I::Item, J::Item)>
+where
+    I: Iterator,
+    I::Item: Clone,
+    J: Iterator,
+{
+    first().flat_map(move |a| second().map(move |b| (a.clone(), b)))
+}
+
+fn evaluate_monomial<const D: usize>(exponents: &[usize; D], x: &Point<D>) -> f64 {
+    exponents
+        .iter()
+        .zip(x)
+        .map(|(&alpha, &x_i)| x_i.powi(alpha as i32))
+        .product()
+}
+
+/// Describes a monomial that is not integrated exactly by a quadrature rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonomialMismatch<const D: usize> {
+    /// The strength that the rule was verified for.
+    pub strength: usize,
+    /// The exponents of the monomial.
+    pub exponents: [usize; D],
+    /// The integral computed with the rule.
+    pub computed: f64,
+    /// The exact integral.
+    pub expected: f64,
+}
+
+impl<const D: usize> Display for MonomialMismatch<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rule of strength {} integrates monomial with exponents {:?} to {}, but the exact integral is {}",
+            self.strength, self.exponents, self.computed, self.expected
+        )
+    }
+}
+
+impl<const D: usize> std::error::Error for MonomialMismatch<D> {}
+
+/// Verifies that a quadrature rule integrates all monomials up to the given total degree exactly.
+///
+/// The integral of a monomial is accepted if it differs from the exact integral $I$ by at most
+/// `tolerance * max(1, |I|)`.
+///
+/// # Errors
+///
+/// Returns the monomial of lowest total degree which is not integrated exactly.
+///
+/// # Panics
+///
+/// Panics if the dimension of the rule does not match the dimension of the domain.
+pub fn verify_strength<const D: usize>(
+    domain: ReferenceDomain,
+    rule: &Rule<D>,
+    strength: usize,
+    tolerance: f64,
+) -> Result<(), MonomialMismatch<D>> {
+    let exact_rule = domain.exact_rule::<D>(strength);
+    for degree in 0..=strength {
+        for exponents in monomial_exponents::<D>(degree) {
+            let computed = integrate(rule, |x| evaluate_monomial(&exponents, x));
+            let expected = integrate(&exact_rule, |x| evaluate_monomial(&exponents, x));
+            if (computed - expected).abs() > tolerance * expected.abs().max(1.0) {
+                return Err(MonomialMismatch {
+                    strength,
+                    exponents,
+                    computed,
+                    expected,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verifies every rule of a family of quadrature rules parametrized by strength.
+///
+/// The rule for each strength $p = 0, 1, 2, \dots$ is obtained from `rule_for_strength`
+/// and verified with [`verify_strength`], until the family returns an error such as
+/// [`Error::NoRuleAvailable`]. Returns the number of verified rules.
+///
+/// # Errors
+///
+/// Returns the first monomial which is not integrated exactly by the rule of the
+/// corresponding strength.
+///
+/// # Panics
+///
+/// Panics if the dimension of the rules does not match the dimension of the domain.
+pub fn verify_rule_family<const D: usize>(
+    domain: ReferenceDomain,
+    mut rule_for_strength: impl FnMut(usize) -> Result<Rule<D>, Error>,
+    tolerance: f64,
+) -> Result<usize, MonomialMismatch<D>> {
+    let mut strength = 0;
+    while let Ok(rule) = rule_for_strength(strength) {
+        verify_strength(domain, &rule, strength, tolerance)?;
+        strength += 1;
+    }
+    Ok(strength)
+}
+
+/// Returns the exponents of all monomials in `D` variables with the given total degree.
+fn monomial_exponents<const D: usize>(degree: usize) -> Vec<[usize; D]> {
+    let mut result = Vec::new();
+    let mut exponents = [0; D];
+    populate_monomial_exponents(&mut result, &mut exponents, 0, degree);
+    result
+}
+
+fn populate_monomial_exponents<const D: usize>(
+    result: &mut Vec<[usize; D]>,
+    exponents: &mut [usize; D],
+    index: usize,
+    remaining_degree: usize,
+) {
+    if index + 1 == D {
+        exponents[index] = remaining_degree;
+        result.push(*exponents);
+    } else if index < D {
+        for alpha in (0..=remaining_degree).rev() {
+            exponents[index] = alpha;
+            populate_monomial_exponents(result, exponents, index + 1, remaining_degree - alpha);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use fenris_quadrature::grundmann_moeller::{simplex, tetrahedron, triangle};
+use fenris_quadrature::verification::{verify_rule_family, verify_strength, ReferenceDomain};
+use fenris_quadrature::{Error, SimplexRuleFamily};
+
+#[test]
+fn grundmann_moeller_rules_have_expected_numbers_of_points() {
+    // The rule with index s has binomial(s + n + 1, n + 1) points in n dimensions
+    let triangle_sizes = [1, 1, 4, 4, 10, 10, 20, 20, 35];
+    for (strength, &size) in triangle_sizes.iter().enumerate() {
+        let (weights, points) = triangle(strength);
+        assert_eq!(weights.len(), size);
+        assert_eq!(points.len(), size);
+    }
+
+    let tetrahedron_sizes = [1, 1, 5, 5, 15, 15, 35, 35, 70];
+    for (strength, &size) in tetrahedron_sizes.iter().enumerate() {
+        let (weights, points) = tetrahedron(strength);
+        assert_eq!(weights.len(), size);
+        assert_eq!(points.len(), size);
+    }
+}
+
+#[test]
+fn grundmann_moeller_rules_satisfy_prescribed_accuracy() {
+    // The rules are available for any strength, so we only verify a finite number of them
+    let tol = 1e-12;
+    let up_to = |max_strength, strength| {
+        if strength <= max_strength {
+            Ok(strength)
+        } else {
+            Err(Error::NoRuleAvailable)
+        }
+    };
+    let triangle_family = |strength| up_to(20, strength).map(triangle);
+    assert_eq!(
+        verify_rule_family(ReferenceDomain::Triangle, triangle_family, tol),
+        Ok(21)
+    );
+    let tetrahedron_family = |strength| up_to(15, strength).map(tetrahedron);
+    assert_eq!(
+        verify_rule_family(ReferenceDomain::Tetrahedron, tetrahedron_family, tol),
+        Ok(16)
+    );
+    let segment_family = |strength| up_to(20, strength).map(simplex::<1>);
+    assert_eq!(
+        verify_rule_family(ReferenceDomain::Segment, segment_family, tol),
+        Ok(21)
+    );
+
+    // Even strengths are rounded up to the next odd strength
+    assert_eq!(verify_strength(ReferenceDomain::Triangle, &triangle(4), 5, tol), Ok(()));
+    assert!(verify_strength(ReferenceDomain::Triangle, &triangle(4), 6, tol).is_err());
+}
+
+#[test]
+fn simplex_rule_families_are_selectable() {
+    let polyquad = SimplexRuleFamily::Polyquad;
+    let gm = SimplexRuleFamily::GrundmannMoeller;
+    assert_eq!(SimplexRuleFamily::default(), polyquad);
+    assert!(polyquad.has_positive_weights());
+    assert!(!gm.has_positive_weights());
+
+    assert_eq!(polyquad.triangle(5), fenris_quadrature::polyquad::triangle(5));
+    assert_eq!(polyquad.tetrahedron(5), fenris_quadrature::polyquad::tetrahedron(5));
+    assert_eq!(gm.triangle(5), Ok(triangle(5)));
+    assert_eq!(gm.tetrahedron(5), Ok(tetrahedron(5)));
+
+    // Beyond the tabulated polyquad rules, Grundmann-Möller rules remain available
+    assert_eq!(polyquad.triangle(21), Err(Error::NoRuleAvailable));
+    assert_eq!(polyquad.tetrahedron(11), Err(Error::NoRuleAvailable));
+    let rule = gm.tetrahedron(11).unwrap();
+    assert_eq!(verify_strength(ReferenceDomain::Tetrahedron, &rule, 11, 1e-12), Ok(()));
+}
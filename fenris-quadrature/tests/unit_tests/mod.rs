@@ -1,3 +1,5 @@
+mod grundmann_moeller;
 mod polyquad;
 mod tensor;
 mod univariate;
+mod verification;
@@ -1,5 +1,7 @@
 use fenris_quadrature::integrate;
-use fenris_quadrature::tensor::{hexahedron_gauss, quadrilateral_gauss};
+use fenris_quadrature::tensor::{
+    hexahedron_gauss, quadrilateral_gauss, try_hexahedron_gauss_lobatto, try_quadrilateral_gauss_lobatto,
+};
 use matrixcompare::assert_scalar_eq;
 
 #[test]
@@ -56,3 +58,41 @@ fn hexahedral_gauss_rules_satisfy_expected_accuracy() {
         }
     }
 }
+
+#[test]
+fn tensor_gauss_lobatto_rules_satisfy_expected_accuracy() {
+    assert!(try_quadrilateral_gauss_lobatto(1).is_none());
+    assert!(try_hexahedron_gauss_lobatto(1).is_none());
+
+    let monomial_integral_1d = |alpha| (1.0 - (-1.0f64).powi(alpha + 1)) / (alpha as f64 + 1.0);
+    for n in 2..=8 {
+        // Expected polynomial degree that the rule can exactly integrate *along each dimension*
+        let expected_polynomial_degree = 2 * n as i32 - 3;
+        let rule = try_quadrilateral_gauss_lobatto(n).unwrap();
+        assert_eq!(rule.0.len(), n * n);
+        assert!(rule.0.iter().all(|&w| w > 0.0));
+        // The corners of the reference domain are quadrature points
+        for corner in [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]] {
+            assert!(rule.1.contains(&corner));
+        }
+        for alpha in 0..=expected_polynomial_degree {
+            for beta in 0..=expected_polynomial_degree {
+                let expected = monomial_integral_1d(alpha) * monomial_integral_1d(beta);
+                let estimated_integral = integrate(&rule, |&[x, y]| x.powi(alpha) * y.powi(beta));
+                assert_scalar_eq!(estimated_integral, expected, comp = abs, tol = 1e-13);
+            }
+        }
+
+        let rule = try_hexahedron_gauss_lobatto(n).unwrap();
+        assert_eq!(rule.0.len(), n * n * n);
+        assert!(rule.0.iter().all(|&w| w > 0.0));
+        assert!(rule.1.contains(&[1.0, -1.0, 1.0]));
+        for alpha in 0..=expected_polynomial_degree {
+            for gamma in 0..=expected_polynomial_degree {
+                let expected = monomial_integral_1d(alpha) * monomial_integral_1d(gamma) * 2.0;
+                let estimated_integral = integrate(&rule, |&[x, _, z]| x.powi(alpha) * z.powi(gamma));
+                assert_scalar_eq!(estimated_integral, expected, comp = abs, tol = 1e-13);
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use fenris_quadrature::polyquad::{hexahedron, prism, pyramid, quadrilateral, tetrahedron, triangle};
+use fenris_quadrature::tensor::{
+    hexahedron_gauss, quadrilateral_gauss, try_hexahedron_gauss_lobatto, try_quadrilateral_gauss_lobatto,
+};
+use fenris_quadrature::univariate::{gauss, try_gauss_lobatto};
+use fenris_quadrature::verification::{verify_rule_family, verify_strength, MonomialMismatch, ReferenceDomain};
+use matrixcompare::assert_scalar_eq;
+
+#[test]
+fn monomial_integrals_match_closed_form_expressions() {
+    use ReferenceDomain::*;
+    assert_scalar_eq!(Segment.integrate_monomial([4]), 2.0 / 5.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(Segment.integrate_monomial([3]), 0.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        Quadrilateral.integrate_monomial([2, 4]),
+        4.0 / 15.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(Hexahedron.integrate_monomial([0, 0, 0]), 8.0, comp = abs, tol = 1e-14);
+
+    // Volumes of the reference domains
+    assert_scalar_eq!(Triangle.integrate_monomial([0, 0]), 2.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        Tetrahedron.integrate_monomial([0, 0, 0]),
+        4.0 / 3.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(Prism.integrate_monomial([0, 0, 0]), 4.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        Pyramid.integrate_monomial([0, 0, 0]),
+        8.0 / 3.0,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // Centroids of the reference domains
+    assert_scalar_eq!(Triangle.integrate_monomial([1, 0]), -2.0 / 3.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        Tetrahedron.integrate_monomial([0, 0, 1]),
+        -2.0 / 3.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(
+        Pyramid.integrate_monomial([0, 0, 1]),
+        -4.0 / 3.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(Pyramid.integrate_monomial([1, 0, 0]), 0.0, comp = abs, tol = 1e-14);
+
+    // Same expression as used in the polyquad tests
+    let (i, j) = (5.0, 3.0);
+    let expected = (-1.0f64).powf(j + 1.0) / (j + 1.0)
+        * ((1.0 - (-1.0f64).powf(i + j)) / (i + j + 2.0) - (1.0 - (-1.0f64).powf(i + 1.0)) / (i + 1.0));
+    assert_scalar_eq!(Triangle.integrate_monomial([5, 3]), expected, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn polyquad_rule_families_pass_verification() {
+    use ReferenceDomain::*;
+    let tol = 1e-13;
+    assert_eq!(verify_rule_family(Triangle, triangle, tol), Ok(21));
+    assert_eq!(verify_rule_family(Quadrilateral, quadrilateral, tol), Ok(22));
+    assert_eq!(verify_rule_family(Tetrahedron, tetrahedron, tol), Ok(11));
+    assert_eq!(verify_rule_family(Hexahedron, hexahedron, tol), Ok(12));
+    assert_eq!(verify_rule_family(Prism, prism, tol), Ok(11));
+    assert_eq!(verify_rule_family(Pyramid, pyramid, tol), Ok(11));
+}
+
+#[test]
+fn gauss_rules_pass_verification() {
+    for n in 1..=10 {
+        let strength = 2 * n - 1;
+        assert_eq!(
+            verify_strength(ReferenceDomain::Segment, &gauss(n), strength, 1e-13),
+            Ok(())
+        );
+        assert_eq!(
+            verify_strength(ReferenceDomain::Quadrilateral, &quadrilateral_gauss(n), strength, 1e-13),
+            Ok(())
+        );
+        assert_eq!(
+            verify_strength(ReferenceDomain::Hexahedron, &hexahedron_gauss(n), strength, 1e-13),
+            Ok(())
+        );
+    }
+
+    for n in 2..=10 {
+        let rule = try_gauss_lobatto(n).unwrap();
+        assert_eq!(
+            verify_strength(ReferenceDomain::Segment, &rule, 2 * n - 3, 1e-13),
+            Ok(())
+        );
+        let rule = try_quadrilateral_gauss_lobatto(n).unwrap();
+        assert_eq!(
+            verify_strength(ReferenceDomain::Quadrilateral, &rule, 2 * n - 3, 1e-13),
+            Ok(())
+        );
+    }
+    for n in 2..=6 {
+        let rule = try_hexahedron_gauss_lobatto(n).unwrap();
+        assert_eq!(
+            verify_strength(ReferenceDomain::Hexahedron, &rule, 2 * n - 3, 1e-13),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn verification_detects_insufficient_strength_and_corrupted_rules() {
+    // The 2-point Gauss rule does not integrate x^4 exactly
+    let mismatch = verify_strength(ReferenceDomain::Segment, &gauss(2), 4, 1e-13).unwrap_err();
+    assert_eq!(mismatch.strength, 4);
+    assert_eq!(mismatch.exponents, [4]);
+    assert_scalar_eq!(mismatch.computed, 2.0 / 9.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(mismatch.expected, 2.0 / 5.0, comp = abs, tol = 1e-14);
+
+    // A transcription error in a single coordinate is detected
+    let (weights, mut points) = triangle(4).unwrap();
+    points[2][1] += 1e-6;
+    let mismatch: MonomialMismatch<2> =
+        verify_strength(ReferenceDomain::Triangle, &(weights, points), 4, 1e-13).unwrap_err();
+    assert_eq!(mismatch.exponents, [0, 1]);
+
+    // So is an error in a weight, which already affects the volume
+    let (mut weights, points) = tetrahedron(3).unwrap();
+    weights[0] *= 1.0 + 1e-8;
+    let mismatch = verify_strength(ReferenceDomain::Tetrahedron, &(weights, points), 3, 1e-13).unwrap_err();
+    assert_eq!(mismatch.exponents, [0, 0, 0]);
+}
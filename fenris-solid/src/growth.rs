@@ -0,0 +1,209 @@
+use crate::{deformation_gradient, transform_stacked_vectors, HyperelasticMaterial};
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, OMatrix, OVector};
+use fenris::{Real, SmallDim};
+
+/// Parameters for a [`GrowthMaterial`].
+///
+/// Contains the growth (or active) part $\vec F_g$ of the deformation gradient in addition to the
+/// parameters of the underlying elastic material. Since parameters are associated with
+/// quadrature points, the growth tensor can vary from quadrature point to quadrature point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrowthParameters<T, D, P>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// The growth deformation gradient $\vec F_g$.
+    pub growth_deformation_gradient: OMatrix<T, D, D>,
+    /// Parameters for the elastic material that governs the elastic response $\vec F_e$.
+    pub material_parameters: P,
+}
+
+impl<T, D, P> Default for GrowthParameters<T, D, P>
+where
+    T: Real,
+    D: SmallDim,
+    P: Default,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            growth_deformation_gradient: OMatrix::<T, D, D>::identity(),
+            material_parameters: P::default(),
+        }
+    }
+}
+
+impl<T, D, P> GrowthParameters<T, D, P>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn new(growth_deformation_gradient: OMatrix<T, D, D>, material_parameters: P) -> Self {
+        Self {
+            growth_deformation_gradient,
+            material_parameters,
+        }
+    }
+
+    /// Parameters for isotropic growth $\vec F_g = \vartheta \vec I$ with growth stretch $\vartheta$.
+    pub fn isotropic(stretch: T, material_parameters: P) -> Self {
+        Self::new(OMatrix::<T, D, D>::identity() * stretch, material_parameters)
+    }
+
+    /// Parameters for growth prescribed by an eigenstrain $\vec \varepsilon^*$, such as a thermal
+    /// expansion or swelling strain, with $\vec F_g = \vec I + \vec \varepsilon^*$.
+    ///
+    /// This is the finite strain counterpart of the additive decomposition used by
+    /// [`EigenstrainOperator`](fenris::assembly::operators::EigenstrainOperator),
+    /// and the two agree to first order in the strains.
+    pub fn from_eigenstrain(eigenstrain: OMatrix<T, D, D>, material_parameters: P) -> Self {
+        Self::new(OMatrix::<T, D, D>::identity() + eigenstrain, material_parameters)
+    }
+
+    /// Compute $\vec F_g^{-1}$ and $J_g = \det \vec F_g$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if $\vec F_g$ is not invertible.
+    #[allow(non_snake_case)]
+    fn inverse_and_determinant(&self) -> (OMatrix<T, D, D>, T) {
+        let F_g = &self.growth_deformation_gradient;
+        let F_g_inv = F_g
+            .clone()
+            .try_inverse()
+            .expect("Growth deformation gradient must be invertible");
+        (F_g_inv, F_g.determinant())
+    }
+}
+
+/// A hyperelastic material with a multiplicative decomposition $\vec F = \vec F_e \vec F_g$ of the
+/// deformation gradient into an elastic part $\vec F_e$ and a growth part $\vec F_g$.
+///
+/// This kind of decomposition is commonly used to model growth or active strain in biomechanics,
+/// such as the growth of soft tissue or the contraction of muscle fibers. The growth part
+/// $\vec F_g$ is prescribed through the [material parameters](GrowthParameters), and only the
+/// elastic part $\vec F_e = \vec F \vec F_g^{-1}$ contributes to the stored energy.
+///
+/// Given an elastic material with energy density $\psi_e$ and stress $\vec P_e$, measured per
+/// unit volume in the intermediate (grown) configuration, the energy density per unit
+/// reference volume is
+/// <div>$$
+/// \psi(\vec F) = J_g \, \psi_e(\vec F \vec F_g^{-1}),
+/// $$</div>
+/// where $J_g = \det \vec F_g$. The associated first Piola-Kirchhoff stress and contraction
+/// operator are
+/// <div>$$
+/// \begin{aligned}
+/// \vec P(\vec F) &= J_g \, \vec P_e(\vec F_e) \vec F_g^{-T}, \\\\
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b)
+///     &= J_g \, \mathcal{C}_{\vec P_e}(\vec F_e, \vec F_g^{-T} \vec a, \vec F_g^{-T} \vec b).
+/// \end{aligned}
+/// $$</div>
+///
+/// # Panics
+///
+/// The material methods panic if $\vec F_g$ is not invertible.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GrowthMaterial<Material>(pub Material);
+
+impl<Material> GrowthMaterial<Material> {
+    pub fn new(material: Material) -> Self {
+        Self(material)
+    }
+}
+
+impl<T, D, Material> HyperelasticMaterial<T, D> for GrowthMaterial<Material>
+where
+    T: Real,
+    D: SmallDim,
+    Material: HyperelasticMaterial<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type Parameters = GrowthParameters<T, D, Material::Parameters>;
+
+    #[allow(non_snake_case)]
+    fn compute_energy_density(&self, deformation_gradient: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let (F_g_inv, J_g) = parameters.inverse_and_determinant();
+        let F_e = deformation_gradient * F_g_inv;
+        self.0
+            .compute_energy_density(&F_e, &parameters.material_parameters)
+            * J_g
+    }
+
+    #[allow(non_snake_case)]
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let (F_g_inv, J_g) = parameters.inverse_and_determinant();
+        let F_e = deformation_gradient * &F_g_inv;
+        let P_e = self
+            .0
+            .compute_stress_tensor(&F_e, &parameters.material_parameters);
+        P_e * F_g_inv.transpose() * J_g
+    }
+
+    #[allow(non_snake_case)]
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let (F_g_inv, J_g) = parameters.inverse_and_determinant();
+        let F_e = deformation_gradient * &F_g_inv;
+        let F_g_inv_t = F_g_inv.transpose();
+        self.0.compute_stress_contraction(
+            &F_e,
+            &(&F_g_inv_t * a),
+            &(&F_g_inv_t * b),
+            &parameters.material_parameters,
+        ) * J_g
+    }
+
+    #[allow(non_snake_case)]
+    fn accumulate_stress_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let (F_g_inv, J_g) = parameters.inverse_and_determinant();
+        let F_e = deformation_gradient * &F_g_inv;
+        let F_g_inv_t = F_g_inv.transpose();
+        // TODO: Avoid allocating here
+        let a_e = transform_stacked_vectors(&F_g_inv_t, a);
+        let b_e = transform_stacked_vectors(&F_g_inv_t, b);
+        self.0.accumulate_stress_contractions_into(
+            output,
+            alpha * J_g,
+            &F_e,
+            DVectorView::from(&a_e),
+            DVectorView::from(&b_e),
+            &parameters.material_parameters,
+        )
+    }
+
+    fn accumulate_stress_contractions_du_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        // The elastic deformation gradient needs to be formed anyway, so there is
+        // no benefit to working with the displacement gradient here
+        self.accumulate_stress_contractions_into(output, alpha, &deformation_gradient(u_grad), a, b, parameters)
+    }
+}
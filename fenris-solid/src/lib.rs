@@ -2,12 +2,13 @@
 use fenris::allocators::DimAllocator;
 use fenris::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
 use fenris::nalgebra::{
-    DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector, RealField, U1, U2, U3,
+    DMatrixViewMut, DVector, DVectorView, DefaultAllocator, DimName, OMatrix, OVector, RealField, U1, U2, U3,
 };
 use fenris::{Real, SmallDim, Symmetry};
 use std::cmp::min;
 
 pub mod materials;
+pub mod stress;
 
 mod logdet;
 pub use logdet::log_det_F;
@@ -15,6 +16,9 @@ pub use logdet::log_det_F;
 mod gravity_source;
 pub use gravity_source::GravitySource;
 
+mod growth;
+pub use growth::{GrowthMaterial, GrowthParameters};
+
 /// Compute the deformation gradient $\vec F$ given the displacement gradient $\nabla \vec u$.
 #[allow(non_snake_case)]
 pub fn deformation_gradient<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
@@ -391,6 +395,28 @@ pub fn compute_batch_contraction<T, GeometryDim>(
     }
 }
 
+/// The kinematic formulation used by [`MaterialEllipticOperator`].
+///
+/// Both formulations describe the same physics and produce the same discrete forces and stiffness
+/// matrices, but they differ in which configuration the finite element space is assumed to
+/// describe, and consequently in which gradients and stress measures are used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LagrangianFormulation {
+    /// The space describes the *reference* (undeformed) configuration.
+    ///
+    /// Gradients are taken with respect to reference coordinates $\vec X$ and the operator
+    /// corresponds to the first Piola-Kirchhoff stress $\vec P$.
+    #[default]
+    Total,
+    /// The space describes the *current* (deformed) configuration $\vec x = \vec X + \vec u$.
+    ///
+    /// Gradients are taken with respect to current coordinates $\vec x$ and the operator
+    /// corresponds to the Cauchy stress $\vec \sigma$. The energy, operator and contraction are
+    /// only consistent with each other when the space moves with the displacement, see
+    /// [`MaterialEllipticOperator`].
+    Updated,
+}
+
 /// A wrapper that turns any hyper elastic material into an elliptic operator for use
 /// with `fenris` assembly operations.
 ///
@@ -400,7 +426,8 @@ pub fn compute_batch_contraction<T, GeometryDim>(
 /// preprocessing step before handing off the resulting displacements to assembly functionality relying on this
 /// operator wrapper.
 ///
-/// This implies the following relations:
+/// By default, a total Lagrangian formulation is used, in which case the finite element space
+/// must describe the reference configuration. This implies the following relations:
 ///
 /// $$
 /// \begin{aligned}
@@ -409,14 +436,77 @@ pub fn compute_batch_contraction<T, GeometryDim>(
 /// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) &= \mathcal{C}_g(\nabla \vec u, \vec a, \vec b). \\\\
 /// \end{aligned}
 /// $$
-pub struct MaterialEllipticOperator<'a, Material>(&'a Material);
+///
+/// With an [updated Lagrangian formulation](LagrangianFormulation::Updated), the finite element
+/// space must instead describe the current configuration, i.e. the vertices of the mesh must be
+/// the deformed positions $\vec x = \vec X + \vec u$, while the nodal weights are still the
+/// total displacements $\vec u$. The gradient $\nabla \vec u$ is then taken with respect to
+/// $\vec x$ and we have
+///
+/// $$
+/// \begin{aligned}
+/// \vec F^{-1} &= \vec I - (\nabla \vec u)^T, \\\\
+/// \vec \sigma (\vec F) &= J^{-1} \vec P(\vec F) \vec F^T = g^T (\nabla \vec u), \\\\
+/// J^{-1} \mathcal{C}_{\vec P}(\vec F, \vec F^T \vec a, \vec F^T \vec b) &= \mathcal{C}_g(\nabla \vec u, \vec a, \vec b), \\\\
+/// \end{aligned}
+/// $$
+///
+/// and the energy density is measured per unit *current* volume, $J^{-1} \psi(\vec F)$.
+/// Assembling with the updated Lagrangian formulation over the current configuration therefore
+/// yields the same discrete quantities as the total Lagrangian formulation over the reference
+/// configuration.
+///
+/// However, since the current configuration itself depends on $\vec u$, the updated Lagrangian
+/// quantities are **not** derivatives of each other with respect to $\nabla \vec u$ at a fixed
+/// configuration, as is otherwise implied by [`EllipticEnergy`], [`EllipticOperator`] and
+/// [`EllipticContraction`]. The energy, operator and contraction are only consistent when the
+/// mesh moves with $\vec u$, i.e. when the vertices are updated to $\vec X + \vec u$ whenever
+/// $\vec u$ changes. In particular, pointwise finite difference checks such as the
+/// [`OperatorConsistencyChecker`](fenris::assembly::operators::test_utils::OperatorConsistencyChecker)
+/// only apply to the total Lagrangian formulation.
+///
+/// Note that the updated Lagrangian formulation needs to form $\vec F$ explicitly, and so
+/// does not benefit from the improved accuracy of the `_du` methods of [`HyperelasticMaterial`].
+///
+/// # Panics
+///
+/// With the updated Lagrangian formulation, the operator methods panic if $\vec F$ is not invertible.
+pub struct MaterialEllipticOperator<'a, Material> {
+    material: &'a Material,
+    formulation: LagrangianFormulation,
+}
 
 impl<'a, Material> MaterialEllipticOperator<'a, Material> {
     pub fn new(material: &'a Material) -> Self {
-        Self(material)
+        Self {
+            material,
+            formulation: LagrangianFormulation::default(),
+        }
+    }
+
+    pub fn with_formulation(self, formulation: LagrangianFormulation) -> Self {
+        Self { formulation, ..self }
+    }
+
+    pub fn formulation(&self) -> LagrangianFormulation {
+        self.formulation
     }
 }
 
+/// Compute $\vec F$ and $J = \det \vec F$ from the spatial displacement gradient.
+#[allow(non_snake_case)]
+fn updated_lagrangian_kinematics<T, D>(u_grad: &OMatrix<T, D, D>) -> (OMatrix<T, D, D>, T)
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let F = stress::deformation_gradient_from_spatial_u_grad(u_grad)
+        .expect("Deformation gradient must be invertible in updated Lagrangian formulation");
+    let J = F.determinant();
+    (F, J)
+}
+
 impl<'a, T, GeometryDim, Material> Operator<T, GeometryDim> for MaterialEllipticOperator<'a, Material>
 where
     T: Real,
@@ -435,8 +525,15 @@ where
     Material: HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
+    #[allow(non_snake_case)]
     fn compute_energy(&self, u_grad: &OMatrix<T, GeometryDim, GeometryDim>, parameters: &Self::Parameters) -> T {
-        self.0.compute_energy_density_du(u_grad, parameters)
+        match self.formulation {
+            LagrangianFormulation::Total => self.material.compute_energy_density_du(u_grad, parameters),
+            LagrangianFormulation::Updated => {
+                let (F, J) = updated_lagrangian_kinematics(u_grad);
+                self.material.compute_energy_density(&F, parameters) / J
+            }
+        }
     }
 }
 
@@ -459,14 +556,22 @@ where
             .transpose()
     }
 
+    #[allow(non_snake_case)]
     fn compute_elliptic_operator_transpose(
         &self,
         u_grad: &OMatrix<T, GeometryDim, Self::SolutionDim>,
         parameters: &Self::Parameters,
     ) -> OMatrix<T, Self::SolutionDim, GeometryDim> {
-        // We avoid forming the deformation gradient here so that we can avoid
-        // the loss of accuracy implied by forming `F = I + grad u` for small `grad u`
-        self.0.compute_stress_tensor_du(u_grad, parameters)
+        match self.formulation {
+            // We avoid forming the deformation gradient here so that we can avoid
+            // the loss of accuracy implied by forming `F = I + grad u` for small `grad u`
+            LagrangianFormulation::Total => self.material.compute_stress_tensor_du(u_grad, parameters),
+            LagrangianFormulation::Updated => {
+                let (F, _) = updated_lagrangian_kinematics(u_grad);
+                let P = self.material.compute_stress_tensor(&F, parameters);
+                stress::cauchy_stress_from_pk1(&F, &P)
+            }
+        }
     }
 }
 
@@ -477,6 +582,7 @@ where
     Material: HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
+    #[allow(non_snake_case)]
     fn contract(
         &self,
         u_grad: &OMatrix<T, GeometryDim, GeometryDim>,
@@ -484,8 +590,19 @@ where
         b: &OVector<T, GeometryDim>,
         parameters: &Self::Parameters,
     ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
-        self.0
-            .compute_stress_contraction_du(u_grad, a, b, parameters)
+        match self.formulation {
+            LagrangianFormulation::Total => self
+                .material
+                .compute_stress_contraction_du(u_grad, a, b, parameters),
+            LagrangianFormulation::Updated => {
+                // Spatial gradients are related to reference gradients by grad_X = F^T grad_x
+                let (F, J) = updated_lagrangian_kinematics(u_grad);
+                let F_t = F.transpose();
+                self.material
+                    .compute_stress_contraction(&F, &(&F_t * a), &(&F_t * b), parameters)
+                    / J
+            }
+        }
     }
 
     fn symmetry(&self) -> Symmetry {
@@ -502,9 +619,48 @@ where
         b: DVectorView<T>,
         parameters: &Self::Parameters,
     ) {
-        self.0
-            .accumulate_stress_contractions_du_into(output, alpha, u_grad, a, b, parameters)
+        match self.formulation {
+            LagrangianFormulation::Total => self
+                .material
+                .accumulate_stress_contractions_du_into(output, alpha, u_grad, a, b, parameters),
+            LagrangianFormulation::Updated => {
+                let (F, J) = updated_lagrangian_kinematics(u_grad);
+                // TODO: Avoid allocating here
+                let a_ref = transform_stacked_vectors(&F.transpose(), a);
+                let b_ref = transform_stacked_vectors(&F.transpose(), b);
+                self.material.accumulate_stress_contractions_into(
+                    output,
+                    alpha / J,
+                    &F,
+                    DVectorView::from(&a_ref),
+                    DVectorView::from(&b_ref),
+                    parameters,
+                )
+            }
+        }
+    }
+}
+
+/// Multiply each $d$-dimensional block of the stacked vector $\vec v$ by the matrix $\vec A$.
+pub(crate) fn transform_stacked_vectors<T, D>(matrix: &OMatrix<T, D, D>, v: DVectorView<T>) -> DVector<T>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let d = D::dim();
+    assert!(
+        v.len().is_multiple_of(d),
+        "Dimension of stacked vector must be divisible by d (GeometryDim)"
+    );
+    let mut result = DVector::zeros(v.len());
+    for i in 0..v.len() / d {
+        let v_i = v.rows_generic(d * i, D::name());
+        result
+            .rows_generic_mut(d * i, D::name())
+            .copy_from(&(matrix * v_i));
     }
+    result
 }
 
 mod internal {
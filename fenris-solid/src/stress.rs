@@ -0,0 +1,105 @@
+//! Conversions between different stress measures and configurations.
+//!
+//! The functions in this module relate the first Piola-Kirchhoff stress $\vec P$, the second
+//! Piola-Kirchhoff stress $\vec S$ and the Cauchy stress $\vec \sigma$ through the deformation
+//! gradient $\vec F$ and its determinant $J = \det \vec F$:
+//!
+//! $$
+//! \begin{aligned}
+//! \vec P &= \vec F \vec S = J \vec \sigma \vec F^{-T}, \\\\
+//! \vec \sigma &= J^{-1} \vec P \vec F^T = J^{-1} \vec F \vec S \vec F^T.
+//! \end{aligned}
+//! $$
+//!
+//! Functions that need to invert $\vec F$ return `None` if $\vec F$ is not invertible.
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DefaultAllocator, OMatrix};
+use fenris::{Real, SmallDim};
+
+/// Compute the deformation gradient $\vec F$ given the displacement gradient $\nabla_{\vec x} \vec u$
+/// with respect to *current* (deformed) coordinates $\vec x$.
+///
+/// Since $\vec X = \vec x - \vec u$, we have $\vec F^{-1} = \vec I - (\nabla_{\vec x} \vec u)^T$.
+/// Returns `None` if $\vec F^{-1}$ is not invertible.
+#[allow(non_snake_case)]
+pub fn deformation_gradient_from_spatial_u_grad<T, D>(u_grad_spatial: &OMatrix<T, D, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let I = OMatrix::<T, D, D>::identity();
+    let F_inv = I - u_grad_spatial.transpose();
+    F_inv.try_inverse()
+}
+
+/// Compute the Cauchy stress $\vec \sigma = J^{-1} \vec P \vec F^T$ from the first Piola-Kirchhoff stress $\vec P$.
+#[allow(non_snake_case)]
+pub fn cauchy_stress_from_pk1<T, D>(F: &OMatrix<T, D, D>, P: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let J = F.determinant();
+    P * F.transpose() / J
+}
+
+/// Compute the first Piola-Kirchhoff stress $\vec P = J \vec \sigma \vec F^{-T}$ from the Cauchy stress $\vec \sigma$.
+#[allow(non_snake_case)]
+pub fn pk1_from_cauchy_stress<T, D>(F: &OMatrix<T, D, D>, sigma: &OMatrix<T, D, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let J = F.determinant();
+    let F_inv = F.clone().try_inverse()?;
+    Some(sigma * F_inv.transpose() * J)
+}
+
+/// Compute the second Piola-Kirchhoff stress $\vec S = \vec F^{-1} \vec P$ from the first Piola-Kirchhoff stress $\vec P$.
+#[allow(non_snake_case)]
+pub fn pk2_from_pk1<T, D>(F: &OMatrix<T, D, D>, P: &OMatrix<T, D, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let F_inv = F.clone().try_inverse()?;
+    Some(F_inv * P)
+}
+
+/// Compute the first Piola-Kirchhoff stress $\vec P = \vec F \vec S$ from the second Piola-Kirchhoff stress $\vec S$.
+#[allow(non_snake_case)]
+pub fn pk1_from_pk2<T, D>(F: &OMatrix<T, D, D>, S: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    F * S
+}
+
+/// Compute the Cauchy stress $\vec \sigma = J^{-1} \vec F \vec S \vec F^T$ from the second Piola-Kirchhoff stress $\vec S$.
+#[allow(non_snake_case)]
+pub fn cauchy_stress_from_pk2<T, D>(F: &OMatrix<T, D, D>, S: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    cauchy_stress_from_pk1(F, &pk1_from_pk2(F, S))
+}
+
+/// Compute the second Piola-Kirchhoff stress $\vec S = J \vec F^{-1} \vec \sigma \vec F^{-T}$ from the Cauchy stress $\vec \sigma$.
+#[allow(non_snake_case)]
+pub fn pk2_from_cauchy_stress<T, D>(F: &OMatrix<T, D, D>, sigma: &OMatrix<T, D, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let P = pk1_from_cauchy_stress(F, sigma)?;
+    pk2_from_pk1(F, &P)
+}
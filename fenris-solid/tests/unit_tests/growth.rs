@@ -0,0 +1,186 @@
+use crate::unit_tests::materials::{approximate_stress_contraction_fd, approximate_stress_tensor_fd};
+use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters};
+use fenris::assembly::operators::{EigenstrainOperator, EigenstrainParameters, EllipticOperator};
+use fenris::nalgebra;
+use fenris::nalgebra::{matrix, vector, DMatrix, DMatrixViewMut, DVectorView, Matrix2, Matrix3};
+use fenris_solid::materials::{LinearElasticMaterial, NeoHookeanMaterial, StVKMaterial};
+use fenris_solid::{GrowthMaterial, GrowthParameters, HyperelasticMaterial, MaterialEllipticOperator};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn growth_tensor_2d() -> Matrix2<f64> {
+    matrix![1.2, 0.1;
+            0.0, 0.9]
+}
+
+fn growth_tensor_3d() -> Matrix3<f64> {
+    matrix![1.2, 0.1, 0.0;
+            0.0, 0.9, 0.2;
+            0.1, 0.0, 1.1]
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_identity_growth_reproduces_material() {
+    let F = deformation_gradient_3d();
+    let material = NeoHookeanMaterial;
+    let growth_material = GrowthMaterial::new(material);
+    let parameters = GrowthParameters::new(Matrix3::identity(), lame_parameters());
+    let a = vector![-3.0, 4.0, -5.0];
+    let b = vector![-5.0, 2.0, 1.0];
+
+    assert_scalar_eq!(
+        growth_material.compute_energy_density(&F, &parameters),
+        material.compute_energy_density(&F, &lame_parameters()),
+        comp = float
+    );
+    assert_matrix_eq!(
+        growth_material.compute_stress_tensor(&F, &parameters),
+        material.compute_stress_tensor(&F, &lame_parameters()),
+        comp = float
+    );
+    assert_matrix_eq!(
+        growth_material.compute_stress_contraction(&F, &a, &b, &parameters),
+        material.compute_stress_contraction(&F, &a, &b, &lame_parameters()),
+        comp = float
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_pure_growth_is_stress_free() {
+    // If the deformation is entirely due to growth, then F_e = I and there should be no stress
+    let F_g = growth_tensor_3d();
+    let growth_material = GrowthMaterial::new(NeoHookeanMaterial);
+    let parameters = GrowthParameters::new(F_g, lame_parameters());
+    assert_scalar_eq!(
+        growth_material.compute_energy_density(&F_g, &parameters),
+        0.0,
+        comp = abs,
+        tol = 1e-9
+    );
+    assert_matrix_eq!(
+        growth_material.compute_stress_tensor(&F_g, &parameters),
+        Matrix3::zeros(),
+        comp = abs,
+        tol = 1e-9
+    );
+
+    let parameters = GrowthParameters::isotropic(1.5, lame_parameters());
+    let F = Matrix2::identity() * 1.5;
+    assert_matrix_eq!(
+        growth_material.compute_stress_tensor(&F, &parameters),
+        Matrix2::zeros(),
+        comp = abs,
+        tol = 1e-9
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_eigenstrain_agrees_with_additive_eigenstrain_to_first_order() {
+    let eigenstrain = matrix![1.0, 0.5, 0.0;
+                              0.5, -2.0, 0.3;
+                              0.0, 0.3, 0.5]
+        * 1e-6;
+    let parameters = GrowthParameters::from_eigenstrain(eigenstrain, lame_parameters());
+    assert_matrix_eq!(
+        parameters.growth_deformation_gradient,
+        Matrix3::identity() + eigenstrain,
+        comp = float
+    );
+
+    // At zero displacement, the stress is approximately the linear elastic stress -C : eps^*
+    let material = GrowthMaterial::new(LinearElasticMaterial);
+    let operator = EigenstrainOperator::new(MaterialEllipticOperator::new(&LinearElasticMaterial));
+    let additive_parameters = EigenstrainParameters::new(eigenstrain, lame_parameters());
+    let P_multiplicative = material.compute_stress_tensor(&Matrix3::identity(), &parameters);
+    let P_additive = operator.compute_elliptic_operator(&Matrix3::zeros(), &additive_parameters);
+    assert!(P_additive.norm() > 1e-4);
+    assert_matrix_eq!(P_multiplicative, P_additive, comp = abs, tol = 1e-8);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_stress_is_derivative_of_energy_2d() {
+    let F = deformation_gradient_2d();
+    let growth_material = GrowthMaterial::new(StVKMaterial);
+    let parameters = GrowthParameters::new(growth_tensor_2d(), lame_parameters());
+
+    let stress_tensor = growth_material.compute_stress_tensor(&F, &parameters);
+    let approx_stress_tensor =
+        approximate_stress_tensor_fd(|F| growth_material.compute_energy_density(F, &parameters), F, 1e-6);
+    assert_matrix_eq!(
+        stress_tensor,
+        approx_stress_tensor,
+        comp = abs,
+        tol = 1e-8 * stress_tensor.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_stress_is_derivative_of_energy_3d() {
+    let F = deformation_gradient_3d();
+    let growth_material = GrowthMaterial::new(NeoHookeanMaterial);
+    let parameters = GrowthParameters::new(growth_tensor_3d(), lame_parameters());
+
+    let stress_tensor = growth_material.compute_stress_tensor(&F, &parameters);
+    let approx_stress_tensor =
+        approximate_stress_tensor_fd(|F| growth_material.compute_energy_density(F, &parameters), F, 1e-6);
+    assert_matrix_eq!(
+        stress_tensor,
+        approx_stress_tensor,
+        comp = abs,
+        tol = 1e-8 * stress_tensor.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_contraction_is_consistent_with_tensor_3d() {
+    let F = deformation_gradient_3d();
+    let growth_material = GrowthMaterial::new(NeoHookeanMaterial);
+    let parameters = GrowthParameters::new(growth_tensor_3d(), lame_parameters());
+    let a = vector![-3.0, 4.0, -5.0];
+    let b = vector![-5.0, 2.0, 1.0];
+
+    let contraction = growth_material.compute_stress_contraction(&F, &a, &b, &parameters);
+    let approx_contraction =
+        approximate_stress_contraction_fd(|F| growth_material.compute_stress_tensor(F, &parameters), F, a, b, 1e-6);
+    assert_matrix_eq!(
+        contraction,
+        approx_contraction,
+        comp = abs,
+        tol = 1e-8 * contraction.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn growth_material_multi_contraction_consistency_3d() {
+    let F = deformation_gradient_3d();
+    let growth_material = GrowthMaterial::new(StVKMaterial);
+    let parameters = GrowthParameters::new(growth_tensor_3d(), lame_parameters());
+    let a = DVectorView::from_slice(&[-3.0, 4.0, -5.0, 1.0, 2.0, 3.0], 6);
+    let b = DVectorView::from_slice(&[-5.0, 2.0, 1.0, 4.0, -1.0, 2.0], 6);
+    let alpha = 2.5;
+
+    let mut output = DMatrix::zeros(6, 6);
+    growth_material.accumulate_stress_contractions_into(
+        DMatrixViewMut::from(&mut output),
+        alpha,
+        &F,
+        a,
+        b,
+        &parameters,
+    );
+
+    // Only the upper triangle is filled, see docs of accumulate_stress_contractions_into
+    for (I, J) in [(0, 0), (0, 1), (1, 1)] {
+        let a_I = a.fixed_rows::<3>(3 * I).clone_owned();
+        let b_J = b.fixed_rows::<3>(3 * J).clone_owned();
+        let expected = growth_material.compute_stress_contraction(&F, &a_I, &b_J, &parameters) * alpha;
+        let block = output.fixed_view::<3, 3>(3 * I, 3 * J);
+        assert_matrix_eq!(block, expected, comp = abs, tol = 1e-12 * expected.amax());
+    }
+}
@@ -1,17 +1,22 @@
-use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters, tet10_element};
+use crate::unit_tests::{
+    deformation_gradient_2d, deformation_gradient_3d, lame_parameters, tet10_element, tet4_element,
+};
 use fenris::assembly::local::{
     assemble_element_elliptic_matrix, assemble_element_elliptic_vector, compute_element_elliptic_energy,
 };
+use fenris::assembly::operators::test_utils::OperatorConsistencyChecker;
 use fenris::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator};
+use fenris::element::Tet4Element;
 use fenris::nalgebra;
 use fenris::nalgebra::{
     vector, DMatrix, DVector, DVectorView, DVectorViewMut, DimName, Dyn, Matrix2, Matrix3, MatrixViewMut, OMatrix, U3,
 };
 use fenris::quadrature;
 use fenris_optimize::calculus::{approximate_gradient_fd, approximate_jacobian_fd};
-use fenris_solid::materials::StVKMaterial;
-use fenris_solid::{HyperelasticMaterial, MaterialEllipticOperator};
-use matrixcompare::assert_matrix_eq;
+use fenris_solid::materials::{NeoHookeanMaterial, StVKMaterial};
+use fenris_solid::{HyperelasticMaterial, LagrangianFormulation, MaterialEllipticOperator};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::array;
 
 #[test]
 #[allow(non_snake_case)]
@@ -171,3 +176,200 @@ fn material_elliptic_operator_vector_matrix_assembly_tet10() {
         tol = 1e-9 * element_matrix.amax()
     );
 }
+
+#[test]
+#[allow(non_snake_case)]
+fn material_elliptic_operator_updated_lagrangian_matches_total_lagrangian_tet4() {
+    // Assembling with the updated Lagrangian formulation on the deformed element must give
+    // the same discrete energy, vector and matrix as the total Lagrangian formulation
+    // on the reference element. We use Tet4 here since the geometry of Tet10 elements
+    // is affine, so that a deformed Tet10 element cannot be represented exactly
+    let lame = lame_parameters();
+    let material = NeoHookeanMaterial;
+    let total = MaterialEllipticOperator::new(&material);
+    let updated = MaterialEllipticOperator::new(&material).with_formulation(LagrangianFormulation::Updated);
+    assert_eq!(total.formulation(), LagrangianFormulation::Total);
+    assert_eq!(updated.formulation(), LagrangianFormulation::Updated);
+
+    let reference_element = tet4_element();
+    let u_element = DVector::from_iterator(12, (0..12).map(|i| 0.1 * (i as f64).sin()));
+    let deformed_vertices =
+        array::from_fn(|i| reference_element.vertices()[i] + u_element.fixed_rows::<3>(3 * i).clone_owned());
+    let deformed_element = Tet4Element::from_vertices(deformed_vertices);
+
+    let (weights, points) = quadrature::total_order::tetrahedron(4).unwrap();
+    let parameters: Vec<_> = std::iter::repeat_n(lame, points.len()).collect();
+    let mut gradient_buffer = OMatrix::zeros_generic(U3::name(), Dyn(4));
+
+    let mut energies = Vec::new();
+    let mut vectors = Vec::new();
+    let mut matrices = Vec::new();
+    for (element, operator) in [(&reference_element, &total), (&deformed_element, &updated)] {
+        let energy = compute_element_elliptic_energy(
+            element,
+            operator,
+            DVectorView::from(&u_element),
+            &weights,
+            &points,
+            &parameters,
+            MatrixViewMut::from(&mut gradient_buffer),
+        )
+        .unwrap();
+
+        let mut element_vector = DVector::zeros(12);
+        assemble_element_elliptic_vector(
+            DVectorViewMut::from(&mut element_vector),
+            element,
+            operator,
+            DVectorView::from(&u_element),
+            &weights,
+            &points,
+            &parameters,
+            MatrixViewMut::from(&mut gradient_buffer),
+        )
+        .unwrap();
+
+        let mut element_matrix = DMatrix::zeros(12, 12);
+        assemble_element_elliptic_matrix(
+            MatrixViewMut::from(&mut element_matrix),
+            element,
+            operator,
+            DVectorView::from(&u_element),
+            &weights,
+            &points,
+            &parameters,
+            MatrixViewMut::from(&mut gradient_buffer),
+        )
+        .unwrap();
+
+        energies.push(energy);
+        vectors.push(element_vector);
+        matrices.push(element_matrix);
+    }
+
+    assert_scalar_eq!(energies[0], energies[1], comp = abs, tol = 1e-9 * energies[0].abs());
+    assert_matrix_eq!(vectors[0], vectors[1], comp = abs, tol = 1e-9 * vectors[0].amax());
+    assert_matrix_eq!(matrices[0], matrices[1], comp = abs, tol = 1e-9 * matrices[0].amax());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn material_elliptic_operator_updated_lagrangian_stvk_3d() {
+    let F = deformation_gradient_3d();
+    let lame = lame_parameters();
+    let material = StVKMaterial;
+    let operator = MaterialEllipticOperator::new(&material).with_formulation(LagrangianFormulation::Updated);
+
+    // The spatial displacement gradient satisfies F^{-1} = I - (grad_x u)^T
+    let u_grad = (Matrix3::identity() - F.try_inverse().unwrap()).transpose();
+    let J = F.determinant();
+
+    let energy_as_elliptic = operator.compute_energy(&u_grad, &lame);
+    let energy_as_material = material.compute_energy_density(&F, &lame) / J;
+    assert_scalar_eq!(energy_as_elliptic, energy_as_material, comp = float);
+
+    // g^T should correspond to the Cauchy stress
+    let P = material.compute_stress_tensor(&F, &lame);
+    let sigma = P * F.transpose() / J;
+    let derivative_as_elliptic = operator.compute_elliptic_operator_transpose(&u_grad, &lame);
+    assert_matrix_eq!(derivative_as_elliptic, sigma, comp = abs, tol = 1e-9 * sigma.amax());
+    // The Cauchy stress is symmetric
+    assert_matrix_eq!(sigma, sigma.transpose(), comp = abs, tol = 1e-9 * sigma.amax());
+
+    let a = vector![3.0, 4.0, -2.0];
+    let b = vector![-3.0, 1.0, 3.0];
+    let contraction_as_elliptic = operator.contract(&u_grad, &a, &b, &lame);
+    let contraction_as_material =
+        material.compute_stress_contraction(&F, &(F.transpose() * a), &(F.transpose() * b), &lame) / J;
+    assert_matrix_eq!(
+        contraction_as_elliptic,
+        contraction_as_material,
+        comp = abs,
+        tol = 1e-9 * contraction_as_material.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn material_elliptic_operator_updated_lagrangian_is_consistent_only_on_moving_mesh() {
+    let lame = lame_parameters();
+    let material = NeoHookeanMaterial;
+    let total = MaterialEllipticOperator::new(&material);
+    let updated = MaterialEllipticOperator::new(&material).with_formulation(LagrangianFormulation::Updated);
+
+    // At a fixed configuration, the updated Lagrangian stress is not the derivative of the energy
+    let checker = OperatorConsistencyChecker::new();
+    assert!(checker.check_all::<U3, _>(&total, &lame).is_ok());
+    assert!(checker.check_energy::<U3, _>(&updated, &lame).is_err());
+    assert!(checker.check_contraction::<U3, _>(&updated, &lame).is_err());
+
+    // When the element moves with u, the assembled vector is the derivative of the assembled
+    // energy, and the assembled matrix is the derivative of the assembled vector
+    let reference_element = tet4_element();
+    let deformed_element = |u: DVectorView<f64>| {
+        Tet4Element::from_vertices(array::from_fn(|i| {
+            reference_element.vertices()[i] + u.fixed_rows::<3>(3 * i).clone_owned()
+        }))
+    };
+    let (weights, points) = quadrature::total_order::tetrahedron(4).unwrap();
+    let parameters: Vec<_> = std::iter::repeat_n(lame, points.len()).collect();
+    let energy = |u: DVectorView<f64>| {
+        let mut gradient_buffer = OMatrix::zeros_generic(U3::name(), Dyn(4));
+        compute_element_elliptic_energy(
+            &deformed_element(u),
+            &updated,
+            u,
+            &weights,
+            &points,
+            &parameters,
+            MatrixViewMut::from(&mut gradient_buffer),
+        )
+        .unwrap()
+    };
+    let vector = |u: DVectorView<f64>, output: DVectorViewMut<f64>| {
+        let mut gradient_buffer = OMatrix::zeros_generic(U3::name(), Dyn(4));
+        assemble_element_elliptic_vector(
+            output,
+            &deformed_element(u),
+            &updated,
+            u,
+            &weights,
+            &points,
+            &parameters,
+            MatrixViewMut::from(&mut gradient_buffer),
+        )
+        .unwrap();
+    };
+
+    let mut u_element = DVector::from_iterator(12, (0..12).map(|i| 0.1 * (i as f64).cos()));
+    let mut element_vector = DVector::zeros(12);
+    vector(DVectorView::from(&u_element), DVectorViewMut::from(&mut element_vector));
+    let mut element_matrix = DMatrix::zeros(12, 12);
+    let mut gradient_buffer = OMatrix::zeros_generic(U3::name(), Dyn(4));
+    assemble_element_elliptic_matrix(
+        MatrixViewMut::from(&mut element_matrix),
+        &deformed_element(DVectorView::from(&u_element)),
+        &updated,
+        DVectorView::from(&u_element),
+        &weights,
+        &points,
+        &parameters,
+        MatrixViewMut::from(&mut gradient_buffer),
+    )
+    .unwrap();
+
+    let approx_element_vector = approximate_gradient_fd(energy, &mut u_element, 1e-6);
+    let approx_element_matrix = approximate_jacobian_fd(12, vector, &mut u_element, 1e-6);
+    assert_matrix_eq!(
+        element_vector,
+        approx_element_vector,
+        comp = abs,
+        tol = 1e-6 * element_vector.amax()
+    );
+    assert_matrix_eq!(
+        element_matrix,
+        approx_element_matrix,
+        comp = abs,
+        tol = 1e-6 * element_matrix.amax()
+    );
+}
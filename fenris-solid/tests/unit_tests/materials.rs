@@ -9,7 +9,7 @@ use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_p
 
 /// Approximates stress tensor using central Finite Differences with step size `h`.
 #[allow(non_snake_case)]
-fn approximate_stress_tensor_fd<const D: usize>(
+pub(crate) fn approximate_stress_tensor_fd<const D: usize>(
     strain_energy_density: impl Fn(&SMatrix<f64, D, D>) -> f64,
     deformation_gradient: SMatrix<f64, D, D>,
     h: f64,
@@ -36,7 +36,7 @@ fn approximate_stress_tensor_fd<const D: usize>(
 
 /// Approximates the stress contraction using central Finite Differences with step size `h`.
 #[allow(non_snake_case)]
-fn approximate_stress_contraction_fd<const D: usize>(
+pub(crate) fn approximate_stress_contraction_fd<const D: usize>(
     stress_tensor: impl Fn(&SMatrix<f64, D, D>) -> SMatrix<f64, D, D>,
     deformation_gradient: SMatrix<f64, D, D>,
     a: SVector<f64, D>,
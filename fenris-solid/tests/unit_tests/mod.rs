@@ -4,9 +4,11 @@ use fenris::nalgebra::{matrix, Matrix2, Matrix3, Point3};
 use fenris_solid::materials::LameParameters;
 
 mod gravity_source;
+mod growth;
 mod logdet;
 mod material_elliptic_operator;
 mod materials;
+mod stress;
 
 fn lame_parameters() -> LameParameters<f64> {
     LameParameters {
@@ -28,12 +30,16 @@ fn deformation_gradient_3d() -> Matrix3<f64> {
             2.0, 8.0, 9.0]
 }
 
-/// An arbitrary Tet10 element used in tests.
-fn tet10_element() -> Tet10Element<f64> {
+/// An arbitrary Tet4 element used in tests.
+fn tet4_element() -> Tet4Element<f64> {
     let a = Point3::new(2.0, 0.0, 1.0);
     let b = Point3::new(3.0, 4.0, 1.0);
     let c = Point3::new(1.0, 1.0, 2.0);
     let d = Point3::new(3.0, 1.0, 4.0);
-    let tet4_element = Tet4Element::from_vertices([a, b, c, d]);
-    Tet10Element::from(&tet4_element)
+    Tet4Element::from_vertices([a, b, c, d])
+}
+
+/// An arbitrary Tet10 element used in tests.
+fn tet10_element() -> Tet10Element<f64> {
+    Tet10Element::from(&tet4_element())
 }
@@ -0,0 +1,53 @@
+use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters};
+use fenris::nalgebra::{Matrix2, Matrix3};
+use fenris_solid::materials::NeoHookeanMaterial;
+use fenris_solid::stress::{
+    cauchy_stress_from_pk1, cauchy_stress_from_pk2, deformation_gradient_from_spatial_u_grad, pk1_from_cauchy_stress,
+    pk1_from_pk2, pk2_from_cauchy_stress, pk2_from_pk1,
+};
+use fenris_solid::HyperelasticMaterial;
+use matrixcompare::assert_matrix_eq;
+
+#[test]
+#[allow(non_snake_case)]
+fn stress_conversions_roundtrip_3d() {
+    let F = deformation_gradient_3d();
+    let P = NeoHookeanMaterial.compute_stress_tensor(&F, &lame_parameters());
+    let tol = 1e-12 * P.amax();
+
+    let sigma = cauchy_stress_from_pk1(&F, &P);
+    let S = pk2_from_pk1(&F, &P).unwrap();
+
+    // Both the Cauchy stress and the second Piola-Kirchhoff stress are symmetric
+    assert_matrix_eq!(sigma, sigma.transpose(), comp = abs, tol = tol);
+    assert_matrix_eq!(S, S.transpose(), comp = abs, tol = tol);
+
+    assert_matrix_eq!(pk1_from_cauchy_stress(&F, &sigma).unwrap(), P, comp = abs, tol = tol);
+    assert_matrix_eq!(pk1_from_pk2(&F, &S), P, comp = abs, tol = tol);
+    assert_matrix_eq!(cauchy_stress_from_pk2(&F, &S), sigma, comp = abs, tol = tol);
+    assert_matrix_eq!(pk2_from_cauchy_stress(&F, &sigma).unwrap(), S, comp = abs, tol = tol);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn stress_conversions_singular_deformation_gradient() {
+    let F = Matrix2::new(1.0, 2.0, 2.0, 4.0);
+    let P = Matrix2::identity();
+    assert!(pk2_from_pk1(&F, &P).is_none());
+    assert!(pk1_from_cauchy_stress(&F, &P).is_none());
+    assert!(pk2_from_cauchy_stress(&F, &P).is_none());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn deformation_gradient_from_spatial_u_grad_is_consistent() {
+    let F = deformation_gradient_2d();
+    let u_grad_spatial = (Matrix2::identity() - F.try_inverse().unwrap()).transpose();
+    let F_computed = deformation_gradient_from_spatial_u_grad(&u_grad_spatial).unwrap();
+    assert_matrix_eq!(F_computed, F, comp = abs, tol = 1e-12);
+
+    let F = deformation_gradient_3d();
+    let u_grad_spatial = (Matrix3::identity() - F.try_inverse().unwrap()).transpose();
+    let F_computed = deformation_gradient_from_spatial_u_grad(&u_grad_spatial).unwrap();
+    assert_matrix_eq!(F_computed, F, comp = abs, tol = 1e-12);
+}
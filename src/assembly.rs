@@ -163,7 +163,16 @@
 //!
 //!
 
+pub mod bc;
+pub mod block;
 pub mod buffers;
+pub mod flux;
 pub mod global;
+pub mod goal;
 pub mod local;
+pub mod matrix_free;
 pub mod operators;
+pub mod patch;
+pub mod projection;
+pub mod saddle_point;
+pub mod stats;
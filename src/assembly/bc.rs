@@ -0,0 +1,466 @@
+//! Essential (Dirichlet) boundary conditions for assembled linear systems.
+//!
+//! Given an assembled linear system $\vec A \vec u = \vec b$, a Dirichlet condition prescribes
+//! the values $\vec u_C = \vec g$ of a set $C$ of constrained degrees of freedom. Denoting the
+//! remaining free degrees of freedom by $F$, the system can be partitioned as
+//! <div>$$
+//! \begin{pmatrix} \vec A_{FF} & \vec A_{FC} \\\\ \vec A_{CF} & \vec A_{CC} \end{pmatrix}
+//! \begin{pmatrix} \vec u_F \\\\ \vec u_C \end{pmatrix}
+//! = \begin{pmatrix} \vec b_F \\\\ \vec b_C \end{pmatrix}.
+//! $$</div>
+//! [`DirichletConditions`] supports three strategies for imposing $\vec u_C = \vec g$:
+//!
+//! - [**Elimination**](DirichletConditions::eliminate) removes the constrained degrees of
+//!   freedom and produces the reduced system
+//!   $\vec A_{FF} \vec u_F = \vec b_F - \vec A_{FC} \vec g$, together with a map that
+//!   recovers the full solution. The reduced matrix is symmetric whenever $\vec A$ is.
+//! - [**Row replacement**](DirichletConditions::apply_row_replacement) keeps the dimensions
+//!   of the system, but replaces the constrained rows and columns with scaled rows of the
+//!   identity matrix and updates the right-hand side accordingly. This preserves symmetry
+//!   and the sparsity pattern.
+//! - The [**penalty method**](DirichletConditions::apply_penalty) adds a large penalty
+//!   $\beta$ to the diagonal entries of the constrained degrees of freedom, so that the
+//!   conditions only hold approximately, with an error of order $\beta^{-1}$.
+use crate::index::{node_dofs, DofIndex, NodeIndex};
+use crate::mesh::sets::MeshSets;
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut};
+use crate::Real;
+use eyre::eyre;
+use nalgebra_sparse::{CsrMatrix, SparseEntry, SparseEntryMut};
+
+/// Prescribed values for a set of degrees of freedom.
+///
+/// Degrees of freedom are numbered node by node, so that the $i$-th component of node $I$ for
+/// a field with solution dimension $s$ has the index $s I + i$.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirichletConditions<T> {
+    num_dofs: usize,
+    /// Sorted indices of constrained degrees of freedom.
+    constrained_dofs: Vec<usize>,
+    /// Prescribed values, in the same order as `constrained_dofs`.
+    values: Vec<T>,
+}
+
+impl<T: Real> DirichletConditions<T> {
+    /// Prescribes the given values for the given degrees of freedom.
+    ///
+    /// The degrees of freedom need not be sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of values does not match the number of degrees of freedom,
+    /// if any degree of freedom is out of bounds or if a degree of freedom is given more than once.
+    pub fn try_from_dofs_and_values(num_dofs: usize, dofs: &[DofIndex], values: &[T]) -> eyre::Result<Self> {
+        if dofs.len() != values.len() {
+            return Err(eyre!(
+                "Number of values ({}) does not match number of constrained degrees of freedom ({})",
+                values.len(),
+                dofs.len()
+            ));
+        }
+        let mut pairs: Vec<_> = DofIndex::as_raw_slice(dofs)
+            .iter()
+            .copied()
+            .zip(values.iter().copied())
+            .collect();
+        pairs.sort_unstable_by_key(|&(dof, _)| dof);
+        if let Some(&(dof, _)) = pairs.last() {
+            if dof >= num_dofs {
+                return Err(eyre!(
+                    "Degree of freedom {dof} is out of bounds for {num_dofs} degrees of freedom"
+                ));
+            }
+        }
+        if let Some(window) = pairs.windows(2).find(|window| window[0].0 == window[1].0) {
+            return Err(eyre!("Degree of freedom {} is constrained more than once", window[0].0));
+        }
+        let (constrained_dofs, values) = pairs.into_iter().unzip();
+        Ok(Self {
+            num_dofs,
+            constrained_dofs,
+            values,
+        })
+    }
+
+    /// Prescribes zero for the given degrees of freedom.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as
+    /// [`try_from_dofs_and_values`](Self::try_from_dofs_and_values).
+    pub fn try_homogeneous(num_dofs: usize, dofs: &[DofIndex]) -> eyre::Result<Self> {
+        Self::try_from_dofs_and_values(num_dofs, dofs, &vec![T::zero(); dofs.len()])
+    }
+
+    /// Prescribes all components of the given nodes.
+    ///
+    /// The values are stored node by node, i.e. `values[s * i + j]` is the prescribed value
+    /// of component `j` of node `nodes[i]`, where `s` is the solution dimension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `values` does not have length `solution_dim * nodes.len()`, or under
+    /// the same conditions as [`try_from_dofs_and_values`](Self::try_from_dofs_and_values).
+    pub fn try_from_nodes_and_values(
+        num_nodes: usize,
+        solution_dim: usize,
+        nodes: &[NodeIndex],
+        values: &[T],
+    ) -> eyre::Result<Self> {
+        Self::try_from_dofs_and_values(solution_dim * num_nodes, &node_dofs(nodes, solution_dim), values)
+    }
+
+    /// Prescribes zero for all components of the given nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as
+    /// [`try_from_dofs_and_values`](Self::try_from_dofs_and_values).
+    pub fn try_homogeneous_nodes(num_nodes: usize, solution_dim: usize, nodes: &[NodeIndex]) -> eyre::Result<Self> {
+        let values = vec![T::zero(); solution_dim * nodes.len()];
+        Self::try_from_nodes_and_values(num_nodes, solution_dim, nodes, &values)
+    }
+
+    /// Prescribes zero for all components of the nodes in the named node set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no node set with the given name, or under the same
+    /// conditions as [`try_from_dofs_and_values`](Self::try_from_dofs_and_values).
+    pub fn try_homogeneous_node_set(
+        num_nodes: usize,
+        solution_dim: usize,
+        sets: &MeshSets,
+        name: &str,
+    ) -> eyre::Result<Self> {
+        let nodes = sets
+            .node_set(name)
+            .ok_or_else(|| eyre!("No node set named \"{name}\""))?;
+        Self::try_homogeneous_nodes(num_nodes, solution_dim, NodeIndex::from_raw_slice(nodes))
+    }
+
+    /// The total number of degrees of freedom, including constrained degrees of freedom.
+    pub fn num_dofs(&self) -> usize {
+        self.num_dofs
+    }
+
+    /// The sorted indices of the constrained degrees of freedom.
+    pub fn constrained_dofs(&self) -> &[DofIndex] {
+        DofIndex::from_raw_slice(&self.constrained_dofs)
+    }
+
+    /// The prescribed values, in the same order as [`constrained_dofs`](Self::constrained_dofs).
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn is_constrained(&self, dof: DofIndex) -> bool {
+        self.constrained_dofs.binary_search(&dof.index()).is_ok()
+    }
+
+    /// The sorted indices of the free (unconstrained) degrees of freedom.
+    pub fn free_dofs(&self) -> Vec<DofIndex> {
+        let mut constrained = self.constrained_dofs.iter().peekable();
+        (0..self.num_dofs)
+            .filter(|dof| {
+                if constrained.peek() == Some(&dof) {
+                    constrained.next();
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(DofIndex::new)
+            .collect()
+    }
+
+    /// Returns the constraint operator $\vec C$ whose $k$-th row selects the $k$-th constrained
+    /// degree of freedom, so that the conditions read $\vec C \vec u = \vec g$.
+    ///
+    /// This allows the conditions to be enforced with Lagrange multipliers, see
+    /// [`SaddlePointSystem`](crate::assembly::saddle_point::SaddlePointSystem).
+    pub fn constraint_operator(&self) -> CsrMatrix<T> {
+        let num_constraints = self.constrained_dofs.len();
+        CsrMatrix::try_from_csr_data(
+            num_constraints,
+            self.num_dofs,
+            (0..=num_constraints).collect(),
+            self.constrained_dofs.clone(),
+            vec![T::one(); num_constraints],
+        )
+        .expect("Constrained degrees of freedom are sorted and in bounds")
+    }
+
+    /// Returns the prescribed value for each degree of freedom, or `None` if it is free.
+    fn dense_values(&self) -> Vec<Option<T>> {
+        let mut dense = vec![None; self.num_dofs];
+        for (&dof, &value) in self.constrained_dofs.iter().zip(&self.values) {
+            dense[dof] = Some(value);
+        }
+        dense
+    }
+
+    fn check_system_dimensions(&self, matrix: &CsrMatrix<T>, rhs_len: usize) -> eyre::Result<()> {
+        if matrix.nrows() != self.num_dofs || matrix.ncols() != self.num_dofs || rhs_len != self.num_dofs {
+            return Err(eyre!(
+                "System dimensions ({}x{} matrix, {} entries in right-hand side) \
+                 do not match the {} degrees of freedom of the boundary conditions",
+                matrix.nrows(),
+                matrix.ncols(),
+                rhs_len,
+                self.num_dofs
+            ));
+        }
+        Ok(())
+    }
+
+    /// Overwrites the constrained entries of the given vector with their prescribed values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector does not match the number of degrees of freedom.
+    pub fn apply_to_vector<'a>(&self, u: impl Into<DVectorViewMut<'a, T>>) {
+        let mut u = u.into();
+        assert_eq!(
+            u.len(),
+            self.num_dofs,
+            "Vector dimension must match number of degrees of freedom"
+        );
+        for (&dof, &value) in self.constrained_dofs.iter().zip(&self.values) {
+            u[dof] = value;
+        }
+    }
+
+    /// Eliminates the constrained degrees of freedom from the system $\vec A \vec u = \vec b$.
+    ///
+    /// The constrained rows of the system are discarded, and the constrained columns are
+    /// moved to the right-hand side. The returned [`ReducedSystem`] can recover the full
+    /// solution from the solution of the reduced system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the system do not match the number of degrees
+    /// of freedom.
+    pub fn eliminate<'a>(
+        &self,
+        matrix: &CsrMatrix<T>,
+        rhs: impl Into<DVectorView<'a, T>>,
+    ) -> eyre::Result<ReducedSystem<T>> {
+        let rhs = rhs.into();
+        self.check_system_dimensions(matrix, rhs.len())?;
+
+        let free_dofs = self.free_dofs();
+        let mut global_to_reduced = vec![usize::MAX; self.num_dofs];
+        for (reduced_index, &dof) in free_dofs.iter().enumerate() {
+            global_to_reduced[dof.index()] = reduced_index;
+        }
+        let prescribed = self.dense_values();
+
+        let num_free = free_dofs.len();
+        let mut offsets = Vec::with_capacity(num_free + 1);
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        let mut reduced_rhs = DVector::zeros(num_free);
+        offsets.push(0);
+        for (reduced_row, &row_index) in free_dofs.iter().enumerate() {
+            let row = matrix.row(row_index.index());
+            let mut b_i = rhs[row_index.index()];
+            for (&col_index, &a_ij) in row.col_indices().iter().zip(row.values()) {
+                match prescribed[col_index] {
+                    Some(g_j) => b_i -= a_ij * g_j,
+                    // The map is monotonic, so column indices remain sorted
+                    None => {
+                        col_indices.push(global_to_reduced[col_index]);
+                        values.push(a_ij);
+                    }
+                }
+            }
+            reduced_rhs[reduced_row] = b_i;
+            offsets.push(col_indices.len());
+        }
+
+        let reduced_matrix = CsrMatrix::try_from_csr_data(num_free, num_free, offsets, col_indices, values)
+            .expect("Reduced CSR data must be valid by construction");
+        Ok(ReducedSystem {
+            matrix: reduced_matrix,
+            rhs: reduced_rhs,
+            free_dofs,
+            conditions: self.clone(),
+        })
+    }
+
+    /// Imposes the conditions by symmetric row and column replacement.
+    ///
+    /// For each constrained degree of freedom $i$ with prescribed value $g_i$, the
+    /// contributions $A_{ji} g_i$ are subtracted from the right-hand side of all free rows $j$,
+    /// row $i$ and column $i$ are zeroed and the diagonal entry $A_{ii}$ is set to a scale
+    /// factor $\alpha$, with $b_i = \alpha g_i$. The scale factor is chosen as the mean
+    /// magnitude of the diagonal entries of the free degrees of freedom in order to not
+    /// deteriorate the condition number of the system.
+    ///
+    /// The sparsity pattern of the matrix is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the system do not match the number of degrees
+    /// of freedom, or if the sparsity pattern does not contain the diagonal entry of a
+    /// constrained degree of freedom.
+    pub fn apply_row_replacement<'a>(
+        &self,
+        matrix: &mut CsrMatrix<T>,
+        rhs: impl Into<DVectorViewMut<'a, T>>,
+    ) -> eyre::Result<()> {
+        let mut rhs = rhs.into();
+        self.check_system_dimensions(matrix, rhs.len())?;
+        let prescribed = self.dense_values();
+
+        let mut diagonal_sum = T::zero();
+        let mut num_free_diagonal_entries = 0;
+        for (i, row) in matrix.row_iter().enumerate() {
+            match (prescribed[i], row.get_entry(i)) {
+                (Some(_), Some(SparseEntry::Zero) | None) => {
+                    return Err(eyre!(
+                        "Sparsity pattern does not contain diagonal entry of constrained degree of freedom {i}"
+                    ))
+                }
+                (None, Some(SparseEntry::NonZero(&a_ii))) => {
+                    diagonal_sum += a_ii.abs();
+                    num_free_diagonal_entries += 1;
+                }
+                _ => {}
+            }
+        }
+        let scale = if num_free_diagonal_entries > 0 && diagonal_sum > T::zero() {
+            diagonal_sum / T::from_usize(num_free_diagonal_entries).unwrap()
+        } else {
+            T::one()
+        };
+
+        for i in 0..self.num_dofs {
+            let mut row = matrix.row_mut(i);
+            let (cols, values) = row.cols_and_values_mut();
+            match prescribed[i] {
+                Some(g_i) => {
+                    for (&j, a_ij) in cols.iter().zip(values) {
+                        *a_ij = if i == j { scale } else { T::zero() };
+                    }
+                    rhs[i] = scale * g_i;
+                }
+                None => {
+                    for (&j, a_ij) in cols.iter().zip(values) {
+                        if let Some(g_j) = prescribed[j] {
+                            rhs[i] -= *a_ij * g_j;
+                            *a_ij = T::zero();
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Imposes the conditions approximately with the penalty method.
+    ///
+    /// For each constrained degree of freedom $i$ with prescribed value $g_i$, the penalty
+    /// $\beta$ is added to the diagonal entry $A_{ii}$ and $\beta g_i$ is added to $b_i$.
+    /// The error in the constrained degrees of freedom is of order $\beta^{-1}$, so the
+    /// penalty should be chosen several orders of magnitude larger than the entries of
+    /// $\vec A$. Very large penalties deteriorate the condition number of the system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the system do not match the number of degrees
+    /// of freedom, or if the sparsity pattern does not contain the diagonal entry of a
+    /// constrained degree of freedom.
+    pub fn apply_penalty<'a>(
+        &self,
+        matrix: &mut CsrMatrix<T>,
+        rhs: impl Into<DVectorViewMut<'a, T>>,
+        penalty: T,
+    ) -> eyre::Result<()> {
+        let mut rhs = rhs.into();
+        self.check_system_dimensions(matrix, rhs.len())?;
+        for (&dof, &g) in self.constrained_dofs.iter().zip(&self.values) {
+            let mut row = matrix.row_mut(dof);
+            match row.get_entry_mut(dof) {
+                Some(SparseEntryMut::NonZero(a_ii)) => *a_ii += penalty,
+                _ => {
+                    return Err(eyre!(
+                        "Sparsity pattern does not contain diagonal entry of constrained degree of freedom {dof}"
+                    ))
+                }
+            }
+            rhs[dof] += penalty * g;
+        }
+        Ok(())
+    }
+}
+
+/// The reduced system obtained by [eliminating](DirichletConditions::eliminate) the constrained
+/// degrees of freedom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReducedSystem<T> {
+    matrix: CsrMatrix<T>,
+    rhs: DVector<T>,
+    free_dofs: Vec<DofIndex>,
+    conditions: DirichletConditions<T>,
+}
+
+impl<T: Real> ReducedSystem<T> {
+    /// The reduced matrix $\vec A_{FF}$.
+    pub fn matrix(&self) -> &CsrMatrix<T> {
+        &self.matrix
+    }
+
+    /// The reduced right-hand side $\vec b_F - \vec A_{FC} \vec g$.
+    pub fn rhs(&self) -> &DVector<T> {
+        &self.rhs
+    }
+
+    /// The global index of each reduced degree of freedom.
+    pub fn free_dofs(&self) -> &[DofIndex] {
+        &self.free_dofs
+    }
+
+    pub fn into_matrix_and_rhs(self) -> (CsrMatrix<T>, DVector<T>) {
+        (self.matrix, self.rhs)
+    }
+
+    /// Gathers the free degrees of freedom of a full vector into a reduced vector.
+    ///
+    /// This is useful e.g. to obtain an initial guess for an iterative solver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector does not match the number of degrees of freedom.
+    pub fn reduce_vector<'a>(&self, u: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let u = u.into();
+        assert_eq!(
+            u.len(),
+            self.conditions.num_dofs(),
+            "Vector dimension must match number of degrees of freedom"
+        );
+        DVector::from_iterator(self.free_dofs.len(), self.free_dofs.iter().map(|dof| u[dof.index()]))
+    }
+
+    /// Recovers the full solution from the solution of the reduced system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the reduced solution does not match the number of free
+    /// degrees of freedom.
+    pub fn recover_solution<'a>(&self, reduced_solution: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let reduced_solution = reduced_solution.into();
+        assert_eq!(
+            reduced_solution.len(),
+            self.free_dofs.len(),
+            "Reduced solution must have one entry per free degree of freedom"
+        );
+        let mut u = DVector::zeros(self.conditions.num_dofs());
+        for (&dof, &u_i) in self.free_dofs.iter().zip(reduced_solution.iter()) {
+            u[dof.index()] = u_i;
+        }
+        self.conditions.apply_to_vector(&mut u);
+        u
+    }
+}
@@ -0,0 +1,558 @@
+//! Block layouts of degrees of freedom for mixed and multi-field problems.
+//!
+//! Mixed formulations, such as velocity-pressure formulations of Stokes flow, involve several
+//! fields that are discretized with different finite element spaces. A [`BlockLayout`] composes
+//! the degrees of freedom of the fields into a single global numbering, where the degrees of
+//! freedom of each field form a contiguous *block*. For fields $\vec u_1, \dots, \vec u_m$,
+//! the global vector of unknowns is
+//! <div>$$
+//! \vec u = \begin{pmatrix} \vec u_1 \\\\ \vdots \\\\ \vec u_m \end{pmatrix},
+//! $$</div>
+//! and within each block, the degrees of freedom are numbered node by node as usual, i.e.
+//! component $i$ of node $I$ of a field with solution dimension $s$ has the block-local index
+//! $s I + i$. The system matrix correspondingly consists of blocks $\vec A_{kl}$ coupling field
+//! $k$ (rows) with field $l$ (columns).
+//!
+//! Diagonal blocks can be assembled with the usual element assemblers and
+//! [`CsrAssembler::assemble`], whereas off-diagonal blocks between fields with different spaces
+//! are assembled from [mixed element assemblers](crate::assembly::local::ElementMixedMatrixAssembler)
+//! with [`CsrAssembler::assemble_mixed`]. The blocks are then combined into the monolithic
+//! system with [`BlockLayout::try_assemble_matrix`]. Alternatively, [`BlockSystemAssembler`]
+//! lets users declare the fields and the operators coupling them, and then assembles the
+//! monolithic system in one go.
+//!
+//! [`CsrAssembler::assemble`]: crate::assembly::global::CsrAssembler::assemble
+//! [`CsrAssembler::assemble_mixed`]: crate::assembly::global::CsrAssembler::assemble_mixed
+use crate::assembly::bc::DirichletConditions;
+use crate::assembly::global::{CsrAssembler, VectorAssembler};
+use crate::assembly::local::{ElementMatrixAssembler, ElementMixedMatrixAssembler, ElementVectorAssembler};
+use crate::index::DofIndex;
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut};
+use crate::space::FiniteElementConnectivity;
+use crate::Real;
+use eyre::eyre;
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Block {
+    num_nodes: usize,
+    solution_dim: usize,
+}
+
+/// A global numbering of the degrees of freedom of several fields.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockLayout {
+    blocks: Vec<Block>,
+    // offsets[k] is the global index of the first degree of freedom in block k,
+    // with an additional entry for the total number of degrees of freedom
+    offsets: Vec<usize>,
+}
+
+impl Default for BlockLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockLayout {
+    /// Creates an empty layout without blocks.
+    pub fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+
+    /// Appends a block for a field with the given number of nodes and solution dimension.
+    pub fn with_block(mut self, num_nodes: usize, solution_dim: usize) -> Self {
+        self.push_block(num_nodes, solution_dim);
+        self
+    }
+
+    /// Appends a block for a field with the given solution dimension on the nodes of the
+    /// given space.
+    pub fn with_space(self, space: &(impl ?Sized + FiniteElementConnectivity), solution_dim: usize) -> Self {
+        self.with_block(space.num_nodes(), solution_dim)
+    }
+
+    /// Appends a block for a field with the given number of nodes and solution dimension,
+    /// returning the index of the new block.
+    pub fn push_block(&mut self, num_nodes: usize, solution_dim: usize) -> usize {
+        self.blocks.push(Block {
+            num_nodes,
+            solution_dim,
+        });
+        self.offsets
+            .push(self.num_dofs() + num_nodes * solution_dim);
+        self.blocks.len() - 1
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The total number of degrees of freedom in all blocks.
+    pub fn num_dofs(&self) -> usize {
+        *self.offsets.last().unwrap()
+    }
+
+    pub fn block_num_nodes(&self, block: usize) -> usize {
+        self.blocks[block].num_nodes
+    }
+
+    pub fn block_solution_dim(&self, block: usize) -> usize {
+        self.blocks[block].solution_dim
+    }
+
+    /// The number of degrees of freedom in the given block.
+    pub fn block_size(&self, block: usize) -> usize {
+        self.offsets[block + 1] - self.offsets[block]
+    }
+
+    /// The global index of the first degree of freedom in the given block.
+    pub fn block_offset(&self, block: usize) -> usize {
+        self.offsets[block]
+    }
+
+    /// The range of global indices of the degrees of freedom in the given block.
+    pub fn block_range(&self, block: usize) -> Range<usize> {
+        self.offsets[block]..self.offsets[block + 1]
+    }
+
+    /// The global index of the given block-local degree of freedom.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block or the local index is out of bounds.
+    pub fn global_index(&self, block: usize, local_index: usize) -> usize {
+        assert!(
+            local_index < self.block_size(block),
+            "Local index {local_index} out of bounds for block {block} of size {}",
+            self.block_size(block)
+        );
+        self.offsets[block] + local_index
+    }
+
+    /// The global index of the given component of the given node in a block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block, node or component is out of bounds.
+    pub fn global_dof(&self, block: usize, node: usize, component: usize) -> usize {
+        let s = self.block_solution_dim(block);
+        assert!(
+            component < s,
+            "Component {component} out of bounds for solution dim {s}"
+        );
+        self.global_index(block, s * node + component)
+    }
+
+    /// Finds the block and the block-local index of the given global degree of freedom.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn find_block(&self, global_index: usize) -> Option<(usize, usize)> {
+        if global_index >= self.num_dofs() {
+            return None;
+        }
+        // Find the last block starting at or before the index. Empty blocks share their offset
+        // with the next block, so this always yields a non-empty block.
+        let block = self
+            .offsets
+            .partition_point(|&offset| offset <= global_index)
+            - 1;
+        Some((block, global_index - self.offsets[block]))
+    }
+
+    /// Returns a view of the given block of a global vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the vector does not match the layout.
+    pub fn block<'a, T: Real>(&self, block: usize, vector: &'a DVector<T>) -> DVectorView<'a, T> {
+        self.assert_vector_dim(vector.len());
+        vector.rows(self.offsets[block], self.block_size(block))
+    }
+
+    /// Returns a mutable view of the given block of a global vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the vector does not match the layout.
+    pub fn block_mut<'a, T: Real>(&self, block: usize, vector: &'a mut DVector<T>) -> DVectorViewMut<'a, T> {
+        self.assert_vector_dim(vector.len());
+        vector.rows_mut(self.offsets[block], self.block_size(block))
+    }
+
+    /// Splits a global vector into views of its blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the vector does not match the layout.
+    pub fn split<'a, T: Real>(&self, vector: &'a DVector<T>) -> Vec<DVectorView<'a, T>> {
+        (0..self.num_blocks())
+            .map(|block| self.block(block, vector))
+            .collect()
+    }
+
+    /// Concatenates vectors for each block into a global vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of vectors does not match the number of blocks, or if the
+    /// dimension of any vector does not match the size of its block.
+    pub fn try_concatenate<T: Real>(&self, blocks: &[DVectorView<T>]) -> eyre::Result<DVector<T>> {
+        if blocks.len() != self.num_blocks() {
+            return Err(eyre!(
+                "Number of vectors ({}) does not match number of blocks ({})",
+                blocks.len(),
+                self.num_blocks()
+            ));
+        }
+        let mut result = DVector::zeros(self.num_dofs());
+        for (block, vector) in blocks.iter().enumerate() {
+            if vector.len() != self.block_size(block) {
+                return Err(eyre!(
+                    "Dimension of vector ({}) does not match size of block {} ({})",
+                    vector.len(),
+                    block,
+                    self.block_size(block)
+                ));
+            }
+            result
+                .rows_mut(self.offsets[block], vector.len())
+                .copy_from(vector);
+        }
+        Ok(result)
+    }
+
+    /// Assembles the monolithic matrix from the given blocks.
+    ///
+    /// Each entry `(k, l, matrix)` places `matrix` in the block coupling block `k` (rows)
+    /// to block `l` (columns). Blocks that are not given are zero, and blocks given more than
+    /// once are summed. The sparsity pattern of the result is the union of the patterns of the
+    /// blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block index is out of bounds or if the dimensions of a matrix do
+    /// not match the sizes of its row and column blocks.
+    pub fn try_assemble_matrix<T: Real>(&self, blocks: &[(usize, usize, &CsrMatrix<T>)]) -> eyre::Result<CsrMatrix<T>> {
+        let n = self.num_dofs();
+        let nnz = blocks.iter().map(|(_, _, matrix)| matrix.nnz()).sum();
+        let mut coo = CooMatrix::new(n, n);
+        coo.reserve(nnz);
+        for &(k, l, matrix) in blocks {
+            if k >= self.num_blocks() || l >= self.num_blocks() {
+                return Err(eyre!(
+                    "Block ({}, {}) out of bounds for layout with {} blocks",
+                    k,
+                    l,
+                    self.num_blocks()
+                ));
+            }
+            if matrix.nrows() != self.block_size(k) || matrix.ncols() != self.block_size(l) {
+                return Err(eyre!(
+                    "Dimensions of matrix ({}x{}) do not match block ({}, {}) of size {}x{}",
+                    matrix.nrows(),
+                    matrix.ncols(),
+                    k,
+                    l,
+                    self.block_size(k),
+                    self.block_size(l)
+                ));
+            }
+            let (row_offset, col_offset) = (self.offsets[k], self.offsets[l]);
+            for (i, j, &a_ij) in matrix.triplet_iter() {
+                coo.push(row_offset + i, col_offset + j, a_ij);
+            }
+        }
+        Ok(CsrMatrix::from(&coo))
+    }
+
+    /// Combines Dirichlet conditions for individual blocks into conditions for the global
+    /// degrees of freedom.
+    ///
+    /// Each entry `(k, conditions)` gives the conditions for the block-local degrees of
+    /// freedom of block `k`. Blocks without conditions are unconstrained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block index is out of bounds, if the number of degrees of freedom of
+    /// any conditions does not match the size of its block, or if a block is given more than once.
+    pub fn try_combine_dirichlet_conditions<T: Real>(
+        &self,
+        conditions: &[(usize, &DirichletConditions<T>)],
+    ) -> eyre::Result<DirichletConditions<T>> {
+        let mut dofs = Vec::new();
+        let mut values = Vec::new();
+        for &(block, block_conditions) in conditions {
+            if block >= self.num_blocks() {
+                return Err(eyre!(
+                    "Block {} out of bounds for layout with {} blocks",
+                    block,
+                    self.num_blocks()
+                ));
+            }
+            if block_conditions.num_dofs() != self.block_size(block) {
+                return Err(eyre!(
+                    "Number of degrees of freedom in conditions ({}) does not match size of block {} ({})",
+                    block_conditions.num_dofs(),
+                    block,
+                    self.block_size(block)
+                ));
+            }
+            let offset = self.offsets[block];
+            dofs.extend(
+                block_conditions
+                    .constrained_dofs()
+                    .iter()
+                    .map(|&dof| DofIndex::new(offset + dof.index())),
+            );
+            values.extend_from_slice(block_conditions.values());
+        }
+        DirichletConditions::try_from_dofs_and_values(self.num_dofs(), &dofs, &values)
+    }
+
+    fn assert_vector_dim(&self, dim: usize) {
+        assert_eq!(
+            dim,
+            self.num_dofs(),
+            "Vector dimension must match number of degrees of freedom in layout"
+        );
+    }
+}
+
+struct OperatorBlock<'a, T: Real> {
+    field: usize,
+    assembler: &'a dyn ElementMatrixAssembler<T>,
+}
+
+struct CouplingBlock<'a, T: Real> {
+    row_field: usize,
+    col_field: usize,
+    assembler: &'a dyn ElementMixedMatrixAssembler<T>,
+    transpose: bool,
+}
+
+struct SourceBlock<'a, T: Real> {
+    field: usize,
+    assembler: &'a dyn ElementVectorAssembler<T>,
+}
+
+/// Assembles monolithic systems for multi-field problems from operators registered per block.
+///
+/// Fields are declared with [`add_field`](Self::add_field), which appends a block to the
+/// underlying [`BlockLayout`]. The system matrix is then described by registering
+///
+/// - [operators](Self::add_operator), whose element assemblers contribute to the diagonal block
+///   of a single field, and
+/// - [couplings](Self::add_coupling), whose mixed element assemblers contribute to the
+///   off-diagonal block coupling two (possibly identical) fields,
+///
+/// and the right-hand side by registering [sources](Self::add_source). Contributions to the same
+/// block are summed. This way, coupled problems such as thermo-mechanics, poroelasticity or
+/// phase-field fracture can be assembled with the existing single-field element assemblers.
+///
+/// Compatibility of the registered assemblers with the layout is checked upon assembly.
+pub struct BlockSystemAssembler<'a, T: Real> {
+    layout: BlockLayout,
+    operators: Vec<OperatorBlock<'a, T>>,
+    couplings: Vec<CouplingBlock<'a, T>>,
+    sources: Vec<SourceBlock<'a, T>>,
+    csr_assembler: CsrAssembler<T>,
+    vector_assembler: VectorAssembler<T>,
+}
+
+impl<'a, T: Real> Default for BlockSystemAssembler<'a, T> {
+    fn default() -> Self {
+        Self::from_layout(BlockLayout::new())
+    }
+}
+
+impl<'a, T: Real> BlockSystemAssembler<'a, T> {
+    /// Creates an assembler without fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an assembler whose fields are given by the blocks of the layout.
+    pub fn from_layout(layout: BlockLayout) -> Self {
+        Self {
+            layout,
+            operators: Vec::new(),
+            couplings: Vec::new(),
+            sources: Vec::new(),
+            csr_assembler: CsrAssembler::default(),
+            vector_assembler: VectorAssembler::default(),
+        }
+    }
+
+    pub fn layout(&self) -> &BlockLayout {
+        &self.layout
+    }
+
+    /// Declares a field with the given solution dimension on the nodes of the given space,
+    /// returning the index of the field.
+    pub fn add_field(&mut self, space: &(impl ?Sized + FiniteElementConnectivity), solution_dim: usize) -> usize {
+        self.layout.push_block(space.num_nodes(), solution_dim)
+    }
+
+    /// Registers an operator contributing to the diagonal block of the given field.
+    pub fn add_operator(&mut self, field: usize, assembler: &'a dyn ElementMatrixAssembler<T>) -> &mut Self {
+        self.operators.push(OperatorBlock { field, assembler });
+        self
+    }
+
+    /// Registers a coupling contributing to the block with rows associated with `row_field`
+    /// and columns associated with `col_field`.
+    ///
+    /// The rows of the element matrices of the mixed assembler must correspond to the row field
+    /// and the columns to the column field.
+    pub fn add_coupling(
+        &mut self,
+        row_field: usize,
+        col_field: usize,
+        assembler: &'a dyn ElementMixedMatrixAssembler<T>,
+    ) -> &mut Self {
+        self.couplings.push(CouplingBlock {
+            row_field,
+            col_field,
+            assembler,
+            transpose: false,
+        });
+        self
+    }
+
+    /// Registers a coupling and its transpose.
+    ///
+    /// The coupling contributes $\vec B$ to the block `(row_field, col_field)` as for
+    /// [`add_coupling`](Self::add_coupling), and additionally $\vec B^T$ to the block
+    /// `(col_field, row_field)`. This is the typical structure of symmetric mixed problems,
+    /// such as the velocity-pressure coupling in Stokes flow.
+    pub fn add_symmetric_coupling(
+        &mut self,
+        row_field: usize,
+        col_field: usize,
+        assembler: &'a dyn ElementMixedMatrixAssembler<T>,
+    ) -> &mut Self {
+        self.couplings.push(CouplingBlock {
+            row_field,
+            col_field,
+            assembler,
+            transpose: true,
+        });
+        self
+    }
+
+    /// Registers a source contributing to the right-hand side of the given field.
+    pub fn add_source(&mut self, field: usize, assembler: &'a dyn ElementVectorAssembler<T>) -> &mut Self {
+        self.sources.push(SourceBlock { field, assembler });
+        self
+    }
+
+    /// Assembles the monolithic system matrix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a registered assembler is incompatible with the blocks of its fields,
+    /// or if element assembly fails.
+    pub fn assemble_matrix(&self) -> eyre::Result<CsrMatrix<T>> {
+        let mut blocks = Vec::with_capacity(self.operators.len() + 2 * self.couplings.len());
+        for &OperatorBlock { field, assembler } in &self.operators {
+            self.check_field(field)?;
+            self.check_connectivity(field, assembler.num_nodes(), assembler.solution_dim(), "operator")?;
+            blocks.push((field, field, self.csr_assembler.assemble(assembler)?));
+        }
+        for coupling in &self.couplings {
+            let CouplingBlock {
+                row_field,
+                col_field,
+                assembler,
+                transpose,
+            } = *coupling;
+            self.check_field(row_field)?;
+            self.check_field(col_field)?;
+            self.check_connectivity(
+                row_field,
+                assembler.num_row_nodes(),
+                assembler.row_solution_dim(),
+                "coupling (rows)",
+            )?;
+            self.check_connectivity(
+                col_field,
+                assembler.num_col_nodes(),
+                assembler.col_solution_dim(),
+                "coupling (columns)",
+            )?;
+            let matrix = self.csr_assembler.assemble_mixed(assembler)?;
+            if transpose {
+                blocks.push((col_field, row_field, matrix.transpose()));
+            }
+            blocks.push((row_field, col_field, matrix));
+        }
+
+        let block_refs: Vec<_> = blocks
+            .iter()
+            .map(|(k, l, matrix)| (*k, *l, matrix))
+            .collect();
+        self.layout.try_assemble_matrix(&block_refs)
+    }
+
+    /// Assembles the monolithic right-hand side vector.
+    ///
+    /// Fields without registered sources have vanishing right-hand sides.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a registered assembler is incompatible with the block of its field,
+    /// or if element assembly fails.
+    pub fn assemble_vector(&self) -> eyre::Result<DVector<T>> {
+        let mut result = DVector::zeros(self.layout.num_dofs());
+        for &SourceBlock { field, assembler } in &self.sources {
+            self.check_field(field)?;
+            self.check_connectivity(field, assembler.num_nodes(), assembler.solution_dim(), "source")?;
+            let output = self.layout.block_mut(field, &mut result);
+            self.vector_assembler
+                .assemble_vector_into(output, assembler)?;
+        }
+        Ok(result)
+    }
+
+    /// Assembles the monolithic system matrix and right-hand side vector.
+    pub fn assemble(&self) -> eyre::Result<(CsrMatrix<T>, DVector<T>)> {
+        Ok((self.assemble_matrix()?, self.assemble_vector()?))
+    }
+
+    fn check_field(&self, field: usize) -> eyre::Result<()> {
+        if field >= self.layout.num_blocks() {
+            return Err(eyre!(
+                "Field {} out of bounds for system with {} fields",
+                field,
+                self.layout.num_blocks()
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_connectivity(&self, field: usize, num_nodes: usize, solution_dim: usize, kind: &str) -> eyre::Result<()> {
+        let (expected_num_nodes, expected_solution_dim) = (
+            self.layout.block_num_nodes(field),
+            self.layout.block_solution_dim(field),
+        );
+        if num_nodes != expected_num_nodes || solution_dim != expected_solution_dim {
+            return Err(eyre!(
+                "Assembler for {} has {} nodes with solution dim {}, but field {} has {} nodes with solution dim {}",
+                kind,
+                num_nodes,
+                solution_dim,
+                field,
+                expected_num_nodes,
+                expected_solution_dim
+            ));
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,14 @@
 use crate::allocators::{BiDimAllocator, DimAllocator};
 use crate::assembly::global::gather_global_to_local;
-use crate::assembly::local::QuadratureTable;
+use crate::assembly::local::{transform_reference_gradients, QuadratureTable};
+use crate::index::NodeIndex;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{DMatrix, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint, Scalar};
 use crate::quadrature::Quadrature;
 use crate::space::FiniteElementSpace;
 use crate::util::{compute_interpolation, compute_interpolation_gradient, reshape_to_slice};
 use crate::{Real, SmallDim};
+use eyre::eyre;
 use itertools::izip;
 use nalgebra::{DVector, DVectorView, OMatrix, OVector, U1};
 
@@ -40,7 +42,10 @@ impl<T: Real> BasisFunctionBuffer<T> {
         Space: FiniteElementSpace<T> + ?Sized,
         DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
     {
-        space.populate_element_nodes(&mut self.element_nodes, element_index);
+        space.populate_element_nodes(
+            NodeIndex::from_raw_slice_mut(&mut self.element_nodes),
+            element_index.into(),
+        );
     }
 
     /// TODO: Document that populate_element_nodes should be called first
@@ -262,7 +267,7 @@ impl<T: Real> InterpolationBuffer<T> {
         Space: FiniteElementSpace<T> + ?Sized,
         DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
     {
-        let node_count = space.element_node_count(element_index);
+        let node_count = space.element_node_count(element_index.into());
         self.basis_buffer
             .resize(node_count, Space::ReferenceDim::dim());
         self.basis_buffer
@@ -345,3 +350,229 @@ where
         self.basis_buffer.element_basis_values()
     }
 }
+
+/// Geometry and basis function data of a single element, prepared for repeated evaluation at
+/// the quadrature points of the element.
+///
+/// In multi-physics assembly, several operators are often evaluated on the same element with
+/// the same quadrature rule. [`prepare_element`](Self::prepare_element) computes the
+/// quadrature points in physical coordinates, the element Jacobians, their determinants and
+/// inverses as well as the basis function values and physical gradients at all quadrature
+/// points once, after which the data can be reused by any number of operators without
+/// redundant geometry computations. For [affine](FiniteElementSpace::is_element_affine) elements,
+/// the Jacobian is inverted only once.
+///
+/// The buffer retains its allocations, so that it can be reused for many elements, for example
+/// as part of a thread-local workspace.
+#[derive(Debug)]
+pub struct PreparedElementBuffer<T, D, Data = ()>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D> + Allocator<T, D, D>,
+{
+    element_index: usize,
+    nodes: Vec<usize>,
+    quadrature: QuadratureBuffer<T, D, Data>,
+    physical_points: Vec<OPoint<T, D>>,
+    jacobians: Vec<OMatrix<T, D, D>>,
+    inverse_jacobians: Vec<OMatrix<T, D, D>>,
+    jacobian_determinants: Vec<T>,
+    // Basis values of the element at each quadrature point, stored consecutively per point
+    basis_values: Vec<T>,
+    // Physical basis gradients of the element at each quadrature point, stored consecutively
+    // per point as the columns of a column-major D x n matrix
+    basis_gradients: Vec<T>,
+}
+
+impl<T, D, Data> Default for PreparedElementBuffer<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            element_index: usize::MAX,
+            nodes: Vec::new(),
+            quadrature: QuadratureBuffer::default(),
+            physical_points: Vec::new(),
+            jacobians: Vec::new(),
+            inverse_jacobians: Vec::new(),
+            jacobian_determinants: Vec::new(),
+            basis_values: Vec::new(),
+            basis_gradients: Vec::new(),
+        }
+    }
+}
+
+impl<T, D, Data> PreparedElementBuffer<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    Data: Default + Clone,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Prepares the buffer for evaluation on the given element of a space with the quadrature
+    /// rule associated with the element in the given table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a singular element Jacobian is encountered, in which case the
+    /// contents of the buffer are unspecified.
+    pub fn prepare_element<Space>(
+        &mut self,
+        space: &Space,
+        element_index: usize,
+        qtable: &(impl ?Sized + QuadratureTable<T, D, Data = Data>),
+    ) -> eyre::Result<()>
+    where
+        Space: ?Sized + FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    {
+        let n = space.element_node_count(element_index.into());
+        self.element_index = element_index;
+        self.nodes.resize(n, usize::MAX);
+        space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut self.nodes), element_index.into());
+        self.quadrature
+            .populate_element_quadrature_from_table(element_index, qtable);
+
+        let num_points = self.quadrature.points().len();
+        self.physical_points.clear();
+        self.jacobians.clear();
+        self.inverse_jacobians.clear();
+        self.jacobian_determinants.clear();
+        self.basis_values.resize(n * num_points, T::zero());
+        self.basis_gradients
+            .resize(D::dim() * n * num_points, T::zero());
+
+        let constant_jacobian = space
+            .element_constant_reference_jacobian(element_index)
+            .map(|jacobian| invert_jacobian(jacobian.clone()).map(|inverse| (jacobian, inverse)))
+            .transpose()?;
+
+        for (q, xi) in self.quadrature.points().iter().enumerate() {
+            let (jacobian, inverse) = match &constant_jacobian {
+                Some((jacobian, inverse)) => (jacobian.clone(), inverse.clone()),
+                None => {
+                    let jacobian = space.element_reference_jacobian(element_index, xi);
+                    let inverse = invert_jacobian(jacobian.clone())?;
+                    (jacobian, inverse)
+                }
+            };
+            space.populate_element_basis(element_index, &mut self.basis_values[q * n..(q + 1) * n], xi);
+            let gradients = &mut self.basis_gradients[D::dim() * n * q..D::dim() * n * (q + 1)];
+            space.populate_element_gradients(
+                element_index,
+                MatrixViewMut::from_slice_generic(gradients, D::name(), Dyn(n)),
+                xi,
+            );
+            transform_reference_gradients(
+                &inverse.transpose(),
+                MatrixViewMut::from_slice_generic(gradients, D::name(), Dyn(n)),
+            );
+
+            self.physical_points
+                .push(space.map_element_reference_coords(element_index, xi));
+            self.jacobian_determinants.push(jacobian.determinant());
+            self.jacobians.push(jacobian);
+            self.inverse_jacobians.push(inverse);
+        }
+        Ok(())
+    }
+
+    /// The index of the element that the buffer was last prepared for.
+    pub fn element_index(&self) -> usize {
+        self.element_index
+    }
+
+    pub fn element_nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+
+    pub fn num_quadrature_points(&self) -> usize {
+        self.quadrature.weights().len()
+    }
+
+    pub fn quadrature(&self) -> &QuadratureBuffer<T, D, Data> {
+        &self.quadrature
+    }
+
+    /// The quadrature points in physical coordinates.
+    pub fn physical_points(&self) -> &[OPoint<T, D>] {
+        &self.physical_points
+    }
+
+    /// The Jacobian of the reference map at quadrature point `q`.
+    pub fn jacobian(&self, q: usize) -> &OMatrix<T, D, D> {
+        &self.jacobians[q]
+    }
+
+    pub fn inverse_jacobian(&self, q: usize) -> &OMatrix<T, D, D> {
+        &self.inverse_jacobians[q]
+    }
+
+    pub fn jacobian_determinant(&self, q: usize) -> T {
+        self.jacobian_determinants[q]
+    }
+
+    /// The quadrature weight at quadrature point `q` scaled by the absolute value of the
+    /// Jacobian determinant, i.e. the weight for integration in physical coordinates.
+    pub fn physical_weight(&self, q: usize) -> T {
+        self.quadrature.weights()[q] * self.jacobian_determinants[q].abs()
+    }
+
+    /// The values of all basis functions of the element at quadrature point `q`.
+    pub fn basis_values(&self, q: usize) -> &[T] {
+        let n = self.nodes.len();
+        &self.basis_values[q * n..(q + 1) * n]
+    }
+
+    /// The gradients of all basis functions of the element with respect to physical
+    /// coordinates at quadrature point `q`, stored as columns.
+    pub fn basis_gradients(&self, q: usize) -> MatrixView<'_, T, D, Dyn> {
+        let n = self.nodes.len();
+        let gradients = &self.basis_gradients[D::dim() * n * q..D::dim() * n * (q + 1)];
+        MatrixView::from_slice_generic(gradients, D::name(), Dyn(n))
+    }
+
+    /// Interpolates the element-local solution vector `u_local` at quadrature point `q`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `u_local` is not the number of element nodes times `S`.
+    pub fn interpolate<'a, S>(&self, u_local: impl Into<DVectorView<'a, T>>, q: usize) -> OVector<T, S>
+    where
+        S: SmallDim,
+        DefaultAllocator: DimAllocator<T, S>,
+    {
+        compute_interpolation(u_local, self.basis_values(q))
+    }
+
+    /// Interpolates the gradient with respect to physical coordinates of the element-local
+    /// solution vector `u_local` at quadrature point `q`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `u_local` is not the number of element nodes times `S`.
+    pub fn interpolate_gradient<'a, S>(&self, u_local: impl Into<DVectorView<'a, T>>, q: usize) -> OMatrix<T, D, S>
+    where
+        S: SmallDim,
+        DefaultAllocator: BiDimAllocator<T, D, S>,
+    {
+        let n = self.nodes.len();
+        let gradients = &self.basis_gradients[D::dim() * n * q..D::dim() * n * (q + 1)];
+        let u_local: DVectorView<T> = u_local.into();
+        compute_interpolation_gradient(u_local, DVectorView::from_slice(gradients, gradients.len()))
+    }
+}
+
+fn invert_jacobian<T, D>(jacobian: OMatrix<T, D, D>) -> eyre::Result<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    jacobian
+        .try_inverse()
+        .ok_or_else(|| eyre!("Singular element Jacobian encountered"))
+}
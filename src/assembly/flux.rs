@@ -0,0 +1,420 @@
+//! Numerical fluxes for scalar hyperbolic conservation laws.
+//!
+//! A scalar conservation law for a quantity $u$ with flux function $\vec f(u)$ reads
+//! <div>$$
+//! \frac{\partial u}{\partial t} + \nabla \cdot \vec f(u) = 0.
+//! $$</div>
+//! Discontinuous Galerkin and finite volume discretizations couple neighboring cells only through
+//! a *numerical flux* $\hat F(u^-, u^+, \vec n)$, which approximates the normal flux
+//! $\vec f(u) \cdot \vec n$ on a face from the traces $u^-$ and $u^+$ on either side of the face,
+//! with $\vec n$ the unit normal pointing from the $u^-$ side towards the $u^+$ side.
+//!
+//! This module provides the conservation laws [`LinearAdvection`] and [`BurgersEquation`], the
+//! numerical fluxes [`UpwindFlux`], [`LaxFriedrichsFlux`] and [`RusanovFlux`], and a
+//! [`FaceFluxIntegrator`] for integrating numerical fluxes over the faces of a mesh. All
+//! numerical fluxes are *consistent*, $\hat F(u, u, \vec n) = \vec f(u) \cdot \vec n$, and
+//! *conservative*, $\hat F(u^-, u^+, \vec n) = - \hat F(u^+, u^-, - \vec n)$.
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::local::surface_measure_and_normal;
+use crate::connectivity::Connectivity;
+use crate::element::{ConnectivityReferenceDim, ElementConnectivity, FiniteElement};
+use crate::mesh::split::{find_interior_faces, InteriorFace};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVector, DefaultAllocator, OPoint, OVector};
+use crate::quadrature::QuadraturePair;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+
+/// A scalar conservation law $\partial_t u + \nabla \cdot \vec f(u, \vec x) = 0$.
+pub trait ScalarConservationLaw<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Evaluates the flux $\vec f(u, \vec x)$.
+    fn flux(&self, u: T, x: &OPoint<T, D>) -> OVector<T, D>;
+
+    /// Evaluates the derivative $\partial \vec f / \partial u$ of the flux, i.e. the
+    /// characteristic velocity.
+    fn flux_derivative(&self, u: T, x: &OPoint<T, D>) -> OVector<T, D>;
+}
+
+/// Linear advection $\vec f(u) = \vec \beta u$ with a constant velocity $\vec \beta$.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearAdvection<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    velocity: OVector<T, D>,
+}
+
+impl<T, D> LinearAdvection<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn new(velocity: OVector<T, D>) -> Self {
+        Self { velocity }
+    }
+
+    pub fn velocity(&self) -> &OVector<T, D> {
+        &self.velocity
+    }
+}
+
+impl<T, D> ScalarConservationLaw<T, D> for LinearAdvection<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn flux(&self, u: T, _x: &OPoint<T, D>) -> OVector<T, D> {
+        &self.velocity * u
+    }
+
+    fn flux_derivative(&self, _u: T, _x: &OPoint<T, D>) -> OVector<T, D> {
+        self.velocity.clone()
+    }
+}
+
+/// The inviscid Burgers equation $\vec f(u) = \frac{1}{2} u^2 \vec \beta$ along a constant
+/// direction $\vec \beta$.
+///
+/// The characteristic velocity $u \vec \beta$ depends on the solution, so that discontinuities
+/// may develop into shocks or rarefaction waves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurgersEquation<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    direction: OVector<T, D>,
+}
+
+impl<T, D> BurgersEquation<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn new(direction: OVector<T, D>) -> Self {
+        Self { direction }
+    }
+
+    pub fn direction(&self) -> &OVector<T, D> {
+        &self.direction
+    }
+}
+
+impl<T, D> ScalarConservationLaw<T, D> for BurgersEquation<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn flux(&self, u: T, _x: &OPoint<T, D>) -> OVector<T, D> {
+        &self.direction * (u * u / T::from_f64(2.0).unwrap())
+    }
+
+    fn flux_derivative(&self, u: T, _x: &OPoint<T, D>) -> OVector<T, D> {
+        &self.direction * u
+    }
+}
+
+/// A numerical flux $\hat F(u^-, u^+, \vec n)$ approximating the normal flux on a face.
+pub trait NumericalFlux<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Computes the numerical flux for the traces `u_minus` and `u_plus` at the point `x` on a
+    /// face with unit normal `normal` pointing from the `u_minus` side towards the `u_plus` side.
+    fn compute_normal_flux<Law>(&self, law: &Law, u_minus: T, u_plus: T, normal: &OVector<T, D>, x: &OPoint<T, D>) -> T
+    where
+        Law: ?Sized + ScalarConservationLaw<T, D>;
+}
+
+/// The upwind flux, which takes the normal flux from the side the information comes from.
+///
+/// The direction of propagation is determined by the sign of the Rankine-Hugoniot speed
+/// <div>$$
+/// s = \frac{(\vec f(u^+) - \vec f(u^-)) \cdot \vec n}{u^+ - u^-},
+/// $$</div>
+/// and the flux is $\vec f(u^-) \cdot \vec n$ if $s \geq 0$ and $\vec f(u^+) \cdot \vec n$
+/// otherwise (the Murman-Roe flux). For linear advection, $s = \vec \beta \cdot \vec n$ and the
+/// flux reduces to the classical upwind flux. For nonlinear problems, the flux does not
+/// distinguish transonic rarefactions from shocks and may therefore admit entropy-violating
+/// expansion shocks, in which case [`RusanovFlux`] is a more robust choice.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct UpwindFlux;
+
+impl<T, D> NumericalFlux<T, D> for UpwindFlux
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn compute_normal_flux<Law>(&self, law: &Law, u_minus: T, u_plus: T, normal: &OVector<T, D>, x: &OPoint<T, D>) -> T
+    where
+        Law: ?Sized + ScalarConservationLaw<T, D>,
+    {
+        let f_minus = law.flux(u_minus, x).dot(normal);
+        if u_plus == u_minus {
+            return f_minus;
+        }
+        let f_plus = law.flux(u_plus, x).dot(normal);
+        let speed = (f_plus - f_minus) / (u_plus - u_minus);
+        if speed >= T::zero() {
+            f_minus
+        } else {
+            f_plus
+        }
+    }
+}
+
+/// The (global) Lax-Friedrichs flux with a fixed dissipation coefficient $\alpha$,
+/// <div>$$
+/// \hat F(u^-, u^+, \vec n) = \frac{1}{2} (\vec f(u^-) + \vec f(u^+)) \cdot \vec n
+///     - \frac{\alpha}{2} (u^+ - u^-).
+/// $$</div>
+///
+/// The flux is monotone if $\alpha$ bounds the characteristic speed
+/// $|\vec f'(u) \cdot \vec n|$ over all states in the solution.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LaxFriedrichsFlux<T> {
+    dissipation: T,
+}
+
+impl<T: Real> LaxFriedrichsFlux<T> {
+    pub fn new(dissipation: T) -> Self {
+        Self { dissipation }
+    }
+
+    pub fn dissipation(&self) -> T {
+        self.dissipation
+    }
+}
+
+impl<T, D> NumericalFlux<T, D> for LaxFriedrichsFlux<T>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn compute_normal_flux<Law>(&self, law: &Law, u_minus: T, u_plus: T, normal: &OVector<T, D>, x: &OPoint<T, D>) -> T
+    where
+        Law: ?Sized + ScalarConservationLaw<T, D>,
+    {
+        central_flux_with_dissipation(law, u_minus, u_plus, normal, x, self.dissipation)
+    }
+}
+
+/// The Rusanov (or local Lax-Friedrichs) flux.
+///
+/// The flux corresponds to [`LaxFriedrichsFlux`] with the dissipation coefficient chosen locally
+/// as the largest characteristic speed of the two traces,
+/// $\alpha = \max(|\vec f'(u^-) \cdot \vec n|, |\vec f'(u^+) \cdot \vec n|)$.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RusanovFlux;
+
+impl<T, D> NumericalFlux<T, D> for RusanovFlux
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn compute_normal_flux<Law>(&self, law: &Law, u_minus: T, u_plus: T, normal: &OVector<T, D>, x: &OPoint<T, D>) -> T
+    where
+        Law: ?Sized + ScalarConservationLaw<T, D>,
+    {
+        let speed_minus = law.flux_derivative(u_minus, x).dot(normal).abs();
+        let speed_plus = law.flux_derivative(u_plus, x).dot(normal).abs();
+        let dissipation = speed_minus.max(speed_plus);
+        central_flux_with_dissipation(law, u_minus, u_plus, normal, x, dissipation)
+    }
+}
+
+fn central_flux_with_dissipation<T, D, Law>(
+    law: &Law,
+    u_minus: T,
+    u_plus: T,
+    normal: &OVector<T, D>,
+    x: &OPoint<T, D>,
+    dissipation: T,
+) -> T
+where
+    T: Real,
+    D: SmallDim,
+    Law: ?Sized + ScalarConservationLaw<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let half = T::from_f64(0.5).unwrap();
+    let central = (law.flux(u_minus, x) + law.flux(u_plus, x)).dot(normal);
+    half * (central - dissipation * (u_plus - u_minus))
+}
+
+/// Integrates numerical fluxes over the faces of a mesh.
+///
+/// Faces are integrated with the given quadrature rule on the reference element of the face
+/// connectivity of the mesh, and the normal of a face of a cell is oriented so that it points
+/// away from the vertex centroid of the cell. This is the outward normal for convex cells.
+///
+/// The face connectivity must have an associated finite element, which is currently the case for
+/// triangle and quadrilateral meshes in 2D and for tetrahedral meshes in 3D.
+pub struct FaceFluxIntegrator<'a, T, D, C, Law: ?Sized, Flux>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+{
+    mesh: &'a Mesh<T, D, C>,
+    law: &'a Law,
+    flux: &'a Flux,
+    face_quadrature: QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+}
+
+impl<'a, T, D, C, Law, Flux> FaceFluxIntegrator<'a, T, D, C, Law, Flux>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    Law: ?Sized + ScalarConservationLaw<T, D>,
+    Flux: NumericalFlux<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+{
+    pub fn new(
+        mesh: &'a Mesh<T, D, C>,
+        law: &'a Law,
+        flux: &'a Flux,
+        face_quadrature: QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+    ) -> Self {
+        Self {
+            mesh,
+            law,
+            flux,
+            face_quadrature,
+        }
+    }
+
+    /// Integrates the numerical flux over the face with the given local index in the given cell.
+    ///
+    /// The normal points out of the cell, so that `u_minus` is the trace of the solution from
+    /// inside the cell and `u_plus` the trace from outside (or the boundary value on a boundary
+    /// face). The traces are evaluated at the physical coordinates of the quadrature points.
+    pub fn integrate_face(
+        &self,
+        cell_index: usize,
+        local_face_index: usize,
+        u_minus: impl Fn(&OPoint<T, D>) -> T,
+        u_plus: impl Fn(&OPoint<T, D>) -> T,
+    ) -> eyre::Result<T> {
+        let cell = self
+            .mesh
+            .connectivity()
+            .get(cell_index)
+            .ok_or_else(|| eyre!("Cell index {} out of bounds", cell_index))?;
+        let face = cell
+            .get_face_connectivity(local_face_index)
+            .ok_or_else(|| eyre!("Cell {} has no face with local index {}", cell_index, local_face_index))?;
+        let face_element = face.element(self.mesh.vertices()).ok_or_else(|| {
+            eyre!(
+                "Failed to construct element for face {} of cell {}",
+                local_face_index,
+                cell_index
+            )
+        })?;
+        let cell_centroid = self.vertex_centroid(cell.vertex_indices());
+
+        let (weights, points) = &self.face_quadrature;
+        let mut integral = T::zero();
+        for (&w, xi) in izip!(weights, points) {
+            let x = face_element.map_reference_coords(xi);
+            let (measure, mut normal) = surface_measure_and_normal(&face_element.reference_jacobian(xi));
+            if (&x - &cell_centroid).dot(&normal) < T::zero() {
+                normal = -normal;
+            }
+            let flux = self
+                .flux
+                .compute_normal_flux(self.law, u_minus(&x), u_plus(&x), &normal, &x);
+            integral += w * measure * flux;
+        }
+        Ok(integral)
+    }
+
+    /// Integrates the numerical flux over an interior face.
+    ///
+    /// The normal points from `face.cells[0]` towards `face.cells[1]`, i.e. `u_minus` is the
+    /// trace from the first cell and `u_plus` the trace from the second cell. The flux out of
+    /// the second cell is the negative of the returned value.
+    pub fn integrate_interior_face(
+        &self,
+        face: &InteriorFace,
+        u_minus: impl Fn(&OPoint<T, D>) -> T,
+        u_plus: impl Fn(&OPoint<T, D>) -> T,
+    ) -> eyre::Result<T> {
+        self.integrate_face(face.cells[0], face.local_faces[0], u_minus, u_plus)
+    }
+
+    /// Assembles the face flux residual of a piecewise constant (finite volume or DG0) solution.
+    ///
+    /// Given one value per cell, computes for each cell $K$ the net outward flux
+    /// <div>$$
+    /// r_K = \sum_{F \subset \partial K} \int_F \hat F(u_K, u_{K'}, \vec n_K) \enspace \mathrm{d} s,
+    /// $$</div>
+    /// where $u_{K'}$ is the value in the neighboring cell across an interior face, and is given
+    /// by `boundary_value` on boundary faces. The semi-discrete finite volume scheme then reads
+    /// $|K| \, \mathrm{d} u_K / \mathrm{d} t = - r_K$. Since each interior face flux is added to
+    /// one cell and subtracted from the other, the sum of the residual equals the net flux
+    /// through the boundary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of cell values does not match the number of cells in the
+    /// mesh, or if a face element cannot be constructed.
+    pub fn assemble_piecewise_constant_residual(
+        &self,
+        u_cells: &[T],
+        boundary_value: impl Fn(&OPoint<T, D>) -> T,
+    ) -> eyre::Result<DVector<T>> {
+        let num_cells = self.mesh.connectivity().len();
+        if u_cells.len() != num_cells {
+            return Err(eyre!(
+                "Number of cell values ({}) does not match number of cells ({})",
+                u_cells.len(),
+                num_cells
+            ));
+        }
+
+        let mut residual = DVector::zeros(num_cells);
+        for face in find_interior_faces(self.mesh) {
+            let [cell0, cell1] = face.cells;
+            let (u0, u1) = (u_cells[cell0], u_cells[cell1]);
+            let face_flux = self.integrate_interior_face(&face, |_| u0, |_| u1)?;
+            residual[cell0] += face_flux;
+            residual[cell1] -= face_flux;
+        }
+        for (_, cell, local_face) in self.mesh.find_boundary_faces() {
+            let u_cell = u_cells[cell];
+            residual[cell] += self.integrate_face(cell, local_face, |_| u_cell, &boundary_value)?;
+        }
+        Ok(residual)
+    }
+
+    fn vertex_centroid(&self, vertex_indices: &[usize]) -> OPoint<T, D> {
+        let vertices = self.mesh.vertices();
+        let sum = vertex_indices
+            .iter()
+            .fold(OVector::<T, D>::zeros(), |sum, &i| sum + &vertices[i].coords);
+        OPoint::from(sum / T::from_usize(vertex_indices.len()).unwrap())
+    }
+}
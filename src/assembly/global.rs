@@ -1,8 +1,12 @@
 use crate::assembly::local::{
-    ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementMixedConnectivityAssembler,
+    ElementMixedMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
 };
+use crate::index::{ElementIndex, NodeIndex};
 use crate::space::FiniteElementConnectivity;
 use crate::Real;
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
 use fenris_nested_vec::NestedVec;
 use fenris_paradis::adapter::BlockAdapter;
 use fenris_paradis::coloring::sequential_greedy_coloring;
@@ -62,66 +66,76 @@ impl<T: Scalar> CsrAssembler<T> {
     ///
     /// The implementation explicitly avoids storing duplicate entries in order to prevent
     /// excessive memory costs.
-    pub fn assemble_pattern(&self, element_assembler: &impl ElementConnectivityAssembler) -> SparsityPattern {
-        let sdim = element_assembler.solution_dim();
-        let num_nodes = element_assembler.num_nodes();
-        let num_rows = sdim * num_nodes;
-        let mut node_sets: Vec<FxHashSet<usize>> = vec![FxHashSet::default(); num_nodes];
-        let mut element_global_nodes = Vec::new();
-        for i in 0..element_assembler.num_elements() {
-            let element_node_count = element_assembler.element_node_count(i);
-            element_global_nodes.resize(element_node_count, usize::MAX);
-            element_assembler.populate_element_nodes(&mut element_global_nodes, i);
+    pub fn assemble_pattern(
+        &self,
+        element_assembler: &(impl ?Sized + ElementConnectivityAssembler),
+    ) -> SparsityPattern {
+        assemble_csr_pattern(element_assembler)
+    }
+}
 
-            for &node_i in &element_global_nodes {
-                for &node_j in &element_global_nodes {
-                    node_sets[node_i].insert(node_j);
-                }
+/// Assembles the sparsity pattern associated with the given element connectivity.
+fn assemble_csr_pattern(element_assembler: &(impl ?Sized + ElementConnectivityAssembler)) -> SparsityPattern {
+    let sdim = element_assembler.solution_dim();
+    let num_nodes = element_assembler.num_nodes();
+    let num_rows = sdim * num_nodes;
+    let mut node_sets: Vec<FxHashSet<usize>> = vec![FxHashSet::default(); num_nodes];
+    let mut element_global_nodes = Vec::new();
+    for i in 0..element_assembler.num_elements() {
+        let element_node_count = element_assembler.element_node_count(i.into());
+        element_global_nodes.resize(element_node_count, usize::MAX);
+        element_assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut element_global_nodes), i.into());
+
+        for &node_i in &element_global_nodes {
+            for &node_j in &element_global_nodes {
+                node_sets[node_i].insert(node_j);
             }
         }
+    }
 
-        let mut offsets = Vec::with_capacity(num_rows);
-        offsets.push(0);
-        let mut current_offset = 0;
-        for node_set in &node_sets {
-            for _ in 0..sdim {
-                let count = sdim * node_set.len();
-                offsets.push(current_offset + count);
-                current_offset += count;
-            }
+    let mut offsets = Vec::with_capacity(num_rows);
+    offsets.push(0);
+    let mut current_offset = 0;
+    for node_set in &node_sets {
+        for _ in 0..sdim {
+            let count = sdim * node_set.len();
+            offsets.push(current_offset + count);
+            current_offset += count;
         }
-        assert_eq!(offsets.len(), num_rows + 1);
-
-        let mut col_indices = Vec::with_capacity(*offsets.last().unwrap());
-        let mut node_buffer: Vec<usize> = Vec::new();
-        for node_set in &node_sets {
-            node_buffer.clear();
-            node_buffer.extend(node_set);
-            node_buffer.sort_unstable();
-            // We have sdim identical rows (in terms of pattern)
-            for _ in 0..sdim {
-                for node_j in &node_buffer {
-                    for j in 0..sdim {
-                        let col_idx = sdim * node_j + j;
-                        col_indices.push(col_idx);
-                    }
+    }
+    assert_eq!(offsets.len(), num_rows + 1);
+
+    let mut col_indices = Vec::with_capacity(*offsets.last().unwrap());
+    let mut node_buffer: Vec<usize> = Vec::new();
+    for node_set in &node_sets {
+        node_buffer.clear();
+        node_buffer.extend(node_set);
+        node_buffer.sort_unstable();
+        // We have sdim identical rows (in terms of pattern)
+        for _ in 0..sdim {
+            for node_j in &node_buffer {
+                for j in 0..sdim {
+                    let col_idx = sdim * node_j + j;
+                    col_indices.push(col_idx);
                 }
             }
         }
+    }
 
-        assert_eq!(*offsets.last().unwrap(), col_indices.len());
+    assert_eq!(*offsets.last().unwrap(), col_indices.len());
 
-        debug_assert!(
-            SparsityPattern::try_from_offsets_and_indices(num_rows, num_rows, offsets.clone(), col_indices.clone())
-                .is_ok(),
-            "Internal error: constructed sparsity pattern is not valid. This is a bug!"
-        );
-        unsafe { SparsityPattern::from_offset_and_indices_unchecked(num_rows, num_rows, offsets, col_indices) }
-    }
+    debug_assert!(
+        SparsityPattern::try_from_offsets_and_indices(num_rows, num_rows, offsets.clone(), col_indices.clone()).is_ok(),
+        "Internal error: constructed sparsity pattern is not valid. This is a bug!"
+    );
+    unsafe { SparsityPattern::from_offset_and_indices_unchecked(num_rows, num_rows, offsets, col_indices) }
 }
 
 impl<T: Real> CsrAssembler<T> {
-    pub fn assemble(&self, element_assembler: &impl ElementMatrixAssembler<T>) -> eyre::Result<CsrMatrix<T>> {
+    pub fn assemble(
+        &self,
+        element_assembler: &(impl ?Sized + ElementMatrixAssembler<T>),
+    ) -> eyre::Result<CsrMatrix<T>> {
         let pattern = self.assemble_pattern(element_assembler);
         let initial_matrix_values = vec![T::zero(); pattern.nnz()];
         let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern, initial_matrix_values)
@@ -133,7 +147,7 @@ impl<T: Real> CsrAssembler<T> {
     pub fn assemble_into_csr(
         &self,
         csr: &mut CsrMatrix<T>,
-        element_assembler: &impl ElementMatrixAssembler<T>,
+        element_assembler: &(impl ?Sized + ElementMatrixAssembler<T>),
     ) -> eyre::Result<()> {
         // Reuse previously allocated buffers
         let ws = &mut *self.workspace.borrow_mut();
@@ -144,7 +158,7 @@ impl<T: Real> CsrAssembler<T> {
         let sdim = element_assembler.solution_dim();
 
         for i in 0..element_assembler.num_elements() {
-            let element_node_count = element_assembler.element_node_count(i);
+            let element_node_count = element_assembler.element_node_count(i.into());
             let element_matrix_dim = sdim * element_node_count;
 
             element_global_nodes.resize(element_node_count, 0);
@@ -152,7 +166,7 @@ impl<T: Real> CsrAssembler<T> {
 
             let matrix_slice = DMatrixViewMut::from(&mut *element_matrix);
             element_assembler.assemble_element_matrix_into(i, matrix_slice)?;
-            element_assembler.populate_element_nodes(element_global_nodes, i);
+            element_assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(element_global_nodes), i.into());
 
             connectivity_permutation.clear();
             connectivity_permutation.extend(0..element_node_count);
@@ -180,6 +194,466 @@ impl<T: Real> CsrAssembler<T> {
 
         Ok(())
     }
+
+    /// Updates a previously assembled matrix after the element matrices of the given elements
+    /// have changed.
+    ///
+    /// Instead of reassembling the full matrix, only the rows associated with the nodes of the
+    /// modified elements are reset and then reassembled from all elements connected to these
+    /// nodes. The remaining rows only receive contributions from unmodified elements, and are
+    /// therefore left untouched. This is useful for adaptive or optimization loops in which
+    /// e.g. material parameters change only locally.
+    ///
+    /// The sparsity pattern of the matrix must contain the pattern associated with the element
+    /// assembler, which is the case if the connectivity has not changed since the matrix was
+    /// assembled. If the mesh changes, for example due to refinement, the matrix must be
+    /// assembled from scratch.
+    ///
+    /// Returns the number of elements that were reassembled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the matrix are not consistent with the element
+    /// assembler, if an element index is out of bounds or if element assembly fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sparsity pattern of the matrix does not contain an entry
+    /// associated with the element assembler.
+    pub fn update_elements_in_csr(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &(impl ?Sized + ElementMatrixAssembler<T>),
+        modified_elements: &[usize],
+    ) -> eyre::Result<usize> {
+        let sdim = element_assembler.solution_dim();
+        let num_nodes = element_assembler.num_nodes();
+        if csr.nrows() != sdim * num_nodes || csr.ncols() != sdim * num_nodes {
+            return Err(eyre!(
+                "Matrix dimensions {}x{} are not consistent with {} nodes with solution dimension {}",
+                csr.nrows(),
+                csr.ncols(),
+                num_nodes,
+                sdim
+            ));
+        }
+
+        // Reuse previously allocated buffers
+        let ws = &mut *self.workspace.borrow_mut();
+        let connectivity_permutation = &mut ws.connectivity_permutation;
+        let element_global_nodes = &mut ws.element_global_nodes;
+        let element_matrix = &mut ws.element_matrix;
+
+        // Mark the nodes of all modified elements, whose rows need to be reassembled
+        let mut is_node_affected = vec![false; num_nodes];
+        for &element_index in modified_elements {
+            if element_index >= element_assembler.num_elements() {
+                return Err(eyre!(
+                    "Element index {element_index} is out of bounds for {} elements",
+                    element_assembler.num_elements()
+                ));
+            }
+            element_global_nodes.resize(element_assembler.element_node_count(element_index.into()), 0);
+            element_assembler.populate_element_nodes(
+                NodeIndex::from_raw_slice_mut(element_global_nodes),
+                element_index.into(),
+            );
+            for &node in element_global_nodes.iter() {
+                is_node_affected[node] = true;
+            }
+        }
+
+        for (node, _) in enumerate(&is_node_affected).filter(|(_, &affected)| affected) {
+            for i in 0..sdim {
+                let mut csr_row = csr.row_mut(sdim * node + i);
+                csr_row.values_mut().fill(T::zero());
+            }
+        }
+
+        let mut num_reassembled_elements = 0;
+        for element_index in 0..element_assembler.num_elements() {
+            let element_node_count = element_assembler.element_node_count(element_index.into());
+            element_global_nodes.resize(element_node_count, 0);
+            element_assembler.populate_element_nodes(
+                NodeIndex::from_raw_slice_mut(element_global_nodes),
+                element_index.into(),
+            );
+            if !element_global_nodes
+                .iter()
+                .any(|&node| is_node_affected[node])
+            {
+                continue;
+            }
+
+            let element_matrix_dim = sdim * element_node_count;
+            element_matrix.resize_mut(element_matrix_dim, element_matrix_dim, T::zero());
+            let matrix_slice = DMatrixViewMut::from(&mut *element_matrix);
+            element_assembler.assemble_element_matrix_into(element_index, matrix_slice)?;
+            num_reassembled_elements += 1;
+
+            connectivity_permutation.clear();
+            connectivity_permutation.extend(0..element_node_count);
+            connectivity_permutation.sort_unstable_by_key(|i| element_global_nodes[*i]);
+
+            for (local_node_idx, &global_node_idx) in element_global_nodes.iter().enumerate() {
+                // Rows of unaffected nodes already contain the contributions of this element
+                if !is_node_affected[global_node_idx] {
+                    continue;
+                }
+                for i in 0..sdim {
+                    let local_row_index = sdim * local_node_idx + i;
+                    let global_row_index = sdim * global_node_idx + i;
+                    let mut csr_row = csr.row_mut(global_row_index);
+                    let (cols, values) = csr_row.cols_and_values_mut();
+
+                    let a_row = element_matrix.row(local_row_index);
+                    add_element_row_to_csr_row(
+                        values,
+                        cols,
+                        element_global_nodes,
+                        connectivity_permutation,
+                        sdim,
+                        &a_row,
+                    );
+                }
+            }
+        }
+
+        Ok(num_reassembled_elements)
+    }
+}
+
+impl<T: Scalar> CsrAssembler<T> {
+    /// Assembles the (generally rectangular) sparsity pattern associated with the given mixed
+    /// element assembler.
+    pub fn assemble_mixed_pattern(
+        &self,
+        element_assembler: &(impl ?Sized + ElementMixedConnectivityAssembler),
+    ) -> SparsityPattern {
+        let row_sdim = element_assembler.row_solution_dim();
+        let col_sdim = element_assembler.col_solution_dim();
+        let num_row_nodes = element_assembler.num_row_nodes();
+        let num_rows = row_sdim * num_row_nodes;
+        let num_cols = col_sdim * element_assembler.num_col_nodes();
+        let mut node_sets: Vec<FxHashSet<usize>> = vec![FxHashSet::default(); num_row_nodes];
+        let mut row_nodes = Vec::new();
+        let mut col_nodes = Vec::new();
+        for i in 0..element_assembler.num_elements() {
+            row_nodes.resize(element_assembler.row_element_node_count(i), usize::MAX);
+            col_nodes.resize(element_assembler.col_element_node_count(i), usize::MAX);
+            element_assembler.populate_row_element_nodes(&mut row_nodes, i);
+            element_assembler.populate_col_element_nodes(&mut col_nodes, i);
+            for &node_i in &row_nodes {
+                node_sets[node_i].extend(col_nodes.iter().copied());
+            }
+        }
+
+        let mut offsets = Vec::with_capacity(num_rows + 1);
+        let mut col_indices = Vec::new();
+        let mut node_buffer: Vec<usize> = Vec::new();
+        offsets.push(0);
+        for node_set in &node_sets {
+            node_buffer.clear();
+            node_buffer.extend(node_set);
+            node_buffer.sort_unstable();
+            // All rows associated with the same node have identical patterns
+            for _ in 0..row_sdim {
+                for &node_j in &node_buffer {
+                    col_indices.extend((0..col_sdim).map(|j| col_sdim * node_j + j));
+                }
+                offsets.push(col_indices.len());
+            }
+        }
+
+        SparsityPattern::try_from_offsets_and_indices(num_rows, num_cols, offsets, col_indices)
+            .expect("Internal error: constructed sparsity pattern is not valid. This is a bug!")
+    }
+}
+
+impl<T: Real> CsrAssembler<T> {
+    /// Assembles the global matrix associated with the given mixed element assembler.
+    ///
+    /// The result has $s_r N_r$ rows and $s_c N_c$ columns, where $s_r, s_c$ are the row and
+    /// column solution dimensions and $N_r, N_c$ the number of row and column nodes.
+    pub fn assemble_mixed(
+        &self,
+        element_assembler: &(impl ?Sized + ElementMixedMatrixAssembler<T>),
+    ) -> eyre::Result<CsrMatrix<T>> {
+        let pattern = self.assemble_mixed_pattern(element_assembler);
+        let initial_matrix_values = vec![T::zero(); pattern.nnz()];
+        let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern, initial_matrix_values)
+            .expect("CSR data must be valid by definition");
+        self.assemble_mixed_into_csr(&mut matrix, element_assembler)?;
+        Ok(matrix)
+    }
+
+    /// Adds the element matrices of the given mixed element assembler to an existing CSR matrix.
+    ///
+    /// The sparsity pattern of the matrix must contain the pattern of the mixed assembler.
+    pub fn assemble_mixed_into_csr(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &(impl ?Sized + ElementMixedMatrixAssembler<T>),
+    ) -> eyre::Result<()> {
+        let ws = &mut *self.workspace.borrow_mut();
+        let connectivity_permutation = &mut ws.connectivity_permutation;
+        let element_matrix = &mut ws.element_matrix;
+        let mut row_nodes = Vec::new();
+        let mut col_nodes = Vec::new();
+
+        let row_sdim = element_assembler.row_solution_dim();
+        let col_sdim = element_assembler.col_solution_dim();
+
+        for i in 0..element_assembler.num_elements() {
+            row_nodes.resize(element_assembler.row_element_node_count(i), 0);
+            col_nodes.resize(element_assembler.col_element_node_count(i), 0);
+            element_matrix.resize_mut(row_sdim * row_nodes.len(), col_sdim * col_nodes.len(), T::zero());
+
+            element_assembler.assemble_element_mixed_matrix_into(i, DMatrixViewMut::from(&mut *element_matrix))?;
+            element_assembler.populate_row_element_nodes(&mut row_nodes, i);
+            element_assembler.populate_col_element_nodes(&mut col_nodes, i);
+
+            connectivity_permutation.clear();
+            connectivity_permutation.extend(0..col_nodes.len());
+            connectivity_permutation.sort_unstable_by_key(|i| col_nodes[*i]);
+
+            for (local_node_idx, global_node_idx) in row_nodes.iter().enumerate() {
+                for i in 0..row_sdim {
+                    let mut csr_row = csr.row_mut(row_sdim * global_node_idx + i);
+                    let (cols, values) = csr_row.cols_and_values_mut();
+                    let a_row = element_matrix.row(row_sdim * local_node_idx + i);
+                    add_element_row_to_csr_row(values, cols, &col_nodes, connectivity_permutation, col_sdim, &a_row);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wraps a [`FiniteElementConnectivity`] in order to use it as an [`ElementConnectivityAssembler`].
+struct ConnectivityWithSolutionDim<'a, C: ?Sized> {
+    connectivity: &'a C,
+    solution_dim: usize,
+}
+
+impl<'a, C> ElementConnectivityAssembler for ConnectivityWithSolutionDim<'a, C>
+where
+    C: ?Sized + FiniteElementConnectivity,
+{
+    fn solution_dim(&self) -> usize {
+        self.solution_dim
+    }
+
+    fn num_elements(&self) -> usize {
+        self.connectivity.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.connectivity.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.connectivity.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.connectivity
+            .populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(PRECOMPUTED_CSR_WORKSPACE);
+
+struct PrecomputedCsrAssemblerWorkspace<T: Scalar> {
+    element_matrix: DMatrix<T>,
+}
+
+impl<T: Real> Default for PrecomputedCsrAssemblerWorkspace<T> {
+    fn default() -> Self {
+        Self {
+            element_matrix: DMatrix::zeros(0, 0),
+        }
+    }
+}
+
+/// A CSR assembler with a precomputed sparsity pattern.
+///
+/// [`CsrAssembler`] needs to search for the position of every entry of each element matrix in
+/// the rows of the CSR matrix. When the same matrix is assembled many times, for example in every
+/// iteration of Newton's method, this assembler instead computes the sparsity pattern and the
+/// position of every element matrix entry in the CSR values array once. Subsequent assemblies
+/// then only need to scatter the element matrices into the values array.
+///
+/// The element matrices are assembled into a per-thread workspace, so that no allocations
+/// are necessary after the first assembly.
+///
+/// Note that the mapping requires storing $(sn)^2$ indices per element, where $s$ is the solution
+/// dimension and $n$ is the number of nodes in the element.
+#[derive(Debug, Clone)]
+pub struct PrecomputedCsrAssembler {
+    pattern: SparsityPattern,
+    solution_dim: usize,
+    num_nodes: usize,
+    /// For each element, the indices into the CSR values array of the entries of the element
+    /// matrix, stored in column-major order.
+    element_value_indices: NestedVec<usize>,
+}
+
+impl PrecomputedCsrAssembler {
+    /// Precomputes the sparsity pattern and element mapping for the given connectivity.
+    pub fn from_connectivity<C>(connectivity: &C, solution_dim: usize) -> Self
+    where
+        C: ?Sized + FiniteElementConnectivity,
+    {
+        Self::from_element_assembler(&ConnectivityWithSolutionDim {
+            connectivity,
+            solution_dim,
+        })
+    }
+
+    /// Precomputes the sparsity pattern and element mapping for the connectivity of the given
+    /// element assembler.
+    pub fn from_element_assembler(element_assembler: &(impl ?Sized + ElementConnectivityAssembler)) -> Self {
+        let sdim = element_assembler.solution_dim();
+        let pattern = assemble_csr_pattern(element_assembler);
+        let offsets = pattern.major_offsets();
+        let col_indices = pattern.minor_indices();
+
+        let mut element_value_indices = NestedVec::new();
+        let mut element_global_nodes = Vec::new();
+        for element_index in 0..element_assembler.num_elements() {
+            element_global_nodes.resize(element_assembler.element_node_count(element_index.into()), 0);
+            element_assembler.populate_element_nodes(
+                NodeIndex::from_raw_slice_mut(&mut element_global_nodes),
+                element_index.into(),
+            );
+
+            let mut indices = element_value_indices.begin_array();
+            // Column-major order, consistent with the storage of the element matrix
+            for &node_col in &element_global_nodes {
+                for j in 0..sdim {
+                    let global_col = sdim * node_col + j;
+                    for &node_row in &element_global_nodes {
+                        for i in 0..sdim {
+                            let global_row = sdim * node_row + i;
+                            let row_begin = offsets[global_row];
+                            let row_cols = &col_indices[row_begin..offsets[global_row + 1]];
+                            let local_idx = row_cols
+                                .binary_search(&global_col)
+                                .expect("Sparsity pattern must contain all element entries");
+                            indices.push_single(row_begin + local_idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            pattern,
+            solution_dim: sdim,
+            num_nodes: element_assembler.num_nodes(),
+            element_value_indices,
+        }
+    }
+
+    pub fn pattern(&self) -> &SparsityPattern {
+        &self.pattern
+    }
+
+    pub fn solution_dim(&self) -> usize {
+        self.solution_dim
+    }
+
+    /// Creates a CSR matrix with the precomputed sparsity pattern and all values set to zero.
+    pub fn create_zeroed_matrix<T: Real>(&self) -> CsrMatrix<T> {
+        let values = vec![T::zero(); self.pattern.nnz()];
+        CsrMatrix::try_from_pattern_and_values(self.pattern.clone(), values)
+            .expect("CSR data must be valid by definition")
+    }
+
+    pub fn assemble<T: Real>(&self, element_assembler: &impl ElementMatrixAssembler<T>) -> eyre::Result<CsrMatrix<T>> {
+        let mut matrix = self.create_zeroed_matrix();
+        self.assemble_into_csr(&mut matrix, element_assembler)?;
+        Ok(matrix)
+    }
+
+    /// Assembles the element matrices of the given element assembler into the given CSR matrix.
+    ///
+    /// The element matrices are *added* to the existing values of the matrix. Use
+    /// [`assemble_into_csr_values`](Self::assemble_into_csr_values) to overwrite them instead.
+    ///
+    /// The element assembler must have the same connectivity as the one used to
+    /// construct this assembler.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix does not have the precomputed sparsity pattern, if
+    /// the element assembler is incompatible with the precomputed connectivity or if the
+    /// element assembler returns an error.
+    pub fn assemble_into_csr<T: Real>(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        if csr.pattern() != &self.pattern {
+            return Err(eyre!("CSR matrix does not have the precomputed sparsity pattern"));
+        }
+        self.assemble_into_values(csr.values_mut(), element_assembler)
+    }
+
+    /// Overwrites the values of the given CSR matrix with the assembled global matrix.
+    ///
+    /// See [`assemble_into_csr`](Self::assemble_into_csr) for details.
+    pub fn assemble_into_csr_values<T: Real>(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        if csr.pattern() != &self.pattern {
+            return Err(eyre!("CSR matrix does not have the precomputed sparsity pattern"));
+        }
+        csr.values_mut().fill(T::zero());
+        self.assemble_into_values(csr.values_mut(), element_assembler)
+    }
+
+    fn assemble_into_values<T: Real>(
+        &self,
+        values: &mut [T],
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        let sdim = element_assembler.solution_dim();
+        if sdim != self.solution_dim
+            || element_assembler.num_nodes() != self.num_nodes
+            || element_assembler.num_elements() != self.element_value_indices.len()
+        {
+            return Err(eyre!(
+                "Element assembler is incompatible with the precomputed connectivity"
+            ));
+        }
+
+        with_thread_local_workspace(
+            &PRECOMPUTED_CSR_WORKSPACE,
+            |ws: &mut PrecomputedCsrAssemblerWorkspace<T>| {
+                let element_matrix = &mut ws.element_matrix;
+                for (element_index, indices) in self.element_value_indices.iter().enumerate() {
+                    let element_matrix_dim = sdim * element_assembler.element_node_count(element_index.into());
+                    if element_matrix_dim * element_matrix_dim != indices.len() {
+                        return Err(eyre!(
+                            "Node count of element {element_index} is incompatible with the precomputed connectivity"
+                        ));
+                    }
+                    element_matrix.resize_mut(element_matrix_dim, element_matrix_dim, T::zero());
+                    element_assembler
+                        .assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut *element_matrix))?;
+                    for (&value_index, &entry) in izip!(indices, element_matrix.as_slice()) {
+                        values[value_index] += entry;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
 }
 
 /// A parallel assembler for CSR matrices relying on a graph coloring of elements.
@@ -224,9 +698,9 @@ impl<T: Scalar + Send> CsrParAssembler<T> {
             assert!(batch_end >= batch_start);
             let mut node_buffer = node_buffer.get_or_default().borrow_mut();
             for i in batch_start..batch_end {
-                let element_node_count = element_assembler.element_node_count(i);
+                let element_node_count = element_assembler.element_node_count(i.into());
                 node_buffer.resize(element_node_count, usize::MAX);
-                element_assembler.populate_element_nodes(&mut node_buffer, i);
+                element_assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut node_buffer), i.into());
 
                 for &node_i in &*node_buffer {
                     let mut node_set = node_sets[node_i].lock();
@@ -328,7 +802,7 @@ impl<T: Real + Send> CsrParAssembler<T> {
                     let ws = &mut *self.workspace.get_or_default().borrow_mut();
 
                     let element_index = subset.label();
-                    let element_node_count = element_assembler.element_node_count(element_index);
+                    let element_node_count = element_assembler.element_node_count(element_index.into());
                     let element_matrix_dim = sdim * element_node_count;
 
                     ws.element_global_nodes.resize(element_node_count, 0);
@@ -337,7 +811,10 @@ impl<T: Real + Send> CsrParAssembler<T> {
 
                     let matrix_slice = DMatrixViewMut::from(&mut ws.element_matrix);
                     element_assembler.assemble_element_matrix_into(element_index, matrix_slice)?;
-                    element_assembler.populate_element_nodes(&mut ws.element_global_nodes, element_index);
+                    element_assembler.populate_element_nodes(
+                        NodeIndex::from_raw_slice_mut(&mut ws.element_global_nodes),
+                        element_index.into(),
+                    );
                     debug_assert_eq!(subset.global_indices(), ws.element_global_nodes.as_slice());
 
                     {
@@ -542,8 +1019,8 @@ pub fn color_nodes<C: FiniteElementConnectivity + ?Sized>(connectivity: &C) -> V
 
     let mut node_buffer = Vec::new();
     for element_index in 0..connectivity.num_elements() {
-        node_buffer.resize(connectivity.element_node_count(element_index), 0);
-        connectivity.populate_element_nodes(&mut node_buffer, element_index);
+        node_buffer.resize(connectivity.element_node_count(element_index.into()), 0);
+        connectivity.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut node_buffer), element_index.into());
         nested.push(&node_buffer);
     }
 
@@ -582,7 +1059,7 @@ impl<T: Real> VectorAssembler<T> {
     pub fn assemble_vector_into<'a>(
         &self,
         output: impl Into<DVectorViewMut<'a, T>>,
-        element_assembler: &impl ElementVectorAssembler<T>,
+        element_assembler: &(impl ?Sized + ElementVectorAssembler<T>),
     ) -> eyre::Result<()> {
         // TODO: Move impl into _ method to remove the impl Into<> compilation overhead
         let mut output = output.into();
@@ -594,12 +1071,12 @@ impl<T: Real> VectorAssembler<T> {
         let mut workspace = self.workspace.borrow_mut();
 
         for i in 0..num_elements {
-            let element_node_count = element_assembler.element_node_count(i);
+            let element_node_count = element_assembler.element_node_count(i.into());
             workspace.nodes.resize(element_node_count, usize::MAX);
             workspace
                 .vector
                 .resize_vertically_mut(s * element_node_count, T::zero());
-            element_assembler.populate_element_nodes(&mut workspace.nodes, i);
+            element_assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut workspace.nodes), i.into());
             element_assembler.assemble_element_vector_into(i, (&mut workspace.vector).into())?;
             add_local_to_global(&workspace.vector, &mut output, &workspace.nodes, s);
         }
@@ -607,7 +1084,10 @@ impl<T: Real> VectorAssembler<T> {
         Ok(())
     }
 
-    pub fn assemble_vector(&self, element_assembler: &impl ElementVectorAssembler<T>) -> eyre::Result<DVector<T>> {
+    pub fn assemble_vector(
+        &self,
+        element_assembler: &(impl ?Sized + ElementVectorAssembler<T>),
+    ) -> eyre::Result<DVector<T>> {
         let n = element_assembler.num_nodes();
         let mut result = DVector::zeros(element_assembler.solution_dim() * n);
         self.assemble_vector_into(&mut result, element_assembler)?;
@@ -660,12 +1140,13 @@ impl<T: Real> VectorParAssembler<T> {
                     let ws = &mut *self.workspace.get_or_default().borrow_mut();
 
                     let element_index = subset.label();
-                    let element_node_count = element_assembler.element_node_count(element_index);
+                    let element_node_count = element_assembler.element_node_count(element_index.into());
 
                     ws.nodes.resize(element_node_count, usize::MAX);
                     ws.vector
                         .resize_vertically_mut(s * element_node_count, T::zero());
-                    element_assembler.populate_element_nodes(&mut ws.nodes, element_index);
+                    element_assembler
+                        .populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut ws.nodes), element_index.into());
                     element_assembler.assemble_element_vector_into(element_index, (&mut ws.vector).into())?;
 
                     for local_node_idx in 0..element_node_count {
@@ -738,6 +1219,73 @@ where
     global_potential
 }
 
+/// Computes the sum of element-wise scalars in parallel by processing contiguous chunks of
+/// elements.
+///
+/// Each chunk of `chunk_size` elements is summed sequentially, and the sums of the chunks are
+/// added in order. In contrast to [`par_assemble_scalar`], the result is therefore deterministic
+/// for a fixed chunk size. Per-element values are only stored if `element_scalars` is provided,
+/// in which case it must have one entry per element.
+///
+/// # Errors
+///
+/// Returns an error if `element_scalars` has the wrong length or if element assembly fails.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+pub fn par_assemble_scalar_chunked<T>(
+    element_assembler: &(impl ElementScalarAssembler<T> + ?Sized + Sync),
+    chunk_size: usize,
+    element_scalars: Option<&mut [T]>,
+) -> eyre::Result<T>
+where
+    T: Real,
+{
+    assert!(chunk_size > 0, "Chunk size must be positive");
+    let num_elements = element_assembler.num_elements();
+    let assemble_chunk = |chunk_index: usize, mut output: Option<&mut [T]>| -> eyre::Result<T> {
+        let chunk_begin = chunk_index * chunk_size;
+        let chunk_end = min(chunk_begin + chunk_size, num_elements);
+        let mut chunk_sum = T::zero();
+        for i in chunk_begin..chunk_end {
+            let element_contrib = element_assembler
+                .assemble_element_scalar(i)
+                .map_err(|error| error.wrap_err(format!("Assembling scalar failed for element {}", i)))?;
+            if let Some(output) = &mut output {
+                output[i - chunk_begin] = element_contrib;
+            }
+            chunk_sum += element_contrib;
+        }
+        Ok(chunk_sum)
+    };
+
+    let chunk_sums: Vec<T> = match element_scalars {
+        Some(element_scalars) => {
+            if element_scalars.len() != num_elements {
+                return Err(eyre!(
+                    "Output for element scalars has length {}, but there are {} elements",
+                    element_scalars.len(),
+                    num_elements
+                ));
+            }
+            element_scalars
+                .par_chunks_mut(chunk_size)
+                .enumerate()
+                .map(|(chunk_index, output)| assemble_chunk(chunk_index, Some(output)))
+                .collect::<eyre::Result<_>>()?
+        }
+        None => (0..div_ceil(num_elements, chunk_size))
+            .into_par_iter()
+            .map(|chunk_index| assemble_chunk(chunk_index, None))
+            .collect::<eyre::Result<_>>()?,
+    };
+
+    Ok(chunk_sums
+        .into_iter()
+        .fold(T::zero(), |sum, chunk_sum| sum + chunk_sum))
+}
+
 // TODO: Maybe move to some other module?
 pub fn gather_global_to_local<'a, T: Scalar>(
     global: impl Into<DVectorView<'a, T>>,
@@ -0,0 +1,166 @@
+//! Assembly of linear goal functionals for goal-oriented error estimation.
+//!
+//! A linear goal functional $J(v)$ represents a quantity of interest of a solution, such as a
+//! weighted average over (parts of) the domain or the value at a point. For a finite element
+//! function $u_h = \sum_I u_I \phi_I$, linearity gives
+//! <div>$$
+//! J(u_h) = \sum_I \sum_i (u_I)_i \, J(\phi_I e_i) = \hat j \cdot \hat u,
+//! $$</div>
+//! so that a goal functional is represented by the global vector $\hat j$ with entries
+//! $\hat j_{Ii} = J(\phi_I e_i)$. This is also the right-hand side of the *dual problem*
+//! $\vec A^T \vec z = \hat j$ used in goal-oriented error estimation, see
+//! [`estimate_dwr_error_indicators`](crate::error::estimate_dwr_error_indicators).
+//!
+//! Goal functionals are provided as element vector assemblers, so that $\hat j$ can be assembled
+//! with [`VectorAssembler`](crate::assembly::global::VectorAssembler) and $J(u_h)$ evaluated
+//! with [`evaluate_linear_functional`]. Weighted volume integrals
+//! $J(v) = \int_\Omega j \cdot v \dx$ correspond to source terms and are assembled with
+//! [`ElementSourceAssemblerBuilder`](crate::assembly::local::ElementSourceAssemblerBuilder),
+//! while point values are given by [`PointValueGoal`].
+use crate::allocators::BiDimAllocator;
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::{ElementConnectivityAssembler, ElementVectorAssembler};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut, DefaultAllocator, OPoint};
+use crate::space::{FindClosestElement, FiniteElementSpace};
+use crate::Real;
+use eyre::eyre;
+
+/// The goal functional $J(v) = \vec w \cdot v(\vec x_0)$ for a fixed point $\vec x_0$.
+///
+/// The point is located in the space once on construction, so that assembly only requires
+/// evaluating the basis functions of a single element.
+#[derive(Debug, Clone)]
+pub struct PointValueGoal<'a, T, Space> {
+    space: &'a Space,
+    element_index: usize,
+    basis_values: Vec<T>,
+    weights: DVector<T>,
+}
+
+impl<'a, T, Space> PointValueGoal<'a, T, Space>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    /// Constructs the goal functional for the given point and component weights $\vec w$.
+    ///
+    /// The solution dimension of the functional is the number of weights. If the point lies
+    /// outside the domain, it is projected onto the closest element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no element could be found for the point, for example because the
+    /// space has no elements.
+    pub fn new(space: &'a Space, point: &OPoint<T, Space::GeometryDim>, weights: &[T]) -> eyre::Result<Self> {
+        let (element_index, xi) = space
+            .find_closest_element_and_reference_coords(point)
+            .ok_or_else(|| eyre!("Failed to find an element for point {:?}", point))?;
+        let mut basis_values = vec![T::zero(); space.element_node_count(element_index.into())];
+        space.populate_element_basis(element_index, &mut basis_values, &xi);
+        Ok(Self {
+            space,
+            element_index,
+            basis_values,
+            weights: DVector::from_column_slice(weights),
+        })
+    }
+
+    /// Returns the index of the element in which the point is evaluated.
+    pub fn element_index(&self) -> usize {
+        self.element_index
+    }
+}
+
+impl<'a, T, Space> ElementConnectivityAssembler for PointValueGoal<'a, T, Space>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, Space> ElementVectorAssembler<T> for PointValueGoal<'a, T, Space>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = self.weights.len();
+        assert_eq!(
+            output.len(),
+            s * self.space.element_node_count(element_index.into()),
+            "Output length must be solution dim times number of element nodes"
+        );
+        output.fill(T::zero());
+        if element_index == self.element_index {
+            for (i, &phi_i) in self.basis_values.iter().enumerate() {
+                output
+                    .rows_mut(s * i, s)
+                    .copy_from(&(&self.weights * phi_i));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates the linear functional represented by an element vector assembler for the
+/// finite element function with the given interpolation weights.
+///
+/// Computes $\hat j \cdot \hat u = \sum_K \hat j^K \cdot R_K \hat u$ without assembling the
+/// global vector $\hat j$.
+///
+/// # Errors
+///
+/// Returns an error if the assembly of an element vector fails.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not the solution dimension times the number of nodes.
+pub fn evaluate_linear_functional<'a, T: Real>(
+    functional: &(impl ?Sized + ElementVectorAssembler<T>),
+    u: impl Into<DVectorView<'a, T>>,
+) -> eyre::Result<T> {
+    let u = u.into();
+    let s = functional.solution_dim();
+    assert_eq!(
+        u.len(),
+        s * functional.num_nodes(),
+        "Length of interpolation weights must be solution dim times number of nodes"
+    );
+
+    let mut nodes = Vec::new();
+    let mut u_local = DVector::zeros(0);
+    let mut value = T::zero();
+    for element_index in 0..functional.num_elements() {
+        let node_count = functional.element_node_count(element_index.into());
+        nodes.resize(node_count, usize::MAX);
+        functional.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+        u_local.resize_vertically_mut(s * node_count, T::zero());
+        gather_global_to_local(u, &mut u_local, &nodes, s);
+        let j_local = functional.assemble_element_vector(element_index)?;
+        value += j_local.dot(&u_local);
+    }
+    Ok(value)
+}
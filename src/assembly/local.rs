@@ -1,19 +1,50 @@
 use crate::connectivity::Connectivity;
+use crate::index::{ElementIndex, NodeIndex};
 use crate::mesh::Mesh;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{DMatrix, DVector, DVectorViewMut};
 use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, Scalar};
 use crate::Real;
 
+mod advection_diffusion;
+mod condensation;
+mod convection;
+mod eigenstrain;
 mod elliptic;
+mod facet;
+mod incompatible_modes;
+mod interior_penalty;
 mod mass;
+mod membrane;
+mod mixed;
+mod quadrature_parameters;
 mod quadrature_table;
+mod replicated;
 mod source;
-
+mod state;
+mod surface;
+mod surface_tension;
+mod traction;
+pub use advection_diffusion::*;
+
+pub use condensation::*;
+pub use convection::*;
+pub use eigenstrain::*;
 pub use elliptic::*;
+pub use facet::*;
+pub use incompatible_modes::*;
+pub use interior_penalty::*;
 pub use mass::*;
+pub use membrane::*;
+pub use mixed::*;
+pub use quadrature_parameters::*;
 pub use quadrature_table::*;
+pub use replicated::*;
 pub use source::*;
+pub use state::*;
+pub use surface::*;
+pub use surface_tension::*;
+pub use traction::*;
 
 pub trait ElementConnectivityAssembler {
     fn solution_dim(&self) -> usize;
@@ -22,9 +53,9 @@ pub trait ElementConnectivityAssembler {
 
     fn num_nodes(&self) -> usize;
 
-    fn element_node_count(&self, element_index: usize) -> usize;
+    fn element_node_count(&self, element_index: ElementIndex) -> usize;
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize);
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex);
 
     /// Returns an adapter that modifies element node indices according to the provided function.
     ///
@@ -65,12 +96,16 @@ where
         self.vertices().len()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
-        self.connectivity()[element_index].vertex_indices().len()
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.connectivity()[element_index.index()]
+            .vertex_indices()
+            .len()
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
-        output.copy_from_slice(self.connectivity()[element_index].vertex_indices());
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        output.copy_from_slice(NodeIndex::from_raw_slice(
+            self.connectivity()[element_index.index()].vertex_indices(),
+        ));
     }
 }
 
@@ -81,7 +116,7 @@ pub trait ElementMatrixAssembler<T: Scalar>: ElementConnectivityAssembler {
     where
         T: Real,
     {
-        let ndof = self.solution_dim() * self.element_node_count(element_index);
+        let ndof = self.solution_dim() * self.element_node_count(element_index.into());
         let mut output = DMatrix::zeros(ndof, ndof);
         self.assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
         Ok(output)
@@ -109,7 +144,7 @@ pub trait ElementVectorAssembler<T: Scalar>: ElementConnectivityAssembler {
     where
         T: Real,
     {
-        let ndof = self.solution_dim() * self.element_node_count(element_index);
+        let ndof = self.solution_dim() * self.element_node_count(element_index.into());
         let mut output = DVector::zeros(ndof);
         self.assemble_element_vector_into(element_index, DVectorViewMut::from(&mut output))?;
         Ok(output)
@@ -228,14 +263,19 @@ where
         self.num_nodes
     }
 
-    fn element_node_count(&self, aggregate_element_index: usize) -> usize {
-        let (assembler, element_offset) = self.find_assembler_and_offset_for_element_index(aggregate_element_index);
-        assembler.element_node_count(aggregate_element_index - element_offset)
+    fn element_node_count(&self, aggregate_element_index: ElementIndex) -> usize {
+        let (assembler, element_offset) =
+            self.find_assembler_and_offset_for_element_index(aggregate_element_index.index());
+        assembler.element_node_count(ElementIndex::new(aggregate_element_index.index() - element_offset))
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], aggregate_element_index: usize) {
-        let (assembler, element_offset) = self.find_assembler_and_offset_for_element_index(aggregate_element_index);
-        assembler.populate_element_nodes(output, aggregate_element_index - element_offset)
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], aggregate_element_index: ElementIndex) {
+        let (assembler, element_offset) =
+            self.find_assembler_and_offset_for_element_index(aggregate_element_index.index());
+        assembler.populate_element_nodes(
+            output,
+            ElementIndex::new(aggregate_element_index.index() - element_offset),
+        )
     }
 }
 
@@ -322,14 +362,14 @@ where
         self.num_nodes
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.assembler.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.assembler.populate_element_nodes(output, element_index);
         for idx in output {
-            *idx = (self.function)(*idx);
+            *idx = NodeIndex::new((self.function)(idx.index()));
         }
     }
 }
@@ -357,11 +397,11 @@ macro_rules! delegate {
                 $self.$delegate_var.num_nodes()
             }
 
-            fn element_node_count(&$self, element_index: usize) -> usize {
+            fn element_node_count(&$self, element_index: ElementIndex) -> usize {
                 $self.$delegate_var.element_node_count(element_index)
             }
 
-            fn populate_element_nodes(&$self, output: &mut [usize], element_index: usize) {
+            fn populate_element_nodes(&$self, output: &mut [NodeIndex], element_index: ElementIndex) {
                 $self.$delegate_var.populate_element_nodes(output, element_index)
             }
         }
@@ -0,0 +1,572 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementVectorAssembler, QuadratureTable,
+};
+use crate::assembly::operators::{AdvectionDiffusionReaction, AdvectionDiffusionReactionCoefficients};
+use crate::element::{FiniteElement, ReferenceFiniteElement, VolumetricFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DMatrixViewMut, DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixViewMut, OPoint};
+use crate::quadrature::{BorrowedQuadratureParts, QuadratureParts};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use crate::util::clone_upper_to_lower;
+use crate::{Real, SmallDim, Symmetry};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use nalgebra::Scalar;
+use numeric_literals::replace_float_literals;
+
+/// An element assembler for advection-diffusion-reaction problems.
+///
+/// Assembles the element matrices and load vectors associated with an
+/// [advection-diffusion-reaction operator](AdvectionDiffusionReaction). The element matrices are
+/// in general not symmetric, see [`assemble_element_advection_diffusion_reaction_matrix`] for
+/// their definition.
+///
+/// The Galerkin discretization of advection-dominated problems is prone to spurious oscillations.
+/// The *streamline upwind Petrov-Galerkin* (SUPG) method stabilizes the discretization by adding
+/// the residual of the equation tested with $\tau_K \beta \cdot \nabla v$ on each element $K$,
+/// where $\tau_K$ is an element-wise stabilization parameter. SUPG stabilization is enabled
+/// by providing the stabilization parameters for all elements with
+/// [`with_supg_parameters`](Self::with_supg_parameters), which can for example be computed with
+/// [`compute_supg_parameters`].
+#[derive(Debug, Clone)]
+pub struct ElementAdvectionDiffusionReactionAssembler<'a, T: Scalar, Space, Op, QTable> {
+    space: &'a Space,
+    operator: &'a Op,
+    qtable: &'a QTable,
+    supg_parameters: Option<&'a [T]>,
+}
+
+impl<'a, T: Scalar> ElementAdvectionDiffusionReactionAssembler<'a, T, (), (), ()> {
+    pub fn new() -> Self {
+        Self {
+            space: &(),
+            operator: &(),
+            qtable: &(),
+            supg_parameters: None,
+        }
+    }
+}
+
+impl<'a, T: Scalar> Default for ElementAdvectionDiffusionReactionAssembler<'a, T, (), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: Scalar, Op, QTable> ElementAdvectionDiffusionReactionAssembler<'a, T, (), Op, QTable> {
+    pub fn with_space<Space>(
+        self,
+        space: &'a Space,
+    ) -> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable> {
+        ElementAdvectionDiffusionReactionAssembler {
+            space,
+            operator: self.operator,
+            qtable: self.qtable,
+            supg_parameters: self.supg_parameters,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space, QTable> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, (), QTable> {
+    pub fn with_operator<Op>(
+        self,
+        operator: &'a Op,
+    ) -> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable> {
+        ElementAdvectionDiffusionReactionAssembler {
+            space: self.space,
+            operator,
+            qtable: self.qtable,
+            supg_parameters: self.supg_parameters,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space, Op> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, ()> {
+    pub fn with_quadrature_table<QTable>(
+        self,
+        table: &'a QTable,
+    ) -> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable> {
+        ElementAdvectionDiffusionReactionAssembler {
+            space: self.space,
+            operator: self.operator,
+            qtable: table,
+            supg_parameters: self.supg_parameters,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space, Op, QTable> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable> {
+    /// Enables SUPG stabilization with the given stabilization parameter $\tau_K$ for each element.
+    pub fn with_supg_parameters(self, supg_parameters: &'a [T]) -> Self {
+        Self {
+            supg_parameters: Some(supg_parameters),
+            ..self
+        }
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementConnectivityAssembler
+    for ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementConnectivity,
+{
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+#[derive(Debug)]
+struct AdvectionDiffusionReactionWorkspace<T: Scalar, D: SmallDim, Data>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+    coefficients: Vec<AdvectionDiffusionReactionCoefficients<T, D>>,
+}
+
+impl<T: Real, D: SmallDim, Data> Default for AdvectionDiffusionReactionWorkspace<T, D, Data>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+            coefficients: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: AdvectionDiffusionReaction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn supg_parameter(&self, element_index: usize) -> eyre::Result<T> {
+        match self.supg_parameters {
+            None => Ok(T::zero()),
+            Some(parameters) => {
+                if parameters.len() != self.space.num_elements() {
+                    return Err(eyre!(
+                        "Number of SUPG parameters ({}) does not match number of elements ({})",
+                        parameters.len(),
+                        self.space.num_elements()
+                    ));
+                }
+                Ok(parameters[element_index])
+            }
+        }
+    }
+
+    /// Prepares the workspace for the given element and calls `f` with the element, the
+    /// quadrature with the coefficients at the quadrature points as data, and the basis buffers.
+    fn with_element_data<R>(
+        &self,
+        element_index: usize,
+        f: impl FnOnce(
+            &ElementInSpace<Space>,
+            BorrowedQuadratureParts<
+                T,
+                Space::ReferenceDim,
+                AdvectionDiffusionReactionCoefficients<T, Space::ReferenceDim>,
+            >,
+            &mut [T],
+            MatrixViewMut<T, Space::ReferenceDim, Dyn>,
+        ) -> eyre::Result<R>,
+    ) -> eyre::Result<R> {
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut AdvectionDiffusionReactionWorkspace<T, Space::ReferenceDim, Op::Parameters>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                ws.coefficients.clear();
+                for (point, data) in izip!(ws.quadrature_buffer.points(), ws.quadrature_buffer.data()) {
+                    let x = element.map_reference_coords(point);
+                    ws.coefficients
+                        .push(self.operator.evaluate_coefficients(&x, data));
+                }
+
+                let (basis_values, basis_gradients) = ws.basis_buffer.element_values_gradients_mut();
+                let quadrature = QuadratureParts {
+                    weights: ws.quadrature_buffer.weights(),
+                    points: ws.quadrature_buffer.points(),
+                    data: ws.coefficients.as_slice(),
+                };
+                f(&element, quadrature, basis_values, basis_gradients)
+            },
+        )
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementMatrixAssembler<T>
+    for ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: AdvectionDiffusionReaction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let tau = self.supg_parameter(element_index)?;
+        let symmetry = self.operator.symmetry();
+        self.with_element_data(element_index, |element, quadrature, basis_values, basis_gradients| {
+            assemble_element_advection_diffusion_reaction_matrix(
+                output,
+                element,
+                quadrature,
+                tau,
+                symmetry,
+                basis_values,
+                basis_gradients,
+            )
+        })
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementVectorAssembler<T>
+    for ElementAdvectionDiffusionReactionAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: AdvectionDiffusionReaction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let tau = self.supg_parameter(element_index)?;
+        self.with_element_data(element_index, |element, quadrature, basis_values, basis_gradients| {
+            assemble_element_advection_diffusion_reaction_vector(
+                output,
+                element,
+                quadrature,
+                tau,
+                basis_values,
+                basis_gradients,
+            )
+        })
+    }
+}
+
+/// Evaluates basis values and physical basis gradients at the given point, and returns the
+/// absolute value of the Jacobian determinant.
+fn populate_basis_values_and_gradients<T, Element>(
+    element: &Element,
+    point: &OPoint<T, Element::ReferenceDim>,
+    basis_values: &mut [T],
+    mut basis_gradients: MatrixViewMut<T, Element::GeometryDim, Dyn>,
+) -> eyre::Result<T>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let j = element.reference_jacobian(point);
+    let j_det = j.determinant();
+    let j_inv_t = j
+        .try_inverse()
+        .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?
+        .transpose();
+    element.populate_basis(basis_values, point);
+    element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients), point);
+    // Transform reference gradients to physical gradients
+    for mut gradient in basis_gradients.column_iter_mut() {
+        let physical_gradient = &j_inv_t * &gradient;
+        gradient.copy_from(&physical_gradient);
+    }
+    Ok(j_det.abs())
+}
+
+/// Assembles the element matrix of an advection-diffusion-reaction operator.
+///
+/// Given a finite element with domain $K$ and $N$ nodes, and the coefficients
+/// (see [`AdvectionDiffusionReaction`]) at each quadrature point, the element matrix is
+/// defined by
+/// <div>$$
+/// A^K_{IJ} := \int_{K} \kappa \nabla \phi_I \cdot \nabla \phi_J
+///     + \phi_I \\, \beta \cdot \nabla \phi_J + c \\, \phi_I \phi_J
+///     + \tau_K (\beta \cdot \nabla \phi_I) (\beta \cdot \nabla \phi_J + c \\, \phi_J) \\, \mathrm{d}x,
+/// $$</div>
+/// where the last term is the SUPG stabilization term with stabilization parameter $\tau_K$.
+/// Since the diffusive term $-\nabla \cdot (\kappa \nabla \phi_J)$ of the residual cannot be
+/// evaluated with first derivatives only, it is omitted from the stabilization term. This term
+/// vanishes for linear simplex elements, and is commonly neglected for other low-order elements.
+/// Setting $\tau_K = 0$ gives the standard Galerkin discretization.
+///
+/// If `symmetry` indicates symmetry, only the upper triangle is computed and then copied to the
+/// lower triangle. This is only correct if the advection velocity vanishes.
+///
+/// The computation requires buffers for basis function values and gradients, which must be able
+/// to hold the values and gradients of all basis functions in the element.
+///
+/// # Errors
+///
+/// Returns an error if the element Jacobian is singular at any quadrature point.
+///
+/// # Panics
+///
+/// Panics if the quadrature weights, points and coefficients do not have the same lengths, or if the sizes of the output
+/// matrix or the buffers are not consistent with the number of nodes in the element.
+#[allow(non_snake_case)]
+pub fn assemble_element_advection_diffusion_reaction_matrix<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    quadrature: BorrowedQuadratureParts<
+        T,
+        Element::ReferenceDim,
+        AdvectionDiffusionReactionCoefficients<T, Element::GeometryDim>,
+    >,
+    supg_parameter: T,
+    symmetry: Symmetry,
+    basis_values_buffer: &mut [T],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::GeometryDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let QuadratureParts {
+        weights: quadrature_weights,
+        points: quadrature_points,
+        data: coefficients,
+    } = quadrature;
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), coefficients.len());
+    let n = element.num_nodes();
+    assert_eq!(basis_values_buffer.len(), n);
+    assert_eq!(basis_gradients_buffer.ncols(), n);
+    assert_eq!(output.nrows(), n, "Output matrix dimension mismatch");
+    assert_eq!(output.ncols(), n, "Output matrix dimension mismatch");
+
+    output.fill(T::zero());
+
+    let phi = basis_values_buffer;
+    let tau = supg_parameter;
+    for (&weight, point, coeffs) in izip!(quadrature_weights, quadrature_points, coefficients) {
+        let j_det =
+            populate_basis_values_and_gradients(element, point, phi, MatrixViewMut::from(&mut basis_gradients_buffer))?;
+        let grad_phi = &basis_gradients_buffer;
+        let AdvectionDiffusionReactionCoefficients {
+            velocity: beta,
+            diffusivity: kappa,
+            reaction: c,
+            ..
+        } = coeffs;
+        let scale = weight * j_det;
+
+        for I in 0..n {
+            let beta_dot_grad_phi_I = beta.dot(&grad_phi.column(I));
+            let J_start = match symmetry {
+                Symmetry::Symmetric => I,
+                Symmetry::NonSymmetric => 0,
+            };
+            for J in J_start..n {
+                let beta_dot_grad_phi_J = beta.dot(&grad_phi.column(J));
+                let galerkin = *kappa * grad_phi.column(I).dot(&grad_phi.column(J))
+                    + phi[I] * beta_dot_grad_phi_J
+                    + *c * phi[I] * phi[J];
+                let supg = tau * beta_dot_grad_phi_I * (beta_dot_grad_phi_J + *c * phi[J]);
+                output[(I, J)] += scale * (galerkin + supg);
+            }
+        }
+    }
+
+    if symmetry == Symmetry::Symmetric {
+        clone_upper_to_lower(&mut output);
+    }
+
+    Ok(())
+}
+
+/// Assembles the element load vector of an advection-diffusion-reaction operator.
+///
+/// With the notation of [`assemble_element_advection_diffusion_reaction_matrix`], the
+/// element load vector is defined by
+/// <div>$$
+/// b^K_I := \int_K (\phi_I + \tau_K \beta \cdot \nabla \phi_I) f \\, \mathrm{d}x,
+/// $$</div>
+/// which includes the contribution of the source term $f$ to the SUPG stabilization term.
+///
+/// # Errors
+///
+/// Returns an error if the element Jacobian is singular at any quadrature point.
+///
+/// # Panics
+///
+/// Panics if the quadrature weights, points and coefficients do not have the same lengths, or if the sizes of the output
+/// vector or the buffers are not consistent with the number of nodes in the element.
+#[allow(non_snake_case)]
+pub fn assemble_element_advection_diffusion_reaction_vector<T, Element>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    quadrature: BorrowedQuadratureParts<
+        T,
+        Element::ReferenceDim,
+        AdvectionDiffusionReactionCoefficients<T, Element::GeometryDim>,
+    >,
+    supg_parameter: T,
+    basis_values_buffer: &mut [T],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::GeometryDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let QuadratureParts {
+        weights: quadrature_weights,
+        points: quadrature_points,
+        data: coefficients,
+    } = quadrature;
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), coefficients.len());
+    let n = element.num_nodes();
+    assert_eq!(basis_values_buffer.len(), n);
+    assert_eq!(basis_gradients_buffer.ncols(), n);
+    assert_eq!(output.len(), n, "Output vector dimension mismatch");
+
+    output.fill(T::zero());
+
+    let phi = basis_values_buffer;
+    for (&weight, point, coeffs) in izip!(quadrature_weights, quadrature_points, coefficients) {
+        let j_det =
+            populate_basis_values_and_gradients(element, point, phi, MatrixViewMut::from(&mut basis_gradients_buffer))?;
+        let scale = weight * j_det * coeffs.source;
+        for (I, (&phi_I, grad_phi_I)) in izip!(phi.iter(), basis_gradients_buffer.column_iter()).enumerate() {
+            output[I] += scale * (phi_I + supg_parameter * coeffs.velocity.dot(&grad_phi_I));
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes SUPG stabilization parameters for each element.
+///
+/// The stabilization parameter of element $K$ is given by the classical formula
+/// <div>$$
+/// \tau_K = \frac{h_K}{2 |\bar \beta|} \left( \coth(\mathrm{Pe}_K) - \frac{1}{\mathrm{Pe}_K} \right),
+/// \qquad \mathrm{Pe}_K = \frac{|\bar \beta| h_K}{2 \bar \kappa},
+/// $$</div>
+/// where $\bar \beta$ and $\bar \kappa$ are the averages of the velocity and diffusivity over the
+/// element, $\mathrm{Pe}_K$ is the element Péclet number and
+/// $h_K = 2 |\bar \beta| \big/ \sum_I |\bar \beta \cdot \nabla \phi_I|$ is the element length in the
+/// direction of the flow, averaged over the element. For vanishing diffusivity, the factor in
+/// parentheses is $1$, and for vanishing velocity, $\tau_K = 0$.
+///
+/// The coefficients are evaluated with the provided quadrature table.
+///
+/// # Errors
+///
+/// Returns an error if the element Jacobian is singular at any quadrature point.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn compute_supg_parameters<T, Space, Op, QTable>(
+    space: &Space,
+    operator: &Op,
+    qtable: &QTable,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: AdvectionDiffusionReaction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let assembler = ElementAdvectionDiffusionReactionAssembler::new()
+        .with_space(space)
+        .with_operator(operator)
+        .with_quadrature_table(qtable);
+    (0..space.num_elements())
+        .map(|element_index| {
+            assembler.with_element_data(
+                element_index,
+                |element, quadrature, basis_values, mut basis_gradients| {
+                    let QuadratureParts {
+                        weights,
+                        points,
+                        data: coefficients,
+                    } = quadrature;
+                    // First compute element averages of the coefficients
+                    let mut volume = 0.0;
+                    let mut beta = nalgebra::OVector::<T, Space::ReferenceDim>::zeros();
+                    let mut kappa = 0.0;
+                    for (&weight, point, coeffs) in izip!(weights, points, coefficients) {
+                        let scale = weight * element.reference_jacobian(point).determinant().abs();
+                        volume += scale;
+                        beta += &coeffs.velocity * scale;
+                        kappa += coeffs.diffusivity * scale;
+                    }
+                    beta /= volume;
+                    kappa /= volume;
+                    let beta_norm = beta.norm();
+                    if beta_norm == 0.0 {
+                        return Ok(0.0);
+                    }
+
+                    // Average sum_I |beta . grad phi_I| over the element
+                    let mut gradient_sum = 0.0;
+                    for (&weight, point) in izip!(weights, points) {
+                        let j_det = populate_basis_values_and_gradients(
+                            element,
+                            point,
+                            basis_values,
+                            MatrixViewMut::from(&mut basis_gradients),
+                        )?;
+                        let sum: T = basis_gradients
+                            .column_iter()
+                            .map(|grad_phi| beta.dot(&grad_phi).abs())
+                            .fold(0.0, |acc, x| acc + x);
+                        gradient_sum += weight * j_det * sum;
+                    }
+                    let h = 2.0 * beta_norm * volume / gradient_sum;
+
+                    let xi = if kappa <= 0.0 {
+                        1.0
+                    } else {
+                        let peclet = beta_norm * h / (2.0 * kappa);
+                        // Use the series expansion coth(x) - 1/x = x/3 - x^3/45 + ... for small x
+                        // to avoid cancellation
+                        if peclet < 1e-3 {
+                            peclet / 3.0
+                        } else {
+                            1.0 / peclet.tanh() - 1.0 / peclet
+                        }
+                    };
+                    Ok(h / (2.0 * beta_norm) * xi)
+                },
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use crate::nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use crate::Real;
+use eyre::eyre;
+
+/// Statically condenses the trailing (internal) degrees of freedom out of an element matrix.
+///
+/// The matrix is assumed to be partitioned as
+/// <div>$$
+/// \vec K = \begin{pmatrix} \vec K_{rr} & \vec K_{ri} \\\\ \vec K_{ir} & \vec K_{ii} \end{pmatrix},
+/// $$</div>
+/// where the first `num_retained` rows/columns correspond to the *retained* degrees of freedom
+/// and the remaining rows/columns correspond to *internal* degrees of freedom that are not
+/// shared with other elements. The returned matrix is the Schur complement
+/// $\vec K_{rr} - \vec K_{ri} \vec K_{ii}^{-1} \vec K_{ir}$.
+///
+/// # Errors
+///
+/// Returns an error if the internal block $\vec K_{ii}$ is singular.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square or if `num_retained` exceeds the size of the matrix.
+pub fn condense_element_matrix<T: Real>(matrix: DMatrixView<T>, num_retained: usize) -> eyre::Result<DMatrix<T>> {
+    let (condensed, _) =
+        condense_element_system(matrix, DVectorView::from(&DVector::zeros(matrix.nrows())), num_retained)?;
+    Ok(condensed)
+}
+
+/// Statically condenses the trailing (internal) degrees of freedom out of an element system
+/// $\vec K \vec u = \vec f$.
+///
+/// With the partitioning described in [`condense_element_matrix`], returns the condensed
+/// matrix $\vec K_{rr} - \vec K_{ri} \vec K_{ii}^{-1} \vec K_{ir}$ and the condensed vector
+/// $\vec f_r - \vec K_{ri} \vec K_{ii}^{-1} \vec f_i$.
+///
+/// # Errors
+///
+/// Returns an error if the internal block $\vec K_{ii}$ is singular.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square, if the dimensions of the matrix and vector are
+/// inconsistent or if `num_retained` exceeds the size of the matrix.
+#[allow(non_snake_case)]
+pub fn condense_element_system<T: Real>(
+    matrix: DMatrixView<T>,
+    vector: DVectorView<T>,
+    num_retained: usize,
+) -> eyre::Result<(DMatrix<T>, DVector<T>)> {
+    let n = matrix.nrows();
+    assert_eq!(matrix.ncols(), n, "Element matrix must be square");
+    assert_eq!(vector.len(), n, "Element vector dimension mismatch");
+    assert!(num_retained <= n, "Number of retained dofs exceeds matrix dimension");
+    let r = num_retained;
+    let m = n - r;
+    if m == 0 {
+        return Ok((matrix.clone_owned(), vector.clone_owned()));
+    }
+
+    let K_rr = matrix.view((0, 0), (r, r));
+    let K_ri = matrix.view((0, r), (r, m));
+    let K_ir = matrix.view((r, 0), (m, r));
+    let K_ii = matrix.view((r, r), (m, m)).clone_owned();
+    let f_r = vector.rows(0, r);
+    let f_i = vector.rows(r, m);
+
+    let K_ii_lu = K_ii.lu();
+    let singular = || eyre!("Internal block of element matrix is singular, cannot condense");
+    let K_ii_inv_K_ir = K_ii_lu.solve(&K_ir).ok_or_else(singular)?;
+    let K_ii_inv_f_i = K_ii_lu.solve(&f_i).ok_or_else(singular)?;
+
+    let condensed_matrix = K_rr - K_ri * K_ii_inv_K_ir;
+    let condensed_vector = f_r - K_ri * K_ii_inv_f_i;
+    Ok((condensed_matrix, condensed_vector))
+}
+
+/// Recovers the internal degrees of freedom that were eliminated by static condensation.
+///
+/// Given the full (uncondensed) element system $\vec K \vec u = \vec f$, partitioned as described in
+/// [`condense_element_matrix`], and the retained degrees of freedom $\vec u_r$, returns the
+/// internal degrees of freedom
+/// $\vec u_i = \vec K_{ii}^{-1} (\vec f_i - \vec K_{ir} \vec u_r)$.
+///
+/// # Errors
+///
+/// Returns an error if the internal block $\vec K_{ii}$ is singular.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square, or if the dimensions of the matrix and the vectors are
+/// inconsistent.
+#[allow(non_snake_case)]
+pub fn recover_condensed_dofs<T: Real>(
+    matrix: DMatrixView<T>,
+    vector: DVectorView<T>,
+    u_retained: DVectorView<T>,
+) -> eyre::Result<DVector<T>> {
+    let n = matrix.nrows();
+    assert_eq!(matrix.ncols(), n, "Element matrix must be square");
+    assert_eq!(vector.len(), n, "Element vector dimension mismatch");
+    assert!(
+        u_retained.len() <= n,
+        "Number of retained dofs exceeds matrix dimension"
+    );
+    let r = u_retained.len();
+    let m = n - r;
+    if m == 0 {
+        return Ok(DVector::zeros(0));
+    }
+
+    let K_ir = matrix.view((r, 0), (m, r));
+    let K_ii = matrix.view((r, r), (m, m)).clone_owned();
+    let rhs = vector.rows(r, m) - K_ir * u_retained;
+    K_ii.lu()
+        .solve(&rhs)
+        .ok_or_else(|| eyre!("Internal block of element matrix is singular, cannot recover internal dofs"))
+}
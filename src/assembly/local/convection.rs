@@ -0,0 +1,288 @@
+use crate::allocators::DimAllocator;
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use crate::element::{ReferenceFiniteElement, VolumetricFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DMatrixViewMut, DVector, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use crate::Real;
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use nalgebra::{Scalar, U1};
+
+/// An element assembler for convective transport terms.
+///
+/// Assembles the element matrices associated with the convective term $(c \cdot \nabla u, v)$
+/// in the weak form of transport problems, where the convective velocity $c = a - w$ is the
+/// difference between the transport velocity $a$ and the mesh velocity $w$. Both velocities
+/// are given as nodal finite element functions on the space, stored vertex by vertex. Omitting
+/// the mesh velocity gives the usual Eulerian convective term, while providing the mesh velocity
+/// of a moving mesh, such as [`MovingMeshSpace::mesh_velocity`](crate::space::MovingMeshSpace::mesh_velocity),
+/// gives the convective term in the *Arbitrary Lagrangian-Eulerian* (ALE) formulation.
+/// Omitting the transport velocity corresponds to $a = 0$.
+///
+/// See [`assemble_element_convection_matrix`] for the definition of the element matrix.
+#[derive(Debug, Clone)]
+pub struct ElementConvectionAssembler<'a, T: Scalar, Space, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    solution_dim: usize,
+    velocity: Option<&'a DVector<T>>,
+    mesh_velocity: Option<&'a DVector<T>>,
+}
+
+impl<'a, T: Scalar> ElementConvectionAssembler<'a, T, (), ()> {
+    pub fn with_solution_dim(solution_dim: usize) -> Self {
+        Self {
+            space: &(),
+            qtable: &(),
+            solution_dim,
+            velocity: None,
+            mesh_velocity: None,
+        }
+    }
+}
+
+impl<'a, T: Scalar, QTable> ElementConvectionAssembler<'a, T, (), QTable> {
+    pub fn with_space<Space>(self, space: &'a Space) -> ElementConvectionAssembler<'a, T, Space, QTable> {
+        ElementConvectionAssembler {
+            space,
+            qtable: self.qtable,
+            solution_dim: self.solution_dim,
+            velocity: self.velocity,
+            mesh_velocity: self.mesh_velocity,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space> ElementConvectionAssembler<'a, T, Space, ()> {
+    pub fn with_quadrature_table<QTable>(self, table: &'a QTable) -> ElementConvectionAssembler<'a, T, Space, QTable> {
+        ElementConvectionAssembler {
+            space: self.space,
+            qtable: table,
+            solution_dim: self.solution_dim,
+            velocity: self.velocity,
+            mesh_velocity: self.mesh_velocity,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space, QTable> ElementConvectionAssembler<'a, T, Space, QTable> {
+    /// Sets the nodal transport velocity $a$.
+    pub fn with_velocity(self, velocity: &'a DVector<T>) -> Self {
+        Self {
+            velocity: Some(velocity),
+            ..self
+        }
+    }
+
+    /// Sets the nodal mesh velocity $w$, which is subtracted from the transport velocity.
+    pub fn with_mesh_velocity(self, mesh_velocity: &'a DVector<T>) -> Self {
+        Self {
+            mesh_velocity: Some(mesh_velocity),
+            ..self
+        }
+    }
+}
+
+impl<'a, T, Space, QTable> ElementConnectivityAssembler for ElementConvectionAssembler<'a, T, Space, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementConnectivity,
+{
+    fn solution_dim(&self) -> usize {
+        self.solution_dim
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+#[derive(Debug)]
+struct ConvectionAssemblerWorkspace<T: Scalar, D: DimName>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D>,
+    basis_buffer: BasisFunctionBuffer<T>,
+    element_velocity: Vec<T>,
+}
+
+impl<T: Real, D: DimName> Default for ConvectionAssemblerWorkspace<T, D>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+            element_velocity: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for ElementConvectionAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim>,
+    DefaultAllocator: DimAllocator<T, Space::GeometryDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let d = Space::GeometryDim::dim();
+        let expected_len = d * self.space.num_nodes();
+        for (name, velocity) in [("Velocity", self.velocity), ("Mesh velocity", self.mesh_velocity)] {
+            if let Some(velocity) = velocity {
+                if velocity.len() != expected_len {
+                    return Err(eyre!(
+                        "{name} has {} entries, but the space requires {expected_len} entries",
+                        velocity.len()
+                    ));
+                }
+            }
+        }
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut ConvectionAssemblerWorkspace<T, Space::GeometryDim>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                let n = element.num_nodes();
+                ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_weights_and_points_from_table(element_index, self.qtable);
+
+                // Gather the nodal convective velocity c = a - w of the element
+                ws.element_velocity.clear();
+                ws.element_velocity.resize(d * n, T::zero());
+                for (local, &node) in ws.basis_buffer.element_nodes().iter().enumerate() {
+                    for i in 0..d {
+                        let a = self.velocity.map(|a| a[d * node + i]).unwrap_or(T::zero());
+                        let w = self
+                            .mesh_velocity
+                            .map(|w| w[d * node + i])
+                            .unwrap_or(T::zero());
+                        ws.element_velocity[d * local + i] = a - w;
+                    }
+                }
+
+                let element_velocity =
+                    MatrixView::from_slice_generic(&ws.element_velocity, Space::GeometryDim::name(), Dyn(n));
+                let (basis_values, basis_gradients) = ws.basis_buffer.element_values_gradients_mut();
+                assemble_element_convection_matrix(
+                    output,
+                    &element,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    element_velocity,
+                    basis_values,
+                    basis_gradients,
+                )
+            },
+        )
+    }
+}
+
+/// Assembles the element convection matrix using the provided quadrature.
+///
+/// Given a finite element with domain $K$ and $N$ nodes, the element convection matrix is the
+/// matrix $C^K \in \mathbb{R}^{s N\times s N}$ whose $s \times s$ blocks $C_{IJ}$ are defined by
+///
+/// $$
+/// C^K_{IJ} := I^s \int_{K} \phi_I(x) \\, c(x) \cdot \nabla \phi_J(x) \\, \mathrm{d} V \qquad I, J = 1, \dots, N,
+/// $$
+///
+/// where $s$ is the dimension of the solution variable, which is inferred from the dimensions
+/// of the output matrix, $I^s$ is the $s \times s$ identity matrix,
+/// $\phi_I$ is the basis function associated with node $I$ and $c = \sum_I c_I \phi_I$ is the
+/// convective velocity, given by its nodal values $c_I$ in the element. The matrix is in general
+/// not symmetric.
+///
+/// The computation requires buffers for evaluating basis functions and their gradients. The buffers
+/// must be able to store the values and gradients for each node in the element.
+///
+/// # Panics
+///
+/// Panics if the quadrature arrays do not have the same lengths.
+///
+/// Panics if the number of columns in the nodal velocity matrix or the sizes of the basis function
+/// buffers do not match the number of nodes in the element, or if the output matrix is not a square
+/// matrix whose dimension is a multiple of the number of nodes.
+#[allow(non_snake_case)]
+pub fn assemble_element_convection_matrix<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    element_velocity: MatrixView<T, Element::GeometryDim, Dyn>,
+    basis_values_buffer: &mut [T],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::GeometryDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    DefaultAllocator: DimAllocator<T, Element::GeometryDim>,
+{
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    let n = element.num_nodes();
+    assert_eq!(element_velocity.ncols(), n);
+    assert_eq!(basis_values_buffer.len(), n);
+    assert_eq!(basis_gradients_buffer.ncols(), n);
+    assert_eq!(output.nrows(), output.ncols(), "Output matrix must be square");
+    assert!(
+        n > 0 && output.nrows().is_multiple_of(n),
+        "Output matrix dimension must be a multiple of the number of nodes"
+    );
+    let s = output.nrows() / n;
+
+    output.fill(T::zero());
+
+    let phi = basis_values_buffer;
+    for (&weight, point) in izip!(quadrature_weights, quadrature_points) {
+        let j = element.reference_jacobian(point);
+        let j_det = j.determinant();
+        let j_inv_t = j
+            .try_inverse()
+            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?
+            .transpose();
+
+        element.populate_basis(phi, point);
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+
+        // Convective velocity at the quadrature point
+        let c = element_velocity * MatrixView::from_slice_generic(&*phi, Dyn(n), U1::name());
+        // c . grad phi_J = c^T J^{-T} grad_ref phi_J
+        let c_ref = j_inv_t.transpose() * c;
+
+        let scale = weight * j_det.abs();
+        for J in 0..n {
+            let c_dot_grad_phi_J = c_ref.dot(&basis_gradients_buffer.column(J));
+            for (I, &phi_I) in phi.iter().enumerate() {
+                let c_IJ_contrib = scale * phi_I * c_dot_grad_phi_J;
+                let mut C_IJ = output.view_mut((s * I, s * J), (s, s));
+                for i in 0..s {
+                    C_IJ[(i, i)] += c_IJ_contrib;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
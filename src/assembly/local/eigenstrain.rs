@@ -0,0 +1,230 @@
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::{
+    compute_volume_u_grad, ElementConnectivityAssembler, ElementEllipticAssembler, ElementVectorAssembler,
+    QuadratureTable,
+};
+use crate::assembly::operators::{EigenstrainOperator, EllipticOperator, Operator};
+use crate::element::VolumetricFiniteElement;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{
+    DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, Scalar,
+};
+use crate::quadrature::{BorrowedQuadratureParts, Quadrature, QuadratureParts};
+use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
+use crate::Real;
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+
+/// Element quadrature weights and points together with the
+/// [eigenstrain parameters](crate::assembly::operators::EigenstrainParameters) at each quadrature point.
+pub type EigenstrainQuadrature<'a, T, D, Op> =
+    BorrowedQuadratureParts<'a, T, D, <EigenstrainOperator<Op> as Operator<T, D>>::Parameters>;
+
+/// An element assembler for load vectors induced by eigenstrains.
+///
+/// The assembler is constructed from an [`ElementEllipticAssembler`] for an
+/// [`EigenstrainOperator`] with [`ElementEllipticAssembler::eigenstrain_load`], and computes
+/// element vectors with [`assemble_element_eigenstrain_load_vector`].
+#[derive(Debug, Clone)]
+pub struct ElementEigenstrainLoadAssembler<'a, T: Scalar, Space, Op, QTable: ?Sized> {
+    assembler: ElementEllipticAssembler<'a, T, Space, EigenstrainOperator<Op>, QTable>,
+}
+
+impl<'a, T: Scalar, Space, Op, QTable: ?Sized> ElementEllipticAssembler<'a, T, Space, EigenstrainOperator<Op>, QTable> {
+    /// Returns an assembler for the load vectors induced by the eigenstrains of the operator.
+    pub fn eigenstrain_load(self) -> ElementEigenstrainLoadAssembler<'a, T, Space, Op, QTable> {
+        ElementEigenstrainLoadAssembler { assembler: self }
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementConnectivityAssembler
+    for ElementEigenstrainLoadAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: Operator<T, Space::ReferenceDim>,
+    QTable: ?Sized,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+#[derive(Debug)]
+struct EigenstrainLoadWorkspace<T, GeometryDim, Data>
+where
+    T: Scalar,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    u_element: DVector<T>,
+    quadrature_buffer: QuadratureBuffer<T, GeometryDim, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, GeometryDim, Data> Default for EigenstrainLoadWorkspace<T, GeometryDim, Data>
+where
+    T: Real,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    fn default() -> Self {
+        Self {
+            u_element: DVector::zeros(0),
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+        }
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+impl<'a, T, Space, Op, QTable> ElementVectorAssembler<T> for ElementEigenstrainLoadAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: EllipticOperator<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<
+            T,
+            Space::ReferenceDim,
+            Data = <EigenstrainOperator<Op> as Operator<T, Space::ReferenceDim>>::Parameters,
+        > + ?Sized,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index.into());
+        assert_eq!(output.len(), s * n, "Output vector dimension mismatch");
+        let assembler = &self.assembler;
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut EigenstrainLoadWorkspace<T, Space::ReferenceDim, QTable::Data>| {
+                ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, assembler.space);
+                ws.u_element.resize_vertically_mut(s * n, T::zero());
+                gather_global_to_local(assembler.u, &mut ws.u_element, ws.basis_buffer.element_nodes(), s);
+
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, assembler.qtable);
+
+                let element = ElementInSpace::from_space_and_element_index(assembler.space, element_index);
+                assemble_element_eigenstrain_load_vector::<T, _, Op>(
+                    output,
+                    &element,
+                    assembler.op.inner(),
+                    DVectorView::from(&ws.u_element),
+                    ws.quadrature_buffer.to_parts(),
+                    ws.basis_buffer.element_gradients_mut(),
+                )
+            },
+        )
+    }
+}
+
+/// Assembles the element load vector induced by eigenstrains.
+///
+/// Given an elliptic operator $g$ and eigenstrains $\vec \varepsilon^*$ associated with each
+/// quadrature point, computes the element vector with entries
+/// <div>$$
+/// f_I = \int_K \big( g(\nabla u_h) - g(\nabla u_h - \vec \varepsilon^*) \big)^T \nabla \varphi_I \dx.
+/// $$</div>
+/// Adding this vector to the right-hand side of a system assembled with the operator $g$
+/// is equivalent to assembling the system with the [`EigenstrainOperator`] associated
+/// with $g$, i.e. the eigenstrain is moved from the operator to the load.
+///
+/// For *linear* operators such as linear elasticity, the load vector does not depend on
+/// $u_h$ and reduces to the classical eigenstrain load $f_I = \int_K g(\vec \varepsilon^*)^T \nabla \varphi_I \dx$,
+/// e.g. the thermal load $\int_K \vec B_I^T \vec C \vec \varepsilon^* \dx$.
+/// For nonlinear operators, the load vector must be re-assembled whenever $u_h$ changes.
+///
+/// # Panics
+///
+/// Panics if the quadrature data arrays do not have the same lengths.
+///
+/// Panics if the number of columns in the gradient buffer is not equal to the number of nodes
+/// in the element.
+pub fn assemble_element_eigenstrain_load_vector<T, Element, Op>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    operator: &Op,
+    u_element: DVectorView<T>,
+    quadrature: EigenstrainQuadrature<T, Element::ReferenceDim, Op>,
+    basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    Op: EllipticOperator<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Op::SolutionDim, Element::GeometryDim>,
+{
+    let QuadratureParts {
+        weights: quadrature_weights,
+        points: quadrature_points,
+        data: quadrature_data,
+    } = quadrature;
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), quadrature_data.len());
+    assert_eq!(basis_gradients_buffer.ncols(), element.num_nodes());
+
+    let s = Op::SolutionDim::dim();
+    let n = element.num_nodes();
+    assert_eq!(
+        u_element.len(),
+        s * n,
+        "Local element dofs (u_element) dimension mismatch"
+    );
+    assert_eq!(output.nrows(), s * n, "Output vector dimension mismatch");
+
+    output.fill(T::zero());
+
+    let mut phi_grad_ref = basis_gradients_buffer;
+
+    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
+    for (&weight, point, data) in quadrature_iter {
+        let j = element.reference_jacobian(point);
+        let j_det = j.determinant();
+        let j_inv = j
+            .try_inverse()
+            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
+        let j_inv_t = j_inv.transpose();
+
+        element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad_ref), point);
+
+        let u_element = MatrixView::from_slice_generic(u_element.as_slice(), Op::SolutionDim::name(), Dyn(n));
+        let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad_ref, u_element);
+        let u_grad_elastic = &u_grad - &data.eigenstrain;
+
+        // See assemble_element_elliptic_vector for the organization of the computation
+        let params = &data.operator_parameters;
+        let g_t = operator.compute_elliptic_operator_transpose(&u_grad, params)
+            - operator.compute_elliptic_operator_transpose(&u_grad_elastic, params);
+        let g_t_j_inv_t = g_t * j_inv_t;
+        let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), Op::SolutionDim::name(), Dyn(n));
+        output.gemm(weight * j_det.abs(), &g_t_j_inv_t, &phi_grad_ref, T::one());
+    }
+
+    Ok(())
+}
@@ -7,6 +7,7 @@ use crate::assembly::local::{
 };
 use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
 use crate::element::VolumetricFiniteElement;
+use crate::index::{ElementIndex, NodeIndex};
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{
     DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, Dim, DimName, Dyn, MatrixView,
@@ -14,8 +15,8 @@ use crate::nalgebra::{
 };
 use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
 use crate::util::{clone_upper_to_lower, reshape_to_slice};
-use crate::Real;
 use crate::Symmetry;
+use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
 use eyre::eyre;
 use itertools::izip;
@@ -58,6 +59,40 @@ where
     jacobian_inv_t * u_grad
 }
 
+/// Transforms basis gradients with respect to reference coordinates into gradients with respect
+/// to physical coordinates.
+///
+/// Each column $\nabla \phi^{\text{ref}}_I$ of `gradients` is replaced by
+/// $J^{-T} \nabla \phi^{\text{ref}}_I$ in place. For [affine](crate::element::FiniteElement::is_affine)
+/// elements, the same inverse Jacobian applies to every quadrature point, so that it only needs
+/// to be computed once per element.
+pub fn transform_reference_gradients<T, D>(jacobian_inv_t: &OMatrix<T, D, D>, mut gradients: MatrixViewMut<T, D, Dyn>)
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    for mut gradient in gradients.column_iter_mut() {
+        let physical_gradient = jacobian_inv_t * &gradient;
+        gradient.copy_from(&physical_gradient);
+    }
+}
+
+/// Computes the determinant and the inverse transpose of an element Jacobian.
+fn jacobian_det_and_inverse_transpose<T, D>(jacobian: OMatrix<T, D, D>) -> eyre::Result<(T, OMatrix<T, D, D>)>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let j_det = jacobian.determinant();
+    let j_inv = jacobian
+        .try_inverse()
+        // TODO: Return a "proper" error instead of using eyre
+        .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
+    Ok((j_det, j_inv.transpose()))
+}
+
 /// TODO: The builder here is pretty complex. Is it possible to simplify without losing too
 /// much type safety?
 pub struct ElementEllipticAssemblerBuilder<Space, Op, QTable, U> {
@@ -151,10 +186,10 @@ where
 
 #[derive(Debug, Clone)]
 pub struct ElementEllipticAssembler<'a, T: Scalar, Space, Op, QTable: ?Sized> {
-    space: &'a Space,
-    op: &'a Op,
-    qtable: &'a QTable,
-    u: DVectorView<'a, T>,
+    pub(super) space: &'a Space,
+    pub(super) op: &'a Op,
+    pub(super) qtable: &'a QTable,
+    pub(super) u: DVectorView<'a, T>,
 }
 
 impl<'a, T, Space, Op, QTable> ElementConnectivityAssembler for ElementEllipticAssembler<'a, T, Space, Op, QTable>
@@ -177,11 +212,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(output, element_index)
     }
 }
@@ -225,7 +260,7 @@ where
 {
     fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
         let s = self.solution_dim();
-        let n = self.element_node_count(element_index);
+        let n = self.element_node_count(element_index.into());
 
         with_thread_local_workspace(
             &WORKSPACE,
@@ -265,7 +300,7 @@ where
     #[allow(non_snake_case)]
     fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
         let s = self.solution_dim();
-        let n = self.element_node_count(element_index);
+        let n = self.element_node_count(element_index.into());
         assert_eq!(output.len(), s * n, "Output vector dimension mismatch");
 
         with_thread_local_workspace(
@@ -307,7 +342,7 @@ where
     #[allow(non_snake_case)]
     fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
         let s = self.solution_dim();
-        let n = self.element_node_count(element_index);
+        let n = self.element_node_count(element_index.into());
         assert_eq!(output.nrows(), s * n, "Output matrix dimension mismatch");
         assert_eq!(output.ncols(), s * n, "Output matrix dimension mismatch");
 
@@ -394,15 +429,18 @@ where
 
     let mut phi_grad = basis_gradients_buffer;
 
+    // Affine elements have the same Jacobian at every quadrature point
+    let constant_jacobian = element
+        .constant_reference_jacobian()
+        .map(jacobian_det_and_inverse_transpose)
+        .transpose()?;
+
     let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
     for (&weight, point, data) in quadrature_iter {
-        let j = element.reference_jacobian(point);
-        let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
-        let j_inv_t = j_inv.transpose();
+        let (j_det, j_inv_t) = match &constant_jacobian {
+            Some(jacobian) => jacobian.clone(),
+            None => jacobian_det_and_inverse_transpose(element.reference_jacobian(point))?,
+        };
 
         // First populate gradients with respect to reference coords
         element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad), &point);
@@ -412,10 +450,7 @@ where
         let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad, u_element);
 
         // Transform reference gradients to gradients with respect to physical coords
-        for mut phi_grad in phi_grad.column_iter_mut() {
-            let new_phi_grad = &j_inv_t * &phi_grad;
-            phi_grad.copy_from(&new_phi_grad);
-        }
+        transform_reference_gradients(&j_inv_t, MatrixViewMut::from(&mut phi_grad));
 
         // Note: We need to multiply the contraction result by a scale factor to account for the
         // quadrature weight and jacobian determinant
@@ -488,15 +523,18 @@ where
 
     let mut phi_grad_ref = basis_gradients_buffer;
 
+    // Affine elements have the same Jacobian at every quadrature point
+    let constant_jacobian = element
+        .constant_reference_jacobian()
+        .map(jacobian_det_and_inverse_transpose)
+        .transpose()?;
+
     let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
     for (&weight, point, data) in quadrature_iter {
-        let j = element.reference_jacobian(point);
-        let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
-        let j_inv_t = j_inv.transpose();
+        let (j_det, j_inv_t) = match &constant_jacobian {
+            Some(jacobian) => jacobian.clone(),
+            None => jacobian_det_and_inverse_transpose(element.reference_jacobian(point))?,
+        };
 
         // First populate gradients with respect to reference coords
         element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad_ref), &point);
@@ -578,17 +616,20 @@ where
     let mut phi_grad_ref = basis_gradients_buffer;
 
     let mut integral = T::zero();
+    // Affine elements have the same Jacobian at every quadrature point
+    let constant_jacobian = element
+        .constant_reference_jacobian()
+        .map(jacobian_det_and_inverse_transpose)
+        .transpose()?;
+
     let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
     for (&weight, point, data) in quadrature_iter {
         // All this stuff is basically the same for energy, vector and matrix. TODO: Consolidate?
 
-        let j = element.reference_jacobian(point);
-        let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
-        let j_inv_t = j_inv.transpose();
+        let (j_det, j_inv_t) = match &constant_jacobian {
+            Some(jacobian) => jacobian.clone(),
+            None => jacobian_det_and_inverse_transpose(element.reference_jacobian(point))?,
+        };
 
         // First populate gradients with respect to reference coords
         element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad_ref), &point);
@@ -0,0 +1,306 @@
+use crate::allocators::{BiDimAllocator, ElementConnectivityAllocator};
+use crate::assembly::local::surface_measure_and_normal;
+use crate::connectivity::Connectivity;
+use crate::element::{map_physical_coordinates, ConnectivityReferenceDim, ElementConnectivity, FiniteElement};
+use crate::mesh::sets::{centroid, CellFace};
+use crate::mesh::split::InteriorFace;
+use crate::mesh::Mesh;
+use crate::nalgebra::{DMatrix, DVector, DefaultAllocator, DimMin, OPoint, OVector};
+use crate::quadrature::QuadraturePair;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+use num::Zero;
+use std::ops::{AddAssign, Mul};
+
+/// A quadrature point on a face of a cell, mapped to physical space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetQuadraturePoint<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    /// The quadrature weight multiplied by the surface measure of the face at the point.
+    pub weight: T,
+    /// The physical coordinates of the point.
+    pub x: OPoint<T, D>,
+    /// The unit normal of the face at the point, pointing out of the cell.
+    pub normal: OVector<T, D>,
+    /// The reference coordinates of the point in the cell.
+    pub reference_coords: OPoint<T, D>,
+}
+
+/// A quadrature point on an interior face, mapped to physical space and to the reference
+/// coordinates of both adjacent cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteriorFacetQuadraturePoint<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    /// The quadrature weight multiplied by the surface measure of the face at the point.
+    pub weight: T,
+    /// The physical coordinates of the point.
+    pub x: OPoint<T, D>,
+    /// The unit normal of the face at the point, pointing from the first into the second cell.
+    pub normal: OVector<T, D>,
+    /// The reference coordinates of the point in the first and second cell of the face.
+    pub reference_coords: [OPoint<T, D>; 2],
+}
+
+/// Maps a quadrature rule on the reference domain of a cell face to physical space.
+///
+/// The face is parametrized by its face connectivity, as given by
+/// [`get_face_connectivity`](crate::connectivity::Connectivity::get_face_connectivity). The
+/// weights are scaled by the surface measure, so that summing `f(x) * weight` over the returned
+/// points approximates the surface integral of `f` over the face. The reference coordinates of
+/// each point in the cell are found by inverting the reference map of the cell, which also
+/// works for curved (isoparametric) cells.
+///
+/// # Errors
+///
+/// Returns an error if the cell or face does not exist, or if a quadrature point cannot be
+/// mapped to the reference coordinates of the cell.
+pub fn cell_face_quadrature<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    cell_face: CellFace,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+) -> eyre::Result<Vec<FacetQuadraturePoint<T, D>>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let (cell_element, face_element) = cell_and_face_elements(mesh, cell_face)?;
+    let cell_centroid = centroid(
+        mesh.vertices(),
+        mesh.connectivity()[cell_face.cell_index].vertex_indices(),
+    );
+    let (weights, points) = face_quadrature;
+    izip!(weights, points)
+        .map(|(&w, xi_face)| {
+            let x = face_element.map_reference_coords(xi_face);
+            let (measure, mut normal) = surface_measure_and_normal(&face_element.reference_jacobian(xi_face));
+            if (&x - &cell_centroid).dot(&normal) < T::zero() {
+                normal = -normal;
+            }
+            let reference_coords = cell_reference_coords(&cell_element, &x, cell_face.cell_index)?;
+            Ok(FacetQuadraturePoint {
+                weight: w * measure,
+                x,
+                normal,
+                reference_coords,
+            })
+        })
+        .collect()
+}
+
+/// Maps a quadrature rule on the reference domain of an interior face to physical space and to
+/// the reference coordinates of both adjacent cells.
+///
+/// The quadrature rule is applied to the parametrization of the face by the first cell of the
+/// face. See [`cell_face_quadrature`] for details.
+///
+/// # Errors
+///
+/// Returns an error if the cells or faces do not exist, or if a quadrature point cannot be
+/// mapped to the reference coordinates of one of the cells.
+pub fn interior_face_quadrature<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    face: &InteriorFace,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+) -> eyre::Result<Vec<InteriorFacetQuadraturePoint<T, D>>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let [first, second] = interior_cell_faces(face);
+    let (second_element, _) = cell_and_face_elements(mesh, second)?;
+    cell_face_quadrature(mesh, first, face_quadrature)?
+        .into_iter()
+        .map(|point| {
+            let second_coords = cell_reference_coords(&second_element, &point.x, second.cell_index)?;
+            Ok(InteriorFacetQuadraturePoint {
+                weight: point.weight,
+                x: point.x,
+                normal: point.normal,
+                reference_coords: [point.reference_coords, second_coords],
+            })
+        })
+        .collect()
+}
+
+/// Approximates the integral of a function over a cell face.
+///
+/// The function is evaluated at the quadrature points returned by [`cell_face_quadrature`].
+pub fn integrate_cell_face<T, D, C, U>(
+    mesh: &Mesh<T, D, C>,
+    cell_face: CellFace,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+    mut f: impl FnMut(&FacetQuadraturePoint<T, D>) -> U,
+) -> eyre::Result<U>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    U: Zero + AddAssign + Mul<T, Output = U>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let mut integral = U::zero();
+    for point in cell_face_quadrature(mesh, cell_face, face_quadrature)? {
+        integral += f(&point) * point.weight;
+    }
+    Ok(integral)
+}
+
+/// Approximates the integral of a function over an interior face.
+///
+/// The function is evaluated at the quadrature points returned by [`interior_face_quadrature`].
+pub fn integrate_interior_face<T, D, C, U>(
+    mesh: &Mesh<T, D, C>,
+    face: &InteriorFace,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+    mut f: impl FnMut(&InteriorFacetQuadraturePoint<T, D>) -> U,
+) -> eyre::Result<U>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    U: Zero + AddAssign + Mul<T, Output = U>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let mut integral = U::zero();
+    for point in interior_face_quadrature(mesh, face, face_quadrature)? {
+        integral += f(&point) * point.weight;
+    }
+    Ok(integral)
+}
+
+/// Maps reference coordinates of an interior face in the parametrization of the first cell to
+/// the corresponding reference coordinates in the parametrization of the second cell.
+///
+/// The two cells sharing a face generally parametrize it differently, since the vertices of
+/// their face connectivities are ordered differently. This function pairs the two
+/// parametrizations, which is needed e.g. to evaluate data stored at the face quadrature points
+/// of the second cell at the quadrature points of the first cell.
+///
+/// # Errors
+///
+/// Returns an error if the cells or faces do not exist, or if the point cannot be located on
+/// the face of the second cell.
+pub fn pair_face_reference_coords<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    face: &InteriorFace,
+    xi_face: &OPoint<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+) -> eyre::Result<OPoint<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let [first, second] = interior_cell_faces(face);
+    let (_, first_face) = cell_and_face_elements(mesh, first)?;
+    let (_, second_face) = cell_and_face_elements(mesh, second)?;
+    let x = first_face.map_reference_coords(xi_face);
+    face_reference_coords(&second_face, &x)
+}
+
+fn interior_cell_faces(face: &InteriorFace) -> [CellFace; 2] {
+    [0, 1].map(|i| CellFace {
+        cell_index: face.cells[i],
+        local_index: face.local_faces[i],
+    })
+}
+
+type FaceElement<T, C> = <<C as Connectivity>::FaceConnectivity as ElementConnectivity<T>>::Element;
+
+fn cell_and_face_elements<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    cell_face: CellFace,
+) -> eyre::Result<(C::Element, FaceElement<T, C>)>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    let CellFace {
+        cell_index,
+        local_index,
+    } = cell_face;
+    let cell_conn = mesh
+        .connectivity()
+        .get(cell_index)
+        .ok_or_else(|| eyre!("Cell index {cell_index} is out of bounds"))?;
+    let cell_element = cell_conn
+        .element(mesh.vertices())
+        .ok_or_else(|| eyre!("Failed to construct element for cell {cell_index}"))?;
+    let face_element = cell_conn
+        .get_face_connectivity(local_index)
+        .ok_or_else(|| eyre!("Cell {cell_index} has no face with local index {local_index}"))?
+        .element(mesh.vertices())
+        .ok_or_else(|| eyre!("Failed to construct element for face {local_index} of cell {cell_index}"))?;
+    Ok((cell_element, face_element))
+}
+
+fn cell_reference_coords<T, D, Element>(
+    element: &Element,
+    x: &OPoint<T, D>,
+    cell_index: usize,
+) -> eyre::Result<OPoint<T, D>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    Element: FiniteElement<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    map_physical_coordinates(element, x).map_err(|err| {
+        eyre!("Failed to map face quadrature point to reference coordinates of cell {cell_index}: {err}")
+    })
+}
+
+/// Finds the reference coordinates of the point on a face element closest to the given point
+/// with the Gauss-Newton method.
+fn face_reference_coords<T, Element>(
+    element: &Element,
+    x: &OPoint<T, Element::GeometryDim>,
+) -> eyre::Result<OPoint<T, Element::ReferenceDim>>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    const MAX_ITERATIONS: usize = 50;
+    let tolerance = T::from_f64(1e3).unwrap() * T::default_epsilon();
+    let mut xi = OPoint::<T, Element::ReferenceDim>::origin();
+    for _ in 0..MAX_ITERATIONS {
+        let residual = x - element.map_reference_coords(&xi);
+        let j = element.reference_jacobian(&xi);
+        let j = DMatrix::from_column_slice(j.nrows(), j.ncols(), j.as_slice());
+        let rhs = j.tr_mul(&DVector::from_column_slice(residual.as_slice()));
+        let delta = (j.tr_mul(&j))
+            .lu()
+            .solve(&rhs)
+            .ok_or_else(|| eyre!("Singular Jacobian of face element"))?;
+        xi.coords
+            .iter_mut()
+            .zip(delta.iter())
+            .for_each(|(xi_i, delta_i)| *xi_i += *delta_i);
+        if delta.norm() <= tolerance {
+            return Ok(xi);
+        }
+    }
+    Err(eyre!("Failed to locate point on face element"))
+}
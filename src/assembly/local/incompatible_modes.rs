@@ -0,0 +1,325 @@
+//! Elements enhanced with incompatible (bubble) modes.
+//!
+//! Low-order quadrilateral and hexahedral elements are notoriously stiff in bending
+//! (shear locking) and for nearly incompressible materials (volumetric locking). A classical
+//! remedy is to enrich the displacement field of each element with *incompatible modes*
+//! (Wilson et al., Taylor et al.), which is equivalent to a particular enhanced assumed strain
+//! (EAS) formulation. Since the additional modes are internal to each element,
+//! they can be eliminated at the element level by [static condensation](condense_element_matrix),
+//! and the resulting element matrices have the same size and sparsity as the standard element.
+use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::{
+    compute_volume_u_grad, condense_element_matrix, ElementConnectivityAssembler, ElementEllipticAssembler,
+    ElementMatrixAssembler, ElementVectorAssembler, QuadratureTable,
+};
+use crate::assembly::operators::{EllipticContraction, Operator};
+use crate::element::VolumetricFiniteElement;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{
+    DMatrix, DMatrixView, DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, Dim, DimName, Dyn,
+    MatrixViewMut, OPoint, OVector, Scalar,
+};
+use crate::quadrature::{BorrowedQuadratureParts, Quadrature, QuadratureParts};
+use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
+use crate::util::{clone_upper_to_lower, reshape_to_slice};
+use crate::{Real, Symmetry};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+
+/// An element assembler for elliptic operators that enhances each element with incompatible modes.
+///
+/// The assembler is constructed from an [`ElementEllipticAssembler`] with
+/// [`ElementEllipticAssembler::with_incompatible_modes`]. Element matrices are computed with
+/// [`assemble_element_incompatible_modes_matrix`], and the incompatible modes are condensed
+/// out at the element level, so that the assembler can be used as a drop-in replacement for the
+/// standard assembler, e.g. with [`CsrAssembler`](crate::assembly::global::CsrAssembler).
+///
+/// Element vectors are computed as $\vec K_e \vec u_e$, where $\vec K_e$ is the condensed
+/// element matrix. This is only consistent with the element matrix for *linear* operators,
+/// such as linear elasticity.
+#[derive(Debug, Clone)]
+pub struct ElementIncompatibleModesAssembler<'a, T: Scalar, Space, Op, QTable: ?Sized> {
+    assembler: ElementEllipticAssembler<'a, T, Space, Op, QTable>,
+}
+
+impl<'a, T: Scalar, Space, Op, QTable: ?Sized> ElementEllipticAssembler<'a, T, Space, Op, QTable> {
+    /// Enhance the elements with incompatible modes, which are statically condensed out of
+    /// the element systems.
+    pub fn with_incompatible_modes(self) -> ElementIncompatibleModesAssembler<'a, T, Space, Op, QTable> {
+        ElementIncompatibleModesAssembler { assembler: self }
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementConnectivityAssembler
+    for ElementIncompatibleModesAssembler<'a, T, Space, Op, QTable>
+where
+    T: Scalar,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: Operator<T, Space::GeometryDim>,
+    QTable: ?Sized,
+    DefaultAllocator: DimAllocator<T, Space::GeometryDim>,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+#[derive(Debug)]
+struct IncompatibleModesWorkspace<T, GeometryDim, Data>
+where
+    T: Scalar,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    u_element: DVector<T>,
+    element_matrix: DMatrix<T>,
+    quadrature_buffer: QuadratureBuffer<T, GeometryDim, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, GeometryDim, Data> Default for IncompatibleModesWorkspace<T, GeometryDim, Data>
+where
+    T: Real,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    fn default() -> Self {
+        Self {
+            u_element: DVector::zeros(0),
+            element_matrix: DMatrix::zeros(0, 0),
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+        }
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+impl<'a, T, Space, Op, QTable> ElementIncompatibleModesAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: EllipticContraction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters> + ?Sized,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    /// Assembles the condensed element matrix into the workspace and calls `f` with the
+    /// workspace.
+    fn with_element_matrix<R>(
+        &self,
+        element_index: usize,
+        f: impl FnOnce(&IncompatibleModesWorkspace<T, Space::ReferenceDim, Op::Parameters>) -> R,
+    ) -> eyre::Result<R> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index.into());
+        let assembler = &self.assembler;
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut IncompatibleModesWorkspace<T, Space::ReferenceDim, Op::Parameters>| {
+                ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, assembler.space);
+                ws.u_element.resize_vertically_mut(s * n, T::zero());
+                gather_global_to_local(assembler.u, &mut ws.u_element, ws.basis_buffer.element_nodes(), s);
+
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, assembler.qtable);
+
+                ws.element_matrix.resize_mut(s * n, s * n, T::zero());
+                let element = ElementInSpace::from_space_and_element_index(assembler.space, element_index);
+                assemble_element_incompatible_modes_matrix(
+                    DMatrixViewMut::from(&mut ws.element_matrix),
+                    &element,
+                    assembler.op,
+                    DVectorView::from(&ws.u_element),
+                    ws.quadrature_buffer.to_parts(),
+                    ws.basis_buffer.element_gradients_mut(),
+                )?;
+                Ok(f(ws))
+            },
+        )
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementVectorAssembler<T> for ElementIncompatibleModesAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: EllipticContraction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters> + ?Sized,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index.into());
+        assert_eq!(output.len(), s * n, "Output vector dimension mismatch");
+        self.with_element_matrix(element_index, |ws| {
+            output.gemv(T::one(), &ws.element_matrix, &ws.u_element, T::zero());
+        })
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementMatrixAssembler<T> for ElementIncompatibleModesAssembler<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: EllipticContraction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Op::Parameters> + ?Sized,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index.into());
+        assert_eq!(output.nrows(), s * n, "Output matrix dimension mismatch");
+        assert_eq!(output.ncols(), s * n, "Output matrix dimension mismatch");
+        self.with_element_matrix(element_index, |ws| output.copy_from(&ws.element_matrix))
+    }
+}
+
+/// Assembles the element matrix associated with the given elliptic operator for an element
+/// enhanced with incompatible modes, with the incompatible modes condensed out.
+///
+/// Each solution component is enriched with the $d$ incompatible modes
+/// $M_k(\vec \xi) = 1 - \xi_k^2, \, k = 1, \dots, d$, where $\vec \xi$ are the reference
+/// coordinates of the element. Following Taylor et al., the physical gradients of the
+/// modes are evaluated as
+/// <div>$$
+/// \nabla M_k = \frac{\det \vec J_0}{\det \vec J} \vec J_0^{-T} \nabla_{\vec \xi} M_k,
+/// $$</div>
+/// where $\vec J_0$ is the Jacobian of the element evaluated at the element center
+/// $\vec \xi = \vec 0$. This choice ensures that the enhanced element passes the patch test
+/// also for distorted elements. The element matrix for the enriched element is assembled
+/// as in [`assemble_element_elliptic_matrix`](crate::assembly::local::assemble_element_elliptic_matrix),
+/// after which the incompatible modes are eliminated by
+/// [static condensation](condense_element_matrix).
+///
+/// The modes are designed for elements whose reference domain is $[-1, 1]^d$, such as
+/// [`Quad4d2Element`](crate::element::Quad4d2Element) and
+/// [`Hex8Element`](crate::element::Hex8Element), and the method is primarily intended for
+/// *linear* operators like linear elasticity. The contraction is evaluated at the
+/// displacement gradient of the compatible part of the solution, i.e. with zero incompatible modes.
+///
+/// # Errors
+///
+/// Returns an error if the element Jacobian is singular or if the condensation fails.
+///
+/// # Panics
+///
+/// Panics if the quadrature data arrays do not have the same lengths.
+///
+/// Panics if the number of columns in the gradient buffer is not equal to the number of nodes
+/// in the element.
+#[allow(non_snake_case)]
+pub fn assemble_element_incompatible_modes_matrix<T, Element, Contraction>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    operator: &Contraction,
+    u_element: DVectorView<T>,
+    quadrature: BorrowedQuadratureParts<T, Element::ReferenceDim, Contraction::Parameters>,
+    basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    Contraction: EllipticContraction<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Contraction::SolutionDim, Element::GeometryDim>,
+{
+    let QuadratureParts {
+        weights: quadrature_weights,
+        points: quadrature_points,
+        data: quadrature_data,
+    } = quadrature;
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), quadrature_data.len());
+    assert_eq!(basis_gradients_buffer.ncols(), element.num_nodes());
+
+    let d = Element::GeometryDim::dim();
+    let s = Contraction::SolutionDim::name();
+    let n = element.num_nodes();
+    // One incompatible mode per reference direction
+    let m = d;
+    assert_eq!(
+        u_element.len(),
+        s.value() * n,
+        "Local element dofs (u_element) dimension mismatch"
+    );
+    assert_eq!(output.nrows(), s.value() * n, "Output matrix dimension mismatch");
+    assert_eq!(output.ncols(), s.value() * n, "Output matrix dimension mismatch");
+
+    let singular_jacobian = || eyre!("Singular element Jacobian encountered");
+    let j0 = element.reference_jacobian(&OPoint::origin());
+    let j0_det = j0.determinant();
+    let j0_inv_t = j0.try_inverse().ok_or_else(singular_jacobian)?.transpose();
+
+    // TODO: Avoid allocating here
+    let mut enhanced_matrix = DMatrix::zeros(s.value() * (n + m), s.value() * (n + m));
+    let mut phi_grad_stacked = DVector::zeros(d * (n + m));
+    let mut phi_grad = basis_gradients_buffer;
+
+    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
+    for (&weight, point, data) in quadrature_iter {
+        let j = element.reference_jacobian(point);
+        let j_det = j.determinant();
+        let j_inv_t = j.try_inverse().ok_or_else(singular_jacobian)?.transpose();
+
+        element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad), point);
+        let u_element = reshape_to_slice(&u_element, (s, Dyn(n)));
+        let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad, u_element);
+
+        // Physical gradients of the standard basis functions
+        for (I, phi_I_grad_ref) in phi_grad.column_iter().enumerate() {
+            phi_grad_stacked
+                .rows_mut(d * I, d)
+                .copy_from(&(&j_inv_t * phi_I_grad_ref));
+        }
+
+        // Physical gradients of the incompatible modes
+        let mode_scale = j0_det / j_det;
+        for k in 0..m {
+            let mut mode_grad_ref = OVector::<T, Element::GeometryDim>::zeros();
+            mode_grad_ref[k] = -T::from_f64(2.0).unwrap() * point[k];
+            phi_grad_stacked
+                .rows_mut(d * (n + k), d)
+                .copy_from(&(&j0_inv_t * mode_grad_ref * mode_scale));
+        }
+
+        let scale = weight * j_det.abs();
+        operator.accumulate_contractions_into(
+            DMatrixViewMut::from(&mut enhanced_matrix),
+            scale,
+            &u_grad,
+            DVectorView::from(&phi_grad_stacked),
+            DVectorView::from(&phi_grad_stacked),
+            data,
+        );
+    }
+
+    if matches!(operator.symmetry(), Symmetry::Symmetric) {
+        clone_upper_to_lower(&mut enhanced_matrix);
+    }
+
+    let condensed = condense_element_matrix(DMatrixView::from(&enhanced_matrix), s.value() * n)?;
+    output.copy_from(&condensed);
+    Ok(())
+}
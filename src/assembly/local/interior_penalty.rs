@@ -0,0 +1,308 @@
+use crate::allocators::{BiDimAllocator, ElementConnectivityAllocator};
+use crate::assembly::local::{
+    surface_measure_and_normal, transform_reference_gradients, ElementConnectivityAssembler, ElementMatrixAssembler,
+    ElementVectorAssembler,
+};
+use crate::element::{
+    map_physical_coordinates, ConnectivityReferenceDim, ElementConnectivity, FiniteElement, ReferenceFiniteElement,
+};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::mesh::adjacency::MeshConnectivity;
+use crate::mesh::sets::{centroid, CellFace};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DMatrixViewMut, DVector, DVectorViewMut, DefaultAllocator, DimMin, Dyn, OMatrix, OPoint};
+use crate::quadrature::QuadraturePair;
+use crate::space::{DiscontinuousSpace, FiniteElementConnectivity};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+
+type BoundaryData<'a, T, D> = dyn 'a + Fn(&OPoint<T, D>) -> T;
+
+/// Assembles the face terms of the symmetric interior penalty discontinuous Galerkin method for
+/// the Laplace operator.
+///
+/// The symmetric interior penalty (SIP) method discretizes $-\Delta u = f$ in a
+/// [discontinuous space](DiscontinuousSpace) by adding to the element-wise Laplace operator the
+/// face terms
+/// <div>$$
+/// \sum_F \int_F - \{ \nabla u \cdot n \} [v] - \{ \nabla v \cdot n \} [u]
+///     + \frac{\sigma}{h_F} [u] [v] \enspace \mathrm{d}s,
+/// $$</div>
+/// where the sum runs over the interior faces $F$ with normal $n$ pointing from the first to the
+/// second cell of the face, $[u] = u^- - u^+$ is the jump and $\{ \cdot \}$ the average of the
+/// traces from the first ($-$) and second ($+$) cell, $\sigma$ is the penalty parameter and $h_F$
+/// the smaller of the diameters of the two cells. The method is stable for sufficiently
+/// large penalty parameters, a common choice being $\sigma = c \, p^2$ with $c \approx 10$ for
+/// polynomial degree $p$.
+///
+/// Dirichlet conditions $u = g$ are imposed weakly by [providing](Self::with_dirichlet_data) the
+/// boundary data, in which case boundary faces contribute the same terms with $\[u\] = u$ and
+/// $\{ \nabla u \cdot n \} = \nabla u \cdot n$ to the matrix, and
+/// <div>$$
+/// \int_F - g \, \nabla v \cdot n + \frac{\sigma}{h_F} g v \enspace \mathrm{d}s
+/// $$</div>
+/// to the right-hand side. Otherwise, boundary faces do not contribute, which corresponds to
+/// natural (Neumann) boundary conditions.
+///
+/// Each "element" of the assembler is one of the unique faces of the mesh, enumerated by
+/// [`MeshConnectivity`], and its nodes are the nodes of the adjacent cells in the
+/// discontinuous space. The assembler can therefore be used with the global assemblers, such as
+/// [`CsrAssembler`](crate::assembly::global::CsrAssembler), and the volume terms are assembled
+/// separately with e.g. an elliptic assembler on the same space. The solution is scalar.
+pub struct InteriorPenaltyFaceAssembler<'a, T, D, C>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    space: &'a DiscontinuousSpace<Mesh<T, D, C>>,
+    connectivity: MeshConnectivity,
+    face_quadrature: QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+    penalty: T,
+    dirichlet_data: Option<&'a BoundaryData<'a, T, D>>,
+}
+
+/// The traces of the basis functions of the cells adjacent to a face at a quadrature point.
+struct FaceQuadraturePoint<T: Real, D: SmallDim>
+where
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    /// Quadrature weight multiplied by the surface measure.
+    weight: T,
+    x: OPoint<T, D>,
+    /// The jumps $[\phi_i]$ of the basis functions of the adjacent cells.
+    jumps: DVector<T>,
+    /// The averages $\{ \nabla \phi_i \cdot n \}$ of the normal derivatives.
+    normal_derivatives: DVector<T>,
+}
+
+impl<'a, T, D, C> InteriorPenaltyFaceAssembler<'a, T, D, C>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    /// Creates an assembler for the faces of the mesh underlying the discontinuous space.
+    ///
+    /// The faces are integrated with the given quadrature rule on the reference element of the
+    /// face connectivity, which should integrate products of the traces of the basis functions
+    /// exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mesh has faces that are shared by more than two cells.
+    pub fn new(
+        space: &'a DiscontinuousSpace<Mesh<T, D, C>>,
+        face_quadrature: QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+        penalty: T,
+    ) -> eyre::Result<Self> {
+        Ok(Self {
+            space,
+            connectivity: MeshConnectivity::from_mesh(space.space())?,
+            face_quadrature,
+            penalty,
+            dirichlet_data: None,
+        })
+    }
+
+    /// Weakly imposes the Dirichlet condition $u = g$ on all boundary faces.
+    pub fn with_dirichlet_data(self, dirichlet_data: &'a BoundaryData<'a, T, D>) -> Self {
+        Self {
+            dirichlet_data: Some(dirichlet_data),
+            ..self
+        }
+    }
+
+    pub fn mesh_connectivity(&self) -> &MeshConnectivity {
+        &self.connectivity
+    }
+
+    fn mesh(&self) -> &Mesh<T, D, C> {
+        self.space.space()
+    }
+
+    fn face_cells(&self, face_index: usize) -> impl Iterator<Item = CellFace> {
+        let face = self.connectivity.face(face_index);
+        std::iter::once(face.first).chain(face.second)
+    }
+
+    fn contributes(&self, face_index: usize) -> bool {
+        !self.connectivity.face(face_index).is_boundary() || self.dirichlet_data.is_some()
+    }
+
+    fn cell_element(&self, cell_index: usize) -> eyre::Result<C::Element> {
+        self.mesh().connectivity()[cell_index]
+            .element(self.mesh().vertices())
+            .ok_or_else(|| eyre!("Failed to construct element for cell {cell_index}"))
+    }
+
+    /// Evaluates the jumps and averaged normal derivatives of the basis functions of the cells
+    /// adjacent to the face at the quadrature points.
+    fn evaluate_face_traces(&self, face_index: usize) -> eyre::Result<Vec<FaceQuadraturePoint<T, D>>> {
+        let first = self.connectivity.face(face_index).first;
+        let mesh = self.mesh();
+        let cell_conn = &mesh.connectivity()[first.cell_index];
+        let face_element = cell_conn
+            .get_face_connectivity(first.local_index)
+            .and_then(|face_conn| face_conn.element(mesh.vertices()))
+            .ok_or_else(|| eyre!("Failed to construct element for face {face_index}"))?;
+        let cell_centroid = centroid(mesh.vertices(), cell_conn.vertex_indices());
+
+        let sides: Vec<_> = self
+            .face_cells(face_index)
+            .map(|cell_face| {
+                let element = self.cell_element(cell_face.cell_index)?;
+                Ok((self.space.element_nodes(cell_face.cell_index).len(), element))
+            })
+            .collect::<eyre::Result<_>>()?;
+        let num_sides = sides.len();
+        let sign = |side: usize| if side == 0 { T::one() } else { -T::one() };
+        let average_weight = T::one() / T::from_usize(num_sides).unwrap();
+        let num_nodes: usize = sides.iter().map(|(n, _)| n).sum();
+
+        let (weights, points) = &self.face_quadrature;
+        let mut traces = Vec::with_capacity(weights.len());
+        for (&w, xi_face) in izip!(weights, points) {
+            let x = face_element.map_reference_coords(xi_face);
+            let (measure, mut normal) = surface_measure_and_normal(&face_element.reference_jacobian(xi_face));
+            if (&x - &cell_centroid).dot(&normal) < T::zero() {
+                normal = -normal;
+            }
+
+            let mut jumps = DVector::zeros(num_nodes);
+            let mut normal_derivatives = DVector::zeros(num_nodes);
+            let mut offset = 0;
+            for (side, (n, element)) in sides.iter().enumerate() {
+                let xi = map_physical_coordinates(element, &x)
+                    .map_err(|err| eyre!("Failed to map quadrature point to reference coordinates: {err}"))?;
+                let mut basis = vec![T::zero(); *n];
+                element.populate_basis(&mut basis, &xi);
+                let mut gradients = OMatrix::<T, D, Dyn>::zeros_generic(D::name(), Dyn(*n));
+                element.populate_basis_gradients(gradients.as_view_mut(), &xi);
+                let jacobian_inv_t = element
+                    .reference_jacobian(&xi)
+                    .try_inverse()
+                    .ok_or_else(|| eyre!("Singular Jacobian in cell adjacent to face {face_index}"))?
+                    .transpose();
+                transform_reference_gradients(&jacobian_inv_t, gradients.as_view_mut());
+
+                for i in 0..*n {
+                    jumps[offset + i] = sign(side) * basis[i];
+                    normal_derivatives[offset + i] = average_weight * gradients.column(i).dot(&normal);
+                }
+                offset += n;
+            }
+
+            traces.push(FaceQuadraturePoint {
+                weight: w * measure,
+                x,
+                jumps,
+                normal_derivatives,
+            });
+        }
+        Ok(traces)
+    }
+
+    /// The penalty parameter divided by the size of the face.
+    fn scaled_penalty(&self, face_index: usize) -> eyre::Result<T> {
+        let mut h = None;
+        for cell_face in self.face_cells(face_index) {
+            let diameter = self.cell_element(cell_face.cell_index)?.diameter();
+            h = Some(h.map_or(diameter, |h: T| h.min(diameter)));
+        }
+        Ok(self.penalty / h.unwrap())
+    }
+}
+
+impl<'a, T, D, C> ElementConnectivityAssembler for InteriorPenaltyFaceAssembler<'a, T, D, C>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.connectivity.num_faces()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        let face = self.connectivity.face(element_index.index());
+        std::iter::once(face.first)
+            .chain(face.second)
+            .map(|cell_face| self.space.element_nodes(cell_face.cell_index).len())
+            .sum()
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        let face = self.connectivity.face(element_index.index());
+        let nodes = std::iter::once(face.first)
+            .chain(face.second)
+            .flat_map(|cell_face| self.space.element_nodes(cell_face.cell_index));
+        for (node_output, node) in output.iter_mut().zip(nodes) {
+            *node_output = NodeIndex::new(node);
+        }
+    }
+}
+
+impl<'a, T, D, C> ElementMatrixAssembler<T> for InteriorPenaltyFaceAssembler<'a, T, D, C>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        output.fill(T::zero());
+        if !self.contributes(element_index) {
+            return Ok(());
+        }
+        let penalty = self.scaled_penalty(element_index)?;
+        for point in self.evaluate_face_traces(element_index)? {
+            let (jumps, normal_derivatives) = (&point.jumps, &point.normal_derivatives);
+            // Symmetric consistency terms and penalty term
+            output.ger(-point.weight, jumps, normal_derivatives, T::one());
+            output.ger(-point.weight, normal_derivatives, jumps, T::one());
+            output.ger(point.weight * penalty, jumps, jumps, T::one());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, D, C> ElementVectorAssembler<T> for InteriorPenaltyFaceAssembler<'a, T, D, C>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + ElementConnectivityAllocator<T, C::FaceConnectivity>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        output.fill(T::zero());
+        let dirichlet_data = match self.dirichlet_data {
+            Some(g) if self.connectivity.face(element_index).is_boundary() => g,
+            _ => return Ok(()),
+        };
+        let penalty = self.scaled_penalty(element_index)?;
+        for point in self.evaluate_face_traces(element_index)? {
+            let g = dirichlet_data(&point.x);
+            output.axpy(-point.weight * g, &point.normal_derivatives, T::one());
+            output.axpy(point.weight * penalty * g, &point.jumps, T::one());
+        }
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use crate::allocators::DimAllocator;
 use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
 use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
 use crate::element::{ReferenceFiniteElement, VolumetricFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
 use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, OPoint};
 use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
 use crate::util::clone_upper_to_lower;
@@ -98,11 +99,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(output, element_index)
     }
 }
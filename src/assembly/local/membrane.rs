@@ -0,0 +1,276 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use crate::assembly::operators::solid::LameParameters;
+use crate::assembly::operators::LinearElasticityOperator;
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar, U2};
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::util::clone_upper_to_lower;
+use crate::{Real, SmallDim};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use std::marker::PhantomData;
+
+pub struct ElementMembraneAssemblerBuilder<T, SpaceRef, QTableRef> {
+    space: SpaceRef,
+    qtable: QTableRef,
+    marker: PhantomData<T>,
+}
+
+impl ElementMembraneAssemblerBuilder<(), (), ()> {
+    pub fn new() -> Self {
+        Self {
+            space: (),
+            qtable: (),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Default for ElementMembraneAssemblerBuilder<(), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SpaceRef, QTableRef> ElementMembraneAssemblerBuilder<(), SpaceRef, QTableRef> {
+    /// Sets the surface finite element space that discretizes the membrane.
+    pub fn with_finite_element_space<Space>(
+        self,
+        space: &Space,
+    ) -> ElementMembraneAssemblerBuilder<(), &Space, QTableRef> {
+        ElementMembraneAssemblerBuilder {
+            space,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_quadrature_table<QTable>(
+        self,
+        qtable: &QTable,
+    ) -> ElementMembraneAssemblerBuilder<(), SpaceRef, &QTable> {
+        ElementMembraneAssemblerBuilder {
+            space: self.space,
+            qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Space, QTable> ElementMembraneAssemblerBuilder<(), &'a Space, &'a QTable> {
+    pub fn build<T>(self) -> ElementMembraneAssembler<'a, T, Space, QTable> {
+        ElementMembraneAssembler {
+            space: self.space,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// An element assembler for the stiffness matrix of linear elastic membranes.
+///
+/// A membrane is a thin elastic sheet that only resists in-plane deformation, such as a fabric,
+/// a balloon or the (small-strain) in-plane response of a shell. Given a displacement $u$ of the
+/// surface $\Gamma$, the membrane strain is the tangential part of the symmetric surface
+/// gradient,
+/// <div>$$
+/// \varepsilon_{\Gamma}(u) = \frac{1}{2} P \left( \nabla_{\Gamma} u + \nabla_{\Gamma} u^T \right) P,
+/// $$</div>
+/// where $P = I - n n^T$ is the projection onto the tangent plane and
+/// $\nabla_{\Gamma} u = \nabla u \\, P$ is the tangential gradient. The membrane is assumed to be
+/// in a state of plane stress, which gives the bilinear form
+/// <div>$$
+/// a(u, v) = \int_{\Gamma} 2 \mu \\, \varepsilon_{\Gamma}(u) : \varepsilon_{\Gamma}(v)
+///     + \bar \lambda \\, \mathrm{tr} \\, \varepsilon_{\Gamma}(u) \\, \mathrm{tr} \\, \varepsilon_{\Gamma}(v) \\, \mathrm{d}s,
+///     \qquad \bar \lambda = \frac{2 \mu \lambda}{\lambda + 2 \mu},
+/// $$</div>
+/// with the Lamé parameters $\mu$ and $\lambda$ given by the [`LameParameters`] associated with
+/// each quadrature point. The parameters are those of the material scaled by the thickness of
+/// the membrane. See [`assemble_element_membrane_matrix`] for the element matrix.
+///
+/// The solution dimension is always equal to the geometric dimension. The finite element space
+/// must be a surface space, such as a mesh of
+/// [`Tri3d3Element`](crate::element::Tri3d3Element)s or
+/// [`Quad4d3Element`](crate::element::Quad4d3Element)s. Since a membrane has no bending stiffness,
+/// the stiffness matrix of a flat membrane is singular with respect to displacements normal to
+/// the membrane.
+#[derive(Debug, Clone)]
+pub struct ElementMembraneAssembler<'a, T, Space, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T, Space, QTable> ElementConnectivityAssembler for ElementMembraneAssembler<'a, T, Space, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        Space::GeometryDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(MEMBRANE_WORKSPACE);
+
+struct MembraneWorkspace<T, D, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, D, Data> Default for MembraneWorkspace<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: QuadratureBuffer::default(),
+            basis_buffer: BasisFunctionBuffer::default(),
+        }
+    }
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for ElementMembraneAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = LameParameters<T>>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &MEMBRANE_WORKSPACE,
+            |ws: &mut MembraneWorkspace<T, Space::ReferenceDim, LameParameters<T>>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                assemble_element_membrane_matrix(
+                    output,
+                    &element,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    ws.quadrature_buffer.data(),
+                    ws.basis_buffer.element_gradients_mut(),
+                )
+            },
+        )
+    }
+}
+
+/// Assembles the element stiffness matrix of a linear elastic membrane.
+///
+/// See [`ElementMembraneAssembler`] for the definition of the bilinear form. With the tangential
+/// gradients $g_I = \nabla_{\Gamma} \phi_I$ of the basis functions, the $d \times d$ block of the
+/// element matrix associated with nodes $I$ and $J$ is given by
+/// <div>$$
+/// K_{IJ} = \int_{K} \mu \\, (g_I \cdot g_J) \\, P + \mu \\, g_J g_I^T + \bar \lambda \\, g_I g_J^T \\, \mathrm{d}s.
+/// $$</div>
+///
+/// A working array for storing the reference gradients of the basis functions must be provided.
+///
+/// **This is a low-level routine**. Most users will not need to call this function directly,
+/// and are instead more likely to use [`ElementMembraneAssembler`].
+///
+/// # Errors
+///
+/// Returns an error if the element is degenerate at any quadrature point.
+///
+/// # Panics
+///
+/// Panics if the quadrature weights, points and data arrays do not have the same length.
+///
+/// Panics if the basis gradients buffer does not have one column per node, or if the output
+/// matrix is not a square matrix of dimension `n * d`, where `n` is the number of nodes in the
+/// element and `d` is the geometric dimension.
+#[allow(non_snake_case)]
+pub fn assemble_element_membrane_matrix<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    quadrature_data: &[LameParameters<T>],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), quadrature_data.len());
+    let n = element.num_nodes();
+    assert_eq!(basis_gradients_buffer.ncols(), n);
+    let d = Element::GeometryDim::dim();
+    assert_eq!(output.nrows(), d * n, "Output matrix dimension mismatch");
+    assert_eq!(output.ncols(), d * n, "Output matrix dimension mismatch");
+
+    output.fill(T::zero());
+
+    let plane_stress = LinearElasticityOperator::plane_stress();
+    for (&weight, point, lame) in izip!(quadrature_weights, quadrature_points, quadrature_data) {
+        let LameParameters { mu, lambda } = plane_stress.effective_lame_parameters::<T, U2>(lame);
+
+        let j = element.reference_jacobian(point);
+        let metric = j.transpose() * &j;
+        let measure = metric.determinant().sqrt();
+        let metric_inv = metric
+            .try_inverse()
+            .ok_or_else(|| eyre!("Degenerate surface element encountered"))?;
+        let gradient_map = &j * metric_inv;
+        // The projection onto the tangent plane is P = J (J^T J)^{-1} J^T
+        let projection = &gradient_map * j.transpose();
+
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+        let g: OMatrix<T, Element::GeometryDim, Dyn> = &gradient_map * &basis_gradients_buffer;
+
+        let scale = weight * measure;
+        for I in 0..n {
+            let g_I = g.column(I);
+            // Fill only the upper triangle, then copy over lower half at the end
+            for J in I..n {
+                let g_J = g.column(J);
+                let K_IJ =
+                    &projection * (mu * g_I.dot(&g_J)) + g_J * g_I.transpose() * mu + g_I * g_J.transpose() * lambda;
+                let mut output_IJ = output.view_mut((d * I, d * J), (d, d));
+                output_IJ += K_IJ * scale;
+            }
+        }
+    }
+
+    clone_upper_to_lower(&mut output);
+
+    Ok(())
+}
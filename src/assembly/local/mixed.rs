@@ -0,0 +1,329 @@
+use crate::allocators::TriDimAllocator;
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::QuadratureTable;
+use crate::assembly::operators::MixedOperator;
+use crate::element::FiniteElement;
+use crate::index::NodeIndex;
+use crate::nalgebra::{DMatrix, DMatrixViewMut, DefaultAllocator, DimName, Scalar};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use crate::Real;
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use std::marker::PhantomData;
+
+/// Connectivity of element assemblers whose element matrices couple two different sets of
+/// nodes.
+///
+/// In mixed problems, the off-diagonal blocks of the system matrix couple the degrees of freedom
+/// of different fields. The rows of the element matrices are associated with the nodes of the
+/// *row* field (the test field) and the columns with the nodes of the *column* field (the trial
+/// field), each with its own solution dimension. Both fields must be defined on the same
+/// elements.
+pub trait ElementMixedConnectivityAssembler {
+    fn num_elements(&self) -> usize;
+
+    fn row_solution_dim(&self) -> usize;
+
+    fn col_solution_dim(&self) -> usize;
+
+    fn num_row_nodes(&self) -> usize;
+
+    fn num_col_nodes(&self) -> usize;
+
+    fn row_element_node_count(&self, element_index: usize) -> usize;
+
+    fn col_element_node_count(&self, element_index: usize) -> usize;
+
+    fn populate_row_element_nodes(&self, output: &mut [usize], element_index: usize);
+
+    fn populate_col_element_nodes(&self, output: &mut [usize], element_index: usize);
+}
+
+/// An element assembler for the rectangular element matrices of a mixed problem.
+///
+/// The global matrix is assembled with
+/// [`CsrAssembler::assemble_mixed`](crate::assembly::global::CsrAssembler::assemble_mixed).
+pub trait ElementMixedMatrixAssembler<T: Scalar>: ElementMixedConnectivityAssembler {
+    /// Assembles the $s_r n_r \times s_c n_c$ element matrix, where $s_r, s_c$ are the row and
+    /// column solution dimensions and $n_r, n_c$ the number of row and column nodes of the
+    /// element.
+    fn assemble_element_mixed_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()>;
+
+    fn assemble_element_mixed_matrix(&self, element_index: usize) -> eyre::Result<DMatrix<T>>
+    where
+        T: Real,
+    {
+        let nrows = self.row_solution_dim() * self.row_element_node_count(element_index);
+        let ncols = self.col_solution_dim() * self.col_element_node_count(element_index);
+        let mut output = DMatrix::zeros(nrows, ncols);
+        self.assemble_element_mixed_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
+        Ok(output)
+    }
+}
+
+/// An element assembler for [mixed operators](MixedOperator).
+///
+/// Assembles the element matrices
+/// <div>$$
+/// \vec B^K_{IJ} = \int_K \mathcal{B}(\phi_I, \nabla \phi_I, \psi_J, \nabla \psi_J) \enspace \mathrm{d} x,
+/// $$</div>
+/// where $\phi_I$ are the basis functions of the test space and $\psi_J$ those of the trial space.
+/// The two spaces must consist of the same elements, i.e. element $K$ in the test space must
+/// occupy the same region as element $K$ in the trial space, although the spaces may use
+/// different elements, such as quadratic velocities and linear pressures. The geometry of the
+/// elements is taken from the test space, and the quadrature rules are given in the reference
+/// coordinates shared by both spaces.
+#[derive(Debug, Clone)]
+pub struct ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable> {
+    test_space: &'a TestSpace,
+    trial_space: &'a TrialSpace,
+    operator: &'a Op,
+    qtable: &'a QTable,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> ElementMixedAssembler<'a, T, (), (), (), ()> {
+    pub fn new() -> Self {
+        Self {
+            test_space: &(),
+            trial_space: &(),
+            operator: &(),
+            qtable: &(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Default for ElementMixedAssembler<'a, T, (), (), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, TrialSpace, Op, QTable> ElementMixedAssembler<'a, T, (), TrialSpace, Op, QTable> {
+    pub fn with_test_space<TestSpace>(
+        self,
+        test_space: &'a TestSpace,
+    ) -> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable> {
+        ElementMixedAssembler {
+            test_space,
+            trial_space: self.trial_space,
+            operator: self.operator,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, TestSpace, Op, QTable> ElementMixedAssembler<'a, T, TestSpace, (), Op, QTable> {
+    pub fn with_trial_space<TrialSpace>(
+        self,
+        trial_space: &'a TrialSpace,
+    ) -> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable> {
+        ElementMixedAssembler {
+            test_space: self.test_space,
+            trial_space,
+            operator: self.operator,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, TestSpace, TrialSpace, QTable> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, (), QTable> {
+    pub fn with_operator<Op>(
+        self,
+        operator: &'a Op,
+    ) -> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable> {
+        ElementMixedAssembler {
+            test_space: self.test_space,
+            trial_space: self.trial_space,
+            operator,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, TestSpace, TrialSpace, Op> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, ()> {
+    pub fn with_quadrature_table<QTable>(
+        self,
+        qtable: &'a QTable,
+    ) -> ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable> {
+        ElementMixedAssembler {
+            test_space: self.test_space,
+            trial_space: self.trial_space,
+            operator: self.operator,
+            qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, TestSpace, TrialSpace, Op, QTable> ElementMixedConnectivityAssembler
+    for ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable>
+where
+    T: Real,
+    TestSpace: VolumetricFiniteElementSpace<T>,
+    TrialSpace: FiniteElementConnectivity,
+    Op: MixedOperator<T, TestSpace::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, TestSpace::ReferenceDim, Op::TestDim, Op::TrialDim>,
+{
+    fn num_elements(&self) -> usize {
+        self.test_space.num_elements()
+    }
+
+    fn row_solution_dim(&self) -> usize {
+        Op::TestDim::dim()
+    }
+
+    fn col_solution_dim(&self) -> usize {
+        Op::TrialDim::dim()
+    }
+
+    fn num_row_nodes(&self) -> usize {
+        self.test_space.num_nodes()
+    }
+
+    fn num_col_nodes(&self) -> usize {
+        self.trial_space.num_nodes()
+    }
+
+    fn row_element_node_count(&self, element_index: usize) -> usize {
+        self.test_space.element_node_count(element_index.into())
+    }
+
+    fn col_element_node_count(&self, element_index: usize) -> usize {
+        self.trial_space.element_node_count(element_index.into())
+    }
+
+    fn populate_row_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.test_space
+            .populate_element_nodes(NodeIndex::from_raw_slice_mut(output), element_index.into())
+    }
+
+    fn populate_col_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.trial_space
+            .populate_element_nodes(NodeIndex::from_raw_slice_mut(output), element_index.into())
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+#[derive(Debug)]
+struct MixedAssemblerWorkspace<T: Scalar, D: DimName, Data>
+where
+    DefaultAllocator: TriDimAllocator<T, D, D, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    test_basis_buffer: BasisFunctionBuffer<T>,
+    trial_basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T: Real, D: DimName, Data> Default for MixedAssemblerWorkspace<T, D, Data>
+where
+    DefaultAllocator: TriDimAllocator<T, D, D, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: Default::default(),
+            test_basis_buffer: Default::default(),
+            trial_basis_buffer: Default::default(),
+        }
+    }
+}
+
+impl<'a, T, TestSpace, TrialSpace, Op, QTable> ElementMixedMatrixAssembler<T>
+    for ElementMixedAssembler<'a, T, TestSpace, TrialSpace, Op, QTable>
+where
+    T: Real,
+    TestSpace: VolumetricFiniteElementSpace<T>,
+    TrialSpace: VolumetricFiniteElementSpace<T, ReferenceDim = TestSpace::ReferenceDim>,
+    Op: MixedOperator<T, TestSpace::ReferenceDim>,
+    QTable: QuadratureTable<T, TestSpace::ReferenceDim, Data = Op::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, TestSpace::ReferenceDim, Op::TestDim, Op::TrialDim>,
+{
+    #[allow(non_snake_case)]
+    fn assemble_element_mixed_matrix_into(
+        &self,
+        element_index: usize,
+        mut output: DMatrixViewMut<T>,
+    ) -> eyre::Result<()> {
+        let num_elements = self.test_space.num_elements();
+        if self.trial_space.num_elements() != num_elements {
+            return Err(eyre!(
+                "Number of elements in trial space ({}) does not match number of elements in test space ({})",
+                self.trial_space.num_elements(),
+                num_elements
+            ));
+        }
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut MixedAssemblerWorkspace<T, TestSpace::ReferenceDim, Op::Parameters>| {
+                let d = TestSpace::ReferenceDim::dim();
+                let s_test = Op::TestDim::dim();
+                let s_trial = Op::TrialDim::dim();
+                let element = ElementInSpace::from_space_and_element_index(self.test_space, element_index);
+                let n_test = self.test_space.element_node_count(element_index.into());
+                let n_trial = self.trial_space.element_node_count(element_index.into());
+                assert_eq!(output.nrows(), s_test * n_test, "Output matrix dimension mismatch");
+                assert_eq!(output.ncols(), s_trial * n_trial, "Output matrix dimension mismatch");
+
+                ws.test_basis_buffer.resize(n_test, d);
+                ws.trial_basis_buffer.resize(n_trial, d);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                output.fill(T::zero());
+                let quadrature_iter = izip!(
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    ws.quadrature_buffer.data()
+                );
+                for (&weight, point, data) in quadrature_iter {
+                    let jacobian = element.reference_jacobian(point);
+                    let j_det = jacobian.determinant();
+                    let j_inv_t = jacobian
+                        .try_inverse()
+                        .ok_or_else(|| eyre!("Singular Jacobian encountered in element {}", element_index))?
+                        .transpose();
+                    let scale = weight * j_det.abs();
+
+                    ws.test_basis_buffer
+                        .populate_element_basis_values_from_space(element_index, self.test_space, point);
+                    ws.test_basis_buffer
+                        .populate_element_basis_gradients_from_space(element_index, self.test_space, point);
+                    ws.trial_basis_buffer
+                        .populate_element_basis_values_from_space(element_index, self.trial_space, point);
+                    ws.trial_basis_buffer
+                        .populate_element_basis_gradients_from_space(element_index, self.trial_space, point);
+
+                    // Transform reference gradients to physical gradients
+                    let test_gradients = &j_inv_t
+                        * ws.test_basis_buffer
+                            .element_gradients::<TestSpace::ReferenceDim>();
+                    let trial_gradients = &j_inv_t
+                        * ws.trial_basis_buffer
+                            .element_gradients::<TestSpace::ReferenceDim>();
+                    let test_values = ws.test_basis_buffer.element_basis_values();
+                    let trial_values = ws.trial_basis_buffer.element_basis_values();
+
+                    for (J, &psi_J) in trial_values.iter().enumerate() {
+                        let grad_psi_J = trial_gradients.column(J).clone_owned();
+                        for (I, &phi_I) in test_values.iter().enumerate() {
+                            let grad_phi_I = test_gradients.column(I).clone_owned();
+                            let b_IJ = self
+                                .operator
+                                .compute_coupling(phi_I, &grad_phi_I, psi_J, &grad_psi_J, data);
+                            let mut output_IJ = output.view_mut((s_test * I, s_trial * J), (s_test, s_trial));
+                            output_IJ += b_IJ * scale;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
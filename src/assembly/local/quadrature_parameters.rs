@@ -0,0 +1,230 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::local::QuadratureTable;
+use crate::memory::HeapSizeEstimate;
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DefaultAllocator, OPoint, Scalar};
+use crate::space::FiniteElementSpace;
+use crate::util::NestedVec;
+use crate::{Real, SmallDim};
+
+/// Storage for parameters associated with the individual quadrature points of each element.
+///
+/// [Operator parameters](crate::assembly::operators::Operator::Parameters) describe quantities
+/// such as material properties, which may vary between quadrature points. The storage keeps one
+/// parameter value for each pair of element and quadrature point, and thereby supports both
+/// heterogeneous materials and history variables, such as plastic strain, which are updated at
+/// each quadrature point over the course of a simulation.
+///
+/// The storage must be consistent with the quadrature table used for assembly, in the sense that
+/// each element has one parameter value for each of its quadrature points. Assemblers consume the
+/// parameters through a [`ParameterizedQuadratureTable`], which pairs the quadrature rules of a
+/// table with the parameters of the storage, see
+/// [`with_quadrature_table`](Self::with_quadrature_table).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuadratureParameterStorage<P> {
+    parameters: NestedVec<P>,
+}
+
+impl<P> QuadratureParameterStorage<P> {
+    /// Constructs the storage from parameters given per element and quadrature point.
+    pub fn from_nested_vec(parameters: NestedVec<P>) -> Self {
+        Self { parameters }
+    }
+
+    /// Constructs the storage with the same value for every quadrature point of the given number
+    /// of elements.
+    pub fn from_constant<T, D, QTable>(table: &QTable, num_elements: usize, value: P) -> Self
+    where
+        T: Scalar,
+        D: SmallDim,
+        P: Clone,
+        QTable: ?Sized + QuadratureTable<T, D>,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let mut parameters = NestedVec::new();
+        for element_index in 0..num_elements {
+            let num_points = table.element_quadrature_size(element_index);
+            parameters.push(&vec![value.clone(); num_points]);
+        }
+        Self { parameters }
+    }
+
+    /// Constructs the storage by evaluating a function at the physical coordinates of each
+    /// quadrature point in the space.
+    ///
+    /// The function is called with the index of the element and the physical coordinates of the
+    /// quadrature point.
+    pub fn from_fn<T, Space, QTable>(
+        space: &Space,
+        table: &QTable,
+        mut f: impl FnMut(usize, &OPoint<T, Space::GeometryDim>) -> P,
+    ) -> Self
+    where
+        T: Real,
+        Space: FiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let mut parameters = NestedVec::new();
+        let mut points = Vec::new();
+        let mut weights = Vec::new();
+        for element_index in 0..space.num_elements() {
+            let num_points = table.element_quadrature_size(element_index);
+            points.resize(num_points, OPoint::origin());
+            weights.resize(num_points, T::zero());
+            table.populate_element_quadrature(element_index, &mut points, &mut weights);
+            let mut element_parameters = parameters.begin_array();
+            for xi in &points {
+                let x = space.map_element_reference_coords(element_index, xi);
+                element_parameters.push_single(f(element_index, &x));
+            }
+        }
+        Self { parameters }
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// The number of quadrature points of the given element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_quadrature_size(&self, element_index: usize) -> usize {
+        self.element_parameters(element_index).len()
+    }
+
+    /// The parameters at the quadrature points of the given element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_parameters(&self, element_index: usize) -> &[P] {
+        self.parameters
+            .get(element_index)
+            .expect("Element index out of bounds")
+    }
+
+    /// Mutable access to the parameters at the quadrature points of the given element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_parameters_mut(&mut self, element_index: usize) -> &mut [P] {
+        self.parameters
+            .get_mut(element_index)
+            .expect("Element index out of bounds")
+    }
+
+    /// The parameter at the given quadrature point of the given element, if it exists.
+    pub fn get(&self, element_index: usize, quadrature_index: usize) -> Option<&P> {
+        self.parameters
+            .get(element_index)
+            .and_then(|parameters| parameters.get(quadrature_index))
+    }
+
+    pub fn get_mut(&mut self, element_index: usize, quadrature_index: usize) -> Option<&mut P> {
+        self.parameters
+            .get_mut(element_index)
+            .and_then(|parameters| parameters.get_mut(quadrature_index))
+    }
+
+    /// Calls the given function with the element index, quadrature point index and a mutable
+    /// reference to the parameter, for every quadrature point.
+    ///
+    /// This is typically used to update history variables after a completed time step.
+    pub fn update(&mut self, mut f: impl FnMut(usize, usize, &mut P)) {
+        for element_index in 0..self.parameters.len() {
+            let element_parameters = self.parameters.get_mut(element_index).unwrap();
+            for (quadrature_index, parameter) in element_parameters.iter_mut().enumerate() {
+                f(element_index, quadrature_index, parameter);
+            }
+        }
+    }
+
+    pub fn as_nested_vec(&self) -> &NestedVec<P> {
+        &self.parameters
+    }
+
+    pub fn into_nested_vec(self) -> NestedVec<P> {
+        self.parameters
+    }
+
+    /// Pairs the quadrature rules of the given table with the parameters of the storage, for use
+    /// with assemblers.
+    pub fn with_quadrature_table<'a, QTable: ?Sized>(
+        &'a self,
+        table: &'a QTable,
+    ) -> ParameterizedQuadratureTable<'a, QTable, P> {
+        ParameterizedQuadratureTable {
+            table,
+            parameters: self,
+        }
+    }
+}
+
+/// Heap memory owned by the parameters themselves is not taken into account.
+impl<P> HeapSizeEstimate for QuadratureParameterStorage<P> {
+    fn heap_size_estimate(&self) -> usize {
+        self.parameters.heap_size_estimate()
+    }
+}
+
+/// A quadrature table whose data is given by a [`QuadratureParameterStorage`].
+///
+/// The quadrature rules of the underlying table are used as is, while any data stored in the
+/// underlying table is replaced by the parameters of the storage.
+#[derive(Debug)]
+pub struct ParameterizedQuadratureTable<'a, QTable: ?Sized, P> {
+    table: &'a QTable,
+    parameters: &'a QuadratureParameterStorage<P>,
+}
+
+impl<'a, QTable: ?Sized, P> Clone for ParameterizedQuadratureTable<'a, QTable, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, QTable: ?Sized, P> Copy for ParameterizedQuadratureTable<'a, QTable, P> {}
+
+impl<'a, QTable: ?Sized, P> ParameterizedQuadratureTable<'a, QTable, P> {
+    pub fn table(&self) -> &'a QTable {
+        self.table
+    }
+
+    pub fn parameters(&self) -> &'a QuadratureParameterStorage<P> {
+        self.parameters
+    }
+}
+
+impl<'a, T, D, QTable, P> QuadratureTable<T, D> for ParameterizedQuadratureTable<'a, QTable, P>
+where
+    T: Scalar,
+    D: SmallDim,
+    QTable: ?Sized + QuadratureTable<T, D>,
+    P: Default + Clone,
+    DefaultAllocator: Allocator<T, D>,
+{
+    type Data = P;
+
+    fn element_quadrature_size(&self, element_index: usize) -> usize {
+        self.table.element_quadrature_size(element_index)
+    }
+
+    fn populate_element_data(&self, element_index: usize, data: &mut [Self::Data]) {
+        let parameters = self.parameters.element_parameters(element_index);
+        assert_eq!(
+            data.len(),
+            parameters.len(),
+            "Length mismatch in data array: Stored parameter array has different length than output array."
+        );
+        data.clone_from_slice(parameters);
+    }
+
+    fn populate_element_quadrature(&self, element_index: usize, points: &mut [OPoint<T, D>], weights: &mut [T]) {
+        self.table
+            .populate_element_quadrature(element_index, points, weights);
+    }
+}
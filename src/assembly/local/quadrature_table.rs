@@ -1,3 +1,4 @@
+use crate::memory::HeapSizeEstimate;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
 use crate::quadrature::QuadraturePair;
@@ -160,6 +161,17 @@ where
     pub data: NestedVec<Data>,
 }
 
+impl<T, GeometryDim, Data> HeapSizeEstimate for GeneralQuadratureTable<T, GeometryDim, Data>
+where
+    T: Scalar,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        self.points.heap_size_estimate() + self.weights.heap_size_estimate() + self.data.heap_size_estimate()
+    }
+}
+
 impl<T, GeometryDim, Data> QuadratureTable<T, GeometryDim> for GeneralQuadratureTable<T, GeometryDim, Data>
 where
     T: Scalar,
@@ -266,6 +278,17 @@ where
     }
 }
 
+impl<T, GeometryDim, Data> HeapSizeEstimate for UniformQuadratureTable<T, GeometryDim, Data>
+where
+    T: Scalar,
+    GeometryDim: DimName,
+    DefaultAllocator: Allocator<T, GeometryDim>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        self.points.heap_size_estimate() + self.weights.heap_size_estimate() + self.data.heap_size_estimate()
+    }
+}
+
 impl<T, GeometryDim, Data> QuadratureTable<T, GeometryDim> for UniformQuadratureTable<T, GeometryDim, Data>
 where
     T: Scalar,
@@ -382,6 +405,20 @@ where
     }
 }
 
+impl<T, D, Data> HeapSizeEstimate for CompactQuadratureTable<T, D, Data>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        self.points.heap_size_estimate()
+            + self.weights.heap_size_estimate()
+            + self.data.heap_size_estimate()
+            + self.element_to_rule_map.heap_size_estimate()
+    }
+}
+
 impl<T, D, Data> QuadratureTable<T, D> for CompactQuadratureTable<T, D, Data>
 where
     T: Scalar,
@@ -0,0 +1,134 @@
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DMatrix, DMatrixViewMut};
+use crate::Real;
+use eyre::eyre;
+
+/// An element matrix assembler that replicates the element matrix of a single reference element.
+///
+/// On structured meshes, such as uniform grids, all elements are translated copies of each other.
+/// For linear operators with constant coefficients, the element matrices are then identical, as
+/// long as the local node ordering is the same in every element. Instead of recomputing the same
+/// matrix for every element, this assembler computes the element matrix of a single reference
+/// element once, and global assembly reduces to scattering this matrix into the global matrix.
+///
+/// Coefficients that are constant on each element, but vary between elements, can be taken
+/// into account by providing a [scale factor for each element](Self::with_element_scaling).
+///
+/// The connectivity is taken from the wrapped assembler. It is the responsibility of the user
+/// to ensure that all elements really share the same element matrix. In particular, the
+/// element matrix must not depend on the current solution, as is the case for nonlinear
+/// operators.
+#[derive(Debug, Clone)]
+pub struct ReplicatedElementMatrixAssembler<Assembler, T: Real> {
+    assembler: Assembler,
+    element_matrix: DMatrix<T>,
+    element_scaling: Option<Vec<T>>,
+}
+
+impl<Assembler, T> ReplicatedElementMatrixAssembler<Assembler, T>
+where
+    T: Real,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    /// Computes the element matrix of the given reference element and uses it for all elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference element index is out of bounds, or if the assembly
+    /// of the reference element matrix fails.
+    pub fn from_reference_element(assembler: Assembler, reference_element_index: usize) -> eyre::Result<Self> {
+        if reference_element_index >= assembler.num_elements() {
+            return Err(eyre!(
+                "Reference element index {reference_element_index} is out of bounds for {} elements",
+                assembler.num_elements()
+            ));
+        }
+        let element_matrix = assembler.assemble_element_matrix(reference_element_index)?;
+        Ok(Self {
+            assembler,
+            element_matrix,
+            element_scaling: None,
+        })
+    }
+
+    /// Scales the element matrix of each element by the corresponding factor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of factors does not match the number of elements.
+    pub fn with_element_scaling(self, factors: Vec<T>) -> eyre::Result<Self> {
+        if factors.len() != self.assembler.num_elements() {
+            return Err(eyre!(
+                "Number of scale factors ({}) does not match number of elements ({})",
+                factors.len(),
+                self.assembler.num_elements()
+            ));
+        }
+        Ok(Self {
+            element_scaling: Some(factors),
+            ..self
+        })
+    }
+
+    /// Returns the element matrix that is used for all elements, before scaling.
+    pub fn element_matrix(&self) -> &DMatrix<T> {
+        &self.element_matrix
+    }
+
+    pub fn assembler(&self) -> &Assembler {
+        &self.assembler
+    }
+
+    pub fn into_assembler(self) -> Assembler {
+        self.assembler
+    }
+}
+
+impl<Assembler, T> ElementConnectivityAssembler for ReplicatedElementMatrixAssembler<Assembler, T>
+where
+    T: Real,
+    Assembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<Assembler, T> ElementMatrixAssembler<T> for ReplicatedElementMatrixAssembler<Assembler, T>
+where
+    T: Real,
+    Assembler: ElementConnectivityAssembler,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        if output.shape() != self.element_matrix.shape() {
+            return Err(eyre!(
+                "Element {element_index} has element matrix dimensions {:?}, but the replicated \
+                 element matrix has dimensions {:?}",
+                output.shape(),
+                self.element_matrix.shape()
+            ));
+        }
+        match &self.element_scaling {
+            Some(factors) => output.zip_apply(&self.element_matrix, |a, b| *a = factors[element_index] * b),
+            None => output.copy_from(&self.element_matrix),
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,18 @@
 use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
 use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::global::gather_global_to_local;
 use crate::assembly::local::{ElementConnectivityAssembler, ElementVectorAssembler, QuadratureTable};
 use crate::assembly::operators::Operator;
 use crate::element::{ReferenceFiniteElement, VolumetricFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
 use crate::nalgebra::{
-    DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint, OVector, Scalar, U1,
+    DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint, OVector,
+    Scalar, U1,
 };
 use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
 use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
 use itertools::izip;
 use std::marker::PhantomData;
 
@@ -21,10 +25,31 @@ where
     fn evaluate(&self, coords: &OPoint<T, GeometryDim>, data: &Self::Parameters) -> OVector<T, Self::SolutionDim>;
 }
 
-pub struct ElementSourceAssemblerBuilder<T, SpaceRef, SourceRef, QTableRef> {
+/// A source function $f(x, u)$ that depends on the solution $u$.
+///
+/// This generalizes [`SourceFunction`] to nonlinear source terms such as reaction terms, for
+/// which the solution is interpolated at each quadrature point. Source terms of this kind are
+/// assembled with [`ElementSolutionDependentSourceAssembler`], which is obtained by providing
+/// the solution with [`ElementSourceAssemblerBuilder::with_u`].
+pub trait SolutionDependentSourceFunction<T, GeometryDim>: Operator<T, GeometryDim>
+where
+    T: Scalar,
+    GeometryDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Self::SolutionDim>,
+{
+    fn evaluate_with_u(
+        &self,
+        coords: &OPoint<T, GeometryDim>,
+        u: &OVector<T, Self::SolutionDim>,
+        data: &Self::Parameters,
+    ) -> OVector<T, Self::SolutionDim>;
+}
+
+pub struct ElementSourceAssemblerBuilder<T, SpaceRef, SourceRef, QTableRef, U = ()> {
     space: SpaceRef,
     source: SourceRef,
     qtable: QTableRef,
+    u: U,
     marker: PhantomData<T>,
 }
 
@@ -34,20 +59,22 @@ impl ElementSourceAssemblerBuilder<(), (), (), ()> {
             space: (),
             source: (),
             qtable: (),
+            u: (),
             marker: PhantomData,
         }
     }
 }
 
-impl<SpaceRef, SourceRef, QTableRef> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, QTableRef> {
+impl<SpaceRef, SourceRef, QTableRef, U> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, QTableRef, U> {
     pub fn with_finite_element_space<Space>(
         self,
         space: &Space,
-    ) -> ElementSourceAssemblerBuilder<(), &Space, SourceRef, QTableRef> {
+    ) -> ElementSourceAssemblerBuilder<(), &Space, SourceRef, QTableRef, U> {
         ElementSourceAssemblerBuilder {
             space,
             source: self.source,
             qtable: self.qtable,
+            u: self.u,
             marker: PhantomData,
         }
     }
@@ -55,11 +82,12 @@ impl<SpaceRef, SourceRef, QTableRef> ElementSourceAssemblerBuilder<(), SpaceRef,
     pub fn with_source<Source>(
         self,
         source: &Source,
-    ) -> ElementSourceAssemblerBuilder<(), SpaceRef, &Source, QTableRef> {
+    ) -> ElementSourceAssemblerBuilder<(), SpaceRef, &Source, QTableRef, U> {
         ElementSourceAssemblerBuilder {
             space: self.space,
             source,
             qtable: self.qtable,
+            u: self.u,
             marker: PhantomData,
         }
     }
@@ -67,11 +95,31 @@ impl<SpaceRef, SourceRef, QTableRef> ElementSourceAssemblerBuilder<(), SpaceRef,
     pub fn with_quadrature_table<QTable>(
         self,
         qtable: &QTable,
-    ) -> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, &QTable> {
+    ) -> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, &QTable, U> {
         ElementSourceAssemblerBuilder {
             space: self.space,
             source: self.source,
             qtable,
+            u: self.u,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<SpaceRef, SourceRef, QTableRef> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, QTableRef, ()> {
+    /// Sets the solution for a [solution-dependent source](SolutionDependentSourceFunction).
+    pub fn with_u<'a, T>(
+        self,
+        u: impl Into<DVectorView<'a, T>>,
+    ) -> ElementSourceAssemblerBuilder<(), SpaceRef, SourceRef, QTableRef, DVectorView<'a, T>>
+    where
+        T: Scalar,
+    {
+        ElementSourceAssemblerBuilder {
+            space: self.space,
+            source: self.source,
+            qtable: self.qtable,
+            u: u.into(),
             marker: PhantomData,
         }
     }
@@ -91,9 +139,33 @@ impl<'a, Space, Source, QTable> ElementSourceAssemblerBuilder<(), &'a Space, &'a
     }
 }
 
+impl<'a, T, Space, Source, QTable>
+    ElementSourceAssemblerBuilder<(), &'a Space, &'a Source, &'a QTable, DVectorView<'a, T>>
+where
+    T: Scalar,
+{
+    pub fn build(self) -> ElementSolutionDependentSourceAssembler<'a, T, Space, Source, QTable> {
+        ElementSolutionDependentSourceAssembler {
+            space: self.space,
+            qtable: self.qtable,
+            source: self.source,
+            u: self.u,
+        }
+    }
+}
+
 /// An element assembler for source functions.
 ///
-/// TODO: Docs
+/// Assembles the term $(f, v)$ for a [source function](SourceFunction) $f(x)$ into the element
+/// vectors, for example the load vector of the Poisson problem. The source function is evaluated
+/// with the data that the quadrature table provides for each quadrature point, so that spatially
+/// varying coefficients can be stored per quadrature point (see e.g.
+/// [`QuadratureParameterStorage`](crate::assembly::local::QuadratureParameterStorage)). The
+/// global load vector is obtained with a
+/// [`VectorAssembler`](crate::assembly::global::VectorAssembler).
+///
+/// Source functions that depend on the solution are assembled with
+/// [`ElementSolutionDependentSourceAssembler`].
 #[derive(Debug, Clone)]
 pub struct ElementSourceAssembler<'a, T, Space, Source, QTable> {
     space: &'a Space,
@@ -121,11 +193,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(output, element_index)
     }
 }
@@ -276,3 +348,197 @@ pub fn assemble_element_source_vector<T, Element, Source>(
         output.gemm(*weight * j.determinant().abs(), &f, &phi, T::one());
     }
 }
+
+/// An element assembler for [solution-dependent source functions](SolutionDependentSourceFunction).
+///
+/// Assembles the term $(f(x, u_h), v)$, where the solution $u_h$ is interpolated at each
+/// quadrature point. Constructed with [`ElementSourceAssemblerBuilder`] by providing the
+/// solution with [`with_u`](ElementSourceAssemblerBuilder::with_u).
+#[derive(Debug, Clone)]
+pub struct ElementSolutionDependentSourceAssembler<'a, T: Scalar, Space, Source, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    source: &'a Source,
+    u: DVectorView<'a, T>,
+}
+
+impl<'a, T, Space, Source, QTable> ElementConnectivityAssembler
+    for ElementSolutionDependentSourceAssembler<'a, T, Space, Source, QTable>
+where
+    T: Scalar,
+    Space: VolumetricFiniteElementSpace<T>,
+    Source: Operator<T, Space::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        Source::SolutionDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(SOLUTION_DEPENDENT_SOURCE_WORKSPACE);
+
+struct SolutionDependentSourceWorkspace<T, D, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    source_workspace: SourceTermWorkspace<T, D, Data>,
+    u_element: DVector<T>,
+}
+
+impl<T, D, Data> Default for SolutionDependentSourceWorkspace<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            source_workspace: SourceTermWorkspace::default(),
+            u_element: DVector::zeros(0),
+        }
+    }
+}
+
+impl<'a, T, Space, Source, QTable> ElementVectorAssembler<T>
+    for ElementSolutionDependentSourceAssembler<'a, T, Space, Source, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Source: SolutionDependentSourceFunction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Source::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Source::SolutionDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = Source::SolutionDim::dim();
+        if self.u.len() != s * self.space.num_nodes() {
+            return Err(eyre!(
+                "Dimension of solution vector does not match space and solution dimension"
+            ));
+        }
+        with_thread_local_workspace(
+            &SOLUTION_DEPENDENT_SOURCE_WORKSPACE,
+            |ws: &mut SolutionDependentSourceWorkspace<T, Space::ReferenceDim, Source::Parameters>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                let n = element.num_nodes();
+                let source_ws = &mut ws.source_workspace;
+                source_ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+                source_ws
+                    .basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                source_ws
+                    .quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+                ws.u_element.resize_vertically_mut(s * n, T::zero());
+                gather_global_to_local(self.u, &mut ws.u_element, source_ws.basis_buffer.element_nodes(), s);
+
+                assemble_element_solution_dependent_source_vector(
+                    output,
+                    &element,
+                    self.source,
+                    DVectorView::from(&ws.u_element),
+                    source_ws.quadrature_buffer.weights(),
+                    source_ws.quadrature_buffer.points(),
+                    source_ws.quadrature_buffer.data(),
+                    source_ws.basis_buffer.element_basis_values_mut(),
+                );
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Assemble the local source term vector associated with a particular finite element and
+/// [solution-dependent source](SolutionDependentSourceFunction).
+///
+/// This is the counterpart of [`assemble_element_source_vector`] for source functions
+/// $f(x, u)$. The local solution vector `u_element` holds the `s` components of the solution
+/// for each of the `n` nodes of the element.
+///
+/// **This is a low-level routine**. Most users will want to use
+/// [`ElementSolutionDependentSourceAssembler`] instead.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`assemble_element_source_vector`], and if the length of
+/// `u_element` is not equal to `n * s`.
+#[allow(clippy::too_many_arguments)]
+pub fn assemble_element_solution_dependent_source_vector<T, Element, Source>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    source: &Source,
+    u_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    quadrature_data: &[Source::Parameters],
+    basis_values_buffer: &mut [T],
+) where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    Source: SolutionDependentSourceFunction<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Source::SolutionDim>,
+{
+    assert_eq!(
+        quadrature_weights.len(),
+        quadrature_points.len(),
+        "Number of quadrature weights must be equal to number of points."
+    );
+    assert_eq!(
+        quadrature_points.len(),
+        quadrature_data.len(),
+        "Number of quadrature points must be equal to length of data"
+    );
+    assert_eq!(
+        basis_values_buffer.len(),
+        element.num_nodes(),
+        "Number of basis functions in buffer must be equal to nodes in element."
+    );
+
+    let n = element.num_nodes();
+    let s = Source::SolutionDim::dim();
+    assert_eq!(
+        output.len(),
+        n * s,
+        "Length of output vector must be consistent with number of nodes and solution dim"
+    );
+    assert_eq!(
+        u_element.len(),
+        n * s,
+        "Length of local solution vector must be consistent with number of nodes and solution dim"
+    );
+    let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), Source::SolutionDim::name(), Dyn(n));
+    let u_element = MatrixView::from_slice_generic(u_element.as_slice(), Source::SolutionDim::name(), Dyn(n));
+
+    output.fill(T::zero());
+
+    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
+    for (weight, point, data) in quadrature_iter {
+        element.populate_basis(&mut *basis_values_buffer, point);
+
+        let x = element.map_reference_coords(point);
+        let j = element.reference_jacobian(point);
+        let phi = MatrixView::from_slice_generic(&*basis_values_buffer, U1::name(), Dyn(n));
+        // u_h(x) = sum_I u_I phi_I
+        let u = u_element * phi.transpose();
+        let f = source.evaluate_with_u(&x, &u, data);
+        output.gemm(*weight * j.determinant().abs(), &f, &phi, T::one());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::allocators::TriDimAllocator;
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::elliptic::compute_volume_u_grad;
+use crate::assembly::local::{QuadratureParameterStorage, QuadratureTable};
+use crate::assembly::operators::StatefulOperator;
+use crate::index::NodeIndex;
+use crate::nalgebra::{
+    DVector, DVectorView, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint,
+};
+use crate::space::VolumetricFiniteElementSpace;
+use crate::Real;
+use eyre::eyre;
+
+/// Commits the internal variables of a [stateful operator](StatefulOperator) for the given
+/// solution.
+///
+/// This is the commit pass for path-dependent materials: after a Newton solve for a load (or
+/// time) step has converged to the solution `u`, the gradient of `u` is evaluated at every
+/// quadrature point, and [`StatefulOperator::commit_state`] is invoked to update the
+/// internal variables stored in `state`. During the Newton iterations, the state is only read,
+/// typically through [`QuadratureParameterStorage::with_quadrature_table`], so that the
+/// iterations do not depend on the history of trial solutions.
+///
+/// The quadrature points are taken from `qtable`, which must have the same number of points for
+/// each element as the storage.
+///
+/// # Errors
+///
+/// Returns an error if the storage is not consistent with the space and the quadrature table,
+/// or if a singular element Jacobian is encountered.
+pub fn commit_operator_state<'a, T, Space, Op, QTable>(
+    space: &Space,
+    op: &Op,
+    u: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+    state: &mut QuadratureParameterStorage<Op::Parameters>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: StatefulOperator<T, Space::ReferenceDim>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, Op::SolutionDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u = u.into();
+    let s = Op::SolutionDim::dim();
+    if state.num_elements() != space.num_elements() {
+        return Err(eyre!(
+            "Number of elements in state storage ({}) does not match number of elements in space ({})",
+            state.num_elements(),
+            space.num_elements()
+        ));
+    }
+    if u.len() != s * space.num_nodes() {
+        return Err(eyre!(
+            "Dimension of solution vector does not match space and solution dimension"
+        ));
+    }
+
+    let mut nodes = Vec::new();
+    let mut u_element = DVector::zeros(0);
+    let mut phi_grad_ref = OMatrix::<T, Space::ReferenceDim, Dyn>::zeros(0);
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    for element_index in 0..space.num_elements() {
+        let n = space.element_node_count(element_index.into());
+        nodes.resize(n, 0);
+        space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+        u_element.resize_vertically_mut(s * n, T::zero());
+        gather_global_to_local(u, &mut u_element, &nodes, s);
+        phi_grad_ref.resize_horizontally_mut(n, T::zero());
+
+        let num_points = qtable.element_quadrature_size(element_index);
+        let element_state = state.element_parameters_mut(element_index);
+        if element_state.len() != num_points {
+            return Err(eyre!(
+                "Element {} has {} quadrature points, but {} state entries",
+                element_index,
+                num_points,
+                element_state.len()
+            ));
+        }
+        points.resize(num_points, OPoint::origin());
+        weights.resize(num_points, T::zero());
+        qtable.populate_element_quadrature(element_index, &mut points, &mut weights);
+
+        for (xi, parameters) in points.iter().zip(element_state) {
+            let j_inv_t = space
+                .element_reference_jacobian(element_index, xi)
+                .try_inverse()
+                .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?
+                .transpose();
+            space.populate_element_gradients(element_index, MatrixViewMut::from(&mut phi_grad_ref), xi);
+            let u_local = MatrixView::from_slice_generic(u_element.as_slice(), Op::SolutionDim::name(), Dyn(n));
+            let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad_ref, u_local);
+            op.commit_state(&u_grad, parameters);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,458 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{Density, ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, FiniteElementSpace};
+use crate::util::clone_upper_to_lower;
+use crate::{Real, SmallDim};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use nalgebra::Scalar;
+
+/// Computes the surface measure $\sqrt{\det (J^T J)}$ associated with the reference Jacobian $J$
+/// of a surface element.
+///
+/// The surface measure relates the area (or length) element on the surface to the area element
+/// of the reference element, i.e. $\mathrm{d}s = \sqrt{\det (J^T J)} \\, \mathrm{d}\xi$. For
+/// volumetric elements it coincides with $|\det J|$.
+pub fn surface_measure<T, GeometryDim, ReferenceDim>(jacobian: &OMatrix<T, GeometryDim, ReferenceDim>) -> T
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    ReferenceDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    (jacobian.transpose() * jacobian).determinant().sqrt()
+}
+
+/// Computes the matrix $J (J^T J)^{-1}$ that maps reference gradients to tangential gradients,
+/// given the reference Jacobian $J$ of a surface element.
+///
+/// The tangential gradient of a function $\phi$ defined on the surface is given by
+/// $\nabla_{\Gamma} \phi = J (J^T J)^{-1} \nabla_{\xi} \phi$, where $\nabla_{\xi} \phi$ is the
+/// gradient with respect to reference coordinates.
+///
+/// # Errors
+///
+/// Returns an error if the element is degenerate, i.e. if $J^T J$ is singular.
+pub fn tangential_gradient_map<T, GeometryDim, ReferenceDim>(
+    jacobian: &OMatrix<T, GeometryDim, ReferenceDim>,
+) -> eyre::Result<OMatrix<T, GeometryDim, ReferenceDim>>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    ReferenceDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    let metric_inv = (jacobian.transpose() * jacobian)
+        .try_inverse()
+        .ok_or_else(|| eyre!("Degenerate surface element encountered"))?;
+    Ok(jacobian * metric_inv)
+}
+
+/// Computes the tangential gradients of the basis functions of a surface element at the given
+/// reference point.
+///
+/// The tangential gradient of basis function $I$ is stored in column $I$ of the output matrix.
+/// The reference gradients are computed by the element and stored in the provided buffer.
+///
+/// # Errors
+///
+/// Returns an error if the element is degenerate at the given point.
+///
+/// # Panics
+///
+/// Panics if the output matrix or the reference gradients buffer do not have one column per node
+/// in the element.
+pub fn compute_tangential_gradients<T, Element>(
+    mut output: MatrixViewMut<T, Element::GeometryDim, Dyn>,
+    element: &Element,
+    reference_coords: &OPoint<T, Element::ReferenceDim>,
+    mut reference_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let n = element.num_nodes();
+    assert_eq!(output.ncols(), n, "Output must have one column per node");
+    assert_eq!(
+        reference_gradients_buffer.ncols(),
+        n,
+        "Buffer must have one column per node"
+    );
+    element.populate_basis_gradients(MatrixViewMut::from(&mut reference_gradients_buffer), reference_coords);
+    let gradient_map = tangential_gradient_map(&element.reference_jacobian(reference_coords))?;
+    output.gemm(T::one(), &gradient_map, &reference_gradients_buffer, T::zero());
+    Ok(())
+}
+
+/// An element assembler for the Laplace–Beltrami operator on surfaces.
+///
+/// Assembles the element stiffness matrices associated with the weak form of the surface
+/// Laplacian $- \Delta_{\Gamma} u$, for use in PDEs posed on surfaces, such as surface diffusion
+/// or heat flow on a shell. See [`assemble_element_laplace_beltrami_matrix`] for the definition
+/// of the element matrix.
+///
+/// The finite element space must be a surface space, such as a mesh of
+/// [`Tri3d3Element`](crate::element::Tri3d3Element) or [`Tri6d3Element`](crate::element::Tri6d3Element).
+/// Since the quadrature is defined on the reference domain of the surface elements, the quadrature
+/// table is defined over the reference dimension of the space. Only weights and points of the
+/// table are used.
+///
+/// Together with [`ElementSurfaceMassAssembler`], this provides the building blocks for
+/// discretizing surface PDEs.
+#[derive(Debug, Clone)]
+pub struct ElementLaplaceBeltramiAssembler<'a, Space, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    solution_dim: usize,
+}
+
+impl<'a> ElementLaplaceBeltramiAssembler<'a, (), ()> {
+    pub fn with_solution_dim(solution_dim: usize) -> Self {
+        Self {
+            space: &(),
+            qtable: &(),
+            solution_dim,
+        }
+    }
+}
+
+impl<'a, QTable> ElementLaplaceBeltramiAssembler<'a, (), QTable> {
+    pub fn with_space<Space>(self, space: &'a Space) -> ElementLaplaceBeltramiAssembler<'a, Space, QTable> {
+        ElementLaplaceBeltramiAssembler {
+            space,
+            qtable: self.qtable,
+            solution_dim: self.solution_dim,
+        }
+    }
+}
+
+impl<'a, Space> ElementLaplaceBeltramiAssembler<'a, Space, ()> {
+    pub fn with_quadrature_table<QTable>(
+        self,
+        table: &'a QTable,
+    ) -> ElementLaplaceBeltramiAssembler<'a, Space, QTable> {
+        ElementLaplaceBeltramiAssembler {
+            space: self.space,
+            qtable: table,
+            solution_dim: self.solution_dim,
+        }
+    }
+}
+
+/// An element assembler for mass matrices on surfaces.
+///
+/// This is the surface counterpart of [`ElementMassAssembler`](crate::assembly::local::ElementMassAssembler),
+/// which only supports volumetric spaces. The quadrature table is defined over the reference
+/// dimension of the space and must provide a [`Density`] for each quadrature point.
+/// See [`assemble_element_surface_mass_matrix`] for the definition of the element matrix.
+#[derive(Debug, Clone)]
+pub struct ElementSurfaceMassAssembler<'a, Space, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    solution_dim: usize,
+}
+
+impl<'a> ElementSurfaceMassAssembler<'a, (), ()> {
+    pub fn with_solution_dim(solution_dim: usize) -> Self {
+        Self {
+            space: &(),
+            qtable: &(),
+            solution_dim,
+        }
+    }
+}
+
+impl<'a, QTable> ElementSurfaceMassAssembler<'a, (), QTable> {
+    pub fn with_space<Space>(self, space: &'a Space) -> ElementSurfaceMassAssembler<'a, Space, QTable> {
+        ElementSurfaceMassAssembler {
+            space,
+            qtable: self.qtable,
+            solution_dim: self.solution_dim,
+        }
+    }
+}
+
+impl<'a, Space> ElementSurfaceMassAssembler<'a, Space, ()> {
+    pub fn with_quadrature_table<QTable>(self, table: &'a QTable) -> ElementSurfaceMassAssembler<'a, Space, QTable> {
+        ElementSurfaceMassAssembler {
+            space: self.space,
+            qtable: table,
+            solution_dim: self.solution_dim,
+        }
+    }
+}
+
+macro_rules! impl_surface_connectivity_assembler {
+    ($assembler:ident) => {
+        impl<'a, Space, QTable> ElementConnectivityAssembler for $assembler<'a, Space, QTable>
+        where
+            Space: FiniteElementConnectivity,
+        {
+            fn solution_dim(&self) -> usize {
+                self.solution_dim
+            }
+
+            fn num_elements(&self) -> usize {
+                self.space.num_elements()
+            }
+
+            fn num_nodes(&self) -> usize {
+                self.space.num_nodes()
+            }
+
+            fn element_node_count(&self, element_index: ElementIndex) -> usize {
+                self.space.element_node_count(element_index)
+            }
+
+            fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+                self.space.populate_element_nodes(output, element_index)
+            }
+        }
+    };
+}
+
+impl_surface_connectivity_assembler!(ElementLaplaceBeltramiAssembler);
+impl_surface_connectivity_assembler!(ElementSurfaceMassAssembler);
+
+define_thread_local_workspace!(WORKSPACE);
+
+#[derive(Debug)]
+struct SurfaceAssemblerWorkspace<T: Scalar, D: DimName, Data = ()>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T: Real, D: DimName, Data> Default for SurfaceAssemblerWorkspace<T, D, Data>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+        }
+    }
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for ElementLaplaceBeltramiAssembler<'a, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut SurfaceAssemblerWorkspace<T, Space::ReferenceDim>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_weights_and_points_from_table(element_index, self.qtable);
+
+                assemble_element_laplace_beltrami_matrix(
+                    output,
+                    &element,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    ws.basis_buffer.element_gradients_mut(),
+                )
+            },
+        )
+    }
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for ElementSurfaceMassAssembler<'a, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Density<T>>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut SurfaceAssemblerWorkspace<T, Space::ReferenceDim, Density<T>>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                assemble_element_surface_mass_matrix(
+                    output,
+                    &element,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    Density::as_inner_slice(ws.quadrature_buffer.data()),
+                    self.solution_dim,
+                    ws.basis_buffer.element_basis_values_mut(),
+                )
+            },
+        )
+    }
+}
+
+/// Assembles the element Laplace–Beltrami matrix of a surface element using the provided quadrature.
+///
+/// Given a surface finite element with domain $K \subset \Gamma$ and $N$ nodes, the element
+/// Laplace–Beltrami matrix is the matrix $A^K \in \mathbb{R}^{s N\times s N}$ whose $s \times s$
+/// blocks $A_{IJ}$ are defined by
+///
+/// $$
+/// A^K_{IJ} := I^s \int_{K} \nabla_{\Gamma} \phi_I \cdot \nabla_{\Gamma} \phi_J \\, \mathrm{d} s
+///     \qquad I, J = 1, \dots, N,
+/// $$
+///
+/// where $s$ is the dimension of the solution variable, which is inferred from the dimensions
+/// of the output matrix, $I^s$ is the $s \times s$ identity matrix and $\nabla_{\Gamma} \phi_I$
+/// is the tangential gradient of the basis function associated with node $I$.
+///
+/// Since $\nabla_{\Gamma} \phi_I \cdot \nabla_{\Gamma} \phi_J = \nabla_{\xi} \phi_I^T (J^T J)^{-1} \nabla_{\xi} \phi_J$,
+/// the tangential gradients are never formed explicitly. The computation requires a buffer for
+/// the reference gradients of the basis functions, with one column for each node in the element.
+///
+/// # Errors
+///
+/// Returns an error if the element is degenerate at any quadrature point.
+///
+/// # Panics
+///
+/// Panics if the quadrature arrays do not have the same lengths.
+///
+/// Panics if the number of columns in the gradients buffer does not match the number of nodes
+/// in the element, or if the output matrix is not a square matrix whose dimension is a multiple
+/// of the number of nodes.
+#[allow(non_snake_case)]
+pub fn assemble_element_laplace_beltrami_matrix<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    let n = element.num_nodes();
+    assert_eq!(basis_gradients_buffer.ncols(), n);
+    assert_eq!(output.nrows(), output.ncols(), "Output matrix must be square");
+    assert!(
+        n > 0 && output.nrows().is_multiple_of(n),
+        "Output matrix dimension must be a multiple of the number of nodes"
+    );
+    let s = output.nrows() / n;
+
+    output.fill(T::zero());
+
+    for (&weight, point) in izip!(quadrature_weights, quadrature_points) {
+        let j = element.reference_jacobian(point);
+        let metric = j.transpose() * &j;
+        let measure = metric.determinant().sqrt();
+        let metric_inv = metric
+            .try_inverse()
+            .ok_or_else(|| eyre!("Degenerate surface element encountered"))?;
+
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+
+        let scale = weight * measure;
+        for I in 0..n {
+            let g_I = &metric_inv * basis_gradients_buffer.column(I);
+            // Fill only upper triangle, then copy over lower half at the end
+            for J in I..n {
+                let a_IJ_contrib = scale * g_I.dot(&basis_gradients_buffer.column(J));
+                let mut A_IJ = output.view_mut((s * I, s * J), (s, s));
+                for i in 0..s {
+                    A_IJ[(i, i)] += a_IJ_contrib;
+                }
+            }
+        }
+    }
+
+    clone_upper_to_lower(&mut output);
+
+    Ok(())
+}
+
+/// Assembles the element mass matrix of a surface element using the provided quadrature.
+///
+/// The element mass matrix is defined as for volumetric elements (see
+/// [`assemble_element_mass_matrix`](crate::assembly::local::assemble_element_mass_matrix)), except that
+/// integration takes place over the surface element with respect to the surface measure, i.e.
+///
+/// $$
+/// M^K_{IJ} := I^s \int_{K} \rho(x) \\, \phi_I(x) \\, \phi_J(x) \\, \mathrm{d} s \qquad I, J = 1, \dots, N.
+/// $$
+///
+/// # Panics
+///
+/// Panics if the quadrature arrays do not have the same lengths.
+///
+/// Panics if the number of elements in the basis value buffer is not equal to the number of nodes
+/// in the element.
+#[allow(non_snake_case)]
+pub fn assemble_element_surface_mass_matrix<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    quadrature_density: &[T],
+    solution_dim: usize,
+    basis_values_buffer: &mut [T],
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    assert_eq!(quadrature_weights.len(), quadrature_points.len());
+    assert_eq!(quadrature_points.len(), quadrature_density.len());
+    assert_eq!(basis_values_buffer.len(), element.num_nodes());
+
+    let s = solution_dim;
+    let n = element.num_nodes();
+    assert_eq!(output.nrows(), s * n, "Output matrix dimension mismatch");
+    assert_eq!(output.ncols(), s * n, "Output matrix dimension mismatch");
+
+    output.fill(T::zero());
+
+    let phi = basis_values_buffer;
+    for (&weight, point, &density) in izip!(quadrature_weights, quadrature_points, quadrature_density) {
+        let measure = surface_measure(&element.reference_jacobian(point));
+        element.populate_basis(phi, point);
+
+        let scale = weight * measure * density;
+        for I in 0..n {
+            // Fill only upper triangle, then copy over lower half at the end
+            for J in I..n {
+                let m_IJ_contrib = scale * phi[I] * phi[J];
+                let mut M_IJ = output.view_mut((s * I, s * J), (s, s));
+                for i in 0..s {
+                    M_IJ[(i, i)] += m_IJ_contrib;
+                }
+            }
+        }
+    }
+
+    clone_upper_to_lower(&mut output);
+
+    Ok(())
+}
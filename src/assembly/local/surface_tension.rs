@@ -0,0 +1,366 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{
+    surface_measure, tangential_gradient_map, ElementConnectivityAssembler, ElementVectorAssembler, QuadratureTable,
+};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{DVector, DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixViewMut, OPoint, Scalar};
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
+use itertools::izip;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// A wrapper type for a number that represents a *surface tension* coefficient.
+///
+/// This is used as a parameter for the assembly of surface tension forces, see
+/// [`ElementSurfaceTensionAssembler`].
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct SurfaceTension<T>(pub T);
+
+impl<T: Real> Default for SurfaceTension<T> {
+    fn default() -> Self {
+        SurfaceTension(T::zero())
+    }
+}
+
+pub struct ElementSurfaceTensionAssemblerBuilder<T, SpaceRef, QTableRef> {
+    space: SpaceRef,
+    qtable: QTableRef,
+    marker: PhantomData<T>,
+}
+
+impl ElementSurfaceTensionAssemblerBuilder<(), (), ()> {
+    pub fn new() -> Self {
+        Self {
+            space: (),
+            qtable: (),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Default for ElementSurfaceTensionAssemblerBuilder<(), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SpaceRef, QTableRef> ElementSurfaceTensionAssemblerBuilder<(), SpaceRef, QTableRef> {
+    /// Sets the surface finite element space on which the surface tension acts.
+    pub fn with_finite_element_space<Space>(
+        self,
+        space: &Space,
+    ) -> ElementSurfaceTensionAssemblerBuilder<(), &Space, QTableRef> {
+        ElementSurfaceTensionAssemblerBuilder {
+            space,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_quadrature_table<QTable>(
+        self,
+        qtable: &QTable,
+    ) -> ElementSurfaceTensionAssemblerBuilder<(), SpaceRef, &QTable> {
+        ElementSurfaceTensionAssemblerBuilder {
+            space: self.space,
+            qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Space, QTable> ElementSurfaceTensionAssemblerBuilder<(), &'a Space, &'a QTable> {
+    pub fn build<T>(self) -> ElementSurfaceTensionAssembler<'a, T, Space, QTable> {
+        ElementSurfaceTensionAssembler {
+            space: self.space,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// An element assembler for surface tension forces.
+///
+/// A surface $\Gamma$ with surface tension coefficient $\gamma$ has the surface energy
+/// $E = \int_{\Gamma} \gamma \\, \mathrm{d}s$. The associated force acting on the surface is
+/// the negative shape derivative of the energy, whose weak form reads
+/// <div>$$
+/// (f, v)_{\Gamma} = - \int_{\Gamma} \gamma \\, \nabla_{\Gamma} x : \nabla_{\Gamma} v \\, \mathrm{d}s
+///     = \int_{\Gamma} \gamma \\, \Delta_{\Gamma} x \cdot v \\, \mathrm{d}s,
+/// $$</div>
+/// where $x$ is the position on the surface and $\Delta_{\Gamma}$ is the Laplace–Beltrami operator.
+/// Since $\Delta_{\Gamma} x = \vec H$ is the mean curvature vector, the force is the
+/// familiar capillary force $\gamma \vec H$ that drives the surface towards smaller area.
+///
+/// The assembler computes the nodal forces
+/// <div>$$
+/// f_I = - \int_{\Gamma} \gamma \\, \nabla_{\Gamma} \phi_I \\, \mathrm{d}s,
+/// $$</div>
+/// which only depend on the geometry of the surface, and the solution dimension is therefore
+/// always equal to the geometric dimension. The surface tension coefficient is given by the
+/// [`SurfaceTension`] data associated with each quadrature point. For piecewise linear surfaces,
+/// $f$ is exactly the negative gradient of the discrete surface energy with respect to the
+/// vertex positions.
+///
+/// The finite element space must be a surface space, such as a mesh of segments in 2D or a
+/// mesh of triangles in 3D.
+#[derive(Debug, Clone)]
+pub struct ElementSurfaceTensionAssembler<'a, T, Space, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T, Space, QTable> ElementConnectivityAssembler for ElementSurfaceTensionAssembler<'a, T, Space, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        Space::GeometryDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(SURFACE_TENSION_WORKSPACE);
+
+struct SurfaceTensionWorkspace<T, D, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, D, Data> Default for SurfaceTensionWorkspace<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: QuadratureBuffer::default(),
+            basis_buffer: BasisFunctionBuffer::default(),
+        }
+    }
+}
+
+impl<'a, T, Space, QTable> ElementVectorAssembler<T> for ElementSurfaceTensionAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = SurfaceTension<T>>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &SURFACE_TENSION_WORKSPACE,
+            |ws: &mut SurfaceTensionWorkspace<T, Space::ReferenceDim, SurfaceTension<T>>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                assemble_element_surface_tension_vector(
+                    output,
+                    &element,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    ws.quadrature_buffer.data(),
+                    ws.basis_buffer.element_gradients_mut(),
+                )
+            },
+        )
+    }
+}
+
+/// Assemble the local surface tension force vector associated with a surface finite element.
+///
+/// See [`ElementSurfaceTensionAssembler`] for the definition of the force vector.
+///
+/// A working array for storing the reference gradients of the basis functions must be provided.
+///
+/// **This is a low-level routine**. Most users will not need to call this function directly,
+/// and are instead more likely to use [`ElementSurfaceTensionAssembler`].
+///
+/// # Errors
+///
+/// Returns an error if the element is degenerate at any quadrature point.
+///
+/// # Panics
+///
+/// The size of the output vector must be equal to `n * d`, where `n` is the number of
+/// nodes in the element and `d` is the geometric dimension.
+///
+/// Panics if the quadrature weights, points and data arrays do not have the same length.
+///
+/// The basis gradients buffer must have `n` columns.
+pub fn assemble_element_surface_tension_vector<T, Element>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    quadrature_data: &[SurfaceTension<T>],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    assert_eq!(
+        quadrature_weights.len(),
+        quadrature_points.len(),
+        "Number of quadrature weights must be equal to number of points."
+    );
+    assert_eq!(
+        quadrature_points.len(),
+        quadrature_data.len(),
+        "Number of quadrature points must be equal to length of data"
+    );
+    let n = element.num_nodes();
+    assert_eq!(
+        basis_gradients_buffer.ncols(),
+        n,
+        "Number of basis gradients in buffer must be equal to nodes in element."
+    );
+    assert_eq!(
+        output.len(),
+        n * Element::GeometryDim::dim(),
+        "Length of output vector must be consistent with number of nodes and geometry dim"
+    );
+    let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), Element::GeometryDim::name(), Dyn(n));
+    output.fill(T::zero());
+
+    for (&weight, point, &SurfaceTension(gamma)) in izip!(quadrature_weights, quadrature_points, quadrature_data) {
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+        let j = element.reference_jacobian(point);
+        let measure = surface_measure(&j);
+        let gradient_map = tangential_gradient_map(&j)?;
+        // The tangential gradients of all basis functions are given by J (J^T J)^{-1} grad_ref phi
+        output.gemm(
+            -weight * gamma * measure,
+            &gradient_map,
+            &basis_gradients_buffer,
+            T::one(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Computes the nodal mean curvature vectors $\vec H = \Delta_{\Gamma} x$ of a surface.
+///
+/// The mean curvature vector is computed from the weak form of the Laplace–Beltrami operator
+/// applied to the position $x$ on the surface, with a row-sum lumped mass matrix. That is, the
+/// mean curvature vector at node $I$ is given by
+/// <div>$$
+/// \vec H_I = - \frac{\int_{\Gamma} \nabla_{\Gamma} \phi_I \\, \mathrm{d}s}{\int_{\Gamma} \phi_I \\, \mathrm{d}s}.
+/// $$</div>
+/// For a surface with outward unit normal $n$, we have $\vec H = - \kappa n$, where $\kappa$ is
+/// the sum of the principal curvatures. For example, $\kappa = 2 / R$ for a sphere of radius $R$.
+///
+/// The vectors are returned as a single vector with $d$ entries per node. Only the weights and
+/// points of the quadrature table are used.
+///
+/// Since row-sum lumping is used, the method is intended for linear surface elements,
+/// such as [`Tri3d3Element`](crate::element::Tri3d3Element).
+///
+/// # Errors
+///
+/// Returns an error if any element is degenerate, or if the lumped mass of any node is not positive,
+/// which for example is the case for the vertices of quadratic triangles.
+pub fn compute_mean_curvature_vectors<T, Space, QTable>(space: &Space, qtable: &QTable) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let d = Space::GeometryDim::dim();
+    let mut curvature = DVector::zeros(d * space.num_nodes());
+    let mut lumped_mass = DVector::zeros(space.num_nodes());
+
+    let mut quadrature_buffer = QuadratureBuffer::<T, Space::ReferenceDim, QTable::Data>::default();
+    let mut basis_buffer = BasisFunctionBuffer::default();
+    let mut unit_tension = Vec::new();
+    let mut element_force = DVector::zeros(0);
+    let mut element_mass = Vec::new();
+
+    for element_index in 0..space.num_elements() {
+        let element = ElementInSpace::from_space_and_element_index(space, element_index);
+        let n = element.num_nodes();
+        basis_buffer.resize(n, Space::ReferenceDim::dim());
+        basis_buffer.populate_element_nodes_from_space(element_index, space);
+        quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+        let weights = quadrature_buffer.weights();
+        let points = quadrature_buffer.points();
+
+        unit_tension.clear();
+        unit_tension.resize(weights.len(), SurfaceTension(T::one()));
+        element_force.resize_vertically_mut(d * n, T::zero());
+        element_mass.clear();
+        element_mass.resize(n, T::zero());
+
+        let (basis_values, basis_gradients) = basis_buffer.element_values_gradients_mut();
+        assemble_element_surface_tension_vector(
+            DVectorViewMut::from(&mut element_force),
+            &element,
+            weights,
+            points,
+            &unit_tension,
+            basis_gradients,
+        )?;
+        for (&weight, point) in izip!(weights, points) {
+            element.populate_basis(&mut *basis_values, point);
+            let measure = surface_measure(&element.reference_jacobian(point));
+            for (m, &phi) in element_mass.iter_mut().zip(basis_values.iter()) {
+                *m += weight * measure * phi;
+            }
+        }
+
+        for (local_index, &node) in basis_buffer.element_nodes().iter().enumerate() {
+            let mut h = curvature.rows_mut(d * node, d);
+            h += element_force.rows(d * local_index, d);
+            lumped_mass[node] += element_mass[local_index];
+        }
+    }
+
+    for (node, &m) in lumped_mass.iter().enumerate() {
+        if m <= T::zero() {
+            return Err(eyre!("Lumped mass of node {node} is not positive"));
+        }
+        let mut h = curvature.rows_mut(d * node, d);
+        h /= m;
+    }
+
+    Ok(curvature)
+}
@@ -0,0 +1,341 @@
+use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{ElementConnectivityAssembler, ElementVectorAssembler, QuadratureTable};
+use crate::assembly::operators::Operator;
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::{
+    DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint, OVector, Scalar, U1,
+};
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use itertools::izip;
+use std::marker::PhantomData;
+
+/// A traction (or, more generally, Neumann boundary) function $t(x, n)$ defined on a surface.
+///
+/// The traction may depend on the position $x$ on the surface, the outward unit normal $n$ at
+/// $x$ and the parameters associated with each quadrature point. For example, a pressure
+/// load $p$ corresponds to the traction $t(x, n) = - p n$.
+pub trait TractionFunction<T, GeometryDim>: Operator<T, GeometryDim>
+where
+    T: Scalar,
+    GeometryDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Self::SolutionDim>,
+{
+    fn evaluate(
+        &self,
+        coords: &OPoint<T, GeometryDim>,
+        normal: &OVector<T, GeometryDim>,
+        data: &Self::Parameters,
+    ) -> OVector<T, Self::SolutionDim>;
+}
+
+pub struct ElementTractionAssemblerBuilder<T, SpaceRef, TractionRef, QTableRef> {
+    space: SpaceRef,
+    traction: TractionRef,
+    qtable: QTableRef,
+    marker: PhantomData<T>,
+}
+
+impl ElementTractionAssemblerBuilder<(), (), (), ()> {
+    pub fn new() -> Self {
+        Self {
+            space: (),
+            traction: (),
+            qtable: (),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Default for ElementTractionAssemblerBuilder<(), (), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SpaceRef, TractionRef, QTableRef> ElementTractionAssemblerBuilder<(), SpaceRef, TractionRef, QTableRef> {
+    /// Sets the surface finite element space over which the traction is integrated.
+    pub fn with_finite_element_space<Space>(
+        self,
+        space: &Space,
+    ) -> ElementTractionAssemblerBuilder<(), &Space, TractionRef, QTableRef> {
+        ElementTractionAssemblerBuilder {
+            space,
+            traction: self.traction,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_traction<Traction>(
+        self,
+        traction: &Traction,
+    ) -> ElementTractionAssemblerBuilder<(), SpaceRef, &Traction, QTableRef> {
+        ElementTractionAssemblerBuilder {
+            space: self.space,
+            traction,
+            qtable: self.qtable,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_quadrature_table<QTable>(
+        self,
+        qtable: &QTable,
+    ) -> ElementTractionAssemblerBuilder<(), SpaceRef, TractionRef, &QTable> {
+        ElementTractionAssemblerBuilder {
+            space: self.space,
+            traction: self.traction,
+            qtable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Space, Traction, QTable> ElementTractionAssemblerBuilder<(), &'a Space, &'a Traction, &'a QTable> {
+    pub fn build<T>(self) -> ElementTractionAssembler<'a, T, Space, Traction, QTable> {
+        ElementTractionAssembler {
+            space: self.space,
+            qtable: self.qtable,
+            traction: self.traction,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// An element assembler for boundary tractions.
+///
+/// The assembler integrates the term $(t, v)_{\Gamma}$ that arises in the weak form of
+/// problems with Neumann (traction) boundary conditions on (part of) the boundary $\Gamma$,
+/// where $t$ is a [`TractionFunction`]. The finite element space must be a surface space,
+/// i.e. its reference dimension must be one less than its geometric dimension, such as a
+/// mesh of segments in 2D or a mesh of triangles in 3D. The outward normal of each surface
+/// element is determined by the orientation of the element, and is consistent with the faces
+/// extracted from volumetric meshes, e.g. with [`Mesh::find_boundary_faces`](crate::mesh::Mesh::find_boundary_faces).
+///
+/// The resulting element vectors are associated with the nodes of the surface space. If the
+/// surface space uses the same node indices as the volumetric space, for example because its
+/// connectivity was built directly from the boundary faces of the volumetric mesh, the
+/// assembler can be used directly with a global vector assembler to assemble contributions to
+/// the volumetric load vector. Otherwise, surface node indices can be mapped to volumetric node
+/// indices with [`map_element_nodes`](ElementConnectivityAssembler::map_element_nodes), e.g. with
+/// the vertex mapping provided by [`Mesh::extract_boundary_mesh`](crate::mesh::Mesh::extract_boundary_mesh).
+#[derive(Debug, Clone)]
+pub struct ElementTractionAssembler<'a, T, Space, Traction, QTable> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    traction: &'a Traction,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T, Space, Traction, QTable> ElementConnectivityAssembler
+    for ElementTractionAssembler<'a, T, Space, Traction, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    Traction: Operator<T, Space::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        Traction::SolutionDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(TRACTION_WORKSPACE);
+
+struct TractionWorkspace<T, D, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, Data>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, D, Data> Default for TractionWorkspace<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: QuadratureBuffer::default(),
+            basis_buffer: BasisFunctionBuffer::default(),
+        }
+    }
+}
+
+impl<'a, T, Space, Traction, QTable> ElementVectorAssembler<T>
+    for ElementTractionAssembler<'a, T, Space, Traction, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    Traction: TractionFunction<T, Space::GeometryDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Traction::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Traction::SolutionDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &TRACTION_WORKSPACE,
+            |ws: &mut TractionWorkspace<T, Space::ReferenceDim, Traction::Parameters>| {
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                ws.basis_buffer
+                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
+                ws.basis_buffer
+                    .populate_element_nodes_from_space(element_index, self.space);
+                ws.quadrature_buffer
+                    .populate_element_quadrature_from_table(element_index, self.qtable);
+
+                assemble_element_traction_vector(
+                    output,
+                    &element,
+                    self.traction,
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    ws.quadrature_buffer.data(),
+                    ws.basis_buffer.element_basis_values_mut(),
+                );
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Computes the surface measure $|\det J|$ and the unit normal of a surface element from its
+/// reference Jacobian $J$.
+///
+/// For a segment in 2D with tangent $J = (t_1, t_2)^T$, the normal is $(t_2, -t_1) / |t|$,
+/// and for a surface in 3D with Jacobian columns $j_1, j_2$ the normal is
+/// $j_1 \times j_2 / |j_1 \times j_2|$, where $|\det J|$ denotes the norm of the
+/// un-normalized normal in both cases.
+///
+/// # Panics
+///
+/// Panics unless the element is a segment in 2D or a surface in 3D.
+pub fn surface_measure_and_normal<T, GeometryDim, ReferenceDim>(
+    jacobian: &OMatrix<T, GeometryDim, ReferenceDim>,
+) -> (T, OVector<T, GeometryDim>)
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    ReferenceDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    let j = jacobian;
+    let mut normal = OVector::<T, GeometryDim>::zeros();
+    match (GeometryDim::dim(), ReferenceDim::dim()) {
+        (2, 1) => {
+            normal[0] = j[(1, 0)];
+            normal[1] = -j[(0, 0)];
+        }
+        (3, 2) => {
+            normal[0] = j[(1, 0)] * j[(2, 1)] - j[(2, 0)] * j[(1, 1)];
+            normal[1] = j[(2, 0)] * j[(0, 1)] - j[(0, 0)] * j[(2, 1)];
+            normal[2] = j[(0, 0)] * j[(1, 1)] - j[(1, 0)] * j[(0, 1)];
+        }
+        (d, r) => panic!("Surface elements with reference dimension {r} in dimension {d} are not supported"),
+    }
+    let measure = normal.norm();
+    (measure, normal / measure)
+}
+
+/// Assemble the local traction vector associated with a particular surface finite element and
+/// traction function.
+///
+/// Assembles the local vector for the provided surface element associated with the
+/// $(t, v)_{\Gamma}$ term in the weak form of problems with Neumann boundary conditions. For
+/// example, the weak form of linear elasticity with tractions $t$ prescribed on $\Gamma_N$ reads
+/// $$ a(u, v) = (f, v) + (t, v)_{\Gamma_N} \qquad \forall v \in V. $$
+///
+/// A working array for storing basis function values must be provided.
+///
+/// **This is a low-level routine**. Most users will not need to call this function directly,
+/// and are instead more likely to use [`ElementTractionAssembler`].
+///
+/// # Panics
+///
+/// The size of the output vector must be equal to `n * s`, where `n` is the number of
+/// nodes in the element and `s` is the solution dimension.
+///
+/// Panics if the quadrature weights, points and data arrays do not have the same length.
+///
+/// The basis values buffer must have size `n`.
+///
+/// Panics unless the element is a segment in 2D or a surface in 3D.
+pub fn assemble_element_traction_vector<T, Element, Traction>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    traction: &Traction,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    quadrature_data: &[Traction::Parameters],
+    basis_values_buffer: &mut [T],
+) where
+    T: Real,
+    Element: FiniteElement<T>,
+    Traction: TractionFunction<T, Element::GeometryDim>,
+    DefaultAllocator: TriDimAllocator<T, Element::GeometryDim, Element::ReferenceDim, Traction::SolutionDim>,
+{
+    assert_eq!(
+        quadrature_weights.len(),
+        quadrature_points.len(),
+        "Number of quadrature weights must be equal to number of points."
+    );
+    assert_eq!(
+        quadrature_points.len(),
+        quadrature_data.len(),
+        "Number of quadrature points must be equal to length of data"
+    );
+    assert_eq!(
+        basis_values_buffer.len(),
+        element.num_nodes(),
+        "Number of basis functions in buffer must be equal to nodes in element."
+    );
+
+    let n = element.num_nodes();
+    assert_eq!(
+        output.len(),
+        n * Traction::SolutionDim::dim(),
+        "Length of output vector must be consistent with number of nodes and solution dim"
+    );
+    let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), Traction::SolutionDim::name(), Dyn(n));
+
+    output.fill(T::zero());
+
+    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
+    for (weight, point, data) in quadrature_iter {
+        element.populate_basis(&mut *basis_values_buffer, point);
+
+        let x = element.map_reference_coords(point);
+        let j = element.reference_jacobian(point);
+        let (measure, normal) = surface_measure_and_normal(&j);
+        let t = traction.evaluate(&x, &normal, data);
+
+        // As for source vectors, the contribution is w * |det J| * t * phi,
+        // where phi is a row vector of basis values
+        let phi = MatrixView::from_slice_generic(&*basis_values_buffer, U1::name(), Dyn(n));
+        output.gemm(*weight * measure, &t, &phi, T::one());
+    }
+}
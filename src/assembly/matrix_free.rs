@@ -0,0 +1,737 @@
+//! Matrix-free application of elliptic operators.
+//!
+//! For large problems, and in particular for high-order elements, the memory required to store
+//! the assembled system matrix is often prohibitive. Iterative solvers only require the action
+//! $\vec y = \vec A \vec x$ of the matrix, which can instead be computed element by element with
+//! numerical integration, without ever forming $\vec A$. The operators in this module compute the
+//! action of the same matrix that is assembled with
+//! [`ElementEllipticAssembler`](crate::assembly::local::ElementEllipticAssembler), i.e. the
+//! derivative of the elliptic term evaluated at a given state $u$. For each quadrature point, the
+//! linearized flux
+//! <div>$$
+//! \delta g_{ki} = \pd{g_{ki}}{G_{mj}} (\nabla u) \\, (\nabla x_h)_{mj}
+//! $$</div>
+//! is obtained from the [contraction operator](EllipticContraction), and the element
+//! contribution to $\vec y$ is $\vec y_I \mathrel{+}= \int_K \delta g^T \nabla \phi_I \\, \mathrm{d}x$.
+//!
+//! Two implementations are provided:
+//!
+//! - [`MatrixFreeEllipticOperator`] works with any volumetric finite element space and quadrature
+//!   table, and evaluates all basis functions at all quadrature points. For an element with $n$
+//!   nodes and $n$ quadrature points, this costs $O(n^2)$ operations per element.
+//! - [`TensorProductEllipticOperator`] exploits the tensor product structure of Lagrange elements
+//!   on quadrilateral and hexahedral meshes together with tensor product Gauss quadrature. With
+//!   *sum factorization*, values and gradients at all quadrature points are computed by
+//!   successive one-dimensional contractions, which for elements of degree $p$ in $d$ dimensions
+//!   costs $O(p^{d + 1})$ rather than $O(p^{2d})$ operations per element.
+//!
+//! Both operators implement [`LinearOperator`], so that they can be used with iterative solvers
+//! like [`ConjugateGradient`](fenris_sparse::cg::ConjugateGradient) and matrix-free smoothers like
+//! the `ChebyshevSmoother` in `solver::chebyshev` (requires the `experimental-solvers` feature).
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::PreparedElementBuffer;
+use crate::assembly::global::{add_local_to_global, gather_global_to_local};
+use crate::assembly::local::QuadratureTable;
+use crate::assembly::operators::{EllipticContraction, Operator};
+use crate::connectivity::{
+    Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+};
+use crate::element::{Hex27Element, Hex8Element, Quad4d2Element, Quad9d2Element};
+use crate::mesh::Mesh;
+use crate::nalgebra::{
+    DMatrix, DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Dyn, OMatrix, OPoint,
+    OVector, Point2, Point3, Scalar, U1, U2, U3,
+};
+use crate::space::FiniteElementSpace;
+use crate::{Real, SmallDim, Symmetry};
+use eyre::eyre;
+use fenris_sparse::cg::LinearOperator;
+use itertools::izip;
+use std::error::Error;
+
+/// Workspace for computing the linearized flux $\delta g$ with a single batch contraction.
+#[derive(Debug)]
+struct LinearizedFluxWorkspace<T: Scalar> {
+    /// The stacked standard basis vectors $e_1, \dots, e_d$.
+    basis: DVector<T>,
+    /// The blocks $C(\nabla u, e_k, e_m)$ of the batch contraction.
+    contractions: DMatrix<T>,
+}
+
+impl<T: Real> LinearizedFluxWorkspace<T> {
+    fn new(d: usize, s: usize) -> Self {
+        Self {
+            basis: DMatrix::identity(d, d).reshape_generic(Dyn(d * d), U1),
+            contractions: DMatrix::zeros(s * d, s * d),
+        }
+    }
+
+    /// Computes the linearized flux $\delta g$ for the gradient $\nabla x_h$ at the state $\nabla u$.
+    fn compute<D, Op>(
+        &mut self,
+        operator: &Op,
+        u_grad: &OMatrix<T, D, Op::SolutionDim>,
+        x_grad: &OMatrix<T, D, Op::SolutionDim>,
+        parameters: &Op::Parameters,
+    ) -> OMatrix<T, D, Op::SolutionDim>
+    where
+        D: SmallDim,
+        Op: EllipticContraction<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let d = D::dim();
+        let s = Op::SolutionDim::dim();
+        self.contractions.fill(T::zero());
+        operator.accumulate_contractions_into(
+            DMatrixViewMut::from(&mut self.contractions),
+            T::one(),
+            u_grad,
+            DVectorView::from(&self.basis),
+            DVectorView::from(&self.basis),
+            parameters,
+        );
+        // Only the block upper triangle is populated for symmetric operators
+        let symmetric = operator.symmetry() == Symmetry::Symmetric;
+
+        // Since C(∇u, e_k, e_m)_ij = ∂g_ki / ∂G_mj, we have δg_ki = Σ_mj C(∇u, e_k, e_m)_ij X_mj
+        let mut flux = OMatrix::<T, D, Op::SolutionDim>::zeros();
+        for k in 0..d {
+            for m in 0..d {
+                for i in 0..s {
+                    for j in 0..s {
+                        let c_ij = if symmetric && k > m {
+                            self.contractions[(s * m + j, s * k + i)]
+                        } else {
+                            self.contractions[(s * k + i, s * m + j)]
+                        };
+                        flux[(k, i)] += c_ij * x_grad[(m, j)];
+                    }
+                }
+            }
+        }
+        flux
+    }
+}
+
+fn check_vector_dimensions(num_dofs: usize, dimensions: &[(&str, usize)]) -> eyre::Result<()> {
+    for &(name, len) in dimensions {
+        if len != num_dofs {
+            return Err(eyre!(
+                "Dimension of {name} ({len}) does not match the number of degrees of freedom ({num_dofs})"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Matrix-free application of the matrix associated with an elliptic operator on a general
+/// finite element space.
+///
+/// The operator parameters at each quadrature point are taken from the quadrature table, as
+/// for [`ElementEllipticAssembler`](crate::assembly::local::ElementEllipticAssembler). See the
+/// [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct MatrixFreeEllipticOperator<'a, T: Scalar, Space, Op, QTable: ?Sized> {
+    space: &'a Space,
+    operator: &'a Op,
+    qtable: &'a QTable,
+    u: DVectorView<'a, T>,
+}
+
+impl<'a, T: Scalar, Space, Op, QTable: ?Sized> MatrixFreeEllipticOperator<'a, T, Space, Op, QTable> {
+    /// Constructs a matrix-free operator for the matrix evaluated at the state `u`.
+    ///
+    /// For linear operators like the [`LaplaceOperator`](crate::assembly::operators::LaplaceOperator),
+    /// the matrix does not depend on `u`, which can be set to zero.
+    pub fn new(space: &'a Space, operator: &'a Op, qtable: &'a QTable, u: impl Into<DVectorView<'a, T>>) -> Self {
+        Self {
+            space,
+            operator,
+            qtable,
+            u: u.into(),
+        }
+    }
+}
+
+impl<'a, T, D, Space, Op, QTable> MatrixFreeEllipticOperator<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    D: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Op: EllipticContraction<T, D>,
+    QTable: ?Sized + QuadratureTable<T, D, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    /// The number of rows and columns of the matrix.
+    pub fn num_dofs(&self) -> usize {
+        Op::SolutionDim::dim() * self.space.num_nodes()
+    }
+
+    /// Computes $\vec y \mathrel{+}= \vec A \vec x$.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of `u`, `y` or `x` do not match the number of degrees of
+    /// freedom, or if a singular element Jacobian is encountered.
+    pub fn apply_add(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> eyre::Result<()> {
+        check_vector_dimensions(self.num_dofs(), &[("u", self.u.len()), ("y", y.len()), ("x", x.len())])?;
+        let s = Op::SolutionDim::dim();
+        let mut buffer = PreparedElementBuffer::default();
+        let mut u_local = DVector::zeros(0);
+        let mut x_local = DVector::zeros(0);
+        let mut y_local = DVector::zeros(0);
+        let mut flux_workspace = LinearizedFluxWorkspace::new(D::dim(), s);
+        for element_index in 0..self.space.num_elements() {
+            buffer
+                .prepare_element(self.space, element_index, self.qtable)
+                .map_err(|err| err.wrap_err(format!("Failed to prepare element {element_index}")))?;
+            let nodes = buffer.element_nodes();
+            let n = nodes.len();
+            u_local.resize_vertically_mut(s * n, T::zero());
+            x_local.resize_vertically_mut(s * n, T::zero());
+            y_local.resize_vertically_mut(s * n, T::zero());
+            y_local.fill(T::zero());
+            gather_global_to_local(self.u, &mut u_local, nodes, s);
+            gather_global_to_local(x, &mut x_local, nodes, s);
+
+            for (q, parameters) in buffer.quadrature().data().iter().enumerate() {
+                let u_grad = buffer.interpolate_gradient::<Op::SolutionDim>(&u_local, q);
+                let x_grad = buffer.interpolate_gradient::<Op::SolutionDim>(&x_local, q);
+                let flux =
+                    flux_workspace.compute(self.operator, &u_grad, &x_grad, parameters) * buffer.physical_weight(q);
+                // Row I of the product holds the contribution ∇φ_I^T δg for node I
+                let contributions = buffer.basis_gradients(q).tr_mul(&flux);
+                for (node_index, contribution) in contributions.row_iter().enumerate() {
+                    for (i, &value) in contribution.iter().enumerate() {
+                        y_local[s * node_index + i] += value;
+                    }
+                }
+            }
+            add_local_to_global(&y_local, &mut y, nodes, s);
+        }
+        Ok(())
+    }
+
+    /// Computes the diagonal of the matrix.
+    ///
+    /// This is useful for Jacobi-type preconditioners and for the
+    /// `ChebyshevSmoother` in `solver::chebyshev`, which can be constructed from the inverse of
+    /// the diagonal and a matrix-free operator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimension of `u` does not match the number of degrees of freedom,
+    /// or if a singular element Jacobian is encountered.
+    pub fn compute_diagonal(&self) -> eyre::Result<DVector<T>> {
+        check_vector_dimensions(self.num_dofs(), &[("u", self.u.len())])?;
+        let mut diagonal = DVector::zeros(self.num_dofs());
+        let s = Op::SolutionDim::dim();
+        let mut buffer = PreparedElementBuffer::default();
+        let mut u_local = DVector::zeros(0);
+        for element_index in 0..self.space.num_elements() {
+            buffer
+                .prepare_element(self.space, element_index, self.qtable)
+                .map_err(|err| err.wrap_err(format!("Failed to prepare element {element_index}")))?;
+            let nodes = buffer.element_nodes();
+            u_local.resize_vertically_mut(s * nodes.len(), T::zero());
+            gather_global_to_local(self.u, &mut u_local, nodes, s);
+            for (q, parameters) in buffer.quadrature().data().iter().enumerate() {
+                let u_grad = buffer.interpolate_gradient::<Op::SolutionDim>(&u_local, q);
+                let weight = buffer.physical_weight(q);
+                for (&node, phi_grad) in izip!(nodes, buffer.basis_gradients(q).column_iter()) {
+                    let phi_grad = phi_grad.clone_owned();
+                    let contraction = self
+                        .operator
+                        .contract(&u_grad, &phi_grad, &phi_grad, parameters);
+                    for i in 0..s {
+                        diagonal[s * node + i] += weight * contraction[(i, i)];
+                    }
+                }
+            }
+        }
+        Ok(diagonal)
+    }
+}
+
+impl<'a, T, D, Space, Op, QTable> LinearOperator<T> for MatrixFreeEllipticOperator<'a, T, Space, Op, QTable>
+where
+    T: Real,
+    D: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Op: EllipticContraction<T, D>,
+    QTable: ?Sized + QuadratureTable<T, D, Data = Op::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        y.fill(T::zero());
+        Ok(self.apply_add(y, x)?)
+    }
+}
+
+/// A connectivity of a Lagrange element whose basis is the tensor product of one-dimensional
+/// Lagrange bases with equispaced nodes on $[-1, 1]$.
+///
+/// As for the corresponding finite elements, the element geometry is given by the multilinear
+/// map defined by the corner vertices of the element, which are the first $2^d$ nodes.
+pub trait TensorProductLagrangeConnectivity: Connectivity {
+    type ReferenceDim: SmallDim;
+
+    /// The polynomial degree of the basis in each coordinate direction.
+    const DEGREE: usize;
+
+    /// The reference coordinates of the nodes, in the element-local order of the connectivity.
+    fn reference_nodes() -> Vec<OPoint<f64, Self::ReferenceDim>>
+    where
+        DefaultAllocator: DimAllocator<f64, Self::ReferenceDim>;
+
+    /// The element-local index of each node of the tensor product grid of nodes.
+    ///
+    /// The nodes of the grid are ordered lexicographically, with the first coordinate
+    /// direction running fastest.
+    fn lexicographic_node_order() -> Vec<usize>
+    where
+        DefaultAllocator: DimAllocator<f64, Self::ReferenceDim>,
+    {
+        lexicographic_order_from_reference_nodes(&Self::reference_nodes(), Self::DEGREE)
+    }
+
+    /// The element-local index of each corner vertex, ordered lexicographically like
+    /// [`lexicographic_node_order`](Self::lexicographic_node_order).
+    fn lexicographic_corner_order() -> Vec<usize>
+    where
+        DefaultAllocator: DimAllocator<f64, Self::ReferenceDim>,
+    {
+        let num_corners = 1 << Self::ReferenceDim::dim();
+        lexicographic_order_from_reference_nodes(&Self::reference_nodes()[..num_corners], 1)
+    }
+}
+
+/// Determines the lexicographic node order from the reference coordinates of the nodes.
+fn lexicographic_order_from_reference_nodes<D>(reference_nodes: &[OPoint<f64, D>], degree: usize) -> Vec<usize>
+where
+    D: DimName,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let num_nodes_1d = degree + 1;
+    let mut order = vec![usize::MAX; reference_nodes.len()];
+    for (node_index, node) in reference_nodes.iter().enumerate() {
+        let lexicographic_index = node.iter().rev().fold(0, |index, &xi| {
+            num_nodes_1d * index + ((xi + 1.0) * degree as f64 / 2.0).round() as usize
+        });
+        order[lexicographic_index] = node_index;
+    }
+    debug_assert!(order.iter().all(|&node_index| node_index != usize::MAX));
+    order
+}
+
+impl TensorProductLagrangeConnectivity for Quad4d2Connectivity {
+    type ReferenceDim = U2;
+    const DEGREE: usize = 1;
+
+    fn reference_nodes() -> Vec<Point2<f64>> {
+        Quad4d2Element::reference().vertices().to_vec()
+    }
+}
+
+impl TensorProductLagrangeConnectivity for Quad9d2Connectivity {
+    type ReferenceDim = U2;
+    const DEGREE: usize = 2;
+
+    fn reference_nodes() -> Vec<Point2<f64>> {
+        Quad9d2Element::reference().vertices().to_vec()
+    }
+}
+
+impl TensorProductLagrangeConnectivity for Hex8Connectivity {
+    type ReferenceDim = U3;
+    const DEGREE: usize = 1;
+
+    fn reference_nodes() -> Vec<Point3<f64>> {
+        Hex8Element::reference().vertices().to_vec()
+    }
+}
+
+impl TensorProductLagrangeConnectivity for Hex27Connectivity {
+    type ReferenceDim = U3;
+    const DEGREE: usize = 2;
+
+    fn reference_nodes() -> Vec<Point3<f64>> {
+        Hex27Element::reference().vertices().to_vec()
+    }
+}
+
+/// Evaluates the one-dimensional Lagrange basis with equispaced nodes on $[-1, 1]$ and its
+/// derivatives at the given points.
+///
+/// Returns matrices whose rows correspond to the points and whose columns correspond to the
+/// basis functions.
+fn lagrange_basis_1d<T: Real>(degree: usize, points: &[T]) -> (DMatrix<T>, DMatrix<T>) {
+    let nodes: Vec<T> = (0..=degree)
+        .map(|i| T::from_f64(-1.0 + 2.0 * i as f64 / degree as f64).unwrap())
+        .collect();
+    let mut values = DMatrix::zeros(points.len(), nodes.len());
+    let mut derivatives = DMatrix::zeros(points.len(), nodes.len());
+    for (q, &x) in points.iter().enumerate() {
+        for (i, &x_i) in nodes.iter().enumerate() {
+            let factor = |j: usize| (x - nodes[j]) / (x_i - nodes[j]);
+            values[(q, i)] = (0..nodes.len())
+                .filter(|&j| j != i)
+                .fold(T::one(), |product, j| product * factor(j));
+            derivatives[(q, i)] = (0..nodes.len())
+                .filter(|&k| k != i)
+                .map(|k| {
+                    (0..nodes.len())
+                        .filter(|&j| j != i && j != k)
+                        .fold(T::one() / (x_i - nodes[k]), |product, j| product * factor(j))
+                })
+                .fold(T::zero(), |sum, term| sum + term);
+        }
+    }
+    (values, derivatives)
+}
+
+/// Applies `matrices[d]` along dimension `d` of a tensor stored with the first index running
+/// fastest, and stores the resulting tensor in `output`.
+///
+/// The shape of the input tensor is given by the number of columns of the matrices. The
+/// intermediate tensors alternate between `output` and `scratch`, so that no allocations are
+/// necessary once the buffers have grown to the required size.
+fn apply_tensor_product<T: Real>(matrices: &[&DMatrix<T>], input: &[T], output: &mut Vec<T>, scratch: &mut Vec<T>) {
+    let num_dims = matrices.len();
+    debug_assert_eq!(
+        input.len(),
+        matrices
+            .iter()
+            .map(|matrix| matrix.ncols())
+            .product::<usize>()
+    );
+    for (dim, matrix) in matrices.iter().enumerate() {
+        // Choose the target such that the last application writes to the output
+        let (source, target) = if (num_dims - dim) % 2 == 1 {
+            (&*scratch, &mut *output)
+        } else {
+            (&*output, &mut *scratch)
+        };
+        let source = if dim == 0 { input } else { source.as_slice() };
+        let n_in = matrix.ncols();
+        let n_out = matrix.nrows();
+        let inner: usize = matrices[..dim]
+            .iter()
+            .map(|matrix| matrix.nrows())
+            .product();
+        let outer: usize = matrices[dim + 1..]
+            .iter()
+            .map(|matrix| matrix.ncols())
+            .product();
+        target.clear();
+        target.resize(inner * n_out * outer, T::zero());
+        for o in 0..outer {
+            for a in 0..n_out {
+                let target = &mut target[(o * n_out + a) * inner..(o * n_out + a + 1) * inner];
+                for b in 0..n_in {
+                    let m_ab = matrix[(a, b)];
+                    let source = &source[(o * n_in + b) * inner..(o * n_in + b + 1) * inner];
+                    for (t, &s) in target.iter_mut().zip(source) {
+                        *t += m_ab * s;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Applies each set of `matrices` to `input`, storing the result of set $r$ in `outputs[r]`.
+fn apply_tensor_products<T: Real>(
+    matrices: &[Vec<&DMatrix<T>>],
+    input: &[T],
+    outputs: &mut [Vec<T>],
+    scratch: &mut Vec<T>,
+) {
+    for (matrices, output) in izip!(matrices, outputs) {
+        apply_tensor_product(matrices, input, output, scratch);
+    }
+}
+
+/// The matrices to apply along each dimension to obtain each component of the reference gradient.
+fn gradient_matrices<'b, T: Real>(
+    dim: usize,
+    values: &'b DMatrix<T>,
+    derivatives: &'b DMatrix<T>,
+) -> Vec<Vec<&'b DMatrix<T>>> {
+    // Reference gradient component r is obtained by differentiating along dimension r
+    (0..dim)
+        .map(|r| {
+            (0..dim)
+                .map(|d| if d == r { derivatives } else { values })
+                .collect()
+        })
+        .collect()
+}
+
+/// Checks that all nodes of each cell coincide with the multilinear map of its corner vertices,
+/// which are given in lexicographic order.
+fn check_multilinear_geometry<T, D, C>(mesh: &Mesh<T, D, C>, lexicographic_corners: &[usize]) -> eyre::Result<()>
+where
+    T: Real,
+    D: SmallDim,
+    C: TensorProductLagrangeConnectivity<ReferenceDim = D>,
+    DefaultAllocator: DimAllocator<T, D> + DimAllocator<f64, D>,
+{
+    let reference_nodes = C::reference_nodes();
+    // The multilinear shape function of each corner evaluated at each node, where bit r of the
+    // lexicographic corner index determines the corner coordinate in direction r
+    let corner_weights: Vec<Vec<T>> = reference_nodes
+        .iter()
+        .map(|xi| {
+            (0..lexicographic_corners.len())
+                .map(|corner| {
+                    let weight = (0..D::dim()).fold(1.0, |w, r| {
+                        let sign = if corner & (1 << r) == 0 { -1.0 } else { 1.0 };
+                        w * 0.5 * (1.0 + sign * xi[r])
+                    });
+                    T::from_f64(weight).unwrap()
+                })
+                .collect()
+        })
+        .collect();
+    let relative_tolerance = T::from_f64(1e-8).unwrap();
+
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        let vertex_indices = cell.vertex_indices();
+        let corner = |c: usize| &mesh.vertices()[vertex_indices[lexicographic_corners[c]]];
+        let size = (1..lexicographic_corners.len())
+            .map(|c| (corner(c) - corner(0)).norm())
+            .fold(T::zero(), |max, distance| max.max(distance));
+        for (local_index, weights) in corner_weights.iter().enumerate() {
+            let mapped = weights
+                .iter()
+                .enumerate()
+                .fold(OVector::<T, D>::zeros(), |x, (c, &w)| x + &corner(c).coords * w);
+            let node = &mesh.vertices()[vertex_indices[local_index]];
+            if (&node.coords - mapped).norm() > relative_tolerance * size {
+                return Err(eyre!(
+                    "Node {local_index} of element {cell_index} does not coincide with the \
+                     multilinear map of the corner vertices of the element"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Matrix-free application of the matrix associated with an elliptic operator on a mesh of
+/// tensor product Lagrange elements, using sum factorization.
+///
+/// The element geometry is the multilinear map of the corner vertices, see
+/// [`TensorProductLagrangeConnectivity`]. Meshes whose remaining nodes do not coincide with this
+/// map, such as curved higher-order meshes, are rejected by [`new`](Self::new).
+///
+/// The integrals are computed with the tensor product Gauss rule with the given number of
+/// points per coordinate direction, so that the operator reproduces the matrix assembled with
+/// e.g. [`quadrilateral_gauss`](crate::quadrature::tensor::quadrilateral_gauss) or
+/// [`hexahedron_gauss`](crate::quadrature::tensor::hexahedron_gauss) with the same number of
+/// points. The same
+/// [operator parameters](Operator::Parameters) are used at all quadrature points, see
+/// [`with_parameters`](Self::with_parameters).
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct TensorProductEllipticOperator<'a, T, C, Op>
+where
+    T: Real,
+    C: TensorProductLagrangeConnectivity,
+    Op: Operator<T, C::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, C::ReferenceDim, C::ReferenceDim>,
+{
+    mesh: &'a Mesh<T, C::ReferenceDim, C>,
+    operator: &'a Op,
+    u: DVectorView<'a, T>,
+    parameters: Op::Parameters,
+    lexicographic_nodes: Vec<usize>,
+    lexicographic_corners: Vec<usize>,
+    weights_1d: Vec<T>,
+    values_1d: DMatrix<T>,
+    derivatives_1d: DMatrix<T>,
+    linear_values_1d: DMatrix<T>,
+    linear_derivatives_1d: DMatrix<T>,
+}
+
+impl<'a, T, D, C, Op> TensorProductEllipticOperator<'a, T, C, Op>
+where
+    T: Real,
+    D: SmallDim,
+    C: TensorProductLagrangeConnectivity<ReferenceDim = D>,
+    Op: EllipticContraction<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim> + DimAllocator<f64, D>,
+{
+    /// Constructs a matrix-free operator for the matrix evaluated at the state `u`, integrated
+    /// with the given number of Gauss points per coordinate direction.
+    ///
+    /// # Errors
+    ///
+    /// Since the element geometry is given by the corner vertices only, returns an error if the
+    /// remaining nodes of an element deviate from the multilinear map of its corner vertices by
+    /// more than a small tolerance relative to the size of the element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of quadrature points is zero.
+    pub fn new(
+        mesh: &'a Mesh<T, D, C>,
+        operator: &'a Op,
+        u: impl Into<DVectorView<'a, T>>,
+        num_quadrature_points_1d: usize,
+    ) -> eyre::Result<Self> {
+        let lexicographic_corners = C::lexicographic_corner_order();
+        check_multilinear_geometry(mesh, &lexicographic_corners)?;
+        let (weights, points) = crate::quadrature::univariate::gauss(num_quadrature_points_1d);
+        let points: Vec<T> = points
+            .iter()
+            .map(|xi| T::from_f64(xi[0]).unwrap())
+            .collect();
+        let (values_1d, derivatives_1d) = lagrange_basis_1d(C::DEGREE, &points);
+        let (linear_values_1d, linear_derivatives_1d) = lagrange_basis_1d(1, &points);
+        Ok(Self {
+            mesh,
+            operator,
+            u: u.into(),
+            parameters: Op::Parameters::default(),
+            lexicographic_nodes: C::lexicographic_node_order(),
+            lexicographic_corners,
+            weights_1d: weights.iter().map(|&w| T::from_f64(w).unwrap()).collect(),
+            values_1d,
+            derivatives_1d,
+            linear_values_1d,
+            linear_derivatives_1d,
+        })
+    }
+
+    /// Sets the operator parameters used at all quadrature points.
+    pub fn with_parameters(self, parameters: Op::Parameters) -> Self {
+        Self { parameters, ..self }
+    }
+
+    /// The number of rows and columns of the matrix.
+    pub fn num_dofs(&self) -> usize {
+        Op::SolutionDim::dim() * self.mesh.vertices().len()
+    }
+
+    /// Computes $\vec y \mathrel{+}= \vec A \vec x$.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of `u`, `y` or `x` do not match the number of degrees of
+    /// freedom, or if a singular element Jacobian is encountered.
+    pub fn apply_add(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> eyre::Result<()> {
+        check_vector_dimensions(self.num_dofs(), &[("u", self.u.len()), ("y", y.len()), ("x", x.len())])?;
+        let d = D::dim();
+        let s = Op::SolutionDim::dim();
+        let num_points_1d = self.weights_1d.len();
+        let num_nodes = self.lexicographic_nodes.len();
+        let num_points = num_points_1d.pow(d as u32);
+
+        // The quadrature weights in the lexicographic order of the quadrature points
+        let weights: Vec<T> = (0..num_points)
+            .map(|q| {
+                (0..d).fold(T::one(), |w, dim| {
+                    w * self.weights_1d[(q / num_points_1d.pow(dim as u32)) % num_points_1d]
+                })
+            })
+            .collect();
+        let values_t = self.values_1d.transpose();
+        let derivatives_t = self.derivatives_1d.transpose();
+        let transposed_gradient_matrices = gradient_matrices(d, &values_t, &derivatives_t);
+        let basis_gradient_matrices = gradient_matrices(d, &self.values_1d, &self.derivatives_1d);
+        let geometry_gradient_matrices = gradient_matrices(d, &self.linear_values_1d, &self.linear_derivatives_1d);
+
+        let mut element_nodes = vec![0; num_nodes];
+        let mut coordinates = vec![vec![T::zero(); self.lexicographic_corners.len()]; d];
+        let mut u_values = vec![vec![T::zero(); num_nodes]; s];
+        let mut x_values = vec![vec![T::zero(); num_nodes]; s];
+        // Entry [a][r] holds the reference derivative of coordinate a (or component a) in
+        // direction r at all quadrature points
+        let mut jacobians = vec![vec![Vec::new(); d]; d];
+        let mut u_gradients = vec![vec![Vec::new(); d]; s];
+        let mut x_gradients = vec![vec![Vec::new(); d]; s];
+        let mut fluxes = vec![vec![vec![T::zero(); num_points]; s]; d];
+        let mut y_values = vec![T::zero(); num_nodes];
+        let mut contribution = Vec::new();
+        let mut scratch = Vec::new();
+        let mut flux_workspace = LinearizedFluxWorkspace::new(d, s);
+        for (cell_index, cell) in self.mesh.connectivity().iter().enumerate() {
+            let vertex_indices = cell.vertex_indices();
+            for (lexicographic_index, &local_index) in self.lexicographic_nodes.iter().enumerate() {
+                let node = vertex_indices[local_index];
+                element_nodes[lexicographic_index] = node;
+                for i in 0..s {
+                    u_values[i][lexicographic_index] = self.u[s * node + i];
+                    x_values[i][lexicographic_index] = x[s * node + i];
+                }
+            }
+
+            for (lexicographic_index, &local_index) in self.lexicographic_corners.iter().enumerate() {
+                let vertex = &self.mesh.vertices()[vertex_indices[local_index]];
+                for a in 0..d {
+                    coordinates[a][lexicographic_index] = vertex[a];
+                }
+            }
+
+            for (values, jacobian) in izip!(&coordinates, &mut jacobians) {
+                apply_tensor_products(&geometry_gradient_matrices, values, jacobian, &mut scratch);
+            }
+            for (values, gradient) in izip!(&u_values, &mut u_gradients) {
+                apply_tensor_products(&basis_gradient_matrices, values, gradient, &mut scratch);
+            }
+            for (values, gradient) in izip!(&x_values, &mut x_gradients) {
+                apply_tensor_products(&basis_gradient_matrices, values, gradient, &mut scratch);
+            }
+
+            for q in 0..num_points {
+                let jacobian = OMatrix::<T, D, D>::from_fn(|a, r| jacobians[a][r][q]);
+                let jacobian_det = jacobian.determinant();
+                let jacobian_inv = jacobian
+                    .try_inverse()
+                    .ok_or_else(|| eyre!("Singular element Jacobian encountered in element {cell_index}"))?;
+                let jacobian_inv_t = jacobian_inv.transpose();
+                let u_grad = &jacobian_inv_t * OMatrix::<T, D, Op::SolutionDim>::from_fn(|r, i| u_gradients[i][r][q]);
+                let x_grad = &jacobian_inv_t * OMatrix::<T, D, Op::SolutionDim>::from_fn(|r, i| x_gradients[i][r][q]);
+                let flux = flux_workspace.compute(self.operator, &u_grad, &x_grad, &self.parameters);
+                // Since ∇φ = J^{-T} ∇_ξ φ, we have ∇φ · δg = ∇_ξ φ · J^{-1} δg
+                let reference_flux = jacobian_inv * flux * (weights[q] * jacobian_det.abs());
+                for r in 0..d {
+                    for i in 0..s {
+                        fluxes[r][i][q] = reference_flux[(r, i)];
+                    }
+                }
+            }
+
+            for i in 0..s {
+                y_values.fill(T::zero());
+                for (matrices, flux) in izip!(&transposed_gradient_matrices, &fluxes) {
+                    apply_tensor_product(matrices, &flux[i], &mut contribution, &mut scratch);
+                    for (y_value, &contribution) in y_values.iter_mut().zip(&contribution) {
+                        *y_value += contribution;
+                    }
+                }
+                for (&node, &y_value) in izip!(&element_nodes, &y_values) {
+                    y[s * node + i] += y_value;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, D, C, Op> LinearOperator<T> for TensorProductEllipticOperator<'a, T, C, Op>
+where
+    T: Real,
+    D: SmallDim,
+    C: TensorProductLagrangeConnectivity<ReferenceDim = D>,
+    Op: EllipticContraction<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim> + DimAllocator<f64, D>,
+{
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        y.fill(T::zero());
+        Ok(self.apply_add(y, x)?)
+    }
+}
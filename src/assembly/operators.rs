@@ -2,9 +2,27 @@ use crate::allocators::BiDimAllocator;
 use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector, Scalar};
 use crate::{Real, SmallDim, Symmetry};
 
+mod advection_diffusion;
+#[cfg(feature = "autodiff")]
+mod autodiff;
+mod eigenstrain;
+mod elasticity;
 mod laplace;
+mod mixed;
+mod phase_field;
+pub mod solid;
+mod stokes;
+pub mod test_utils;
+pub use advection_diffusion::*;
+#[cfg(feature = "autodiff")]
+pub use autodiff::*;
+pub use eigenstrain::*;
+pub use elasticity::*;
 pub use laplace::*;
+pub use mixed::*;
 use nalgebra::min;
+pub use phase_field::*;
+pub use stokes::*;
 
 pub trait Operator<T, GeometryDim> {
     type SolutionDim: SmallDim;
@@ -13,7 +31,9 @@ pub trait Operator<T, GeometryDim> {
     ///
     /// Typically this encodes material information, such as density, stiffness and other physical
     /// quantities. This is intended to be paired with data associated with individual
-    /// quadrature points during numerical integration.
+    /// quadrature points during numerical integration. Parameters that vary between quadrature
+    /// points can be managed with a
+    /// [`QuadratureParameterStorage`](crate::assembly::local::QuadratureParameterStorage).
     type Parameters: Default + Clone + 'static;
 }
 
@@ -45,6 +65,31 @@ where
     }
 }
 
+/// An operator with internal variables that evolve with the solution.
+///
+/// Path-dependent materials, such as plastic or viscoelastic materials, depend not only on the
+/// current gradient $\nabla u$, but also on internal (history) variables like the plastic strain.
+/// For such operators, the [parameters](Operator::Parameters) at each quadrature point hold the
+/// internal variables of the last converged step, typically in a
+/// [`QuadratureParameterStorage`](crate::assembly::local::QuadratureParameterStorage).
+///
+/// During the Newton iterations of a step, the operator (e.g. its
+/// [`EllipticOperator`] and [`EllipticContraction`] implementations) evaluates the trial state
+/// from the committed internal variables and the current gradient, without modifying the
+/// internal variables. Once the step has converged, the commit pass
+/// [`commit_operator_state`](crate::assembly::local::commit_operator_state) calls
+/// [`commit_state`](Self::commit_state) at every quadrature point to update the internal
+/// variables.
+pub trait StatefulOperator<T, GeometryDim>: Operator<T, GeometryDim>
+where
+    T: Scalar,
+    GeometryDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Self::SolutionDim>,
+{
+    /// Updates the internal variables in `parameters` for the converged gradient $\nabla u$.
+    fn commit_state(&self, gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>, parameters: &mut Self::Parameters);
+}
+
 /// A contraction operator encoding derivative information for an elliptic operator.
 ///
 /// The contraction operator for an elliptic operator $g = g(\nabla u)$ evaluated at $\nabla u$
@@ -56,6 +101,11 @@ where
 /// where $G = \nabla u$. We have used Einstein summation notation to simplify the notation
 /// for the above expression.
 ///
+/// Implementing the contraction by hand can be error-prone. With the `autodiff` feature, the
+/// contraction can instead be derived by automatic differentiation from a generic
+/// implementation of the elliptic operator or its energy, see `OperatorAutoDiff` and
+/// `EnergyAutoDiff`.
+///
 /// TODO: Maybe return results in impls...?
 pub trait EllipticContraction<T, GeometryDim>: Operator<T, GeometryDim>
 where
@@ -0,0 +1,128 @@
+use crate::allocators::DimAllocator;
+use crate::assembly::operators::Operator;
+use crate::nalgebra::{DefaultAllocator, OPoint, OVector, Scalar, U1};
+use crate::{Real, SmallDim, Symmetry};
+
+/// Coefficients of the advection-diffusion-reaction equation at a point.
+///
+/// See [`AdvectionDiffusionReaction`] for the meaning of the coefficients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvectionDiffusionReactionCoefficients<T, D>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// The advection velocity $\beta$.
+    pub velocity: OVector<T, D>,
+    /// The (isotropic) diffusivity $\kappa$.
+    pub diffusivity: T,
+    /// The reaction coefficient $c$.
+    pub reaction: T,
+    /// The source term $f$.
+    pub source: T,
+}
+
+impl<T, D> Default for AdvectionDiffusionReactionCoefficients<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            velocity: OVector::<T, D>::zeros(),
+            diffusivity: T::zero(),
+            reaction: T::zero(),
+            source: T::zero(),
+        }
+    }
+}
+
+impl<T, D> AdvectionDiffusionReactionCoefficients<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn new(velocity: OVector<T, D>, diffusivity: T, reaction: T, source: T) -> Self {
+        Self {
+            velocity,
+            diffusivity,
+            reaction,
+            source,
+        }
+    }
+}
+
+/// A scalar advection-diffusion-reaction operator.
+///
+/// The operator describes the (stationary) advection-diffusion-reaction equation
+/// $$
+/// \beta \cdot \nabla u - \nabla \cdot (\kappa \nabla u) + c u = f,
+/// $$
+/// with advection velocity $\beta$, diffusivity $\kappa$, reaction coefficient $c$ and
+/// source term $f$, whose weak form reads
+/// <div>$$
+/// \int_{\Omega} \kappa \nabla u \cdot \nabla v + (\beta \cdot \nabla u) v + c u v \\, \mathrm{d}x
+///     = \int_{\Omega} f v \\, \mathrm{d}x.
+/// $$</div>
+/// Unlike elliptic operators, the advective term is not symmetric in $u$ and $v$, and the
+/// resulting matrices are in general not symmetric.
+///
+/// The coefficients may depend on the position $x$ and the [operator parameters](Operator::Parameters)
+/// associated with each quadrature point. See
+/// [`ElementAdvectionDiffusionReactionAssembler`](crate::assembly::local::ElementAdvectionDiffusionReactionAssembler)
+/// for assembly, including optional SUPG stabilization.
+pub trait AdvectionDiffusionReaction<T, GeometryDim>: Operator<T, GeometryDim, SolutionDim = U1>
+where
+    T: Scalar,
+    GeometryDim: SmallDim,
+    DefaultAllocator: DimAllocator<T, GeometryDim>,
+{
+    /// Evaluate the coefficients at the given point with the provided parameters.
+    fn evaluate_coefficients(
+        &self,
+        coords: &OPoint<T, GeometryDim>,
+        parameters: &Self::Parameters,
+    ) -> AdvectionDiffusionReactionCoefficients<T, GeometryDim>;
+
+    /// Whether the bilinear form of the operator is symmetric.
+    ///
+    /// The bilinear form is only symmetric in the absence of advection, i.e. for
+    /// diffusion-reaction problems. Assemblers may exploit symmetry to only compute about half
+    /// of the matrix entries. The default implementation indicates non-symmetry.
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::NonSymmetric
+    }
+}
+
+/// An advection-diffusion-reaction operator whose coefficients are given directly
+/// as the parameters at each quadrature point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdvectionDiffusionReactionOperator;
+
+impl<T, D> Operator<T, D> for AdvectionDiffusionReactionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type SolutionDim = U1;
+    type Parameters = AdvectionDiffusionReactionCoefficients<T, D>;
+}
+
+impl<T, D> AdvectionDiffusionReaction<T, D> for AdvectionDiffusionReactionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn evaluate_coefficients(
+        &self,
+        _coords: &OPoint<T, D>,
+        parameters: &Self::Parameters,
+    ) -> AdvectionDiffusionReactionCoefficients<T, D> {
+        parameters.clone()
+    }
+}
@@ -0,0 +1,253 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::autodiff::{AutoDiffScalar, Dual};
+use crate::nalgebra::{DefaultAllocator, DimName, OMatrix, OVector};
+use crate::{Real, SmallDim, Symmetry};
+
+/// An elliptic energy that can be evaluated for any [scalar supporting automatic
+/// differentiation](AutoDiffScalar).
+///
+/// This is the generic counterpart of [`EllipticEnergy`]. Wrapping the operator in an
+/// [`EnergyAutoDiff`] provides the elliptic operator $g = \pd{\psi}{G}$ and its contraction by
+/// automatic differentiation, so that a new material can be prototyped with only an energy
+/// function. Constants of type `T` stored in the parameters can be converted with
+/// [`AutoDiffScalar::from_primal`].
+pub trait AutoDiffEllipticEnergy<T, GeometryDim>: Operator<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+{
+    /// Compute the energy $\psi(\nabla u)$ with the provided
+    /// [operator parameters](Operator::Parameters).
+    fn compute_energy_ad<S>(
+        &self,
+        gradient: &OMatrix<S, GeometryDim, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> S
+    where
+        S: AutoDiffScalar<T>,
+        DefaultAllocator: BiDimAllocator<S, GeometryDim, Self::SolutionDim>;
+}
+
+/// An elliptic operator that can be evaluated for any [scalar supporting automatic
+/// differentiation](AutoDiffScalar).
+///
+/// This is the generic counterpart of [`EllipticOperator`]. Wrapping the operator in an
+/// [`OperatorAutoDiff`] provides the [contraction](EllipticContraction) by automatic
+/// differentiation.
+pub trait AutoDiffEllipticOperator<T, GeometryDim>: Operator<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+{
+    /// Compute the elliptic operator $g = g(\nabla u)$ with the provided
+    /// [operator parameters](Operator::Parameters).
+    fn compute_elliptic_operator_ad<S>(
+        &self,
+        gradient: &OMatrix<S, GeometryDim, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<S, GeometryDim, Self::SolutionDim>
+    where
+        S: AutoDiffScalar<T>,
+        DefaultAllocator: BiDimAllocator<S, GeometryDim, Self::SolutionDim>;
+}
+
+/// Derives an elliptic operator and its contraction from an [`AutoDiffEllipticEnergy`].
+///
+/// The wrapper implements [`EllipticEnergy`], [`EllipticOperator`] and [`EllipticContraction`].
+/// The elliptic operator is computed with one evaluation of the energy on [dual numbers](Dual)
+/// per entry of $\nabla u$, and the contraction with $s^2$ evaluations on nested dual numbers.
+/// Since the contraction is derived from the Hessian of the energy, it is
+/// [symmetric](Symmetry::Symmetric) and always consistent with the elliptic operator.
+///
+/// Automatic differentiation is considerably more expensive than hand-written derivatives, so
+/// the wrapper is primarily intended for prototyping, and for verifying hand-written
+/// implementations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnergyAutoDiff<Op>(pub Op);
+
+/// Derives the contraction of an [`AutoDiffEllipticOperator`].
+///
+/// The wrapper implements [`EllipticOperator`] and [`EllipticContraction`], where the
+/// contraction is computed with $s$ evaluations of the operator on [dual numbers](Dual). Since
+/// the elliptic operator is not assumed to derive from an energy, the contraction is treated as
+/// [non-symmetric](Symmetry::NonSymmetric).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorAutoDiff<Op>(pub Op);
+
+impl<Op> EnergyAutoDiff<Op> {
+    pub fn new(operator: Op) -> Self {
+        Self(operator)
+    }
+
+    pub fn operator(&self) -> &Op {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Op {
+        self.0
+    }
+}
+
+impl<Op> OperatorAutoDiff<Op> {
+    pub fn new(operator: Op) -> Self {
+        Self(operator)
+    }
+
+    pub fn operator(&self) -> &Op {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Op {
+        self.0
+    }
+}
+
+impl<T, D, Op> Operator<T, D> for EnergyAutoDiff<Op>
+where
+    Op: Operator<T, D>,
+{
+    type SolutionDim = Op::SolutionDim;
+    type Parameters = Op::Parameters;
+}
+
+impl<T, D, Op> Operator<T, D> for OperatorAutoDiff<Op>
+where
+    Op: Operator<T, D>,
+{
+    type SolutionDim = Op::SolutionDim;
+    type Parameters = Op::Parameters;
+}
+
+impl<T, D, Op> EllipticEnergy<T, D> for EnergyAutoDiff<Op>
+where
+    T: Real,
+    D: SmallDim,
+    Op: AutoDiffEllipticEnergy<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn compute_energy(&self, gradient: &OMatrix<T, D, Self::SolutionDim>, parameters: &Self::Parameters) -> T {
+        self.0.compute_energy_ad(gradient, parameters)
+    }
+}
+
+impl<T, D, Op> EllipticOperator<T, D> for EnergyAutoDiff<Op>
+where
+    T: Real,
+    D: SmallDim,
+    Op: AutoDiffEllipticEnergy<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim> + BiDimAllocator<Dual<T>, D, Op::SolutionDim>,
+{
+    fn compute_elliptic_operator(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, Self::SolutionDim> {
+        let mut dual_gradient = gradient.map(Dual::constant);
+        let mut g = OMatrix::<T, D, Self::SolutionDim>::zeros();
+        for idx in 0..g.len() {
+            dual_gradient[idx].set_derivative(T::one());
+            g[idx] = self
+                .0
+                .compute_energy_ad(&dual_gradient, parameters)
+                .derivative();
+            dual_gradient[idx].set_derivative(T::zero());
+        }
+        g
+    }
+}
+
+impl<T, D, Op> EllipticContraction<T, D> for EnergyAutoDiff<Op>
+where
+    T: Real,
+    D: SmallDim,
+    Op: AutoDiffEllipticEnergy<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim> + BiDimAllocator<Dual<Dual<T>>, D, Op::SolutionDim>,
+{
+    fn contract(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
+        // The (i, j) entry of the contraction is the second directional derivative of the energy
+        // in the directions a ⊗ e_i and b ⊗ e_j, which we obtain by perturbing column i with
+        // the outer and column j with the inner dual part.
+        let s = Op::SolutionDim::dim();
+        let constant_gradient = gradient.map(|x| Dual::constant(Dual::constant(x)));
+        let mut dual_gradient = constant_gradient.clone();
+        let mut c = OMatrix::<T, Self::SolutionDim, Self::SolutionDim>::zeros();
+        for i in 0..s {
+            for j in 0..s {
+                for k in 0..D::dim() {
+                    let mut entry = dual_gradient[(k, i)];
+                    entry.set_derivative(Dual::constant(a[k]));
+                    dual_gradient[(k, i)] = entry;
+                    let mut entry = dual_gradient[(k, j)];
+                    let mut value = entry.value();
+                    value.set_derivative(b[k]);
+                    entry.set_value(value);
+                    dual_gradient[(k, j)] = entry;
+                }
+                let energy = self.0.compute_energy_ad(&dual_gradient, parameters);
+                c[(i, j)] = energy.derivative().derivative();
+                dual_gradient.copy_from(&constant_gradient);
+            }
+        }
+        c
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
+
+impl<T, D, Op> EllipticOperator<T, D> for OperatorAutoDiff<Op>
+where
+    T: Real,
+    D: SmallDim,
+    Op: AutoDiffEllipticOperator<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn compute_elliptic_operator(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, Self::SolutionDim> {
+        self.0.compute_elliptic_operator_ad(gradient, parameters)
+    }
+}
+
+impl<T, D, Op> EllipticContraction<T, D> for OperatorAutoDiff<Op>
+where
+    T: Real,
+    D: SmallDim,
+    Op: AutoDiffEllipticOperator<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim> + BiDimAllocator<Dual<T>, D, Op::SolutionDim>,
+{
+    fn contract(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
+        // Column j of the contraction is a contracted with the directional derivative of g in
+        // the direction b ⊗ e_j
+        let s = Op::SolutionDim::dim();
+        let mut c = OMatrix::<T, Self::SolutionDim, Self::SolutionDim>::zeros();
+        for j in 0..s {
+            let mut dual_gradient = gradient.map(Dual::constant);
+            for k in 0..D::dim() {
+                dual_gradient[(k, j)].set_derivative(b[k]);
+            }
+            let dg = self
+                .0
+                .compute_elliptic_operator_ad(&dual_gradient, parameters)
+                .map(|x| x.derivative());
+            c.set_column(j, &dg.tr_mul(a));
+        }
+        c
+    }
+}
@@ -0,0 +1,203 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, OMatrix, OVector, Scalar};
+use crate::{Real, SmallDim, Symmetry};
+
+/// Parameters for an [`EigenstrainOperator`].
+///
+/// Contains the eigenstrain $\vec \varepsilon^*$ in addition to the parameters of the underlying
+/// operator. Since parameters are associated with quadrature points, this allows the eigenstrain
+/// to be prescribed as a per-quadrature-point field.
+///
+/// The eigenstrain is stored in the same layout as the gradient $\nabla u$ passed to
+/// elliptic operators. For the symmetric eigenstrains typically encountered in mechanics,
+/// this distinction is irrelevant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EigenstrainParameters<T, GeometryDim, SolutionDim, P>
+where
+    T: Scalar,
+    GeometryDim: SmallDim,
+    SolutionDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    /// The eigenstrain $\vec \varepsilon^*$.
+    pub eigenstrain: OMatrix<T, GeometryDim, SolutionDim>,
+    /// Parameters for the underlying operator.
+    pub operator_parameters: P,
+}
+
+impl<T, GeometryDim, SolutionDim, P> Default for EigenstrainParameters<T, GeometryDim, SolutionDim, P>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    SolutionDim: SmallDim,
+    P: Default,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    fn default() -> Self {
+        Self {
+            eigenstrain: OMatrix::<T, GeometryDim, SolutionDim>::zeros(),
+            operator_parameters: P::default(),
+        }
+    }
+}
+
+impl<T, GeometryDim, SolutionDim, P> EigenstrainParameters<T, GeometryDim, SolutionDim, P>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    SolutionDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    pub fn new(eigenstrain: OMatrix<T, GeometryDim, SolutionDim>, operator_parameters: P) -> Self {
+        Self {
+            eigenstrain,
+            operator_parameters,
+        }
+    }
+}
+
+impl<T, D, P> EigenstrainParameters<T, D, D, P>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    /// Parameters for the isotropic thermal expansion strain $\vec \varepsilon^* = \alpha \Delta T \vec I$
+    /// with thermal expansion coefficient $\alpha$ and temperature change $\Delta T$.
+    pub fn thermal_expansion(expansion_coefficient: T, temperature_change: T, operator_parameters: P) -> Self {
+        let eigenstrain = OMatrix::<T, D, D>::identity() * (expansion_coefficient * temperature_change);
+        Self::new(eigenstrain, operator_parameters)
+    }
+}
+
+/// An elliptic operator with an additive eigenstrain.
+///
+/// Eigenstrains $\vec \varepsilon^*$ are stress-free strains such as thermal expansion, swelling
+/// or phase transformation strains. Given an elliptic operator $g$, the eigenstrain operator is
+/// defined by
+/// <div>$$
+/// g^*(\nabla u) := g(\nabla u - \vec \varepsilon^*),
+/// $$</div>
+/// and analogously for the energy and the contraction operator. The eigenstrain is prescribed
+/// through the [operator parameters](EigenstrainParameters), so that it can vary between
+/// quadrature points.
+///
+/// This corresponds to the additive decomposition of the strain used in small-strain mechanics.
+/// For finite strain mechanics, a multiplicative decomposition of the deformation gradient
+/// is usually more appropriate.
+///
+/// The associated load vectors can be assembled with
+/// [`assemble_element_eigenstrain_load_vector`](crate::assembly::local::assemble_element_eigenstrain_load_vector).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EigenstrainOperator<Op>(pub Op);
+
+impl<Op> EigenstrainOperator<Op> {
+    pub fn new(operator: Op) -> Self {
+        Self(operator)
+    }
+
+    pub fn inner(&self) -> &Op {
+        &self.0
+    }
+}
+
+impl<T, GeometryDim, Op> Operator<T, GeometryDim> for EigenstrainOperator<Op>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    Op: Operator<T, GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Op::SolutionDim>,
+{
+    type SolutionDim = Op::SolutionDim;
+    type Parameters = EigenstrainParameters<T, GeometryDim, Op::SolutionDim, Op::Parameters>;
+}
+
+impl<T, GeometryDim, Op> EllipticEnergy<T, GeometryDim> for EigenstrainOperator<Op>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    Op: EllipticEnergy<T, GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Op::SolutionDim>,
+{
+    fn compute_energy(
+        &self,
+        gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> T {
+        self.0
+            .compute_energy(&(gradient - &parameters.eigenstrain), &parameters.operator_parameters)
+    }
+}
+
+impl<T, GeometryDim, Op> EllipticOperator<T, GeometryDim> for EigenstrainOperator<Op>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    Op: EllipticOperator<T, GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Op::SolutionDim>,
+{
+    fn compute_elliptic_operator(
+        &self,
+        gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, GeometryDim, Self::SolutionDim> {
+        self.0
+            .compute_elliptic_operator(&(gradient - &parameters.eigenstrain), &parameters.operator_parameters)
+    }
+
+    fn compute_elliptic_operator_transpose(
+        &self,
+        gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, GeometryDim> {
+        self.0
+            .compute_elliptic_operator_transpose(&(gradient - &parameters.eigenstrain), &parameters.operator_parameters)
+    }
+}
+
+impl<T, GeometryDim, Op> EllipticContraction<T, GeometryDim> for EigenstrainOperator<Op>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    Op: EllipticContraction<T, GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, Op::SolutionDim>,
+{
+    fn contract(
+        &self,
+        gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>,
+        a: &OVector<T, GeometryDim>,
+        b: &OVector<T, GeometryDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
+        self.0.contract(
+            &(gradient - &parameters.eigenstrain),
+            a,
+            b,
+            &parameters.operator_parameters,
+        )
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        self.0.symmetry()
+    }
+
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        gradient: &OMatrix<T, GeometryDim, Self::SolutionDim>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        self.0.accumulate_contractions_into(
+            output,
+            alpha,
+            &(gradient - &parameters.eigenstrain),
+            a,
+            b,
+            &parameters.operator_parameters,
+        )
+    }
+}
@@ -0,0 +1,174 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::solid::{accumulate_symmetric_contractions, LameParameters};
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector};
+use crate::{Real, SmallDim, Symmetry};
+use numeric_literals::replace_float_literals;
+
+/// The model used to reduce three-dimensional linear elasticity to two dimensions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlaneElasticityModel {
+    /// Plane strain, in which the out-of-plane strain vanishes. This is appropriate for bodies
+    /// that are long in the out-of-plane direction, and uses the Lamé parameters unchanged.
+    #[default]
+    PlaneStrain,
+    /// Plane stress, in which the out-of-plane stress vanishes. This is appropriate for thin
+    /// plates loaded in their plane, and amounts to replacing $\lambda$ by the effective parameter
+    /// $\lambda^* = \frac{2 \mu \lambda}{\lambda + 2 \mu}$.
+    PlaneStress,
+}
+
+/// The operator of small-strain linear elasticity.
+///
+/// With the small-strain tensor $\vec \varepsilon = \frac{1}{2}(\nabla \vec u + (\nabla \vec u)^T)$,
+/// the stress is given by Hooke's law for isotropic materials,
+/// $$
+/// \vec \sigma = \lambda \tr(\vec \varepsilon) \vec I + 2 \mu \vec \varepsilon,
+/// $$
+/// which derives from the strain energy density
+/// $\psi = \mu \vec \varepsilon : \vec \varepsilon + \frac{\lambda}{2} \tr(\vec \varepsilon)^2$.
+/// Since the stress is linear in the displacement gradient, the contraction operator
+/// <div>$$
+/// \mathcal{C}(\vec a, \vec b) = \mu \left( (\vec a \cdot \vec b) \vec I + \vec b \vec a^T \right)
+///     + \lambda \vec a \vec b^T
+/// $$</div>
+/// is constant, and the assembled stiffness matrices are exact. For example, for linear triangles
+/// and tetrahedra they coincide with the textbook constant-strain element stiffness matrices
+/// $\int_K \vec B^T \vec D \vec B \\, \mathrm{d}V$ in Voigt notation.
+///
+/// In two dimensions, the [plane elasticity model](PlaneElasticityModel) determines how the
+/// out-of-plane direction is treated. It has no effect in other dimensions.
+///
+/// The operator is parametrized by [Lamé parameters](LameParameters).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinearElasticityOperator {
+    plane_model: PlaneElasticityModel,
+}
+
+impl LinearElasticityOperator {
+    /// Linear elasticity in 3D, or plane strain in 2D.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Linear elasticity with the plane strain model in 2D.
+    pub fn plane_strain() -> Self {
+        Self::with_plane_model(PlaneElasticityModel::PlaneStrain)
+    }
+
+    /// Linear elasticity with the plane stress model in 2D.
+    pub fn plane_stress() -> Self {
+        Self::with_plane_model(PlaneElasticityModel::PlaneStress)
+    }
+
+    pub fn with_plane_model(plane_model: PlaneElasticityModel) -> Self {
+        Self { plane_model }
+    }
+
+    pub fn plane_model(&self) -> PlaneElasticityModel {
+        self.plane_model
+    }
+
+    /// The Lamé parameters that are effectively used in dimension `D`.
+    ///
+    /// These are the given parameters, except for plane stress in 2D, for which $\lambda$ is
+    /// replaced by $\lambda^* = \frac{2 \mu \lambda}{\lambda + 2 \mu}$.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn effective_lame_parameters<T: Real, D: DimName>(&self, parameters: &LameParameters<T>) -> LameParameters<T> {
+        let &LameParameters { mu, lambda } = parameters;
+        match self.plane_model {
+            PlaneElasticityModel::PlaneStress if D::dim() == 2 => LameParameters {
+                mu,
+                lambda: 2.0 * mu * lambda / (lambda + 2.0 * mu),
+            },
+            _ => *parameters,
+        }
+    }
+}
+
+impl<T, D> Operator<T, D> for LinearElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+{
+    type SolutionDim = D;
+    type Parameters = LameParameters<T>;
+}
+
+impl<T, D> EllipticEnergy<T, D> for LinearElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_energy(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(parameters);
+        let eps = u_grad.symmetric_part();
+        mu * eps.dot(&eps) + 0.5 * lambda * eps.trace().powi(2)
+    }
+}
+
+impl<T, D> EllipticOperator<T, D> for LinearElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(parameters);
+        let eps = u_grad.symmetric_part();
+        let eps_trace = eps.trace();
+        eps * (2.0 * mu) + OMatrix::<T, D, D>::identity() * (lambda * eps_trace)
+    }
+
+    fn compute_elliptic_operator_transpose(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        // The stress is symmetric
+        self.compute_elliptic_operator(u_grad, parameters)
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticContraction<T, D> for LinearElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn contract(
+        &self,
+        _u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(parameters);
+        let I = OMatrix::<T, D, D>::identity();
+        (I * a.dot(b) + b * a.transpose()) * mu + a * b.transpose() * lambda
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        _u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(parameters);
+        let I = OMatrix::<T, D, D>::identity();
+        accumulate_symmetric_contractions(output, alpha, a, b, |a_I, b_J| {
+            (&I * a_I.dot(b_J) + b_J * a_I.transpose()) * mu + a_I * b_J.transpose() * lambda
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use crate::allocators::TriDimAllocator;
+use crate::nalgebra::{DefaultAllocator, OMatrix, OVector};
+use crate::{Real, SmallDim};
+
+/// A bilinear form coupling a trial field with a (possibly different) test field.
+///
+/// Mixed formulations, such as velocity-pressure formulations of Stokes flow, give rise to
+/// bilinear forms $b(u, v)$ where the trial function $u$ and the test function $v$ belong
+/// to different finite element spaces with possibly different solution dimensions. We consider
+/// forms that can be written as
+/// <div>$$
+/// b(u, v) = \int_{\Omega} \mathcal{B}(u, \nabla u, v, \nabla v) \enspace \mathrm{d} x,
+/// $$</div>
+/// where $\mathcal{B}$ is linear in both $(u, \nabla u)$ and $(v, \nabla v)$. For a test basis
+/// function $\phi$ and a trial basis function $\psi$, the operator computes the
+/// $s_v \times s_u$ block
+/// <div>$$
+/// \mathcal{B}_{ij} = \mathcal{B}(\psi \vec e_j, \vec e_j \otimes \nabla \psi,
+///     \phi \vec e_i, \vec e_i \otimes \nabla \phi),
+/// $$</div>
+/// where $s_v$ and $s_u$ are the solution dimensions of the test and trial fields, respectively.
+/// The element matrices are assembled with
+/// [`ElementMixedAssembler`](crate::assembly::local::ElementMixedAssembler).
+pub trait MixedOperator<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, GeometryDim, Self::TestDim, Self::TrialDim>,
+{
+    /// The solution dimension $s_v$ of the test field.
+    type TestDim: SmallDim;
+    /// The solution dimension $s_u$ of the trial field.
+    type TrialDim: SmallDim;
+
+    type Parameters: Default + Clone + 'static;
+
+    /// Computes the coupling block for a test basis function with the given value and gradient
+    /// and a trial basis function with the given value and gradient.
+    fn compute_coupling(
+        &self,
+        test_value: T,
+        test_gradient: &OVector<T, GeometryDim>,
+        trial_value: T,
+        trial_gradient: &OVector<T, GeometryDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::TestDim, Self::TrialDim>;
+}
@@ -0,0 +1,356 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::operators::solid::{accumulate_symmetric_contractions, LameParameters};
+use crate::assembly::operators::{
+    AdvectionDiffusionReaction, AdvectionDiffusionReactionCoefficients, EllipticContraction, EllipticEnergy,
+    EllipticOperator, Operator,
+};
+use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OPoint, OVector, U1};
+use crate::{Real, SmallDim, Symmetry};
+use numeric_literals::replace_float_literals;
+
+/// The degradation function $g(d) = (1 - d)^2 + k$ of the phase-field $d$.
+///
+/// The small residual stiffness $k \geq 0$ keeps the elastic problem well-posed in fully
+/// broken regions.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn degradation_function<T: Real>(phase_field: T, residual_stiffness: T) -> T {
+    (1.0 - phase_field).powi(2) + residual_stiffness
+}
+
+/// The split of the strain energy density into a part that drives and is degraded by the
+/// fracture, and a part that is not.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum EnergySplit {
+    /// The whole strain energy is degraded, so that cracks may also form under compression.
+    #[default]
+    None,
+    /// The volumetric-deviatoric split of Amor et al. (2009). Only the deviatoric part and
+    /// the volumetric part under expansion are degraded, which prevents crack formation and
+    /// interpenetration of the crack faces under compression.
+    ///
+    /// The split is performed in the dimension of the problem, i.e. with the bulk modulus
+    /// $K = \lambda + 2 \mu / d$ and the deviatoric strain
+    /// $\vec \varepsilon_{\text{dev}} = \vec \varepsilon - \frac{1}{d} \tr(\vec \varepsilon) \vec I$.
+    VolumetricDeviatoric,
+}
+
+/// Parameters of the [`DegradedElasticityOperator`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DegradedElasticityParameters<T> {
+    pub lame: LameParameters<T>,
+    /// The value $g(d)$ of the [degradation function](degradation_function).
+    pub degradation: T,
+}
+
+impl<T: Real> Default for DegradedElasticityParameters<T> {
+    fn default() -> Self {
+        Self {
+            lame: LameParameters::default(),
+            degradation: T::one(),
+        }
+    }
+}
+
+/// Small-strain linear elasticity with an energy degraded by a phase-field.
+///
+/// With the strain energy density split into $\psi = \psi^+ + \psi^-$ according to the
+/// [energy split](EnergySplit), the degraded energy density reads
+/// $$
+/// \psi_g(\vec \varepsilon) = g(d) \\, \psi^+(\vec \varepsilon) + \psi^-(\vec \varepsilon),
+/// $$
+/// where the value of the [degradation function](degradation_function) $g(d)$ is given as a
+/// parameter at each quadrature point. With the volumetric-deviatoric split,
+/// <div>$$
+/// \psi^+ = \frac{K}{2} \langle \tr \vec \varepsilon \rangle_+^2
+///     + \mu \\, \vec \varepsilon_{\text{dev}} : \vec \varepsilon_{\text{dev}},
+/// \qquad
+/// \psi^- = \frac{K}{2} \langle \tr \vec \varepsilon \rangle_-^2,
+/// $$</div>
+/// where $\langle \cdot \rangle_\pm$ denote the positive and negative parts. The stress is then
+/// piecewise linear in the strain, and the operator is nonlinear only through the sign of
+/// $\tr \vec \varepsilon$. The tensile energy density $\psi^+$, which drives the evolution of
+/// the phase-field, is available from
+/// [`tensile_energy_density`](Self::tensile_energy_density).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DegradedElasticityOperator {
+    split: EnergySplit,
+}
+
+impl DegradedElasticityOperator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_split(split: EnergySplit) -> Self {
+        Self { split }
+    }
+
+    pub fn split(&self) -> EnergySplit {
+        self.split
+    }
+
+    /// Computes the tensile and compressive parts $(\psi^+, \psi^-)$ of the undegraded strain
+    /// energy density for the given strain.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn split_energy_density<T, D>(&self, strain: &OMatrix<T, D, D>, lame: &LameParameters<T>) -> (T, T)
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: BiDimAllocator<T, D, D>,
+    {
+        let &LameParameters { mu, lambda } = lame;
+        let trace = strain.trace();
+        let strain_squared = strain.norm_squared();
+        match self.split {
+            EnergySplit::None => (mu * strain_squared + 0.5 * lambda * trace * trace, 0.0),
+            EnergySplit::VolumetricDeviatoric => {
+                let d = T::from_usize(D::dim()).unwrap();
+                let bulk = lambda + 2.0 * mu / d;
+                let deviatoric_squared = strain_squared - trace * trace / d;
+                let positive = trace.max(0.0);
+                let negative = trace.min(0.0);
+                (
+                    0.5 * bulk * positive * positive + mu * deviatoric_squared,
+                    0.5 * bulk * negative * negative,
+                )
+            }
+        }
+    }
+
+    /// Computes the tensile energy density $\psi^+$ for the given displacement gradient.
+    pub fn tensile_energy_density<T, D>(&self, u_grad: &OMatrix<T, D, D>, lame: &LameParameters<T>) -> T
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: BiDimAllocator<T, D, D>,
+    {
+        self.split_energy_density(&u_grad.symmetric_part(), lame).0
+    }
+
+    /// The Lamé parameters $(\mu', \lambda')$ such that the degraded stress is
+    /// $\vec \sigma = 2 \mu' \vec \varepsilon + \lambda' \tr(\vec \varepsilon) \vec I$ for a
+    /// strain with the given trace.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn effective_lame_parameters<T: Real, D: DimName>(
+        &self,
+        trace: T,
+        parameters: &DegradedElasticityParameters<T>,
+    ) -> LameParameters<T> {
+        let DegradedElasticityParameters {
+            lame: LameParameters { mu, lambda },
+            degradation: g,
+        } = *parameters;
+        match self.split {
+            EnergySplit::None => LameParameters {
+                mu: g * mu,
+                lambda: g * lambda,
+            },
+            EnergySplit::VolumetricDeviatoric => {
+                let d = T::from_usize(D::dim()).unwrap();
+                let bulk = lambda + 2.0 * mu / d;
+                let effective_bulk = if trace > 0.0 { g * bulk } else { bulk };
+                LameParameters {
+                    mu: g * mu,
+                    lambda: effective_bulk - 2.0 * g * mu / d,
+                }
+            }
+        }
+    }
+}
+
+impl<T, D> Operator<T, D> for DegradedElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+{
+    type SolutionDim = D;
+    type Parameters = DegradedElasticityParameters<T>;
+}
+
+impl<T, D> EllipticEnergy<T, D> for DegradedElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn compute_energy(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let (tensile, compressive) = self.split_energy_density(&u_grad.symmetric_part(), &parameters.lame);
+        parameters.degradation * tensile + compressive
+    }
+}
+
+impl<T, D> EllipticOperator<T, D> for DegradedElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        let eps = u_grad.symmetric_part();
+        let eps_trace = eps.trace();
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(eps_trace, parameters);
+        eps * (2.0 * mu) + OMatrix::<T, D, D>::identity() * (lambda * eps_trace)
+    }
+
+    fn compute_elliptic_operator_transpose(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        // The stress is symmetric
+        self.compute_elliptic_operator(u_grad, parameters)
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticContraction<T, D> for DegradedElasticityOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn contract(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(u_grad.trace(), parameters);
+        let I = OMatrix::<T, D, D>::identity();
+        (I * a.dot(b) + b * a.transpose()) * mu + a * b.transpose() * lambda
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let LameParameters { mu, lambda } = self.effective_lame_parameters::<T, D>(u_grad.trace(), parameters);
+        let I = OMatrix::<T, D, D>::identity();
+        accumulate_symmetric_contractions(output, alpha, a, b, |a_I, b_J| {
+            (&I * a_I.dot(b_J) + b_J * a_I.transpose()) * mu + a_I * b_J.transpose() * lambda
+        })
+    }
+}
+
+/// The regularization model of the crack surface energy.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PhaseFieldModel {
+    /// The AT2 model with the crack density $\frac{1}{2 l} (d^2 + l^2 |\nabla d|^2)$. The
+    /// phase-field evolves as soon as the material is loaded.
+    #[default]
+    At2,
+    /// The AT1 model with the crack density $\frac{3}{8 l} (d + l^2 |\nabla d|^2)$, which has
+    /// a purely elastic stage before the onset of damage. The phase-field must be explicitly
+    /// constrained to $d \geq 0$.
+    At1,
+}
+
+/// Parameters of the [`PhaseFieldOperator`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhaseFieldParameters<T> {
+    /// The critical energy release rate $G_c$.
+    pub fracture_toughness: T,
+    /// The regularization length $l$.
+    pub length_scale: T,
+    /// The history field $\mathcal{H}$, i.e. the maximum tensile energy density $\psi^+$
+    /// attained so far.
+    pub history: T,
+}
+
+impl<T: Real> Default for PhaseFieldParameters<T> {
+    fn default() -> Self {
+        Self {
+            fracture_toughness: T::one(),
+            length_scale: T::one(),
+            history: T::zero(),
+        }
+    }
+}
+
+/// The evolution equation of the phase-field $d$ for a given history field.
+///
+/// Minimizing the sum of the degraded elastic energy $g(d) \mathcal{H}$ and the regularized
+/// crack surface energy of the [phase-field model](PhaseFieldModel) with respect to $d$ leads to
+/// the linear diffusion-reaction equations
+/// <div>$$
+/// \begin{aligned}
+/// - G_c l \\, \Delta d + \left( \frac{G_c}{l} + 2 \mathcal{H} \right) d &= 2 \mathcal{H}
+///     && \text{(AT2)}, \\\\
+/// - \frac{3}{4} G_c l \\, \Delta d + 2 \mathcal{H} d &= 2 \mathcal{H} - \frac{3 G_c}{8 l}
+///     && \text{(AT1)},
+/// \end{aligned}
+/// $$</div>
+/// with homogeneous Neumann boundary conditions. Here the history field
+/// $\mathcal{H} = \max_{s \leq t} \psi^+(\vec \varepsilon(s))$ ensures the irreversibility of the
+/// crack evolution. The equations are assembled with
+/// [`ElementAdvectionDiffusionReactionAssembler`](crate::assembly::local::ElementAdvectionDiffusionReactionAssembler).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhaseFieldOperator {
+    model: PhaseFieldModel,
+}
+
+impl PhaseFieldOperator {
+    pub fn new(model: PhaseFieldModel) -> Self {
+        Self { model }
+    }
+
+    pub fn model(&self) -> PhaseFieldModel {
+        self.model
+    }
+}
+
+impl<T, D> Operator<T, D> for PhaseFieldOperator
+where
+    T: Real,
+    D: SmallDim,
+{
+    type SolutionDim = U1;
+    type Parameters = PhaseFieldParameters<T>;
+}
+
+impl<T, D> AdvectionDiffusionReaction<T, D> for PhaseFieldOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn evaluate_coefficients(
+        &self,
+        _coords: &OPoint<T, D>,
+        parameters: &Self::Parameters,
+    ) -> AdvectionDiffusionReactionCoefficients<T, D> {
+        let &PhaseFieldParameters {
+            fracture_toughness: g_c,
+            length_scale: l,
+            history: h,
+        } = parameters;
+        let velocity = OVector::<T, D>::zeros();
+        match self.model {
+            PhaseFieldModel::At2 => {
+                AdvectionDiffusionReactionCoefficients::new(velocity, g_c * l, g_c / l + 2.0 * h, 2.0 * h)
+            }
+            PhaseFieldModel::At1 => AdvectionDiffusionReactionCoefficients::new(
+                velocity,
+                0.75 * g_c * l,
+                2.0 * h,
+                2.0 * h - 3.0 * g_c / (8.0 * l),
+            ),
+        }
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
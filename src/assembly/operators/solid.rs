@@ -0,0 +1,562 @@
+//! Hyperelastic material models for solid mechanics.
+//!
+//! The operators in this module assume a **displacement-based** total Lagrangian formulation,
+//! in which the solution variable is the displacement $\vec u(\vec X) = \vec x(\vec X) - \vec X$
+//! and the finite element space describes the reference (undeformed) configuration. The
+//! deformation gradient is then given by $\vec F = \vec I + (\nabla \vec u)^T$, and each
+//! model is defined by its strain energy density $\psi(\vec F)$. The operators relate to the
+//! usual quantities in continuum mechanics by
+//!
+//! $$
+//! \begin{aligned}
+//! \psi(\nabla \vec u) &= \psi(\vec F), \\\\
+//! g(\nabla \vec u) &= \vec P^T(\vec F), \\\\
+//! \mathcal{C}_g(\nabla \vec u, \vec a, \vec b) &= \mathcal{C}\_{\vec P}(\vec F, \vec a, \vec b)
+//!     := a_k \pd{P_{ik}}{F_{jm}} (\vec F) \\, b_m \enspace \vec e_i \otimes \vec e_j,
+//! \end{aligned}
+//! $$
+//!
+//! where $\vec P = \pd{\psi}{\vec F}$ is the first Piola-Kirchhoff stress tensor.
+//! All models are parametrized by [Lamé parameters](LameParameters).
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, DimSub, OMatrix, OVector, U1};
+use crate::util::rotation_svd;
+use crate::{Real, SmallDim, Symmetry};
+use numeric_literals::replace_float_literals;
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+/// Lamé parameters $\mu$ and $\lambda$ of an isotropic material.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LameParameters<T> {
+    pub mu: T,
+    pub lambda: T,
+}
+
+impl<T: Real> Default for LameParameters<T> {
+    fn default() -> Self {
+        Self {
+            mu: T::zero(),
+            lambda: T::zero(),
+        }
+    }
+}
+
+impl<T: Real> LameParameters<T> {
+    /// Lamé parameters corresponding to the given Young's modulus $E$ and Poisson's ratio $\nu$.
+    ///
+    /// The parameters are given by
+    /// $$
+    /// \mu = \frac{E}{2 (1 + \nu)}, \qquad \lambda = \frac{E \nu}{(1 + \nu)(1 - 2 \nu)}.
+    /// $$
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn from_young_poisson(young: T, poisson: T) -> Self {
+        let mu = 0.5 * young / (1.0 + poisson);
+        let lambda = young * poisson / ((1.0 + poisson) * (1.0 - 2.0 * poisson));
+        Self { mu, lambda }
+    }
+}
+
+/// Compute the deformation gradient $\vec F = \vec I + (\nabla \vec u)^T$.
+#[allow(non_snake_case)]
+fn deformation_gradient<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    OMatrix::<T, D, D>::identity() + u_grad.transpose()
+}
+
+/// Accumulates $C_{IJ} \gets C_{IJ} + \alpha \mathcal{C}(\vec a_I, \vec b_J)$ for the block upper
+/// triangle $I \leq J$ of a symmetric contraction.
+///
+/// See [`EllipticContraction::accumulate_contractions_into`] for the meaning of the arguments.
+#[allow(non_snake_case)]
+pub(super) fn accumulate_symmetric_contractions<T, D>(
+    mut output: DMatrixViewMut<T>,
+    alpha: T,
+    a: DVectorView<T>,
+    b: DVectorView<T>,
+    mut contraction: impl FnMut(&OVector<T, D>, &OVector<T, D>) -> OMatrix<T, D, D>,
+) where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let d = D::dim();
+    assert!(
+        a.len().is_multiple_of(d),
+        "Dimension of a must be divisible by d (GeometryDim)"
+    );
+    assert!(
+        b.len().is_multiple_of(d),
+        "Dimension of b must be divisible by d (GeometryDim)"
+    );
+    let M = a.len() / d;
+    let N = b.len() / d;
+    assert_eq!(
+        output.nrows(),
+        d * M,
+        "Number of rows in output matrix is not consistent with a"
+    );
+    assert_eq!(
+        output.ncols(),
+        d * N,
+        "Number of columns in output matrix is not consistent with b"
+    );
+
+    for J in 0..N {
+        let b_J = b.rows_generic(d * J, D::name()).clone_owned();
+        for I in 0..min(J + 1, M) {
+            let a_I = a.rows_generic(d * I, D::name()).clone_owned();
+            let mut c_IJ = output.generic_view_mut((d * I, d * J), (D::name(), D::name()));
+            let c = contraction(&a_I, &b_J);
+            c_IJ.zip_apply(&c, |c_ij, x| *c_ij += alpha * x);
+        }
+    }
+}
+
+/// The compressible Neo-Hookean material model.
+///
+/// The strain energy density is given by
+/// $$
+/// \psi(\vec F) = \frac{\mu}{2}(I_C - d) - \mu \log J + \frac{\lambda}{2}(\log J)^2,
+/// $$
+/// where $J = \det \vec F$ and $I_C = \tr{\vec F^T \vec F}$ is the first invariant of the right
+/// Cauchy-Green tensor. The Piola-Kirchhoff stress tensor is
+/// $$
+/// \vec P = \mu (\vec F - \vec F^{-T}) + \lambda (\log J) \vec F^{-T}.
+/// $$
+/// With $\alpha = -\mu + \lambda \log J$, the contraction operator is given by
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b)
+///   = \lambda (\vec F^{-T} \vec a) \otimes (\vec F^{-T} \vec b)
+///    - \alpha (\vec F^{-T} \vec b) \otimes (\vec F^{-T} \vec a)
+///    + \mu (\vec a \cdot \vec b) \vec I.
+/// $$</div>
+///
+/// The energy is only well-defined for $J > 0$. For inverted configurations, the energy is
+/// infinite, and the operator and its contraction evaluate to `NaN`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NeoHookeanOperator;
+
+/// The Saint Venant-Kirchhoff material model.
+///
+/// The strain energy density is given by
+/// <div>$$
+/// \psi(\vec F) = \mu \vec E : \vec E + \frac{\lambda}{2} \operatorname{tr}^2(\vec E),
+/// $$</div>
+/// where $\vec E = \frac{1}{2} (\vec F^T \vec F - \vec I)$ is the Green strain tensor. The
+/// Piola-Kirchhoff stress tensor is
+/// <div>$$
+/// \vec P = \vec F (2 \mu \vec E + \lambda \tr{\vec E} \vec I)
+/// $$</div>
+/// and the contraction operator is
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) =
+///     \left[ 2 \mu \vec a^T \vec E \vec b + \lambda \tr{\vec E} (\vec a \cdot \vec b) \right] \vec I
+///     + \mu (\vec F \vec b) (\vec F \vec a)^T
+///     + \lambda (\vec F \vec a) (\vec F \vec b)^T
+///     + \mu (\vec a \cdot \vec b) \vec F \vec F^T.
+/// $$</div>
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StVKOperator;
+
+/// The corotated linear elastic material model.
+///
+/// Let $\vec F = \vec R \vec S$ denote the polar decomposition of the deformation gradient into
+/// a rotation $\vec R$ and a symmetric stretch $\vec S$. The corotated linear model applies
+/// linear elasticity to the corotated strain $\vec \epsilon = \vec S - \vec I$, with the strain
+/// energy density
+/// <div>$$
+/// \psi(\vec F) = \mu \vec \epsilon : \vec \epsilon + \frac{\lambda}{2} \operatorname{tr}^2(\vec \epsilon).
+/// $$</div>
+/// The Piola-Kirchhoff stress tensor is
+/// <div>$$
+/// \vec P = \vec R (2 \mu \vec \epsilon + \lambda \tr{\vec \epsilon} \vec I).
+/// $$</div>
+/// The contraction operator is the contraction of linear elasticity rotated by $\vec R$,
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) = \vec R \left[
+///     \mu \left( (\vec a \cdot \vec b) \vec I + \vec b \vec a^T \right)
+///     + \lambda \vec a \vec b^T \right] \vec R^T,
+/// $$</div>
+/// which neglects the dependence of $\vec R$ on $\vec F$. It is therefore only an approximation
+/// of the exact derivative of the stress, but it is always positive semi-definite and coincides
+/// with the exact derivative for pure rotations, which makes it a popular choice for
+/// simulations with large rotations but small strains.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CorotatedLinearOperator;
+
+macro_rules! impl_solid_operator {
+    ($operator:ty) => {
+        impl<T, D> Operator<T, D> for $operator
+        where
+            T: Real,
+            D: SmallDim,
+        {
+            type SolutionDim = D;
+            type Parameters = LameParameters<T>;
+        }
+    };
+}
+
+impl_solid_operator!(NeoHookeanOperator);
+impl_solid_operator!(StVKOperator);
+impl_solid_operator!(CorotatedLinearOperator);
+
+/// Computes $\log J$ and $\vec F^{-T}$, or `None` if $J \leq 0$.
+#[allow(non_snake_case)]
+fn neo_hookean_kinematics<T, D>(F: &OMatrix<T, D, D>) -> Option<(T, OMatrix<T, D, D>)>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let J = F.determinant();
+    if J > T::zero() {
+        let F_inv = F.clone().try_inverse()?;
+        Some((J.ln(), F_inv.transpose()))
+    } else {
+        None
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticEnergy<T, D> for NeoHookeanOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_energy(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let &LameParameters { mu, lambda } = parameters;
+        let J = deformation_gradient(u_grad).determinant();
+        if J > T::zero() {
+            let logJ = J.ln();
+            // I_C - d = tr(F^T F - I) = 2 tr(grad u) + |grad u|^2, which avoids cancellation
+            // for small displacement gradients
+            let tr_E = u_grad.trace() + 0.5 * u_grad.norm_squared();
+            mu * tr_E - mu * logJ + 0.5 * lambda * logJ * logJ
+        } else {
+            T::from_f64(f64::INFINITY).expect("T must be able to represent infinity")
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticOperator<T, D> for NeoHookeanOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        self.compute_elliptic_operator_transpose(u_grad, parameters)
+            .transpose()
+    }
+
+    fn compute_elliptic_operator_transpose(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        match neo_hookean_kinematics(&F) {
+            Some((logJ, F_inv_T)) => (F - &F_inv_T) * mu + F_inv_T * (lambda * logJ),
+            None => OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap()),
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticContraction<T, D> for NeoHookeanOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn contract(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        match neo_hookean_kinematics(&F) {
+            Some((logJ, F_inv_T)) => {
+                let alpha = -mu + lambda * logJ;
+                let F_inv_T_a = &F_inv_T * a;
+                let F_inv_T_b = &F_inv_T * b;
+                &F_inv_T_a * F_inv_T_b.transpose() * lambda - F_inv_T_b * F_inv_T_a.transpose() * alpha
+                    + OMatrix::<T, D, D>::identity() * (mu * a.dot(b))
+            }
+            None => OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap()),
+        }
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        let (logJ, F_inv_T) = neo_hookean_kinematics(&F).unwrap_or_else(|| {
+            let nan = T::from_f64(f64::NAN).unwrap();
+            (nan, OMatrix::<T, D, D>::repeat(nan))
+        });
+        let alpha_nh = -mu + lambda * logJ;
+        let I = OMatrix::<T, D, D>::identity();
+
+        accumulate_symmetric_contractions(output, alpha, a, b, |a_I, b_J| {
+            let F_inv_T_a = &F_inv_T * a_I;
+            let F_inv_T_b = &F_inv_T * b_J;
+            &F_inv_T_a * F_inv_T_b.transpose() * lambda - F_inv_T_b * F_inv_T_a.transpose() * alpha_nh
+                + &I * (mu * a_I.dot(b_J))
+        })
+    }
+}
+
+/// Computes the Green strain tensor $\vec E = \frac{1}{2}(\nabla \vec u + \nabla \vec u^T
+/// + \nabla \vec u \nabla \vec u^T)$ directly from the displacement gradient.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn green_strain_tensor<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    (u_grad + u_grad.transpose() + u_grad * u_grad.transpose()) * 0.5
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticEnergy<T, D> for StVKOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_energy(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let &LameParameters { mu, lambda } = parameters;
+        let E = green_strain_tensor(u_grad);
+        mu * E.dot(&E) + 0.5 * lambda * E.trace().powi(2)
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticOperator<T, D> for StVKOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        self.compute_elliptic_operator_transpose(u_grad, parameters)
+            .transpose()
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_elliptic_operator_transpose(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        let E = green_strain_tensor(u_grad);
+        let S = &E * (2.0 * mu) + OMatrix::<T, D, D>::identity() * (lambda * E.trace());
+        F * S
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticContraction<T, D> for StVKOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn contract(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        let E = green_strain_tensor(u_grad);
+        let a_dot_b = a.dot(b);
+        let Fa = &F * a;
+        let Fb = &F * b;
+
+        OMatrix::<T, D, D>::identity() * (2.0 * mu * a.dot(&(&E * b)) + lambda * E.trace() * a_dot_b)
+            + &Fb * Fa.transpose() * mu
+            + Fa * Fb.transpose() * lambda
+            + &F * F.transpose() * (mu * a_dot_b)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient(u_grad);
+        let E = green_strain_tensor(u_grad);
+        let E_trace = E.trace();
+        let FFt = &F * F.transpose();
+        let I = OMatrix::<T, D, D>::identity();
+
+        accumulate_symmetric_contractions(output, alpha, a, b, |a_I, b_J| {
+            let a_dot_b = a_I.dot(b_J);
+            let Fa = &F * a_I;
+            let Fb = &F * b_J;
+            &I * (2.0 * mu * a_I.dot(&(&E * b_J)) + lambda * E_trace * a_dot_b)
+                + &Fb * Fa.transpose() * mu
+                + Fa * Fb.transpose() * lambda
+                + &FFt * (mu * a_dot_b)
+        })
+    }
+}
+
+/// Computes the rotation $\vec R$ in the polar decomposition $\vec F = \vec R \vec S$ and the
+/// corotated strain $\vec \epsilon = \vec S - \vec I$.
+#[allow(non_snake_case)]
+fn corotated_kinematics<T, D>(u_grad: &OMatrix<T, D, D>) -> (OMatrix<T, D, D>, OMatrix<T, D, D>)
+where
+    T: Real,
+    D: SmallDim + DimSub<U1>,
+    DefaultAllocator: BiDimAllocator<T, D, D>
+        + Allocator<T, <D as DimSub<U1>>::Output>
+        + Allocator<(usize, usize), D>
+        + Allocator<(T, usize), D>,
+{
+    let F = deformation_gradient(u_grad);
+    let (U, _, V_T) = rotation_svd(&F);
+    let R = U * V_T;
+    // S = R^T F is symmetric up to round-off
+    let eps = (R.transpose() * F).symmetric_part() - OMatrix::<T, D, D>::identity();
+    (R, eps)
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticEnergy<T, D> for CorotatedLinearOperator
+where
+    T: Real,
+    D: SmallDim + DimSub<U1>,
+    DefaultAllocator: BiDimAllocator<T, D, D>
+        + Allocator<T, <D as DimSub<U1>>::Output>
+        + Allocator<(usize, usize), D>
+        + Allocator<(T, usize), D>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_energy(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let &LameParameters { mu, lambda } = parameters;
+        let (_, eps) = corotated_kinematics(u_grad);
+        mu * eps.dot(&eps) + 0.5 * lambda * eps.trace().powi(2)
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticOperator<T, D> for CorotatedLinearOperator
+where
+    T: Real,
+    D: SmallDim + DimSub<U1>,
+    DefaultAllocator: BiDimAllocator<T, D, D>
+        + Allocator<T, <D as DimSub<U1>>::Output>
+        + Allocator<(usize, usize), D>
+        + Allocator<(T, usize), D>,
+{
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        self.compute_elliptic_operator_transpose(u_grad, parameters)
+            .transpose()
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_elliptic_operator_transpose(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let (R, eps) = corotated_kinematics(u_grad);
+        let eps_trace = eps.trace();
+        R * (eps * (2.0 * mu) + OMatrix::<T, D, D>::identity() * (lambda * eps_trace))
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D> EllipticContraction<T, D> for CorotatedLinearOperator
+where
+    T: Real,
+    D: SmallDim + DimSub<U1>,
+    DefaultAllocator: BiDimAllocator<T, D, D>
+        + Allocator<T, <D as DimSub<U1>>::Output>
+        + Allocator<(usize, usize), D>
+        + Allocator<(T, usize), D>,
+{
+    fn contract(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let (R, _) = corotated_kinematics(u_grad);
+        let I = OMatrix::<T, D, D>::identity();
+        let c = (I * a.dot(b) + b * a.transpose()) * mu + a * b.transpose() * lambda;
+        &R * c * R.transpose()
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    fn accumulate_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let &LameParameters { mu, lambda } = parameters;
+        let (R, _) = corotated_kinematics(u_grad);
+        let I = OMatrix::<T, D, D>::identity();
+
+        accumulate_symmetric_contractions(output, alpha, a, b, |a_I, b_J| {
+            let c = (&I * a_I.dot(b_J) + b_J * a_I.transpose()) * mu + a_I * b_J.transpose() * lambda;
+            &R * c * R.transpose()
+        })
+    }
+}
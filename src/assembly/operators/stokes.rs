@@ -0,0 +1,108 @@
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
+use crate::assembly::operators::{EllipticContraction, EllipticOperator, MixedOperator, Operator};
+use crate::nalgebra::{DefaultAllocator, OMatrix, OVector, U1};
+use crate::{Real, SmallDim, Symmetry};
+use serde::{Deserialize, Serialize};
+
+/// A wrapper type for a number that represents a (dynamic) *viscosity*.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct Viscosity<T>(pub T);
+
+impl<T: Real> Default for Viscosity<T> {
+    fn default() -> Self {
+        Self(T::one())
+    }
+}
+
+/// The viscous operator of Stokes flow.
+///
+/// For a velocity field $\vec u$ and viscosity $\mu$, the operator corresponds to the bilinear form
+/// <div>$$
+/// a(\vec u, \vec v) = \int_{\Omega} \mu \nabla \vec u : \nabla \vec v \enspace \mathrm{d} x,
+/// $$</div>
+/// i.e. the weak form of the vector Laplacian $- \mu \Delta \vec u$. For divergence-free
+/// velocities with Dirichlet boundary conditions, this is equivalent to the symmetric
+/// gradient form $2 \mu \, \epsilon(\vec u) : \epsilon(\vec v)$.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ViscousOperator;
+
+impl<T, D> Operator<T, D> for ViscousOperator
+where
+    T: Real,
+    D: SmallDim,
+{
+    type SolutionDim = D;
+    type Parameters = Viscosity<T>;
+}
+
+impl<T, D> EllipticOperator<T, D> for ViscousOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn compute_elliptic_operator(&self, u_grad: &OMatrix<T, D, D>, viscosity: &Viscosity<T>) -> OMatrix<T, D, D> {
+        u_grad * viscosity.0
+    }
+}
+
+impl<T, D> EllipticContraction<T, D> for ViscousOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn contract(
+        &self,
+        _u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        viscosity: &Viscosity<T>,
+    ) -> OMatrix<T, D, D> {
+        OMatrix::<T, D, D>::identity() * (a.dot(b) * viscosity.0)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
+
+/// The (negative) divergence operator coupling pressure and velocity in Stokes flow.
+///
+/// For a velocity trial function $\vec u$ and a pressure test function $q$, the operator
+/// corresponds to the bilinear form
+/// <div>$$
+/// b(\vec u, q) = - \int_{\Omega} q \, \nabla \cdot \vec u \enspace \mathrm{d} x.
+/// $$</div>
+/// With $\vec A$ the matrix of the [`ViscousOperator`] and $\vec B$ the matrix of this operator,
+/// the discrete Stokes system takes the symmetric saddle point form
+/// <div>$$
+/// \begin{pmatrix} \vec A & \vec B^T \\\\ \vec B & \vec 0 \end{pmatrix}
+/// \begin{pmatrix} \vec u \\\\ \vec p \end{pmatrix}
+/// = \begin{pmatrix} \vec f \\\\ \vec 0 \end{pmatrix}.
+/// $$</div>
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DivergenceOperator;
+
+impl<T, D> MixedOperator<T, D> for DivergenceOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, D, U1, D>,
+{
+    type TestDim = U1;
+    type TrialDim = D;
+    type Parameters = ();
+
+    fn compute_coupling(
+        &self,
+        test_value: T,
+        _test_gradient: &OVector<T, D>,
+        _trial_value: T,
+        trial_gradient: &OVector<T, D>,
+        _parameters: &(),
+    ) -> OMatrix<T, U1, D> {
+        -trial_gradient.transpose() * test_value
+    }
+}
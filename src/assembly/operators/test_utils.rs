@@ -0,0 +1,373 @@
+//! Utilities for verifying the consistency of operator implementations.
+//!
+//! The [elliptic operator](EllipticOperator), its [energy](EllipticEnergy) and its
+//! [contraction](EllipticContraction) are usually implemented separately, and must be
+//! consistent with each other: the operator must be the derivative of the energy, and the
+//! contraction must encode the derivative of the operator. Inconsistencies are easy to introduce
+//! and typically manifest as poor convergence of Newton's method rather than as outright errors.
+//! The [`OperatorConsistencyChecker`] compares the implementations against central finite
+//! differences at a number of pseudo-random states:
+//!
+//! ```
+//! # use fenris::assembly::operators::solid::LameParameters;
+//! # use fenris::assembly::operators::test_utils::OperatorConsistencyChecker;
+//! # use fenris::assembly::operators::LinearElasticityOperator;
+//! # use fenris::nalgebra::U3;
+//! let parameters = LameParameters { mu: 2.0, lambda: 5.0 };
+//! let checker = OperatorConsistencyChecker::<f64>::new();
+//! checker
+//!     .check_all::<U3, _>(&LinearElasticityOperator::new(), &parameters)
+//!     .unwrap();
+//! ```
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DMatrix, DVector, DefaultAllocator, Dim, DimName, OMatrix, OVector};
+use crate::{Real, SmallDim, Symmetry};
+use eyre::eyre;
+use std::fmt::Display;
+
+/// Verifies elliptic operators, energies and contractions with finite differences.
+///
+/// The checks are performed at a number of pseudo-random gradients $\nabla u$, whose entries are
+/// drawn uniformly from $[-s, s]$ for the [gradient scale](Self::with_gradient_scale) $s$. The
+/// default scale is small enough that $I + \nabla u$ is invertible with positive determinant,
+/// so that the states are admissible for typical hyperelastic materials. The samples are
+/// deterministic for a given [seed](Self::with_seed).
+///
+/// Derivatives are approximated by central finite differences with a step size that is
+/// proportional to the [configured step size](Self::with_step_size) and the magnitude of
+/// $\nabla u$. A comparison fails if the largest absolute deviation exceeds the
+/// [tolerance](Self::with_tolerance) times $1 + m$, where $m$ is the largest absolute entry of
+/// the compared quantities, so that the tolerance is relative for operators of large magnitude.
+#[derive(Debug, Clone)]
+pub struct OperatorConsistencyChecker<T> {
+    num_samples: usize,
+    gradient_scale: T,
+    step_size: T,
+    tolerance: T,
+    seed: u64,
+}
+
+impl<T: Real> Default for OperatorConsistencyChecker<T> {
+    fn default() -> Self {
+        Self {
+            num_samples: 10,
+            gradient_scale: T::from_f64(0.1).unwrap(),
+            step_size: T::from_f64(1e-6).unwrap(),
+            tolerance: T::from_f64(1e-6).unwrap(),
+            seed: 0x5EED,
+        }
+    }
+}
+
+impl<T: Real> OperatorConsistencyChecker<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of pseudo-random states at which the checks are performed.
+    pub fn with_num_samples(self, num_samples: usize) -> Self {
+        Self { num_samples, ..self }
+    }
+
+    /// The bound on the magnitude of the entries of the sampled gradients.
+    pub fn with_gradient_scale(self, gradient_scale: T) -> Self {
+        Self { gradient_scale, ..self }
+    }
+
+    /// The relative step size used for the finite difference approximations.
+    pub fn with_step_size(self, step_size: T) -> Self {
+        Self { step_size, ..self }
+    }
+
+    /// The tolerance for the deviation from the finite difference approximations, relative to
+    /// the magnitude of the compared quantities.
+    pub fn with_tolerance(self, tolerance: T) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    pub fn tolerance(&self) -> T {
+        self.tolerance
+    }
+
+    /// Performs all checks that are applicable to an operator with an energy and a contraction.
+    ///
+    /// See [`check_energy`](Self::check_energy) and
+    /// [`check_contraction`](Self::check_contraction).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency that is found.
+    pub fn check_all<D, Op>(&self, operator: &Op, parameters: &Op::Parameters) -> eyre::Result<()>
+    where
+        D: SmallDim,
+        Op: EllipticEnergy<T, D> + EllipticOperator<T, D> + EllipticContraction<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        self.check_energy(operator, parameters)?;
+        self.check_contraction(operator, parameters)
+    }
+
+    /// Checks that the elliptic operator is the derivative of the energy at pseudo-random states.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency that is found.
+    pub fn check_energy<D, Op>(&self, operator: &Op, parameters: &Op::Parameters) -> eyre::Result<()>
+    where
+        D: SmallDim,
+        Op: EllipticEnergy<T, D> + EllipticOperator<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let mut samples = SampleGenerator::new(self.seed);
+        for sample in 0..self.num_samples {
+            let gradient = samples.matrix(self.gradient_scale);
+            self.check_energy_at(operator, &gradient, parameters)
+                .map_err(|err| err.wrap_err(format!("Energy check failed for sample {sample}")))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the elliptic operator is the derivative of the energy at the given gradient.
+    ///
+    /// Additionally checks that
+    /// [`compute_elliptic_operator_transpose`](EllipticOperator::compute_elliptic_operator_transpose)
+    /// is consistent with the operator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency that is found.
+    pub fn check_energy_at<D, Op>(
+        &self,
+        operator: &Op,
+        gradient: &OMatrix<T, D, Op::SolutionDim>,
+        parameters: &Op::Parameters,
+    ) -> eyre::Result<()>
+    where
+        D: SmallDim,
+        Op: EllipticEnergy<T, D> + EllipticOperator<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let h = self.absolute_step_size(gradient);
+        let two = T::one() + T::one();
+        let mut perturbed = gradient.clone();
+        let mut g_fd = OMatrix::<T, D, Op::SolutionDim>::zeros();
+        for idx in 0..gradient.len() {
+            perturbed[idx] = gradient[idx] + h;
+            let psi_plus = operator.compute_energy(&perturbed, parameters);
+            perturbed[idx] = gradient[idx] - h;
+            let psi_minus = operator.compute_energy(&perturbed, parameters);
+            perturbed[idx] = gradient[idx];
+            g_fd[idx] = (psi_plus - psi_minus) / (two * h);
+        }
+
+        let g = operator.compute_elliptic_operator(gradient, parameters);
+        check_close("Elliptic operator vs. derivative of energy", &g, &g_fd, self.tolerance)?;
+        let g_t = operator.compute_elliptic_operator_transpose(gradient, parameters);
+        check_close(
+            "Transposed elliptic operator vs. transpose of elliptic operator",
+            &g_t,
+            &g.transpose(),
+            self.tolerance,
+        )
+    }
+
+    /// Checks that the contraction is the derivative of the elliptic operator at pseudo-random
+    /// states and vectors.
+    ///
+    /// See [`check_contraction_at`](Self::check_contraction_at) for the performed checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency that is found.
+    pub fn check_contraction<D, Op>(&self, operator: &Op, parameters: &Op::Parameters) -> eyre::Result<()>
+    where
+        D: SmallDim,
+        Op: EllipticOperator<T, D> + EllipticContraction<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let mut samples = SampleGenerator::new(self.seed);
+        for sample in 0..self.num_samples {
+            let gradient = samples.matrix(self.gradient_scale);
+            let a = samples.matrix(T::one());
+            let b = samples.matrix(T::one());
+            self.check_contraction_at(operator, &gradient, &a, &b, parameters)
+                .map_err(|err| err.wrap_err(format!("Contraction check failed for sample {sample}")))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the contraction is the derivative of the elliptic operator at the given
+    /// gradient, for the given vectors $a$ and $b$.
+    ///
+    /// Additionally checks that the contraction is symmetric if the operator
+    /// [claims symmetry](EllipticContraction::symmetry), and that
+    /// [`accumulate_contractions_into`](EllipticContraction::accumulate_contractions_into)
+    /// is consistent with [`contract`](EllipticContraction::contract).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency that is found.
+    pub fn check_contraction_at<D, Op>(
+        &self,
+        operator: &Op,
+        gradient: &OMatrix<T, D, Op::SolutionDim>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Op::Parameters,
+    ) -> eyre::Result<()>
+    where
+        D: SmallDim,
+        Op: EllipticOperator<T, D> + EllipticContraction<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let s = Op::SolutionDim::dim();
+        let h = self.absolute_step_size(gradient);
+        let two = T::one() + T::one();
+
+        // Column j of the contraction is a contracted with the directional derivative of g in
+        // the direction b ⊗ e_j
+        let mut c_fd = OMatrix::<T, Op::SolutionDim, Op::SolutionDim>::zeros();
+        for j in 0..s {
+            let mut direction = OMatrix::<T, D, Op::SolutionDim>::zeros();
+            direction.set_column(j, b);
+            let g_plus = operator.compute_elliptic_operator(&(gradient + &direction * h), parameters);
+            let g_minus = operator.compute_elliptic_operator(&(gradient - &direction * h), parameters);
+            let dg = (g_plus - g_minus) / (two * h);
+            c_fd.set_column(j, &dg.tr_mul(a));
+        }
+
+        let c = operator.contract(gradient, a, b, parameters);
+        check_close(
+            "Contraction vs. derivative of elliptic operator",
+            &c,
+            &c_fd,
+            self.tolerance,
+        )?;
+
+        let symmetric = matches!(operator.symmetry(), Symmetry::Symmetric);
+        if symmetric {
+            let c_swapped = operator.contract(gradient, b, a, parameters);
+            check_close(
+                "Contraction claims symmetry, but C(a, b) differs from C(b, a)^T",
+                &c,
+                &c_swapped.transpose(),
+                self.tolerance,
+            )?;
+        }
+
+        // Batched contractions with M = 2 and N = 3 vectors
+        let d = D::dim();
+        let (m, n) = (2, 3);
+        let a_stacked = DVector::from_fn(d * m, |i, _| a[i % d] * T::from_usize(i / d + 1).unwrap());
+        let b_stacked = DVector::from_fn(d * n, |i, _| b[i % d] - T::from_usize(i / d).unwrap() * a[i % d]);
+        let mut output = DMatrix::zeros(s * m, s * n);
+        operator.accumulate_contractions_into(
+            (&mut output).into(),
+            two,
+            gradient,
+            (&a_stacked).into(),
+            (&b_stacked).into(),
+            parameters,
+        );
+        let mut expected = DMatrix::zeros(s * m, s * n);
+        for block_j in 0..n {
+            // Only the block upper triangle is filled for symmetric operators
+            let num_block_rows = if symmetric { m.min(block_j + 1) } else { m };
+            for block_i in 0..num_block_rows {
+                let a_i = OVector::<T, D>::from_fn(|k, _| a_stacked[d * block_i + k]);
+                let b_j = OVector::<T, D>::from_fn(|k, _| b_stacked[d * block_j + k]);
+                let c_ij = operator.contract(gradient, &a_i, &b_j, parameters) * two;
+                expected
+                    .view_mut((s * block_i, s * block_j), (s, s))
+                    .copy_from(&c_ij);
+            }
+            for block_i in num_block_rows..m {
+                // Entries in the block lower triangle of symmetric operators are unspecified
+                let block = output
+                    .view((s * block_i, s * block_j), (s, s))
+                    .clone_owned();
+                expected
+                    .view_mut((s * block_i, s * block_j), (s, s))
+                    .copy_from(&block);
+            }
+        }
+        check_close(
+            "Batched contractions vs. individual contractions",
+            &output,
+            &expected,
+            self.tolerance,
+        )
+    }
+
+    fn absolute_step_size<R: Dim, C: Dim>(&self, gradient: &OMatrix<T, R, C>) -> T
+    where
+        DefaultAllocator: Allocator<T, R, C>,
+    {
+        self.step_size * T::max(T::one(), gradient.amax())
+    }
+}
+
+fn check_close<T, R, C>(
+    description: &str,
+    actual: &OMatrix<T, R, C>,
+    expected: &OMatrix<T, R, C>,
+    tolerance: T,
+) -> eyre::Result<()>
+where
+    T: Real + Display,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<T, R, C>,
+{
+    let magnitude = T::max(actual.amax(), expected.amax());
+    let deviation = (actual - expected).amax();
+    // Also catches NaN
+    if deviation <= tolerance * (T::one() + magnitude) {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "{description}: maximum deviation {deviation} exceeds tolerance {tolerance} \
+            (relative to magnitude {magnitude}).\nActual: {actual}\nExpected: {expected}"
+        ))
+    }
+}
+
+/// A minimal deterministic generator of pseudo-random samples (xorshift64*).
+struct SampleGenerator {
+    state: u64,
+}
+
+impl SampleGenerator {
+    fn new(seed: u64) -> Self {
+        // The state must be non-zero
+        Self {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// A sample uniformly distributed in $[-1, 1]$.
+    fn next_unit(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let bits = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        2.0 * (bits as f64 / (1u64 << 53) as f64) - 1.0
+    }
+
+    fn matrix<T: Real, R: DimName, C: DimName>(&mut self, scale: T) -> OMatrix<T, R, C>
+    where
+        DefaultAllocator: Allocator<T, R, C>,
+    {
+        OMatrix::from_fn(|_, _| T::from_f64(self.next_unit()).unwrap() * scale)
+    }
+}
@@ -0,0 +1,381 @@
+//! Patches of elements, such as all elements sharing a vertex, an edge or a face.
+//!
+//! Many algorithms operate on small groups of neighboring elements rather than on single
+//! elements. Examples include patch-based error estimators, patch smoothers for multigrid and
+//! superconvergent patch recovery. [`PatchTopology`] stores the node-element adjacency of a
+//! finite element space once, so that patches can be queried cheaply. A [`Patch`] provides
+//! a local numbering of the nodes in a patch, which can be used to assemble local
+//! patch problems.
+use crate::index::NodeIndex;
+use crate::nalgebra::DMatrix;
+use crate::space::FiniteElementConnectivity;
+use crate::util::NestedVec;
+use crate::Real;
+use nalgebra_sparse::CsrMatrix;
+
+/// Adjacency information between the nodes and elements of a finite element space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchTopology {
+    element_nodes: NestedVec<usize>,
+    node_elements: NestedVec<usize>,
+}
+
+impl PatchTopology {
+    /// Computes the node-element adjacency of the given space.
+    pub fn from_space<Space>(space: &Space) -> Self
+    where
+        Space: ?Sized + FiniteElementConnectivity,
+    {
+        let mut element_nodes = NestedVec::new();
+        let mut node_valence = vec![0; space.num_nodes()];
+        for element_index in 0..space.num_elements() {
+            let mut nodes = vec![0; space.element_node_count(element_index.into())];
+            space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+            for &node in &nodes {
+                node_valence[node] += 1;
+            }
+            element_nodes.push(&nodes);
+        }
+
+        let mut offsets = Vec::with_capacity(node_valence.len() + 1);
+        offsets.push(0);
+        for &valence in &node_valence {
+            offsets.push(offsets.last().unwrap() + valence);
+        }
+        let mut adjacent_elements = vec![0; *offsets.last().unwrap()];
+        let mut next = offsets.clone();
+        for (element_index, nodes) in element_nodes.iter().enumerate() {
+            for &node in nodes {
+                adjacent_elements[next[node]] = element_index;
+                next[node] += 1;
+            }
+        }
+
+        // Elements are visited in order, so each node's elements are sorted
+        let mut node_elements = NestedVec::new();
+        for node in 0..node_valence.len() {
+            node_elements.push(&adjacent_elements[offsets[node]..offsets[node + 1]]);
+        }
+
+        Self {
+            element_nodes,
+            node_elements,
+        }
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.element_nodes.len()
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.node_elements.len()
+    }
+
+    /// The nodes of the given element.
+    pub fn element_nodes(&self, element_index: usize) -> &[usize] {
+        self.element_nodes
+            .get(element_index)
+            .expect("Element index out of bounds")
+    }
+
+    /// The (sorted) indices of the elements that contain the given node.
+    pub fn node_elements(&self, node_index: usize) -> &[usize] {
+        self.node_elements
+            .get(node_index)
+            .expect("Node index out of bounds")
+    }
+
+    /// The number of elements that contain the given node.
+    pub fn node_valence(&self, node_index: usize) -> usize {
+        self.node_elements(node_index).len()
+    }
+
+    /// The (sorted) indices of the elements that contain *all* the given nodes.
+    ///
+    /// Given the nodes of an edge or a face, this gives the elements sharing the edge or face.
+    /// Returns an empty vector if `nodes` is empty.
+    pub fn elements_sharing_nodes(&self, nodes: &[usize]) -> Vec<usize> {
+        let (first, rest) = match nodes.split_first() {
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+        self.node_elements(*first)
+            .iter()
+            .copied()
+            .filter(|&element| {
+                rest.iter()
+                    .all(|&node| self.node_elements(node).binary_search(&element).is_ok())
+            })
+            .collect()
+    }
+
+    /// The (sorted) indices of the elements that share at least one node with the given element,
+    /// including the element itself.
+    pub fn element_star(&self, element_index: usize) -> Vec<usize> {
+        let mut elements: Vec<_> = self
+            .element_nodes(element_index)
+            .iter()
+            .flat_map(|&node| self.node_elements(node))
+            .copied()
+            .collect();
+        elements.sort_unstable();
+        elements.dedup();
+        elements
+    }
+
+    /// The patch of all elements that contain the given node.
+    pub fn vertex_patch(&self, node_index: usize) -> Patch {
+        self.patch(self.node_elements(node_index).iter().copied())
+    }
+
+    /// The patch of all elements that contain all the given nodes, e.g. the nodes of an edge or face.
+    pub fn shared_nodes_patch(&self, nodes: &[usize]) -> Patch {
+        self.patch(self.elements_sharing_nodes(nodes))
+    }
+
+    /// The patch of all elements that share at least one node with the given element.
+    pub fn element_star_patch(&self, element_index: usize) -> Patch {
+        self.patch(self.element_star(element_index))
+    }
+
+    /// Constructs the patch consisting of the given elements.
+    ///
+    /// Duplicate elements are ignored.
+    pub fn patch(&self, elements: impl IntoIterator<Item = usize>) -> Patch {
+        let mut elements: Vec<_> = elements.into_iter().collect();
+        elements.sort_unstable();
+        elements.dedup();
+
+        let mut nodes: Vec<_> = elements
+            .iter()
+            .flat_map(|&element| self.element_nodes(element))
+            .copied()
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        // A node is interior to the patch if all elements that contain it belong to the patch
+        let interior = nodes
+            .iter()
+            .map(|&node| {
+                self.node_elements(node)
+                    .iter()
+                    .all(|element| elements.binary_search(element).is_ok())
+            })
+            .collect();
+
+        let mut element_local_nodes = NestedVec::new();
+        for &element in &elements {
+            let mut local_nodes = element_local_nodes.begin_array();
+            for node in self.element_nodes(element) {
+                local_nodes.push_single(nodes.binary_search(node).unwrap());
+            }
+        }
+
+        Patch {
+            elements,
+            nodes,
+            interior,
+            element_local_nodes,
+        }
+    }
+}
+
+/// A set of elements together with a local numbering of their nodes.
+///
+/// Local node indices refer to positions in [`nodes`](Self::nodes), which contains the global
+/// node indices in ascending order. The local degrees of freedom of a field with solution
+/// dimension $s$ are numbered node by node, so that local node $i$ is associated with the local
+/// degrees of freedom $s i, \dots, s i + s - 1$.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    elements: Vec<usize>,
+    nodes: Vec<usize>,
+    interior: Vec<bool>,
+    element_local_nodes: NestedVec<usize>,
+}
+
+impl Patch {
+    /// The (sorted) global indices of the elements in the patch.
+    pub fn elements(&self) -> &[usize] {
+        &self.elements
+    }
+
+    /// The (sorted) global indices of the nodes in the patch.
+    pub fn nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The local index of the given global node, or `None` if the node is not in the patch.
+    pub fn local_node_index(&self, global_node_index: usize) -> Option<usize> {
+        self.nodes.binary_search(&global_node_index).ok()
+    }
+
+    /// The local node indices of the `i`-th element in the patch.
+    pub fn element_local_nodes(&self, i: usize) -> &[usize] {
+        self.element_local_nodes
+            .get(i)
+            .expect("Patch element index out of bounds")
+    }
+
+    /// Whether the given local node is interior to the patch.
+    ///
+    /// A node is interior if all elements that contain it belong to the patch. Basis functions
+    /// associated with interior nodes are therefore supported entirely inside the patch.
+    pub fn is_interior_node(&self, local_node_index: usize) -> bool {
+        self.interior[local_node_index]
+    }
+
+    /// The local indices of the nodes that are interior to the patch.
+    pub fn interior_local_nodes(&self) -> impl '_ + Iterator<Item = usize> {
+        self.interior
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &interior)| interior.then_some(i))
+    }
+
+    /// The global indices of the nodes that are interior to the patch.
+    pub fn interior_nodes(&self) -> impl '_ + Iterator<Item = usize> {
+        self.interior_local_nodes().map(|i| self.nodes[i])
+    }
+
+    /// Maps the local degrees of freedom of the patch to global degrees of freedom.
+    pub fn global_dofs(&self, solution_dim: usize) -> Vec<usize> {
+        node_dofs(self.nodes.iter().copied(), solution_dim)
+    }
+
+    /// The global degrees of freedom associated with the interior nodes of the patch.
+    pub fn interior_global_dofs(&self, solution_dim: usize) -> Vec<usize> {
+        node_dofs(self.interior_nodes(), solution_dim)
+    }
+
+    /// The local degrees of freedom of the `i`-th element in the patch, in the same order as
+    /// the degrees of freedom of the element in the global system.
+    pub fn element_local_dofs(&self, i: usize, solution_dim: usize) -> Vec<usize> {
+        node_dofs(self.element_local_nodes(i).iter().copied(), solution_dim)
+    }
+}
+
+fn node_dofs(nodes: impl Iterator<Item = usize>, solution_dim: usize) -> Vec<usize> {
+    let s = solution_dim;
+    nodes
+        .flat_map(|node| (0..s).map(move |i| s * node + i))
+        .collect()
+}
+
+/// Extracts the dense submatrix of a sparse matrix associated with the given degrees of freedom.
+///
+/// Entry $(i, j)$ of the result is the entry of `matrix` at row `dofs[i]` and column `dofs[j]`.
+/// This is typically used together with [`Patch::global_dofs`] or
+/// [`Patch::interior_global_dofs`] to set up local patch problems.
+///
+/// The cost is proportional to the number of non-zeros in the selected rows (up to a
+/// logarithmic factor), independent of the total number of columns. When extracting many
+/// submatrices of the same matrix, [`DenseSubmatrixExtractor`] avoids the logarithmic factor.
+///
+/// # Panics
+///
+/// Panics if any of the indices is out of bounds or if the indices are not unique.
+pub fn extract_dense_submatrix<T: Real>(matrix: &CsrMatrix<T>, dofs: &[usize]) -> DMatrix<T> {
+    let mut sorted_dofs: Vec<(usize, usize)> = dofs
+        .iter()
+        .enumerate()
+        .map(|(local, &global)| (global, local))
+        .collect();
+    sorted_dofs.sort_unstable();
+    assert!(
+        sorted_dofs.windows(2).all(|pair| pair[0].0 != pair[1].0),
+        "Indices must be unique"
+    );
+    assert!(
+        sorted_dofs
+            .last()
+            .is_none_or(|&(global, _)| global < matrix.ncols()),
+        "Index out of bounds"
+    );
+    populate_dense_submatrix(matrix, dofs, |global_col| {
+        sorted_dofs
+            .binary_search_by_key(&global_col, |&(global, _)| global)
+            .ok()
+            .map(|i| sorted_dofs[i].1)
+    })
+}
+
+/// Extracts dense submatrices of sparse matrices, reusing a global-to-local index map between
+/// extractions.
+///
+/// The map is allocated once for the largest number of columns encountered and only the
+/// entries touched by an extraction are reset afterwards, so that each extraction costs time
+/// proportional to the number of non-zeros in the selected rows. This makes it suitable for
+/// extracting the local matrices of many small patches, see [`extract_dense_submatrix`].
+#[derive(Debug, Clone, Default)]
+pub struct DenseSubmatrixExtractor {
+    global_to_local: Vec<usize>,
+}
+
+impl DenseSubmatrixExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extracts the dense submatrix associated with the given degrees of freedom.
+    ///
+    /// See [`extract_dense_submatrix`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the indices is out of bounds or if the indices are not unique.
+    pub fn extract<T: Real>(&mut self, matrix: &CsrMatrix<T>, dofs: &[usize]) -> DMatrix<T> {
+        if self.global_to_local.len() < matrix.ncols() {
+            self.global_to_local.resize(matrix.ncols(), usize::MAX);
+        }
+        for (local, &global) in dofs.iter().enumerate() {
+            assert!(global < matrix.ncols(), "Index out of bounds");
+            if self.global_to_local[global] != usize::MAX {
+                self.reset(&dofs[..local]);
+                panic!("Indices must be unique");
+            }
+            self.global_to_local[global] = local;
+        }
+
+        let global_to_local = &self.global_to_local;
+        let result = populate_dense_submatrix(matrix, dofs, |global_col| {
+            let local_col = global_to_local[global_col];
+            (local_col != usize::MAX).then_some(local_col)
+        });
+        self.reset(dofs);
+        result
+    }
+
+    fn reset(&mut self, dofs: &[usize]) {
+        for &global in dofs {
+            self.global_to_local[global] = usize::MAX;
+        }
+    }
+}
+
+fn populate_dense_submatrix<T: Real>(
+    matrix: &CsrMatrix<T>,
+    dofs: &[usize],
+    local_col: impl Fn(usize) -> Option<usize>,
+) -> DMatrix<T> {
+    let m = dofs.len();
+    let mut result = DMatrix::zeros(m, m);
+    for (local_row, &global_row) in dofs.iter().enumerate() {
+        let row = matrix.row(global_row);
+        for (&global_col, &value) in row.col_indices().iter().zip(row.values()) {
+            if let Some(local_col) = local_col(global_col) {
+                result[(local_row, local_col)] += value;
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,254 @@
+//! Element-local solves and projections.
+//!
+//! Many post-processing and recovery methods reduce to solving a small dense linear system on
+//! each element independently, for example the local $L^2$ projection of quadrature point data,
+//! such as stresses or internal variables, onto the basis functions of each element. Since the
+//! systems are independent, they are solved in parallel, with elements processed in batches to
+//! amortize the overhead of parallel execution for cheap systems.
+//!
+//! The results of element-local projections are discontinuous across elements. They are
+//! stored in the node numbering of the corresponding
+//! [`DiscontinuousSpace`](crate::space::DiscontinuousSpace), in which they can be interpolated
+//! and further processed. In contrast, [`project_l2`] computes the global (continuous) $L^2$
+//! projection of a function onto a finite element space, which is commonly used to set up
+//! initial conditions and manufactured solutions.
+use crate::allocators::DimAllocator;
+use crate::assembly::buffers::{PreparedElementBuffer, QuadratureBuffer};
+use crate::assembly::local::QuadratureTable;
+use crate::linear_solver::{ConjugateGradientSolver, LinearSolver};
+use crate::nalgebra::{DMatrix, DVector, DefaultAllocator, OPoint, OVector};
+use crate::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use crate::space::{FiniteElementSpace, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+/// Solves a small dense linear system $A X = B$ for each element in parallel.
+///
+/// The function `assemble_system` returns the matrix $A$ and the right-hand side $B$ of the given
+/// element, where $B$ may have several columns. The system is solved with a Cholesky
+/// factorization if $A$ is symmetric positive definite, and with an LU factorization otherwise.
+/// Elements are distributed among threads in batches of at least `batch_size` elements.
+///
+/// # Errors
+///
+/// Returns an error if the assembly of a system fails, if the dimensions of $A$ and $B$ are not
+/// compatible, or if $A$ is singular.
+///
+/// # Panics
+///
+/// Panics if the batch size is zero.
+pub fn par_solve_element_systems<T: Real>(
+    num_elements: usize,
+    batch_size: usize,
+    assemble_system: impl Fn(usize) -> eyre::Result<(DMatrix<T>, DMatrix<T>)> + Sync,
+) -> eyre::Result<Vec<DMatrix<T>>> {
+    assert!(batch_size > 0, "Batch size must be positive");
+    (0..num_elements)
+        .into_par_iter()
+        .with_min_len(batch_size)
+        .map(|element_index| {
+            let (a, b) = assemble_system(element_index)
+                .map_err(|error| error.wrap_err(format!("Assembling system failed for element {element_index}")))?;
+            solve_dense_system(a, b)
+                .map_err(|error| error.wrap_err(format!("Solve failed for element {element_index}")))
+        })
+        .collect()
+}
+
+fn solve_dense_system<T: Real>(a: DMatrix<T>, b: DMatrix<T>) -> eyre::Result<DMatrix<T>> {
+    if !a.is_square() || a.nrows() != b.nrows() {
+        return Err(eyre!(
+            "Incompatible dimensions of system matrix ({}x{}) and right-hand side ({}x{})",
+            a.nrows(),
+            a.ncols(),
+            b.nrows(),
+            b.ncols()
+        ));
+    }
+    let is_symmetric = a.relative_eq(&a.transpose(), T::default_epsilon(), T::default_epsilon());
+    if is_symmetric {
+        if let Some(cholesky) = a.clone().cholesky() {
+            return Ok(cholesky.solve(&b));
+        }
+    }
+    a.lu()
+        .solve(&b)
+        .ok_or_else(|| eyre!("System matrix is singular"))
+}
+
+/// Computes the element-wise $L^2$ projection of a field sampled at quadrature points onto the
+/// basis functions of each element.
+///
+/// For each element $K$, finds the coefficients of the function $p_K = \sum_i p_i \varphi_i$ in the
+/// span of the basis functions of the element that minimizes
+/// <div>$$
+/// \int_K | p_K - f |^2 \enspace \mathrm{d}x,
+/// $$</div>
+/// where the integral is approximated with the quadrature table and $f$ is given by its values at
+/// the quadrature points. This requires the solution of a system with the element mass matrix.
+/// The quadrature must have at least as many points as the element has basis functions, so that
+/// the element mass matrix is non-singular.
+///
+/// The function `sample` is called for each element and each (reference) quadrature point of the
+/// quadrature table and must return the `field_dim` components of the field at the point. The
+/// result is stored in the interleaved format with `field_dim` components per node of the
+/// [`DiscontinuousSpace`](crate::space::DiscontinuousSpace) of the given space.
+///
+/// # Errors
+///
+/// Returns an error if `sample` returns an error or a vector of the wrong length, or if an
+/// element mass matrix is singular.
+pub fn par_project_element_l2<T, D, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    field_dim: usize,
+    batch_size: usize,
+    sample: impl Fn(usize, &OPoint<T, D>) -> eyre::Result<DVector<T>> + Sync,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    D: SmallDim,
+    Space: VolumetricFiniteElementSpace<T, ReferenceDim = D> + Sync,
+    QTable: QuadratureTable<T, D> + Sync,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let assemble_system = |element_index: usize| {
+        let n = space.element_node_count(element_index.into());
+        let mut quadrature_buffer = QuadratureBuffer::<T, D>::default();
+        quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+        let mut mass = DMatrix::zeros(n, n);
+        let mut rhs = DMatrix::zeros(n, field_dim);
+        let mut basis = DVector::zeros(n);
+        for (&w, xi) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+            let value = sample(element_index, xi)?;
+            if value.len() != field_dim {
+                return Err(eyre!(
+                    "Sampled field has {} components, expected {}",
+                    value.len(),
+                    field_dim
+                ));
+            }
+            space.populate_element_basis(element_index, basis.as_mut_slice(), xi);
+            let j_det = space
+                .element_reference_jacobian(element_index, xi)
+                .determinant()
+                .abs();
+            mass.ger(w * j_det, &basis, &basis, T::one());
+            rhs.ger(w * j_det, &basis, &value, T::one());
+        }
+        Ok((mass, rhs))
+    };
+
+    let coefficients = par_solve_element_systems(space.num_elements(), batch_size, assemble_system)?;
+    let num_nodes: usize = coefficients.iter().map(|c| c.nrows()).sum();
+    let mut result = DVector::zeros(field_dim * num_nodes);
+    let mut offset = 0;
+    for element_coefficients in coefficients {
+        // Each row contains the field components of one node, which are stored consecutively
+        for row in element_coefficients.row_iter() {
+            result
+                .rows_mut(offset, field_dim)
+                .copy_from(&row.transpose());
+            offset += field_dim;
+        }
+    }
+    Ok(result)
+}
+
+/// Computes the global $L^2$ projection of a function onto a finite element space.
+///
+/// Finds the interpolation weights of the function $u_h$ in the space that minimizes
+/// <div>$$
+/// \int_\Omega | u_h - f |^2 \enspace \mathrm{d}x,
+/// $$</div>
+/// which amounts to solving $\vec M \vec u = \vec b$ for each component of $f$, where $\vec M$
+/// is the (scalar) mass matrix of the space and $b_i = \int_\Omega f \, \varphi_i \, \mathrm{d}x$.
+/// Both are integrated with the given quadrature table, with the function `f` evaluated at the
+/// quadrature points in physical coordinates. The mass matrix systems are solved with an
+/// unpreconditioned [conjugate gradient solver](ConjugateGradientSolver), see
+/// [`project_l2_with_solver`] for using a different solver.
+///
+/// The result is stored in the interleaved format with `SolutionDim` components per node.
+///
+/// # Errors
+///
+/// Returns an error if a singular element Jacobian is encountered, or if the solver fails.
+pub fn project_l2<T, SolutionDim, D, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    f: impl Fn(&OPoint<T, D>) -> OVector<T, SolutionDim>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    D: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: ?Sized + QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D> + DimAllocator<T, SolutionDim>,
+{
+    let mut solver = ConjugateGradientSolver::with_tolerance(T::from_f64(1e-12).unwrap());
+    project_l2_with_solver(space, qtable, &mut solver, f)
+}
+
+/// Computes the global $L^2$ projection of a function onto a finite element space with the
+/// given linear solver.
+///
+/// See [`project_l2`] for details.
+///
+/// # Errors
+///
+/// Returns an error if a singular element Jacobian is encountered, or if the solver fails.
+pub fn project_l2_with_solver<T, SolutionDim, D, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    solver: &mut impl LinearSolver<T>,
+    f: impl Fn(&OPoint<T, D>) -> OVector<T, SolutionDim>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    D: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: ?Sized + QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D> + DimAllocator<T, SolutionDim>,
+{
+    let s = SolutionDim::dim();
+    let num_nodes = space.num_nodes();
+    let mut mass = CooMatrix::new(num_nodes, num_nodes);
+    // Column k holds the load vector of component k
+    let mut loads = DMatrix::zeros(num_nodes, s);
+    let mut buffer = PreparedElementBuffer::default();
+    for element_index in 0..space.num_elements() {
+        buffer
+            .prepare_element(space, element_index, qtable)
+            .map_err(|err| err.wrap_err(format!("Failed to prepare element {element_index}")))?;
+        for (q, x) in buffer.physical_points().iter().enumerate() {
+            let w = buffer.physical_weight(q);
+            let f_x = f(x);
+            for (&i, &phi_i) in izip!(buffer.element_nodes(), buffer.basis_values(q)) {
+                for (&j, &phi_j) in izip!(buffer.element_nodes(), buffer.basis_values(q)) {
+                    mass.push(i, j, w * phi_i * phi_j);
+                }
+                for k in 0..s {
+                    loads[(i, k)] += w * phi_i * f_x[k];
+                }
+            }
+        }
+    }
+    let mass = CsrMatrix::from(&mass);
+
+    let mut result = DVector::zeros(s * num_nodes);
+    let mut component = DVector::zeros(num_nodes);
+    for k in 0..s {
+        component.fill(T::zero());
+        solver
+            .solve(&mass, component.as_view_mut(), loads.column(k))
+            .map_err(|err| err.wrap_err(format!("Failed to solve mass matrix system for component {k}")))?;
+        for (node, &value) in component.iter().enumerate() {
+            result[s * node + k] = value;
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,177 @@
+//! Assembly of saddle point systems for constraints enforced by Lagrange multipliers.
+//!
+//! Given a linear system $\vec A \vec u = \vec f$ with $n$ primal degrees of freedom and $m$
+//! linear constraints $\vec C \vec u = \vec g$ with a constraint operator
+//! $\vec C \in \mathbb{R}^{m \times n}$, the constrained problem can be formulated in terms of
+//! Lagrange multipliers $\vec \lambda \in \mathbb{R}^m$ as the block system
+//! <div>$$
+//! \begin{pmatrix} \vec A & \vec C^T \\\\ \vec C & \vec 0 \end{pmatrix}
+//! \begin{pmatrix} \vec u \\\\ \vec \lambda \end{pmatrix}
+//! = \begin{pmatrix} \vec f \\\\ \vec g \end{pmatrix}.
+//! $$</div>
+//! Unlike [elimination](crate::assembly::bc), this supports arbitrary linear constraints, such
+//! as incompressibility, contact or mean value constraints. The system is symmetric whenever
+//! $\vec A$ is, but it is indefinite, so it must be solved with a suitable solver, for example
+//! a direct $LU$ or $LDL^T$ factorization.
+//!
+//! [`SaddlePointSystem`] assembles the block matrix as a single [`CscMatrix`] and provides
+//! helpers for assembling the right-hand side and extracting the primal and multiplier parts
+//! of the solution.
+use crate::nalgebra::{DVector, DVectorView};
+use crate::Real;
+use eyre::eyre;
+use nalgebra_sparse::{CscMatrix, CsrMatrix};
+
+/// The block matrix of a saddle point system with Lagrange multipliers.
+///
+/// The first [`num_primal_dofs`](Self::num_primal_dofs) unknowns of the system are the primal
+/// degrees of freedom, and the remaining [`num_multipliers`](Self::num_multipliers) unknowns
+/// are the Lagrange multipliers, one for each row of the constraint operator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaddlePointSystem<T> {
+    matrix: CscMatrix<T>,
+    num_primal_dofs: usize,
+    num_multipliers: usize,
+}
+
+impl<T: Real> SaddlePointSystem<T> {
+    /// Assembles the block matrix $\begin{pmatrix} \vec A & \vec C^T \\\\ \vec C & \vec 0 \end{pmatrix}$.
+    ///
+    /// The sparsity pattern of the result is the union of the patterns of the blocks. In
+    /// particular, no explicit zeros are stored in the lower-right block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if $\vec A$ is not square or if the number of columns of $\vec C$
+    /// does not match the dimension of $\vec A$.
+    pub fn assemble(a: &CsrMatrix<T>, c: &CsrMatrix<T>) -> eyre::Result<Self> {
+        if a.nrows() != a.ncols() {
+            return Err(eyre!("Matrix A must be square, but is {}x{}", a.nrows(), a.ncols()));
+        }
+        if c.ncols() != a.ncols() {
+            return Err(eyre!(
+                "Number of columns of constraint operator ({}) does not match dimension of A ({})",
+                c.ncols(),
+                a.ncols()
+            ));
+        }
+        let n = a.nrows();
+        let m = c.nrows();
+
+        // Columns of the first block column are given by the columns of A followed by the
+        // columns of C, whereas the columns of C^T are given by the rows of C.
+        let a_csc = CscMatrix::from(a);
+        let c_csc = CscMatrix::from(c);
+        let nnz = a.nnz() + 2 * c.nnz();
+        let mut col_offsets = Vec::with_capacity(n + m + 1);
+        let mut row_indices = Vec::with_capacity(nnz);
+        let mut values = Vec::with_capacity(nnz);
+        col_offsets.push(0);
+        for j in 0..n {
+            let a_col = a_csc.col(j);
+            let c_col = c_csc.col(j);
+            row_indices.extend_from_slice(a_col.row_indices());
+            row_indices.extend(c_col.row_indices().iter().map(|&i| n + i));
+            values.extend_from_slice(a_col.values());
+            values.extend_from_slice(c_col.values());
+            col_offsets.push(row_indices.len());
+        }
+        for k in 0..m {
+            let c_row = c.row(k);
+            row_indices.extend_from_slice(c_row.col_indices());
+            values.extend_from_slice(c_row.values());
+            col_offsets.push(row_indices.len());
+        }
+
+        let matrix = CscMatrix::try_from_csc_data(n + m, n + m, col_offsets, row_indices, values)
+            .map_err(|err| eyre!("Failed to construct saddle point matrix: {err}"))?;
+        Ok(Self {
+            matrix,
+            num_primal_dofs: n,
+            num_multipliers: m,
+        })
+    }
+
+    /// The assembled block matrix.
+    pub fn matrix(&self) -> &CscMatrix<T> {
+        &self.matrix
+    }
+
+    pub fn into_matrix(self) -> CscMatrix<T> {
+        self.matrix
+    }
+
+    /// The number of primal degrees of freedom $n$.
+    pub fn num_primal_dofs(&self) -> usize {
+        self.num_primal_dofs
+    }
+
+    /// The number of Lagrange multipliers $m$, equal to the number of constraints.
+    pub fn num_multipliers(&self) -> usize {
+        self.num_multipliers
+    }
+
+    /// The total number of unknowns $n + m$.
+    pub fn num_unknowns(&self) -> usize {
+        self.num_primal_dofs + self.num_multipliers
+    }
+
+    /// Assembles the right-hand side $\begin{pmatrix} \vec f \\\\ \vec g \end{pmatrix}$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of `f` or `g` do not match the system.
+    pub fn assemble_rhs<'a, 'b>(
+        &self,
+        f: impl Into<DVectorView<'a, T>>,
+        g: impl Into<DVectorView<'b, T>>,
+    ) -> DVector<T> {
+        let (f, g) = (f.into(), g.into());
+        assert_eq!(
+            f.len(),
+            self.num_primal_dofs,
+            "Dimension of f must match number of primal degrees of freedom"
+        );
+        assert_eq!(
+            g.len(),
+            self.num_multipliers,
+            "Dimension of g must match number of multipliers"
+        );
+        DVector::from_iterator(self.num_unknowns(), f.iter().chain(g.iter()).copied())
+    }
+
+    /// The primal part $\vec u$ of a solution of the saddle point system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the solution does not match the system.
+    pub fn primal_part<'a>(&self, solution: &'a DVector<T>) -> DVectorView<'a, T> {
+        self.split_solution(solution).0
+    }
+
+    /// The multiplier part $\vec \lambda$ of a solution of the saddle point system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the solution does not match the system.
+    pub fn multiplier_part<'a>(&self, solution: &'a DVector<T>) -> DVectorView<'a, T> {
+        self.split_solution(solution).1
+    }
+
+    /// Splits a solution of the saddle point system into its primal and multiplier parts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the solution does not match the system.
+    pub fn split_solution<'a>(&self, solution: &'a DVector<T>) -> (DVectorView<'a, T>, DVectorView<'a, T>) {
+        assert_eq!(
+            solution.len(),
+            self.num_unknowns(),
+            "Dimension of solution must match number of unknowns"
+        );
+        (
+            solution.rows(0, self.num_primal_dofs),
+            solution.rows(self.num_primal_dofs, self.num_multipliers),
+        )
+    }
+}
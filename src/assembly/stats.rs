@@ -0,0 +1,524 @@
+//! Performance counters for assembly.
+//!
+//! Statistics are collected by wrapping an element assembler in an [`InstrumentedAssembler`],
+//! which forwards all calls to the wrapped assembler and records the time spent in the element
+//! kernels, the number of assembled elements and quadrature points, estimates of the number of
+//! floating point operations and the size of the per-element working set. Global assembly
+//! operations can additionally be timed with [`InstrumentedAssembler::record_assembly`], which
+//! also records the number of entries in the assembled output. Since the instrumentation is
+//! opt-in, assembly without a wrapper incurs no overhead.
+//!
+//! To diagnose performance pathologies that are tied to individual elements, such as badly shaped
+//! elements, the time spent in each element kernel can additionally be recorded by enabling
+//! [element profiling](InstrumentedAssembler::with_element_profiling). The resulting
+//! [`ElementProfile`] flags *hot elements* whose cost is far above that of a typical element,
+//! and can be exported as a cell field, for example with
+//! [`FieldExporter::with_cell_field`](crate::io::vtk::FieldExporter::with_cell_field), for
+//! visualization. Similarly, [`inverse_mapping_profile`] records the number of Newton
+//! iterations needed to map physical coordinates back to reference coordinates in each element,
+//! which is a common source of excessive cost for distorted elements.
+//!
+//! The counters are updated atomically, so that instrumented assemblers can also be used with
+//! parallel global assemblers such as [`CsrParAssembler`](crate::assembly::global::CsrParAssembler).
+//!
+//! ```
+//! # use fenris::assembly::global::CsrAssembler;
+//! # use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+//! # use fenris::assembly::operators::LaplaceOperator;
+//! # use fenris::assembly::stats::InstrumentedAssembler;
+//! # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+//! # use fenris::nalgebra::DVector;
+//! # use fenris::quadrature;
+//! # fn main() -> eyre::Result<()> {
+//! let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+//! let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+//! let u = DVector::zeros(mesh.vertices().len());
+//! let element_assembler = ElementEllipticAssemblerBuilder::new()
+//!     .with_finite_element_space(&mesh)
+//!     .with_operator(&LaplaceOperator)
+//!     .with_quadrature_table(&qtable)
+//!     .with_u(&u)
+//!     .build();
+//!
+//! let instrumented = InstrumentedAssembler::new(&element_assembler).with_quadrature_table(&qtable);
+//! let matrix = instrumented.record_assembly(|assembler| CsrAssembler::default().assemble(assembler))?;
+//! let stats = instrumented.stats();
+//! assert_eq!(stats.num_elements, 16);
+//! assert_eq!(stats.num_quadrature_points, 64);
+//! assert_eq!(stats.output_entries, matrix.nnz());
+//! # Ok(())
+//! # }
+//! ```
+use crate::allocators::BiDimAllocator;
+use crate::assembly::local::{
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
+    QuadratureTable,
+};
+use crate::element::{map_physical_coordinates_with_iterations, FiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DMatrix, DMatrixViewMut, DVector, DVectorViewMut, DefaultAllocator, Scalar};
+use crate::nalgebra::{DimMin, OPoint};
+use crate::nalgebra_sparse::CsrMatrix;
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Statistics collected by an [`InstrumentedAssembler`].
+///
+/// All quantities are accumulated over all recorded assembly operations, except for
+/// `max_element_working_set_bytes` and `output_entries`, which refer to the largest element and
+/// the most recent assembly, respectively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssemblyStats {
+    /// The number of global assembly operations recorded with
+    /// [`InstrumentedAssembler::record_assembly`].
+    pub num_assemblies: usize,
+    /// The number of element kernel invocations.
+    pub num_elements: usize,
+    /// The number of quadrature points visited by the element kernels.
+    ///
+    /// Zero if no quadrature table was provided.
+    pub num_quadrature_points: usize,
+    /// The accumulated time spent in element kernels.
+    pub element_time: Duration,
+    /// The accumulated wall-clock time of the recorded global assembly operations.
+    pub total_time: Duration,
+    /// A rough estimate of the number of floating point operations performed by the element
+    /// kernels.
+    ///
+    /// For each quadrature point, an element kernel contracts $d$-dimensional gradients for
+    /// every entry of its output, where $d$ is the geometry dimension. The estimate is therefore
+    /// $2 d m q$ for an element with $m$ output entries and $q$ quadrature points. This neglects
+    /// the cost of evaluating basis functions, Jacobians and the operator, and is only meant for
+    /// comparing the relative cost of different discretizations. Zero if no quadrature table
+    /// was provided.
+    pub estimated_flops: u64,
+    /// The largest number of bytes needed by an element kernel for its output, its node indices
+    /// and its quadrature points and weights.
+    ///
+    /// If this exceeds the size of the L1 or L2 cache, element assembly is likely memory bound.
+    pub max_element_working_set_bytes: usize,
+    /// The number of stored entries in the output of the most recent recorded global assembly,
+    /// i.e. the number of non-zeros of a sparse matrix or the number of entries of a vector.
+    pub output_entries: usize,
+}
+
+impl AssemblyStats {
+    /// The time spent outside of element kernels in the recorded global assembly operations,
+    /// for example for computing sparsity patterns and scattering element contributions.
+    pub fn global_time(&self) -> Duration {
+        self.total_time.saturating_sub(self.element_time)
+    }
+
+    /// The estimated number of floating point operations per second spent in element kernels.
+    ///
+    /// Returns `None` if no time has been spent in element kernels.
+    pub fn estimated_element_flops_per_second(&self) -> Option<f64> {
+        let seconds = self.element_time.as_secs_f64();
+        (seconds > 0.0).then(|| self.estimated_flops as f64 / seconds)
+    }
+}
+
+/// The output of a global assembly operation whose size can be recorded in [`AssemblyStats`].
+pub trait AssemblyOutput {
+    /// The number of explicitly stored entries.
+    fn num_stored_entries(&self) -> usize;
+}
+
+impl<T> AssemblyOutput for CsrMatrix<T> {
+    fn num_stored_entries(&self) -> usize {
+        self.nnz()
+    }
+}
+
+impl<T: Scalar> AssemblyOutput for DVector<T> {
+    fn num_stored_entries(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Scalar> AssemblyOutput for DMatrix<T> {
+    fn num_stored_entries(&self) -> usize {
+        self.len()
+    }
+}
+
+impl AssemblyOutput for f32 {
+    fn num_stored_entries(&self) -> usize {
+        1
+    }
+}
+
+impl AssemblyOutput for f64 {
+    fn num_stored_entries(&self) -> usize {
+        1
+    }
+}
+
+/// Assembly into existing storage produces no output of its own.
+impl AssemblyOutput for () {
+    fn num_stored_entries(&self) -> usize {
+        0
+    }
+}
+
+/// A per-element measurement, such as the time spent in the element kernel of each element.
+///
+/// Values that are not finite indicate elements for which the measurement failed, and are
+/// always considered outliers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementProfile {
+    values: Vec<f64>,
+}
+
+impl ElementProfile {
+    pub fn from_values(values: Vec<f64>) -> Self {
+        Self { values }
+    }
+
+    /// Returns the measured value of each element, suitable for export as a cell field.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The median of the finite values, or `None` if there are no finite values.
+    pub fn median(&self) -> Option<f64> {
+        let mut finite: Vec<_> = self
+            .values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        if finite.is_empty() {
+            return None;
+        }
+        finite.sort_unstable_by(f64::total_cmp);
+        let mid = finite.len() / 2;
+        if finite.len().is_multiple_of(2) {
+            Some(0.5 * (finite[mid - 1] + finite[mid]))
+        } else {
+            Some(finite[mid])
+        }
+    }
+
+    /// Returns the indices of the elements whose value exceeds `factor` times the median, as well
+    /// as the elements whose value is not finite.
+    pub fn outliers(&self, factor: f64) -> Vec<usize> {
+        let threshold = self.median().map(|median| factor * median);
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| !v.is_finite() || threshold.is_some_and(|threshold| v > threshold))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns a cell field that is one for [outliers](Self::outliers) and zero otherwise.
+    pub fn outlier_indicator(&self, factor: f64) -> Vec<f64> {
+        let mut indicator = vec![0.0; self.values.len()];
+        for i in self.outliers(factor) {
+            indicator[i] = 1.0;
+        }
+        indicator
+    }
+}
+
+/// Records the number of Newton iterations needed to map points back to reference coordinates in
+/// each element of the space.
+///
+/// For each element, the given reference points are mapped to physical coordinates, and then
+/// back to reference coordinates with [`map_physical_coordinates_with_iterations`]. The value of
+/// each element in the returned profile is the largest number of iterations
+/// over all points, or `NaN` if the inverse mapping failed for any point. Affine elements typically
+/// need a single iteration, so elements with many iterations are usually strongly distorted.
+pub fn inverse_mapping_profile<T, Space>(
+    space: &Space,
+    reference_points: &[OPoint<T, Space::ReferenceDim>],
+) -> ElementProfile
+where
+    T: Real,
+    Space: FiniteElementSpace<T, GeometryDim = <Space as FiniteElementSpace<T>>::ReferenceDim>,
+    Space::ReferenceDim: DimMin<Space::ReferenceDim, Output = Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let values = (0..space.num_elements())
+        .map(|element_index| {
+            let element = ElementInSpace::from_space_and_element_index(space, element_index);
+            reference_points
+                .iter()
+                .try_fold(0.0, |max_iterations: f64, xi| {
+                    let x = element.map_reference_coords(xi);
+                    map_physical_coordinates_with_iterations(&element, &x)
+                        .map(|(_, iterations)| max_iterations.max(iterations as f64))
+                })
+                .unwrap_or(f64::NAN)
+        })
+        .collect();
+    ElementProfile::from_values(values)
+}
+
+#[derive(Debug, Default)]
+struct AtomicStats {
+    num_assemblies: AtomicUsize,
+    num_elements: AtomicUsize,
+    num_quadrature_points: AtomicUsize,
+    element_nanos: AtomicU64,
+    total_nanos: AtomicU64,
+    estimated_flops: AtomicU64,
+    max_element_working_set_bytes: AtomicUsize,
+    output_entries: AtomicUsize,
+}
+
+/// An element assembler that records [`AssemblyStats`] for a wrapped element assembler.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct InstrumentedAssembler<'a, Assembler: ?Sized> {
+    assembler: &'a Assembler,
+    element_quadrature_sizes: Vec<usize>,
+    geometry_dim: usize,
+    stats: AtomicStats,
+    element_nanos: Option<Vec<AtomicU64>>,
+}
+
+impl<'a, Assembler> InstrumentedAssembler<'a, Assembler>
+where
+    Assembler: ?Sized + ElementConnectivityAssembler,
+{
+    pub fn new(assembler: &'a Assembler) -> Self {
+        Self {
+            assembler,
+            element_quadrature_sizes: Vec::new(),
+            geometry_dim: 0,
+            stats: AtomicStats::default(),
+            element_nanos: None,
+        }
+    }
+
+    /// Additionally records the time spent in the element kernel of each individual element.
+    ///
+    /// This requires storage proportional to the number of elements. See
+    /// [`element_time_profile`](Self::element_time_profile).
+    pub fn with_element_profiling(self) -> Self {
+        let element_nanos = (0..self.assembler.num_elements())
+            .map(|_| AtomicU64::new(0))
+            .collect();
+        Self {
+            element_nanos: Some(element_nanos),
+            ..self
+        }
+    }
+
+    /// Uses the quadrature table of the wrapped assembler for counting quadrature points and
+    /// estimating floating point operations.
+    pub fn with_quadrature_table<T, GeometryDim>(self, table: &impl QuadratureTable<T, GeometryDim>) -> Self
+    where
+        T: Scalar,
+        GeometryDim: SmallDim,
+        DefaultAllocator: Allocator<T, GeometryDim>,
+    {
+        let element_quadrature_sizes = (0..self.assembler.num_elements())
+            .map(|i| table.element_quadrature_size(i))
+            .collect();
+        Self {
+            element_quadrature_sizes,
+            geometry_dim: GeometryDim::dim(),
+            ..self
+        }
+    }
+
+    pub fn assembler(&self) -> &'a Assembler {
+        self.assembler
+    }
+
+    /// Returns a snapshot of the statistics collected so far.
+    pub fn stats(&self) -> AssemblyStats {
+        let stats = &self.stats;
+        AssemblyStats {
+            num_assemblies: stats.num_assemblies.load(Ordering::Relaxed),
+            num_elements: stats.num_elements.load(Ordering::Relaxed),
+            num_quadrature_points: stats.num_quadrature_points.load(Ordering::Relaxed),
+            element_time: Duration::from_nanos(stats.element_nanos.load(Ordering::Relaxed)),
+            total_time: Duration::from_nanos(stats.total_nanos.load(Ordering::Relaxed)),
+            estimated_flops: stats.estimated_flops.load(Ordering::Relaxed),
+            max_element_working_set_bytes: stats.max_element_working_set_bytes.load(Ordering::Relaxed),
+            output_entries: stats.output_entries.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the accumulated time in seconds spent in the element kernel of each element.
+    ///
+    /// Returns `None` if [element profiling](Self::with_element_profiling) is not enabled.
+    pub fn element_time_profile(&self) -> Option<ElementProfile> {
+        self.element_nanos.as_ref().map(|element_nanos| {
+            let values = element_nanos
+                .iter()
+                .map(|nanos| Duration::from_nanos(nanos.load(Ordering::Relaxed)).as_secs_f64())
+                .collect();
+            ElementProfile::from_values(values)
+        })
+    }
+
+    /// Resets all statistics to zero.
+    pub fn reset_stats(&self) {
+        let stats = &self.stats;
+        for counter in [
+            &stats.num_assemblies,
+            &stats.num_elements,
+            &stats.num_quadrature_points,
+            &stats.max_element_working_set_bytes,
+            &stats.output_entries,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        for counter in [&stats.element_nanos, &stats.total_nanos, &stats.estimated_flops] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        for counter in self.element_nanos.iter().flatten() {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Performs and times a global assembly operation with this assembler.
+    ///
+    /// The closure is given the instrumented assembler, which should be passed on to a global
+    /// assembler. The number of stored entries of the output is recorded if the assembly
+    /// succeeds.
+    pub fn record_assembly<R, F>(&self, assemble: F) -> eyre::Result<R>
+    where
+        R: AssemblyOutput,
+        F: FnOnce(&Self) -> eyre::Result<R>,
+    {
+        let start = Instant::now();
+        let result = assemble(self);
+        add_duration(&self.stats.total_nanos, start.elapsed());
+        self.stats.num_assemblies.fetch_add(1, Ordering::Relaxed);
+        let output = result?;
+        self.stats
+            .output_entries
+            .store(output.num_stored_entries(), Ordering::Relaxed);
+        Ok(output)
+    }
+
+    /// Records an element kernel invocation with the given number of output entries and scalar
+    /// size.
+    fn record_element<R>(
+        &self,
+        element_index: usize,
+        num_output_entries: usize,
+        scalar_size: usize,
+        kernel: impl FnOnce() -> R,
+    ) -> R {
+        let start = Instant::now();
+        let result = kernel();
+        let elapsed = start.elapsed();
+        add_duration(&self.stats.element_nanos, elapsed);
+        if let Some(counter) = self
+            .element_nanos
+            .as_ref()
+            .and_then(|element_nanos| element_nanos.get(element_index))
+        {
+            add_duration(counter, elapsed);
+        }
+
+        let num_points = self
+            .element_quadrature_sizes
+            .get(element_index)
+            .copied()
+            .unwrap_or(0);
+        let node_count = self.assembler.element_node_count(element_index.into());
+        let working_set =
+            scalar_size * (num_output_entries + num_points * (self.geometry_dim + 1)) + size_of::<usize>() * node_count;
+        let flops = 2 * self.geometry_dim * num_output_entries * num_points;
+
+        let stats = &self.stats;
+        stats.num_elements.fetch_add(1, Ordering::Relaxed);
+        stats
+            .num_quadrature_points
+            .fetch_add(num_points, Ordering::Relaxed);
+        stats
+            .estimated_flops
+            .fetch_add(flops as u64, Ordering::Relaxed);
+        stats
+            .max_element_working_set_bytes
+            .fetch_max(working_set, Ordering::Relaxed);
+        result
+    }
+}
+
+fn add_duration(counter: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    counter.fetch_add(nanos, Ordering::Relaxed);
+}
+
+impl<'a, Assembler> ElementConnectivityAssembler for InstrumentedAssembler<'a, Assembler>
+where
+    Assembler: ?Sized + ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, Assembler> ElementMatrixAssembler<T> for InstrumentedAssembler<'a, Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let num_entries = output.len();
+        self.record_element(element_index, num_entries, size_of::<T>(), || {
+            self.assembler
+                .assemble_element_matrix_into(element_index, output)
+        })
+    }
+}
+
+impl<'a, T, Assembler> ElementVectorAssembler<T> for InstrumentedAssembler<'a, Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementVectorAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let num_entries = output.len();
+        self.record_element(element_index, num_entries, size_of::<T>(), || {
+            self.assembler
+                .assemble_element_vector_into(element_index, output)
+        })
+    }
+}
+
+impl<'a, T, Assembler> ElementScalarAssembler<T> for InstrumentedAssembler<'a, Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementScalarAssembler<T>,
+{
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
+        self.record_element(element_index, 1, size_of::<T>(), || {
+            self.assembler.assemble_element_scalar(element_index)
+        })
+    }
+}
@@ -0,0 +1,729 @@
+//! Forward-mode automatic differentiation with dual numbers.
+//!
+//! A [dual number](https://en.wikipedia.org/wiki/Dual_number) $a + b \varepsilon$ with
+//! $\varepsilon^2 = 0$ carries a value $a$ together with a directional derivative $b$. Evaluating
+//! a function $f$ on $x + \varepsilon$ gives $f(x) + f'(x) \varepsilon$, so that derivatives can
+//! be computed exactly (up to rounding) by evaluating code that is generic over the scalar type
+//! with [`Dual`] in place of the usual scalar. Since [`Dual<T>`] is itself a [`Real`], duals can
+//! be nested to obtain second derivatives.
+//!
+//! The main purpose of this module is to derive the derivative information required by
+//! assemblers from a single function, see
+//! [`EnergyAutoDiff`](crate::assembly::operators::EnergyAutoDiff) and
+//! [`OperatorAutoDiff`](crate::assembly::operators::OperatorAutoDiff).
+//!
+//! This module requires the `autodiff` feature.
+use crate::Real;
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+use num::traits::{FromPrimitive, Num, One, Signed, Zero};
+use simba::scalar::{ComplexField, Field, RealField, SubsetOf, SupersetOf};
+use simba::simd::SimdValue;
+use std::fmt;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign};
+
+/// A dual number $a + b \varepsilon$ with $\varepsilon^2 = 0$.
+///
+/// Comparisons only consider the [value](Self::value), so that branches in generic code take the
+/// same path as for the underlying scalar.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Dual<T> {
+    value: T,
+    derivative: T,
+}
+
+impl<T> Dual<T> {
+    pub const fn new(value: T, derivative: T) -> Self {
+        Self { value, derivative }
+    }
+}
+
+impl<T: Copy> Dual<T> {
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// The derivative part, i.e. the coefficient of $\varepsilon$.
+    pub fn derivative(&self) -> T {
+        self.derivative
+    }
+
+    pub fn set_value(&mut self, value: T) {
+        self.value = value;
+    }
+
+    pub fn set_derivative(&mut self, derivative: T) {
+        self.derivative = derivative;
+    }
+}
+
+impl<T: Real> Dual<T> {
+    /// A dual number with vanishing derivative.
+    pub fn constant(value: T) -> Self {
+        Self::new(value, T::zero())
+    }
+
+    /// A dual number with unit derivative, representing the variable of differentiation.
+    pub fn variable(value: T) -> Self {
+        Self::new(value, T::one())
+    }
+
+    /// Applies a function with the given value $f(a)$ and derivative $f'(a)$ at the value $a$
+    /// by the chain rule.
+    fn chain(self, value: T, derivative: T) -> Self {
+        Self::new(value, derivative * self.derivative)
+    }
+}
+
+/// Scalars that can represent values of the primal scalar type `T`.
+///
+/// This is implemented by `T` itself as well as by (nested) [dual numbers](Dual) over `T`, and is
+/// used as a bound on functions that should be differentiable with respect to some of their
+/// arguments. Constants of type `T`, such as material parameters, can be converted with
+/// [`from_primal`](Self::from_primal).
+pub trait AutoDiffScalar<T: Real>: Real {
+    /// Converts a value of the primal type to a constant.
+    fn from_primal(value: T) -> Self;
+
+    /// The underlying value of the primal type, disregarding any derivatives.
+    fn primal(&self) -> T;
+}
+
+impl<T: Real> AutoDiffScalar<T> for T {
+    fn from_primal(value: T) -> Self {
+        value
+    }
+
+    fn primal(&self) -> T {
+        *self
+    }
+}
+
+impl<T: Real> AutoDiffScalar<T> for Dual<T> {
+    fn from_primal(value: T) -> Self {
+        Self::constant(value)
+    }
+
+    fn primal(&self) -> T {
+        self.value
+    }
+}
+
+impl<T: Real> AutoDiffScalar<T> for Dual<Dual<T>> {
+    fn from_primal(value: T) -> Self {
+        Self::constant(Dual::constant(value))
+    }
+
+    fn primal(&self) -> T {
+        self.value.value
+    }
+}
+
+impl<T: Real> Display for Dual<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {}ε", self.value, self.derivative)
+    }
+}
+
+impl<T: Real> PartialOrd for Dual<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Real> Neg for Dual<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value, -self.derivative)
+    }
+}
+
+impl<T: Real> Add for Dual<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value + rhs.value, self.derivative + rhs.derivative)
+    }
+}
+
+impl<T: Real> Sub for Dual<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.value - rhs.value, self.derivative - rhs.derivative)
+    }
+}
+
+impl<T: Real> Mul for Dual<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.value * rhs.value,
+            self.derivative * rhs.value + self.value * rhs.derivative,
+        )
+    }
+}
+
+impl<T: Real> Div for Dual<T> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let value = self.value / rhs.value;
+        Self::new(value, (self.derivative - value * rhs.derivative) / rhs.value)
+    }
+}
+
+impl<T: Real> Rem for Dual<T> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        // a % b = a - b * trunc(a / b), where the truncated quotient is piecewise constant
+        let quotient = (self.value / rhs.value).trunc();
+        Self::new(self.value % rhs.value, self.derivative - rhs.derivative * quotient)
+    }
+}
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl<T: Real> $trait for Dual<T> {
+            fn $method(&mut self, rhs: Self) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+impl_assign_op!(AddAssign, add_assign, +);
+impl_assign_op!(SubAssign, sub_assign, -);
+impl_assign_op!(MulAssign, mul_assign, *);
+impl_assign_op!(DivAssign, div_assign, /);
+impl_assign_op!(RemAssign, rem_assign, %);
+
+impl<T: Real> Zero for Dual<T> {
+    fn zero() -> Self {
+        Self::constant(T::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value.is_zero() && self.derivative.is_zero()
+    }
+}
+
+impl<T: Real> One for Dual<T> {
+    fn one() -> Self {
+        Self::constant(T::one())
+    }
+}
+
+impl<T: Real> Num for Dual<T> {
+    type FromStrRadixErr = T::FromStrRadixErr;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        T::from_str_radix(str, radix).map(Self::constant)
+    }
+}
+
+impl<T: Real> Signed for Dual<T> {
+    fn abs(&self) -> Self {
+        if self.value.is_sign_negative() {
+            -*self
+        } else {
+            *self
+        }
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self.value <= other.value {
+            Self::zero()
+        } else {
+            *self - *other
+        }
+    }
+
+    fn signum(&self) -> Self {
+        Self::constant(Signed::signum(&self.value))
+    }
+
+    fn is_positive(&self) -> bool {
+        self.value.is_positive()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.value.is_negative()
+    }
+}
+
+impl<T: Real> FromPrimitive for Dual<T> {
+    fn from_i64(n: i64) -> Option<Self> {
+        T::from_i64(n).map(Self::constant)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        T::from_u64(n).map(Self::constant)
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        T::from_f64(n).map(Self::constant)
+    }
+}
+
+impl<T: Real> AbsDiffEq for Dual<T> {
+    type Epsilon = Self;
+
+    fn default_epsilon() -> Self {
+        Self::constant(T::default_epsilon())
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self) -> bool {
+        self.value.abs_diff_eq(&other.value, epsilon.value)
+            && self
+                .derivative
+                .abs_diff_eq(&other.derivative, epsilon.value)
+    }
+}
+
+impl<T: Real> RelativeEq for Dual<T> {
+    fn default_max_relative() -> Self {
+        Self::constant(T::default_max_relative())
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Self, max_relative: Self) -> bool {
+        self.value
+            .relative_eq(&other.value, epsilon.value, max_relative.value)
+            && self
+                .derivative
+                .relative_eq(&other.derivative, epsilon.value, max_relative.value)
+    }
+}
+
+impl<T: Real> UlpsEq for Dual<T> {
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Self, max_ulps: u32) -> bool {
+        self.value.ulps_eq(&other.value, epsilon.value, max_ulps)
+            && self
+                .derivative
+                .ulps_eq(&other.derivative, epsilon.value, max_ulps)
+    }
+}
+
+impl<T: Real> SimdValue for Dual<T> {
+    type Element = Self;
+    type SimdBool = bool;
+
+    fn lanes() -> usize {
+        1
+    }
+
+    fn splat(val: Self) -> Self {
+        val
+    }
+
+    fn extract(&self, _: usize) -> Self {
+        *self
+    }
+
+    unsafe fn extract_unchecked(&self, _: usize) -> Self {
+        *self
+    }
+
+    fn replace(&mut self, _: usize, val: Self) {
+        *self = val;
+    }
+
+    unsafe fn replace_unchecked(&mut self, _: usize, val: Self) {
+        *self = val;
+    }
+
+    fn select(self, cond: bool, other: Self) -> Self {
+        if cond {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+impl<T: Real> Field for Dual<T> {}
+
+impl<T: Real> SubsetOf<Dual<T>> for Dual<T> {
+    fn to_superset(&self) -> Dual<T> {
+        *self
+    }
+
+    fn from_superset_unchecked(element: &Dual<T>) -> Self {
+        *element
+    }
+
+    fn is_in_subset(_: &Dual<T>) -> bool {
+        true
+    }
+}
+
+impl<T: Real> SubsetOf<Dual<T>> for f64 {
+    fn to_superset(&self) -> Dual<T> {
+        Dual::constant(T::from_subset(self))
+    }
+
+    fn from_superset_unchecked(element: &Dual<T>) -> Self {
+        <T as SupersetOf<f64>>::to_subset_unchecked(&element.value)
+    }
+
+    fn is_in_subset(element: &Dual<T>) -> bool {
+        element.derivative.is_zero() && <T as SupersetOf<f64>>::is_in_subset(&element.value)
+    }
+}
+
+impl<T: Real> ComplexField for Dual<T> {
+    type RealField = Self;
+
+    fn from_real(re: Self) -> Self {
+        re
+    }
+
+    fn real(self) -> Self {
+        self
+    }
+
+    fn imaginary(self) -> Self {
+        Self::zero()
+    }
+
+    fn modulus(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn modulus_squared(self) -> Self {
+        self * self
+    }
+
+    fn argument(self) -> Self {
+        if self.value.is_sign_negative() {
+            Self::pi()
+        } else {
+            Self::zero()
+        }
+    }
+
+    fn norm1(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn scale(self, factor: Self) -> Self {
+        self * factor
+    }
+
+    fn unscale(self, factor: Self) -> Self {
+        self / factor
+    }
+
+    fn floor(self) -> Self {
+        Self::constant(self.value.floor())
+    }
+
+    fn ceil(self) -> Self {
+        Self::constant(self.value.ceil())
+    }
+
+    fn round(self) -> Self {
+        Self::constant(self.value.round())
+    }
+
+    fn trunc(self) -> Self {
+        Self::constant(self.value.trunc())
+    }
+
+    fn fract(self) -> Self {
+        Self::new(self.value.fract(), self.derivative)
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn abs(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let value = self.value.hypot(other.value);
+        if value.is_zero() {
+            Self::constant(value)
+        } else {
+            Self::new(
+                value,
+                (self.value * self.derivative + other.value * other.derivative) / value,
+            )
+        }
+    }
+
+    fn recip(self) -> Self {
+        let value = self.value.recip();
+        self.chain(value, -value * value)
+    }
+
+    fn conjugate(self) -> Self {
+        self
+    }
+
+    fn sin(self) -> Self {
+        let (sin, cos) = self.value.sin_cos();
+        self.chain(sin, cos)
+    }
+
+    fn cos(self) -> Self {
+        let (sin, cos) = self.value.sin_cos();
+        self.chain(cos, -sin)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        let (sin, cos) = self.value.sin_cos();
+        (self.chain(sin, cos), self.chain(cos, -sin))
+    }
+
+    fn tan(self) -> Self {
+        let value = self.value.tan();
+        self.chain(value, T::one() + value * value)
+    }
+
+    fn asin(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.asin(), (one - self.value * self.value).sqrt().recip())
+    }
+
+    fn acos(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.acos(), -(one - self.value * self.value).sqrt().recip())
+    }
+
+    fn atan(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.atan(), (one + self.value * self.value).recip())
+    }
+
+    fn sinh(self) -> Self {
+        self.chain(self.value.sinh(), self.value.cosh())
+    }
+
+    fn cosh(self) -> Self {
+        self.chain(self.value.cosh(), self.value.sinh())
+    }
+
+    fn tanh(self) -> Self {
+        let value = self.value.tanh();
+        self.chain(value, T::one() - value * value)
+    }
+
+    fn asinh(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.asinh(), (self.value * self.value + one).sqrt().recip())
+    }
+
+    fn acosh(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.acosh(), (self.value * self.value - one).sqrt().recip())
+    }
+
+    fn atanh(self) -> Self {
+        let one = T::one();
+        self.chain(self.value.atanh(), (one - self.value * self.value).recip())
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.chain(self.value.log2(), (self.value * T::ln_2()).recip())
+    }
+
+    fn log10(self) -> Self {
+        self.chain(self.value.log10(), (self.value * T::ln_10()).recip())
+    }
+
+    fn ln(self) -> Self {
+        self.chain(self.value.ln(), self.value.recip())
+    }
+
+    fn ln_1p(self) -> Self {
+        self.chain(self.value.ln_1p(), (T::one() + self.value).recip())
+    }
+
+    fn sqrt(self) -> Self {
+        let value = self.value.sqrt();
+        self.chain(value, (value + value).recip())
+    }
+
+    fn exp(self) -> Self {
+        let value = self.value.exp();
+        self.chain(value, value)
+    }
+
+    fn exp2(self) -> Self {
+        let value = self.value.exp2();
+        self.chain(value, value * T::ln_2())
+    }
+
+    fn exp_m1(self) -> Self {
+        self.chain(self.value.exp_m1(), self.value.exp())
+    }
+
+    fn powi(self, n: i32) -> Self {
+        if n == 0 {
+            Self::one()
+        } else {
+            let n_real = T::from_i32(n).unwrap();
+            self.chain(self.value.powi(n), n_real * self.value.powi(n - 1))
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        let value = self.value.powf(n.value);
+        let mut derivative = n.value * self.value.powf(n.value - T::one()) * self.derivative;
+        // Only include the derivative with respect to the exponent if it is present, so that
+        // negative bases with constant exponents do not produce NaN
+        if !n.derivative.is_zero() {
+            derivative += value * self.value.ln() * n.derivative;
+        }
+        Self::new(value, derivative)
+    }
+
+    fn powc(self, n: Self) -> Self {
+        self.powf(n)
+    }
+
+    fn cbrt(self) -> Self {
+        let value = self.value.cbrt();
+        let three = T::one() + T::one() + T::one();
+        self.chain(value, (three * value * value).recip())
+    }
+
+    fn is_finite(&self) -> bool {
+        self.value.is_finite() && self.derivative.is_finite()
+    }
+
+    fn try_sqrt(self) -> Option<Self> {
+        (!self.value.is_sign_negative() || self.value.is_zero()).then(|| self.sqrt())
+    }
+}
+
+impl<T: Real> RealField for Dual<T> {
+    fn is_sign_positive(&self) -> bool {
+        self.value.is_sign_positive()
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        self.value.is_sign_negative()
+    }
+
+    fn copysign(self, sign: Self) -> Self {
+        if sign.value.is_sign_negative() == self.value.is_sign_negative() {
+            self
+        } else {
+            -self
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other.value > self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other.value < self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        RealField::min(RealField::max(self, min), max)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let (y, x) = (self.value, other.value);
+        let r2 = x * x + y * y;
+        let derivative = if r2.is_zero() {
+            T::zero()
+        } else {
+            (x * self.derivative - y * other.derivative) / r2
+        };
+        Self::new(y.atan2(x), derivative)
+    }
+
+    fn min_value() -> Option<Self> {
+        T::min_value().map(Self::constant)
+    }
+
+    fn max_value() -> Option<Self> {
+        T::max_value().map(Self::constant)
+    }
+
+    fn pi() -> Self {
+        Self::constant(T::pi())
+    }
+
+    fn two_pi() -> Self {
+        Self::constant(T::two_pi())
+    }
+
+    fn frac_pi_2() -> Self {
+        Self::constant(T::frac_pi_2())
+    }
+
+    fn frac_pi_3() -> Self {
+        Self::constant(T::frac_pi_3())
+    }
+
+    fn frac_pi_4() -> Self {
+        Self::constant(T::frac_pi_4())
+    }
+
+    fn frac_pi_6() -> Self {
+        Self::constant(T::frac_pi_6())
+    }
+
+    fn frac_pi_8() -> Self {
+        Self::constant(T::frac_pi_8())
+    }
+
+    fn frac_1_pi() -> Self {
+        Self::constant(T::frac_1_pi())
+    }
+
+    fn frac_2_pi() -> Self {
+        Self::constant(T::frac_2_pi())
+    }
+
+    fn frac_2_sqrt_pi() -> Self {
+        Self::constant(T::frac_2_sqrt_pi())
+    }
+
+    fn e() -> Self {
+        Self::constant(T::e())
+    }
+
+    fn log2_e() -> Self {
+        Self::constant(T::log2_e())
+    }
+
+    fn log10_e() -> Self {
+        Self::constant(T::log10_e())
+    }
+
+    fn ln_2() -> Self {
+        Self::constant(T::ln_2())
+    }
+
+    fn ln_10() -> Self {
+        Self::constant(T::ln_10())
+    }
+}
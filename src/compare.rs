@@ -0,0 +1,296 @@
+//! Tools for comparing meshes and finite element fields, e.g. for regression testing.
+//!
+//! The main entry point is [`compare_fields`], which compares two discrete fields that may be
+//! defined on entirely different meshes or finite element spaces. This makes it possible to
+//! compare the output of a solver against a stored reference solution even when the
+//! discretization has changed between versions.
+use crate::allocators::TriDimAllocator;
+use crate::assembly::buffers::{BufferUpdate, InterpolationBuffer, QuadratureBuffer};
+use crate::assembly::local::QuadratureTable;
+use crate::connectivity::Connectivity;
+use crate::integrate::volume_form;
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVectorView, DefaultAllocator, OPoint, OVector};
+use crate::space::{InterpolateInSpace, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+use nalgebra::allocator::Allocator;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Tolerances used to decide whether two fields are considered equal.
+///
+/// A field comparison passes only if *all* tolerances are satisfied. Tolerances that are
+/// `None` are not checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldComparisonTolerances<T> {
+    /// Upper bound on the absolute difference $\norm{u_a - u_b}_{L^2}$.
+    pub l2_abs: Option<T>,
+    /// Upper bound on the relative difference $\norm{u_a - u_b}_{L^2} / \norm{u_b}_{L^2}$.
+    pub l2_rel: Option<T>,
+    /// Upper bound on the largest pointwise difference $\max_i \abs{u_a(\vec x_i) - u_b(\vec x_i)}$,
+    /// measured in the Euclidean norm over all quadrature points $\vec x_i$.
+    pub max_abs: Option<T>,
+}
+
+impl<T> Default for FieldComparisonTolerances<T> {
+    fn default() -> Self {
+        Self {
+            l2_abs: None,
+            l2_rel: None,
+            max_abs: None,
+        }
+    }
+}
+
+impl<T> FieldComparisonTolerances<T> {
+    pub fn with_l2_abs(self, tol: T) -> Self {
+        Self {
+            l2_abs: Some(tol),
+            ..self
+        }
+    }
+
+    pub fn with_l2_rel(self, tol: T) -> Self {
+        Self {
+            l2_rel: Some(tol),
+            ..self
+        }
+    }
+
+    pub fn with_max_abs(self, tol: T) -> Self {
+        Self {
+            max_abs: Some(tol),
+            ..self
+        }
+    }
+}
+
+/// The result of comparing two finite element fields with [`compare_fields`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldComparison<T> {
+    /// The difference $\norm{u_a - u_b}_{L^2}$.
+    pub l2_difference: T,
+    /// The norm $\norm{u_b}_{L^2}$ of the reference field.
+    pub l2_reference_norm: T,
+    /// The largest pointwise difference over all quadrature points.
+    pub max_difference: T,
+    /// The number of points at which the fields were compared.
+    pub num_points: usize,
+}
+
+impl<T: Real> FieldComparison<T> {
+    /// The relative $L^2$ difference $\norm{u_a - u_b}_{L^2} / \norm{u_b}_{L^2}$.
+    ///
+    /// If the reference norm is zero, the relative difference is zero if the absolute difference
+    /// is also zero, and infinite otherwise.
+    pub fn l2_relative_difference(&self) -> T {
+        if self.l2_reference_norm > T::zero() {
+            self.l2_difference / self.l2_reference_norm
+        } else if self.l2_difference == T::zero() {
+            T::zero()
+        } else {
+            T::from_f64(f64::INFINITY).unwrap()
+        }
+    }
+
+    /// Returns `true` if all tolerances are satisfied.
+    pub fn is_within(&self, tolerances: &FieldComparisonTolerances<T>) -> bool {
+        self.check(tolerances).is_ok()
+    }
+
+    /// Checks that all tolerances are satisfied, and otherwise returns an error that describes
+    /// which tolerances were violated.
+    pub fn check(&self, tolerances: &FieldComparisonTolerances<T>) -> eyre::Result<()> {
+        let mut violations = Vec::new();
+        if let Some(tol) = tolerances.l2_abs {
+            if exceeds(self.l2_difference, tol) {
+                violations.push(format!(
+                    "L2 difference {} exceeds tolerance {}",
+                    self.l2_difference, tol
+                ));
+            }
+        }
+        if let Some(tol) = tolerances.l2_rel {
+            let rel = self.l2_relative_difference();
+            if exceeds(rel, tol) {
+                violations.push(format!("relative L2 difference {} exceeds tolerance {}", rel, tol));
+            }
+        }
+        if let Some(tol) = tolerances.max_abs {
+            if exceeds(self.max_difference, tol) {
+                violations.push(format!(
+                    "max difference {} exceeds tolerance {}",
+                    self.max_difference, tol
+                ));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("Field comparison failed: {}", violations.join(", ")))
+        }
+    }
+}
+
+/// Returns `true` if `value` is not smaller than or equal to `tol`, which in particular
+/// means that NaN values are never considered to be within tolerance.
+fn exceeds<T: Real>(value: T, tol: T) -> bool {
+    !matches!(value.partial_cmp(&tol), Some(Ordering::Less | Ordering::Equal))
+}
+
+impl<T: Real> fmt::Display for FieldComparison<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "L2 difference: {} (relative: {}), max difference: {}, points: {}",
+            self.l2_difference,
+            self.l2_relative_difference(),
+            self.max_difference,
+            self.num_points
+        )
+    }
+}
+
+/// Compares the field $u_a$ defined on `space_a` with the field $u_b$ defined on `space_b`.
+///
+/// The comparison is carried out by integrating over the elements of `space_a` with the
+/// quadrature rules provided by `qtable_a`. At each quadrature point $\vec x$,
+/// $u_a(\vec x)$ is evaluated directly in the element of `space_a`, while $u_b(\vec x)$ is
+/// obtained by interpolating in `space_b`. The two spaces therefore do not need to share
+/// a mesh, and `space_b` will typically be a
+/// [`SpatiallyIndexed`](crate::space::SpatiallyIndexed) space.
+///
+/// The field $u_b$ is considered to be the reference field for the purpose of computing
+/// relative differences.
+///
+/// Points of `space_a` that lie outside the domain of `space_b` are treated as described by
+/// [`InterpolateInSpace::interpolate_at_points`].
+///
+/// # Errors
+///
+/// Returns an error if the lengths of the weight vectors are inconsistent with the number of
+/// nodes in the respective spaces.
+pub fn compare_fields<T, SolutionDim, SpaceA, SpaceB, QTable>(
+    space_a: &SpaceA,
+    u_a: DVectorView<T>,
+    space_b: &SpaceB,
+    u_b: DVectorView<T>,
+    qtable_a: &QTable,
+) -> eyre::Result<FieldComparison<T>>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    SpaceA: VolumetricFiniteElementSpace<T>,
+    SpaceB: InterpolateInSpace<T, SolutionDim, GeometryDim = SpaceA::GeometryDim>,
+    QTable: ?Sized + QuadratureTable<T, SpaceA::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, SpaceA::GeometryDim, SpaceA::ReferenceDim, SolutionDim>
+        + TriDimAllocator<T, SpaceB::GeometryDim, SpaceB::ReferenceDim, SolutionDim>,
+{
+    let s = SolutionDim::dim();
+    if u_a.len() != s * space_a.num_nodes() {
+        return Err(eyre!(
+            "Length of u_a ({}) is not consistent with solution dimension {} and {} nodes in space_a",
+            u_a.len(),
+            s,
+            space_a.num_nodes()
+        ));
+    }
+    if u_b.len() != s * space_b.num_nodes() {
+        return Err(eyre!(
+            "Length of u_b ({}) is not consistent with solution dimension {} and {} nodes in space_b",
+            u_b.len(),
+            s,
+            space_b.num_nodes()
+        ));
+    }
+
+    let mut quadrature_buffer = QuadratureBuffer::<T, SpaceA::ReferenceDim>::default();
+    let mut interpolation_buffer = InterpolationBuffer::default();
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    let mut values_a = Vec::new();
+
+    for element_index in 0..space_a.num_elements() {
+        quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable_a);
+        let mut element_buffer = interpolation_buffer.prepare_element_in_space(element_index, space_a, u_a, s);
+        for (&w, xi) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+            element_buffer.update_reference_point(xi, BufferUpdate::BasisValues);
+            let j = element_buffer.element_reference_jacobian();
+            points.push(element_buffer.map_reference_coords());
+            weights.push(w * volume_form(&j));
+            values_a.push(element_buffer.interpolate::<SolutionDim>());
+        }
+    }
+
+    let mut values_b = vec![OVector::<T, SolutionDim>::zeros(); points.len()];
+    space_b.interpolate_at_points(&points, u_b, &mut values_b);
+
+    let mut l2_difference_squared = T::zero();
+    let mut l2_reference_norm_squared = T::zero();
+    let mut max_difference = T::zero();
+    for (&w, u_a_x, u_b_x) in izip!(&weights, &values_a, &values_b) {
+        let diff = u_a_x - u_b_x;
+        l2_difference_squared += w * diff.norm_squared();
+        l2_reference_norm_squared += w * u_b_x.norm_squared();
+        max_difference = max_difference.max(diff.norm());
+    }
+
+    Ok(FieldComparison {
+        l2_difference: l2_difference_squared.sqrt(),
+        l2_reference_norm: l2_reference_norm_squared.sqrt(),
+        max_difference,
+        num_points: points.len(),
+    })
+}
+
+/// The result of comparing two meshes with [`compare_meshes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshComparison<T> {
+    /// Whether the two meshes have the same number of vertices.
+    pub same_vertex_count: bool,
+    /// Whether the two meshes have identical connectivity, i.e. the same cells in the same order.
+    pub same_connectivity: bool,
+    /// The largest distance between corresponding vertices, or `None` if the vertex counts differ.
+    pub max_vertex_distance: Option<T>,
+}
+
+impl<T: Real> MeshComparison<T> {
+    /// Returns `true` if the meshes have identical topology and all corresponding vertices
+    /// are within the given distance of each other.
+    pub fn is_within(&self, vertex_tolerance: T) -> bool {
+        self.same_connectivity
+            && self
+                .max_vertex_distance
+                .map(|dist| dist <= vertex_tolerance)
+                .unwrap_or(false)
+    }
+}
+
+/// Compares two meshes vertex by vertex and cell by cell.
+///
+/// Unlike [`compare_fields`], this requires the meshes to have the same ordering of vertices
+/// and cells in order to be considered equal.
+pub fn compare_meshes<T, D, C>(mesh_a: &Mesh<T, D, C>, mesh_b: &Mesh<T, D, C>) -> MeshComparison<T>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity + PartialEq,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let same_vertex_count = mesh_a.vertices().len() == mesh_b.vertices().len();
+    let same_connectivity = mesh_a.connectivity() == mesh_b.connectivity();
+    let max_vertex_distance = same_vertex_count.then(|| {
+        izip!(mesh_a.vertices(), mesh_b.vertices())
+            .map(|(a, b): (&OPoint<T, D>, &OPoint<T, D>)| (a - b).norm())
+            .fold(T::zero(), |max, dist| max.max(dist))
+    });
+
+    MeshComparison {
+        same_vertex_count,
+        same_connectivity: same_vertex_count && same_connectivity,
+        max_vertex_distance,
+    }
+}
@@ -13,12 +13,20 @@ use numeric_literals::replace_float_literals;
 use std::error::Error;
 use std::fmt::Debug;
 
+mod archetype;
+mod curved;
 mod hexahedron;
+mod hierarchical;
+mod lagrange;
 mod quadrilateral;
 mod segment;
 mod tetrahedron;
 mod triangle;
+pub use archetype::*;
+pub use curved::*;
 pub use hexahedron::*;
+pub use hierarchical::*;
+pub use lagrange::*;
 pub use quadrilateral::*;
 pub use segment::*;
 pub use tetrahedron::*;
@@ -132,14 +140,20 @@ macro_rules! impl_reference_finite_element_for_fixed {
 impl_reference_finite_element_for_fixed!(Tri3d2Element<T>);
 impl_reference_finite_element_for_fixed!(Tri6d2Element<T>);
 impl_reference_finite_element_for_fixed!(Quad4d2Element<T>);
+impl_reference_finite_element_for_fixed!(CurvedTri3d2Element<T>);
+impl_reference_finite_element_for_fixed!(CurvedQuad4d2Element<T>);
+impl_reference_finite_element_for_fixed!(ArcSegment2dElement<T>);
 impl_reference_finite_element_for_fixed!(Quad9d2Element<T>);
+impl_reference_finite_element_for_fixed!(Quad4d3Element<T>);
 impl_reference_finite_element_for_fixed!(Segment2d1Element<T>);
 impl_reference_finite_element_for_fixed!(Segment2d2Element<T>);
+impl_reference_finite_element_for_fixed!(Segment3d2Element<T>);
 impl_reference_finite_element_for_fixed!(Tet4Element<T>);
 impl_reference_finite_element_for_fixed!(Hex8Element<T>);
 impl_reference_finite_element_for_fixed!(Hex27Element<T>);
 impl_reference_finite_element_for_fixed!(Hex20Element<T>);
 impl_reference_finite_element_for_fixed!(Tri3d3Element<T>);
+impl_reference_finite_element_for_fixed!(Tri6d3Element<T>);
 impl_reference_finite_element_for_fixed!(Tet10Element<T>);
 impl_reference_finite_element_for_fixed!(Tet20Element<T>);
 
@@ -167,6 +181,30 @@ where
     ///  h = min |x - y| for x, y in K
     /// where K is the element and h is the diameter.
     fn diameter(&self) -> T;
+
+    /// Returns whether the transformation from the reference element is affine.
+    ///
+    /// This is the case for elements whose geometry is given by the vertices of a straight-sided
+    /// simplex, regardless of the order of the basis functions. The default implementation
+    /// returns `false`.
+    fn is_affine(&self) -> bool {
+        false
+    }
+
+    /// Returns the Jacobian of the transformation from the reference element if it is known to
+    /// be constant over the element.
+    ///
+    /// Assemblers may then use the same Jacobian, together with its determinant and inverse,
+    /// for every quadrature point in the element. The default implementation returns the
+    /// Jacobian at the origin for [affine](Self::is_affine) elements and `None` otherwise,
+    /// in which case the Jacobian is evaluated at every point.
+    fn constant_reference_jacobian(&self) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.is_affine()
+            .then(|| self.reference_jacobian(&OPoint::origin()))
+    }
 }
 
 /// TODO: Do we *really* need the Debug bound?
@@ -304,6 +342,24 @@ pub fn map_physical_coordinates<T, Element, GeometryDim>(
     element: &Element,
     x: &OPoint<T, GeometryDim>,
 ) -> Result<OPoint<T, GeometryDim>, Box<dyn Error>>
+where
+    T: Real,
+    Element: FiniteElement<T, GeometryDim = GeometryDim, ReferenceDim = GeometryDim>,
+    GeometryDim: DimName + DimMin<GeometryDim, Output = GeometryDim>,
+    DefaultAllocator: DimAllocator<T, GeometryDim>,
+{
+    map_physical_coordinates_with_iterations(element, x).map(|(xi, _)| xi)
+}
+
+/// Same as [`map_physical_coordinates`], but additionally returns the number of Newton iterations
+/// that were needed.
+///
+/// The iteration count is a useful indicator of the quality of the element geometry: strongly
+/// distorted elements typically require many more iterations than well-shaped elements.
+pub fn map_physical_coordinates_with_iterations<T, Element, GeometryDim>(
+    element: &Element,
+    x: &OPoint<T, GeometryDim>,
+) -> Result<(OPoint<T, GeometryDim>, usize), Box<dyn Error>>
 where
     T: Real,
     Element: FiniteElement<T, GeometryDim = GeometryDim, ReferenceDim = GeometryDim>,
@@ -359,6 +415,8 @@ where
         // TODO: eps is here hard-coded without respect to the type T, so it will not be appropriate
         // across e.g. different floating point types. Fix this!
         tolerance: T::from_f64(1e-12).unwrap() * element.diameter(),
+        relative_tolerance: T::zero(),
+        increment_tolerance: None,
     };
 
     let mut xi = OVector::<T, GeometryDim>::zeros();
@@ -376,9 +434,9 @@ where
         };
     }
 
-    newton(f, &mut slice!(xi), &mut slice!(f_val), &mut slice!(dx), settings)?;
+    let iterations = newton(f, &mut slice!(xi), &mut slice!(f_val), &mut slice!(dx), settings)?;
 
-    Ok(OPoint::from(xi))
+    Ok((OPoint::from(xi), iterations))
 }
 
 /// Projects physical coordinates `x` to reference coordinates `xi` by solving the equation
@@ -0,0 +1,307 @@
+use crate::allocators::BiDimAllocator;
+use crate::element::{
+    FiniteElement, Hex20Element, Hex27Element, Hex8Element, LagrangeSimplexBasis, LagrangeSimplexElement,
+    Quad4d2Element, Quad4d3Element, Quad9d2Element, Segment2d1Element, Segment2d2Element, Segment3d2Element,
+    Tet10Element, Tet20Element, Tet4Element, Tri3d2Element, Tri3d3Element, Tri6d2Element, Tri6d3Element,
+};
+use crate::nalgebra::{point, DMatrix, DefaultAllocator, DimName, MatrixViewMut, OMatrix, OPoint, OVector, Point1};
+use crate::{Real, SmallDim};
+use itertools::Itertools;
+use std::any::type_name;
+use std::sync::Arc;
+
+/// A finite element with known reference nodes and a representative instance.
+///
+/// Implementing this trait for an element makes it possible to check the element with
+/// [`assert_element_invariants`].
+pub trait ElementArchetype<T>: FiniteElement<T>
+where
+    T: Real,
+    DefaultAllocator: BiDimAllocator<T, Self::GeometryDim, Self::ReferenceDim>,
+{
+    /// Returns the reference coordinates of the nodes, in the order of the basis functions.
+    ///
+    /// The convex hull of the nodes must be the reference domain.
+    fn reference_nodes() -> Vec<OPoint<T, Self::ReferenceDim>>;
+
+    /// Returns an instance of the element with non-degenerate affine geometry.
+    ///
+    /// The geometry should not coincide with the reference element, so that errors in the
+    /// mapping from reference to physical coordinates are not masked by an identity map.
+    fn archetype() -> Self;
+}
+
+/// Absolute tolerance for quantities that are exact up to round-off.
+const EXACT_TOLERANCE: f64 = 1e-12;
+/// Absolute tolerance for comparisons with finite difference approximations.
+const FINITE_DIFFERENCE_TOLERANCE: f64 = 1e-6;
+const FINITE_DIFFERENCE_STEP: f64 = 1e-6;
+
+/// Checks basic invariants of an element, panicking with a descriptive message if any of them
+/// is violated.
+///
+/// The checks are intended to give uniform test coverage for all elements, and comprise
+///
+/// - the number of reference nodes matches the number of nodes of the element,
+/// - the Lagrange property $\varphi_i(\vec \xi_j) = \delta_{ij}$ at the reference nodes,
+/// - the partition of unity $\sum_i \varphi_i = 1$ and correspondingly
+///   $\sum_i \nabla \varphi_i = 0$,
+/// - consistency of the basis gradients with finite differences of the basis functions,
+/// - consistency of the reference Jacobian with finite differences of the reference map,
+/// - the reference map is reproduced by interpolating the mapped nodes with the basis,
+/// - the reference Jacobian has full rank,
+/// - the diameter is at least the largest distance between mapped nodes.
+///
+/// Everything except the Lagrange property is checked at a fixed set of points inside the
+/// reference domain, given by the centroid of the reference nodes and points between the
+/// centroid and each node.
+///
+/// A new element is typically tested with a single line such as
+/// `assert_element_invariants::<Tri3d2Element<f64>>()`.
+///
+/// # Panics
+///
+/// Panics if any of the invariants is violated.
+pub fn assert_element_invariants<E>()
+where
+    E: ElementArchetype<f64>,
+    DefaultAllocator: BiDimAllocator<f64, E::GeometryDim, E::ReferenceDim>,
+{
+    let name = type_name::<E>();
+    let element = E::archetype();
+    let nodes = E::reference_nodes();
+    let n = element.num_nodes();
+    assert_eq!(
+        nodes.len(),
+        n,
+        "{}: Number of reference nodes must match the number of element nodes",
+        name
+    );
+
+    let basis = |xi: &OPoint<f64, E::ReferenceDim>| {
+        let mut phi = vec![0.0; n];
+        element.populate_basis(&mut phi, xi);
+        phi
+    };
+    let gradients = |xi: &OPoint<f64, E::ReferenceDim>| {
+        let mut gradients = DMatrix::zeros(E::ReferenceDim::dim(), n);
+        element.populate_basis_gradients(MatrixViewMut::from(&mut gradients), xi);
+        gradients
+    };
+
+    for (j, xi_j) in nodes.iter().enumerate() {
+        for (i, phi_i) in basis(xi_j).into_iter().enumerate() {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!(
+                (phi_i - expected).abs() <= EXACT_TOLERANCE,
+                "{}: Lagrange property violated, basis function {} is {} at node {}",
+                name,
+                i,
+                phi_i,
+                j
+            );
+        }
+    }
+
+    let mapped_nodes: Vec<_> = nodes
+        .iter()
+        .map(|xi| element.map_reference_coords(xi))
+        .collect();
+    let max_node_distance = mapped_nodes
+        .iter()
+        .tuple_combinations()
+        .map(|(x, y)| (x - y).norm())
+        .fold(0.0, f64::max);
+    assert!(
+        element.diameter() >= max_node_distance - EXACT_TOLERANCE,
+        "{}: Diameter {} is smaller than the largest distance {} between nodes",
+        name,
+        element.diameter(),
+        max_node_distance
+    );
+
+    let h = FINITE_DIFFERENCE_STEP;
+    for xi in sample_points(&nodes) {
+        let phi = basis(&xi);
+        let phi_sum: f64 = phi.iter().sum();
+        assert!(
+            (phi_sum - 1.0).abs() <= EXACT_TOLERANCE,
+            "{}: Basis functions sum to {} at {:?}",
+            name,
+            phi_sum,
+            xi
+        );
+
+        let grad = gradients(&xi);
+        let grad_sum = grad.column_sum();
+        assert!(
+            grad_sum.amax() <= EXACT_TOLERANCE,
+            "{}: Basis gradients sum to {:?} at {:?}",
+            name,
+            grad_sum.as_slice(),
+            xi
+        );
+
+        let jacobian = element.reference_jacobian(&xi);
+        for k in 0..E::ReferenceDim::dim() {
+            let mut xi_plus = xi.clone();
+            let mut xi_minus = xi.clone();
+            xi_plus[k] += h;
+            xi_minus[k] -= h;
+
+            for (i, (phi_plus, phi_minus)) in basis(&xi_plus)
+                .into_iter()
+                .zip(basis(&xi_minus))
+                .enumerate()
+            {
+                let fd_derivative = (phi_plus - phi_minus) / (2.0 * h);
+                assert!(
+                    (grad[(k, i)] - fd_derivative).abs() <= FINITE_DIFFERENCE_TOLERANCE,
+                    "{}: Derivative {} of basis function {} is {}, but finite differences give {} at {:?}",
+                    name,
+                    k,
+                    i,
+                    grad[(k, i)],
+                    fd_derivative,
+                    xi
+                );
+            }
+
+            let fd_column =
+                (element.map_reference_coords(&xi_plus) - element.map_reference_coords(&xi_minus)) / (2.0 * h);
+            let difference = (jacobian.column(k) - &fd_column).amax();
+            assert!(
+                difference <= FINITE_DIFFERENCE_TOLERANCE,
+                "{}: Column {} of the reference Jacobian differs from finite differences by {} at {:?}",
+                name,
+                k,
+                difference,
+                xi
+            );
+        }
+
+        let interpolated = mapped_nodes
+            .iter()
+            .zip(&phi)
+            .fold(OVector::<f64, E::GeometryDim>::zeros(), |sum, (x, &phi_i)| {
+                sum + &x.coords * phi_i
+            });
+        let difference = (element.map_reference_coords(&xi).coords - interpolated).amax();
+        assert!(
+            difference <= EXACT_TOLERANCE,
+            "{}: Reference map differs from the interpolation of the mapped nodes by {} at {:?}",
+            name,
+            difference,
+            xi
+        );
+
+        // The Jacobian has full column rank if and only if the Gram determinant is positive
+        let gram: OMatrix<f64, E::ReferenceDim, E::ReferenceDim> = jacobian.transpose() * &jacobian;
+        assert!(
+            gram.determinant() > EXACT_TOLERANCE,
+            "{}: Reference Jacobian is degenerate at {:?}",
+            name,
+            xi
+        );
+    }
+}
+
+/// Returns the centroid of the nodes, and for each node the points a quarter and nine tenths
+/// of the way from the centroid to the node.
+fn sample_points<D>(nodes: &[OPoint<f64, D>]) -> Vec<OPoint<f64, D>>
+where
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let centroid = nodes
+        .iter()
+        .fold(OVector::<f64, D>::zeros(), |sum, node| sum + &node.coords)
+        / nodes.len() as f64;
+    let centroid = OPoint::from(centroid);
+    let mut points = vec![centroid.clone()];
+    for node in nodes {
+        for t in [0.25, 0.9] {
+            points.push(OPoint::from(&centroid.coords + (&node.coords - &centroid.coords) * t));
+        }
+    }
+    points
+}
+
+/// Maps reference coordinates to physical coordinates with a fixed, orientation-preserving
+/// affine map of full rank.
+fn archetype_map<T, GeometryDim, ReferenceDim>(xi: &OPoint<T, ReferenceDim>) -> OPoint<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: DimName,
+    ReferenceDim: DimName,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    const A: [[f64; 3]; 3] = [[1.2, 0.3, 0.1], [-0.2, 0.9, 0.25], [0.15, -0.1, 1.1]];
+    const B: [f64; 3] = [0.5, -0.25, 1.0];
+    let a = OMatrix::<T, GeometryDim, ReferenceDim>::from_fn(|i, j| T::from_f64(A[i][j]).unwrap());
+    let b = OVector::<T, GeometryDim>::from_fn(|i, _| T::from_f64(B[i]).unwrap());
+    OPoint::from(a * &xi.coords + b)
+}
+
+/// Implements `ElementArchetype` for an element with a `from_vertices` constructor, given an
+/// expression for the array of reference nodes.
+macro_rules! impl_element_archetype {
+    ($element:ident, $reference_nodes:expr) => {
+        impl<T: Real> ElementArchetype<T> for $element<T> {
+            fn reference_nodes() -> Vec<OPoint<T, Self::ReferenceDim>> {
+                $reference_nodes.to_vec()
+            }
+
+            fn archetype() -> Self {
+                Self::from_vertices($reference_nodes.map(|xi| archetype_map(&xi)))
+            }
+        }
+    };
+}
+
+/// Returns the reference nodes of a segment with the given reference coordinates.
+fn segment_nodes<T: Real, const N: usize>(coords: [f64; N]) -> [Point1<T>; N] {
+    coords.map(|xi| point![T::from_f64(xi).unwrap()])
+}
+
+impl_element_archetype!(Segment2d1Element, segment_nodes::<T, 2>([-1.0, 1.0]));
+impl_element_archetype!(Segment2d2Element, segment_nodes::<T, 2>([-1.0, 1.0]));
+impl_element_archetype!(Segment3d2Element, segment_nodes::<T, 3>([-1.0, 0.0, 1.0]));
+impl_element_archetype!(Tri3d2Element, *Tri3d2Element::<T>::reference().vertices());
+impl_element_archetype!(Tri6d2Element, *Tri6d2Element::<T>::reference().vertices());
+impl_element_archetype!(Tri3d3Element, *Tri3d2Element::<T>::reference().vertices());
+impl_element_archetype!(Tri6d3Element, *Tri6d2Element::<T>::reference().vertices());
+impl_element_archetype!(Quad4d2Element, *Quad4d2Element::<T>::reference().vertices());
+impl_element_archetype!(Quad4d3Element, *Quad4d2Element::<T>::reference().vertices());
+impl_element_archetype!(Quad9d2Element, *Quad9d2Element::<T>::reference().vertices());
+impl_element_archetype!(Tet4Element, *Tet4Element::<T>::reference().vertices());
+impl_element_archetype!(Tet10Element, *Tet10Element::<T>::reference().vertices());
+impl_element_archetype!(Tet20Element, *Tet20Element::<T>::reference().vertices());
+impl_element_archetype!(Hex8Element, *Hex8Element::<T>::reference().vertices());
+impl_element_archetype!(
+    Hex20Element,
+    <[_; 20]>::try_from(Hex20Element::<T>::reference().vertices()).unwrap()
+);
+impl_element_archetype!(
+    Hex27Element,
+    <[_; 27]>::try_from(Hex27Element::<T>::reference().vertices()).unwrap()
+);
+
+impl<T, D, const ORDER: usize> ElementArchetype<T> for LagrangeSimplexElement<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn reference_nodes() -> Vec<OPoint<T, D>> {
+        LagrangeSimplexBasis::<T, D, ORDER>::equispaced()
+            .reference_nodes()
+            .to_vec()
+    }
+
+    fn archetype() -> Self {
+        let reference = Self::reference(Arc::new(LagrangeSimplexBasis::equispaced()));
+        let vertices = reference.vertices().iter().map(archetype_map).collect();
+        Self::from_vertices_and_basis(vertices, reference.basis().clone())
+    }
+}
@@ -0,0 +1,512 @@
+use crate::allocators::BiDimAllocator;
+use crate::element::{
+    FiniteElement, FixedNodesReferenceFiniteElement, Quad4d2Element, SurfaceFiniteElement, Tri3d2Element,
+};
+use crate::nalgebra::{
+    distance, DefaultAllocator, Matrix1x2, Matrix1x3, Matrix1x4, Matrix2, Matrix2x3, Matrix2x4, Point1, Point2, Scalar,
+    Vector2, U1, U2, U3, U4,
+};
+use crate::quadrature::QuadraturePair;
+use crate::Real;
+use itertools::Itertools;
+use numeric_literals::replace_float_literals;
+
+/// A circular arc in two dimensions.
+///
+/// The arc is parametrized by the angle $\theta$, running from the
+/// [start angle](Self::start_angle) $\theta_0$ to $\theta_0 + \Delta \theta$, where
+/// $\Delta \theta$ is the [sweep angle](Self::sweep_angle). A positive sweep angle corresponds to
+/// a counter-clockwise arc. Points on the arc are given by
+/// <div>$$
+/// x(\theta) = c + r (\cos \theta, \sin \theta),
+/// $$</div>
+/// where $c$ is the center and $r$ the radius of the arc. As a boundary of finite elements, the
+/// arc is parametrized by the reference coordinate $s \in [-1, 1]$ through
+/// $\theta(s) = \theta_0 + \frac{1}{2} (1 + s) \Delta \theta$, see [`point`](Self::point).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CircularArc2d<T>
+where
+    T: Scalar,
+{
+    center: Point2<T>,
+    radius: T,
+    start_angle: T,
+    sweep_angle: T,
+}
+
+impl<T: Real> CircularArc2d<T> {
+    /// Constructs the arc around the given center from the start angle to the end angle.
+    ///
+    /// The arc is counter-clockwise if `end_angle > start_angle` and clockwise otherwise.
+    pub fn from_center_and_angles(center: Point2<T>, radius: T, start_angle: T, end_angle: T) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            sweep_angle: end_angle - start_angle,
+        }
+    }
+
+    /// Constructs the shorter of the two arcs around the given center that connect the start and
+    /// end points.
+    ///
+    /// The radius is taken to be the distance between the start point and the center. The end
+    /// point is assumed to have the same distance to the center.
+    pub fn from_end_points_and_center(start: Point2<T>, end: Point2<T>, center: Point2<T>) -> Self {
+        let a = start - center;
+        let b = end - center;
+        let start_angle = a.y.atan2(a.x);
+        // The signed angle between a and b, in [-pi, pi]
+        let sweep_angle = a.perp(&b).atan2(a.dot(&b));
+        Self {
+            center,
+            radius: a.norm(),
+            start_angle,
+            sweep_angle,
+        }
+    }
+
+    pub fn center(&self) -> &Point2<T> {
+        &self.center
+    }
+
+    pub fn radius(&self) -> T {
+        self.radius
+    }
+
+    pub fn start_angle(&self) -> T {
+        self.start_angle
+    }
+
+    pub fn end_angle(&self) -> T {
+        self.start_angle + self.sweep_angle
+    }
+
+    pub fn sweep_angle(&self) -> T {
+        self.sweep_angle
+    }
+
+    /// The arc traversed in the opposite direction.
+    pub fn reversed(&self) -> Self {
+        Self::from_center_and_angles(self.center, self.radius, self.end_angle(), self.start_angle)
+    }
+
+    pub fn length(&self) -> T {
+        self.radius * self.sweep_angle.abs()
+    }
+
+    /// The point on the arc with the given angle.
+    pub fn point_at_angle(&self, angle: T) -> Point2<T> {
+        self.center + Vector2::new(angle.cos(), angle.sin()) * self.radius
+    }
+
+    pub fn start(&self) -> Point2<T> {
+        self.point_at_angle(self.start_angle)
+    }
+
+    pub fn end(&self) -> Point2<T> {
+        self.point_at_angle(self.end_angle())
+    }
+
+    /// The point on the arc with the given reference coordinate $s \in [-1, 1]$.
+    pub fn point(&self, s: T) -> Point2<T> {
+        self.point_at_angle(self.angle(s))
+    }
+
+    /// The derivative $\mathrm{d}x / \mathrm{d}s$ of the point on the arc with respect to the
+    /// reference coordinate.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn tangent(&self, s: T) -> Vector2<T> {
+        let theta = self.angle(s);
+        Vector2::new(-theta.sin(), theta.cos()) * (self.radius * self.sweep_angle / 2.0)
+    }
+
+    /// The largest distance between two points on the arc.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn diameter(&self) -> T {
+        if self.sweep_angle.abs() >= T::pi() {
+            2.0 * self.radius
+        } else {
+            distance(&self.start(), &self.end())
+        }
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn angle(&self, s: T) -> T {
+        self.start_angle + (1.0 + s) * self.sweep_angle / 2.0
+    }
+
+    /// Returns the arc oriented such that it starts at the end point closest to `a`.
+    fn oriented(&self, a: &Point2<T>) -> Self {
+        if distance(&self.start(), a) <= distance(&self.end(), a) {
+            *self
+        } else {
+            self.reversed()
+        }
+    }
+
+    /// Samples points along the arc, excluding the end points.
+    fn interior_samples(&self) -> impl Iterator<Item = Point2<T>> + '_ {
+        let n = 8;
+        (1..n).map(move |i| {
+            let s = T::from_usize(2 * i).unwrap() / T::from_usize(n).unwrap() - T::one();
+            self.point(s)
+        })
+    }
+}
+
+/// The deviation $d(s)$ of an arc from the straight edge between `a` and `b`, together with its
+/// derivative $d'(s)$.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn edge_deviation<T: Real>(arc: &CircularArc2d<T>, a: &Point2<T>, b: &Point2<T>, s: T) -> (Vector2<T>, Vector2<T>) {
+    let chord = a.coords * ((1.0 - s) / 2.0) + b.coords * ((1.0 + s) / 2.0);
+    let deviation = arc.point(s).coords - chord;
+    let deviation_derivative = arc.tangent(s) - (b - a) / 2.0;
+    (deviation, deviation_derivative)
+}
+
+/// A boundary element in two dimensions whose geometry is a [circular arc](CircularArc2d).
+///
+/// The element has two nodes at the end points of the arc with linear basis functions in the
+/// reference coordinate, like [`Segment2d2Element`](crate::element::Segment2d2Element), but the
+/// geometry is represented exactly. Line integrals over circular boundaries, such as the
+/// boundaries of holes or fillets, are therefore not affected by geometric approximation errors.
+/// The normal is oriented like that of [`Segment2d2Element`](crate::element::Segment2d2Element),
+/// i.e. it points outwards from the arc for counter-clockwise arcs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ArcSegment2dElement<T>
+where
+    T: Scalar,
+{
+    arc: CircularArc2d<T>,
+}
+
+impl<T: Real> ArcSegment2dElement<T> {
+    pub fn from_arc(arc: CircularArc2d<T>) -> Self {
+        Self { arc }
+    }
+
+    pub fn arc(&self) -> &CircularArc2d<T> {
+        &self.arc
+    }
+
+    pub fn vertices(&self) -> [Point2<T>; 2] {
+        [self.arc.start(), self.arc.end()]
+    }
+}
+
+impl<T: Real> FixedNodesReferenceFiniteElement<T> for ArcSegment2dElement<T> {
+    type ReferenceDim = U1;
+    type NodalDim = U2;
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn evaluate_basis(&self, xi: &Point1<T>) -> Matrix1x2<T> {
+        Matrix1x2::new((1.0 - xi.x) / 2.0, (1.0 + xi.x) / 2.0)
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn gradients(&self, _xi: &Point1<T>) -> Matrix1x2<T> {
+        Matrix1x2::new(-0.5, 0.5)
+    }
+}
+
+impl<T: Real> FiniteElement<T> for ArcSegment2dElement<T> {
+    type GeometryDim = U2;
+
+    fn reference_jacobian(&self, xi: &Point1<T>) -> Vector2<T> {
+        self.arc.tangent(xi.x)
+    }
+
+    fn map_reference_coords(&self, xi: &Point1<T>) -> Point2<T> {
+        self.arc.point(xi.x)
+    }
+
+    fn diameter(&self) -> T {
+        self.arc.diameter()
+    }
+}
+
+impl<T: Real> SurfaceFiniteElement<T> for ArcSegment2dElement<T> {
+    fn normal(&self, xi: &Point1<T>) -> Vector2<T> {
+        let tangent = self.reference_jacobian(xi);
+        Vector2::new(tangent.y, -tangent.x).normalize()
+    }
+}
+
+/// A linear triangle in two dimensions whose edges may be [circular arcs](CircularArc2d).
+///
+/// The basis functions are those of [`Tri3d2Element`], but the geometry is given by a blending
+/// map that reproduces the curved edges exactly. Denoting the barycentric coordinates by
+/// $\lambda_i$, the map is
+/// <div>$$
+/// x = \sum_{i} \lambda_i x_i
+///   + \sum_{(a, b)} \frac{4 \lambda_a \lambda_b}{1 - s^2} d_{ab}(s),
+///   \qquad s = \lambda_b - \lambda_a,
+/// $$</div>
+/// where the second sum runs over the curved edges from vertex $a$ to vertex $b$, and
+/// $d_{ab}(s)$ is the deviation of the arc from the straight edge. Edge $i$ connects vertex
+/// $i$ to vertex $i + 1$ (modulo 3). Since the map is exact, integrals over domains with circular
+/// holes or fillets converge with the accuracy of the quadrature rule, rather than being limited
+/// by the approximation of the geometry.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CurvedTri3d2Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point2<T>; 3],
+    arcs: [Option<CircularArc2d<T>>; 3],
+}
+
+impl<T: Real> CurvedTri3d2Element<T> {
+    /// Constructs a triangle with straight edges.
+    pub fn from_vertices(vertices: [Point2<T>; 3]) -> Self {
+        Self {
+            vertices,
+            arcs: [None; 3],
+        }
+    }
+
+    /// Replaces the given edge by an arc.
+    ///
+    /// The end points of the arc must coincide with the vertices of the edge, but the arc may
+    /// be oriented in either direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edge index is out of bounds.
+    pub fn with_arc_edge(mut self, edge_index: usize, arc: CircularArc2d<T>) -> Self {
+        let a = &self.vertices[edge_index];
+        self.arcs[edge_index] = Some(arc.oriented(a));
+        self
+    }
+
+    pub fn vertices(&self) -> &[Point2<T>; 3] {
+        &self.vertices
+    }
+
+    /// The arcs of the curved edges, or `None` for straight edges.
+    pub fn arc_edges(&self) -> &[Option<CircularArc2d<T>>; 3] {
+        &self.arcs
+    }
+
+    fn barycentric_coordinates(xi: &Point2<T>) -> [T; 3] {
+        let phi = Tri3d2Element::reference().evaluate_basis(xi);
+        [phi[0], phi[1], phi[2]]
+    }
+}
+
+impl<T: Real> FixedNodesReferenceFiniteElement<T> for CurvedTri3d2Element<T> {
+    type ReferenceDim = U2;
+    type NodalDim = U3;
+
+    fn evaluate_basis(&self, xi: &Point2<T>) -> Matrix1x3<T> {
+        Tri3d2Element::reference().evaluate_basis(xi)
+    }
+
+    fn gradients(&self, xi: &Point2<T>) -> Matrix2x3<T> {
+        Tri3d2Element::reference().gradients(xi)
+    }
+}
+
+impl<T: Real> FiniteElement<T> for CurvedTri3d2Element<T> {
+    type GeometryDim = U2;
+
+    #[allow(non_snake_case)]
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix2<T> {
+        let lambda = Self::barycentric_coordinates(xi);
+        let G = self.gradients(xi);
+        let mut J = Tri3d2Element::from_vertices(self.vertices).reference_jacobian(xi);
+        for (i, arc) in self.arcs.iter().enumerate() {
+            let (a, b) = (i, (i + 1) % 3);
+            let q = 1.0 - (lambda[b] - lambda[a]).powi(2);
+            if let Some(arc) = arc.filter(|_| q > T::default_epsilon().sqrt()) {
+                let s = lambda[b] - lambda[a];
+                let (d, d_prime) = edge_deviation(&arc, &self.vertices[a], &self.vertices[b], s);
+                // Term: lambda_a lambda_b g(s) with g(s) = 4 d(s) / (1 - s^2)
+                let g = d * (4.0 / q);
+                let g_prime = (d_prime * q + d * (2.0 * s)) * (4.0 / (q * q));
+                let grad_product = G.column(a) * lambda[b] + G.column(b) * lambda[a];
+                let grad_s = G.column(b) - G.column(a);
+                J += g * grad_product.transpose() + g_prime * grad_s.transpose() * (lambda[a] * lambda[b]);
+            }
+        }
+        J
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point2<T> {
+        let lambda = Self::barycentric_coordinates(xi);
+        let mut x = Tri3d2Element::from_vertices(self.vertices).map_reference_coords(xi);
+        for (i, arc) in self.arcs.iter().enumerate() {
+            let (a, b) = (i, (i + 1) % 3);
+            let s = lambda[b] - lambda[a];
+            let q = 1.0 - s * s;
+            if let Some(arc) = arc.filter(|_| q > T::default_epsilon().sqrt()) {
+                let (d, _) = edge_deviation(&arc, &self.vertices[a], &self.vertices[b], s);
+                x += d * (4.0 * lambda[a] * lambda[b] / q);
+            }
+        }
+        x
+    }
+
+    /// The diameter, approximated by the largest distance between points sampled along the
+    /// edges of the element.
+    fn diameter(&self) -> T {
+        boundary_diameter(&self.vertices, &self.arcs)
+    }
+
+    fn is_affine(&self) -> bool {
+        self.arcs.iter().all(Option::is_none)
+    }
+}
+
+/// A bilinear quadrilateral in two dimensions whose edges may be [circular arcs](CircularArc2d).
+///
+/// The basis functions are those of [`Quad4d2Element`], but the geometry is given by the
+/// transfinite (Gordon-Hall) interpolation of the edges, which reproduces the curved edges
+/// exactly. Edge $i$ connects vertex $i$ to vertex $i + 1$ (modulo 4), so that edge $0$ is the
+/// edge $\eta = -1$ of the reference quadrilateral and the edges are ordered counter-clockwise.
+///
+/// Curved quadrilaterals are well suited to structured meshes around circular holes, where each
+/// ring of elements is bounded by concentric arcs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CurvedQuad4d2Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point2<T>; 4],
+    arcs: [Option<CircularArc2d<T>>; 4],
+}
+
+impl<T: Real> CurvedQuad4d2Element<T> {
+    /// Constructs a quadrilateral with straight edges.
+    pub fn from_vertices(vertices: [Point2<T>; 4]) -> Self {
+        Self {
+            vertices,
+            arcs: [None; 4],
+        }
+    }
+
+    /// Replaces the given edge by an arc.
+    ///
+    /// The end points of the arc must coincide with the vertices of the edge, but the arc may
+    /// be oriented in either direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edge index is out of bounds.
+    pub fn with_arc_edge(mut self, edge_index: usize, arc: CircularArc2d<T>) -> Self {
+        let a = &self.vertices[edge_index];
+        self.arcs[edge_index] = Some(arc.oriented(a));
+        self
+    }
+
+    pub fn vertices(&self) -> &[Point2<T>; 4] {
+        &self.vertices
+    }
+
+    /// The arcs of the curved edges, or `None` for straight edges.
+    pub fn arc_edges(&self) -> &[Option<CircularArc2d<T>>; 4] {
+        &self.arcs
+    }
+
+    /// Returns the edge parameter $s$, the factor $\partial s / \partial \xi$ and the blending
+    /// weight, together with its gradient, for each edge.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn edge_blending(xi: &Point2<T>) -> [(T, Vector2<T>, T, Vector2<T>); 4] {
+        let (x, y) = (xi.x, xi.y);
+        [
+            (x, Vector2::new(1.0, 0.0), (1.0 - y) / 2.0, Vector2::new(0.0, -0.5)),
+            (y, Vector2::new(0.0, 1.0), (1.0 + x) / 2.0, Vector2::new(0.5, 0.0)),
+            (-x, Vector2::new(-1.0, 0.0), (1.0 + y) / 2.0, Vector2::new(0.0, 0.5)),
+            (-y, Vector2::new(0.0, -1.0), (1.0 - x) / 2.0, Vector2::new(-0.5, 0.0)),
+        ]
+    }
+}
+
+impl<T: Real> FixedNodesReferenceFiniteElement<T> for CurvedQuad4d2Element<T> {
+    type ReferenceDim = U2;
+    type NodalDim = U4;
+
+    fn evaluate_basis(&self, xi: &Point2<T>) -> Matrix1x4<T> {
+        Quad4d2Element::reference().evaluate_basis(xi)
+    }
+
+    fn gradients(&self, xi: &Point2<T>) -> Matrix2x4<T> {
+        Quad4d2Element::reference().gradients(xi)
+    }
+}
+
+impl<T: Real> FiniteElement<T> for CurvedQuad4d2Element<T> {
+    type GeometryDim = U2;
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix2<T> {
+        let mut J = Quad4d2Element::from_vertices(self.vertices).reference_jacobian(xi);
+        for (i, (s, grad_s, w, grad_w)) in Self::edge_blending(xi).into_iter().enumerate() {
+            if let Some(arc) = &self.arcs[i] {
+                let (d, d_prime) = edge_deviation(arc, &self.vertices[i], &self.vertices[(i + 1) % 4], s);
+                J += d * grad_w.transpose() + d_prime * grad_s.transpose() * w;
+            }
+        }
+        J
+    }
+
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point2<T> {
+        let mut x = Quad4d2Element::from_vertices(self.vertices).map_reference_coords(xi);
+        for (i, (s, _, w, _)) in Self::edge_blending(xi).into_iter().enumerate() {
+            if let Some(arc) = &self.arcs[i] {
+                let (d, _) = edge_deviation(arc, &self.vertices[i], &self.vertices[(i + 1) % 4], s);
+                x += d * w;
+            }
+        }
+        x
+    }
+
+    /// The diameter, approximated by the largest distance between points sampled along the
+    /// edges of the element.
+    fn diameter(&self) -> T {
+        boundary_diameter(&self.vertices, &self.arcs)
+    }
+}
+
+fn boundary_diameter<T: Real>(vertices: &[Point2<T>], arcs: &[Option<CircularArc2d<T>>]) -> T {
+    let arc_samples = arcs.iter().flatten().flat_map(|arc| arc.interior_samples());
+    let samples: Vec<_> = vertices.iter().copied().chain(arc_samples).collect();
+    samples
+        .iter()
+        .tuple_combinations()
+        .map(|(x, y)| distance(x, y))
+        .fold(T::zero(), T::max)
+}
+
+/// Maps a quadrature rule on the reference domain of an element to a rule on the element.
+///
+/// The points are mapped to physical coordinates, and the weights are scaled by the measure
+/// $\sqrt{\det (J^T J)}$ of the Jacobian $J$ of the reference-to-physical map, which reduces to
+/// $|\det J|$ for volumetric elements and to $|J|$ for curves. For elements with
+/// [exact geometry](CurvedQuad4d2Element), the resulting rule integrates over the exact domain
+/// of the element, e.g. a disk with a circular hole or a circular boundary segment.
+pub fn map_quadrature_to_element<T, Element>(
+    element: &Element,
+    (weights, points): &QuadraturePair<T, Element::ReferenceDim>,
+) -> QuadraturePair<T, Element::GeometryDim>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    weights
+        .iter()
+        .zip(points)
+        .map(|(&w, xi)| {
+            let jacobian = element.reference_jacobian(xi);
+            let measure = (jacobian.transpose() * &jacobian)
+                .determinant()
+                .abs()
+                .sqrt();
+            (w * measure, element.map_reference_coords(xi))
+        })
+        .unzip()
+}
@@ -0,0 +1,465 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::nalgebra::{DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar};
+use crate::{Real, SmallDim};
+use itertools::Itertools;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Evaluates the one-dimensional hierarchical shape functions $l_0, \dots, l_p$ and their
+/// derivatives at the given point.
+///
+/// The first two functions are the linear functions $l_0 = (1 - \xi) / 2$ and
+/// $l_1 = (1 + \xi) / 2$. The remaining functions are the integrated Legendre (Lobatto) bubbles
+/// <div>$$
+/// l_k(\xi) = \frac{P_k(\xi) - P_{k - 2}(\xi)}{\sqrt{2 (2 k - 1)}}, \quad k \geq 2,
+/// $$</div>
+/// which vanish at $\pm 1$, and satisfy $l_k(-\xi) = (-1)^k l_k(\xi)$.
+fn lobatto_functions<T: Real>(p: usize, xi: T) -> (Vec<T>, Vec<T>) {
+    let c = |x: f64| T::from_f64(x).unwrap();
+    let mut legendre = vec![T::one(), xi];
+    for n in 1..p {
+        let n_t = c(n as f64);
+        let next = ((c(2.0) * n_t + T::one()) * xi * legendre[n] - n_t * legendre[n - 1]) / (n_t + T::one());
+        legendre.push(next);
+    }
+    let mut values = vec![(T::one() - xi) / c(2.0), (T::one() + xi) / c(2.0)];
+    let mut derivatives = vec![c(-0.5), c(0.5)];
+    for k in 2..=p {
+        let k_f = k as f64;
+        values.push((legendre[k] - legendre[k - 2]) / c((2.0 * (2.0 * k_f - 1.0)).sqrt()));
+        derivatives.push(c(((2.0 * k_f - 1.0) / 2.0).sqrt()) * legendre[k - 1]);
+    }
+    (values, derivatives)
+}
+
+/// The topological entity of the reference element that a hierarchical mode is associated with.
+///
+/// The one-dimensional index of a mode in each coordinate direction refers to the functions
+/// $l_0, l_1, l_2, \dots$, where $l_0$ and $l_1$ are linear and $l_k$ for $k \geq 2$ are
+/// bubbles of degree $k$.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HierarchicalMode {
+    /// The (multi)linear mode of the given local vertex.
+    Vertex(usize),
+    /// A mode of the given local edge, whose restriction to the edge is the bubble of the given
+    /// degree along the edge direction.
+    Edge { edge: usize, degree: usize },
+    /// A mode of the given local face, whose restriction to the face is the product of bubbles
+    /// of the given degrees along the two face directions.
+    Face { face: usize, degrees: [usize; 2] },
+    /// An interior mode, which is the product of bubbles of the given degrees.
+    Interior { degrees: Vec<usize> },
+}
+
+/// Polynomial orders of the modes of a [`HierarchicalBasis`].
+///
+/// Each edge and face of an element may have a lower order than the interior. When elements of
+/// different order share an edge or face, the modes of the shared entity must be the same on both
+/// sides for the resulting space to be continuous, which is achieved by choosing the minimum
+/// order of the adjacent elements for the entity. Edges and faces are ordered as in
+/// [`HierarchicalBasis::reference_edges`] and [`HierarchicalBasis::reference_faces`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HierarchicalOrders {
+    pub interior: usize,
+    pub edges: Vec<usize>,
+    pub faces: Vec<usize>,
+}
+
+impl HierarchicalOrders {
+    /// The orders of an element of the given dimension in which all entities have the same order.
+    pub fn uniform(dim: usize, order: usize) -> Self {
+        let (num_edges, num_faces) = match dim {
+            2 => (4, 0),
+            3 => (12, 6),
+            _ => panic!("Hierarchical elements are only available in 2D and 3D"),
+        };
+        Self {
+            interior: order,
+            edges: vec![order; num_edges],
+            faces: vec![order; num_faces],
+        }
+    }
+}
+
+/// Hierarchical (modal) basis functions of the tensor-product space $Q_p$ on the reference
+/// quadrilateral $[-1, 1]^2$ or hexahedron $[-1, 1]^3$.
+///
+/// The basis functions are tensor products of one-dimensional integrated Legendre (Lobatto)
+/// polynomials. Unlike the nodal basis of Lagrange elements, the basis of order $p$ contains the
+/// basis of order $p - 1$, so that p-refinement only adds basis functions and keeps the existing
+/// coefficients. Moreover, the basis functions are associated with the vertices, edges, faces and
+/// interior of the element (see [`HierarchicalMode`]), which allows the orders of the edges and
+/// faces to be chosen independently of the interior order, as is needed to maintain continuity
+/// between elements of different order.
+///
+/// The vertices are ordered as in [`Quad4d2Element`](crate::element::Quad4d2Element) and
+/// [`Hex8Element`](crate::element::Hex8Element). The modes are ordered by entity: vertex modes
+/// come first, followed by the modes of each edge, each face and the interior.
+///
+/// Since the basis functions are not nodal, the coefficients of a function in the basis are
+/// not point values, except for the coefficients of the vertex modes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalBasis<T, D> {
+    orders: HierarchicalOrders,
+    modes: Vec<HierarchicalMode>,
+    /// One-dimensional function indices in each coordinate direction, for each mode.
+    indices: Vec<Vec<usize>>,
+    max_order: usize,
+    marker: PhantomData<(T, D)>,
+}
+
+impl<T, D> HierarchicalBasis<T, D>
+where
+    T: Real,
+    D: SmallDim,
+{
+    /// Constructs the basis with the given orders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension is not 2 or 3, if any order is zero, or if the number of edge or
+    /// face orders does not match the reference element.
+    pub fn from_orders(orders: HierarchicalOrders) -> Self {
+        let d = D::dim();
+        let edges = reference_edge_corners(d);
+        let faces = reference_face_corners(d);
+        assert_eq!(
+            orders.edges.len(),
+            edges.len(),
+            "Number of edge orders must match number of edges"
+        );
+        assert_eq!(
+            orders.faces.len(),
+            faces.len(),
+            "Number of face orders must match number of faces"
+        );
+        assert!(
+            orders.interior > 0 && orders.edges.iter().chain(&orders.faces).all(|&p| p > 0),
+            "Orders of hierarchical elements must be positive"
+        );
+
+        let mut modes = Vec::new();
+        let mut indices = Vec::new();
+        for (v, corner) in reference_vertex_corners(d).into_iter().enumerate() {
+            modes.push(HierarchicalMode::Vertex(v));
+            indices.push(corner);
+        }
+        for (e, (direction, corner)) in edges.into_iter().enumerate() {
+            for degree in 2..=orders.edges[e] {
+                let mut mode_indices = corner.clone();
+                mode_indices[direction] = degree;
+                modes.push(HierarchicalMode::Edge { edge: e, degree });
+                indices.push(mode_indices);
+            }
+        }
+        for (f, ([d1, d2], corner)) in faces.into_iter().enumerate() {
+            for (i, j) in (2..=orders.faces[f]).cartesian_product(2..=orders.faces[f]) {
+                let mut mode_indices = corner.clone();
+                mode_indices[d1] = i;
+                mode_indices[d2] = j;
+                modes.push(HierarchicalMode::Face {
+                    face: f,
+                    degrees: [i, j],
+                });
+                indices.push(mode_indices);
+            }
+        }
+        for degrees in (0..d)
+            .map(|_| 2..=orders.interior)
+            .multi_cartesian_product()
+        {
+            modes.push(HierarchicalMode::Interior {
+                degrees: degrees.clone(),
+            });
+            indices.push(degrees);
+        }
+
+        let max_order = orders
+            .edges
+            .iter()
+            .chain(&orders.faces)
+            .fold(orders.interior, |max, &p| max.max(p));
+        Self {
+            orders,
+            modes,
+            indices,
+            max_order,
+            marker: PhantomData,
+        }
+    }
+
+    /// Constructs the basis of the full space $Q_p$ of the given order.
+    pub fn uniform(order: usize) -> Self {
+        Self::from_orders(HierarchicalOrders::uniform(D::dim(), order))
+    }
+
+    pub fn orders(&self) -> &HierarchicalOrders {
+        &self.orders
+    }
+
+    /// The entities that the basis functions are associated with, in the order of the basis
+    /// functions.
+    pub fn modes(&self) -> &[HierarchicalMode] {
+        &self.modes
+    }
+
+    /// The local vertices of each edge of the reference element.
+    ///
+    /// The first vertex is the one in the negative coordinate direction along the edge, so that
+    /// the edge modes are oriented from the first to the second vertex.
+    pub fn reference_edges() -> Vec<[usize; 2]> {
+        let vertices = reference_vertex_corners(D::dim());
+        reference_edge_corners(D::dim())
+            .into_iter()
+            .map(|(direction, corner)| {
+                let mut end = corner.clone();
+                end[direction] = 1;
+                [corner, end].map(|c| vertices.iter().position(|v| v == &c).unwrap())
+            })
+            .collect()
+    }
+
+    /// The local vertices of each face of the reference element (3D only).
+    ///
+    /// The vertices are given in the order $(0, 0)$, $(1, 0)$, $(0, 1)$ and $(1, 1)$ in terms of
+    /// the two local face directions, which are the two free coordinate directions of the face
+    /// in increasing order. The face modes with degrees $(i, j)$ are the products of the bubbles
+    /// of degree $i$ and $j$ along the first and second face direction, respectively.
+    pub fn reference_faces() -> Vec<[usize; 4]> {
+        let vertices = reference_vertex_corners(D::dim());
+        reference_face_corners(D::dim())
+            .into_iter()
+            .map(|([d1, d2], corner)| {
+                [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(s1, s2)| {
+                    let mut c = corner.clone();
+                    c[d1] = s1;
+                    c[d2] = s2;
+                    vertices.iter().position(|v| v == &c).unwrap()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Corners of the reference element as indices of $l_0$ or $l_1$ in each direction, in the
+/// vertex order of Quad4 and Hex8 elements.
+fn reference_vertex_corners(dim: usize) -> Vec<Vec<usize>> {
+    let quad = [[0, 0], [1, 0], [1, 1], [0, 1]];
+    match dim {
+        2 => quad.iter().map(|c| c.to_vec()).collect(),
+        3 => [0, 1]
+            .iter()
+            .flat_map(|&z| quad.iter().map(move |&[x, y]| vec![x, y, z]))
+            .collect(),
+        _ => panic!("Hierarchical elements are only available in 2D and 3D"),
+    }
+}
+
+/// Edges of the reference element as the edge direction and the corner at the start of the edge.
+fn reference_edge_corners(dim: usize) -> Vec<(usize, Vec<usize>)> {
+    (0..dim)
+        .flat_map(|direction| {
+            (0..dim - 1)
+                .map(|_| 0..2)
+                .multi_cartesian_product()
+                .map(move |fixed| {
+                    let mut corner = fixed;
+                    corner.insert(direction, 0);
+                    (direction, corner)
+                })
+        })
+        .collect()
+}
+
+/// Faces of the reference element as the two face directions and the corner at the origin of
+/// the face.
+fn reference_face_corners(dim: usize) -> Vec<([usize; 2], Vec<usize>)> {
+    if dim != 3 {
+        return Vec::new();
+    }
+    (0..3)
+        .rev()
+        .flat_map(|normal| {
+            let directions: Vec<usize> = (0..3).filter(|&d| d != normal).collect();
+            (0..2).map(move |side| {
+                let mut corner = vec![0; 3];
+                corner[normal] = side;
+                ([directions[0], directions[1]], corner)
+            })
+        })
+        .collect()
+}
+
+impl<T, D> ReferenceFiniteElement<T> for HierarchicalBasis<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type ReferenceDim = D;
+
+    fn num_nodes(&self) -> usize {
+        self.modes.len()
+    }
+
+    fn populate_basis(&self, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        assert_eq!(
+            basis_values.len(),
+            self.num_nodes(),
+            "Basis value buffer has wrong length"
+        );
+        let values_1d: Vec<_> = reference_coords
+            .iter()
+            .map(|&xi| lobatto_functions(self.max_order, xi).0)
+            .collect();
+        for (value, indices) in basis_values.iter_mut().zip(&self.indices) {
+            *value = indices
+                .iter()
+                .enumerate()
+                .fold(T::one(), |product, (d, &k)| product * values_1d[d][k]);
+        }
+    }
+
+    fn populate_basis_gradients(&self, mut basis_gradients: MatrixViewMut<T, D, Dyn>, reference_coords: &OPoint<T, D>) {
+        assert_eq!(
+            basis_gradients.ncols(),
+            self.num_nodes(),
+            "Basis gradient buffer has wrong number of columns"
+        );
+        let (values_1d, derivatives_1d): (Vec<_>, Vec<_>) = reference_coords
+            .iter()
+            .map(|&xi| lobatto_functions(self.max_order, xi))
+            .unzip();
+        for (mut gradient, indices) in basis_gradients.column_iter_mut().zip(&self.indices) {
+            for d in 0..D::dim() {
+                gradient[d] = indices
+                    .iter()
+                    .enumerate()
+                    .fold(T::one(), |product, (m, &k)| {
+                        let factor = if m == d { derivatives_1d[m][k] } else { values_1d[m][k] };
+                        product * factor
+                    });
+            }
+        }
+    }
+}
+
+/// A quadrilateral or hexahedral element with a [`HierarchicalBasis`].
+///
+/// The geometry is given by the (multi)linear map of the vertices, as for
+/// [`Quad4d2Element`](crate::element::Quad4d2Element) and
+/// [`Hex8Element`](crate::element::Hex8Element), regardless of the order of the basis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalElement<T, D>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    vertices: Vec<OPoint<T, D>>,
+    geometry: HierarchicalBasis<T, D>,
+    basis: Arc<HierarchicalBasis<T, D>>,
+}
+
+/// A quadrilateral element with a hierarchical basis.
+pub type HierarchicalQuadElement<T> = HierarchicalElement<T, nalgebra::U2>;
+/// A hexahedral element with a hierarchical basis.
+pub type HierarchicalHexElement<T> = HierarchicalElement<T, nalgebra::U3>;
+
+impl<T, D> HierarchicalElement<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Constructs an element with the given vertices and basis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of vertices does not match the reference element.
+    pub fn from_vertices_and_basis(vertices: Vec<OPoint<T, D>>, basis: Arc<HierarchicalBasis<T, D>>) -> Self {
+        assert_eq!(
+            vertices.len(),
+            1 << D::dim(),
+            "Number of vertices must match the reference element"
+        );
+        Self {
+            vertices,
+            geometry: HierarchicalBasis::uniform(1),
+            basis,
+        }
+    }
+
+    /// Constructs an element of the full space $Q_p$ of the given order.
+    pub fn from_vertices(vertices: Vec<OPoint<T, D>>, order: usize) -> Self {
+        Self::from_vertices_and_basis(vertices, Arc::new(HierarchicalBasis::uniform(order)))
+    }
+
+    pub fn vertices(&self) -> &[OPoint<T, D>] {
+        &self.vertices
+    }
+
+    pub fn basis(&self) -> &Arc<HierarchicalBasis<T, D>> {
+        &self.basis
+    }
+}
+
+impl<T, D> ReferenceFiniteElement<T> for HierarchicalElement<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type ReferenceDim = D;
+
+    fn num_nodes(&self) -> usize {
+        self.basis.num_nodes()
+    }
+
+    fn populate_basis(&self, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        self.basis.populate_basis(basis_values, reference_coords)
+    }
+
+    fn populate_basis_gradients(&self, basis_gradients: MatrixViewMut<T, D, Dyn>, reference_coords: &OPoint<T, D>) {
+        self.basis
+            .populate_basis_gradients(basis_gradients, reference_coords)
+    }
+}
+
+impl<T, D> FiniteElement<T> for HierarchicalElement<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    type GeometryDim = D;
+
+    fn reference_jacobian(&self, reference_coords: &OPoint<T, D>) -> OMatrix<T, D, D> {
+        let mut gradients = OMatrix::<T, D, Dyn>::zeros(self.vertices.len());
+        self.geometry
+            .populate_basis_gradients(gradients.as_view_mut(), reference_coords);
+        let mut jacobian = OMatrix::<T, D, D>::zeros();
+        for (x, gradient) in self.vertices.iter().zip(gradients.column_iter()) {
+            jacobian.ger(T::one(), &x.coords, &gradient, T::one());
+        }
+        jacobian
+    }
+
+    fn map_reference_coords(&self, reference_coords: &OPoint<T, D>) -> OPoint<T, D> {
+        let mut values = vec![T::zero(); self.vertices.len()];
+        self.geometry.populate_basis(&mut values, reference_coords);
+        self.vertices
+            .iter()
+            .zip(values)
+            .fold(OPoint::origin(), |x, (v, phi)| x + &v.coords * phi)
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| (a - b).norm())
+            .fold(T::zero(), |max, d| max.max(d))
+    }
+}
@@ -0,0 +1,408 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::nalgebra::{DMatrix, DVector, DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, OVector, Scalar};
+use crate::quadrature::univariate::try_gauss_lobatto;
+use crate::{Real, SmallDim};
+use itertools::Itertools;
+use std::sync::Arc;
+
+/// The distribution of the nodes of a [`LagrangeSimplexBasis`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LagrangeNodes {
+    /// Nodes on a uniform lattice.
+    Equispaced,
+    /// Nodes that coincide with the Gauss-Lobatto points on the edges of the simplex, and are
+    /// blended into the interior with the construction of Blyth and Pozrikidis, "A Lobatto
+    /// interpolation grid over the triangle", IMA Journal of Applied Mathematics (2006).
+    ///
+    /// Compared to equispaced nodes, the interpolation is much better conditioned at high
+    /// order.
+    Warped,
+}
+
+/// The basis functions of a Lagrange element of arbitrary order on the reference simplex.
+///
+/// The reference simplex has the vertices $-\vec 1$ and $-\vec 1 + 2 \vec e_k$ for
+/// $k = 1, \dots, d$, consistent with the other simplex elements such as [`Tri3d2Element`]
+/// and [`Tet4Element`]. The basis spans all polynomials of total degree at most `ORDER`, and
+/// the basis functions are the Lagrange polynomials associated with the nodes. They are computed
+/// by inverting the (generalized) Vandermonde matrix of a basis of products of Legendre
+/// polynomials at the nodes, which is done once when the basis is constructed.
+///
+/// The nodes are ordered by the dimension of the sub-simplex (vertex, edge, face or interior)
+/// that they are located in the interior of. Vertices come first, followed by the nodes on the
+/// edges, faces and finally the interior nodes, where sub-simplices with the same dimension are
+/// ordered lexicographically by their vertices. Within an edge, the nodes are ordered from the
+/// vertex with the smaller index to the vertex with the larger index.
+///
+/// [`Tri3d2Element`]: crate::element::Tri3d2Element
+/// [`Tet4Element`]: crate::element::Tet4Element
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagrangeSimplexBasis<T, D, const ORDER: usize>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    nodes: LagrangeNodes,
+    reference_nodes: Vec<OPoint<T, D>>,
+    /// The degrees of the Legendre polynomials in each coordinate direction, for each
+    /// polynomial in the Vandermonde basis.
+    degrees: Vec<Vec<usize>>,
+    /// The inverse of the Vandermonde matrix, whose columns give the coefficients of the
+    /// Lagrange basis functions in the Legendre basis.
+    vandermonde_inverse: DMatrix<T>,
+}
+
+impl<T, D, const ORDER: usize> LagrangeSimplexBasis<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Constructs the basis with the given distribution of nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ORDER` is zero, or if warped nodes are requested for an order for which
+    /// Gauss-Lobatto points are not available.
+    pub fn new(nodes: LagrangeNodes) -> Self {
+        assert!(ORDER > 0, "Order of Lagrange elements must be positive");
+        let d = D::dim();
+        let p = ORDER;
+
+        let lattice_points = simplex_lattice_points(d, p);
+        let barycentric_coords: Vec<Vec<T>> = match nodes {
+            LagrangeNodes::Equispaced => lattice_points
+                .iter()
+                .map(|indices| {
+                    indices
+                        .iter()
+                        .map(|&i| T::from_usize(i).unwrap() / T::from_usize(p).unwrap())
+                        .collect()
+                })
+                .collect(),
+            LagrangeNodes::Warped => {
+                let (_, gll_points) = try_gauss_lobatto::<T>(p + 1)
+                    .unwrap_or_else(|| panic!("Gauss-Lobatto points for order {p} are not available"));
+                let half = T::from_f64(0.5).unwrap();
+                let v: Vec<T> = gll_points
+                    .iter()
+                    .map(|x| (x[0] + T::one()) * half)
+                    .collect();
+                let d_t = T::from_usize(d).unwrap();
+                lattice_points
+                    .iter()
+                    .map(|indices| {
+                        let sum: T = indices.iter().map(|&i| v[i]).fold(T::zero(), |a, b| a + b);
+                        indices
+                            .iter()
+                            .map(|&i| (T::one() + (d_t + T::one()) * v[i] - sum) / (d_t + T::one()))
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+        let two = T::from_f64(2.0).unwrap();
+        let reference_nodes: Vec<OPoint<T, D>> = barycentric_coords
+            .iter()
+            .map(|lambda| OPoint::from(OVector::<T, D>::from_fn(|k, _| two * lambda[k + 1] - T::one())))
+            .collect();
+
+        let degrees = simplex_lattice_points(d, p)
+            .into_iter()
+            .map(|indices| indices[1..].to_vec())
+            .collect_vec();
+        let n = reference_nodes.len();
+        let mut vandermonde = DMatrix::zeros(n, n);
+        for (i, xi) in reference_nodes.iter().enumerate() {
+            let legendre = legendre_values_and_derivatives(xi.coords.as_slice(), p);
+            for (j, degree) in degrees.iter().enumerate() {
+                vandermonde[(i, j)] = (0..d)
+                    .map(|k| legendre[k][degree[k]].0)
+                    .fold(T::one(), |a, b| a * b);
+            }
+        }
+        let vandermonde_inverse = vandermonde
+            .try_inverse()
+            .expect("Vandermonde matrix of Lagrange nodes must be invertible");
+
+        Self {
+            nodes,
+            reference_nodes,
+            degrees,
+            vandermonde_inverse,
+        }
+    }
+
+    pub fn equispaced() -> Self {
+        Self::new(LagrangeNodes::Equispaced)
+    }
+
+    pub fn warped() -> Self {
+        Self::new(LagrangeNodes::Warped)
+    }
+}
+
+impl<T, D, const ORDER: usize> LagrangeSimplexBasis<T, D, ORDER>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn nodes(&self) -> LagrangeNodes {
+        self.nodes
+    }
+
+    /// Returns the reference coordinates of the nodes, in the order of the basis functions.
+    pub fn reference_nodes(&self) -> &[OPoint<T, D>] {
+        &self.reference_nodes
+    }
+
+    /// Returns the barycentric lattice indices $(i_0, \dots, i_d)$ of the nodes, in the order of
+    /// the basis functions.
+    ///
+    /// The node with indices $(i_0, \dots, i_d)$ is associated with the barycentric coordinates
+    /// $(i_0 / p, \dots, i_d / p)$ on the lattice, where $p$ is the order and index $a$ refers
+    /// to vertex $a$ of the simplex. For warped nodes, the actual barycentric coordinates of the
+    /// node differ, but depend symmetrically on the lattice indices.
+    pub fn lattice_indices(&self) -> Vec<Vec<usize>> {
+        simplex_lattice_points(D::dim(), ORDER)
+    }
+}
+
+impl<T, D, const ORDER: usize> ReferenceFiniteElement<T> for LagrangeSimplexBasis<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type ReferenceDim = D;
+
+    fn num_nodes(&self) -> usize {
+        self.reference_nodes.len()
+    }
+
+    fn populate_basis(&self, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        let n = self.num_nodes();
+        assert_eq!(basis_values.len(), n, "Basis value buffer must have one entry per node");
+        let legendre = legendre_values_and_derivatives(reference_coords.coords.as_slice(), ORDER);
+        let polynomials = DVector::from_iterator(
+            n,
+            self.degrees.iter().map(|degree| {
+                (0..D::dim())
+                    .map(|k| legendre[k][degree[k]].0)
+                    .fold(T::one(), |a, b| a * b)
+            }),
+        );
+        basis_values.copy_from_slice(self.vandermonde_inverse.tr_mul(&polynomials).as_slice());
+    }
+
+    fn populate_basis_gradients(&self, mut basis_gradients: MatrixViewMut<T, D, Dyn>, reference_coords: &OPoint<T, D>) {
+        let n = self.num_nodes();
+        let d = D::dim();
+        let legendre = legendre_values_and_derivatives(reference_coords.coords.as_slice(), ORDER);
+        let polynomial_gradients = OMatrix::<T, D, Dyn>::from_fn_generic(D::name(), Dyn(n), |k, j| {
+            let degree = &self.degrees[j];
+            (0..d)
+                .map(|l| {
+                    let (value, derivative) = legendre[l][degree[l]];
+                    if l == k {
+                        derivative
+                    } else {
+                        value
+                    }
+                })
+                .fold(T::one(), |a, b| a * b)
+        });
+        basis_gradients.copy_from(&(polynomial_gradients * &self.vandermonde_inverse));
+    }
+}
+
+/// A Lagrange element of arbitrary order on a straight-sided simplex.
+///
+/// The geometry of the element is given by its $d + 1$ vertices, and the basis functions are
+/// given by a shared [`LagrangeSimplexBasis`], so that the Vandermonde matrix only needs to be
+/// inverted once for all elements of a mesh. Unlike e.g. [`Tet10Element`], the element is not
+/// isoparametric, which means that the reference map is always affine.
+///
+/// [`Tet10Element`]: crate::element::Tet10Element
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagrangeSimplexElement<T, D, const ORDER: usize>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    vertices: Vec<OPoint<T, D>>,
+    basis: Arc<LagrangeSimplexBasis<T, D, ORDER>>,
+}
+
+/// A Lagrange triangle of arbitrary order.
+pub type LagrangeTriangleElement<T, const ORDER: usize> = LagrangeSimplexElement<T, nalgebra::U2, ORDER>;
+/// A Lagrange tetrahedron of arbitrary order.
+pub type LagrangeTetrahedronElement<T, const ORDER: usize> = LagrangeSimplexElement<T, nalgebra::U3, ORDER>;
+
+impl<T, D, const ORDER: usize> LagrangeSimplexElement<T, D, ORDER>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Constructs an element with the given vertices and basis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of vertices is not $d + 1$.
+    pub fn from_vertices_and_basis(vertices: Vec<OPoint<T, D>>, basis: Arc<LagrangeSimplexBasis<T, D, ORDER>>) -> Self {
+        assert_eq!(
+            vertices.len(),
+            D::dim() + 1,
+            "A simplex in dimension {} must have {} vertices",
+            D::dim(),
+            D::dim() + 1
+        );
+        Self { vertices, basis }
+    }
+
+    pub fn vertices(&self) -> &[OPoint<T, D>] {
+        &self.vertices
+    }
+
+    pub fn basis(&self) -> &Arc<LagrangeSimplexBasis<T, D, ORDER>> {
+        &self.basis
+    }
+}
+
+impl<T, D, const ORDER: usize> LagrangeSimplexElement<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    /// Constructs an element with the given vertices and equispaced nodes.
+    ///
+    /// This computes a new basis. Use [`from_vertices_and_basis`](Self::from_vertices_and_basis)
+    /// to share a basis between many elements.
+    pub fn from_vertices(vertices: Vec<OPoint<T, D>>) -> Self {
+        Self::from_vertices_and_basis(vertices, Arc::new(LagrangeSimplexBasis::equispaced()))
+    }
+
+    /// The reference element, whose vertices are the vertices of the reference simplex.
+    pub fn reference(basis: Arc<LagrangeSimplexBasis<T, D, ORDER>>) -> Self {
+        let vertices = (0..=D::dim())
+            .map(|a| {
+                OPoint::from(OVector::<T, D>::from_fn(
+                    |k, _| if k + 1 == a { T::one() } else { -T::one() },
+                ))
+            })
+            .collect();
+        Self::from_vertices_and_basis(vertices, basis)
+    }
+
+    /// Returns the physical coordinates of the nodes, in the order of the basis functions.
+    pub fn nodes(&self) -> Vec<OPoint<T, D>> {
+        self.basis
+            .reference_nodes()
+            .iter()
+            .map(|xi| self.map_reference_coords(xi))
+            .collect()
+    }
+}
+
+impl<T, D, const ORDER: usize> ReferenceFiniteElement<T> for LagrangeSimplexElement<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type ReferenceDim = D;
+
+    fn num_nodes(&self) -> usize {
+        self.basis.num_nodes()
+    }
+
+    fn populate_basis(&self, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        self.basis.populate_basis(basis_values, reference_coords)
+    }
+
+    fn populate_basis_gradients(&self, basis_gradients: MatrixViewMut<T, D, Dyn>, reference_coords: &OPoint<T, D>) {
+        self.basis
+            .populate_basis_gradients(basis_gradients, reference_coords)
+    }
+}
+
+impl<T, D, const ORDER: usize> FiniteElement<T> for LagrangeSimplexElement<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    type GeometryDim = D;
+
+    fn reference_jacobian(&self, _reference_coords: &OPoint<T, D>) -> OMatrix<T, D, D> {
+        let half = T::from_f64(0.5).unwrap();
+        let v0 = &self.vertices[0];
+        OMatrix::<T, D, D>::from_fn(|i, k| (self.vertices[k + 1][i] - v0[i]) * half)
+    }
+
+    fn map_reference_coords(&self, reference_coords: &OPoint<T, D>) -> OPoint<T, D> {
+        let half = T::from_f64(0.5).unwrap();
+        let offset = reference_coords.coords.map(|xi| (xi + T::one()) * half);
+        let v0 = &self.vertices[0];
+        let j = OMatrix::<T, D, D>::from_fn(|i, k| self.vertices[k + 1][i] - v0[i]);
+        v0 + j * offset
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| (x - y).norm())
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the barycentric lattice points $(i_0, \dots, i_d)$ with $\sum_a i_a = p$, ordered
+/// as described for [`LagrangeSimplexBasis`].
+fn simplex_lattice_points(d: usize, p: usize) -> Vec<Vec<usize>> {
+    let mut points: Vec<Vec<usize>> = (0..=d)
+        .map(|_| 0..=p)
+        .multi_cartesian_product()
+        .filter(|indices| indices.iter().sum::<usize>() == p)
+        .collect();
+    points.sort_by_key(|indices| {
+        let support: Vec<usize> = (0..=d).filter(|&a| indices[a] > 0).collect();
+        let descending: Vec<usize> = indices.iter().map(|&i| p - i).collect();
+        (support.len(), support, descending)
+    });
+    points
+}
+
+/// Evaluates the Legendre polynomials $P_0, \dots, P_p$ and their derivatives in each coordinate.
+fn legendre_values_and_derivatives<T: Real>(coords: &[T], p: usize) -> Vec<Vec<(T, T)>> {
+    coords
+        .iter()
+        .map(|&x| {
+            let mut values = Vec::with_capacity(p + 1);
+            values.push((T::one(), T::zero()));
+            if p > 0 {
+                values.push((x, T::one()));
+            }
+            for m in 1..p {
+                let m_t = T::from_usize(m).unwrap();
+                let (p_m, _) = values[m];
+                let (p_prev, dp_prev) = values[m - 1];
+                let two_m_plus_one = T::from_usize(2 * m + 1).unwrap();
+                let p_next = (two_m_plus_one * x * p_m - m_t * p_prev) / (m_t + T::one());
+                let dp_next = dp_prev + two_m_plus_one * p_m;
+                values.push((p_next, dp_next));
+            }
+            values
+        })
+        .collect()
+}
@@ -3,11 +3,12 @@ use std::convert::TryFrom;
 use itertools::Itertools;
 use numeric_literals::replace_float_literals;
 
-use crate::connectivity::{Quad4d2Connectivity, Quad9d2Connectivity};
-use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement};
+use crate::connectivity::{Quad4d2Connectivity, Quad4d3Connectivity, Quad9d2Connectivity};
+use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement, SurfaceFiniteElement};
 use crate::geometry::{ConcavePolygonError, ConvexPolygon, LineSegment2d, Quad2d};
 use crate::nalgebra::{
-    distance, Matrix1x4, Matrix2, Matrix2x4, OMatrix, OPoint, Point2, Scalar, Vector2, U1, U2, U4, U9,
+    distance, Matrix1x4, Matrix2, Matrix2x4, Matrix3x2, Matrix3x4, OMatrix, OPoint, Point2, Point3, Scalar, Vector2,
+    Vector3, U1, U2, U3, U4, U9,
 };
 use crate::Real;
 
@@ -379,3 +380,106 @@ where
         Some(Quad9d2Element::from_vertices(vertices_array))
     }
 }
+
+/// A (surface) finite element representing bilinear basis functions on a quad, in three dimensions.
+///
+/// The nodes are ordered as for [`Quad4d2Element`]. Since the vertices need not be coplanar,
+/// the element in general represents a curved (bilinear) surface patch, such as a face of a
+/// distorted [`Hex8Element`](crate::element::Hex8Element).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Quad4d3Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point3<T>; 4],
+}
+
+impl<T> Quad4d3Element<T>
+where
+    T: Scalar,
+{
+    pub fn from_vertices(vertices: [Point3<T>; 4]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 4] {
+        &self.vertices
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U4;
+    type ReferenceDim = U2;
+
+    fn evaluate_basis(&self, xi: &Point2<T>) -> Matrix1x4<T> {
+        // The basis functions are independent of the geometry
+        Quad4d2Element::reference().evaluate_basis(xi)
+    }
+
+    fn gradients(&self, xi: &Point2<T>) -> Matrix2x4<T> {
+        Quad4d2Element::reference().gradients(xi)
+    }
+}
+
+impl<T> FiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix3x2<T> {
+        let X = Matrix3x4::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point3<T> {
+        let X = Matrix3x4::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(X * N.transpose())
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+impl<T> SurfaceFiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    fn normal(&self, xi: &Point2<T>) -> Vector3<T> {
+        let j = self.reference_jacobian(xi);
+        j.column(0).cross(&j.column(1)).normalize()
+    }
+}
+
+impl<T> ElementConnectivity<T> for Quad4d3Connectivity
+where
+    T: Real,
+{
+    type Element = Quad4d3Element<T>;
+    type ReferenceDim = U2;
+    type GeometryDim = U3;
+
+    fn element(&self, vertices: &[Point3<T>]) -> Option<Self::Element> {
+        let Self(indices) = self;
+        let lookup_vertex = |local_index| vertices.get(indices[local_index]).cloned();
+
+        Some(Quad4d3Element::from_vertices([
+            lookup_vertex(0)?,
+            lookup_vertex(1)?,
+            lookup_vertex(2)?,
+            lookup_vertex(3)?,
+        ]))
+    }
+}
@@ -1,8 +1,9 @@
-use crate::connectivity::{Segment2d1Connectivity, Segment2d2Connectivity};
+use crate::connectivity::{Segment2d1Connectivity, Segment2d2Connectivity, Segment3d2Connectivity};
 use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement, SurfaceFiniteElement};
 use crate::geometry::LineSegment2d;
-use crate::nalgebra::{OMatrix, OPoint, Point1, Point2, Scalar, Vector2, U1, U2};
+use crate::nalgebra::{Matrix2x3, OMatrix, OPoint, Point1, Point2, Scalar, Vector2, U1, U2, U3};
 use crate::Real;
+use itertools::Itertools;
 use nalgebra::{point, Vector1};
 use numeric_literals::replace_float_literals;
 
@@ -78,6 +79,28 @@ impl<'a, T: Scalar> From<&'a Segment2d2Element<T>> for LineSegment2d<T> {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A quadratic surface element embedded in two dimensions.
+///
+/// The nodes are ordered as in [`Segment3d2Connectivity`], i.e. the first end point, the
+/// midpoint and the second end point, corresponding to the reference coordinates -1, 0 and 1.
+pub struct Segment3d2Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point2<T>; 3],
+}
+
+impl<T: Scalar> Segment3d2Element<T> {
+    pub fn from_vertices(vertices: [Point2<T>; 3]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point2<T>; 3] {
+        &self.vertices
+    }
+}
+
 #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
 fn segment2_basis<T: Real>(xi: T) -> OMatrix<T, U1, U2> {
     let phi_1 = (1.0 - xi) / 2.0;
@@ -124,6 +147,26 @@ where
     }
 }
 
+impl<T> FixedNodesReferenceFiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U3;
+    type ReferenceDim = U1;
+
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn evaluate_basis(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        let xi = xi[0];
+        OMatrix::<_, U1, U3>::new(0.5 * xi * (xi - 1.0), 1.0 - xi * xi, 0.5 * xi * (xi + 1.0))
+    }
+
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn gradients(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        let xi = xi[0];
+        OMatrix::<_, U1, U3>::new(xi - 0.5, -2.0 * xi, xi + 0.5)
+    }
+}
+
 impl<T> FiniteElement<T> for Segment2d1Element<T>
 where
     T: Real,
@@ -150,6 +193,10 @@ where
     fn diameter(&self) -> T {
         (self.vertices[1] - self.vertices[0]).norm()
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 impl<T> FiniteElement<T> for Segment2d2Element<T>
@@ -180,6 +227,10 @@ where
         let line_segment: LineSegment2d<T> = s.into();
         line_segment.length()
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 impl<T> SurfaceFiniteElement<T> for Segment2d2Element<T>
@@ -191,6 +242,45 @@ where
     }
 }
 
+impl<T> FiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U2;
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point1<T>) -> Vector2<T> {
+        let X = Matrix2x3::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point1<T>) -> Point2<T> {
+        let X = Matrix2x3::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(X * N.transpose())
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| (y - x).norm())
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+impl<T> SurfaceFiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    fn normal(&self, xi: &Point1<T>) -> Vector2<T> {
+        let tangent = self.reference_jacobian(xi);
+        Vector2::new(tangent.y, -tangent.x).normalize()
+    }
+}
+
 impl<T> ElementConnectivity<T> for Segment2d2Connectivity
 where
     T: Real,
@@ -221,3 +311,23 @@ where
         Some(Segment2d1Element::from_vertices([a, b]))
     }
 }
+
+impl<T> ElementConnectivity<T> for Segment3d2Connectivity
+where
+    T: Real,
+{
+    type Element = Segment3d2Element<T>;
+    type ReferenceDim = U1;
+    type GeometryDim = U2;
+
+    fn element(&self, vertices: &[Point2<T>]) -> Option<Self::Element> {
+        let Self(indices) = self;
+        let lookup_vertex = |local_index| vertices.get(indices[local_index]).cloned();
+
+        Some(Segment3d2Element::from_vertices([
+            lookup_vertex(0)?,
+            lookup_vertex(1)?,
+            lookup_vertex(2)?,
+        ]))
+    }
+}
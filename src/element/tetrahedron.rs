@@ -233,6 +233,10 @@ where
     fn diameter(&self) -> T {
         self.tet4.diameter()
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -479,6 +483,10 @@ where
     fn diameter(&self) -> T {
         self.tet4.diameter()
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 impl<'a, T> From<&'a Tet4Element<T>> for Tet20Element<T>
@@ -589,4 +597,8 @@ where
             .map(|(x, y)| distance(x, y))
             .fold(T::zero(), |a, b| a.max(b.clone()))
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
@@ -4,15 +4,15 @@ use nalgebra::distance_squared;
 use numeric_literals::replace_float_literals;
 use std::cmp::Ordering;
 
-use crate::connectivity::{Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity};
+use crate::connectivity::{Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity, Tri6d3Connectivity};
 use crate::element::{
     BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement,
     FixedNodesReferenceFiniteElement, SurfaceFiniteElement,
 };
 use crate::geometry::{LineSegment2d, Triangle, Triangle2d, Triangle3d};
 use crate::nalgebra::{
-    distance, Matrix1x3, Matrix1x6, Matrix2, Matrix2x3, Matrix2x6, Matrix3, Matrix3x2, OPoint, Point2, Point3, Scalar,
-    Vector2, Vector3, U2, U3, U6,
+    distance, Matrix1x3, Matrix1x6, Matrix2, Matrix2x3, Matrix2x6, Matrix3, Matrix3x2, Matrix3x6, OPoint, Point2,
+    Point3, Scalar, Vector2, Vector3, U2, U3, U6,
 };
 use crate::Real;
 
@@ -119,6 +119,10 @@ where
             .map(|(x, y)| distance(x, y))
             .fold(T::zero(), |a, b| a.max(b.clone()))
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 /// A finite element representing quadratic basis functions on a triangle, in two dimensions.
@@ -267,6 +271,10 @@ where
     fn diameter(&self) -> T {
         self.tri3.diameter()
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 impl<T> ElementConnectivity<T> for Tri3d2Connectivity
@@ -411,6 +419,10 @@ where
             .map(|(x, y)| distance(x, y))
             .fold(T::zero(), |a, b| a.max(b.clone()))
     }
+
+    fn is_affine(&self) -> bool {
+        true
+    }
 }
 
 impl<T> SurfaceFiniteElement<T> for Tri3d3Element<T>
@@ -442,6 +454,109 @@ where
     }
 }
 
+/// A (surface) finite element representing quadratic basis functions on a triangle,
+/// in three dimensions.
+///
+/// The nodes are ordered as for [`Tri6d2Element`], and the geometry is isoparametric,
+/// so that the element may represent curved surfaces, such as the faces of curved
+/// [`Tet10Element`](crate::element::Tet10Element)s.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tri6d3Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point3<T>; 6],
+}
+
+impl<T: Scalar> Tri6d3Element<T> {
+    pub fn from_vertices(vertices: [Point3<T>; 6]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 6] {
+        &self.vertices
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Tri6d3Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U6;
+    type ReferenceDim = U2;
+
+    fn evaluate_basis(&self, xi: &Point2<T>) -> Matrix1x6<T> {
+        // The basis functions are independent of the geometry
+        Tri6d2Element::reference().evaluate_basis(xi)
+    }
+
+    fn gradients(&self, xi: &Point2<T>) -> Matrix2x6<T> {
+        Tri6d2Element::reference().gradients(xi)
+    }
+}
+
+impl<T> FiniteElement<T> for Tri6d3Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix3x2<T> {
+        let X = Matrix3x6::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point3<T> {
+        let X = Matrix3x6::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(X * N.transpose())
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+impl<T> SurfaceFiniteElement<T> for Tri6d3Element<T>
+where
+    T: Real,
+{
+    fn normal(&self, xi: &Point2<T>) -> Vector3<T> {
+        let j = self.reference_jacobian(xi);
+        j.column(0).cross(&j.column(1)).normalize()
+    }
+}
+
+impl<T> ElementConnectivity<T> for Tri6d3Connectivity
+where
+    T: Real,
+{
+    type Element = Tri6d3Element<T>;
+    type ReferenceDim = U2;
+    type GeometryDim = U3;
+
+    fn element(&self, vertices: &[Point3<T>]) -> Option<Self::Element> {
+        let Self(indices) = self;
+        let lookup_vertex = |local_index| vertices.get(indices[local_index]).cloned();
+
+        Some(Tri6d3Element::from_vertices([
+            lookup_vertex(0)?,
+            lookup_vertex(1)?,
+            lookup_vertex(2)?,
+            lookup_vertex(3)?,
+            lookup_vertex(4)?,
+            lookup_vertex(5)?,
+        ]))
+    }
+}
+
 #[replace_float_literals(T::from_f64(literal).unwrap())]
 fn is_likely_in_tri_ref_interior<T: Real>(xi: &Point2<T>) -> bool {
     let eps = 4.0 * T::default_epsilon();
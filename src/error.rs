@@ -1,6 +1,6 @@
 //! Functionality for error estimation.
 use crate::allocators::{BiDimAllocator, TriDimAllocator};
-use crate::assembly::global::assemble_scalar;
+use crate::assembly::global::{assemble_scalar, par_assemble_scalar, par_assemble_scalar_chunked};
 use crate::assembly::local::QuadratureTable;
 use crate::element::VolumetricFiniteElement;
 use crate::integrate::dependency::DependsOnGrad;
@@ -14,6 +14,15 @@ use crate::space::{InterpolateGradientInSpace, InterpolateInSpace, VolumetricFin
 use crate::{Real, SmallDim};
 use nalgebra::{OMatrix, Scalar, Vector1, U1};
 
+mod convergence;
+mod recovery;
+mod residual;
+mod two_grid;
+pub use convergence::*;
+pub use recovery::*;
+pub use residual::*;
+pub use two_grid::*;
+
 /// A function $u: \mathbb{R}^d \rightarrow \mathbb{R}^s$ of the form $u(x)$ used to represent a reference solution.
 ///
 /// The trait is implemented by closures with the appropriate signature. Finite element
@@ -245,6 +254,162 @@ where
     .sqrt()
 }
 
+/// Estimate the squared $H^1$ error
+/// $\norm{u_h - u}^2_{H^1} = \norm{u_h - u}^2_{L^2} + \seminorm{u_h - u}^2_{H^1}$ on the given
+/// element with the given basis weights and quadrature points.
+///
+/// # Panics
+///
+/// Panics if the basis buffer does not have the length $n$, where $n$ is the number of nodes
+/// in the element.
+#[allow(non_snake_case)]
+pub fn estimate_element_H1_error_squared<T, Element, SolutionDim>(
+    element: &Element,
+    u: &impl SolutionFunction<T, Element::GeometryDim, SolutionDim>,
+    u_grad: &impl SolutionGradient<T, Element::GeometryDim, SolutionDim>,
+    u_h_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    workspace: &mut IntegrationWorkspace<T>,
+) -> T
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    SolutionDim: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, Element::GeometryDim, Element::ReferenceDim, SolutionDim>,
+{
+    let n = element.num_nodes();
+    assert_eq!(u_h_element.len(), n * SolutionDim::dim());
+    let result_as_vector: Vector1<T> = integrate_over_volume_element(
+        &make_H1_error_squared_integrand(u, u_grad),
+        element,
+        (quadrature_weights, quadrature_points),
+        u_h_element,
+        workspace,
+    )
+    .expect("TODO: Handle the case where this might fail (due to e.g. singular Jacobian)");
+
+    // Result is a 1-vector, we want to return a scalar
+    result_as_vector[0]
+}
+
+/// Estimate the $H^1$ error $\norm{u_h - u}_{H^1}$ on the given element with the given basis
+/// weights and quadrature points.
+///
+/// See [`estimate_element_H1_error_squared`] for the definition of the norm.
+///
+/// # Panics
+///
+/// Panics if the basis buffer does not have the length $n$, where $n$ is the number of nodes
+/// in the element.
+#[allow(non_snake_case)]
+pub fn estimate_element_H1_error<T, Element, SolutionDim>(
+    element: &Element,
+    u: &impl SolutionFunction<T, Element::GeometryDim, SolutionDim>,
+    u_grad: &impl SolutionGradient<T, Element::GeometryDim, SolutionDim>,
+    u_h_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    workspace: &mut IntegrationWorkspace<T>,
+) -> T
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    SolutionDim: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, Element::GeometryDim, Element::ReferenceDim, SolutionDim>,
+{
+    estimate_element_H1_error_squared(
+        element,
+        u,
+        u_grad,
+        u_h_element,
+        quadrature_weights,
+        quadrature_points,
+        workspace,
+    )
+    .sqrt()
+}
+
+/// Estimate the squared $H(\mathrm{div})$ error
+/// $\norm{u_h - u}^2_{H(\mathrm{div})} = \norm{u_h - u}^2_{L^2} + \norm{\nabla \cdot u_h - \nabla \cdot u}^2_{L^2}$
+/// of a vector field $u$ on the given element with the given basis weights and quadrature points.
+///
+/// The exact divergence $\nabla \cdot u$ is given as a scalar function.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension, or if the basis
+/// buffer does not have the length $n d$, where $n$ is the number of nodes in the element and
+/// $d$ the dimension.
+#[allow(non_snake_case)]
+pub fn estimate_element_Hdiv_error_squared<T, Element, SolutionDim>(
+    element: &Element,
+    u: &impl SolutionFunction<T, Element::GeometryDim, SolutionDim>,
+    u_div: &impl Fn(&OPoint<T, Element::GeometryDim>) -> T,
+    u_h_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    workspace: &mut IntegrationWorkspace<T>,
+) -> T
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    SolutionDim: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, Element::GeometryDim, Element::ReferenceDim, SolutionDim>,
+{
+    let n = element.num_nodes();
+    assert_eq!(u_h_element.len(), n * SolutionDim::dim());
+    let result_as_vector: Vector1<T> = integrate_over_volume_element(
+        &make_Hdiv_error_squared_integrand(u, u_div),
+        element,
+        (quadrature_weights, quadrature_points),
+        u_h_element,
+        workspace,
+    )
+    .expect("TODO: Handle the case where this might fail (due to e.g. singular Jacobian)");
+
+    // Result is a 1-vector, we want to return a scalar
+    result_as_vector[0]
+}
+
+/// Estimate the $H(\mathrm{div})$ error $\norm{u_h - u}_{H(\mathrm{div})}$ of a vector field on
+/// the given element with the given basis weights and quadrature points.
+///
+/// See [`estimate_element_Hdiv_error_squared`] for the definition of the norm.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension, or if the basis
+/// buffer does not have the length $n d$, where $n$ is the number of nodes in the element and
+/// $d$ the dimension.
+#[allow(non_snake_case)]
+pub fn estimate_element_Hdiv_error<T, Element, SolutionDim>(
+    element: &Element,
+    u: &impl SolutionFunction<T, Element::GeometryDim, SolutionDim>,
+    u_div: &impl Fn(&OPoint<T, Element::GeometryDim>) -> T,
+    u_h_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    workspace: &mut IntegrationWorkspace<T>,
+) -> T
+where
+    T: Real,
+    Element: VolumetricFiniteElement<T>,
+    SolutionDim: SmallDim,
+    DefaultAllocator: TriDimAllocator<T, Element::GeometryDim, Element::ReferenceDim, SolutionDim>,
+{
+    estimate_element_Hdiv_error_squared(
+        element,
+        u,
+        u_div,
+        u_h_element,
+        quadrature_weights,
+        quadrature_points,
+        workspace,
+    )
+    .sqrt()
+}
+
 #[allow(non_snake_case)]
 fn make_L2_error_squared_integrand<'a, T, SolutionDim, GeometryDim>(
     u: &'a (impl SolutionFunction<T, GeometryDim, SolutionDim> + ?Sized),
@@ -281,6 +446,55 @@ where
     FnFunction::new(function).with_dependencies::<DependsOnGrad>()
 }
 
+#[allow(non_snake_case)]
+fn make_H1_error_squared_integrand<'a, T, SolutionDim, GeometryDim>(
+    u: &'a (impl SolutionFunction<T, GeometryDim, SolutionDim> + ?Sized),
+    u_grad: &'a (impl SolutionGradient<T, GeometryDim, SolutionDim> + ?Sized),
+) -> impl 'a + UGradFunction<T, GeometryDim, SolutionDim, OutputDim = U1>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    GeometryDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    let function = move |x: &OPoint<T, GeometryDim>,
+                         u_h: &OVector<T, SolutionDim>,
+                         u_h_grad: &OMatrix<T, GeometryDim, SolutionDim>| {
+        let error = u_h - u.evaluate(x);
+        let grad_error = u_h_grad - u_grad.evaluate_grad(x);
+        Vector1::new(error.norm_squared() + grad_error.norm_squared())
+    };
+    FnFunction::new(function)
+}
+
+#[allow(non_snake_case)]
+fn make_Hdiv_error_squared_integrand<'a, T, SolutionDim, GeometryDim>(
+    u: &'a (impl SolutionFunction<T, GeometryDim, SolutionDim> + ?Sized),
+    u_div: &'a (impl Fn(&OPoint<T, GeometryDim>) -> T + ?Sized),
+) -> impl 'a + UGradFunction<T, GeometryDim, SolutionDim, OutputDim = U1>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    GeometryDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    assert_eq!(
+        SolutionDim::dim(),
+        GeometryDim::dim(),
+        "Solution dimension must be equal to geometry dimension"
+    );
+    let function = move |x: &OPoint<T, GeometryDim>,
+                         u_h: &OVector<T, SolutionDim>,
+                         u_h_grad: &OMatrix<T, GeometryDim, SolutionDim>| {
+        let error = u_h - u.evaluate(x);
+        // Column i of the gradient is the gradient of component i, so the divergence is the trace
+        let divergence = (0..GeometryDim::dim()).fold(T::zero(), |div, i| div + u_h_grad[(i, i)]);
+        let div_error = divergence - u_div(x);
+        Vector1::new(error.norm_squared() + div_error * div_error)
+    };
+    FnFunction::new(function)
+}
+
 /// Estimate the squared $L^2$ error $\norm{u_h - u}^2_{L^2}$ on the given finite element space
 /// with the given solution weights and quadrature table.
 #[allow(non_snake_case)]
@@ -370,3 +584,431 @@ where
 {
     estimate_H1_seminorm_error_squared(space, u_grad, u_h, qtable).map(|err2| err2.sqrt())
 }
+
+/// Estimate the squared $H^1$ error $\norm{u_h - u}^2_{H^1}$ on the given finite element space
+/// with the given solution weights and quadrature table.
+///
+/// See [`estimate_element_H1_error_squared`] for the definition of the norm.
+#[allow(non_snake_case)]
+pub fn estimate_H1_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &impl SolutionFunction<T, Space::GeometryDim, SolutionDim>,
+    u_grad: &impl SolutionGradient<T, Space::GeometryDim, SolutionDim>,
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_error_squared_integrand(u, u_grad))
+        .build_volume_integrator();
+
+    assemble_scalar(&assembler)
+}
+
+/// Estimate the $H^1$ error $\norm{u_h - u}_{H^1}$ on the given finite element space
+/// with the given solution weights and quadrature table.
+///
+/// See [`estimate_element_H1_error_squared`] for the definition of the norm.
+#[allow(non_snake_case)]
+pub fn estimate_H1_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &impl SolutionFunction<T, Space::GeometryDim, SolutionDim>,
+    u_grad: &impl SolutionGradient<T, Space::GeometryDim, SolutionDim>,
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    estimate_H1_error_squared(space, u, u_grad, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $H(\mathrm{div})$ error $\norm{u_h - u}^2_{H(\mathrm{div})}$ of a vector
+/// field on the given finite element space with the given solution weights and quadrature table.
+///
+/// See [`estimate_element_Hdiv_error_squared`] for the definition of the norm.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension.
+#[allow(non_snake_case)]
+pub fn estimate_Hdiv_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &impl SolutionFunction<T, Space::GeometryDim, SolutionDim>,
+    u_div: &impl Fn(&OPoint<T, Space::GeometryDim>) -> T,
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_Hdiv_error_squared_integrand(u, u_div))
+        .build_volume_integrator();
+
+    assemble_scalar(&assembler)
+}
+
+/// Estimate the $H(\mathrm{div})$ error $\norm{u_h - u}_{H(\mathrm{div})}$ of a vector field on
+/// the given finite element space with the given solution weights and quadrature table.
+///
+/// See [`estimate_element_Hdiv_error_squared`] for the definition of the norm.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension.
+#[allow(non_snake_case)]
+pub fn estimate_Hdiv_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &impl SolutionFunction<T, Space::GeometryDim, SolutionDim>,
+    u_div: &impl Fn(&OPoint<T, Space::GeometryDim>) -> T,
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    estimate_Hdiv_error_squared(space, u, u_div, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $L^2$ error $\norm{u_h - u}^2_{L^2}$ on the given finite element space
+/// in parallel.
+///
+/// See [`estimate_L2_error_squared`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_L2_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync + ?Sized),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_L2_error_squared_integrand(u))
+        .build_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $L^2$ error $\norm{u_h - u}_{L^2}$ on the given finite element space in parallel.
+///
+/// See [`estimate_L2_error`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_L2_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync + ?Sized),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_estimate_L2_error_squared(space, u, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $H^1$ *seminorm* error $\seminorm{u_h - u}^2_{H^1}$ on the given finite
+/// element space in parallel.
+///
+/// See [`estimate_H1_seminorm_error_squared`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_seminorm_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_seminorm_error_squared_integrand(u_grad))
+        .build_volume_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $H^1$ *seminorm* error $\seminorm{u_h - u}_{H^1}$ on the given finite element
+/// space in parallel.
+///
+/// See [`estimate_H1_seminorm_error`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_seminorm_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_estimate_H1_seminorm_error_squared(space, u_grad, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $H^1$ error $\norm{u_h - u}^2_{H^1}$ on the given finite element space
+/// in parallel.
+///
+/// See [`estimate_H1_error_squared`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_error_squared_integrand(u, u_grad))
+        .build_volume_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $H^1$ error $\norm{u_h - u}_{H^1}$ on the given finite element space in parallel.
+///
+/// See [`estimate_H1_error`] for the sequential version.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_estimate_H1_error_squared(space, u, u_grad, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $H(\mathrm{div})$ error $\norm{u_h - u}^2_{H(\mathrm{div})}$ of a vector
+/// field on the given finite element space in parallel.
+///
+/// See [`estimate_Hdiv_error_squared`] for the sequential version.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension.
+#[allow(non_snake_case)]
+pub fn par_estimate_Hdiv_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_div: &(impl Fn(&OPoint<T, Space::GeometryDim>) -> T + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_Hdiv_error_squared_integrand(u, u_div))
+        .build_volume_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $H(\mathrm{div})$ error $\norm{u_h - u}_{H(\mathrm{div})}$ of a vector field on
+/// the given finite element space in parallel.
+///
+/// See [`estimate_Hdiv_error`] for the sequential version.
+///
+/// # Panics
+///
+/// Panics if the solution dimension is not equal to the geometry dimension.
+#[allow(non_snake_case)]
+pub fn par_estimate_Hdiv_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_div: &(impl Fn(&OPoint<T, Space::GeometryDim>) -> T + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_estimate_Hdiv_error_squared(space, u, u_div, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Estimate the squared $L^2$ error $\norm{u_h - u}^2_{L^2}$ on the given finite element space
+/// in parallel, processing elements in contiguous chunks.
+///
+/// This function is intended for post-processing very large meshes. No per-element data is
+/// stored unless `element_errors_squared` is provided, in which case the squared error of each
+/// element is written to it. The result is deterministic for a fixed chunk size. The solution
+/// weights only need to be available as a (possibly strided) view, so they may for example
+/// reside in a memory-mapped file that holds many time steps of a transient simulation.
+///
+/// See [`par_assemble_scalar_chunked`] for details and [`estimate_L2_error_squared`] for the
+/// sequential version.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+#[allow(non_snake_case)]
+pub fn par_estimate_L2_error_squared_chunked<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync + ?Sized),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+    chunk_size: usize,
+    element_errors_squared: Option<&mut [T]>,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_L2_error_squared_integrand(u))
+        .build_integrator();
+
+    par_assemble_scalar_chunked(&assembler, chunk_size, element_errors_squared)
+}
+
+/// Estimate the squared $H^1$ *seminorm* error $\seminorm{u_h - u}^2_{H^1}$ on the given finite
+/// element space in parallel, processing elements in contiguous chunks.
+///
+/// See [`par_estimate_L2_error_squared_chunked`] for details on the chunked evaluation and
+/// [`estimate_H1_seminorm_error_squared`] for the sequential version.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_seminorm_error_squared_chunked<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+    chunk_size: usize,
+    element_errors_squared: Option<&mut [T]>,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_seminorm_error_squared_integrand(u_grad))
+        .build_volume_integrator();
+
+    par_assemble_scalar_chunked(&assembler, chunk_size, element_errors_squared)
+}
+
+/// Estimate the squared $H^1$ error $\norm{u_h - u}^2_{H^1}$ on the given finite element space
+/// in parallel, processing elements in contiguous chunks.
+///
+/// See [`par_estimate_L2_error_squared_chunked`] for details on the chunked evaluation and
+/// [`estimate_H1_error_squared`] for the sequential version.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_error_squared_chunked<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+    chunk_size: usize,
+    element_errors_squared: Option<&mut [T]>,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_error_squared_integrand(u, u_grad))
+        .build_volume_integrator();
+
+    par_assemble_scalar_chunked(&assembler, chunk_size, element_errors_squared)
+}
@@ -0,0 +1,247 @@
+use crate::Real;
+use eyre::eyre;
+
+/// The error of a single discretization in a convergence study.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConvergenceSample<T> {
+    /// The number of degrees of freedom of the discretization.
+    pub num_dofs: usize,
+    /// The error of the discrete solution, measured in a norm of choice.
+    pub error: T,
+}
+
+/// A least-squares fit of errors to a model of convergence.
+///
+/// For [algebraic convergence](fit_algebraic_convergence), the model is $e = C h^r$, and for
+/// [exponential convergence](fit_exponential_convergence), the model is $e = C \exp(-r x)$.
+/// Here, $C$ is the [coefficient](Self::coefficient) and $r$ the [rate](Self::rate) of
+/// convergence.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConvergenceFit<T> {
+    coefficient: T,
+    rate: T,
+}
+
+impl<T: Real> ConvergenceFit<T> {
+    pub fn coefficient(&self) -> T {
+        self.coefficient
+    }
+
+    pub fn rate(&self) -> T {
+        self.rate
+    }
+}
+
+/// Fits errors obtained by h-refinement to the algebraic model $e = C h^r$.
+///
+/// The fit is a least-squares fit of $\log e$ against $\log h$, where $h$ is the resolution
+/// (typically the mesh size) of each discretization.
+///
+/// # Errors
+///
+/// Returns an error if the number of resolutions and errors differ, if fewer than two samples
+/// with distinct resolutions are given, or if a resolution or error is not positive.
+pub fn fit_algebraic_convergence<T: Real>(resolutions: &[T], errors: &[T]) -> eyre::Result<ConvergenceFit<T>> {
+    if resolutions.iter().any(|&h| h <= T::zero()) {
+        return Err(eyre!("Resolutions must be positive"));
+    }
+    let log_resolutions: Vec<_> = resolutions.iter().map(|h| h.ln()).collect();
+    let (intercept, slope) = fit_line_to_log_errors(&log_resolutions, errors)?;
+    Ok(ConvergenceFit {
+        coefficient: intercept.exp(),
+        rate: slope,
+    })
+}
+
+/// Fits errors to the exponential model $e = C \exp(-r x)$.
+///
+/// The fit is a least-squares fit of $\log e$ against $x$. For p-refinement, $x$ is typically
+/// the polynomial order $p$, or $N^{1/d}$ for $N$ degrees of freedom in dimension $d$,
+/// see [`PRefinementStudy`].
+///
+/// # Errors
+///
+/// Returns an error if the number of parameters and errors differ, if fewer than two samples
+/// with distinct parameters are given, or if an error is not positive.
+pub fn fit_exponential_convergence<T: Real>(parameters: &[T], errors: &[T]) -> eyre::Result<ConvergenceFit<T>> {
+    let (intercept, slope) = fit_line_to_log_errors(parameters, errors)?;
+    Ok(ConvergenceFit {
+        coefficient: intercept.exp(),
+        rate: -slope,
+    })
+}
+
+/// Computes the least-squares line $\log e = a + b x$, returning $(a, b)$.
+fn fit_line_to_log_errors<T: Real>(x: &[T], errors: &[T]) -> eyre::Result<(T, T)> {
+    if x.len() != errors.len() {
+        return Err(eyre!(
+            "Number of samples ({}) does not match number of errors ({})",
+            x.len(),
+            errors.len()
+        ));
+    }
+    if errors.iter().any(|&e| e <= T::zero()) {
+        return Err(eyre!("Errors must be positive"));
+    }
+    if x.len() < 2 {
+        return Err(eyre!("At least two samples are required to fit convergence"));
+    }
+    let n = T::from_usize(x.len()).unwrap();
+    let y: Vec<_> = errors.iter().map(|e| e.ln()).collect();
+    let x_mean = x.iter().fold(T::zero(), |sum, &x_i| sum + x_i) / n;
+    let y_mean = y.iter().fold(T::zero(), |sum, &y_i| sum + y_i) / n;
+    let (mut sxy, mut sxx) = (T::zero(), T::zero());
+    for (&x_i, &y_i) in x.iter().zip(&y) {
+        sxy += (x_i - x_mean) * (y_i - y_mean);
+        sxx += (x_i - x_mean) * (x_i - x_mean);
+    }
+    if sxx == T::zero() {
+        return Err(eyre!("At least two distinct samples are required to fit convergence"));
+    }
+    let slope = sxy / sxx;
+    Ok((y_mean - slope * x_mean, slope))
+}
+
+/// The largest order supported by [`solve_with_lagrange_order`].
+pub const MAX_LAGRANGE_SWEEP_ORDER: usize = 10;
+
+/// A problem that can be discretized with Lagrange elements of any (compile-time) order.
+///
+/// Since the order of [`LagrangeSimplexSpace`](crate::space::LagrangeSimplexSpace) is a
+/// const generic parameter, a p-refinement sweep needs to instantiate the discretization for
+/// each order. Implementing this trait allows [`solve_with_lagrange_order`] and
+/// [`PRefinementStudy::run_lagrange`] to select the order at runtime.
+pub trait LagrangeOrderSweep<T> {
+    /// Solves the problem with elements of order `ORDER` and returns the error.
+    fn solve<const ORDER: usize>(&mut self) -> eyre::Result<ConvergenceSample<T>>;
+}
+
+/// Calls [`LagrangeOrderSweep::solve`] with the given runtime order.
+///
+/// # Errors
+///
+/// Returns an error if the order is zero or exceeds [`MAX_LAGRANGE_SWEEP_ORDER`], or if the
+/// sweep returns an error.
+pub fn solve_with_lagrange_order<T>(
+    sweep: &mut impl LagrangeOrderSweep<T>,
+    order: usize,
+) -> eyre::Result<ConvergenceSample<T>> {
+    match order {
+        1 => sweep.solve::<1>(),
+        2 => sweep.solve::<2>(),
+        3 => sweep.solve::<3>(),
+        4 => sweep.solve::<4>(),
+        5 => sweep.solve::<5>(),
+        6 => sweep.solve::<6>(),
+        7 => sweep.solve::<7>(),
+        8 => sweep.solve::<8>(),
+        9 => sweep.solve::<9>(),
+        10 => sweep.solve::<10>(),
+        _ => Err(eyre!(
+            "Order {order} is not supported by Lagrange order sweeps (supported orders: 1 to {MAX_LAGRANGE_SWEEP_ORDER})"
+        )),
+    }
+}
+
+/// The results of a p-refinement study, in which a problem is solved on a fixed mesh with
+/// increasing polynomial order.
+///
+/// For problems with analytic solutions, the error of p-refinement decreases exponentially,
+/// i.e. $e \approx C \exp(-r p)$ in terms of the order $p$, or
+/// $e \approx C \exp(-r N^{1/d})$ in terms of the number of degrees of freedom $N$ in
+/// dimension $d$. The rates can be estimated with [`fit_in_order`](Self::fit_in_order) and
+/// [`fit_in_dofs`](Self::fit_in_dofs). For h-refinement studies, which exhibit algebraic
+/// convergence, see [`fit_algebraic_convergence`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PRefinementStudy<T> {
+    orders: Vec<usize>,
+    samples: Vec<ConvergenceSample<T>>,
+}
+
+impl<T: Real> PRefinementStudy<T> {
+    /// Runs a p-refinement study by calling `solve` for each of the given orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `solve`.
+    pub fn run(
+        orders: impl IntoIterator<Item = usize>,
+        mut solve: impl FnMut(usize) -> eyre::Result<ConvergenceSample<T>>,
+    ) -> eyre::Result<Self> {
+        let orders: Vec<_> = orders.into_iter().collect();
+        let samples = orders
+            .iter()
+            .map(|&order| solve(order))
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self { orders, samples })
+    }
+
+    /// Runs a p-refinement study for a problem discretized with Lagrange elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an order is not supported by [`solve_with_lagrange_order`], or the
+    /// first error returned by the sweep.
+    pub fn run_lagrange(
+        orders: impl IntoIterator<Item = usize>,
+        sweep: &mut impl LagrangeOrderSweep<T>,
+    ) -> eyre::Result<Self> {
+        Self::run(orders, |order| solve_with_lagrange_order(sweep, order))
+    }
+
+    pub fn orders(&self) -> &[usize] {
+        &self.orders
+    }
+
+    pub fn samples(&self) -> &[ConvergenceSample<T>] {
+        &self.samples
+    }
+
+    pub fn errors(&self) -> Vec<T> {
+        self.samples.iter().map(|sample| sample.error).collect()
+    }
+
+    /// The rates $r_k = \log(e_{k-1} / e_k) / (p_k - p_{k-1})$ between consecutive orders.
+    ///
+    /// Exponential convergence manifests itself in rates that do not decrease with the order.
+    pub fn observed_rates(&self) -> Vec<T> {
+        self.orders
+            .windows(2)
+            .zip(self.samples.windows(2))
+            .map(|(p, e)| {
+                let dp = T::from_usize(p[1]).unwrap() - T::from_usize(p[0]).unwrap();
+                (e[0].error / e[1].error).ln() / dp
+            })
+            .collect()
+    }
+
+    /// Fits the errors to the model $e = C \exp(-r p)$ in terms of the order $p$.
+    ///
+    /// # Errors
+    ///
+    /// See [`fit_exponential_convergence`].
+    pub fn fit_in_order(&self) -> eyre::Result<ConvergenceFit<T>> {
+        let orders: Vec<_> = self
+            .orders
+            .iter()
+            .map(|&p| T::from_usize(p).unwrap())
+            .collect();
+        fit_exponential_convergence(&orders, &self.errors())
+    }
+
+    /// Fits the errors to the model $e = C \exp(-r N^{1/d})$ in terms of the number of degrees
+    /// of freedom $N$ in dimension $d$.
+    ///
+    /// # Errors
+    ///
+    /// See [`fit_exponential_convergence`].
+    pub fn fit_in_dofs(&self, dim: usize) -> eyre::Result<ConvergenceFit<T>> {
+        let exponent = T::one() / T::from_usize(dim).unwrap();
+        let x: Vec<_> = self
+            .samples
+            .iter()
+            .map(|sample| T::from_usize(sample.num_dofs).unwrap().powf(exponent))
+            .collect();
+        fit_exponential_convergence(&x, &self.errors())
+    }
+}
@@ -0,0 +1,553 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::QuadratureTable;
+use crate::assembly::operators::EllipticOperator;
+use crate::assembly::patch::PatchTopology;
+use crate::connectivity::Connectivity;
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DMatrix, DMatrixView, DVector, DVectorView, DefaultAllocator, DimName, Dyn, OMatrix, OPoint};
+use crate::space::{ElementInSpace, FiniteElementSpace, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+
+/// The number of times a patch is enlarged by its neighboring elements if it does not contain
+/// enough sampling points for a well-posed fit.
+const MAX_PATCH_ENLARGEMENTS: usize = 2;
+
+/// Recovers nodal values of a field sampled at quadrature points with superconvergent patch
+/// recovery (SPR).
+///
+/// Quantities derived from the gradient of a finite element solution, such as gradients, fluxes
+/// or stresses, are discontinuous across elements, but are often particularly accurate at
+/// certain points inside the elements (the superconvergent points, e.g. the Gauss points of
+/// quadrilaterals). Following Zienkiewicz and Zhu, the value at each node $I$ is recovered by
+/// fitting a linear polynomial
+/// <div>$$
+/// p(\vec x) = \vec a_0 + \sum_{k = 1}^d \vec a_k \frac{x_k - x_{I, k}}{h_I}
+/// $$</div>
+/// in the least-squares sense to the samples at the quadrature points of all elements that
+/// contain the node, and evaluating $p(\vec x_I) = \vec a_0$. Here, $h_I$ is the largest distance
+/// from the node to a sampling point and only serves to improve conditioning.
+///
+/// Patches of boundary nodes only contain samples on one side of the node, so that the fit
+/// extrapolates poorly. The value at a boundary node is therefore the average of the
+/// polynomials of the neighboring interior nodes evaluated at the boundary node. Nodes without
+/// such neighbors use their own patch, which is enlarged by the neighboring elements if it does
+/// not contain enough points for a well-posed fit. If the fit remains ill-posed, the weighted
+/// average of the samples in the patch is used instead.
+///
+/// The recovered field is continuous and can be interpolated with the basis functions of the
+/// mesh, which makes this a general-purpose facility for smoothing nodal stresses for
+/// postprocessing.
+///
+/// The function `sample` is called for each element and each (reference) quadrature point
+/// of the quadrature table and must return the `field_dim` components of the field at the
+/// point. The nodal values are returned in the usual interleaved format with `field_dim`
+/// components per node.
+///
+/// # Errors
+///
+/// Returns an error if `sample` returns an error or a vector of the wrong length, or if a node is
+/// not contained in any element with quadrature points.
+pub fn recover_nodal_field<T, D, C, QTable>(
+    mesh: &Mesh<T, D, C>,
+    qtable: &QTable,
+    field_dim: usize,
+    sample: impl FnMut(usize, &OPoint<T, D>) -> eyre::Result<DVector<T>>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let samples = FieldSamples::from_mesh(mesh, qtable, field_dim, sample)?;
+    samples.recover(mesh)
+}
+
+/// Recovers the gradient $\nabla u_h$ of a finite element solution at the nodes of a mesh with
+/// superconvergent patch recovery.
+///
+/// The solution `u` is stored in the interleaved format with `solution_dim` components per node.
+/// For each node, the recovered gradient is a $d \times s$ matrix, stored in column-major order
+/// as $d s$ consecutive entries of the returned vector, where $d$ is the dimension and $s$ the
+/// solution dimension. See [`recover_nodal_field`] for details of the recovery.
+///
+/// # Errors
+///
+/// Returns an error if an element Jacobian is singular, or if a node is not contained in any
+/// element with quadrature points.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not `solution_dim` times the number of nodes.
+pub fn recover_nodal_gradients<'a, T, D, C, QTable>(
+    mesh: &Mesh<T, D, C>,
+    qtable: &QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    solution_dim: usize,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let mut gradient = GradientSampler::new(mesh, u.into(), solution_dim);
+    recover_nodal_field(mesh, qtable, D::dim() * solution_dim, |element_index, xi| {
+        let gradient = gradient.evaluate(element_index, xi)?;
+        Ok(DVector::from_column_slice(gradient.as_slice()))
+    })
+}
+
+/// Recovers the flux $g(\nabla u_h)$ of an elliptic operator at the nodes of a mesh with
+/// superconvergent patch recovery.
+///
+/// For elasticity operators, the flux is the (first Piola-Kirchhoff) stress, so that this
+/// computes smoothed nodal stresses. The fluxes are stored in the same format as the gradients
+/// returned by [`recover_nodal_gradients`]. The operator parameters are assumed to be the same
+/// everywhere.
+///
+/// # Errors
+///
+/// Returns an error if an element Jacobian is singular, or if a node is not contained in any
+/// element with quadrature points.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not $s$ times the number of nodes, where $s$ is the
+/// solution dimension of the operator.
+pub fn recover_nodal_fluxes<'a, T, D, C, QTable, Op>(
+    mesh: &Mesh<T, D, C>,
+    qtable: &QTable,
+    operator: &Op,
+    operator_parameters: &Op::Parameters,
+    u: impl Into<DVectorView<'a, T>>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: QuadratureTable<T, D>,
+    Op: EllipticOperator<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    let s = Op::SolutionDim::dim();
+    let mut gradient = GradientSampler::new(mesh, u.into(), s);
+    recover_nodal_field(mesh, qtable, D::dim() * s, |element_index, xi| {
+        let gradient = gradient.evaluate(element_index, xi)?;
+        let gradient = OMatrix::<T, D, Op::SolutionDim>::from_column_slice(gradient.as_slice());
+        let flux = operator.compute_elliptic_operator(&gradient, operator_parameters);
+        Ok(DVector::from_column_slice(flux.as_slice()))
+    })
+}
+
+/// Computes recovery-based error indicators for a field sampled at quadrature points.
+///
+/// The field $\sigma_h$ given by `sample` is recovered with [`recover_nodal_field`], and the
+/// recovered field $\sigma^*_h$ is interpolated with the basis functions of the mesh. The
+/// indicator for cell $K$ is then
+/// <div>$$
+/// \eta_K = \norm{\sigma^*_h - \sigma_h}_{L^2(K)},
+/// $$</div>
+/// where the integral is computed with the quadrature table. Since the recovered field is
+/// generally more accurate than the sampled field, $\eta_K$ estimates the error of
+/// $\sigma_h$ in cell $K$. The quadrature should integrate the squared difference exactly,
+/// e.g. with degree 2 for gradients of linear triangles, since otherwise the indicators
+/// are underestimated.
+///
+/// # Errors
+///
+/// Returns an error if `sample` returns an error or a vector of the wrong length, or if a node is
+/// not contained in any element with quadrature points.
+pub fn estimate_recovery_error_indicators<T, D, C, QTable>(
+    mesh: &Mesh<T, D, C>,
+    qtable: &QTable,
+    field_dim: usize,
+    sample: impl FnMut(usize, &OPoint<T, D>) -> eyre::Result<DVector<T>>,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let samples = FieldSamples::from_mesh(mesh, qtable, field_dim, sample)?;
+    let recovered = samples.recover(mesh)?;
+
+    let mut basis_buffer = BasisFunctionBuffer::default();
+    let mut recovered_local = DVector::zeros(0);
+    (0..mesh.connectivity().len())
+        .map(|element_index| {
+            let element = ElementInSpace::from_space_and_element_index(mesh, element_index);
+            basis_buffer.resize(element.num_nodes(), D::dim());
+            basis_buffer.populate_element_nodes_from_space(element_index, mesh);
+            recovered_local.resize_vertically_mut(field_dim * element.num_nodes(), T::zero());
+            gather_global_to_local(
+                &recovered,
+                &mut recovered_local,
+                basis_buffer.element_nodes(),
+                field_dim,
+            );
+            let recovered_local = DMatrixView::from_slice(recovered_local.as_slice(), field_dim, element.num_nodes());
+
+            let mut error_squared = T::zero();
+            for i in samples.element_samples(element_index) {
+                basis_buffer.populate_element_basis_values_from_space(
+                    element_index,
+                    mesh,
+                    &samples.reference_points[i],
+                );
+                let phi = DVectorView::from(basis_buffer.element_basis_values());
+                let difference = recovered_local * phi - samples.value(i);
+                error_squared += samples.weights[i] * difference.norm_squared();
+            }
+            Ok(error_squared.sqrt())
+        })
+        .collect()
+}
+
+/// Computes the Zienkiewicz-Zhu (ZZ) error indicators of a finite element solution.
+///
+/// The indicator for cell $K$ is $\eta_K = \norm{G^*_h - \nabla u_h}_{L^2(K)}$, where $G^*_h$ is
+/// the gradient recovered with [`recover_nodal_gradients`]. For elliptic problems, the global
+/// estimate $\eta = (\sum_K \eta_K^2)^{1/2}$ is asymptotically exact for the $H^1$ seminorm error
+/// on sufficiently regular meshes, and the indicators can be used for marking cells for adaptive
+/// refinement. See [`estimate_recovery_error_indicators`] for details.
+///
+/// # Errors
+///
+/// Returns an error if an element Jacobian is singular, or if a node is not contained in any
+/// element with quadrature points.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not `solution_dim` times the number of nodes.
+pub fn estimate_zz_error_indicators<'a, T, D, C, QTable>(
+    mesh: &Mesh<T, D, C>,
+    qtable: &QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    solution_dim: usize,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: QuadratureTable<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let mut gradient = GradientSampler::new(mesh, u.into(), solution_dim);
+    estimate_recovery_error_indicators(mesh, qtable, D::dim() * solution_dim, |element_index, xi| {
+        let gradient = gradient.evaluate(element_index, xi)?;
+        Ok(DVector::from_column_slice(gradient.as_slice()))
+    })
+}
+
+/// Evaluates the physical gradient of a finite element solution at reference points.
+struct GradientSampler<'a, T: Real, Space: ?Sized> {
+    space: &'a Space,
+    u: DVectorView<'a, T>,
+    solution_dim: usize,
+    basis_buffer: BasisFunctionBuffer<T>,
+    u_local: DVector<T>,
+}
+
+impl<'a, T, D, Space> GradientSampler<'a, T, Space>
+where
+    T: Real,
+    D: SmallDim,
+    Space: ?Sized + FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn new(space: &'a Space, u: DVectorView<'a, T>, solution_dim: usize) -> Self {
+        assert_eq!(
+            u.len(),
+            solution_dim * space.num_nodes(),
+            "Length of solution vector must be solution dim times number of nodes"
+        );
+        Self {
+            space,
+            u,
+            solution_dim,
+            basis_buffer: BasisFunctionBuffer::default(),
+            u_local: DVector::zeros(0),
+        }
+    }
+
+    fn evaluate(&mut self, element_index: usize, reference_point: &OPoint<T, D>) -> eyre::Result<OMatrix<T, D, Dyn>> {
+        let node_count = self.space.element_node_count(element_index.into());
+        self.basis_buffer.resize(node_count, D::dim());
+        self.basis_buffer
+            .populate_element_nodes_from_space(element_index, self.space);
+        self.basis_buffer
+            .populate_element_basis_gradients_from_space(element_index, self.space, reference_point);
+        self.u_local
+            .resize_vertically_mut(self.solution_dim * node_count, T::zero());
+        gather_global_to_local(
+            self.u,
+            &mut self.u_local,
+            self.basis_buffer.element_nodes(),
+            self.solution_dim,
+        );
+
+        // grad_xi u_h = sum_I grad_xi phi_I (x) u_I
+        let u_local = DMatrixView::from_slice(self.u_local.as_slice(), self.solution_dim, node_count);
+        let ref_gradient = self.basis_buffer.element_gradients::<D>() * u_local.transpose();
+        let jacobian = self
+            .space
+            .element_reference_jacobian(element_index, reference_point);
+        let inv_j_t = jacobian
+            .try_inverse()
+            .ok_or_else(|| eyre!("Singular Jacobian in element {}", element_index))?
+            .transpose();
+        Ok(inv_j_t * ref_gradient)
+    }
+}
+
+/// Samples of a field at the quadrature points of all elements of a mesh.
+struct FieldSamples<T: Real, D: DimName>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    field_dim: usize,
+    reference_points: Vec<OPoint<T, D>>,
+    physical_points: Vec<OPoint<T, D>>,
+    // Quadrature weights multiplied with the absolute Jacobian determinant
+    weights: Vec<T>,
+    values: Vec<T>,
+    element_offsets: Vec<usize>,
+}
+
+impl<T, D> FieldSamples<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn from_mesh<C, QTable>(
+        mesh: &Mesh<T, D, C>,
+        qtable: &QTable,
+        field_dim: usize,
+        mut sample: impl FnMut(usize, &OPoint<T, D>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<Self>
+    where
+        C: Connectivity,
+        Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+        QTable: QuadratureTable<T, D>,
+    {
+        let mut samples = Self {
+            field_dim,
+            reference_points: Vec::new(),
+            physical_points: Vec::new(),
+            weights: Vec::new(),
+            values: Vec::new(),
+            element_offsets: vec![0],
+        };
+        let mut quadrature_buffer = QuadratureBuffer::<T, D>::default();
+        for element_index in 0..mesh.connectivity().len() {
+            let element = ElementInSpace::from_space_and_element_index(mesh, element_index);
+            quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+            for (&w, xi) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+                let value = sample(element_index, xi)?;
+                if value.len() != field_dim {
+                    return Err(eyre!(
+                        "Sampled field has {} components, expected {}",
+                        value.len(),
+                        field_dim
+                    ));
+                }
+                samples.values.extend_from_slice(value.as_slice());
+                samples
+                    .weights
+                    .push(w * element.reference_jacobian(xi).determinant().abs());
+                samples
+                    .physical_points
+                    .push(element.map_reference_coords(xi));
+                samples.reference_points.push(xi.clone());
+            }
+            samples.element_offsets.push(samples.weights.len());
+        }
+        Ok(samples)
+    }
+
+    fn element_samples(&self, element_index: usize) -> std::ops::Range<usize> {
+        self.element_offsets[element_index]..self.element_offsets[element_index + 1]
+    }
+
+    fn value(&self, sample_index: usize) -> DVectorView<'_, T> {
+        let m = self.field_dim;
+        DVectorView::from_slice(&self.values[m * sample_index..m * (sample_index + 1)], m)
+    }
+
+    fn recover<C>(&self, mesh: &Mesh<T, D, C>) -> eyre::Result<DVector<T>>
+    where
+        C: Connectivity,
+        Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    {
+        let m = self.field_dim;
+        let num_nodes = mesh.vertices().len();
+        let topology = PatchTopology::from_space(mesh);
+        let mut is_boundary = vec![false; num_nodes];
+        for node in mesh.find_boundary_vertices() {
+            is_boundary[node] = true;
+        }
+
+        let fits: Vec<_> = (0..num_nodes)
+            .map(|node| {
+                (!is_boundary[node])
+                    .then(|| self.fit_patch(&mesh.vertices()[node], topology.node_elements(node)))
+                    .flatten()
+            })
+            .collect();
+
+        let mut recovered = DVector::zeros(m * num_nodes);
+        for node in 0..num_nodes {
+            let x = &mesh.vertices()[node];
+            let value = if let Some(fit) = &fits[node] {
+                fit.evaluate(x)
+            } else {
+                // Boundary patches only contain samples on one side of the node, so the value is
+                // instead given by the average of the fits of the neighboring interior patches
+                let mut neighbors: Vec<usize> = topology
+                    .node_elements(node)
+                    .iter()
+                    .flat_map(|&element| topology.element_nodes(element))
+                    .copied()
+                    .filter(|&neighbor| fits[neighbor].is_some())
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                if neighbors.is_empty() {
+                    self.recover_from_own_patch(&topology, node, x)
+                        .ok_or_else(|| eyre!("Node {} is not contained in any element with quadrature points", node))?
+                } else {
+                    let sum = neighbors
+                        .iter()
+                        .filter_map(|&neighbor| fits[neighbor].as_ref())
+                        .fold(DVector::zeros(m), |sum, fit| sum + fit.evaluate(x));
+                    sum / T::from_usize(neighbors.len()).unwrap()
+                }
+            };
+            recovered.rows_mut(m * node, m).copy_from(&value);
+        }
+        Ok(recovered)
+    }
+
+    /// Recovers the value at a node from the patch of the node, which is enlarged if necessary.
+    ///
+    /// Returns `None` if the enlarged patch contains no samples.
+    fn recover_from_own_patch(&self, topology: &PatchTopology, node: usize, x: &OPoint<T, D>) -> Option<DVector<T>> {
+        let mut elements = topology.node_elements(node).to_vec();
+        for _ in 0..MAX_PATCH_ENLARGEMENTS {
+            if let Some(fit) = self.fit_patch(x, &elements) {
+                return Some(fit.evaluate(x));
+            }
+            elements = elements
+                .iter()
+                .flat_map(|&element| topology.element_star(element))
+                .collect();
+            elements.sort_unstable();
+            elements.dedup();
+        }
+        self.fit_patch(x, &elements)
+            .map(|fit| fit.evaluate(x))
+            .or_else(|| self.average_patch(&elements))
+    }
+
+    /// Fits a linear polynomial around `x` to the samples of the given elements.
+    ///
+    /// Returns `None` if the least-squares problem is ill-posed.
+    fn fit_patch(&self, x: &OPoint<T, D>, elements: &[usize]) -> Option<PatchFit<T, D>> {
+        let sample_indices: Vec<usize> = elements
+            .iter()
+            .flat_map(|&element| self.element_samples(element))
+            .collect();
+        let d = D::dim();
+        if sample_indices.len() < d + 1 {
+            return None;
+        }
+        let h = sample_indices
+            .iter()
+            .map(|&i| (&self.physical_points[i] - x).norm())
+            .fold(T::zero(), T::max);
+        if h <= T::zero() {
+            return None;
+        }
+
+        let mut a = DMatrix::zeros(sample_indices.len(), d + 1);
+        let mut b = DMatrix::zeros(sample_indices.len(), self.field_dim);
+        for (row, &i) in sample_indices.iter().enumerate() {
+            a[(row, 0)] = T::one();
+            let offset = (&self.physical_points[i] - x) / h;
+            for k in 0..d {
+                a[(row, k + 1)] = offset[k];
+            }
+            b.row_mut(row).copy_from(&self.value(i).transpose());
+        }
+
+        let svd = a.svd(true, true);
+        let tolerance = T::from_f64(1e-10).unwrap() * svd.singular_values.max();
+        if svd.singular_values.min() <= tolerance {
+            return None;
+        }
+        let coefficients = svd.solve(&b, T::zero()).ok()?;
+        Some(PatchFit {
+            center: x.clone(),
+            scale: h,
+            coefficients,
+        })
+    }
+
+    /// Computes the weighted average of the samples of the given elements.
+    fn average_patch(&self, elements: &[usize]) -> Option<DVector<T>> {
+        let mut sum = DVector::zeros(self.field_dim);
+        let mut total_weight = T::zero();
+        for i in elements
+            .iter()
+            .flat_map(|&element| self.element_samples(element))
+        {
+            sum += self.value(i) * self.weights[i];
+            total_weight += self.weights[i];
+        }
+        (total_weight > T::zero()).then(|| sum / total_weight)
+    }
+}
+
+/// A linear polynomial fitted to the samples of a patch.
+struct PatchFit<T: Real, D: DimName>
+where
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    center: OPoint<T, D>,
+    scale: T,
+    // Row 0 holds the value at the center, row k the derivative with respect to the k-th scaled
+    // coordinate
+    coefficients: DMatrix<T>,
+}
+
+impl<T, D> PatchFit<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn evaluate(&self, x: &OPoint<T, D>) -> DVector<T> {
+        let offset = (x - &self.center) / self.scale;
+        let mut value = self.coefficients.row(0).transpose();
+        for k in 0..D::dim() {
+            value += self.coefficients.row(k + 1).transpose() * offset[k];
+        }
+        value
+    }
+}
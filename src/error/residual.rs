@@ -0,0 +1,413 @@
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
+use crate::assembly::buffers::{BufferUpdate, InterpolationBuffer};
+use crate::assembly::local::{surface_measure_and_normal, SourceFunction};
+use crate::assembly::operators::EllipticOperator;
+use crate::connectivity::Connectivity;
+use crate::element::{map_physical_coordinates, ConnectivityReferenceDim, ElementConnectivity, FiniteElement};
+use crate::mesh::split::find_interior_faces;
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVector, DVectorView, DefaultAllocator, DimMin, DimName, OMatrix, OPoint, OVector};
+use crate::quadrature::QuadraturePair;
+use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+use std::cmp::Ordering;
+
+/// Computes residual-based a posteriori error indicators for each cell of a mesh.
+///
+/// Given a finite element approximation $u_h$ of the elliptic problem
+/// $- \nabla \cdot g(\nabla u) = f$ with homogeneous or inhomogeneous Dirichlet boundary
+/// conditions, the indicator for cell $K$ is
+/// <div>$$
+/// \eta_K^2 = h_K^2 \norm{f + \nabla \cdot g(\nabla u_h)}^2_{L^2(K)}
+///     + \frac{1}{2} \sum_{F \subset \partial K \setminus \partial \Omega}
+///         h_F \norm{[\![ g(\nabla u_h)^T \vec n ]\!]}^2_{L^2(F)},
+/// $$</div>
+/// where $h_K$ and $h_F$ are the diameters of the cell and the face, and
+/// $[\![ g(\nabla u_h)^T \vec n ]\!]$ is the jump of the normal flux across an interior face.
+/// The global estimate $\eta = (\sum_K \eta_K^2)^{1/2}$ is, up to constants, an upper and local
+/// lower bound of the energy norm of the error. Unlike the estimates in this module that compare
+/// with a known solution, the indicators only require the discrete solution, and are therefore
+/// suitable for marking cells for adaptive refinement, for example with [`mark_dorfler`] and
+/// [`refine_marked_cells`](crate::mesh::refinement::refine_marked_cells).
+///
+/// Boundary faces do not contribute, which corresponds to Dirichlet conditions on the entire
+/// boundary. The operator parameters are assumed to be the same everywhere, and the source
+/// function is evaluated with `()` parameters.
+///
+/// Since elements do not provide second derivatives of their basis functions, the divergence
+/// of the flux inside each cell is computed by central differences in reference coordinates.
+/// This is exact (up to round-off) if the flux is at most quadratic in each reference
+/// coordinate, which includes linear and quadratic elements with affine geometry. For linear
+/// elements with affine geometry, the divergence vanishes.
+///
+/// The cell quadrature is used for all cells and the face quadrature for the reference element
+/// of the face connectivity. The face connectivity must have an associated finite element,
+/// which is currently the case for triangle and quadrilateral meshes in 2D and for tetrahedral
+/// meshes in 3D.
+///
+/// # Errors
+///
+/// Returns an error if the point of a face quadrature point in the reference coordinates of an
+/// adjacent cell could not be determined, or if a face element could not be constructed.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not $s N$, where $s$ is the solution dimension and $N$ the
+/// number of vertices in the mesh.
+pub fn estimate_residual_error_indicators<T, D, C, Op, Source>(
+    mesh: &Mesh<T, D, C>,
+    operator: &Op,
+    operator_parameters: &Op::Parameters,
+    source: &Source,
+    u: DVectorView<T>,
+    cell_quadrature: &QuadraturePair<T, D>,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Op: EllipticOperator<T, D>,
+    Source: SourceFunction<T, D, SolutionDim = Op::SolutionDim, Parameters = ()>,
+    DefaultAllocator: TriDimAllocator<T, D, D, Op::SolutionDim>
+        + BiDimAllocator<T, D, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+{
+    let diameters: Vec<T> = (0..mesh.connectivity().len())
+        .map(|cell| ElementInSpace::from_space_and_element_index(mesh, cell).diameter())
+        .collect();
+    let two = T::from_f64(2.0).unwrap();
+    let indicators_squared = integrate_residuals(
+        mesh,
+        operator,
+        operator_parameters,
+        source,
+        u,
+        cell_quadrature,
+        face_quadrature,
+        |cell, _, residual| Ok(diameters[cell] * diameters[cell] * residual.norm_squared()),
+        |_, face_diameter, _, jump| Ok(face_diameter * jump.norm_squared() / two),
+    )?;
+
+    Ok(indicators_squared
+        .into_iter()
+        .map(|eta_squared| eta_squared.sqrt())
+        .collect())
+}
+
+/// Computes dual-weighted residual (DWR) error contributions for each cell of a mesh.
+///
+/// Given a finite element approximation $u_h$ of the elliptic problem
+/// $- \nabla \cdot g(\nabla u) = f$ (see [`estimate_residual_error_indicators`]) and a linear goal
+/// functional $J$, the goal-oriented error is represented by
+/// <div>$$
+/// J(u) - J(u_h) \approx \sum_K \eta_K, \qquad
+/// \eta_K = \int_K \vec r \cdot (z - I_h z) \dx
+///     - \frac{1}{2} \sum_{F \subset \partial K \setminus \partial \Omega}
+///         \int_F [\![ g(\nabla u_h)^T \vec n ]\!] \cdot (z - I_h z) \, \mathrm{d}s,
+/// $$</div>
+/// where $\vec r = f + \nabla \cdot g(\nabla u_h)$ is the cell residual, $z$ is the solution of
+/// the dual problem associated with $J$, and $I_h z$ is the nodal interpolant of $z$ in the space
+/// of $u_h$. For linear problems, the representation is exact if $z$ is the exact dual solution.
+///
+/// Since the dual weight $z - I_h z$ vanishes for $z$ in the same space as $u_h$, the dual
+/// solution must be computed in a richer space. Here, it is given by its interpolation weights `z`
+/// on `dual_mesh`, which must consist of the same cells as `mesh` with higher order elements, as
+/// obtained by converting the mesh (e.g. `Tri3` to `Tri6`). The right-hand side of the discrete
+/// dual problem $\vec A^T \vec z = \hat j$ on `dual_mesh` can be assembled from the goal functional
+/// as described in [`goal`](crate::assembly::goal).
+///
+/// The contributions are signed, and their sum estimates the error in the goal functional.
+/// For marking, the magnitudes $|\eta_K|$ are used, which is how [`mark_dorfler`] treats them.
+/// The quadratures must account for the higher polynomial degree of the dual weight.
+///
+/// # Errors
+///
+/// Returns an error if a node of `mesh` or a face quadrature point could not be mapped into the
+/// reference coordinates of a cell, or if a face element could not be constructed.
+///
+/// # Panics
+///
+/// Panics if the number of cells in the meshes differ, or if the lengths of `u` and `z` are not
+/// the solution dimension times the number of vertices of the respective mesh.
+#[allow(clippy::too_many_arguments)]
+pub fn estimate_dwr_error_indicators<T, D, C, DualC, Op, Source>(
+    mesh: &Mesh<T, D, C>,
+    operator: &Op,
+    operator_parameters: &Op::Parameters,
+    source: &Source,
+    u: DVectorView<T>,
+    dual_mesh: &Mesh<T, D, DualC>,
+    z: DVectorView<T>,
+    cell_quadrature: &QuadraturePair<T, D>,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DualC: Connectivity,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Mesh<T, D, DualC>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Op: EllipticOperator<T, D>,
+    Source: SourceFunction<T, D, SolutionDim = Op::SolutionDim, Parameters = ()>,
+    DefaultAllocator: TriDimAllocator<T, D, D, Op::SolutionDim>
+        + BiDimAllocator<T, D, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+{
+    let s = Op::SolutionDim::dim();
+    assert_eq!(
+        mesh.connectivity().len(),
+        dual_mesh.connectivity().len(),
+        "Dual mesh must have the same number of cells as the mesh"
+    );
+    assert_eq!(
+        z.len(),
+        s * dual_mesh.vertices().len(),
+        "Length of dual solution vector must be solution dim times number of dual vertices"
+    );
+
+    // Evaluate the dual solution at the nodes of the primal mesh to obtain its interpolant
+    let mut z_interpolant = DVector::zeros(s * mesh.vertices().len());
+    let mut visited = vec![false; mesh.vertices().len()];
+    let mut buffer = InterpolationBuffer::default();
+    for (cell, connectivity) in mesh.connectivity().iter().enumerate() {
+        let dual_element = ElementInSpace::from_space_and_element_index(dual_mesh, cell);
+        for &node in connectivity.vertex_indices() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            let xi = map_physical_coordinates(&dual_element, &mesh.vertices()[node])
+                .map_err(|err| eyre!("Failed to map node {} into dual cell {}: {}", node, cell, err))?;
+            let mut element_buffer = buffer.prepare_element_in_space(cell, dual_mesh, z, s);
+            element_buffer.update_reference_point(&xi, BufferUpdate::BasisValues);
+            let z_node: OVector<T, Op::SolutionDim> = element_buffer.interpolate();
+            z_interpolant.rows_mut(s * node, s).copy_from(&z_node);
+        }
+    }
+
+    let mut cell_weight = dual_weight_fn::<_, _, _, _, Op::SolutionDim>(mesh, dual_mesh, z, &z_interpolant);
+    let mut face_weight = dual_weight_fn::<_, _, _, _, Op::SolutionDim>(mesh, dual_mesh, z, &z_interpolant);
+    let half = T::from_f64(0.5).unwrap();
+    integrate_residuals(
+        mesh,
+        operator,
+        operator_parameters,
+        source,
+        u,
+        cell_quadrature,
+        face_quadrature,
+        |cell, xi, residual| Ok(residual.dot(&cell_weight(cell, xi))),
+        |cell, _, xi, jump| Ok(-half * jump.dot(&face_weight(cell, xi))),
+    )
+}
+
+/// Returns a function that evaluates the dual weight $z - I_h z$ at reference coordinates of a cell.
+fn dual_weight_fn<'a, T, D, C, DualC, S>(
+    mesh: &'a Mesh<T, D, C>,
+    dual_mesh: &'a Mesh<T, D, DualC>,
+    z: DVectorView<'a, T>,
+    z_interpolant: &'a DVector<T>,
+) -> impl 'a + FnMut(usize, &OPoint<T, D>) -> OVector<T, S>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    DualC: Connectivity,
+    S: SmallDim,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Mesh<T, D, DualC>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: TriDimAllocator<T, D, D, S>,
+{
+    let s = S::dim();
+    let mut buffer = InterpolationBuffer::default();
+    let mut dual_buffer = InterpolationBuffer::default();
+    move |cell, xi| {
+        let mut dual_element_buffer = dual_buffer.prepare_element_in_space(cell, dual_mesh, z, s);
+        dual_element_buffer.update_reference_point(xi, BufferUpdate::BasisValues);
+        let z_value: OVector<T, S> = dual_element_buffer.interpolate();
+        let mut element_buffer = buffer.prepare_element_in_space(cell, mesh, z_interpolant, s);
+        element_buffer.update_reference_point(xi, BufferUpdate::BasisValues);
+        z_value - element_buffer.interpolate::<S>()
+    }
+}
+
+/// Integrates terms involving the cell residual and the flux jumps of a discrete solution.
+///
+/// For each cell $K$, returns
+/// <div>$$
+/// \int_K c_K(\vec \xi, \vec r) \dx
+///     + \sum_{F \subset \partial K \setminus \partial \Omega} \int_F f_K(h_F, \vec \xi, \vec j) \, \mathrm{d}s,
+/// $$</div>
+/// where $c_K$ is given by `cell_term` and $f_K$ by `face_term`. Here,
+/// $\vec r = f + \nabla \cdot g(\nabla u_h)$ is the cell residual and
+/// $\vec j = [\![ g(\nabla u_h)^T \vec n ]\!]$ the jump of the normal flux across the face $F$
+/// with diameter $h_F$, and both terms are evaluated at the reference coordinates $\vec \xi$ of
+/// the quadrature points in $K$. The jump is taken as $(g_K - g_{K'})^T \vec n_K$ with the outward
+/// unit normal $\vec n_K$ of $K$ and the neighbor $K'$, which is the same for both cells of a face. See [`estimate_residual_error_indicators`] for the assumptions
+/// and the computation of the divergence.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn integrate_residuals<T, D, C, Op, Source>(
+    mesh: &Mesh<T, D, C>,
+    operator: &Op,
+    operator_parameters: &Op::Parameters,
+    source: &Source,
+    u: DVectorView<T>,
+    cell_quadrature: &QuadraturePair<T, D>,
+    face_quadrature: &QuadraturePair<T, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+    mut cell_term: impl FnMut(usize, &OPoint<T, D>, &OVector<T, Op::SolutionDim>) -> eyre::Result<T>,
+    mut face_term: impl FnMut(usize, T, &OPoint<T, D>, &OVector<T, Op::SolutionDim>) -> eyre::Result<T>,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    Mesh<T, D, C>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Op: EllipticOperator<T, D>,
+    Source: SourceFunction<T, D, SolutionDim = Op::SolutionDim, Parameters = ()>,
+    DefaultAllocator: TriDimAllocator<T, D, D, Op::SolutionDim>
+        + BiDimAllocator<T, D, ConnectivityReferenceDim<T, C::FaceConnectivity>>,
+{
+    let s = Op::SolutionDim::dim();
+    assert_eq!(
+        u.len(),
+        s * mesh.vertices().len(),
+        "Length of solution vector must be solution dim times number of vertices"
+    );
+
+    let mut buffer = InterpolationBuffer::default();
+    let mut flux_at = |cell: usize, xi: &OPoint<T, D>| -> OMatrix<T, D, Op::SolutionDim> {
+        let mut element_buffer = buffer.prepare_element_in_space(cell, mesh, u, s);
+        element_buffer.update_reference_point(xi, BufferUpdate::BasisGradients);
+        let ref_gradient = element_buffer.interpolate_ref_gradient();
+        let jacobian = element_buffer.element_reference_jacobian();
+        let inv_j_t = jacobian
+            .try_inverse()
+            .expect("Element Jacobian must be invertible")
+            .transpose();
+        operator.compute_elliptic_operator(&(inv_j_t * ref_gradient), operator_parameters)
+    };
+
+    let num_cells = mesh.connectivity().len();
+    let mut totals = vec![T::zero(); num_cells];
+    let two = T::from_f64(2.0).unwrap();
+    let step = T::from_f64(1e-4).unwrap();
+    let (weights, points) = cell_quadrature;
+    for (cell, total) in totals.iter_mut().enumerate() {
+        let element = ElementInSpace::from_space_and_element_index(mesh, cell);
+        for (&w, xi) in izip!(weights, points) {
+            let jacobian = element.reference_jacobian(xi);
+            let inv_j = jacobian
+                .clone()
+                .try_inverse()
+                .ok_or_else(|| eyre!("Singular Jacobian in cell {}", cell))?;
+
+            // div g = sum_d sum_k dg_{d, :} / dxi_k * dxi_k / dx_d
+            let mut divergence = OVector::<T, Op::SolutionDim>::zeros();
+            for k in 0..D::dim() {
+                let mut xi_plus = xi.clone();
+                let mut xi_minus = xi.clone();
+                xi_plus[k] += step;
+                xi_minus[k] -= step;
+                let dg_dxi_k = (flux_at(cell, &xi_plus) - flux_at(cell, &xi_minus)) / (two * step);
+                for d in 0..D::dim() {
+                    divergence += dg_dxi_k.row(d).transpose() * inv_j[(k, d)];
+                }
+            }
+
+            let x = element.map_reference_coords(xi);
+            let residual = source.evaluate(&x, &()) + divergence;
+            *total += w * jacobian.determinant().abs() * cell_term(cell, xi, &residual)?;
+        }
+    }
+
+    let (face_weights, face_points) = face_quadrature;
+    for face in find_interior_faces(mesh) {
+        let [cell0, cell1] = face.cells;
+        let face_element = mesh.connectivity()[cell0]
+            .get_face_connectivity(face.local_faces[0])
+            .and_then(|face_conn| face_conn.element(mesh.vertices()))
+            .ok_or_else(|| {
+                eyre!(
+                    "Failed to construct element for face {} of cell {}",
+                    face.local_faces[0],
+                    cell0
+                )
+            })?;
+        let element0 = ElementInSpace::from_space_and_element_index(mesh, cell0);
+        let element1 = ElementInSpace::from_space_and_element_index(mesh, cell1);
+        let cell0_vertices = mesh.connectivity()[cell0].vertex_indices();
+        let centroid0 = cell0_vertices
+            .iter()
+            .fold(OVector::<T, D>::zeros(), |sum, &v| sum + &mesh.vertices()[v].coords)
+            / T::from_usize(cell0_vertices.len()).unwrap();
+
+        let face_diameter = face_element.diameter();
+        for (&w, xi_face) in izip!(face_weights, face_points) {
+            let x = face_element.map_reference_coords(xi_face);
+            let (measure, mut normal) = surface_measure_and_normal(&face_element.reference_jacobian(xi_face));
+            // Orient the normal so that it points out of the first cell
+            if (&x.coords - &centroid0).dot(&normal) < T::zero() {
+                normal = -normal;
+            }
+            let map_into = |element, cell: usize| {
+                map_physical_coordinates(element, &x)
+                    .map_err(|err| eyre!("Failed to map face quadrature point into cell {}: {}", cell, err))
+            };
+            let xi0 = map_into(&element0, cell0)?;
+            let xi1 = map_into(&element1, cell1)?;
+            let jump = (flux_at(cell0, &xi0) - flux_at(cell1, &xi1)).transpose() * &normal;
+            totals[cell0] += w * measure * face_term(cell0, face_diameter, &xi0, &jump)?;
+            totals[cell1] += w * measure * face_term(cell1, face_diameter, &xi1, &jump)?;
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Marks cells for refinement with the bulk criterion of Dörfler.
+///
+/// Returns the indices of a smallest set $\mathcal{M}$ of cells with the largest indicators
+/// such that
+/// <div>$$
+/// \sum_{K \in \mathcal{M}} \eta_K^2 \geq \theta \sum_K \eta_K^2,
+/// $$</div>
+/// sorted by decreasing indicator. The parameter $\theta \in [0, 1]$ controls the fraction of
+/// the estimated error that is targeted by refinement. No cells are marked if $\theta = 0$
+/// or all indicators vanish.
+///
+/// # Panics
+///
+/// Panics if $\theta$ is not in $[0, 1]$.
+pub fn mark_dorfler<T: Real>(indicators: &[T], theta: T) -> Vec<usize> {
+    assert!(
+        theta >= T::zero() && theta <= T::one(),
+        "Dörfler parameter must be in [0, 1]"
+    );
+    let mut order: Vec<usize> = (0..indicators.len()).collect();
+    order.sort_by(|&i, &j| {
+        indicators[j]
+            .partial_cmp(&indicators[i])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let total = indicators
+        .iter()
+        .fold(T::zero(), |sum, &eta| sum + eta * eta);
+    let target = theta * total;
+    let mut marked_sum = T::zero();
+    let mut marked = Vec::new();
+    for cell in order {
+        if marked_sum >= target || indicators[cell] == T::zero() {
+            break;
+        }
+        marked_sum += indicators[cell] * indicators[cell];
+        marked.push(cell);
+    }
+    marked
+}
@@ -0,0 +1,197 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::patch::{DenseSubmatrixExtractor, PatchTopology};
+use crate::connectivity::Connectivity;
+use crate::element::{map_physical_coordinates, ReferenceFiniteElement};
+use crate::index::NodeIndex;
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVector, DVectorView, DefaultAllocator, Scalar};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+/// Assembles the prolongation matrix that interpolates a field on a coarse space onto the nodes
+/// of a fine mesh nested inside it.
+///
+/// The fine mesh is typically obtained from the coarse mesh by conversion to higher order
+/// elements (e.g. `Tri3` to `Tri6`), or by refinement. Each fine element must be contained
+/// in the coarse element given by `fine_to_coarse_element`, which for conversions to higher
+/// order is simply the identity map. The value at each fine node is obtained by evaluating the
+/// coarse field inside the parent element of any fine element that contains the node.
+///
+/// The resulting matrix $\vec P$ has dimensions $s N_f \times s N_c$, where $s$ is the solution
+/// dimension and $N_f$ and $N_c$ are the number of fine and coarse nodes, respectively.
+/// Entries whose magnitude is below `1e-12` are dropped.
+///
+/// # Errors
+///
+/// Returns an error if the reference coordinates of a fine node could not be determined in its
+/// parent element.
+///
+/// # Panics
+///
+/// Panics if the length of `fine_to_coarse_element` does not match the number of fine elements.
+pub fn assemble_nested_prolongation<T, D, C, CoarseSpace>(
+    coarse_space: &CoarseSpace,
+    fine_mesh: &Mesh<T, D, C>,
+    fine_to_coarse_element: &[usize],
+    solution_dim: usize,
+) -> eyre::Result<CsrMatrix<T>>
+where
+    T: Real,
+    D: SmallDim,
+    C: Connectivity,
+    CoarseSpace: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, D> + Allocator<T, D>,
+{
+    assert_eq!(
+        fine_to_coarse_element.len(),
+        fine_mesh.connectivity().len(),
+        "Number of parent elements must match number of fine elements"
+    );
+    let s = solution_dim;
+    let threshold = T::from_f64(1e-12).unwrap();
+    let mut coo = CooMatrix::new(s * fine_mesh.vertices().len(), s * coarse_space.num_nodes());
+    let mut visited = vec![false; fine_mesh.vertices().len()];
+    let mut coarse_nodes = Vec::new();
+    let mut basis_values = Vec::new();
+
+    for (cell, &parent) in fine_mesh.connectivity().iter().zip(fine_to_coarse_element) {
+        let coarse_element = ElementInSpace::from_space_and_element_index(coarse_space, parent);
+        let n = coarse_space.element_node_count(parent.into());
+        coarse_nodes.resize(n, 0);
+        basis_values.resize(n, T::zero());
+        coarse_space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut coarse_nodes), parent.into());
+
+        for &fine_node in cell.vertex_indices() {
+            if visited[fine_node] {
+                continue;
+            }
+            visited[fine_node] = true;
+            let x = &fine_mesh.vertices()[fine_node];
+            let xi = map_physical_coordinates(&coarse_element, x).map_err(|err| {
+                eyre!(
+                    "Failed to map fine node {} into parent element {}: {}",
+                    fine_node,
+                    parent,
+                    err
+                )
+            })?;
+            coarse_element.populate_basis(&mut basis_values, &xi);
+            for (&coarse_node, &value) in coarse_nodes.iter().zip(&basis_values) {
+                if value.abs() > threshold {
+                    for i in 0..s {
+                        coo.push(s * fine_node + i, s * coarse_node + i, value);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(CsrMatrix::from(&coo))
+}
+
+/// The fine (enriched) system used by [`estimate_two_grid_error_indicators`].
+#[derive(Debug, Clone)]
+pub struct TwoGridSystem<'a, T: Scalar> {
+    /// The system matrix $\vec A_f$ on the fine space, with boundary conditions applied.
+    pub fine_matrix: &'a CsrMatrix<T>,
+    /// The right-hand side $\vec b_f$ on the fine space, with boundary conditions applied.
+    pub fine_rhs: DVectorView<'a, T>,
+    /// The prolongation $\vec P$ from the coarse space to the fine space.
+    pub prolongation: &'a CsrMatrix<T>,
+}
+
+/// Computes two-grid (defect correction) error indicators for each element of a coarse space.
+///
+/// Given a coarse solution $u_c$, the fine (enriched) system $\vec A_f \vec u_f = \vec b_f$
+/// and a prolongation operator $\vec P$ that transfers coarse fields to the fine space,
+/// the defect $\vec r = \vec b_f - \vec A_f \vec P \vec u_c$ of the prolonged solution
+/// is computed. For each coarse element $K$, we then solve the local defect problem
+/// <div>$$
+/// \vec A_{\omega_K} \vec e_K = \vec r_{\omega_K}
+/// $$</div>
+/// on the patch $\omega_K$, consisting of all coarse elements that share a node with $K$.
+/// The local problem involves the fine degrees of freedom that belong exclusively to
+/// fine elements inside the patch, which corresponds to homogeneous Dirichlet conditions
+/// on the interior boundary of the patch. The indicator for $K$ is the energy of the local
+/// correction, $\eta_K = (\vec e_K^T \vec r_{\omega_K})^{1/2}$.
+///
+/// The fine space is typically obtained by increasing the polynomial degree or refining the
+/// coarse mesh, and the prolongation can be obtained with [`assemble_nested_prolongation`].
+/// Boundary conditions must already be applied to $\vec A_f$ and $\vec b_f$. Degrees of freedom
+/// with homogeneous Dirichlet conditions should have identity rows/columns and zero right-hand side,
+/// so that the correction vanishes there.
+///
+/// Since the patches overlap, $\sum_K \eta_K^2$ overestimates the global estimate
+/// by a bounded factor. The indicators are primarily intended for marking elements for adaptive refinement.
+///
+/// # Errors
+///
+/// Returns an error if one of the local patch problems is singular.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the matrices and vectors are not consistent with the spaces,
+/// or if the length of `fine_to_coarse_element` does not match the number of fine elements.
+pub fn estimate_two_grid_error_indicators<T>(
+    coarse_space: &impl FiniteElementConnectivity,
+    fine_space: &impl FiniteElementConnectivity,
+    fine_to_coarse_element: &[usize],
+    solution_dim: usize,
+    fine_system: TwoGridSystem<T>,
+    u_coarse: DVectorView<T>,
+) -> eyre::Result<Vec<T>>
+where
+    T: Real,
+{
+    let TwoGridSystem {
+        fine_matrix,
+        fine_rhs,
+        prolongation,
+    } = fine_system;
+    let s = solution_dim;
+    let num_fine_dofs = s * fine_space.num_nodes();
+    assert_eq!(fine_to_coarse_element.len(), fine_space.num_elements());
+    assert_eq!(u_coarse.len(), s * coarse_space.num_nodes());
+    assert_eq!(prolongation.nrows(), num_fine_dofs);
+    assert_eq!(prolongation.ncols(), u_coarse.len());
+    assert_eq!(fine_matrix.nrows(), num_fine_dofs);
+    assert_eq!(fine_matrix.ncols(), num_fine_dofs);
+    assert_eq!(fine_rhs.len(), num_fine_dofs);
+
+    let u_fine = prolongation * u_coarse;
+    let defect = fine_rhs - fine_matrix * u_fine;
+
+    let coarse_topology = PatchTopology::from_space(coarse_space);
+    let fine_topology = PatchTopology::from_space(fine_space);
+    let mut coarse_children = vec![Vec::new(); coarse_space.num_elements()];
+    for (fine_element, &parent) in fine_to_coarse_element.iter().enumerate() {
+        coarse_children[parent].push(fine_element);
+    }
+
+    let mut indicators = Vec::with_capacity(coarse_space.num_elements());
+    let mut extractor = DenseSubmatrixExtractor::new();
+    for coarse_element in 0..coarse_space.num_elements() {
+        let coarse_patch = coarse_topology.element_star(coarse_element);
+        let fine_patch = fine_topology.patch(
+            coarse_patch
+                .iter()
+                .flat_map(|&element| coarse_children[element].iter().copied()),
+        );
+
+        let local_dofs = fine_patch.interior_global_dofs(s);
+        let a_local = extractor.extract(fine_matrix, &local_dofs);
+        let r_local = DVector::from_iterator(local_dofs.len(), local_dofs.iter().map(|&dof| defect[dof]));
+
+        let e_local = a_local
+            .lu()
+            .solve(&r_local)
+            .ok_or_else(|| eyre!("Singular local defect problem encountered"))?;
+        // Clamp tiny negative values that may arise from round-off
+        indicators.push(e_local.dot(&r_local).max(T::zero()).sqrt());
+    }
+
+    Ok(indicators)
+}
@@ -0,0 +1,240 @@
+//! Strongly typed indices for nodes, elements, faces and degrees of freedom.
+//!
+//! Most of the library refers to nodes, elements, faces and degrees of freedom (DOFs) by plain
+//! `usize` indices, which makes it easy to accidentally use e.g. a node index where a DOF
+//! index is expected. For vector-valued problems, the two differ by a factor of the solution
+//! dimension, and mixing them up silently produces wrong results rather than errors. The
+//! newtypes in this module make the kind of index part of the type:
+//!
+//! ```
+//! use fenris::index::{DofIndex, NodeIndex};
+//! let node = NodeIndex::new(4);
+//! // The second component of a 3-dimensional field at node 4
+//! let dof = node.dof(3, 1);
+//! assert_eq!(dof, DofIndex::new(13));
+//! assert_eq!(dof.node_and_component(3), (node, 1));
+//! ```
+//!
+//! The connectivity of finite element spaces and element assemblers is expressed in typed
+//! indices (see
+//! [`populate_element_nodes`](crate::space::FiniteElementConnectivity::populate_element_nodes)),
+//! and so are the constrained DOFs of
+//! [`DirichletConditions`](crate::assembly::bc::DirichletConditions). Meshes, quadrature
+//! and the evaluation of basis functions still use plain `usize` indices.
+//! Typed indices deliberately do not index arbitrary slices and vectors, since `values[node]`
+//! would then compile for any kind of index. Use [`TypedIndex::index`] to obtain the raw index
+//! where a plain index is needed. Since typed indices are `#[repr(transparent)]`, slices of
+//! typed indices can also be converted to and from slices of `usize` without copying (see
+//! [`TypedIndex::from_raw_slice`] and [`TypedIndex::as_raw_slice`]).
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Display;
+
+/// Common functionality of typed indices.
+pub trait TypedIndex: Copy + Eq + Ord + From<usize> + Into<usize> {
+    fn new(index: usize) -> Self;
+
+    /// The raw index.
+    fn index(self) -> usize;
+
+    /// Iterates over the first `n` indices.
+    fn range(n: usize) -> TypedIndexRange<Self> {
+        TypedIndexRange {
+            range: 0..n,
+            marker: Default::default(),
+        }
+    }
+
+    /// Reinterprets a slice of raw indices as typed indices.
+    fn from_raw_slice(indices: &[usize]) -> &[Self];
+
+    /// Reinterprets a mutable slice of raw indices as typed indices.
+    fn from_raw_slice_mut(indices: &mut [usize]) -> &mut [Self];
+
+    /// Reinterprets a slice of typed indices as raw indices.
+    fn as_raw_slice(indices: &[Self]) -> &[usize];
+
+    /// Reinterprets a mutable slice of typed indices as raw indices.
+    fn as_raw_slice_mut(indices: &mut [Self]) -> &mut [usize];
+}
+
+/// An iterator over consecutive typed indices, see [`TypedIndex::range`].
+#[derive(Debug, Clone)]
+pub struct TypedIndexRange<I> {
+    range: std::ops::Range<usize>,
+    marker: std::marker::PhantomData<I>,
+}
+
+impl<I: TypedIndex> Iterator for TypedIndexRange<I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<I> {
+        self.range.next().map(I::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<I: TypedIndex> DoubleEndedIterator for TypedIndexRange<I> {
+    fn next_back(&mut self) -> Option<I> {
+        self.range.next_back().map(I::new)
+    }
+}
+
+impl<I: TypedIndex> ExactSizeIterator for TypedIndexRange<I> {}
+
+macro_rules! define_typed_index {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        #[repr(transparent)]
+        pub struct $name(usize);
+
+        impl $name {
+            pub const fn new(index: usize) -> Self {
+                Self(index)
+            }
+
+            /// The raw index.
+            pub const fn index(self) -> usize {
+                self.0
+            }
+
+            /// Reinterprets a slice of raw indices as typed indices.
+            pub fn from_raw_slice(indices: &[usize]) -> &[Self] {
+                // SAFETY: The type is a #[repr(transparent)] wrapper around usize, so it has the
+                // same layout as usize and every usize is a valid value of the type.
+                unsafe { std::slice::from_raw_parts(indices.as_ptr() as *const Self, indices.len()) }
+            }
+
+            /// Reinterprets a mutable slice of raw indices as typed indices.
+            pub fn from_raw_slice_mut(indices: &mut [usize]) -> &mut [Self] {
+                // SAFETY: See from_raw_slice.
+                unsafe { std::slice::from_raw_parts_mut(indices.as_mut_ptr() as *mut Self, indices.len()) }
+            }
+
+            /// Reinterprets a slice of typed indices as raw indices.
+            pub fn as_raw_slice(indices: &[Self]) -> &[usize] {
+                // SAFETY: See from_raw_slice.
+                unsafe { std::slice::from_raw_parts(indices.as_ptr() as *const usize, indices.len()) }
+            }
+
+            /// Reinterprets a mutable slice of typed indices as raw indices.
+            pub fn as_raw_slice_mut(indices: &mut [Self]) -> &mut [usize] {
+                // SAFETY: See from_raw_slice.
+                unsafe { std::slice::from_raw_parts_mut(indices.as_mut_ptr() as *mut usize, indices.len()) }
+            }
+        }
+
+        impl TypedIndex for $name {
+            fn new(index: usize) -> Self {
+                Self(index)
+            }
+
+            fn index(self) -> usize {
+                self.0
+            }
+
+            fn from_raw_slice(indices: &[usize]) -> &[Self] {
+                $name::from_raw_slice(indices)
+            }
+
+            fn from_raw_slice_mut(indices: &mut [usize]) -> &mut [Self] {
+                $name::from_raw_slice_mut(indices)
+            }
+
+            fn as_raw_slice(indices: &[Self]) -> &[usize] {
+                $name::as_raw_slice(indices)
+            }
+
+            fn as_raw_slice_mut(indices: &mut [Self]) -> &mut [usize] {
+                $name::as_raw_slice_mut(indices)
+            }
+        }
+
+        impl From<usize> for $name {
+            fn from(index: usize) -> Self {
+                Self(index)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(index: $name) -> Self {
+                index.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+define_typed_index!(
+    /// The index of a node (or vertex) in a mesh or finite element space.
+    NodeIndex
+);
+define_typed_index!(
+    /// The index of an element (or cell) in a mesh or finite element space.
+    ElementIndex
+);
+define_typed_index!(
+    /// The index of a face in a mesh.
+    FaceIndex
+);
+define_typed_index!(
+    /// The index of a degree of freedom in a global vector or matrix.
+    ///
+    /// For a field with solution dimension $s$, the degrees of freedom of node $i$ are
+    /// $s i, \dots, s i + s - 1$.
+    DofIndex
+);
+
+impl NodeIndex {
+    /// The degree of freedom of the given component of a field with the given solution dimension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is not smaller than the solution dimension.
+    pub fn dof(self, solution_dim: usize, component: usize) -> DofIndex {
+        assert!(
+            component < solution_dim,
+            "Component must be smaller than solution dimension"
+        );
+        DofIndex(solution_dim * self.0 + component)
+    }
+
+    /// The degrees of freedom of all components of a field with the given solution dimension.
+    pub fn dofs(self, solution_dim: usize) -> TypedIndexRange<DofIndex> {
+        TypedIndexRange {
+            range: solution_dim * self.0..solution_dim * (self.0 + 1),
+            marker: Default::default(),
+        }
+    }
+}
+
+impl DofIndex {
+    /// The node and component associated with the degree of freedom for a field with the given
+    /// solution dimension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the solution dimension is zero.
+    pub fn node_and_component(self, solution_dim: usize) -> (NodeIndex, usize) {
+        assert!(solution_dim > 0, "Solution dimension must be positive");
+        (NodeIndex(self.0 / solution_dim), self.0 % solution_dim)
+    }
+}
+
+/// The degrees of freedom of all components of the given nodes, in order.
+pub fn node_dofs(nodes: &[NodeIndex], solution_dim: usize) -> Vec<DofIndex> {
+    nodes
+        .iter()
+        .flat_map(|node| node.dofs(solution_dim))
+        .collect()
+}
@@ -4,6 +4,7 @@ use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
 use crate::assembly::global::gather_global_to_local;
 use crate::assembly::local::{ElementConnectivityAssembler, ElementScalarAssembler, QuadratureTable};
 use crate::element::{FiniteElement, VolumetricFiniteElement};
+use crate::index::{ElementIndex, NodeIndex};
 use crate::nalgebra::{DVector, DefaultAllocator, DimName, OMatrix, OPoint, Scalar, U1};
 use crate::quadrature::Quadrature;
 use crate::space::{ElementInSpace, FiniteElementSpace, VolumetricFiniteElementSpace};
@@ -861,11 +862,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(output, element_index)
     }
 }
@@ -912,7 +913,7 @@ where
         TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim> + DimAllocator<T, F::OutputDim>,
 {
     fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
-        let n = self.element_node_count(element_index);
+        let n = self.element_node_count(element_index.into());
         let s = self.solution_dim();
         let element_ndof = n * s;
         let integral = with_thread_local_workspace(
@@ -925,7 +926,10 @@ where
                     .local_interpolation_weights
                     .resize_vertically_mut(element_ndof, T::zero());
                 workspace.nodes.resize(n, usize::MAX);
-                self.populate_element_nodes(&mut workspace.nodes, element_index);
+                self.populate_element_nodes(
+                    NodeIndex::from_raw_slice_mut(&mut workspace.nodes),
+                    element_index.into(),
+                );
                 let u_local = &mut workspace.local_interpolation_weights;
                 let quadrature = workspace.quadrature_buffer.weights_and_points();
                 gather_global_to_local(&self.u, &mut *u_local, &workspace.nodes, s);
@@ -968,11 +972,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(output, element_index)
     }
 }
@@ -990,7 +994,7 @@ where
         TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim> + DimAllocator<T, F::OutputDim>,
 {
     fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
-        let n = self.element_node_count(element_index);
+        let n = self.element_node_count(element_index.into());
         let s = self.solution_dim();
         let element_ndof = n * s;
         let integral = with_thread_local_workspace(
@@ -1003,7 +1007,10 @@ where
                     .local_interpolation_weights
                     .resize_vertically_mut(element_ndof, T::zero());
                 workspace.nodes.resize(n, usize::MAX);
-                self.populate_element_nodes(&mut workspace.nodes, element_index);
+                self.populate_element_nodes(
+                    NodeIndex::from_raw_slice_mut(&mut workspace.nodes),
+                    element_index.into(),
+                );
                 let u_local = &mut workspace.local_interpolation_weights;
                 let quadrature = workspace.quadrature_buffer.weights_and_points();
                 gather_global_to_local(&self.u, &mut *u_local, &workspace.nodes, s);
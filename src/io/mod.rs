@@ -1,2 +1,14 @@
 pub mod msh;
+pub mod partitioned;
+pub mod quadrature;
 pub mod vtk;
+pub mod xdmf;
+
+pub use partitioned::PartitionedFieldExporter;
+pub use quadrature::QuadratureTableFile;
+pub use vtk::FieldExporter;
+
+/// Alias for [`msh`], the Gmsh MSH file format support.
+pub mod gmsh {
+    pub use super::msh::*;
+}
@@ -2,7 +2,10 @@
 //!
 //! This module supports Gmsh MSH files with the file format version 4.1 as specified in the
 //! [Gmsh documentation](https://gmsh.info/doc/texinfo/gmsh.html#MSH-file-format). All element types
-//! that implement the [`MshConnectivity`] trait are supported.
+//! that implement the [`MshConnectivity`] trait are supported. Both ASCII and binary files can be loaded.
+//!
+//! Physical groups assigned in Gmsh can be retrieved as per-cell labels with
+//! [`load_msh_with_physical_groups_from_file`], see [`MshMesh`].
 //!
 //! Example usage:
 //! ```
@@ -18,8 +21,8 @@
 //! ```
 
 use crate::connectivity::{
-    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
 };
 use crate::mesh::Mesh;
 use eyre::{eyre, Context};
@@ -42,6 +45,89 @@ where
 
 /// Loads a [`Mesh`] by parsing the given bytes as a Gmsh MSH file.
 pub fn load_msh_from_bytes<T, D, C>(bytes: &[u8]) -> eyre::Result<Mesh<T, D, C>>
+where
+    T: RealField,
+    D: DimName,
+    C: MshConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    Ok(load_msh_with_physical_groups_from_bytes(bytes)?.mesh)
+}
+
+/// A physical group name as declared in the `$PhysicalNames` section of a MSH file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MshPhysicalName {
+    /// The dimension of the physical group.
+    pub dim: usize,
+    /// The tag of the physical group.
+    pub tag: i32,
+    /// The name of the physical group.
+    pub name: String,
+}
+
+/// A [`Mesh`] loaded from a MSH file together with physical group information.
+#[derive(Debug, Clone)]
+pub struct MshMesh<T, D, C>
+where
+    T: RealField,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// The mesh.
+    pub mesh: Mesh<T, D, C>,
+    /// The physical group tag associated with each cell in the mesh.
+    ///
+    /// The physical tag of a cell is determined by the geometrical entity that the cell belongs to.
+    /// If the entity does not belong to a physical group, the label is `None`. If the entity
+    /// belongs to several physical groups, only the first tag is used.
+    pub cell_labels: Vec<Option<i32>>,
+    /// The names of physical groups, if the MSH file contains a `$PhysicalNames` section.
+    pub physical_names: Vec<MshPhysicalName>,
+}
+
+impl<T, D, C> MshMesh<T, D, C>
+where
+    T: RealField,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Returns the tag of the physical group with the given name and dimension, if it exists.
+    pub fn physical_tag_by_name(&self, dim: usize, name: &str) -> Option<i32> {
+        self.physical_names
+            .iter()
+            .find(|physical_name| physical_name.dim == dim && physical_name.name == name)
+            .map(|physical_name| physical_name.tag)
+    }
+
+    /// Returns the indices of all cells labeled with the given physical tag.
+    pub fn cells_with_label(&self, tag: i32) -> Vec<usize> {
+        self.cell_labels
+            .iter()
+            .enumerate()
+            .filter(|(_, &label)| label == Some(tag))
+            .map(|(cell_index, _)| cell_index)
+            .collect()
+    }
+}
+
+/// Loads a [`Mesh`] with physical group labels from a Gmsh MSH file at the given path.
+///
+/// See [`MshMesh`] for the information that is extracted in addition to the mesh itself.
+pub fn load_msh_with_physical_groups_from_file<T, D, C, P: AsRef<Path>>(file_path: P) -> eyre::Result<MshMesh<T, D, C>>
+where
+    T: RealField,
+    D: DimName,
+    C: MshConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let msh_bytes = std::fs::read(file_path).wrap_err("failed to read file")?;
+    load_msh_with_physical_groups_from_bytes(&msh_bytes).wrap_err("failed to load mesh from msh file")
+}
+
+/// Loads a [`Mesh`] with physical group labels by parsing the given bytes as a Gmsh MSH file.
+///
+/// See [`MshMesh`] for the information that is extracted in addition to the mesh itself.
+pub fn load_msh_with_physical_groups_from_bytes<T, D, C>(bytes: &[u8]) -> eyre::Result<MshMesh<T, D, C>>
 where
     T: RealField,
     D: DimName,
@@ -60,9 +146,11 @@ where
         .elements
         .take()
         .ok_or(eyre!("MSH file does not contain elements"))?;
+    let msh_entities = msh_file.data.entities.take();
 
     let mut vertices = Vec::new();
     let mut connectivity = Vec::new();
+    let mut cell_labels = Vec::new();
 
     // Ensure that at least one element block matches the target mesh connectivity
     if !msh_elements
@@ -98,11 +186,110 @@ where
 
     // Collect all connectivity matching the target connectivity
     for element_block in &msh_elements.element_blocks {
-        let block_connectivity = connectivity_from_element_block(element_block)?;
+        let block_connectivity: Vec<C> = connectivity_from_element_block(element_block)?;
+        let label = entity_physical_tag(
+            msh_entities.as_ref(),
+            element_block.entity_dim,
+            element_block.entity_tag,
+        );
+        cell_labels.resize(cell_labels.len() + block_connectivity.len(), label);
         connectivity.extend(block_connectivity);
     }
 
-    Ok(Mesh::from_vertices_and_connectivity(vertices, connectivity))
+    Ok(MshMesh {
+        mesh: Mesh::from_vertices_and_connectivity(vertices, connectivity),
+        cell_labels,
+        physical_names: parse_physical_names(bytes)?,
+    })
+}
+
+/// Returns the first physical tag of the entity with the given dimension and tag, if any.
+fn entity_physical_tag(entities: Option<&mshio::Entities<i32, f64>>, entity_dim: i32, entity_tag: i32) -> Option<i32> {
+    let entities = entities?;
+    let physical_tags = match entity_dim {
+        0 => entities
+            .points
+            .iter()
+            .find(|point| point.tag == entity_tag)
+            .map(|point| &point.physical_tags),
+        1 => entities
+            .curves
+            .iter()
+            .find(|curve| curve.tag == entity_tag)
+            .map(|curve| &curve.physical_tags),
+        2 => entities
+            .surfaces
+            .iter()
+            .find(|surface| surface.tag == entity_tag)
+            .map(|surface| &surface.physical_tags),
+        3 => entities
+            .volumes
+            .iter()
+            .find(|volume| volume.tag == entity_tag)
+            .map(|volume| &volume.physical_tags),
+        _ => None,
+    }?;
+    physical_tags.first().copied()
+}
+
+/// Parses the `$PhysicalNames` section of a MSH file, which is not handled by `mshio`.
+///
+/// The section is always stored as ASCII text, even in binary MSH files. Returns an empty
+/// vector if the file does not contain the section.
+fn parse_physical_names(bytes: &[u8]) -> eyre::Result<Vec<MshPhysicalName>> {
+    let begin_marker = b"$PhysicalNames";
+    let end_marker = b"$EndPhysicalNames";
+    let find = |haystack: &[u8], needle: &[u8]| {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    };
+
+    let begin = match find(bytes, begin_marker) {
+        Some(begin) => begin + begin_marker.len(),
+        None => return Ok(Vec::new()),
+    };
+    let end =
+        begin + find(&bytes[begin..], end_marker).ok_or_else(|| eyre!("$PhysicalNames section is not terminated"))?;
+    let section = std::str::from_utf8(&bytes[begin..end]).wrap_err("$PhysicalNames section is not valid UTF-8")?;
+
+    let mut lines = section
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let num_names: usize = lines
+        .next()
+        .ok_or_else(|| eyre!("$PhysicalNames section is empty"))?
+        .parse()
+        .wrap_err("failed to parse number of physical names")?;
+
+    let mut physical_names = Vec::with_capacity(num_names);
+    for line in lines.take(num_names) {
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let mut next_part = || {
+            parts
+                .next()
+                .ok_or_else(|| eyre!("invalid physical name entry: {}", line))
+        };
+        let dim = next_part()?
+            .parse()
+            .wrap_err("failed to parse physical group dimension")?;
+        let tag = next_part()?
+            .parse()
+            .wrap_err("failed to parse physical group tag")?;
+        let name = next_part()?.trim().trim_matches('"').to_string();
+        physical_names.push(MshPhysicalName { dim, tag, name });
+    }
+
+    if physical_names.len() != num_names {
+        return Err(eyre!(
+            "$PhysicalNames section claims to contain {} names, but only {} were found",
+            num_names,
+            physical_names.len()
+        ));
+    }
+
+    Ok(physical_names)
 }
 
 /// Tries to convert a `mshio::NodeBlock` to a `Vec<OPoint<T, D>>`.
@@ -280,6 +467,7 @@ impl_msh_connectivity!(Quad9d2Connectivity, Qua9, num_nodes = 9);
 impl_msh_connectivity!(Tet4Connectivity, Tet4, num_nodes = 4);
 impl_msh_connectivity!(Tet10Connectivity, Tet10, num_nodes = 10);
 impl_msh_connectivity!(Hex8Connectivity, Hex8, num_nodes = 8);
+impl_msh_connectivity!(Hex20Connectivity, Hex20, num_nodes = 20);
 impl_msh_connectivity!(Hex27Connectivity, Hex27, num_nodes = 27);
 
 // The following connectivities do not implement ElementConnectivity yet
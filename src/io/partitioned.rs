@@ -0,0 +1,320 @@
+//! Output of partitioned meshes with one file per partition.
+//!
+//! Writing a single file for a large mesh is inherently sequential. When the elements of a mesh
+//! have been partitioned, for example with
+//! [`partition_elements_by_coordinate_bisection`](crate::mesh::partition::partition_elements_by_coordinate_bisection),
+//! [`PartitionedFieldExporter`] instead writes one piece per partition concurrently, together
+//! with a small index file that allows ParaView (and other readers) to load all pieces as a
+//! single data set. Both parallel VTU (`.pvtu`) and partitioned XDMF (`.xmf`) output are
+//! supported.
+//!
+//! For distributed computations, in which each process only holds its own part of the mesh,
+//! each process can instead write its piece with [`write_vtu`] or [`write_xdmf`], while a
+//! single process writes the index file with [`write_pvtu_index`] or [`write_xdmf_index`].
+use crate::connectivity::ConnectivityMut;
+use crate::io::vtk::{
+    write_pvtu_index, write_vtu, FiniteElementMeshDataSetBuilder, VtkCellConnectivity, VtuCompression,
+};
+use crate::io::xdmf::{write_xdmf, write_xdmf_index};
+use crate::mesh::Mesh;
+use crate::vtkio::model::{Attribute, Attributes, DataArray, DataSet};
+use crate::vtkio::IOBuffer;
+use crate::Real;
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, Scalar};
+use num::ToPrimitive;
+use rayon::prelude::*;
+use std::path::Path;
+
+/// Gathers the values of a field associated with the given (sorted) vertices or cells.
+type GatherFn<'a> = Box<dyn Fn(&[usize]) -> IOBuffer + 'a>;
+
+struct PartitionedField<'a> {
+    name: String,
+    num_components: usize,
+    gather: GatherFn<'a>,
+}
+
+impl<'a> PartitionedField<'a> {
+    fn new<S>(name: String, values: &'a [S], num_components: usize) -> Self
+    where
+        S: Scalar + ToPrimitive,
+    {
+        let gather = move |indices: &[usize]| -> IOBuffer {
+            indices
+                .iter()
+                .flat_map(|&i| {
+                    values[num_components * i..num_components * (i + 1)]
+                        .iter()
+                        .cloned()
+                })
+                .collect::<Vec<S>>()
+                .into()
+        };
+        Self {
+            name,
+            num_components,
+            gather: Box::new(gather),
+        }
+    }
+
+    fn gather(&self, indices: &[usize]) -> Attribute {
+        let num_components = self
+            .num_components
+            .try_into()
+            .expect("Number of components is ridiculously huge, stop it!");
+        Attribute::DataArray(DataArray::scalars(self.name.clone(), num_components).with_data((self.gather)(indices)))
+    }
+}
+
+/// Builder for exporting a partitioned mesh together with nodal and cell fields, with one
+/// file per partition.
+///
+/// The partition is given as the index of the partition of each element. Each non-empty
+/// partition is written as a separate piece, consisting of the elements in the partition and
+/// the vertices they reference, with the file name of the index file suffixed by the partition
+/// index. Vertices shared between partitions are duplicated, so that each piece is
+/// self-contained. Empty partitions are skipped. The pieces are written to file in parallel.
+///
+/// As with [`FieldExporter`](crate::io::FieldExporter), inconsistent fields are reported as
+/// errors when writing.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use fenris::io::PartitionedFieldExporter;
+/// # use fenris::mesh::partition::partition_elements_by_coordinate_bisection;
+/// # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+/// # use fenris::mesh::QuadMesh2d;
+/// # fn main() -> eyre::Result<()> {
+/// let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(16);
+/// let partition = partition_elements_by_coordinate_bisection(&mesh, 4);
+/// let u = vec![0.0; 2 * mesh.vertices().len()];
+/// // Writes output/solution.pvtu and the pieces output/solution_0.vtu, ..., output/solution_3.vtu
+/// PartitionedFieldExporter::new(&mesh, &partition)
+///     .with_point_field("displacement", &u, 2)
+///     .write_pvtu("output/solution.pvtu")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PartitionedFieldExporter<'a, T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    mesh: &'a Mesh<T, D, C>,
+    element_partition: &'a [usize],
+    point_fields: Vec<PartitionedField<'a>>,
+    cell_fields: Vec<PartitionedField<'a>>,
+    compression: VtuCompression,
+    error: Option<eyre::Report>,
+}
+
+impl<'a, T, D, C> PartitionedFieldExporter<'a, T, D, C>
+where
+    T: Real + ToPrimitive,
+    D: DimName,
+    C: ConnectivityMut + VtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Creates an exporter for the given mesh, where `element_partition[i]` is the index of
+    /// the partition that contains element `i`.
+    pub fn new(mesh: &'a Mesh<T, D, C>, element_partition: &'a [usize]) -> Self {
+        let num_cells = mesh.connectivity().len();
+        let error = (element_partition.len() != num_cells).then(|| {
+            eyre!(
+                "Element partition has {} entries, but the mesh has {num_cells} cells",
+                element_partition.len()
+            )
+        });
+        Self {
+            mesh,
+            element_partition,
+            point_fields: Vec::new(),
+            cell_fields: Vec::new(),
+            compression: VtuCompression::default(),
+            error,
+        }
+    }
+
+    /// Sets the compression used when exporting to VTU files.
+    pub fn with_compression(self, compression: VtuCompression) -> Self {
+        Self { compression, ..self }
+    }
+
+    /// Attaches a nodal field with the given number of components per vertex.
+    ///
+    /// The values are expected to be stored vertex by vertex, as in
+    /// [`FieldExporter::with_point_field`](crate::io::FieldExporter::with_point_field).
+    pub fn with_point_field<S: Scalar + ToPrimitive>(
+        mut self,
+        name: impl Into<String>,
+        values: &'a [S],
+        num_components: usize,
+    ) -> Self {
+        let name = name.into();
+        let num_points = self.mesh.vertices().len();
+        if num_components == 0 || values.len() != num_components * num_points {
+            let error = eyre!(
+                "Point field {name} has {} entries, but the mesh has {num_points} vertices \
+                 and the field has {num_components} components",
+                values.len()
+            );
+            return self.with_error(error);
+        }
+        self.point_fields
+            .push(PartitionedField::new(name, values, num_components));
+        self
+    }
+
+    /// Attaches a cell field.
+    ///
+    /// The number of components per cell is inferred from the number of values, as in
+    /// [`FieldExporter::with_cell_field`](crate::io::FieldExporter::with_cell_field).
+    pub fn with_cell_field<S: Scalar + ToPrimitive>(mut self, name: impl Into<String>, values: &'a [S]) -> Self {
+        let name = name.into();
+        let num_cells = self.mesh.connectivity().len();
+        if num_cells == 0 || values.is_empty() || !values.len().is_multiple_of(num_cells) {
+            let error = eyre!(
+                "Cell field {name} has {} entries, which is not a positive multiple \
+                 of the number of cells ({num_cells}) in the mesh",
+                values.len()
+            );
+            return self.with_error(error);
+        }
+        let num_components = values.len() / num_cells;
+        self.cell_fields
+            .push(PartitionedField::new(name, values, num_components));
+        self
+    }
+
+    fn with_error(self, error: eyre::Report) -> Self {
+        Self {
+            error: self.error.or(Some(error)),
+            ..self
+        }
+    }
+
+    fn check(&self) -> eyre::Result<()> {
+        match &self.error {
+            Some(error) => Err(eyre!("Invalid field for export: {error}")),
+            None => Ok(()),
+        }
+    }
+
+    /// The number of partitions, i.e. one more than the largest partition index.
+    pub fn num_partitions(&self) -> usize {
+        self.element_partition
+            .iter()
+            .max()
+            .map(|&max| max + 1)
+            .unwrap_or(0)
+    }
+
+    /// Builds the VTK data set of each non-empty partition, together with the partition index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the partition or any of the attached fields is inconsistent with
+    /// the mesh.
+    pub fn try_build_pieces(&self) -> eyre::Result<Vec<(usize, DataSet)>> {
+        self.check()?;
+        let mut partition_elements = vec![Vec::new(); self.num_partitions()];
+        for (element, &part) in self.element_partition.iter().enumerate() {
+            partition_elements[part].push(element);
+        }
+        partition_elements
+            .into_iter()
+            .enumerate()
+            .filter(|(_, elements)| !elements.is_empty())
+            .map(|(part, elements)| Ok((part, self.build_piece(&elements)?)))
+            .collect()
+    }
+
+    fn build_piece(&self, elements: &[usize]) -> eyre::Result<DataSet> {
+        // Mesh::keep_cells retains the referenced vertices in their original order
+        let mut vertices: Vec<usize> = elements
+            .iter()
+            .flat_map(|&element| self.mesh.connectivity()[element].vertex_indices())
+            .copied()
+            .collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+
+        let attributes = Attributes {
+            point: self
+                .point_fields
+                .iter()
+                .map(|field| field.gather(&vertices))
+                .collect(),
+            cell: self
+                .cell_fields
+                .iter()
+                .map(|field| field.gather(elements))
+                .collect(),
+        };
+        let submesh = self.mesh.keep_cells(elements);
+        FiniteElementMeshDataSetBuilder::from_mesh(&submesh)
+            .with_attributes(attributes)
+            .try_build()
+    }
+
+    /// Writes the pieces with the given writer in parallel and returns their file names.
+    fn write_pieces(
+        &self,
+        index_path: &Path,
+        extension: &str,
+        write_piece: impl Fn(&Path, &DataSet) -> eyre::Result<()> + Sync,
+    ) -> eyre::Result<(Vec<String>, DataSet)> {
+        let stem = index_path
+            .file_stem()
+            .ok_or_else(|| eyre!("Invalid index file path {}", index_path.display()))?
+            .to_string_lossy()
+            .into_owned();
+        let directory = index_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut pieces = self.try_build_pieces()?;
+        let sources: Vec<String> = pieces
+            .iter()
+            .map(|(part, _)| format!("{stem}_{part}.{extension}"))
+            .collect();
+        pieces
+            .par_iter()
+            .zip(&sources)
+            .try_for_each(|((_, dataset), source)| write_piece(&directory.join(source), dataset))?;
+        let (_, first_piece) = pieces
+            .drain(..)
+            .next()
+            .ok_or_else(|| eyre!("Cannot export a mesh without cells"))?;
+        Ok((sources, first_piece))
+    }
+
+    /// Writes one VTU file per partition with [`write_vtu`] and a PVTU index file at the given
+    /// path with [`write_pvtu_index`].
+    ///
+    /// For an index file `dir/name.pvtu`, the piece of partition `i` is written to
+    /// `dir/name_i.vtu`.
+    pub fn write_pvtu(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let compression = self.compression;
+        let (sources, first_piece) = self.write_pieces(path, "vtu", |piece_path, dataset| {
+            write_vtu(piece_path, dataset, compression)
+        })?;
+        write_pvtu_index(path, &sources, &first_piece)
+    }
+
+    /// Writes one XDMF file per partition with [`write_xdmf`] and an XDMF index file at the
+    /// given path with [`write_xdmf_index`].
+    ///
+    /// For an index file `dir/name.xmf`, the piece of partition `i` is written to
+    /// `dir/name_i.xmf`, with its array data stored in `dir/name_i.bin`.
+    pub fn write_xdmf(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let (sources, _) = self.write_pieces(path, "xmf", |piece_path, dataset| write_xdmf(piece_path, dataset))?;
+        write_xdmf_index(path, &sources)
+    }
+}
@@ -0,0 +1,419 @@
+//! Import and export of quadrature tables.
+//!
+//! Quadrature rules are often generated by external tools, for example by cut-cell libraries or
+//! by moment fitting in other languages. [`QuadratureTableFile`] is a simple, language-agnostic
+//! representation of a [quadrature table](crate::assembly::local::QuadratureTable) that can be
+//! stored either as JSON or in a compact binary format, and converted into a
+//! [`UniformQuadratureTable`] or a [`CompactQuadratureTable`] for use with assemblers.
+//!
+//! A table consists of a number of quadrature rules in reference coordinates and, optionally,
+//! the index of the rule to use for each element. If the element map is absent, the table is
+//! *uniform*: it has exactly one rule, which is used for every element. In JSON, a table with two
+//! rules for a mesh with three elements reads
+//!
+//! ```json
+//! {
+//!   "dim": 2,
+//!   "rules": [
+//!     { "points": [[0.0, 0.0]], "weights": [4.0] },
+//!     { "points": [[-0.5, 0.0], [0.5, 0.0]], "weights": [2.0, 2.0] }
+//!   ],
+//!   "element_rules": [0, 1, 0]
+//! }
+//! ```
+//!
+//! The binary format stores the same information with all values in little-endian byte order:
+//!
+//! 1. the magic bytes `FENRISQT`, followed by the format version `1` as `u32`,
+//! 2. the dimension as `u32` and the number of rules as `u64`,
+//! 3. for each rule, the number of points as `u64`, the coordinates of all points as `f64`
+//!    (point by point), and the weights as `f64`,
+//! 4. a `u8` flag which is `0` for a uniform table and `1` otherwise, in which case it is
+//!    followed by the number of elements as `u64` and the rule index of each element as `u64`.
+//!
+//! Values are always stored in double precision, regardless of the scalar type of the table.
+use crate::assembly::local::{CompactQuadratureTable, QuadratureTable, UniformQuadratureTable};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DefaultAllocator, DimName, OPoint};
+use crate::util::NestedVec;
+use crate::{Real, SmallDim};
+use eyre::{eyre, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const BINARY_MAGIC: &[u8; 8] = b"FENRISQT";
+const BINARY_VERSION: u32 = 1;
+
+/// A quadrature rule as stored in a [`QuadratureTableFile`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuadratureRuleRecord {
+    /// The quadrature points in reference coordinates.
+    pub points: Vec<Vec<f64>>,
+    pub weights: Vec<f64>,
+}
+
+/// A serializable quadrature table, see the [module documentation](self) for the format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuadratureTableFile {
+    /// The dimension of the reference domain.
+    pub dim: usize,
+    pub rules: Vec<QuadratureRuleRecord>,
+    /// The index of the rule associated with each element, or `None` for a uniform table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_rules: Option<Vec<usize>>,
+}
+
+impl QuadratureTableFile {
+    /// Constructs a uniform table from the rule that the given table associates with the first
+    /// element.
+    ///
+    /// This is primarily intended for exporting a [`UniformQuadratureTable`].
+    pub fn uniform_from_table<T, D>(table: &impl QuadratureTable<T, D>) -> Self
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        Self {
+            dim: D::dim(),
+            rules: vec![extract_element_rule(table, 0)],
+            element_rules: None,
+        }
+    }
+
+    /// Constructs a table with a separate rule for each of the first `num_elements` elements of
+    /// the given table.
+    pub fn per_element_from_table<T, D>(table: &impl QuadratureTable<T, D>, num_elements: usize) -> Self
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        Self {
+            dim: D::dim(),
+            rules: (0..num_elements)
+                .map(|element_index| extract_element_rule(table, element_index))
+                .collect(),
+            element_rules: Some((0..num_elements).collect()),
+        }
+    }
+
+    /// Whether the table uses the same rule for every element.
+    pub fn is_uniform(&self) -> bool {
+        self.element_rules.is_none()
+    }
+
+    /// Checks that the table is consistent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimension is zero, if a point does not have `dim` coordinates, if a
+    /// rule has a different number of points and weights, if a uniform table does not have
+    /// exactly one rule, or if an element refers to a rule that does not exist.
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.dim == 0 {
+            return Err(eyre!("Quadrature table dimension must be positive"));
+        }
+        for (rule_index, rule) in self.rules.iter().enumerate() {
+            if rule.points.len() != rule.weights.len() {
+                return Err(eyre!(
+                    "Quadrature rule {rule_index} has {} points, but {} weights",
+                    rule.points.len(),
+                    rule.weights.len()
+                ));
+            }
+            if let Some(point) = rule.points.iter().find(|point| point.len() != self.dim) {
+                return Err(eyre!(
+                    "Quadrature rule {rule_index} contains a point with {} coordinates in a table of dimension {}",
+                    point.len(),
+                    self.dim
+                ));
+            }
+        }
+        match &self.element_rules {
+            None if self.rules.len() != 1 => Err(eyre!(
+                "Uniform quadrature table must have exactly one rule, but has {}",
+                self.rules.len()
+            )),
+            None => Ok(()),
+            Some(element_rules) => match element_rules
+                .iter()
+                .position(|&rule_index| rule_index >= self.rules.len())
+            {
+                Some(element_index) => Err(eyre!(
+                    "Element {element_index} refers to quadrature rule {}, but the table only has {} rules",
+                    element_rules[element_index],
+                    self.rules.len()
+                )),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Converts a uniform table into a [`UniformQuadratureTable`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table is invalid, is not uniform, or if its dimension is not `D`.
+    pub fn to_uniform_table<T, D>(&self) -> eyre::Result<UniformQuadratureTable<T, D>>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        self.validate_for_dim(D::dim())?;
+        if !self.is_uniform() {
+            return Err(eyre!(
+                "Cannot convert a quadrature table with per-element rules to a uniform table"
+            ));
+        }
+        let rule = &self.rules[0];
+        Ok(UniformQuadratureTable::from_points_and_weights(
+            rule.points
+                .iter()
+                .map(|point| convert_point(point))
+                .collect(),
+            rule.weights
+                .iter()
+                .map(|&w| T::from_f64(w).unwrap())
+                .collect(),
+        ))
+    }
+
+    /// Converts the table into a [`CompactQuadratureTable`] for `num_elements` elements.
+    ///
+    /// A uniform table associates its rule with all elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table is invalid, if its dimension is not `D`, or if it has
+    /// per-element rules for a number of elements different from `num_elements`.
+    pub fn to_compact_table<T, D>(&self, num_elements: usize) -> eyre::Result<CompactQuadratureTable<T, D>>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        self.validate_for_dim(D::dim())?;
+        let element_to_rule_map = match &self.element_rules {
+            None => vec![0; num_elements],
+            Some(element_rules) if element_rules.len() == num_elements => element_rules.clone(),
+            Some(element_rules) => {
+                return Err(eyre!(
+                    "Quadrature table has rules for {} elements, but {num_elements} elements were expected",
+                    element_rules.len()
+                ))
+            }
+        };
+        let mut points = NestedVec::new();
+        let mut weights = NestedVec::new();
+        for rule in &self.rules {
+            let rule_points: Vec<OPoint<T, D>> = rule
+                .points
+                .iter()
+                .map(|point| convert_point(point))
+                .collect();
+            let rule_weights: Vec<T> = rule
+                .weights
+                .iter()
+                .map(|&w| T::from_f64(w).unwrap())
+                .collect();
+            points.push(&rule_points);
+            weights.push(&rule_weights);
+        }
+        Ok(CompactQuadratureTable::from_points_weights_and_map(
+            points,
+            weights,
+            element_to_rule_map,
+        ))
+    }
+
+    /// Reads a table in JSON format and validates it.
+    pub fn read_json(reader: impl Read) -> eyre::Result<Self> {
+        let table: Self = serde_json::from_reader(reader).wrap_err("Failed to parse quadrature table JSON")?;
+        table.validate()?;
+        Ok(table)
+    }
+
+    /// Validates the table and writes it in JSON format.
+    pub fn write_json(&self, writer: impl Write) -> eyre::Result<()> {
+        self.validate()?;
+        serde_json::to_writer_pretty(writer, self).wrap_err("Failed to write quadrature table JSON")
+    }
+
+    /// Reads a table in binary format and validates it.
+    pub fn read_binary(mut reader: impl Read) -> eyre::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BINARY_MAGIC {
+            return Err(eyre!("Not a binary quadrature table: invalid magic bytes"));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != BINARY_VERSION {
+            return Err(eyre!("Unsupported binary quadrature table version {version}"));
+        }
+        let dim = read_u32(&mut reader)? as usize;
+        let num_rules = read_u64(&mut reader)?;
+        let mut rules = Vec::new();
+        for _ in 0..num_rules {
+            let num_points = read_u64(&mut reader)?;
+            let mut points = Vec::new();
+            for _ in 0..num_points {
+                let point = (0..dim)
+                    .map(|_| read_f64(&mut reader))
+                    .collect::<eyre::Result<_>>()?;
+                points.push(point);
+            }
+            let weights = (0..num_points)
+                .map(|_| read_f64(&mut reader))
+                .collect::<eyre::Result<_>>()?;
+            rules.push(QuadratureRuleRecord { points, weights });
+        }
+        let mut flag = [0u8];
+        reader.read_exact(&mut flag)?;
+        let element_rules = match flag[0] {
+            0 => None,
+            1 => {
+                let num_elements = read_u64(&mut reader)?;
+                let element_rules = (0..num_elements)
+                    .map(|_| read_u64(&mut reader).map(|rule_index| rule_index as usize))
+                    .collect::<eyre::Result<_>>()?;
+                Some(element_rules)
+            }
+            flag => return Err(eyre!("Invalid element map flag {flag} in binary quadrature table")),
+        };
+        let table = Self {
+            dim,
+            rules,
+            element_rules,
+        };
+        table.validate()?;
+        Ok(table)
+    }
+
+    /// Validates the table and writes it in binary format.
+    pub fn write_binary(&self, mut writer: impl Write) -> eyre::Result<()> {
+        self.validate()?;
+        writer.write_all(BINARY_MAGIC)?;
+        writer.write_all(&BINARY_VERSION.to_le_bytes())?;
+        writer.write_all(&u32::try_from(self.dim)?.to_le_bytes())?;
+        writer.write_all(&(self.rules.len() as u64).to_le_bytes())?;
+        for rule in &self.rules {
+            writer.write_all(&(rule.points.len() as u64).to_le_bytes())?;
+            for &x in rule.points.iter().flatten() {
+                writer.write_all(&x.to_le_bytes())?;
+            }
+            for &w in &rule.weights {
+                writer.write_all(&w.to_le_bytes())?;
+            }
+        }
+        match &self.element_rules {
+            None => writer.write_all(&[0])?,
+            Some(element_rules) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&(element_rules.len() as u64).to_le_bytes())?;
+                for &rule_index in element_rules {
+                    writer.write_all(&(rule_index as u64).to_le_bytes())?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a table from the given file, which is read as JSON if it has the extension `.json`
+    /// and in binary format otherwise.
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+        let reader = BufReader::new(file);
+        if is_json_path(path) {
+            Self::read_json(reader)
+        } else {
+            Self::read_binary(reader)
+        }
+        .wrap_err_with(|| format!("Failed to load quadrature table from {}", path.display()))
+    }
+
+    /// Saves the table to the given file, which is written as JSON if it has the extension
+    /// `.json` and in binary format otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).wrap_err_with(|| format!("Failed to create {}", path.display()))?;
+        let writer = BufWriter::new(file);
+        if is_json_path(path) {
+            self.write_json(writer)
+        } else {
+            self.write_binary(writer)
+        }
+        .wrap_err_with(|| format!("Failed to save quadrature table to {}", path.display()))
+    }
+
+    fn validate_for_dim(&self, dim: usize) -> eyre::Result<()> {
+        if self.dim != dim {
+            return Err(eyre!(
+                "Quadrature table has dimension {}, but dimension {dim} was expected",
+                self.dim
+            ));
+        }
+        self.validate()
+    }
+}
+
+fn extract_element_rule<T, D>(table: &impl QuadratureTable<T, D>, element_index: usize) -> QuadratureRuleRecord
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let n = table.element_quadrature_size(element_index);
+    let mut points = vec![OPoint::<T, D>::origin(); n];
+    let mut weights = vec![T::zero(); n];
+    table.populate_element_quadrature(element_index, &mut points, &mut weights);
+    QuadratureRuleRecord {
+        points: points
+            .iter()
+            .map(|point| point.iter().map(|x| x.to_subset().unwrap()).collect())
+            .collect(),
+        weights: weights.iter().map(|w| w.to_subset().unwrap()).collect(),
+    }
+}
+
+fn convert_point<T, D>(coords: &[f64]) -> OPoint<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    OPoint::from_slice(
+        &coords
+            .iter()
+            .map(|&x| T::from_f64(x).unwrap())
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+fn read_u32(reader: &mut impl Read) -> eyre::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> eyre::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64(reader: &mut impl Read) -> eyre::Result<f64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
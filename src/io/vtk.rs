@@ -5,8 +5,8 @@ use vtkio::model::{Attribute, CellType, Cells, DataSet, UnstructuredGridPiece, V
 
 use crate::connectivity::{
     Connectivity, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
-    Segment2d2Connectivity, Segment2d3Connectivity, Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity,
-    Tri3d3Connectivity, Tri6d2Connectivity,
+    Segment2d2Connectivity, Segment2d3Connectivity, Segment3d2Connectivity, Tet10Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity, Tri6d3Connectivity,
 };
 
 use nalgebra::allocator::Allocator;
@@ -16,10 +16,15 @@ use std::convert::TryInto;
 // TODO: This is kind of a dirty hack to get around the fact that some VTK things are in
 // the geometry crate and some are in this crate. Need to clean this up!
 use crate::vtkio::model::{Attributes, ByteOrder, DataArray, Piece, Version, Vtk};
+use crate::vtkio::xml::Compressor;
+use crate::vtkio::IOBuffer;
 // TODO: We've currently disabled all vtkio impls, might have to re-enable/re-implement some of them in the future
 //pub use fenris_geometry::vtkio::*;
+use eyre::eyre;
 use num::{ToPrimitive, Zero};
-use std::fs::create_dir_all;
+use std::fmt::Write as _;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 /// Represents connectivity that is supported by VTK.
@@ -51,6 +56,19 @@ impl VtkCellConnectivity for Segment2d3Connectivity {
     }
 }
 
+impl VtkCellConnectivity for Segment3d2Connectivity {
+    fn cell_type(&self) -> CellType {
+        CellType::QuadraticEdge
+    }
+
+    fn write_vtk_connectivity(&self, connectivity: &mut [usize]) {
+        assert_eq!(connectivity.len(), self.vertex_indices().len());
+        // VTK expects the two end points before the midpoint
+        let [a, midpoint, b] = self.0;
+        connectivity.clone_from_slice(&[a, b, midpoint]);
+    }
+}
+
 impl VtkCellConnectivity for Tri3d2Connectivity {
     fn cell_type(&self) -> CellType {
         CellType::Triangle
@@ -93,6 +111,12 @@ impl VtkCellConnectivity for Tri3d3Connectivity {
     }
 }
 
+impl VtkCellConnectivity for Tri6d3Connectivity {
+    fn cell_type(&self) -> CellType {
+        CellType::QuadraticTriangle
+    }
+}
+
 impl VtkCellConnectivity for Tet10Connectivity {
     fn cell_type(&self) -> CellType {
         CellType::QuadraticTetra
@@ -448,6 +472,18 @@ where
         }
     }
 
+    /// Appends the given point and cell attributes.
+    pub(crate) fn with_attributes(self, attributes: Attributes) -> Self {
+        let mut attribs = self.attributes;
+        attribs.point.extend(attributes.point);
+        attribs.cell.extend(attributes.cell);
+        Self {
+            mesh: self.mesh,
+            attributes: attribs,
+            title: self.title,
+        }
+    }
+
     // TODO: Different error type
     pub fn try_build(&self) -> eyre::Result<DataSet>
     where
@@ -544,4 +580,549 @@ where
         .export(filepath)?;
         Ok(())
     }
+
+    /// Convenience function for directly exporting the dataset to a VTU file with [`write_vtu`].
+    pub fn try_export_vtu(&self, filename: impl AsRef<Path>, compression: VtuCompression) -> eyre::Result<()>
+    where
+        C: VtkCellConnectivity,
+    {
+        let dataset = self.try_build()?;
+        write_vtu(filename, &dataset, compression)
+    }
+}
+
+/// Builder for exporting a mesh together with nodal and cell fields.
+///
+/// Unlike [`FiniteElementMeshDataSetBuilder`], the exporter does not panic when a field is
+/// inconsistent with the mesh. Instead, the first such error is reported when the data set
+/// is built or exported.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use fenris::io::FieldExporter;
+/// # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+/// # use fenris::mesh::QuadMesh2d;
+/// # fn main() -> eyre::Result<()> {
+/// let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+/// let u = vec![0.0; 2 * mesh.vertices().len()];
+/// let s = vec![0.0; mesh.connectivity().len()];
+/// FieldExporter::new(&mesh)
+///     .with_point_field("displacement", &u, 2)
+///     .with_cell_field("von_mises", &s)
+///     .export("output/solution.vtu")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FieldExporter<'a, T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    builder: FiniteElementMeshDataSetBuilder<'a, T, D, C>,
+    compression: VtuCompression,
+    error: Option<eyre::Report>,
+}
+
+impl<'a, T, D, C> FieldExporter<'a, T, D, C>
+where
+    T: Real + ToPrimitive,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    pub fn new(mesh: &'a Mesh<T, D, C>) -> Self {
+        Self {
+            builder: FiniteElementMeshDataSetBuilder::from_mesh(mesh),
+            compression: VtuCompression::default(),
+            error: None,
+        }
+    }
+
+    pub fn with_title(self, title: impl Into<String>) -> Self {
+        Self {
+            builder: self.builder.with_title(title),
+            ..self
+        }
+    }
+
+    /// Sets the compression used when exporting to VTU files.
+    pub fn with_compression(self, compression: VtuCompression) -> Self {
+        Self { compression, ..self }
+    }
+
+    /// Attaches a nodal field with the given number of components per vertex.
+    ///
+    /// The values are expected to be stored vertex by vertex, i.e. the components associated
+    /// with vertex `i` are stored in `values[num_components * i .. num_components * (i + 1)]`.
+    pub fn with_point_field<S: Scalar + ToPrimitive>(
+        self,
+        name: impl Into<String>,
+        values: &[S],
+        num_components: usize,
+    ) -> Self {
+        let name = name.into();
+        let num_points = self.builder.mesh.vertices().len();
+        if num_components == 0 || values.len() != num_components * num_points {
+            let error = eyre!(
+                "Point field {name} has {} entries, but the mesh has {num_points} vertices \
+                 and the field has {num_components} components",
+                values.len()
+            );
+            return self.with_error(error);
+        }
+        Self {
+            builder: self
+                .builder
+                .with_point_scalar_attributes(name, num_components, values),
+            ..self
+        }
+    }
+
+    /// Attaches a cell field.
+    ///
+    /// The number of components per cell is inferred from the number of values, which must
+    /// be a positive multiple of the number of cells in the mesh. Values are stored cell by cell.
+    pub fn with_cell_field<S: Scalar + ToPrimitive>(self, name: impl Into<String>, values: &[S]) -> Self {
+        let name = name.into();
+        let num_cells = self.builder.mesh.connectivity().len();
+        if num_cells == 0 || values.is_empty() || !values.len().is_multiple_of(num_cells) {
+            let error = eyre!(
+                "Cell field {name} has {} entries, which is not a positive multiple \
+                 of the number of cells ({num_cells}) in the mesh",
+                values.len()
+            );
+            return self.with_error(error);
+        }
+        let num_components = values.len() / num_cells;
+        Self {
+            builder: self
+                .builder
+                .with_cell_scalar_attributes(name, num_components, values),
+            ..self
+        }
+    }
+
+    /// Attaches a piecewise constant field with the given number of components per cell.
+    ///
+    /// The values are expected to be stored in the node order of a
+    /// [`PiecewiseConstantSpace`](crate::space::PiecewiseConstantSpace) over the mesh, i.e. the
+    /// components associated with cell `i` are stored in
+    /// `values[num_components * i .. num_components * (i + 1)]`. Unlike
+    /// [`with_cell_field`](Self::with_cell_field), the number of components is given explicitly
+    /// and checked against the number of values.
+    pub fn with_piecewise_constant_field<S: Scalar + ToPrimitive>(
+        self,
+        name: impl Into<String>,
+        values: &[S],
+        num_components: usize,
+    ) -> Self {
+        let name = name.into();
+        let num_cells = self.builder.mesh.connectivity().len();
+        if num_components == 0 || values.len() != num_components * num_cells {
+            let error = eyre!(
+                "Piecewise constant field {name} has {} entries, but the mesh has {num_cells} cells \
+                 and the field has {num_components} components",
+                values.len()
+            );
+            return self.with_error(error);
+        }
+        Self {
+            builder: self
+                .builder
+                .with_cell_scalar_attributes(name, num_components, values),
+            ..self
+        }
+    }
+
+    fn with_error(self, error: eyre::Report) -> Self {
+        Self {
+            error: self.error.or(Some(error)),
+            ..self
+        }
+    }
+
+    fn check(&self) -> eyre::Result<()> {
+        match &self.error {
+            Some(error) => Err(eyre!("Invalid field for export: {error}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the VTK data set for the mesh and all attached fields.
+    pub fn try_build(&self) -> eyre::Result<DataSet>
+    where
+        C: VtkCellConnectivity,
+    {
+        self.check()?;
+        self.builder.try_build()
+    }
+
+    /// Writes the mesh and its fields to a VTU file with [`write_vtu`].
+    pub fn write_vtu(&self, path: impl AsRef<Path>) -> eyre::Result<()>
+    where
+        C: VtkCellConnectivity,
+    {
+        write_vtu(path, &self.try_build()?, self.compression)
+    }
+
+    /// Writes the mesh and its fields to a legacy VTK file.
+    pub fn write_legacy_vtk(&self, path: impl AsRef<Path>) -> eyre::Result<()>
+    where
+        C: VtkCellConnectivity,
+    {
+        self.check()?;
+        self.builder.try_export(path)
+    }
+
+    /// Writes the mesh and its fields to a file, choosing the format based on the file extension.
+    ///
+    /// Files with the `vtu` extension are written with [`write_vtu`](Self::write_vtu), and all
+    /// other files are written in the legacy VTK format.
+    pub fn export(&self, path: impl AsRef<Path>) -> eyre::Result<()>
+    where
+        C: VtkCellConnectivity,
+    {
+        let path = path.as_ref();
+        let is_vtu = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("vtu"))
+            .unwrap_or(false);
+        if is_vtu {
+            self.write_vtu(path)
+        } else {
+            self.write_legacy_vtk(path)
+        }
+    }
+}
+
+/// Compression of the binary data in VTU files written by [`write_vtu`].
+///
+/// Compression levels range from 1 (fastest) to 9 (smallest file size).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum VtuCompression {
+    #[default]
+    None,
+    ZLib {
+        level: u32,
+    },
+    Lz4,
+    Lzma {
+        level: u32,
+    },
+}
+
+impl VtuCompression {
+    fn compressor_and_level(&self) -> (Compressor, u32) {
+        // vtkio skips compression of byte arrays altogether if the level is zero,
+        // so we must make sure that the level is always positive
+        match *self {
+            VtuCompression::None => (Compressor::None, 0),
+            VtuCompression::ZLib { level } => (Compressor::ZLib, level.clamp(1, 9)),
+            VtuCompression::Lz4 => (Compressor::LZ4, 1),
+            VtuCompression::Lzma { level } => (Compressor::LZMA, level.clamp(1, 9)),
+        }
+    }
+
+    fn vtk_compressor_name(&self) -> Option<&'static str> {
+        match self {
+            VtuCompression::None => None,
+            VtuCompression::ZLib { .. } => Some("vtkZLibDataCompressor"),
+            VtuCompression::Lz4 => Some("vtkLZ4DataCompressor"),
+            VtuCompression::Lzma { .. } => Some("vtkLZMADataCompressor"),
+        }
+    }
+}
+
+/// Writes the given unstructured grid data set to a VTU file at the given path.
+///
+/// Unlike the legacy VTK format, the data is stored in the VTK XML format with all arrays
+/// written as raw (and optionally compressed) little-endian binary data in an appended data
+/// section. This is much faster to write and read than ASCII output and preserves all
+/// floating-point values exactly. Point and cell attributes may have any number of components.
+///
+/// Parent directories are created if they do not already exist.
+///
+/// # Errors
+///
+/// Returns an error if the data set is not an unstructured grid, if it contains legacy
+/// field attributes, if the size of an attribute is inconsistent with the number of points
+/// or cells, or if writing to the file fails.
+pub fn write_vtu(path: impl AsRef<Path>, dataset: &DataSet, compression: VtuCompression) -> eyre::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_vtu_to(&mut writer, dataset, compression)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the given unstructured grid data set in the VTU format to the given writer.
+///
+/// See [`write_vtu`] for details.
+pub fn write_vtu_to(mut writer: impl Write, dataset: &DataSet, compression: VtuCompression) -> eyre::Result<()> {
+    let pieces = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } => pieces,
+        _ => return Err(eyre!("Only unstructured grids can be written to VTU files")),
+    };
+
+    let mut appended = AppendedDataWriter::new(compression);
+    let mut xml = String::new();
+    write!(
+        xml,
+        r#"<?xml version="1.0"?>
+<VTKFile type="UnstructuredGrid" version="1.0" byte_order="LittleEndian" header_type="UInt64""#
+    )?;
+    if let Some(compressor) = compression.vtk_compressor_name() {
+        write!(xml, r#" compressor="{compressor}""#)?;
+    }
+    writeln!(xml, ">")?;
+    writeln!(xml, "  <UnstructuredGrid>")?;
+
+    for piece in pieces {
+        let piece = piece.load_piece_data(None)?;
+        let num_points = piece.num_points();
+        let num_cells = piece.cells.types.len();
+        writeln!(
+            xml,
+            r#"    <Piece NumberOfPoints="{num_points}" NumberOfCells="{num_cells}">"#
+        )?;
+
+        for (tag, attributes, count) in [
+            ("PointData", &piece.data.point, num_points),
+            ("CellData", &piece.data.cell, num_cells),
+        ] {
+            writeln!(xml, "      <{tag}>")?;
+            for attribute in attributes {
+                let array = match attribute {
+                    Attribute::DataArray(array) => array,
+                    Attribute::Field { name, .. } => {
+                        return Err(eyre!("Field attribute {name} is not supported in VTU files"));
+                    }
+                };
+                let num_components = array.num_comp();
+                if array.data.len() != num_components * count {
+                    return Err(eyre!(
+                        "Attribute {} has {} entries, expected {} for {} components",
+                        array.name,
+                        array.data.len(),
+                        num_components * count,
+                        num_components
+                    ));
+                }
+                appended.write_data_array(&mut xml, &array.name, num_components, array.data.clone())?;
+            }
+            writeln!(xml, "      </{tag}>")?;
+        }
+
+        writeln!(xml, "      <Points>")?;
+        appended.write_data_array(&mut xml, "Points", 3, piece.points.clone())?;
+        writeln!(xml, "      </Points>")?;
+
+        let (connectivity, offsets) = piece.cells.cell_verts.clone().into_xml();
+        let to_i64 = |indices: Vec<u64>| -> eyre::Result<Vec<i64>> {
+            indices
+                .into_iter()
+                .map(|idx| Ok(i64::try_from(idx)?))
+                .collect()
+        };
+        let types: Vec<u8> = piece
+            .cells
+            .types
+            .iter()
+            .map(|&cell_type| cell_type as u8)
+            .collect();
+        writeln!(xml, "      <Cells>")?;
+        appended.write_data_array(&mut xml, "connectivity", 1, to_i64(connectivity)?.into())?;
+        appended.write_data_array(&mut xml, "offsets", 1, to_i64(offsets)?.into())?;
+        appended.write_data_array(&mut xml, "types", 1, types.into())?;
+        writeln!(xml, "      </Cells>")?;
+        writeln!(xml, "    </Piece>")?;
+    }
+
+    writeln!(xml, "  </UnstructuredGrid>")?;
+    write!(xml, "  <AppendedData encoding=\"raw\">\n    _")?;
+
+    writer.write_all(xml.as_bytes())?;
+    writer.write_all(&appended.bytes)?;
+    writer.write_all(b"\n  </AppendedData>\n</VTKFile>\n")?;
+    Ok(())
+}
+
+/// Writes a parallel VTU (PVTU) index file that combines the given VTU pieces into a single
+/// data set.
+///
+/// The pieces are typically written concurrently with [`write_vtu`], for example one per mesh
+/// partition, see [`PartitionedFieldExporter`](crate::io::PartitionedFieldExporter). The
+/// `piece_sources` are written verbatim to the index file, so relative paths are interpreted
+/// relative to the directory of the index file.
+///
+/// The names, types and number of components of the point and cell arrays are taken from the
+/// given data set, which should be the data set of one of the pieces. All pieces must have
+/// arrays with the same names, types and number of components.
+///
+/// Parent directories are created if they do not already exist.
+///
+/// # Errors
+///
+/// Returns an error if the data set is not an unstructured grid with at least one piece, if it
+/// contains legacy field attributes or if writing to the file fails.
+pub fn write_pvtu_index(
+    path: impl AsRef<Path>,
+    piece_sources: &[impl AsRef<str>],
+    dataset: &DataSet,
+) -> eyre::Result<()> {
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } => pieces
+            .first()
+            .ok_or_else(|| eyre!("Data set must contain at least one piece"))?
+            .load_piece_data(None)?,
+        _ => return Err(eyre!("Only unstructured grids can be written to PVTU files")),
+    };
+
+    let mut xml = String::new();
+    writeln!(
+        xml,
+        r#"<?xml version="1.0"?>
+<VTKFile type="PUnstructuredGrid" version="1.0" byte_order="LittleEndian" header_type="UInt64">
+  <PUnstructuredGrid GhostLevel="0">"#
+    )?;
+    for (tag, attributes) in [("PPointData", &piece.data.point), ("PCellData", &piece.data.cell)] {
+        writeln!(xml, "    <{tag}>")?;
+        for attribute in attributes {
+            let array = match attribute {
+                Attribute::DataArray(array) => array,
+                Attribute::Field { name, .. } => {
+                    return Err(eyre!("Field attribute {name} is not supported in PVTU files"));
+                }
+            };
+            writeln!(
+                xml,
+                r#"      <PDataArray type="{}" Name="{}" NumberOfComponents="{}"/>"#,
+                vtk_xml_scalar_type_name(&array.data),
+                escape_xml_attribute(&array.name),
+                array.num_comp()
+            )?;
+        }
+        writeln!(xml, "    </{tag}>")?;
+    }
+    writeln!(xml, "    <PPoints>")?;
+    writeln!(
+        xml,
+        r#"      <PDataArray type="{}" Name="Points" NumberOfComponents="3"/>"#,
+        vtk_xml_scalar_type_name(&piece.points)
+    )?;
+    writeln!(xml, "    </PPoints>")?;
+    for source in piece_sources {
+        writeln!(
+            xml,
+            r#"    <Piece Source="{}"/>"#,
+            escape_xml_attribute(source.as_ref())
+        )?;
+    }
+    writeln!(xml, "  </PUnstructuredGrid>")?;
+    writeln!(xml, "</VTKFile>")?;
+
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+/// Collects the binary data of all arrays in a VTU file.
+struct AppendedDataWriter {
+    compression: VtuCompression,
+    bytes: Vec<u8>,
+}
+
+impl AppendedDataWriter {
+    fn new(compression: VtuCompression) -> Self {
+        Self {
+            compression,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Appends the data to the binary data and writes the corresponding `DataArray` element to `xml`.
+    fn write_data_array(
+        &mut self,
+        xml: &mut String,
+        name: &str,
+        num_components: usize,
+        data: IOBuffer,
+    ) -> eyre::Result<()> {
+        let scalar_type = vtk_xml_scalar_type_name(&data);
+        let offset = self.append(data);
+        writeln!(
+            xml,
+            r#"        <DataArray type="{scalar_type}" Name="{}" NumberOfComponents="{num_components}" format="appended" offset="{offset}"/>"#,
+            escape_xml_attribute(name)
+        )?;
+        Ok(())
+    }
+
+    /// Appends the data and returns its offset relative to the start of the appended data.
+    fn append(&mut self, data: IOBuffer) -> usize {
+        const HEADER_SIZE: usize = 8;
+        let offset = self.bytes.len();
+        let num_bytes = data.num_bytes();
+        let (compressor, level) = self.compression.compressor_and_level();
+        let encoded = data.into_bytes_with_size(ByteOrder::LittleEndian, compressor, level);
+        if compressor == Compressor::None {
+            // Uncompressed data is prefixed by its size in bytes, which is exactly what vtkio gives us
+            self.bytes.extend_from_slice(&encoded);
+        } else {
+            // vtkio only prefixes compressed data by its compressed size, but VTK expects
+            // the header [num blocks][block size][last partial block size][compressed block sizes...].
+            // We store all data in a single block, so there is no partial block.
+            let compressed = &encoded[HEADER_SIZE..];
+            let header: &[usize] = if num_bytes > 0 {
+                &[1, num_bytes, 0, compressed.len()]
+            } else {
+                &[0, 0, 0]
+            };
+            for &entry in header {
+                self.bytes.extend_from_slice(&(entry as u64).to_le_bytes());
+            }
+            if num_bytes > 0 {
+                self.bytes.extend_from_slice(compressed);
+            }
+        }
+        offset
+    }
+}
+
+pub(crate) fn vtk_xml_scalar_type_name(data: &IOBuffer) -> &'static str {
+    use crate::vtkio::model::ScalarType;
+    match data.scalar_type() {
+        ScalarType::Bit | ScalarType::U8 => "UInt8",
+        ScalarType::I8 => "Int8",
+        ScalarType::U16 => "UInt16",
+        ScalarType::I16 => "Int16",
+        ScalarType::U32 => "UInt32",
+        ScalarType::I32 => "Int32",
+        ScalarType::U64 => "UInt64",
+        ScalarType::I64 => "Int64",
+        ScalarType::F32 => "Float32",
+        ScalarType::F64 => "Float64",
+    }
+}
+
+pub(crate) fn escape_xml_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
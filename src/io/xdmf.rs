@@ -0,0 +1,282 @@
+//! Output of unstructured grids in the XDMF format.
+//!
+//! An XDMF file consists of an XML file (`.xmf`) that describes the structure of the data set
+//! and references the actual array data, which is stored separately. Here, the array data of
+//! each XML file is stored as raw little-endian binary data in a single file with the same name
+//! and the extension `.bin`, located next to the XML file.
+//!
+//! Several XDMF files can be combined into a single data set with [`write_xdmf_index`], which
+//! is used to write the output of partitioned meshes, see
+//! [`PartitionedFieldExporter`](crate::io::PartitionedFieldExporter). Both ParaView and VisIt
+//! can read the resulting files.
+use crate::io::vtk::escape_xml_attribute;
+use crate::vtkio::model::{Attribute, ByteOrder, CellType, DataSet, ScalarType};
+use crate::vtkio::xml::Compressor;
+use crate::vtkio::IOBuffer;
+use eyre::eyre;
+use std::fmt::Write as _;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+/// Writes the given unstructured grid data set to an XDMF file at the given path.
+///
+/// The array data is written to a binary file with the same name as the XDMF file, but with
+/// the extension `.bin`. The cells must all have the same type, and cell vertices are written
+/// in the VTK node ordering.
+///
+/// Parent directories are created if they do not already exist.
+///
+/// # Errors
+///
+/// Returns an error if the data set is not an unstructured grid with a single piece, if the
+/// cells do not all have the same type, if the cell type is not supported by XDMF, if the data
+/// set contains legacy field attributes or bit arrays, or if writing to the files fails.
+pub fn write_xdmf(path: impl AsRef<Path>, dataset: &DataSet) -> eyre::Result<()> {
+    let path = path.as_ref();
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } if pieces.len() == 1 => pieces[0].load_piece_data(None)?,
+        DataSet::UnstructuredGrid { pieces, .. } => {
+            return Err(eyre!(
+                "XDMF output requires a single piece, but the data set has {} pieces",
+                pieces.len()
+            ))
+        }
+        _ => return Err(eyre!("Only unstructured grids can be written to XDMF files")),
+    };
+    let num_points = piece.num_points();
+    let num_cells = piece.cells.types.len();
+
+    let cell_type = piece
+        .cells
+        .types
+        .first()
+        .copied()
+        .ok_or_else(|| eyre!("XDMF output requires at least one cell"))?;
+    if piece.cells.types.iter().any(|&ty| ty != cell_type) {
+        return Err(eyre!("XDMF output requires all cells to have the same type"));
+    }
+    let (topology_type, nodes_per_element) = xdmf_topology_type(cell_type)?;
+    let (connectivity, offsets) = piece.cells.cell_verts.clone().into_xml();
+    if offsets
+        .iter()
+        .zip(1..)
+        .any(|(&offset, i)| offset as usize != i * nodes_per_element)
+    {
+        return Err(eyre!(
+            "Cells of type {cell_type:?} must have {nodes_per_element} vertices each"
+        ));
+    }
+
+    let heavy_data_path = path.with_extension("bin");
+    let heavy_data_name = heavy_data_path
+        .file_name()
+        .ok_or_else(|| eyre!("Invalid XDMF file path {}", path.display()))?
+        .to_string_lossy()
+        .into_owned();
+    let grid_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut heavy_data = HeavyDataWriter::new(heavy_data_name);
+
+    let mut xml = String::new();
+    writeln!(
+        xml,
+        r#"<?xml version="1.0"?>
+<Xdmf Version="3.0">
+  <Domain>
+    <Grid Name="{}" GridType="Uniform">"#,
+        escape_xml_attribute(&grid_name)
+    )?;
+
+    write!(
+        xml,
+        r#"      <Topology TopologyType="{topology_type}" NumberOfElements="{num_cells}""#
+    )?;
+    if topology_type == "Polyline" {
+        write!(xml, r#" NodesPerElement="{nodes_per_element}""#)?;
+    }
+    writeln!(xml, ">")?;
+    let connectivity: Vec<i64> = connectivity
+        .into_iter()
+        .map(i64::try_from)
+        .collect::<Result<_, _>>()?;
+    heavy_data.write_data_item(&mut xml, connectivity.into(), num_cells, nodes_per_element)?;
+    writeln!(xml, "      </Topology>")?;
+
+    writeln!(xml, r#"      <Geometry GeometryType="XYZ">"#)?;
+    heavy_data.write_data_item(&mut xml, piece.points.clone(), num_points, 3)?;
+    writeln!(xml, "      </Geometry>")?;
+
+    for (center, attributes, count) in [
+        ("Node", &piece.data.point, num_points),
+        ("Cell", &piece.data.cell, num_cells),
+    ] {
+        for attribute in attributes {
+            let array = match attribute {
+                Attribute::DataArray(array) => array,
+                Attribute::Field { name, .. } => {
+                    return Err(eyre!("Field attribute {name} is not supported in XDMF files"));
+                }
+            };
+            let num_components = array.num_comp();
+            if array.data.len() != num_components * count {
+                return Err(eyre!(
+                    "Attribute {} has {} entries, expected {} for {} components",
+                    array.name,
+                    array.data.len(),
+                    num_components * count,
+                    num_components
+                ));
+            }
+            let attribute_type = match num_components {
+                1 => "Scalar",
+                3 => "Vector",
+                6 => "Tensor6",
+                9 => "Tensor",
+                _ => "Matrix",
+            };
+            writeln!(
+                xml,
+                r#"      <Attribute Name="{}" AttributeType="{attribute_type}" Center="{center}">"#,
+                escape_xml_attribute(&array.name)
+            )?;
+            heavy_data.write_data_item(&mut xml, array.data.clone(), count, num_components)?;
+            writeln!(xml, "      </Attribute>")?;
+        }
+    }
+
+    writeln!(xml, "    </Grid>")?;
+    writeln!(xml, "  </Domain>")?;
+    writeln!(xml, "</Xdmf>")?;
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    std::fs::write(&heavy_data_path, &heavy_data.bytes)?;
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+/// Writes an XDMF index file that combines the grids of the given XDMF files into a single
+/// (spatially partitioned) data set.
+///
+/// The pieces are typically written concurrently with [`write_xdmf`], for example one per mesh
+/// partition. The pieces are included with XInclude, and the `piece_sources` are written
+/// verbatim to the index file, so relative paths are interpreted relative to the directory of
+/// the index file.
+///
+/// Parent directories are created if they do not already exist.
+///
+/// # Errors
+///
+/// Returns an error if writing to the file fails.
+pub fn write_xdmf_index(path: impl AsRef<Path>, piece_sources: &[impl AsRef<str>]) -> eyre::Result<()> {
+    let path = path.as_ref();
+    let grid_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut xml = String::new();
+    writeln!(
+        xml,
+        r#"<?xml version="1.0"?>
+<Xdmf Version="3.0" xmlns:xi="http://www.w3.org/2001/XInclude">
+  <Domain>
+    <Grid Name="{}" GridType="Collection" CollectionType="Spatial">"#,
+        escape_xml_attribute(&grid_name)
+    )?;
+    for source in piece_sources {
+        writeln!(
+            xml,
+            r#"      <xi:include href="{}" xpointer="xpointer(//Xdmf/Domain/Grid)"/>"#,
+            escape_xml_attribute(source.as_ref())
+        )?;
+    }
+    writeln!(xml, "    </Grid>")?;
+    writeln!(xml, "  </Domain>")?;
+    writeln!(xml, "</Xdmf>")?;
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+/// Returns the XDMF topology type and the number of nodes per element for the given cell type.
+fn xdmf_topology_type(cell_type: CellType) -> eyre::Result<(&'static str, usize)> {
+    match cell_type {
+        CellType::Line => Ok(("Polyline", 2)),
+        CellType::QuadraticEdge => Ok(("Edge_3", 3)),
+        CellType::Triangle => Ok(("Triangle", 3)),
+        CellType::QuadraticTriangle => Ok(("Triangle_6", 6)),
+        CellType::Quad => Ok(("Quadrilateral", 4)),
+        CellType::BiquadraticQuad => Ok(("Quadrilateral_9", 9)),
+        CellType::Tetra => Ok(("Tetrahedron", 4)),
+        CellType::QuadraticTetra => Ok(("Tetrahedron_10", 10)),
+        CellType::Hexahedron => Ok(("Hexahedron", 8)),
+        CellType::QuadraticHexahedron => Ok(("Hexahedron_20", 20)),
+        CellType::TriquadraticHexahedron => Ok(("Hexahedron_27", 27)),
+        _ => Err(eyre!("Cell type {cell_type:?} is not supported in XDMF files")),
+    }
+}
+
+/// Collects the raw binary data of all data items in an XDMF file.
+struct HeavyDataWriter {
+    file_name: String,
+    bytes: Vec<u8>,
+}
+
+impl HeavyDataWriter {
+    fn new(file_name: String) -> Self {
+        Self {
+            file_name,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Appends the data to the binary data and writes the corresponding `DataItem` element to `xml`.
+    fn write_data_item(
+        &mut self,
+        xml: &mut String,
+        data: IOBuffer,
+        num_rows: usize,
+        num_components: usize,
+    ) -> eyre::Result<()> {
+        let (number_type, precision) = xdmf_number_type(data.scalar_type())?;
+        // vtkio prefixes uncompressed data by its size, which XDMF does not expect
+        const HEADER_SIZE: usize = 8;
+        let offset = self.bytes.len();
+        let encoded = data.into_bytes_with_size(ByteOrder::LittleEndian, Compressor::None, 0);
+        self.bytes.extend_from_slice(&encoded[HEADER_SIZE..]);
+        let dimensions = if num_components == 1 {
+            format!("{num_rows}")
+        } else {
+            format!("{num_rows} {num_components}")
+        };
+        writeln!(
+            xml,
+            r#"        <DataItem Dimensions="{dimensions}" NumberType="{number_type}" Precision="{precision}" Format="Binary" Endian="Little" Seek="{offset}">{}</DataItem>"#,
+            escape_xml_attribute(&self.file_name)
+        )?;
+        Ok(())
+    }
+}
+
+fn xdmf_number_type(scalar_type: ScalarType) -> eyre::Result<(&'static str, usize)> {
+    match scalar_type {
+        ScalarType::Bit => Err(eyre!("Bit arrays are not supported in XDMF files")),
+        ScalarType::I8 => Ok(("Char", 1)),
+        ScalarType::U8 => Ok(("UChar", 1)),
+        ScalarType::I16 => Ok(("Int", 2)),
+        ScalarType::U16 => Ok(("UInt", 2)),
+        ScalarType::I32 => Ok(("Int", 4)),
+        ScalarType::U32 => Ok(("UInt", 4)),
+        ScalarType::I64 => Ok(("Int", 8)),
+        ScalarType::U64 => Ok(("UInt", 8)),
+        ScalarType::F32 => Ok(("Float", 4)),
+        ScalarType::F64 => Ok(("Float", 8)),
+    }
+}
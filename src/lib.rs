@@ -5,19 +5,48 @@
 //! at this point.**
 //!
 //! Please see the [repository README](https://github.com/InteractiveComputerGraphics/fenris) for more information.
+//!
+//! # API stability
+//!
+//! The public API of `fenris` is divided into tiers with different stability commitments:
+//!
+//! - The [prelude] contains the *core* API: mesh types, finite element spaces, basic assembly
+//!   and I/O. Items in the prelude are only changed in incompatible ways after a deprecation
+//!   period, so that downstream crates can depend on the core without being broken by every
+//!   internal refactor.
+//! - The remaining public modules are *unstable*: they are generally useful and maintained, but
+//!   may change in any release.
+//! - *Experimental* subsystems are only available with feature flags. They are enabled by
+//!   default through the `experimental` feature, which downstream crates that only need the
+//!   core can disable with `default-features = false`. The experimental features are
+//!   - `experimental-solvers`: the iterative solvers and smoothers in `solver`,
+//!   - `experimental-models`: the Newton solver and the problem-specific models in [`model`],
+//!     such as Stokes flow, phase-field fracture and flux-corrected transport,
+//!   - `autodiff`: forward-mode automatic differentiation of operators.
 use nalgebra::{DimMin, DimName};
 
 pub mod allocators;
 pub mod assembly;
+#[cfg(feature = "autodiff")]
+pub mod autodiff;
+pub mod compare;
 pub mod connectivity;
 pub mod element;
 pub mod error;
+pub mod index;
 pub mod integrate;
 pub mod io;
+pub mod linear_solver;
+pub mod memory;
 pub mod mesh;
 pub mod model;
+pub mod nonlinear;
+pub mod prelude;
 pub mod quadrature;
+#[cfg(feature = "experimental-solvers")]
+pub mod solver;
 pub mod space;
+pub mod timestepping;
 pub mod util;
 
 pub mod geometry {
@@ -0,0 +1,85 @@
+//! Solvers for the sparse linear systems arising from finite element discretizations.
+//!
+//! Algorithms that need to solve linear systems, such as the
+//! [time integrators](crate::timestepping) or the Newton solver for nonlinear systems
+//! (see [`nonlinear`](crate::nonlinear)), are generic over the [`LinearSolver`] trait. Available
+//! implementations are [`DenseLuSolver`] for small problems and [`ConjugateGradientSolver`] for
+//! symmetric positive definite systems.
+use crate::Real;
+use eyre::eyre;
+use fenris_sparse::cg::{ConjugateGradient, RelativeResidualCriterion};
+use nalgebra::{DMatrix, DVectorView, DVectorViewMut};
+use nalgebra_sparse::CsrMatrix;
+
+/// A solver for linear systems $\vec A \vec x = \vec b$.
+pub trait LinearSolver<T: Real> {
+    /// Solves the linear system, using the provided `x` as initial guess if applicable.
+    fn solve(&mut self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>) -> eyre::Result<()>;
+}
+
+impl<T, S> LinearSolver<T> for &mut S
+where
+    T: Real,
+    S: ?Sized + LinearSolver<T>,
+{
+    fn solve(&mut self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>) -> eyre::Result<()> {
+        S::solve(self, matrix, x, b)
+    }
+}
+
+/// A linear solver that converts the matrix to a dense matrix and solves with an LU decomposition.
+///
+/// Only suitable for small problems.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DenseLuSolver;
+
+impl<T: Real> LinearSolver<T> for DenseLuSolver {
+    fn solve(&mut self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) -> eyre::Result<()> {
+        let solution = DMatrix::from(matrix)
+            .lu()
+            .solve(&b)
+            .ok_or_else(|| eyre!("Matrix is singular"))?;
+        x.copy_from(&solution);
+        Ok(())
+    }
+}
+
+/// A linear solver based on the (unpreconditioned) conjugate gradient method.
+///
+/// The matrix must be symmetric positive definite.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConjugateGradientSolver<T> {
+    tolerance: T,
+    max_iterations: Option<usize>,
+}
+
+impl<T: Real> ConjugateGradientSolver<T> {
+    /// Creates a solver with the given tolerance for the residual relative to the right-hand side.
+    pub fn with_tolerance(tolerance: T) -> Self {
+        Self {
+            tolerance,
+            max_iterations: None,
+        }
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        Self {
+            max_iterations: Some(max_iterations),
+            ..self
+        }
+    }
+}
+
+impl<T: Real> LinearSolver<T> for ConjugateGradientSolver<T> {
+    fn solve(&mut self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>) -> eyre::Result<()> {
+        let mut cg = ConjugateGradient::new()
+            .with_operator(matrix)
+            .with_stopping_criterion(RelativeResidualCriterion::new(self.tolerance));
+        if let Some(max_iterations) = self.max_iterations {
+            cg = cg.with_max_iter(max_iterations);
+        }
+        cg.solve_with_guess(b, x)
+            .map_err(|err| eyre!("Conjugate gradient solver failed: {err}"))?;
+        Ok(())
+    }
+}
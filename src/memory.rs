@@ -0,0 +1,191 @@
+//! Estimates of heap memory usage.
+//!
+//! For large problems, it is useful to know how much memory the individual data structures of a
+//! simulation occupy, both to plan the capacity of the hardware and to find out which data
+//! structures are worth optimizing. Meshes, spatial indices, quadrature tables, sparse matrices
+//! and other large data structures implement [`HeapSizeEstimate`], and a [`MemoryReport`]
+//! collects the estimates of several data structures into a summary:
+//!
+//! ```
+//! # use fenris::assembly::global::CsrAssembler;
+//! # use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+//! # use fenris::assembly::operators::LaplaceOperator;
+//! # use fenris::memory::{HeapSizeEstimate, MemoryReport};
+//! # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+//! # use fenris::nalgebra::DVector;
+//! # use fenris::quadrature;
+//! # fn main() -> eyre::Result<()> {
+//! let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(16);
+//! let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+//! let u = DVector::zeros(mesh.vertices().len());
+//! let assembler = ElementEllipticAssemblerBuilder::new()
+//!     .with_finite_element_space(&mesh)
+//!     .with_operator(&LaplaceOperator)
+//!     .with_quadrature_table(&qtable)
+//!     .with_u(&u)
+//!     .build();
+//! let matrix = CsrAssembler::default().assemble(&assembler)?;
+//!
+//! let report = MemoryReport::new()
+//!     .with_entry("mesh", &mesh)
+//!     .with_entry("quadrature", &qtable)
+//!     .with_entry("stiffness matrix", &matrix)
+//!     .with_entry("solution", &u);
+//! assert!(report.total_bytes() > matrix.heap_size_estimate());
+//! println!("{report}");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The estimates account for the allocated buffers that dominate the memory usage of each data
+//! structure, but neglect allocator overhead and small auxiliary allocations. They are therefore
+//! slight underestimates, which are accurate enough for capacity planning.
+use crate::nalgebra::{Dim, Dyn, Matrix, Scalar, VecStorage};
+use crate::nalgebra_sparse::pattern::SparsityPattern;
+use crate::nalgebra_sparse::{CooMatrix, CscMatrix, CsrMatrix};
+use fenris_nested_vec::NestedVec;
+use std::fmt;
+use std::fmt::Display;
+use std::mem::size_of;
+
+/// Data structures that can estimate the amount of heap memory they own.
+pub trait HeapSizeEstimate {
+    /// An estimate of the number of bytes of heap memory owned by the value.
+    ///
+    /// This does not include the size of the value itself, i.e. `std::mem::size_of_val(self)`.
+    fn heap_size_estimate(&self) -> usize;
+}
+
+impl<T: ?Sized + HeapSizeEstimate> HeapSizeEstimate for &T {
+    fn heap_size_estimate(&self) -> usize {
+        (**self).heap_size_estimate()
+    }
+}
+
+/// The estimate is based on the capacity of the vector. Heap memory owned by the elements is not
+/// taken into account.
+impl<T> HeapSizeEstimate for Vec<T> {
+    fn heap_size_estimate(&self) -> usize {
+        self.capacity() * size_of::<T>()
+    }
+}
+
+/// Heap memory owned by the elements is not taken into account.
+impl<T> HeapSizeEstimate for NestedVec<T> {
+    fn heap_size_estimate(&self) -> usize {
+        // The data array and two offset arrays
+        self.total_num_elements() * size_of::<T>() + 2 * self.len() * size_of::<usize>()
+    }
+}
+
+impl<T: Scalar, C: Dim> HeapSizeEstimate for Matrix<T, Dyn, C, VecStorage<T, Dyn, C>> {
+    fn heap_size_estimate(&self) -> usize {
+        self.len() * size_of::<T>()
+    }
+}
+
+impl HeapSizeEstimate for SparsityPattern {
+    fn heap_size_estimate(&self) -> usize {
+        (self.major_offsets().len() + self.minor_indices().len()) * size_of::<usize>()
+    }
+}
+
+impl<T> HeapSizeEstimate for CsrMatrix<T> {
+    fn heap_size_estimate(&self) -> usize {
+        self.pattern().heap_size_estimate() + self.nnz() * size_of::<T>()
+    }
+}
+
+impl<T> HeapSizeEstimate for CscMatrix<T> {
+    fn heap_size_estimate(&self) -> usize {
+        self.pattern().heap_size_estimate() + self.nnz() * size_of::<T>()
+    }
+}
+
+impl<T> HeapSizeEstimate for CooMatrix<T> {
+    fn heap_size_estimate(&self) -> usize {
+        self.nnz() * (2 * size_of::<usize>() + size_of::<T>())
+    }
+}
+
+/// A summary of the memory usage of a number of named data structures.
+///
+/// The report is displayed as a table with the estimated size of each entry and its share of the
+/// total, see the [module documentation](self) for an example.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    entries: Vec<(String, usize)>,
+}
+
+impl MemoryReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry with the [estimated heap size](HeapSizeEstimate) of the given value.
+    pub fn with_entry(mut self, name: impl Into<String>, value: &(impl ?Sized + HeapSizeEstimate)) -> Self {
+        self.add_entry(name, value);
+        self
+    }
+
+    /// Adds an entry with the given number of bytes, for data structures that do not implement
+    /// [`HeapSizeEstimate`].
+    pub fn with_bytes(mut self, name: impl Into<String>, bytes: usize) -> Self {
+        self.add_bytes(name, bytes);
+        self
+    }
+
+    pub fn add_entry(&mut self, name: impl Into<String>, value: &(impl ?Sized + HeapSizeEstimate)) {
+        self.add_bytes(name, value.heap_size_estimate());
+    }
+
+    pub fn add_bytes(&mut self, name: impl Into<String>, bytes: usize) {
+        self.entries.push((name.into(), bytes));
+    }
+
+    /// The names and sizes in bytes of the entries, in the order in which they were added.
+    pub fn entries(&self) -> &[(String, usize)] {
+        &self.entries
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+impl Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total_bytes();
+        let name_width = self
+            .entries
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .chain(["total".len()])
+            .max()
+            .unwrap();
+        for (name, bytes) in &self.entries {
+            let share = if total > 0 {
+                100.0 * *bytes as f64 / total as f64
+            } else {
+                0.0
+            };
+            writeln!(f, "{name:<name_width$}  {:>10}  {share:>5.1}%", format_bytes(*bytes))?;
+        }
+        write!(f, "{:<name_width$}  {:>10}", "total", format_bytes(total))
+    }
+}
+
+/// Formats a number of bytes with binary prefixes, e.g. `"1.50 MiB"`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
@@ -4,17 +4,33 @@ use crate::connectivity::{
     Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
 };
 use crate::geometry::{AxisAlignedBoundingBox, BoundedGeometry, GeometryCollection};
+use crate::memory::HeapSizeEstimate;
+use crate::mesh::boundary::BoundaryMesh;
+use crate::mesh::sets::MeshSets;
 use crate::Real;
 use fenris_nested_vec::NestedVec;
 use nalgebra::allocator::Allocator;
 use nalgebra::{DefaultAllocator, DimName, OPoint, OVector, Scalar, U2, U3};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::iter::once;
 
+pub mod adjacency;
+pub mod boundary;
+pub mod editor;
+pub mod features;
+pub mod halo;
+pub mod inflation;
+pub mod partition;
 pub mod procedural;
 pub mod refinement;
 pub mod reorder;
+pub mod selection;
+pub mod sets;
+pub mod split;
+pub mod structured;
+pub mod submesh;
+pub mod supermesh;
 
 /// Index-based data structure for conforming meshes (i.e. no hanging nodes).
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -37,12 +53,27 @@ where
         deserialize = "Connectivity: Deserialize<'de>"
     ))]
     connectivity: Vec<Connectivity>,
+    #[serde(default)]
+    sets: MeshSets,
 }
 
 /// Index-based data structure for conforming meshes (i.e. no hanging nodes).
 pub type Mesh2d<T, Connectivity> = Mesh<T, U2, Connectivity>;
 pub type Mesh3d<T, Connectivity> = Mesh<T, U3, Connectivity>;
 
+/// The connectivity is assumed to be stored inline, as is the case for all connectivity types with
+/// a fixed number of vertices.
+impl<T, D, C> HeapSizeEstimate for Mesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        self.vertices.heap_size_estimate() + self.connectivity.heap_size_estimate() + self.sets.heap_size_estimate()
+    }
+}
+
 pub type TriangleMesh2d<T> = Mesh2d<T, Tri3d2Connectivity>;
 pub type Tri6Mesh2d<T> = Mesh2d<T, Tri6d2Connectivity>;
 pub type QuadMesh2d<T> = Mesh2d<T, Quad4d2Connectivity>;
@@ -85,7 +116,11 @@ where
     /// or unsafe indexing in which the user is *trusted* to provide valid indices may
     /// produce undefined behavior.Therefore, the connectivity must always be checked.
     pub fn from_vertices_and_connectivity(vertices: Vec<OPoint<T, D>>, connectivity: Vec<Connectivity>) -> Self {
-        Self { vertices, connectivity }
+        Self {
+            vertices,
+            connectivity,
+            sets: MeshSets::default(),
+        }
     }
 }
 
@@ -302,55 +337,15 @@ where
 {
     /// Returns a new mesh in which only the desired cells are kept. The vertices are removed or
     /// relabeled as necessary.
+    ///
+    /// Named [sets](Self::sets) are relabeled accordingly, and removed nodes and cells are
+    /// removed from the sets.
+    ///
+    /// See [`extract_submesh`](Self::extract_submesh) for a variant that validates the cell
+    /// indices and also returns the mapping between the old and new indices.
     pub fn keep_cells(&self, cell_indices: &[usize]) -> Self {
         // TODO: Return Result instead of panicking if indices are out of bounds
-
-        // Each entry is true if this vertex should be kept, false otherwise
-        let vertex_keep_table = {
-            let mut table = vec![false; self.vertices.len()];
-            for cell_index in cell_indices {
-                let cell_connectivity = &self.connectivity[*cell_index];
-
-                for vertex_index in cell_connectivity.vertex_indices() {
-                    table[*vertex_index] = true;
-                }
-            }
-            table
-        };
-
-        let old_to_new_label_map = {
-            let mut label_map = HashMap::new();
-            let mut next_label = 0;
-            for (i, keep) in vertex_keep_table.iter().enumerate() {
-                if *keep {
-                    label_map.insert(i, next_label);
-                    next_label += 1;
-                }
-            }
-            label_map
-        };
-
-        let relabeled_cells: Vec<_> = cell_indices
-            .iter()
-            .map(|i| self.connectivity()[*i].clone())
-            .map(|mut cell| {
-                for index in cell.vertex_indices_mut() {
-                    *index = *old_to_new_label_map
-                        .get(index)
-                        .expect("Index must be in map");
-                }
-                cell
-            })
-            .collect();
-
-        let relabeled_vertices: Vec<_> = vertex_keep_table
-            .iter()
-            .enumerate()
-            .filter_map(|(i, should_keep)| if *should_keep { Some(i) } else { None })
-            .map(|index| self.vertices[index].clone())
-            .collect();
-
-        Mesh::from_vertices_and_connectivity(relabeled_vertices, relabeled_cells)
+        self.extract_submesh_unchecked(cell_indices).0
     }
 }
 
@@ -502,17 +497,59 @@ where
     /// Constructs a new mesh from the surface cells of the mesh.
     ///
     /// The orientation of the faces are preserved.
+    ///
+    /// See [`extract_boundary_mesh`](Self::extract_boundary_mesh) for extracting the surface
+    /// mesh together with its relation to this mesh.
     pub fn extract_surface_mesh(&self) -> Mesh<T, D, C::FaceConnectivity> {
-        let connectivity = self
-            .find_boundary_faces()
-            .into_iter()
-            .map(|(face, _, _)| face)
+        self.extract_boundary_mesh().into_mesh()
+    }
+
+    /// Extracts the boundary of the mesh as a new, lower-dimensional mesh, together with the
+    /// mapping of its vertices and faces to the vertices and cells of this mesh.
+    ///
+    /// The boundary mesh consists of the boundary faces of the mesh, with their orientation
+    /// preserved, and the vertices referenced by these faces in increasing order.
+    /// For example, the boundary of a [`Tet10Mesh`] is a mesh of quadratic triangles,
+    /// which may be used as a finite element space on the surface.
+    pub fn extract_boundary_mesh(&self) -> BoundaryMesh<T, D, C::FaceConnectivity> {
+        let boundary_faces = self.find_boundary_faces();
+
+        let mut parent_vertices: Vec<usize> = boundary_faces
+            .iter()
+            .flat_map(|(face, _, _)| face.vertex_indices())
+            .copied()
             .collect();
+        parent_vertices.sort_unstable();
+        parent_vertices.dedup();
+
+        let mut parent_to_boundary = vec![usize::MAX; self.vertices.len()];
+        for (boundary_index, &parent_index) in parent_vertices.iter().enumerate() {
+            parent_to_boundary[parent_index] = boundary_index;
+        }
+
+        let vertices = parent_vertices
+            .iter()
+            .map(|&i| self.vertices[i].clone())
+            .collect();
+        let mut connectivity = Vec::with_capacity(boundary_faces.len());
+        let mut parent_cells = Vec::with_capacity(boundary_faces.len());
+        let mut parent_local_faces = Vec::with_capacity(boundary_faces.len());
+        for (mut face, cell_index, local_index) in boundary_faces {
+            for index in face.vertex_indices_mut() {
+                *index = parent_to_boundary[*index];
+            }
+            connectivity.push(face);
+            parent_cells.push(cell_index);
+            parent_local_faces.push(local_index);
+        }
 
-        // TODO: This is rather inefficient
-        let new_mesh = Mesh::from_vertices_and_connectivity(self.vertices.clone(), connectivity);
-        let cells_to_keep: Vec<_> = (0..new_mesh.connectivity().len()).collect();
-        new_mesh.keep_cells(&cells_to_keep)
+        BoundaryMesh::from_parts(
+            Mesh::from_vertices_and_connectivity(vertices, connectivity),
+            self.vertices.len(),
+            parent_vertices,
+            parent_cells,
+            parent_local_faces,
+        )
     }
 }
 
@@ -0,0 +1,199 @@
+//! Adjacency queries for the cells, faces and vertices of a mesh.
+//!
+//! Many algorithms need to know how the entities of a mesh are connected: error estimators
+//! and discontinuous Galerkin methods iterate over the faces between cells, limiters and
+//! refinement need the neighbors of a cell, and patch-based methods need the cells around a
+//! vertex. [`MeshConnectivity`] computes all of these relations once, so that they can be
+//! queried efficiently afterwards.
+use crate::connectivity::Connectivity;
+use crate::mesh::sets::CellFace;
+use crate::mesh::split::{collect_faces, InteriorFace};
+use crate::mesh::Mesh;
+use eyre::eyre;
+use fenris_nested_vec::NestedVec;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, Scalar};
+
+/// A unique face of a mesh, given by the one or two cell faces that it coincides with.
+///
+/// For interior faces, the first cell has the smaller index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MeshFace {
+    pub first: CellFace,
+    pub second: Option<CellFace>,
+}
+
+impl MeshFace {
+    /// Returns `true` if the face belongs to only a single cell.
+    pub fn is_boundary(&self) -> bool {
+        self.second.is_none()
+    }
+
+    /// Returns the face as an [`InteriorFace`], or `None` if it is a boundary face.
+    pub fn to_interior_face(&self) -> Option<InteriorFace> {
+        self.second.map(|second| InteriorFace {
+            cells: [self.first.cell_index, second.cell_index],
+            local_faces: [self.first.local_index, second.local_index],
+        })
+    }
+}
+
+/// Adjacency information for the cells, faces and vertices of a mesh.
+///
+/// The unique faces of the mesh are enumerated in the order given by their sorted vertex
+/// indices, so that the enumeration is deterministic.
+#[derive(Debug, Clone)]
+pub struct MeshConnectivity {
+    num_vertices: usize,
+    vertex_cells: NestedVec<usize>,
+    faces: Vec<MeshFace>,
+    cell_faces: NestedVec<usize>,
+    cell_neighbors: NestedVec<usize>,
+}
+
+impl MeshConnectivity {
+    /// Computes the adjacency information of the given mesh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a face is shared by more than two cells.
+    pub fn from_mesh<T, D, C>(mesh: &Mesh<T, D, C>) -> eyre::Result<Self>
+    where
+        T: Scalar,
+        D: DimName,
+        C: Connectivity,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let num_vertices = mesh.vertices().len();
+        let num_cells = mesh.connectivity().len();
+
+        let mut vertex_cells = vec![Vec::new(); num_vertices];
+        for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+            for &vertex_index in cell.vertex_indices() {
+                vertex_cells[vertex_index].push(cell_index);
+            }
+        }
+
+        let mut faces = Vec::new();
+        let mut cell_faces: Vec<_> = mesh
+            .connectivity()
+            .iter()
+            .map(|cell| vec![usize::MAX; cell.num_faces()])
+            .collect();
+        let mut cell_neighbors = vec![Vec::new(); num_cells];
+        for (vertices, cells) in collect_faces(mesh) {
+            let face_index = faces.len();
+            let (first, second) = match *cells.as_slice() {
+                [first] => (first, None),
+                [first, second] => (first, Some(second)),
+                _ => {
+                    return Err(eyre!(
+                        "Face with vertices {vertices:?} is shared by {} cells",
+                        cells.len()
+                    ))
+                }
+            };
+            for (cell_index, local_index) in cells {
+                cell_faces[cell_index][local_index] = face_index;
+            }
+            if let Some((cell1, _)) = second {
+                cell_neighbors[first.0].push(cell1);
+                cell_neighbors[cell1].push(first.0);
+            }
+            let to_cell_face = |(cell_index, local_index)| CellFace {
+                cell_index,
+                local_index,
+            };
+            faces.push(MeshFace {
+                first: to_cell_face(first),
+                second: second.map(to_cell_face),
+            });
+        }
+
+        let to_nested = |arrays: Vec<Vec<usize>>, sort: bool| {
+            let mut nested = NestedVec::new();
+            for mut array in arrays {
+                if sort {
+                    array.sort_unstable();
+                    array.dedup();
+                }
+                nested.push(&array);
+            }
+            nested
+        };
+
+        Ok(Self {
+            num_vertices,
+            vertex_cells: to_nested(vertex_cells, true),
+            faces,
+            cell_faces: to_nested(cell_faces, false),
+            cell_neighbors: to_nested(cell_neighbors, true),
+        })
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.num_vertices
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cell_faces.len()
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// The cells that contain the given vertex, in increasing order.
+    pub fn vertex_cells(&self, vertex_index: usize) -> &[usize] {
+        self.vertex_cells
+            .get(vertex_index)
+            .expect("Vertex index must be in bounds")
+    }
+
+    /// The cells that share a face with the given cell, in increasing order.
+    pub fn cell_neighbors(&self, cell_index: usize) -> &[usize] {
+        self.cell_neighbors
+            .get(cell_index)
+            .expect("Cell index must be in bounds")
+    }
+
+    /// The indices of the unique faces of the given cell, ordered by the local face index.
+    pub fn cell_faces(&self, cell_index: usize) -> &[usize] {
+        self.cell_faces
+            .get(cell_index)
+            .expect("Cell index must be in bounds")
+    }
+
+    /// Returns the face of the neighboring cell that coincides with the given face,
+    /// or `None` if the face is on the boundary.
+    pub fn opposite_face(&self, cell_face: CellFace) -> Option<CellFace> {
+        let face = &self.faces[self.cell_faces(cell_face.cell_index)[cell_face.local_index]];
+        if face.first == cell_face {
+            face.second
+        } else {
+            Some(face.first)
+        }
+    }
+
+    /// All unique faces of the mesh.
+    pub fn faces(&self) -> &[MeshFace] {
+        &self.faces
+    }
+
+    pub fn face(&self, face_index: usize) -> &MeshFace {
+        &self.faces[face_index]
+    }
+
+    /// The faces that are shared by two cells.
+    pub fn interior_faces(&self) -> impl '_ + Iterator<Item = InteriorFace> {
+        self.faces.iter().filter_map(MeshFace::to_interior_face)
+    }
+
+    /// The faces that belong to only a single cell.
+    pub fn boundary_faces(&self) -> impl '_ + Iterator<Item = CellFace> {
+        self.faces
+            .iter()
+            .filter(|face| face.is_boundary())
+            .map(|face| face.first)
+    }
+}
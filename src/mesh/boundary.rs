@@ -0,0 +1,184 @@
+//! Boundary (trace) meshes extracted from volumetric meshes.
+//!
+//! Many operations are naturally formulated on the boundary of a mesh, such as traction
+//! boundary conditions, surface integrals of fluxes or the visualization of the skin of a
+//! volumetric mesh. [`Mesh::extract_boundary_mesh`] constructs the boundary of a mesh as a
+//! new, lower-dimensional mesh, e.g. a mesh of [`Tri6d3Connectivity`](crate::connectivity::Tri6d3Connectivity)
+//! faces for a [`Tet10Mesh`](crate::mesh::Tet10Mesh), which is itself a finite element space
+//! whenever its faces have an associated element. The returned [`BoundaryMesh`] additionally
+//! records how the vertices and faces of the boundary mesh relate to the parent mesh.
+use crate::mesh::Mesh;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Scalar};
+use std::ops::AddAssign;
+
+/// The boundary of a mesh, represented as a lower-dimensional mesh together with its relation
+/// to the parent mesh.
+///
+/// The vertices of the boundary mesh are the boundary vertices of the parent mesh, in
+/// increasing order of their index in the parent mesh. Face `i` of the boundary mesh is the
+/// face with local index `parent_local_faces()[i]` of the cell `parent_cells()[i]` in the
+/// parent mesh, and has the same orientation.
+///
+/// Since the nodes of a mesh-backed finite element space coincide with its vertices, the
+/// vertex mapping is also the node mapping between the boundary space and the parent space.
+/// In particular, element assemblers for the boundary space can be used to assemble directly
+/// into vectors and matrices of the parent space by mapping their node indices, e.g.
+///
+/// ```
+/// # use fenris::assembly::global::CsrAssembler;
+/// # use fenris::assembly::local::{
+/// #     Density, ElementConnectivityAssembler, ElementSurfaceMassAssembler, UniformQuadratureTable,
+/// # };
+/// # use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+/// # use fenris::mesh::Tet10Mesh;
+/// # use fenris::quadrature;
+/// let mesh = Tet10Mesh::from(&create_unit_box_uniform_tet_mesh_3d::<f64>(2));
+/// let boundary = mesh.extract_boundary_mesh();
+/// let triangle_quadrature = quadrature::total_order::triangle(4).unwrap();
+/// let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(triangle_quadrature, Density(1.0));
+/// let assembler = ElementSurfaceMassAssembler::with_solution_dim(1)
+///     .with_space(boundary.mesh())
+///     .with_quadrature_table(&qtable);
+///
+/// let num_parent_nodes = boundary.num_parent_vertices();
+/// let assembler = assembler.map_element_nodes(num_parent_nodes, |i| boundary.parent_vertices()[i]);
+/// let boundary_mass = CsrAssembler::default().assemble(&assembler).unwrap();
+/// assert_eq!(boundary_mass.nrows(), mesh.vertices().len());
+/// ```
+///
+/// See [`ElementConnectivityAssembler::map_element_nodes`](crate::assembly::local::ElementConnectivityAssembler::map_element_nodes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundaryMesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    num_parent_vertices: usize,
+    parent_vertices: Vec<usize>,
+    parent_cells: Vec<usize>,
+    parent_local_faces: Vec<usize>,
+}
+
+impl<T, D, C> BoundaryMesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    pub(crate) fn from_parts(
+        mesh: Mesh<T, D, C>,
+        num_parent_vertices: usize,
+        parent_vertices: Vec<usize>,
+        parent_cells: Vec<usize>,
+        parent_local_faces: Vec<usize>,
+    ) -> Self {
+        debug_assert_eq!(mesh.vertices().len(), parent_vertices.len());
+        debug_assert_eq!(mesh.connectivity().len(), parent_cells.len());
+        debug_assert_eq!(mesh.connectivity().len(), parent_local_faces.len());
+        Self {
+            mesh,
+            num_parent_vertices,
+            parent_vertices,
+            parent_cells,
+            parent_local_faces,
+        }
+    }
+
+    /// The boundary mesh.
+    pub fn mesh(&self) -> &Mesh<T, D, C> {
+        &self.mesh
+    }
+
+    pub fn into_mesh(self) -> Mesh<T, D, C> {
+        self.mesh
+    }
+
+    /// The number of vertices in the parent mesh.
+    pub fn num_parent_vertices(&self) -> usize {
+        self.num_parent_vertices
+    }
+
+    /// The index in the parent mesh of each vertex in the boundary mesh.
+    ///
+    /// The indices are sorted in increasing order.
+    pub fn parent_vertices(&self) -> &[usize] {
+        &self.parent_vertices
+    }
+
+    /// The index of the parent cell of each face in the boundary mesh.
+    pub fn parent_cells(&self) -> &[usize] {
+        &self.parent_cells
+    }
+
+    /// The local index of each face in the boundary mesh with respect to its parent cell.
+    pub fn parent_local_faces(&self) -> &[usize] {
+        &self.parent_local_faces
+    }
+
+    /// Gathers the values of the boundary nodes from a nodal vector of the parent space.
+    ///
+    /// This is useful for evaluating fields on the boundary, e.g. for skin visualization.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the parent vector does not match the number of parent
+    /// vertices and the solution dimension.
+    pub fn gather_from_parent<'a, S: Scalar>(
+        &self,
+        parent: impl Into<DVectorView<'a, S>>,
+        solution_dim: usize,
+    ) -> DVector<S> {
+        let parent = parent.into();
+        let s = solution_dim;
+        assert_eq!(
+            parent.len(),
+            s * self.num_parent_vertices,
+            "Parent vector must have one entry per parent vertex and solution component"
+        );
+        DVector::from_iterator(
+            s * self.parent_vertices.len(),
+            self.parent_vertices
+                .iter()
+                .flat_map(|&node| (0..s).map(move |i| s * node + i))
+                .map(|index| parent[index].clone()),
+        )
+    }
+
+    /// Adds the values of a nodal vector of the boundary space to the corresponding entries
+    /// of a nodal vector of the parent space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the vectors do not match the number of vertices and the
+    /// solution dimension.
+    pub fn add_to_parent<'a, 'b, S>(
+        &self,
+        boundary: impl Into<DVectorView<'a, S>>,
+        parent: impl Into<DVectorViewMut<'b, S>>,
+        solution_dim: usize,
+    ) where
+        S: Scalar + AddAssign,
+    {
+        let boundary = boundary.into();
+        let mut parent = parent.into();
+        let s = solution_dim;
+        assert_eq!(
+            boundary.len(),
+            s * self.parent_vertices.len(),
+            "Boundary vector must have one entry per boundary vertex and solution component"
+        );
+        assert_eq!(
+            parent.len(),
+            s * self.num_parent_vertices,
+            "Parent vector must have one entry per parent vertex and solution component"
+        );
+        for (local, &node) in self.parent_vertices.iter().enumerate() {
+            for i in 0..s {
+                parent[s * node + i] += boundary[s * local + i].clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Safe mutation of meshes with lazily rebuilt derived data structures.
+//!
+//! Adaptive methods, moving interfaces and coupled simulations frequently need to modify a mesh
+//! in place, for example by adding or removing cells or moving vertices. Data structures derived
+//! from the mesh, such as the [adjacency information](MeshConnectivity), the boundary faces or a
+//! spatial index over the cells, become stale after such modifications, and using them afterwards
+//! is a common source of subtle bugs.
+//!
+//! A [`MeshEditor`] owns a mesh and performs all modifications through its own methods. Derived
+//! data structures are computed lazily on first use and cached. Every modification invalidates
+//! exactly those caches that depend on the modified data: moving vertices only invalidates the
+//! spatial index, while changes to the cells or the number of vertices invalidate everything.
+//! Since modifications require mutable access to the editor, the borrow checker guarantees that
+//! no references to stale derived data can exist.
+//!
+//! Derived data that is stored outside of the editor can be kept consistent with the
+//! [revision counters](MeshEditor::topology_revision), which are incremented with every
+//! modification.
+//!
+//! ```
+//! # use fenris::mesh::editor::MeshEditor;
+//! # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+//! # fn main() -> eyre::Result<()> {
+//! let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+//! let mut editor = MeshEditor::from_mesh(mesh);
+//! let num_boundary_faces = editor.boundary_faces().len();
+//! assert_eq!(num_boundary_faces, 8);
+//!
+//! // Removing a cell at a corner of the square replaces its two boundary faces by its two
+//! // interior faces
+//! editor.remove_cells(&[0])?;
+//! assert_eq!(editor.boundary_faces().len(), num_boundary_faces);
+//! assert_eq!(editor.adjacency()?.num_cells(), 3);
+//! # Ok(())
+//! # }
+//! ```
+use crate::allocators::DimAllocator;
+use crate::connectivity::{Connectivity, ConnectivityMut};
+use crate::geometry::AxisAlignedBoundingBox;
+use crate::mesh::adjacency::MeshConnectivity;
+use crate::mesh::Mesh;
+use crate::space::spatially_indexed::RTreeAccelerationStructure;
+use crate::Real;
+use eyre::eyre;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
+use std::sync::OnceLock;
+
+/// Owns a mesh and keeps data structures derived from the mesh consistent with modifications.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct MeshEditor<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    topology_revision: u64,
+    geometry_revision: u64,
+    adjacency: OnceLock<MeshConnectivity>,
+    boundary_faces: OnceLock<Vec<(C::FaceConnectivity, usize, usize)>>,
+    boundary_vertices: OnceLock<Vec<usize>>,
+    spatial_index: OnceLock<RTreeAccelerationStructure<D>>,
+}
+
+impl<T, D, C> MeshEditor<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn from_mesh(mesh: Mesh<T, D, C>) -> Self {
+        Self {
+            mesh,
+            topology_revision: 0,
+            geometry_revision: 0,
+            adjacency: OnceLock::new(),
+            boundary_faces: OnceLock::new(),
+            boundary_vertices: OnceLock::new(),
+            spatial_index: OnceLock::new(),
+        }
+    }
+
+    pub fn mesh(&self) -> &Mesh<T, D, C> {
+        &self.mesh
+    }
+
+    pub fn into_mesh(self) -> Mesh<T, D, C> {
+        self.mesh
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.mesh.vertices.len()
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.mesh.connectivity.len()
+    }
+
+    /// A counter that is incremented whenever the cells or the number of vertices change.
+    pub fn topology_revision(&self) -> u64 {
+        self.topology_revision
+    }
+
+    /// A counter that is incremented whenever the mesh changes in any way, including changes to
+    /// the positions of vertices.
+    pub fn geometry_revision(&self) -> u64 {
+        self.geometry_revision
+    }
+
+    /// Returns the adjacency information of the mesh, computing it if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a face is shared by more than two cells.
+    pub fn adjacency(&self) -> eyre::Result<&MeshConnectivity> {
+        if let Some(adjacency) = self.adjacency.get() {
+            return Ok(adjacency);
+        }
+        let adjacency = MeshConnectivity::from_mesh(&self.mesh)?;
+        Ok(self.adjacency.get_or_init(|| adjacency))
+    }
+
+    /// Returns the boundary faces of the mesh, computing them if necessary.
+    ///
+    /// See [`Mesh::find_boundary_faces`].
+    pub fn boundary_faces(&self) -> &[(C::FaceConnectivity, usize, usize)] {
+        self.boundary_faces
+            .get_or_init(|| self.mesh.find_boundary_faces())
+    }
+
+    /// Returns the sorted boundary vertices of the mesh, computing them if necessary.
+    ///
+    /// See [`Mesh::find_boundary_vertices`].
+    pub fn boundary_vertices(&self) -> &[usize] {
+        self.boundary_vertices.get_or_init(|| {
+            let mut indices: Vec<_> = self
+                .boundary_faces()
+                .iter()
+                .flat_map(|(face, _, _)| face.vertex_indices())
+                .copied()
+                .collect();
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+        })
+    }
+
+    /// Returns `true` if the adjacency information is currently cached.
+    pub fn has_cached_adjacency(&self) -> bool {
+        self.adjacency.get().is_some()
+    }
+
+    /// Returns `true` if the spatial index is currently cached.
+    pub fn has_cached_spatial_index(&self) -> bool {
+        self.spatial_index.get().is_some()
+    }
+
+    /// Adds a vertex to the mesh and returns its index.
+    pub fn add_vertex(&mut self, vertex: OPoint<T, D>) -> usize {
+        self.mesh.vertices.push(vertex);
+        self.invalidate_topology();
+        self.mesh.vertices.len() - 1
+    }
+
+    /// Moves the vertex with the given index to a new position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vertex index is out of bounds.
+    pub fn set_vertex(&mut self, index: usize, vertex: OPoint<T, D>) -> eyre::Result<()> {
+        let num_vertices = self.num_vertices();
+        let target = self
+            .mesh
+            .vertices
+            .get_mut(index)
+            .ok_or_else(|| eyre!("Vertex index {index} is out of bounds for mesh with {num_vertices} vertices"))?;
+        *target = vertex;
+        self.invalidate_geometry();
+        Ok(())
+    }
+
+    /// Applies the given transformation to all vertices of the mesh.
+    pub fn transform_vertices(&mut self, mut transformation: impl FnMut(&mut OPoint<T, D>)) {
+        for vertex in &mut self.mesh.vertices {
+            transformation(vertex);
+        }
+        self.invalidate_geometry();
+    }
+
+    /// Adds a cell to the mesh and returns its index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cell references a vertex that does not exist.
+    pub fn add_cell(&mut self, cell: C) -> eyre::Result<usize> {
+        self.check_cell_vertices(&cell)?;
+        self.mesh.connectivity.push(cell);
+        self.invalidate_topology();
+        Ok(self.mesh.connectivity.len() - 1)
+    }
+
+    /// Replaces the cell with the given index and returns the previous cell.
+    ///
+    /// Named [sets](Mesh::sets) are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cell index is out of bounds or if the new cell references a vertex
+    /// that does not exist.
+    pub fn replace_cell(&mut self, index: usize, cell: C) -> eyre::Result<C> {
+        self.check_cell_vertices(&cell)?;
+        let num_cells = self.num_cells();
+        let target = self
+            .mesh
+            .connectivity
+            .get_mut(index)
+            .ok_or_else(|| eyre!("Cell index {index} is out of bounds for mesh with {num_cells} cells"))?;
+        let previous = std::mem::replace(target, cell);
+        self.invalidate_topology();
+        Ok(previous)
+    }
+
+    /// Removes the given cells from the mesh.
+    ///
+    /// The remaining cells keep their relative order. Returns the previous index of each
+    /// remaining cell, which can be used to transfer cell data to the modified mesh. Vertices are
+    /// not removed, see [`remove_unreferenced_vertices`](Self::remove_unreferenced_vertices).
+    /// Named [sets](Mesh::sets) are relabeled accordingly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cell index is out of bounds. The mesh is not modified in this case.
+    pub fn remove_cells(&mut self, cell_indices: &[usize]) -> eyre::Result<Vec<usize>> {
+        let num_cells = self.num_cells();
+        let mut is_removed = vec![false; num_cells];
+        for &cell_index in cell_indices {
+            *is_removed
+                .get_mut(cell_index)
+                .ok_or_else(|| eyre!("Cell index {cell_index} is out of bounds for mesh with {num_cells} cells"))? =
+                true;
+        }
+
+        let mut new_cell_indices = vec![None; num_cells];
+        let mut kept_cells = Vec::with_capacity(num_cells);
+        for (cell_index, removed) in is_removed.iter().enumerate() {
+            if !removed {
+                new_cell_indices[cell_index] = Some(kept_cells.len());
+                kept_cells.push(cell_index);
+            }
+        }
+
+        let connectivity = std::mem::take(&mut self.mesh.connectivity);
+        self.mesh.connectivity = connectivity
+            .into_iter()
+            .zip(is_removed)
+            .filter(|(_, removed)| !removed)
+            .map(|(cell, _)| cell)
+            .collect();
+        self.mesh.sets = self
+            .mesh
+            .sets
+            .relabeled(Some, |cell_index| new_cell_indices[cell_index]);
+        self.invalidate_topology();
+        Ok(kept_cells)
+    }
+
+    fn check_cell_vertices(&self, cell: &C) -> eyre::Result<()> {
+        let num_vertices = self.num_vertices();
+        match cell
+            .vertex_indices()
+            .iter()
+            .find(|&&index| index >= num_vertices)
+        {
+            Some(index) => Err(eyre!(
+                "Cell references vertex {index}, but the mesh only has {num_vertices} vertices"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn invalidate_geometry(&mut self) {
+        self.geometry_revision += 1;
+        self.spatial_index.take();
+    }
+
+    fn invalidate_topology(&mut self) {
+        self.topology_revision += 1;
+        self.invalidate_geometry();
+        self.adjacency.take();
+        self.boundary_faces.take();
+        self.boundary_vertices.take();
+    }
+}
+
+impl<T, D, C> MeshEditor<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: ConnectivityMut,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Removes all vertices that are not referenced by any cell.
+    ///
+    /// The remaining vertices keep their relative order. Returns the previous index of each
+    /// remaining vertex, which can be used to transfer nodal data to the modified mesh. Named
+    /// [sets](Mesh::sets) are relabeled accordingly.
+    pub fn remove_unreferenced_vertices(&mut self) -> Vec<usize> {
+        let num_vertices = self.num_vertices();
+        let mut is_referenced = vec![false; num_vertices];
+        for cell in &self.mesh.connectivity {
+            for &index in cell.vertex_indices() {
+                is_referenced[index] = true;
+            }
+        }
+
+        let mut new_vertex_indices = vec![None; num_vertices];
+        let mut kept_vertices = Vec::with_capacity(num_vertices);
+        for (vertex_index, &referenced) in is_referenced.iter().enumerate() {
+            if referenced {
+                new_vertex_indices[vertex_index] = Some(kept_vertices.len());
+                kept_vertices.push(vertex_index);
+            }
+        }
+        if kept_vertices.len() == num_vertices {
+            return kept_vertices;
+        }
+
+        let vertices = std::mem::take(&mut self.mesh.vertices);
+        self.mesh.vertices = vertices
+            .into_iter()
+            .zip(is_referenced)
+            .filter(|(_, referenced)| *referenced)
+            .map(|(vertex, _)| vertex)
+            .collect();
+        for cell in &mut self.mesh.connectivity {
+            for index in cell.vertex_indices_mut() {
+                *index = new_vertex_indices[*index].expect("Referenced vertices are always kept");
+            }
+        }
+        self.mesh.sets = self
+            .mesh
+            .sets
+            .relabeled(|vertex_index| new_vertex_indices[vertex_index], Some);
+        self.invalidate_topology();
+        kept_vertices
+    }
+}
+
+impl<T, D, C> MeshEditor<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Returns the indices of all cells whose bounding boxes intersect the given bounding box.
+    ///
+    /// The query uses a spatial index over the bounding boxes of the cells, which is built if
+    /// necessary. The returned indices are sorted.
+    pub fn cells_intersecting(&self, bounding_box: &AxisAlignedBoundingBox<T, D>) -> Vec<usize> {
+        let spatial_index = self.spatial_index.get_or_init(|| {
+            let bounds: Vec<_> = (0..self.num_cells())
+                .map(|cell_index| self.cell_bounds(cell_index))
+                .collect();
+            RTreeAccelerationStructure::from_bounding_boxes(&bounds)
+        });
+        // The spatial index slightly enlarges the bounding boxes, so check the candidates exactly
+        let mut cells: Vec<_> = spatial_index
+            .intersecting_cell_candidates(bounding_box)
+            .into_iter()
+            .filter(|&cell_index| self.cell_bounds(cell_index).intersects(bounding_box))
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    fn cell_bounds(&self, cell_index: usize) -> AxisAlignedBoundingBox<T, D> {
+        let vertex_indices = self.mesh.connectivity[cell_index].vertex_indices();
+        AxisAlignedBoundingBox::from_points(vertex_indices.iter().map(|&i| &self.mesh.vertices[i]))
+            .expect("Cells must have at least one vertex")
+    }
+}
+
+impl<T, D, C> From<Mesh<T, D, C>> for MeshEditor<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn from(mesh: Mesh<T, D, C>) -> Self {
+        Self::from_mesh(mesh)
+    }
+}
@@ -0,0 +1,137 @@
+//! Detection of geometric features on triangle surface meshes.
+//!
+//! Surface meshes of CAD geometries typically contain sharp edges and corners, for example along
+//! the rim of a cylinder or at the corners of a box. Many operations need to respect these
+//! features: boundary conditions are often prescribed on individual faces of the geometry,
+//! and mesh smoothing must not move vertices away from sharp edges or corners.
+//!
+//! A *feature edge* is an edge at which the surface is not smooth, which is detected by
+//! comparing the normals of the two adjacent triangles. A *corner* is a vertex at which
+//! feature edges meet or at which a curve of feature edges has a kink. Both are made available
+//! as [named sets](crate::mesh::sets) of the surface mesh through
+//! [`tag_feature_edges`](Mesh::tag_feature_edges) and
+//! [`tag_feature_corners`](Mesh::tag_feature_corners).
+//!
+//! All angles are given in radians.
+use crate::connectivity::Tri3d3Connectivity;
+use crate::mesh::{Mesh, TriangleMesh3d};
+use crate::Real;
+use nalgebra::{Point3, Vector3, U3};
+use std::collections::BTreeMap;
+
+/// Finds the feature edges of a triangle surface mesh.
+///
+/// An edge is a feature edge if the angle between the normals of its two adjacent triangles
+/// exceeds the given threshold, i.e. if the surface bends by more than the threshold angle
+/// across the edge. Edges that are adjacent to only a single triangle (the boundary of an open
+/// surface) or to more than two triangles (non-manifold edges) are always feature edges.
+/// Neighboring triangles do not need to be consistently oriented. Edges adjacent to degenerate
+/// triangles, whose normals are not defined, are only considered feature edges for
+/// topological reasons.
+///
+/// The edges are returned as pairs of vertex indices in ascending order, sorted
+/// lexicographically.
+pub fn find_feature_edges<T: Real>(surface: &TriangleMesh3d<T>, angle_threshold: T) -> Vec<[usize; 2]> {
+    let normals: Vec<_> = surface
+        .connectivity()
+        .iter()
+        .map(|conn| triangle_normal(surface.vertices(), conn))
+        .collect();
+
+    // For each edge, collect the adjacent triangles along with the direction in which the
+    // triangle traverses the edge. Consistently oriented neighbors traverse it in opposite directions.
+    let mut adjacent_triangles = BTreeMap::<_, Vec<_>>::new();
+    for (triangle_index, conn) in surface.connectivity().iter().enumerate() {
+        for i in 0..3 {
+            let (a, b) = (conn.0[i], conn.0[(i + 1) % 3]);
+            adjacent_triangles
+                .entry([a.min(b), a.max(b)])
+                .or_default()
+                .push((triangle_index, a < b));
+        }
+    }
+
+    let cos_threshold = angle_threshold.cos();
+    adjacent_triangles
+        .into_iter()
+        .filter(|(_, triangles)| match triangles.as_slice() {
+            &[(t1, forward1), (t2, forward2)] => match (&normals[t1], &normals[t2]) {
+                (Some(n1), Some(n2)) => {
+                    let cos_angle = if forward1 != forward2 { n1.dot(n2) } else { -n1.dot(n2) };
+                    cos_angle < cos_threshold
+                }
+                _ => false,
+            },
+            _ => true,
+        })
+        .map(|(edge, _)| edge)
+        .collect()
+}
+
+/// Finds the corners of a triangle surface mesh, given its feature edges.
+///
+/// A vertex is a corner if the number of feature edges that meet at the vertex is different
+/// from zero and two, i.e. if it is the end point of a curve of feature edges or the meeting
+/// point of several such curves. A vertex with exactly two feature edges is a corner if the
+/// curve of feature edges turns by more than the given threshold angle at the vertex.
+///
+/// The corners are returned in ascending order.
+pub fn find_feature_corners<T: Real>(
+    surface: &TriangleMesh3d<T>,
+    feature_edges: &[[usize; 2]],
+    angle_threshold: T,
+) -> Vec<usize> {
+    let mut incident_edges = vec![Vec::new(); surface.vertices().len()];
+    for &[a, b] in feature_edges {
+        incident_edges[a].push(b);
+        incident_edges[b].push(a);
+    }
+
+    let vertices = surface.vertices();
+    let cos_threshold = angle_threshold.cos();
+    incident_edges
+        .iter()
+        .enumerate()
+        .filter(|(vertex_index, neighbors)| match neighbors.as_slice() {
+            [] => false,
+            &[prev, next] => {
+                let x = &vertices[*vertex_index];
+                let incoming = (x - vertices[prev]).try_normalize(T::zero());
+                let outgoing = (vertices[next] - x).try_normalize(T::zero());
+                match (incoming, outgoing) {
+                    (Some(incoming), Some(outgoing)) => incoming.dot(&outgoing) < cos_threshold,
+                    _ => false,
+                }
+            }
+            _ => true,
+        })
+        .map(|(vertex_index, _)| vertex_index)
+        .collect()
+}
+
+fn triangle_normal<T: Real>(vertices: &[Point3<T>], conn: &Tri3d3Connectivity) -> Option<Vector3<T>> {
+    let [a, b, c] = conn.0.map(|i| vertices[i]);
+    (b - a).cross(&(c - a)).try_normalize(T::zero())
+}
+
+impl<T: Real> Mesh<T, U3, Tri3d3Connectivity> {
+    /// Stores the [feature edges](find_feature_edges) of the surface mesh as a named edge set.
+    ///
+    /// Returns the tagged edges.
+    pub fn tag_feature_edges(&mut self, name: impl Into<String>, angle_threshold: T) -> &[[usize; 2]] {
+        let edges = find_feature_edges(self, angle_threshold);
+        self.sets_mut().insert_edge_set(name, edges)
+    }
+
+    /// Stores the [corners](find_feature_corners) of the surface mesh as a named node set.
+    ///
+    /// The same threshold angle is used both for detecting the feature edges and for detecting
+    /// kinks along curves of feature edges.
+    ///
+    /// Returns the tagged nodes.
+    pub fn tag_feature_corners(&mut self, name: impl Into<String>, angle_threshold: T) -> &[usize] {
+        let edges = find_feature_edges(self, angle_threshold);
+        let corners = find_feature_corners(self, &edges, angle_threshold);
+        self.sets_mut().insert_node_set(name, corners)
+    }
+}
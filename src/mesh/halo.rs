@@ -0,0 +1,512 @@
+//! Ghost node communication patterns and halo exchange for partitioned meshes.
+//!
+//! When the elements of a mesh are distributed among several partitions, for example with
+//! [`partition_elements_by_coordinate_bisection`](crate::mesh::partition::partition_elements_by_coordinate_bisection),
+//! each partition holds the nodes of its own elements. Nodes on the interface between
+//! partitions are shared by several partitions. Each node is *owned* by exactly one partition,
+//! namely the partition with the smallest index among the partitions that contain it. In every
+//! other partition that contains it, it is a *ghost* node.
+//!
+//! A [`NodeHalo`] describes the nodes of a single partition in a local numbering, in which the
+//! owned nodes come first, together with the communication lists that are needed to keep the
+//! values at ghost nodes consistent with the values at their owners. Nodal vectors are
+//! exchanged between partitions through a [`HaloTransport`]:
+//!
+//! - [`NodeHalo::exchange`] sends the values of owned nodes to all partitions in which they are
+//!   ghosts, which is for example needed before evaluating a residual.
+//! - [`NodeHalo::accumulate`] sends the values of ghost nodes to their owners, where they are
+//!   added to the owned values, which is for example needed after assembling local
+//!   contributions to a global vector.
+//!
+//! [`ChannelTransport`] implements [`HaloTransport`] for partitions that live in different
+//! threads of the same process, and is currently the only provided transport. In particular,
+//! fenris does not provide an MPI transport for distributed memory, since it does not depend on
+//! an MPI implementation. Applications that run partitions in separate MPI processes must
+//! implement [`HaloTransport`] themselves, e.g. with non-blocking point-to-point messages, where
+//! the partition index is the rank in the communicator. Since every partition first posts all
+//! of its sends and only then its receives, [`HaloTransport::send`] must not block until the
+//! matching receive has been posted.
+use crate::connectivity::{Connectivity, ConnectivityMut};
+use crate::mesh::Mesh;
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{ClosedAdd, DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Scalar};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Transport of messages between the partitions of a partitioned mesh.
+///
+/// Messages between any two partitions must be delivered in the order in which they were sent.
+pub trait HaloTransport<T> {
+    /// The index of the partition that this transport sends from and receives to.
+    fn partition(&self) -> usize;
+
+    /// Sends data to the given partition.
+    ///
+    /// This must not block until the data is received.
+    fn send(&mut self, destination: usize, data: Vec<T>) -> eyre::Result<()>;
+
+    /// Receives the next message from the given partition, blocking until it is available.
+    fn receive(&mut self, source: usize) -> eyre::Result<Vec<T>>;
+}
+
+/// A [`HaloTransport`] for partitions that live in different threads of the same process,
+/// based on channels.
+#[derive(Debug)]
+pub struct ChannelTransport<T> {
+    partition: usize,
+    senders: Vec<Sender<Vec<T>>>,
+    receivers: Vec<Receiver<Vec<T>>>,
+}
+
+impl<T> ChannelTransport<T> {
+    /// Creates connected transports for the given number of partitions, where the transport
+    /// with index `i` belongs to partition `i`.
+    ///
+    /// The transports are typically moved to different threads.
+    pub fn create_group(num_partitions: usize) -> Vec<Self> {
+        let mut senders: Vec<Vec<_>> = (0..num_partitions).map(|_| Vec::new()).collect();
+        let mut receivers: Vec<Vec<_>> = (0..num_partitions).map(|_| Vec::new()).collect();
+        for source_senders in &mut senders {
+            for destination_receivers in &mut receivers {
+                let (sender, receiver) = channel();
+                source_senders.push(sender);
+                destination_receivers.push(receiver);
+            }
+        }
+        senders
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(partition, (senders, receivers))| Self {
+                partition,
+                senders,
+                receivers,
+            })
+            .collect()
+    }
+}
+
+impl<T> HaloTransport<T> for ChannelTransport<T> {
+    fn partition(&self) -> usize {
+        self.partition
+    }
+
+    fn send(&mut self, destination: usize, data: Vec<T>) -> eyre::Result<()> {
+        self.senders
+            .get(destination)
+            .ok_or_else(|| eyre!("Destination partition {destination} does not exist"))?
+            .send(data)
+            .map_err(|_| eyre!("Partition {destination} is no longer receiving"))
+    }
+
+    fn receive(&mut self, source: usize) -> eyre::Result<Vec<T>> {
+        self.receivers
+            .get(source)
+            .ok_or_else(|| eyre!("Source partition {source} does not exist"))?
+            .recv()
+            .map_err(|_| eyre!("Partition {source} is no longer sending"))
+    }
+}
+
+/// Communication lists between a partition and one of its neighbors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HaloNeighbor {
+    partition: usize,
+    send_nodes: Vec<usize>,
+    receive_nodes: Vec<usize>,
+}
+
+impl HaloNeighbor {
+    /// The index of the neighboring partition.
+    pub fn partition(&self) -> usize {
+        self.partition
+    }
+
+    /// Local indices of the owned nodes that are ghosts in the neighbor.
+    pub fn send_nodes(&self) -> &[usize] {
+        &self.send_nodes
+    }
+
+    /// Local indices of the ghost nodes that are owned by the neighbor.
+    pub fn receive_nodes(&self) -> &[usize] {
+        &self.receive_nodes
+    }
+}
+
+/// The nodes of a single partition of a partitioned mesh and the communication lists for
+/// their halo exchange.
+///
+/// Local node indices are ordered such that the owned nodes come first, ordered by global
+/// index, followed by the ghost nodes, ordered by owner and then by global index. Nodal
+/// vectors are stored node by node, i.e. for a field with solution dimension $s$, the values
+/// of local node $I$ are stored in the entries $s I, \dots, s I + s - 1$.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeHalo {
+    partition: usize,
+    elements: Vec<usize>,
+    global_nodes: Vec<usize>,
+    num_owned_nodes: usize,
+    neighbors: Vec<HaloNeighbor>,
+}
+
+impl NodeHalo {
+    /// Constructs the halos of all partitions of a mesh, where `element_partition[i]` is the
+    /// index of the partition that contains element `i`.
+    ///
+    /// The number of partitions is one more than the largest partition index. Nodes that are
+    /// not referenced by any element do not belong to any partition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of the partition does not match the number of elements
+    /// in the mesh.
+    pub fn from_element_partition<T, D, C>(mesh: &Mesh<T, D, C>, element_partition: &[usize]) -> eyre::Result<Vec<Self>>
+    where
+        T: Scalar,
+        D: DimName,
+        C: Connectivity,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let connectivity = mesh.connectivity();
+        if element_partition.len() != connectivity.len() {
+            return Err(eyre!(
+                "Element partition has {} entries, but the mesh has {} elements",
+                element_partition.len(),
+                connectivity.len()
+            ));
+        }
+        let num_partitions = element_partition
+            .iter()
+            .max()
+            .map(|&max| max + 1)
+            .unwrap_or(0);
+
+        let mut partition_elements = vec![Vec::new(); num_partitions];
+        let mut owners = vec![usize::MAX; mesh.vertices().len()];
+        for (element, (conn, &part)) in connectivity.iter().zip(element_partition).enumerate() {
+            partition_elements[part].push(element);
+            for &node in conn.vertex_indices() {
+                owners[node] = owners[node].min(part);
+            }
+        }
+
+        let mut halos: Vec<_> = partition_elements
+            .into_iter()
+            .enumerate()
+            .map(|(partition, elements)| {
+                let mut nodes: Vec<usize> = elements
+                    .iter()
+                    .flat_map(|&element| connectivity[element].vertex_indices())
+                    .copied()
+                    .collect();
+                nodes.sort_unstable();
+                nodes.dedup();
+                let (mut global_nodes, mut ghosts): (Vec<_>, Vec<_>) = nodes
+                    .into_iter()
+                    .partition(|&node| owners[node] == partition);
+                let num_owned_nodes = global_nodes.len();
+                ghosts.sort_unstable_by_key(|&node| (owners[node], node));
+                global_nodes.extend(ghosts);
+                Self {
+                    partition,
+                    elements,
+                    global_nodes,
+                    num_owned_nodes,
+                    neighbors: Vec::new(),
+                }
+            })
+            .collect();
+
+        // sends[p][q] holds the (global) nodes that partition p sends to partition q
+        let mut sends = vec![vec![Vec::new(); num_partitions]; num_partitions];
+        let mut receives = vec![vec![Vec::new(); num_partitions]; num_partitions];
+        for halo in &halos {
+            for (local_index, &node) in halo
+                .global_nodes
+                .iter()
+                .enumerate()
+                .skip(halo.num_owned_nodes)
+            {
+                let owner = owners[node];
+                sends[owner][halo.partition].push(node);
+                receives[halo.partition][owner].push(local_index);
+            }
+        }
+
+        for ((halo, sends), receives) in halos.iter_mut().zip(sends).zip(receives) {
+            let owned_nodes = &halo.global_nodes[..halo.num_owned_nodes];
+            halo.neighbors = sends
+                .into_iter()
+                .zip(receives)
+                .enumerate()
+                .filter(|(_, (send, receive))| !send.is_empty() || !receive.is_empty())
+                .map(|(partition, (send, receive_nodes))| HaloNeighbor {
+                    partition,
+                    send_nodes: send
+                        .iter()
+                        .map(|node| {
+                            owned_nodes
+                                .binary_search(node)
+                                .expect("Sent nodes are owned by the sending partition")
+                        })
+                        .collect(),
+                    receive_nodes,
+                })
+                .collect();
+        }
+        Ok(halos)
+    }
+
+    /// The index of the partition.
+    pub fn partition(&self) -> usize {
+        self.partition
+    }
+
+    /// The (global) indices of the elements in the partition.
+    pub fn elements(&self) -> &[usize] {
+        &self.elements
+    }
+
+    /// The global index of each local node.
+    pub fn global_nodes(&self) -> &[usize] {
+        &self.global_nodes
+    }
+
+    /// The global indices of the owned nodes, which are the first local nodes.
+    pub fn owned_nodes(&self) -> &[usize] {
+        &self.global_nodes[..self.num_owned_nodes]
+    }
+
+    /// The global indices of the ghost nodes, which are the last local nodes.
+    pub fn ghost_nodes(&self) -> &[usize] {
+        &self.global_nodes[self.num_owned_nodes..]
+    }
+
+    pub fn num_local_nodes(&self) -> usize {
+        self.global_nodes.len()
+    }
+
+    pub fn num_owned_nodes(&self) -> usize {
+        self.num_owned_nodes
+    }
+
+    /// The partitions that this partition communicates with, ordered by partition index.
+    pub fn neighbors(&self) -> &[HaloNeighbor] {
+        &self.neighbors
+    }
+
+    /// Extracts the elements of the partition as a mesh in the local node numbering.
+    pub fn extract_local_mesh<T, D, C>(&self, mesh: &Mesh<T, D, C>) -> Mesh<T, D, C>
+    where
+        T: Scalar,
+        D: DimName,
+        C: ConnectivityMut,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let mut global_to_local = vec![usize::MAX; mesh.vertices().len()];
+        for (local, &global) in self.global_nodes.iter().enumerate() {
+            global_to_local[global] = local;
+        }
+        let vertices = self
+            .global_nodes
+            .iter()
+            .map(|&node| mesh.vertices()[node].clone())
+            .collect();
+        let connectivity = self
+            .elements
+            .iter()
+            .map(|&element| {
+                let mut conn = mesh.connectivity()[element].clone();
+                for node in conn.vertex_indices_mut() {
+                    *node = global_to_local[*node];
+                }
+                conn
+            })
+            .collect();
+        Mesh::from_vertices_and_connectivity(vertices, connectivity)
+    }
+
+    /// Gathers the values of all local nodes from a global nodal vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the global vector does not cover all local nodes.
+    pub fn gather_from_global<'a, T: Scalar>(
+        &self,
+        global: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> DVector<T> {
+        let global = global.into();
+        let s = solution_dim;
+        DVector::from_iterator(
+            s * self.num_local_nodes(),
+            self.global_nodes
+                .iter()
+                .flat_map(|&node| (0..s).map(move |i| s * node + i))
+                .map(|index| global[index].clone()),
+        )
+    }
+
+    /// Writes the values of the owned nodes of a local nodal vector into a global nodal vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the local vector does not match the number of local nodes,
+    /// or if the global vector does not cover all owned nodes.
+    pub fn scatter_owned_to_global<'a, 'b, T: Scalar>(
+        &self,
+        local: impl Into<DVectorView<'a, T>>,
+        global: impl Into<DVectorViewMut<'b, T>>,
+        solution_dim: usize,
+    ) {
+        let (local, mut global) = (local.into(), global.into());
+        let s = solution_dim;
+        self.assert_local_dimension(local.len(), s);
+        for (local_index, &node) in self.owned_nodes().iter().enumerate() {
+            global
+                .rows_mut(s * node, s)
+                .copy_from(&local.rows(s * local_index, s));
+        }
+    }
+
+    fn assert_local_dimension(&self, len: usize, solution_dim: usize) {
+        assert_eq!(
+            len,
+            solution_dim * self.num_local_nodes(),
+            "Dimension of local vector must match number of local nodes"
+        );
+    }
+
+    fn check_transport<T>(&self, transport: &impl HaloTransport<T>) -> eyre::Result<()> {
+        if transport.partition() != self.partition {
+            return Err(eyre!(
+                "Transport belongs to partition {}, but the halo belongs to partition {}",
+                transport.partition(),
+                self.partition
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sends the values of the owned nodes to all partitions in which they are ghosts, and
+    /// overwrites the values of the ghost nodes with the values received from their owners.
+    ///
+    /// All partitions must participate in the exchange.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport does not belong to this partition, if communication
+    /// fails or if a received message does not have the expected size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the local vector does not match the number of local nodes.
+    pub fn exchange<'a, T: Scalar>(
+        &self,
+        transport: &mut impl HaloTransport<T>,
+        values: impl Into<DVectorViewMut<'a, T>>,
+        solution_dim: usize,
+    ) -> eyre::Result<()> {
+        let mut values = values.into();
+        self.assert_local_dimension(values.len(), solution_dim);
+        self.check_transport(transport)?;
+        for neighbor in self.neighbors.iter().filter(|n| !n.send_nodes.is_empty()) {
+            let data = gather_nodal_values(&values, &neighbor.send_nodes, solution_dim);
+            transport.send(neighbor.partition, data)?;
+        }
+        for neighbor in self
+            .neighbors
+            .iter()
+            .filter(|n| !n.receive_nodes.is_empty())
+        {
+            let data = receive_checked(
+                transport,
+                neighbor.partition,
+                neighbor.receive_nodes.len() * solution_dim,
+            )?;
+            for (&node, node_data) in neighbor
+                .receive_nodes
+                .iter()
+                .zip(data.chunks_exact(solution_dim))
+            {
+                for (i, value) in node_data.iter().enumerate() {
+                    values[solution_dim * node + i] = value.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the values of the ghost nodes to their owners, where they are added to the values
+    /// of the owned nodes.
+    ///
+    /// This is typically used after assembling local contributions of each partition into a
+    /// local vector. Afterwards, the owned values hold the complete sums, whereas the ghost
+    /// values are unchanged. Use [`exchange`](Self::exchange) to make the ghost values
+    /// consistent.
+    ///
+    /// All partitions must participate in the accumulation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport does not belong to this partition, if communication
+    /// fails or if a received message does not have the expected size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the local vector does not match the number of local nodes.
+    pub fn accumulate<'a, T: Scalar + ClosedAdd>(
+        &self,
+        transport: &mut impl HaloTransport<T>,
+        values: impl Into<DVectorViewMut<'a, T>>,
+        solution_dim: usize,
+    ) -> eyre::Result<()> {
+        let mut values = values.into();
+        self.assert_local_dimension(values.len(), solution_dim);
+        self.check_transport(transport)?;
+        for neighbor in self
+            .neighbors
+            .iter()
+            .filter(|n| !n.receive_nodes.is_empty())
+        {
+            let data = gather_nodal_values(&values, &neighbor.receive_nodes, solution_dim);
+            transport.send(neighbor.partition, data)?;
+        }
+        for neighbor in self.neighbors.iter().filter(|n| !n.send_nodes.is_empty()) {
+            let data = receive_checked(transport, neighbor.partition, neighbor.send_nodes.len() * solution_dim)?;
+            for (&node, node_data) in neighbor
+                .send_nodes
+                .iter()
+                .zip(data.chunks_exact(solution_dim))
+            {
+                for (i, value) in node_data.iter().enumerate() {
+                    values[solution_dim * node + i] += value.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn gather_nodal_values<T: Scalar>(values: &DVectorViewMut<T>, nodes: &[usize], solution_dim: usize) -> Vec<T> {
+    nodes
+        .iter()
+        .flat_map(|&node| (0..solution_dim).map(move |i| solution_dim * node + i))
+        .map(|index| values[index].clone())
+        .collect()
+}
+
+fn receive_checked<T>(
+    transport: &mut impl HaloTransport<T>,
+    source: usize,
+    expected_len: usize,
+) -> eyre::Result<Vec<T>> {
+    let data = transport.receive(source)?;
+    if data.len() != expected_len {
+        return Err(eyre!(
+            "Received {} values from partition {source}, but expected {expected_len}",
+            data.len()
+        ));
+    }
+    Ok(data)
+}
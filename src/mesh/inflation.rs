@@ -0,0 +1,159 @@
+//! Generation of boundary layer meshes by inflation of surface meshes.
+//!
+//! Boundary layers, in which the solution varies rapidly in the direction normal to a surface,
+//! are best resolved by thin layers of elements aligned with the surface. Such layers are
+//! generated by *inflating* a surface mesh, i.e. by offsetting its vertices along the surface
+//! normals a number of times. The thickness of the layers grows geometrically with the distance
+//! to the surface, so that the first layer can be very thin while the outer layers transition
+//! smoothly to the element size of the adjacent volume mesh.
+//!
+//! Each triangle of the surface mesh sweeps out a triangular prism in each layer. Since `fenris`
+//! does not have prismatic elements, each prism is split into three tetrahedra. The split is
+//! chosen based on the global vertex indices, which guarantees that the resulting mesh is
+//! conforming.
+use crate::connectivity::{Connectivity, Tet4Connectivity};
+use crate::mesh::{Tet4Mesh, TriangleMesh3d};
+use crate::Real;
+use nalgebra::{Matrix3, Point3, Vector3};
+
+/// Computes the thicknesses of `num_layers` boundary layers whose thickness grows geometrically
+/// with the given growth rate, starting with the given thickness of the first layer.
+pub fn boundary_layer_thicknesses<T: Real>(first_layer_thickness: T, growth_rate: T, num_layers: usize) -> Vec<T> {
+    let mut thickness = first_layer_thickness;
+    let mut thicknesses = Vec::with_capacity(num_layers);
+    for _ in 0..num_layers {
+        thicknesses.push(thickness);
+        thickness *= growth_rate;
+    }
+    thicknesses
+}
+
+/// Grows boundary layers of tetrahedra from a triangle surface mesh.
+///
+/// The surface vertices are offset along the vertex normals, which are computed as the
+/// area-weighted average of the normals of the adjacent triangles. The normal of a triangle
+/// with vertices $a$, $b$, $c$ points in the direction of $(b - a) \times (c - a)$, so the
+/// layers grow towards the side given by the orientation of the surface triangles. The thickness
+/// of layer $k = 0, 1, \dots$ is $h_0 r^k$, where $h_0$ is the thickness of the first layer and
+/// $r$ is the growth rate.
+///
+/// The vertices of layer $k$ (where layer $0$ is the surface itself) are stored contiguously
+/// starting at index $k n$, where $n$ is the number of surface vertices. Every prism between two
+/// consecutive layers is split into three tetrahedra with positive orientation.
+///
+/// Since the vertices are offset independently, the total thickness of the layers should
+/// be small compared to the radius of curvature of the surface and the size of the surface
+/// elements. Otherwise the layers might intersect themselves.
+///
+/// # Panics
+///
+/// Panics if the thickness of the first layer or the growth rate is not positive,
+/// or if the normal of a surface vertex is not well defined.
+pub fn inflate_boundary_layers<T: Real>(
+    surface: &TriangleMesh3d<T>,
+    first_layer_thickness: T,
+    growth_rate: T,
+    num_layers: usize,
+) -> Tet4Mesh<T> {
+    assert!(
+        first_layer_thickness > T::zero(),
+        "Thickness of first layer must be positive"
+    );
+    assert!(growth_rate > T::zero(), "Growth rate must be positive");
+
+    let surface_vertices = surface.vertices();
+    let n = surface_vertices.len();
+
+    // The cross product of two edges has a magnitude of twice the triangle area, so summing
+    // the cross products automatically weighs the normals by area
+    let mut normals = vec![Vector3::zeros(); n];
+    for conn in surface.connectivity() {
+        let [a, b, c] = conn.0.map(|i| surface_vertices[i]);
+        let area_normal = (b - a).cross(&(c - a));
+        for &i in conn.vertex_indices() {
+            normals[i] += area_normal;
+        }
+    }
+    let referenced_by_triangle = {
+        let mut referenced = vec![false; n];
+        for &i in surface.connectivity().iter().flat_map(|conn| &conn.0) {
+            referenced[i] = true;
+        }
+        referenced
+    };
+    for (normal, &referenced) in normals.iter_mut().zip(&referenced_by_triangle) {
+        if referenced {
+            *normal = normal
+                .try_normalize(T::zero())
+                .expect("Vertex normal of surface mesh is not well defined");
+        }
+    }
+
+    let mut vertices = surface_vertices.to_vec();
+    let mut offset = T::zero();
+    for thickness in boundary_layer_thicknesses(first_layer_thickness, growth_rate, num_layers) {
+        offset += thickness;
+        vertices.extend(
+            surface_vertices
+                .iter()
+                .zip(&normals)
+                .map(|(x, normal)| x + normal * offset),
+        );
+    }
+
+    let mut connectivity = Vec::with_capacity(3 * num_layers * surface.connectivity().len());
+    for layer in 0..num_layers {
+        for conn in surface.connectivity() {
+            let bottom = conn.0.map(|i| layer * n + i);
+            let top = conn.0.map(|i| (layer + 1) * n + i);
+            for tet in split_prism_into_tetrahedra(bottom, top) {
+                connectivity.push(positively_oriented_tet(tet, &vertices));
+            }
+        }
+    }
+
+    Tet4Mesh::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+/// Splits the prism with the given bottom and top triangles into three tetrahedra.
+///
+/// Every quadrilateral face of the prism is split along the diagonal that contains the vertex
+/// with the smallest global index among the vertices of the face, so that adjacent prisms
+/// are split conformingly. See Dompierre et al., "How to Subdivide Pyramids, Prisms and
+/// Hexahedra into Tetrahedra", 8th International Meshing Roundtable (1999).
+fn split_prism_into_tetrahedra(bottom: [usize; 3], top: [usize; 3]) -> [[usize; 4]; 3] {
+    // Rotate the prism so that the vertex with the smallest index comes first. Since the indices
+    // of the top vertices are larger than those of the bottom vertices, this vertex is always
+    // in the bottom triangle.
+    let first = (0..3).min_by_key(|&i| bottom[i]).unwrap();
+    let v = [
+        bottom[first],
+        bottom[(first + 1) % 3],
+        bottom[(first + 2) % 3],
+        top[first],
+        top[(first + 1) % 3],
+        top[(first + 2) % 3],
+    ];
+    if v[1].min(v[5]) < v[2].min(v[4]) {
+        [
+            [v[0], v[1], v[2], v[5]],
+            [v[0], v[1], v[5], v[4]],
+            [v[0], v[4], v[5], v[3]],
+        ]
+    } else {
+        [
+            [v[0], v[1], v[2], v[4]],
+            [v[0], v[4], v[2], v[5]],
+            [v[0], v[4], v[5], v[3]],
+        ]
+    }
+}
+
+fn positively_oriented_tet<T: Real>(mut tet: [usize; 4], vertices: &[Point3<T>]) -> Tet4Connectivity {
+    let [a, b, c, d] = tet.map(|i| vertices[i]);
+    let edges = Matrix3::from_columns(&[b - a, c - a, d - a]);
+    if edges.determinant() < T::zero() {
+        tet.swap(1, 2);
+    }
+    Tet4Connectivity(tet)
+}
@@ -0,0 +1,101 @@
+//! Partitioning of mesh elements into subdomains.
+use crate::connectivity::Connectivity;
+use crate::mesh::Mesh;
+use crate::Real;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint};
+
+/// Partitions the elements of a mesh into the given number of parts by recursive coordinate
+/// bisection of the element centroids.
+///
+/// The elements are recursively split along the coordinate axis in which the centroids have
+/// the largest extent, with the split position chosen such that the number of elements in each
+/// half is proportional to the number of parts assigned to it. The resulting parts therefore
+/// differ in size by at most one element per level of recursion, and are typically compact,
+/// although they are not guaranteed to be connected.
+///
+/// Returns the part index of each element. The result is deterministic. If there are fewer
+/// elements than parts, some parts are empty.
+///
+/// # Panics
+///
+/// Panics if `num_parts` is zero.
+pub fn partition_elements_by_coordinate_bisection<T, D, C>(mesh: &Mesh<T, D, C>, num_parts: usize) -> Vec<usize>
+where
+    T: Real,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    assert!(num_parts > 0, "Number of parts must be positive");
+    let centroids: Vec<OPoint<T, D>> = mesh
+        .connectivity()
+        .iter()
+        .map(|conn| {
+            let vertices = conn.vertex_indices();
+            let sum = vertices.iter().fold(OPoint::<T, D>::origin(), |sum, &v| {
+                sum + mesh.vertices()[v].coords.clone()
+            });
+            sum / T::from_usize(vertices.len().max(1)).unwrap()
+        })
+        .collect();
+
+    let mut partition = vec![0; centroids.len()];
+    let mut elements: Vec<_> = (0..centroids.len()).collect();
+    bisect_recursively(&centroids, &mut elements, 0, num_parts, &mut partition);
+    partition
+}
+
+fn bisect_recursively<T, D>(
+    centroids: &[OPoint<T, D>],
+    elements: &mut [usize],
+    first_part: usize,
+    num_parts: usize,
+    partition: &mut [usize],
+) where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    if num_parts == 1 || elements.len() <= 1 {
+        for &element in elements.iter() {
+            partition[element] = first_part;
+        }
+        return;
+    }
+
+    let axis = (0..D::dim())
+        .max_by(|&a, &b| {
+            let extent = |axis: usize| {
+                let (min, max) = elements
+                    .iter()
+                    .map(|&element| centroids[element][axis])
+                    .fold((T::max_value().unwrap(), T::min_value().unwrap()), |(min, max), x| {
+                        (min.min(x), max.max(x))
+                    });
+                max - min
+            };
+            extent(a).partial_cmp(&extent(b)).unwrap()
+        })
+        .unwrap_or(0);
+
+    // Sort by coordinate, breaking ties by index for determinism
+    elements.sort_unstable_by(|&a, &b| {
+        centroids[a][axis]
+            .partial_cmp(&centroids[b][axis])
+            .unwrap()
+            .then(a.cmp(&b))
+    });
+
+    let left_parts = num_parts / 2;
+    let split = elements.len() * left_parts / num_parts;
+    let (left, right) = elements.split_at_mut(split);
+    bisect_recursively(centroids, left, first_part, left_parts, partition);
+    bisect_recursively(
+        centroids,
+        right,
+        first_part + left_parts,
+        num_parts - left_parts,
+        partition,
+    );
+}
@@ -1,15 +1,26 @@
 //! Basic procedural mesh generation routines.
-use crate::connectivity::{Hex8Connectivity, Quad4d2Connectivity, Tet4Connectivity};
+use crate::connectivity::{
+    ConnectivityMut, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri6d2Connectivity,
+};
 use crate::geometry::polymesh::PolyMesh3d;
 use crate::geometry::sdf::BoundedSdf;
 use crate::geometry::{AxisAlignedBoundingBox2d, HalfSpace};
-use crate::mesh::{HexMesh, Mesh, QuadMesh2d, Tet4Mesh, TriangleMesh2d};
+use crate::mesh::{
+    Hex20Mesh, Hex27Mesh, HexMesh, Mesh, Mesh2d, Mesh3d, Quad9Mesh2d, QuadMesh2d, Tet10Mesh, Tet4Mesh, Tri6Mesh2d,
+    TriangleMesh2d,
+};
 use crate::Real;
 use itertools::{iproduct, Itertools};
-use nalgebra::{convert, point, try_convert, vector, Point2, Point3, Unit, Vector2, Vector3};
+use nalgebra::allocator::Allocator;
+use nalgebra::{
+    convert, point, try_convert, vector, DefaultAllocator, DimName, OPoint, Point2, Point3, Unit, Vector2, Vector3, U2,
+    U3,
+};
 use numeric_literals::replace_float_literals;
 use ordered_float::NotNan;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::f64::consts::PI;
 
 pub fn create_unit_square_uniform_quad_mesh_2d<T>(cells_per_dim: usize) -> QuadMesh2d<T>
@@ -402,6 +413,492 @@ where
     Mesh::from_vertices_and_connectivity(vertices, connectivity)
 }
 
+/// The subdivision of each cube of a structured grid into tetrahedra.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CubeTetSplit {
+    /// Splits each cube into six tetrahedra sharing the diagonal from the lowest to the highest
+    /// corner (Kuhn/Freudenthal subdivision).
+    ///
+    /// All tetrahedra are congruent, and the same split is used for every cube.
+    #[default]
+    Six,
+    /// Splits each cube into a central tetrahedron and four corner tetrahedra.
+    ///
+    /// The orientation of the split alternates between neighboring cubes so that the face
+    /// diagonals of adjacent cubes match.
+    Five,
+}
+
+/// Creates a structured tetrahedral mesh of an axis-aligned box.
+///
+/// The box is given by `[0, e_x] x [0, e_y] x [0, e_z]`, where `extents` $= (e_x, e_y, e_z)$.
+/// It is divided into a grid of `resolutions[0] x resolutions[1] x resolutions[2]` cuboids,
+/// each of which is split into tetrahedra according to `split`. The vertices are those of the
+/// grid, ordered with the $x$-index running fastest, and all tetrahedra are positively oriented,
+/// i.e. the Jacobian of the map from the reference element has positive determinant.
+///
+/// Returns an empty mesh if any of the resolutions is zero.
+pub fn create_rectangular_uniform_tet_mesh_3d<T>(
+    extents: &Vector3<T>,
+    resolutions: [usize; 3],
+    split: CubeTetSplit,
+) -> Tet4Mesh<T>
+where
+    T: Real,
+{
+    if resolutions.contains(&0) {
+        return Mesh::from_vertices_and_connectivity(vec![], vec![]);
+    }
+
+    let [cx, cy, cz] = resolutions;
+    let [vx, vy, vz] = resolutions.map(|num_cells| num_cells + 1);
+    let coordinate = |axis: usize, index: usize| {
+        extents[axis] * T::from_usize(index).unwrap() / T::from_usize(resolutions[axis]).unwrap()
+    };
+    let vertices = iproduct!(0..vz, 0..vy, 0..vx)
+        .map(|(k, j, i)| point![coordinate(0, i), coordinate(1, j), coordinate(2, k)])
+        .collect();
+
+    let vertex_to_global_idx = |[i, j, k]: [usize; 3]| (vx * vy) * k + vx * j + i;
+
+    // Tetrahedra are given by local corner offsets of a cube, and flipped if necessary to
+    // obtain a positive orientation
+    let push_tet = |connectivity: &mut Vec<_>, cell: [usize; 3], corners: [[usize; 3]; 4]| {
+        let edge = |a: [usize; 3], b: [usize; 3]| Vector3::from_fn(|d, _| b[d] as i64 - a[d] as i64);
+        let [c0, c1, c2, c3] = corners;
+        let signed_volume = edge(c0, c1).cross(&edge(c0, c2)).dot(&edge(c0, c3));
+        let [c0, c1, c2, c3] = if signed_volume > 0 {
+            [c0, c1, c2, c3]
+        } else {
+            [c0, c1, c3, c2]
+        };
+        connectivity.push(Tet4Connectivity(
+            [c0, c1, c2, c3].map(|c| vertex_to_global_idx([cell[0] + c[0], cell[1] + c[1], cell[2] + c[2]])),
+        ));
+    };
+
+    let mut connectivity = Vec::new();
+    for (k, j, i) in iproduct!(0..cz, 0..cy, 0..cx) {
+        let cell = [i, j, k];
+        match split {
+            CubeTetSplit::Six => {
+                // Each permutation of the axes defines a monotone path from [0, 0, 0] to [1, 1, 1]
+                for axes in [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+                    let mut corners = [[0; 3]; 4];
+                    for (n, &axis) in axes.iter().enumerate() {
+                        corners[n + 1] = corners[n];
+                        corners[n + 1][axis] = 1;
+                    }
+                    push_tet(&mut connectivity, cell, corners);
+                }
+            }
+            CubeTetSplit::Five => {
+                // The central tetrahedron connects the corners with even global index sum,
+                // so that the face diagonals of neighboring cubes coincide
+                let is_central = |[a, b, c]: [usize; 3]| (i + j + k + a + b + c) % 2 == 0;
+                let corners: Vec<[usize; 3]> = iproduct!(0..2, 0..2, 0..2)
+                    .map(|(c, b, a)| [a, b, c])
+                    .collect();
+                let central: Vec<_> = corners.iter().copied().filter(|&c| is_central(c)).collect();
+                push_tet(
+                    &mut connectivity,
+                    cell,
+                    [central[0], central[1], central[2], central[3]],
+                );
+                for &corner in corners.iter().filter(|&&c| !is_central(c)) {
+                    let neighbor = |axis: usize| {
+                        let mut neighbor = corner;
+                        neighbor[axis] = 1 - neighbor[axis];
+                        neighbor
+                    };
+                    push_tet(&mut connectivity, cell, [corner, neighbor(0), neighbor(1), neighbor(2)]);
+                }
+            }
+        }
+    }
+
+    Mesh::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+/// Creates a structured quadratic tetrahedral mesh of an axis-aligned box.
+///
+/// The mesh is obtained by promoting the elements of the mesh created by
+/// [`create_rectangular_uniform_tet_mesh_3d`] to `Tet10` elements.
+pub fn create_rectangular_uniform_tet10_mesh_3d<T>(
+    extents: &Vector3<T>,
+    resolutions: [usize; 3],
+    split: CubeTetSplit,
+) -> Tet10Mesh<T>
+where
+    T: Real,
+{
+    Tet10Mesh::from(&create_rectangular_uniform_tet_mesh_3d(extents, resolutions, split))
+}
+
+/// Connectivities for which structured meshes of the unit square or unit cube can be generated.
+///
+/// This is used by the parametric mesh generators such as [`create_annulus_mesh_2d`]: the
+/// structured mesh is generated in the parameter domain, including any higher-order nodes,
+/// and all nodes are then mapped onto the physical domain. In particular, higher-order nodes
+/// lie exactly on curved boundaries.
+pub trait StructuredConnectivity<T, D>: ConnectivityMut
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Creates a structured mesh of $[0, 1]^D$ with `resolutions[i]` cells along axis `i`.
+    ///
+    /// The cells are positively oriented. Tetrahedral meshes use [`CubeTetSplit::Six`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of resolutions does not match the dimension, or if any of the
+    /// resolutions is zero.
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Mesh<T, D, Self>;
+}
+
+/// Marker trait for tensor-product (quadrilateral and hexahedral) connectivities.
+///
+/// Structured meshes of these connectivities are symmetric with respect to reflections of the
+/// parameter domain, which allows several parametric patches to be glued together
+/// conformingly, as in [`create_spherical_shell_mesh_3d`].
+pub trait TensorProductConnectivity<T, D>: StructuredConnectivity<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+}
+
+fn unit_grid_coordinate<T: Real>(index: usize, num_cells: usize) -> T {
+    T::from_usize(index).unwrap() / T::from_usize(num_cells).unwrap()
+}
+
+fn create_unit_quad_grid_mesh<T: Real>(resolutions: &[usize]) -> QuadMesh2d<T> {
+    let &[cx, cy] = resolutions else {
+        panic!("Expected two resolutions for a two-dimensional grid")
+    };
+    assert!(cx > 0 && cy > 0, "Resolutions must be positive");
+    let vertices = iproduct!(0..=cy, 0..=cx)
+        .map(|(j, i)| point![unit_grid_coordinate(i, cx), unit_grid_coordinate(j, cy)])
+        .collect();
+    let idx = |i: usize, j: usize| (cx + 1) * j + i;
+    let cells = iproduct!(0..cy, 0..cx)
+        .map(|(j, i)| Quad4d2Connectivity([idx(i, j), idx(i + 1, j), idx(i + 1, j + 1), idx(i, j + 1)]))
+        .collect();
+    Mesh::from_vertices_and_connectivity(vertices, cells)
+}
+
+fn create_unit_hex_grid_mesh<T: Real>(resolutions: &[usize]) -> HexMesh<T> {
+    let &[cx, cy, cz] = resolutions else {
+        panic!("Expected three resolutions for a three-dimensional grid")
+    };
+    assert!(cx > 0 && cy > 0 && cz > 0, "Resolutions must be positive");
+    let vertices = iproduct!(0..=cz, 0..=cy, 0..=cx)
+        .map(|(k, j, i)| {
+            point![
+                unit_grid_coordinate(i, cx),
+                unit_grid_coordinate(j, cy),
+                unit_grid_coordinate(k, cz)
+            ]
+        })
+        .collect();
+    let idx = |i: usize, j: usize, k: usize| (cx + 1) * (cy + 1) * k + (cx + 1) * j + i;
+    let cells = iproduct!(0..cz, 0..cy, 0..cx)
+        .map(|(k, j, i)| {
+            Hex8Connectivity([
+                idx(i, j, k),
+                idx(i + 1, j, k),
+                idx(i + 1, j + 1, k),
+                idx(i, j + 1, k),
+                idx(i, j, k + 1),
+                idx(i + 1, j, k + 1),
+                idx(i + 1, j + 1, k + 1),
+                idx(i, j + 1, k + 1),
+            ])
+        })
+        .collect();
+    Mesh::from_vertices_and_connectivity(vertices, cells)
+}
+
+fn create_unit_tet_grid_mesh<T: Real>(resolutions: &[usize]) -> Tet4Mesh<T> {
+    let &[cx, cy, cz] = resolutions else {
+        panic!("Expected three resolutions for a three-dimensional grid")
+    };
+    assert!(cx > 0 && cy > 0 && cz > 0, "Resolutions must be positive");
+    create_rectangular_uniform_tet_mesh_3d(&Vector3::repeat(T::one()), [cx, cy, cz], CubeTetSplit::Six)
+}
+
+impl<T: Real> StructuredConnectivity<T, U2> for Quad4d2Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> QuadMesh2d<T> {
+        create_unit_quad_grid_mesh(resolutions)
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U2> for Quad9d2Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Quad9Mesh2d<T> {
+        Quad9Mesh2d::from(create_unit_quad_grid_mesh(resolutions))
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U2> for Tri3d2Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> TriangleMesh2d<T> {
+        create_unit_quad_grid_mesh(resolutions).split_into_triangles()
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U2> for Tri6d2Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Tri6Mesh2d<T> {
+        Tri6Mesh2d::from(create_unit_quad_grid_mesh(resolutions).split_into_triangles())
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U3> for Hex8Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> HexMesh<T> {
+        create_unit_hex_grid_mesh(resolutions)
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U3> for Hex20Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Hex20Mesh<T> {
+        Hex20Mesh::from(&create_unit_hex_grid_mesh(resolutions))
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U3> for Hex27Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Hex27Mesh<T> {
+        Hex27Mesh::from(&create_unit_hex_grid_mesh(resolutions))
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U3> for Tet4Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Tet4Mesh<T> {
+        create_unit_tet_grid_mesh(resolutions)
+    }
+}
+
+impl<T: Real> StructuredConnectivity<T, U3> for Tet10Connectivity {
+    fn create_unit_grid_mesh(resolutions: &[usize]) -> Tet10Mesh<T> {
+        Tet10Mesh::from(&create_unit_tet_grid_mesh(resolutions))
+    }
+}
+
+impl<T: Real> TensorProductConnectivity<T, U2> for Quad4d2Connectivity {}
+impl<T: Real> TensorProductConnectivity<T, U2> for Quad9d2Connectivity {}
+impl<T: Real> TensorProductConnectivity<T, U3> for Hex8Connectivity {}
+impl<T: Real> TensorProductConnectivity<T, U3> for Hex20Connectivity {}
+impl<T: Real> TensorProductConnectivity<T, U3> for Hex27Connectivity {}
+
+/// Merges vertices that are closer to each other than the given tolerance.
+///
+/// Each vertex is replaced by the first vertex (in the original order) within the tolerance,
+/// and unused vertices are removed.
+fn merge_coincident_vertices<T, D, C>(mesh: Mesh<T, D, C>, tolerance: T) -> Mesh<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: ConnectivityMut,
+    DefaultAllocator: Allocator<T, D>,
+{
+    // Vertices are binned into a uniform grid with cells of the size of the tolerance,
+    // so that coincident vertices are found in the same or a neighboring cell
+    let bin = |v: &OPoint<T, D>| -> Vec<i64> {
+        v.coords
+            .iter()
+            .map(|&x| (x / tolerance).floor().to_subset().unwrap() as i64)
+            .collect()
+    };
+    let neighbor_offsets: Vec<Vec<i64>> = (0..D::dim())
+        .map(|_| -1..=1)
+        .multi_cartesian_product()
+        .collect();
+
+    let (vertices, mut connectivity) = (mesh.vertices().to_vec(), mesh.connectivity().to_vec());
+    let mut bins: HashMap<Vec<i64>, Vec<usize>> = HashMap::new();
+    let mut new_vertices: Vec<OPoint<T, D>> = Vec::new();
+    let mut vertex_map = Vec::with_capacity(vertices.len());
+    for vertex in &vertices {
+        let key = bin(vertex);
+        let existing = neighbor_offsets.iter().find_map(|offset| {
+            let neighbor_key: Vec<i64> = key.iter().zip(offset).map(|(k, o)| k + o).collect();
+            bins.get(&neighbor_key)?
+                .iter()
+                .copied()
+                .find(|&idx| (&new_vertices[idx].coords - &vertex.coords).norm() <= tolerance)
+        });
+        let new_index = existing.unwrap_or_else(|| {
+            new_vertices.push(vertex.clone());
+            bins.entry(key).or_default().push(new_vertices.len() - 1);
+            new_vertices.len() - 1
+        });
+        vertex_map.push(new_index);
+    }
+
+    for cell in &mut connectivity {
+        for idx in cell.vertex_indices_mut() {
+            *idx = vertex_map[*idx];
+        }
+    }
+    Mesh::from_vertices_and_connectivity(new_vertices, connectivity)
+}
+
+/// Creates a mesh of an annulus centered at the origin.
+///
+/// The annulus is bounded by circles with radii `inner_radius` and `outer_radius`, and is
+/// divided into `radial_cells` layers of `circumferential_cells` cells each. The type of cells
+/// (and therefore the element order) is determined by the connectivity `C`, for example
+/// [`Quad4d2Connectivity`] or [`Tri6d2Connectivity`]. All nodes, including higher-order nodes,
+/// are obtained by mapping a structured grid in polar coordinates, so that the nodes on the
+/// boundary lie exactly on the circles.
+///
+/// Returns an empty mesh if any of the resolutions is zero.
+///
+/// # Panics
+///
+/// Panics if the radii do not satisfy `0 < inner_radius < outer_radius`, or if there are fewer
+/// than three circumferential cells.
+pub fn create_annulus_mesh_2d<T, C>(
+    inner_radius: T,
+    outer_radius: T,
+    radial_cells: usize,
+    circumferential_cells: usize,
+) -> Mesh2d<T, C>
+where
+    T: Real,
+    C: StructuredConnectivity<T, U2>,
+{
+    assert!(
+        T::zero() < inner_radius && inner_radius < outer_radius,
+        "Radii must satisfy 0 < inner_radius < outer_radius"
+    );
+    if radial_cells == 0 || circumferential_cells == 0 {
+        return Mesh::from_vertices_and_connectivity(vec![], vec![]);
+    }
+    assert!(circumferential_cells >= 3, "Need at least three circumferential cells");
+
+    let mut mesh = C::create_unit_grid_mesh(&[radial_cells, circumferential_cells]);
+    mesh.transform_all_vertices(|vertices| {
+        for v in vertices {
+            let r = inner_radius + v.x * (outer_radius - inner_radius);
+            let theta = T::two_pi() * v.y;
+            *v = point![r * theta.cos(), r * theta.sin()];
+        }
+    });
+    merge_coincident_vertices(mesh, T::default_epsilon().sqrt() * outer_radius)
+}
+
+/// Creates a mesh of a thick-walled cylinder (a tube) aligned with the $z$-axis.
+///
+/// The cylinder occupies $r_i \leq \sqrt{x^2 + y^2} \leq r_o$, $0 \leq z \leq h$, where
+/// $r_i$ and $r_o$ are the inner and outer radii and $h$ is the height. It is divided into
+/// `radial_cells`, `circumferential_cells` and `axial_cells` layers of cells along the respective
+/// directions. The type of cells (and therefore the element order) is determined by the
+/// connectivity `C`, for example [`Hex27Connectivity`] or [`Tet4Connectivity`]. Higher-order
+/// nodes lie exactly on the curved surfaces.
+///
+/// Returns an empty mesh if any of the resolutions is zero.
+///
+/// # Panics
+///
+/// Panics if the radii do not satisfy `0 < inner_radius < outer_radius`, if the height is not
+/// positive, or if there are fewer than three circumferential cells.
+pub fn create_cylinder_shell_mesh_3d<T, C>(
+    inner_radius: T,
+    outer_radius: T,
+    height: T,
+    radial_cells: usize,
+    circumferential_cells: usize,
+    axial_cells: usize,
+) -> Mesh3d<T, C>
+where
+    T: Real,
+    C: StructuredConnectivity<T, U3>,
+{
+    assert!(
+        T::zero() < inner_radius && inner_radius < outer_radius,
+        "Radii must satisfy 0 < inner_radius < outer_radius"
+    );
+    assert!(height > T::zero(), "Height must be positive");
+    if radial_cells == 0 || circumferential_cells == 0 || axial_cells == 0 {
+        return Mesh::from_vertices_and_connectivity(vec![], vec![]);
+    }
+    assert!(circumferential_cells >= 3, "Need at least three circumferential cells");
+
+    let mut mesh = C::create_unit_grid_mesh(&[radial_cells, circumferential_cells, axial_cells]);
+    mesh.transform_all_vertices(|vertices| {
+        for v in vertices {
+            let r = inner_radius + v.x * (outer_radius - inner_radius);
+            let theta = T::two_pi() * v.y;
+            *v = point![r * theta.cos(), r * theta.sin(), height * v.z];
+        }
+    });
+    merge_coincident_vertices(mesh, T::default_epsilon().sqrt() * outer_radius.max(height))
+}
+
+/// Creates a mesh of a spherical shell centered at the origin.
+///
+/// The shell is bounded by spheres with radii `inner_radius` and `outer_radius`. The mesh is
+/// a "cubed sphere": each of the six faces of a cube is projected onto the sphere with an
+/// equiangular map and divided into `cells_per_face_edge x cells_per_face_edge` cells, and the
+/// shell is divided into `radial_cells` layers. The type of cells (and therefore the element
+/// order) is determined by the tensor-product connectivity `C`, for example
+/// [`Hex8Connectivity`] or [`Hex27Connectivity`]. Higher-order nodes lie exactly on the
+/// spherical surfaces.
+///
+/// Returns an empty mesh if any of the resolutions is zero.
+///
+/// # Panics
+///
+/// Panics if the radii do not satisfy `0 < inner_radius < outer_radius`.
+pub fn create_spherical_shell_mesh_3d<T, C>(
+    inner_radius: T,
+    outer_radius: T,
+    radial_cells: usize,
+    cells_per_face_edge: usize,
+) -> Mesh3d<T, C>
+where
+    T: Real,
+    C: TensorProductConnectivity<T, U3>,
+{
+    assert!(
+        T::zero() < inner_radius && inner_radius < outer_radius,
+        "Radii must satisfy 0 < inner_radius < outer_radius"
+    );
+    if radial_cells == 0 || cells_per_face_edge == 0 {
+        return Mesh::from_vertices_and_connectivity(vec![], vec![]);
+    }
+
+    // Each face of the cube is described by two tangent directions and the outward normal,
+    // which form a right-handed frame so that the mapped cells are positively oriented
+    let [x, y, z] = [Vector3::x(), Vector3::y(), Vector3::z()];
+    let frames = [[y, z, x], [z, y, -x], [z, x, y], [x, z, -y], [x, y, z], [y, x, -z]];
+
+    let patch = C::create_unit_grid_mesh(&[cells_per_face_edge, cells_per_face_edge, radial_cells]);
+    let mut vertices = Vec::new();
+    let mut connectivity = Vec::new();
+    for [e1, e2, n] in frames {
+        let [e1, e2, n] = [e1, e2, n].map(|v| v.cast::<T>());
+        let offset = vertices.len();
+        vertices.extend(patch.vertices().iter().map(|v| {
+            let alpha = T::frac_pi_4() * (v.x + v.x - T::one());
+            let beta = T::frac_pi_4() * (v.y + v.y - T::one());
+            let direction = (n + e1 * alpha.tan() + e2 * beta.tan()).normalize();
+            let r = inner_radius + v.z * (outer_radius - inner_radius);
+            Point3::from(direction * r)
+        }));
+        connectivity.extend(patch.connectivity().iter().map(|cell| {
+            let mut cell = cell.clone();
+            for idx in cell.vertex_indices_mut() {
+                *idx += offset;
+            }
+            cell
+        }));
+    }
+    let mesh = Mesh::from_vertices_and_connectivity(vertices, connectivity);
+    merge_coincident_vertices(mesh, T::default_epsilon().sqrt() * outer_radius)
+}
+
 pub fn create_simple_stupid_sphere(center: &Point3<f64>, radius: f64, num_sweeps: usize) -> PolyMesh3d<f64> {
     assert!(num_sweeps > 0);
 
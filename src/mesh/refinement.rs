@@ -1,16 +1,28 @@
 //! Functionality and abstractions for mesh refinement.
 //!
-//! Currently we only provide uniform refinement for select element types through
-//! [`refine_mesh`] and [`UniformRefinement`].
+//! Uniform refinement is provided for select element types through [`refine_mesh`] and
+//! [`UniformRefinement`]. Triangle and tetrahedral meshes can additionally be refined locally
+//! and conformingly by edge bisection with [`refine_marked_cells`].
+//!
+//! Since refinement records how the vertices of the refined mesh are constructed from the
+//! vertices of the parent mesh, sparse prolongation operators that interpolate piecewise linear
+//! fields from the parent mesh to the refined mesh can be obtained directly from the refinement,
+//! without any point location. See [`refine_mesh_with_prolongation`],
+//! [`UniformRefinementHierarchy`] and [`LocalRefinement::prolongation`].
 use crate::allocators::DimAllocator;
 use crate::connectivity::Connectivity;
 use crate::mesh::Mesh;
+use crate::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use crate::Real;
 use nalgebra::{DefaultAllocator, DimName, OPoint, RealField};
 use std::collections::HashMap;
 use std::hash::Hash;
 
+mod bisection;
 pub mod detail;
 
+pub use bisection::*;
+
 #[derive(Debug, Clone)]
 pub struct InvalidVertexCount;
 
@@ -22,6 +34,17 @@ pub trait VertexRepresentation: Clone {
         DefaultAllocator: DimAllocator<T, D>;
 }
 
+/// A vertex representation for vertices that are affine combinations of parent vertices.
+pub trait InterpolatedVertex: VertexRepresentation {
+    /// Populates the indices of the parent vertices that the vertex is constructed from, together
+    /// with the associated weights.
+    ///
+    /// The weights must sum to one, and the vertex constructed by
+    /// [`construct_vertex`](VertexRepresentation::construct_vertex) must be the corresponding
+    /// weighted combination of the parent vertices. Each parent vertex may occur at most once.
+    fn populate_parent_weights(&self, weights: &mut Vec<(usize, f64)>);
+}
+
 /// Defines a refinement scheme for a given connectivity.
 pub trait RefineConnectivity<Connectivity> {
     /// An intermediate connectivity type that holds the necessary information
@@ -63,6 +86,62 @@ pub fn refine_mesh<T, D, C, Refinement>(
     mesh: &Mesh<T, D, C>,
     refinement_scheme: Refinement,
 ) -> Mesh<T, D, Refinement::OutputConnectivity>
+where
+    T: RealField,
+    D: DimName,
+    Refinement: RefineConnectivity<C>,
+    Refinement::VertexLabel: Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    refine_mesh_with_vertex_labels(mesh, refinement_scheme).0
+}
+
+/// Refine a mesh with the provided refinement scheme and construct the prolongation operator
+/// from the parent mesh to the refined mesh.
+///
+/// The prolongation matrix $\vec P$ has dimensions $s N_f \times s N_c$, where $s$ is the
+/// solution dimension and $N_f$ and $N_c$ are the number of vertices in the refined and the
+/// parent mesh, respectively. It maps nodal values stored in the usual interleaved format on the
+/// parent mesh to nodal values on the refined mesh by evaluating the weights with which each
+/// new vertex is constructed from the parent vertices. Since the weights are known from the
+/// refinement, this is much cheaper than locating the new vertices in the parent mesh, as done by
+/// [`assemble_nested_prolongation`](crate::error::assemble_nested_prolongation).
+///
+/// For the midpoint-based refinement of linear elements, the prolongation exactly transfers
+/// piecewise linear finite element functions. The corresponding restriction is the transpose
+/// $\vec P^T$.
+pub fn refine_mesh_with_prolongation<T, D, C, Refinement>(
+    mesh: &Mesh<T, D, C>,
+    refinement_scheme: Refinement,
+    solution_dim: usize,
+) -> (Mesh<T, D, Refinement::OutputConnectivity>, CsrMatrix<T>)
+where
+    T: Real,
+    D: DimName,
+    Refinement: RefineConnectivity<C>,
+    Refinement::VertexLabel: InterpolatedVertex + Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let (refined_mesh, labels) = refine_mesh_with_vertex_labels(mesh, refinement_scheme);
+    let mut weights = Vec::new();
+    let prolongation = assemble_prolongation(
+        refined_mesh.vertices().len(),
+        mesh.vertices().len(),
+        labels.iter().map(|label| {
+            weights.clear();
+            label.populate_parent_weights(&mut weights);
+            weights.clone()
+        }),
+        solution_dim,
+    );
+    (refined_mesh, prolongation)
+}
+
+/// Refines the mesh and returns the label of each vertex in the refined mesh.
+fn refine_mesh_with_vertex_labels<T, D, C, Refinement>(
+    mesh: &Mesh<T, D, C>,
+    refinement_scheme: Refinement,
+) -> (Mesh<T, D, Refinement::OutputConnectivity>, Vec<Refinement::VertexLabel>)
 where
     T: RealField,
     D: DimName,
@@ -103,11 +182,43 @@ where
     }
 
     let mut new_vertices = vec![Default::default(); next_vertex_idx];
+    let mut labels = vec![None; next_vertex_idx];
     for (label, index) in label_to_idx_map {
         let vertex = label.construct_vertex(mesh.vertices());
         new_vertices[index] = vertex;
+        labels[index] = Some(label);
     }
-    Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity)
+    let labels = labels
+        .into_iter()
+        .map(|label| label.expect("Every new vertex has a label"))
+        .collect();
+    (
+        Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity),
+        labels,
+    )
+}
+
+/// Assembles a prolongation matrix with `solution_dim` components per node from the parent
+/// weights of each fine vertex.
+///
+/// The `i`-th item of `parent_weights` holds the weights of the `i`-th fine vertex.
+fn assemble_prolongation<T: Real>(
+    num_fine_vertices: usize,
+    num_parent_vertices: usize,
+    parent_weights: impl IntoIterator<Item = Vec<(usize, f64)>>,
+    solution_dim: usize,
+) -> CsrMatrix<T> {
+    let s = solution_dim;
+    let mut coo = CooMatrix::new(s * num_fine_vertices, s * num_parent_vertices);
+    for (fine_vertex, weights) in parent_weights.into_iter().enumerate() {
+        for (parent_vertex, weight) in weights {
+            let weight = T::from_f64(weight).unwrap();
+            for i in 0..s {
+                coo.push(s * fine_vertex + i, s * parent_vertex + i, weight);
+            }
+        }
+    }
+    CsrMatrix::from(&coo)
 }
 
 /// Apply one round of uniform mesh refinement.
@@ -140,3 +251,120 @@ where
     }
     mesh
 }
+
+/// Apply one round of uniform mesh refinement and construct the prolongation operator from the
+/// parent mesh to the refined mesh.
+///
+/// This is a convenience function for
+/// `refine_mesh_with_prolongation(mesh, UniformRefinement, solution_dim)`.
+pub fn refine_uniformly_with_prolongation<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    solution_dim: usize,
+) -> (Mesh<T, D, C>, CsrMatrix<T>)
+where
+    T: Real,
+    D: DimName,
+    UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+    <UniformRefinement as RefineConnectivity<C>>::VertexLabel: InterpolatedVertex + Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    refine_mesh_with_prolongation(mesh, UniformRefinement, solution_dim)
+}
+
+/// A hierarchy of nested meshes obtained by repeated uniform refinement, together with the
+/// prolongation operators between consecutive levels.
+///
+/// Level `0` is the coarsest mesh. The prolongations are stored for scalar fields and expanded to
+/// the requested solution dimension on demand, see [`refine_mesh_with_prolongation`] for the
+/// layout. This provides the grid transfer operators required for geometric multigrid.
+#[derive(Debug, Clone)]
+pub struct UniformRefinementHierarchy<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    meshes: Vec<Mesh<T, D, C>>,
+    prolongations: Vec<CsrMatrix<T>>,
+}
+
+impl<T, D, C> UniformRefinementHierarchy<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Constructs a hierarchy by refining the given coarse mesh `num_refinements` times.
+    ///
+    /// The resulting hierarchy has `num_refinements + 1` levels.
+    pub fn new(coarse_mesh: Mesh<T, D, C>, num_refinements: usize) -> Self
+    where
+        UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+        <UniformRefinement as RefineConnectivity<C>>::VertexLabel: InterpolatedVertex + Eq + Hash,
+    {
+        let mut meshes = vec![coarse_mesh];
+        let mut prolongations = Vec::with_capacity(num_refinements);
+        for _ in 0..num_refinements {
+            let (fine_mesh, prolongation) = refine_uniformly_with_prolongation(meshes.last().unwrap(), 1);
+            meshes.push(fine_mesh);
+            prolongations.push(prolongation);
+        }
+        Self { meshes, prolongations }
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// The meshes of all levels, ordered from coarsest to finest.
+    pub fn meshes(&self) -> &[Mesh<T, D, C>] {
+        &self.meshes
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the level is out of bounds.
+    pub fn mesh(&self, level: usize) -> &Mesh<T, D, C> {
+        &self.meshes[level]
+    }
+
+    pub fn finest_mesh(&self) -> &Mesh<T, D, C> {
+        self.meshes.last().unwrap()
+    }
+
+    /// The prolongation from level `level` to level `level + 1` for the given solution dimension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level + 1` is not a valid level.
+    pub fn prolongation(&self, level: usize, solution_dim: usize) -> CsrMatrix<T> {
+        expand_scalar_operator(&self.prolongations[level], solution_dim)
+    }
+
+    /// The restriction from level `level + 1` to level `level` for the given solution dimension,
+    /// which is the transpose of the corresponding [prolongation](Self::prolongation).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level + 1` is not a valid level.
+    pub fn restriction(&self, level: usize, solution_dim: usize) -> CsrMatrix<T> {
+        self.prolongation(level, solution_dim).transpose()
+    }
+}
+
+/// Expands an operator acting on scalar nodal values to an operator acting on nodal values with
+/// `solution_dim` interleaved components.
+fn expand_scalar_operator<T: Real>(operator: &CsrMatrix<T>, solution_dim: usize) -> CsrMatrix<T> {
+    if solution_dim == 1 {
+        return operator.clone();
+    }
+    let s = solution_dim;
+    let mut coo = CooMatrix::new(s * operator.nrows(), s * operator.ncols());
+    for (row, col, &value) in operator.triplet_iter() {
+        for i in 0..s {
+            coo.push(s * row + i, s * col + i, value);
+        }
+    }
+    CsrMatrix::from(&coo)
+}
@@ -0,0 +1,316 @@
+use crate::allocators::DimAllocator;
+use crate::connectivity::{Connectivity, Tet4Connectivity, Tri3d2Connectivity};
+use crate::mesh::refinement::assemble_prolongation;
+use crate::mesh::Mesh;
+use crate::nalgebra_sparse::CsrMatrix;
+use crate::Real;
+use eyre::eyre;
+use nalgebra::{DVector, DefaultAllocator, DimName, OPoint};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A simplex connectivity that can be refined by edge bisection.
+pub trait BisectConnectivity: Connectivity {
+    /// Constructs a connectivity from the vertex indices of the simplex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of vertex indices does not match the number of vertices of the
+    /// simplex.
+    fn from_simplex_vertices(vertices: &[usize]) -> Self;
+}
+
+impl BisectConnectivity for Tri3d2Connectivity {
+    fn from_simplex_vertices(vertices: &[usize]) -> Self {
+        Self(
+            vertices
+                .try_into()
+                .expect("A triangle must have 3 vertices"),
+        )
+    }
+}
+
+impl BisectConnectivity for Tet4Connectivity {
+    fn from_simplex_vertices(vertices: &[usize]) -> Self {
+        Self(
+            vertices
+                .try_into()
+                .expect("A tetrahedron must have 4 vertices"),
+        )
+    }
+}
+
+/// The result of locally refining a simplex mesh with [`refine_marked_cells`].
+///
+/// In addition to the refined mesh, the refinement records how the refined mesh relates to the
+/// parent mesh, which can be used to transfer data from the parent mesh to the refined mesh.
+/// The vertices of the parent mesh are kept at the same indices in the refined mesh, and each
+/// new vertex is the midpoint of an edge of the parent mesh. The children of each parent cell
+/// are stored contiguously and in the order of the parent cells.
+#[derive(Debug, Clone)]
+pub struct LocalRefinement<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    num_parent_vertices: usize,
+    midpoint_edges: Vec<[usize; 2]>,
+    cell_parents: Vec<usize>,
+    child_offsets: Vec<usize>,
+}
+
+impl<T, D, C> LocalRefinement<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn mesh(&self) -> &Mesh<T, D, C> {
+        &self.mesh
+    }
+
+    pub fn into_mesh(self) -> Mesh<T, D, C> {
+        self.mesh
+    }
+
+    /// The number of vertices in the parent mesh.
+    pub fn num_parent_vertices(&self) -> usize {
+        self.num_parent_vertices
+    }
+
+    /// The number of cells in the parent mesh.
+    pub fn num_parent_cells(&self) -> usize {
+        self.child_offsets.len() - 1
+    }
+
+    /// The parent edges bisected by the new vertices.
+    ///
+    /// Vertex `num_parent_vertices + i` of the refined mesh is the midpoint of the edge of the
+    /// parent mesh given by entry `i`.
+    pub fn midpoint_edges(&self) -> &[[usize; 2]] {
+        &self.midpoint_edges
+    }
+
+    /// The index of the parent cell of each cell in the refined mesh.
+    pub fn cell_parents(&self) -> &[usize] {
+        &self.cell_parents
+    }
+
+    /// The indices of the cells in the refined mesh that are children of the given parent cell.
+    ///
+    /// A parent cell that is not refined has exactly one child, which is identical to the
+    /// parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent cell index is out of bounds.
+    pub fn children(&self, parent_cell: usize) -> Range<usize> {
+        self.child_offsets[parent_cell]..self.child_offsets[parent_cell + 1]
+    }
+
+    /// Linearly interpolates values associated with the vertices of the parent mesh onto the
+    /// vertices of the refined mesh.
+    ///
+    /// The values are stored in the usual interleaved format with `solution_dim` components per
+    /// vertex. Since the new vertices are edge midpoints, this exactly transfers piecewise linear
+    /// finite element functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `values` is not `solution_dim` times the number of parent vertices.
+    pub fn interpolate_vertex_values(&self, values: &DVector<T>, solution_dim: usize) -> DVector<T> {
+        let s = solution_dim;
+        assert_eq!(
+            values.len(),
+            s * self.num_parent_vertices,
+            "Number of values must be solution dim times number of parent vertices"
+        );
+        let half = T::from_f64(0.5).unwrap();
+        let num_vertices = self.num_parent_vertices + self.midpoint_edges.len();
+        let mut interpolated = DVector::zeros(s * num_vertices);
+        interpolated.rows_mut(0, values.len()).copy_from(values);
+        for (i, &[a, b]) in self.midpoint_edges.iter().enumerate() {
+            let vertex = self.num_parent_vertices + i;
+            for j in 0..s {
+                interpolated[s * vertex + j] = half * (values[s * a + j] + values[s * b + j]);
+            }
+        }
+        interpolated
+    }
+
+    /// The prolongation matrix that linearly interpolates values associated with the vertices of
+    /// the parent mesh onto the vertices of the refined mesh.
+    ///
+    /// The matrix has dimensions $s N_f \times s N_c$ for solution dimension $s$, with $N_f$ and
+    /// $N_c$ the number of vertices in the refined and the parent mesh, respectively. Applying it
+    /// is equivalent to [`interpolate_vertex_values`](Self::interpolate_vertex_values).
+    pub fn prolongation(&self, solution_dim: usize) -> CsrMatrix<T> {
+        let parent_weights = (0..self.num_parent_vertices)
+            .map(|vertex| vec![(vertex, 1.0)])
+            .chain(
+                self.midpoint_edges
+                    .iter()
+                    .map(|&[a, b]| vec![(a, 0.5), (b, 0.5)]),
+            );
+        let num_vertices = self.num_parent_vertices + self.midpoint_edges.len();
+        assemble_prolongation(num_vertices, self.num_parent_vertices, parent_weights, solution_dim)
+    }
+
+    /// The restriction matrix from the refined mesh to the parent mesh, which is the transpose of
+    /// the [prolongation](Self::prolongation).
+    pub fn restriction(&self, solution_dim: usize) -> CsrMatrix<T> {
+        self.prolongation(solution_dim).transpose()
+    }
+
+    /// Transfers values associated with the cells of the parent mesh to the cells of the
+    /// refined mesh by copying the value of each parent cell to its children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values does not match the number of parent cells.
+    pub fn transfer_cell_values<X: Clone>(&self, values: &[X]) -> Vec<X> {
+        assert_eq!(
+            values.len(),
+            self.num_parent_cells(),
+            "Number of values must match number of parent cells"
+        );
+        self.cell_parents
+            .iter()
+            .map(|&parent| values[parent].clone())
+            .collect()
+    }
+}
+
+/// Conformingly refines the marked cells of a triangle or tetrahedral mesh by edge bisection.
+///
+/// Each marked cell is bisected along its longest edge, and the refinement is propagated to
+/// neighboring cells so that the resulting mesh has no hanging nodes: every cell that contains a
+/// bisected edge is itself bisected along its longest edge, and this is repeated until no
+/// further edges need to be bisected. Each cell is then recursively bisected along its longest
+/// bisected edge until all bisected edges are split. Ties between edges of equal length are
+/// broken by the vertex indices, so that neighboring cells split their shared faces in the same
+/// way. Each bisected edge is split exactly once, so a single call refines every marked cell
+/// into at least two children.
+///
+/// This is the longest-edge bisection algorithm of Rivara. For triangles, repeated refinement
+/// never reduces the smallest angle below half of the smallest angle of the initial mesh. For
+/// tetrahedra no such bound has been proven, but the quality of the cells is observed to remain
+/// bounded in practice.
+///
+/// The orientation of the cells is preserved. Marked cells may occur repeatedly.
+///
+/// # Errors
+///
+/// Returns an error if a marked cell index is out of bounds.
+pub fn refine_marked_cells<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    marked_cells: &[usize],
+) -> eyre::Result<LocalRefinement<T, D, C>>
+where
+    T: Real,
+    D: DimName,
+    C: BisectConnectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let num_cells = mesh.connectivity().len();
+    if let Some(&cell) = marked_cells.iter().find(|&&cell| cell >= num_cells) {
+        return Err(eyre!(
+            "Marked cell index {} out of bounds for mesh with {} cells",
+            cell,
+            num_cells
+        ));
+    }
+
+    let vertices = mesh.vertices();
+    let compare_edges = |e1: &[usize; 2], e2: &[usize; 2]| -> Ordering {
+        let length1 = (&vertices[e1[0]] - &vertices[e1[1]]).norm_squared();
+        let length2 = (&vertices[e2[0]] - &vertices[e2[1]]).norm_squared();
+        length1
+            .partial_cmp(&length2)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| e2.cmp(e1))
+    };
+    let longest_edge = |cell_vertices: &[usize]| -> [usize; 2] {
+        cell_edges(cell_vertices)
+            .max_by(|e1, e2| compare_edges(e1, e2))
+            .expect("Simplices have at least one edge")
+    };
+
+    let mut edge_cells: HashMap<[usize; 2], Vec<usize>> = HashMap::new();
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        for edge in cell_edges(cell.vertex_indices()) {
+            edge_cells.entry(edge).or_default().push(cell_index);
+        }
+    }
+
+    // Mark the longest edge of every cell that contains a marked edge, starting from the marked
+    // cells, until no more edges are marked
+    let mut marked_edges = HashSet::new();
+    let mut pending_cells = marked_cells.to_vec();
+    while let Some(cell_index) = pending_cells.pop() {
+        let edge = longest_edge(mesh.connectivity()[cell_index].vertex_indices());
+        if marked_edges.insert(edge) {
+            pending_cells.extend_from_slice(&edge_cells[&edge]);
+        }
+    }
+
+    let num_parent_vertices = vertices.len();
+    let mut new_vertices = vertices.to_vec();
+    let mut midpoint_edges = Vec::new();
+    let mut midpoint_indices = HashMap::new();
+    let mut new_connectivity = Vec::new();
+    let mut cell_parents = Vec::new();
+    let mut child_offsets = vec![0];
+    let mut stack = Vec::new();
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        stack.push(cell.vertex_indices().to_vec());
+        while let Some(simplex) = stack.pop() {
+            let bisection_edge = cell_edges(&simplex)
+                .filter(|edge| marked_edges.contains(edge))
+                .max_by(|e1, e2| compare_edges(e1, e2));
+            if let Some([a, b]) = bisection_edge {
+                let midpoint = *midpoint_indices.entry([a, b]).or_insert_with(|| {
+                    let midpoint =
+                        OPoint::from((&vertices[a].coords + &vertices[b].coords) * T::from_f64(0.5).unwrap());
+                    new_vertices.push(midpoint);
+                    midpoint_edges.push([a, b]);
+                    new_vertices.len() - 1
+                });
+                let replace = |old: usize| -> Vec<usize> {
+                    simplex
+                        .iter()
+                        .map(|&v| if v == old { midpoint } else { v })
+                        .collect()
+                };
+                // Push in reverse order so that the child containing `a` is processed first
+                stack.push(replace(a));
+                stack.push(replace(b));
+            } else {
+                new_connectivity.push(C::from_simplex_vertices(&simplex));
+                cell_parents.push(cell_index);
+            }
+        }
+        child_offsets.push(new_connectivity.len());
+    }
+
+    Ok(LocalRefinement {
+        mesh: Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity),
+        num_parent_vertices,
+        midpoint_edges,
+        cell_parents,
+        child_offsets,
+    })
+}
+
+/// Returns the edges of a simplex with sorted vertex indices.
+fn cell_edges(vertices: &[usize]) -> impl Iterator<Item = [usize; 2]> + '_ {
+    (0..vertices.len()).flat_map(move |i| {
+        (i + 1..vertices.len()).map(move |j| {
+            let (a, b) = (vertices[i], vertices[j]);
+            [a.min(b), a.max(b)]
+        })
+    })
+}
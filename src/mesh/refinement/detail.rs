@@ -2,7 +2,9 @@
 
 use crate::allocators::DimAllocator;
 use crate::connectivity::Tri3d2Connectivity;
-use crate::mesh::refinement::{InvalidVertexCount, RefineConnectivity, UniformRefinement, VertexRepresentation};
+use crate::mesh::refinement::{
+    InterpolatedVertex, InvalidVertexCount, RefineConnectivity, UniformRefinement, VertexRepresentation,
+};
 use core::cmp::{max, min};
 use core::hash::{Hash, Hasher};
 use nalgebra::base::default_allocator::DefaultAllocator;
@@ -25,6 +27,12 @@ impl VertexRepresentation for VertexLabel {
     }
 }
 
+impl InterpolatedVertex for VertexLabel {
+    fn populate_parent_weights(&self, weights: &mut Vec<(usize, f64)>) {
+        weights.push((self.0, 1.0));
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq)]
 pub struct EdgeMidpointLabel(pub [usize; 2]);
 
@@ -48,6 +56,13 @@ impl VertexRepresentation for EdgeMidpointLabel {
     }
 }
 
+impl InterpolatedVertex for EdgeMidpointLabel {
+    fn populate_parent_weights(&self, weights: &mut Vec<(usize, f64)>) {
+        let [a, b] = self.canonical_vertex_indices();
+        weights.extend([(a, 0.5), (b, 0.5)]);
+    }
+}
+
 impl PartialEq for EdgeMidpointLabel {
     fn eq(&self, other: &Self) -> bool {
         self.canonical_vertex_indices() == other.canonical_vertex_indices()
@@ -92,6 +107,15 @@ impl VertexRepresentation for VertexOrEdgeMidpointVertex {
     }
 }
 
+impl InterpolatedVertex for VertexOrEdgeMidpointVertex {
+    fn populate_parent_weights(&self, weights: &mut Vec<(usize, f64)>) {
+        match self {
+            Self::Vertex(label) => label.populate_parent_weights(weights),
+            Self::EdgeMidpoint(label) => label.populate_parent_weights(weights),
+        }
+    }
+}
+
 pub fn edge_midpoint(vertices: [usize; 2]) -> EdgeMidpointLabel {
     EdgeMidpointLabel(vertices)
 }
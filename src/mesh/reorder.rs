@@ -1,10 +1,10 @@
 //! Routines for reordering mesh vertices and elements.
-use crate::assembly::global::CsrParAssembler;
+use crate::assembly::global::{CsrAssembler, CsrParAssembler};
 use crate::connectivity::{Connectivity, ConnectivityMut};
 use crate::mesh::Mesh;
 use core::fmt;
 use nalgebra::allocator::Allocator;
-use nalgebra::{DefaultAllocator, DimName, Scalar};
+use nalgebra::{DVector, DVectorView, DefaultAllocator, DimName, Scalar};
 use nalgebra_sparse::pattern::SparsityPattern;
 use std::collections::VecDeque;
 use std::error::Error;
@@ -25,6 +25,38 @@ impl MeshPermutation {
         &self.connectivity_perm
     }
 
+    /// Reorders a nodal vector on the original mesh to match the vertex order of the permuted mesh.
+    ///
+    /// The vector stores `solution_dim` entries per vertex, so that the entries of each vertex
+    /// are moved together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector is not `solution_dim` times the number of vertices.
+    pub fn permute_nodal_vector<'a, T: Scalar>(
+        &self,
+        u: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> DVector<T> {
+        permute_blocks(&self.vertex_perm, u.into(), solution_dim)
+    }
+
+    /// Reorders a nodal vector on the permuted mesh back to the vertex order of the original mesh.
+    ///
+    /// This is the inverse of [`permute_nodal_vector`](Self::permute_nodal_vector), and is
+    /// typically used to map a solution computed on the permuted mesh back to the original mesh.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector is not `solution_dim` times the number of vertices.
+    pub fn restore_nodal_vector<'a, T: Scalar>(
+        &self,
+        u: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> DVector<T> {
+        permute_blocks(&self.vertex_perm.inverse(), u.into(), solution_dim)
+    }
+
     pub fn apply<T, D, C>(&self, mesh: &Mesh<T, D, C>) -> Mesh<T, D, C>
     where
         T: Scalar,
@@ -46,7 +78,14 @@ impl MeshPermutation {
                 *vertex_idx = new_vertex_index;
             }
         }
-        Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity)
+        let inv_connectivity_perm = self.connectivity_permutation().inverse();
+        let new_sets = mesh.sets().relabeled(
+            |old_vertex| Some(inv_vertex_perm.source_index(old_vertex)),
+            |old_cell| Some(inv_connectivity_perm.source_index(old_cell)),
+        );
+        let mut new_mesh = Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity);
+        *new_mesh.sets_mut() = new_sets;
+        new_mesh
     }
 }
 
@@ -64,6 +103,61 @@ where
     // Construct the CSR adjacency matrix for the graph represented by the mesh
     let csr_graph = assembler.assemble_pattern(mesh);
     let vertex_perm = reverse_cuthill_mckee(&csr_graph);
+    mesh_permutation_from_vertex_permutation(mesh, vertex_perm)
+}
+
+/// Creates a mesh permutation by computing a Reverse Cuthill-McKee permutation.
+///
+/// This is the sequential version of [`reorder_mesh_par`]. The resulting permutation
+/// is identical.
+pub fn reorder_mesh<T, D, C>(mesh: &Mesh<T, D, C>) -> MeshPermutation
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    reorder_mesh_with(mesh, reverse_cuthill_mckee)
+}
+
+/// Creates a mesh permutation from a vertex ordering computed by the given function.
+///
+/// The function receives the vertex adjacency graph of the mesh, represented by the sparsity
+/// pattern of a scalar matrix assembled on the mesh, and must return a permutation of the
+/// vertices. This makes it possible to use other orderings than
+/// [Reverse Cuthill-McKee](reverse_cuthill_mckee), such as nested dissection orderings computed
+/// by external graph partitioning libraries.
+///
+/// # Panics
+///
+/// Panics if the returned permutation does not have one entry per vertex.
+pub fn reorder_mesh_with<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    vertex_ordering: impl FnOnce(&SparsityPattern) -> Permutation,
+) -> MeshPermutation
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let csr_graph = CsrAssembler::<i32>::default().assemble_pattern(mesh);
+    let vertex_perm = vertex_ordering(&csr_graph);
+    assert_eq!(
+        vertex_perm.len(),
+        mesh.vertices().len(),
+        "Vertex permutation must have one entry per vertex"
+    );
+    mesh_permutation_from_vertex_permutation(mesh, vertex_perm)
+}
+
+fn mesh_permutation_from_vertex_permutation<T, D, C>(mesh: &Mesh<T, D, C>, vertex_perm: Permutation) -> MeshPermutation
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
     let inv_vertex_perm = vertex_perm.inverse();
 
     // Reorder connectivity by sorting the connectivities by minimum (permuted) vertex index,
@@ -88,6 +182,32 @@ where
     }
 }
 
+/// Computes the bandwidth of a square sparsity pattern.
+///
+/// The bandwidth is the largest distance $|i - j|$ between the row index $i$ and the column
+/// index $j$ of any explicitly stored entry.
+pub fn bandwidth(sparsity_pattern: &SparsityPattern) -> usize {
+    (0..sparsity_pattern.major_dim())
+        .flat_map(|i| sparsity_pattern.lane(i).iter().map(move |&j| i.abs_diff(j)))
+        .max()
+        .unwrap_or(0)
+}
+
+fn permute_blocks<T: Scalar>(perm: &Permutation, u: DVectorView<T>, block_size: usize) -> DVector<T> {
+    assert_eq!(
+        u.len(),
+        block_size * perm.len(),
+        "Vector length must be equal to the solution dimension times the number of vertices"
+    );
+    let u = &u;
+    DVector::from_iterator(
+        u.len(),
+        perm.perm()
+            .iter()
+            .flat_map(|&source| (0..block_size).map(move |i| u[block_size * source + i].clone())),
+    )
+}
+
 /// A representation of an index permutation.
 ///
 /// More precisely, given `n` objects stored contiguously, the permutation internally
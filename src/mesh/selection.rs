@@ -0,0 +1,278 @@
+//! Composable selection of mesh regions for boundary conditions and material assignment.
+//!
+//! A [`Region`] is a set of points described by a signed distance function $d$, with the
+//! region given by $\{ x : d(x) \leq 0 \}$. Regions are built from simple shapes, such as boxes,
+//! balls and planes, and combined with set operations. Since comparisons of floating-point
+//! coordinates are fragile, a region can be [inflated](Region::inflated) by a tolerance, which
+//! selects all points within the given distance of the region. For example, the following
+//! selects the nodes on the left wall of the unit square, except for those close to its corners:
+//!
+//! ```rust
+//! use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+//! use fenris::mesh::selection::Region;
+//! use fenris::nalgebra::point;
+//!
+//! let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+//! let left_wall = Region::coordinate_equals(0, 0.0, 1e-12);
+//! let corners = Region::ball(point![0.0, 0.0], 0.1) | Region::ball(point![0.0, 1.0], 0.1);
+//! let nodes = (left_wall - corners).select_nodes(&mesh);
+//! assert_eq!(nodes.len(), 3);
+//! ```
+//!
+//! Selected nodes and cells can be stored as [named sets](crate::mesh::sets) by passing
+//! [`Region::predicate`] to the tagging methods of [`Mesh`].
+use crate::connectivity::Connectivity;
+use crate::geometry::AxisAlignedBoundingBox;
+use crate::mesh::sets::centroid;
+use crate::mesh::Mesh;
+use crate::Real;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, OVector};
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitOr, Not, Sub};
+use std::rc::Rc;
+
+type SignedDistanceFn<T, D> = dyn Fn(&OPoint<T, D>) -> T;
+
+/// A region of space, described by a signed distance function.
+///
+/// The signed distance is negative inside the region, positive outside and zero on its
+/// boundary. For the set operations, the signed distance of the result is only a bound on
+/// the exact signed distance, which is sufficient for deciding whether points lie in the region
+/// and for tolerances that are small compared to the features of the region.
+///
+/// The set operations are also available as the operators `|` (union), `&` (intersection),
+/// `-` (difference) and `!` (complement).
+#[derive(Clone)]
+pub struct Region<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    signed_distance: Rc<SignedDistanceFn<T, D>>,
+}
+
+impl<T, D> Debug for Region<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Region").finish_non_exhaustive()
+    }
+}
+
+fn infinity<T: Real>() -> T {
+    T::from_f64(f64::INFINITY).unwrap()
+}
+
+impl<T, D> Region<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Creates a region from the given signed distance function.
+    pub fn from_signed_distance(signed_distance: impl Fn(&OPoint<T, D>) -> T + 'static) -> Self {
+        Self {
+            signed_distance: Rc::new(signed_distance),
+        }
+    }
+
+    /// Creates a region consisting of the points that satisfy the predicate.
+    ///
+    /// Since there is no notion of distance for a general predicate, the signed distance is
+    /// $-\infty$ inside and $+\infty$ outside of the region, so that inflating the region has
+    /// no effect.
+    pub fn from_predicate(predicate: impl Fn(&OPoint<T, D>) -> bool + 'static) -> Self {
+        Self::from_signed_distance(move |x| if predicate(x) { -infinity::<T>() } else { infinity() })
+    }
+
+    /// The region containing all points.
+    pub fn everywhere() -> Self {
+        Self::from_signed_distance(|_| -infinity::<T>())
+    }
+
+    /// The region containing no points.
+    pub fn nowhere() -> Self {
+        Self::from_signed_distance(|_| infinity())
+    }
+
+    /// The closed ball with the given center and radius.
+    pub fn ball(center: OPoint<T, D>, radius: T) -> Self {
+        Self::from_signed_distance(move |x| (x - &center).norm() - radius)
+    }
+
+    /// The closed axis-aligned box with the given minimum and maximum corners.
+    pub fn aabb(aabb: AxisAlignedBoundingBox<T, D>) -> Self {
+        let center = aabb.center();
+        let half_extents = aabb.extents() / T::from_f64(2.0).unwrap();
+        Self::from_signed_distance(move |x| {
+            let q: OVector<T, D> = (x - &center).abs() - &half_extents;
+            let outside = q.map(|q_i| q_i.max(T::zero())).norm();
+            let inside = q.max().min(T::zero());
+            outside + inside
+        })
+    }
+
+    /// The points within the given distance of the hyperplane through `point` with the given
+    /// normal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the normal is zero.
+    pub fn plane(point: OPoint<T, D>, normal: OVector<T, D>, tolerance: T) -> Self {
+        let normal = normal
+            .try_normalize(T::zero())
+            .expect("Normal of plane must be non-zero");
+        Self::from_signed_distance(move |x| (x - &point).dot(&normal).abs() - tolerance)
+    }
+
+    /// The closed half-space bounded by the hyperplane through `point`, with the normal pointing
+    /// away from the half-space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the normal is zero.
+    pub fn half_space(point: OPoint<T, D>, normal: OVector<T, D>) -> Self {
+        let normal = normal
+            .try_normalize(T::zero())
+            .expect("Normal of half-space must be non-zero");
+        Self::from_signed_distance(move |x| (x - &point).dot(&normal))
+    }
+
+    /// The points whose coordinate along the given axis is within the tolerance of the value.
+    pub fn coordinate_equals(axis: usize, value: T, tolerance: T) -> Self {
+        assert!(axis < D::dim(), "Axis must be smaller than the dimension");
+        Self::from_signed_distance(move |x| (x[axis] - value).abs() - tolerance)
+    }
+
+    /// The points whose coordinate along the given axis is at most the given value.
+    pub fn coordinate_below(axis: usize, value: T) -> Self {
+        assert!(axis < D::dim(), "Axis must be smaller than the dimension");
+        Self::from_signed_distance(move |x| x[axis] - value)
+    }
+
+    /// The points whose coordinate along the given axis is at least the given value.
+    pub fn coordinate_above(axis: usize, value: T) -> Self {
+        assert!(axis < D::dim(), "Axis must be smaller than the dimension");
+        Self::from_signed_distance(move |x| value - x[axis])
+    }
+
+    /// The union of the two regions.
+    pub fn union(self, other: Self) -> Self {
+        Self::from_signed_distance(move |x| self.signed_distance(x).min(other.signed_distance(x)))
+    }
+
+    /// The intersection of the two regions.
+    pub fn intersection(self, other: Self) -> Self {
+        Self::from_signed_distance(move |x| self.signed_distance(x).max(other.signed_distance(x)))
+    }
+
+    /// The points in this region that are not in the other region.
+    ///
+    /// Since the complement of the other region is closed, points on the boundary of the other
+    /// region are kept.
+    pub fn difference(self, other: Self) -> Self {
+        self.intersection(other.complement())
+    }
+
+    /// The closure of the complement of the region.
+    pub fn complement(self) -> Self {
+        Self::from_signed_distance(move |x| -self.signed_distance(x))
+    }
+
+    /// The points within the given distance of the region.
+    pub fn inflated(self, distance: T) -> Self {
+        Self::from_signed_distance(move |x| self.signed_distance(x) - distance)
+    }
+
+    pub fn signed_distance(&self, point: &OPoint<T, D>) -> T {
+        (self.signed_distance)(point)
+    }
+
+    pub fn contains(&self, point: &OPoint<T, D>) -> bool {
+        self.signed_distance(point) <= T::zero()
+    }
+
+    /// Returns a predicate that determines whether a point is in the region.
+    ///
+    /// The predicate can be passed to the tagging methods of [`Mesh`], such as
+    /// [`Mesh::tag_nodes`].
+    pub fn predicate(&self) -> impl '_ + Fn(&OPoint<T, D>) -> bool {
+        |x| self.contains(x)
+    }
+
+    /// Returns the indices of the mesh vertices in the region, in ascending order.
+    pub fn select_nodes<C>(&self, mesh: &Mesh<T, D, C>) -> Vec<usize> {
+        mesh.vertices()
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| self.contains(x))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the indices of the cells whose centroid is in the region, in ascending order.
+    ///
+    /// The centroid is the average of the positions of the nodes of the cell.
+    pub fn select_cells<C: Connectivity>(&self, mesh: &Mesh<T, D, C>) -> Vec<usize> {
+        mesh.connectivity()
+            .iter()
+            .enumerate()
+            .filter(|(_, conn)| self.contains(&centroid(mesh.vertices(), conn.vertex_indices())))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the indices of the cells whose nodes are all in the region, in ascending order.
+    pub fn select_cells_fully_inside<C: Connectivity>(&self, mesh: &Mesh<T, D, C>) -> Vec<usize> {
+        mesh.connectivity()
+            .iter()
+            .enumerate()
+            .filter(|(_, conn)| {
+                conn.vertex_indices()
+                    .iter()
+                    .all(|&i| self.contains(&mesh.vertices()[i]))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+macro_rules! impl_region_operator {
+    ($trait:ident, $method:ident, $region_method:ident) => {
+        impl<T, D> $trait for Region<T, D>
+        where
+            T: Real,
+            D: DimName,
+            DefaultAllocator: Allocator<T, D>,
+        {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self {
+                self.$region_method(other)
+            }
+        }
+    };
+}
+
+impl_region_operator!(BitOr, bitor, union);
+impl_region_operator!(BitAnd, bitand, intersection);
+impl_region_operator!(Sub, sub, difference);
+
+impl<T, D> Not for Region<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
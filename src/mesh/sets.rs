@@ -0,0 +1,352 @@
+//! Named sets of nodes, faces and cells in a mesh.
+//!
+//! Boundary conditions, material assignments and post-processing usually refer to parts of a
+//! mesh, such as "all nodes on the left wall" or "all cells in the inclusion". [`MeshSets`] stores
+//! such parts by name, so that they can be defined once and referenced later, for example when
+//! constructing [`DirichletConditions`](crate::assembly::bc::DirichletConditions).
+//!
+//! Sets are usually defined through the tagging methods on [`Mesh`], which select entities
+//! with a user-defined predicate:
+//!
+//! ```rust
+//! use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+//!
+//! let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+//! mesh.tag_nodes("left_wall", |x| x[0] < 1e-12);
+//! mesh.tag_cells("lower_half", |x| x[1] < 0.5);
+//! assert_eq!(mesh.sets().node_set("left_wall").unwrap().len(), 5);
+//! assert_eq!(mesh.sets().cell_set("lower_half").unwrap().len(), 8);
+//! ```
+//!
+//! Faces are identified by the index of a cell together with the local index of the face
+//! within the cell, in the same way as in [`Mesh::find_boundary_faces`]. In two dimensions,
+//! the faces of a cell are its edges. Edges, such as the
+//! [feature edges](crate::mesh::features) of a surface mesh, are identified by their two
+//! vertex indices in ascending order.
+use crate::connectivity::Connectivity;
+use crate::memory::HeapSizeEstimate;
+use crate::mesh::Mesh;
+use crate::Real;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A face of a cell, given by the cell index and the local index of the face within the cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CellFace {
+    pub cell_index: usize,
+    pub local_index: usize,
+}
+
+/// Named sets of nodes, edges, faces and cells.
+///
+/// Every set is stored as a sorted list of indices without duplicates. Inserting a set with a name
+/// that already exists replaces the previous set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeshSets {
+    node_sets: BTreeMap<String, Vec<usize>>,
+    #[serde(default)]
+    edge_sets: BTreeMap<String, Vec<[usize; 2]>>,
+    face_sets: BTreeMap<String, Vec<CellFace>>,
+    cell_sets: BTreeMap<String, Vec<usize>>,
+}
+
+fn sorted_unique<I: Ord>(mut indices: Vec<I>) -> Vec<I> {
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+fn sorted_edge([a, b]: [usize; 2]) -> [usize; 2] {
+    [a.min(b), a.max(b)]
+}
+
+impl MeshSets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if no sets are stored.
+    pub fn is_empty(&self) -> bool {
+        self.node_sets.is_empty() && self.edge_sets.is_empty() && self.face_sets.is_empty() && self.cell_sets.is_empty()
+    }
+
+    pub fn insert_node_set(&mut self, name: impl Into<String>, nodes: Vec<usize>) -> &[usize] {
+        let name = name.into();
+        self.node_sets.insert(name.clone(), sorted_unique(nodes));
+        &self.node_sets[&name]
+    }
+
+    /// Inserts a set of edges, each given by its two vertex indices in arbitrary order.
+    pub fn insert_edge_set(&mut self, name: impl Into<String>, edges: Vec<[usize; 2]>) -> &[[usize; 2]] {
+        let name = name.into();
+        self.edge_sets.insert(
+            name.clone(),
+            sorted_unique(edges.into_iter().map(sorted_edge).collect()),
+        );
+        &self.edge_sets[&name]
+    }
+
+    pub fn insert_face_set(&mut self, name: impl Into<String>, faces: Vec<CellFace>) -> &[CellFace] {
+        let name = name.into();
+        self.face_sets.insert(name.clone(), sorted_unique(faces));
+        &self.face_sets[&name]
+    }
+
+    pub fn insert_cell_set(&mut self, name: impl Into<String>, cells: Vec<usize>) -> &[usize] {
+        let name = name.into();
+        self.cell_sets.insert(name.clone(), sorted_unique(cells));
+        &self.cell_sets[&name]
+    }
+
+    pub fn node_set(&self, name: &str) -> Option<&[usize]> {
+        self.node_sets.get(name).map(Vec::as_slice)
+    }
+
+    pub fn edge_set(&self, name: &str) -> Option<&[[usize; 2]]> {
+        self.edge_sets.get(name).map(Vec::as_slice)
+    }
+
+    /// Returns the union of the nodes of the edges in the named edge set, or `None` if
+    /// there is no edge set with the given name.
+    pub fn edge_set_nodes(&self, name: &str) -> Option<Vec<usize>> {
+        let edges = self.edge_set(name)?;
+        Some(sorted_unique(edges.iter().flatten().copied().collect()))
+    }
+
+    pub fn face_set(&self, name: &str) -> Option<&[CellFace]> {
+        self.face_sets.get(name).map(Vec::as_slice)
+    }
+
+    pub fn cell_set(&self, name: &str) -> Option<&[usize]> {
+        self.cell_sets.get(name).map(Vec::as_slice)
+    }
+
+    pub fn remove_node_set(&mut self, name: &str) -> Option<Vec<usize>> {
+        self.node_sets.remove(name)
+    }
+
+    pub fn remove_edge_set(&mut self, name: &str) -> Option<Vec<[usize; 2]>> {
+        self.edge_sets.remove(name)
+    }
+
+    pub fn remove_face_set(&mut self, name: &str) -> Option<Vec<CellFace>> {
+        self.face_sets.remove(name)
+    }
+
+    pub fn remove_cell_set(&mut self, name: &str) -> Option<Vec<usize>> {
+        self.cell_sets.remove(name)
+    }
+
+    /// Returns the names of all node sets in lexicographical order.
+    pub fn node_set_names(&self) -> impl '_ + Iterator<Item = &str> {
+        self.node_sets.keys().map(String::as_str)
+    }
+
+    /// Returns the names of all edge sets in lexicographical order.
+    pub fn edge_set_names(&self) -> impl '_ + Iterator<Item = &str> {
+        self.edge_sets.keys().map(String::as_str)
+    }
+
+    /// Returns the names of all face sets in lexicographical order.
+    pub fn face_set_names(&self) -> impl '_ + Iterator<Item = &str> {
+        self.face_sets.keys().map(String::as_str)
+    }
+
+    /// Returns the names of all cell sets in lexicographical order.
+    pub fn cell_set_names(&self) -> impl '_ + Iterator<Item = &str> {
+        self.cell_sets.keys().map(String::as_str)
+    }
+
+    /// Returns new sets in which all node and cell indices have been relabeled.
+    ///
+    /// The functions return the new index of an old node or cell index, or `None` if the node or
+    /// cell no longer exists, in which case it is removed from all sets. Edges are removed if
+    /// either of their nodes no longer exists. The local indices of faces are assumed to be
+    /// unchanged.
+    pub(crate) fn relabeled(
+        &self,
+        new_node_index: impl Fn(usize) -> Option<usize>,
+        new_cell_index: impl Fn(usize) -> Option<usize>,
+    ) -> Self {
+        let relabel_indices = |indices: &Vec<usize>, new_index: &dyn Fn(usize) -> Option<usize>| {
+            sorted_unique(
+                indices
+                    .iter()
+                    .filter_map(|&index| new_index(index))
+                    .collect(),
+            )
+        };
+        let node_sets = self
+            .node_sets
+            .iter()
+            .map(|(name, nodes)| (name.clone(), relabel_indices(nodes, &new_node_index)))
+            .collect();
+        let edge_sets = self
+            .edge_sets
+            .iter()
+            .map(|(name, edges)| {
+                let edges = edges
+                    .iter()
+                    .filter_map(|&[a, b]| Some(sorted_edge([new_node_index(a)?, new_node_index(b)?])))
+                    .collect();
+                (name.clone(), sorted_unique(edges))
+            })
+            .collect();
+        let cell_sets = self
+            .cell_sets
+            .iter()
+            .map(|(name, cells)| (name.clone(), relabel_indices(cells, &new_cell_index)))
+            .collect();
+        let face_sets = self
+            .face_sets
+            .iter()
+            .map(|(name, faces)| {
+                let faces = faces
+                    .iter()
+                    .filter_map(|face| {
+                        new_cell_index(face.cell_index).map(|cell_index| CellFace {
+                            cell_index,
+                            local_index: face.local_index,
+                        })
+                    })
+                    .collect();
+                (name.clone(), sorted_unique(faces))
+            })
+            .collect();
+        Self {
+            node_sets,
+            edge_sets,
+            face_sets,
+            cell_sets,
+        }
+    }
+}
+
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Stores the nodes whose position satisfies the predicate as a named node set.
+    ///
+    /// Returns the tagged nodes.
+    pub fn tag_nodes(&mut self, name: impl Into<String>, predicate: impl Fn(&OPoint<T, D>) -> bool) -> &[usize] {
+        let nodes = self
+            .vertices()
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| predicate(x))
+            .map(|(i, _)| i)
+            .collect();
+        self.sets_mut().insert_node_set(name, nodes)
+    }
+
+    /// Stores the cells whose centroid satisfies the predicate as a named cell set.
+    ///
+    /// The centroid is the average of the positions of the nodes of the cell.
+    ///
+    /// Returns the tagged cells.
+    pub fn tag_cells(&mut self, name: impl Into<String>, predicate: impl Fn(&OPoint<T, D>) -> bool) -> &[usize] {
+        let cells = self
+            .connectivity()
+            .iter()
+            .enumerate()
+            .filter(|(_, conn)| predicate(&centroid(self.vertices(), conn.vertex_indices())))
+            .map(|(i, _)| i)
+            .collect();
+        self.sets_mut().insert_cell_set(name, cells)
+    }
+
+    /// Returns the union of the nodes of the faces in the named face set, or `None` if
+    /// there is no face set with the given name.
+    ///
+    /// This is typically used to impose Dirichlet boundary conditions on a tagged part of the
+    /// boundary.
+    pub fn face_set_nodes(&self, name: &str) -> Option<Vec<usize>> {
+        let faces = self.sets().face_set(name)?;
+        let mut nodes = Vec::new();
+        for face in faces {
+            let face_conn = self.connectivity()[face.cell_index]
+                .get_face_connectivity(face.local_index)
+                .expect("Face sets must only contain valid faces");
+            nodes.extend_from_slice(face_conn.vertex_indices());
+        }
+        Some(sorted_unique(nodes))
+    }
+}
+
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: Connectivity,
+    C::FaceConnectivity: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Stores the boundary faces whose centroid satisfies the predicate as a named face set.
+    ///
+    /// The centroid is the average of the positions of the nodes of the face.
+    ///
+    /// Returns the tagged faces.
+    pub fn tag_boundary_faces(
+        &mut self,
+        name: impl Into<String>,
+        predicate: impl Fn(&OPoint<T, D>) -> bool,
+    ) -> &[CellFace] {
+        let faces = self
+            .find_boundary_faces()
+            .into_iter()
+            .filter(|(face_conn, _, _)| predicate(&centroid(self.vertices(), face_conn.vertex_indices())))
+            .map(|(_, cell_index, local_index)| CellFace {
+                cell_index,
+                local_index,
+            })
+            .collect();
+        self.sets_mut().insert_face_set(name, faces)
+    }
+}
+
+pub(crate) fn centroid<T, D>(vertices: &[OPoint<T, D>], indices: &[usize]) -> OPoint<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let mut sum = OPoint::<T, D>::origin();
+    for &index in indices {
+        sum.coords += &vertices[index].coords;
+    }
+    sum / T::from_usize(indices.len()).unwrap()
+}
+
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Returns the named sets associated with the mesh.
+    pub fn sets(&self) -> &MeshSets {
+        &self.sets
+    }
+
+    pub fn sets_mut(&mut self) -> &mut MeshSets {
+        &mut self.sets
+    }
+}
+
+/// The overhead of the tree structure of the maps is neglected.
+impl HeapSizeEstimate for MeshSets {
+    fn heap_size_estimate(&self) -> usize {
+        fn map_size<V: HeapSizeEstimate>(map: &BTreeMap<String, V>) -> usize {
+            map.iter()
+                .map(|(name, set)| name.capacity() + set.heap_size_estimate())
+                .sum()
+        }
+        map_size(&self.node_sets) + map_size(&self.edge_sets) + map_size(&self.face_sets) + map_size(&self.cell_sets)
+    }
+}
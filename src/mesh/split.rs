@@ -0,0 +1,359 @@
+//! Splitting of meshes along interior faces.
+//!
+//! Cracks, material interfaces with cohesive laws and other explicit discontinuities are
+//! represented in a conforming mesh by duplicating the vertices along a set of interior faces,
+//! so that the cells on either side of a face no longer share vertices. [`split_mesh_along_faces`]
+//! performs this operation for a set of [`InteriorFace`]s, which may be selected geometrically
+//! with [`find_interior_faces_where`] or from cell labels with [`find_interface_faces`].
+use crate::connectivity::{Connectivity, ConnectivityMut};
+use crate::mesh::Mesh;
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A face shared by exactly two cells of a mesh.
+///
+/// The face is the face with local index `local_faces[i]` of cell `cells[i]` for `i = 0, 1`.
+/// Faces returned by functions in this module have their cells ordered by increasing index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InteriorFace {
+    pub cells: [usize; 2],
+    pub local_faces: [usize; 2],
+}
+
+impl InteriorFace {
+    /// Returns the same face with its cells ordered by increasing index.
+    pub fn normalized(&self) -> Self {
+        if self.cells[0] <= self.cells[1] {
+            *self
+        } else {
+            Self {
+                cells: [self.cells[1], self.cells[0]],
+                local_faces: [self.local_faces[1], self.local_faces[0]],
+            }
+        }
+    }
+}
+
+/// Maps each face of the mesh, identified by its sorted vertex indices, to the cells and
+/// local face indices it belongs to.
+pub(crate) fn collect_faces<T, D, C>(mesh: &Mesh<T, D, C>) -> BTreeMap<Vec<usize>, Vec<(usize, usize)>>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    // Use a BTreeMap to make the result independent of HashMap's internal randomization
+    let mut faces = BTreeMap::new();
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        for local_face in 0..cell.num_faces() {
+            let face = cell.get_face_connectivity(local_face).unwrap();
+            let mut key = face.vertex_indices().to_vec();
+            key.sort_unstable();
+            faces
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .push((cell_index, local_face));
+        }
+    }
+    faces
+}
+
+fn sorted_face_vertices<C: Connectivity>(cell: &C, local_face: usize) -> Option<Vec<usize>> {
+    cell.get_face_connectivity(local_face).map(|face| {
+        let mut vertices = face.vertex_indices().to_vec();
+        vertices.sort_unstable();
+        vertices
+    })
+}
+
+/// Finds all faces of the mesh that are shared by exactly two cells.
+///
+/// The faces are ordered by their sorted vertex indices, so that the result is deterministic.
+pub fn find_interior_faces<T, D, C>(mesh: &Mesh<T, D, C>) -> Vec<InteriorFace>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    collect_faces(mesh)
+        .into_values()
+        .filter_map(|cells| match cells.as_slice() {
+            &[(cell0, local_face0), (cell1, local_face1)] => Some(InteriorFace {
+                cells: [cell0, cell1],
+                local_faces: [local_face0, local_face1],
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds the interior faces whose vertices satisfy the given predicate.
+///
+/// The predicate is called with the vertices of each interior face, in the order given by the
+/// face connectivity of the first cell of the face. For example, the faces of a planar crack are
+/// selected by checking that all vertices lie in the crack plane and inside the crack region.
+pub fn find_interior_faces_where<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    mut predicate: impl FnMut(&[OPoint<T, D>]) -> bool,
+) -> Vec<InteriorFace>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let mut face_vertices = Vec::new();
+    find_interior_faces(mesh)
+        .into_iter()
+        .filter(|face| {
+            let face_connectivity = mesh.connectivity()[face.cells[0]]
+                .get_face_connectivity(face.local_faces[0])
+                .unwrap();
+            face_vertices.clear();
+            face_vertices.extend(
+                face_connectivity
+                    .vertex_indices()
+                    .iter()
+                    .map(|&v| mesh.vertices()[v].clone()),
+            );
+            predicate(&face_vertices)
+        })
+        .collect()
+}
+
+/// Finds the interior faces that separate cells with different labels.
+///
+/// This selects the interfaces between regions of the mesh, such as the interfaces between
+/// different materials.
+///
+/// # Panics
+///
+/// Panics if the number of labels does not match the number of cells in the mesh.
+pub fn find_interface_faces<T, D, C, L>(mesh: &Mesh<T, D, C>, cell_labels: &[L]) -> Vec<InteriorFace>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    L: PartialEq,
+    DefaultAllocator: Allocator<T, D>,
+{
+    assert_eq!(
+        cell_labels.len(),
+        mesh.connectivity().len(),
+        "Number of labels must match number of cells"
+    );
+    find_interior_faces(mesh)
+        .into_iter()
+        .filter(|face| cell_labels[face.cells[0]] != cell_labels[face.cells[1]])
+        .collect()
+}
+
+/// A mesh that has been split along a set of interior faces.
+///
+/// The split mesh has the same cells as the original mesh, in the same order. The first vertices
+/// of the split mesh are the vertices of the original mesh, followed by the duplicated vertices.
+/// The original vertex of each vertex is given by [`parent_vertices`](Self::parent_vertices).
+///
+/// The split faces are retained as [`interface_faces`](Self::interface_faces), which refer to
+/// the cells on either side of each face. Since the cells are unchanged, the two sides of an
+/// interface face, e.g. for the construction of cohesive-zone elements, are obtained from the
+/// face connectivities of the two cells, see [`interface_face_connectivities`](Self::interface_face_connectivities).
+/// The two sides generally have different vertex indices, and their vertices can be paired
+/// through their parent vertices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitMesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    num_parent_vertices: usize,
+    parent_vertices: Vec<usize>,
+    interface_faces: Vec<InteriorFace>,
+}
+
+impl<T, D, C> SplitMesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    pub fn mesh(&self) -> &Mesh<T, D, C> {
+        &self.mesh
+    }
+
+    pub fn into_mesh(self) -> Mesh<T, D, C> {
+        self.mesh
+    }
+
+    /// The index of the vertex in the original mesh that each vertex of the split mesh
+    /// originates from.
+    pub fn parent_vertices(&self) -> &[usize] {
+        &self.parent_vertices
+    }
+
+    /// The number of vertices that were added by duplication.
+    pub fn num_duplicated_vertices(&self) -> usize {
+        self.parent_vertices.len() - self.num_parent_vertices
+    }
+
+    /// The number of vertices in the original mesh.
+    pub fn num_parent_vertices(&self) -> usize {
+        self.num_parent_vertices
+    }
+
+    /// The faces along which the mesh was split, [normalized](InteriorFace::normalized),
+    /// without duplicates and in sorted order.
+    pub fn interface_faces(&self) -> &[InteriorFace] {
+        &self.interface_faces
+    }
+
+    /// The face connectivities of the two sides of the given interface face in the split mesh.
+    ///
+    /// Each face connectivity has the orientation of the face in the corresponding cell,
+    /// so that the two sides have opposite orientations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn interface_face_connectivities(&self, interface_face_index: usize) -> [C::FaceConnectivity; 2] {
+        let face = &self.interface_faces[interface_face_index];
+        [0, 1].map(|side| {
+            self.mesh.connectivity()[face.cells[side]]
+                .get_face_connectivity(face.local_faces[side])
+                .unwrap()
+        })
+    }
+}
+
+/// Minimal union-find structure for the cells incident to a single vertex.
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Splits the mesh along the given interior faces by duplicating vertices.
+///
+/// Two cells remain connected at a vertex if they can be reached from each other by crossing
+/// faces that contain the vertex and are not split. For each vertex, each group of connected cells
+/// beyond the first (the group containing the cell with smallest index) receives a new copy of
+/// the vertex. In particular, vertices on the front of a crack, around which all cells remain
+/// connected, are not duplicated, whereas vertices on faces that separate the mesh into distinct
+/// regions are duplicated for every region. Vertices that are not incident to any split face are
+/// never duplicated.
+///
+/// Splitting the mesh along the faces returned by [`find_interface_faces`] separates all regions
+/// with different labels, while a subset of faces selected with [`find_interior_faces_where`]
+/// describes a crack that may end inside the mesh.
+///
+/// # Errors
+///
+/// Returns an error if any of the faces does not correspond to a face shared by the two given cells.
+pub fn split_mesh_along_faces<T, D, C>(mesh: &Mesh<T, D, C>, faces: &[InteriorFace]) -> eyre::Result<SplitMesh<T, D, C>>
+where
+    T: Scalar,
+    D: DimName,
+    C: ConnectivityMut,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let num_cells = mesh.connectivity().len();
+    let all_faces = collect_faces(mesh);
+
+    let mut split_faces = BTreeSet::new();
+    let mut interface_faces = BTreeSet::new();
+    for face in faces {
+        if face.cells.iter().any(|&cell| cell >= num_cells) {
+            return Err(eyre!("Face {face:?} refers to cell out of bounds"));
+        }
+        let [key0, key1] =
+            [0, 1].map(|side| sorted_face_vertices(&mesh.connectivity()[face.cells[side]], face.local_faces[side]));
+        match (key0, key1) {
+            (Some(key0), Some(key1)) if key0 == key1 && face.cells[0] != face.cells[1] => {
+                if all_faces[&key0].len() != 2 {
+                    return Err(eyre!("Face {face:?} is not shared by exactly two cells"));
+                }
+                split_faces.insert(key0);
+                interface_faces.insert(face.normalized());
+            }
+            _ => return Err(eyre!("Face {face:?} is not a face shared by the two cells")),
+        }
+    }
+
+    // Cells incident to each vertex
+    let mut vertex_cells = vec![Vec::new(); mesh.vertices().len()];
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        for &v in cell.vertex_indices() {
+            if vertex_cells[v].last() != Some(&cell_index) {
+                vertex_cells[v].push(cell_index);
+            }
+        }
+    }
+
+    // Connect cells at each vertex across faces that are not split. The union-find parents
+    // are local indices into the list of incident cells of each vertex
+    let mut vertex_cell_parents: Vec<Vec<usize>> = vertex_cells
+        .iter()
+        .map(|cells| (0..cells.len()).collect())
+        .collect();
+    for (key, cells) in &all_faces {
+        if cells.len() != 2 || split_faces.contains(key) {
+            continue;
+        }
+        let (cell0, cell1) = (cells[0].0, cells[1].0);
+        for &v in key {
+            let local = |cell| vertex_cells[v].iter().position(|&c| c == cell).unwrap();
+            let (root0, root1) = {
+                let parents = &mut vertex_cell_parents[v];
+                let (local0, local1) = (local(cell0), local(cell1));
+                (find_root(parents, local0), find_root(parents, local1))
+            };
+            let parents = &mut vertex_cell_parents[v];
+            parents[root0.max(root1)] = root0.min(root1);
+        }
+    }
+
+    // Assign a vertex to each group of connected cells at each vertex. The group containing
+    // the first incident cell keeps the original vertex
+    let mut vertices = mesh.vertices().to_vec();
+    let mut parent_vertices: Vec<_> = (0..vertices.len()).collect();
+    let mut connectivity = mesh.connectivity().to_vec();
+    for (v, cells) in vertex_cells.iter().enumerate() {
+        let parents = &mut vertex_cell_parents[v];
+        let mut group_vertices = vec![None; cells.len()];
+        for (local, &cell_index) in cells.iter().enumerate() {
+            let root = find_root(parents, local);
+            let new_vertex = *group_vertices[root].get_or_insert_with(|| {
+                if root == 0 {
+                    v
+                } else {
+                    vertices.push(mesh.vertices()[v].clone());
+                    parent_vertices.push(v);
+                    vertices.len() - 1
+                }
+            });
+            if new_vertex != v {
+                for index in connectivity[cell_index].vertex_indices_mut() {
+                    if *index == v {
+                        *index = new_vertex;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(SplitMesh {
+        mesh: Mesh::from_vertices_and_connectivity(vertices, connectivity),
+        num_parent_vertices: mesh.vertices().len(),
+        parent_vertices,
+        interface_faces: interface_faces.into_iter().collect(),
+    })
+}
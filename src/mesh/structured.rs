@@ -0,0 +1,391 @@
+//! Axis-aligned structured quadrilateral grids.
+//!
+//! A [`StructuredQuadGrid2d`] is a uniform grid of axis-aligned rectangular cells. In contrast to
+//! a general [`QuadMesh2d`], neither the vertices nor the connectivity are stored: both follow
+//! from the origin, the cell size and the number of cells in each direction. Cells and vertices
+//! can be indexed directly by their grid coordinates $(i, j)$, and since every cell is a
+//! rectangle of the same size, the Jacobian of the reference map is constant and identical for
+//! all cells. This makes the grid a natural fit for image-based problems and benchmarks.
+//!
+//! The grid implements [`FiniteElementSpace`] with bilinear basis functions, so it can be used
+//! with the assemblers directly. Since all cells report a
+//! [constant Jacobian](FiniteElementSpace::element_constant_reference_jacobian), the assemblers
+//! skip the evaluation of the geometry at individual quadrature points. For IO and for the
+//! functionality that is only available for meshes, the grid can be converted to a
+//! [`QuadMesh2d`] with the same vertex and cell numbering:
+//!
+//! ```
+//! # use fenris::mesh::QuadMesh2d;
+//! # use fenris::mesh::structured::StructuredQuadGrid2d;
+//! # use fenris::nalgebra::{Point2, Vector2};
+//! let grid = StructuredQuadGrid2d::new(Point2::new(0.0, 0.0), Vector2::new(0.5, 0.25), 4, 2);
+//! assert_eq!(grid.num_cells(), 8);
+//! assert_eq!(grid.vertex(4, 2), Point2::new(2.0, 0.5));
+//!
+//! let mesh = QuadMesh2d::from(&grid);
+//! assert_eq!(mesh.vertices()[grid.vertex_index(4, 2)], grid.vertex(4, 2));
+//! ```
+use crate::connectivity::Quad4d2Connectivity;
+use crate::element::{ClosestPoint, Quad4d2Element, ReferenceFiniteElement};
+use crate::geometry::{AxisAlignedBoundingBox, AxisAlignedBoundingBox2d};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::memory::HeapSizeEstimate;
+use crate::mesh::QuadMesh2d;
+use crate::nalgebra::{Dyn, Matrix2, MatrixViewMut, Point2, Scalar, Vector2, U2};
+use crate::space::{
+    BoundsForElementInSpace, ClosestPointInElementInSpace, FindClosestElement, FiniteElementConnectivity,
+    FiniteElementSpace,
+};
+use crate::Real;
+
+/// A uniform grid of axis-aligned rectangular cells in two dimensions.
+///
+/// Vertex $(i, j)$ is located at $x_0 + (i h_x, j h_y)$, where $x_0$ is the origin (the corner
+/// with the smallest coordinates) and $(h_x, h_y)$ the cell size. Vertices and cells are numbered
+/// row by row, i.e. with $i$ varying fastest. The vertices of each cell are ordered
+/// counter-clockwise starting from the corner with the smallest coordinates, consistent with
+/// [`Quad4d2Connectivity`].
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredQuadGrid2d<T: Scalar> {
+    origin: Point2<T>,
+    cell_size: Vector2<T>,
+    num_cells_x: usize,
+    num_cells_y: usize,
+}
+
+impl<T: Real> StructuredQuadGrid2d<T> {
+    /// Creates a grid with the given number of cells in each direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell size is not positive in both directions.
+    pub fn new(origin: Point2<T>, cell_size: Vector2<T>, num_cells_x: usize, num_cells_y: usize) -> Self {
+        assert!(
+            cell_size.x > T::zero() && cell_size.y > T::zero(),
+            "Cell size must be positive"
+        );
+        Self {
+            origin,
+            cell_size,
+            num_cells_x,
+            num_cells_y,
+        }
+    }
+
+    /// Creates a grid that covers the given bounding box with the given number of cells in each
+    /// direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of cells is zero in either direction, or if the bounding box has
+    /// zero extent in either direction.
+    pub fn from_bounds(bounds: &AxisAlignedBoundingBox2d<T>, num_cells_x: usize, num_cells_y: usize) -> Self {
+        assert!(num_cells_x > 0 && num_cells_y > 0, "Number of cells must be positive");
+        let extents = bounds.extents();
+        let cell_size = Vector2::new(
+            extents.x / T::from_usize(num_cells_x).unwrap(),
+            extents.y / T::from_usize(num_cells_y).unwrap(),
+        );
+        Self::new(*bounds.min(), cell_size, num_cells_x, num_cells_y)
+    }
+
+    pub fn origin(&self) -> &Point2<T> {
+        &self.origin
+    }
+
+    pub fn cell_size(&self) -> &Vector2<T> {
+        &self.cell_size
+    }
+
+    pub fn num_cells_x(&self) -> usize {
+        self.num_cells_x
+    }
+
+    pub fn num_cells_y(&self) -> usize {
+        self.num_cells_y
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.num_cells_x * self.num_cells_y
+    }
+
+    pub fn num_vertices_x(&self) -> usize {
+        self.num_cells_x + 1
+    }
+
+    pub fn num_vertices_y(&self) -> usize {
+        self.num_cells_y + 1
+    }
+
+    /// The number of vertices of the grid.
+    ///
+    /// A grid without cells has no vertices.
+    pub fn num_vertices(&self) -> usize {
+        if self.num_cells() == 0 {
+            0
+        } else {
+            self.num_vertices_x() * self.num_vertices_y()
+        }
+    }
+
+    /// The bounding box of the grid.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox2d<T> {
+        AxisAlignedBoundingBox::new(self.origin, self.vertex(self.num_cells_x, self.num_cells_y))
+    }
+
+    /// The index of vertex $(i, j)$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vertex is out of bounds.
+    pub fn vertex_index(&self, i: usize, j: usize) -> usize {
+        assert!(
+            i < self.num_vertices_x() && j < self.num_vertices_y(),
+            "Vertex index out of bounds"
+        );
+        self.num_vertices_x() * j + i
+    }
+
+    /// The grid coordinates $(i, j)$ of the vertex with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vertex index is out of bounds.
+    pub fn vertex_ij(&self, vertex_index: usize) -> (usize, usize) {
+        assert!(vertex_index < self.num_vertices(), "Vertex index out of bounds");
+        (
+            vertex_index % self.num_vertices_x(),
+            vertex_index / self.num_vertices_x(),
+        )
+    }
+
+    /// The position of vertex $(i, j)$.
+    ///
+    /// Indices beyond the grid are extrapolated.
+    pub fn vertex(&self, i: usize, j: usize) -> Point2<T> {
+        let offset = Vector2::new(
+            T::from_usize(i).unwrap() * self.cell_size.x,
+            T::from_usize(j).unwrap() * self.cell_size.y,
+        );
+        self.origin + offset
+    }
+
+    /// The index of cell $(i, j)$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell is out of bounds.
+    pub fn cell_index(&self, i: usize, j: usize) -> usize {
+        assert!(i < self.num_cells_x && j < self.num_cells_y, "Cell index out of bounds");
+        self.num_cells_x * j + i
+    }
+
+    /// The grid coordinates $(i, j)$ of the cell with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell index is out of bounds.
+    pub fn cell_ij(&self, cell_index: usize) -> (usize, usize) {
+        assert!(cell_index < self.num_cells(), "Cell index out of bounds");
+        (cell_index % self.num_cells_x, cell_index / self.num_cells_x)
+    }
+
+    /// The vertex indices of cell $(i, j)$, in the order of [`Quad4d2Connectivity`].
+    pub fn cell_vertex_indices(&self, i: usize, j: usize) -> [usize; 4] {
+        assert!(i < self.num_cells_x && j < self.num_cells_y, "Cell index out of bounds");
+        [
+            self.vertex_index(i, j),
+            self.vertex_index(i + 1, j),
+            self.vertex_index(i + 1, j + 1),
+            self.vertex_index(i, j + 1),
+        ]
+    }
+
+    /// The connectivity of cell $(i, j)$.
+    pub fn cell_connectivity(&self, i: usize, j: usize) -> Quad4d2Connectivity {
+        Quad4d2Connectivity(self.cell_vertex_indices(i, j))
+    }
+
+    /// The bounding box of cell $(i, j)$, which coincides with the cell itself.
+    pub fn cell_bounds(&self, i: usize, j: usize) -> AxisAlignedBoundingBox2d<T> {
+        AxisAlignedBoundingBox::new(self.vertex(i, j), self.vertex(i + 1, j + 1))
+    }
+
+    /// Finds the grid coordinates of the cell that contains the given point.
+    ///
+    /// Points on the boundary between cells are attributed to the cell with the larger index.
+    /// Returns `None` if the point lies outside the grid.
+    pub fn find_cell_containing_point(&self, point: &Point2<T>) -> Option<(usize, usize)> {
+        let locate = |x: T, x0: T, h: T, n: usize| {
+            let s = (x - x0) / h;
+            let n_as_t = T::from_usize(n).unwrap();
+            if s >= T::zero() && s <= n_as_t && n > 0 {
+                // Points on the upper boundary belong to the last cell
+                Some(s.floor().to_subset().unwrap().min(n as f64 - 1.0) as usize)
+            } else {
+                None
+            }
+        };
+        let i = locate(point.x, self.origin.x, self.cell_size.x, self.num_cells_x)?;
+        let j = locate(point.y, self.origin.y, self.cell_size.y, self.num_cells_y)?;
+        Some((i, j))
+    }
+
+    /// The reference coordinates of the given point with respect to cell $(i, j)$.
+    ///
+    /// Points outside the cell map to reference coordinates outside $[-1, 1]^2$.
+    pub fn reference_coords_in_cell(&self, i: usize, j: usize, point: &Point2<T>) -> Point2<T> {
+        let two = T::from_f64(2.0).unwrap();
+        let offset = point - self.vertex(i, j);
+        Point2::new(
+            two * offset.x / self.cell_size.x - T::one(),
+            two * offset.y / self.cell_size.y - T::one(),
+        )
+    }
+
+    /// The Jacobian of the reference map, which is the same for all cells.
+    pub fn reference_jacobian(&self) -> Matrix2<T> {
+        let half = T::from_f64(0.5).unwrap();
+        Matrix2::from_diagonal(&(self.cell_size * half))
+    }
+
+    /// Creates a [`QuadMesh2d`] with the same vertices and cells.
+    pub fn to_mesh(&self) -> QuadMesh2d<T> {
+        let vertices = (0..self.num_vertices())
+            .map(|v| {
+                let (i, j) = self.vertex_ij(v);
+                self.vertex(i, j)
+            })
+            .collect();
+        let cells = (0..self.num_cells())
+            .map(|cell| {
+                let (i, j) = self.cell_ij(cell);
+                self.cell_connectivity(i, j)
+            })
+            .collect();
+        QuadMesh2d::from_vertices_and_connectivity(vertices, cells)
+    }
+}
+
+impl<'a, T: Real> From<&'a StructuredQuadGrid2d<T>> for QuadMesh2d<T> {
+    fn from(grid: &'a StructuredQuadGrid2d<T>) -> Self {
+        grid.to_mesh()
+    }
+}
+
+/// The grid owns no heap memory.
+impl<T: Scalar> HeapSizeEstimate for StructuredQuadGrid2d<T> {
+    fn heap_size_estimate(&self) -> usize {
+        0
+    }
+}
+
+impl<T: Real> FiniteElementConnectivity for StructuredQuadGrid2d<T> {
+    fn num_elements(&self) -> usize {
+        self.num_cells()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.num_vertices()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        assert!(element_index.index() < self.num_cells(), "Element index out of bounds");
+        4
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        let (i, j) = self.cell_ij(element_index.index());
+        nodes.copy_from_slice(&self.cell_vertex_indices(i, j).map(NodeIndex::new));
+    }
+}
+
+impl<T: Real> FiniteElementSpace<T> for StructuredQuadGrid2d<T> {
+    type GeometryDim = U2;
+    type ReferenceDim = U2;
+
+    fn populate_element_basis(&self, element_index: usize, basis_values: &mut [T], reference_coords: &Point2<T>) {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        Quad4d2Element::reference().populate_basis(basis_values, reference_coords);
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        gradients: MatrixViewMut<T, U2, Dyn>,
+        reference_coords: &Point2<T>,
+    ) {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        Quad4d2Element::reference().populate_basis_gradients(gradients, reference_coords);
+    }
+
+    fn element_reference_jacobian(&self, element_index: usize, _reference_coords: &Point2<T>) -> Matrix2<T> {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        self.reference_jacobian()
+    }
+
+    fn map_element_reference_coords(&self, element_index: usize, reference_coords: &Point2<T>) -> Point2<T> {
+        let (i, j) = self.cell_ij(element_index);
+        let half = T::from_f64(0.5).unwrap();
+        let x0 = self.vertex(i, j);
+        let scaled = (reference_coords.coords.add_scalar(T::one()) * half).component_mul(&self.cell_size);
+        x0 + scaled
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        self.cell_size.norm()
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        true
+    }
+
+    fn element_constant_reference_jacobian(&self, element_index: usize) -> Option<Matrix2<T>> {
+        assert!(element_index < self.num_cells(), "Element index out of bounds");
+        Some(self.reference_jacobian())
+    }
+}
+
+impl<T: Real> BoundsForElementInSpace<T> for StructuredQuadGrid2d<T> {
+    fn bounds_for_element(&self, element_index: usize) -> AxisAlignedBoundingBox2d<T> {
+        let (i, j) = self.cell_ij(element_index);
+        self.cell_bounds(i, j)
+    }
+}
+
+impl<T: Real> ClosestPointInElementInSpace<T> for StructuredQuadGrid2d<T> {
+    fn closest_point_in_element(&self, element_index: usize, p: &Point2<T>) -> ClosestPoint<T, U2> {
+        let (i, j) = self.cell_ij(element_index);
+        let xi = self.reference_coords_in_cell(i, j, p);
+        let clamped = xi.map(|xi_k| xi_k.max(-T::one()).min(T::one()));
+        if clamped == xi {
+            ClosestPoint::InElement(xi)
+        } else {
+            ClosestPoint::ClosestPoint(clamped)
+        }
+    }
+}
+
+/// Finds the closest element in constant time from the grid coordinates of the point.
+impl<T: Real> FindClosestElement<T> for StructuredQuadGrid2d<T> {
+    fn find_closest_element_and_reference_coords(&self, point: &Point2<T>) -> Option<(usize, Point2<T>)> {
+        if self.num_cells() == 0 {
+            return None;
+        }
+        // Project the point onto the grid, which is inside the closest cell
+        let bounds = self.bounds();
+        let projected = Point2::new(
+            point.x.max(bounds.min().x).min(bounds.max().x),
+            point.y.max(bounds.min().y).min(bounds.max().y),
+        );
+        let (i, j) = self.find_cell_containing_point(&projected)?;
+        let xi = self
+            .reference_coords_in_cell(i, j, point)
+            .map(|xi_k| xi_k.max(-T::one()).min(T::one()));
+        Some((self.cell_index(i, j), xi))
+    }
+}
@@ -0,0 +1,202 @@
+//! Extraction of submeshes with a mapping between local and global indices.
+//!
+//! A submesh consists of a subset of the cells of a mesh, for example the cells of a single
+//! material in a multi-material domain, the cells of a partition or the cells in the
+//! neighborhood of a problematic region. The submesh only contains the vertices referenced by
+//! its cells, which are numbered consecutively. The [`SubmeshMapping`] returned by
+//! [`Mesh::extract_submesh`] relates the local indices of nodes and cells in the submesh to the
+//! global indices in the original mesh, so that quantities can be transferred between the two.
+use crate::connectivity::ConnectivityMut;
+use crate::mesh::Mesh;
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Scalar};
+
+/// The mapping between local indices in a submesh and global indices in the original mesh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmeshMapping {
+    global_nodes: Vec<usize>,
+    global_cells: Vec<usize>,
+    /// Pairs of global and local cell indices, sorted by global index.
+    sorted_cells: Vec<(usize, usize)>,
+}
+
+impl SubmeshMapping {
+    /// The global index of each local node.
+    ///
+    /// The local nodes are ordered by their global index, so this is sorted in ascending order.
+    pub fn global_nodes(&self) -> &[usize] {
+        &self.global_nodes
+    }
+
+    /// The global index of each local cell.
+    ///
+    /// The local cells are ordered in the same way as the cells passed to
+    /// [`Mesh::extract_submesh`].
+    pub fn global_cells(&self) -> &[usize] {
+        &self.global_cells
+    }
+
+    pub fn num_local_nodes(&self) -> usize {
+        self.global_nodes.len()
+    }
+
+    pub fn num_local_cells(&self) -> usize {
+        self.global_cells.len()
+    }
+
+    /// Returns the local index of the given global node, or `None` if the node is not part of
+    /// the submesh.
+    pub fn local_node(&self, global_node: usize) -> Option<usize> {
+        self.global_nodes.binary_search(&global_node).ok()
+    }
+
+    /// Returns the local index of the given global cell, or `None` if the cell is not part of
+    /// the submesh.
+    pub fn local_cell(&self, global_cell: usize) -> Option<usize> {
+        self.sorted_cells
+            .binary_search_by_key(&global_cell, |&(global, _)| global)
+            .ok()
+            .map(|i| self.sorted_cells[i].1)
+    }
+
+    /// Gathers the values of all local nodes from a global nodal vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the global vector does not cover all local nodes.
+    pub fn gather_from_global<'a, T: Scalar>(
+        &self,
+        global: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> DVector<T> {
+        let global = global.into();
+        let s = solution_dim;
+        DVector::from_iterator(
+            s * self.num_local_nodes(),
+            self.global_nodes
+                .iter()
+                .flat_map(|&node| (0..s).map(move |i| s * node + i))
+                .map(|index| global[index].clone()),
+        )
+    }
+
+    /// Writes the values of a local nodal vector into the corresponding entries of a global
+    /// nodal vector. Entries of nodes that are not part of the submesh are left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the local vector does not match the number of local nodes,
+    /// or if the global vector does not cover all local nodes.
+    pub fn scatter_to_global<'a, 'b, T: Scalar>(
+        &self,
+        local: impl Into<DVectorView<'a, T>>,
+        global: impl Into<DVectorViewMut<'b, T>>,
+        solution_dim: usize,
+    ) {
+        let (local, mut global) = (local.into(), global.into());
+        let s = solution_dim;
+        assert_eq!(
+            local.len(),
+            s * self.num_local_nodes(),
+            "Dimension of local vector must match number of local nodes"
+        );
+        for (local_index, &node) in self.global_nodes.iter().enumerate() {
+            global
+                .rows_mut(s * node, s)
+                .copy_from(&local.rows(s * local_index, s));
+        }
+    }
+}
+
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: ConnectivityMut,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Extracts a submesh consisting of the given cells, along with the mapping between local
+    /// and global indices.
+    ///
+    /// The submesh only contains the vertices that are referenced by the given cells, with
+    /// each vertex stored once. The local cells are stored in the given order. Named
+    /// [sets](Self::sets) are restricted to the submesh and relabeled accordingly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cell index is out of bounds or if a cell is given more than once.
+    pub fn extract_submesh(&self, cell_indices: &[usize]) -> eyre::Result<(Self, SubmeshMapping)> {
+        let mut is_selected = vec![false; self.connectivity().len()];
+        for &cell_index in cell_indices {
+            let selected = is_selected.get_mut(cell_index).ok_or_else(|| {
+                eyre!(
+                    "Cell index {cell_index} is out of bounds for mesh with {} cells",
+                    self.connectivity().len()
+                )
+            })?;
+            if *selected {
+                return Err(eyre!("Cell {cell_index} is selected more than once"));
+            }
+            *selected = true;
+        }
+        Ok(self.extract_submesh_unchecked(cell_indices))
+    }
+
+    /// Extracts the submesh without checking for out-of-bounds or duplicate cell indices.
+    ///
+    /// Out-of-bounds indices lead to a panic. Duplicate cells are duplicated in the submesh,
+    /// in which case the local index of the cell in the mapping refers to one of the copies.
+    pub(crate) fn extract_submesh_unchecked(&self, cell_indices: &[usize]) -> (Self, SubmeshMapping) {
+        let mut global_to_local_node = vec![None; self.vertices().len()];
+        for &cell_index in cell_indices {
+            for &vertex_index in self.connectivity()[cell_index].vertex_indices() {
+                global_to_local_node[vertex_index] = Some(0);
+            }
+        }
+
+        // Number the selected nodes in ascending order of their global index
+        let mut global_nodes = Vec::new();
+        for (global_index, local_index) in global_to_local_node.iter_mut().enumerate() {
+            if local_index.is_some() {
+                *local_index = Some(global_nodes.len());
+                global_nodes.push(global_index);
+            }
+        }
+
+        let vertices = global_nodes
+            .iter()
+            .map(|&node| self.vertices()[node].clone())
+            .collect();
+        let connectivity = cell_indices
+            .iter()
+            .map(|&cell_index| {
+                let mut conn = self.connectivity()[cell_index].clone();
+                for vertex_index in conn.vertex_indices_mut() {
+                    *vertex_index = global_to_local_node[*vertex_index].expect("Vertex must be part of the submesh");
+                }
+                conn
+            })
+            .collect();
+
+        let mut sorted_cells: Vec<_> = cell_indices
+            .iter()
+            .enumerate()
+            .map(|(local, &global)| (global, local))
+            .collect();
+        sorted_cells.sort_unstable();
+        let mapping = SubmeshMapping {
+            global_nodes,
+            global_cells: cell_indices.to_vec(),
+            sorted_cells,
+        };
+
+        let sets = self.sets().relabeled(
+            |global_node| global_to_local_node[global_node],
+            |global_cell| mapping.local_cell(global_cell),
+        );
+        let mut submesh = Mesh::from_vertices_and_connectivity(vertices, connectivity);
+        *submesh.sets_mut() = sets;
+        (submesh, mapping)
+    }
+}
@@ -0,0 +1,184 @@
+//! Supermesh construction for two overlapping meshes in 2D.
+//!
+//! The *supermesh* of two meshes covering (parts of) the same domain consists of all non-empty
+//! intersections of a cell in the first mesh with a cell in the second mesh. Each supermesh cell
+//! is therefore contained in exactly one cell of each mesh, which makes the supermesh the basis
+//! of conservative remap and of other operations that need to integrate products of functions
+//! defined on non-matching meshes.
+//!
+//! In 2D, the intersection of two convex cells is a convex polygon, which is computed by clipping
+//! one cell against the half-planes of the other.
+use crate::connectivity::{CellConnectivity, Connectivity, Tri3d2Connectivity};
+use crate::geometry::{AxisAlignedBoundingBox, ConvexPolygon};
+use crate::mesh::{Mesh2d, TriangleMesh2d};
+use crate::space::spatially_indexed::RTreeAccelerationStructure;
+use crate::Real;
+use eyre::eyre;
+use nalgebra::{Point2, U2};
+
+/// A cell of a [`Supermesh2d`], i.e. the intersection of a cell in each of the two meshes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupermeshCell<T: Real> {
+    /// The intersection of the two parent cells.
+    pub polygon: ConvexPolygon<T>,
+    /// The index of the parent cell in the first mesh.
+    pub first_cell: usize,
+    /// The index of the parent cell in the second mesh.
+    pub second_cell: usize,
+}
+
+impl<T: Real> SupermeshCell<T> {
+    pub fn area(&self) -> T {
+        polygon_area(&self.polygon)
+    }
+}
+
+/// The supermesh of two 2D meshes with convex cells.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supermesh2d<T: Real> {
+    cells: Vec<SupermeshCell<T>>,
+}
+
+impl<T: Real> Supermesh2d<T> {
+    /// Computes the supermesh of the two given meshes.
+    ///
+    /// Candidate pairs of cells are found with a bounding volume hierarchy over the cells of the
+    /// second mesh. Intersections with an area that is negligible compared to the parent cells,
+    /// such as cells that only share an edge or a vertex, are discarded. The cells of the
+    /// supermesh are ordered by the index of their parent cell in the first mesh, then by the
+    /// index of the parent cell in the second mesh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cell in either mesh is not convex.
+    pub fn from_meshes<C1, C2>(first: &Mesh2d<T, C1>, second: &Mesh2d<T, C2>) -> eyre::Result<Self>
+    where
+        C1: CellConnectivity<T, U2>,
+        C2: CellConnectivity<T, U2>,
+        C1::Cell: TryInto<ConvexPolygon<T>>,
+        C2::Cell: TryInto<ConvexPolygon<T>>,
+    {
+        let second_polygons = convex_polygons(second).map_err(|err| err.wrap_err("Invalid cell in second mesh"))?;
+        let second_bounds: Vec<_> = second
+            .connectivity()
+            .iter()
+            .map(|conn| cell_bounds(second.vertices(), conn))
+            .collect();
+        let second_tree = RTreeAccelerationStructure::from_bounding_boxes(&second_bounds);
+
+        let tolerance = T::from_f64(1e-12).unwrap();
+        let first_polygons = convex_polygons(first).map_err(|err| err.wrap_err("Invalid cell in first mesh"))?;
+        let mut cells = Vec::new();
+        for (first_cell, (first_polygon, first_conn)) in first_polygons.iter().zip(first.connectivity()).enumerate() {
+            let first_area = polygon_area(first_polygon);
+            let mut candidates = second_tree.intersecting_cell_candidates(&cell_bounds(first.vertices(), first_conn));
+            candidates.sort_unstable();
+            for second_cell in candidates {
+                let second_polygon = &second_polygons[second_cell];
+                let min_area = first_area.min(polygon_area(second_polygon));
+                let polygon = remove_duplicate_vertices(
+                    &first_polygon.intersect_polygon(second_polygon),
+                    tolerance * min_area.sqrt(),
+                );
+                if polygon_area(&polygon) > tolerance * min_area {
+                    cells.push(SupermeshCell {
+                        polygon,
+                        first_cell,
+                        second_cell,
+                    });
+                }
+            }
+        }
+        Ok(Self { cells })
+    }
+
+    pub fn cells(&self) -> &[SupermeshCell<T>] {
+        &self.cells
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The total area of all supermesh cells, i.e. the area of the overlap of the two meshes.
+    pub fn area(&self) -> T {
+        self.cells
+            .iter()
+            .map(SupermeshCell::area)
+            .fold(T::zero(), |a, b| a + b)
+    }
+
+    /// Triangulates the supermesh.
+    ///
+    /// Returns the triangle mesh along with the index of the supermesh cell that each triangle
+    /// belongs to. Vertices are shared between the triangles of a single supermesh cell, but
+    /// not between different supermesh cells.
+    pub fn triangulate(&self) -> (TriangleMesh2d<T>, Vec<usize>) {
+        let mut vertices = Vec::new();
+        let mut connectivity = Vec::new();
+        let mut parent_cells = Vec::new();
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            let offset = vertices.len();
+            vertices.extend_from_slice(cell.polygon.vertices());
+            for i in 1..cell.polygon.vertices().len() - 1 {
+                connectivity.push(Tri3d2Connectivity([offset, offset + i, offset + i + 1]));
+                parent_cells.push(cell_idx);
+            }
+        }
+        (
+            TriangleMesh2d::from_vertices_and_connectivity(vertices, connectivity),
+            parent_cells,
+        )
+    }
+}
+
+fn convex_polygons<T, C>(mesh: &Mesh2d<T, C>) -> eyre::Result<Vec<ConvexPolygon<T>>>
+where
+    T: Real,
+    C: CellConnectivity<T, U2>,
+    C::Cell: TryInto<ConvexPolygon<T>>,
+{
+    mesh.connectivity()
+        .iter()
+        .enumerate()
+        .map(|(cell_idx, conn)| {
+            conn.cell(mesh.vertices())
+                .and_then(|cell| cell.try_into().ok())
+                .ok_or_else(|| eyre!("Cell {cell_idx} is not convex"))
+        })
+        .collect()
+}
+
+fn cell_bounds<T: Real>(vertices: &[Point2<T>], conn: &impl Connectivity) -> AxisAlignedBoundingBox<T, U2> {
+    AxisAlignedBoundingBox::from_points(conn.vertex_indices().iter().map(|&i| &vertices[i]))
+        .expect("Cells must have at least one vertex")
+}
+
+fn polygon_area<T: Real>(polygon: &ConvexPolygon<T>) -> T {
+    polygon
+        .triangulate()
+        .map(|triangle| triangle.area())
+        .fold(T::zero(), |a, b| a + b)
+}
+
+/// Removes consecutive vertices that are closer than the given distance.
+///
+/// Clipping may produce nearly coincident vertices where an edge of one cell passes through
+/// a vertex of the other, which would otherwise lead to degenerate triangles.
+fn remove_duplicate_vertices<T: Real>(polygon: &ConvexPolygon<T>, distance: T) -> ConvexPolygon<T> {
+    let mut vertices: Vec<Point2<T>> = Vec::with_capacity(polygon.vertices().len());
+    for v in polygon.vertices() {
+        if vertices
+            .last()
+            .is_none_or(|last| (v - last).norm() > distance)
+        {
+            vertices.push(*v);
+        }
+    }
+    while vertices.len() > 1 && (vertices[0] - vertices[vertices.len() - 1]).norm() <= distance {
+        vertices.pop();
+    }
+    ConvexPolygon::from_vertices(vertices)
+}
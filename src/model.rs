@@ -1,3 +1,16 @@
+#[cfg(feature = "experimental-models")]
+pub mod fct;
+#[cfg(feature = "experimental-models")]
+pub mod inversion;
+#[cfg(feature = "experimental-models")]
+pub mod limiter;
+#[cfg(feature = "experimental-models")]
+pub mod newton;
+#[cfg(feature = "experimental-models")]
+pub mod phase_field;
+#[cfg(feature = "experimental-models")]
+pub mod stokes;
+
 use crate::allocators::BiDimAllocator;
 use crate::geometry::DistanceQuery;
 use crate::space::GeometricFiniteElementSpace;
@@ -0,0 +1,260 @@
+//! Algebraic flux-corrected transport (FCT) for continuous finite element advection.
+//!
+//! The Galerkin discretization of the transport of a scalar $u$ leads to the semi-discrete system
+//! <div>$$
+//! \vec M_C \dot{\vec u} = \vec K \vec u,
+//! $$</div>
+//! where $\vec M_C$ is the consistent mass matrix and $\vec K$ the (negative) transport operator,
+//! e.g. $k_{ij} = - \int_\Omega \varphi_i \, \vec \beta \cdot \nabla \varphi_j \enspace \mathrm{d} x$
+//! for linear advection with velocity $\vec \beta$. The Galerkin scheme is accurate, but produces
+//! spurious oscillations near steep fronts and therefore violates bounds such as non-negativity
+//! of concentrations.
+//!
+//! Algebraic flux correction (Kuzmin and Möller) constructs a *low-order* scheme
+//! <div>$$
+//! \vec M_L \dot{\vec u} = \vec L \vec u, \qquad \vec L = \vec K + \vec D,
+//! $$</div>
+//! by lumping the mass matrix and adding the *discrete diffusion operator* $\vec D$ with
+//! $d_{ij} = \max(-k_{ij}, 0, -k_{ji})$ for $i \neq j$ and zero row sums. The low-order operator
+//! $\vec L$ has no negative off-diagonal entries, so that explicit time stepping with a sufficiently
+//! small time step is positivity preserving and satisfies a discrete maximum principle. The
+//! difference between the Galerkin and the low-order scheme decomposes into *antidiffusive fluxes*
+//! <div>$$
+//! f_{ij} = m_{ij} (\dot u_i - \dot u_j) + d_{ij} (u_i - u_j), \qquad f_{ji} = - f_{ij},
+//! $$</div>
+//! which are limited with Zalesak's limiter and added to the low-order solution, recovering as
+//! much of the accuracy of the Galerkin scheme as the local bounds admit. Since the fluxes are
+//! antisymmetric, the scheme conserves the total mass $\sum_i m_i u_i$.
+//!
+//! [`FluxCorrectedTransport`] implements the explicit FCT algorithm for a forward Euler step,
+//! which can be used as the building block of strong stability preserving Runge-Kutta methods.
+use crate::nalgebra::DVector;
+use crate::nalgebra_sparse::CsrMatrix;
+use crate::Real;
+use eyre::eyre;
+use itertools::izip;
+
+/// Explicit flux-corrected transport for the semi-discrete system $\vec M_C \dot{\vec u} = \vec K \vec u$.
+///
+/// See the [module documentation](self) for the details of the method.
+#[derive(Debug, Clone)]
+pub struct FluxCorrectedTransport<T: Real> {
+    consistent_mass: CsrMatrix<T>,
+    lumped_mass: DVector<T>,
+    discrete_diffusion: CsrMatrix<T>,
+    low_order_operator: CsrMatrix<T>,
+}
+
+impl<T: Real> FluxCorrectedTransport<T> {
+    /// Constructs the low-order operator for the given consistent mass matrix and transport
+    /// operator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrices are not square, if they do not have the same sparsity
+    /// pattern, if the sparsity pattern is not symmetric or lacks diagonal entries, or if a
+    /// lumped mass is not positive.
+    pub fn try_new(mass: &CsrMatrix<T>, transport: &CsrMatrix<T>) -> eyre::Result<Self> {
+        if mass.nrows() != mass.ncols() {
+            return Err(eyre!("Mass matrix must be square"));
+        }
+        if mass.pattern() != transport.pattern() {
+            return Err(eyre!(
+                "Mass matrix and transport operator must have the same sparsity pattern"
+            ));
+        }
+        let transport_transpose = transport.transpose();
+        if transport_transpose.pattern() != transport.pattern() {
+            return Err(eyre!("Sparsity pattern of the transport operator must be symmetric"));
+        }
+
+        let n = mass.nrows();
+        let mut lumped_mass = DVector::zeros(n);
+        for (i, row) in mass.row_iter().enumerate() {
+            lumped_mass[i] = row.values().iter().fold(T::zero(), |sum, &m| sum + m);
+            if lumped_mass[i] <= T::zero() {
+                return Err(eyre!("Lumped mass of node {} is not positive", i));
+            }
+        }
+
+        // Since the pattern is symmetric, the values of the transpose are aligned with the values
+        // of the original matrix, i.e. entry k of the transpose holds k_ji for entry k = (i, j)
+        let mut discrete_diffusion = transport.clone();
+        for (i, (mut row, row_transpose)) in discrete_diffusion
+            .row_iter_mut()
+            .zip(transport_transpose.row_iter())
+            .enumerate()
+        {
+            let (cols, values) = row.cols_and_values_mut();
+            let mut diagonal_index = None;
+            let mut row_sum = T::zero();
+            for (idx, (&j, d_ij, &k_ji)) in izip!(cols.iter(), values.iter_mut(), row_transpose.values()).enumerate() {
+                if i == j {
+                    diagonal_index = Some(idx);
+                } else {
+                    let k_ij = *d_ij;
+                    *d_ij = T::zero().max(-k_ij).max(-k_ji);
+                    row_sum += *d_ij;
+                }
+            }
+            let diagonal_index =
+                diagonal_index.ok_or_else(|| eyre!("Sparsity pattern does not contain diagonal entry in row {}", i))?;
+            values[diagonal_index] = -row_sum;
+        }
+        let low_order_operator = transport + &discrete_diffusion;
+
+        Ok(Self {
+            consistent_mass: mass.clone(),
+            lumped_mass,
+            discrete_diffusion,
+            low_order_operator,
+        })
+    }
+
+    /// The lumped (row-sum) mass matrix $\vec M_L$ as a vector of its diagonal entries.
+    pub fn lumped_mass(&self) -> &DVector<T> {
+        &self.lumped_mass
+    }
+
+    /// The discrete diffusion operator $\vec D$.
+    pub fn discrete_diffusion(&self) -> &CsrMatrix<T> {
+        &self.discrete_diffusion
+    }
+
+    /// The low-order operator $\vec L = \vec K + \vec D$.
+    pub fn low_order_operator(&self) -> &CsrMatrix<T> {
+        &self.low_order_operator
+    }
+
+    /// The largest time step for which the low-order forward Euler step preserves positivity,
+    /// $\Delta t = \min_i m_i / |l_{ii}|$.
+    ///
+    /// Returns `None` if the low-order operator has no negative diagonal entries, in which case
+    /// the time step is not restricted.
+    pub fn max_low_order_time_step(&self) -> Option<T> {
+        self.low_order_operator
+            .diagonal_as_csr()
+            .triplet_iter()
+            .filter(|(_, _, &l_ii)| l_ii < T::zero())
+            .map(|(i, _, &l_ii)| self.lumped_mass[i] / -l_ii)
+            .reduce(|a, b| a.min(b))
+    }
+
+    /// Computes the low-order solution $\vec u + \Delta t \vec M_L^{-1} \vec L \vec u$ after a
+    /// forward Euler step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `u` does not match the dimensions of the operators.
+    pub fn low_order_step(&self, u: &DVector<T>, dt: T) -> eyre::Result<DVector<T>> {
+        let u_dot = self.low_order_time_derivative(u)?;
+        Ok(u + u_dot * dt)
+    }
+
+    /// Performs a forward Euler step with flux correction.
+    ///
+    /// The antidiffusive fluxes are computed with the time derivative of the low-order solution
+    /// and *prelimited*, i.e. fluxes that flow down the gradient of the low-order solution are
+    /// removed. The remaining fluxes are limited with Zalesak's limiter, such that the solution of
+    /// node $i$ is bounded by the smallest and largest values of $u_j$ and $u^L_j$ over the nodes
+    /// $j$ coupled to $i$, where $u^L$ is the low-order solution.
+    ///
+    /// Provided that the time step does not exceed
+    /// [`max_low_order_time_step`](Self::max_low_order_time_step), the solution therefore satisfies
+    /// a local discrete maximum principle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `u` does not match the dimensions of the operators.
+    pub fn step(&self, u: &DVector<T>, dt: T) -> eyre::Result<DVector<T>> {
+        let u_dot = self.low_order_time_derivative(u)?;
+        let u_low = u + &u_dot * dt;
+
+        // Raw antidiffusive fluxes, stored with the sparsity pattern of the operators
+        let mut fluxes: Vec<T> = Vec::with_capacity(self.consistent_mass.nnz());
+        for (i, (mass_row, diffusion_row)) in self
+            .consistent_mass
+            .row_iter()
+            .zip(self.discrete_diffusion.row_iter())
+            .enumerate()
+        {
+            for (&j, &m_ij, &d_ij) in izip!(mass_row.col_indices(), mass_row.values(), diffusion_row.values()) {
+                let f_ij = if i == j {
+                    T::zero()
+                } else {
+                    m_ij * (u_dot[i] - u_dot[j]) + d_ij * (u[i] - u[j])
+                };
+                // Prelimiting removes fluxes that are diffusive rather than antidiffusive
+                let f_ij = if f_ij * (u_low[j] - u_low[i]) > T::zero() {
+                    T::zero()
+                } else {
+                    f_ij
+                };
+                fluxes.push(f_ij);
+            }
+        }
+
+        // Zalesak's limiter
+        let n = u.len();
+        let mut r_plus = DVector::zeros(n);
+        let mut r_minus = DVector::zeros(n);
+        let pattern = self.consistent_mass.pattern();
+        for i in 0..n {
+            let range = pattern.major_offsets()[i]..pattern.major_offsets()[i + 1];
+            let (mut p_plus, mut p_minus) = (T::zero(), T::zero());
+            let (mut u_max, mut u_min) = (u[i].max(u_low[i]), u[i].min(u_low[i]));
+            for (&j, &f_ij) in pattern.minor_indices()[range.clone()]
+                .iter()
+                .zip(&fluxes[range])
+            {
+                p_plus += f_ij.max(T::zero());
+                p_minus += f_ij.min(T::zero());
+                u_max = u_max.max(u[j]).max(u_low[j]);
+                u_min = u_min.min(u[j]).min(u_low[j]);
+            }
+            let q_plus = self.lumped_mass[i] * (u_max - u_low[i]) / dt;
+            let q_minus = self.lumped_mass[i] * (u_min - u_low[i]) / dt;
+            r_plus[i] = if p_plus > T::zero() {
+                T::one().min(q_plus / p_plus)
+            } else {
+                T::one()
+            };
+            r_minus[i] = if p_minus < T::zero() {
+                T::one().min(q_minus / p_minus)
+            } else {
+                T::one()
+            };
+        }
+
+        let mut u_new = u_low;
+        for i in 0..n {
+            let range = pattern.major_offsets()[i]..pattern.major_offsets()[i + 1];
+            let mut correction = T::zero();
+            for (&j, &f_ij) in pattern.minor_indices()[range.clone()]
+                .iter()
+                .zip(&fluxes[range])
+            {
+                let alpha_ij = if f_ij > T::zero() {
+                    r_plus[i].min(r_minus[j])
+                } else {
+                    r_minus[i].min(r_plus[j])
+                };
+                correction += alpha_ij * f_ij;
+            }
+            u_new[i] += dt * correction / self.lumped_mass[i];
+        }
+        Ok(u_new)
+    }
+
+    fn low_order_time_derivative(&self, u: &DVector<T>) -> eyre::Result<DVector<T>> {
+        if u.len() != self.lumped_mass.len() {
+            return Err(eyre!(
+                "Length of solution vector ({}) does not match number of nodes ({})",
+                u.len(),
+                self.lumped_mass.len()
+            ));
+        }
+        let l_u: DVector<T> = &self.low_order_operator * u;
+        Ok(l_u.component_div(&self.lumped_mass))
+    }
+}
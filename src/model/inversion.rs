@@ -0,0 +1,488 @@
+//! Detection of and recovery from inverted elements in Lagrangian simulations.
+//!
+//! In a Lagrangian simulation of solids, the mesh follows the deformation $x = X + u(X)$ of the
+//! material. Under large deformations, a trial displacement produced by a Newton iteration or a
+//! time step may locally invert the mesh, i.e. the volume ratio $J = \det F$ with the
+//! deformation gradient $F = I + \nabla u$ becomes non-positive. Most hyperelastic materials are
+//! undefined for $J \leq 0$, so a single inverted element usually terminates the simulation.
+//!
+//! [`InversionRecovery`] detects inverted elements by evaluating $J$ at the quadrature points of
+//! every element, and provides two recovery strategies for a displacement update
+//! $u_n \to u_{n + 1}$:
+//!
+//! - *Local untangling*: the displacements of the free nodes of inverted elements are
+//!   repeatedly replaced by the average displacement of their neighboring nodes, which removes
+//!   localized tangles caused e.g. by a single overshooting node.
+//! - *Step rejection*: if untangling fails, the update is rejected, and the largest fraction
+//!   $\alpha \in [0, 1]$ of the update for which $u_n + \alpha (u_{n + 1} - u_n)$ is free of
+//!   inverted elements is reported, so that the time integrator can retry with a step size
+//!   reduced accordingly.
+//!
+//! [`InversionRecovery::recover`] combines both strategies and returns a [`RecoveryOutcome`]
+//! that describes the action that was taken.
+use crate::allocators::BiDimAllocator;
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::QuadratureTable;
+use crate::index::NodeIndex;
+use crate::nalgebra::{
+    DVector, DVectorView, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint,
+};
+use crate::space::VolumetricFiniteElementSpace;
+use crate::Real;
+use eyre::eyre;
+use std::collections::BTreeSet;
+
+/// An element whose volume ratio falls below the threshold at one or more quadrature points.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InvertedElement<T> {
+    pub element_index: usize,
+    /// The smallest volume ratio $J = \det F$ at the quadrature points of the element.
+    pub min_volume_ratio: T,
+}
+
+/// The result of checking a displacement field for inverted elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InversionReport<T> {
+    num_elements: usize,
+    min_volume_ratio: Option<T>,
+    inverted_elements: Vec<InvertedElement<T>>,
+}
+
+impl<T: Real> InversionReport<T> {
+    /// Returns `true` if no element is inverted.
+    pub fn is_valid(&self) -> bool {
+        self.inverted_elements.is_empty()
+    }
+
+    /// The number of elements that were checked.
+    pub fn num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    /// The smallest volume ratio over all quadrature points, or `None` if there are no
+    /// quadrature points.
+    pub fn min_volume_ratio(&self) -> Option<T> {
+        self.min_volume_ratio
+    }
+
+    /// The inverted elements, in increasing order of element index.
+    pub fn inverted_elements(&self) -> &[InvertedElement<T>] {
+        &self.inverted_elements
+    }
+
+    pub fn inverted_element_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inverted_elements
+            .iter()
+            .map(|element| element.element_index)
+    }
+}
+
+/// The action taken by [`InversionRecovery::recover`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryOutcome<T> {
+    /// The updated displacement has no inverted elements and was left unchanged.
+    Valid,
+    /// Inverted elements were removed by local untangling, which modified the updated
+    /// displacement.
+    Untangled {
+        /// The elements that were inverted before untangling.
+        inverted_elements: Vec<InvertedElement<T>>,
+        /// The number of smoothing iterations.
+        num_iterations: usize,
+        /// The number of nodes whose displacement was modified.
+        num_moved_nodes: usize,
+    },
+    /// The update was rejected, and the updated displacement was reset to the previous
+    /// displacement.
+    Rejected {
+        /// The report for the (rejected) updated displacement.
+        report: InversionReport<T>,
+        /// The largest fraction of the update for which no element is inverted, up to the
+        /// accuracy of the bisection.
+        max_step_fraction: T,
+    },
+}
+
+impl<T> RecoveryOutcome<T> {
+    /// Returns `true` if the updated displacement can be accepted, possibly after untangling.
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, RecoveryOutcome::Rejected { .. })
+    }
+}
+
+/// Detects inverted elements and recovers from them during a Lagrangian update.
+///
+/// The displacement $u$ is a vector-valued field on a
+/// [volumetric finite element space](VolumetricFiniteElementSpace) whose nodes coincide with the
+/// geometric nodes, such as a mesh, so that the deformed configuration is obtained by
+/// displacing the nodes of each element. The volume ratio is evaluated at the points of the
+/// given quadrature table, which are the points at which the material model is evaluated during
+/// assembly. An element is considered inverted if its volume ratio falls below the
+/// [threshold](Self::with_min_volume_ratio) at any of these points.
+///
+/// See the [module documentation](self) for an overview of the recovery strategies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InversionRecovery<T> {
+    min_volume_ratio: T,
+    max_untangling_iterations: usize,
+    max_bisection_steps: usize,
+    fixed_nodes: BTreeSet<usize>,
+}
+
+impl<T: Real> Default for InversionRecovery<T> {
+    fn default() -> Self {
+        Self {
+            min_volume_ratio: T::zero(),
+            max_untangling_iterations: 20,
+            max_bisection_steps: 10,
+            fixed_nodes: BTreeSet::new(),
+        }
+    }
+}
+
+impl<T: Real> InversionRecovery<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the threshold below which the volume ratio is considered inverted.
+    ///
+    /// The default is zero. A small positive threshold additionally treats nearly degenerate
+    /// elements as inverted, which keeps the simulation away from the singularity of the
+    /// material model at $J = 0$.
+    pub fn with_min_volume_ratio(self, min_volume_ratio: T) -> Self {
+        Self {
+            min_volume_ratio,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of smoothing iterations for local untangling.
+    ///
+    /// Setting the number of iterations to zero disables untangling.
+    pub fn with_max_untangling_iterations(self, max_untangling_iterations: usize) -> Self {
+        Self {
+            max_untangling_iterations,
+            ..self
+        }
+    }
+
+    /// Sets the number of bisection steps used to determine the largest admissible fraction of
+    /// a rejected update.
+    pub fn with_max_bisection_steps(self, max_bisection_steps: usize) -> Self {
+        Self {
+            max_bisection_steps,
+            ..self
+        }
+    }
+
+    /// Sets the nodes whose displacement must not be modified by untangling, typically nodes with
+    /// prescribed displacements and nodes on the boundary.
+    pub fn with_fixed_nodes(self, fixed_nodes: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            fixed_nodes: fixed_nodes.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Checks the displacement `u` for inverted elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimension of `u` does not match the space, or if an element is
+    /// degenerate or inverted in the reference configuration.
+    pub fn check<'a, Space, QTable>(
+        &self,
+        space: &Space,
+        u: impl Into<DVectorView<'a, T>>,
+        qtable: &QTable,
+    ) -> eyre::Result<InversionReport<T>>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let u = u.into();
+        if u.len() != Space::GeometryDim::dim() * space.num_nodes() {
+            return Err(eyre!(
+                "Dimension of displacement vector does not match space and geometry dimension"
+            ));
+        }
+
+        let mut min_volume_ratio: Option<T> = None;
+        let mut inverted_elements = Vec::new();
+        let mut buffers = ElementBuffers::default();
+        for element_index in 0..space.num_elements() {
+            if let Some(element_min) = buffers.element_min_volume_ratio(space, element_index, u, qtable)? {
+                min_volume_ratio = Some(min_volume_ratio.map_or(element_min, |current| current.min(element_min)));
+                if element_min <= self.min_volume_ratio {
+                    inverted_elements.push(InvertedElement {
+                        element_index,
+                        min_volume_ratio: element_min,
+                    });
+                }
+            }
+        }
+
+        Ok(InversionReport {
+            num_elements: space.num_elements(),
+            min_volume_ratio,
+            inverted_elements,
+        })
+    }
+
+    /// Attempts to remove inverted elements from the displacement `u` by local smoothing.
+    ///
+    /// In each iteration, the displacement of every free node of an inverted element is replaced
+    /// by the average displacement of the nodes that share an element with it. The iterations
+    /// stop as soon as no element is inverted, or after the
+    /// [maximum number of iterations](Self::with_max_untangling_iterations). Returns the number
+    /// of iterations, the number of modified nodes and the report for the final displacement,
+    /// which may still contain inverted elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`check`](Self::check).
+    pub fn untangle<Space, QTable>(
+        &self,
+        space: &Space,
+        u: &mut DVector<T>,
+        qtable: &QTable,
+    ) -> eyre::Result<(usize, usize, InversionReport<T>)>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let d = Space::GeometryDim::dim();
+        let mut report = self.check(space, &*u, qtable)?;
+        let mut num_iterations = 0;
+        let mut moved_nodes = BTreeSet::new();
+        let mut node_neighbors: Option<Vec<BTreeSet<usize>>> = None;
+        let mut nodes = Vec::new();
+        while !report.is_valid() && num_iterations < self.max_untangling_iterations {
+            let neighbors = node_neighbors.get_or_insert_with(|| compute_node_neighbors(space));
+
+            let mut patch = BTreeSet::new();
+            for element_index in report.inverted_element_indices() {
+                nodes.resize(space.element_node_count(element_index.into()), 0);
+                space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+                patch.extend(
+                    nodes
+                        .iter()
+                        .copied()
+                        .filter(|node| !self.fixed_nodes.contains(node)),
+                );
+            }
+            if patch.is_empty() {
+                // All nodes of the inverted elements are fixed
+                break;
+            }
+
+            // Jacobi update, so that the result does not depend on the order of the nodes
+            let updates: Vec<_> = patch
+                .iter()
+                .filter(|&&node| !neighbors[node].is_empty())
+                .map(|&node| {
+                    let mut average = DVector::zeros(d);
+                    for &neighbor in &neighbors[node] {
+                        average += u.rows(d * neighbor, d);
+                    }
+                    average /= T::from_usize(neighbors[node].len()).unwrap();
+                    (node, average)
+                })
+                .collect();
+            for (node, average) in updates {
+                u.rows_mut(d * node, d).copy_from(&average);
+                moved_nodes.insert(node);
+            }
+
+            num_iterations += 1;
+            report = self.check(space, &*u, qtable)?;
+        }
+        Ok((num_iterations, moved_nodes.len(), report))
+    }
+
+    /// Computes the largest fraction $\alpha \in [0, 1]$ such that
+    /// $u_n + \alpha (u_{n + 1} - u_n)$ has no inverted elements.
+    ///
+    /// The fraction is determined by bisection, assuming that the elements only invert once
+    /// along the update, and is accurate up to $2^{-k}$ for $k$
+    /// [bisection steps](Self::with_max_bisection_steps).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the previous displacement $u_n$ has inverted elements, or under the
+    /// same conditions as [`check`](Self::check).
+    pub fn max_step_fraction<'a, 'b, Space, QTable>(
+        &self,
+        space: &Space,
+        u_previous: impl Into<DVectorView<'a, T>>,
+        u_new: impl Into<DVectorView<'b, T>>,
+        qtable: &QTable,
+    ) -> eyre::Result<T>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let u_previous = u_previous.into();
+        let u_new = u_new.into();
+        if u_previous.len() != u_new.len() {
+            return Err(eyre!("Previous and new displacement vectors must have the same length"));
+        }
+        if !self.check(space, u_previous, qtable)?.is_valid() {
+            return Err(eyre!("Previous displacement has inverted elements"));
+        }
+        if self.check(space, u_new, qtable)?.is_valid() {
+            return Ok(T::one());
+        }
+
+        let increment = u_new - u_previous;
+        let half = T::from_f64(0.5).unwrap();
+        let (mut lower, mut upper) = (T::zero(), T::one());
+        let mut u = DVector::zeros(u_previous.len());
+        for _ in 0..self.max_bisection_steps {
+            let alpha = (lower + upper) * half;
+            u.copy_from(&u_previous);
+            u.axpy(alpha, &increment, T::one());
+            if self.check(space, &u, qtable)?.is_valid() {
+                lower = alpha;
+            } else {
+                upper = alpha;
+            }
+        }
+        Ok(lower)
+    }
+
+    /// Checks the update from `u_previous` to `u_new` and recovers from inverted elements.
+    ///
+    /// If `u_new` has inverted elements, local [untangling](Self::untangle) is attempted first.
+    /// If untangling fails, the update is rejected: `u_new` is reset to `u_previous`, and the
+    /// returned outcome contains the [largest admissible fraction](Self::max_step_fraction) of
+    /// the update, which can be used to reduce the step size before retrying the step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`max_step_fraction`](Self::max_step_fraction).
+    pub fn recover<'a, Space, QTable>(
+        &self,
+        space: &Space,
+        u_previous: impl Into<DVectorView<'a, T>>,
+        u_new: &mut DVector<T>,
+        qtable: &QTable,
+    ) -> eyre::Result<RecoveryOutcome<T>>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let u_previous = u_previous.into();
+        let report = self.check(space, &*u_new, qtable)?;
+        if report.is_valid() {
+            return Ok(RecoveryOutcome::Valid);
+        }
+
+        let mut u_untangled = u_new.clone();
+        let (num_iterations, num_moved_nodes, untangled_report) = self.untangle(space, &mut u_untangled, qtable)?;
+        if untangled_report.is_valid() {
+            *u_new = u_untangled;
+            return Ok(RecoveryOutcome::Untangled {
+                inverted_elements: report.inverted_elements,
+                num_iterations,
+                num_moved_nodes,
+            });
+        }
+
+        let max_step_fraction = self.max_step_fraction(space, u_previous, &*u_new, qtable)?;
+        u_new.copy_from(&u_previous);
+        Ok(RecoveryOutcome::Rejected {
+            report,
+            max_step_fraction,
+        })
+    }
+}
+
+/// The nodes that share an element with each node, excluding the node itself.
+fn compute_node_neighbors<T, Space>(space: &Space) -> Vec<BTreeSet<usize>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let mut neighbors = vec![BTreeSet::new(); space.num_nodes()];
+    let mut nodes = Vec::new();
+    for element_index in 0..space.num_elements() {
+        nodes.resize(space.element_node_count(element_index.into()), 0);
+        space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+        for &node in &nodes {
+            neighbors[node].extend(nodes.iter().copied().filter(|&other| other != node));
+        }
+    }
+    neighbors
+}
+
+struct ElementBuffers<T> {
+    nodes: Vec<usize>,
+    u_element: DVector<T>,
+    weights: Vec<T>,
+}
+
+impl<T: Real> Default for ElementBuffers<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            u_element: DVector::zeros(0),
+            weights: Vec::new(),
+        }
+    }
+}
+
+impl<T: Real> ElementBuffers<T> {
+    /// The smallest volume ratio at the quadrature points of the element, or `None` if the
+    /// element has no quadrature points.
+    fn element_min_volume_ratio<Space, QTable>(
+        &mut self,
+        space: &Space,
+        element_index: usize,
+        u: DVectorView<T>,
+        qtable: &QTable,
+    ) -> eyre::Result<Option<T>>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let d = Space::GeometryDim::dim();
+        let n = space.element_node_count(element_index.into());
+        self.nodes.resize(n, 0);
+        space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut self.nodes), element_index.into());
+        self.u_element.resize_vertically_mut(d * n, T::zero());
+        gather_global_to_local(u, &mut self.u_element, &self.nodes, d);
+        let u_element = MatrixView::from_slice_generic(self.u_element.as_slice(), Space::GeometryDim::name(), Dyn(n));
+
+        let num_points = qtable.element_quadrature_size(element_index);
+        let mut points = vec![OPoint::<T, Space::ReferenceDim>::origin(); num_points];
+        self.weights.resize(num_points, T::zero());
+        qtable.populate_element_quadrature(element_index, &mut points, &mut self.weights);
+
+        let mut gradients = OMatrix::<T, Space::ReferenceDim, Dyn>::zeros(n);
+        let mut min_volume_ratio: Option<T> = None;
+        for xi in &points {
+            let j_ref = space.element_reference_jacobian(element_index, xi);
+            let j_ref_det = j_ref.determinant();
+            if j_ref_det <= T::zero() {
+                return Err(eyre!(
+                    "Element {} is degenerate or inverted in the reference configuration",
+                    element_index
+                ));
+            }
+            space.populate_element_gradients(element_index, MatrixViewMut::from(&mut gradients), xi);
+            // dx/dξ = dX/dξ + Σ_I u_I (∇_ξ N_I)^T
+            let j_deformed = j_ref + u_element * gradients.transpose();
+            let volume_ratio = j_deformed.determinant() / j_ref_det;
+            min_volume_ratio = Some(min_volume_ratio.map_or(volume_ratio, |current| current.min(volume_ratio)));
+        }
+        Ok(min_volume_ratio)
+    }
+}
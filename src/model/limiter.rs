@@ -0,0 +1,500 @@
+//! Limiters for enforcing physical bounds on solutions of transport problems.
+//!
+//! High-order discretizations of advection-dominated problems produce spurious oscillations near
+//! steep fronts, which may violate physical bounds such as non-negativity of a concentration.
+//! Limiters are applied as post-processing steps after each time step (or stage) of a transport
+//! solver and modify the solution so that it satisfies local or global bounds while preserving
+//! the total mass.
+//!
+//! - [`BarthJespersenLimiter`] limits the gradients of elementwise linear fields, such as
+//!   DG1 solutions or reconstructed finite volume solutions, so that the field does not exceed
+//!   the range of the cell means of neighboring cells.
+//! - [`clip_and_scale`] enforces bounds on nodal values with associated (lumped) masses by
+//!   clipping the values and redistributing the clipped mass among the nodes.
+//! - [`BoundConstrainedProjection`] enforces bounds on the weights of a finite element field by
+//!   projecting the field onto the bounds in the norm induced by the (consistent) mass matrix.
+use crate::allocators::DimAllocator;
+use crate::connectivity::Connectivity;
+use crate::mesh::split::find_interior_faces;
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVector, DVectorViewMut, DefaultAllocator, DimName, OPoint, OVector};
+use crate::nalgebra_sparse::CsrMatrix;
+use crate::util::NestedVec;
+use crate::Real;
+use eyre::eyre;
+use itertools::izip;
+
+/// The Barth-Jespersen limiter for elementwise linear fields.
+///
+/// An elementwise linear field is represented in each cell $K$ by its mean value $\bar u_K$ and
+/// its gradient $\nabla u_K$, i.e.
+/// <div>$$
+/// u_K(\vec x) = \bar u_K + \nabla u_K \cdot (\vec x - \vec x_K),
+/// $$</div>
+/// where $\vec x_K$ is the vertex centroid of the cell. For simplices and parallelograms, the
+/// vertex centroid coincides with the centroid, so that $\bar u_K$ is indeed the mean value of
+/// $u_K$.
+///
+/// The limiter computes a coefficient $\alpha_K \in [0, 1]$ for each cell as the largest value
+/// such that the limited field $\bar u_K + \alpha_K \nabla u_K \cdot (\vec x - \vec x_K)$ lies
+/// within the bounds $[u_K^{\min}, u_K^{\max}]$ at the vertices of the cell, where the bounds are
+/// the smallest and largest mean values of the cell and its face neighbors. Since the limited
+/// field is linear, it then satisfies the bounds everywhere in the cell. Limiting does not change
+/// the cell means and is therefore conservative.
+#[derive(Debug, Clone)]
+pub struct BarthJespersenLimiter<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    centroids: Vec<OPoint<T, D>>,
+    // The vertex offsets x - x_K of each cell
+    vertex_offsets: NestedVec<OVector<T, D>>,
+    neighbors: NestedVec<usize>,
+}
+
+impl<T, D> BarthJespersenLimiter<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Creates a limiter for the cells of the given mesh, whose neighbors are the cells sharing
+    /// a face.
+    pub fn from_mesh<C: Connectivity>(mesh: &Mesh<T, D, C>) -> Self {
+        let num_cells = mesh.connectivity().len();
+        let mut centroids = Vec::with_capacity(num_cells);
+        let mut vertex_offsets = NestedVec::new();
+        for cell in mesh.connectivity() {
+            let cell_vertices: Vec<_> = cell
+                .vertex_indices()
+                .iter()
+                .map(|&i| &mesh.vertices()[i])
+                .collect();
+            let sum = cell_vertices
+                .iter()
+                .fold(OVector::<T, D>::zeros(), |sum, x| sum + &x.coords);
+            let centroid = OPoint::from(sum / T::from_usize(cell_vertices.len()).unwrap());
+            let offsets: Vec<_> = cell_vertices.iter().map(|&x| x - &centroid).collect();
+            vertex_offsets.push(&offsets);
+            centroids.push(centroid);
+        }
+
+        let mut cell_neighbors = vec![Vec::new(); num_cells];
+        for face in find_interior_faces(mesh) {
+            let [cell0, cell1] = face.cells;
+            cell_neighbors[cell0].push(cell1);
+            cell_neighbors[cell1].push(cell0);
+        }
+        let mut neighbors = NestedVec::new();
+        for mut cell_neighbors in cell_neighbors {
+            cell_neighbors.sort_unstable();
+            neighbors.push(&cell_neighbors);
+        }
+
+        Self {
+            centroids,
+            vertex_offsets,
+            neighbors,
+        }
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.centroids.len()
+    }
+
+    /// The vertex centroids $\vec x_K$ around which the linear fields are expanded.
+    pub fn centroids(&self) -> &[OPoint<T, D>] {
+        &self.centroids
+    }
+
+    /// The face neighbors of the given cell, in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell index is out of bounds.
+    pub fn cell_neighbors(&self, cell_index: usize) -> &[usize] {
+        self.neighbors
+            .get(cell_index)
+            .expect("Cell index out of bounds")
+    }
+
+    /// Computes the bounds $[u_K^{\min}, u_K^{\max}]$ given by the smallest and largest mean
+    /// value of each cell and its face neighbors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of mean values does not match the number of cells.
+    pub fn compute_local_bounds(&self, means: &[T]) -> eyre::Result<(Vec<T>, Vec<T>)> {
+        self.check_len(means.len(), "mean values")?;
+        Ok((0..self.num_cells())
+            .map(|cell_index| {
+                self.cell_neighbors(cell_index)
+                    .iter()
+                    .map(|&neighbor| means[neighbor])
+                    .fold((means[cell_index], means[cell_index]), |(min, max), u| {
+                        (min.min(u), max.max(u))
+                    })
+            })
+            .unzip())
+    }
+
+    /// Computes the limiter coefficient $\alpha_K \in [0, 1]$ of each cell.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of mean values or gradients does not match the number of
+    /// cells.
+    pub fn compute_coefficients(&self, means: &[T], gradients: &[OVector<T, D>]) -> eyre::Result<Vec<T>> {
+        self.check_len(gradients.len(), "gradients")?;
+        let (lower, upper) = self.compute_local_bounds(means)?;
+        Ok(izip!(self.vertex_offsets.iter(), means, gradients, lower, upper)
+            .map(|(offsets, &mean, gradient, lower, upper)| {
+                offsets
+                    .iter()
+                    .map(|offset| {
+                        let increment = gradient.dot(offset);
+                        if increment > T::zero() {
+                            ((upper - mean) / increment).min(T::one())
+                        } else if increment < T::zero() {
+                            ((lower - mean) / increment).min(T::one())
+                        } else {
+                            T::one()
+                        }
+                    })
+                    .fold(T::one(), |alpha, alpha_vertex| alpha.min(alpha_vertex))
+                    .max(T::zero())
+            })
+            .collect())
+    }
+
+    /// Limits the gradients of the given elementwise linear field in place.
+    ///
+    /// Returns the limiter coefficients that the gradients were scaled with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of mean values or gradients does not match the number of
+    /// cells.
+    pub fn limit_gradients(&self, means: &[T], gradients: &mut [OVector<T, D>]) -> eyre::Result<Vec<T>> {
+        let coefficients = self.compute_coefficients(means, gradients)?;
+        for (gradient, &alpha) in gradients.iter_mut().zip(&coefficients) {
+            *gradient *= alpha;
+        }
+        Ok(coefficients)
+    }
+
+    fn check_len(&self, len: usize, name: &str) -> eyre::Result<()> {
+        if len != self.num_cells() {
+            return Err(eyre!(
+                "Number of {} ({}) does not match number of cells ({})",
+                name,
+                len,
+                self.num_cells()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Enforces bounds on values with associated masses while conserving the total mass.
+///
+/// Given values $u_i$ with (positive) masses $m_i$, such as nodal values with the entries of a
+/// lumped mass matrix, the values are first clipped to the bounds $[u_i^{\min}, u_i^{\max}]$.
+/// The mass $\Delta m = \sum_i m_i (u_i - \tilde u_i)$ removed by clipping is then redistributed
+/// among the clipped values $\tilde u_i$ in proportion to the available capacity. For example, if
+/// $\Delta m > 0$, then
+/// <div>$$
+/// u_i \leftarrow \tilde u_i
+///     + \frac{\Delta m}{\sum_j m_j (u_j^{\max} - \tilde u_j)} (u_i^{\max} - \tilde u_i),
+/// $$</div>
+/// and analogously with the lower bounds if $\Delta m < 0$. The resulting values satisfy the
+/// bounds and have the same total mass $\sum_i m_i u_i$ as the input values. Values that satisfy
+/// the bounds are not modified, unless mass needs to be redistributed to them.
+///
+/// The limiter can be applied globally or separately to the nodes of each element, in which case
+/// the mass of each element is conserved.
+///
+/// # Errors
+///
+/// Returns an error if the lengths of the slices differ, if a lower bound exceeds the
+/// corresponding upper bound, or if the total mass cannot be attained within the bounds. In
+/// the case of an error, the values are not modified.
+pub fn clip_and_scale<T: Real>(
+    values: &mut [T],
+    masses: &[T],
+    lower_bounds: &[T],
+    upper_bounds: &[T],
+) -> eyre::Result<()> {
+    let n = values.len();
+    if masses.len() != n || lower_bounds.len() != n || upper_bounds.len() != n {
+        return Err(eyre!("Values, masses and bounds must have the same length"));
+    }
+    if let Some(i) = (0..n).find(|&i| lower_bounds[i] > upper_bounds[i]) {
+        return Err(eyre!("Lower bound exceeds upper bound for value {}", i));
+    }
+
+    let total_mass = |u: &dyn Fn(usize) -> T| (0..n).fold(T::zero(), |sum, i| sum + masses[i] * u(i));
+    let mass = total_mass(&|i| values[i]);
+    let min_mass = total_mass(&|i| lower_bounds[i]);
+    let max_mass = total_mass(&|i| upper_bounds[i]);
+    if mass < min_mass || mass > max_mass {
+        return Err(eyre!(
+            "Total mass {} cannot be attained within bounds (admissible range [{}, {}])",
+            mass,
+            min_mass,
+            max_mass
+        ));
+    }
+
+    let clipped: Vec<T> = izip!(values.iter(), lower_bounds, upper_bounds)
+        .map(|(&u, &lower, &upper)| u.max(lower).min(upper))
+        .collect();
+    let excess = mass - total_mass(&|i| clipped[i]);
+    let capacity = if excess > T::zero() {
+        total_mass(&|i| upper_bounds[i] - clipped[i])
+    } else {
+        total_mass(&|i| lower_bounds[i] - clipped[i])
+    };
+    let ratio = if capacity != T::zero() {
+        excess / capacity
+    } else {
+        T::zero()
+    };
+    for (u, &u_clipped, &lower, &upper) in izip!(values.iter_mut(), &clipped, lower_bounds, upper_bounds) {
+        let bound = if excess > T::zero() { upper } else { lower };
+        // The ratio is in [0, 1] up to round-off errors, so clamp to the bounds to be safe
+        *u = (u_clipped + ratio * (bound - u_clipped))
+            .max(lower)
+            .min(upper);
+    }
+    Ok(())
+}
+
+/// Projection of finite element fields onto bound constraints in the mass-weighted norm.
+///
+/// Given the weights $\vec u_0$ of a finite element field and the mass matrix $\vec M$, the
+/// projection computes the weights $\vec u$ of the closest field that satisfies the bounds
+/// $u_i^{\min} \leq u_i \leq u_i^{\max}$, i.e. it solves the quadratic program
+/// <div>$$
+/// \min_{\vec u} \frac{1}{2} (\vec u - \vec u_0)^T \vec M (\vec u - \vec u_0)
+///     \quad \text{subject to} \quad \vec u^{\min} \leq \vec u \leq \vec u^{\max}.
+/// $$</div>
+/// For the consistent mass matrix, this is the $L^2$ projection of the field onto the set of
+/// fields with bounded weights, which for Lagrange elements of degree one includes fields that
+/// are non-negative everywhere. Unlike [`clip_and_scale`], which only uses the (lumped) masses,
+/// the projection changes the field as little as possible in the $L^2$ norm, and is therefore
+/// suitable as a post-processing step of transport solvers with consistent mass matrices.
+///
+/// If [mass conservation](Self::with_mass_conservation) is enabled, the projection is additionally
+/// constrained to preserve the total mass $\vec 1^T \vec M \vec u = \vec 1^T \vec M \vec u_0$.
+///
+/// The quadratic program is solved with the projected gradient method with step size
+/// $1 / L$, where $L$ is the largest absolute row sum of $\vec M$, which bounds the largest
+/// eigenvalue. The Euclidean projection onto the bounds, which in the mass conserving case are
+/// intersected with the hyperplane of constant mass, is computed exactly in each iteration.
+/// Since mass matrices are well conditioned, the method converges in a moderate number of
+/// iterations that is independent of the mesh resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundConstrainedProjection<T> {
+    max_iterations: usize,
+    tolerance: T,
+    conserve_mass: bool,
+}
+
+impl<T: Real> Default for BoundConstrainedProjection<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Real> BoundConstrainedProjection<T> {
+    /// Creates a projection without mass conservation, with a tolerance of `1e-12` and at most
+    /// 1000 iterations.
+    pub fn new() -> Self {
+        Self {
+            max_iterations: 1000,
+            tolerance: T::from_f64(1e-12).unwrap(),
+            conserve_mass: false,
+        }
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        Self { max_iterations, ..self }
+    }
+
+    /// Sets the tolerance for the change of the weights in a single iteration, relative to the
+    /// magnitude of the weights.
+    pub fn with_tolerance(self, tolerance: T) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    pub fn with_mass_conservation(self, conserve_mass: bool) -> Self {
+        Self { conserve_mass, ..self }
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    pub fn tolerance(&self) -> T {
+        self.tolerance
+    }
+
+    pub fn conserves_mass(&self) -> bool {
+        self.conserve_mass
+    }
+
+    /// Projects the given weights onto the bounds in place.
+    ///
+    /// Returns the number of iterations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the mass matrix, weights and bounds are
+    /// inconsistent, if a lower bound exceeds the corresponding upper bound, if the total mass
+    /// cannot be attained within the bounds when mass conservation is enabled, or if the method
+    /// does not converge within the maximum number of iterations. In the case of an error, the
+    /// weights are not modified.
+    pub fn project(
+        &self,
+        mass_matrix: &CsrMatrix<T>,
+        mut weights: DVectorViewMut<T>,
+        lower_bounds: &[T],
+        upper_bounds: &[T],
+    ) -> eyre::Result<usize> {
+        let n = weights.len();
+        if mass_matrix.nrows() != n || mass_matrix.ncols() != n {
+            return Err(eyre!(
+                "Mass matrix dimensions {}x{} are inconsistent with number of weights ({})",
+                mass_matrix.nrows(),
+                mass_matrix.ncols(),
+                n
+            ));
+        }
+        if lower_bounds.len() != n || upper_bounds.len() != n {
+            return Err(eyre!("Weights and bounds must have the same length"));
+        }
+        if let Some(i) = (0..n).find(|&i| lower_bounds[i] > upper_bounds[i]) {
+            return Err(eyre!("Lower bound exceeds upper bound for weight {}", i));
+        }
+
+        let u0 = weights.clone_owned();
+        // The row sums m = M 1 define the total mass m^T u of the weights u
+        let row_sums = mass_matrix * DVector::repeat(n, T::one());
+        let constraint = if self.conserve_mass {
+            let mass = row_sums.dot(&u0);
+            let min_mass = bounded_mass(&row_sums, lower_bounds, upper_bounds, true);
+            let max_mass = bounded_mass(&row_sums, lower_bounds, upper_bounds, false);
+            if mass < min_mass || mass > max_mass {
+                return Err(eyre!(
+                    "Total mass {} cannot be attained within bounds (admissible range [{}, {}])",
+                    mass,
+                    min_mass,
+                    max_mass
+                ));
+            }
+            Some((&row_sums, mass))
+        } else {
+            None
+        };
+
+        let lipschitz = mass_matrix
+            .row_iter()
+            .map(|row| {
+                row.values()
+                    .iter()
+                    .fold(T::zero(), |sum, m_ij| sum + m_ij.abs())
+            })
+            .fold(T::zero(), |max, sum| max.max(sum));
+        let step = if lipschitz > T::zero() {
+            T::one() / lipschitz
+        } else {
+            T::zero()
+        };
+
+        let mut u = project_onto_bounds(&u0, lower_bounds, upper_bounds, constraint);
+        for iteration in 1..=self.max_iterations {
+            let gradient = mass_matrix * (&u - &u0);
+            let u_next = project_onto_bounds(&(&u - gradient * step), lower_bounds, upper_bounds, constraint);
+            let change = (&u_next - &u).amax();
+            let scale = T::one() + u_next.amax();
+            u = u_next;
+            if change <= self.tolerance * scale {
+                weights.copy_from(&u);
+                return Ok(iteration);
+            }
+        }
+        Err(eyre!(
+            "Bound-constrained projection did not converge within {} iterations",
+            self.max_iterations
+        ))
+    }
+}
+
+/// The smallest (or largest) mass $m^T u$ of weights within the bounds.
+fn bounded_mass<T: Real>(row_sums: &DVector<T>, lower_bounds: &[T], upper_bounds: &[T], min: bool) -> T {
+    izip!(row_sums.iter(), lower_bounds, upper_bounds).fold(T::zero(), |sum, (&m, &lower, &upper)| {
+        // Row sums may be negative for some higher-order elements
+        let bound = if (m >= T::zero()) == min { lower } else { upper };
+        sum + m * bound
+    })
+}
+
+/// Computes the Euclidean projection of `v` onto the bounds, optionally intersected with the
+/// hyperplane $m^T u = c$.
+///
+/// The projection onto the intersection is $u(\tau) = \mathrm{clamp}(v - \tau m)$, where the
+/// multiplier $\tau$ is determined by bisection, since $m^T u(\tau)$ is non-increasing in $\tau$.
+fn project_onto_bounds<T: Real>(
+    v: &DVector<T>,
+    lower_bounds: &[T],
+    upper_bounds: &[T],
+    constraint: Option<(&DVector<T>, T)>,
+) -> DVector<T> {
+    let clamp = |tau: T, m: Option<&DVector<T>>| {
+        DVector::from_iterator(
+            v.len(),
+            izip!(v.iter(), lower_bounds, upper_bounds)
+                .enumerate()
+                .map(|(i, (&v_i, &lower, &upper))| {
+                    let shift = m.map(|m| tau * m[i]).unwrap_or(T::zero());
+                    (v_i - shift).max(lower).min(upper)
+                }),
+        )
+    };
+    let Some((m, mass)) = constraint else {
+        return clamp(T::zero(), None);
+    };
+
+    let mass_at = |tau: T| m.dot(&clamp(tau, Some(m)));
+    let two = T::from_f64(2.0).unwrap();
+    let (mut tau_low, mut tau_high) = (-T::one(), T::one());
+    // Expand the bracket until it contains the multiplier. Since the mass is attainable, this
+    // terminates once all weights are clamped to the bounds.
+    for _ in 0..200 {
+        if mass_at(tau_low) >= mass {
+            break;
+        }
+        tau_low *= two;
+    }
+    for _ in 0..200 {
+        if mass_at(tau_high) <= mass {
+            break;
+        }
+        tau_high *= two;
+    }
+    for _ in 0..200 {
+        let tau = (tau_low + tau_high) / two;
+        if tau <= tau_low || tau >= tau_high {
+            break;
+        }
+        if mass_at(tau) > mass {
+            tau_low = tau;
+        } else {
+            tau_high = tau;
+        }
+    }
+    clamp((tau_low + tau_high) / two, Some(m))
+}
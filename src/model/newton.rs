@@ -0,0 +1,144 @@
+//! Newton-Raphson driver for nonlinear systems of equations.
+//!
+//! The discretization of a nonlinear problem, for example with an
+//! [`EllipticOperator`](crate::assembly::operators::EllipticOperator), leads to a nonlinear
+//! system of equations
+//! <div>$$
+//! \vec r(\vec u) = \vec 0.
+//! $$</div>
+//! [`NewtonSolver`] solves such systems with Newton's method, optionally globalized with a
+//! [`BacktrackingLineSearch`]. Systems are described by the
+//! [`NonlinearSystem`](crate::nonlinear::NonlinearSystem) trait. The iterations are carried out by
+//! [`fenris_optimize::newton`], whose line search and diagnostics types are re-exported here.
+use crate::linear_solver::LinearSolver;
+use crate::nonlinear::{solve_with_newton, NonlinearSystem};
+use crate::Real;
+use fenris_optimize::newton::NewtonSettings;
+use log::debug;
+use nalgebra::DVectorViewMut;
+
+pub use fenris_optimize::newton::{BacktrackingLineSearch, ConvergenceReason, NewtonIteration};
+
+/// Diagnostics for a converged Newton solve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewtonReport<T> {
+    pub initial_residual_norm: T,
+    pub iterations: Vec<NewtonIteration<T>>,
+    pub converged_by: ConvergenceReason,
+}
+
+impl<T: Real> NewtonReport<T> {
+    /// The number of iterations, i.e. the number of linear systems solved.
+    pub fn num_iterations(&self) -> usize {
+        self.iterations.len()
+    }
+
+    pub fn final_residual_norm(&self) -> T {
+        self.iterations
+            .last()
+            .map(|iteration| iteration.residual_norm)
+            .unwrap_or(self.initial_residual_norm)
+    }
+}
+
+/// Solves nonlinear systems $\vec r(\vec u) = \vec 0$ with Newton's method.
+///
+/// Each iteration solves the linear system $\vec J \Delta \vec u = - \vec r$ and updates
+/// $\vec u \leftarrow \vec u + \alpha \Delta \vec u$, where the step length $\alpha$ is $1$ unless
+/// a line search is used. The iterations have converged when either
+/// $\\| \vec r \\| \leq \epsilon_{\text{abs}} + \epsilon_{\text{rel}} \\| \vec r_0 \\|$,
+/// where $\vec r_0$ is the residual for the initial guess, or, if an increment tolerance is given,
+/// $\\| \alpha \Delta \vec u \\| \leq \epsilon_{\text{inc}}$.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NewtonSolver<T> {
+    absolute_tolerance: T,
+    relative_tolerance: T,
+    increment_tolerance: Option<T>,
+    max_iterations: usize,
+    line_search: Option<BacktrackingLineSearch<T>>,
+}
+
+impl<T: Real> Default for NewtonSolver<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Real> NewtonSolver<T> {
+    /// Creates a solver with absolute and relative residual tolerances $10^{-12}$ and $10^{-10}$,
+    /// no increment criterion, at most 20 iterations and no line search.
+    pub fn new() -> Self {
+        Self {
+            absolute_tolerance: T::from_f64(1e-12).unwrap(),
+            relative_tolerance: T::from_f64(1e-10).unwrap(),
+            increment_tolerance: None,
+            max_iterations: 20,
+            line_search: None,
+        }
+    }
+
+    pub fn with_residual_tolerance(self, absolute_tolerance: T, relative_tolerance: T) -> Self {
+        Self {
+            absolute_tolerance,
+            relative_tolerance,
+            ..self
+        }
+    }
+
+    pub fn with_increment_tolerance(self, increment_tolerance: T) -> Self {
+        Self {
+            increment_tolerance: Some(increment_tolerance),
+            ..self
+        }
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        Self { max_iterations, ..self }
+    }
+
+    pub fn with_line_search(self, line_search: BacktrackingLineSearch<T>) -> Self {
+        Self {
+            line_search: Some(line_search),
+            ..self
+        }
+    }
+
+    /// Solves the system, using the provided `u` as the initial guess.
+    ///
+    /// On success, `u` holds the solution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the evaluation of the system, the linear solver or the line search
+    /// fails, or if the iterations do not converge within the maximum number of iterations.
+    /// In case of errors, `u` holds the last iterate.
+    pub fn solve<'a>(
+        &self,
+        system: &mut impl NonlinearSystem<T>,
+        u: impl Into<DVectorViewMut<'a, T>>,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<NewtonReport<T>> {
+        let settings = NewtonSettings {
+            max_iterations: Some(self.max_iterations),
+            tolerance: self.absolute_tolerance,
+            relative_tolerance: self.relative_tolerance,
+            increment_tolerance: self.increment_tolerance,
+        };
+        let mut iterations = Vec::new();
+        let convergence = solve_with_newton(system, u, linear_solver, settings, self.line_search, |iteration| {
+            debug!(
+                "Newton iteration {}: residual norm {}, increment norm {}, step length {}",
+                iterations.len() + 1,
+                iteration.residual_norm,
+                iteration.increment_norm,
+                iteration.step_length
+            );
+            iterations.push(*iteration);
+        })?;
+        Ok(NewtonReport {
+            initial_residual_norm: convergence.initial_residual_norm,
+            iterations,
+            converged_by: convergence.converged_by,
+        })
+    }
+}
@@ -0,0 +1,425 @@
+//! Staggered solution of phase-field fracture problems.
+//!
+//! In the phase-field approach to brittle fracture, cracks are represented by a smooth
+//! phase-field $d \in [0, 1]$, where $d = 0$ denotes intact and $d = 1$ fully broken material.
+//! The displacement $\vec u$ minimizes the elastic energy degraded by the phase-field, see
+//! [`DegradedElasticityOperator`], while the phase-field is governed by the evolution equation
+//! of the [`PhaseFieldOperator`], driven by the history field $\mathcal{H}$ of maximum tensile
+//! energy density.
+//!
+//! [`PhaseFieldFractureSolver`] solves the coupled problem for each load step with the
+//! alternate minimization (*staggered*) scheme of Miehe et al. (2010): the displacement and
+//! the phase-field are solved for alternately, with the other field held fixed, until the
+//! phase-field no longer changes. The two fields are discretized on the same finite element
+//! space but stored and assembled separately.
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
+use crate::assembly::bc::DirichletConditions;
+use crate::assembly::buffers::{BufferUpdate, InterpolationBuffer};
+use crate::assembly::global::{CsrAssembler, VectorAssembler};
+use crate::assembly::local::{
+    ElementAdvectionDiffusionReactionAssembler, ElementEllipticAssemblerBuilder, QuadratureTable,
+};
+use crate::assembly::operators::solid::LameParameters;
+use crate::assembly::operators::{
+    degradation_function, DegradedElasticityOperator, DegradedElasticityParameters, EnergySplit, PhaseFieldModel,
+    PhaseFieldOperator, PhaseFieldParameters,
+};
+use crate::linear_solver::LinearSolver;
+use crate::model::newton::NewtonSolver;
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, OMatrix, OPoint, U1};
+use crate::nonlinear::NonlinearSystemFn;
+use crate::quadrature::QuadraturePair;
+use crate::space::VolumetricFiniteElementSpace;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use log::debug;
+
+/// Material parameters of a phase-field fracture model.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PhaseFieldMaterial<T> {
+    pub lame: LameParameters<T>,
+    /// The critical energy release rate $G_c$.
+    pub fracture_toughness: T,
+    /// The regularization length $l$.
+    pub length_scale: T,
+    /// The residual stiffness $k$ of the [degradation function](degradation_function).
+    pub residual_stiffness: T,
+}
+
+/// Settings for the staggered iterations of [`PhaseFieldFractureSolver`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StaggeredSettings<T> {
+    /// The iterations have converged once the maximum change of the nodal phase-field values
+    /// in an iteration is below this tolerance.
+    pub tolerance: T,
+    pub max_iterations: usize,
+}
+
+impl<T: Real> Default for StaggeredSettings<T> {
+    fn default() -> Self {
+        Self {
+            tolerance: T::from_f64(1e-6).unwrap(),
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Diagnostics for a single step of [`PhaseFieldFractureSolver`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StaggeredStepInfo {
+    pub staggered_iterations: usize,
+    /// The total number of Newton iterations of the displacement solves.
+    pub newton_iterations: usize,
+}
+
+/// A quadrature table with the same rule for all elements, but with data varying per quadrature
+/// point.
+#[derive(Debug, Clone)]
+struct ElementwiseQuadratureTable<T, D, Data>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    weights: Vec<T>,
+    points: Vec<OPoint<T, D>>,
+    data: Vec<Data>,
+}
+
+impl<T, D, Data> ElementwiseQuadratureTable<T, D, Data>
+where
+    T: Real,
+    D: DimName,
+    Data: Clone,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn new(quadrature: QuadraturePair<T, D>, num_elements: usize, data: Data) -> Self {
+        let (weights, points) = quadrature;
+        let data = vec![data; num_elements * weights.len()];
+        Self { weights, points, data }
+    }
+
+    fn num_points_per_element(&self) -> usize {
+        self.weights.len()
+    }
+}
+
+impl<T, D, Data> QuadratureTable<T, D> for ElementwiseQuadratureTable<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    Data: Default + Clone,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    type Data = Data;
+
+    fn element_quadrature_size(&self, _element_index: usize) -> usize {
+        self.num_points_per_element()
+    }
+
+    fn populate_element_data(&self, element_index: usize, data: &mut [Self::Data]) {
+        let n = self.num_points_per_element();
+        data.clone_from_slice(&self.data[n * element_index..n * (element_index + 1)]);
+    }
+
+    fn populate_element_quadrature(&self, _element_index: usize, points: &mut [OPoint<T, D>], weights: &mut [T]) {
+        points.clone_from_slice(&self.points);
+        weights.clone_from_slice(&self.weights);
+    }
+}
+
+/// Solves quasi-static phase-field fracture problems with a staggered scheme.
+///
+/// Each call to [`step`](Self::step) solves for the state at the next load step, given by the
+/// Dirichlet boundary conditions of the displacement. In each staggered iteration,
+///
+/// 1. the displacement is solved for with Newton's method, with the elastic energy degraded by
+///    the current phase-field,
+/// 2. the history field is updated with the tensile energy density of the new displacement,
+/// 3. the linear phase-field equation is solved and the phase-field is projected onto
+///    $[d_n, 1]$, where $d_n$ is the phase-field at the end of the previous step. This enforces
+///    the irreversibility of the crack evolution and, for the AT1 model, the lower bound
+///    $d \geq 0$.
+///
+/// The history field is a quadrature point quantity and is committed at the end of a converged
+/// step. The phase-field is subject to homogeneous Neumann boundary conditions.
+#[derive(Debug)]
+pub struct PhaseFieldFractureSolver<'a, T, Space>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    space: &'a Space,
+    material: PhaseFieldMaterial<T>,
+    elasticity_operator: DegradedElasticityOperator,
+    phase_field_operator: PhaseFieldOperator,
+    newton_solver: NewtonSolver<T>,
+    settings: StaggeredSettings<T>,
+    elasticity_table: ElementwiseQuadratureTable<T, Space::ReferenceDim, DegradedElasticityParameters<T>>,
+    phase_field_table: ElementwiseQuadratureTable<T, Space::ReferenceDim, PhaseFieldParameters<T>>,
+    displacement: DVector<T>,
+    phase_field: DVector<T>,
+    history: Vec<T>,
+}
+
+impl<'a, T, Space> PhaseFieldFractureSolver<'a, T, Space>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::ReferenceDim, Space::GeometryDim, Space::ReferenceDim>,
+{
+    /// Creates a solver for the AT2 model without energy split, starting from zero displacement
+    /// and phase-field.
+    ///
+    /// The quadrature rule is used for all elements and for both fields.
+    pub fn new(
+        space: &'a Space,
+        quadrature: QuadraturePair<T, Space::ReferenceDim>,
+        material: PhaseFieldMaterial<T>,
+    ) -> Self {
+        let num_elements = space.num_elements();
+        let elasticity_parameters = DegradedElasticityParameters {
+            lame: material.lame,
+            degradation: T::one(),
+        };
+        let phase_field_parameters = PhaseFieldParameters {
+            fracture_toughness: material.fracture_toughness,
+            length_scale: material.length_scale,
+            history: T::zero(),
+        };
+        let elasticity_table = ElementwiseQuadratureTable::new(quadrature.clone(), num_elements, elasticity_parameters);
+        let phase_field_table = ElementwiseQuadratureTable::new(quadrature, num_elements, phase_field_parameters);
+        let num_nodes = space.num_nodes();
+        Self {
+            space,
+            material,
+            elasticity_operator: DegradedElasticityOperator::new(),
+            phase_field_operator: PhaseFieldOperator::default(),
+            newton_solver: NewtonSolver::new(),
+            settings: StaggeredSettings::default(),
+            history: vec![T::zero(); elasticity_table.data.len()],
+            elasticity_table,
+            phase_field_table,
+            displacement: DVector::zeros(Space::ReferenceDim::dim() * num_nodes),
+            phase_field: DVector::zeros(num_nodes),
+        }
+    }
+
+    pub fn with_model(self, model: PhaseFieldModel) -> Self {
+        Self {
+            phase_field_operator: PhaseFieldOperator::new(model),
+            ..self
+        }
+    }
+
+    pub fn with_energy_split(self, split: EnergySplit) -> Self {
+        Self {
+            elasticity_operator: DegradedElasticityOperator::with_split(split),
+            ..self
+        }
+    }
+
+    pub fn with_staggered_settings(self, settings: StaggeredSettings<T>) -> Self {
+        Self { settings, ..self }
+    }
+
+    /// Sets the Newton solver used for the displacement.
+    pub fn with_newton_solver(self, newton_solver: NewtonSolver<T>) -> Self {
+        Self { newton_solver, ..self }
+    }
+
+    pub fn displacement(&self) -> &DVector<T> {
+        &self.displacement
+    }
+
+    pub fn phase_field(&self) -> &DVector<T> {
+        &self.phase_field
+    }
+
+    /// The history field, stored per quadrature point in element order.
+    pub fn history(&self) -> &[T] {
+        &self.history
+    }
+
+    /// Advances the solution to the next load step with the given displacement boundary
+    /// conditions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the boundary conditions do not match the displacement degrees of
+    /// freedom, if a Newton or linear solve fails, or if the staggered iterations do not converge
+    /// within the maximum number of iterations. In case of errors, the history field is not
+    /// updated.
+    pub fn step(
+        &mut self,
+        bc: &DirichletConditions<T>,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<StaggeredStepInfo> {
+        if bc.num_dofs() != self.displacement.len() {
+            return Err(eyre!(
+                "Number of degrees of freedom in boundary conditions ({}) does not match number of displacement \
+                 degrees of freedom ({})",
+                bc.num_dofs(),
+                self.displacement.len()
+            ));
+        }
+        bc.apply_to_vector(&mut self.displacement);
+        let homogeneous_bc = DirichletConditions::try_homogeneous(bc.num_dofs(), bc.constrained_dofs())?;
+        let previous_phase_field = self.phase_field.clone();
+        let mut trial_history = self.history.clone();
+        let mut info = StaggeredStepInfo {
+            staggered_iterations: 0,
+            newton_iterations: 0,
+        };
+
+        while info.staggered_iterations < self.settings.max_iterations {
+            info.staggered_iterations += 1;
+            self.update_degradation();
+            info.newton_iterations += self.solve_displacement(&homogeneous_bc, linear_solver)?;
+            self.update_history(&mut trial_history);
+
+            let change = self.solve_phase_field(&previous_phase_field, linear_solver)?;
+            debug!(
+                "Staggered iteration {}: phase-field change {}",
+                info.staggered_iterations, change
+            );
+            if change <= self.settings.tolerance {
+                self.history = trial_history;
+                return Ok(info);
+            }
+        }
+
+        Err(eyre!(
+            "Staggered iterations failed to converge within {} iterations",
+            self.settings.max_iterations
+        ))
+    }
+
+    /// Calls the closure with the index of each quadrature point, the interpolated phase-field
+    /// and the interpolated displacement gradient.
+    fn for_each_quadrature_point(
+        &self,
+        mut f: impl FnMut(usize, T, OMatrix<T, Space::ReferenceDim, Space::ReferenceDim>),
+    ) {
+        let dim = Space::ReferenceDim::dim();
+        let mut u_buffer = InterpolationBuffer::default();
+        let mut d_buffer = InterpolationBuffer::default();
+        let n = self.elasticity_table.num_points_per_element();
+        for element_index in 0..self.space.num_elements() {
+            let mut u_element = u_buffer.prepare_element_in_space(element_index, self.space, &self.displacement, dim);
+            let mut d_element = d_buffer.prepare_element_in_space(element_index, self.space, &self.phase_field, 1);
+            for (k, xi) in self.elasticity_table.points.iter().enumerate() {
+                u_element.update_reference_point(xi, BufferUpdate::BasisGradients);
+                d_element.update_reference_point(xi, BufferUpdate::BasisValues);
+                let j = u_element.element_reference_jacobian();
+                let inv_j_t = j
+                    .try_inverse()
+                    .expect("Element Jacobian must be invertible")
+                    .transpose();
+                let u_grad = inv_j_t * u_element.interpolate_ref_gradient::<Space::ReferenceDim>();
+                let d = d_element.interpolate::<U1>()[0];
+                f(n * element_index + k, d, u_grad);
+            }
+        }
+    }
+
+    fn update_degradation(&mut self) {
+        let mut degradation = vec![T::zero(); self.history.len()];
+        let k = self.material.residual_stiffness;
+        self.for_each_quadrature_point(|i, d, _| degradation[i] = degradation_function(d, k));
+        for (parameters, g) in self.elasticity_table.data.iter_mut().zip(degradation) {
+            parameters.degradation = g;
+        }
+    }
+
+    fn update_history(&mut self, trial_history: &mut [T]) {
+        let operator = &self.elasticity_operator;
+        let lame = &self.material.lame;
+        let history = &self.history;
+        self.for_each_quadrature_point(|i, _, u_grad| {
+            let tensile = operator.tensile_energy_density(&u_grad, lame);
+            trial_history[i] = history[i].max(tensile);
+        });
+        for (parameters, &h) in self
+            .phase_field_table
+            .data
+            .iter_mut()
+            .zip(trial_history.iter())
+        {
+            parameters.history = h;
+        }
+    }
+
+    /// Solves for the displacement with fixed phase-field, returning the number of Newton
+    /// iterations.
+    fn solve_displacement(
+        &mut self,
+        homogeneous_bc: &DirichletConditions<T>,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<usize> {
+        let space = self.space;
+        let operator = &self.elasticity_operator;
+        let qtable = &self.elasticity_table;
+        let mut system = NonlinearSystemFn::new(
+            |mut output: DVectorViewMut<T>, u: DVectorView<T>| {
+                let assembler = ElementEllipticAssemblerBuilder::new()
+                    .with_finite_element_space(space)
+                    .with_operator(operator)
+                    .with_quadrature_table(qtable)
+                    .with_u(u)
+                    .build();
+                output.copy_from(&VectorAssembler::default().assemble_vector(&assembler)?);
+                for dof in homogeneous_bc.constrained_dofs() {
+                    output[dof.index()] = T::zero();
+                }
+                Ok(())
+            },
+            |u: DVectorView<T>| {
+                let assembler = ElementEllipticAssemblerBuilder::new()
+                    .with_finite_element_space(space)
+                    .with_operator(operator)
+                    .with_quadrature_table(qtable)
+                    .with_u(u)
+                    .build();
+                let mut jacobian = CsrAssembler::default().assemble(&assembler)?;
+                let mut rhs = DVector::zeros(jacobian.nrows());
+                homogeneous_bc.apply_row_replacement(&mut jacobian, &mut rhs)?;
+                Ok(jacobian)
+            },
+        );
+        let report = self
+            .newton_solver
+            .solve(&mut system, &mut self.displacement, linear_solver)?;
+        Ok(report.num_iterations())
+    }
+
+    /// Solves for the phase-field with fixed history field, returning the maximum change of the
+    /// nodal values.
+    fn solve_phase_field(
+        &mut self,
+        previous_phase_field: &DVector<T>,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<T> {
+        let assembler = ElementAdvectionDiffusionReactionAssembler::new()
+            .with_space(self.space)
+            .with_operator(&self.phase_field_operator)
+            .with_quadrature_table(&self.phase_field_table);
+        let matrix = CsrAssembler::default().assemble(&assembler)?;
+        let rhs = VectorAssembler::default().assemble_vector(&assembler)?;
+        let mut phase_field = self.phase_field.clone();
+        linear_solver.solve(&matrix, DVectorViewMut::from(&mut phase_field), DVectorView::from(&rhs))?;
+
+        let mut change = T::zero();
+        for ((d, &d_old), &d_previous) in phase_field
+            .iter_mut()
+            .zip(self.phase_field.iter())
+            .zip(previous_phase_field.iter())
+        {
+            *d = d.max(d_previous).min(T::one());
+            change = change.max((*d - d_old).abs());
+        }
+        self.phase_field = phase_field;
+        Ok(change)
+    }
+}
@@ -0,0 +1,468 @@
+//! Assembly of the Stokes equations for incompressible flow.
+//!
+//! The Stokes equations for a velocity $\vec u$ and pressure $p$ with viscosity $\mu$ and body
+//! force $\vec f$ read
+//! <div>$$
+//! \begin{aligned}
+//! - \mu \Delta \vec u + \nabla p &= \vec f, \\\\
+//! \nabla \cdot \vec u &= 0.
+//! \end{aligned}
+//! $$</div>
+//! The velocity and pressure must be discretized with an inf-sup stable pair of spaces, such as
+//! the [Taylor-Hood elements](crate::space::TaylorHoodMesh) used with [`StokesAssembler`] or
+//! the cheaper MINI elements used with [`MiniStokesAssembler`]. The same structure arises in
+//! incompressible (or nearly incompressible) elasticity, where $\vec u$ is the displacement.
+use crate::allocators::TriDimAllocator;
+use crate::assembly::block::{BlockLayout, BlockSystemAssembler};
+use crate::assembly::global::{CsrAssembler, VectorAssembler};
+use crate::assembly::local::{
+    condense_element_system, recover_condensed_dofs, Density, ElementConnectivityAssembler,
+    ElementEllipticAssemblerBuilder, ElementMassAssembler, ElementMatrixAssembler, ElementMixedAssembler,
+    ElementMixedMatrixAssembler, ElementSourceAssemblerBuilder, ElementVectorAssembler, SourceFunction,
+    UniformQuadratureTable,
+};
+use crate::assembly::operators::{DivergenceOperator, Viscosity, ViscousOperator};
+use crate::connectivity::Connectivity;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::mesh::Mesh;
+use crate::nalgebra::{
+    DMatrix, DMatrixView, DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Scalar, U1,
+};
+use crate::nalgebra_sparse::CsrMatrix;
+use crate::quadrature::QuadraturePair;
+use crate::space::{BubbleEnrichedSpace, FiniteElementConnectivity, TaylorHoodMesh, VolumetricFiniteElementSpace};
+use crate::Real;
+
+/// Assembles the discrete Stokes system for a pair of velocity and pressure spaces.
+///
+/// The assembled system has the symmetric saddle point form
+/// <div>$$
+/// \begin{pmatrix} \vec A & \vec B^T \\\\ \vec B & \vec 0 \end{pmatrix}
+/// \begin{pmatrix} \vec u \\\\ \vec p \end{pmatrix}
+/// = \begin{pmatrix} \vec f \\\\ \vec 0 \end{pmatrix},
+/// $$</div>
+/// where $\vec A$ is the matrix of the [`ViscousOperator`] and $\vec B$ the matrix of the
+/// [`DivergenceOperator`]. The unknowns are numbered according to [`layout`](Self::layout),
+/// i.e. the velocity degrees of freedom are followed by the pressure degrees of freedom.
+///
+/// Note that with Dirichlet conditions for the velocity on the entire boundary, the pressure is
+/// only determined up to a constant, which is typically fixed by constraining the pressure at a
+/// single node. To this end, the zero pressure block is stored with the sparsity pattern of the
+/// pressure mass matrix, so that the matrix contains the diagonal entries needed by
+/// [`DirichletConditions::apply_row_replacement`](crate::assembly::bc::DirichletConditions::apply_row_replacement).
+pub struct StokesAssembler<'a, T, VelocitySpace, PressureSpace>
+where
+    T: Real,
+    VelocitySpace: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, VelocitySpace::GeometryDim, VelocitySpace::ReferenceDim, U1>,
+{
+    velocity_space: &'a VelocitySpace,
+    pressure_space: &'a PressureSpace,
+    viscous_table: UniformQuadratureTable<T, VelocitySpace::ReferenceDim, Viscosity<T>>,
+    pressure_table: UniformQuadratureTable<T, VelocitySpace::ReferenceDim, Density<T>>,
+    table: UniformQuadratureTable<T, VelocitySpace::ReferenceDim>,
+}
+
+impl<'a, T, VelocitySpace, PressureSpace> StokesAssembler<'a, T, VelocitySpace, PressureSpace>
+where
+    T: Real,
+    VelocitySpace: VolumetricFiniteElementSpace<T>,
+    PressureSpace: VolumetricFiniteElementSpace<T, ReferenceDim = VelocitySpace::ReferenceDim>,
+    DefaultAllocator: TriDimAllocator<T, VelocitySpace::GeometryDim, VelocitySpace::ReferenceDim, U1>,
+{
+    /// Creates an assembler for the given velocity and pressure spaces with unit viscosity.
+    ///
+    /// The elements of the two spaces must coincide, and the quadrature rule is used for all
+    /// elements. For Taylor-Hood elements, the quadrature should integrate products of gradients
+    /// of the velocity basis functions exactly on affine elements.
+    pub fn new(
+        velocity_space: &'a VelocitySpace,
+        pressure_space: &'a PressureSpace,
+        quadrature: QuadraturePair<T, VelocitySpace::ReferenceDim>,
+    ) -> Self {
+        let table = UniformQuadratureTable::from_quadrature(quadrature);
+        Self {
+            velocity_space,
+            pressure_space,
+            viscous_table: table.clone().with_uniform_data(Viscosity(T::one())),
+            pressure_table: table.clone().with_uniform_data(Density(T::zero())),
+            table,
+        }
+    }
+
+    pub fn with_viscosity(self, viscosity: T) -> Self {
+        Self {
+            viscous_table: self.table.clone().with_uniform_data(Viscosity(viscosity)),
+            ..self
+        }
+    }
+
+    /// The block layout of the velocity and pressure degrees of freedom.
+    ///
+    /// The velocity is in block `0` and the pressure in block `1`.
+    pub fn layout(&self) -> BlockLayout {
+        BlockLayout::new()
+            .with_space(self.velocity_space, VelocitySpace::ReferenceDim::dim())
+            .with_space(self.pressure_space, 1)
+    }
+
+    /// Assembles the system matrix.
+    pub fn assemble_matrix(&self) -> eyre::Result<CsrMatrix<T>> {
+        let zero_velocity = DVector::zeros(VelocitySpace::ReferenceDim::dim() * self.velocity_space.num_nodes());
+        let viscous_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(self.velocity_space)
+            .with_operator(&ViscousOperator)
+            .with_quadrature_table(&self.viscous_table)
+            .with_u(&zero_velocity)
+            .build();
+        let divergence_assembler = ElementMixedAssembler::new()
+            .with_test_space(self.pressure_space)
+            .with_trial_space(self.velocity_space)
+            .with_operator(&DivergenceOperator)
+            .with_quadrature_table(&self.table);
+        // Vanishing "mass matrix" providing the sparsity pattern of the pressure block
+        let pressure_assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(self.pressure_space)
+            .with_quadrature_table(&self.pressure_table);
+
+        let mut system = BlockSystemAssembler::from_layout(self.layout());
+        system
+            .add_operator(0, &viscous_assembler)
+            .add_symmetric_coupling(1, 0, &divergence_assembler)
+            .add_operator(1, &pressure_assembler);
+        system.assemble_matrix()
+    }
+
+    /// Assembles the right-hand side for the given body force.
+    pub fn assemble_rhs<Source>(&self, body_force: &Source) -> eyre::Result<DVector<T>>
+    where
+        Source:
+            SourceFunction<T, VelocitySpace::ReferenceDim, SolutionDim = VelocitySpace::ReferenceDim, Parameters = ()>,
+    {
+        let layout = self.layout();
+        let source_assembler = ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(self.velocity_space)
+            .with_source(body_force)
+            .with_quadrature_table(&self.table)
+            .build();
+        let mut rhs = DVector::zeros(layout.num_dofs());
+        VectorAssembler::default().assemble_vector_into(layout.block_mut(0, &mut rhs), &source_assembler)?;
+        Ok(rhs)
+    }
+}
+
+impl<'a, T, D, VelocityConnectivity, PressureConnectivity>
+    StokesAssembler<'a, T, Mesh<T, D, VelocityConnectivity>, Mesh<T, D, PressureConnectivity>>
+where
+    T: Real,
+    D: DimName,
+    VelocityConnectivity: Connectivity,
+    PressureConnectivity: Connectivity,
+    Mesh<T, D, VelocityConnectivity>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    Mesh<T, D, PressureConnectivity>: VolumetricFiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: TriDimAllocator<T, D, D, U1>,
+{
+    /// Creates an assembler for the velocity and pressure meshes of the given Taylor-Hood mesh.
+    pub fn from_taylor_hood_mesh(
+        mesh: &'a TaylorHoodMesh<T, D, VelocityConnectivity, PressureConnectivity>,
+        quadrature: QuadraturePair<T, D>,
+    ) -> Self {
+        Self::new(mesh.velocity_mesh(), mesh.pressure_mesh(), quadrature)
+    }
+}
+
+/// Assembles the discrete Stokes system for MINI elements, with the velocity bubbles condensed out.
+///
+/// The MINI element discretizes the velocity with continuous piecewise linear functions enriched
+/// with a [bubble function](BubbleEnrichedSpace) on each element, and the pressure with
+/// continuous piecewise linear functions on the same triangles or tetrahedra. The pair is
+/// inf-sup stable, but considerably cheaper than the P2-P1
+/// [Taylor-Hood elements](crate::space::TaylorHoodMesh), as the bubble degrees of freedom are
+/// internal to each element and are eliminated by
+/// [static condensation](crate::assembly::local::condense_element_system) during assembly.
+///
+/// The assembled system has the same block structure as for [`StokesAssembler`], with the
+/// velocity at the nodes of the linear space in block `0` followed by the pressure in block `1`,
+/// see [`layout`](Self::layout). After condensation, the pressure block is no longer zero, but
+/// contains the (negative semi-definite) contribution
+/// $- \vec B_b \vec A_{bb}^{-1} \vec B_b^T$ of the bubbles, which stabilizes the pressure.
+/// Once the condensed system has been solved, the bubble coefficients can be recovered with
+/// [`recover_velocity`](Self::recover_velocity).
+pub struct MiniStokesAssembler<'a, T, Space>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, U1>,
+{
+    velocity_space: &'a BubbleEnrichedSpace<Space>,
+    viscous_table: UniformQuadratureTable<T, Space::ReferenceDim, Viscosity<T>>,
+    table: UniformQuadratureTable<T, Space::ReferenceDim>,
+}
+
+impl<'a, T, Space> MiniStokesAssembler<'a, T, Space>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, U1>,
+{
+    /// Creates an assembler for the given bubble-enriched velocity space with unit viscosity.
+    ///
+    /// The pressure is discretized with the underlying linear space of the velocity space.
+    /// The quadrature should integrate products of gradients of the bubble functions exactly,
+    /// i.e. it should have strength $4$ for triangles and $6$ for tetrahedra.
+    pub fn new(
+        velocity_space: &'a BubbleEnrichedSpace<Space>,
+        quadrature: QuadraturePair<T, Space::ReferenceDim>,
+    ) -> Self {
+        let table = UniformQuadratureTable::from_quadrature(quadrature);
+        Self {
+            velocity_space,
+            viscous_table: table.clone().with_uniform_data(Viscosity(T::one())),
+            table,
+        }
+    }
+
+    pub fn with_viscosity(self, viscosity: T) -> Self {
+        Self {
+            viscous_table: self.table.clone().with_uniform_data(Viscosity(viscosity)),
+            ..self
+        }
+    }
+
+    /// The block layout of the condensed velocity and pressure degrees of freedom.
+    ///
+    /// The velocity at the nodes of the linear space is in block `0` and the pressure in block `1`.
+    pub fn layout(&self) -> BlockLayout {
+        let linear_space = self.velocity_space.space();
+        BlockLayout::new()
+            .with_space(linear_space, Space::ReferenceDim::dim())
+            .with_space(linear_space, 1)
+    }
+
+    /// Assembles the condensed system matrix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if element assembly fails, or if the bubble block of an element matrix
+    /// is singular.
+    pub fn assemble_matrix(&self) -> eyre::Result<CsrMatrix<T>> {
+        self.with_condensed_assembler(None, |assembler| CsrAssembler::default().assemble(assembler))
+    }
+
+    /// Assembles the condensed right-hand side for the given body force.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if element assembly fails, or if the bubble block of an element matrix
+    /// is singular.
+    pub fn assemble_rhs<Source>(&self, body_force: &Source) -> eyre::Result<DVector<T>>
+    where
+        Source: SourceFunction<T, Space::ReferenceDim, SolutionDim = Space::ReferenceDim, Parameters = ()>,
+    {
+        let source_assembler = self.source_assembler(body_force);
+        self.with_condensed_assembler(Some(&source_assembler), |assembler| {
+            VectorAssembler::default().assemble_vector(assembler)
+        })
+    }
+
+    /// Recovers the velocity in the bubble-enriched space from a solution of the condensed system.
+    ///
+    /// The body force must be the same as the one used to assemble the right-hand side. The
+    /// returned vector contains the velocity at all nodes of the
+    /// [velocity space](BubbleEnrichedSpace), i.e. the nodal velocities of the solution followed
+    /// by the bubble coefficients of each element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if element assembly fails, or if the bubble block of an element matrix
+    /// is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the solution does not match the [layout](Self::layout).
+    pub fn recover_velocity<'b, Source>(
+        &self,
+        body_force: &Source,
+        solution: impl Into<DVectorView<'b, T>>,
+    ) -> eyre::Result<DVector<T>>
+    where
+        Source: SourceFunction<T, Space::ReferenceDim, SolutionDim = Space::ReferenceDim, Parameters = ()>,
+    {
+        let solution = solution.into();
+        let layout = self.layout();
+        assert_eq!(solution.len(), layout.num_dofs(), "Solution dimension mismatch");
+        let d = Space::ReferenceDim::dim();
+        let mut velocity = DVector::zeros(d * self.velocity_space.num_nodes());
+        velocity
+            .rows_mut(0, layout.block_size(0))
+            .copy_from(&solution.rows_range(layout.block_range(0)));
+
+        let source_assembler = self.source_assembler(body_force);
+        self.with_condensed_assembler(Some(&source_assembler), |assembler| {
+            let mut dofs = Vec::new();
+            for element_index in 0..assembler.num_elements() {
+                dofs.resize(assembler.element_node_count(element_index.into()), 0);
+                assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut dofs), element_index.into());
+                let u_retained = DVector::from_iterator(dofs.len(), dofs.iter().map(|&dof| solution[dof]));
+                let (matrix, vector) = assembler.element_system(element_index)?;
+                let bubble = recover_condensed_dofs(
+                    DMatrixView::from(&matrix),
+                    DVectorView::from(&vector),
+                    DVectorView::from(&u_retained),
+                )?;
+                let bubble_node = self.velocity_space.bubble_node(element_index);
+                velocity.rows_mut(d * bubble_node, d).copy_from(&bubble);
+            }
+            Ok(())
+        })?;
+        Ok(velocity)
+    }
+
+    fn source_assembler<'b, Source>(&'b self, body_force: &'b Source) -> impl ElementVectorAssembler<T> + 'b
+    where
+        Source: SourceFunction<T, Space::ReferenceDim, SolutionDim = Space::ReferenceDim, Parameters = ()>,
+    {
+        ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(self.velocity_space)
+            .with_source(body_force)
+            .with_quadrature_table(&self.table)
+            .build()
+    }
+
+    fn with_condensed_assembler<R>(
+        &self,
+        source: Option<&dyn ElementVectorAssembler<T>>,
+        f: impl FnOnce(&CondensedMiniElementAssembler<T>) -> eyre::Result<R>,
+    ) -> eyre::Result<R> {
+        let d = Space::ReferenceDim::dim();
+        let zero_velocity = DVector::zeros(d * self.velocity_space.num_nodes());
+        let viscous_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(self.velocity_space)
+            .with_operator(&ViscousOperator)
+            .with_quadrature_table(&self.viscous_table)
+            .with_u(&zero_velocity)
+            .build();
+        let divergence_assembler = ElementMixedAssembler::new()
+            .with_test_space(self.velocity_space.space())
+            .with_trial_space(self.velocity_space)
+            .with_operator(&DivergenceOperator)
+            .with_quadrature_table(&self.table);
+        f(&CondensedMiniElementAssembler {
+            viscous: &viscous_assembler,
+            divergence: &divergence_assembler,
+            source,
+            dim: d,
+            num_linear_nodes: self.velocity_space.space().num_nodes(),
+        })
+    }
+}
+
+/// Element assembler for the condensed MINI element systems.
+///
+/// Since the velocity and pressure have different solution dimensions, the assembler treats
+/// each (monolithic) degree of freedom as a scalar node, so that the element systems can be
+/// assembled directly into the monolithic system.
+struct CondensedMiniElementAssembler<'b, T: Scalar> {
+    viscous: &'b dyn ElementMatrixAssembler<T>,
+    divergence: &'b dyn ElementMixedMatrixAssembler<T>,
+    source: Option<&'b dyn ElementVectorAssembler<T>>,
+    dim: usize,
+    num_linear_nodes: usize,
+}
+
+impl<'b, T: Real> CondensedMiniElementAssembler<'b, T> {
+    /// Assembles the uncondensed element system, with the retained degrees of freedom (nodal
+    /// velocities followed by pressures) followed by the internal bubble degrees of freedom.
+    fn element_system(&self, element_index: usize) -> eyre::Result<(DMatrix<T>, DVector<T>)> {
+        let d = self.dim;
+        let n = self.divergence.row_element_node_count(element_index);
+        let num_velocity_dofs = d * (n + 1);
+        let num_retained = (d + 1) * n;
+        let a = self.viscous.assemble_element_matrix(element_index)?;
+        let b = self
+            .divergence
+            .assemble_element_mixed_matrix(element_index)?;
+        let f = match self.source {
+            Some(source) => source.assemble_element_vector(element_index)?,
+            None => DVector::zeros(num_velocity_dofs),
+        };
+
+        // The bubble is the last velocity node, so only its dofs need to be moved past the pressure
+        let local_index = |i: usize| if i < d * n { i } else { i + n };
+        let mut matrix = DMatrix::zeros(num_retained + d, num_retained + d);
+        let mut vector = DVector::zeros(num_retained + d);
+        for i in 0..num_velocity_dofs {
+            vector[local_index(i)] = f[i];
+            for k in 0..num_velocity_dofs {
+                matrix[(local_index(i), local_index(k))] = a[(i, k)];
+            }
+            for j in 0..n {
+                matrix[(d * n + j, local_index(i))] = b[(j, i)];
+                matrix[(local_index(i), d * n + j)] = b[(j, i)];
+            }
+        }
+        Ok((matrix, vector))
+    }
+
+    fn condensed_element_system(&self, element_index: usize) -> eyre::Result<(DMatrix<T>, DVector<T>)> {
+        let (matrix, vector) = self.element_system(element_index)?;
+        let num_retained = self.element_node_count(element_index.into());
+        condense_element_system(DMatrixView::from(&matrix), DVectorView::from(&vector), num_retained)
+    }
+}
+
+impl<'b, T: Scalar> ElementConnectivityAssembler for CondensedMiniElementAssembler<'b, T> {
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.divergence.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        (self.dim + 1) * self.num_linear_nodes
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        (self.dim + 1)
+            * self
+                .divergence
+                .row_element_node_count(element_index.index())
+    }
+
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        let output = NodeIndex::as_raw_slice_mut(output);
+        let d = self.dim;
+        let n = self
+            .divergence
+            .row_element_node_count(element_index.index());
+        assert_eq!(output.len(), (d + 1) * n);
+        let (velocity_dofs, pressure_dofs) = output.split_at_mut(d * n);
+        self.divergence
+            .populate_row_element_nodes(pressure_dofs, element_index.index());
+        for (i, pressure_dof) in pressure_dofs.iter_mut().enumerate() {
+            let node = *pressure_dof;
+            for k in 0..d {
+                velocity_dofs[d * i + k] = d * node + k;
+            }
+            *pressure_dof = d * self.num_linear_nodes + node;
+        }
+    }
+}
+
+impl<'b, T: Real> ElementMatrixAssembler<T> for CondensedMiniElementAssembler<'b, T> {
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let (matrix, _) = self.condensed_element_system(element_index)?;
+        output.copy_from(&matrix);
+        Ok(())
+    }
+}
+
+impl<'b, T: Real> ElementVectorAssembler<T> for CondensedMiniElementAssembler<'b, T> {
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let (_, vector) = self.condensed_element_system(element_index)?;
+        output.copy_from(&vector);
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+//! Nonlinear systems of equations.
+//!
+//! The discretization of a nonlinear problem, for example with an
+//! [`EllipticOperator`](crate::assembly::operators::EllipticOperator), or an implicit time step
+//! of a nonlinear transient problem (see [`timestepping`](crate::timestepping)), leads to a
+//! nonlinear system of equations
+//! <div>$$
+//! \vec r(\vec u) = \vec 0.
+//! $$</div>
+//! Such systems are described by the [`NonlinearSystem`] trait, which can be implemented directly
+//! or constructed from residual and Jacobian closures with [`NonlinearSystemFn`].
+use crate::linear_solver::LinearSolver;
+use crate::Real;
+use eyre::eyre;
+use fenris_optimize::calculus::{DifferentiableVectorFunction, VectorFunction};
+use fenris_optimize::newton::{
+    newton_with_callback, BacktrackingLineSearch, NewtonConvergence, NewtonIteration, NewtonSettings, NoLineSearch,
+};
+use nalgebra::{DVector, DVectorView, DVectorViewMut};
+use nalgebra_sparse::CsrMatrix;
+use std::error::Error;
+
+/// A nonlinear system of equations $\vec r(\vec u) = \vec 0$.
+pub trait NonlinearSystem<T: Real> {
+    /// Evaluates the residual $\vec r(\vec u)$.
+    fn compute_residual(&mut self, output: DVectorViewMut<T>, u: DVectorView<T>) -> eyre::Result<()>;
+
+    /// Computes the Jacobian $\partial \vec r / \partial \vec u$ at $\vec u$.
+    fn compute_jacobian(&mut self, u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>>;
+}
+
+impl<T, S> NonlinearSystem<T> for &mut S
+where
+    T: Real,
+    S: ?Sized + NonlinearSystem<T>,
+{
+    fn compute_residual(&mut self, output: DVectorViewMut<T>, u: DVectorView<T>) -> eyre::Result<()> {
+        S::compute_residual(self, output, u)
+    }
+
+    fn compute_jacobian(&mut self, u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>> {
+        S::compute_jacobian(self, u)
+    }
+}
+
+/// A nonlinear system defined by closures for the residual and the Jacobian.
+#[derive(Debug, Clone)]
+pub struct NonlinearSystemFn<R, J> {
+    residual: R,
+    jacobian: J,
+}
+
+impl<R, J> NonlinearSystemFn<R, J> {
+    pub fn new(residual: R, jacobian: J) -> Self {
+        Self { residual, jacobian }
+    }
+}
+
+impl<T, R, J> NonlinearSystem<T> for NonlinearSystemFn<R, J>
+where
+    T: Real,
+    R: FnMut(DVectorViewMut<T>, DVectorView<T>) -> eyre::Result<()>,
+    J: FnMut(DVectorView<T>) -> eyre::Result<CsrMatrix<T>>,
+{
+    fn compute_residual(&mut self, output: DVectorViewMut<T>, u: DVectorView<T>) -> eyre::Result<()> {
+        (self.residual)(output, u)
+    }
+
+    fn compute_jacobian(&mut self, u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>> {
+        (self.jacobian)(u)
+    }
+}
+
+/// Adapts a [`NonlinearSystem`] and a [`LinearSolver`] to the function interface of
+/// [`fenris_optimize::newton`].
+///
+/// Since the function interface is infallible, the first error raised by the system or the linear
+/// solver is stored, and the residual is subsequently set to NaN, which terminates the iterations.
+struct NewtonFunction<'a, S, L> {
+    system: &'a mut S,
+    linear_solver: &'a mut L,
+    dimension: usize,
+    error: Option<eyre::Report>,
+}
+
+impl<'a, T, S, L> VectorFunction<T> for NewtonFunction<'a, S, L>
+where
+    T: Real,
+    S: NonlinearSystem<T>,
+    L: LinearSolver<T>,
+{
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn eval_into(&mut self, f: &mut DVectorViewMut<T>, x: &DVectorView<T>) {
+        if self.error.is_none() {
+            match self
+                .system
+                .compute_residual(DVectorViewMut::from(&mut *f), *x)
+            {
+                Ok(()) => return,
+                Err(err) => self.error = Some(err),
+            }
+        }
+        f.fill(T::from_f64(f64::NAN).unwrap());
+    }
+}
+
+impl<'a, T, S, L> DifferentiableVectorFunction<T> for NewtonFunction<'a, S, L>
+where
+    T: Real,
+    S: NonlinearSystem<T>,
+    L: LinearSolver<T>,
+{
+    fn solve_jacobian_system(
+        &mut self,
+        sol: &mut DVectorViewMut<T>,
+        x: &DVectorView<T>,
+        rhs: &DVectorView<T>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.error.is_none() {
+            let result = self.system.compute_jacobian(*x).and_then(|jacobian| {
+                sol.fill(T::zero());
+                self.linear_solver
+                    .solve(&jacobian, DVectorViewMut::from(&mut *sol), *rhs)
+            });
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) => self.error = Some(err),
+            }
+        }
+        Err(Box::from("Failed to evaluate the nonlinear system"))
+    }
+}
+
+/// Solves the system with [`fenris_optimize::newton`], using the provided `u` as the initial guess.
+///
+/// Errors raised by the system or the linear solver are returned in place of the error reported
+/// by the Newton iterations.
+pub(crate) fn solve_with_newton<'a, T: Real>(
+    system: &mut impl NonlinearSystem<T>,
+    u: impl Into<DVectorViewMut<'a, T>>,
+    linear_solver: &mut impl LinearSolver<T>,
+    settings: NewtonSettings<T>,
+    line_search: Option<BacktrackingLineSearch<T>>,
+    callback: impl FnMut(&NewtonIteration<T>),
+) -> eyre::Result<NewtonConvergence<T>> {
+    let u = u.into();
+    let mut f = DVector::zeros(u.len());
+    let mut dx = DVector::zeros(u.len());
+    let mut function = NewtonFunction {
+        system,
+        linear_solver,
+        dimension: u.len(),
+        error: None,
+    };
+    let result = match line_search {
+        Some(mut line_search) => {
+            newton_with_callback(&mut function, u, &mut f, &mut dx, settings, &mut line_search, callback)
+        }
+        None => newton_with_callback(&mut function, u, &mut f, &mut dx, settings, &mut NoLineSearch, callback),
+    };
+    match function.error {
+        Some(err) => Err(err),
+        None => result.map_err(|err| eyre!("Newton iterations failed: {err}")),
+    }
+}
@@ -0,0 +1,67 @@
+//! The core API of `fenris`.
+//!
+//! The prelude re-exports a curated subset of the library that covers the most common workflow:
+//! creating or loading meshes, defining finite element spaces on them, assembling matrices and
+//! vectors, and exporting the results. This subset is considered the *core* of `fenris`, and is
+//! subject to stronger stability commitments than the rest of the library: items are only
+//! removed or changed in incompatible ways after a deprecation period of at least one minor
+//! release. Downstream crates that restrict themselves to the prelude should therefore rarely be
+//! broken by internal refactoring.
+//!
+//! ```
+//! use fenris::prelude::*;
+//! # fn main() -> eyre::Result<()> {
+//! let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(8);
+//! let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+//! let u = DVector::zeros(mesh.vertices().len());
+//! let stiffness = CsrAssembler::default().assemble(
+//!     &ElementEllipticAssemblerBuilder::new()
+//!         .with_finite_element_space(&mesh)
+//!         .with_operator(&LaplaceOperator)
+//!         .with_quadrature_table(&qtable)
+//!         .with_u(&u)
+//!         .build(),
+//! )?;
+//! assert_eq!(stiffness.nrows(), mesh.vertices().len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See the [crate documentation](crate#api-stability) for an overview of the stability tiers of
+//! the library.
+pub use crate::{quadrature, Real, SmallDim, Symmetry};
+
+pub use crate::nalgebra::{DMatrix, DVector, Point2, Point3, Vector2, Vector3, U1, U2, U3};
+pub use crate::nalgebra_sparse::CsrMatrix;
+
+pub use crate::connectivity::{
+    CellConnectivity, Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity,
+    Tet4Connectivity, Tri3d2Connectivity, Tri6d2Connectivity,
+};
+pub use crate::element::{ElementConnectivity, FiniteElement, ReferenceFiniteElement};
+pub use crate::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+pub use crate::mesh::{HexMesh, Mesh, Mesh2d, Mesh3d, QuadMesh2d, Tet10Mesh, Tet4Mesh, Tri6Mesh2d, TriangleMesh2d};
+
+pub use crate::space::{
+    interpolate_at_points, FindClosestElement, FiniteElementConnectivity, FiniteElementSpace, SpatiallyIndexed,
+    VolumetricFiniteElementSpace,
+};
+
+pub use crate::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, CsrParAssembler,
+    VectorAssembler, VectorParAssembler,
+};
+pub use crate::assembly::local::{
+    CompactQuadratureTable, ElementConnectivityAssembler, ElementEllipticAssemblerBuilder, ElementMassAssembler,
+    ElementMatrixAssembler, ElementScalarAssembler, ElementSourceAssemblerBuilder, ElementVectorAssembler,
+    GeneralQuadratureTable, QuadratureTable, SourceFunction, UniformQuadratureTable,
+};
+pub use crate::assembly::operators::{
+    EllipticContraction, EllipticEnergy, EllipticOperator, LaplaceOperator, Operator,
+};
+
+pub use crate::io::msh::load_msh_from_file;
+pub use crate::io::vtk::FieldExporter;
@@ -1,12 +1,21 @@
+//! Proptest strategies for elements, meshes and fields.
+//!
+//! Strategies that have tunable size or degeneracy take their parameters explicitly, with
+//! defaults that match the strategies without parameters. To make failures reproducible outside
+//! of a proptest harness, for example in fuzzing setups or when a failing case should be turned
+//! into a regular unit test, values can also be generated deterministically from a seed with
+//! [`value_from_seed`] and related functions.
 use crate::element::{Tet4Element, Tri3d2Element};
 use crate::geometry::proptest::Triangle3dParams;
 use crate::geometry::Orientation::Counterclockwise;
 use crate::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
 use crate::mesh::QuadMesh2d;
 use ::proptest::prelude::*;
-use fenris_geometry::proptest::Triangle2dParams;
+use ::proptest::strategy::ValueTree;
+use ::proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+use fenris_geometry::proptest::{nondegenerate_triangle2d_strategy_f64, Triangle2dParams};
 use fenris_geometry::{Triangle2d, Triangle3d};
-use nalgebra::{Point2, Point3, Vector2};
+use nalgebra::{DVector, Point2, Point3, Vector2};
 use std::cmp::max;
 
 pub fn point2() -> impl Strategy<Value = Point2<f64>> {
@@ -25,25 +34,76 @@ pub fn point3() -> impl Strategy<Value = Point3<f64>> {
     [range.clone(), range.clone(), range.clone()].prop_map(|[x, y, z]| Point3::new(x, y, z))
 }
 
+/// Parameters for the [`Arbitrary`] implementations of elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementParams {
+    scale: f64,
+    nondegenerate: bool,
+}
+
+impl ElementParams {
+    /// Scales the coordinates of the vertices, which by default lie roughly in $[-10, 10]$.
+    pub fn with_scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Only generates elements with a volume bounded away from zero.
+    ///
+    /// By default, degenerate elements with zero volume may be generated.
+    pub fn nondegenerate(self) -> Self {
+        Self {
+            nondegenerate: true,
+            ..self
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn is_nondegenerate(&self) -> bool {
+        self.nondegenerate
+    }
+}
+
+impl Default for ElementParams {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            nondegenerate: false,
+        }
+    }
+}
+
 impl Arbitrary for Tri3d2Element<f64> {
-    type Parameters = ();
+    type Parameters = ElementParams;
     type Strategy = BoxedStrategy<Self>;
 
-    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
-        any_with::<Triangle2d<f64>>(Triangle2dParams::default().with_orientation(Counterclockwise))
-            .prop_map(|triangle| Self::from(triangle))
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        let triangles = if args.nondegenerate {
+            nondegenerate_triangle2d_strategy_f64().boxed()
+        } else {
+            any_with::<Triangle2d<f64>>(Triangle2dParams::default().with_orientation(Counterclockwise)).boxed()
+        };
+        triangles
+            .prop_map(move |triangle| Self::from_vertices(triangle.0.map(|v| v * args.scale)))
             .boxed()
     }
 }
 
 impl Arbitrary for Tet4Element<f64> {
-    // TODO: Reasonable parameters?
-    type Parameters = ();
+    type Parameters = ElementParams;
     type Strategy = BoxedStrategy<Self>;
 
-    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        // Bounds on the (relative) height of the fourth vertex and the area of the base triangle
+        // of nondegenerate tetrahedra
+        let (min_normal_param, min_normal_norm) = if args.nondegenerate { (1e-2, 1e-2) } else { (0.0, 0.0) };
         any_with::<Triangle3d<f64>>(Triangle3dParams::default().with_orientation(Counterclockwise))
-            .prop_flat_map(|triangle| {
+            .prop_filter("Degenerate base triangle", move |triangle| {
+                !args.nondegenerate || triangle.normal_dir().norm() > min_normal_norm
+            })
+            .prop_flat_map(move |triangle| {
                 // To create an arbitrary tetrahedron element, we take a counter-clockwise oriented
                 // triangle, and pick a point somewhere on the "positive" side of the
                 // triangle plane. We do this by associating a parameter with each
@@ -51,17 +111,18 @@ impl Arbitrary for Tet4Element<f64> {
                 // plus a non-negative parameter that scales along the normal direction
                 let range = -10.0..10.0;
                 let tangent_params = [range.clone(), range.clone(), range.clone()];
-                let normal_param = 0.0..=10.0;
+                let normal_param = min_normal_param..=10.0;
                 (Just(triangle), tangent_params, normal_param)
             })
-            .prop_map(|(triangle, tangent_params, normal_param)| {
+            .prop_map(move |(triangle, tangent_params, normal_param)| {
                 let mut tangent_pos = triangle.centroid();
 
                 for (side, param) in triangle.sides().iter().zip(&tangent_params) {
                     tangent_pos.coords += *param * side;
                 }
                 let coord = tangent_pos + normal_param * triangle.normal_dir().normalize();
-                Tet4Element::from_vertices([triangle.0[0], triangle.0[1], triangle.0[2], coord])
+                let vertices = [triangle.0[0], triangle.0[1], triangle.0[2], coord];
+                Tet4Element::from_vertices(vertices.map(|v| v * args.scale))
             })
             .boxed()
     }
@@ -82,22 +143,170 @@ fn rectangular_uniform_mesh_cell_distribution_strategy(
 }
 
 pub fn rectangular_uniform_mesh_strategy(unit_length: f64, max_cells: usize) -> impl Strategy<Value = QuadMesh2d<f64>> {
-    rectangular_uniform_mesh_cell_distribution_strategy(max_cells).prop_map(
-        move |(cells_per_unit, units_x, units_y)| {
-            create_rectangular_uniform_quad_mesh_2d(
+    rectangular_mesh_strategy(RectangularMeshParams::new(unit_length, max_cells))
+}
+
+/// Parameters for [`rectangular_mesh_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectangularMeshParams {
+    unit_length: f64,
+    max_cells: usize,
+    perturbation: f64,
+}
+
+impl RectangularMeshParams {
+    /// Parameters for uniform meshes of rectangles made up of squares with the given side length,
+    /// with at most `max_cells` cells.
+    pub fn new(unit_length: f64, max_cells: usize) -> Self {
+        Self {
+            unit_length,
+            max_cells,
+            perturbation: 0.0,
+        }
+    }
+
+    /// Randomly displaces interior vertices by at most the given fraction of the cell size in
+    /// each coordinate direction.
+    ///
+    /// Perturbations below $1/2$ keep all cells convex, while larger perturbations generate
+    /// increasingly distorted and eventually inverted cells.
+    pub fn with_perturbation(self, perturbation: f64) -> Self {
+        Self { perturbation, ..self }
+    }
+
+    pub fn unit_length(&self) -> f64 {
+        self.unit_length
+    }
+
+    pub fn max_cells(&self) -> usize {
+        self.max_cells
+    }
+
+    pub fn perturbation(&self) -> f64 {
+        self.perturbation
+    }
+}
+
+/// A strategy for meshes of rectangles made up of quadrilaterals.
+///
+/// The meshes are uniform meshes of squares with a corner at the origin, whose interior
+/// vertices are optionally perturbed.
+pub fn rectangular_mesh_strategy(params: RectangularMeshParams) -> impl Strategy<Value = QuadMesh2d<f64>> {
+    let RectangularMeshParams {
+        unit_length,
+        max_cells,
+        perturbation,
+    } = params;
+    rectangular_uniform_mesh_cell_distribution_strategy(max_cells)
+        .prop_flat_map(move |(cells_per_unit, units_x, units_y)| {
+            let mesh = create_rectangular_uniform_quad_mesh_2d(
                 unit_length,
                 units_x,
                 units_y,
                 cells_per_unit,
                 &Vector2::new(0.0, 0.0),
-            )
-        },
-    )
+            );
+            let cell_size = unit_length / cells_per_unit as f64;
+            let max_displacement = perturbation * cell_size;
+            let displacement = if max_displacement > 0.0 {
+                (-max_displacement..=max_displacement).boxed()
+            } else {
+                Just(0.0).boxed()
+            };
+            let displacements = proptest::collection::vec([displacement.clone(), displacement], mesh.vertices().len());
+            (Just(mesh), displacements)
+        })
+        .prop_map(|(mut mesh, displacements)| {
+            let (min, max) = mesh.vertices().iter().fold(
+                (
+                    Point2::new(f64::INFINITY, f64::INFINITY),
+                    Point2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+                ),
+                |(min, max), v| (min.inf(v), max.sup(v)),
+            );
+            for (v, [dx, dy]) in mesh.vertices_mut().iter_mut().zip(displacements) {
+                let on_boundary = v.x == min.x || v.x == max.x || v.y == min.y || v.y == max.y;
+                if !on_boundary {
+                    v.x += dx;
+                    v.y += dy;
+                }
+            }
+            mesh
+        })
+}
+
+/// A strategy for nodal fields with `solution_dim` components per node, whose entries are
+/// bounded by `max_abs` in absolute value.
+pub fn nodal_field_strategy(
+    num_nodes: usize,
+    solution_dim: usize,
+    max_abs: f64,
+) -> impl Strategy<Value = DVector<f64>> {
+    proptest::collection::vec(-max_abs..=max_abs, num_nodes * solution_dim).prop_map(DVector::from_vec)
+}
+
+/// Deterministically generates a value from the given strategy and seed.
+///
+/// The same seed always generates the same value (for a fixed version of `proptest`), which
+/// makes it possible to reproduce values outside of a proptest harness.
+///
+/// # Panics
+///
+/// Panics if the strategy fails to generate a value, which can happen if it rejects too many
+/// values.
+pub fn value_from_seed<S: Strategy>(strategy: &S, seed: u64) -> S::Value {
+    let mut runner = seeded_runner(seed);
+    strategy
+        .new_tree(&mut runner)
+        .unwrap_or_else(|reason| panic!("Failed to generate value from seed {seed}: {reason}"))
+        .current()
+}
+
+/// Deterministically generates a number of values from the given strategy and seed.
+///
+/// See [`value_from_seed`] for details.
+pub fn values_from_seed<S: Strategy>(strategy: &S, seed: u64, count: usize) -> Vec<S::Value> {
+    let mut runner = seeded_runner(seed);
+    (0..count)
+        .map(|_| {
+            strategy
+                .new_tree(&mut runner)
+                .unwrap_or_else(|reason| panic!("Failed to generate value from seed {seed}: {reason}"))
+                .current()
+        })
+        .collect()
+}
+
+/// Deterministically generates an arbitrary value with the given parameters from a seed.
+///
+/// For example, `arbitrary_from_seed::<Tet4Element<f64>>(ElementParams::default(), 42)`
+/// always generates the same tetrahedron. See [`value_from_seed`] for details.
+pub fn arbitrary_from_seed<A: Arbitrary>(params: A::Parameters, seed: u64) -> A {
+    value_from_seed(&any_with::<A>(params), seed)
+}
+
+/// Deterministically generates a mesh from the given parameters and seed.
+///
+/// See [`rectangular_mesh_strategy`] and [`value_from_seed`] for details.
+pub fn rectangular_mesh_from_seed(params: RectangularMeshParams, seed: u64) -> QuadMesh2d<f64> {
+    value_from_seed(&rectangular_mesh_strategy(params), seed)
+}
+
+fn seeded_runner(seed: u64) -> TestRunner {
+    let mut seed_bytes = [0; 32];
+    seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed_bytes);
+    TestRunner::new_with_rng(Config::default(), rng)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::rectangular_uniform_mesh_cell_distribution_strategy;
+    use super::{
+        arbitrary_from_seed, nodal_field_strategy, rectangular_mesh_from_seed, rectangular_mesh_strategy,
+        rectangular_uniform_mesh_cell_distribution_strategy, value_from_seed, values_from_seed, ElementParams,
+        RectangularMeshParams,
+    };
+    use crate::element::{ElementConnectivity, FiniteElement, Tet4Element, Tri3d2Element};
     use crate::geometry::proptest::{
         convex_quad2d_strategy_f64, nondegenerate_convex_quad2d_strategy_f64, nondegenerate_triangle2d_strategy_f64,
     };
@@ -139,5 +348,52 @@ mod tests {
         ){
             prop_assert!(quad.area() > 0.0);
         }
+
+        #[test]
+        fn nondegenerate_tetrahedra_have_positive_volume(
+            tet in any_with::<Tet4Element<f64>>(ElementParams::default().nondegenerate())
+        ) {
+            let volume = tet.reference_jacobian(&Default::default()).determinant() / 6.0;
+            prop_assert!(volume > 0.0);
+        }
+
+        #[test]
+        fn perturbed_rectangular_meshes_have_positively_oriented_cells(
+            mesh in rectangular_mesh_strategy(RectangularMeshParams::new(2.0, 16).with_perturbation(0.3))
+        ) {
+            for cell in mesh.connectivity() {
+                let quad = cell.element(mesh.vertices()).unwrap();
+                prop_assert!(quad.reference_jacobian(&Default::default()).determinant() > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn values_from_seed_are_deterministic() {
+        let params = ElementParams::default().nondegenerate();
+        let tet: Tet4Element<f64> = arbitrary_from_seed(params, 42);
+        assert_eq!(tet, arbitrary_from_seed(params, 42));
+        assert_ne!(tet, arbitrary_from_seed(params, 43));
+        let scaled_tet: Tet4Element<f64> = arbitrary_from_seed(params.with_scale(0.5), 42);
+        for (v, v_scaled) in tet.vertices().iter().zip(scaled_tet.vertices()) {
+            assert_eq!(v * 0.5, *v_scaled);
+        }
+
+        let triangles: Vec<Tri3d2Element<f64>> = values_from_seed(&any::<Tri3d2Element<f64>>(), 7, 5);
+        assert_eq!(triangles, values_from_seed(&any::<Tri3d2Element<f64>>(), 7, 5));
+        assert_eq!(triangles[0], value_from_seed(&any::<Tri3d2Element<f64>>(), 7));
+
+        let params = RectangularMeshParams::new(1.0, 50).with_perturbation(0.2);
+        let mesh = rectangular_mesh_from_seed(params, 3);
+        assert_eq!(mesh.vertices(), rectangular_mesh_from_seed(params, 3).vertices());
+        assert_eq!(
+            mesh.connectivity(),
+            rectangular_mesh_from_seed(params, 3).connectivity()
+        );
+
+        let field = value_from_seed(&nodal_field_strategy(4, 3, 2.0), 11);
+        assert_eq!(field.len(), 12);
+        assert!(field.amax() <= 2.0);
+        assert_eq!(field, value_from_seed(&nodal_field_strategy(4, 3, 2.0), 11));
     }
 }
@@ -13,6 +13,9 @@ pub use canonical::*;
 /// TODO: How to prevent collapse?
 pub use fenris_quadrature::Error as QuadratureError;
 
+pub mod adaptive;
+pub mod cut;
+pub mod registry;
 pub mod subdivide;
 pub mod tensor;
 pub mod total_order;
@@ -0,0 +1,532 @@
+//! Adaptive quadrature with error control.
+//!
+//! Standard quadrature rules integrate polynomials up to a fixed degree exactly, but may lose
+//! accuracy without warning for integrands that are not well approximated by polynomials, such
+//! as discontinuous coefficients or sharply varying source terms. [`AdaptiveSubdivision`]
+//! instead recursively subdivides the reference domain of an element until the integral of a
+//! given integrand is resolved to a prescribed tolerance.
+//!
+//! The error on each cell of the subdivision is estimated by comparing the result of a base rule
+//! applied to the children of the cell with the result of a comparison rule of at least the same
+//! strength on the cell itself. The comparison rule is a (collapsed) Gauss-Lobatto rule, whose
+//! points include the vertices of the cell, so that discontinuities that only cut off a corner
+//! of a cell are detected. The cells
+//! with the largest estimated errors are refined until the total estimated error is below the
+//! tolerance. Boxes (segments, quadrilaterals and
+//! hexahedra) are split into $2^d$ boxes, and simplices are refined into $2^d$ simplices by
+//! red refinement.
+//!
+//! Integrands are given in reference coordinates. In order to integrate a function $f$ over a
+//! physical element, the integrand must include the determinant of the Jacobian of the
+//! reference-to-physical map, i.e. $\xi \mapsto f(x(\xi)) \\, |\det J(\xi)|$. The resulting
+//! [adapted rule](AdaptiveIntegral::quadrature) may be reused for other integrands with similar
+//! features on the same element.
+//!
+//! # Example
+//!
+//! ```
+//! use fenris::quadrature::adaptive::AdaptiveSubdivision;
+//!
+//! // The area of the part of the reference square [-1, 1]^2 that lies inside the unit circle
+//! let result = AdaptiveSubdivision::new()
+//!     .with_absolute_tolerance(1e-3)
+//!     .with_max_levels(12)
+//!     .integrate_quadrilateral(|xi| if xi.coords.norm() < 1.0 { 1.0 } else { 0.0 });
+//! assert!(result.converged());
+//! assert!((result.integral() - std::f64::consts::PI).abs() < 1e-3);
+//! ```
+use crate::allocators::DimAllocator;
+use crate::quadrature::cut::{convert_simplex, refine_simplex, SimplexRule};
+use crate::quadrature::{univariate, QuadratureError, QuadraturePair};
+use crate::{Real, SmallDim};
+use nalgebra::{point, DefaultAllocator, OPoint, Point1, Point2, Point3, U1, U2, U3};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The result of an adaptive integration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveIntegral<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    integral: T,
+    error_estimate: T,
+    num_evaluations: usize,
+    converged: bool,
+    quadrature: QuadraturePair<T, D>,
+}
+
+impl<T, D> AdaptiveIntegral<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// The approximate integral.
+    pub fn integral(&self) -> T {
+        self.integral
+    }
+
+    /// The estimated absolute error of the integral.
+    ///
+    /// The estimate is the sum of the differences between the comparison rule and the refined
+    /// base rule on each of the cells of the final subdivision. Since the returned integral is
+    /// computed with the refined rules, the estimate is usually pessimistic for smooth
+    /// integrands. Like any estimate that is based on point evaluations, it may miss features
+    /// of the integrand that lie between the quadrature points.
+    pub fn error_estimate(&self) -> T {
+        self.error_estimate
+    }
+
+    /// The number of times the integrand was evaluated.
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations
+    }
+
+    /// Returns `true` if the tolerance was met before reaching the maximum number of levels.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The adapted quadrature rule in reference coordinates, which reproduces the integral.
+    pub fn quadrature(&self) -> &QuadraturePair<T, D> {
+        &self.quadrature
+    }
+
+    pub fn into_quadrature(self) -> QuadraturePair<T, D> {
+        self.quadrature
+    }
+}
+
+/// Integrates functions over reference domains by adaptive subdivision.
+///
+/// The cell with the largest estimated error is refined until the total estimated error is below
+/// $\max(\epsilon_{\mathrm{abs}}, \\, \epsilon_{\mathrm{rel}} \\, |I|)$, where $I$ is the
+/// approximate integral. Cells are not refined beyond the
+/// [maximum number of levels](Self::with_max_levels). If the tolerance cannot be met with the
+/// maximum number of levels, the result is flagged as [not converged](AdaptiveIntegral::converged).
+/// The domain is uniformly refined up to the [minimum number of levels](Self::with_min_levels)
+/// before any errors are estimated, which reduces the risk of missing small features of the
+/// integrand. The [strength](Self::with_strength) determines the polynomial degree that the base
+/// rule integrates exactly on each cell.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSubdivision {
+    strength: usize,
+    absolute_tolerance: f64,
+    relative_tolerance: f64,
+    min_levels: usize,
+    max_levels: usize,
+}
+
+impl Default for AdaptiveSubdivision {
+    fn default() -> Self {
+        Self {
+            strength: 5,
+            absolute_tolerance: 1e-10,
+            relative_tolerance: 1e-8,
+            min_levels: 0,
+            max_levels: 8,
+        }
+    }
+}
+
+impl AdaptiveSubdivision {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_strength(self, strength: usize) -> Self {
+        Self { strength, ..self }
+    }
+
+    pub fn with_absolute_tolerance(self, absolute_tolerance: f64) -> Self {
+        Self {
+            absolute_tolerance,
+            ..self
+        }
+    }
+
+    pub fn with_relative_tolerance(self, relative_tolerance: f64) -> Self {
+        Self {
+            relative_tolerance,
+            ..self
+        }
+    }
+
+    pub fn with_min_levels(self, min_levels: usize) -> Self {
+        Self { min_levels, ..self }
+    }
+
+    pub fn with_max_levels(self, max_levels: usize) -> Self {
+        Self { max_levels, ..self }
+    }
+
+    pub fn strength(&self) -> usize {
+        self.strength
+    }
+
+    pub fn absolute_tolerance(&self) -> f64 {
+        self.absolute_tolerance
+    }
+
+    pub fn relative_tolerance(&self) -> f64 {
+        self.relative_tolerance
+    }
+
+    pub fn min_levels(&self) -> usize {
+        self.min_levels
+    }
+
+    pub fn max_levels(&self) -> usize {
+        self.max_levels
+    }
+
+    /// Integrates the given function over the reference segment $[-1, 1]$.
+    pub fn integrate_segment<T: Real>(&self, f: impl FnMut(&Point1<T>) -> T) -> AdaptiveIntegral<T, U1> {
+        let cell = (point![-T::one()], point![T::one()]);
+        self.integrate_boxes(vec![cell], f)
+    }
+
+    /// Integrates the given function over the reference quadrilateral $[-1, 1]^2$.
+    pub fn integrate_quadrilateral<T: Real>(&self, f: impl FnMut(&Point2<T>) -> T) -> AdaptiveIntegral<T, U2> {
+        let cell = (point![-T::one(), -T::one()], point![T::one(), T::one()]);
+        self.integrate_boxes(vec![cell], f)
+    }
+
+    /// Integrates the given function over the reference hexahedron $[-1, 1]^3$.
+    pub fn integrate_hexahedron<T: Real>(&self, f: impl FnMut(&Point3<T>) -> T) -> AdaptiveIntegral<T, U3> {
+        let cell = (
+            point![-T::one(), -T::one(), -T::one()],
+            point![T::one(), T::one(), T::one()],
+        );
+        self.integrate_boxes(vec![cell], f)
+    }
+
+    /// Integrates the given function over the reference triangle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no triangle rule with the requested strength is available.
+    pub fn integrate_triangle<T: Real>(
+        &self,
+        f: impl FnMut(&Point2<T>) -> T,
+    ) -> Result<AdaptiveIntegral<T, U2>, QuadratureError> {
+        let triangle = vec![point![-1.0, -1.0], point![1.0, -1.0], point![-1.0, 1.0]];
+        self.integrate_simplices(vec![convert_simplex(triangle)], f)
+    }
+
+    /// Integrates the given function over the reference tetrahedron.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no tetrahedron rule with the requested strength is available.
+    pub fn integrate_tetrahedron<T: Real>(
+        &self,
+        f: impl FnMut(&Point3<T>) -> T,
+    ) -> Result<AdaptiveIntegral<T, U3>, QuadratureError> {
+        let tetrahedron = vec![
+            point![-1.0, -1.0, -1.0],
+            point![1.0, -1.0, -1.0],
+            point![-1.0, 1.0, -1.0],
+            point![-1.0, -1.0, 1.0],
+        ];
+        self.integrate_simplices(vec![convert_simplex(tetrahedron)], f)
+    }
+
+    fn integrate_boxes<T, D>(
+        &self,
+        cells: Vec<(OPoint<T, D>, OPoint<T, D>)>,
+        f: impl FnMut(&OPoint<T, D>) -> T,
+    ) -> AdaptiveIntegral<T, D>
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let base_rule = univariate::gauss::<T>(self.strength / 2 + 1);
+        // The Gauss-Lobatto rule includes the vertices of the box, so that features that cut
+        // off a corner of the box are detected even if they contain no Gauss point
+        let comparison_rule = univariate::try_gauss_lobatto::<T>(self.strength / 2 + 2)
+            .expect("Gauss-Lobatto rules with at least two points always exist");
+        let apply_rule = |cell: &(OPoint<T, D>, OPoint<T, D>), output: &mut QuadraturePair<T, D>| {
+            apply_tensor_rule_to_box(&base_rule, cell, output)
+        };
+        let apply_comparison_rule = |cell: &(OPoint<T, D>, OPoint<T, D>), output: &mut QuadraturePair<T, D>| {
+            apply_tensor_rule_to_box(&comparison_rule, cell, output)
+        };
+        let refine = |(lower, upper): &(OPoint<T, D>, OPoint<T, D>)| {
+            let midpoint = OPoint::from(lower.coords.lerp(&upper.coords, T::from_f64(0.5).unwrap()));
+            (0..1 << D::dim())
+                .map(|octant: usize| {
+                    let mut child = (lower.clone(), midpoint.clone());
+                    for i in (0..D::dim()).filter(|i| octant & (1 << i) != 0) {
+                        child.0[i] = midpoint[i];
+                        child.1[i] = upper[i];
+                    }
+                    child
+                })
+                .collect()
+        };
+        self.integrate_cells(cells, apply_rule, apply_comparison_rule, refine, f)
+    }
+
+    fn integrate_simplices<T, D>(
+        &self,
+        simplices: Vec<Vec<OPoint<T, D>>>,
+        f: impl FnMut(&OPoint<T, D>) -> T,
+    ) -> Result<AdaptiveIntegral<T, D>, QuadratureError>
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let base_rule = SimplexRule::new(D::dim(), self.strength)?;
+        let comparison_rule = collapsed_gauss_lobatto_rule(D::dim(), self.strength);
+        let apply_rule = |simplex: &Vec<OPoint<T, D>>, output: &mut QuadraturePair<T, D>| {
+            base_rule.apply_to_simplex(simplex, output);
+        };
+        let apply_comparison_rule = |simplex: &Vec<OPoint<T, D>>, output: &mut QuadraturePair<T, D>| {
+            comparison_rule.apply_to_simplex(simplex, output);
+        };
+        let refine = |simplex: &Vec<OPoint<T, D>>| refine_simplex(simplex);
+        Ok(self.integrate_cells(simplices, apply_rule, apply_comparison_rule, refine, f))
+    }
+
+    fn integrate_cells<T, D, Cell>(
+        &self,
+        cells: Vec<Cell>,
+        apply_rule: impl Fn(&Cell, &mut QuadraturePair<T, D>),
+        apply_comparison_rule: impl Fn(&Cell, &mut QuadraturePair<T, D>),
+        refine: impl Fn(&Cell) -> Vec<Cell>,
+        mut f: impl FnMut(&OPoint<T, D>) -> T,
+    ) -> AdaptiveIntegral<T, D>
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let mut num_evaluations = 0;
+        // Applies a rule to a cell and returns the rule along with the integral
+        let mut integrate_cell = |cell: &Cell, apply: &dyn Fn(&Cell, &mut QuadraturePair<T, D>)| {
+            let mut rule = (Vec::new(), Vec::new());
+            apply(cell, &mut rule);
+            num_evaluations += rule.0.len();
+            let integral = rule
+                .0
+                .iter()
+                .zip(&rule.1)
+                .fold(T::zero(), |sum, (&w, x)| sum + w * f(x));
+            (rule, integral)
+        };
+        // Compares the comparison rule on a cell with the base rule on its children
+        let mut estimate_cell = |cell: Cell, level: usize| {
+            let (_, coarse_integral) = integrate_cell(&cell, &apply_comparison_rule);
+            let children: Vec<_> = refine(&cell)
+                .into_iter()
+                .map(|child| {
+                    let (rule, integral) = integrate_cell(&child, &apply_rule);
+                    (child, rule, integral)
+                })
+                .collect();
+            let fine_integral = children
+                .iter()
+                .fold(T::zero(), |sum, (_, _, integral)| sum + *integral);
+            EstimatedCell {
+                children,
+                integral: fine_integral,
+                error: (fine_integral - coarse_integral).abs(),
+                level,
+            }
+        };
+
+        let min_levels = self.min_levels.min(self.max_levels.saturating_sub(1));
+        let mut cells = cells;
+        for _ in 0..min_levels {
+            cells = cells.iter().flat_map(&refine).collect();
+        }
+        let mut active = BinaryHeap::new();
+        for cell in cells {
+            active.push(estimate_cell(cell, min_levels));
+        }
+
+        let absolute_tolerance = T::from_f64(self.absolute_tolerance).unwrap();
+        let relative_tolerance = T::from_f64(self.relative_tolerance).unwrap();
+        let sum_errors =
+            |cells: &BinaryHeap<EstimatedCell<Cell, T, D>>| cells.iter().fold(T::zero(), |sum, cell| sum + cell.error);
+        let mut finished = Vec::new();
+        let mut error_estimate = sum_errors(&active);
+        let mut integral = active
+            .iter()
+            .fold(T::zero(), |sum, cell| sum + cell.integral);
+        while error_estimate > absolute_tolerance.max(relative_tolerance * integral.abs()) {
+            let Some(cell) = active.pop() else { break };
+            if cell.level + 1 >= self.max_levels {
+                // The cell cannot be refined further, but its error still counts
+                finished.push(cell);
+                continue;
+            }
+            error_estimate -= cell.error;
+            integral -= cell.integral;
+            for (child, _, _) in cell.children {
+                let child = estimate_cell(child, cell.level + 1);
+                error_estimate += child.error;
+                integral += child.integral;
+                active.push(child);
+            }
+        }
+
+        // Recompute the sums to avoid accumulating round-off errors from the updates above
+        let leaves: Vec<_> = active.into_iter().chain(finished).collect();
+        let integral = leaves
+            .iter()
+            .fold(T::zero(), |sum, cell| sum + cell.integral);
+        let error_estimate = leaves.iter().fold(T::zero(), |sum, cell| sum + cell.error);
+        let mut quadrature = (Vec::new(), Vec::new());
+        for (_, (weights, points), _) in leaves.into_iter().flat_map(|cell| cell.children) {
+            quadrature.0.extend(weights);
+            quadrature.1.extend(points);
+        }
+        AdaptiveIntegral {
+            integral,
+            error_estimate,
+            num_evaluations,
+            converged: error_estimate <= absolute_tolerance.max(relative_tolerance * integral.abs()),
+            quadrature,
+        }
+    }
+}
+
+/// Appends the tensor product of the given univariate rule, mapped to the given box, to the output.
+fn apply_tensor_rule_to_box<T, D>(
+    (weights_1d, points_1d): &QuadraturePair<T, U1>,
+    (lower, upper): &(OPoint<T, D>, OPoint<T, D>),
+    output: &mut QuadraturePair<T, D>,
+) where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let half = T::from_f64(0.5).unwrap();
+    let center = lower.coords.lerp(&upper.coords, half);
+    let half_extents = (&upper.coords - &lower.coords) * half;
+    let num_points = weights_1d.len().pow(D::dim() as u32);
+    for index in 0..num_points {
+        let mut remainder = index;
+        let mut w = T::one();
+        let mut x = center.clone();
+        for i in 0..D::dim() {
+            let k = remainder % weights_1d.len();
+            remainder /= weights_1d.len();
+            w *= weights_1d[k] * half_extents[i];
+            x[i] += points_1d[k][0] * half_extents[i];
+        }
+        output.0.push(w);
+        output.1.push(OPoint::from(x));
+    }
+}
+
+/// Constructs a simplex rule of the given strength whose points include the vertices of the
+/// simplex.
+///
+/// A tensor-product Gauss-Lobatto rule on the unit cube is mapped to the simplex by collapsing
+/// it onto the vertices $1, \dots, d$ (the Duffy transform), which puts points with non-zero
+/// weights at the vertices $0$ and $d$. The rule is symmetrized with a copy for the rotated
+/// vertices $d - 1, \dots, 2 d - 1 \pmod{d + 1}$, so that all vertices carry non-zero weights.
+fn collapsed_gauss_lobatto_rule<T: Real>(dim: usize, strength: usize) -> SimplexRule<T> {
+    // The Jacobian of the Duffy transform adds up to d - 1 to the degree in each direction, and
+    // a Gauss-Lobatto rule with n points has strength 2n - 3
+    let (weights_1d, points_1d) = univariate::try_gauss_lobatto::<T>((strength + dim + 3) / 2)
+        .expect("Gauss-Lobatto rules with at least two points always exist");
+    let half = T::from_f64(0.5).unwrap();
+    let points_1d: Vec<T> = points_1d.iter().map(|p| half * (p[0] + T::one())).collect();
+
+    let mut weights = Vec::new();
+    let mut barycentric_points = Vec::new();
+    for index in 0..weights_1d.len().pow(dim as u32) {
+        let mut remainder = index;
+        let mut weight = T::one();
+        let mut lambda = vec![T::zero(); dim + 1];
+        // The barycentric coordinates are given by lambda_{i + 1} = t_i (1 - t_0) ... (1 - t_{i - 1}),
+        // and the Jacobian of the transform is the product of the scale factors
+        let mut scale = T::one();
+        for i in 0..dim {
+            let k = remainder % weights_1d.len();
+            remainder /= weights_1d.len();
+            weight *= weights_1d[k] * scale;
+            lambda[i + 1] = scale * points_1d[k];
+            scale *= T::one() - points_1d[k];
+        }
+        lambda[0] = T::one() - lambda[1..].iter().fold(T::zero(), |a, &b| a + b);
+        for rotation in [0, dim - 1] {
+            let mut rotated = vec![T::zero(); dim + 1];
+            for (i, &lambda_i) in lambda.iter().enumerate() {
+                rotated[(i + rotation) % (dim + 1)] = lambda_i;
+            }
+            weights.push(weight);
+            barycentric_points.push(rotated);
+        }
+    }
+    SimplexRule::from_barycentric_points(weights, barycentric_points)
+}
+
+/// A cell along with the base rule applied to each of its children.
+struct EstimatedCell<Cell, T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    children: Vec<(Cell, QuadraturePair<T, D>, T)>,
+    integral: T,
+    error: T,
+    level: usize,
+}
+
+// Cells are ordered by their estimated error, so that the cell with the largest error is
+// refined first
+impl<Cell, T, D> PartialEq for EstimatedCell<Cell, T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Cell, T, D> Eq for EstimatedCell<Cell, T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+}
+
+impl<Cell, T, D> PartialOrd for EstimatedCell<Cell, T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Cell, T, D> Ord for EstimatedCell<Cell, T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.error
+            .partial_cmp(&other.error)
+            .unwrap_or(Ordering::Equal)
+    }
+}
@@ -0,0 +1,658 @@
+//! Quadrature rules for elements cut by an implicit interface.
+//!
+//! Fictitious domain methods, XFEM and related cut-cell methods describe a domain or an interface
+//! implicitly as (part of) the zero level set of a function $\phi$, which generally cuts through
+//! the elements of a background mesh. Integrals over the part of an element on either side of the
+//! interface, or over the interface itself, then require quadrature rules that are tailored to the
+//! individual element. Given a level set function in the reference coordinates of an element,
+//! [`LevelSetSubdivision`] constructs rules for the *negative* subdomain $\\{\phi < 0\\}$, the
+//! *positive* subdomain $\\{\phi > 0\\}$ and the interface $\\{\phi = 0\\}$.
+//!
+//! The rules are constructed by subdivision. The reference element is split into simplices,
+//! which are recursively refined where they are crossed by the interface. On each of the
+//! resulting leaf simplices, the level set function is replaced by its linear interpolant,
+//! whose zero level set splits the simplex into simplices belonging to either subdomain and a
+//! planar facet of the interface. A standard simplex rule is then applied to each piece. The
+//! error of the rules is dominated by the linear approximation of the interface, and decreases
+//! quadratically with the size of the leaf simplices.
+//!
+//! Subdivided rules may have a large number of points. [`moment_fit`] computes weights for a
+//! fixed, smaller set of points, typically the points of a standard rule for the uncut element,
+//! that reproduce the polynomial moments of a subdivided rule up to a given degree.
+//!
+//! All rules are given in reference coordinates. Weights of the subdomain rules must be multiplied
+//! by $|\det J|$ as usual to obtain physical integrals, where $J$ is the Jacobian of the
+//! reference-to-physical map. The weights of the interface rule must instead be multiplied by
+//! $|\det J| \\, |J^{-T} n|$, where $n$ is the reference [interface normal](CutQuadrature::interface_normals).
+use crate::allocators::DimAllocator;
+use crate::quadrature::{total_order, univariate, Quadrature, QuadratureError, QuadraturePair};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+use nalgebra::{point, DMatrix, DVector, DefaultAllocator, OPoint, OVector, Point2, Point3, U2, U3};
+
+/// Quadrature rules for the two subdomains and the interface of an element cut by a level set.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutQuadrature<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    negative: QuadraturePair<T, D>,
+    positive: QuadraturePair<T, D>,
+    interface: QuadraturePair<T, D>,
+    interface_normals: Vec<OVector<T, D>>,
+}
+
+impl<T, D> CutQuadrature<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// The quadrature rule for the part of the element where the level set is negative.
+    pub fn negative(&self) -> &QuadraturePair<T, D> {
+        &self.negative
+    }
+
+    /// The quadrature rule for the part of the element where the level set is non-negative.
+    pub fn positive(&self) -> &QuadraturePair<T, D> {
+        &self.positive
+    }
+
+    /// The quadrature rule for the interface, i.e. the zero level set inside the element.
+    pub fn interface(&self) -> &QuadraturePair<T, D> {
+        &self.interface
+    }
+
+    /// The unit normals of the interface at each interface quadrature point, in reference
+    /// coordinates.
+    ///
+    /// The normals point from the negative into the positive subdomain.
+    pub fn interface_normals(&self) -> &[OVector<T, D>] {
+        &self.interface_normals
+    }
+
+    /// Returns `true` if the element is cut by the interface.
+    pub fn is_cut(&self) -> bool {
+        !self.interface.0.is_empty()
+    }
+}
+
+/// Constructs [cut-cell quadrature rules](CutQuadrature) by recursive subdivision of the
+/// reference element.
+///
+/// Simplices that are crossed by the interface are refined up to the
+/// [maximum number of refinement levels](Self::with_max_refinement_levels). A simplex is
+/// considered to be crossed by the interface if the level set changes sign between its vertices
+/// or the midpoints of its edges. The [strength](Self::with_strength) determines the polynomial
+/// degree that is integrated exactly on each of the pieces of the subdivision.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelSetSubdivision {
+    strength: usize,
+    max_refinement_levels: usize,
+}
+
+impl Default for LevelSetSubdivision {
+    fn default() -> Self {
+        Self {
+            strength: 2,
+            max_refinement_levels: 3,
+        }
+    }
+}
+
+impl LevelSetSubdivision {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_strength(self, strength: usize) -> Self {
+        Self { strength, ..self }
+    }
+
+    pub fn with_max_refinement_levels(self, max_refinement_levels: usize) -> Self {
+        Self {
+            max_refinement_levels,
+            ..self
+        }
+    }
+
+    pub fn strength(&self) -> usize {
+        self.strength
+    }
+
+    pub fn max_refinement_levels(&self) -> usize {
+        self.max_refinement_levels
+    }
+
+    /// Constructs cut-cell quadrature rules for the reference triangle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no simplex rule with the requested strength is available.
+    pub fn cut_triangle<T: Real>(
+        &self,
+        level_set: impl Fn(&Point2<T>) -> T,
+    ) -> Result<CutQuadrature<T, U2>, QuadratureError> {
+        let triangle = vec![point![-1.0, -1.0], point![1.0, -1.0], point![-1.0, 1.0]];
+        self.cut_simplices(vec![convert_simplex(triangle)], level_set)
+    }
+
+    /// Constructs cut-cell quadrature rules for the reference quadrilateral $[-1, 1]^2$.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no simplex rule with the requested strength is available.
+    pub fn cut_quadrilateral<T: Real>(
+        &self,
+        level_set: impl Fn(&Point2<T>) -> T,
+    ) -> Result<CutQuadrature<T, U2>, QuadratureError> {
+        let triangles = vec![
+            vec![point![-1.0, -1.0], point![1.0, -1.0], point![1.0, 1.0]],
+            vec![point![-1.0, -1.0], point![1.0, 1.0], point![-1.0, 1.0]],
+        ];
+        self.cut_simplices(triangles.into_iter().map(convert_simplex).collect(), level_set)
+    }
+
+    /// Constructs cut-cell quadrature rules for the reference tetrahedron.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no simplex rule with the requested strength is available.
+    pub fn cut_tetrahedron<T: Real>(
+        &self,
+        level_set: impl Fn(&Point3<T>) -> T,
+    ) -> Result<CutQuadrature<T, U3>, QuadratureError> {
+        let tetrahedron = vec![
+            point![-1.0, -1.0, -1.0],
+            point![1.0, -1.0, -1.0],
+            point![-1.0, 1.0, -1.0],
+            point![-1.0, -1.0, 1.0],
+        ];
+        self.cut_simplices(vec![convert_simplex(tetrahedron)], level_set)
+    }
+
+    /// Constructs cut-cell quadrature rules for the reference hexahedron $[-1, 1]^3$.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no simplex rule with the requested strength is available.
+    pub fn cut_hexahedron<T: Real>(
+        &self,
+        level_set: impl Fn(&Point3<T>) -> T,
+    ) -> Result<CutQuadrature<T, U3>, QuadratureError> {
+        // Kuhn subdivision into six tetrahedra that share the diagonal from (-1, -1, -1) to (1, 1, 1)
+        let permutations = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        let tetrahedra = permutations
+            .iter()
+            .map(|permutation| {
+                let mut vertex = point![-1.0, -1.0, -1.0];
+                let mut vertices = vec![vertex];
+                for &axis in permutation {
+                    vertex[axis] = 1.0;
+                    vertices.push(vertex);
+                }
+                convert_simplex(vertices)
+            })
+            .collect();
+        self.cut_simplices(tetrahedra, level_set)
+    }
+
+    fn cut_simplices<T, D>(
+        &self,
+        simplices: Vec<Vec<OPoint<T, D>>>,
+        level_set: impl Fn(&OPoint<T, D>) -> T,
+    ) -> Result<CutQuadrature<T, D>, QuadratureError>
+    where
+        T: Real,
+        D: SmallDim,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let volume_rule = SimplexRule::new(D::dim(), self.strength)?;
+        let facet_rule = SimplexRule::new(D::dim() - 1, self.strength)?;
+
+        let mut result = CutQuadrature {
+            negative: (Vec::new(), Vec::new()),
+            positive: (Vec::new(), Vec::new()),
+            interface: (Vec::new(), Vec::new()),
+            interface_normals: Vec::new(),
+        };
+
+        let mut stack: Vec<_> = simplices.into_iter().map(|simplex| (simplex, 0)).collect();
+        while let Some((simplex, level)) = stack.pop() {
+            let values: Vec<T> = simplex.iter().map(&level_set).collect();
+            if level < self.max_refinement_levels && is_crossed(&simplex, &values, &level_set) {
+                stack.extend(
+                    refine_simplex(&simplex)
+                        .into_iter()
+                        .map(|child| (child, level + 1)),
+                );
+                continue;
+            }
+
+            let split = split_simplex(&simplex, &values);
+            for piece in &split.negative {
+                volume_rule.apply_to_simplex(piece, &mut result.negative);
+            }
+            for piece in &split.positive {
+                volume_rule.apply_to_simplex(piece, &mut result.positive);
+            }
+            if !split.interface.is_empty() {
+                let normal = linear_interpolant_gradient(&simplex, &values)
+                    .and_then(|gradient| gradient.try_normalize(T::zero()));
+                if let Some(normal) = normal {
+                    for facet in &split.interface {
+                        let num_points = facet_rule.apply_to_simplex(facet, &mut result.interface);
+                        result
+                            .interface_normals
+                            .extend((0..num_points).map(|_| normal.clone()));
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Computes weights for the given points that reproduce the polynomial moments of a reference
+/// quadrature rule.
+///
+/// The returned rule integrates all polynomials of total degree at most `degree` in the same way
+/// as the reference rule. Typically, the points are those of a standard rule for the uncut
+/// element, and the reference rule is a subdivided [cut-cell rule](CutQuadrature), which gives
+/// a rule with a fixed number of points for each cut element. Moments are computed for tensor
+/// products of Legendre polynomials on $[-1, 1]^d$. If there are more points than moments, the
+/// weights with the smallest Euclidean norm are returned. The weights are not guaranteed to be
+/// positive.
+///
+/// # Errors
+///
+/// Returns an error if the moments cannot be reproduced with the given points, for example if
+/// there are too few points for the requested degree.
+pub fn moment_fit<T, D>(
+    points: &[OPoint<T, D>],
+    reference: impl Quadrature<T, D>,
+    degree: usize,
+) -> eyre::Result<QuadraturePair<T, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let exponents = total_degree_exponents(D::dim(), degree);
+    let basis = |x: &OPoint<T, D>| -> Vec<T> {
+        let legendre: Vec<Vec<T>> = x
+            .iter()
+            .map(|&x_i| legendre_polynomials(x_i, degree))
+            .collect();
+        exponents
+            .iter()
+            .map(|alpha| {
+                izip!(alpha, &legendre)
+                    .map(|(&k, values)| values[k])
+                    .fold(T::one(), |a, b| a * b)
+            })
+            .collect()
+    };
+
+    let mut moment_matrix = DMatrix::zeros(exponents.len(), points.len());
+    for (j, x) in points.iter().enumerate() {
+        moment_matrix.column_mut(j).copy_from_slice(&basis(x));
+    }
+    let mut moments = DVector::zeros(exponents.len());
+    let mut scale = T::zero();
+    for (&w, x) in reference.weights().iter().zip(reference.points()) {
+        moments += DVector::from_vec(basis(x)) * w;
+        scale += w.abs();
+    }
+
+    let weights = moment_matrix
+        .clone()
+        .svd(true, true)
+        .solve(&moments, T::default_epsilon())
+        .map_err(|err| eyre!("Failed to solve moment equations: {err}"))?;
+    let residual = (&moment_matrix * &weights - &moments).norm();
+    let tolerance = T::from_f64(1e-10).unwrap() * scale.max(T::one());
+    if residual > tolerance {
+        return Err(eyre!(
+            "Moments of degree {degree} cannot be reproduced with the given {} points (residual {residual})",
+            points.len()
+        ));
+    }
+
+    Ok((weights.iter().copied().collect(), points.to_vec()))
+}
+
+/// A quadrature rule for a simplex, given in barycentric coordinates with weights that sum to one.
+pub(super) struct SimplexRule<T> {
+    weights: Vec<T>,
+    barycentric_points: Vec<Vec<T>>,
+}
+
+impl<T: Real> SimplexRule<T> {
+    pub(super) fn new(dim: usize, strength: usize) -> Result<Self, QuadratureError> {
+        let (weights, points): (Vec<T>, Vec<Vec<T>>) = match dim {
+            1 => {
+                let (weights, points) = univariate::gauss::<T>(strength / 2 + 1);
+                (
+                    weights,
+                    points
+                        .iter()
+                        .map(|p| p.coords.as_slice().to_vec())
+                        .collect(),
+                )
+            }
+            2 => {
+                let (weights, points) = total_order::triangle::<T>(strength)?;
+                (
+                    weights,
+                    points
+                        .iter()
+                        .map(|p| p.coords.as_slice().to_vec())
+                        .collect(),
+                )
+            }
+            3 => {
+                let (weights, points) = total_order::tetrahedron::<T>(strength)?;
+                (
+                    weights,
+                    points
+                        .iter()
+                        .map(|p| p.coords.as_slice().to_vec())
+                        .collect(),
+                )
+            }
+            _ => unreachable!("Simplex rules are only supported in 1D, 2D and 3D"),
+        };
+
+        let half = T::from_f64(0.5).unwrap();
+        let barycentric_points = points
+            .into_iter()
+            .map(|xi| {
+                // Reference simplices have vertices at -1 and the unit vectors scaled and shifted
+                // to [-1, 1]
+                let mut lambda = vec![T::zero(); dim + 1];
+                for (i, &xi_i) in xi.iter().enumerate() {
+                    lambda[i + 1] = half * (xi_i + T::one());
+                }
+                lambda[0] = T::one() - lambda[1..].iter().fold(T::zero(), |a, &b| a + b);
+                lambda
+            })
+            .collect();
+        Ok(Self::from_barycentric_points(weights, barycentric_points))
+    }
+
+    /// Constructs a rule from weights and barycentric points. The weights are normalized to sum
+    /// to one.
+    pub(super) fn from_barycentric_points(weights: Vec<T>, barycentric_points: Vec<Vec<T>>) -> Self {
+        let total_weight = weights.iter().fold(T::zero(), |a, &b| a + b);
+        Self {
+            weights: weights.into_iter().map(|w| w / total_weight).collect(),
+            barycentric_points,
+        }
+    }
+
+    /// Maps the rule to the given simplex and appends it to the output, returning the number of
+    /// points that were appended.
+    pub(super) fn apply_to_simplex<D>(&self, vertices: &[OPoint<T, D>], output: &mut QuadraturePair<T, D>) -> usize
+    where
+        D: SmallDim,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let measure = simplex_measure(vertices);
+        if measure == T::zero() {
+            return 0;
+        }
+        for (&w, lambda) in self.weights.iter().zip(&self.barycentric_points) {
+            let mut x = OVector::<T, D>::zeros();
+            for (&lambda_i, v) in lambda.iter().zip(vertices) {
+                x += &v.coords * lambda_i;
+            }
+            output.0.push(w * measure);
+            output.1.push(OPoint::from(x));
+        }
+        self.weights.len()
+    }
+}
+
+/// The pieces of a simplex that is split by the zero level set of a linear function.
+struct SimplexSplit<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    negative: Vec<Vec<OPoint<T, D>>>,
+    positive: Vec<Vec<OPoint<T, D>>>,
+    interface: Vec<Vec<OPoint<T, D>>>,
+}
+
+/// Splits a triangle or tetrahedron by the zero level set of the linear interpolant of the
+/// given vertex values.
+fn split_simplex<T, D>(vertices: &[OPoint<T, D>], values: &[T]) -> SimplexSplit<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let (negative, positive): (Vec<usize>, Vec<usize>) = (0..vertices.len()).partition(|&i| values[i] < T::zero());
+    let mut split = SimplexSplit {
+        negative: Vec::new(),
+        positive: Vec::new(),
+        interface: Vec::new(),
+    };
+    if negative.is_empty() {
+        split.positive.push(vertices.to_vec());
+        return split;
+    } else if positive.is_empty() {
+        split.negative.push(vertices.to_vec());
+        return split;
+    }
+
+    let crossing = |a: usize, b: usize| {
+        let t = values[a] / (values[a] - values[b]);
+        &vertices[a] + (&vertices[b] - &vertices[a]) * t
+    };
+
+    if negative.len() == 1 || positive.len() == 1 {
+        // A single vertex is separated from the others, so the interface cuts off a simplex at
+        // this vertex, and the remainder is a prism
+        let (isolated, others, isolated_is_negative) = if negative.len() == 1 {
+            (negative[0], positive, true)
+        } else {
+            (positive[0], negative, false)
+        };
+        let cut_points: Vec<_> = others.iter().map(|&o| crossing(isolated, o)).collect();
+        let other_points: Vec<_> = others.iter().map(|&o| vertices[o].clone()).collect();
+        let mut corner = vec![vertices[isolated].clone()];
+        corner.extend(cut_points.iter().cloned());
+        let prism = prism_simplices(&cut_points, &other_points);
+        if isolated_is_negative {
+            split.negative.push(corner);
+            split.positive.extend(prism);
+        } else {
+            split.positive.push(corner);
+            split.negative.extend(prism);
+        }
+        split.interface.push(cut_points);
+    } else {
+        // Two vertices on each side of a tetrahedron: the interface is a quadrilateral, and both
+        // sides are prisms
+        let [a, b] = [negative[0], negative[1]];
+        let [c, d] = [positive[0], positive[1]];
+        let [p_ac, p_ad, p_bc, p_bd] = [crossing(a, c), crossing(a, d), crossing(b, c), crossing(b, d)];
+        split.negative.extend(prism_simplices(
+            &[vertices[a].clone(), p_ac.clone(), p_ad.clone()],
+            &[vertices[b].clone(), p_bc.clone(), p_bd.clone()],
+        ));
+        split.positive.extend(prism_simplices(
+            &[vertices[c].clone(), p_ac.clone(), p_bc.clone()],
+            &[vertices[d].clone(), p_ad.clone(), p_bd.clone()],
+        ));
+        split.interface.push(vec![p_ac.clone(), p_ad, p_bd.clone()]);
+        split.interface.push(vec![p_ac, p_bd, p_bc]);
+    }
+    split
+}
+
+/// Splits a prism into simplices, given the vertices of its two opposite faces such that
+/// `bottom[i]` and `top[i]` are connected by an edge.
+///
+/// In 2D, the "prism" is a quadrilateral with edges `bottom` and `top`.
+fn prism_simplices<T, D>(bottom: &[OPoint<T, D>], top: &[OPoint<T, D>]) -> Vec<Vec<OPoint<T, D>>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let (a, b) = (bottom, top);
+    match bottom.len() {
+        2 => vec![
+            vec![a[0].clone(), a[1].clone(), b[1].clone()],
+            vec![a[0].clone(), b[0].clone(), b[1].clone()],
+        ],
+        3 => vec![
+            vec![a[0].clone(), a[1].clone(), a[2].clone(), b[2].clone()],
+            vec![a[0].clone(), a[1].clone(), b[1].clone(), b[2].clone()],
+            vec![a[0].clone(), b[0].clone(), b[1].clone(), b[2].clone()],
+        ],
+        _ => unreachable!("Cut-cell quadrature is only supported in 2D and 3D"),
+    }
+}
+
+/// Uniformly refines a triangle into four triangles, or a tetrahedron into eight tetrahedra
+/// with Bey's algorithm.
+pub(super) fn refine_simplex<T, D>(vertices: &[OPoint<T, D>]) -> Vec<Vec<OPoint<T, D>>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let v = |i: usize| vertices[i].clone();
+    let m = |i: usize, j: usize| OPoint::from((&vertices[i].coords + &vertices[j].coords) * T::from_f64(0.5).unwrap());
+    match vertices.len() {
+        3 => vec![
+            vec![v(0), m(0, 1), m(0, 2)],
+            vec![m(0, 1), v(1), m(1, 2)],
+            vec![m(0, 2), m(1, 2), v(2)],
+            vec![m(0, 1), m(1, 2), m(0, 2)],
+        ],
+        4 => vec![
+            vec![v(0), m(0, 1), m(0, 2), m(0, 3)],
+            vec![m(0, 1), v(1), m(1, 2), m(1, 3)],
+            vec![m(0, 2), m(1, 2), v(2), m(2, 3)],
+            vec![m(0, 3), m(1, 3), m(2, 3), v(3)],
+            vec![m(0, 1), m(0, 2), m(0, 3), m(1, 3)],
+            vec![m(0, 1), m(0, 2), m(1, 2), m(1, 3)],
+            vec![m(0, 2), m(0, 3), m(1, 3), m(2, 3)],
+            vec![m(0, 2), m(1, 2), m(1, 3), m(2, 3)],
+        ],
+        _ => unreachable!("Simplex refinement is only supported in 2D and 3D"),
+    }
+}
+
+/// Determines whether the level set changes sign between the vertices or edge midpoints of the
+/// simplex.
+fn is_crossed<T, D>(vertices: &[OPoint<T, D>], values: &[T], level_set: impl Fn(&OPoint<T, D>) -> T) -> bool
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let is_negative = values[0] < T::zero();
+    if values.iter().any(|&v| (v < T::zero()) != is_negative) {
+        return true;
+    }
+    let half = T::from_f64(0.5).unwrap();
+    (0..vertices.len()).any(|i| {
+        (i + 1..vertices.len()).any(|j| {
+            let midpoint = OPoint::from((&vertices[i].coords + &vertices[j].coords) * half);
+            (level_set(&midpoint) < T::zero()) != is_negative
+        })
+    })
+}
+
+/// Computes the gradient of the linear interpolant of the vertex values on a full-dimensional
+/// simplex.
+fn linear_interpolant_gradient<T, D>(vertices: &[OPoint<T, D>], values: &[T]) -> Option<OVector<T, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let d = D::dim();
+    let edges = DMatrix::from_fn(d, d, |i, j| vertices[j + 1][i] - vertices[0][i]);
+    let differences = DVector::from_fn(d, |i, _| values[i + 1] - values[0]);
+    let gradient = edges.transpose().lu().solve(&differences)?;
+    Some(OVector::<T, D>::from_iterator(gradient.iter().copied()))
+}
+
+/// Computes the measure of a (possibly lower-dimensional) simplex.
+fn simplex_measure<T, D>(vertices: &[OPoint<T, D>]) -> T
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let k = vertices.len() - 1;
+    let edges = DMatrix::from_fn(D::dim(), k, |i, j| vertices[j + 1][i] - vertices[0][i]);
+    let scaled_measure = if k == D::dim() {
+        edges.determinant().abs()
+    } else {
+        (edges.transpose() * &edges)
+            .determinant()
+            .max(T::zero())
+            .sqrt()
+    };
+    let factorial = (1..=k).fold(T::one(), |f, i| f * T::from_usize(i).unwrap());
+    scaled_measure / factorial
+}
+
+/// Enumerates all exponents of polynomials in `dim` variables with total degree at most `degree`.
+fn total_degree_exponents(dim: usize, degree: usize) -> Vec<Vec<usize>> {
+    if dim == 0 {
+        return vec![Vec::new()];
+    }
+    let mut exponents = Vec::new();
+    for k in 0..=degree {
+        for mut tail in total_degree_exponents(dim - 1, degree - k) {
+            tail.insert(0, k);
+            exponents.push(tail);
+        }
+    }
+    exponents
+}
+
+/// Evaluates the Legendre polynomials of degree `0, ..., degree` at `x`.
+fn legendre_polynomials<T: Real>(x: T, degree: usize) -> Vec<T> {
+    let mut values = vec![T::one()];
+    if degree > 0 {
+        values.push(x);
+    }
+    for n in 1..degree {
+        let n_scalar = T::from_usize(n).unwrap();
+        let next =
+            ((n_scalar + n_scalar + T::one()) * x * values[n] - n_scalar * values[n - 1]) / (n_scalar + T::one());
+        values.push(next);
+    }
+    values
+}
+
+pub(super) fn convert_simplex<T, D>(vertices: Vec<OPoint<f64, D>>) -> Vec<OPoint<T, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    vertices
+        .into_iter()
+        .map(|v| OPoint::from(v.coords.map(|x| T::from_f64(x).unwrap())))
+        .collect()
+}
@@ -0,0 +1,237 @@
+//! Selection of quadrature rules by element type and polynomial strength.
+//!
+//! Generic code that works with meshes of several element types typically needs a quadrature
+//! rule of a given strength for each element type. [`QuadratureRegistry`] selects such a rule
+//! from the reference shape of the elements, which is provided by connectivities through the
+//! [`ElementReferenceShape`] trait:
+//!
+//! ```
+//! use fenris::connectivity::{Hex8Connectivity, Tet4Connectivity};
+//! use fenris::quadrature::registry::{BoxRuleFamily, QuadratureRegistry};
+//! # fn main() -> eyre::Result<()> {
+//! let registry = QuadratureRegistry::new();
+//! // A total-order rule for tetrahedra and a tensor-product Gauss rule for hexahedra
+//! let (tet_weights, _) = registry.rule::<f64, Tet4Connectivity>(3)?;
+//! let (hex_weights, _) = registry.rule::<f64, Hex8Connectivity>(3)?;
+//! assert_eq!(hex_weights.len(), 8);
+//!
+//! // Total-order rules for hexahedra require fewer points
+//! let registry = registry.with_box_family(BoxRuleFamily::TotalOrder);
+//! let (hex_weights, _) = registry.rule::<f64, Hex8Connectivity>(3)?;
+//! assert!(hex_weights.len() < 8);
+//! # let _ = tet_weights;
+//! # Ok(()) }
+//! ```
+use crate::assembly::local::UniformQuadratureTable;
+use crate::connectivity::*;
+use crate::element::ElementConnectivity;
+use crate::mesh::Mesh;
+use crate::quadrature::{QuadratureError, QuadraturePair};
+use crate::Real;
+use fenris_quadrature::{polyquad, tensor, univariate, SimplexRuleFamily};
+use fenris_traits::allocators::{BiDimAllocator, DimAllocator};
+use nalgebra::{DefaultAllocator, DimName, OPoint};
+
+/// The shape of the reference domain of an element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceShape {
+    /// The reference segment $[-1, 1]$.
+    Segment,
+    /// The reference triangle with vertices $(-1, -1)$, $(1, -1)$ and $(-1, 1)$.
+    Triangle,
+    /// The reference quadrilateral $[-1, 1]^2$.
+    Quadrilateral,
+    /// The reference tetrahedron with vertices $(-1, -1, -1)$, $(1, -1, -1)$, $(-1, 1, -1)$ and
+    /// $(-1, -1, 1)$.
+    Tetrahedron,
+    /// The reference hexahedron $[-1, 1]^3$.
+    Hexahedron,
+}
+
+impl ReferenceShape {
+    /// The dimension of the reference domain.
+    pub fn reference_dim(&self) -> usize {
+        match self {
+            Self::Segment => 1,
+            Self::Triangle | Self::Quadrilateral => 2,
+            Self::Tetrahedron | Self::Hexahedron => 3,
+        }
+    }
+
+    pub fn is_simplex(&self) -> bool {
+        matches!(self, Self::Segment | Self::Triangle | Self::Tetrahedron)
+    }
+}
+
+/// Connectivities whose elements have a fixed [reference shape](ReferenceShape).
+pub trait ElementReferenceShape<T: Real>: ElementConnectivity<T>
+where
+    DefaultAllocator: BiDimAllocator<T, Self::GeometryDim, Self::ReferenceDim>,
+{
+    const REFERENCE_SHAPE: ReferenceShape;
+}
+
+macro_rules! impl_reference_shape {
+    ($shape:ident: $($connectivity:ty),*) => {
+        $(
+            impl<T: Real> ElementReferenceShape<T> for $connectivity {
+                const REFERENCE_SHAPE: ReferenceShape = ReferenceShape::$shape;
+            }
+        )*
+    };
+}
+
+impl_reference_shape!(Segment: Segment2d1Connectivity, Segment2d2Connectivity, Segment3d2Connectivity);
+impl_reference_shape!(Triangle: Tri3d2Connectivity, Tri6d2Connectivity, Tri3d3Connectivity, Tri6d3Connectivity);
+impl_reference_shape!(Quadrilateral: Quad4d2Connectivity, Quad9d2Connectivity, Quad4d3Connectivity);
+impl_reference_shape!(Tetrahedron: Tet4Connectivity, Tet10Connectivity, Tet20Connectivity);
+impl_reference_shape!(Hexahedron: Hex8Connectivity, Hex20Connectivity, Hex27Connectivity);
+
+/// The family of rules used for quadrilaterals and hexahedra.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BoxRuleFamily {
+    /// Tensor products of Gauss rules, which are available for any strength.
+    #[default]
+    TensorGauss,
+    /// Tabulated total-order rules (see [`total_order`](crate::quadrature::total_order)), which
+    /// require fewer points than tensor-product rules, but are only available up to a certain
+    /// strength.
+    TotalOrder,
+}
+
+/// Selects quadrature rules of a given strength for elements of a given type.
+///
+/// The strength of a rule is the maximum total degree of polynomials that the rule integrates
+/// exactly on the reference domain. Segments are always integrated with Gauss rules, simplices
+/// with rules from the configured [`SimplexRuleFamily`] and quadrilaterals and hexahedra
+/// with rules from the configured [`BoxRuleFamily`]. In each case, the smallest rule of the
+/// family with sufficient strength is selected.
+///
+/// Rules can be obtained either for a [reference shape](Self::rule_for_shape), or for a
+/// connectivity type that implements [`ElementReferenceShape`]. The latter provides rules
+/// with the correct reference dimension for the elements, so that generic code does not need
+/// to distinguish between element types.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct QuadratureRegistry {
+    simplex_family: SimplexRuleFamily,
+    box_family: BoxRuleFamily,
+}
+
+impl QuadratureRegistry {
+    /// Creates a registry with the default rule families.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_simplex_family(self, simplex_family: SimplexRuleFamily) -> Self {
+        Self { simplex_family, ..self }
+    }
+
+    pub fn with_box_family(self, box_family: BoxRuleFamily) -> Self {
+        Self { box_family, ..self }
+    }
+
+    pub fn simplex_family(&self) -> SimplexRuleFamily {
+        self.simplex_family
+    }
+
+    pub fn box_family(&self) -> BoxRuleFamily {
+        self.box_family
+    }
+
+    /// Returns a rule with at least the given strength for the given reference shape.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the selected family has no rule with sufficient strength.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension `D` does not match the dimension of the reference shape.
+    pub fn rule_for_shape<T, D>(
+        &self,
+        shape: ReferenceShape,
+        strength: usize,
+    ) -> Result<QuadraturePair<T, D>, QuadratureError>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        assert_eq!(
+            D::dim(),
+            shape.reference_dim(),
+            "Dimension must match dimension of reference shape"
+        );
+        // Gauss rules with n points are exact for polynomials of degree 2n - 1
+        let num_gauss_points = strength / 2 + 1;
+        match (shape, self.box_family) {
+            (ReferenceShape::Segment, _) => Ok(convert_rule(univariate::gauss(num_gauss_points))),
+            (ReferenceShape::Triangle, _) => Ok(convert_rule(self.simplex_family.triangle(strength)?)),
+            (ReferenceShape::Tetrahedron, _) => Ok(convert_rule(self.simplex_family.tetrahedron(strength)?)),
+            (ReferenceShape::Quadrilateral, BoxRuleFamily::TensorGauss) => {
+                Ok(convert_rule(tensor::quadrilateral_gauss(num_gauss_points)))
+            }
+            (ReferenceShape::Quadrilateral, BoxRuleFamily::TotalOrder) => {
+                Ok(convert_rule(polyquad::quadrilateral(strength)?))
+            }
+            (ReferenceShape::Hexahedron, BoxRuleFamily::TensorGauss) => {
+                Ok(convert_rule(tensor::hexahedron_gauss(num_gauss_points)))
+            }
+            (ReferenceShape::Hexahedron, BoxRuleFamily::TotalOrder) => {
+                Ok(convert_rule(polyquad::hexahedron(strength)?))
+            }
+        }
+    }
+
+    /// Returns a rule with at least the given strength for elements with the given connectivity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the selected family has no rule with sufficient strength.
+    pub fn rule<T, C>(&self, strength: usize) -> Result<QuadraturePair<T, C::ReferenceDim>, QuadratureError>
+    where
+        T: Real,
+        C: ElementReferenceShape<T>,
+        DefaultAllocator: BiDimAllocator<T, C::GeometryDim, C::ReferenceDim>,
+    {
+        self.rule_for_shape(C::REFERENCE_SHAPE, strength)
+    }
+
+    /// Returns a quadrature table with a rule of at least the given strength for all elements
+    /// of the mesh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the selected family has no rule with sufficient strength.
+    pub fn table_for_mesh<T, D, C>(
+        &self,
+        _mesh: &Mesh<T, D, C>,
+        strength: usize,
+    ) -> Result<UniformQuadratureTable<T, C::ReferenceDim>, QuadratureError>
+    where
+        T: Real,
+        D: DimName,
+        C: ElementReferenceShape<T>,
+        DefaultAllocator: DimAllocator<T, D> + BiDimAllocator<T, C::GeometryDim, C::ReferenceDim>,
+    {
+        Ok(UniformQuadratureTable::from_quadrature(self.rule::<T, C>(strength)?))
+    }
+}
+
+fn convert_rule<T, D, const N: usize>((weights, points): fenris_quadrature::Rule<N>) -> QuadraturePair<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let weights = weights
+        .into_iter()
+        .map(|w| T::from_f64(w).unwrap())
+        .collect();
+    let points = points
+        .into_iter()
+        .map(|p| OPoint::from_slice(&p.map(|x| T::from_f64(x).unwrap())))
+        .collect();
+    (weights, points)
+}
@@ -14,3 +14,17 @@ pub fn hexahedron_gauss<T: Real>(num_points_per_dim: usize) -> QuadraturePair3d<
     let (weights, points) = tensor::hexahedron_gauss(num_points_per_dim);
     convert_quadrature_rule_from_3d_f64((weights, points))
 }
+
+/// A tensor-product Gauss-Lobatto rule for the reference quadrilateral.
+///
+/// See [`fenris_quadrature::tensor::try_quadrilateral_gauss_lobatto`] for more information.
+pub fn try_quadrilateral_gauss_lobatto<T: Real>(num_points_per_dim: usize) -> Option<QuadraturePair2d<T>> {
+    tensor::try_quadrilateral_gauss_lobatto(num_points_per_dim).map(convert_quadrature_rule_from_2d_f64)
+}
+
+/// A tensor-product Gauss-Lobatto rule for the reference hexahedron.
+///
+/// See [`fenris_quadrature::tensor::try_hexahedron_gauss_lobatto`] for more information.
+pub fn try_hexahedron_gauss_lobatto<T: Real>(num_points_per_dim: usize) -> Option<QuadraturePair3d<T>> {
+    tensor::try_hexahedron_gauss_lobatto(num_points_per_dim).map(convert_quadrature_rule_from_3d_f64)
+}
@@ -10,11 +10,25 @@ use crate::quadrature;
 use crate::quadrature::{QuadratureError, QuadraturePair2d, QuadraturePair3d};
 use crate::Real;
 
+pub use fenris_quadrature::SimplexRuleFamily;
+
 pub fn triangle<T: Real>(strength: usize) -> Result<QuadraturePair2d<T>, QuadratureError> {
     let (weights, points) = polyquad::triangle(strength)?;
     Ok(quadrature::convert_quadrature_rule_from_2d_f64((weights, points)))
 }
 
+/// Returns the smallest triangle rule of the given family with sufficient strength.
+///
+/// See [`SimplexRuleFamily`] for the available families. [`triangle`] corresponds to
+/// [`SimplexRuleFamily::Polyquad`].
+pub fn triangle_from_family<T: Real>(
+    family: SimplexRuleFamily,
+    strength: usize,
+) -> Result<QuadraturePair2d<T>, QuadratureError> {
+    let (weights, points) = family.triangle(strength)?;
+    Ok(quadrature::convert_quadrature_rule_from_2d_f64((weights, points)))
+}
+
 pub fn quadrilateral<T: Real>(strength: usize) -> Result<QuadraturePair2d<T>, QuadratureError> {
     let (weights, points) = polyquad::quadrilateral(strength)?;
     Ok(quadrature::convert_quadrature_rule_from_2d_f64((weights, points)))
@@ -25,6 +39,18 @@ pub fn tetrahedron<T: Real>(strength: usize) -> Result<QuadraturePair3d<T>, Quad
     Ok(quadrature::convert_quadrature_rule_from_3d_f64((weights, points)))
 }
 
+/// Returns the smallest tetrahedron rule of the given family with sufficient strength.
+///
+/// See [`SimplexRuleFamily`] for the available families. [`tetrahedron`] corresponds to
+/// [`SimplexRuleFamily::Polyquad`].
+pub fn tetrahedron_from_family<T: Real>(
+    family: SimplexRuleFamily,
+    strength: usize,
+) -> Result<QuadraturePair3d<T>, QuadratureError> {
+    let (weights, points) = family.tetrahedron(strength)?;
+    Ok(quadrature::convert_quadrature_rule_from_3d_f64((weights, points)))
+}
+
 pub fn hexahedron<T: Real>(strength: usize) -> Result<QuadraturePair3d<T>, QuadratureError> {
     let (weights, points) = polyquad::hexahedron(strength)?;
     Ok(quadrature::convert_quadrature_rule_from_3d_f64((weights, points)))
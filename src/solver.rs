@@ -0,0 +1,5 @@
+//! Iterative solvers and smoothers for the linear systems arising from finite element discretizations.
+pub mod chebyshev;
+pub mod multigrid;
+pub mod schwarz;
+pub mod smoothers;
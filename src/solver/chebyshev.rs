@@ -0,0 +1,261 @@
+//! Chebyshev polynomial smoothing and eigenvalue estimation.
+//!
+//! The Chebyshev smoother applies a polynomial in the Jacobi-preconditioned operator
+//! $\vec D^{-1} \vec A$ to the residual, where $\vec D$ is the diagonal of $\vec A$. The polynomial
+//! is chosen to minimize the error components associated with eigenvalues in an interval
+//! $[\lambda_{\max} / r, \lambda_{\max}]$, where $r$ is the *smoothing range*. Since the smoother
+//! only requires the action of the operator and its diagonal, it is well suited for matrix-free
+//! multigrid methods, and unlike Gauss-Seidel-type smoothers it is trivially parallel.
+//!
+//! The largest eigenvalue $\lambda_{\max}$ of $\vec D^{-1} \vec A$ is usually not known, and is
+//! estimated with [`estimate_largest_eigenvalue`].
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut};
+use crate::solver::smoothers::Smoother;
+use crate::Real;
+use eyre::eyre;
+use fenris_sparse::cg::LinearOperator;
+use nalgebra_sparse::CsrMatrix;
+
+/// Estimates the largest eigenvalue of the Jacobi-preconditioned operator $\vec D^{-1} \vec A$
+/// by power iteration.
+///
+/// Here `inverse_diagonal` holds the entries of $\vec D^{-1}$. The iteration starts from a
+/// fixed pseudo-random vector, so that the result is deterministic. For symmetric positive
+/// definite $\vec A$, the estimate converges to $\lambda_{\max}$ from below, so a safety
+/// factor should be applied when the estimate is used as an upper bound.
+///
+/// # Errors
+///
+/// Returns an error if `num_iterations` is zero or if the operator returns an error.
+pub fn estimate_largest_eigenvalue<T: Real>(
+    operator: &impl LinearOperator<T>,
+    inverse_diagonal: DVectorView<T>,
+    num_iterations: usize,
+) -> eyre::Result<T> {
+    if num_iterations == 0 {
+        return Err(eyre!("Power iteration requires at least one iteration"));
+    }
+    let n = inverse_diagonal.len();
+    let mut x = DVector::from_fn(n, |i, _| T::from_f64((12.9898 * (i as f64 + 1.0)).sin()).unwrap());
+    let mut y = DVector::zeros(n);
+    let mut estimate = T::zero();
+    for _ in 0..num_iterations {
+        let x_norm = x.norm();
+        if x_norm == T::zero() {
+            // x lies in the null space of the operator
+            return Ok(T::zero());
+        }
+        x /= x_norm;
+        apply_operator(operator, &mut y, &x)?;
+        y.component_mul_assign(&inverse_diagonal);
+        estimate = y.norm();
+        std::mem::swap(&mut x, &mut y);
+    }
+    Ok(estimate)
+}
+
+/// Extracts the inverse of the diagonal of a square matrix.
+///
+/// # Errors
+///
+/// Returns an error if the matrix is not square or if any diagonal entry is zero or missing.
+pub fn inverse_diagonal<T: Real>(matrix: &CsrMatrix<T>) -> eyre::Result<DVector<T>> {
+    if matrix.nrows() != matrix.ncols() {
+        return Err(eyre!("Matrix must be square"));
+    }
+    let mut inverse_diagonal = DVector::zeros(matrix.nrows());
+    for (i, d_inv) in inverse_diagonal.iter_mut().enumerate() {
+        let row = matrix.row(i);
+        let d = row
+            .get_entry(i)
+            .map(|entry| entry.into_value())
+            .unwrap_or_else(T::zero);
+        if d == T::zero() {
+            return Err(eyre!("Diagonal entry in row {i} is zero"));
+        }
+        *d_inv = T::one() / d;
+    }
+    Ok(inverse_diagonal)
+}
+
+fn apply_operator<T: Real>(operator: &impl LinearOperator<T>, y: &mut DVector<T>, x: &DVector<T>) -> eyre::Result<()> {
+    operator
+        .apply(y.as_view_mut(), x.as_view())
+        .map_err(|err| eyre!("Failed to apply operator: {err}"))
+}
+
+/// A Chebyshev polynomial smoother with Jacobi preconditioning.
+///
+/// Given bounds $[\lambda_{\min}, \lambda_{\max}]$ with $\lambda_{\min} = \lambda_{\max} / r$
+/// for the smoothing range $r$, let $\theta = (\lambda_{\max} + \lambda_{\min}) / 2$ and
+/// $\delta = (\lambda_{\max} - \lambda_{\min}) / 2$. A single sweep of degree $k$ performs the
+/// iteration
+/// <div>$$
+/// \begin{aligned}
+/// \vec d_0 &= \theta^{-1} \vec D^{-1} (\vec b - \vec A \vec x_0), \\\\
+/// \vec d_j &= \rho_j \rho_{j - 1} \vec d_{j - 1}
+///     + 2 \rho_j \delta^{-1} \vec D^{-1} (\vec b - \vec A \vec x_j), \\\\
+/// \vec x_{j + 1} &= \vec x_j + \vec d_j,
+/// \end{aligned}
+/// $$</div>
+/// for $j = 0, \dots, k - 1$, where $\rho_0 = \delta / \theta$ and
+/// $\rho_j = (2 \theta / \delta - \rho_{j - 1})^{-1}$. The error is thereby multiplied by the
+/// scaled and shifted Chebyshev polynomial of degree $k$ in $\vec D^{-1} \vec A$ that has minimal
+/// maximum magnitude on $[\lambda_{\min}, \lambda_{\max}]$.
+///
+/// By default, $\lambda_{\max}$ is estimated with [`estimate_largest_eigenvalue`] and
+/// multiplied by a safety factor of $1.1$, the degree is $2$ and the smoothing range is $20$.
+/// For a multigrid method with coarsening factor $2$ in each direction, the smoothing range
+/// should be chosen such that $\lambda_{\min}$ lies below the eigenvalues of the modes that
+/// are not resolved on the coarse grid.
+#[derive(Debug, Clone)]
+pub struct ChebyshevSmoother<T: Real> {
+    inverse_diagonal: DVector<T>,
+    max_eigenvalue: T,
+    smoothing_range: T,
+    degree: usize,
+}
+
+impl<T: Real> ChebyshevSmoother<T> {
+    /// The number of power iterations used to estimate the largest eigenvalue.
+    pub const DEFAULT_POWER_ITERATIONS: usize = 20;
+
+    /// Constructs a Chebyshev smoother for the given matrix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square or if any diagonal entry is zero or missing.
+    pub fn from_matrix(matrix: &CsrMatrix<T>) -> eyre::Result<Self> {
+        Self::from_operator_and_inverse_diagonal(matrix, inverse_diagonal(matrix)?)
+    }
+
+    /// Constructs a Chebyshev smoother for a (possibly matrix-free) operator with the given
+    /// inverse diagonal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operator returns an error during eigenvalue estimation.
+    pub fn from_operator_and_inverse_diagonal(
+        operator: &impl LinearOperator<T>,
+        inverse_diagonal: DVector<T>,
+    ) -> eyre::Result<Self> {
+        let estimate =
+            estimate_largest_eigenvalue(operator, inverse_diagonal.as_view(), Self::DEFAULT_POWER_ITERATIONS)?;
+        Ok(Self {
+            inverse_diagonal,
+            max_eigenvalue: T::from_f64(1.1).unwrap() * estimate,
+            smoothing_range: T::from_f64(20.0).unwrap(),
+            degree: 2,
+        })
+    }
+
+    /// Replaces the upper bound $\lambda_{\max}$ for the eigenvalues of $\vec D^{-1} \vec A$.
+    pub fn with_max_eigenvalue(self, max_eigenvalue: T) -> Self {
+        Self { max_eigenvalue, ..self }
+    }
+
+    /// Sets the smoothing range $r$, so that $\lambda_{\min} = \lambda_{\max} / r$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the smoothing range is not larger than one.
+    pub fn with_smoothing_range(self, smoothing_range: T) -> Self {
+        assert!(smoothing_range > T::one(), "Smoothing range must be larger than one");
+        Self {
+            smoothing_range,
+            ..self
+        }
+    }
+
+    /// Sets the degree of the Chebyshev polynomial, which is the number of operator
+    /// applications per sweep.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the degree is zero.
+    pub fn with_degree(self, degree: usize) -> Self {
+        assert!(degree > 0, "Degree must be positive");
+        Self { degree, ..self }
+    }
+
+    pub fn max_eigenvalue(&self) -> T {
+        self.max_eigenvalue
+    }
+
+    pub fn min_eigenvalue(&self) -> T {
+        self.max_eigenvalue / self.smoothing_range
+    }
+
+    pub fn smoothing_range(&self) -> T {
+        self.smoothing_range
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn inverse_diagonal(&self) -> &DVector<T> {
+        &self.inverse_diagonal
+    }
+
+    /// Applies a single smoothing sweep to `x` for the given (possibly matrix-free) operator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operator returns an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of `x` or `b` do not match the smoother.
+    pub fn smooth_with_operator(
+        &self,
+        operator: &impl LinearOperator<T>,
+        mut x: DVectorViewMut<T>,
+        b: DVectorView<T>,
+    ) -> eyre::Result<()> {
+        let n = self.inverse_diagonal.len();
+        assert_eq!(x.len(), n, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), n, "Dimension of b must match the smoother");
+
+        let two = T::from_f64(2.0).unwrap();
+        let lambda_max = self.max_eigenvalue();
+        let lambda_min = self.min_eigenvalue();
+        let theta = (lambda_max + lambda_min) / two;
+        let delta = (lambda_max - lambda_min) / two;
+
+        let mut x_current = x.clone_owned();
+        let mut r = DVector::zeros(n);
+        let mut d = DVector::zeros(n);
+        let mut rho_prev = delta / theta;
+        for j in 0..self.degree {
+            // r = D^{-1} (b - A x)
+            apply_operator(operator, &mut r, &x_current)?;
+            r.zip_zip_apply(&b, &self.inverse_diagonal, |r_i, b_i, d_inv_i| {
+                *r_i = d_inv_i * (b_i - *r_i)
+            });
+            if j == 0 {
+                d.copy_from(&r);
+                d /= theta;
+            } else {
+                let rho = T::one() / (two * theta / delta - rho_prev);
+                d.axpy(two * rho / delta, &r, rho * rho_prev);
+                rho_prev = rho;
+            }
+            x_current += &d;
+        }
+        x.copy_from(&x_current);
+        Ok(())
+    }
+}
+
+impl<T: Real> Smoother<T> for ChebyshevSmoother<T> {
+    fn smooth(&self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>) {
+        assert_eq!(
+            matrix.nrows(),
+            self.inverse_diagonal.len(),
+            "Matrix dimensions must match the smoother"
+        );
+        self.smooth_with_operator(matrix, x, b)
+            .expect("Sparse matrix-vector products cannot fail")
+    }
+}
@@ -0,0 +1,288 @@
+//! Geometric multigrid methods.
+//!
+//! Given a hierarchy of nested discretizations with prolongation operators $\vec P_l$ from level
+//! $l$ to level $l + 1$, the matrices on the coarser levels are obtained by the Galerkin product
+//! $\vec A_l = \vec P_l^T \vec A_{l + 1} \vec P_l$, starting from the matrix $\vec A_L$ on the
+//! finest level $L$. A V-cycle for $\vec A_l \vec x_l = \vec b_l$ then consists of
+//!
+//! 1. $\nu_1$ sweeps of a smoother applied to $\vec x_l$,
+//! 2. restriction of the residual $\vec b_{l - 1} = \vec P_{l - 1}^T (\vec b_l - \vec A_l \vec x_l)$,
+//! 3. the recursive solution of $\vec A_{l - 1} \vec e_{l - 1} = \vec b_{l - 1}$ with a single
+//!    V-cycle from a zero initial guess, or with a direct solver on the coarsest level,
+//! 4. the coarse grid correction $\vec x_l \leftarrow \vec x_l + \vec P_{l - 1} \vec e_{l - 1}$,
+//! 5. $\nu_2$ sweeps of the adjoint smoother applied to $\vec x_l$.
+//!
+//! For symmetric positive definite systems arising from elliptic problems, the convergence rate of
+//! the V-cycle is typically independent of the mesh size. Nested meshes and the associated
+//! prolongations are conveniently obtained with
+//! [`UniformRefinementHierarchy`].
+use crate::allocators::DimAllocator;
+use crate::connectivity::Connectivity;
+use crate::mesh::refinement::UniformRefinementHierarchy;
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName};
+use crate::solver::smoothers::Smoother;
+use crate::Real;
+use eyre::eyre;
+use fenris_sparse::cg::LinearOperator;
+use nalgebra_sparse::factorization::CscCholesky;
+use nalgebra_sparse::{CscMatrix, CsrMatrix};
+use std::error::Error;
+
+#[derive(Debug, Clone)]
+struct Level<T: Real, S> {
+    matrix: CsrMatrix<T>,
+    smoother: S,
+    /// The prolongation from the next coarser level.
+    prolongation: CsrMatrix<T>,
+    restriction: CsrMatrix<T>,
+}
+
+/// A geometric multigrid method based on V-cycles with Galerkin coarse grid operators.
+///
+/// The coarsest level is solved with a sparse Cholesky factorization, and the matrix must
+/// therefore be symmetric positive definite, as is the case e.g. after imposing Dirichlet
+/// boundary conditions with
+/// [`apply_homogeneous_dirichlet_bc_csr`](crate::assembly::global::apply_homogeneous_dirichlet_bc_csr).
+/// Any [`Smoother`] can be used on the finer levels, for example a damped
+/// [`JacobiSmoother`](crate::solver::smoothers::JacobiSmoother), a
+/// [`GaussSeidelSmoother`](crate::solver::smoothers::GaussSeidelSmoother) or a
+/// [`ChebyshevSmoother`](crate::solver::chebyshev::ChebyshevSmoother).
+///
+/// The method can be used as a standalone iterative solver with [`solve`](Self::solve). It also
+/// implements [`LinearOperator`] by applying a single V-cycle with a zero initial guess, so that
+/// it can be used as a preconditioner for Krylov methods, including as a
+/// `&dyn LinearOperator<T>` trait object. Since post-smoothing uses the
+/// [adjoint smoother](Smoother::smooth_adjoint), the preconditioner is symmetric if the numbers
+/// of pre- and post-smoothing steps are equal, which makes it suitable for the conjugate
+/// gradient method.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct GeometricMultigrid<T: Real, S> {
+    coarse_matrix: CsrMatrix<T>,
+    coarse_factorization: CscCholesky<T>,
+    /// The levels above the coarsest level, ordered from coarse to fine.
+    levels: Vec<Level<T, S>>,
+    pre_smoothing_steps: usize,
+    post_smoothing_steps: usize,
+}
+
+impl<T: Real, S: Smoother<T>> GeometricMultigrid<T, S> {
+    /// Constructs a multigrid method for the given matrix on the finest level from the
+    /// prolongations between consecutive levels.
+    ///
+    /// The prolongations are ordered from coarse to fine, so that the last prolongation maps to
+    /// the finest level, and the method has one more level than there are prolongations.
+    /// `create_smoother` is called with the matrix of each level except the coarsest. By default,
+    /// a single pre- and post-smoothing step is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square, if the dimensions of the prolongations are
+    /// incompatible, if a smoother cannot be created, or if the factorization of the coarse
+    /// matrix fails.
+    pub fn from_prolongations(
+        matrix: CsrMatrix<T>,
+        prolongations: Vec<CsrMatrix<T>>,
+        mut create_smoother: impl FnMut(&CsrMatrix<T>) -> eyre::Result<S>,
+    ) -> eyre::Result<Self> {
+        if matrix.nrows() != matrix.ncols() {
+            return Err(eyre!("Matrix must be square"));
+        }
+        let mut levels = Vec::with_capacity(prolongations.len());
+        let mut matrix = matrix;
+        for (index, prolongation) in prolongations.into_iter().enumerate().rev() {
+            let level = index + 1;
+            if prolongation.nrows() != matrix.nrows() {
+                return Err(eyre!(
+                    "Prolongation to level {level} has {} rows, but the matrix on level {level} has {} rows",
+                    prolongation.nrows(),
+                    matrix.nrows()
+                ));
+            }
+            let restriction = prolongation.transpose();
+            let coarse_matrix = &restriction * &(&matrix * &prolongation);
+            let smoother = create_smoother(&matrix)
+                .map_err(|err| err.wrap_err(format!("Failed to create smoother for level {level}")))?;
+            levels.push(Level {
+                matrix,
+                smoother,
+                prolongation,
+                restriction,
+            });
+            matrix = coarse_matrix;
+        }
+        levels.reverse();
+
+        let coarse_factorization = CscCholesky::factor(&CscMatrix::from(&matrix))
+            .map_err(|err| eyre!("Failed to factorize coarse matrix: {err:?}"))?;
+        Ok(Self {
+            coarse_matrix: matrix,
+            coarse_factorization,
+            levels,
+            pre_smoothing_steps: 1,
+            post_smoothing_steps: 1,
+        })
+    }
+
+    /// Constructs a multigrid method from a hierarchy of uniformly refined meshes.
+    ///
+    /// The matrix must be assembled on the finest mesh of the hierarchy, with `solution_dim`
+    /// degrees of freedom per vertex in the usual interleaved format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as
+    /// [`from_prolongations`](Self::from_prolongations).
+    pub fn from_hierarchy<D, C>(
+        matrix: CsrMatrix<T>,
+        hierarchy: &UniformRefinementHierarchy<T, D, C>,
+        solution_dim: usize,
+        create_smoother: impl FnMut(&CsrMatrix<T>) -> eyre::Result<S>,
+    ) -> eyre::Result<Self>
+    where
+        D: DimName,
+        C: Connectivity,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let prolongations = (0..hierarchy.num_levels() - 1)
+            .map(|level| hierarchy.prolongation(level, solution_dim))
+            .collect();
+        Self::from_prolongations(matrix, prolongations, create_smoother)
+    }
+
+    /// Sets the number of pre- and post-smoothing steps on each level.
+    pub fn with_smoothing_steps(self, pre_smoothing_steps: usize, post_smoothing_steps: usize) -> Self {
+        Self {
+            pre_smoothing_steps,
+            post_smoothing_steps,
+            ..self
+        }
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.levels.len() + 1
+    }
+
+    /// The number of degrees of freedom on the finest level.
+    pub fn num_dofs(&self) -> usize {
+        self.level_matrix(self.num_levels() - 1).nrows()
+    }
+
+    /// The matrix on the given level, where level `0` is the coarsest level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level is out of bounds.
+    pub fn level_matrix(&self, level: usize) -> &CsrMatrix<T> {
+        if level == 0 {
+            &self.coarse_matrix
+        } else {
+            &self.levels[level - 1].matrix
+        }
+    }
+
+    /// Applies a single V-cycle to the approximate solution `x` of the system on the finest level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of `x` or `b` do not match the number of degrees of freedom.
+    pub fn v_cycle(&self, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        assert_eq!(
+            x.len(),
+            self.num_dofs(),
+            "Dimension of x must match the multigrid method"
+        );
+        assert_eq!(
+            b.len(),
+            self.num_dofs(),
+            "Dimension of b must match the multigrid method"
+        );
+        let mut x_owned = x.clone_owned();
+        self.cycle(self.num_levels() - 1, &mut x_owned, &b.clone_owned());
+        x.copy_from(&x_owned);
+    }
+
+    /// Iteratively solves the system on the finest level with V-cycles, using the given `x` as
+    /// initial guess.
+    ///
+    /// Iteration stops once the residual satisfies
+    /// $\\| \vec b - \vec A \vec x \\| \leq \text{tol} \\, \\| \vec b \\|$. Returns the number of
+    /// V-cycles performed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tolerance is not reached within `max_cycles` V-cycles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of `x` or `b` do not match the number of degrees of freedom.
+    pub fn solve(
+        &self,
+        mut x: DVectorViewMut<T>,
+        b: DVectorView<T>,
+        tolerance: T,
+        max_cycles: usize,
+    ) -> eyre::Result<usize> {
+        assert_eq!(
+            x.len(),
+            self.num_dofs(),
+            "Dimension of x must match the multigrid method"
+        );
+        assert_eq!(
+            b.len(),
+            self.num_dofs(),
+            "Dimension of b must match the multigrid method"
+        );
+        let matrix = self.level_matrix(self.num_levels() - 1);
+        let b = b.clone_owned();
+        let b_norm = b.norm();
+        let mut x_owned = x.clone_owned();
+        let mut residual_norm = (&b - matrix * &x_owned).norm();
+        let mut num_cycles = 0;
+        while residual_norm > tolerance * b_norm {
+            if num_cycles == max_cycles {
+                return Err(eyre!(
+                    "Multigrid did not converge within {max_cycles} cycles (relative residual {})",
+                    residual_norm / b_norm
+                ));
+            }
+            self.cycle(self.num_levels() - 1, &mut x_owned, &b);
+            residual_norm = (&b - matrix * &x_owned).norm();
+            num_cycles += 1;
+        }
+        x.copy_from(&x_owned);
+        Ok(num_cycles)
+    }
+
+    fn cycle(&self, level: usize, x: &mut DVector<T>, b: &DVector<T>) {
+        if level == 0 {
+            x.copy_from(&self.coarse_factorization.solve(b));
+            return;
+        }
+        let Level {
+            matrix,
+            smoother,
+            prolongation,
+            restriction,
+        } = &self.levels[level - 1];
+        for _ in 0..self.pre_smoothing_steps {
+            smoother.smooth(matrix, x.as_view_mut(), b.as_view());
+        }
+        let coarse_b = restriction * (b - matrix * &*x);
+        let mut coarse_x = DVector::zeros(coarse_b.len());
+        self.cycle(level - 1, &mut coarse_x, &coarse_b);
+        *x += prolongation * coarse_x;
+        for _ in 0..self.post_smoothing_steps {
+            smoother.smooth_adjoint(matrix, x.as_view_mut(), b.as_view());
+        }
+    }
+}
+
+impl<T: Real, S: Smoother<T>> LinearOperator<T> for GeometricMultigrid<T, S> {
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        y.fill(T::zero());
+        self.v_cycle(y, x);
+        Ok(())
+    }
+}
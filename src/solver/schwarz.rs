@@ -0,0 +1,330 @@
+//! Overlapping additive Schwarz preconditioners.
+//!
+//! Given a decomposition of the degrees of freedom into overlapping subdomains
+//! $I_1, \dots, I_m$ with restriction operators $\vec R_i$ and local matrices
+//! $\vec A_i = \vec R_i \vec A \vec R_i^T$, the one-level additive Schwarz preconditioner is
+//! <div>$$
+//! \vec M_1^{-1} = \sum_{i = 1}^m \vec R_i^T \vec A_i^{-1} \vec R_i.
+//! $$</div>
+//! The local problems are independent and are solved in parallel. Since information only
+//! propagates between neighboring subdomains in each application, the number of iterations
+//! of a Krylov method preconditioned by $\vec M_1^{-1}$ grows with the number of subdomains.
+//! The two-level preconditioner
+//! <div>$$
+//! \vec M_2^{-1} = \vec P_0 \vec A_0^{-1} \vec P_0^T + \sum_{i = 1}^m \vec R_i^T \vec A_i^{-1} \vec R_i,
+//! \qquad \vec A_0 = \vec P_0^T \vec A \vec P_0,
+//! $$</div>
+//! adds a global coarse correction, which for a suitable coarse space $\vec P_0$ makes the
+//! iteration count largely independent of the number of subdomains.
+//!
+//! Subdomains are typically obtained by partitioning the elements of a mesh, e.g. with
+//! [`partition_elements_by_coordinate_bisection`](crate::mesh::partition::partition_elements_by_coordinate_bisection),
+//! and extending each part by a number of layers of neighboring elements with
+//! [`overlapping_subdomain_elements`].
+use crate::assembly::patch::{extract_dense_submatrix, PatchTopology};
+use crate::nalgebra::{Cholesky, DVector, DVectorView, DVectorViewMut, Dyn};
+use crate::Real;
+use eyre::eyre;
+use fenris_sparse::cg::LinearOperator;
+use nalgebra_sparse::factorization::CscCholesky;
+use nalgebra_sparse::{CooMatrix, CscMatrix, CsrMatrix};
+use rayon::prelude::*;
+use std::error::Error;
+
+/// Extends each part of an element partition by the given number of layers of neighboring
+/// elements.
+///
+/// `element_partition` holds the part index of each element. Each layer of overlap adds all
+/// elements that share a node with an element of the subdomain. Returns the sorted element
+/// indices of each subdomain. Parts without elements give empty subdomains.
+///
+/// # Errors
+///
+/// Returns an error if the length of the partition does not match the number of elements
+/// in the topology.
+pub fn overlapping_subdomain_elements(
+    topology: &PatchTopology,
+    element_partition: &[usize],
+    overlap: usize,
+) -> eyre::Result<Vec<Vec<usize>>> {
+    if element_partition.len() != topology.num_elements() {
+        return Err(eyre!(
+            "Partition has {} entries, but topology has {} elements",
+            element_partition.len(),
+            topology.num_elements()
+        ));
+    }
+    let num_parts = element_partition.iter().max().map_or(0, |&part| part + 1);
+    let mut subdomains = vec![Vec::new(); num_parts];
+    for (element, &part) in element_partition.iter().enumerate() {
+        subdomains[part].push(element);
+    }
+
+    let mut in_subdomain = vec![false; topology.num_elements()];
+    for elements in &mut subdomains {
+        for &element in elements.iter() {
+            in_subdomain[element] = true;
+        }
+        for _ in 0..overlap {
+            let mut layer = Vec::new();
+            for &element in elements.iter() {
+                for &node in topology.element_nodes(element) {
+                    for &neighbor in topology.node_elements(node) {
+                        if !in_subdomain[neighbor] {
+                            in_subdomain[neighbor] = true;
+                            layer.push(neighbor);
+                        }
+                    }
+                }
+            }
+            elements.extend(layer);
+        }
+        elements.sort_unstable();
+        for &element in elements.iter() {
+            in_subdomain[element] = false;
+        }
+    }
+    Ok(subdomains)
+}
+
+/// Extracts the sparse submatrix associated with the given (unique) degrees of freedom.
+fn extract_sparse_submatrix<T: Real>(matrix: &CsrMatrix<T>, dofs: &[usize]) -> CscMatrix<T> {
+    let mut global_to_local = vec![usize::MAX; matrix.ncols()];
+    for (local, &global) in dofs.iter().enumerate() {
+        global_to_local[global] = local;
+    }
+    let mut coo = CooMatrix::new(dofs.len(), dofs.len());
+    for (local_row, &global_row) in dofs.iter().enumerate() {
+        let row = matrix.row(global_row);
+        for (&global_col, &value) in row.col_indices().iter().zip(row.values()) {
+            let local_col = global_to_local[global_col];
+            if local_col != usize::MAX {
+                coo.push(local_row, local_col, value);
+            }
+        }
+    }
+    CscMatrix::from(&coo)
+}
+
+#[derive(Debug, Clone)]
+struct Subdomain<T: Real> {
+    dofs: Vec<usize>,
+    factorization: CscCholesky<T>,
+}
+
+#[derive(Debug, Clone)]
+struct CoarseSpace<T: Real> {
+    prolongation: CsrMatrix<T>,
+    restriction: CsrMatrix<T>,
+    factorization: Cholesky<T, Dyn>,
+}
+
+/// An overlapping additive Schwarz preconditioner with an optional coarse space.
+///
+/// The local subdomain matrices are factorized with a sparse Cholesky factorization, and the
+/// coarse matrix with a dense Cholesky factorization. The matrix must therefore be symmetric
+/// positive definite, as is the case e.g. after imposing Dirichlet boundary conditions with
+/// [`DirichletConditions`](crate::assembly::bc::DirichletConditions). The preconditioner is
+/// itself symmetric positive definite and implements [`LinearOperator`], so that it can
+/// be used with the conjugate gradient method.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct AdditiveSchwarzPreconditioner<T: Real> {
+    num_dofs: usize,
+    solution_dim: usize,
+    subdomains: Vec<Subdomain<T>>,
+    coarse: Option<CoarseSpace<T>>,
+}
+
+impl<T: Real> AdditiveSchwarzPreconditioner<T> {
+    /// Constructs a one-level preconditioner from the given subdomain degrees of freedom.
+    ///
+    /// Empty subdomains are ignored. The solution dimension is only used by
+    /// [`with_nicolaides_coarse_space`](Self::with_nicolaides_coarse_space).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square, if a subdomain contains degrees of
+    /// freedom that are out of bounds or duplicated, or if the factorization of a local
+    /// matrix fails.
+    pub fn from_subdomain_dofs(
+        matrix: &CsrMatrix<T>,
+        subdomain_dofs: Vec<Vec<usize>>,
+        solution_dim: usize,
+    ) -> eyre::Result<Self> {
+        if matrix.nrows() != matrix.ncols() {
+            return Err(eyre!("Matrix must be square"));
+        }
+        let num_dofs = matrix.nrows();
+        let subdomains = subdomain_dofs
+            .into_par_iter()
+            .enumerate()
+            .filter(|(_, dofs)| !dofs.is_empty())
+            .map(|(index, dofs)| {
+                if let Some(&dof) = dofs.iter().find(|&&dof| dof >= num_dofs) {
+                    return Err(eyre!(
+                        "Degree of freedom {dof} in subdomain {index} is out of bounds for {num_dofs} degrees of freedom"
+                    ));
+                }
+                let mut sorted = dofs.clone();
+                sorted.sort_unstable();
+                sorted.dedup();
+                if sorted.len() != dofs.len() {
+                    return Err(eyre!("Subdomain {index} contains duplicate degrees of freedom"));
+                }
+                let local_matrix = extract_sparse_submatrix(matrix, &dofs);
+                let factorization = CscCholesky::factor(&local_matrix)
+                    .map_err(|err| eyre!("Failed to factorize local matrix of subdomain {index}: {err:?}"))?;
+                Ok(Subdomain { dofs, factorization })
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self {
+            num_dofs,
+            solution_dim,
+            subdomains,
+            coarse: None,
+        })
+    }
+
+    /// Constructs a one-level preconditioner from an element partition.
+    ///
+    /// Each part is extended by `overlap` layers of elements (see
+    /// [`overlapping_subdomain_elements`]), and each subdomain comprises the degrees of freedom
+    /// of all nodes of its elements. The degrees of freedom of the nodes on the boundary of a
+    /// subdomain are therefore shared with neighboring subdomains even without overlap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the partition does not match the topology, if the matrix dimensions
+    /// do not match the number of degrees of freedom of the topology, or under the same
+    /// conditions as [`from_subdomain_dofs`](Self::from_subdomain_dofs).
+    pub fn from_element_partition(
+        matrix: &CsrMatrix<T>,
+        topology: &PatchTopology,
+        element_partition: &[usize],
+        solution_dim: usize,
+        overlap: usize,
+    ) -> eyre::Result<Self> {
+        if matrix.nrows() != solution_dim * topology.num_nodes() {
+            return Err(eyre!(
+                "Matrix has {} rows, but topology has {} degrees of freedom",
+                matrix.nrows(),
+                solution_dim * topology.num_nodes()
+            ));
+        }
+        let subdomain_dofs = overlapping_subdomain_elements(topology, element_partition, overlap)?
+            .into_iter()
+            .map(|elements| topology.patch(elements).global_dofs(solution_dim))
+            .collect();
+        Self::from_subdomain_dofs(matrix, subdomain_dofs, solution_dim)
+    }
+
+    /// Adds a coarse space with the given prolongation $\vec P_0$.
+    ///
+    /// The prolongation could e.g. be the interpolation from a coarse mesh, as obtained from
+    /// [`assemble_nested_prolongation`](crate::error::assemble_nested_prolongation).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dimensions of the prolongation are incompatible with the matrix,
+    /// or if the coarse matrix $\vec P_0^T \vec A \vec P_0$ is not positive definite.
+    pub fn with_coarse_space(self, matrix: &CsrMatrix<T>, prolongation: CsrMatrix<T>) -> eyre::Result<Self> {
+        if matrix.nrows() != self.num_dofs || matrix.ncols() != self.num_dofs {
+            return Err(eyre!("Matrix dimensions do not match the preconditioner"));
+        }
+        if prolongation.nrows() != self.num_dofs {
+            return Err(eyre!(
+                "Prolongation has {} rows, but the system has {} degrees of freedom",
+                prolongation.nrows(),
+                self.num_dofs
+            ));
+        }
+        let restriction = prolongation.transpose();
+        let coarse_matrix = &restriction * &(matrix * &prolongation);
+        let factorization = extract_dense_submatrix(&coarse_matrix, &(0..coarse_matrix.nrows()).collect::<Vec<_>>())
+            .cholesky()
+            .ok_or_else(|| eyre!("Coarse matrix is not positive definite"))?;
+        Ok(Self {
+            coarse: Some(CoarseSpace {
+                prolongation,
+                restriction,
+                factorization,
+            }),
+            ..self
+        })
+    }
+
+    /// Adds the Nicolaides coarse space, which consists of one basis function per subdomain
+    /// and solution component.
+    ///
+    /// Each basis function is the restriction of a constant (per component) to the subdomain,
+    /// weighted by a partition of unity so that the basis functions sum to the constant.
+    /// The coarse space therefore represents the rigid translations exactly, which are the
+    /// low-energy modes responsible for the slow convergence of one-level methods for
+    /// problems like diffusion and elasticity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`with_coarse_space`](Self::with_coarse_space).
+    pub fn with_nicolaides_coarse_space(self, matrix: &CsrMatrix<T>) -> eyre::Result<Self> {
+        let s = self.solution_dim;
+        let mut multiplicity = vec![0usize; self.num_dofs];
+        for subdomain in &self.subdomains {
+            for &dof in &subdomain.dofs {
+                multiplicity[dof] += 1;
+            }
+        }
+        let mut coo = CooMatrix::new(self.num_dofs, s * self.subdomains.len());
+        for (index, subdomain) in self.subdomains.iter().enumerate() {
+            for &dof in &subdomain.dofs {
+                let weight = T::one() / T::from_usize(multiplicity[dof]).unwrap();
+                coo.push(dof, s * index + dof % s, weight);
+            }
+        }
+        self.with_coarse_space(matrix, CsrMatrix::from(&coo))
+    }
+
+    pub fn num_subdomains(&self) -> usize {
+        self.subdomains.len()
+    }
+
+    /// The degrees of freedom of the given subdomain.
+    pub fn subdomain_dofs(&self, subdomain_index: usize) -> &[usize] {
+        &self.subdomains[subdomain_index].dofs
+    }
+
+    pub fn has_coarse_space(&self) -> bool {
+        self.coarse.is_some()
+    }
+}
+
+impl<T: Real> LinearOperator<T> for AdditiveSchwarzPreconditioner<T> {
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        assert_eq!(y.len(), self.num_dofs, "Dimension of y must match the preconditioner");
+        assert_eq!(x.len(), self.num_dofs, "Dimension of x must match the preconditioner");
+
+        let local_corrections: Vec<_> = self
+            .subdomains
+            .par_iter()
+            .map(|subdomain| {
+                let r_local = DVector::from_iterator(subdomain.dofs.len(), subdomain.dofs.iter().map(|&dof| x[dof]));
+                subdomain.factorization.solve(&r_local)
+            })
+            .collect();
+
+        y.fill(T::zero());
+        for (subdomain, correction) in self.subdomains.iter().zip(&local_corrections) {
+            for (&dof, &c) in subdomain.dofs.iter().zip(correction.iter()) {
+                y[dof] += c;
+            }
+        }
+
+        if let Some(coarse) = &self.coarse {
+            let r_coarse = &coarse.restriction * x;
+            let correction = coarse.factorization.solve(&r_coarse);
+            y += &coarse.prolongation * correction;
+        }
+        Ok(())
+    }
+}
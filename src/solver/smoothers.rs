@@ -0,0 +1,472 @@
+//! Smoothers for the linear systems arising from finite element discretizations.
+//!
+//! The classical point smoothers [`JacobiSmoother`] and [`GaussSeidelSmoother`] update one
+//! degree of freedom at a time. The remaining smoothers are based on local dense solves over
+//! blocks of degrees of freedom.
+//!
+//! Given a linear system $\vec A \vec x = \vec b$ and a collection of (possibly overlapping)
+//! blocks of degrees of freedom $I_1, \dots, I_m$, let $\vec R_i$ denote the restriction to the
+//! degrees of freedom in $I_i$ and $\vec A_i = \vec R_i \vec A \vec R_i^T$ the corresponding
+//! block of $\vec A$. The smoothers in this module perform local corrections
+//! <div>$$
+//! \vec x \leftarrow \vec x + \omega \vec R_i^T \vec A_i^{-1} \vec R_i (\vec b - \vec A \vec x),
+//! $$</div>
+//! where $\omega$ is a damping factor. [`BlockJacobiSmoother`] computes all corrections from
+//! the same residual and adds them up, whereas [`VankaSmoother`] applies the corrections one
+//! block at a time, updating the residual in between.
+//!
+//! Blocks are typically obtained from patches of elements (see [`crate::assembly::patch`]),
+//! for example with [`vertex_patch_blocks`]. For saddle-point systems, Vanka-type smoothers
+//! use blocks that couple e.g. the velocity degrees of freedom of a patch with the associated
+//! pressure degrees of freedom, so that each local problem is itself a small saddle-point problem.
+use crate::assembly::patch::{DenseSubmatrixExtractor, PatchTopology};
+use crate::nalgebra::{DVector, DVectorView, DVectorViewMut, Dyn, LU};
+use crate::solver::chebyshev::inverse_diagonal;
+use crate::Real;
+use eyre::eyre;
+use fenris_sparse::cg::LinearOperator;
+use nalgebra_sparse::CsrMatrix;
+use std::error::Error;
+
+/// A smoother for the linear system $\vec A \vec x = \vec b$.
+pub trait Smoother<T: Real> {
+    /// Applies a single smoothing sweep to the approximate solution `x`.
+    fn smooth(&self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>);
+
+    /// Applies a single sweep of the adjoint smoother to the approximate solution `x`.
+    ///
+    /// For symmetric $\vec A$, the adjoint smoother is the smoother whose iteration matrix is
+    /// the $\vec A$-adjoint of the iteration matrix of [`smooth`](Self::smooth). Using the
+    /// smoother for pre-smoothing and its adjoint for post-smoothing makes a multigrid cycle
+    /// symmetric. The default implementation assumes that the smoother is self-adjoint and
+    /// calls [`smooth`](Self::smooth).
+    fn smooth_adjoint(&self, matrix: &CsrMatrix<T>, x: DVectorViewMut<T>, b: DVectorView<T>) {
+        self.smooth(matrix, x, b)
+    }
+}
+
+/// A damped point Jacobi smoother.
+///
+/// A single sweep computes the update
+/// <div>$$
+/// \vec x \leftarrow \vec x + \omega \vec D^{-1} (\vec b - \vec A \vec x),
+/// $$</div>
+/// where $\vec D$ is the diagonal of $\vec A$. Without damping, the Jacobi iteration does not
+/// reduce the high-frequency error components of typical elliptic problems, and a damping factor
+/// of $\omega = 2/3$ is a common choice for multigrid smoothing.
+///
+/// The smoother also implements [`LinearOperator`] as the Jacobi preconditioner
+/// $\vec D^{-1}$. The damping factor is not applied in this case.
+#[derive(Debug, Clone)]
+pub struct JacobiSmoother<T: Real> {
+    inverse_diagonal: DVector<T>,
+    damping: T,
+}
+
+impl<T: Real> JacobiSmoother<T> {
+    /// Constructs a Jacobi smoother for the given matrix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square or if any diagonal entry is zero or missing.
+    pub fn from_matrix(matrix: &CsrMatrix<T>) -> eyre::Result<Self> {
+        Ok(Self {
+            inverse_diagonal: inverse_diagonal(matrix)?,
+            damping: T::one(),
+        })
+    }
+
+    pub fn with_damping(self, damping: T) -> Self {
+        Self { damping, ..self }
+    }
+
+    pub fn damping(&self) -> T {
+        self.damping
+    }
+
+    pub fn inverse_diagonal(&self) -> &DVector<T> {
+        &self.inverse_diagonal
+    }
+}
+
+impl<T: Real> Smoother<T> for JacobiSmoother<T> {
+    fn smooth(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        let n = self.inverse_diagonal.len();
+        assert_eq!(matrix.nrows(), n, "Matrix dimensions must match the smoother");
+        assert_eq!(x.len(), n, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), n, "Dimension of b must match the smoother");
+        let residual = DVector::from_iterator(n, (0..n).map(|i| row_residual(matrix, &x, &b, i)));
+        for (i, (&r_i, &d_inv_i)) in residual.iter().zip(&self.inverse_diagonal).enumerate() {
+            x[i] += self.damping * d_inv_i * r_i;
+        }
+    }
+}
+
+impl<T: Real> LinearOperator<T> for JacobiSmoother<T> {
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            y.len(),
+            self.inverse_diagonal.len(),
+            "Dimension of y must match the smoother"
+        );
+        assert_eq!(
+            x.len(),
+            self.inverse_diagonal.len(),
+            "Dimension of x must match the smoother"
+        );
+        y.copy_from(&x);
+        y.component_mul_assign(&self.inverse_diagonal);
+        Ok(())
+    }
+}
+
+/// A point Gauss-Seidel smoother.
+///
+/// A single sweep visits the degrees of freedom in increasing order and sets
+/// <div>$$
+/// x_i \leftarrow x_i + a_{ii}^{-1} \Big(b_i - \sum_j a_{ij} x_j\Big),
+/// $$</div>
+/// using the already updated values of all previously visited degrees of freedom. The adjoint
+/// smoother visits the degrees of freedom in decreasing order. With
+/// [`with_symmetric_sweeps`](Self::with_symmetric_sweeps), each sweep additionally visits the
+/// degrees of freedom in reverse order afterwards, which gives the symmetric Gauss-Seidel method.
+#[derive(Debug, Clone)]
+pub struct GaussSeidelSmoother<T: Real> {
+    inverse_diagonal: DVector<T>,
+    symmetric: bool,
+}
+
+impl<T: Real> GaussSeidelSmoother<T> {
+    /// Constructs a Gauss-Seidel smoother for the given matrix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square or if any diagonal entry is zero or missing.
+    pub fn from_matrix(matrix: &CsrMatrix<T>) -> eyre::Result<Self> {
+        Ok(Self {
+            inverse_diagonal: inverse_diagonal(matrix)?,
+            symmetric: false,
+        })
+    }
+
+    pub fn with_symmetric_sweeps(self, symmetric: bool) -> Self {
+        Self { symmetric, ..self }
+    }
+
+    fn sweep(
+        &self,
+        matrix: &CsrMatrix<T>,
+        x: &mut DVectorViewMut<T>,
+        b: &DVectorView<T>,
+        rows: impl Iterator<Item = usize>,
+    ) {
+        for i in rows {
+            let r_i = row_residual(matrix, x, b, i);
+            x[i] += self.inverse_diagonal[i] * r_i;
+        }
+    }
+
+    fn check_dimensions(&self, matrix: &CsrMatrix<T>, x: &DVectorViewMut<T>, b: &DVectorView<T>) {
+        let n = self.inverse_diagonal.len();
+        assert_eq!(matrix.nrows(), n, "Matrix dimensions must match the smoother");
+        assert_eq!(x.len(), n, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), n, "Dimension of b must match the smoother");
+    }
+}
+
+impl<T: Real> Smoother<T> for GaussSeidelSmoother<T> {
+    fn smooth(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        self.check_dimensions(matrix, &x, &b);
+        let n = self.inverse_diagonal.len();
+        self.sweep(matrix, &mut x, &b, 0..n);
+        if self.symmetric {
+            self.sweep(matrix, &mut x, &b, (0..n).rev());
+        }
+    }
+
+    fn smooth_adjoint(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        self.check_dimensions(matrix, &x, &b);
+        let n = self.inverse_diagonal.len();
+        self.sweep(matrix, &mut x, &b, (0..n).rev());
+        if self.symmetric {
+            self.sweep(matrix, &mut x, &b, 0..n);
+        }
+    }
+}
+
+/// Blocks of degrees of freedom together with the factorizations of the associated local matrices.
+#[derive(Debug, Clone)]
+struct FactorizedBlocks<T: Real> {
+    num_dofs: usize,
+    blocks: Vec<Vec<usize>>,
+    factorizations: Vec<LU<T, Dyn, Dyn>>,
+}
+
+impl<T: Real> FactorizedBlocks<T> {
+    fn factorize(matrix: &CsrMatrix<T>, blocks: Vec<Vec<usize>>) -> eyre::Result<Self> {
+        if matrix.nrows() != matrix.ncols() {
+            return Err(eyre!("Matrix must be square"));
+        }
+        let num_dofs = matrix.nrows();
+        let mut factorizations = Vec::with_capacity(blocks.len());
+        let mut extractor = DenseSubmatrixExtractor::new();
+        for (block_index, block) in blocks.iter().enumerate() {
+            if let Some(&dof) = block.iter().find(|&&dof| dof >= num_dofs) {
+                return Err(eyre!(
+                    "Degree of freedom {dof} in block {block_index} is out of bounds for {num_dofs} degrees of freedom"
+                ));
+            }
+            let mut sorted = block.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            if sorted.len() != block.len() {
+                return Err(eyre!("Block {block_index} contains duplicate degrees of freedom"));
+            }
+            let lu = extractor.extract(matrix, block).lu();
+            if !lu.is_invertible() {
+                return Err(eyre!("Local matrix for block {block_index} is singular"));
+            }
+            factorizations.push(lu);
+        }
+        Ok(Self {
+            num_dofs,
+            blocks,
+            factorizations,
+        })
+    }
+
+    /// Computes the local correction $\vec A_i^{-1} \vec R_i \vec r$ for block $i$.
+    fn solve_local(&self, block_index: usize, residual: impl Fn(usize) -> T) -> DVector<T> {
+        let block = &self.blocks[block_index];
+        let mut r_local = DVector::from_iterator(block.len(), block.iter().map(|&dof| residual(dof)));
+        // Factorizations are checked to be invertible on construction
+        let solved = self.factorizations[block_index].solve_mut(&mut r_local);
+        debug_assert!(solved);
+        r_local
+    }
+}
+
+/// Computes the residual $r_i = b_i - (\vec A \vec x)_i$ for the given row $i$.
+fn row_residual<T: Real>(matrix: &CsrMatrix<T>, x: &DVectorViewMut<T>, b: &DVectorView<T>, row_index: usize) -> T {
+    let row = matrix.row(row_index);
+    let ax = row
+        .col_indices()
+        .iter()
+        .zip(row.values())
+        .fold(T::zero(), |sum, (&j, &a_ij)| sum + a_ij * x[j]);
+    b[row_index] - ax
+}
+
+/// An additive block Jacobi smoother.
+///
+/// A single sweep computes the update
+/// <div>$$
+/// \vec x \leftarrow \vec x + \omega \sum_i \vec R_i^T \vec A_i^{-1} \vec R_i (\vec b - \vec A \vec x).
+/// $$</div>
+/// When the blocks overlap, the damping factor $\omega$ must generally be chosen smaller than
+/// one over the maximum number of blocks that any degree of freedom belongs to in order
+/// for the iteration to converge.
+///
+/// Since the block Jacobi preconditioner $\vec M^{-1} = \sum_i \vec R_i^T \vec A_i^{-1} \vec R_i$
+/// is symmetric whenever $\vec A$ is symmetric, the smoother also implements [`LinearOperator`]
+/// so that it can be used as a preconditioner for the conjugate gradient method. The damping
+/// factor is not applied in this case.
+#[derive(Debug, Clone)]
+pub struct BlockJacobiSmoother<T: Real> {
+    blocks: FactorizedBlocks<T>,
+    damping: T,
+}
+
+impl<T: Real> BlockJacobiSmoother<T> {
+    /// Constructs a block Jacobi smoother for the given matrix and blocks of degrees of freedom.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square, if a block contains degrees of freedom
+    /// that are out of bounds or duplicated, or if any of the local matrices is singular.
+    pub fn from_blocks(matrix: &CsrMatrix<T>, blocks: Vec<Vec<usize>>) -> eyre::Result<Self> {
+        Ok(Self {
+            blocks: FactorizedBlocks::factorize(matrix, blocks)?,
+            damping: T::one(),
+        })
+    }
+
+    pub fn with_damping(self, damping: T) -> Self {
+        Self { damping, ..self }
+    }
+
+    pub fn damping(&self) -> T {
+        self.damping
+    }
+
+    pub fn blocks(&self) -> &[Vec<usize>] {
+        &self.blocks.blocks
+    }
+
+    /// Computes $\vec y = \alpha \vec M^{-1} \vec r$ and adds it to `y`.
+    fn accumulate_correction(&self, mut y: DVectorViewMut<T>, alpha: T, residual: impl Fn(usize) -> T) {
+        for (block_index, block) in self.blocks.blocks.iter().enumerate() {
+            let correction = self.blocks.solve_local(block_index, &residual);
+            for (&dof, &c) in block.iter().zip(correction.iter()) {
+                y[dof] += alpha * c;
+            }
+        }
+    }
+}
+
+impl<T: Real> Smoother<T> for BlockJacobiSmoother<T> {
+    fn smooth(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        assert_eq!(
+            matrix.nrows(),
+            self.blocks.num_dofs,
+            "Matrix dimensions must match the smoother"
+        );
+        assert_eq!(x.len(), self.blocks.num_dofs, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), self.blocks.num_dofs, "Dimension of b must match the smoother");
+        let residual = DVector::from_iterator(x.len(), (0..x.len()).map(|i| row_residual(matrix, &x, &b, i)));
+        self.accumulate_correction(x.as_view_mut(), self.damping, |dof| residual[dof]);
+    }
+}
+
+impl<T: Real> LinearOperator<T> for BlockJacobiSmoother<T> {
+    fn apply(&self, mut y: DVectorViewMut<T>, x: DVectorView<T>) -> Result<(), Box<dyn Error>> {
+        assert_eq!(y.len(), self.blocks.num_dofs, "Dimension of y must match the smoother");
+        assert_eq!(x.len(), self.blocks.num_dofs, "Dimension of x must match the smoother");
+        y.fill(T::zero());
+        self.accumulate_correction(y, T::one(), |dof| x[dof]);
+        Ok(())
+    }
+}
+
+/// A multiplicative block smoother of Vanka type.
+///
+/// The blocks are visited one at a time, and each local correction
+/// <div>$$
+/// \vec x \leftarrow \vec x + \omega \vec R_i^T \vec A_i^{-1} \vec R_i (\vec b - \vec A \vec x)
+/// $$</div>
+/// uses the residual after all previous corrections in the sweep have been applied. This is
+/// the block analogue of the Gauss-Seidel method and is the classical choice of smoother for
+/// saddle-point problems such as the Stokes equations, where each block contains the degrees
+/// of freedom of all fields associated with a patch.
+///
+/// A single sweep visits the blocks in the order they were given. With
+/// [`with_symmetric_sweeps`](Self::with_symmetric_sweeps), each sweep additionally visits the
+/// blocks in reverse order afterwards.
+#[derive(Debug, Clone)]
+pub struct VankaSmoother<T: Real> {
+    blocks: FactorizedBlocks<T>,
+    damping: T,
+    symmetric: bool,
+}
+
+impl<T: Real> VankaSmoother<T> {
+    /// Constructs a Vanka smoother for the given matrix and blocks of degrees of freedom.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not square, if a block contains degrees of freedom
+    /// that are out of bounds or duplicated, or if any of the local matrices is singular.
+    pub fn from_blocks(matrix: &CsrMatrix<T>, blocks: Vec<Vec<usize>>) -> eyre::Result<Self> {
+        Ok(Self {
+            blocks: FactorizedBlocks::factorize(matrix, blocks)?,
+            damping: T::one(),
+            symmetric: false,
+        })
+    }
+
+    pub fn with_damping(self, damping: T) -> Self {
+        Self { damping, ..self }
+    }
+
+    pub fn with_symmetric_sweeps(self, symmetric: bool) -> Self {
+        Self { symmetric, ..self }
+    }
+
+    pub fn damping(&self) -> T {
+        self.damping
+    }
+
+    pub fn blocks(&self) -> &[Vec<usize>] {
+        &self.blocks.blocks
+    }
+
+    fn correct_block(&self, block_index: usize, matrix: &CsrMatrix<T>, x: &mut DVectorViewMut<T>, b: &DVectorView<T>) {
+        let correction = self
+            .blocks
+            .solve_local(block_index, |dof| row_residual(matrix, x, b, dof));
+        for (&dof, &c) in self.blocks.blocks[block_index]
+            .iter()
+            .zip(correction.iter())
+        {
+            x[dof] += self.damping * c;
+        }
+    }
+}
+
+impl<T: Real> Smoother<T> for VankaSmoother<T> {
+    fn smooth(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        assert_eq!(
+            matrix.nrows(),
+            self.blocks.num_dofs,
+            "Matrix dimensions must match the smoother"
+        );
+        assert_eq!(x.len(), self.blocks.num_dofs, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), self.blocks.num_dofs, "Dimension of b must match the smoother");
+        let num_blocks = self.blocks.blocks.len();
+        for block_index in 0..num_blocks {
+            self.correct_block(block_index, matrix, &mut x, &b);
+        }
+        if self.symmetric {
+            for block_index in (0..num_blocks).rev() {
+                self.correct_block(block_index, matrix, &mut x, &b);
+            }
+        }
+    }
+
+    /// Applies a sweep that visits the blocks in reverse order, followed by a sweep in the
+    /// original order if symmetric sweeps are enabled.
+    fn smooth_adjoint(&self, matrix: &CsrMatrix<T>, mut x: DVectorViewMut<T>, b: DVectorView<T>) {
+        assert_eq!(
+            matrix.nrows(),
+            self.blocks.num_dofs,
+            "Matrix dimensions must match the smoother"
+        );
+        assert_eq!(x.len(), self.blocks.num_dofs, "Dimension of x must match the smoother");
+        assert_eq!(b.len(), self.blocks.num_dofs, "Dimension of b must match the smoother");
+        let num_blocks = self.blocks.blocks.len();
+        for block_index in (0..num_blocks).rev() {
+            self.correct_block(block_index, matrix, &mut x, &b);
+        }
+        if self.symmetric {
+            for block_index in 0..num_blocks {
+                self.correct_block(block_index, matrix, &mut x, &b);
+            }
+        }
+    }
+}
+
+/// Blocks consisting of the degrees of freedom associated with the interior nodes of each vertex patch.
+///
+/// The interior nodes of the patch of all elements sharing a node are the nodes whose basis
+/// functions are supported entirely in the patch (see [`Patch`](crate::assembly::patch::Patch)).
+/// For high order elements, this includes the edge, face and interior nodes of all elements
+/// in the patch, which makes the resulting smoothers robust with respect to the polynomial
+/// degree. Empty blocks are omitted.
+///
+/// Only nodes for which `is_patch_center` returns `true` are used as patch centers, which
+/// can be used to e.g. only consider the vertices of higher order elements.
+pub fn vertex_patch_blocks(
+    topology: &PatchTopology,
+    solution_dim: usize,
+    is_patch_center: impl Fn(usize) -> bool,
+) -> Vec<Vec<usize>> {
+    (0..topology.num_nodes())
+        .filter(|&node| is_patch_center(node) && topology.node_valence(node) > 0)
+        .map(|node| {
+            topology
+                .vertex_patch(node)
+                .interior_global_dofs(solution_dim)
+        })
+        .filter(|block| !block.is_empty())
+        .collect()
+}
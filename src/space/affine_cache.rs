@@ -0,0 +1,216 @@
+use crate::element::ClosestPoint;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::space::{
+    BoundsForElementInSpace, ClosestPointInElementInSpace, FiniteElementConnectivity, FiniteElementSpace,
+};
+use fenris_geometry::AxisAlignedBoundingBox;
+use fenris_traits::allocators::BiDimAllocator;
+use fenris_traits::Real;
+use itertools::Itertools;
+use nalgebra::{DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar};
+use num::Zero;
+use std::marker::PhantomData;
+
+type ElementJacobian<T, Space> =
+    OMatrix<T, <Space as FiniteElementSpace<T>>::GeometryDim, <Space as FiniteElementSpace<T>>::ReferenceDim>;
+
+/// Caches the Jacobians of elements with an affine reference map in a
+/// [finite element space](crate::space::FiniteElementSpace).
+///
+/// Straight-sided simplices (and parallelograms/parallelepipeds) have a constant Jacobian.
+/// Mesh-based spaces already report simplex elements as
+/// [affine](FiniteElementSpace::is_element_affine), but they cannot know that e.g. a given
+/// quadrilateral is a parallelogram without inspecting its geometry. `AffineGeometryCache` wraps
+/// a space, detects numerically which elements have a constant Jacobian and stores the Jacobian
+/// of each such element. The cached Jacobian is exposed through
+/// [`FiniteElementSpace::element_constant_reference_jacobian`], which lets assemblers (such as
+/// the elliptic assemblers) compute the determinant and inverse only once per element.
+///
+/// An element is considered affine if its Jacobian at the points $\{-1/2, 0, 1/2\}^d$ in reference
+/// coordinates agrees up to round-off. Since the Jacobian of elements of up to (tri)quadratic
+/// order is uniquely determined by its values at these points, this is an exact test for all
+/// elements available in `fenris`.
+#[derive(Debug, Clone)]
+pub struct AffineGeometryCache<T, Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    space: Space,
+    jacobians: Vec<Option<ElementJacobian<T, Space>>>,
+    marker: PhantomData<T>,
+}
+
+impl<T, Space> AffineGeometryCache<T, Space>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    pub fn from_space(space: Space) -> Self {
+        let half = T::from_f64(0.5).unwrap();
+        let sample_points: Vec<OPoint<T, Space::ReferenceDim>> = (0..Space::ReferenceDim::dim())
+            .map(|_| [-half, T::zero(), half])
+            .multi_cartesian_product()
+            .map(|coords| OPoint::from_slice(&coords))
+            .collect();
+        let tolerance = T::from_f64(1e3).unwrap() * T::default_epsilon();
+
+        let jacobians = (0..space.num_elements())
+            .map(|element_index| {
+                let j0 = space.element_reference_jacobian(element_index, &OPoint::origin());
+                let threshold = tolerance * j0.norm();
+                sample_points
+                    .iter()
+                    .all(|xi| (space.element_reference_jacobian(element_index, xi) - &j0).norm() <= threshold)
+                    .then_some(j0)
+            })
+            .collect();
+
+        Self {
+            space,
+            jacobians,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Space> AffineGeometryCache<T, Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+
+    /// Returns the number of elements that were detected to be affine.
+    pub fn num_affine_elements(&self) -> usize {
+        self.jacobians.iter().filter(|j| j.is_some()).count()
+    }
+}
+
+impl<T, Space> FiniteElementConnectivity for AffineGeometryCache<T, Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        self.space.populate_element_nodes(nodes, element_index)
+    }
+}
+
+impl<T, Space> FiniteElementSpace<T> for AffineGeometryCache<T, Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    type GeometryDim = Space::GeometryDim;
+    type ReferenceDim = Space::ReferenceDim;
+
+    fn populate_element_basis(
+        &self,
+        element_index: usize,
+        basis_values: &mut [T],
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.space
+            .populate_element_basis(element_index, basis_values, reference_coords)
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        gradients: MatrixViewMut<T, Self::ReferenceDim, Dyn>,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.space
+            .populate_element_gradients(element_index, gradients, reference_coords)
+    }
+
+    fn element_reference_jacobian(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        match &self.jacobians[element_index] {
+            Some(jacobian) => jacobian.clone(),
+            None => self
+                .space
+                .element_reference_jacobian(element_index, reference_coords),
+        }
+    }
+
+    fn map_element_reference_coords(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OPoint<T, Self::GeometryDim> {
+        self.space
+            .map_element_reference_coords(element_index, reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.space.diameter(element_index)
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.jacobians[element_index].is_some()
+    }
+
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.jacobians[element_index].clone()
+    }
+}
+
+impl<T, Space> ClosestPointInElementInSpace<T> for AffineGeometryCache<T, Space>
+where
+    T: Real,
+    Space: ClosestPointInElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn closest_point_in_element(
+        &self,
+        element_index: usize,
+        p: &OPoint<T, Self::GeometryDim>,
+    ) -> ClosestPoint<T, Self::ReferenceDim> {
+        self.space.closest_point_in_element(element_index, p)
+    }
+}
+
+impl<T, Space> BoundsForElementInSpace<T> for AffineGeometryCache<T, Space>
+where
+    T: Real,
+    Space: BoundsForElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn bounds_for_element(&self, element_index: usize) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        self.space.bounds_for_element(element_index)
+    }
+}
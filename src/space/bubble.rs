@@ -0,0 +1,201 @@
+use crate::element::ClosestPoint;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::space::{
+    BoundsForElementInSpace, ClosestPointInElementInSpace, FiniteElementConnectivity, FiniteElementSpace,
+};
+use fenris_geometry::AxisAlignedBoundingBox;
+use fenris_traits::allocators::BiDimAllocator;
+use fenris_traits::Real;
+use nalgebra::{DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, U1};
+use num::Zero;
+
+/// A space of linear simplex elements enriched with an element bubble function.
+///
+/// Each element of the underlying space is enriched with the bubble function
+/// <div>$$
+/// b = n^n \prod_{i = 1}^{n} \lambda_i,
+/// $$</div>
+/// where $\lambda_1, \dots, \lambda_n$ are the basis functions of the $n$ nodes of the element.
+/// For linear triangles and tetrahedra, these are the barycentric coordinates of the element, so
+/// that $b$ is the cubic (quartic) bubble that vanishes on the boundary of the triangle
+/// (tetrahedron) and takes the value $1$ at its barycenter. The underlying space must therefore
+/// consist of linear simplex elements, such as those of a mesh with
+/// [`Tri3d2Connectivity`](crate::connectivity::Tri3d2Connectivity) or
+/// [`Tet4Connectivity`](crate::connectivity::Tet4Connectivity).
+///
+/// The nodes of the underlying space keep their indices, and the bubble of element $e$ is
+/// associated with the node $N + e$, where $N$ is the number of nodes in the underlying space
+/// (see [`bubble_node`](Self::bubble_node)). The bubble node is the last node of each element.
+///
+/// Enriching the P1 velocity with bubbles gives the velocity space of the MINI element for
+/// Stokes flow, which together with a P1 pressure satisfies the inf-sup condition. Since the
+/// bubbles are internal to each element, they are typically eliminated by static condensation,
+/// see `MiniStokesAssembler` in `model::stokes` (requires the `experimental-models` feature).
+#[derive(Debug, Clone)]
+pub struct BubbleEnrichedSpace<Space> {
+    space: Space,
+}
+
+impl<Space> BubbleEnrichedSpace<Space> {
+    pub fn from_space(space: Space) -> Self {
+        Self { space }
+    }
+
+    /// The underlying space without bubbles.
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+}
+
+impl<Space: FiniteElementConnectivity> BubbleEnrichedSpace<Space> {
+    /// The index of the node associated with the bubble of the given element.
+    pub fn bubble_node(&self, element_index: usize) -> usize {
+        self.space.num_nodes() + element_index
+    }
+}
+
+impl<Space: FiniteElementConnectivity> FiniteElementConnectivity for BubbleEnrichedSpace<Space> {
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes() + self.space.num_elements()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index) + 1
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        let n = self.space.element_node_count(element_index);
+        assert_eq!(nodes.len(), n + 1);
+        self.space
+            .populate_element_nodes(&mut nodes[..n], element_index);
+        nodes[n] = NodeIndex::new(self.bubble_node(element_index.index()));
+    }
+}
+
+impl<T, Space> FiniteElementSpace<T> for BubbleEnrichedSpace<Space>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    type GeometryDim = Space::GeometryDim;
+    type ReferenceDim = Space::ReferenceDim;
+
+    fn populate_element_basis(
+        &self,
+        element_index: usize,
+        basis_values: &mut [T],
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        let n = self.space.element_node_count(element_index.into());
+        assert_eq!(basis_values.len(), n + 1);
+        let (lambda, bubble) = basis_values.split_at_mut(n);
+        self.space
+            .populate_element_basis(element_index, lambda, reference_coords);
+        bubble[0] = lambda
+            .iter()
+            .fold(bubble_scale::<T>(n), |product, &lambda_i| product * lambda_i);
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        mut gradients: MatrixViewMut<T, Self::ReferenceDim, Dyn>,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        let n = self.space.element_node_count(element_index.into());
+        assert_eq!(gradients.ncols(), n + 1);
+        // TODO: Avoid allocating here
+        let mut lambda = vec![T::zero(); n];
+        self.space
+            .populate_element_basis(element_index, &mut lambda, reference_coords);
+        self.space
+            .populate_element_gradients(element_index, gradients.columns_mut(0, n), reference_coords);
+
+        // Product rule: grad b = n^n sum_i (prod_{j != i} lambda_j) grad lambda_i
+        let mut bubble_gradient = OMatrix::<T, Self::ReferenceDim, U1>::zeros();
+        for i in 0..n {
+            let product = (0..n)
+                .filter(|&j| j != i)
+                .fold(bubble_scale::<T>(n), |product, j| product * lambda[j]);
+            bubble_gradient += gradients.column(i) * product;
+        }
+        gradients.column_mut(n).copy_from(&bubble_gradient);
+    }
+
+    fn element_reference_jacobian(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.space
+            .element_reference_jacobian(element_index, reference_coords)
+    }
+
+    fn map_element_reference_coords(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OPoint<T, Self::GeometryDim> {
+        self.space
+            .map_element_reference_coords(element_index, reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.space.diameter(element_index)
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.space.is_element_affine(element_index)
+    }
+
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.space
+            .element_constant_reference_jacobian(element_index)
+    }
+}
+
+impl<T, Space> ClosestPointInElementInSpace<T> for BubbleEnrichedSpace<Space>
+where
+    T: Real,
+    Space: ClosestPointInElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn closest_point_in_element(
+        &self,
+        element_index: usize,
+        p: &OPoint<T, Self::GeometryDim>,
+    ) -> ClosestPoint<T, Self::ReferenceDim> {
+        self.space.closest_point_in_element(element_index, p)
+    }
+}
+
+impl<T, Space> BoundsForElementInSpace<T> for BubbleEnrichedSpace<Space>
+where
+    T: Real,
+    Space: BoundsForElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn bounds_for_element(&self, element_index: usize) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        self.space.bounds_for_element(element_index)
+    }
+}
+
+/// The scale $n^n$ that normalizes the bubble of an element with $n$ nodes.
+fn bubble_scale<T: Real>(n: usize) -> T {
+    T::from_usize(n).unwrap().powi(n as i32)
+}
@@ -0,0 +1,264 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::{surface_measure_and_normal, QuadratureTable};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::nalgebra::{DefaultAllocator, DimName, OMatrix, OVector, Scalar};
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+
+/// Determines how element normals are averaged to obtain normals at the nodes of a surface.
+///
+/// The normal of a surface mesh is in general discontinuous across elements, so that a normal
+/// at each node must be obtained by averaging the normals of the elements that share the node.
+/// Given the unit normal $n$ of the surface, the strategies compute the nodal normal $n_I$ by
+/// normalizing the following sums over the elements $K$ that contain node $I$.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NormalAveraging {
+    /// $\sum_K \bar n_K$, where $\bar n_K$ is the normalized average normal of element $K$.
+    Uniform,
+    /// $\sum_K \int_K n \\, \mathrm{d}s$, i.e. element normals weighted by element area.
+    #[default]
+    AreaWeighted,
+    /// $\sum_K \int_K \phi_I n \\, \mathrm{d}s$, the $L^2$ projection of the normal onto the finite
+    /// element space with a row-sum lumped mass matrix.
+    ///
+    /// This is consistent with the finite element discretization, but requires basis functions
+    /// with positive integrals, such as those of linear elements. For example, the integrals of
+    /// the basis functions associated with the vertices of quadratic triangles vanish.
+    Consistent,
+}
+
+/// Computes unit normals at the nodes of a surface finite element space.
+///
+/// The normals are obtained by averaging element normals as determined by `averaging`,
+/// where the integrals are computed with the weights and points of the provided quadrature table.
+/// The orientation of the normals follows the orientation of the elements, see
+/// [`surface_measure_and_normal`].
+///
+/// # Errors
+///
+/// Returns an error if the averaged normal of any node vanishes, which may for example happen
+/// for nodes that are not part of any element, or for nodes at sharp edges where the element
+/// normals cancel.
+///
+/// # Panics
+///
+/// Panics unless the space is a space of segments in 2D or of surface elements in 3D.
+pub fn compute_nodal_normals<T, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    averaging: NormalAveraging,
+) -> eyre::Result<Vec<OVector<T, Space::GeometryDim>>>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let mut normals = vec![OVector::<T, Space::GeometryDim>::zeros(); space.num_nodes()];
+
+    let mut quadrature_buffer = QuadratureBuffer::<T, Space::ReferenceDim>::default();
+    let mut basis_buffer = BasisFunctionBuffer::default();
+    let mut basis_values = Vec::new();
+
+    for element_index in 0..space.num_elements() {
+        let element = ElementInSpace::from_space_and_element_index(space, element_index);
+        basis_buffer.resize(element.num_nodes(), Space::ReferenceDim::dim());
+        basis_buffer.populate_element_nodes_from_space(element_index, space);
+        quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+
+        match averaging {
+            NormalAveraging::Uniform | NormalAveraging::AreaWeighted => {
+                let mut element_normal = OVector::<T, Space::GeometryDim>::zeros();
+                for (&weight, point) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+                    let (measure, normal) = surface_measure_and_normal(&element.reference_jacobian(point));
+                    element_normal += normal * (weight * measure);
+                }
+                if averaging == NormalAveraging::Uniform {
+                    element_normal
+                        .try_normalize_mut(T::zero())
+                        .ok_or_else(|| eyre!("Element {element_index} has vanishing average normal"))?;
+                }
+                for &node in basis_buffer.element_nodes() {
+                    normals[node] += &element_normal;
+                }
+            }
+            NormalAveraging::Consistent => {
+                basis_values.resize(element.num_nodes(), T::zero());
+                for (&weight, point) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+                    let (measure, normal) = surface_measure_and_normal(&element.reference_jacobian(point));
+                    element.populate_basis(&mut basis_values, point);
+                    for (&node, &phi) in izip!(basis_buffer.element_nodes(), &basis_values) {
+                        normals[node] += &normal * (weight * measure * phi);
+                    }
+                }
+            }
+        }
+    }
+
+    for (node, normal) in normals.iter_mut().enumerate() {
+        normal
+            .try_normalize_mut(T::zero())
+            .ok_or_else(|| eyre!("Averaged normal of node {node} vanishes"))?;
+    }
+
+    Ok(normals)
+}
+
+/// Computes the minimal rotation that rotates the unit vector `a` into the unit vector `b`.
+///
+/// The rotation acts in the plane spanned by `a` and `b` and leaves the orthogonal complement
+/// unchanged. With $c = a \cdot b$ and the skew-symmetric matrix $K = b a^T - a b^T$, it is given by
+/// $$
+/// R = I + K + \frac{1}{1 + c} K^2.
+/// $$
+///
+/// Returns `None` if `a` and `b` point in opposite directions, in which case the rotation is not unique.
+pub fn minimal_rotation<T, D>(a: &OVector<T, D>, b: &OVector<T, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let c = a.dot(b);
+    let one_plus_c = T::one() + c;
+    if one_plus_c <= T::default_epsilon() {
+        return None;
+    }
+    let k = b * a.transpose() - a * b.transpose();
+    let k_squared = &k * &k;
+    Some(OMatrix::<T, D, D>::identity() + k + k_squared / one_plus_c)
+}
+
+/// A field of directors on the nodes of a surface finite element space.
+///
+/// Shell formulations and surface PDEs with director fields associate a unit vector, the
+/// *director*, with each node of a surface mesh. The director is initially the nodal normal of
+/// the surface, computed with [`compute_nodal_normals`], but may deviate from the normal as the
+/// surface deforms, e.g. due to transverse shear in Reissner-Mindlin shells.
+///
+/// In addition to the directors, the field stores the nodal normals of the surface configuration
+/// it was last updated with. Directors are updated under deformation in one of two ways:
+///
+/// - [`update_from_space`](Self::update_from_space) follows a deformation of the surface.
+///   Each director is rotated by the [minimal rotation](minimal_rotation) that takes the previous
+///   nodal normal into the new one. This preserves the angle between director and normal, so that
+///   directors that coincide with the normals continue to do so, as in Kirchhoff-Love shells.
+/// - [`rotate_directors`](Self::rotate_directors) applies prescribed nodal rotations,
+///   such as those obtained from rotational degrees of freedom of shell elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectorField<T, D>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    normals: Vec<OVector<T, D>>,
+    directors: Vec<OVector<T, D>>,
+    averaging: NormalAveraging,
+}
+
+impl<T, D> DirectorField<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Creates a director field whose directors are the nodal normals of the given surface space.
+    ///
+    /// See [`compute_nodal_normals`] for the computation of the normals, including errors and panics.
+    pub fn from_space<Space, QTable>(space: &Space, qtable: &QTable, averaging: NormalAveraging) -> eyre::Result<Self>
+    where
+        Space: FiniteElementSpace<T, GeometryDim = D>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, D, Space::ReferenceDim>,
+    {
+        let normals = compute_nodal_normals(space, qtable, averaging)?;
+        Ok(Self {
+            directors: normals.clone(),
+            normals,
+            averaging,
+        })
+    }
+
+    /// The nodal normals of the surface configuration the field was last updated with.
+    pub fn normals(&self) -> &[OVector<T, D>] {
+        &self.normals
+    }
+
+    /// The nodal directors.
+    pub fn directors(&self) -> &[OVector<T, D>] {
+        &self.directors
+    }
+
+    pub fn averaging(&self) -> NormalAveraging {
+        self.averaging
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.directors.len()
+    }
+
+    /// Updates the field to follow the surface described by the given (deformed) space.
+    ///
+    /// The nodal normals are recomputed on the new configuration, and each director is rotated
+    /// by the minimal rotation that takes the previous normal into the new normal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of nodes in the space does not match the field, if the
+    /// normals cannot be computed, or if the normal at any node is reversed by the deformation.
+    /// The field is left unchanged in case of errors.
+    pub fn update_from_space<Space, QTable>(&mut self, space: &Space, qtable: &QTable) -> eyre::Result<()>
+    where
+        Space: FiniteElementSpace<T, GeometryDim = D>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, D, Space::ReferenceDim>,
+    {
+        if space.num_nodes() != self.num_nodes() {
+            return Err(eyre!(
+                "Space has {} nodes, but the director field has {} nodes",
+                space.num_nodes(),
+                self.num_nodes()
+            ));
+        }
+        let normals = compute_nodal_normals(space, qtable, self.averaging)?;
+        let directors = izip!(&self.normals, &normals, &self.directors)
+            .enumerate()
+            .map(|(node, (old_normal, new_normal, director))| {
+                let rotation = minimal_rotation(old_normal, new_normal)
+                    .ok_or_else(|| eyre!("Normal of node {node} is reversed by the deformation"))?;
+                Ok(rotation * director)
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        self.normals = normals;
+        self.directors = directors;
+        Ok(())
+    }
+
+    /// Rotates each director by the corresponding rotation matrix.
+    ///
+    /// The nodal normals are not affected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of rotations does not match the number of nodes.
+    pub fn rotate_directors(&mut self, rotations: &[OMatrix<T, D, D>]) -> eyre::Result<()>
+    where
+        DefaultAllocator: BiDimAllocator<T, D, D>,
+    {
+        if rotations.len() != self.num_nodes() {
+            return Err(eyre!(
+                "Expected {} rotations, but got {}",
+                self.num_nodes(),
+                rotations.len()
+            ));
+        }
+        for (director, rotation) in self.directors.iter_mut().zip(rotations) {
+            *director = rotation * &*director;
+        }
+        Ok(())
+    }
+}
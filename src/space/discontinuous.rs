@@ -0,0 +1,210 @@
+use crate::element::ClosestPoint;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::space::{
+    BoundsForElementInSpace, ClosestPointInElementInSpace, FiniteElementConnectivity, FiniteElementSpace,
+};
+use fenris_geometry::AxisAlignedBoundingBox;
+use fenris_traits::allocators::BiDimAllocator;
+use fenris_traits::Real;
+use nalgebra::{DVector, DVectorView, DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar};
+use num::Zero;
+use std::ops::Range;
+
+/// A discontinuous finite element space, in which no nodes are shared between elements.
+///
+/// Discontinuous Galerkin (DG) methods approximate the solution with functions that are
+/// polynomials on each element, but that may be discontinuous across element boundaries.
+/// `DiscontinuousSpace` wraps a (continuous) finite element space and uses the same basis
+/// functions and geometry on each element, but assigns a separate set of nodes to each element.
+/// The nodes of element $e$ are numbered consecutively, starting with the nodes of element $0$,
+/// so that the element-local node $i$ of element $e$ has the global index
+/// `element_nodes(e).start + i`.
+///
+/// Since element assemblers only depend on the space, the volume terms of DG methods are
+/// assembled with the usual assemblers. The coupling between elements is provided by face terms,
+/// for example by the
+/// [`InteriorPenaltyFaceAssembler`](crate::assembly::local::InteriorPenaltyFaceAssembler).
+#[derive(Debug, Clone)]
+pub struct DiscontinuousSpace<Space> {
+    space: Space,
+    element_offsets: Vec<usize>,
+}
+
+impl<Space: FiniteElementConnectivity> DiscontinuousSpace<Space> {
+    pub fn from_space(space: Space) -> Self {
+        let mut element_offsets = Vec::with_capacity(space.num_elements() + 1);
+        element_offsets.push(0);
+        for element_index in 0..space.num_elements() {
+            let offset = element_offsets.last().unwrap() + space.element_node_count(element_index.into());
+            element_offsets.push(offset);
+        }
+        Self { space, element_offsets }
+    }
+
+    /// The underlying continuous space.
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+
+    /// The range of (discontinuous) node indices that belong to the given element.
+    pub fn element_nodes(&self, element_index: usize) -> Range<usize> {
+        self.element_offsets[element_index]..self.element_offsets[element_index + 1]
+    }
+
+    /// Maps a nodal vector in the underlying continuous space to the discontinuous space.
+    ///
+    /// The value of each node of an element is copied from the corresponding node of the
+    /// continuous space, so that the result represents the same function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the vector does not match the number of nodes in the
+    /// continuous space.
+    pub fn map_continuous_vector<'a, T: Scalar + Zero>(
+        &self,
+        u: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> DVector<T> {
+        let u = u.into();
+        let s = solution_dim;
+        assert_eq!(
+            u.len(),
+            s * self.space.num_nodes(),
+            "Dimension of vector must match number of nodes in continuous space"
+        );
+        let mut result = DVector::zeros(s * self.num_nodes());
+        let mut nodes = Vec::new();
+        for element_index in 0..self.num_elements() {
+            nodes.resize(self.space.element_node_count(element_index.into()), 0);
+            self.space
+                .populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+            for (dg_node, &node) in self.element_nodes(element_index).zip(&nodes) {
+                result
+                    .rows_mut(s * dg_node, s)
+                    .copy_from(&u.rows(s * node, s));
+            }
+        }
+        result
+    }
+}
+
+impl<Space: FiniteElementConnectivity> FiniteElementConnectivity for DiscontinuousSpace<Space> {
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        *self.element_offsets.last().unwrap()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        assert_eq!(nodes.len(), self.element_node_count(element_index));
+        for (node, index) in nodes
+            .iter_mut()
+            .zip(self.element_nodes(element_index.index()))
+        {
+            *node = NodeIndex::new(index);
+        }
+    }
+}
+
+impl<T, Space> FiniteElementSpace<T> for DiscontinuousSpace<Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    type GeometryDim = Space::GeometryDim;
+    type ReferenceDim = Space::ReferenceDim;
+
+    fn populate_element_basis(
+        &self,
+        element_index: usize,
+        basis_values: &mut [T],
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.space
+            .populate_element_basis(element_index, basis_values, reference_coords)
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        gradients: MatrixViewMut<T, Self::ReferenceDim, Dyn>,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.space
+            .populate_element_gradients(element_index, gradients, reference_coords)
+    }
+
+    fn element_reference_jacobian(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.space
+            .element_reference_jacobian(element_index, reference_coords)
+    }
+
+    fn map_element_reference_coords(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OPoint<T, Self::GeometryDim> {
+        self.space
+            .map_element_reference_coords(element_index, reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.space.diameter(element_index)
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.space.is_element_affine(element_index)
+    }
+
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.space
+            .element_constant_reference_jacobian(element_index)
+    }
+}
+
+impl<T, Space> ClosestPointInElementInSpace<T> for DiscontinuousSpace<Space>
+where
+    T: Real,
+    Space: ClosestPointInElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn closest_point_in_element(
+        &self,
+        element_index: usize,
+        p: &OPoint<T, Self::GeometryDim>,
+    ) -> ClosestPoint<T, Self::ReferenceDim> {
+        self.space.closest_point_in_element(element_index, p)
+    }
+}
+
+impl<T, Space> BoundsForElementInSpace<T> for DiscontinuousSpace<Space>
+where
+    T: Real,
+    Space: BoundsForElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn bounds_for_element(&self, element_index: usize) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        self.space.bounds_for_element(element_index)
+    }
+}
@@ -0,0 +1,386 @@
+use crate::allocators::BiDimAllocator;
+use crate::element::{
+    FiniteElement, HierarchicalBasis, HierarchicalElement, HierarchicalMode, HierarchicalOrders, ReferenceFiniteElement,
+};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::mesh::{HexMesh, QuadMesh2d};
+use crate::space::{FiniteElementConnectivity, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use fenris_nested_vec::NestedVec;
+use itertools::Itertools;
+use nalgebra::{DVector, DVectorView, DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, U2, U3};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A global degree of freedom of a [`HierarchicalSpace`].
+///
+/// Edges and faces are identified by their (sorted) global vertex indices. The modes of an edge
+/// are oriented from the vertex with the smaller global index to the vertex with the larger index.
+/// The two directions of a face are given by the edges from the vertex with the smallest global
+/// index to its two neighbors in the face, with the first direction pointing towards the neighbor
+/// with the smaller global index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HierarchicalDof {
+    Vertex(usize),
+    Edge { vertices: [usize; 2], degree: usize },
+    Face { vertices: [usize; 4], degrees: [usize; 2] },
+    Interior { element: usize, degrees: Vec<usize> },
+}
+
+/// A continuous finite element space of hierarchical quadrilateral or hexahedral elements of
+/// variable order.
+///
+/// Each element has its own polynomial order, and the basis of each element is a
+/// [`HierarchicalBasis`]. Continuity across elements of different order is enforced with the
+/// *minimum rule*: the order of each edge and face is the minimum order of the elements that
+/// share it, and the higher order modes of the shared entity are left out of the elements with
+/// higher order. Since the basis is hierarchical, this amounts to constraining the coefficients
+/// of these modes to zero, which is why no explicit constraint equations are needed. Modes that
+/// are shared between elements are oriented consistently by flipping the sign of odd modes in
+/// elements whose local orientation of the edge or face does not match the
+/// [global orientation](HierarchicalDof).
+///
+/// The nodes of the space are the global degrees of freedom, which are described by
+/// [`dofs`](Self::dofs). The first nodes correspond to the vertices of the mesh, and their
+/// coefficients are the values of the function at the vertices.
+///
+/// When the orders are increased, the new space contains the old space, and coefficients can be
+/// [transferred](Self::transfer_weights) exactly by copying the coefficients of the common
+/// degrees of freedom.
+#[derive(Debug, Clone)]
+pub struct HierarchicalSpace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    vertices: Vec<OPoint<T, D>>,
+    cells: Vec<Vec<usize>>,
+    element_orders: Vec<usize>,
+    elements: Vec<HierarchicalElement<T, D>>,
+    element_dofs: NestedVec<usize>,
+    element_signs: NestedVec<T>,
+    dofs: Vec<HierarchicalDof>,
+}
+
+impl<T: Real> HierarchicalSpace<T, U2> {
+    /// Constructs a space on a quadrilateral mesh with the given order for each element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of orders does not match the number of elements, or if any order
+    /// is zero.
+    pub fn from_quad_mesh(mesh: &QuadMesh2d<T>, element_orders: Vec<usize>) -> Self {
+        let cells = mesh
+            .connectivity()
+            .iter()
+            .map(|conn| conn.0.to_vec())
+            .collect();
+        Self::from_cells(mesh.vertices().to_vec(), cells, element_orders)
+    }
+}
+
+impl<T: Real> HierarchicalSpace<T, U3> {
+    /// Constructs a space on a hexahedral mesh with the given order for each element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of orders does not match the number of elements, or if any order
+    /// is zero.
+    pub fn from_hex_mesh(mesh: &HexMesh<T>, element_orders: Vec<usize>) -> Self {
+        let cells = mesh
+            .connectivity()
+            .iter()
+            .map(|conn| conn.0.to_vec())
+            .collect();
+        Self::from_cells(mesh.vertices().to_vec(), cells, element_orders)
+    }
+}
+
+impl<T, D> HierarchicalSpace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn from_cells(vertices: Vec<OPoint<T, D>>, cells: Vec<Vec<usize>>, element_orders: Vec<usize>) -> Self {
+        assert_eq!(
+            element_orders.len(),
+            cells.len(),
+            "Number of element orders must match number of elements"
+        );
+        let reference_edges = HierarchicalBasis::<T, D>::reference_edges();
+        let reference_faces = HierarchicalBasis::<T, D>::reference_faces();
+        let edge_key = |cell: &[usize], [a, b]: [usize; 2]| {
+            let mut key = [cell[a], cell[b]];
+            key.sort_unstable();
+            key
+        };
+        let face_key = |cell: &[usize], face: [usize; 4]| {
+            let mut key = face.map(|v| cell[v]);
+            key.sort_unstable();
+            key
+        };
+
+        // Minimum rule: shared entities have the minimum order of the adjacent elements
+        let mut edge_orders = HashMap::new();
+        let mut face_orders = HashMap::new();
+        for (cell, &order) in cells.iter().zip(&element_orders) {
+            for &edge in &reference_edges {
+                let p = edge_orders.entry(edge_key(cell, edge)).or_insert(order);
+                *p = (*p).min(order);
+            }
+            for &face in &reference_faces {
+                let p = face_orders.entry(face_key(cell, face)).or_insert(order);
+                *p = (*p).min(order);
+            }
+        }
+
+        let mut dofs: Vec<_> = (0..vertices.len()).map(HierarchicalDof::Vertex).collect();
+        let mut edge_offsets = HashMap::new();
+        let mut face_offsets = HashMap::new();
+        let mut bases = HashMap::new();
+        let mut elements = Vec::with_capacity(cells.len());
+        let mut element_dofs = NestedVec::new();
+        let mut element_signs = NestedVec::new();
+        for (element_index, (cell, &order)) in cells.iter().zip(&element_orders).enumerate() {
+            let orders = HierarchicalOrders {
+                interior: order,
+                edges: reference_edges
+                    .iter()
+                    .map(|&edge| edge_orders[&edge_key(cell, edge)])
+                    .collect(),
+                faces: reference_faces
+                    .iter()
+                    .map(|&face| face_orders[&face_key(cell, face)])
+                    .collect(),
+            };
+            let basis = bases
+                .entry(orders.clone())
+                .or_insert_with(|| Arc::new(HierarchicalBasis::from_orders(orders)))
+                .clone();
+
+            let interior_offset = dofs.len();
+            let p = order;
+            if p >= 2 {
+                dofs.extend(
+                    (0..D::dim())
+                        .map(|_| 2..=p)
+                        .multi_cartesian_product()
+                        .map(|degrees| HierarchicalDof::Interior {
+                            element: element_index,
+                            degrees,
+                        }),
+                );
+            }
+
+            let mut local_dofs = element_dofs.begin_array();
+            let mut local_signs = element_signs.begin_array();
+            for mode in basis.modes() {
+                let (dof, negative) = match mode {
+                    HierarchicalMode::Vertex(v) => (cell[*v], false),
+                    HierarchicalMode::Edge { edge, degree } => {
+                        let [a, b] = reference_edges[*edge].map(|v| cell[v]);
+                        let key = edge_key(cell, reference_edges[*edge]);
+                        let p_edge = edge_orders[&key];
+                        let offset = *edge_offsets.entry(key).or_insert_with(|| {
+                            let offset = dofs.len();
+                            dofs.extend((2..=p_edge).map(|degree| HierarchicalDof::Edge { vertices: key, degree }));
+                            offset
+                        });
+                        (offset + degree - 2, a > b && degree % 2 == 1)
+                    }
+                    HierarchicalMode::Face { face, degrees: [i, j] } => {
+                        let corners = reference_faces[*face].map(|v| cell[v]);
+                        let key = face_key(cell, reference_faces[*face]);
+                        let p_face = face_orders[&key];
+                        let offset = *face_offsets.entry(key).or_insert_with(|| {
+                            let offset = dofs.len();
+                            for (i, j) in (2..=p_face).flat_map(|i| (2..=p_face).map(move |j| (i, j))) {
+                                dofs.push(HierarchicalDof::Face {
+                                    vertices: key,
+                                    degrees: [i, j],
+                                });
+                            }
+                            offset
+                        });
+                        let (global_degrees, negative) = global_face_degrees(corners, [*i, *j]);
+                        let [gi, gj] = global_degrees;
+                        (offset + (gi - 2) * (p_face - 1) + (gj - 2), negative)
+                    }
+                    HierarchicalMode::Interior { degrees } => {
+                        let index = degrees
+                            .iter()
+                            .fold(0, |index, &degree| index * (p - 1) + degree - 2);
+                        (interior_offset + index, false)
+                    }
+                };
+                local_dofs.push_single(dof);
+                local_signs.push_single(if negative { -T::one() } else { T::one() });
+            }
+            drop(local_dofs);
+            drop(local_signs);
+
+            let element_vertices = cell.iter().map(|&v| vertices[v].clone()).collect();
+            elements.push(HierarchicalElement::from_vertices_and_basis(element_vertices, basis));
+        }
+
+        Self {
+            vertices,
+            cells,
+            element_orders,
+            elements,
+            element_dofs,
+            element_signs,
+            dofs,
+        }
+    }
+
+    /// Constructs a space on the same mesh with different element orders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of orders does not match the number of elements, or if any order
+    /// is zero.
+    pub fn with_element_orders(&self, element_orders: Vec<usize>) -> Self {
+        Self::from_cells(self.vertices.clone(), self.cells.clone(), element_orders)
+    }
+
+    pub fn element_orders(&self) -> &[usize] {
+        &self.element_orders
+    }
+
+    pub fn element(&self, element_index: usize) -> &HierarchicalElement<T, D> {
+        &self.elements[element_index]
+    }
+
+    /// The global degrees of freedom, in the order of the nodes of the space.
+    pub fn dofs(&self) -> &[HierarchicalDof] {
+        &self.dofs
+    }
+
+    /// Transfers the weights of a function in another space on the same mesh to this space.
+    ///
+    /// The coefficients of degrees of freedom that exist in both spaces are copied, and the
+    /// remaining coefficients are set to zero. If the orders of this space are at least the orders
+    /// of the other space, this space contains the other space and the transfer is exact.
+    /// Otherwise, the higher order modes are truncated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the spaces are not defined on the same mesh, or if the number of weights does
+    /// not match the other space.
+    pub fn transfer_weights(&self, source: &Self, weights: DVectorView<T>, solution_dim: usize) -> DVector<T> {
+        assert!(
+            self.vertices.len() == source.vertices.len() && self.cells == source.cells,
+            "Spaces must be defined on the same mesh"
+        );
+        let s = solution_dim;
+        assert_eq!(
+            weights.len(),
+            s * source.dofs.len(),
+            "Number of weights must match source space"
+        );
+        let indices: HashMap<_, _> = self
+            .dofs
+            .iter()
+            .enumerate()
+            .map(|(index, dof)| (dof, index))
+            .collect();
+        let mut result = DVector::zeros(s * self.dofs.len());
+        for (source_index, dof) in source.dofs.iter().enumerate() {
+            if let Some(&index) = indices.get(dof) {
+                result
+                    .rows_mut(s * index, s)
+                    .copy_from(&weights.rows(s * source_index, s));
+            }
+        }
+        result
+    }
+}
+
+/// Maps the degrees of a face mode from the local face directions to the global face directions,
+/// and determines whether the mode changes sign.
+///
+/// The corners are the global vertex indices of the face in the local order $(0, 0)$, $(1, 0)$,
+/// $(0, 1)$, $(1, 1)$.
+fn global_face_degrees(corners: [usize; 4], [i, j]: [usize; 2]) -> ([usize; 2], bool) {
+    let corner = |s1: usize, s2: usize| corners[s1 + 2 * s2];
+    let origin = (0..4).min_by_key(|&k| corners[k]).unwrap();
+    let (s1, s2) = (origin % 2, origin / 2);
+    // Reversing a local direction changes the sign of modes of odd degree in that direction
+    let negative = (s1 == 1 && i % 2 == 1) != (s2 == 1 && j % 2 == 1);
+    let swap = corner(s1, 1 - s2) < corner(1 - s1, s2);
+    let degrees = if swap { [j, i] } else { [i, j] };
+    (degrees, negative)
+}
+
+impl<T, D> FiniteElementConnectivity for HierarchicalSpace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.dofs.len()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.elements[element_index.index()].num_nodes()
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        let dofs = self
+            .element_dofs
+            .get(element_index.index())
+            .expect("Element index out of bounds");
+        nodes.copy_from_slice(NodeIndex::from_raw_slice(dofs));
+    }
+}
+
+impl<T, D> FiniteElementSpace<T> for HierarchicalSpace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    type GeometryDim = D;
+    type ReferenceDim = D;
+
+    fn populate_element_basis(&self, element_index: usize, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        self.elements[element_index].populate_basis(basis_values, reference_coords);
+        let signs = self.element_signs.get(element_index).unwrap();
+        for (value, &sign) in basis_values.iter_mut().zip(signs) {
+            *value *= sign;
+        }
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        mut gradients: MatrixViewMut<T, D, Dyn>,
+        reference_coords: &OPoint<T, D>,
+    ) {
+        self.elements[element_index].populate_basis_gradients(gradients.as_view_mut(), reference_coords);
+        let signs = self.element_signs.get(element_index).unwrap();
+        for (mut gradient, &sign) in gradients.column_iter_mut().zip(signs) {
+            gradient *= sign;
+        }
+    }
+
+    fn element_reference_jacobian(&self, element_index: usize, reference_coords: &OPoint<T, D>) -> OMatrix<T, D, D> {
+        self.elements[element_index].reference_jacobian(reference_coords)
+    }
+
+    fn map_element_reference_coords(&self, element_index: usize, reference_coords: &OPoint<T, D>) -> OPoint<T, D> {
+        self.elements[element_index].map_reference_coords(reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.elements[element_index].diameter()
+    }
+}
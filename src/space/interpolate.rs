@@ -1,10 +1,14 @@
-use crate::allocators::TriDimAllocator;
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
 use crate::assembly::buffers::{BufferUpdate, InterpolationBuffer};
+use crate::index::NodeIndex;
 use crate::space::{FindClosestElement, FiniteElementSpace, VolumetricFiniteElementSpace};
 use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::eyre;
 use itertools::izip;
 use nalgebra::{DVectorView, DefaultAllocator, OMatrix, OPoint, OVector};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use std::array;
 
 /// A finite element space that allows interpolation at arbitrary points.
@@ -229,3 +233,155 @@ pub fn interpolate_gradient_at_points<T, SolutionDim, Space>(
         }
     })
 }
+
+/// Assembles the matrix that evaluates a scalar finite element function at a set of points.
+///
+/// The matrix $E$ has one row for each point $\vec x_i$ and one column for each node of the
+/// space, with entries $E_{iJ} = N_J(\vec x_i)$, so that $E \vec u$ gives the values of the
+/// function with interpolation weights $\vec u$ at the points. Points are located in the same
+/// way as in [`interpolate_at_points`]: if a point is outside the domain of the space, the
+/// basis functions of the closest element are evaluated at the closest point. Rows of points
+/// for which no element is found, which only happens if the space has no elements, are empty.
+pub fn assemble_evaluation_matrix<T, Space>(space: &Space, points: &[OPoint<T, Space::GeometryDim>]) -> CsrMatrix<T>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let mut coo = CooMatrix::new(points.len(), space.num_nodes());
+    let mut nodes = Vec::new();
+    let mut basis_values = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        if let Some((element, ref_coords)) = space.find_closest_element_and_reference_coords(point) {
+            let n = space.element_node_count(element.into());
+            nodes.resize(n, usize::MAX);
+            basis_values.resize(n, T::zero());
+            space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element.into());
+            space.populate_element_basis(element, &mut basis_values, &ref_coords);
+            for (&node, &value) in izip!(&nodes, &basis_values) {
+                coo.push(i, node, value);
+            }
+        }
+    }
+    CsrMatrix::from(&coo)
+}
+
+/// Determines how [`par_interpolate_at_points`] treats points outside the finite element space.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OutsidePointPolicy {
+    /// Interpolate in the closest element, at the closest point to the query point.
+    ///
+    /// This amounts to constant extrapolation in the direction normal to the boundary.
+    #[default]
+    NearestElement,
+    /// Return `None` for points outside the space.
+    Skip,
+    /// Return an error if any point lies outside the space.
+    Error,
+}
+
+/// The value and gradient of a finite element function at a point, as computed by
+/// [`par_interpolate_at_points`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointInterpolation<T, D, SolutionDim>
+where
+    T: Real,
+    D: SmallDim,
+    SolutionDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, SolutionDim>,
+{
+    /// The element in which the function was interpolated.
+    pub element_index: usize,
+    /// The reference coordinates in the element at which the function was interpolated.
+    pub reference_coords: OPoint<T, D>,
+    /// Whether the point lies inside the element, as opposed to being outside the space.
+    pub is_inside: bool,
+    pub value: OVector<T, SolutionDim>,
+    pub gradient: OMatrix<T, D, SolutionDim>,
+}
+
+type SpacePointInterpolation<T, Space, SolutionDim> =
+    PointInterpolation<T, <Space as FiniteElementSpace<T>>::GeometryDim, SolutionDim>;
+
+/// Interpolates the values and gradients of a finite element function at a set of arbitrary
+/// points in parallel.
+///
+/// This is the batched counterpart of [`interpolate_at_points`] and
+/// [`interpolate_gradient_at_points`], intended for coupling finite element solutions to
+/// particles or other point clouds. The points are processed in parallel, and each point is
+/// located with [`FindClosestElement`], which is efficient for a
+/// [`SpatiallyIndexed`](crate::space::SpatiallyIndexed) space. Points that do not lie in any
+/// element are treated according to the given [policy](OutsidePointPolicy).
+///
+/// The result contains one entry for each point, which is `None` if the point was skipped or if
+/// the space has no elements.
+///
+/// # Errors
+///
+/// Returns an error if the length of `u` is not compatible with the space and the solution
+/// dimension, if a point lies outside the space and the policy is
+/// [`OutsidePointPolicy::Error`], or if a singular element Jacobian is encountered.
+pub fn par_interpolate_at_points<'a, T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    u: impl Into<DVectorView<'a, T>>,
+    policy: OutsidePointPolicy,
+) -> eyre::Result<Vec<Option<SpacePointInterpolation<T, Space, SolutionDim>>>>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T> + Sync,
+    OPoint<T, Space::GeometryDim>: Sync,
+    PointInterpolation<T, Space::GeometryDim, SolutionDim>: Send,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let u = u.into();
+    let s = SolutionDim::dim();
+    if u.len() != s * space.num_nodes() {
+        return Err(eyre!(
+            "Dimension of solution vector does not match space and solution dimension"
+        ));
+    }
+
+    points
+        .par_iter()
+        .enumerate()
+        .map(|(point_index, point)| {
+            let Some((element_index, reference_coords)) = space.find_closest_element_and_reference_coords(point) else {
+                return Ok(None);
+            };
+            with_thread_local_workspace(&INTERPOLATE_WORKSPACE, |buf: &mut InterpolationBuffer<T>| {
+                let mut element_buf = buf.prepare_element_in_space(element_index, space, u, s);
+                element_buf.update_reference_point(&reference_coords, BufferUpdate::Both);
+
+                // The closest point coincides with the query point if the point is inside
+                let tolerance = T::default_epsilon().sqrt() * space.diameter(element_index);
+                let is_inside = (element_buf.map_reference_coords() - point).norm() <= tolerance;
+                if !is_inside {
+                    match policy {
+                        OutsidePointPolicy::NearestElement => {}
+                        OutsidePointPolicy::Skip => return Ok(None),
+                        OutsidePointPolicy::Error => {
+                            return Err(eyre!("Point {} is outside the finite element space", point_index))
+                        }
+                    }
+                }
+
+                let value = element_buf.interpolate();
+                let inv_j_t = element_buf
+                    .element_reference_jacobian()
+                    .try_inverse()
+                    .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?
+                    .transpose();
+                let gradient = inv_j_t * element_buf.interpolate_ref_gradient();
+                Ok(Some(PointInterpolation {
+                    element_index,
+                    reference_coords,
+                    is_inside,
+                    value,
+                    gradient,
+                }))
+            })
+        })
+        .collect()
+}
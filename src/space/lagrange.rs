@@ -0,0 +1,194 @@
+use crate::allocators::BiDimAllocator;
+use crate::element::{
+    FiniteElement, LagrangeNodes, LagrangeSimplexBasis, LagrangeSimplexElement, ReferenceFiniteElement,
+};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::mesh::{Tet4Mesh, TriangleMesh2d};
+use crate::space::{FiniteElementConnectivity, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use fenris_nested_vec::NestedVec;
+use nalgebra::{DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, U2, U3};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A continuous finite element space of [Lagrange simplex elements](LagrangeSimplexElement)
+/// of arbitrary order.
+///
+/// The space is constructed from a mesh of linear triangles or tetrahedra, whose cells are
+/// interpreted as straight-sided simplices of order `ORDER`. All elements share the same
+/// [`LagrangeSimplexBasis`]. Nodes on shared edges and faces are identified by their barycentric
+/// [lattice indices](LagrangeSimplexBasis::lattice_indices) with respect to the global vertex
+/// indices, so that the space is continuous regardless of the orientation of the cells.
+///
+/// The first nodes of the space correspond to the vertices of the mesh, followed by the
+/// remaining nodes in the order in which they are first encountered. The physical coordinates
+/// of all nodes are available through [`nodes`](Self::nodes), so that functions can be
+/// interpolated by evaluating them at the nodes.
+///
+/// Since the order is a compile-time parameter, sweeps over the order are typically
+/// implemented with [`LagrangeOrderSweep`](crate::error::LagrangeOrderSweep).
+#[derive(Debug, Clone)]
+pub struct LagrangeSimplexSpace<T, D, const ORDER: usize>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    nodes: Vec<OPoint<T, D>>,
+    elements: Vec<LagrangeSimplexElement<T, D, ORDER>>,
+    element_nodes: NestedVec<usize>,
+}
+
+impl<T: Real, const ORDER: usize> LagrangeSimplexSpace<T, U2, ORDER> {
+    /// Constructs a space on a triangle mesh with the given distribution of nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the basis cannot be constructed, see [`LagrangeSimplexBasis::new`].
+    pub fn from_triangle_mesh(mesh: &TriangleMesh2d<T>, nodes: LagrangeNodes) -> Self {
+        let cells = mesh
+            .connectivity()
+            .iter()
+            .map(|conn| conn.0.to_vec())
+            .collect();
+        Self::from_cells(mesh.vertices().to_vec(), cells, nodes)
+    }
+}
+
+impl<T: Real, const ORDER: usize> LagrangeSimplexSpace<T, U3, ORDER> {
+    /// Constructs a space on a tetrahedral mesh with the given distribution of nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the basis cannot be constructed, see [`LagrangeSimplexBasis::new`].
+    pub fn from_tet_mesh(mesh: &Tet4Mesh<T>, nodes: LagrangeNodes) -> Self {
+        let cells = mesh
+            .connectivity()
+            .iter()
+            .map(|conn| conn.0.to_vec())
+            .collect();
+        Self::from_cells(mesh.vertices().to_vec(), cells, nodes)
+    }
+}
+
+impl<T, D, const ORDER: usize> LagrangeSimplexSpace<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn from_cells(vertices: Vec<OPoint<T, D>>, cells: Vec<Vec<usize>>, nodes: LagrangeNodes) -> Self {
+        let basis = Arc::new(LagrangeSimplexBasis::<T, D, ORDER>::new(nodes));
+        let lattice_indices = basis.lattice_indices();
+
+        // A node is identified by the global vertices with non-zero lattice index, together
+        // with their indices. Vertex nodes keep the indices of the vertices.
+        let mut node_indices: HashMap<Vec<(usize, usize)>, usize> =
+            (0..vertices.len()).map(|v| (vec![(v, ORDER)], v)).collect();
+        let mut global_nodes = vertices.clone();
+        let mut elements = Vec::with_capacity(cells.len());
+        let mut element_nodes = NestedVec::new();
+        for cell in &cells {
+            let element_vertices = cell.iter().map(|&v| vertices[v].clone()).collect();
+            let element = LagrangeSimplexElement::from_vertices_and_basis(element_vertices, basis.clone());
+            let mut local_nodes = element_nodes.begin_array();
+            for (indices, xi) in lattice_indices.iter().zip(basis.reference_nodes()) {
+                let mut key: Vec<_> = cell
+                    .iter()
+                    .zip(indices)
+                    .filter(|(_, &i)| i > 0)
+                    .map(|(&v, &i)| (v, i))
+                    .collect();
+                key.sort_unstable();
+                let node = *node_indices.entry(key).or_insert_with(|| {
+                    global_nodes.push(element.map_reference_coords(xi));
+                    global_nodes.len() - 1
+                });
+                local_nodes.push_single(node);
+            }
+            drop(local_nodes);
+            elements.push(element);
+        }
+
+        Self {
+            nodes: global_nodes,
+            elements,
+            element_nodes,
+        }
+    }
+
+    /// The physical coordinates of the nodes of the space.
+    pub fn nodes(&self) -> &[OPoint<T, D>] {
+        &self.nodes
+    }
+
+    pub fn element(&self, element_index: usize) -> &LagrangeSimplexElement<T, D, ORDER> {
+        &self.elements[element_index]
+    }
+}
+
+impl<T, D, const ORDER: usize> FiniteElementConnectivity for LagrangeSimplexSpace<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.elements[element_index.index()].num_nodes()
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        let element_nodes = self
+            .element_nodes
+            .get(element_index.index())
+            .expect("Element index out of bounds");
+        nodes.copy_from_slice(NodeIndex::from_raw_slice(element_nodes));
+    }
+}
+
+impl<T, D, const ORDER: usize> FiniteElementSpace<T> for LagrangeSimplexSpace<T, D, ORDER>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    type GeometryDim = D;
+    type ReferenceDim = D;
+
+    fn populate_element_basis(&self, element_index: usize, basis_values: &mut [T], reference_coords: &OPoint<T, D>) {
+        self.elements[element_index].populate_basis(basis_values, reference_coords);
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        gradients: MatrixViewMut<T, D, Dyn>,
+        reference_coords: &OPoint<T, D>,
+    ) {
+        self.elements[element_index].populate_basis_gradients(gradients, reference_coords);
+    }
+
+    fn element_reference_jacobian(&self, element_index: usize, reference_coords: &OPoint<T, D>) -> OMatrix<T, D, D> {
+        self.elements[element_index].reference_jacobian(reference_coords)
+    }
+
+    fn map_element_reference_coords(&self, element_index: usize, reference_coords: &OPoint<T, D>) -> OPoint<T, D> {
+        self.elements[element_index].map_reference_coords(reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.elements[element_index].diameter()
+    }
+
+    fn is_element_affine(&self, _element_index: usize) -> bool {
+        true
+    }
+}
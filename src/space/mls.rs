@@ -0,0 +1,321 @@
+use crate::allocators::DimAllocator;
+use crate::space::spatially_indexed::RTreePoint;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DMatrix, DVector, DefaultAllocator, OPoint, OVector};
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+
+/// Polynomial basis used for moving least squares approximation.
+///
+/// An MLS approximation with a basis of degree $k$ exactly reproduces all polynomials of
+/// total degree at most $k$.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MlsBasis {
+    /// A constant basis, which gives Shepard interpolation.
+    Constant,
+    /// Polynomials of total degree at most one.
+    Linear,
+    /// Polynomials of total degree at most two.
+    Quadratic,
+}
+
+impl MlsBasis {
+    /// The total polynomial degree of the basis.
+    pub fn degree(&self) -> usize {
+        match self {
+            Self::Constant => 0,
+            Self::Linear => 1,
+            Self::Quadratic => 2,
+        }
+    }
+
+    /// The number of monomials in the basis in the given spatial dimension.
+    pub fn num_monomials(&self, dim: usize) -> usize {
+        monomial_exponents(dim, self.degree()).len()
+    }
+}
+
+/// Weight function used for moving least squares approximation.
+///
+/// The weight functions are given in terms of the normalized distance $q = r / h$, where
+/// $r$ is the distance to a sample point and $h$ is the support radius. All weight functions
+/// vanish for $q \geq 1$.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MlsWeight<T> {
+    /// The $C^2$ Wendland function $(1 - q)^4 (4 q + 1)$.
+    Wendland,
+    /// The cubic B-spline kernel, with value $2/3 - 4 q^2 + 4 q^3$ for $q \leq 1/2$
+    /// and $4/3 (1 - q)^3$ for $1/2 < q < 1$.
+    CubicSpline,
+    /// The truncated Gaussian $\exp(-(q / \alpha)^2)$ with shape parameter $\alpha$.
+    Gaussian { shape_parameter: T },
+}
+
+impl<T: Real> MlsWeight<T> {
+    /// Evaluates the weight function at the given normalized distance $q$.
+    pub fn evaluate(&self, q: T) -> T {
+        let one = T::one();
+        if q >= one {
+            return T::zero();
+        }
+        let c = |x: f64| T::from_f64(x).unwrap();
+        match self {
+            Self::Wendland => (one - q).powi(4) * (c(4.0) * q + one),
+            Self::CubicSpline if q <= c(0.5) => c(2.0 / 3.0) - c(4.0) * q * q + c(4.0) * q * q * q,
+            Self::CubicSpline => c(4.0 / 3.0) * (one - q).powi(3),
+            Self::Gaussian { shape_parameter } => {
+                let s = q / *shape_parameter;
+                (-s * s).exp()
+            }
+        }
+    }
+}
+
+/// Moving least squares (MLS) approximation over a point cloud.
+///
+/// MLS approximates data given at scattered points without requiring a mesh, which makes it
+/// useful to transfer e.g. particle or measurement data onto finite element spaces, where
+/// [interpolation](crate::space::interpolate_at_points) is not available because the source
+/// data has no mesh.
+///
+/// At an evaluation point $\vec x$, MLS fits a polynomial $\vec p(\vec y)^T \vec a(\vec x)$ to
+/// the values $v_i$ at the sample points $\vec x_i$ within the support radius $h$, by minimizing
+/// the weighted least squares error
+/// <div>$$
+/// \sum_i w(|\vec x - \vec x_i| / h) \, (\vec p(\vec x_i)^T \vec a(\vec x) - v_i)^2.
+/// $$</div>
+/// This gives the approximation $u(\vec x) = \sum_i \phi_i(\vec x) v_i$ with the
+/// [shape functions](Self::shape_functions) $\phi_i$. The approximation reproduces all
+/// polynomials in the [basis](MlsBasis) exactly.
+///
+/// The sample points are stored in an R-tree, so that the neighbors within the support radius
+/// are found efficiently. The approximation is only defined at points with sufficiently many
+/// neighbors in non-degenerate positions, e.g. at least three non-collinear neighbors in 2D for a
+/// linear basis.
+#[derive(Debug, Clone)]
+pub struct MovingLeastSquares<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D> + Allocator<f64, D>,
+{
+    points: Vec<OPoint<T, D>>,
+    tree: RTree<GeomWithData<RTreePoint<D>, usize>>,
+    support_radius: T,
+    basis: MlsBasis,
+    weight: MlsWeight<T>,
+    exponents: Vec<Vec<usize>>,
+}
+
+impl<T, D> MovingLeastSquares<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D> + Allocator<f64, D>,
+{
+    /// Creates an MLS approximation over the given points with the given support radius.
+    ///
+    /// By default, a linear basis and the Wendland weight function are used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the support radius is not positive.
+    pub fn from_points(points: Vec<OPoint<T, D>>, support_radius: T) -> Self {
+        assert!(support_radius > T::zero(), "Support radius must be positive");
+        let geometries = points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| GeomWithData::new(RTreePoint(to_f64_point(point)), i))
+            .collect();
+        let basis = MlsBasis::Linear;
+        Self {
+            points,
+            tree: RTree::bulk_load(geometries),
+            support_radius,
+            basis,
+            weight: MlsWeight::Wendland,
+            exponents: monomial_exponents(D::dim(), basis.degree()),
+        }
+    }
+
+    pub fn with_basis(self, basis: MlsBasis) -> Self {
+        Self {
+            basis,
+            exponents: monomial_exponents(D::dim(), basis.degree()),
+            ..self
+        }
+    }
+
+    pub fn with_weight(self, weight: MlsWeight<T>) -> Self {
+        Self { weight, ..self }
+    }
+
+    pub fn points(&self) -> &[OPoint<T, D>] {
+        &self.points
+    }
+
+    pub fn support_radius(&self) -> T {
+        self.support_radius
+    }
+
+    pub fn basis(&self) -> MlsBasis {
+        self.basis
+    }
+
+    pub fn weight(&self) -> &MlsWeight<T> {
+        &self.weight
+    }
+
+    /// Computes the values $\phi_i(\vec x)$ of the shape functions that are non-zero at the
+    /// given point.
+    ///
+    /// Returns pairs of the index of the sample point and the value of its shape function.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sample points within the support radius do not determine the
+    /// polynomial fit, i.e. if the moment matrix is singular.
+    pub fn shape_functions(&self, x: &OPoint<T, D>) -> eyre::Result<Vec<(usize, T)>> {
+        let h = self.support_radius;
+        let h_f64: f64 = h.to_subset().unwrap();
+        let neighbors: Vec<usize> = self
+            .tree
+            .locate_within_distance(RTreePoint(to_f64_point(x)), h_f64 * h_f64)
+            .map(|geom| geom.data)
+            .collect();
+
+        // Monomials are evaluated in shifted and scaled coordinates (x_i - x) / h, which keeps
+        // the moment matrix well conditioned and makes the constant monomial the only one
+        // that does not vanish at x
+        let m = self.exponents.len();
+        let mut basis_values = DMatrix::<T>::zeros(m, neighbors.len());
+        let mut weights = DVector::<T>::zeros(neighbors.len());
+        for (j, &i) in neighbors.iter().enumerate() {
+            let y = (&self.points[i] - x) / h;
+            weights[j] = self.weight.evaluate(y.norm());
+            for (k, exponents) in self.exponents.iter().enumerate() {
+                basis_values[(k, j)] = exponents
+                    .iter()
+                    .enumerate()
+                    .fold(T::one(), |p, (d, &e)| p * y[d].powi(e as i32));
+            }
+        }
+
+        let mut weighted_basis_values = basis_values.clone();
+        for (mut column, &w) in weighted_basis_values.column_iter_mut().zip(weights.iter()) {
+            column *= w;
+        }
+        let moment_matrix = &weighted_basis_values * basis_values.transpose();
+        let mut e0 = DVector::zeros(m);
+        e0[0] = T::one();
+        let coefficients = moment_matrix
+            .cholesky()
+            .map(|cholesky| cholesky.solve(&e0))
+            .filter(|c| c.iter().all(|c_k| c_k.is_finite()))
+            .ok_or_else(|| {
+                eyre!(
+                    "Moment matrix is singular at point {:?}: {} neighbors within support radius \
+                     do not determine a fit with {} monomials",
+                    x,
+                    neighbors.len(),
+                    m
+                )
+            })?;
+
+        let phi = basis_values.tr_mul(&coefficients).component_mul(&weights);
+        Ok(neighbors.into_iter().zip(phi.iter().copied()).collect())
+    }
+
+    /// Evaluates the MLS approximation of the given values at a point.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of values does not match the number of sample points, or
+    /// if the shape functions are not defined at the point.
+    pub fn evaluate<S>(&self, x: &OPoint<T, D>, values: &[OVector<T, S>]) -> eyre::Result<OVector<T, S>>
+    where
+        S: SmallDim,
+        DefaultAllocator: DimAllocator<T, S>,
+    {
+        self.check_num_values(values.len())?;
+        let mut result = OVector::<T, S>::zeros();
+        for (i, phi_i) in self.shape_functions(x)? {
+            result += &values[i] * phi_i;
+        }
+        Ok(result)
+    }
+
+    /// Evaluates the MLS approximation of the given values at each of the given points.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the evaluation fails at any point, see [`evaluate`](Self::evaluate).
+    pub fn evaluate_at_points<S>(
+        &self,
+        points: &[OPoint<T, D>],
+        values: &[OVector<T, S>],
+    ) -> eyre::Result<Vec<OVector<T, S>>>
+    where
+        S: SmallDim,
+        DefaultAllocator: DimAllocator<T, S>,
+    {
+        self.check_num_values(values.len())?;
+        points
+            .iter()
+            .enumerate()
+            .map(|(j, x)| {
+                self.evaluate(x, values)
+                    .map_err(|err| err.wrap_err(format!("Failed to evaluate MLS approximation at point {j}")))
+            })
+            .collect()
+    }
+
+    fn check_num_values(&self, num_values: usize) -> eyre::Result<()> {
+        if num_values != self.points.len() {
+            return Err(eyre!(
+                "Number of values ({num_values}) does not match number of points ({})",
+                self.points.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn to_f64_point<T, D>(point: &OPoint<T, D>) -> OPoint<f64, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D> + Allocator<f64, D>,
+{
+    point.map(|x_i| x_i.to_subset().unwrap())
+}
+
+/// Exponents of all monomials in `dim` variables with total degree at most `degree`,
+/// ordered by total degree.
+fn monomial_exponents(dim: usize, degree: usize) -> Vec<Vec<usize>> {
+    let mut exponents = Vec::new();
+    for total in 0..=degree {
+        let mut current = vec![0; dim];
+        push_exponents_with_total_degree(&mut exponents, &mut current, 0, total);
+    }
+    exponents
+}
+
+fn push_exponents_with_total_degree(
+    exponents: &mut Vec<Vec<usize>>,
+    current: &mut [usize],
+    index: usize,
+    remaining: usize,
+) {
+    if index + 1 == current.len() {
+        current[index] = remaining;
+        exponents.push(current.to_vec());
+    } else if index < current.len() {
+        for e in (0..=remaining).rev() {
+            current[index] = e;
+            push_exponents_with_total_degree(exponents, current, index + 1, remaining - e);
+        }
+    }
+}
@@ -3,17 +3,45 @@
 use crate::allocators::BiDimAllocator;
 use crate::element::{ClosestPoint, FiniteElement, ReferenceFiniteElement};
 use crate::geometry::GeometryCollection;
+use crate::index::{node_dofs, DofIndex, ElementIndex, NodeIndex};
 use crate::nalgebra::{Dyn, MatrixViewMut, OMatrix};
 use crate::SmallDim;
 use fenris_geometry::AxisAlignedBoundingBox;
 use nalgebra::{DefaultAllocator, OPoint, Scalar};
+use num::Zero;
 
+mod affine_cache;
+mod bubble;
+mod directors;
+mod discontinuous;
+mod hierarchical;
 mod interpolate;
+mod lagrange;
+mod mls;
+mod moving;
+mod piecewise_constant;
+mod remap;
+mod scattered;
 mod space_impl;
-mod spatially_indexed;
+pub(crate) mod spatially_indexed;
+mod taylor_hood;
+mod transfer;
 
+pub use affine_cache::AffineGeometryCache;
+pub use bubble::BubbleEnrichedSpace;
+pub use directors::*;
+pub use discontinuous::DiscontinuousSpace;
+pub use hierarchical::*;
 pub use interpolate::*;
+pub use lagrange::LagrangeSimplexSpace;
+pub use mls::*;
+pub use moving::MovingMeshSpace;
+pub use piecewise_constant::PiecewiseConstantSpace;
+pub use remap::*;
+pub use scattered::ScatteredDataFit;
 pub use spatially_indexed::SpatiallyIndexed;
+pub use taylor_hood::*;
+pub use transfer::*;
 
 /// Describes the connectivity of elements in a finite element space.
 pub trait FiniteElementConnectivity {
@@ -21,9 +49,26 @@ pub trait FiniteElementConnectivity {
 
     fn num_nodes(&self) -> usize;
 
-    fn element_node_count(&self, element_index: usize) -> usize;
+    fn element_node_count(&self, element_index: ElementIndex) -> usize;
 
-    fn populate_element_nodes(&self, nodes: &mut [usize], element_index: usize);
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex);
+
+    /// The nodes of the given element.
+    ///
+    /// This is an allocating alternative to
+    /// [`populate_element_nodes`](Self::populate_element_nodes).
+    fn element_node_indices(&self, element: ElementIndex) -> Vec<NodeIndex> {
+        let mut nodes = vec![NodeIndex::default(); self.element_node_count(element)];
+        self.populate_element_nodes(&mut nodes, element);
+        nodes
+    }
+
+    /// The degrees of freedom of the given element for a field with the given solution dimension.
+    ///
+    /// The degrees of freedom are ordered by node, and by component for each node.
+    fn element_dof_indices(&self, element: ElementIndex, solution_dim: usize) -> Vec<DofIndex> {
+        node_dofs(&self.element_node_indices(element), solution_dim)
+    }
 }
 
 /// A finite element space.
@@ -73,6 +118,31 @@ where
     ///  h = min |x - y| for x, y in K
     /// where K is the element and h is the diameter.
     fn diameter(&self, element_index: usize) -> T;
+
+    /// Returns whether the transformation from the reference element to the given element is
+    /// affine.
+    ///
+    /// See [`FiniteElement::is_affine`]. The default implementation returns `false`.
+    fn is_element_affine(&self, _element_index: usize) -> bool {
+        false
+    }
+
+    /// Returns the Jacobian of the transformation from the reference element to the given
+    /// element if it is known to be constant over the element.
+    ///
+    /// See [`FiniteElement::constant_reference_jacobian`]. The default implementation returns
+    /// the Jacobian at the origin for [affine](Self::is_element_affine) elements and `None`
+    /// otherwise.
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.is_element_affine(element_index)
+            .then(|| self.element_reference_jacobian(element_index, &OPoint::origin()))
+    }
 }
 
 /// A finite element space where `GeometryDim == ReferenceDim`.
@@ -127,7 +197,7 @@ where
     type ReferenceDim = Space::ReferenceDim;
 
     fn num_nodes(&self) -> usize {
-        self.space.element_node_count(self.element_index)
+        self.space.element_node_count(self.element_index.into())
     }
 
     fn populate_basis(&self, basis_values: &mut [T], reference_coords: &OPoint<T, Self::ReferenceDim>) {
@@ -169,6 +239,18 @@ where
     fn diameter(&self) -> T {
         self.space.diameter(self.element_index)
     }
+
+    fn is_affine(&self) -> bool {
+        self.space.is_element_affine(self.element_index)
+    }
+
+    fn constant_reference_jacobian(&self) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.space
+            .element_constant_reference_jacobian(self.element_index)
+    }
 }
 
 /// A finite element space you can query for the closest point in an element to a given point.
@@ -0,0 +1,287 @@
+use crate::allocators::{DimAllocator, ElementConnectivityAllocator};
+use crate::element::{map_physical_coordinates, ElementConnectivity};
+use crate::index::{ElementIndex, NodeIndex};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DVector, DefaultAllocator, DimMin, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar};
+use crate::space::{FiniteElementConnectivity, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+
+/// A finite element space on a mesh that moves with a prescribed motion.
+///
+/// Problems on moving domains are commonly formulated in an *Arbitrary Lagrangian-Eulerian*
+/// (ALE) framework, in which the mesh moves independently of the material. Over a time step
+/// from $t^n$ to $t^{n+1}$, each vertex moves from its position $x^n_I$ to $x^{n+1}_I$, and the
+/// (discrete) mesh velocity is the finite element function with nodal values
+/// <div>$$
+/// w_I = \frac{x^{n+1}_I - x^n_I}{t^{n+1} - t^n}.
+/// $$</div>
+/// Since both configurations share the same connectivity, a point with reference coordinates
+/// $\xi$ in element $K$ is identified with the points $x^n(\xi)$ and $x^{n+1}(\xi)$ in the
+/// two configurations, which gives the mapping between them.
+///
+/// `MovingMeshSpace` stores the mesh in the current configuration at $t^{n+1}$, which is also
+/// the configuration used when the wrapper is used as a [`FiniteElementSpace`], together with
+/// the vertex positions at $t^n$, the vertex positions in the initial (reference)
+/// configuration and the nodal mesh velocity. The mesh velocity is typically subtracted from
+/// the transport velocity in convective terms, see e.g.
+/// [`ElementConvectionAssembler::with_mesh_velocity`](crate::assembly::local::ElementConvectionAssembler::with_mesh_velocity).
+///
+/// The mesh is moved either by prescribing new vertex positions with
+/// [`move_vertices`](Self::move_vertices), or by prescribing the motion $x = \varphi(X, t)$ of
+/// the reference configuration with [`move_with`](Self::move_with).
+#[derive(Debug, Clone)]
+pub struct MovingMeshSpace<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    reference_vertices: Vec<OPoint<T, D>>,
+    previous_vertices: Vec<OPoint<T, D>>,
+    mesh_velocity: DVector<T>,
+    time: T,
+    time_step: T,
+}
+
+impl<T, D, C> MovingMeshSpace<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: Clone,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Creates a space on the given mesh at rest at time $t = 0$.
+    ///
+    /// The vertices of the mesh define the reference configuration.
+    pub fn from_mesh(mesh: Mesh<T, D, C>) -> Self {
+        let num_vertices = mesh.vertices().len();
+        Self {
+            reference_vertices: mesh.vertices().to_vec(),
+            previous_vertices: mesh.vertices().to_vec(),
+            mesh,
+            mesh_velocity: DVector::zeros(D::dim() * num_vertices),
+            time: T::zero(),
+            time_step: T::zero(),
+        }
+    }
+
+    /// Sets the current time.
+    pub fn with_time(self, time: T) -> Self {
+        Self { time, ..self }
+    }
+
+    /// The mesh in the current configuration.
+    pub fn mesh(&self) -> &Mesh<T, D, C> {
+        &self.mesh
+    }
+
+    /// The vertex positions in the reference configuration.
+    pub fn reference_vertices(&self) -> &[OPoint<T, D>] {
+        &self.reference_vertices
+    }
+
+    /// The vertex positions in the previous configuration, i.e. before the last move.
+    pub fn previous_vertices(&self) -> &[OPoint<T, D>] {
+        &self.previous_vertices
+    }
+
+    /// The mesh in the previous configuration, i.e. before the last move.
+    pub fn previous_mesh(&self) -> Mesh<T, D, C> {
+        Mesh::from_vertices_and_connectivity(self.previous_vertices.clone(), self.mesh.connectivity().to_vec())
+    }
+
+    /// The mesh in the intermediate configuration $x^{n + \theta} = (1 - \theta) x^n + \theta x^{n+1}$.
+    ///
+    /// Intermediate configurations are required by time integration schemes that satisfy the
+    /// *geometric conservation law*, such as the ALE midpoint rule with $\theta = 1/2$.
+    pub fn intermediate_mesh(&self, theta: T) -> Mesh<T, D, C> {
+        let vertices = self
+            .previous_vertices
+            .iter()
+            .zip(self.mesh.vertices())
+            .map(|(x_prev, x)| x_prev + (x - x_prev) * theta)
+            .collect();
+        Mesh::from_vertices_and_connectivity(vertices, self.mesh.connectivity().to_vec())
+    }
+
+    /// The nodal mesh velocity over the last time step, stored vertex by vertex.
+    ///
+    /// The mesh velocity is zero before the mesh has been moved.
+    pub fn mesh_velocity(&self) -> &DVector<T> {
+        &self.mesh_velocity
+    }
+
+    /// The current time $t^{n+1}$.
+    pub fn time(&self) -> T {
+        self.time
+    }
+
+    /// The length of the last time step $t^{n+1} - t^n$, or zero if the mesh has not been moved.
+    pub fn time_step(&self) -> T {
+        self.time_step
+    }
+
+    /// Moves the mesh to the given vertex positions over a time step of the given length.
+    ///
+    /// The current configuration becomes the previous configuration, and the mesh velocity
+    /// is updated accordingly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of vertices does not match the mesh or if the time step
+    /// is not positive. The space is left unchanged in this case.
+    pub fn move_vertices(&mut self, vertices: Vec<OPoint<T, D>>, time_step: T) -> eyre::Result<()> {
+        let num_vertices = self.mesh.vertices().len();
+        if vertices.len() != num_vertices {
+            return Err(eyre!(
+                "Expected {num_vertices} vertices, but {} vertices were provided",
+                vertices.len()
+            ));
+        }
+        if time_step <= T::zero() {
+            return Err(eyre!("Time step must be positive"));
+        }
+
+        let d = D::dim();
+        for (i, (x_new, x)) in vertices.iter().zip(self.mesh.vertices()).enumerate() {
+            let w = (x_new - x) / time_step;
+            self.mesh_velocity.rows_mut(d * i, d).copy_from(&w);
+        }
+        self.previous_vertices
+            .clone_from_slice(self.mesh.vertices());
+        self.mesh.vertices_mut().clone_from_slice(&vertices);
+        self.time += time_step;
+        self.time_step = time_step;
+        Ok(())
+    }
+
+    /// Moves the mesh to the given time according to the prescribed motion of the reference
+    /// configuration.
+    ///
+    /// The motion maps a point $X$ in the reference configuration to its position
+    /// $x = \varphi(X, t)$ at time $t$.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new time is not later than the current time.
+    pub fn move_with(&mut self, motion: impl Fn(&OPoint<T, D>, T) -> OPoint<T, D>, new_time: T) -> eyre::Result<()> {
+        let vertices = self
+            .reference_vertices
+            .iter()
+            .map(|x| motion(x, new_time))
+            .collect();
+        self.move_vertices(vertices, new_time - self.time)
+    }
+}
+
+impl<T, D, C> MovingMeshSpace<T, D, C>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C>,
+{
+    /// Maps a point in the given element in the previous configuration to the corresponding
+    /// point in the current configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference coordinates of the point could not be determined.
+    pub fn map_previous_to_current(&self, element_index: usize, x: &OPoint<T, D>) -> eyre::Result<OPoint<T, D>> {
+        let connectivity = self
+            .mesh
+            .connectivity()
+            .get(element_index)
+            .ok_or_else(|| eyre!("Element index {element_index} out of bounds"))?;
+        let previous_element = connectivity
+            .element(&self.previous_vertices)
+            .ok_or_else(|| eyre!("Invalid element connectivity"))?;
+        let xi = map_physical_coordinates(&previous_element, x)
+            .map_err(|err| eyre!("Failed to map point to reference coordinates: {err}"))?;
+        Ok(self.mesh.map_element_reference_coords(element_index, &xi))
+    }
+}
+
+impl<T, D, C> FiniteElementConnectivity for MovingMeshSpace<T, D, C>
+where
+    T: Scalar,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C>,
+{
+    fn num_elements(&self) -> usize {
+        self.mesh.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.mesh.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.mesh.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        self.mesh.populate_element_nodes(nodes, element_index)
+    }
+}
+
+impl<T, D, C> FiniteElementSpace<T> for MovingMeshSpace<T, D, C>
+where
+    T: Scalar,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    C::ReferenceDim: SmallDim,
+    DefaultAllocator: ElementConnectivityAllocator<T, C>,
+{
+    type GeometryDim = D;
+    type ReferenceDim = C::ReferenceDim;
+
+    fn populate_element_basis(
+        &self,
+        element_index: usize,
+        basis_values: &mut [T],
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.mesh
+            .populate_element_basis(element_index, basis_values, reference_coords)
+    }
+
+    fn populate_element_gradients(
+        &self,
+        element_index: usize,
+        gradients: MatrixViewMut<T, Self::ReferenceDim, Dyn>,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        self.mesh
+            .populate_element_gradients(element_index, gradients, reference_coords)
+    }
+
+    fn element_reference_jacobian(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.mesh
+            .element_reference_jacobian(element_index, reference_coords)
+    }
+
+    fn map_element_reference_coords(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OPoint<T, Self::GeometryDim> {
+        self.mesh
+            .map_element_reference_coords(element_index, reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.mesh.diameter(element_index)
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.mesh.is_element_affine(element_index)
+    }
+}
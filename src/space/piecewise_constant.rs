@@ -0,0 +1,318 @@
+use crate::assembly::buffers::QuadratureBuffer;
+use crate::assembly::local::QuadratureTable;
+use crate::element::ClosestPoint;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::space::{
+    BoundsForElementInSpace, ClosestPointInElementInSpace, FiniteElementConnectivity, FiniteElementSpace,
+    VolumetricFiniteElementSpace,
+};
+use eyre::eyre;
+use fenris_geometry::AxisAlignedBoundingBox;
+use fenris_traits::allocators::BiDimAllocator;
+use fenris_traits::Real;
+use itertools::izip;
+use nalgebra::{DVector, DVectorView, DefaultAllocator, Dyn, MatrixViewMut, OMatrix, OPoint, Scalar};
+use nalgebra_sparse::CsrMatrix;
+use num::{One, Zero};
+
+/// A space of piecewise constant functions, with a single node per element.
+///
+/// The piecewise constant space, often denoted P0 or DG0, is the lowest-order discontinuous
+/// finite element space. It is the natural home of quantities that are only defined per
+/// element, such as densities, error indicators or summaries of material state.
+/// `PiecewiseConstantSpace` wraps a finite element space that provides the geometry of the
+/// elements, and the node of element $e$ has index $e$. The basis function of each element is
+/// the constant $1$, so that a function in the space is given by its value on each element.
+///
+/// Since the space implements [`FiniteElementSpace`], it may be used with the usual assemblers
+/// and interpolation routines. The mass matrix of the space is diagonal, and is directly
+/// available through [`assemble_mass_matrix`](Self::assemble_mass_matrix). The values of a
+/// P0 function over a mesh are stored cell by cell, which is the layout expected by
+/// [`FieldExporter::with_piecewise_constant_field`](crate::io::vtk::FieldExporter::with_piecewise_constant_field).
+#[derive(Debug, Clone)]
+pub struct PiecewiseConstantSpace<Space> {
+    space: Space,
+}
+
+impl<Space> PiecewiseConstantSpace<Space> {
+    pub fn from_space(space: Space) -> Self {
+        Self { space }
+    }
+
+    /// The underlying space that provides the geometry of the elements.
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+
+    /// Computes the measure (length, area or volume) of each element with the given quadrature.
+    pub fn element_measures<T, QTable>(&self, qtable: &QTable) -> DVector<T>
+    where
+        T: Real,
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let mut quadrature_buffer = QuadratureBuffer::<T, Space::ReferenceDim>::default();
+        DVector::from_iterator(
+            self.space.num_elements(),
+            (0..self.space.num_elements()).map(|element_index| {
+                quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+                izip!(quadrature_buffer.weights(), quadrature_buffer.points())
+                    .map(|(&w, xi)| {
+                        let j_det = self
+                            .space
+                            .element_reference_jacobian(element_index, xi)
+                            .determinant()
+                            .abs();
+                        w * j_det
+                    })
+                    .fold(T::zero(), |a, b| a + b)
+            }),
+        )
+    }
+
+    /// Assembles the (diagonal) mass matrix of the space for the given solution dimension.
+    ///
+    /// The diagonal entries associated with element $e$ are given by the measure of the element,
+    /// computed with [`element_measures`](Self::element_measures).
+    pub fn assemble_mass_matrix<T, QTable>(&self, qtable: &QTable, solution_dim: usize) -> CsrMatrix<T>
+    where
+        T: Real,
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let measures = self.element_measures(qtable);
+        let mut mass = CsrMatrix::identity(solution_dim * measures.len());
+        for (entry, i) in mass.values_mut().iter_mut().zip(0..) {
+            *entry = measures[i / solution_dim];
+        }
+        mass
+    }
+
+    /// Computes the $L^2$ projection of a field sampled at quadrature points onto the space.
+    ///
+    /// The projection of a field $f$ onto the space is the element-wise average
+    /// <div>$$
+    /// f_K = \frac{1}{|K|} \int_K f \enspace \mathrm{d}x,
+    /// $$</div>
+    /// where the integral is approximated with the quadrature table. The function `sample` is
+    /// called for each element and each (reference) quadrature point of the quadrature table and
+    /// must return the `field_dim` components of the field at the point. The result stores the
+    /// `field_dim` components of each element consecutively.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `sample` returns an error or a vector of the wrong length, or if the
+    /// measure of an element is not positive.
+    pub fn project_l2<T, QTable>(
+        &self,
+        qtable: &QTable,
+        field_dim: usize,
+        mut sample: impl FnMut(usize, &OPoint<T, Space::ReferenceDim>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<DVector<T>>
+    where
+        T: Real,
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let mut quadrature_buffer = QuadratureBuffer::<T, Space::ReferenceDim>::default();
+        let mut result = DVector::zeros(field_dim * self.space.num_elements());
+        for element_index in 0..self.space.num_elements() {
+            quadrature_buffer.populate_element_weights_and_points_from_table(element_index, qtable);
+            let mut measure = T::zero();
+            let mut integral = DVector::zeros(field_dim);
+            for (&w, xi) in izip!(quadrature_buffer.weights(), quadrature_buffer.points()) {
+                let value = sample(element_index, xi)?;
+                if value.len() != field_dim {
+                    return Err(eyre!(
+                        "Sampled field has {} components, expected {}",
+                        value.len(),
+                        field_dim
+                    ));
+                }
+                let j_det = self
+                    .space
+                    .element_reference_jacobian(element_index, xi)
+                    .determinant()
+                    .abs();
+                measure += w * j_det;
+                integral.axpy(w * j_det, &value, T::one());
+            }
+            if measure <= T::zero() {
+                return Err(eyre!("Element {element_index} has non-positive measure"));
+            }
+            result
+                .rows_mut(field_dim * element_index, field_dim)
+                .copy_from(&(integral / measure));
+        }
+        Ok(result)
+    }
+
+    /// Computes the $L^2$ projection of a field in the underlying space onto the space.
+    ///
+    /// The field is given by its nodal values `u` in the underlying space, with `solution_dim`
+    /// components per node. See [`project_l2`](Self::project_l2) for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the measure of an element is not positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the vector does not match the number of nodes in the
+    /// underlying space.
+    pub fn project_from_space<'a, T, QTable>(
+        &self,
+        qtable: &QTable,
+        u: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> eyre::Result<DVector<T>>
+    where
+        T: Real,
+        Space: VolumetricFiniteElementSpace<T>,
+        QTable: QuadratureTable<T, Space::ReferenceDim>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let u = u.into();
+        let s = solution_dim;
+        assert_eq!(
+            u.len(),
+            s * self.space.num_nodes(),
+            "Dimension of vector must match number of nodes in underlying space"
+        );
+        let mut nodes = Vec::new();
+        let mut basis = Vec::new();
+        self.project_l2(qtable, s, |element_index, xi| {
+            let n = self.space.element_node_count(element_index.into());
+            nodes.resize(n, 0);
+            basis.resize(n, T::zero());
+            self.space
+                .populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+            self.space
+                .populate_element_basis(element_index, &mut basis, xi);
+            let mut value = DVector::zeros(s);
+            for (&node, &phi) in izip!(&nodes, &basis) {
+                value.axpy(phi, &u.rows(s * node, s), T::one());
+            }
+            Ok(value)
+        })
+    }
+}
+
+impl<Space: FiniteElementConnectivity> FiniteElementConnectivity for PiecewiseConstantSpace<Space> {
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn element_node_count(&self, _element_index: ElementIndex) -> usize {
+        1
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
+        assert_eq!(nodes.len(), 1);
+        nodes[0] = NodeIndex::new(element_index.index());
+    }
+}
+
+impl<T, Space> FiniteElementSpace<T> for PiecewiseConstantSpace<Space>
+where
+    T: Scalar + Zero + One,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    type GeometryDim = Space::GeometryDim;
+    type ReferenceDim = Space::ReferenceDim;
+
+    fn populate_element_basis(
+        &self,
+        _element_index: usize,
+        basis_values: &mut [T],
+        _reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        assert_eq!(basis_values.len(), 1);
+        basis_values[0] = T::one();
+    }
+
+    fn populate_element_gradients(
+        &self,
+        _element_index: usize,
+        mut gradients: MatrixViewMut<T, Self::ReferenceDim, Dyn>,
+        _reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) {
+        assert_eq!(gradients.ncols(), 1);
+        gradients.fill(T::zero());
+    }
+
+    fn element_reference_jacobian(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.space
+            .element_reference_jacobian(element_index, reference_coords)
+    }
+
+    fn map_element_reference_coords(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+    ) -> OPoint<T, Self::GeometryDim> {
+        self.space
+            .map_element_reference_coords(element_index, reference_coords)
+    }
+
+    fn diameter(&self, element_index: usize) -> T {
+        self.space.diameter(element_index)
+    }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.space.is_element_affine(element_index)
+    }
+
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.space
+            .element_constant_reference_jacobian(element_index)
+    }
+}
+
+impl<T, Space> ClosestPointInElementInSpace<T> for PiecewiseConstantSpace<Space>
+where
+    T: Real,
+    Space: ClosestPointInElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn closest_point_in_element(
+        &self,
+        element_index: usize,
+        p: &OPoint<T, Self::GeometryDim>,
+    ) -> ClosestPoint<T, Self::ReferenceDim> {
+        self.space.closest_point_in_element(element_index, p)
+    }
+}
+
+impl<T, Space> BoundsForElementInSpace<T> for PiecewiseConstantSpace<Space>
+where
+    T: Real,
+    Space: BoundsForElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn bounds_for_element(&self, element_index: usize) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        self.space.bounds_for_element(element_index)
+    }
+}
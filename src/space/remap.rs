@@ -0,0 +1,455 @@
+use crate::allocators::DimAllocator;
+use crate::connectivity::{CellConnectivity, Connectivity, Hex8Connectivity, Tet4Connectivity};
+use crate::element::{map_physical_coordinates, ElementConnectivity, ReferenceFiniteElement};
+use crate::geometry::polymesh::PolyMesh3d;
+use crate::geometry::{AxisAlignedBoundingBox, ConvexPolygon, Hexahedron, Quad2d, Tetrahedron, Triangle2d};
+use crate::linear_solver::{ConjugateGradientSolver, LinearSolver};
+use crate::mesh::{HexMesh, Mesh, Tet4Mesh};
+use crate::quadrature::QuadraturePair;
+use crate::space::spatially_indexed::RTreeAccelerationStructure;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DVector, DVectorView, DefaultAllocator, DimMin, OMatrix, OPoint, Point3, U2, U3};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+/// Quadrature rules for the intersection of two cells.
+///
+/// The intersection of two convex cells is a convex polytope, which is decomposed into
+/// simplices. Each simplex is integrated with a given quadrature rule for the reference simplex,
+/// i.e. the reference triangle or tetrahedron with vertices at $-1$ and $1$. Since the simplices
+/// exactly cover the intersection, the resulting quadrature integrates polynomials up to the
+/// strength of the simplex rule exactly over the intersection.
+///
+/// Cells with curved or non-planar faces, such as quadrilaterals and hexahedra that are not
+/// parallelograms or parallelepipeds, are approximated by their planar faces.
+pub trait CellIntersectionQuadrature<T, D, Other>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Computes a quadrature in physical coordinates for the intersection of this cell and
+    /// the other cell.
+    ///
+    /// The quadrature is empty if the cells do not intersect, or if the intersection has
+    /// zero volume.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the cells is not convex.
+    fn intersection_quadrature(
+        &self,
+        other: &Other,
+        simplex_quadrature: &QuadraturePair<T, D>,
+    ) -> eyre::Result<QuadraturePair<T, D>>;
+}
+
+macro_rules! impl_polygon_intersection_quadrature {
+    ($cell:ident, $other:ident) => {
+        impl<T: Real> CellIntersectionQuadrature<T, U2, $other<T>> for $cell<T> {
+            fn intersection_quadrature(
+                &self,
+                other: &$other<T>,
+                simplex_quadrature: &QuadraturePair<T, U2>,
+            ) -> eyre::Result<QuadraturePair<T, U2>> {
+                let intersection = convex_polygon(*self)?.intersect_polygon(&convex_polygon(*other)?);
+                let mut quadrature = (Vec::new(), Vec::new());
+                for triangle in intersection.triangulate() {
+                    push_simplex_quadrature(&mut quadrature, &triangle.0, simplex_quadrature);
+                }
+                Ok(quadrature)
+            }
+        }
+    };
+}
+
+impl_polygon_intersection_quadrature!(Triangle2d, Triangle2d);
+impl_polygon_intersection_quadrature!(Triangle2d, Quad2d);
+impl_polygon_intersection_quadrature!(Quad2d, Triangle2d);
+impl_polygon_intersection_quadrature!(Quad2d, Quad2d);
+
+macro_rules! impl_polyhedron_intersection_quadrature {
+    ($cell:ident, $other:ident) => {
+        impl<T: Real> CellIntersectionQuadrature<T, U3, $other<T>> for $cell<T> {
+            fn intersection_quadrature(
+                &self,
+                other: &$other<T>,
+                simplex_quadrature: &QuadraturePair<T, U3>,
+            ) -> eyre::Result<QuadraturePair<T, U3>> {
+                let intersection = self.poly_mesh().intersect_convex_polyhedron(other);
+                Ok(polyhedron_quadrature(&intersection, simplex_quadrature))
+            }
+        }
+    };
+}
+
+impl_polyhedron_intersection_quadrature!(Tetrahedron, Tetrahedron);
+impl_polyhedron_intersection_quadrature!(Tetrahedron, Hexahedron);
+impl_polyhedron_intersection_quadrature!(Hexahedron, Tetrahedron);
+impl_polyhedron_intersection_quadrature!(Hexahedron, Hexahedron);
+
+fn convex_polygon<T: Real>(cell: impl TryInto<ConvexPolygon<T>>) -> eyre::Result<ConvexPolygon<T>> {
+    cell.try_into()
+        .map_err(|_| eyre!("Cannot intersect non-convex cell"))
+}
+
+trait PolyhedralCell<T: Real> {
+    fn poly_mesh(&self) -> PolyMesh3d<T>;
+}
+
+impl<T: Real> PolyhedralCell<T> for Tetrahedron<T> {
+    fn poly_mesh(&self) -> PolyMesh3d<T> {
+        let mesh =
+            Tet4Mesh::from_vertices_and_connectivity(self.vertices().to_vec(), vec![Tet4Connectivity([0, 1, 2, 3])]);
+        PolyMesh3d::from(&mesh)
+    }
+}
+
+impl<T: Real> PolyhedralCell<T> for Hexahedron<T> {
+    fn poly_mesh(&self) -> PolyMesh3d<T> {
+        let mesh = HexMesh::from_vertices_and_connectivity(
+            self.vertices().to_vec(),
+            vec![Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7])],
+        );
+        PolyMesh3d::from(&mesh)
+    }
+}
+
+/// Integrates a convex polyhedron by connecting one of its vertices to all (triangulated) faces.
+///
+/// Unlike a proper tetrahedralization, this also works when faces are coplanar, in which case
+/// some of the tetrahedra are degenerate and do not contribute to the quadrature.
+fn polyhedron_quadrature<T: Real>(
+    polyhedron: &PolyMesh3d<T>,
+    simplex_quadrature: &QuadraturePair<T, U3>,
+) -> QuadraturePair<T, U3> {
+    let mut quadrature = (Vec::new(), Vec::new());
+    for cell in polyhedron.cell_connectivity_iter() {
+        let faces: Vec<Vec<Point3<T>>> = cell
+            .iter()
+            .map(|&face_idx| polyhedron.face_vertices(face_idx).copied().collect())
+            .filter(|face: &Vec<_>| face.len() >= 3)
+            .collect();
+        let Some(apex) = faces.first().map(|face| face[0]) else {
+            continue;
+        };
+        for face in &faces {
+            for i in 1..face.len() - 1 {
+                let tet = [apex, face[0], face[i], face[i + 1]];
+                push_simplex_quadrature(&mut quadrature, &tet, simplex_quadrature);
+            }
+        }
+    }
+    quadrature
+}
+
+fn cell_bounds<T, D>(vertices: &[OPoint<T, D>], conn: &impl Connectivity) -> AxisAlignedBoundingBox<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    AxisAlignedBoundingBox::from_points(conn.vertex_indices().iter().map(|&i| &vertices[i]))
+        .expect("Cells must have at least one vertex")
+}
+
+/// Maps the quadrature for the reference simplex to the simplex with the given vertices.
+fn push_simplex_quadrature<T, D>(
+    quadrature: &mut QuadraturePair<T, D>,
+    vertices: &[OPoint<T, D>],
+    simplex_quadrature: &QuadraturePair<T, D>,
+) where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    DefaultAllocator: DimAllocator<T, D> + Allocator<T, D, D>,
+{
+    let a = &vertices[0];
+    let edges = OMatrix::<T, D, D>::from_fn(|i, j| vertices[j + 1][i] - a[i]);
+    // The reference simplex has edges of length 2, so the Jacobian is half the edge matrix
+    let two = T::from_f64(2.0).unwrap();
+    let det = edges.determinant().abs() / two.powi(D::dim() as i32);
+    if det == T::zero() {
+        return;
+    }
+    let (weights, points) = simplex_quadrature;
+    for (w, xi) in weights.iter().zip(points) {
+        let shifted = xi.coords.map(|xi_i| (xi_i + T::one()) / two);
+        quadrature.0.push(*w * det);
+        quadrature.1.push(a + &edges * shifted);
+    }
+}
+
+/// Quadrature for the intersection of a target cell and a source cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellIntersection<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub target_cell: usize,
+    pub source_cell: usize,
+    /// Quadrature weights and points in physical coordinates.
+    pub quadrature: QuadraturePair<T, D>,
+}
+
+/// Computes quadrature rules for all non-empty intersections of target and source cells.
+///
+/// Together, the intersections form a *supermesh* of the two meshes, whose cells are each
+/// contained in exactly one target and one source cell. Integrals over the supermesh can
+/// therefore involve functions defined on both meshes, as long as they are smooth on each cell
+/// of the respective mesh. Candidate pairs of cells are found with a bounding volume hierarchy
+/// over the source cells.
+///
+/// # Errors
+///
+/// Returns an error if a cell is not convex.
+pub fn mesh_intersection_quadrature<T, D, SourceConnectivity, TargetConnectivity>(
+    source_mesh: &Mesh<T, D, SourceConnectivity>,
+    target_mesh: &Mesh<T, D, TargetConnectivity>,
+    simplex_quadrature: &QuadraturePair<T, D>,
+) -> eyre::Result<Vec<CellIntersection<T, D>>>
+where
+    T: Real,
+    D: SmallDim,
+    SourceConnectivity: CellConnectivity<T, D>,
+    TargetConnectivity: CellConnectivity<T, D>,
+    TargetConnectivity::Cell: CellIntersectionQuadrature<T, D, SourceConnectivity::Cell>,
+    DefaultAllocator: DimAllocator<T, D> + Allocator<f64, D>,
+{
+    let source_bounds: Vec<_> = source_mesh
+        .connectivity()
+        .iter()
+        .map(|conn| cell_bounds(source_mesh.vertices(), conn))
+        .collect();
+    let source_tree = RTreeAccelerationStructure::from_bounding_boxes(&source_bounds);
+
+    let mut intersections = Vec::new();
+    for (target_cell, target_conn) in target_mesh.connectivity().iter().enumerate() {
+        let target_bounds = cell_bounds(target_mesh.vertices(), target_conn);
+        let target_geometry = target_conn
+            .cell(target_mesh.vertices())
+            .ok_or_else(|| eyre!("Failed to construct geometry of target cell {target_cell}"))?;
+        let mut candidates = source_tree.intersecting_cell_candidates(&target_bounds);
+        candidates.sort_unstable();
+        for source_cell in candidates {
+            let source_geometry = source_mesh.connectivity()[source_cell]
+                .cell(source_mesh.vertices())
+                .ok_or_else(|| eyre!("Failed to construct geometry of source cell {source_cell}"))?;
+            let quadrature = target_geometry
+                .intersection_quadrature(&source_geometry, simplex_quadrature)
+                .map_err(|err| {
+                    err.wrap_err(format!(
+                        "Failed to intersect target cell {target_cell} with source cell {source_cell}"
+                    ))
+                })?;
+            if !quadrature.0.is_empty() {
+                intersections.push(CellIntersection {
+                    target_cell,
+                    source_cell,
+                    quadrature,
+                });
+            }
+        }
+    }
+    Ok(intersections)
+}
+
+/// Conservative (Galerkin) remap of finite element functions between non-matching meshes.
+///
+/// Given a function $u_s$ on the source mesh, the remap computes the $L^2$ projection $u_t$ onto
+/// the finite element space of the target mesh, i.e. the function that satisfies
+/// <div>$$
+/// \int_{\Omega_t} u_t \, \varphi_i \, \mathrm{d}x = \int_{\Omega_t} u_s \, \varphi_i \, \mathrm{d}x
+/// $$</div>
+/// for all target basis functions $\varphi_i$, where $u_s$ is taken to be zero outside the
+/// source mesh. In matrix form, this reads $\vec M \vec u_t = \vec B \vec u_s$, where $\vec M$
+/// is the mass matrix of the target mesh and $\vec B$ is the mixed mass matrix between the target
+/// and source basis functions. The mixed mass matrix is integrated exactly (up to the strength
+/// of the simplex quadrature) over the [intersections](mesh_intersection_quadrature) of
+/// target and source cells.
+///
+/// Since the target basis functions form a partition of unity, summing the equations gives
+/// $\int u_t = \int u_s$ over the target domain, i.e. the remap preserves the integral of the
+/// transferred field. In contrast, interpolation at the target nodes does not. The matrices are
+/// computed once, so that many fields can be remapped between the same meshes cheaply.
+#[derive(Debug, Clone)]
+pub struct ConservativeRemap<T: Real, Solver = ConjugateGradientSolver<T>> {
+    target_mass_matrix: CsrMatrix<T>,
+    mixed_mass_matrix: CsrMatrix<T>,
+    solver: Solver,
+}
+
+impl<T: Real> ConservativeRemap<T> {
+    /// Assembles the matrices for remapping from the source mesh to the target mesh.
+    ///
+    /// The simplex quadrature is used on the simplices of the cell intersections, and should be
+    /// strong enough to integrate products of source and target basis functions exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cell is not convex, or if the mapping of a quadrature point to the
+    /// reference coordinates of a cell fails.
+    pub fn from_meshes<D, SourceConnectivity, TargetConnectivity>(
+        source_mesh: &Mesh<T, D, SourceConnectivity>,
+        target_mesh: &Mesh<T, D, TargetConnectivity>,
+        simplex_quadrature: &QuadraturePair<T, D>,
+    ) -> eyre::Result<Self>
+    where
+        D: SmallDim + DimMin<D, Output = D>,
+        SourceConnectivity: CellConnectivity<T, D> + ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+        TargetConnectivity: CellConnectivity<T, D> + ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+        TargetConnectivity::Cell: CellIntersectionQuadrature<T, D, SourceConnectivity::Cell>
+            + CellIntersectionQuadrature<T, D, TargetConnectivity::Cell>,
+        DefaultAllocator: DimAllocator<T, D> + Allocator<f64, D>,
+    {
+        let num_target_nodes = target_mesh.vertices().len();
+        let num_source_nodes = source_mesh.vertices().len();
+
+        // The mass matrix is integrated over each target cell intersected with itself,
+        // so that it is computed with the same decomposition into simplices
+        let mut target_mass = CooMatrix::new(num_target_nodes, num_target_nodes);
+        for (cell, conn) in target_mesh.connectivity().iter().enumerate() {
+            let geometry = conn
+                .cell(target_mesh.vertices())
+                .ok_or_else(|| eyre!("Failed to construct geometry of target cell {cell}"))?;
+            let quadrature = geometry.intersection_quadrature(&geometry, simplex_quadrature)?;
+            let intersection = CellIntersection {
+                target_cell: cell,
+                source_cell: cell,
+                quadrature,
+            };
+            accumulate_cell_products(&mut target_mass, target_mesh, target_mesh, &intersection)?;
+        }
+
+        let mut mixed_mass = CooMatrix::new(num_target_nodes, num_source_nodes);
+        for intersection in mesh_intersection_quadrature(source_mesh, target_mesh, simplex_quadrature)? {
+            accumulate_cell_products(&mut mixed_mass, target_mesh, source_mesh, &intersection)?;
+        }
+
+        Ok(Self {
+            target_mass_matrix: CsrMatrix::from(&target_mass),
+            mixed_mass_matrix: CsrMatrix::from(&mixed_mass),
+            solver: ConjugateGradientSolver::with_tolerance(T::from_f64(1e-12).unwrap()),
+        })
+    }
+}
+
+impl<T: Real, Solver: LinearSolver<T>> ConservativeRemap<T, Solver> {
+    /// Uses the given linear solver for the mass matrix system.
+    pub fn with_solver<Solver2: LinearSolver<T>>(self, solver: Solver2) -> ConservativeRemap<T, Solver2> {
+        ConservativeRemap {
+            target_mass_matrix: self.target_mass_matrix,
+            mixed_mass_matrix: self.mixed_mass_matrix,
+            solver,
+        }
+    }
+
+    /// Returns the target mass matrix, the mixed mass matrix and the linear solver.
+    pub fn into_parts(self) -> (CsrMatrix<T>, CsrMatrix<T>, Solver) {
+        (self.target_mass_matrix, self.mixed_mass_matrix, self.solver)
+    }
+
+    /// The (scalar) mass matrix $\vec M$ of the target mesh.
+    pub fn target_mass_matrix(&self) -> &CsrMatrix<T> {
+        &self.target_mass_matrix
+    }
+
+    /// The (scalar) mixed mass matrix $\vec B$, with rows corresponding to target nodes and
+    /// columns corresponding to source nodes.
+    pub fn mixed_mass_matrix(&self) -> &CsrMatrix<T> {
+        &self.mixed_mass_matrix
+    }
+
+    /// Remaps the interpolation weights of a field on the source mesh to the target mesh.
+    ///
+    /// Both the source weights and the returned target weights are stored in the usual
+    /// interleaved format with `solution_dim` components per node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of source weights is not compatible with the source mesh,
+    /// or if the linear solver fails.
+    pub fn remap(&mut self, source_weights: DVectorView<T>, solution_dim: usize) -> eyre::Result<DVector<T>> {
+        let num_source_nodes = self.mixed_mass_matrix.ncols();
+        let num_target_nodes = self.target_mass_matrix.nrows();
+        if source_weights.len() != solution_dim * num_source_nodes {
+            return Err(eyre!(
+                "Number of source weights ({}) does not match {} source nodes with solution dimension {}",
+                source_weights.len(),
+                num_source_nodes,
+                solution_dim
+            ));
+        }
+
+        let mut target_weights = DVector::zeros(solution_dim * num_target_nodes);
+        let mut component_weights = DVector::zeros(num_target_nodes);
+        for k in 0..solution_dim {
+            let source_component = DVector::from_iterator(
+                num_source_nodes,
+                source_weights.iter().skip(k).step_by(solution_dim).copied(),
+            );
+            let rhs = &self.mixed_mass_matrix * &source_component;
+            component_weights.fill(T::zero());
+            self.solver
+                .solve(&self.target_mass_matrix, component_weights.as_view_mut(), rhs.as_view())
+                .map_err(|err| err.wrap_err(format!("Failed to solve mass matrix system for component {k}")))?;
+            for (node, &w) in component_weights.iter().enumerate() {
+                target_weights[solution_dim * node + k] = w;
+            }
+        }
+        Ok(target_weights)
+    }
+}
+
+/// Accumulates $\int \varphi_i \psi_j$ over the intersection, where $\varphi_i$ and $\psi_j$ are
+/// the basis functions of the target and source cell, respectively.
+fn accumulate_cell_products<T, D, TargetConnectivity, SourceConnectivity>(
+    matrix: &mut CooMatrix<T>,
+    target_mesh: &Mesh<T, D, TargetConnectivity>,
+    source_mesh: &Mesh<T, D, SourceConnectivity>,
+    intersection: &CellIntersection<T, D>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    TargetConnectivity: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    SourceConnectivity: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let target_conn = &target_mesh.connectivity()[intersection.target_cell];
+    let source_conn = &source_mesh.connectivity()[intersection.source_cell];
+    let target_element = target_conn
+        .element(target_mesh.vertices())
+        .ok_or_else(|| eyre!("Failed to construct target element {}", intersection.target_cell))?;
+    let source_element = source_conn
+        .element(source_mesh.vertices())
+        .ok_or_else(|| eyre!("Failed to construct source element {}", intersection.source_cell))?;
+
+    let mut target_basis = vec![T::zero(); target_element.num_nodes()];
+    let mut source_basis = vec![T::zero(); source_element.num_nodes()];
+    let (weights, points) = &intersection.quadrature;
+    for (&w, x) in weights.iter().zip(points) {
+        let xi_target = map_physical_coordinates(&target_element, x).map_err(|err| {
+            eyre!(
+                "Failed to map point to target element {}: {err}",
+                intersection.target_cell
+            )
+        })?;
+        let xi_source = map_physical_coordinates(&source_element, x).map_err(|err| {
+            eyre!(
+                "Failed to map point to source element {}: {err}",
+                intersection.source_cell
+            )
+        })?;
+        target_element.populate_basis(&mut target_basis, &xi_target);
+        source_element.populate_basis(&mut source_basis, &xi_source);
+        for (&i, &phi_i) in target_conn.vertex_indices().iter().zip(&target_basis) {
+            for (&j, &psi_j) in source_conn.vertex_indices().iter().zip(&source_basis) {
+                matrix.push(i, j, w * phi_i * psi_j);
+            }
+        }
+    }
+    Ok(())
+}
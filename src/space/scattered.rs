@@ -0,0 +1,126 @@
+use crate::allocators::BiDimAllocator;
+use crate::linear_solver::{ConjugateGradientSolver, LinearSolver};
+use crate::space::{assemble_evaluation_matrix, FindClosestElement};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use nalgebra::{DVector, DefaultAllocator, OPoint, OVector};
+use nalgebra_sparse::CsrMatrix;
+
+/// Fits a finite element function to scattered point samples by regularized least squares.
+///
+/// Given samples $(\vec x_i, \vec v_i)$, e.g. from measurements or particle data, the fit
+/// computes the interpolation weights $\vec u$ of the finite element function that minimizes
+/// <div>$$
+/// \sum_i | u_h(\vec x_i) - \vec v_i |^2 + \lambda \, \vec u^T \vec R \vec u,
+/// $$</div>
+/// where $\lambda \geq 0$ is the regularization parameter and $\vec R$ is a symmetric positive
+/// semi-definite regularization matrix, applied to each component of the solution separately.
+/// By default $\vec R = \vec I$, which gives Tikhonov regularization. Passing e.g. the stiffness
+/// matrix of the Laplace operator instead penalizes the gradient of the function, which gives
+/// smoother fits.
+///
+/// The minimizer solves the normal equations $(\vec E^T \vec E + \lambda \vec R) \vec u = \vec
+/// E^T \vec v$, where $\vec E$ is the [evaluation matrix](assemble_evaluation_matrix) of the
+/// sample points. Without regularization, the normal equations are only solvable if the data
+/// determines all weights, i.e. every basis function must be supported at enough sample points.
+/// The normal equations are solved by default with the conjugate gradient method, but any
+/// [`LinearSolver`] can be used.
+#[derive(Debug, Clone)]
+pub struct ScatteredDataFit<'a, T: Real, Solver = ConjugateGradientSolver<T>> {
+    regularization: T,
+    regularization_matrix: Option<&'a CsrMatrix<T>>,
+    solver: Solver,
+}
+
+impl<'a, T: Real> ScatteredDataFit<'a, T> {
+    /// Creates a fit with the given regularization parameter $\lambda$.
+    pub fn with_regularization(regularization: T) -> Self {
+        Self {
+            regularization,
+            regularization_matrix: None,
+            solver: ConjugateGradientSolver::with_tolerance(T::from_f64(1e-12).unwrap()),
+        }
+    }
+}
+
+impl<'a, T: Real, Solver: LinearSolver<T>> ScatteredDataFit<'a, T, Solver> {
+    /// Uses the given (scalar) matrix $\vec R$ instead of the identity for regularization.
+    pub fn with_regularization_matrix(self, matrix: &'a CsrMatrix<T>) -> Self {
+        Self {
+            regularization_matrix: Some(matrix),
+            ..self
+        }
+    }
+
+    /// Uses the given linear solver for the normal equations.
+    pub fn with_solver<Solver2: LinearSolver<T>>(self, solver: Solver2) -> ScatteredDataFit<'a, T, Solver2> {
+        ScatteredDataFit {
+            regularization: self.regularization,
+            regularization_matrix: self.regularization_matrix,
+            solver,
+        }
+    }
+
+    /// Computes the interpolation weights that best fit the given samples.
+    ///
+    /// The weights are returned in the usual interleaved format with `SolutionDim` components
+    /// per node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of values does not match the number of points, if the
+    /// regularization matrix does not match the number of nodes, or if the linear solver fails.
+    pub fn fit<Space, SolutionDim>(
+        &mut self,
+        space: &Space,
+        points: &[OPoint<T, Space::GeometryDim>],
+        values: &[OVector<T, SolutionDim>],
+    ) -> eyre::Result<DVector<T>>
+    where
+        Space: FindClosestElement<T>,
+        SolutionDim: SmallDim,
+        DefaultAllocator:
+            BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim> + BiDimAllocator<T, SolutionDim, SolutionDim>,
+    {
+        if points.len() != values.len() {
+            return Err(eyre!(
+                "Number of values ({}) does not match number of points ({})",
+                values.len(),
+                points.len()
+            ));
+        }
+        let num_nodes = space.num_nodes();
+        let evaluation_matrix = assemble_evaluation_matrix(space, points);
+        let regularization_matrix = match self.regularization_matrix {
+            Some(matrix) if matrix.nrows() != num_nodes || matrix.ncols() != num_nodes => {
+                return Err(eyre!(
+                    "Regularization matrix has dimensions {}x{}, but the space has {} nodes",
+                    matrix.nrows(),
+                    matrix.ncols(),
+                    num_nodes
+                ))
+            }
+            Some(matrix) => matrix.clone(),
+            None => CsrMatrix::identity(num_nodes),
+        };
+        let evaluation_matrix_t = evaluation_matrix.transpose();
+        let normal_matrix =
+            &(&evaluation_matrix_t * &evaluation_matrix) + &(regularization_matrix * self.regularization);
+
+        let s = SolutionDim::dim();
+        let mut weights = DVector::zeros(s * num_nodes);
+        let mut component_weights = DVector::zeros(num_nodes);
+        for k in 0..s {
+            let component_values = DVector::from_iterator(values.len(), values.iter().map(|v| v[k]));
+            let rhs = &evaluation_matrix_t * &component_values;
+            component_weights.fill(T::zero());
+            self.solver
+                .solve(&normal_matrix, component_weights.as_view_mut(), rhs.as_view())
+                .map_err(|err| err.wrap_err(format!("Failed to solve normal equations for component {k}")))?;
+            for (node, &w) in component_weights.iter().enumerate() {
+                weights[s * node + k] = w;
+            }
+        }
+        Ok(weights)
+    }
+}
@@ -3,6 +3,7 @@ use crate::connectivity::CellConnectivity;
 use crate::element::{
     BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement, ReferenceFiniteElement,
 };
+use crate::index::{ElementIndex, NodeIndex};
 use crate::mesh::Mesh;
 use crate::nalgebra::{Dyn, MatrixViewMut, OMatrix};
 use crate::space::{
@@ -29,18 +30,18 @@ where
         self.vertices().len()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.connectivity()
-            .get(element_index)
+            .get(element_index.index())
             .expect("Element index out of bounds")
             .vertex_indices()
             .len()
     }
 
-    fn populate_element_nodes(&self, nodes: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
         let indices = self
             .connectivity()
-            .get(element_index)
+            .get(element_index.index())
             .expect("Element index out of bounds")
             .vertex_indices();
         assert_eq!(
@@ -48,7 +49,7 @@ where
             nodes.len(),
             "Incompatible slice length for node population"
         );
-        nodes.copy_from_slice(&indices);
+        nodes.copy_from_slice(NodeIndex::from_raw_slice(indices));
     }
 }
 
@@ -150,6 +151,16 @@ where
             .unwrap();
         element.diameter()
     }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        let element = self
+            .connectivity()
+            .get(element_index)
+            .expect("Element index out of bounds")
+            .element(self.vertices())
+            .unwrap();
+        element.is_affine()
+    }
 }
 
 impl<T, D, C> ClosestPointInElementInSpace<T> for Mesh<T, D, C>
@@ -1,4 +1,6 @@
 use crate::element::ClosestPoint;
+use crate::index::{ElementIndex, NodeIndex};
+use crate::memory::HeapSizeEstimate;
 use crate::space::{
     interpolate_at_points, interpolate_gradient_at_points, BoundsForElementInSpace, ClosestPointInElementInSpace,
     FindClosestElement, FiniteElementConnectivity, FiniteElementSpace, InterpolateGradientInSpace, InterpolateInSpace,
@@ -10,12 +12,14 @@ use fenris_traits::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
 use fenris_traits::Real;
 use nalgebra::allocator::Allocator;
 use nalgebra::{DVectorView, DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, OVector, Scalar};
+use num::Zero;
 use rstar::primitives::GeomWithData;
-use rstar::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+use rstar::{Envelope, PointDistance, RTree, RTreeNode, RTreeObject, AABB};
 use std::marker::PhantomData;
+use std::mem::size_of;
 
 #[derive(Debug, Clone)]
-struct RTreeAccelerationStructure<D: DimName>
+pub(crate) struct RTreeAccelerationStructure<D: DimName>
 where
     DefaultAllocator: Allocator<f64, D>,
 {
@@ -23,7 +27,7 @@ where
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct RTreePoint<D>(pub OPoint<f64, D>)
+pub(crate) struct RTreePoint<D>(pub OPoint<f64, D>)
 where
     D: DimName,
     DefaultAllocator: Allocator<f64, D>;
@@ -135,6 +139,20 @@ where
             .take_while(move |&(aabb, _)| aabb.dist2_to(&point_f64) <= d2_max)
             .map(|(_, index)| index)
     }
+
+    /// Returns the indices of all cells whose bounding boxes intersect the given bounding box.
+    pub fn intersecting_cell_candidates<T: Real>(&self, bounding_box: &AxisAlignedBoundingBox<T, D>) -> Vec<usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let box_min = bounding_box.min().map(|x_i| x_i.to_subset().unwrap());
+        let box_max = bounding_box.max().map(|x_i| x_i.to_subset().unwrap());
+        let envelope = AABB::from_corners(RTreePoint(box_min), RTreePoint(box_max));
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|geom| geom.data)
+            .collect()
+    }
 }
 
 /// Provides accelerated geometry queries for a
@@ -147,6 +165,19 @@ where
 /// In addition, `SpatiallyIndexed` provides interpolation of arbitrary points by implementing
 /// the [`InterpolateInSpace`] and [`InterpolateGradientInSpace`] finite element space
 /// traits.
+impl<D: DimName> HeapSizeEstimate for RTreeAccelerationStructure<D>
+where
+    DefaultAllocator: Allocator<f64, D>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        // Every node of the tree except the root is stored in the children of its parent. Since
+        // parents have at least three children, there are at most half as many parents as leaves.
+        let num_leaves = self.tree.size();
+        let num_nodes = num_leaves + num_leaves / 2;
+        num_nodes * size_of::<RTreeNode<GeomWithData<RTreeAABB<D>, usize>>>()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SpatiallyIndexed<T, Space>
 where
@@ -187,6 +218,18 @@ where
     }
 }
 
+/// Includes the estimated size of the spatial index.
+impl<T, Space> HeapSizeEstimate for SpatiallyIndexed<T, Space>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T> + HeapSizeEstimate,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn heap_size_estimate(&self) -> usize {
+        self.space.heap_size_estimate() + self.tree.heap_size_estimate()
+    }
+}
+
 impl<T, Space> FiniteElementConnectivity for SpatiallyIndexed<T, Space>
 where
     T: Scalar,
@@ -201,11 +244,11 @@ where
         self.space.num_nodes()
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
         self.space.element_node_count(element_index)
     }
 
-    fn populate_element_nodes(&self, nodes: &mut [usize], element_index: usize) {
+    fn populate_element_nodes(&self, nodes: &mut [NodeIndex], element_index: ElementIndex) {
         self.space.populate_element_nodes(nodes, element_index)
     }
 }
@@ -260,6 +303,21 @@ where
     fn diameter(&self, element_index: usize) -> T {
         self.space.diameter(element_index)
     }
+
+    fn is_element_affine(&self, element_index: usize) -> bool {
+        self.space.is_element_affine(element_index)
+    }
+
+    fn element_constant_reference_jacobian(
+        &self,
+        element_index: usize,
+    ) -> Option<OMatrix<T, Self::GeometryDim, Self::ReferenceDim>>
+    where
+        T: Zero,
+    {
+        self.space
+            .element_constant_reference_jacobian(element_index)
+    }
 }
 
 impl<T, Space> ClosestPointInElementInSpace<T> for SpatiallyIndexed<T, Space>
@@ -0,0 +1,137 @@
+use crate::allocators::DimAllocator;
+use crate::assembly::block::BlockLayout;
+use crate::connectivity::{
+    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity, Tri6d2Connectivity,
+};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DefaultAllocator, DimName, Scalar, U2, U3};
+use crate::space::FiniteElementConnectivity;
+use crate::Real;
+use eyre::eyre;
+
+/// A pair of meshes discretizing velocity and pressure with Taylor-Hood elements.
+///
+/// Taylor-Hood elements approximate the velocity of incompressible flow (or the displacement of
+/// incompressible elasticity) with continuous polynomials of degree $k + 1$ and the pressure with
+/// continuous polynomials of degree $k$ on the same elements. The lowest-order pairs, Q2-Q1 on
+/// quadrilaterals and hexahedra and P2-P1 on triangles and tetrahedra, satisfy the inf-sup
+/// condition and are therefore stable for saddle point problems such as Stokes flow.
+///
+/// The velocity and pressure meshes consist of the same cells in the same order, i.e. element
+/// $K$ of the velocity mesh occupies the same region as element $K$ of the pressure mesh, so
+/// that they can be used together with
+/// [`ElementMixedAssembler`](crate::assembly::local::ElementMixedAssembler). The meshes are
+/// typically obtained from a linear mesh by converting it with `From`, which uses the linear
+/// mesh for the pressure and its quadratic counterpart for the velocity.
+///
+/// The degrees of freedom are numbered according to [`layout`](Self::layout), with the
+/// velocity in block [`VELOCITY_BLOCK`](Self::VELOCITY_BLOCK) followed by the pressure in block
+/// [`PRESSURE_BLOCK`](Self::PRESSURE_BLOCK).
+#[derive(Debug, Clone)]
+pub struct TaylorHoodMesh<T, D, VelocityConnectivity, PressureConnectivity>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    velocity_mesh: Mesh<T, D, VelocityConnectivity>,
+    pressure_mesh: Mesh<T, D, PressureConnectivity>,
+}
+
+/// Q2-Q1 Taylor-Hood elements on quadrilaterals.
+pub type TaylorHoodQuadMesh2d<T> = TaylorHoodMesh<T, U2, Quad9d2Connectivity, Quad4d2Connectivity>;
+/// P2-P1 Taylor-Hood elements on triangles.
+pub type TaylorHoodTriMesh2d<T> = TaylorHoodMesh<T, U2, Tri6d2Connectivity, Tri3d2Connectivity>;
+/// Q2-Q1 Taylor-Hood elements on hexahedra.
+pub type TaylorHoodHexMesh<T> = TaylorHoodMesh<T, U3, Hex27Connectivity, Hex8Connectivity>;
+/// P2-P1 Taylor-Hood elements on tetrahedra.
+pub type TaylorHoodTetMesh<T> = TaylorHoodMesh<T, U3, Tet10Connectivity, Tet4Connectivity>;
+
+impl<T, D, VelocityConnectivity, PressureConnectivity> TaylorHoodMesh<T, D, VelocityConnectivity, PressureConnectivity>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+    Mesh<T, D, VelocityConnectivity>: FiniteElementConnectivity,
+    Mesh<T, D, PressureConnectivity>: FiniteElementConnectivity,
+{
+    /// The index of the velocity block in the [layout](Self::layout).
+    pub const VELOCITY_BLOCK: usize = 0;
+    /// The index of the pressure block in the [layout](Self::layout).
+    pub const PRESSURE_BLOCK: usize = 1;
+
+    /// Creates a Taylor-Hood mesh from the given velocity and pressure meshes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the meshes have different numbers of elements. Beyond this, the
+    /// caller is responsible for ensuring that the elements of the two meshes coincide.
+    pub fn try_from_meshes(
+        velocity_mesh: Mesh<T, D, VelocityConnectivity>,
+        pressure_mesh: Mesh<T, D, PressureConnectivity>,
+    ) -> eyre::Result<Self> {
+        if velocity_mesh.num_elements() != pressure_mesh.num_elements() {
+            return Err(eyre!(
+                "Number of elements in velocity mesh ({}) does not match number of elements in pressure mesh ({})",
+                velocity_mesh.num_elements(),
+                pressure_mesh.num_elements()
+            ));
+        }
+        Ok(Self {
+            velocity_mesh,
+            pressure_mesh,
+        })
+    }
+
+    pub fn velocity_mesh(&self) -> &Mesh<T, D, VelocityConnectivity> {
+        &self.velocity_mesh
+    }
+
+    pub fn pressure_mesh(&self) -> &Mesh<T, D, PressureConnectivity> {
+        &self.pressure_mesh
+    }
+
+    /// The block layout of the velocity and pressure degrees of freedom.
+    pub fn layout(&self) -> BlockLayout {
+        BlockLayout::new()
+            .with_space(&self.velocity_mesh, D::dim())
+            .with_space(&self.pressure_mesh, 1)
+    }
+}
+
+impl<T: Real> From<Mesh<T, U2, Quad4d2Connectivity>> for TaylorHoodQuadMesh2d<T> {
+    fn from(mesh: Mesh<T, U2, Quad4d2Connectivity>) -> Self {
+        Self {
+            velocity_mesh: Mesh::from(mesh.clone()),
+            pressure_mesh: mesh,
+        }
+    }
+}
+
+impl<T: Real> From<Mesh<T, U2, Tri3d2Connectivity>> for TaylorHoodTriMesh2d<T> {
+    fn from(mesh: Mesh<T, U2, Tri3d2Connectivity>) -> Self {
+        Self {
+            velocity_mesh: Mesh::from(mesh.clone()),
+            pressure_mesh: mesh,
+        }
+    }
+}
+
+impl<T: Real> From<Mesh<T, U3, Hex8Connectivity>> for TaylorHoodHexMesh<T> {
+    fn from(mesh: Mesh<T, U3, Hex8Connectivity>) -> Self {
+        Self {
+            velocity_mesh: Mesh::from(&mesh),
+            pressure_mesh: mesh,
+        }
+    }
+}
+
+impl<T: Real> From<Mesh<T, U3, Tet4Connectivity>> for TaylorHoodTetMesh<T> {
+    fn from(mesh: Mesh<T, U3, Tet4Connectivity>) -> Self {
+        Self {
+            velocity_mesh: Mesh::from(&mesh),
+            pressure_mesh: mesh,
+        }
+    }
+}
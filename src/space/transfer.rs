@@ -0,0 +1,248 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::PreparedElementBuffer;
+use crate::assembly::local::QuadratureTable;
+use crate::index::NodeIndex;
+use crate::linear_solver::{ConjugateGradientSolver, LinearSolver};
+use crate::nalgebra::{DVector, DVectorView, DefaultAllocator, OPoint};
+use crate::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use crate::space::{assemble_evaluation_matrix, ConservativeRemap, FindClosestElement, FiniteElementSpace};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use itertools::izip;
+
+/// The method used by a [`FieldTransfer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransferMethod {
+    /// Evaluation of the source field at the target nodes.
+    Interpolation,
+    /// $L^2$ projection of the source field onto the target space.
+    L2Projection,
+}
+
+#[derive(Debug, Clone)]
+enum TransferOperator<T: Real> {
+    Interpolation {
+        evaluation_matrix: CsrMatrix<T>,
+    },
+    L2Projection {
+        target_mass_matrix: CsrMatrix<T>,
+        mixed_mass_matrix: CsrMatrix<T>,
+    },
+}
+
+/// Transfer of nodal fields from one finite element space to another, non-matching space.
+///
+/// Transferring fields between spaces is needed for example when remeshing during
+/// large-deformation simulations, or when coupling simulations at different resolutions. Two
+/// methods are supported:
+///
+/// - [Interpolation](Self::interpolation) evaluates the source field at the nodes of the
+///   target space. This is cheap and local, but does not preserve integral quantities.
+/// - [$L^2$ projection](Self::l2_projection) computes the function $u_t$ in the target space
+///   that satisfies
+///   <div>$$
+///   \int_{\Omega_t} u_t \, \varphi_i \, \mathrm{d}x = \int_{\Omega_t} u_s \, \varphi_i \, \mathrm{d}x
+///   $$</div>
+///   for all target basis functions $\varphi_i$, which requires the solution of a mass matrix
+///   system. For target spaces whose basis functions form a partition of unity, this preserves
+///   the integral of the field.
+///
+/// The transfer operator is assembled once, after which any number of fields can be transferred
+/// cheaply with [`transfer`](Self::transfer).
+#[derive(Debug, Clone)]
+pub struct FieldTransfer<T: Real, Solver = ConjugateGradientSolver<T>> {
+    operator: TransferOperator<T>,
+    solver: Solver,
+}
+
+impl<T: Real> FieldTransfer<T> {
+    /// Constructs a transfer that interpolates the source field at the given target nodes.
+    ///
+    /// The node positions must be given in the order of the nodes of the target space, which
+    /// for Lagrange elements on a mesh are the mesh vertices. Nodes outside the source space
+    /// are assigned the value at the closest point of the source space, see
+    /// [`assemble_evaluation_matrix`].
+    pub fn interpolation<Source>(source: &Source, target_nodes: &[OPoint<T, Source::GeometryDim>]) -> Self
+    where
+        Source: FindClosestElement<T>,
+        DefaultAllocator: BiDimAllocator<T, Source::GeometryDim, Source::ReferenceDim>,
+    {
+        Self::from_operator(TransferOperator::Interpolation {
+            evaluation_matrix: assemble_evaluation_matrix(source, target_nodes),
+        })
+    }
+
+    /// Constructs a transfer that computes the $L^2$ projection onto the target space.
+    ///
+    /// Both the target mass matrix and the mixed mass matrix are integrated with the
+    /// quadrature rules of the target elements, with the source basis functions evaluated at
+    /// the physical quadrature points. This works for arbitrary pairs of spaces, but since the
+    /// source field is generally not smooth across a target element, the mixed mass matrix is
+    /// only integrated approximately, and the integral of the field is only preserved up to
+    /// the quadrature error. For Lagrange elements on meshes, a [`ConservativeRemap`], which
+    /// integrates exactly over the intersections of source and target cells, can be converted
+    /// into a transfer with the same interface. Target quadrature points outside the source
+    /// space are assigned the value at the closest point of the source space.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a target element has a singular Jacobian at a quadrature point.
+    pub fn l2_projection<Source, Target, D>(
+        source: &Source,
+        target: &Target,
+        target_qtable: &(impl ?Sized + QuadratureTable<T, D>),
+    ) -> eyre::Result<Self>
+    where
+        D: SmallDim,
+        Source: FindClosestElement<T, GeometryDim = D>,
+        Target: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+        DefaultAllocator: DimAllocator<T, D> + BiDimAllocator<T, D, Source::ReferenceDim>,
+    {
+        let mut target_mass = CooMatrix::new(target.num_nodes(), target.num_nodes());
+        let mut mixed_mass = CooMatrix::new(target.num_nodes(), source.num_nodes());
+        let mut buffer = PreparedElementBuffer::default();
+        let mut source_nodes = Vec::new();
+        let mut source_basis = Vec::new();
+        for element_index in 0..target.num_elements() {
+            buffer
+                .prepare_element(target, element_index, target_qtable)
+                .map_err(|err| err.wrap_err(format!("Failed to prepare target element {element_index}")))?;
+            for (q, x) in buffer.physical_points().iter().enumerate() {
+                let w = buffer.physical_weight(q);
+                let target_basis = buffer.basis_values(q);
+                for (&i, &phi_i) in izip!(buffer.element_nodes(), target_basis) {
+                    for (&j, &phi_j) in izip!(buffer.element_nodes(), target_basis) {
+                        target_mass.push(i, j, w * phi_i * phi_j);
+                    }
+                }
+
+                if let Some((source_element, xi)) = source.find_closest_element_and_reference_coords(x) {
+                    let n = source.element_node_count(source_element.into());
+                    source_nodes.resize(n, usize::MAX);
+                    source_basis.resize(n, T::zero());
+                    source.populate_element_nodes(
+                        NodeIndex::from_raw_slice_mut(&mut source_nodes),
+                        source_element.into(),
+                    );
+                    source.populate_element_basis(source_element, &mut source_basis, &xi);
+                    for (&i, &phi_i) in izip!(buffer.element_nodes(), target_basis) {
+                        for (&j, &psi_j) in izip!(&source_nodes, &source_basis) {
+                            mixed_mass.push(i, j, w * phi_i * psi_j);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self::from_operator(TransferOperator::L2Projection {
+            target_mass_matrix: CsrMatrix::from(&target_mass),
+            mixed_mass_matrix: CsrMatrix::from(&mixed_mass),
+        }))
+    }
+
+    fn from_operator(operator: TransferOperator<T>) -> Self {
+        Self {
+            operator,
+            solver: ConjugateGradientSolver::with_tolerance(T::from_f64(1e-12).unwrap()),
+        }
+    }
+}
+
+impl<T: Real, Solver: LinearSolver<T>> From<ConservativeRemap<T, Solver>> for FieldTransfer<T, Solver> {
+    fn from(remap: ConservativeRemap<T, Solver>) -> Self {
+        let (target_mass_matrix, mixed_mass_matrix, solver) = remap.into_parts();
+        Self {
+            operator: TransferOperator::L2Projection {
+                target_mass_matrix,
+                mixed_mass_matrix,
+            },
+            solver,
+        }
+    }
+}
+
+impl<T: Real, Solver: LinearSolver<T>> FieldTransfer<T, Solver> {
+    /// Uses the given linear solver for the mass matrix system of an $L^2$ projection.
+    pub fn with_solver<Solver2: LinearSolver<T>>(self, solver: Solver2) -> FieldTransfer<T, Solver2> {
+        FieldTransfer {
+            operator: self.operator,
+            solver,
+        }
+    }
+
+    pub fn method(&self) -> TransferMethod {
+        match self.operator {
+            TransferOperator::Interpolation { .. } => TransferMethod::Interpolation,
+            TransferOperator::L2Projection { .. } => TransferMethod::L2Projection,
+        }
+    }
+
+    pub fn num_source_nodes(&self) -> usize {
+        match &self.operator {
+            TransferOperator::Interpolation { evaluation_matrix } => evaluation_matrix.ncols(),
+            TransferOperator::L2Projection { mixed_mass_matrix, .. } => mixed_mass_matrix.ncols(),
+        }
+    }
+
+    pub fn num_target_nodes(&self) -> usize {
+        match &self.operator {
+            TransferOperator::Interpolation { evaluation_matrix } => evaluation_matrix.nrows(),
+            TransferOperator::L2Projection { mixed_mass_matrix, .. } => mixed_mass_matrix.nrows(),
+        }
+    }
+
+    /// Transfers the interpolation weights of a field in the source space to the target space.
+    ///
+    /// Both the source weights and the returned target weights are stored in the usual
+    /// interleaved format with `solution_dim` components per node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of source weights is not compatible with the source space,
+    /// or if the linear solver fails.
+    pub fn transfer<'a>(
+        &mut self,
+        source_weights: impl Into<DVectorView<'a, T>>,
+        solution_dim: usize,
+    ) -> eyre::Result<DVector<T>> {
+        let source_weights = source_weights.into();
+        let num_source_nodes = self.num_source_nodes();
+        let num_target_nodes = self.num_target_nodes();
+        if source_weights.len() != solution_dim * num_source_nodes {
+            return Err(eyre!(
+                "Number of source weights ({}) does not match {} source nodes with solution dimension {}",
+                source_weights.len(),
+                num_source_nodes,
+                solution_dim
+            ));
+        }
+
+        let mut target_weights = DVector::zeros(solution_dim * num_target_nodes);
+        let mut component_weights = DVector::zeros(num_target_nodes);
+        for k in 0..solution_dim {
+            let source_component = DVector::from_iterator(
+                num_source_nodes,
+                source_weights.iter().skip(k).step_by(solution_dim).copied(),
+            );
+            match &self.operator {
+                TransferOperator::Interpolation { evaluation_matrix } => {
+                    component_weights = evaluation_matrix * &source_component;
+                }
+                TransferOperator::L2Projection {
+                    target_mass_matrix,
+                    mixed_mass_matrix,
+                } => {
+                    let rhs = mixed_mass_matrix * &source_component;
+                    component_weights.fill(T::zero());
+                    self.solver
+                        .solve(target_mass_matrix, component_weights.as_view_mut(), rhs.as_view())
+                        .map_err(|err| err.wrap_err(format!("Failed to solve mass matrix system for component {k}")))?;
+                }
+            }
+            for (node, &w) in component_weights.iter().enumerate() {
+                target_weights[solution_dim * node + k] = w;
+            }
+        }
+        Ok(target_weights)
+    }
+}
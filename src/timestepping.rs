@@ -0,0 +1,735 @@
+//! Implicit time integration of semi-discrete finite element systems.
+//!
+//! The spatial discretization of a transient problem with finite elements leads to a system of
+//! ordinary differential equations in time. This module provides implicit time integration schemes
+//! for *first-order systems*
+//! <div>$$
+//! \vec M \dot{\vec u} + \vec r(t, \vec u) = \vec 0,
+//! $$</div>
+//! such as the heat equation, and *second-order systems*
+//! <div>$$
+//! \vec M \ddot{\vec u} + \vec r(t, \vec u, \dot{\vec u}) = \vec 0,
+//! $$</div>
+//! such as elastodynamics, where $\vec M$ is a constant mass matrix and $\vec r$ collects the
+//! (possibly nonlinear) internal forces minus the external forces. Systems are described by the
+//! [`FirstOrderSystem`] and [`SecondOrderSystem`] traits, which are implemented for linear systems
+//! with assembled matrices by [`LinearFirstOrderSystem`] and [`LinearSecondOrderSystem`].
+//!
+//! The following schemes are available:
+//!
+//! - [`ThetaMethod`] for first-order systems, including backward Euler and the trapezoidal rule
+//!   (Crank-Nicolson).
+//! - [`Newmark`] for second-order systems, including the average acceleration (trapezoidal) method.
+//! - [`GeneralizedAlpha`] for second-order systems, with controllable high-frequency dissipation.
+//!
+//! Each time step requires the solution of a (nonlinear) system of equations, which is solved
+//! with Newton's method (see [`fenris_optimize::newton`]). The linear systems are solved by a
+//! [`LinearSolver`], for example [`DenseLuSolver`](crate::linear_solver::DenseLuSolver) for small
+//! problems or [`ConjugateGradientSolver`](crate::linear_solver::ConjugateGradientSolver) for
+//! symmetric positive definite systems. For linear problems, Newton's method converges in a
+//! single iteration.
+use crate::linear_solver::LinearSolver;
+use crate::nonlinear::{solve_with_newton, NonlinearSystem};
+use crate::Real;
+use nalgebra::{DVector, DVectorView, DVectorViewMut};
+use nalgebra_sparse::CsrMatrix;
+use numeric_literals::replace_float_literals;
+
+/// A time-dependent load vector $\vec f(t)$.
+pub trait TimeDependentLoad<T: Real> {
+    /// Adds the load vector at time `t` to `output`.
+    fn add_load(&mut self, output: DVectorViewMut<T>, t: T);
+}
+
+/// No load.
+impl<T: Real> TimeDependentLoad<T> for () {
+    fn add_load(&mut self, _output: DVectorViewMut<T>, _t: T) {}
+}
+
+impl<T, F> TimeDependentLoad<T> for F
+where
+    T: Real,
+    F: FnMut(DVectorViewMut<T>, T),
+{
+    fn add_load(&mut self, output: DVectorViewMut<T>, t: T) {
+        self(output, t)
+    }
+}
+
+/// A first-order system $\vec M \dot{\vec u} + \vec r(t, \vec u) = \vec 0$.
+pub trait FirstOrderSystem<T: Real> {
+    /// The constant mass matrix $\vec M$.
+    fn mass_matrix(&self) -> &CsrMatrix<T>;
+
+    /// Evaluates $\vec r(t, \vec u)$.
+    fn compute_residual(&mut self, output: DVectorViewMut<T>, t: T, u: DVectorView<T>) -> eyre::Result<()>;
+
+    /// Computes the Jacobian $\partial \vec r / \partial \vec u$ at $(t, \vec u)$.
+    fn compute_jacobian(&mut self, t: T, u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>>;
+}
+
+/// A second-order system $\vec M \ddot{\vec u} + \vec r(t, \vec u, \dot{\vec u}) = \vec 0$.
+pub trait SecondOrderSystem<T: Real> {
+    /// The constant mass matrix $\vec M$.
+    fn mass_matrix(&self) -> &CsrMatrix<T>;
+
+    /// Evaluates $\vec r(t, \vec u, \vec v)$, where $\vec v = \dot{\vec u}$.
+    fn compute_residual(
+        &mut self,
+        output: DVectorViewMut<T>,
+        t: T,
+        u: DVectorView<T>,
+        v: DVectorView<T>,
+    ) -> eyre::Result<()>;
+
+    /// Computes the linear combination $\alpha_K \vec K + \alpha_C \vec C$ of the
+    /// stiffness matrix $\vec K = \partial \vec r / \partial \vec u$ and the damping matrix
+    /// $\vec C = \partial \vec r / \partial \vec v$ at $(t, \vec u, \vec v)$.
+    fn compute_jacobian_combination(
+        &mut self,
+        t: T,
+        u: DVectorView<T>,
+        v: DVectorView<T>,
+        stiffness_weight: T,
+        damping_weight: T,
+    ) -> eyre::Result<CsrMatrix<T>>;
+}
+
+/// Computes $a \vec A + b \vec B$.
+fn linear_combination<T: Real>(a: T, matrix_a: &CsrMatrix<T>, b: T, matrix_b: &CsrMatrix<T>) -> CsrMatrix<T> {
+    &(matrix_a * a) + &(matrix_b * b)
+}
+
+/// The linear first-order system $\vec M \dot{\vec u} + \vec K \vec u = \vec f(t)$.
+#[derive(Debug, Clone)]
+pub struct LinearFirstOrderSystem<'a, T, Load = ()> {
+    mass: &'a CsrMatrix<T>,
+    stiffness: &'a CsrMatrix<T>,
+    load: Load,
+}
+
+impl<'a, T> LinearFirstOrderSystem<'a, T> {
+    pub fn new(mass: &'a CsrMatrix<T>, stiffness: &'a CsrMatrix<T>) -> Self {
+        Self {
+            mass,
+            stiffness,
+            load: (),
+        }
+    }
+
+    pub fn with_load<Load>(self, load: Load) -> LinearFirstOrderSystem<'a, T, Load> {
+        LinearFirstOrderSystem {
+            mass: self.mass,
+            stiffness: self.stiffness,
+            load,
+        }
+    }
+}
+
+impl<'a, T, Load> FirstOrderSystem<T> for LinearFirstOrderSystem<'a, T, Load>
+where
+    T: Real,
+    Load: TimeDependentLoad<T>,
+{
+    fn mass_matrix(&self) -> &CsrMatrix<T> {
+        self.mass
+    }
+
+    fn compute_residual(&mut self, mut output: DVectorViewMut<T>, t: T, u: DVectorView<T>) -> eyre::Result<()> {
+        output.fill(T::zero());
+        self.load.add_load(DVectorViewMut::from(&mut output), t);
+        // r = K u - f
+        output.neg_mut();
+        output += self.stiffness * u;
+        Ok(())
+    }
+
+    fn compute_jacobian(&mut self, _t: T, _u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>> {
+        Ok(self.stiffness.clone())
+    }
+}
+
+/// The linear second-order system $\vec M \ddot{\vec u} + \vec C \dot{\vec u} + \vec K \vec u = \vec f(t)$.
+#[derive(Debug, Clone)]
+pub struct LinearSecondOrderSystem<'a, T, Load = ()> {
+    mass: &'a CsrMatrix<T>,
+    damping: Option<&'a CsrMatrix<T>>,
+    stiffness: &'a CsrMatrix<T>,
+    load: Load,
+}
+
+impl<'a, T> LinearSecondOrderSystem<'a, T> {
+    /// Creates an undamped system.
+    pub fn new(mass: &'a CsrMatrix<T>, stiffness: &'a CsrMatrix<T>) -> Self {
+        Self {
+            mass,
+            damping: None,
+            stiffness,
+            load: (),
+        }
+    }
+
+    pub fn with_load<Load>(self, load: Load) -> LinearSecondOrderSystem<'a, T, Load> {
+        LinearSecondOrderSystem {
+            mass: self.mass,
+            damping: self.damping,
+            stiffness: self.stiffness,
+            load,
+        }
+    }
+}
+
+impl<'a, T, Load> LinearSecondOrderSystem<'a, T, Load> {
+    pub fn with_damping(self, damping: &'a CsrMatrix<T>) -> Self {
+        Self {
+            damping: Some(damping),
+            ..self
+        }
+    }
+}
+
+impl<'a, T, Load> SecondOrderSystem<T> for LinearSecondOrderSystem<'a, T, Load>
+where
+    T: Real,
+    Load: TimeDependentLoad<T>,
+{
+    fn mass_matrix(&self) -> &CsrMatrix<T> {
+        self.mass
+    }
+
+    fn compute_residual(
+        &mut self,
+        mut output: DVectorViewMut<T>,
+        t: T,
+        u: DVectorView<T>,
+        v: DVectorView<T>,
+    ) -> eyre::Result<()> {
+        output.fill(T::zero());
+        self.load.add_load(DVectorViewMut::from(&mut output), t);
+        // r = K u + C v - f
+        output.neg_mut();
+        output += self.stiffness * u;
+        if let Some(damping) = self.damping {
+            output += damping * v;
+        }
+        Ok(())
+    }
+
+    fn compute_jacobian_combination(
+        &mut self,
+        _t: T,
+        _u: DVectorView<T>,
+        _v: DVectorView<T>,
+        stiffness_weight: T,
+        damping_weight: T,
+    ) -> eyre::Result<CsrMatrix<T>> {
+        Ok(match self.damping {
+            Some(damping) => linear_combination(stiffness_weight, self.stiffness, damping_weight, damping),
+            None => self.stiffness * stiffness_weight,
+        })
+    }
+}
+
+/// Settings for the Newton iterations performed in each time step.
+///
+/// The iterations have converged when the norm of the residual of the time step equations
+/// satisfies $\\| \vec g \\| \leq \epsilon_{\text{abs}} + \epsilon_{\text{rel}} \\| \vec g_0 \\|$,
+/// where $\vec g_0$ is the residual for the initial guess, i.e. the solution of the previous step.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NewtonSettings<T> {
+    pub absolute_tolerance: T,
+    pub relative_tolerance: T,
+    pub max_iterations: usize,
+}
+
+impl<T: Real> Default for NewtonSettings<T> {
+    fn default() -> Self {
+        Self {
+            absolute_tolerance: T::from_f64(1e-12).unwrap(),
+            relative_tolerance: T::from_f64(1e-10).unwrap(),
+            max_iterations: 20,
+        }
+    }
+}
+
+/// Information about a completed time step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StepInfo {
+    /// The number of Newton iterations, i.e. the number of linear systems solved.
+    pub newton_iterations: usize,
+}
+
+/// Solves the step equations $\vec g(\vec x) = \vec 0$ with Newton's method, starting from the
+/// given `x`.
+fn solve_newton<T: Real>(
+    equations: &mut impl NonlinearSystem<T>,
+    x: &mut DVector<T>,
+    settings: &NewtonSettings<T>,
+    linear_solver: &mut impl LinearSolver<T>,
+) -> eyre::Result<StepInfo> {
+    let settings = fenris_optimize::newton::NewtonSettings {
+        max_iterations: Some(settings.max_iterations),
+        tolerance: settings.absolute_tolerance,
+        relative_tolerance: settings.relative_tolerance,
+        increment_tolerance: None,
+    };
+    let convergence = solve_with_newton(equations, x, linear_solver, settings, None, |_| {})?;
+    Ok(StepInfo {
+        newton_iterations: convergence.iterations,
+    })
+}
+
+/// The state $(t, \vec u)$ of a first-order system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstOrderState<T> {
+    pub t: T,
+    pub u: DVector<T>,
+}
+
+/// The state of a second-order system, consisting of time $t$, displacement $\vec u$,
+/// velocity $\vec v = \dot{\vec u}$ and acceleration $\vec a = \ddot{\vec u}$.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecondOrderState<T> {
+    pub t: T,
+    pub u: DVector<T>,
+    pub v: DVector<T>,
+    pub a: DVector<T>,
+}
+
+impl<T: Real> SecondOrderState<T> {
+    /// Creates a state from initial displacement and velocity, with the initial acceleration
+    /// determined from the equations of motion.
+    ///
+    /// Requires the solution of a linear system with the mass matrix.
+    pub fn from_initial_conditions(
+        system: &mut impl SecondOrderSystem<T>,
+        linear_solver: &mut impl LinearSolver<T>,
+        t: T,
+        u: DVector<T>,
+        v: DVector<T>,
+    ) -> eyre::Result<Self> {
+        // M a = -r(t, u, v)
+        let mut r = DVector::zeros(u.len());
+        system.compute_residual(
+            DVectorViewMut::from(&mut r),
+            t,
+            DVectorView::from(&u),
+            DVectorView::from(&v),
+        )?;
+        r.neg_mut();
+        let mut a = DVector::zeros(u.len());
+        linear_solver.solve(
+            system.mass_matrix(),
+            DVectorViewMut::from(&mut a),
+            DVectorView::from(&r),
+        )?;
+        Ok(Self { t, u, v, a })
+    }
+}
+
+/// The $\theta$-method for first-order systems.
+///
+/// Given the state $(t_n, \vec u_n)$, the state at $t_{n + 1} = t_n + \Delta t$ is determined by
+/// <div>$$
+/// \vec M \frac{\vec u_{n + 1} - \vec u_n}{\Delta t}
+///   + \theta \vec r(t_{n + 1}, \vec u_{n + 1}) + (1 - \theta) \vec r(t_n, \vec u_n) = \vec 0.
+/// $$</div>
+/// The method is unconditionally stable for $\theta \geq 1/2$. With $\theta = 1$, it is the
+/// first-order accurate backward Euler method, and with $\theta = 1/2$ the second-order accurate
+/// trapezoidal rule (Crank-Nicolson), which however does not damp high-frequency components.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThetaMethod<T> {
+    theta: T,
+    settings: NewtonSettings<T>,
+}
+
+struct ThetaStepEquations<'a, T: Real, System> {
+    system: &'a mut System,
+    theta_dt: T,
+    t_next: T,
+    u_prev: &'a DVector<T>,
+    /// The contribution $(1 - \theta) \Delta t \vec r(t_n, \vec u_n)$ of the previous step
+    r_prev: DVector<T>,
+    r: DVector<T>,
+}
+
+impl<'a, T, System> NonlinearSystem<T> for ThetaStepEquations<'a, T, System>
+where
+    T: Real,
+    System: FirstOrderSystem<T>,
+{
+    fn compute_residual(&mut self, mut output: DVectorViewMut<T>, u: DVectorView<T>) -> eyre::Result<()> {
+        // g = M (u - u_n) + theta dt r(t_{n + 1}, u) + (1 - theta) dt r(t_n, u_n)
+        self.system
+            .compute_residual(DVectorViewMut::from(&mut self.r), self.t_next, u)?;
+        output.copy_from(&(self.system.mass_matrix() * (u - self.u_prev)));
+        output.axpy(self.theta_dt, &self.r, T::one());
+        output += &self.r_prev;
+        Ok(())
+    }
+
+    fn compute_jacobian(&mut self, u: DVectorView<T>) -> eyre::Result<CsrMatrix<T>> {
+        let jacobian = self.system.compute_jacobian(self.t_next, u)?;
+        Ok(linear_combination(
+            T::one(),
+            self.system.mass_matrix(),
+            self.theta_dt,
+            &jacobian,
+        ))
+    }
+}
+
+impl<T: Real> ThetaMethod<T> {
+    /// # Panics
+    ///
+    /// Panics if $\theta$ is not in $(0, 1]$.
+    pub fn new(theta: T) -> Self {
+        assert!(theta > T::zero() && theta <= T::one(), "theta must be in (0, 1]");
+        Self {
+            theta,
+            settings: Default::default(),
+        }
+    }
+
+    pub fn backward_euler() -> Self {
+        Self::new(T::one())
+    }
+
+    pub fn trapezoidal() -> Self {
+        Self::new(T::from_f64(0.5).unwrap())
+    }
+
+    pub fn with_newton_settings(self, settings: NewtonSettings<T>) -> Self {
+        Self { settings, ..self }
+    }
+
+    pub fn theta(&self) -> T {
+        self.theta
+    }
+
+    /// Advances the state by the time step `dt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the evaluation of the system or the linear solver fails, or if
+    /// the Newton iterations do not converge. The state is left unchanged in case of errors.
+    pub fn step(
+        &self,
+        system: &mut impl FirstOrderSystem<T>,
+        state: &mut FirstOrderState<T>,
+        dt: T,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<StepInfo> {
+        let n = state.u.len();
+        let mut r_prev = DVector::zeros(n);
+        if self.theta < T::one() {
+            system.compute_residual(DVectorViewMut::from(&mut r_prev), state.t, DVectorView::from(&state.u))?;
+            r_prev *= (T::one() - self.theta) * dt;
+        }
+
+        let t_next = state.t + dt;
+        let mut u = state.u.clone();
+        let mut equations = ThetaStepEquations {
+            system,
+            theta_dt: self.theta * dt,
+            t_next,
+            u_prev: &state.u,
+            r_prev,
+            r: DVector::zeros(n),
+        };
+        let info = solve_newton(&mut equations, &mut u, &self.settings, linear_solver)?;
+        state.t = t_next;
+        state.u = u;
+        Ok(info)
+    }
+}
+
+/// Parameters of the generalized-$\alpha$ family of methods, which includes the Newmark family.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct AlphaParameters<T> {
+    alpha_m: T,
+    alpha_f: T,
+    beta: T,
+    gamma: T,
+}
+
+struct AlphaStepEquations<'a, T: Real, System> {
+    system: &'a mut System,
+    parameters: AlphaParameters<T>,
+    dt: T,
+    state: &'a SecondOrderState<T>,
+    /// Buffers for the displacement and velocity at the intermediate time
+    u_alpha: DVector<T>,
+    v_alpha: DVector<T>,
+    r: DVector<T>,
+}
+
+impl<'a, T: Real, System> AlphaStepEquations<'a, T, System> {
+    /// Computes displacement and velocity at $t_{n + 1}$ for the given acceleration with the
+    /// Newmark formulas.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn newmark_update(&self, a_next: DVectorView<T>) -> (DVector<T>, DVector<T>) {
+        let AlphaParameters { beta, gamma, .. } = self.parameters;
+        let SecondOrderState { u, v, a, .. } = self.state;
+        let dt = self.dt;
+        let u_next = u + v * dt + (a * (0.5 - beta) + a_next * beta) * (dt * dt);
+        let v_next = v + (a * (1.0 - gamma) + a_next * gamma) * dt;
+        (u_next, v_next)
+    }
+
+    fn t_alpha(&self) -> T {
+        self.state.t + (T::one() - self.parameters.alpha_f) * self.dt
+    }
+
+    /// Evaluates displacement and velocity at the intermediate time $t_{n + 1 - \alpha_f}$.
+    fn update_intermediate_state(&mut self, a_next: DVectorView<T>) {
+        let alpha_f = self.parameters.alpha_f;
+        let (u_next, v_next) = self.newmark_update(a_next);
+        self.u_alpha.copy_from(&self.state.u);
+        self.u_alpha.axpy(T::one() - alpha_f, &u_next, alpha_f);
+        self.v_alpha.copy_from(&self.state.v);
+        self.v_alpha.axpy(T::one() - alpha_f, &v_next, alpha_f);
+    }
+}
+
+impl<'a, T, System> NonlinearSystem<T> for AlphaStepEquations<'a, T, System>
+where
+    T: Real,
+    System: SecondOrderSystem<T>,
+{
+    fn compute_residual(&mut self, mut output: DVectorViewMut<T>, a_next: DVectorView<T>) -> eyre::Result<()> {
+        // g = M a_{n + 1 - alpha_m} + r(t_{n + 1 - alpha_f}, u_{n + 1 - alpha_f}, v_{n + 1 - alpha_f})
+        let alpha_m = self.parameters.alpha_m;
+        self.update_intermediate_state(a_next);
+        let t_alpha = self.t_alpha();
+        self.system.compute_residual(
+            DVectorViewMut::from(&mut self.r),
+            t_alpha,
+            DVectorView::from(&self.u_alpha),
+            DVectorView::from(&self.v_alpha),
+        )?;
+        let a_alpha = a_next * (T::one() - alpha_m) + &self.state.a * alpha_m;
+        output.copy_from(&(self.system.mass_matrix() * a_alpha));
+        output += &self.r;
+        Ok(())
+    }
+
+    fn compute_jacobian(&mut self, a_next: DVectorView<T>) -> eyre::Result<CsrMatrix<T>> {
+        let AlphaParameters {
+            alpha_m,
+            alpha_f,
+            beta,
+            gamma,
+        } = self.parameters;
+        self.update_intermediate_state(a_next);
+        let t_alpha = self.t_alpha();
+        let dt = self.dt;
+        let jacobian = self.system.compute_jacobian_combination(
+            t_alpha,
+            DVectorView::from(&self.u_alpha),
+            DVectorView::from(&self.v_alpha),
+            (T::one() - alpha_f) * beta * dt * dt,
+            (T::one() - alpha_f) * gamma * dt,
+        )?;
+        Ok(linear_combination(
+            T::one() - alpha_m,
+            self.system.mass_matrix(),
+            T::one(),
+            &jacobian,
+        ))
+    }
+}
+
+fn alpha_step<T: Real>(
+    parameters: AlphaParameters<T>,
+    settings: &NewtonSettings<T>,
+    system: &mut impl SecondOrderSystem<T>,
+    state: &mut SecondOrderState<T>,
+    dt: T,
+    linear_solver: &mut impl LinearSolver<T>,
+) -> eyre::Result<StepInfo> {
+    let n = state.u.len();
+    let mut a_next = state.a.clone();
+    let mut equations = AlphaStepEquations {
+        system,
+        parameters,
+        dt,
+        state,
+        u_alpha: DVector::zeros(n),
+        v_alpha: DVector::zeros(n),
+        r: DVector::zeros(n),
+    };
+    let info = solve_newton(&mut equations, &mut a_next, settings, linear_solver)?;
+    let (u_next, v_next) = equations.newmark_update(DVectorView::from(&a_next));
+    *state = SecondOrderState {
+        t: state.t + dt,
+        u: u_next,
+        v: v_next,
+        a: a_next,
+    };
+    Ok(info)
+}
+
+/// The Newmark-$\beta$ method for second-order systems.
+///
+/// Given the state $(t_n, \vec u_n, \vec v_n, \vec a_n)$, the state at $t_{n + 1} = t_n + \Delta t$
+/// is determined by the equations of motion at $t_{n + 1}$ together with the updates
+/// <div>$$
+/// \begin{aligned}
+/// \vec u_{n + 1} &= \vec u_n + \Delta t \vec v_n
+///     + \Delta t^2 \left( (\tfrac{1}{2} - \beta) \vec a_n + \beta \vec a_{n + 1} \right), \\\\
+/// \vec v_{n + 1} &= \vec v_n + \Delta t \left( (1 - \gamma) \vec a_n + \gamma \vec a_{n + 1} \right).
+/// \end{aligned}
+/// $$</div>
+/// The method is second-order accurate if and only if $\gamma = 1/2$, and unconditionally stable
+/// for linear problems if $2 \beta \geq \gamma \geq 1/2$. The average acceleration method
+/// ($\beta = 1/4$, $\gamma = 1/2$), which is the trapezoidal rule for second-order systems, is both.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Newmark<T> {
+    beta: T,
+    gamma: T,
+    settings: NewtonSettings<T>,
+}
+
+impl<T: Real> Newmark<T> {
+    pub fn new(beta: T, gamma: T) -> Self {
+        Self {
+            beta,
+            gamma,
+            settings: Default::default(),
+        }
+    }
+
+    /// The average acceleration method with $\beta = 1/4$ and $\gamma = 1/2$.
+    pub fn average_acceleration() -> Self {
+        Self::new(T::from_f64(0.25).unwrap(), T::from_f64(0.5).unwrap())
+    }
+
+    pub fn with_newton_settings(self, settings: NewtonSettings<T>) -> Self {
+        Self { settings, ..self }
+    }
+
+    pub fn beta(&self) -> T {
+        self.beta
+    }
+
+    pub fn gamma(&self) -> T {
+        self.gamma
+    }
+
+    /// Advances the state by the time step `dt`.
+    ///
+    /// The state must contain an acceleration consistent with the equations of motion,
+    /// e.g. as obtained with [`SecondOrderState::from_initial_conditions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the evaluation of the system or the linear solver fails, or if
+    /// the Newton iterations do not converge. The state is left unchanged in case of errors.
+    pub fn step(
+        &self,
+        system: &mut impl SecondOrderSystem<T>,
+        state: &mut SecondOrderState<T>,
+        dt: T,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<StepInfo> {
+        let parameters = AlphaParameters {
+            alpha_m: T::zero(),
+            alpha_f: T::zero(),
+            beta: self.beta,
+            gamma: self.gamma,
+        };
+        alpha_step(parameters, &self.settings, system, state, dt, linear_solver)
+    }
+}
+
+/// The generalized-$\alpha$ method of Chung and Hulbert for second-order systems.
+///
+/// The method uses the [Newmark] updates, but evaluates the equations of motion at
+/// intermediate points in time,
+/// <div>$$
+/// \vec M \vec a_{n + 1 - \alpha_m}
+///   + \vec r(t_{n + 1 - \alpha_f}, \vec u_{n + 1 - \alpha_f}, \vec v_{n + 1 - \alpha_f}) = \vec 0,
+/// $$</div>
+/// where $x_{n + 1 - \alpha} = (1 - \alpha) x_{n + 1} + \alpha x_n$. The parameters are usually
+/// chosen from the desired spectral radius $\rho_\infty \in [0, 1]$ in the high-frequency limit
+/// with [`from_spectral_radius`](Self::from_spectral_radius), which gives a second-order accurate,
+/// unconditionally stable method with optimal dissipation of high-frequency components.
+/// With $\rho_\infty = 1$, there is no dissipation and the method reduces to the average
+/// acceleration method.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeneralizedAlpha<T> {
+    parameters: AlphaParameters<T>,
+    settings: NewtonSettings<T>,
+}
+
+impl<T: Real> GeneralizedAlpha<T> {
+    /// Creates the method with parameters determined by the spectral radius $\rho_\infty$,
+    /// <div>$$
+    /// \alpha_m = \frac{2 \rho_\infty - 1}{\rho_\infty + 1}, \quad
+    /// \alpha_f = \frac{\rho_\infty}{\rho_\infty + 1}, \quad
+    /// \gamma = \frac{1}{2} - \alpha_m + \alpha_f, \quad
+    /// \beta = \frac{1}{4} (1 - \alpha_m + \alpha_f)^2.
+    /// $$</div>
+    ///
+    /// # Panics
+    ///
+    /// Panics if $\rho_\infty$ is not in $[0, 1]$.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn from_spectral_radius(rho_infinity: T) -> Self {
+        assert!(
+            rho_infinity >= 0.0 && rho_infinity <= 1.0,
+            "Spectral radius must be in [0, 1]"
+        );
+        let alpha_m = (2.0 * rho_infinity - 1.0) / (rho_infinity + 1.0);
+        let alpha_f = rho_infinity / (rho_infinity + 1.0);
+        let gamma = 0.5 - alpha_m + alpha_f;
+        let beta = 0.25 * (1.0 - alpha_m + alpha_f).powi(2);
+        Self::from_parameters(alpha_m, alpha_f, beta, gamma)
+    }
+
+    pub fn from_parameters(alpha_m: T, alpha_f: T, beta: T, gamma: T) -> Self {
+        Self {
+            parameters: AlphaParameters {
+                alpha_m,
+                alpha_f,
+                beta,
+                gamma,
+            },
+            settings: Default::default(),
+        }
+    }
+
+    pub fn with_newton_settings(self, settings: NewtonSettings<T>) -> Self {
+        Self { settings, ..self }
+    }
+
+    pub fn alpha_m(&self) -> T {
+        self.parameters.alpha_m
+    }
+
+    pub fn alpha_f(&self) -> T {
+        self.parameters.alpha_f
+    }
+
+    pub fn beta(&self) -> T {
+        self.parameters.beta
+    }
+
+    pub fn gamma(&self) -> T {
+        self.parameters.gamma
+    }
+
+    /// Advances the state by the time step `dt`.
+    ///
+    /// See [`Newmark::step`].
+    pub fn step(
+        &self,
+        system: &mut impl SecondOrderSystem<T>,
+        state: &mut SecondOrderState<T>,
+        dt: T,
+        linear_solver: &mut impl LinearSolver<T>,
+    ) -> eyre::Result<StepInfo> {
+        alpha_step(self.parameters, &self.settings, system, state, dt, linear_solver)
+    }
+}
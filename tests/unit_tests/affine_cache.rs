@@ -0,0 +1,121 @@
+use fenris::assembly::global::{assemble_scalar, CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::Quad4d2Connectivity;
+use fenris::mesh::procedural::{
+    create_annulus_mesh_2d, create_rectangular_uniform_tet10_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d, CubeTetSplit,
+};
+use fenris::nalgebra::{point, vector, DMatrix, DVector, Point2};
+use fenris::quadrature;
+use fenris::space::{AffineGeometryCache, FiniteElementConnectivity, FiniteElementSpace};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn affine_geometry_cache_detects_affine_elements() {
+    // Linear triangles are always affine, even after distorting the mesh
+    let mut tri_mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    tri_mesh.transform_vertices(|v| *v = point![v.x + 0.3 * v.y * v.y, v.y + 0.1 * v.x]);
+    let cache = AffineGeometryCache::from_space(tri_mesh.clone());
+    assert_eq!(cache.num_affine_elements(), tri_mesh.connectivity().len());
+
+    let tet10_mesh = create_rectangular_uniform_tet10_mesh_3d(&vector![1.0, 2.0, 1.0], [2, 1, 1], CubeTetSplit::Six);
+    let cache = AffineGeometryCache::from_space(tet10_mesh.clone());
+    assert_eq!(cache.num_affine_elements(), tet10_mesh.connectivity().len());
+
+    // Quadrilaterals are only affine if they are parallelograms
+    let mut quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let center = quad_mesh
+        .vertices()
+        .iter()
+        .position(|v| v == &Point2::new(0.5, 0.5))
+        .unwrap();
+    quad_mesh.vertices_mut()[center] = point![0.6, 0.45];
+    let cache = AffineGeometryCache::from_space(quad_mesh.clone());
+    assert_eq!(cache.num_affine_elements(), 0);
+
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let cache = AffineGeometryCache::from_space(quad_mesh.clone());
+    assert_eq!(cache.num_affine_elements(), 4);
+    let xi = point![0.3, -0.2];
+    for i in 0..cache.num_elements() {
+        assert!(cache.is_element_affine(i));
+        assert_matrix_eq!(
+            cache.element_reference_jacobian(i, &xi),
+            quad_mesh.element_reference_jacobian(i, &xi),
+            comp = abs,
+            tol = 1e-15
+        );
+    }
+
+    let annulus = create_annulus_mesh_2d::<f64, Quad4d2Connectivity>(1.0, 2.0, 2, 8);
+    assert_eq!(AffineGeometryCache::from_space(annulus).num_affine_elements(), 0);
+}
+
+#[test]
+fn affine_geometry_cache_gives_same_elliptic_assembly_results() {
+    // Distort the mesh so that it has both affine and non-affine elements
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    mesh.transform_vertices(|v| {
+        if v.x < 0.5 {
+            *v = point![v.x + 0.1 * v.x * v.y, v.y];
+        }
+    });
+    let cache = AffineGeometryCache::from_space(mesh.clone());
+    assert!(cache.num_affine_elements() > 0);
+    assert!(cache.num_affine_elements() < mesh.connectivity().len());
+
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let cached_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&cache)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    let a = CsrAssembler::default().assemble(&assembler).unwrap();
+    let a_cached = CsrAssembler::default().assemble(&cached_assembler).unwrap();
+    assert_matrix_eq!(DMatrix::from(&a_cached), DMatrix::from(&a), comp = abs, tol = 1e-13);
+
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    let f_cached = VectorAssembler::default()
+        .assemble_vector(&cached_assembler)
+        .unwrap();
+    assert_matrix_eq!(f_cached, f, comp = abs, tol = 1e-13);
+
+    let energy = assemble_scalar(&assembler).unwrap();
+    let energy_cached = assemble_scalar(&cached_assembler).unwrap();
+    assert_scalar_eq!(energy_cached, energy, comp = abs, tol = 1e-13);
+}
+
+#[test]
+fn mesh_space_reports_affine_simplices() {
+    let mut tri_mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    tri_mesh.transform_vertices(|v| *v = point![v.x + 0.3 * v.y * v.y, v.y + 0.1 * v.x]);
+    for i in 0..tri_mesh.num_elements() {
+        assert!(tri_mesh.is_element_affine(i));
+        let jacobian = tri_mesh.element_constant_reference_jacobian(i).unwrap();
+        assert_matrix_eq!(
+            jacobian,
+            tri_mesh.element_reference_jacobian(i, &point![0.3, -0.6]),
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+
+    // Even parallelograms are not reported as affine without inspecting the geometry
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    for i in 0..quad_mesh.num_elements() {
+        assert!(!quad_mesh.is_element_affine(i));
+        assert!(quad_mesh.element_constant_reference_jacobian(i).is_none());
+    }
+}
@@ -3,8 +3,19 @@
 // use fenris_solid::ElasticMaterialModel;
 // use fenris_solid::ElasticityModel;
 
+mod bc;
+mod block;
+mod buffers;
+mod flux;
 mod global;
+mod goal;
 mod local;
+mod matrix_free;
+mod operators;
+mod patch;
+mod projection;
+mod saddle_point;
+mod stats;
 
 // TODO: Re-enable/rewrite tests here as appropriate when possible (most tests rely on some
 // solid mechanics stuff)
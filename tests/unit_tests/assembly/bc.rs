@@ -0,0 +1,140 @@
+use fenris::assembly::bc::DirichletConditions;
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::index::{DofIndex, NodeIndex};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+fn assemble_laplace_matrix(mesh: &QuadMesh2d<f64>) -> CsrMatrix<f64> {
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    CsrAssembler::default().assemble(&assembler).unwrap()
+}
+
+fn solve_dense(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> DVector<f64> {
+    DMatrix::from(matrix).lu().solve(rhs).unwrap()
+}
+
+/// Sets up the Laplace problem whose exact solution u = 1 + x + 2y is reproduced exactly by
+/// bilinear elements, with Dirichlet conditions on the whole boundary.
+fn linear_laplace_problem() -> (CsrMatrix<f64>, DVector<f64>, DirichletConditions<f64>, DVector<f64>) {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let u_exact = |x: f64, y: f64| 1.0 + x + 2.0 * y;
+    let expected = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(|v| u_exact(v.x, v.y)));
+    let boundary_nodes = mesh.find_boundary_vertices();
+    let boundary_values: Vec<_> = boundary_nodes.iter().map(|&node| expected[node]).collect();
+    let conditions = DirichletConditions::try_from_nodes_and_values(
+        mesh.vertices().len(),
+        1,
+        NodeIndex::from_raw_slice(&boundary_nodes),
+        &boundary_values,
+    )
+    .unwrap();
+    let a = assemble_laplace_matrix(&mesh);
+    let b = DVector::zeros(a.nrows());
+    (a, b, conditions, expected)
+}
+
+#[test]
+fn dirichlet_conditions_constructors() {
+    let conditions = DirichletConditions::try_from_dofs_and_values(6, &[4, 1].map(DofIndex::new), &[2.0, 3.0]).unwrap();
+    assert_eq!(conditions.num_dofs(), 6);
+    assert_eq!(conditions.constrained_dofs(), &[1, 4].map(DofIndex::new));
+    assert_eq!(conditions.values(), &[3.0, 2.0]);
+    assert_eq!(conditions.free_dofs(), [0, 2, 3, 5].map(DofIndex::new));
+    assert!(conditions.is_constrained(DofIndex::new(4)));
+    assert!(!conditions.is_constrained(DofIndex::new(0)));
+
+    let conditions =
+        DirichletConditions::try_from_nodes_and_values(3, 2, &[2, 0].map(NodeIndex::new), &[1.0, 2.0, 3.0, 4.0])
+            .unwrap();
+    assert_eq!(conditions.num_dofs(), 6);
+    assert_eq!(conditions.constrained_dofs(), &[0, 1, 4, 5].map(DofIndex::new));
+    assert_eq!(conditions.values(), &[3.0, 4.0, 1.0, 2.0]);
+
+    let conditions = DirichletConditions::<f64>::try_homogeneous_nodes(3, 2, &[NodeIndex::new(1)]).unwrap();
+    assert_eq!(conditions.constrained_dofs(), &[2, 3].map(DofIndex::new));
+    assert_eq!(conditions.values(), &[0.0, 0.0]);
+
+    let mut u = DVector::repeat(6, 5.0);
+    conditions.apply_to_vector(&mut u);
+    assert_eq!(u.as_slice(), &[5.0, 5.0, 0.0, 0.0, 5.0, 5.0]);
+
+    assert!(DirichletConditions::try_from_dofs_and_values(6, &[1, 2].map(DofIndex::new), &[1.0]).is_err());
+    assert!(DirichletConditions::try_from_dofs_and_values(6, &[DofIndex::new(6)], &[1.0]).is_err());
+    assert!(DirichletConditions::try_from_dofs_and_values(6, &[2, 2].map(DofIndex::new), &[1.0, 1.0]).is_err());
+    assert!(DirichletConditions::<f64>::try_homogeneous_nodes(3, 2, &[NodeIndex::new(3)]).is_err());
+}
+
+#[test]
+fn dirichlet_elimination_reproduces_linear_solution() {
+    let (a, b, conditions, expected) = linear_laplace_problem();
+    let reduced = conditions.eliminate(&a, &b).unwrap();
+    let num_free = a.nrows() - conditions.constrained_dofs().len();
+    assert_eq!(reduced.matrix().nrows(), num_free);
+    assert_eq!(reduced.free_dofs(), conditions.free_dofs().as_slice());
+
+    // The reduced matrix is the submatrix of free degrees of freedom, and therefore symmetric
+    let dense = DMatrix::from(&a);
+    let free = conditions.free_dofs();
+    let expected_matrix = DMatrix::from_fn(num_free, num_free, |i, j| dense[(free[i].index(), free[j].index())]);
+    assert_matrix_eq!(DMatrix::from(reduced.matrix()), expected_matrix);
+
+    let u_reduced = solve_dense(reduced.matrix(), reduced.rhs());
+    let u = reduced.recover_solution(&u_reduced);
+    assert_matrix_eq!(u, expected, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(reduced.reduce_vector(&u), u_reduced);
+}
+
+#[test]
+fn dirichlet_row_replacement_reproduces_linear_solution() {
+    let (mut a, mut b, conditions, expected) = linear_laplace_problem();
+    let pattern = a.pattern().clone();
+    conditions.apply_row_replacement(&mut a, &mut b).unwrap();
+    assert_eq!(a.pattern(), &pattern);
+
+    let dense = DMatrix::from(&a);
+    assert_matrix_eq!(dense, dense.transpose());
+    for &dof in DofIndex::as_raw_slice(conditions.constrained_dofs()) {
+        assert!(dense[(dof, dof)] > 0.0);
+        assert_eq!(dense.row(dof).iter().filter(|&&a_ij| a_ij != 0.0).count(), 1);
+    }
+
+    let u = solve_dense(&a, &b);
+    assert_matrix_eq!(u, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn dirichlet_penalty_approximates_linear_solution() {
+    let (mut a, mut b, conditions, expected) = linear_laplace_problem();
+    conditions.apply_penalty(&mut a, &mut b, 1e10).unwrap();
+    let u = solve_dense(&a, &b);
+    assert_matrix_eq!(u, expected, comp = abs, tol = 1e-8);
+}
+
+#[test]
+fn dirichlet_conditions_reject_incompatible_systems() {
+    let (mut a, mut b, _, _) = linear_laplace_problem();
+    let conditions = DirichletConditions::try_homogeneous(a.nrows() + 1, &[DofIndex::new(0)]).unwrap();
+    assert!(conditions.eliminate(&a, &b).is_err());
+    assert!(conditions.apply_row_replacement(&mut a, &mut b).is_err());
+    assert!(conditions.apply_penalty(&mut a, &mut b, 1e10).is_err());
+
+    // Diagonal entries of constrained degrees of freedom must be present in the sparsity pattern
+    let mut a = CsrMatrix::from(&DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 1.0, 0.0]));
+    let mut b = DVector::zeros(2);
+    let conditions = DirichletConditions::try_homogeneous(2, &[DofIndex::new(1)]).unwrap();
+    assert!(conditions.apply_row_replacement(&mut a, &mut b).is_err());
+    assert!(conditions.apply_penalty(&mut a, &mut b, 1e10).is_err());
+}
@@ -0,0 +1,362 @@
+use fenris::assembly::bc::DirichletConditions;
+use fenris::assembly::block::{BlockLayout, BlockSystemAssembler};
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    Density, ElementMassAssembler, ElementMixedAssembler, ElementSourceAssemblerBuilder, SourceFunction,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::{MixedOperator, Operator};
+use fenris::index::DofIndex;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::{Quad9Mesh2d, QuadMesh2d};
+use fenris::nalgebra::{DMatrix, DVector, Matrix1, Matrix1x2, OPoint, Vector1, Vector2, U1, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+/// The coupling $\int q u \, dx$ between two scalar fields.
+struct ValueCoupling;
+
+impl MixedOperator<f64, U2> for ValueCoupling {
+    type TestDim = U1;
+    type TrialDim = U1;
+    type Parameters = ();
+
+    fn compute_coupling(
+        &self,
+        test_value: f64,
+        _: &Vector2<f64>,
+        trial_value: f64,
+        _: &Vector2<f64>,
+        _: &(),
+    ) -> Matrix1<f64> {
+        Matrix1::new(test_value * trial_value)
+    }
+}
+
+/// The coupling $-\int q \nabla \cdot \vec u \, dx$ between a scalar test field and a vector-valued
+/// trial field.
+struct DivergenceCoupling;
+
+impl MixedOperator<f64, U2> for DivergenceCoupling {
+    type TestDim = U1;
+    type TrialDim = U2;
+    type Parameters = ();
+
+    fn compute_coupling(
+        &self,
+        test_value: f64,
+        _: &Vector2<f64>,
+        _: f64,
+        trial_gradient: &Vector2<f64>,
+        _: &(),
+    ) -> Matrix1x2<f64> {
+        -test_value * trial_gradient.transpose()
+    }
+}
+
+struct UnitSource;
+
+impl Operator<f64, U2> for UnitSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for UnitSource {
+    fn evaluate(&self, _coords: &OPoint<f64, U2>, _data: &Self::Parameters) -> Vector1<f64> {
+        Vector1::new(1.0)
+    }
+}
+
+fn example_layout() -> BlockLayout {
+    BlockLayout::new()
+        .with_block(3, 2)
+        .with_block(0, 1)
+        .with_block(2, 1)
+}
+
+#[test]
+fn block_layout_indexing() {
+    let layout = example_layout();
+    assert_eq!(layout.num_blocks(), 3);
+    assert_eq!(layout.num_dofs(), 8);
+    assert_eq!(layout.block_size(0), 6);
+    assert_eq!(layout.block_size(1), 0);
+    assert_eq!(layout.block_solution_dim(0), 2);
+    assert_eq!(layout.block_num_nodes(2), 2);
+    assert_eq!(layout.block_range(0), 0..6);
+    assert_eq!(layout.block_range(1), 6..6);
+    assert_eq!(layout.block_range(2), 6..8);
+    assert_eq!(layout.block_offset(2), 6);
+
+    assert_eq!(layout.global_dof(0, 1, 1), 3);
+    assert_eq!(layout.global_dof(2, 1, 0), 7);
+    assert_eq!(layout.global_index(2, 0), 6);
+
+    // Empty blocks are never found
+    assert_eq!(layout.find_block(0), Some((0, 0)));
+    assert_eq!(layout.find_block(5), Some((0, 5)));
+    assert_eq!(layout.find_block(6), Some((2, 0)));
+    assert_eq!(layout.find_block(7), Some((2, 1)));
+    assert_eq!(layout.find_block(8), None);
+
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let layout = BlockLayout::default()
+        .with_space(&mesh, 2)
+        .with_space(&mesh, 1);
+    assert_eq!(layout.num_dofs(), 27);
+}
+
+#[test]
+#[should_panic]
+fn block_layout_global_dof_panics_for_out_of_bounds_component() {
+    example_layout().global_dof(0, 0, 2);
+}
+
+#[test]
+fn block_layout_split_and_concatenate() {
+    let layout = example_layout();
+    let mut u = DVector::from_fn(8, |i, _| i as f64);
+    let blocks = layout.split(&u);
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[0].as_slice(), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(blocks[1].len(), 0);
+    assert_eq!(blocks[2].as_slice(), &[6.0, 7.0]);
+    assert_eq!(layout.try_concatenate(&blocks).unwrap(), u);
+
+    // Wrong number of blocks or wrong block sizes
+    assert!(layout.try_concatenate(&blocks[..2]).is_err());
+    let mut wrong_sizes = blocks.clone();
+    wrong_sizes.swap(1, 2);
+    assert!(layout.try_concatenate(&wrong_sizes).is_err());
+
+    layout.block_mut(2, &mut u).fill(-1.0);
+    assert_eq!(layout.block(2, &u).as_slice(), &[-1.0, -1.0]);
+    assert_eq!(layout.block(0, &u).as_slice(), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn block_layout_combines_dirichlet_conditions() {
+    let layout = example_layout();
+    let bc_0 = DirichletConditions::try_from_dofs_and_values(6, &[1, 4].map(DofIndex::new), &[1.0, 2.0]).unwrap();
+    let bc_2 = DirichletConditions::try_from_dofs_and_values(2, &[DofIndex::new(1)], &[3.0]).unwrap();
+    let bc = layout
+        .try_combine_dirichlet_conditions(&[(2, &bc_2), (0, &bc_0)])
+        .unwrap();
+    assert_eq!(bc.num_dofs(), 8);
+    assert_eq!(bc.constrained_dofs(), &[1, 4, 7].map(DofIndex::new));
+    assert_eq!(bc.values(), &[1.0, 2.0, 3.0]);
+
+    assert!(layout
+        .try_combine_dirichlet_conditions(&[(2, &bc_0)])
+        .is_err());
+    assert!(layout
+        .try_combine_dirichlet_conditions(&[(3, &bc_2)])
+        .is_err());
+    assert!(layout
+        .try_combine_dirichlet_conditions(&[(2, &bc_2), (2, &bc_2)])
+        .is_err());
+}
+
+#[test]
+fn block_layout_assembles_monolithic_matrix() {
+    let layout = BlockLayout::new().with_block(3, 1).with_block(2, 1);
+    let a = DMatrix::from_row_slice(3, 3, &[4.0, -1.0, 0.0, -1.0, 4.0, -1.0, 0.0, -1.0, 4.0]);
+    let b = DMatrix::from_row_slice(2, 3, &[1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+    let c = DMatrix::from_row_slice(2, 2, &[0.0, 1.0, 1.0, 0.0]);
+    let (a_csr, b_csr, bt_csr, c_csr) = (
+        CsrMatrix::from(&a),
+        CsrMatrix::from(&b),
+        CsrMatrix::from(&b.transpose()),
+        CsrMatrix::from(&c),
+    );
+
+    // Blocks given more than once are summed
+    let matrix = layout
+        .try_assemble_matrix(&[
+            (0, 0, &a_csr),
+            (0, 1, &bt_csr),
+            (1, 0, &b_csr),
+            (1, 1, &c_csr),
+            (1, 1, &c_csr),
+        ])
+        .unwrap();
+    let mut expected = DMatrix::zeros(5, 5);
+    expected.view_mut((0, 0), (3, 3)).copy_from(&a);
+    expected.view_mut((0, 3), (3, 2)).copy_from(&b.transpose());
+    expected.view_mut((3, 0), (2, 3)).copy_from(&b);
+    expected.view_mut((3, 3), (2, 2)).copy_from(&(2.0 * c));
+    assert_matrix_eq!(DMatrix::from(&matrix), expected);
+    assert_eq!(matrix.nnz(), 7 + 2 * 3 + 2);
+
+    // Dimension mismatches and out-of-bounds blocks
+    assert!(layout.try_assemble_matrix(&[(0, 1, &b_csr)]).is_err());
+    assert!(layout.try_assemble_matrix(&[(2, 0, &b_csr)]).is_err());
+}
+
+#[test]
+fn mixed_assembly_with_value_coupling_matches_mass_matrix() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let gauss = quadrature::tensor::quadrilateral_gauss(2);
+
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), ());
+    let assembler = ElementMixedAssembler::new()
+        .with_test_space(&mesh)
+        .with_trial_space(&mesh)
+        .with_operator(&ValueCoupling)
+        .with_quadrature_table(&qtable);
+    let mixed = CsrAssembler::default().assemble_mixed(&assembler).unwrap();
+
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss, Density(1.0));
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_table);
+    let mass = CsrAssembler::default().assemble(&mass_assembler).unwrap();
+
+    assert_eq!(mixed.pattern(), mass.pattern());
+    assert_matrix_eq!(DMatrix::from(&mixed), DMatrix::from(&mass), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn mixed_assembly_of_divergence_coupling_between_quad9_and_quad4() {
+    let pressure_mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let velocity_mesh = Quad9Mesh2d::from(pressure_mesh.clone());
+    let gauss = quadrature::tensor::quadrilateral_gauss(3);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), ());
+
+    let assembler = ElementMixedAssembler::new()
+        .with_test_space(&pressure_mesh)
+        .with_trial_space(&velocity_mesh)
+        .with_operator(&DivergenceCoupling)
+        .with_quadrature_table(&qtable);
+    let b = CsrAssembler::default().assemble_mixed(&assembler).unwrap();
+    let num_pressure_nodes = pressure_mesh.vertices().len();
+    let num_velocity_nodes = velocity_mesh.vertices().len();
+    assert_eq!(b.nrows(), num_pressure_nodes);
+    assert_eq!(b.ncols(), 2 * num_velocity_nodes);
+
+    // The quadratic space interpolates u(x, y) = (x^2, 3y) exactly, so that B u = -M_p (2x + 3),
+    // where M_p is the pressure mass matrix
+    let u = DVector::from_iterator(
+        2 * num_velocity_nodes,
+        velocity_mesh
+            .vertices()
+            .iter()
+            .flat_map(|v| [v.x * v.x, 3.0 * v.y]),
+    );
+    let div_u = DVector::from_iterator(
+        num_pressure_nodes,
+        pressure_mesh.vertices().iter().map(|v| 2.0 * v.x + 3.0),
+    );
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss, Density(1.0));
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&pressure_mesh)
+        .with_quadrature_table(&mass_table);
+    let mass = CsrAssembler::default().assemble(&mass_assembler).unwrap();
+    assert_matrix_eq!(&b * &u, -(&mass * &div_u), comp = abs, tol = 1e-13);
+
+    // Spaces with different numbers of elements are rejected
+    let coarse_mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let assembler = ElementMixedAssembler::new()
+        .with_test_space(&coarse_mesh)
+        .with_trial_space(&velocity_mesh)
+        .with_operator(&DivergenceCoupling)
+        .with_quadrature_table(&qtable);
+    assert!(CsrAssembler::default().assemble_mixed(&assembler).is_err());
+}
+
+#[test]
+fn block_system_assembler_matches_manual_block_assembly() {
+    let pressure_mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let velocity_mesh = Quad9Mesh2d::from(pressure_mesh.clone());
+    let gauss = quadrature::tensor::quadrilateral_gauss(3);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), ());
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss, Density(1.0));
+
+    let velocity_mass = ElementMassAssembler::with_solution_dim(2)
+        .with_space(&velocity_mesh)
+        .with_quadrature_table(&mass_table);
+    let pressure_mass = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&pressure_mesh)
+        .with_quadrature_table(&mass_table);
+    let divergence = ElementMixedAssembler::new()
+        .with_test_space(&pressure_mesh)
+        .with_trial_space(&velocity_mesh)
+        .with_operator(&DivergenceCoupling)
+        .with_quadrature_table(&qtable);
+    let source = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&pressure_mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+
+    let mut system = BlockSystemAssembler::new();
+    let u = system.add_field(&velocity_mesh, 2);
+    let p = system.add_field(&pressure_mesh, 1);
+    system
+        .add_operator(u, &velocity_mass)
+        .add_symmetric_coupling(p, u, &divergence)
+        .add_operator(p, &pressure_mass)
+        .add_operator(p, &pressure_mass)
+        .add_source(p, &source);
+    let (matrix, rhs) = system.assemble().unwrap();
+
+    let layout = system.layout();
+    assert_eq!(layout.num_blocks(), 2);
+    assert_eq!(layout.block_size(u), 2 * velocity_mesh.vertices().len());
+    assert_eq!(layout.block_size(p), pressure_mesh.vertices().len());
+
+    let csr_assembler = CsrAssembler::default();
+    let m_u = csr_assembler.assemble(&velocity_mass).unwrap();
+    let m_p = csr_assembler.assemble(&pressure_mass).unwrap();
+    let b = csr_assembler.assemble_mixed(&divergence).unwrap();
+    let expected_matrix = layout
+        .try_assemble_matrix(&[(u, u, &m_u), (u, p, &b.transpose()), (p, u, &b), (p, p, &(2.0 * m_p))])
+        .unwrap();
+    assert_matrix_eq!(
+        DMatrix::from(&matrix),
+        DMatrix::from(&expected_matrix),
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_matrix_eq!(
+        DMatrix::from(&matrix),
+        DMatrix::from(&matrix).transpose(),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    let f_p = VectorAssembler::default().assemble_vector(&source).unwrap();
+    assert_eq!(layout.block(u, &rhs).amax(), 0.0);
+    assert_matrix_eq!(layout.block(p, &rhs), f_p, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn block_system_assembler_rejects_incompatible_assemblers() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(1.0),
+    );
+    let mass = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable);
+
+    // Solution dimension mismatch
+    let mut system = BlockSystemAssembler::new();
+    let u = system.add_field(&mesh, 2);
+    system.add_operator(u, &mass);
+    assert!(system.assemble_matrix().is_err());
+
+    // Field out of bounds
+    let mut system = BlockSystemAssembler::new();
+    let u = system.add_field(&mesh, 1);
+    system.add_operator(u + 1, &mass);
+    assert!(system.assemble_matrix().is_err());
+
+    // Number of nodes mismatch
+    let mut system = BlockSystemAssembler::from_layout(BlockLayout::new().with_block(3, 1));
+    system.add_operator(0, &mass);
+    assert!(system.assemble_matrix().is_err());
+    assert_eq!(system.assemble_vector().unwrap().len(), 3);
+}
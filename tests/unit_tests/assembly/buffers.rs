@@ -0,0 +1,128 @@
+use fenris::assembly::buffers::PreparedElementBuffer;
+use fenris::assembly::global::gather_global_to_local;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, ElementMatrixAssembler, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::Quad4d2Connectivity;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix2, Point2, Vector2, U1};
+use fenris::quadrature;
+use fenris::space::FiniteElementSpace;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn distorted_quad_mesh() -> QuadMesh2d<f64> {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    mesh.transform_vertices(|v| {
+        let (x, y) = (v.x, v.y);
+        v.x += 0.1 * x * y * (1.0 - x);
+        v.y += 0.15 * x * x * (1.0 - y);
+    });
+    mesh
+}
+
+#[test]
+fn prepared_element_buffer_reproduces_laplace_stiffness_and_geometry() {
+    let mesh = distorted_quad_mesh();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    let mut buffer = PreparedElementBuffer::default();
+    for element_index in 0..mesh.connectivity().len() {
+        buffer
+            .prepare_element(&mesh, element_index, &qtable)
+            .unwrap();
+        assert_eq!(buffer.element_index(), element_index);
+        assert_eq!(buffer.element_nodes(), &mesh.connectivity()[element_index].0);
+        assert_eq!(buffer.num_quadrature_points(), 9);
+
+        let mut stiffness = DMatrix::zeros(4, 4);
+        for q in 0..buffer.num_quadrature_points() {
+            let xi = &buffer.quadrature().points()[q];
+            let jacobian = mesh.element_reference_jacobian(element_index, xi);
+            assert_matrix_eq!(buffer.jacobian(q), jacobian, comp = abs, tol = 1e-14);
+            assert_matrix_eq!(
+                buffer.jacobian(q) * buffer.inverse_jacobian(q),
+                Matrix2::identity(),
+                comp = abs,
+                tol = 1e-12
+            );
+            assert_scalar_eq!(
+                buffer.jacobian_determinant(q),
+                jacobian.determinant(),
+                comp = abs,
+                tol = 1e-14
+            );
+            assert_matrix_eq!(
+                buffer.physical_points()[q].coords,
+                mesh.map_element_reference_coords(element_index, xi).coords,
+                comp = abs,
+                tol = 1e-14
+            );
+            let g = buffer.basis_gradients(q);
+            stiffness += buffer.physical_weight(q) * g.transpose() * g;
+        }
+        let expected = assembler.assemble_element_matrix(element_index).unwrap();
+        assert_matrix_eq!(stiffness, expected, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn prepared_element_buffer_interpolates_linear_fields_exactly() {
+    let a = Vector2::new(2.0, -3.0);
+    let u_exact = |x: &Point2<f64>| a.dot(&x.coords) + 0.5;
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let tri_mesh = create_unit_square_uniform_tri_mesh_2d(3);
+    let quad_mesh = distorted_quad_mesh();
+    let quad_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    let mut buffer = PreparedElementBuffer::default();
+    let mut u_local = DVector::zeros(0);
+    let mut check_element = |buffer: &PreparedElementBuffer<f64, _>, u: &DVector<f64>| {
+        u_local.resize_vertically_mut(buffer.element_nodes().len(), 0.0);
+        gather_global_to_local(u, &mut u_local, buffer.element_nodes(), 1);
+        for q in 0..buffer.num_quadrature_points() {
+            let x = &buffer.physical_points()[q];
+            let value = buffer.interpolate::<U1>(&u_local, q);
+            let gradient = buffer.interpolate_gradient::<U1>(&u_local, q);
+            assert_scalar_eq!(value[0], u_exact(x), comp = abs, tol = 1e-12);
+            assert_matrix_eq!(gradient, a, comp = abs, tol = 1e-12);
+            assert_scalar_eq!(buffer.basis_values(q).iter().sum::<f64>(), 1.0, comp = abs, tol = 1e-12);
+        }
+    };
+
+    let u = DVector::from_iterator(tri_mesh.vertices().len(), tri_mesh.vertices().iter().map(u_exact));
+    for element_index in 0..tri_mesh.connectivity().len() {
+        buffer
+            .prepare_element(&tri_mesh, element_index, &qtable)
+            .unwrap();
+        check_element(&buffer, &u);
+    }
+    // Reuse the same buffer for elements of a different space and quadrature size
+    let u = DVector::from_iterator(quad_mesh.vertices().len(), quad_mesh.vertices().iter().map(u_exact));
+    for element_index in 0..quad_mesh.connectivity().len() {
+        buffer
+            .prepare_element(&quad_mesh, element_index, &quad_qtable)
+            .unwrap();
+        check_element(&buffer, &u);
+    }
+}
+
+#[test]
+fn prepared_element_buffer_rejects_singular_elements() {
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(3.0, 0.0),
+    ];
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(vertices, vec![Quad4d2Connectivity([0, 1, 2, 3])]);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let mut buffer = PreparedElementBuffer::<f64, _>::default();
+    assert!(buffer.prepare_element(&mesh, 0, &qtable).is_err());
+}
@@ -0,0 +1,176 @@
+use fenris::assembly::flux::{
+    BurgersEquation, FaceFluxIntegrator, LaxFriedrichsFlux, LinearAdvection, NumericalFlux, RusanovFlux,
+    ScalarConservationLaw, UpwindFlux,
+};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::nalgebra::{Point2, Vector2, Vector3, U2};
+use fenris::quadrature;
+use matrixcompare::assert_scalar_eq;
+
+fn normals_2d() -> Vec<Vector2<f64>> {
+    vec![
+        Vector2::new(1.0, 0.0),
+        Vector2::new(0.0, -1.0),
+        Vector2::new(0.6, 0.8),
+        Vector2::new(-0.8, 0.6),
+    ]
+}
+
+fn assert_consistent_and_conservative<Law, Flux>(law: &Law, flux: &Flux)
+where
+    Law: ScalarConservationLaw<f64, U2>,
+    Flux: NumericalFlux<f64, U2>,
+{
+    let x = Point2::new(0.3, 0.7);
+    let states = [-2.0, -0.5, 0.0, 0.25, 1.5];
+    for n in normals_2d() {
+        for &a in &states {
+            let f_a = law.flux(a, &x).dot(&n);
+            assert_scalar_eq!(
+                flux.compute_normal_flux(law, a, a, &n, &x),
+                f_a,
+                comp = abs,
+                tol = 1e-14
+            );
+            for &b in &states {
+                let forward = flux.compute_normal_flux(law, a, b, &n, &x);
+                let backward = flux.compute_normal_flux(law, b, a, &-n, &x);
+                assert_scalar_eq!(forward, -backward, comp = abs, tol = 1e-14);
+            }
+        }
+    }
+}
+
+#[test]
+fn numerical_fluxes_are_consistent_and_conservative() {
+    let advection = LinearAdvection::new(Vector2::new(1.0, -0.5));
+    let burgers = BurgersEquation::new(Vector2::new(0.6, 0.8));
+    assert_consistent_and_conservative(&advection, &UpwindFlux);
+    assert_consistent_and_conservative(&advection, &LaxFriedrichsFlux::new(2.0));
+    assert_consistent_and_conservative(&advection, &RusanovFlux);
+    assert_consistent_and_conservative(&burgers, &UpwindFlux);
+    assert_consistent_and_conservative(&burgers, &LaxFriedrichsFlux::new(2.0));
+    assert_consistent_and_conservative(&burgers, &RusanovFlux);
+}
+
+#[test]
+fn upwind_flux_selects_upwind_state() {
+    let x = Point2::origin();
+    let advection = LinearAdvection::new(Vector2::new(2.0, 0.0));
+    let n = Vector2::new(1.0, 0.0);
+    assert_eq!(UpwindFlux.compute_normal_flux(&advection, 3.0, 5.0, &n, &x), 6.0);
+    assert_eq!(UpwindFlux.compute_normal_flux(&advection, 3.0, 5.0, &-n, &x), -10.0);
+
+    // For Burgers, a shock with u- > u+ moves in the direction of the normal with speed
+    // (u- + u+) / 2, while a rarefaction with u- < u+ < 0 moves against it
+    let burgers = BurgersEquation::new(Vector2::new(1.0, 0.0));
+    assert_eq!(UpwindFlux.compute_normal_flux(&burgers, 2.0, 0.0, &n, &x), 2.0);
+    assert_eq!(UpwindFlux.compute_normal_flux(&burgers, -2.0, -1.0, &n, &x), 0.5);
+    assert_eq!(UpwindFlux.compute_normal_flux(&burgers, 1.0, 2.0, &n, &x), 0.5);
+}
+
+#[test]
+fn rusanov_flux_matches_lax_friedrichs_with_local_dissipation() {
+    let x = Point2::origin();
+    let n = Vector2::new(0.6, 0.8);
+    let burgers = BurgersEquation::new(Vector2::new(1.0, 0.0));
+    let (u_minus, u_plus) = (-1.0, 3.0);
+    // The characteristic speeds are 0.6 * u, with the largest magnitude 1.8 for u = 3
+    let rusanov = RusanovFlux.compute_normal_flux(&burgers, u_minus, u_plus, &n, &x);
+    let lax_friedrichs = LaxFriedrichsFlux::new(1.8).compute_normal_flux(&burgers, u_minus, u_plus, &n, &x);
+    assert_scalar_eq!(rusanov, lax_friedrichs, comp = abs, tol = 1e-14);
+    // Central flux 0.5 * 0.6 * (0.5 + 4.5) = 1.5 minus dissipation 0.5 * 1.8 * 4 = 3.6
+    assert_scalar_eq!(rusanov, -2.1, comp = abs, tol = 1e-14);
+
+    // For linear advection, Rusanov dissipation is |beta . n|, which recovers the upwind flux
+    let advection = LinearAdvection::new(Vector2::new(-1.0, 2.0));
+    for n in normals_2d() {
+        let rusanov = RusanovFlux.compute_normal_flux(&advection, 0.5, -1.5, &n, &x);
+        let upwind = UpwindFlux.compute_normal_flux(&advection, 0.5, -1.5, &n, &x);
+        assert_scalar_eq!(rusanov, upwind, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn piecewise_constant_residual_vanishes_for_constant_state() {
+    let advection = LinearAdvection::new(Vector2::new(1.0, 0.5));
+    let face_quadrature = || quadrature::univariate::gauss(2);
+
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let integrator = FaceFluxIntegrator::new(&quad_mesh, &advection, &UpwindFlux, face_quadrature());
+    let u = vec![2.0; quad_mesh.connectivity().len()];
+    let residual = integrator
+        .assemble_piecewise_constant_residual(&u, |_| 2.0)
+        .unwrap();
+    assert_scalar_eq!(residual.amax(), 0.0, comp = abs, tol = 1e-13);
+
+    let tri_mesh = create_unit_square_uniform_tri_mesh_2d(3);
+    let integrator = FaceFluxIntegrator::new(&tri_mesh, &advection, &RusanovFlux, face_quadrature());
+    let u = vec![2.0; tri_mesh.connectivity().len()];
+    let residual = integrator
+        .assemble_piecewise_constant_residual(&u, |_| 2.0)
+        .unwrap();
+    assert_scalar_eq!(residual.amax(), 0.0, comp = abs, tol = 1e-13);
+    assert!(integrator
+        .assemble_piecewise_constant_residual(&u[1..], |_| 2.0)
+        .is_err());
+
+    let advection = LinearAdvection::new(Vector3::new(1.0, -0.5, 0.25));
+    let tet_mesh = create_unit_box_uniform_tet_mesh_3d(2);
+    let integrator = FaceFluxIntegrator::new(
+        &tet_mesh,
+        &advection,
+        &UpwindFlux,
+        quadrature::total_order::triangle(1).unwrap(),
+    );
+    let u = vec![-1.0; tet_mesh.connectivity().len()];
+    let residual = integrator
+        .assemble_piecewise_constant_residual(&u, |_| -1.0)
+        .unwrap();
+    assert_scalar_eq!(residual.amax(), 0.0, comp = abs, tol = 1e-13);
+}
+
+#[test]
+fn piecewise_constant_residual_sums_to_net_boundary_flux() {
+    let burgers = BurgersEquation::new(Vector2::new(1.0, 1.0));
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let flux = LaxFriedrichsFlux::new(3.0);
+    let integrator = FaceFluxIntegrator::new(&mesh, &burgers, &flux, quadrature::univariate::gauss(2));
+    let u: Vec<f64> = (0..mesh.connectivity().len())
+        .map(|i| (i as f64 * 0.7).sin())
+        .collect();
+    let inflow = |x: &Point2<f64>| x.x - x.y;
+
+    let residual = integrator
+        .assemble_piecewise_constant_residual(&u, inflow)
+        .unwrap();
+    let boundary_flux: f64 = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(_, cell, local_face)| {
+            integrator
+                .integrate_face(cell, local_face, |_| u[cell], inflow)
+                .unwrap()
+        })
+        .sum();
+    assert_scalar_eq!(residual.sum(), boundary_flux, comp = abs, tol = 1e-12);
+
+    // The normal of the face of a cell points out of the cell: a unit state advected to the
+    // right has outflow 1/4 through the right face of the bottom left cell
+    let advection = LinearAdvection::new(Vector2::new(1.0, 0.0));
+    let integrator = FaceFluxIntegrator::new(&mesh, &advection, &UpwindFlux, quadrature::univariate::gauss(1));
+    let face_fluxes: Vec<f64> = (0..4)
+        .map(|local_face| {
+            integrator
+                .integrate_face(0, local_face, |_| 1.0, |_| 0.0)
+                .unwrap()
+        })
+        .collect();
+    assert_scalar_eq!(face_fluxes.iter().sum::<f64>(), 0.25, comp = abs, tol = 1e-14);
+    assert!(face_fluxes
+        .iter()
+        .all(|&f| f == 0.0 || (f - 0.25).abs() < 1e-14));
+}
@@ -7,13 +7,20 @@ use proptest::prelude::*;
 use eyre::eyre;
 use fenris::assembly::global::{
     apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_matrix, assemble_scalar, gather_global_to_local,
-    par_assemble_scalar, CsrAssembler, CsrParAssembler,
+    par_assemble_scalar, CsrAssembler, CsrParAssembler, PrecomputedCsrAssembler,
 };
-use fenris::assembly::local::{ElementConnectivityAssembler, ElementScalarAssembler};
+use fenris::assembly::local::{
+    Density, ElementConnectivityAssembler, ElementMassAssembler, ElementScalarAssembler,
+    ReplicatedElementMatrixAssembler, UniformQuadratureTable,
+};
+use fenris::index::{ElementIndex, NodeIndex};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
 use fenris::nalgebra::{DMatrix, DVector, U2};
 use fenris::nalgebra_sparse::pattern::SparsityPattern;
 use fenris::nalgebra_sparse::CsrMatrix;
-use matrixcompare::assert_scalar_eq;
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 
 #[test]
 fn apply_homogeneous_dirichlet_bc_matrix_simple_example() {
@@ -215,6 +222,122 @@ fn csr_par_assemble_mock_pattern() {
     // TODO: Would be good to have some property tests...
 }
 
+#[test]
+fn precomputed_csr_assembler_matches_csr_assembler() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(2.0),
+    );
+    let element_assembler = ElementMassAssembler::with_solution_dim(2)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable);
+
+    let expected = CsrAssembler::default()
+        .assemble(&element_assembler)
+        .unwrap();
+    let assembler = PrecomputedCsrAssembler::from_connectivity(&mesh, 2);
+    assert_eq!(assembler.pattern(), expected.pattern());
+
+    let mut matrix = assembler.assemble(&element_assembler).unwrap();
+    assert_eq!(matrix, expected);
+
+    // Assembling again adds to the existing values, unless they are overwritten
+    assembler
+        .assemble_into_csr(&mut matrix, &element_assembler)
+        .unwrap();
+    assert_eq!(matrix.pattern(), expected.pattern());
+    assert_matrix_eq!(matrix, 2.0 * &expected, comp = float);
+    assembler
+        .assemble_into_csr_values(&mut matrix, &element_assembler)
+        .unwrap();
+    assert_eq!(matrix, expected);
+}
+
+#[test]
+fn precomputed_csr_assembler_rejects_incompatible_input() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(2.0),
+    );
+    let element_assembler = ElementMassAssembler::with_solution_dim(2)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable);
+
+    // Wrong solution dimension
+    let assembler = PrecomputedCsrAssembler::from_connectivity(&mesh, 1);
+    assert!(assembler.assemble(&element_assembler).is_err());
+
+    // Wrong sparsity pattern
+    let assembler = PrecomputedCsrAssembler::from_connectivity(&mesh, 2);
+    let mut matrix = CsrMatrix::identity(2 * mesh.vertices().len());
+    assert!(assembler
+        .assemble_into_csr(&mut matrix, &element_assembler)
+        .is_err());
+
+    // Different mesh
+    let other_mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let assembler = PrecomputedCsrAssembler::from_connectivity(&other_mesh, 2);
+    assert!(assembler.assemble(&element_assembler).is_err());
+}
+
+#[test]
+fn csr_assembler_update_elements_matches_full_assembly() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(2.0),
+    );
+    let mass_assembler = ElementMassAssembler::with_solution_dim(2)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable);
+    let num_elements = mesh.connectivity().len();
+    let element_assembler = |factors: Vec<f64>| {
+        ReplicatedElementMatrixAssembler::from_reference_element(mass_assembler.clone(), 0)
+            .unwrap()
+            .with_element_scaling(factors)
+            .unwrap()
+    };
+
+    let assembler = CsrAssembler::default();
+    let mut matrix = assembler
+        .assemble(&element_assembler(vec![1.0; num_elements]))
+        .unwrap();
+
+    // Change the material of two elements
+    let mut factors = vec![1.0; num_elements];
+    factors[5] = 3.0;
+    factors[15] = 0.5;
+    let modified = element_assembler(factors);
+    let num_reassembled = assembler
+        .update_elements_in_csr(&mut matrix, &modified, &[5, 15])
+        .unwrap();
+    // Elements 5 and 15 are connected to 9 and 4 elements, with one element in common
+    assert_eq!(num_reassembled, 12);
+
+    let expected = assembler.assemble(&modified).unwrap();
+    assert_eq!(matrix.pattern(), expected.pattern());
+    assert_matrix_eq!(matrix, expected, comp = abs, tol = 1e-15);
+
+    // Updating without any modified elements leaves the matrix untouched
+    assert_eq!(
+        assembler
+            .update_elements_in_csr(&mut matrix, &modified, &[])
+            .unwrap(),
+        0
+    );
+    assert_matrix_eq!(matrix, expected, comp = abs, tol = 1e-15);
+
+    assert!(assembler
+        .update_elements_in_csr(&mut matrix, &modified, &[num_elements])
+        .is_err());
+    let mut wrong_size = CsrMatrix::identity(3);
+    assert!(assembler
+        .update_elements_in_csr(&mut wrong_size, &modified, &[0])
+        .is_err());
+}
+
 fn gather_global_to_local_args() -> impl Strategy<Value = GatherGlobalToLocalArgs> {
     let sol_dim = 0..10usize;
     let num_nodes = 0..10usize;
@@ -254,12 +377,14 @@ impl ElementConnectivityAssembler for MockElementAssembler {
         self.num_nodes
     }
 
-    fn element_node_count(&self, element_index: usize) -> usize {
-        self.element_connectivities[element_index].len()
+    fn element_node_count(&self, element_index: ElementIndex) -> usize {
+        self.element_connectivities[element_index.index()].len()
     }
 
-    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
-        output.copy_from_slice(&self.element_connectivities[element_index])
+    fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+        output.copy_from_slice(NodeIndex::from_raw_slice(
+            &self.element_connectivities[element_index.index()],
+        ))
     }
 }
 
@@ -270,8 +395,8 @@ impl ElementConnectivityAssembler for MockScalarElementAssembler {
     fn solution_dim(&self) -> usize { unreachable!() }
     fn num_elements(&self) -> usize { 4 }
     fn num_nodes(&self) -> usize { unreachable!() }
-    fn element_node_count(&self, _element_index: usize) -> usize { unreachable!() }
-    fn populate_element_nodes(&self, _output: &mut [usize], _element_index: usize) { unreachable!() }
+    fn element_node_count(&self, _element_index: ElementIndex) -> usize { unreachable!() }
+    fn populate_element_nodes(&self, _output: &mut [NodeIndex], _element_index: ElementIndex) { unreachable!() }
 }
 
 #[rustfmt::skip]
@@ -0,0 +1,67 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::goal::{evaluate_linear_functional, PointValueGoal};
+use fenris::assembly::local::{ElementSourceAssemblerBuilder, SourceFunction, UniformQuadratureTable};
+use fenris::assembly::operators::Operator;
+use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
+use fenris::nalgebra::{point, DVector, Point2, Vector1, U1, U2};
+use fenris::quadrature;
+use fenris::space::SpatiallyIndexed;
+use matrixcompare::assert_scalar_eq;
+
+struct UnitSource;
+
+impl Operator<f64, U2> for UnitSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for UnitSource {
+    fn evaluate(&self, _x: &Point2<f64>, _data: &()) -> Vector1<f64> {
+        Vector1::new(1.0)
+    }
+}
+
+#[test]
+fn point_value_goal_evaluates_linear_field() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let field = |x: &Point2<f64>| [1.0 + 2.0 * x.x - 3.0 * x.y, x.x * 0.5 - x.y];
+    let u = DVector::from_iterator(2 * mesh.vertices().len(), mesh.vertices().iter().flat_map(field));
+
+    let x0 = point![0.3, 0.55];
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+    let goal = PointValueGoal::new(&space, &x0, &[2.0, -1.0]).unwrap();
+    let [u0, u1] = field(&x0);
+    let expected = 2.0 * u0 - u1;
+    assert_scalar_eq!(
+        evaluate_linear_functional(&goal, &u).unwrap(),
+        expected,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // The assembled goal vector represents the same functional
+    let j = VectorAssembler::default().assemble_vector(&goal).unwrap();
+    assert_eq!(j.len(), u.len());
+    assert_scalar_eq!(j.dot(&u), expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn source_assembler_evaluates_mean_value_goal() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let goal = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+    let u = DVector::from_iterator(
+        mesh.vertices().len(),
+        mesh.vertices().iter().map(|x| 1.0 + 2.0 * x.x - 3.0 * x.y),
+    );
+    assert_scalar_eq!(
+        evaluate_linear_functional(&goal, &u).unwrap(),
+        0.5,
+        comp = abs,
+        tol = 1e-12
+    );
+}
@@ -5,23 +5,65 @@ use fenris::assembly::local::{
     ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
     UniformQuadratureTable,
 };
-use fenris::assembly::operators::LaplaceOperator;
+use fenris::assembly::operators::{EllipticContraction, EllipticOperator, LaplaceOperator, Operator};
 use fenris::element::{Quad4d2Element, VolumetricFiniteElement};
 use fenris::geometry::Quad2d;
+use fenris::index::{ElementIndex, NodeIndex};
 use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
 use fenris::mesh::QuadMesh2d;
-use fenris::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, Matrix4, OPoint, OVector, Point2};
+use fenris::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, Matrix4, OPoint, OVector, Point2, Vector2, U2};
 use fenris::quadrature;
 use fenris::quadrature::QuadraturePair;
-use fenris::Real;
+use fenris::{Real, Symmetry};
 use itertools::izip;
 use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 use nalgebra::{DMatrixViewMut, Matrix2};
 use std::iter::repeat;
 
+mod advection_diffusion;
+mod condensation;
+mod convection;
+mod eigenstrain;
 mod elliptic;
+mod facet;
+mod incompatible_modes;
+mod interior_penalty;
 mod mass;
+mod membrane;
+mod quadrature_parameters;
+mod replicated;
 mod source;
+mod state;
+mod surface;
+mod surface_tension;
+mod traction;
+
+/// Linear elasticity with Lamé parameters $\mu$ and $\lambda$.
+pub(crate) struct LinearElasticity {
+    pub mu: f64,
+    pub lambda: f64,
+}
+
+impl Operator<f64, U2> for LinearElasticity {
+    type SolutionDim = U2;
+    type Parameters = ();
+}
+
+impl EllipticOperator<f64, U2> for LinearElasticity {
+    fn compute_elliptic_operator(&self, gradient: &Matrix2<f64>, _parameters: &()) -> Matrix2<f64> {
+        (gradient + gradient.transpose()) * self.mu + Matrix2::identity() * (self.lambda * gradient.trace())
+    }
+}
+
+impl EllipticContraction<f64, U2> for LinearElasticity {
+    fn contract(&self, _gradient: &Matrix2<f64>, a: &Vector2<f64>, b: &Vector2<f64>, _parameters: &()) -> Matrix2<f64> {
+        (Matrix2::identity() * a.dot(b) + b * a.transpose()) * self.mu + a * b.transpose() * self.lambda
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
 
 fn reference_quad<T>() -> Quad2d<T>
 where
@@ -150,8 +192,8 @@ fn element_connectivity_assembler_map_node() {
             6
         }
 
-        fn element_node_count(&self, element_index: usize) -> usize {
-            match element_index {
+        fn element_node_count(&self, element_index: ElementIndex) -> usize {
+            match element_index.index() {
                 0 => 3,
                 1 => 5,
                 2 => 4,
@@ -159,14 +201,14 @@ fn element_connectivity_assembler_map_node() {
             }
         }
 
-        fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
-            let slice = match element_index {
+        fn populate_element_nodes(&self, output: &mut [NodeIndex], element_index: ElementIndex) {
+            let slice = match element_index.index() {
                 0 => &[0, 2, 4].as_ref(),
                 1 => [1, 2, 3, 4, 5].as_ref(),
                 2 => &[0, 1, 3, 5].as_ref(),
                 _ => panic!(),
             };
-            output.copy_from_slice(slice);
+            output.copy_from_slice(NodeIndex::from_raw_slice(slice));
         }
     }
 
@@ -174,16 +216,16 @@ fn element_connectivity_assembler_map_node() {
     let mapped_assembler = MockElementConnectivityAssembler.map_element_nodes(new_node_count, |node_idx| 2 * node_idx);
     assert_eq!(mapped_assembler.num_nodes(), new_node_count);
 
-    let mut nodes = vec![0; 5];
+    let mut nodes = [NodeIndex::default(); 5];
 
-    mapped_assembler.populate_element_nodes(&mut nodes[0..3], 0);
-    assert_eq!(&nodes[0..3], &vec![0, 4, 8]);
+    mapped_assembler.populate_element_nodes(&mut nodes[0..3], ElementIndex::new(0));
+    assert_eq!(NodeIndex::as_raw_slice(&nodes[0..3]), &[0, 4, 8]);
 
-    mapped_assembler.populate_element_nodes(&mut nodes[0..5], 1);
-    assert_eq!(&nodes[0..5], &vec![2, 4, 6, 8, 10]);
+    mapped_assembler.populate_element_nodes(&mut nodes[0..5], ElementIndex::new(1));
+    assert_eq!(NodeIndex::as_raw_slice(&nodes[0..5]), &[2, 4, 6, 8, 10]);
 
-    mapped_assembler.populate_element_nodes(&mut nodes[0..4], 2);
-    assert_eq!(&nodes[0..4], &vec![0, 2, 6, 10]);
+    mapped_assembler.populate_element_nodes(&mut nodes[0..4], ElementIndex::new(2));
+    assert_eq!(NodeIndex::as_raw_slice(&nodes[0..4]), &[0, 2, 6, 10]);
 }
 
 #[test]
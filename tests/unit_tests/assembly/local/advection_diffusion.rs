@@ -0,0 +1,178 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    compute_supg_parameters, Density, ElementAdvectionDiffusionReactionAssembler, ElementEllipticAssemblerBuilder,
+    ElementMassAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{
+    AdvectionDiffusionReaction, AdvectionDiffusionReactionCoefficients, AdvectionDiffusionReactionOperator,
+    LaplaceOperator, Operator,
+};
+use fenris::mesh::procedural::{create_rectangular_uniform_quad_mesh_2d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, Vector2, U1, U2};
+use fenris::quadrature;
+use fenris::Symmetry;
+use matrixcompare::assert_matrix_eq;
+
+fn coefficients(
+    velocity: [f64; 2],
+    diffusivity: f64,
+    reaction: f64,
+    source: f64,
+) -> UniformQuadratureTable<f64, U2, AdvectionDiffusionReactionCoefficients<f64, U2>> {
+    UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(3),
+        AdvectionDiffusionReactionCoefficients::new(Vector2::from(velocity), diffusivity, reaction, source),
+    )
+}
+
+fn assemble_system(
+    mesh: &QuadMesh2d<f64>,
+    qtable: &UniformQuadratureTable<f64, U2, AdvectionDiffusionReactionCoefficients<f64, U2>>,
+    supg_parameters: Option<&[f64]>,
+) -> (DMatrix<f64>, DVector<f64>) {
+    let mut assembler = ElementAdvectionDiffusionReactionAssembler::new()
+        .with_space(mesh)
+        .with_operator(&AdvectionDiffusionReactionOperator)
+        .with_quadrature_table(qtable);
+    if let Some(supg_parameters) = supg_parameters {
+        assembler = assembler.with_supg_parameters(supg_parameters);
+    }
+    let a = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+    let b = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    (a, b)
+}
+
+/// A diffusion-reaction operator with spatially varying diffusivity that may declare its symmetry.
+struct DiffusionReaction(Symmetry);
+
+impl Operator<f64, U2> for DiffusionReaction {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl AdvectionDiffusionReaction<f64, U2> for DiffusionReaction {
+    fn evaluate_coefficients(&self, x: &Point2<f64>, _: &()) -> AdvectionDiffusionReactionCoefficients<f64, U2> {
+        AdvectionDiffusionReactionCoefficients::new(Vector2::zeros(), 1.0 + x.x * x.y, 2.0, 0.0)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        self.0
+    }
+}
+
+#[test]
+fn advection_diffusion_reaction_without_advection_is_stiffness_plus_mass() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let (a, _) = assemble_system(&mesh, &coefficients([0.0, 0.0], 2.0, 3.0, 0.0), None);
+
+    let gauss = quadrature::tensor::quadrilateral_gauss(3);
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), ());
+    let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&laplace_table)
+        .with_u(&u)
+        .build();
+    let k = DMatrix::from(
+        &CsrAssembler::default()
+            .assemble(&stiffness_assembler)
+            .unwrap(),
+    );
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), Density(1.0));
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_table);
+    let m = DMatrix::from(&CsrAssembler::default().assemble(&mass_assembler).unwrap());
+    assert_matrix_eq!(a, 2.0 * k + 3.0 * m, comp = abs, tol = 1e-12);
+
+    // Operators that declare symmetry produce the same matrix
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss, ());
+    let assemble = |symmetry| {
+        let operator = DiffusionReaction(symmetry);
+        let assembler = ElementAdvectionDiffusionReactionAssembler::new()
+            .with_space(&mesh)
+            .with_operator(&operator)
+            .with_quadrature_table(&qtable);
+        DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap())
+    };
+    let symmetric = assemble(Symmetry::Symmetric);
+    assert_matrix_eq!(symmetric, symmetric.transpose(), comp = abs, tol = 1e-14);
+    assert_matrix_eq!(symmetric, assemble(Symmetry::NonSymmetric), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn advection_diffusion_reaction_reproduces_linear_solutions() {
+    // For linear u = 1 + 2x - y and vanishing reaction, the equation holds with constant
+    // source f = beta . grad u, so that the residual vanishes at interior nodes, with and
+    // without stabilization
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let beta = [1.0, 0.5];
+    let qtable = coefficients(beta, 0.1, 0.0, 1.5);
+    let u = DVector::from_iterator(
+        mesh.vertices().len(),
+        mesh.vertices().iter().map(|p| 1.0 + 2.0 * p.x - p.y),
+    );
+    let ones = DVector::repeat(u.len(), 1.0);
+    let supg_parameters = compute_supg_parameters(&mesh, &AdvectionDiffusionReactionOperator, &qtable).unwrap();
+    assert!(supg_parameters.iter().all(|&tau| tau > 0.0));
+
+    for supg in [None, Some(supg_parameters.as_slice())] {
+        let (a, b) = assemble_system(&mesh, &qtable, supg);
+        assert!((&a - a.transpose()).amax() > 1e-3);
+        assert_matrix_eq!(&a * &ones, DVector::zeros(u.len()), comp = abs, tol = 1e-12);
+        let residual = &a * &u - &b;
+        for (p, r) in mesh.vertices().iter().zip(residual.iter()) {
+            let is_interior = [p.x, p.y].iter().all(|&x| x > 1e-12 && x < 1.0 - 1e-12);
+            if is_interior {
+                assert!(r.abs() < 1e-12, "Residual {r} at interior node {p}");
+            }
+        }
+    }
+}
+
+#[test]
+fn supg_removes_oscillations_in_boundary_layer() {
+    // The one-dimensional problem u' - kappa u'' = 1 with u(0) = u(1) = 0 has a boundary layer
+    // at x = 1. We solve it on a strip of square elements, so that the discrete solution is
+    // independent of y. With the optimal stabilization parameter, SUPG gives the exact solution
+    // at the nodes
+    let kappa = 1e-3;
+    let n = 20;
+    let mesh = create_rectangular_uniform_quad_mesh_2d(1.0 / n as f64, n, 1, 1, &Vector2::new(0.0, 1.0 / n as f64));
+    let qtable = coefficients([1.0, 0.0], kappa, 0.0, 1.0);
+    let exact = |x: f64| x - ((x - 1.0) / kappa).exp() + (-1.0 / kappa).exp();
+
+    let solve = |supg: Option<&[f64]>| {
+        let (a, b) = assemble_system(&mesh, &qtable, supg);
+        let is_free = |p: &Point2<f64>| p.x > 1e-12 && p.x < 1.0 - 1e-12;
+        let free: Vec<_> = (0..mesh.vertices().len())
+            .filter(|&i| is_free(&mesh.vertices()[i]))
+            .collect();
+        let a_free = a.select_rows(&free).select_columns(&free);
+        let b_free = b.select_rows(&free);
+        let u_free = a_free.lu().solve(&b_free).unwrap();
+        let mut u = DVector::zeros(mesh.vertices().len());
+        for (&i, &u_i) in free.iter().zip(u_free.iter()) {
+            u[i] = u_i;
+        }
+        u
+    };
+    let max_nodal_error = |u: &DVector<f64>| {
+        mesh.vertices()
+            .iter()
+            .zip(u.iter())
+            .map(|(p, u_i)| (u_i - exact(p.x)).abs())
+            .fold(0.0, f64::max)
+    };
+
+    let galerkin = solve(None);
+    assert!(max_nodal_error(&galerkin) > 1.0);
+
+    let supg_parameters = compute_supg_parameters(&mesh, &AdvectionDiffusionReactionOperator, &qtable).unwrap();
+    let supg = solve(Some(&supg_parameters));
+    assert!(max_nodal_error(&supg) < 1e-10);
+}
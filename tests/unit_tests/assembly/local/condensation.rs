@@ -0,0 +1,42 @@
+use fenris::assembly::local::{condense_element_matrix, condense_element_system, recover_condensed_dofs};
+use fenris::nalgebra::{DMatrix, DVector};
+use matrixcompare::assert_matrix_eq;
+
+fn spd_matrix(n: usize) -> DMatrix<f64> {
+    let a = DMatrix::from_fn(n, n, |i, j| ((3 * i + 7 * j) % 5) as f64 - 2.0);
+    &a * a.transpose() + DMatrix::identity(n, n) * n as f64
+}
+
+#[test]
+fn condensed_system_reproduces_full_solution() {
+    let k = spd_matrix(6);
+    let f = DVector::from_fn(6, |i, _| 1.0 + i as f64);
+    let u = k.clone().lu().solve(&f).unwrap();
+
+    let (k_condensed, f_condensed) = condense_element_system((&k).into(), (&f).into(), 4).unwrap();
+    assert_eq!(k_condensed.shape(), (4, 4));
+    assert_eq!(f_condensed.len(), 4);
+    let u_retained = k_condensed.clone().lu().solve(&f_condensed).unwrap();
+    assert_matrix_eq!(u_retained, u.rows(0, 4), comp = abs, tol = 1e-12);
+
+    let u_internal = recover_condensed_dofs((&k).into(), (&f).into(), (&u_retained).into()).unwrap();
+    assert_matrix_eq!(u_internal, u.rows(4, 2), comp = abs, tol = 1e-12);
+
+    let k_condensed_only = condense_element_matrix((&k).into(), 4).unwrap();
+    assert_matrix_eq!(k_condensed_only, k_condensed, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn condensation_without_internal_dofs_is_identity() {
+    let k = spd_matrix(4);
+    let k_condensed = condense_element_matrix((&k).into(), 4).unwrap();
+    assert_matrix_eq!(k_condensed, k, comp = abs, tol = 0.0);
+}
+
+#[test]
+fn condensation_of_singular_internal_block_fails() {
+    let mut k = spd_matrix(4);
+    k.view_mut((2, 0), (2, 4)).fill(0.0);
+    k.view_mut((0, 2), (4, 2)).fill(0.0);
+    assert!(condense_element_matrix((&k).into(), 2).is_err());
+}
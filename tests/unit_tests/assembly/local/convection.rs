@@ -0,0 +1,95 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementConvectionAssembler, UniformQuadratureTable};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn nodal_vector(mesh: &QuadMesh2d<f64>, s: usize, f: impl Fn(&Point2<f64>) -> Vec<f64>) -> DVector<f64> {
+    DVector::from_iterator(s * mesh.vertices().len(), mesh.vertices().iter().flat_map(f))
+}
+
+#[test]
+fn convection_matrix_reproduces_convective_term() {
+    // For bilinear u, v and constant c, v^T C u = int_Ω v c . grad u dx, which we can compute
+    // analytically on the unit square
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let velocity = nodal_vector(&mesh, 2, |_| vec![1.0, 2.0]);
+    let assembler = ElementConvectionAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_velocity(&velocity);
+    let c = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+
+    // c . grad u = 7
+    let u = nodal_vector(&mesh, 1, |p| vec![p.x + 3.0 * p.y]);
+    let ones = DVector::repeat(u.len(), 1.0);
+    let v = nodal_vector(&mesh, 1, |p| vec![p.x]);
+    assert_scalar_eq!(ones.dot(&(&c * &u)), 7.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(v.dot(&(&c * &u)), 3.5, comp = abs, tol = 1e-12);
+
+    // The convection matrix of a vector-valued solution acts on each component separately
+    let assembler2 = ElementConvectionAssembler::with_solution_dim(2)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_velocity(&velocity);
+    let c2 = DMatrix::from(&CsrAssembler::default().assemble(&assembler2).unwrap());
+    let u2 = nodal_vector(&mesh, 2, |p| vec![p.x + 3.0 * p.y, 2.0 * p.x * p.y]);
+    let u2_second: DVector<f64> = u2.rows_with_step(1, u.len(), 1).into_owned();
+    let expected = DVector::from_iterator(
+        u2.len(),
+        (&c * &u)
+            .iter()
+            .zip((&c * &u2_second).iter())
+            .flat_map(|(&a, &b)| [a, b]),
+    );
+    assert_matrix_eq!(&c2 * &u2, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn convection_matrix_uses_relative_velocity_with_mesh_velocity() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let velocity = nodal_vector(&mesh, 2, |p| vec![p.y, -p.x]);
+    let mesh_velocity = nodal_vector(&mesh, 2, |p| vec![0.5 * p.x, 1.0]);
+    let relative_velocity = &velocity - &mesh_velocity;
+
+    let assemble = |velocity: Option<&DVector<f64>>, mesh_velocity: Option<&DVector<f64>>| {
+        let mut assembler = ElementConvectionAssembler::with_solution_dim(1)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        if let Some(velocity) = velocity {
+            assembler = assembler.with_velocity(velocity);
+        }
+        if let Some(mesh_velocity) = mesh_velocity {
+            assembler = assembler.with_mesh_velocity(mesh_velocity);
+        }
+        DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap())
+    };
+
+    let c_ale = assemble(Some(&velocity), Some(&mesh_velocity));
+    let c_relative = assemble(Some(&relative_velocity), None);
+    assert_matrix_eq!(c_ale, c_relative, comp = abs, tol = 1e-14);
+
+    // A mesh moving with the transport velocity has no convection
+    let c_lagrangian = assemble(Some(&velocity), Some(&velocity));
+    assert_matrix_eq!(
+        c_lagrangian,
+        DMatrix::zeros(c_ale.nrows(), c_ale.ncols()),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    let c_mesh_only = assemble(None, Some(&mesh_velocity));
+    let c_velocity_only = assemble(Some(&velocity), None);
+    assert_matrix_eq!(c_ale, c_velocity_only + c_mesh_only, comp = abs, tol = 1e-14);
+
+    let invalid_velocity = DVector::zeros(3);
+    let assembler = ElementConvectionAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_velocity(&invalid_velocity);
+    assert!(CsrAssembler::default().assemble(&assembler).is_err());
+}
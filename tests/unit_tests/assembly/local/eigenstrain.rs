@@ -0,0 +1,141 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    assemble_element_eigenstrain_load_vector, assemble_element_elliptic_matrix, assemble_element_elliptic_vector,
+    ElementEllipticAssemblerBuilder, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{EigenstrainOperator, EigenstrainParameters};
+use fenris::element::{Quad4d2Element, ReferenceFiniteElement};
+use fenris::geometry::Quad2d;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{matrix, DMatrix, DVector, Matrix2, Point2};
+use fenris::quadrature;
+use fenris::quadrature::QuadratureParts;
+use matrixcompare::assert_matrix_eq;
+
+use crate::unit_tests::assembly::local::LinearElasticity;
+
+fn distorted_quad() -> Quad4d2Element<f64> {
+    Quad4d2Element::from(Quad2d([
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.3),
+        Point2::new(1.7, 1.5),
+        Point2::new(-0.2, 1.1),
+    ]))
+}
+
+fn eigenstrain() -> Matrix2<f64> {
+    matrix![0.02, 0.005;
+            0.005, -0.01]
+}
+
+#[test]
+fn eigenstrain_operator_shifts_gradient() {
+    let element = distorted_quad();
+    let operator = LinearElasticity { mu: 2.0, lambda: 5.0 };
+    let eigenstrain_operator = EigenstrainOperator::new(LinearElasticity { mu: 2.0, lambda: 5.0 });
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss(2);
+    let data = vec![EigenstrainParameters::new(eigenstrain(), ()); weights.len()];
+    let mut gradient_buffer = DMatrix::zeros(2, element.num_nodes());
+
+    // A displacement field whose gradient is exactly the (symmetric) eigenstrain is stress-free
+    let u_stress_free = DVector::from_iterator(
+        8,
+        element
+            .vertices()
+            .iter()
+            .flat_map(|x| (eigenstrain() * x.coords).data.0[0]),
+    );
+    let mut residual = DVector::zeros(8);
+    assemble_element_elliptic_vector(
+        (&mut residual).into(),
+        &element,
+        &eigenstrain_operator,
+        (&u_stress_free).into(),
+        &weights,
+        &points,
+        &data,
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+    assert_matrix_eq!(residual, DVector::zeros(8), comp = abs, tol = 1e-14);
+
+    // For a linear operator, the load vector is K u where u is the stress-free displacement
+    let mut load = DVector::zeros(8);
+    let u_arbitrary = DVector::from_fn(8, |i, _| 0.1 * (i as f64).sin());
+    assemble_element_eigenstrain_load_vector(
+        (&mut load).into(),
+        &element,
+        &operator,
+        (&u_arbitrary).into(),
+        QuadratureParts {
+            weights: &weights,
+            points: &points,
+            data: &data,
+        },
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+    let mut stiffness = DMatrix::zeros(8, 8);
+    assemble_element_elliptic_matrix(
+        (&mut stiffness).into(),
+        &element,
+        &operator,
+        (&u_arbitrary).into(),
+        &weights,
+        &points,
+        &vec![(); weights.len()],
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+    assert_matrix_eq!(load, &stiffness * &u_stress_free, comp = abs, tol = 1e-12);
+
+    // The residual of the eigenstrain operator is the residual of the operator minus the load
+    let mut residual = DVector::zeros(8);
+    assemble_element_elliptic_vector(
+        (&mut residual).into(),
+        &element,
+        &eigenstrain_operator,
+        (&u_arbitrary).into(),
+        &weights,
+        &points,
+        &data,
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+    assert_matrix_eq!(residual, &stiffness * &u_arbitrary - &load, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn eigenstrain_load_assembly_thermal_expansion() {
+    // A freely expanding body with uniform thermal strain undergoes the displacement u = alpha dT x,
+    // which must therefore solve K u = f for the assembled thermal load f
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let (alpha, delta_t) = (1e-3, 20.0);
+    let operator = EigenstrainOperator::new(LinearElasticity { mu: 1.0, lambda: 3.0 });
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        EigenstrainParameters::thermal_expansion(alpha, delta_t, ()),
+    );
+    let u = DVector::zeros(2 * mesh.vertices().len());
+    let elliptic_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&operator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let stiffness = CsrAssembler::default()
+        .assemble(&elliptic_assembler)
+        .unwrap();
+    let load = VectorAssembler::default()
+        .assemble_vector(&elliptic_assembler.eigenstrain_load())
+        .unwrap();
+
+    let u_expansion = DVector::from_iterator(
+        u.len(),
+        mesh.vertices()
+            .iter()
+            .flat_map(|x| (x.coords * alpha * delta_t).data.0[0]),
+    );
+    assert_matrix_eq!(&stiffness * &u_expansion, load, comp = abs, tol = 1e-12);
+}
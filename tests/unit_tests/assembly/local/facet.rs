@@ -0,0 +1,136 @@
+use fenris::assembly::local::{
+    cell_face_quadrature, integrate_cell_face, integrate_interior_face, interior_face_quadrature,
+    pair_face_reference_coords,
+};
+use fenris::connectivity::Connectivity;
+use fenris::element::{ElementConnectivity, FiniteElement};
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::sets::CellFace;
+use fenris::mesh::split::find_interior_faces;
+use fenris::nalgebra::point;
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn integrate_cell_face_satisfies_divergence_theorem() {
+    // The integral of x . n over the boundary of a cell is d times its volume
+    let mut quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    quad_mesh.transform_vertices(|v| *v = point![v.x + 0.2 * v.y, 0.5 * v.y]);
+    for cell_index in 0..quad_mesh.connectivity().len() {
+        let mut integral = 0.0;
+        for local_index in 0..4 {
+            let cell_face = CellFace {
+                cell_index,
+                local_index,
+            };
+            integral += integrate_cell_face(&quad_mesh, cell_face, &quadrature::univariate::gauss(2), |p| {
+                p.x.coords.dot(&p.normal)
+            })
+            .unwrap();
+        }
+        assert_scalar_eq!(integral, 2.0 * 0.125, comp = abs, tol = 1e-14);
+    }
+
+    let tet_mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(1);
+    let face_quadrature = quadrature::total_order::triangle(1).unwrap();
+    let mut total = 0.0;
+    for cell_index in 0..tet_mesh.connectivity().len() {
+        for local_index in 0..4 {
+            let cell_face = CellFace {
+                cell_index,
+                local_index,
+            };
+            total +=
+                integrate_cell_face(&tet_mesh, cell_face, &face_quadrature, |p| p.x.coords.dot(&p.normal)).unwrap();
+        }
+    }
+    assert_scalar_eq!(total, 3.0, comp = abs, tol = 1e-13);
+
+    let invalid_face = CellFace {
+        cell_index: 0,
+        local_index: 4,
+    };
+    assert!(cell_face_quadrature(&tet_mesh, invalid_face, &face_quadrature).is_err());
+}
+
+#[test]
+fn cell_face_quadrature_gives_reference_coords_on_cell_face() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let face_quadrature = quadrature::univariate::gauss(3);
+    let cell_face = CellFace {
+        cell_index: 3,
+        local_index: 2,
+    };
+    let points = cell_face_quadrature(&mesh, cell_face, &face_quadrature).unwrap();
+    let element = mesh.connectivity()[3].element(mesh.vertices()).unwrap();
+    let area: f64 = points.iter().map(|p| p.weight).sum();
+    assert_scalar_eq!(area, 0.5, comp = abs, tol = 1e-14);
+    for p in &points {
+        assert_matrix_eq!(
+            element.map_reference_coords(&p.reference_coords).coords,
+            p.x.coords,
+            comp = abs,
+            tol = 1e-12
+        );
+        // Points on the faces of a quadrilateral have one reference coordinate equal to -1 or 1
+        assert!(p
+            .reference_coords
+            .iter()
+            .any(|xi| (xi.abs() - 1.0).abs() < 1e-12));
+    }
+}
+
+#[test]
+fn interior_face_quadrature_pairs_reference_coords_of_adjacent_cells() {
+    let mut mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    mesh.transform_vertices(|v| *v = point![v.x + 0.1 * v.y * v.z, v.y, v.z - 0.2 * v.x]);
+    let face_quadrature = quadrature::total_order::triangle(2).unwrap();
+    let faces = find_interior_faces(&mesh);
+    assert!(!faces.is_empty());
+    for face in &faces {
+        let [first, second] = face
+            .cells
+            .map(|c| mesh.connectivity()[c].element(mesh.vertices()).unwrap());
+        let centroid_difference = second.map_reference_coords(&point![-0.5, -0.5, -0.5])
+            - first.map_reference_coords(&point![-0.5, -0.5, -0.5]);
+        for p in interior_face_quadrature(&mesh, face, &face_quadrature).unwrap() {
+            assert_matrix_eq!(
+                first.map_reference_coords(&p.reference_coords[0]).coords,
+                p.x.coords,
+                comp = abs,
+                tol = 1e-12
+            );
+            assert_matrix_eq!(
+                second.map_reference_coords(&p.reference_coords[1]).coords,
+                p.x.coords,
+                comp = abs,
+                tol = 1e-12
+            );
+            assert!(p.normal.dot(&centroid_difference) > 0.0);
+        }
+
+        let area = integrate_interior_face(&mesh, face, &face_quadrature, |_| 1.0).unwrap();
+        let face_conn = mesh.connectivity()[face.cells[0]]
+            .get_face_connectivity(face.local_faces[0])
+            .unwrap();
+        let face_element = face_conn.element(mesh.vertices()).unwrap();
+        let [a, b, c] = *face_element.vertices();
+        assert_scalar_eq!(area, 0.5 * (b - a).cross(&(c - a)).norm(), comp = abs, tol = 1e-13);
+
+        // Both parametrizations of the face map to the same physical point
+        let second_face_element = mesh.connectivity()[face.cells[1]]
+            .get_face_connectivity(face.local_faces[1])
+            .unwrap()
+            .element(mesh.vertices())
+            .unwrap();
+        for xi_face in &face_quadrature.1 {
+            let xi_second = pair_face_reference_coords(&mesh, face, xi_face).unwrap();
+            assert_matrix_eq!(
+                second_face_element.map_reference_coords(&xi_second).coords,
+                face_element.map_reference_coords(xi_face).coords,
+                comp = abs,
+                tol = 1e-12
+            );
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use fenris::assembly::local::{
+    assemble_element_elliptic_matrix, assemble_element_incompatible_modes_matrix, ElementEllipticAssemblerBuilder,
+    ElementMatrixAssembler, UniformQuadratureTable,
+};
+use fenris::connectivity::{CellConnectivity, Quad4d2Connectivity};
+use fenris::element::{Quad4d2Element, ReferenceFiniteElement};
+use fenris::geometry::Quad2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, Vector2};
+use fenris::quadrature;
+use fenris::quadrature::QuadratureParts;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+use crate::unit_tests::assembly::local::LinearElasticity;
+
+fn nodal_values(element: &Quad4d2Element<f64>, f: impl Fn(&Point2<f64>) -> Vector2<f64>) -> DVector<f64> {
+    DVector::from_iterator(8, element.vertices().iter().flat_map(|x| f(x).data.0[0]))
+}
+
+fn element_matrices(element: &Quad4d2Element<f64>, operator: &LinearElasticity) -> (DMatrix<f64>, DMatrix<f64>) {
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss(2);
+    let data = vec![(); weights.len()];
+    let u = DVector::zeros(8);
+    let mut gradient_buffer = DMatrix::zeros(2, element.num_nodes());
+
+    let mut standard = DMatrix::zeros(8, 8);
+    assemble_element_elliptic_matrix(
+        (&mut standard).into(),
+        element,
+        operator,
+        (&u).into(),
+        &weights,
+        &points,
+        &data,
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+
+    let mut enhanced = DMatrix::zeros(8, 8);
+    assemble_element_incompatible_modes_matrix(
+        (&mut enhanced).into(),
+        element,
+        operator,
+        (&u).into(),
+        QuadratureParts {
+            weights: &weights,
+            points: &points,
+            data: &data,
+        },
+        (&mut gradient_buffer).into(),
+    )
+    .unwrap();
+    (standard, enhanced)
+}
+
+#[test]
+fn incompatible_modes_quad4_pass_patch_test_on_distorted_element() {
+    // For a linear displacement field, the incompatible modes must not be activated,
+    // so the enhanced element must produce the same nodal forces as the standard element
+    let element = Quad4d2Element::from(Quad2d([
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.3),
+        Point2::new(1.7, 1.5),
+        Point2::new(-0.2, 1.1),
+    ]));
+    let operator = LinearElasticity { mu: 2.0, lambda: 5.0 };
+    let (standard, enhanced) = element_matrices(&element, &operator);
+
+    assert_matrix_eq!(enhanced, enhanced.transpose(), comp = abs, tol = 1e-12);
+
+    let u_linear = nodal_values(&element, |x| {
+        Vector2::new(0.1 + 0.3 * x.x - 0.2 * x.y, -0.4 + 0.5 * x.x + 0.25 * x.y)
+    });
+    assert_matrix_eq!(&enhanced * &u_linear, &standard * &u_linear, comp = abs, tol = 1e-12);
+
+    // Rigid body modes must remain zero energy modes
+    let u_rigid = nodal_values(&element, |x| Vector2::new(1.0 - x.y, 2.0 + x.x));
+    assert_matrix_eq!(&enhanced * &u_rigid, DVector::zeros(8), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn incompatible_modes_quad4_reproduce_pure_bending() {
+    // Pure bending of a rectangular beam segment [-a, a] x [-b, b] with zero Poisson ratio
+    // has the exact displacement field u = kappa * (x y, -x^2 / 2), which the standard
+    // bilinear element cannot represent without spurious shear strain
+    let (a, b) = (2.0, 0.5);
+    let element = Quad4d2Element::from(Quad2d([
+        Point2::new(-a, -b),
+        Point2::new(a, -b),
+        Point2::new(a, b),
+        Point2::new(-a, b),
+    ]));
+    let youngs_modulus = 3.0;
+    let operator = LinearElasticity {
+        mu: 0.5 * youngs_modulus,
+        lambda: 0.0,
+    };
+    let (standard, enhanced) = element_matrices(&element, &operator);
+
+    let kappa = 0.1;
+    let u = nodal_values(&element, |x| Vector2::new(kappa * x.x * x.y, -0.5 * kappa * x.x * x.x));
+    let exact_energy = 0.5 * youngs_modulus * kappa * kappa * (2.0 * a) * (2.0 * b.powi(3) / 3.0);
+    let standard_energy = 0.5 * u.dot(&(&standard * &u));
+    let enhanced_energy = 0.5 * u.dot(&(&enhanced * &u));
+
+    assert_scalar_eq!(enhanced_energy, exact_energy, comp = abs, tol = 1e-12);
+    // The parasitic shear energy of the standard element is a^2 / (2 b^2) times the bending energy
+    assert_scalar_eq!(
+        standard_energy,
+        exact_energy * (1.0 + a * a / (2.0 * b * b)),
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn incompatible_modes_assembler_matches_element_routine() {
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(
+        vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.1),
+            Point2::new(2.0, 0.0),
+            Point2::new(-0.1, 1.0),
+            Point2::new(1.2, 0.9),
+            Point2::new(2.0, 1.1),
+        ],
+        vec![Quad4d2Connectivity([0, 1, 4, 3]), Quad4d2Connectivity([1, 2, 5, 4])],
+    );
+    let operator = LinearElasticity { mu: 1.0, lambda: 2.0 };
+    let u = DVector::zeros(2 * mesh.vertices().len());
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss::<f64>(2));
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&operator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build()
+        .with_incompatible_modes();
+
+    for element_index in 0..2 {
+        let cell = &mesh.connectivity()[element_index];
+        let element = Quad4d2Element::from(cell.cell(mesh.vertices()).unwrap());
+        let (_, expected) = element_matrices(&element, &operator);
+        let matrix = assembler.assemble_element_matrix(element_index).unwrap();
+        assert_matrix_eq!(matrix, expected, comp = abs, tol = 1e-12);
+    }
+}
@@ -0,0 +1,88 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, InteriorPenaltyFaceAssembler, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::Segment2d2Connectivity;
+use fenris::element::ElementConnectivity;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::Mesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, U2};
+use fenris::quadrature;
+use fenris::quadrature::QuadraturePair2d;
+use fenris::space::{DiscontinuousSpace, FiniteElementConnectivity};
+use matrixcompare::assert_matrix_eq;
+
+type DirichletData<'a> = &'a dyn Fn(&Point2<f64>) -> f64;
+
+fn assemble_sip_system<C>(
+    space: &DiscontinuousSpace<Mesh2d<f64, C>>,
+    volume_quadrature: QuadraturePair2d<f64>,
+    dirichlet_data: Option<DirichletData>,
+) -> (DMatrix<f64>, DVector<f64>)
+where
+    C: ElementConnectivity<f64, GeometryDim = U2, ReferenceDim = U2, FaceConnectivity = Segment2d2Connectivity>,
+{
+    let qtable = UniformQuadratureTable::from_quadrature(volume_quadrature);
+    let u = DVector::zeros(space.num_nodes());
+    let volume_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(space)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let mut face_assembler = InteriorPenaltyFaceAssembler::new(space, quadrature::univariate::gauss(2), 10.0).unwrap();
+    if let Some(g) = dirichlet_data {
+        face_assembler = face_assembler.with_dirichlet_data(g);
+    }
+
+    let a_volume = CsrAssembler::default().assemble(&volume_assembler).unwrap();
+    let a_faces = CsrAssembler::default().assemble(&face_assembler).unwrap();
+    let b = VectorAssembler::default()
+        .assemble_vector(&face_assembler)
+        .unwrap();
+    (DMatrix::from(&a_volume) + DMatrix::from(&a_faces), b)
+}
+
+fn check_sip_reproduces_linear_solution<C>(mesh: Mesh2d<f64, C>, volume_quadrature: QuadraturePair2d<f64>)
+where
+    C: ElementConnectivity<f64, GeometryDim = U2, ReferenceDim = U2, FaceConnectivity = Segment2d2Connectivity>,
+{
+    // The SIP method is consistent, so it reproduces solutions that lie in the discrete space
+    let u_exact = |x: &Point2<f64>| 1.0 + 2.0 * x.x - 0.5 * x.y;
+    let space = DiscontinuousSpace::from_space(mesh);
+    let (a, b) = assemble_sip_system(&space, volume_quadrature, Some(&u_exact));
+    assert_matrix_eq!(a, a.transpose(), comp = abs, tol = 1e-12);
+
+    let u = a.lu().solve(&b).unwrap();
+    let u_exact_continuous = DVector::from_iterator(
+        space.space().vertices().len(),
+        space.space().vertices().iter().map(u_exact),
+    );
+    let u_expected = space.map_continuous_vector(&u_exact_continuous, 1);
+    assert_matrix_eq!(u, u_expected, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn sip_reproduces_linear_solution_on_triangles() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d(3);
+    check_sip_reproduces_linear_solution(mesh, quadrature::total_order::triangle(2).unwrap());
+}
+
+#[test]
+fn sip_reproduces_linear_solution_on_quads() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    check_sip_reproduces_linear_solution(mesh, quadrature::tensor::quadrilateral_gauss(2));
+}
+
+#[test]
+fn sip_without_dirichlet_data_has_constants_in_null_space() {
+    let space = DiscontinuousSpace::from_space(create_unit_square_uniform_tri_mesh_2d(2));
+    let (a, b) = assemble_sip_system(&space, quadrature::total_order::triangle(2).unwrap(), None);
+    assert!(b.iter().all(|&b_i| b_i == 0.0));
+    let ones = DVector::repeat(space.num_nodes(), 1.0);
+    assert_matrix_eq!(&a * &ones, DVector::zeros(space.num_nodes()), comp = abs, tol = 1e-12);
+
+    // Discontinuous functions are penalized
+    let mut u = DVector::zeros(space.num_nodes());
+    u.rows_mut(0, 3).fill(1.0);
+    assert!(u.dot(&(&a * &u)) > 0.0);
+}
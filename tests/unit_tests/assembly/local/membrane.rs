@@ -0,0 +1,119 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{
+    assemble_element_elliptic_matrix, assemble_element_membrane_matrix, ElementMembraneAssemblerBuilder,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::solid::LameParameters;
+use fenris::assembly::operators::LinearElasticityOperator;
+use fenris::connectivity::Quad4d3Connectivity;
+use fenris::element::{Quad4d2Element, Quad4d3Element};
+use fenris::mesh::Mesh3d;
+use fenris::nalgebra::{DMatrix, DVector, DVectorView, Matrix2x4, Matrix3x2, Point2, Point3, Rotation3, Vector3};
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+/// A mesh of bilinear quads on the quarter cylinder of radius `r` and height 1 around the z-axis.
+fn quarter_cylinder(r: f64, cells_per_dim: usize) -> Mesh3d<f64, Quad4d3Connectivity> {
+    let n = cells_per_dim;
+    let mut vertices = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let theta = 0.5 * std::f64::consts::PI * i as f64 / n as f64;
+            vertices.push(Point3::new(r * theta.cos(), r * theta.sin(), j as f64 / n as f64));
+        }
+    }
+    let v = |i: usize, j: usize| j * (n + 1) + i;
+    let mut cells = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            cells.push(Quad4d3Connectivity([
+                v(i, j),
+                v(i + 1, j),
+                v(i + 1, j + 1),
+                v(i, j + 1),
+            ]));
+        }
+    }
+    Mesh3d::from_vertices_and_connectivity(vertices, cells)
+}
+
+#[test]
+fn membrane_matrix_of_flat_quad_matches_plane_stress_stiffness() {
+    let vertices_2d = [
+        Point2::new(0.1, 0.2),
+        Point2::new(1.3, -0.1),
+        Point2::new(1.1, 0.9),
+        Point2::new(0.2, 1.0),
+    ];
+    // Embed the quad in 3D through a rigid motion
+    let rotation = Rotation3::from_euler_angles(0.3, -0.7, 1.1);
+    let embedding = rotation.matrix() * Matrix3x2::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    let vertices_3d = vertices_2d.map(|x| Point3::from(embedding * x.coords + Vector3::new(1.0, 2.0, 3.0)));
+
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss(2);
+    let lame = LameParameters::from_young_poisson(2.0, 0.3);
+    let data = vec![lame; weights.len()];
+    let mut k_membrane = DMatrix::zeros(12, 12);
+    assemble_element_membrane_matrix(
+        (&mut k_membrane).into(),
+        &Quad4d3Element::from_vertices(vertices_3d),
+        &weights,
+        &points,
+        &data,
+        (&mut Matrix2x4::zeros()).into(),
+    )
+    .unwrap();
+
+    let mut k_planar = DMatrix::zeros(8, 8);
+    let u = DVector::zeros(8);
+    assemble_element_elliptic_matrix(
+        (&mut k_planar).into(),
+        &Quad4d2Element::from_vertices(vertices_2d),
+        &LinearElasticityOperator::plane_stress(),
+        DVectorView::from(&u),
+        &weights,
+        &points,
+        &data,
+        (&mut Matrix2x4::zeros()).into(),
+    )
+    .unwrap();
+
+    // Map the in-plane displacements of each node to 3D displacements
+    let e = DMatrix::from_column_slice(3, 2, embedding.as_slice());
+    let b = DMatrix::<f64>::identity(4, 4).kronecker(&e);
+    let expected = &b * k_planar * b.transpose();
+    assert_matrix_eq!(k_membrane, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn membrane_stiffness_of_curved_surface_has_rigid_body_modes_in_kernel() {
+    let mesh = quarter_cylinder(1.5, 4);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        LameParameters::from_young_poisson(10.0, 0.4),
+    );
+    let assembler = ElementMembraneAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .build();
+    let k = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+    let n = mesh.vertices().len();
+    assert_eq!(k.nrows(), 3 * n);
+    assert_matrix_eq!(k, k.transpose(), comp = abs, tol = 1e-12);
+
+    let translation = Vector3::new(0.3, -1.2, 0.7);
+    let omega = Vector3::new(-0.4, 0.9, 0.2);
+    let rigid_motion = |f: &dyn Fn(&Point3<f64>) -> Vector3<f64>| {
+        DVector::from_iterator(3 * n, mesh.vertices().iter().flat_map(|x| f(x).data.0[0]))
+    };
+    for u in [
+        rigid_motion(&|_| translation),
+        rigid_motion(&|x| omega.cross(&x.coords)),
+    ] {
+        assert_matrix_eq!(&k * u, DVector::zeros(3 * n), comp = abs, tol = 1e-12);
+    }
+
+    // A uniform radial expansion stretches the cylinder in the circumferential direction
+    let radial = rigid_motion(&|x| Vector3::new(x.x, x.y, 0.0));
+    assert!(radial.dot(&(&k * &radial)) > 1e-3);
+}
@@ -0,0 +1,101 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{
+    Density, ElementMassAssembler, GeneralQuadratureTable, QuadratureParameterStorage, QuadratureTable,
+    UniformQuadratureTable,
+};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, Vector2};
+use fenris::quadrature;
+use fenris::util::NestedVec;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn quadrature_parameter_storage_construction_and_access() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    let mut storage = QuadratureParameterStorage::from_constant(&table, 4, 3.0);
+    assert_eq!(storage.num_elements(), 4);
+    assert_eq!(storage.element_quadrature_size(2), 4);
+    assert_eq!(storage.element_parameters(1), &[3.0; 4]);
+    assert_eq!(storage.get(3, 3), Some(&3.0));
+    assert_eq!(storage.get(3, 4), None);
+    assert_eq!(storage.get(4, 0), None);
+
+    // History variables are updated in place
+    *storage.get_mut(1, 2).unwrap() = 5.0;
+    storage.element_parameters_mut(2)[0] = 7.0;
+    storage.update(|element_index, quadrature_index, value| {
+        if element_index == 0 && quadrature_index == 0 {
+            *value += 1.0;
+        }
+    });
+    assert_eq!(storage.element_parameters(0), &[4.0, 3.0, 3.0, 3.0]);
+    assert_eq!(storage.element_parameters(1), &[3.0, 3.0, 5.0, 3.0]);
+    assert_eq!(storage.element_parameters(2), &[7.0, 3.0, 3.0, 3.0]);
+
+    // Parameters evaluated at physical coordinates lie within the elements
+    let storage =
+        QuadratureParameterStorage::from_fn(&mesh, &table, |element_index, x: &Point2<f64>| (element_index, *x));
+    assert_eq!(storage.num_elements(), 4);
+    for element_index in 0..4 {
+        let parameters = storage.element_parameters(element_index);
+        assert_eq!(parameters.len(), 4);
+        assert!(parameters.iter().all(|(e, _)| *e == element_index));
+    }
+    let centroid_of_first_element = storage
+        .element_parameters(0)
+        .iter()
+        .fold(Point2::origin(), |sum, (_, x)| sum + x.coords / 4.0);
+    let expected_centroid = mesh.vertices()[mesh.connectivity()[0].0[0]] + Vector2::new(0.25, 0.25);
+    assert_matrix_eq!(
+        centroid_of_first_element.coords,
+        expected_centroid.coords,
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+#[test]
+fn mass_assembly_with_heterogeneous_density() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let storage = QuadratureParameterStorage::from_fn(&mesh, &table, |_, x: &Point2<f64>| Density(1.0 + x.x));
+    let parameterized_table = storage.with_quadrature_table(&table);
+    assert_eq!(parameterized_table.element_quadrature_size(0), 9);
+
+    let assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&parameterized_table);
+    let mass = CsrAssembler::default().assemble(&assembler).unwrap();
+    // The sum of all entries of the mass matrix is the total mass, i.e. the integral of the density
+    let ones = DVector::repeat(mesh.vertices().len(), 1.0);
+    let total_mass = ones.dot(&(&mass * &ones));
+    assert_scalar_eq!(total_mass, 1.5, comp = abs, tol = 1e-12);
+
+    // The result agrees with a table that stores the same densities as quadrature data
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss(3);
+    let mut table_points = NestedVec::new();
+    let mut table_weights = NestedVec::new();
+    for _ in 0..storage.num_elements() {
+        table_points.push(&points);
+        table_weights.push(&weights);
+    }
+    let general_table = GeneralQuadratureTable::from_points_weights_and_data(
+        table_points,
+        table_weights,
+        storage.as_nested_vec().clone(),
+    );
+    let general_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&general_table);
+    let expected_mass = CsrAssembler::default()
+        .assemble(&general_assembler)
+        .unwrap();
+    assert_matrix_eq!(
+        DMatrix::from(&mass),
+        DMatrix::from(&expected_mass),
+        comp = abs,
+        tol = 1e-14
+    );
+}
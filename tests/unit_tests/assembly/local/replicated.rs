@@ -0,0 +1,78 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{
+    ElementConnectivityAssembler, ElementEllipticAssemblerBuilder, ElementMatrixAssembler,
+    ReplicatedElementMatrixAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::index::NodeIndex;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector};
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+#[test]
+fn replicated_element_matrix_assembler_matches_laplace_assembly_on_uniform_grid() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(5);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let element_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    let expected = CsrAssembler::default()
+        .assemble(&element_assembler)
+        .unwrap();
+    let replicated = ReplicatedElementMatrixAssembler::from_reference_element(element_assembler.clone(), 7).unwrap();
+    assert_matrix_eq!(
+        replicated.element_matrix(),
+        element_assembler.assemble_element_matrix(0).unwrap(),
+        comp = abs,
+        tol = 1e-14
+    );
+    let a = CsrAssembler::default().assemble(&replicated).unwrap();
+    assert_eq!(a.pattern(), expected.pattern());
+    assert_matrix_eq!(DMatrix::from(&a), DMatrix::from(&expected), comp = abs, tol = 1e-13);
+
+    // Piecewise constant coefficients are accounted for by scaling each element matrix
+    let factors: Vec<_> = (0..mesh.connectivity().len())
+        .map(|i| 1.0 + i as f64)
+        .collect();
+    let scaled = replicated.with_element_scaling(factors.clone()).unwrap();
+    let a_scaled = CsrAssembler::default().assemble(&scaled).unwrap();
+    let n = mesh.vertices().len();
+    let mut expected_scaled = DMatrix::zeros(n, n);
+    for (element_index, factor) in factors.iter().enumerate() {
+        let mut nodes = vec![0; element_assembler.element_node_count(element_index.into())];
+        element_assembler.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element_index.into());
+        let a_element = element_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        for (i, &node_i) in nodes.iter().enumerate() {
+            for (j, &node_j) in nodes.iter().enumerate() {
+                expected_scaled[(node_i, node_j)] += factor * a_element[(i, j)];
+            }
+        }
+    }
+    assert_matrix_eq!(DMatrix::from(&a_scaled), expected_scaled, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn replicated_element_matrix_assembler_rejects_invalid_input() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let element_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    assert!(ReplicatedElementMatrixAssembler::from_reference_element(element_assembler.clone(), 4).is_err());
+    let replicated = ReplicatedElementMatrixAssembler::from_reference_element(element_assembler.clone(), 0).unwrap();
+    assert!(replicated.with_element_scaling(vec![1.0; 3]).is_err());
+}
@@ -1,15 +1,17 @@
 use crate::unit_tests::assembly::local;
 use crate::unit_tests::assembly::local::density;
+use fenris::assembly::global::VectorAssembler;
 use fenris::assembly::local::ElementVectorAssembler;
 use fenris::assembly::local::{
-    assemble_element_source_vector, ElementSourceAssemblerBuilder, GeneralQuadratureTable, SourceFunction,
+    assemble_element_source_vector, ElementSourceAssemblerBuilder, GeneralQuadratureTable,
+    SolutionDependentSourceFunction, SourceFunction, UniformQuadratureTable,
 };
 use fenris::assembly::operators::Operator;
 use fenris::element::{ElementConnectivity, FiniteElement, ReferenceFiniteElement, Tet10Element, Tet4Element};
 use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
 use fenris::mesh::QuadMesh2d;
 use fenris::nalgebra::base::coordinates::XYZ;
-use fenris::nalgebra::{DVector, DVectorViewMut, OPoint, Point2, Point3, Vector2, U2, U3};
+use fenris::nalgebra::{DVector, DVectorViewMut, OPoint, OVector, Point2, Point3, Vector1, Vector2, U1, U2, U3};
 use fenris::quadrature;
 use fenris::quadrature::Quadrature;
 use fenris_nested_vec::NestedVec;
@@ -198,3 +200,76 @@ fn source_vector_assembler_matches_individual_element_assembly() {
         assert_matrix_eq!(element_vector, element_vector_expected);
     }
 }
+
+#[test]
+fn solution_dependent_source_vector_matches_source_of_interpolated_solution() {
+    // A linear field u is reproduced exactly by bilinear elements, so that assembling the
+    // solution-dependent source f(x, u_h) = c * u_h(x) * x_1 must give the same result as the
+    // source g(x) = c * u(x) * x_1
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let u_exact = |x: &Point2<f64>| 2.0 * x.x - 3.0 * x.y + 0.5;
+    let u = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(u_exact));
+
+    struct Reaction;
+
+    impl Operator<f64, U2> for Reaction {
+        type SolutionDim = U1;
+        // Reaction coefficient
+        type Parameters = f64;
+    }
+
+    impl SolutionDependentSourceFunction<f64, U2> for Reaction {
+        fn evaluate_with_u(&self, coords: &Point2<f64>, u: &Vector1<f64>, &c: &f64) -> OVector<f64, U1> {
+            u * (c * coords.x)
+        }
+    }
+
+    struct ExactSource<F>(F);
+
+    impl<F> Operator<f64, U2> for ExactSource<F> {
+        type SolutionDim = U1;
+        type Parameters = f64;
+    }
+
+    impl<F: Fn(&Point2<f64>) -> f64> SourceFunction<f64, U2> for ExactSource<F> {
+        fn evaluate(&self, coords: &Point2<f64>, &c: &f64) -> Vector1<f64> {
+            Vector1::new(c * (self.0)(coords) * coords.x)
+        }
+    }
+
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(3), 1.5);
+    let nonlinear_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&Reaction)
+        .with_u(&u)
+        .build();
+    let exact_source = ExactSource(u_exact);
+    let exact_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&exact_source)
+        .build();
+
+    let f = VectorAssembler::default()
+        .assemble_vector(&nonlinear_assembler)
+        .unwrap();
+    let f_expected = VectorAssembler::default()
+        .assemble_vector(&exact_assembler)
+        .unwrap();
+    assert!(f_expected.norm() > 0.1);
+    assert_matrix_eq!(f, f_expected, comp = abs, tol = 1e-12);
+
+    // The solution vector must be consistent with the space
+    let u_wrong = DVector::zeros(u.len() + 1);
+    let invalid_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&Reaction)
+        .with_u(&u_wrong)
+        .build();
+    assert!(VectorAssembler::default()
+        .assemble_vector(&invalid_assembler)
+        .is_err());
+}
@@ -0,0 +1,131 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{
+    commit_operator_state, ElementEllipticAssemblerBuilder, QuadratureParameterStorage, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{EllipticOperator, Operator, StatefulOperator};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::{DVector, Vector2, U1, U2};
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+/// A scalar analogue of perfect plasticity: the gradient decomposes into an elastic part and a
+/// plastic part, and the magnitude of the elastic part is bounded by the yield limit.
+struct ScalarPlasticity {
+    stiffness: f64,
+    yield_limit: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct PlasticState {
+    plastic_gradient: Vector2<f64>,
+}
+
+impl ScalarPlasticity {
+    fn trial_plastic_gradient(&self, gradient: &Vector2<f64>, state: &PlasticState) -> Vector2<f64> {
+        let elastic = gradient - state.plastic_gradient;
+        let norm = elastic.norm();
+        if norm > self.yield_limit {
+            state.plastic_gradient + elastic * ((norm - self.yield_limit) / norm)
+        } else {
+            state.plastic_gradient
+        }
+    }
+}
+
+impl Operator<f64, U2> for ScalarPlasticity {
+    type SolutionDim = U1;
+    type Parameters = PlasticState;
+}
+
+impl EllipticOperator<f64, U2> for ScalarPlasticity {
+    fn compute_elliptic_operator(&self, gradient: &Vector2<f64>, parameters: &PlasticState) -> Vector2<f64> {
+        (gradient - self.trial_plastic_gradient(gradient, parameters)) * self.stiffness
+    }
+}
+
+impl StatefulOperator<f64, U2> for ScalarPlasticity {
+    fn commit_state(&self, gradient: &Vector2<f64>, parameters: &mut PlasticState) {
+        parameters.plastic_gradient = self.trial_plastic_gradient(gradient, parameters);
+    }
+}
+
+#[test]
+fn commit_operator_state_updates_history_variables() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let op = ScalarPlasticity {
+        stiffness: 2.0,
+        yield_limit: 1.0,
+    };
+    let mut state = QuadratureParameterStorage::from_constant(&qtable, 4, PlasticState::default());
+
+    // u(x) = a^T x has constant gradient a with |a| = 5
+    let a = Vector2::new(3.0, 4.0);
+    let u = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(|v| a.dot(&v.coords)));
+    let residual = |state: &QuadratureParameterStorage<PlasticState>, u: &DVector<f64>| {
+        let table = state.with_quadrature_table(&qtable);
+        let assembler = ElementEllipticAssemblerBuilder::new()
+            .with_operator(&op)
+            .with_finite_element_space(&mesh)
+            .with_quadrature_table(&table)
+            .with_u(u)
+            .build();
+        VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap()
+    };
+
+    // Assembly only reads the state
+    let u_zero = DVector::zeros(u.len());
+    let residual_before = residual(&state, &u_zero);
+    let _ = residual(&state, &u);
+    assert!(state
+        .as_nested_vec()
+        .iter()
+        .flatten()
+        .all(|p| p == &PlasticState::default()));
+
+    commit_operator_state(&mesh, &op, &u, &qtable, &mut state).unwrap();
+    let expected_plastic_gradient = a * (4.0 / 5.0);
+    for parameters in state.as_nested_vec().iter().flatten() {
+        assert_matrix_eq!(
+            parameters.plastic_gradient,
+            expected_plastic_gradient,
+            comp = abs,
+            tol = 1e-12
+        );
+    }
+
+    // After unloading, the committed plastic gradient leaves a residual stress
+    let residual_after = residual(&state, &u_zero);
+    assert_matrix_eq!(residual_before, DVector::zeros(u.len()), comp = abs, tol = 1e-14);
+    assert!(residual_after.norm() > 1e-3);
+
+    // Committing again for a gradient within the elastic range leaves the state unchanged
+    let committed = state.clone();
+    let u_elastic = &u * 0.9;
+    commit_operator_state(&mesh, &op, &u_elastic, &qtable, &mut state).unwrap();
+    assert_eq!(state, committed);
+}
+
+#[test]
+fn commit_operator_state_rejects_inconsistent_storage() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let op = ScalarPlasticity {
+        stiffness: 1.0,
+        yield_limit: 1.0,
+    };
+    let u = DVector::zeros(mesh.vertices().len());
+
+    let mut too_few_elements = QuadratureParameterStorage::from_constant(&qtable, 3, PlasticState::default());
+    assert!(commit_operator_state(&mesh, &op, &u, &qtable, &mut too_few_elements).is_err());
+
+    let other_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss::<f64>(3));
+    let mut wrong_points = QuadratureParameterStorage::from_constant(&other_qtable, 4, PlasticState::default());
+    assert!(commit_operator_state(&mesh, &op, &u, &qtable, &mut wrong_points).is_err());
+
+    let mut state = QuadratureParameterStorage::from_constant(&qtable, 4, PlasticState::default());
+    let wrong_u = DVector::zeros(u.len() + 1);
+    assert!(commit_operator_state(&mesh, &op, &wrong_u, &qtable, &mut state).is_err());
+}
@@ -0,0 +1,199 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{
+    assemble_element_elliptic_matrix, assemble_element_laplace_beltrami_matrix, compute_tangential_gradients,
+    surface_measure, Density, ElementLaplaceBeltramiAssembler, ElementSurfaceMassAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::{Quad4d3Connectivity, Tri6d3Connectivity};
+use fenris::element::{FiniteElement, Tri3d2Element, Tri3d3Element};
+use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::mesh::{Mesh3d, Tet10Mesh, Tet4Mesh};
+use fenris::nalgebra::{
+    DMatrix, DVector, DVectorView, Matrix2x3, Matrix3, Matrix3x2, Point2, Point3, Rotation3, Vector3,
+};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+type ScalarField = fn(&Point3<f64>) -> f64;
+
+/// A quadratic triangle mesh of the sphere of radius `r` centered at the origin, obtained by
+/// projecting the boundary of a tetrahedral box mesh onto the sphere.
+fn quadratic_sphere(r: f64, resolution: usize) -> Mesh3d<f64, Tri6d3Connectivity> {
+    let box_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(resolution);
+    let mut surface = Tet10Mesh::from(&box_mesh)
+        .extract_boundary_mesh()
+        .into_mesh();
+    for v in surface.vertices_mut() {
+        let x = v.coords - Vector3::repeat(0.5);
+        *v = Point3::from(x.normalize() * r);
+    }
+    surface
+}
+
+#[test]
+fn tangential_gradients_of_linear_function_on_tilted_triangle() {
+    let element = Tri3d3Element::from_vertices([
+        Point3::new(0.1, 0.2, 0.3),
+        Point3::new(1.2, 0.4, -0.2),
+        Point3::new(0.3, 1.1, 0.6),
+    ]);
+    let [a, b, c] = *element.vertices();
+    let normal = (b - a).cross(&(c - a)).normalize();
+    let xi = Point2::new(0.2, 0.3);
+
+    let mut gradients = Matrix3::zeros();
+    let mut reference_gradients = Matrix2x3::zeros();
+    compute_tangential_gradients(
+        (&mut gradients).into(),
+        &element,
+        &xi,
+        (&mut reference_gradients).into(),
+    )
+    .unwrap();
+
+    // The tangential gradient of the interpolant of u(x) = g^T x is the projection (I - n n^T) g
+    let g = Vector3::new(1.0, -2.0, 0.5);
+    let nodal_values = Vector3::new(g.dot(&a.coords), g.dot(&b.coords), g.dot(&c.coords));
+    let expected = (Matrix3::identity() - normal * normal.transpose()) * g;
+    assert_matrix_eq!(gradients * nodal_values, expected, comp = abs, tol = 1e-12);
+
+    // The reference triangle has area 2, so the surface measure is half the area of the triangle
+    let area = 0.5 * (b - a).cross(&(c - a)).norm();
+    let j: Matrix3x2<f64> = element.reference_jacobian(&xi);
+    assert_scalar_eq!(surface_measure(&j), 0.5 * area, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn laplace_beltrami_matrix_of_flat_triangle_matches_laplace_stiffness() {
+    let vertices_2d = [Point2::new(0.1, 0.2), Point2::new(1.3, -0.1), Point2::new(0.4, 0.9)];
+    // Embed the triangle in 3D through a rigid motion
+    let rotation = Rotation3::from_euler_angles(0.3, -0.7, 1.1);
+    let embedding = rotation.matrix() * Matrix3x2::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    let vertices_3d = vertices_2d.map(|x| Point3::from(embedding * x.coords + Vector3::new(1.0, 2.0, 3.0)));
+
+    let (weights, points) = quadrature::total_order::triangle(0).unwrap();
+    let s = 2;
+    let mut a_surface = DMatrix::zeros(3 * s, 3 * s);
+    assemble_element_laplace_beltrami_matrix(
+        (&mut a_surface).into(),
+        &Tri3d3Element::from_vertices(vertices_3d),
+        &weights,
+        &points,
+        (&mut Matrix2x3::zeros()).into(),
+    )
+    .unwrap();
+
+    let mut a_planar = DMatrix::zeros(3, 3);
+    let u = DVector::zeros(3);
+    assemble_element_elliptic_matrix(
+        (&mut a_planar).into(),
+        &Tri3d2Element::from_vertices(vertices_2d),
+        &LaplaceOperator,
+        DVectorView::from(&u),
+        &weights,
+        &points,
+        &vec![(); weights.len()],
+        (&mut Matrix2x3::zeros()).into(),
+    )
+    .unwrap();
+
+    let expected = a_planar.kronecker(&DMatrix::identity(s, s));
+    assert_matrix_eq!(a_surface, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn laplace_beltrami_on_quadratic_sphere_approximates_spherical_harmonics() {
+    let r = 1.5;
+    let mesh = quadratic_sphere(r, 3);
+    let quadrature = quadrature::total_order::triangle(4).unwrap();
+
+    let lb_qtable = UniformQuadratureTable::from_quadrature(quadrature.clone());
+    let lb_assembler = ElementLaplaceBeltramiAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&lb_qtable);
+    let a = DMatrix::from(&CsrAssembler::default().assemble(&lb_assembler).unwrap());
+
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature, Density(1.0));
+    let mass_assembler = ElementSurfaceMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let m = DMatrix::from(&CsrAssembler::default().assemble(&mass_assembler).unwrap());
+
+    assert_matrix_eq!(a, a.transpose(), comp = abs, tol = 1e-12);
+    assert_matrix_eq!(m, m.transpose(), comp = abs, tol = 1e-12);
+
+    // Constants are in the kernel of the Laplace-Beltrami operator, and 1^T M 1 is the area
+    let n = mesh.vertices().len();
+    let ones = DVector::repeat(n, 1.0);
+    assert_matrix_eq!(&a * &ones, DVector::zeros(n), comp = abs, tol = 1e-10);
+    let area = ones.dot(&(&m * &ones));
+    assert_scalar_eq!(area, 4.0 * PI * r * r, comp = abs, tol = 1e-2 * area);
+
+    // Spherical harmonics of degree l are eigenfunctions of -Δ_Γ with eigenvalue l (l + 1) / r^2,
+    // which are approximated by the Rayleigh quotients of their interpolants
+    let interpolate = |f: ScalarField| DVector::from_iterator(n, mesh.vertices().iter().map(f));
+    let rayleigh_quotient = |u: &DVector<f64>| u.dot(&(&a * u)) / u.dot(&(&m * u));
+    let harmonics: [(usize, ScalarField); 3] = [
+        (1, |x| x.z),
+        (2, |x| x.x * x.y),
+        (2, |x| 2.0 * x.z * x.z - x.x * x.x - x.y * x.y),
+    ];
+    for (l, harmonic) in harmonics {
+        let eigenvalue = (l * (l + 1)) as f64 / (r * r);
+        let quotient = rayleigh_quotient(&interpolate(harmonic));
+        assert_scalar_eq!(quotient, eigenvalue, comp = abs, tol = 1e-2 * eigenvalue);
+    }
+}
+
+#[test]
+fn laplace_beltrami_on_bilinear_cylinder_approximates_circumferential_modes() {
+    // A closed cylinder of radius r and height 1 around the z-axis, discretized by bilinear quads
+    let (r, n_theta, n_z) = (0.8, 32, 3);
+    let mut vertices = Vec::new();
+    for j in 0..=n_z {
+        for i in 0..n_theta {
+            let theta = 2.0 * PI * i as f64 / n_theta as f64;
+            vertices.push(Point3::new(r * theta.cos(), r * theta.sin(), j as f64 / n_z as f64));
+        }
+    }
+    let v = |i: usize, j: usize| j * n_theta + (i % n_theta);
+    let cells = (0..n_z)
+        .flat_map(|j| {
+            (0..n_theta).map(move |i| Quad4d3Connectivity([v(i, j), v(i + 1, j), v(i + 1, j + 1), v(i, j + 1)]))
+        })
+        .collect();
+    let mesh: Mesh3d<f64, Quad4d3Connectivity> = Mesh3d::from_vertices_and_connectivity(vertices, cells);
+
+    let quadrature = quadrature::tensor::quadrilateral_gauss(2);
+    let lb_qtable = UniformQuadratureTable::from_quadrature(quadrature.clone());
+    let lb_assembler = ElementLaplaceBeltramiAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&lb_qtable);
+    let a = DMatrix::from(&CsrAssembler::default().assemble(&lb_assembler).unwrap());
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature, Density(1.0));
+    let mass_assembler = ElementSurfaceMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let m = DMatrix::from(&CsrAssembler::default().assemble(&mass_assembler).unwrap());
+
+    // The faceted cylinder has the area of a prism over the inscribed regular polygon
+    let n = mesh.vertices().len();
+    let ones = DVector::repeat(n, 1.0);
+    let area = n_theta as f64 * 2.0 * r * (PI / n_theta as f64).sin();
+    assert_scalar_eq!(ones.dot(&(&m * &ones)), area, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(&a * &ones, DVector::zeros(n), comp = abs, tol = 1e-12);
+
+    // cos(k θ) is an eigenfunction of -Δ_Γ with eigenvalue k^2 / r^2
+    for k in [1, 2] {
+        let u = DVector::from_iterator(
+            n,
+            mesh.vertices()
+                .iter()
+                .map(|x| (k as f64 * x.y.atan2(x.x)).cos()),
+        );
+        let eigenvalue = (k * k) as f64 / (r * r);
+        let quotient = u.dot(&(&a * &u)) / u.dot(&(&m * &u));
+        assert_scalar_eq!(quotient, eigenvalue, comp = abs, tol = 3e-2 * eigenvalue);
+    }
+}
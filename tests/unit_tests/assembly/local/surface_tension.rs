@@ -0,0 +1,156 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{
+    compute_mean_curvature_vectors, ElementSurfaceTensionAssemblerBuilder, SurfaceTension, UniformQuadratureTable,
+};
+use fenris::connectivity::{Connectivity, Segment2d2Connectivity};
+use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::mesh::{Mesh2d, Tet4Mesh, TriangleMesh3d};
+use fenris::nalgebra::{DVector, Point2, Point3, Vector2, Vector3};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+/// A regular polygon with `n` vertices on the circle of radius `r`, oriented counter-clockwise.
+fn regular_polygon(n: usize, r: f64) -> Mesh2d<f64, Segment2d2Connectivity> {
+    let vertices = (0..n)
+        .map(|i| {
+            let theta = 2.0 * PI * i as f64 / n as f64;
+            Point2::new(r * theta.cos(), r * theta.sin())
+        })
+        .collect();
+    let connectivity = (0..n)
+        .map(|i| Segment2d2Connectivity([i, (i + 1) % n]))
+        .collect();
+    Mesh2d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+/// A triangulated sphere of radius `r` centered at the origin, obtained by projecting the
+/// boundary of a tetrahedral box mesh onto the sphere.
+fn sphere(r: f64) -> TriangleMesh3d<f64> {
+    let box_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(3);
+    let mut surface = box_mesh.extract_surface_mesh();
+    for v in surface.vertices_mut() {
+        let x = v.coords - Vector3::repeat(0.5);
+        *v = Point3::from(x.normalize() * r);
+    }
+    surface
+}
+
+fn surface_area(mesh: &TriangleMesh3d<f64>) -> f64 {
+    mesh.connectivity()
+        .iter()
+        .map(|tri| {
+            let [a, b, c] = tri.vertex_indices() else {
+                unreachable!()
+            };
+            let x = mesh.vertices();
+            0.5 * (x[*b] - x[*a]).cross(&(x[*c] - x[*a])).norm()
+        })
+        .sum()
+}
+
+fn assemble_surface_tension(mesh: &TriangleMesh3d<f64>, gamma: f64) -> DVector<f64> {
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::total_order::triangle(1).unwrap(),
+        SurfaceTension(gamma),
+    );
+    let assembler = ElementSurfaceTensionAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_quadrature_table(&qtable)
+        .build();
+    VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap()
+}
+
+#[test]
+fn mean_curvature_of_regular_polygon_is_exact() {
+    // For a regular polygon inscribed in a circle of radius r, the discrete mean curvature vector
+    // is -n / r at every vertex, where n is the outward normal
+    let r = 1.5;
+    let mesh = regular_polygon(12, r);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+    let curvature = compute_mean_curvature_vectors(&mesh, &qtable).unwrap();
+    for (i, x) in mesh.vertices().iter().enumerate() {
+        let expected = -x.coords / (r * r);
+        assert_matrix_eq!(curvature.fixed_rows::<2>(2 * i), expected, comp = abs, tol = 1e-12);
+    }
+
+    // The surface tension force is the line tension times the turning of the tangent at each vertex
+    let gamma = 0.3;
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::univariate::gauss(1),
+        SurfaceTension(gamma),
+    );
+    let assembler = ElementSurfaceTensionAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .build();
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    let turning_angle = 2.0 * PI / 12.0;
+    for (i, x) in mesh.vertices().iter().enumerate() {
+        let expected: Vector2<f64> = -x.coords.normalize() * (2.0 * gamma * (0.5 * turning_angle).sin());
+        assert_matrix_eq!(f.fixed_rows::<2>(2 * i), expected, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn surface_tension_force_on_sphere() {
+    let r = 2.0;
+    let gamma = 0.7;
+    let mesh = sphere(r);
+    let f = assemble_surface_tension(&mesh, gamma);
+    let n = mesh.vertices().len();
+
+    // The total force on a closed surface vanishes
+    let total_force = (0..n).fold(Vector3::zeros(), |sum, i| sum + f.fixed_rows::<3>(3 * i));
+    assert_matrix_eq!(total_force, Vector3::zeros(), comp = abs, tol = 1e-12);
+
+    // Since grad_Γ x : grad_Γ x = 2 on a surface, we have x^T f = -2 γ |Γ|
+    let x = DVector::from_iterator(3 * n, mesh.vertices().iter().flat_map(|v| v.coords.data.0[0]));
+    assert_scalar_eq!(x.dot(&f), -2.0 * gamma * surface_area(&mesh), comp = abs, tol = 1e-12);
+
+    // The force is the negative gradient of the surface energy γ |Γ|
+    let h = 1e-6;
+    for vertex_index in [0, n / 2, n - 1] {
+        for k in 0..3 {
+            let mut perturbed = mesh.clone();
+            perturbed.vertices_mut()[vertex_index][k] += h;
+            let area_plus = surface_area(&perturbed);
+            perturbed.vertices_mut()[vertex_index][k] -= 2.0 * h;
+            let area_minus = surface_area(&perturbed);
+            let energy_derivative = gamma * (area_plus - area_minus) / (2.0 * h);
+            assert_scalar_eq!(f[3 * vertex_index + k], -energy_derivative, comp = abs, tol = 1e-8);
+        }
+    }
+
+    // The mean curvature vector approximates -2/r n. The discrete curvature is not pointwise
+    // accurate on irregular meshes, but its average weighted by the lumped mass is exact
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let curvature = compute_mean_curvature_vectors(&mesh, &qtable).unwrap();
+    let mut lumped_mass = vec![0.0; n];
+    for tri in mesh.connectivity() {
+        let x = mesh.vertices();
+        let [a, b, c] = *tri.vertex_indices() else {
+            unreachable!()
+        };
+        let area = 0.5 * (x[b] - x[a]).cross(&(x[c] - x[a])).norm();
+        for i in [a, b, c] {
+            lumped_mass[i] += area / 3.0;
+        }
+    }
+    let weighted_curvature: f64 = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .map(|(i, x)| -lumped_mass[i] * curvature.fixed_rows::<3>(3 * i).dot(&x.coords) / r)
+        .sum();
+    assert_scalar_eq!(
+        weighted_curvature / surface_area(&mesh),
+        2.0 / r,
+        comp = abs,
+        tol = 1e-12
+    );
+}
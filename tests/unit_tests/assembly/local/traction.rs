@@ -0,0 +1,134 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{
+    ElementConnectivityAssembler, ElementTractionAssemblerBuilder, TractionFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::Operator;
+use fenris::connectivity::Connectivity;
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{Mesh, QuadMesh2d, Tet4Mesh};
+use fenris::nalgebra::allocator::Allocator;
+use fenris::nalgebra::{DVector, DefaultAllocator, DimName, OPoint, OVector, Point2, Point3, Vector2, Vector3, U2, U3};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// Traction equal to the outward unit normal.
+struct NormalTraction;
+
+impl Operator<f64, U2> for NormalTraction {
+    type SolutionDim = U2;
+    type Parameters = ();
+}
+
+impl Operator<f64, U3> for NormalTraction {
+    type SolutionDim = U3;
+    type Parameters = ();
+}
+
+impl TractionFunction<f64, U2> for NormalTraction {
+    fn evaluate(&self, _coords: &OPoint<f64, U2>, normal: &OVector<f64, U2>, _data: &()) -> Vector2<f64> {
+        *normal
+    }
+}
+
+impl TractionFunction<f64, U3> for NormalTraction {
+    fn evaluate(&self, _coords: &OPoint<f64, U3>, normal: &OVector<f64, U3>, _data: &()) -> Vector3<f64> {
+        *normal
+    }
+}
+
+/// Builds the surface mesh of the given mesh, retaining all vertices (and therefore node indices) of the mesh.
+fn surface_mesh_with_parent_vertices<D, C>(mesh: &Mesh<f64, D, C>) -> Mesh<f64, D, C::FaceConnectivity>
+where
+    D: DimName,
+    C: Connectivity,
+    C::FaceConnectivity: Connectivity,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    let faces = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(face, _, _)| face)
+        .collect();
+    Mesh::from_vertices_and_connectivity(mesh.vertices().to_vec(), faces)
+}
+
+#[test]
+fn traction_vector_reproduces_divergence_theorem_2d() {
+    // With t = n, the traction vector f satisfies
+    //  u . f = int_{dΩ} u . n ds = int_Ω div u dx
+    // for any u that is exactly represented by the finite element space. For the unit square and
+    // u = (1 + 2x + y, 3x - y) we have div u = 1 and hence u . f = 1.
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let surface = surface_mesh_with_parent_vertices(&mesh);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+    let assembler = ElementTractionAssemblerBuilder::new()
+        .with_finite_element_space(&surface)
+        .with_traction(&NormalTraction)
+        .with_quadrature_table(&qtable)
+        .build();
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    assert_eq!(f.len(), 2 * mesh.vertices().len());
+
+    let u_fn = |p: &Point2<f64>| Vector2::new(1.0 + 2.0 * p.x + p.y, 3.0 * p.x - p.y);
+    let u = DVector::from_iterator(f.len(), mesh.vertices().iter().flat_map(|p| u_fn(p).data.0[0]));
+    assert_scalar_eq!(u.dot(&f), 1.0, comp = abs, tol = 1e-12);
+
+    // The total traction of a closed surface with t = n vanishes
+    let total: f64 = f.iter().sum();
+    assert_scalar_eq!(total, 0.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn traction_vector_reproduces_divergence_theorem_3d() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let surface = surface_mesh_with_parent_vertices(&mesh);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let assembler = ElementTractionAssemblerBuilder::new()
+        .with_finite_element_space(&surface)
+        .with_traction(&NormalTraction)
+        .with_quadrature_table(&qtable)
+        .build();
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    // div u = 2 - 1 + 3 = 4
+    let u_fn = |p: &Point3<f64>| Vector3::new(2.0 * p.x + p.z, p.x - p.y + 1.0, 3.0 * p.z - p.y);
+    let u = DVector::from_iterator(f.len(), mesh.vertices().iter().flat_map(|p| u_fn(p).data.0[0]));
+    assert_scalar_eq!(u.dot(&f), 4.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn traction_vector_on_compact_surface_mesh_can_be_mapped_to_parent_nodes() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+
+    let surface = surface_mesh_with_parent_vertices(&mesh);
+    let expected = VectorAssembler::default()
+        .assemble_vector(
+            &ElementTractionAssemblerBuilder::new()
+                .with_finite_element_space(&surface)
+                .with_traction(&NormalTraction)
+                .with_quadrature_table(&qtable)
+                .build(),
+        )
+        .unwrap();
+
+    // The vertices of the extracted surface mesh correspond to the (sorted) boundary vertices
+    let compact_surface = mesh.extract_surface_mesh();
+    let boundary_vertices = mesh.find_boundary_vertices();
+    assert_eq!(compact_surface.vertices().len(), boundary_vertices.len());
+    let assembler = ElementTractionAssemblerBuilder::new()
+        .with_finite_element_space(&compact_surface)
+        .with_traction(&NormalTraction)
+        .with_quadrature_table(&qtable)
+        .build()
+        .map_element_nodes(mesh.vertices().len(), |i| boundary_vertices[i]);
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    assert_matrix_eq!(f, expected, comp = abs, tol = 1e-14);
+}
@@ -0,0 +1,242 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::matrix_free::{MatrixFreeEllipticOperator, TensorProductEllipticOperator};
+use fenris::assembly::operators::solid::{LameParameters, StVKOperator};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::Quad9d2Connectivity;
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{Hex27Mesh, Mesh2d};
+use fenris::nalgebra::{DVector, Point2, Vector2, Vector3};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris_sparse::cg::{ConjugateGradient, IdentityOperator, LinearOperator, RelativeResidualCriterion};
+use matrixcompare::assert_matrix_eq;
+
+fn test_vector(n: usize, phase: f64) -> DVector<f64> {
+    DVector::from_fn(n, |i, _| (1.3 * i as f64 + phase).sin())
+}
+
+fn apply(operator: &dyn LinearOperator<f64>, x: &DVector<f64>) -> DVector<f64> {
+    let mut y = DVector::repeat(x.len(), f64::NAN);
+    operator.apply(y.as_view_mut(), x.as_view()).unwrap();
+    y
+}
+
+/// A smooth perturbation of the vertices of the unit square.
+fn perturb_2d(v: &mut Point2<f64>) {
+    let (x, y) = (v.x, v.y);
+    v.coords += 0.05 * Vector2::new((3.0 * y).sin(), (2.0 * x).cos() * x);
+}
+
+fn diagonal(matrix: &CsrMatrix<f64>) -> DVector<f64> {
+    DVector::from_fn(matrix.nrows(), |i, _| matrix.index_entry(i, i).into_value())
+}
+
+#[test]
+fn matrix_free_laplace_matches_assembled_matrix_on_triangles() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let u = DVector::zeros(mesh.vertices().len());
+    let a = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&LaplaceOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build(),
+        )
+        .unwrap();
+
+    let operator = MatrixFreeEllipticOperator::new(&mesh, &LaplaceOperator, &qtable, &u);
+    assert_eq!(operator.num_dofs(), a.nrows());
+    let x = test_vector(a.ncols(), 0.0);
+    assert_matrix_eq!(apply(&operator, &x), &a * &x, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(
+        operator.compute_diagonal().unwrap(),
+        diagonal(&a),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // apply_add accumulates into y
+    let mut y = x.clone();
+    operator.apply_add(y.as_view_mut(), x.as_view()).unwrap();
+    assert_matrix_eq!(y, &x + &a * &x, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn matrix_free_stvk_matches_assembled_matrix_on_perturbed_quad9() {
+    let mut quad4_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    quad4_mesh.transform_vertices(perturb_2d);
+    let mesh = Mesh2d::<f64, Quad9d2Connectivity>::from(quad4_mesh);
+    let lame = LameParameters::from_young_poisson(10.0, 0.3);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(4), lame);
+    let u = 0.05 * test_vector(2 * mesh.vertices().len(), 0.5);
+    let a: CsrMatrix<f64> = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&StVKOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build(),
+        )
+        .unwrap();
+    let x = test_vector(a.ncols(), 1.0);
+    let expected = &a * &x;
+
+    let general = MatrixFreeEllipticOperator::new(&mesh, &StVKOperator, &qtable, &u);
+    assert_matrix_eq!(apply(&general, &x), expected, comp = abs, tol = 1e-10);
+    assert_matrix_eq!(
+        general.compute_diagonal().unwrap(),
+        diagonal(&a),
+        comp = abs,
+        tol = 1e-10
+    );
+
+    let tensor = TensorProductEllipticOperator::new(&mesh, &StVKOperator, &u, 4)
+        .unwrap()
+        .with_parameters(lame);
+    assert_eq!(tensor.num_dofs(), a.nrows());
+    assert_matrix_eq!(apply(&tensor, &x), expected, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn tensor_product_operator_rejects_curved_elements() {
+    // Perturbing the Quad9 mesh moves the edge and center nodes off the bilinear map of the corners
+    let mut mesh = Mesh2d::<f64, Quad9d2Connectivity>::from(create_unit_square_uniform_quad_mesh_2d(3));
+    let u = DVector::zeros(mesh.vertices().len());
+    assert!(TensorProductEllipticOperator::new(&mesh, &LaplaceOperator, &u, 3).is_ok());
+    mesh.transform_vertices(perturb_2d);
+    assert!(TensorProductEllipticOperator::new(&mesh, &LaplaceOperator, &u, 3).is_err());
+
+    // The general matrix-free operator supports the isoparametric geometry
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let operator = MatrixFreeEllipticOperator::new(&mesh, &LaplaceOperator, &qtable, &u);
+    assert!(operator.compute_diagonal().is_ok());
+}
+
+#[test]
+fn tensor_product_laplace_matches_assembled_matrix_on_quad4() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    mesh.transform_vertices(|v| v.coords += 0.03 * Vector2::new((5.0 * v.y).sin(), (4.0 * v.x).sin()));
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let a = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&LaplaceOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build(),
+        )
+        .unwrap();
+    let operator = TensorProductEllipticOperator::new(&mesh, &LaplaceOperator, &u, 2).unwrap();
+    let x = test_vector(a.ncols(), 0.3);
+    assert_matrix_eq!(apply(&operator, &x), &a * &x, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn tensor_product_operators_match_assembled_matrices_on_hexahedra() {
+    let mut hex8_mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    hex8_mesh.transform_vertices(|v| {
+        v.coords += 0.04 * Vector3::new((3.0 * v.y).sin(), (2.0 * v.z).sin(), (4.0 * v.x).cos())
+    });
+    let hex27_mesh = Hex27Mesh::from(&hex8_mesh);
+
+    let lame = LameParameters::from_young_poisson(5.0, 0.25);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::hexahedron_gauss(3), lame);
+
+    let u8 = 0.02 * test_vector(3 * hex8_mesh.vertices().len(), 0.2);
+    let a8 = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&hex8_mesh)
+                .with_operator(&StVKOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u8)
+                .build(),
+        )
+        .unwrap();
+    let x8 = test_vector(a8.ncols(), 2.0);
+    let tensor8 = TensorProductEllipticOperator::new(&hex8_mesh, &StVKOperator, &u8, 3)
+        .unwrap()
+        .with_parameters(lame);
+    assert_matrix_eq!(apply(&tensor8, &x8), &a8 * &x8, comp = abs, tol = 1e-10);
+
+    let u27 = 0.02 * test_vector(3 * hex27_mesh.vertices().len(), 0.7);
+    let a27 = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&hex27_mesh)
+                .with_operator(&StVKOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u27)
+                .build(),
+        )
+        .unwrap();
+    let x27 = test_vector(a27.ncols(), 2.5);
+    let expected27 = &a27 * &x27;
+    let tensor27 = TensorProductEllipticOperator::new(&hex27_mesh, &StVKOperator, &u27, 3)
+        .unwrap()
+        .with_parameters(lame);
+    assert_matrix_eq!(apply(&tensor27, &x27), expected27, comp = abs, tol = 1e-10);
+    let general27 = MatrixFreeEllipticOperator::new(&hex27_mesh, &StVKOperator, &qtable, &u27);
+    assert_matrix_eq!(apply(&general27, &x27), expected27, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn matrix_free_operator_can_be_used_with_conjugate_gradient() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(6);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let a = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&LaplaceOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build(),
+        )
+        .unwrap();
+
+    // The pure Neumann Laplace matrix is singular, but CG converges for a consistent right-hand side
+    let x_exact = test_vector(a.ncols(), 0.1);
+    let b = &a * &x_exact;
+    let operator = TensorProductEllipticOperator::new(&mesh, &LaplaceOperator, &u, 2).unwrap();
+    let mut x = DVector::zeros(b.len());
+    ConjugateGradient::new()
+        .with_operator(&operator)
+        .with_preconditioner(&IdentityOperator)
+        .with_stopping_criterion(RelativeResidualCriterion::new(1e-10))
+        .solve_with_guess(&b, &mut x)
+        .unwrap();
+    assert!((&b - &a * &x).norm() <= 1e-8 * b.norm());
+}
+
+#[test]
+fn matrix_free_operators_reject_incompatible_vectors() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let n = mesh.vertices().len();
+    let u = DVector::zeros(n);
+    let x = DVector::zeros(n);
+    let mut y = DVector::zeros(n + 1);
+
+    let general = MatrixFreeEllipticOperator::new(&mesh, &LaplaceOperator, &qtable, &u);
+    assert!(general.apply_add(y.as_view_mut(), x.as_view()).is_err());
+    let tensor = TensorProductEllipticOperator::new(&mesh, &LaplaceOperator, &u, 2).unwrap();
+    assert!(tensor.apply_add(y.as_view_mut(), x.as_view()).is_err());
+
+    let short_u = DVector::zeros(n - 1);
+    let general = MatrixFreeEllipticOperator::new(&mesh, &LaplaceOperator, &qtable, &short_u);
+    assert!(general.compute_diagonal().is_err());
+}
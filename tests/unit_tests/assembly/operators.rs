@@ -0,0 +1,5 @@
+mod autodiff;
+mod elasticity;
+mod phase_field;
+mod solid;
+mod test_utils;
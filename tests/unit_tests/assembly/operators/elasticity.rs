@@ -0,0 +1,170 @@
+use fenris::assembly::local::assemble_element_elliptic_matrix;
+use fenris::assembly::operators::solid::LameParameters;
+use fenris::assembly::operators::{EllipticOperator, LinearElasticityOperator, PlaneElasticityModel};
+use fenris::element::{Tet4Element, Tri3d2Element};
+use fenris::nalgebra::{matrix, DMatrix, DVector, DVectorView, Matrix2, Matrix2x3, Matrix3, Matrix3x4, Point2, Point3};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+use super::solid::{assert_solid_operator_consistent, lame, u_grad_2d, u_grad_3d};
+
+const YOUNG: f64 = 200.0;
+const POISSON: f64 = 0.3;
+
+/// Gradients of the linear basis functions of a simplex, stored column by column, given the
+/// matrix $J = [x_1 - x_0, \dots, x_d - x_0]$.
+fn simplex_basis_gradients(j: &DMatrix<f64>) -> DMatrix<f64> {
+    let d = j.nrows();
+    let j_inv_t = j.clone().try_inverse().unwrap().transpose();
+    let mut gradients = DMatrix::zeros(d, d + 1);
+    for k in 0..d {
+        gradients.set_column(k + 1, &j_inv_t.column(k));
+        let g = gradients.column(0) - j_inv_t.column(k);
+        gradients.set_column(0, &g);
+    }
+    gradients
+}
+
+/// The textbook constant-strain stiffness matrix $V \vec B^T \vec D \vec B$ in Voigt notation,
+/// with engineering shear strains.
+fn voigt_stiffness(gradients: &DMatrix<f64>, d_matrix: &DMatrix<f64>, volume: f64) -> DMatrix<f64> {
+    let d = gradients.nrows();
+    let n = gradients.ncols();
+    // Pairs (i, j) of spatial directions corresponding to the shear strains
+    let shear_pairs: &[(usize, usize)] = if d == 2 { &[(0, 1)] } else { &[(1, 2), (0, 2), (0, 1)] };
+    let mut b = DMatrix::zeros(d + shear_pairs.len(), d * n);
+    for node in 0..n {
+        let g = gradients.column(node);
+        for i in 0..d {
+            b[(i, d * node + i)] = g[i];
+        }
+        for (row, &(i, j)) in shear_pairs.iter().enumerate() {
+            b[(d + row, d * node + i)] = g[j];
+            b[(d + row, d * node + j)] = g[i];
+        }
+    }
+    b.transpose() * d_matrix * b * volume
+}
+
+#[test]
+fn linear_elasticity_operator_consistency() {
+    assert_solid_operator_consistent(&LinearElasticityOperator::new(), &u_grad_3d(), true);
+    assert_solid_operator_consistent(&LinearElasticityOperator::plane_strain(), &u_grad_2d(), true);
+    assert_solid_operator_consistent(&LinearElasticityOperator::plane_stress(), &u_grad_2d(), true);
+    // The plane model is ignored outside of 2D
+    assert_solid_operator_consistent(&LinearElasticityOperator::plane_stress(), &u_grad_3d(), true);
+}
+
+#[test]
+fn linear_elasticity_operator_is_hookes_law() {
+    let LameParameters { mu, lambda } = lame();
+    let u_grad = u_grad_3d();
+    let eps = 0.5 * (u_grad + u_grad.transpose());
+    let expected = Matrix3::identity() * (lambda * eps.trace()) + 2.0 * mu * eps;
+    let operator = LinearElasticityOperator::plane_stress();
+    assert_eq!(operator.plane_model(), PlaneElasticityModel::PlaneStress);
+    assert_matrix_eq!(
+        operator.compute_elliptic_operator(&u_grad, &lame()),
+        expected,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // In plane stress, a uniaxial strain state with the out-of-plane strain eliminated has vanishing
+    // transverse stress, with the in-plane Young's modulus and Poisson ratio recovered
+    let lame = LameParameters::from_young_poisson(YOUNG, POISSON);
+    let strain = 1e-3;
+    let u_grad = matrix![strain, 0.0;
+                         0.0, -POISSON * strain];
+    let stress: Matrix2<f64> = operator.compute_elliptic_operator(&u_grad, &lame);
+    assert_scalar_eq!(stress[(1, 1)], 0.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(stress[(0, 0)], YOUNG * strain, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn linear_elasticity_triangle_stiffness_matches_textbook() {
+    let vertices = [Point2::new(0.2, 0.1), Point2::new(1.4, 0.3), Point2::new(0.5, 1.2)];
+    let j = DMatrix::from_fn(2, 2, |i, k| vertices[k + 1][i] - vertices[0][i]);
+    let gradients = simplex_basis_gradients(&j);
+    let area = 0.5 * j.determinant().abs();
+
+    let (e, nu) = (YOUNG, POISSON);
+    #[rustfmt::skip]
+    let plane_stress = DMatrix::from_row_slice(3, 3, &[
+        1.0, nu, 0.0,
+        nu, 1.0, 0.0,
+        0.0, 0.0, 0.5 * (1.0 - nu),
+    ]) * (e / (1.0 - nu * nu));
+    #[rustfmt::skip]
+    let plane_strain = DMatrix::from_row_slice(3, 3, &[
+        1.0 - nu, nu, 0.0,
+        nu, 1.0 - nu, 0.0,
+        0.0, 0.0, 0.5 * (1.0 - 2.0 * nu),
+    ]) * (e / ((1.0 + nu) * (1.0 - 2.0 * nu)));
+
+    let element = Tri3d2Element::from_vertices(vertices);
+    let (weights, points) = quadrature::total_order::triangle(0).unwrap();
+    let lame = vec![LameParameters::from_young_poisson(e, nu); weights.len()];
+    let u = DVector::zeros(6);
+    for (operator, d_matrix) in [
+        (LinearElasticityOperator::plane_stress(), plane_stress),
+        (LinearElasticityOperator::plane_strain(), plane_strain),
+    ] {
+        let mut k = DMatrix::zeros(6, 6);
+        assemble_element_elliptic_matrix(
+            (&mut k).into(),
+            &element,
+            &operator,
+            DVectorView::from(&u),
+            &weights,
+            &points,
+            &lame,
+            (&mut Matrix2x3::zeros()).into(),
+        )
+        .unwrap();
+        let expected = voigt_stiffness(&gradients, &d_matrix, area);
+        assert_matrix_eq!(k, expected, comp = abs, tol = 1e-10);
+    }
+}
+
+#[test]
+fn linear_elasticity_tetrahedron_stiffness_matches_textbook() {
+    let vertices = [
+        Point3::new(0.1, 0.0, 0.2),
+        Point3::new(1.1, 0.2, 0.1),
+        Point3::new(0.3, 0.9, 0.0),
+        Point3::new(0.2, 0.3, 1.3),
+    ];
+    let j = DMatrix::from_fn(3, 3, |i, k| vertices[k + 1][i] - vertices[0][i]);
+    let gradients = simplex_basis_gradients(&j);
+    let volume = j.determinant().abs() / 6.0;
+
+    let (e, nu) = (YOUNG, POISSON);
+    let mut d_matrix = DMatrix::zeros(6, 6);
+    for i in 0..3 {
+        for k in 0..3 {
+            d_matrix[(i, k)] = if i == k { 1.0 - nu } else { nu };
+        }
+        d_matrix[(3 + i, 3 + i)] = 0.5 * (1.0 - 2.0 * nu);
+    }
+    d_matrix *= e / ((1.0 + nu) * (1.0 - 2.0 * nu));
+
+    let element = Tet4Element::from_vertices(vertices);
+    let (weights, points) = quadrature::total_order::tetrahedron(0).unwrap();
+    let lame = vec![LameParameters::from_young_poisson(e, nu); weights.len()];
+    let u = DVector::zeros(12);
+    let mut k = DMatrix::zeros(12, 12);
+    assemble_element_elliptic_matrix(
+        (&mut k).into(),
+        &element,
+        &LinearElasticityOperator::new(),
+        DVectorView::from(&u),
+        &weights,
+        &points,
+        &lame,
+        (&mut Matrix3x4::zeros()).into(),
+    )
+    .unwrap();
+    let expected = voigt_stiffness(&gradients, &d_matrix, volume);
+    assert_matrix_eq!(k, expected, comp = abs, tol = 1e-9);
+}
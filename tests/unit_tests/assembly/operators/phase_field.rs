@@ -0,0 +1,154 @@
+use fenris::assembly::operators::solid::LameParameters;
+use fenris::assembly::operators::{
+    degradation_function, AdvectionDiffusionReaction, DegradedElasticityOperator, DegradedElasticityParameters,
+    EllipticEnergy, EllipticOperator, EnergySplit, LinearElasticityOperator, PhaseFieldModel, PhaseFieldOperator,
+    PhaseFieldParameters,
+};
+use fenris::nalgebra::{Matrix2, Matrix3, Point2};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+use super::solid::{assert_solid_operator_consistent_with_parameters, lame, u_grad_2d, u_grad_3d};
+
+fn degraded(degradation: f64) -> DegradedElasticityParameters<f64> {
+    DegradedElasticityParameters {
+        lame: lame(),
+        degradation,
+    }
+}
+
+#[test]
+fn degraded_elasticity_operator_consistency() {
+    for split in [EnergySplit::None, EnergySplit::VolumetricDeviatoric] {
+        let operator = DegradedElasticityOperator::with_split(split);
+        assert_eq!(operator.split(), split);
+        // The contraction is exact away from the kink at vanishing volumetric strain
+        for sign in [1.0, -1.0] {
+            assert_solid_operator_consistent_with_parameters(&operator, &(sign * u_grad_2d()), &degraded(0.3), true);
+            assert_solid_operator_consistent_with_parameters(&operator, &(sign * u_grad_3d()), &degraded(0.3), true);
+        }
+    }
+}
+
+#[test]
+fn degraded_elasticity_operator_reduces_to_linear_elasticity_when_intact() {
+    let linear = LinearElasticityOperator::plane_strain();
+    for split in [EnergySplit::None, EnergySplit::VolumetricDeviatoric] {
+        let operator = DegradedElasticityOperator::with_split(split);
+        for sign in [1.0, -1.0] {
+            let u_grad = sign * u_grad_2d();
+            assert_matrix_eq!(
+                operator.compute_elliptic_operator(&u_grad, &degraded(1.0)),
+                linear.compute_elliptic_operator(&u_grad, &lame()),
+                comp = abs,
+                tol = 1e-13
+            );
+            assert_scalar_eq!(
+                operator.compute_energy(&u_grad, &degraded(1.0)),
+                linear.compute_energy(&u_grad, &lame()),
+                comp = abs,
+                tol = 1e-13
+            );
+
+            let u_grad = sign * u_grad_3d();
+            let (tensile, compressive) = operator.split_energy_density(&u_grad.symmetric_part(), &lame());
+            assert_scalar_eq!(
+                tensile + compressive,
+                linear.compute_energy(&u_grad, &lame()),
+                comp = abs,
+                tol = 1e-13
+            );
+        }
+    }
+}
+
+#[test]
+fn volumetric_deviatoric_split_does_not_degrade_compression() {
+    let operator = DegradedElasticityOperator::with_split(EnergySplit::VolumetricDeviatoric);
+    let linear = LinearElasticityOperator::new();
+    let broken = degraded(0.0);
+
+    // Pure volumetric compression is unaffected by the phase-field
+    let compression = Matrix3::identity() * -0.01;
+    assert_scalar_eq!(
+        operator.tensile_energy_density(&compression, &lame()),
+        0.0,
+        comp = abs,
+        tol = 1e-16
+    );
+    assert_matrix_eq!(
+        operator.compute_elliptic_operator(&compression, &broken),
+        linear.compute_elliptic_operator(&compression, &lame()),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // ... whereas pure volumetric expansion of fully broken material is stress-free
+    let expansion = Matrix3::identity() * 0.01;
+    assert_scalar_eq!(
+        operator.compute_energy(&expansion, &broken),
+        0.0,
+        comp = abs,
+        tol = 1e-16
+    );
+    assert_matrix_eq!(
+        operator.compute_elliptic_operator(&expansion, &broken),
+        Matrix3::zeros(),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // Without split, compression is degraded as well
+    let no_split = DegradedElasticityOperator::new();
+    assert_matrix_eq!(
+        no_split.compute_elliptic_operator(&compression, &broken),
+        Matrix3::zeros(),
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(
+        no_split.tensile_energy_density(&compression, &lame()),
+        linear.compute_energy(&compression, &lame()),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+#[test]
+fn degraded_elasticity_operator_degrades_shear() {
+    let operator = DegradedElasticityOperator::with_split(EnergySplit::VolumetricDeviatoric);
+    let shear = Matrix2::new(0.0, 0.02, 0.0, 0.0);
+    let LameParameters { mu, .. } = lame();
+    let g = degradation_function(0.5, 1e-3);
+    assert_scalar_eq!(g, 0.251, comp = abs, tol = 1e-15);
+    let stress = operator.compute_elliptic_operator(&shear, &degraded(g));
+    assert_matrix_eq!(
+        stress,
+        Matrix2::new(0.0, 0.02 * mu * g, 0.02 * mu * g, 0.0),
+        comp = abs,
+        tol = 1e-15
+    );
+}
+
+#[test]
+fn phase_field_operator_coefficients() {
+    let parameters = PhaseFieldParameters {
+        fracture_toughness: 2.0,
+        length_scale: 0.1,
+        history: 3.0,
+    };
+    let x = Point2::new(0.3, 0.7);
+
+    let at2 = PhaseFieldOperator::default();
+    assert_eq!(at2.model(), PhaseFieldModel::At2);
+    let coefficients = at2.evaluate_coefficients(&x, &parameters);
+    assert_eq!(coefficients.velocity.norm(), 0.0);
+    assert_scalar_eq!(coefficients.diffusivity, 0.2, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(coefficients.reaction, 26.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(coefficients.source, 6.0, comp = abs, tol = 1e-14);
+
+    let at1 = PhaseFieldOperator::new(PhaseFieldModel::At1);
+    let coefficients = at1.evaluate_coefficients(&x, &parameters);
+    assert_scalar_eq!(coefficients.diffusivity, 0.15, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(coefficients.reaction, 6.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(coefficients.source, 6.0 - 7.5, comp = abs, tol = 1e-14);
+}
@@ -0,0 +1,385 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::solid::{CorotatedLinearOperator, LameParameters, NeoHookeanOperator, StVKOperator};
+use fenris::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{
+    matrix, DMatrix, DVector, DefaultAllocator, DimName, Matrix2, Matrix3, OMatrix, OVector, Rotation2, Rotation3,
+    Vector3, U2, U3,
+};
+use fenris::quadrature;
+use fenris::SmallDim;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+use crate::unit_tests::assembly::local::LinearElasticity;
+
+pub(crate) fn lame() -> LameParameters<f64> {
+    LameParameters { mu: 2.0, lambda: 5.0 }
+}
+
+pub(crate) fn u_grad_2d() -> Matrix2<f64> {
+    matrix![0.1, -0.2;
+            0.05, 0.15]
+}
+
+pub(crate) fn u_grad_3d() -> Matrix3<f64> {
+    matrix![0.1, -0.2, 0.05;
+            0.05, 0.15, -0.1;
+            -0.03, 0.08, 0.2]
+}
+
+/// The displacement gradient $\nabla u = (\vec Q \vec F - \vec I)^T$ corresponding to
+/// the deformation gradient $\vec Q \vec F$.
+fn rotated_u_grad<D>(q: &OMatrix<f64, D, D>, u_grad: &OMatrix<f64, D, D>) -> OMatrix<f64, D, D>
+where
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let identity = OMatrix::<f64, D, D>::identity();
+    let f = &identity + u_grad.transpose();
+    (q * f - identity).transpose()
+}
+
+/// Approximates $g = \partial \psi / \partial G$ with central finite differences.
+fn approximate_operator_fd<D>(
+    energy: impl Fn(&OMatrix<f64, D, D>) -> f64,
+    u_grad: &OMatrix<f64, D, D>,
+    h: f64,
+) -> OMatrix<f64, D, D>
+where
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let mut g = OMatrix::<f64, D, D>::zeros();
+    let mut u_grad = u_grad.clone();
+    for i in 0..D::dim() {
+        for j in 0..D::dim() {
+            let u_ij = u_grad[(i, j)];
+            u_grad[(i, j)] = u_ij + h;
+            let psi_plus = energy(&u_grad);
+            u_grad[(i, j)] = u_ij - h;
+            let psi_minus = energy(&u_grad);
+            u_grad[(i, j)] = u_ij;
+            g[(i, j)] = (psi_plus - psi_minus) / (2.0 * h);
+        }
+    }
+    g
+}
+
+/// Approximates $\mathcal{C}_g(\nabla u, a, b) = a_k (\partial g_{ki} / \partial G_{mj}) b_m$ with
+/// central finite differences.
+fn approximate_contraction_fd<D>(
+    operator: impl Fn(&OMatrix<f64, D, D>) -> OMatrix<f64, D, D>,
+    u_grad: &OMatrix<f64, D, D>,
+    a: &OVector<f64, D>,
+    b: &OVector<f64, D>,
+    h: f64,
+) -> OMatrix<f64, D, D>
+where
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let d = D::dim();
+    let mut contraction = OMatrix::<f64, D, D>::zeros();
+    let mut u_grad = u_grad.clone();
+    for m in 0..d {
+        for j in 0..d {
+            let u_mj = u_grad[(m, j)];
+            u_grad[(m, j)] = u_mj + h;
+            let g_plus = operator(&u_grad);
+            u_grad[(m, j)] = u_mj - h;
+            let g_minus = operator(&u_grad);
+            u_grad[(m, j)] = u_mj;
+            let dg = (g_plus - g_minus) / (2.0 * h);
+            for i in 0..d {
+                for k in 0..d {
+                    contraction[(i, j)] += a[k] * dg[(k, i)] * b[m];
+                }
+            }
+        }
+    }
+    contraction
+}
+
+fn test_vectors<D>() -> (OVector<f64, D>, OVector<f64, D>)
+where
+    D: DimName,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let a = OVector::<f64, D>::from_fn(|i, _| 1.0 + 0.5 * i as f64);
+    let b = OVector::<f64, D>::from_fn(|i, _| (i as f64 + 0.3).sin());
+    (a, b)
+}
+
+/// Checks that the elliptic operator is the derivative of the energy, that the batched
+/// contractions agree with individual contractions and, if `exact_contraction` is set,
+/// that the contraction is the derivative of the elliptic operator.
+pub(crate) fn assert_solid_operator_consistent<D, Op>(
+    operator: &Op,
+    u_grad: &OMatrix<f64, D, D>,
+    exact_contraction: bool,
+) where
+    D: SmallDim,
+    Op: EllipticEnergy<f64, D, SolutionDim = D, Parameters = LameParameters<f64>>
+        + EllipticOperator<f64, D>
+        + EllipticContraction<f64, D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    assert_solid_operator_consistent_with_parameters(operator, u_grad, &lame(), exact_contraction)
+}
+
+/// Same as [`assert_solid_operator_consistent`], but with the given operator parameters.
+pub(crate) fn assert_solid_operator_consistent_with_parameters<D, Op>(
+    operator: &Op,
+    u_grad: &OMatrix<f64, D, D>,
+    params: &Op::Parameters,
+    exact_contraction: bool,
+) where
+    D: SmallDim,
+    Op: EllipticEnergy<f64, D, SolutionDim = D> + EllipticOperator<f64, D> + EllipticContraction<f64, D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let d = D::dim();
+    let h = 1e-6;
+
+    let g = operator.compute_elliptic_operator(u_grad, params);
+    let g_fd = approximate_operator_fd(|u_grad| operator.compute_energy(u_grad, params), u_grad, h);
+    assert_matrix_eq!(g, g_fd, comp = abs, tol = 1e-7);
+    assert_matrix_eq!(
+        operator.compute_elliptic_operator_transpose(u_grad, params),
+        g.transpose(),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    let (a, b) = test_vectors::<D>();
+    let c = operator.contract(u_grad, &a, &b, params);
+    assert_matrix_eq!(
+        c,
+        operator.contract(u_grad, &b, &a, params).transpose(),
+        comp = abs,
+        tol = 1e-12
+    );
+    if exact_contraction {
+        let c_fd = approximate_contraction_fd(
+            |u_grad| operator.compute_elliptic_operator(u_grad, params),
+            u_grad,
+            &a,
+            &b,
+            h,
+        );
+        assert_matrix_eq!(c, c_fd, comp = abs, tol = 1e-7);
+    }
+
+    let (m, n) = (2, 3);
+    let a_stacked = DVector::from_fn(d * m, |i, _| (0.7 * i as f64).cos());
+    let b_stacked = DVector::from_fn(d * n, |i, _| 0.2 + (1.3 * i as f64).sin());
+    let mut output = DMatrix::repeat(d * m, d * n, 1.0);
+    operator.accumulate_contractions_into(
+        (&mut output).into(),
+        2.0,
+        u_grad,
+        (&a_stacked).into(),
+        (&b_stacked).into(),
+        params,
+    );
+    // Only the block upper triangle is filled for symmetric operators
+    for j in 0..n {
+        for i in 0..=j.min(m - 1) {
+            let a_i = OVector::<f64, D>::from_fn(|k, _| a_stacked[d * i + k]);
+            let b_j = OVector::<f64, D>::from_fn(|k, _| b_stacked[d * j + k]);
+            let expected = operator.contract(u_grad, &a_i, &b_j, params) * 2.0 + OMatrix::<f64, D, D>::repeat(1.0);
+            let block = output.view((d * i, d * j), (d, d));
+            assert_matrix_eq!(block, expected, comp = abs, tol = 1e-12);
+        }
+    }
+}
+
+/// At zero displacement, all models reduce to linear elasticity.
+fn assert_linear_elastic_at_rest<D, Op>(operator: &Op)
+where
+    D: SmallDim,
+    Op: EllipticEnergy<f64, D, SolutionDim = D, Parameters = LameParameters<f64>>
+        + EllipticOperator<f64, D>
+        + EllipticContraction<f64, D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let LameParameters { mu, lambda } = lame();
+    let zero = OMatrix::<f64, D, D>::zeros();
+    assert_scalar_eq!(operator.compute_energy(&zero, &lame()), 0.0, comp = abs, tol = 1e-14);
+    assert_matrix_eq!(
+        operator.compute_elliptic_operator(&zero, &lame()),
+        zero,
+        comp = abs,
+        tol = 1e-14
+    );
+    let (a, b) = test_vectors::<D>();
+    let identity = OMatrix::<f64, D, D>::identity();
+    let expected = (identity * a.dot(&b) + &b * a.transpose()) * mu + &a * b.transpose() * lambda;
+    assert_matrix_eq!(
+        operator.contract(&zero, &a, &b, &lame()),
+        expected,
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn lame_parameters_from_young_poisson() {
+    let (young, poisson) = (1e3, 0.3);
+    let LameParameters { mu, lambda } = LameParameters::from_young_poisson(young, poisson);
+    assert_scalar_eq!(mu, 1e3 / 2.6, comp = abs, tol = 1e-10);
+    assert_scalar_eq!(
+        mu * (3.0 * lambda + 2.0 * mu) / (lambda + mu),
+        young,
+        comp = abs,
+        tol = 1e-10
+    );
+    assert_scalar_eq!(lambda / (2.0 * (lambda + mu)), poisson, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn neo_hookean_operator_consistency() {
+    assert_solid_operator_consistent::<U2, _>(&NeoHookeanOperator, &u_grad_2d(), true);
+    assert_solid_operator_consistent::<U3, _>(&NeoHookeanOperator, &u_grad_3d(), true);
+    assert_linear_elastic_at_rest::<U2, _>(&NeoHookeanOperator);
+    assert_linear_elastic_at_rest::<U3, _>(&NeoHookeanOperator);
+
+    // Inverted configurations have infinite energy
+    let inverted = matrix![-2.0, 0.0;
+                            0.0, 0.0];
+    assert_eq!(NeoHookeanOperator.compute_energy(&inverted, &lame()), f64::INFINITY);
+    assert!(NeoHookeanOperator
+        .compute_elliptic_operator(&inverted, &lame())
+        .iter()
+        .all(|x| x.is_nan()));
+}
+
+#[test]
+fn stvk_operator_consistency() {
+    assert_solid_operator_consistent::<U2, _>(&StVKOperator, &u_grad_2d(), true);
+    assert_solid_operator_consistent::<U3, _>(&StVKOperator, &u_grad_3d(), true);
+    assert_linear_elastic_at_rest::<U2, _>(&StVKOperator);
+    assert_linear_elastic_at_rest::<U3, _>(&StVKOperator);
+}
+
+#[test]
+fn corotated_linear_operator_consistency() {
+    // The contraction neglects the derivative of the rotation, so it is only exact
+    // for pure rotations
+    assert_solid_operator_consistent::<U2, _>(&CorotatedLinearOperator, &u_grad_2d(), false);
+    assert_solid_operator_consistent::<U3, _>(&CorotatedLinearOperator, &u_grad_3d(), false);
+    assert_linear_elastic_at_rest::<U2, _>(&CorotatedLinearOperator);
+    assert_linear_elastic_at_rest::<U3, _>(&CorotatedLinearOperator);
+
+    let q2 = Rotation2::new(0.7).into_inner();
+    let q3 = Rotation3::from_scaled_axis(Vector3::new(0.3, -0.5, 0.8)).into_inner();
+    let rotation_2d = rotated_u_grad(&q2, &Matrix2::zeros());
+    let rotation_3d = rotated_u_grad(&q3, &Matrix3::zeros());
+    assert_solid_operator_consistent::<U2, _>(&CorotatedLinearOperator, &rotation_2d, true);
+    assert_solid_operator_consistent::<U3, _>(&CorotatedLinearOperator, &rotation_3d, true);
+
+    // For pure rotations, the contraction is the rotated contraction of linear elasticity
+    let (a, b) = test_vectors::<U3>();
+    let c_rest = CorotatedLinearOperator.contract(&Matrix3::zeros(), &a, &b, &lame());
+    assert_matrix_eq!(
+        CorotatedLinearOperator.contract(&rotation_3d, &a, &b, &lame()),
+        q3 * c_rest * q3.transpose(),
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn solid_operators_are_rotationally_invariant() {
+    fn check<D, Op>(operator: &Op, q: &OMatrix<f64, D, D>, u_grad: &OMatrix<f64, D, D>)
+    where
+        D: SmallDim,
+        Op: EllipticEnergy<f64, D, SolutionDim = D, Parameters = LameParameters<f64>> + EllipticOperator<f64, D>,
+        DefaultAllocator: BiDimAllocator<f64, D, D>,
+    {
+        // psi(Q F) = psi(F) and P(Q F) = Q P(F)
+        let rotated = rotated_u_grad(q, u_grad);
+        assert_scalar_eq!(
+            operator.compute_energy(&rotated, &lame()),
+            operator.compute_energy(u_grad, &lame()),
+            comp = abs,
+            tol = 1e-12
+        );
+        assert_matrix_eq!(
+            operator.compute_elliptic_operator_transpose(&rotated, &lame()),
+            q * operator.compute_elliptic_operator_transpose(u_grad, &lame()),
+            comp = abs,
+            tol = 1e-12
+        );
+    }
+
+    let q2 = Rotation2::new(-1.3).into_inner();
+    let q3 = Rotation3::from_scaled_axis(Vector3::new(-0.4, 1.1, 0.2)).into_inner();
+    check(&NeoHookeanOperator, &q2, &u_grad_2d());
+    check(&NeoHookeanOperator, &q3, &u_grad_3d());
+    check(&StVKOperator, &q2, &u_grad_2d());
+    check(&StVKOperator, &q3, &u_grad_3d());
+    check(&CorotatedLinearOperator, &q2, &u_grad_2d());
+    check(&CorotatedLinearOperator, &q3, &u_grad_3d());
+}
+
+#[test]
+fn solid_operator_assembly_rigid_motion_and_linearization() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), lame());
+    let n = 2 * mesh.vertices().len();
+
+    // At rest, the stiffness matrix of every model is the stiffness matrix of linear elasticity
+    let LameParameters { mu, lambda } = lame();
+    let linear_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u_zero = DVector::zeros(n);
+    let k_linear = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&LinearElasticity { mu, lambda })
+                .with_quadrature_table(&linear_qtable)
+                .with_u(&u_zero)
+                .build(),
+        )
+        .unwrap();
+
+    // A rigid rotation does not produce any internal forces
+    let q = Rotation2::new(0.9).into_inner();
+    let u_rotation = DVector::from_iterator(
+        n,
+        mesh.vertices()
+            .iter()
+            .flat_map(|x| ((q * x.coords) - x.coords).data.0[0]),
+    );
+
+    macro_rules! check {
+        ($operator:expr) => {{
+            let assemble = |u: &DVector<f64>| {
+                let assembler = ElementEllipticAssemblerBuilder::new()
+                    .with_finite_element_space(&mesh)
+                    .with_operator(&$operator)
+                    .with_quadrature_table(&qtable)
+                    .with_u(u)
+                    .build();
+                let f = VectorAssembler::default()
+                    .assemble_vector(&assembler)
+                    .unwrap();
+                let k = CsrAssembler::default().assemble(&assembler).unwrap();
+                (f, k)
+            };
+            let (_, k) = assemble(&u_zero);
+            assert_matrix_eq!(DMatrix::from(&k), DMatrix::from(&k_linear), comp = abs, tol = 1e-12);
+            let (f, _) = assemble(&u_rotation);
+            assert_matrix_eq!(f, DVector::zeros(n), comp = abs, tol = 1e-12);
+        }};
+    }
+
+    check!(NeoHookeanOperator);
+    check!(StVKOperator);
+    check!(CorotatedLinearOperator);
+}
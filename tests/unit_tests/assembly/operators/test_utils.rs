@@ -0,0 +1,177 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::operators::solid::LameParameters;
+use fenris::assembly::operators::test_utils::OperatorConsistencyChecker;
+use fenris::assembly::operators::{
+    EllipticContraction, EllipticEnergy, EllipticOperator, LaplaceOperator, LinearElasticityOperator, Operator,
+};
+use fenris::nalgebra::{DefaultAllocator, OMatrix, Vector2, U1, U2, U3};
+use fenris::{SmallDim, Symmetry};
+
+use super::solid::lame;
+
+/// A nonlinear scalar operator with energy $\psi = \sqrt{1 + |G|^2}$, with configurable mistakes.
+#[derive(Debug, Clone, Copy, Default)]
+struct MinimalSurfaceOperator {
+    contraction_scale: f64,
+    energy_scale: f64,
+    claims_symmetry: bool,
+    non_symmetric: bool,
+}
+
+impl MinimalSurfaceOperator {
+    fn correct() -> Self {
+        Self {
+            contraction_scale: 1.0,
+            energy_scale: 1.0,
+            claims_symmetry: true,
+            non_symmetric: false,
+        }
+    }
+}
+
+impl Operator<f64, U2> for MinimalSurfaceOperator {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl EllipticEnergy<f64, U2> for MinimalSurfaceOperator {
+    fn compute_energy(&self, gradient: &Vector2<f64>, _: &()) -> f64 {
+        self.energy_scale * (1.0 + gradient.norm_squared()).sqrt()
+    }
+}
+
+impl EllipticOperator<f64, U2> for MinimalSurfaceOperator {
+    fn compute_elliptic_operator(&self, gradient: &Vector2<f64>, _: &()) -> Vector2<f64> {
+        let mut g = gradient / (1.0 + gradient.norm_squared()).sqrt();
+        if self.non_symmetric {
+            g[0] += 0.1 * gradient[1];
+        }
+        g
+    }
+}
+
+impl EllipticContraction<f64, U2> for MinimalSurfaceOperator {
+    fn contract(&self, gradient: &Vector2<f64>, a: &Vector2<f64>, b: &Vector2<f64>, _: &()) -> OMatrix<f64, U1, U1> {
+        let q = 1.0 + gradient.norm_squared();
+        let mut c = a.dot(b) / q.sqrt() - a.dot(gradient) * b.dot(gradient) / q.powf(1.5);
+        if self.non_symmetric {
+            c += 0.1 * a[0] * b[1];
+        }
+        OMatrix::<f64, U1, U1>::new(self.contraction_scale * c)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        if self.claims_symmetry {
+            Symmetry::Symmetric
+        } else {
+            Symmetry::NonSymmetric
+        }
+    }
+}
+
+fn check_laplace<D>()
+where
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<f64, D, U1>,
+{
+    OperatorConsistencyChecker::new()
+        .check_all::<D, _>(&LaplaceOperator, &())
+        .unwrap();
+}
+
+#[test]
+fn consistent_operators_pass_checks() {
+    check_laplace::<U1>();
+    check_laplace::<U2>();
+    check_laplace::<U3>();
+
+    let checker = OperatorConsistencyChecker::new();
+    checker
+        .check_all::<U2, _>(&LinearElasticityOperator::plane_stress(), &lame())
+        .unwrap();
+    checker
+        .check_all::<U3, _>(&LinearElasticityOperator::new(), &lame())
+        .unwrap();
+    checker
+        .check_all::<U2, _>(&MinimalSurfaceOperator::correct(), &())
+        .unwrap();
+
+    // Large parameters scale the tolerance accordingly
+    let stiff = LameParameters { mu: 1e9, lambda: 5e9 };
+    checker
+        .check_all::<U3, _>(&LinearElasticityOperator::new(), &stiff)
+        .unwrap();
+
+    // Checks at explicitly given states
+    let gradient = Vector2::new(2.0, -3.0);
+    let (a, b) = (Vector2::new(1.0, 0.5), Vector2::new(-0.3, 2.0));
+    checker
+        .check_energy_at(&MinimalSurfaceOperator::correct(), &gradient, &())
+        .unwrap();
+    checker
+        .check_contraction_at(&MinimalSurfaceOperator::correct(), &gradient, &a, &b, &())
+        .unwrap();
+}
+
+#[test]
+fn inconsistent_operators_fail_checks() {
+    let checker = OperatorConsistencyChecker::new()
+        .with_num_samples(3)
+        .with_gradient_scale(2.0);
+
+    let wrong_energy = MinimalSurfaceOperator {
+        energy_scale: 1.01,
+        ..MinimalSurfaceOperator::correct()
+    };
+    assert!(checker.check_energy::<U2, _>(&wrong_energy, &()).is_err());
+    assert!(checker
+        .check_contraction::<U2, _>(&wrong_energy, &())
+        .is_ok());
+
+    let wrong_contraction = MinimalSurfaceOperator {
+        contraction_scale: 1.01,
+        ..MinimalSurfaceOperator::correct()
+    };
+    assert!(checker
+        .check_energy::<U2, _>(&wrong_contraction, &())
+        .is_ok());
+    let err = checker
+        .check_contraction::<U2, _>(&wrong_contraction, &())
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Contraction vs. derivative of elliptic operator"));
+
+    // A non-symmetric contraction that is consistent with its (non-potential) operator passes
+    // the derivative check, but must not claim symmetry
+    let false_symmetry = MinimalSurfaceOperator {
+        non_symmetric: true,
+        ..MinimalSurfaceOperator::correct()
+    };
+    let err = checker
+        .check_contraction::<U2, _>(&false_symmetry, &())
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("claims symmetry"));
+    let no_symmetry_claim = MinimalSurfaceOperator {
+        claims_symmetry: false,
+        ..false_symmetry
+    };
+    checker
+        .check_contraction::<U2, _>(&no_symmetry_claim, &())
+        .unwrap();
+}
+
+#[test]
+fn checks_are_deterministic_for_fixed_seed() {
+    // The same seed reproduces the same failure
+    let wrong_contraction = MinimalSurfaceOperator {
+        contraction_scale: 1.01,
+        ..MinimalSurfaceOperator::correct()
+    };
+    let checker = OperatorConsistencyChecker::new().with_seed(42);
+    let first = checker
+        .check_contraction::<U2, _>(&wrong_contraction, &())
+        .unwrap_err();
+    let second = checker
+        .check_contraction::<U2, _>(&wrong_contraction, &())
+        .unwrap_err();
+    assert_eq!(format!("{first:#}"), format!("{second:#}"));
+}
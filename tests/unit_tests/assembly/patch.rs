@@ -0,0 +1,125 @@
+use fenris::assembly::patch::{extract_dense_submatrix, DenseSubmatrixExtractor, PatchTopology};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::DMatrix;
+use fenris::nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+#[test]
+fn patch_topology_vertex_edge_and_element_patches() {
+    // 3x3 grid of quads, with vertices numbered row by row
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let topology = PatchTopology::from_space(&mesh);
+    assert_eq!(topology.num_elements(), 9);
+    assert_eq!(topology.num_nodes(), 16);
+
+    // Corner vertices belong to a single element, interior vertices to four elements
+    assert_eq!(topology.node_valence(0), 1);
+    assert_eq!(topology.node_valence(5), 4);
+    for node in 0..topology.num_nodes() {
+        for &element in topology.node_elements(node) {
+            assert!(topology.element_nodes(element).contains(&node));
+        }
+    }
+
+    let patch = topology.vertex_patch(5);
+    assert_eq!(patch.elements(), topology.node_elements(5));
+    assert_eq!(patch.num_nodes(), 9);
+    // Nodes on the domain boundary next to the corner are only contained in elements of the patch
+    let interior: Vec<_> = patch.interior_nodes().collect();
+    assert_eq!(interior, vec![0, 1, 4, 5]);
+
+    // An interior edge is shared by exactly two elements
+    let element = topology.node_elements(5)[0];
+    let edge_elements = topology.elements_sharing_nodes(&[5, 6]);
+    assert_eq!(edge_elements.len(), 2);
+    assert_eq!(
+        topology.shared_nodes_patch(&[5, 6]).elements(),
+        edge_elements.as_slice()
+    );
+    assert!(topology.elements_sharing_nodes(&[]).is_empty());
+
+    // The center element shares nodes with all elements in the grid
+    let center = topology.elements_sharing_nodes(&[5, 6, 9, 10]);
+    assert_eq!(center.len(), 1);
+    assert_eq!(topology.element_star(center[0]), (0..9).collect::<Vec<_>>());
+    let star = topology.element_star_patch(center[0]);
+    assert_eq!(star.num_nodes(), 16);
+    assert_eq!(star.interior_nodes().count(), 16);
+    assert!(topology.element_star(element).contains(&element));
+}
+
+#[test]
+fn patch_local_numbering_is_consistent() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let topology = PatchTopology::from_space(&mesh);
+    let patch = topology.patch([4, 0, 4, 1]);
+    assert_eq!(patch.elements(), &[0, 1, 4]);
+
+    for (i, &element) in patch.elements().iter().enumerate() {
+        let global_nodes: Vec<_> = patch
+            .element_local_nodes(i)
+            .iter()
+            .map(|&local| patch.nodes()[local])
+            .collect();
+        assert_eq!(global_nodes, topology.element_nodes(element));
+
+        let global_dofs = patch.global_dofs(2);
+        let element_dofs: Vec<_> = patch
+            .element_local_dofs(i, 2)
+            .iter()
+            .map(|&local| global_dofs[local])
+            .collect();
+        let expected: Vec<_> = topology
+            .element_nodes(element)
+            .iter()
+            .flat_map(|&node| [2 * node, 2 * node + 1])
+            .collect();
+        assert_eq!(element_dofs, expected);
+    }
+
+    for (local, &global) in patch.nodes().iter().enumerate() {
+        assert_eq!(patch.local_node_index(global), Some(local));
+        let interior = topology
+            .node_elements(global)
+            .iter()
+            .all(|element| patch.elements().contains(element));
+        assert_eq!(patch.is_interior_node(local), interior);
+    }
+    assert_eq!(patch.local_node_index(15), None);
+    assert_eq!(
+        patch.interior_global_dofs(2),
+        patch
+            .interior_nodes()
+            .flat_map(|node| [2 * node, 2 * node + 1])
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn extract_dense_submatrix_basic() {
+    let dense = DMatrix::from_fn(5, 5, |i, j| if (i + j) % 2 == 0 { (5 * i + j) as f64 } else { 0.0 });
+    let matrix = CsrMatrix::from(&CooMatrix::from(&dense));
+    let dofs = [3, 0, 4];
+    let submatrix = extract_dense_submatrix(&matrix, &dofs);
+    let expected = DMatrix::from_fn(3, 3, |i, j| dense[(dofs[i], dofs[j])]);
+    assert_eq!(submatrix, expected);
+}
+
+#[test]
+fn dense_submatrix_extractor_can_be_reused() {
+    let dense = DMatrix::from_fn(6, 6, |i, j| if (i + 2 * j) % 3 == 0 { (6 * i + j) as f64 } else { 0.0 });
+    let matrix = CsrMatrix::from(&CooMatrix::from(&dense));
+    let mut extractor = DenseSubmatrixExtractor::new();
+    for dofs in [vec![3, 0, 4], vec![5, 1], vec![], vec![0, 1, 2, 3, 4, 5], vec![4, 3]] {
+        let expected = DMatrix::from_fn(dofs.len(), dofs.len(), |i, j| dense[(dofs[i], dofs[j])]);
+        assert_eq!(extractor.extract(&matrix, &dofs), expected);
+        assert_eq!(extract_dense_submatrix(&matrix, &dofs), expected);
+    }
+}
+
+#[test]
+#[should_panic]
+fn extract_dense_submatrix_panics_for_duplicate_indices() {
+    let matrix = CsrMatrix::<f64>::identity(4);
+    extract_dense_submatrix(&matrix, &[1, 2, 1]);
+}
@@ -0,0 +1,139 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::assembly::projection::{
+    par_project_element_l2, par_solve_element_systems, project_l2, project_l2_with_solver,
+};
+use fenris::linear_solver::DenseLuSolver;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::nalgebra::{dvector, vector, DMatrix, DVector, Point2, Vector1};
+use fenris::quadrature;
+use fenris::space::{DiscontinuousSpace, FiniteElementSpace};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn par_solve_element_systems_matches_direct_solves() {
+    // Symmetric positive definite systems for even elements, non-symmetric systems for odd elements
+    let system = |element_index: usize| {
+        let s = element_index as f64;
+        let a = if element_index.is_multiple_of(2) {
+            DMatrix::from_row_slice(2, 2, &[4.0 + s, 1.0, 1.0, 3.0])
+        } else {
+            DMatrix::from_row_slice(2, 2, &[1.0, 2.0 + s, 0.5, 3.0])
+        };
+        let b = DMatrix::from_row_slice(2, 2, &[1.0, s, -1.0, 2.0]);
+        (a, b)
+    };
+
+    for batch_size in [1, 3, 100] {
+        let solutions = par_solve_element_systems(17, batch_size, |i| Ok(system(i))).unwrap();
+        assert_eq!(solutions.len(), 17);
+        for (i, x) in solutions.iter().enumerate() {
+            let (a, b) = system(i);
+            let expected = a.lu().solve(&b).unwrap();
+            assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+        }
+    }
+
+    let singular = par_solve_element_systems(4, 1, |i| {
+        let a: DMatrix<f64> = if i == 2 {
+            DMatrix::zeros(2, 2)
+        } else {
+            DMatrix::identity(2, 2)
+        };
+        Ok((a, DMatrix::zeros(2, 1)))
+    });
+    assert!(singular.is_err());
+
+    let incompatible = par_solve_element_systems(1, 1, |_| Ok((DMatrix::<f64>::identity(2, 2), DMatrix::zeros(3, 1))));
+    assert!(incompatible.is_err());
+}
+
+#[test]
+fn par_project_element_l2_reproduces_fields_in_element_space() {
+    // A bilinear vector field lies in the span of the element basis, so the projection is exact
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let field = |x: f64, y: f64| dvector![1.0 + 2.0 * x - y + 3.0 * x * y, x - 4.0 * y];
+    let projected = par_project_element_l2(&mesh, &qtable, 2, 2, |element_index, xi| {
+        let x = mesh.map_element_reference_coords(element_index, xi);
+        Ok(field(x.x, x.y))
+    })
+    .unwrap();
+
+    let u = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices()
+            .iter()
+            .flat_map(|v| Vec::from(field(v.x, v.y).data)),
+    );
+    let expected = DiscontinuousSpace::from_space(mesh.clone()).map_continuous_vector(&u, 2);
+    assert_matrix_eq!(projected, expected, comp = abs, tol = 1e-12);
+
+    let wrong_dimension = par_project_element_l2(&mesh, &qtable, 3, 1, |_, _| Ok(dvector![1.0]));
+    assert!(wrong_dimension.is_err());
+}
+
+#[test]
+fn par_project_element_l2_of_discontinuous_field_gives_element_means() {
+    // A field that is constant on each element projects onto linear triangles as the constant
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let projected = par_project_element_l2(&mesh, &qtable, 1, 1, |element_index, _| {
+        Ok(dvector![element_index as f64])
+    })
+    .unwrap();
+    let space = DiscontinuousSpace::from_space(mesh.clone());
+    for element_index in 0..mesh.connectivity().len() {
+        for node in space.element_nodes(element_index) {
+            assert!((projected[node] - element_index as f64).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn project_l2_reproduces_functions_in_the_space() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let f = |x: &Point2<f64>| vector![1.0 + 2.0 * x.x - x.y, 0.5 * x.y];
+
+    let u = project_l2(&mesh, &qtable, f).unwrap();
+    let expected = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices()
+            .iter()
+            .flat_map(|x| f(x).iter().copied().collect::<Vec<_>>()),
+    );
+    assert_matrix_eq!(u, expected, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn project_l2_preserves_integral() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(5);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let f = |x: &Point2<f64>| Vector1::new(x.x * x.x + x.x * x.y * x.y);
+
+    let u = project_l2(&mesh, &qtable, f).unwrap();
+    let u_lu = project_l2_with_solver(&mesh, &qtable, &mut DenseLuSolver, f).unwrap();
+    assert_matrix_eq!(u, u_lu, comp = abs, tol = 1e-10);
+
+    // The basis functions form a partition of unity, so the projection has the same integral as
+    // the function, which is 1/3 + 1/6 over the unit square
+    let mut integral = 0.0;
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss::<f64>(3);
+    let mut basis = vec![0.0; 4];
+    for element_index in 0..mesh.connectivity().len() {
+        let nodes = mesh.connectivity()[element_index].0;
+        for (w, xi) in weights.iter().zip(&points) {
+            let j_det = mesh
+                .element_reference_jacobian(element_index, xi)
+                .determinant();
+            mesh.populate_element_basis(element_index, &mut basis, xi);
+            let u_h: f64 = nodes
+                .iter()
+                .zip(&basis)
+                .map(|(&node, phi)| u[node] * phi)
+                .sum();
+            integral += w * j_det * u_h;
+        }
+    }
+    assert_scalar_eq!(integral, 0.5, comp = abs, tol = 1e-10);
+}
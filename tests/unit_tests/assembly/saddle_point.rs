@@ -0,0 +1,117 @@
+use fenris::assembly::bc::DirichletConditions;
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::assembly::saddle_point::SaddlePointSystem;
+use fenris::index::NodeIndex;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::{DMatrix, DVector};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+
+fn solve_dense(system: &SaddlePointSystem<f64>, rhs: &DVector<f64>) -> DVector<f64> {
+    DMatrix::from(system.matrix()).lu().solve(rhs).unwrap()
+}
+
+#[test]
+fn saddle_point_matrix_has_expected_block_structure() {
+    let a = DMatrix::from_row_slice(3, 3, &[4.0, -1.0, 0.0, -1.0, 4.0, -1.0, 0.0, -1.0, 4.0]);
+    let c = DMatrix::from_row_slice(2, 3, &[1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+    let system = SaddlePointSystem::assemble(&CsrMatrix::from(&a), &CsrMatrix::from(&c)).unwrap();
+    assert_eq!(system.num_primal_dofs(), 3);
+    assert_eq!(system.num_multipliers(), 2);
+    assert_eq!(system.num_unknowns(), 5);
+
+    let mut expected = DMatrix::zeros(5, 5);
+    expected.view_mut((0, 0), (3, 3)).copy_from(&a);
+    expected.view_mut((3, 0), (2, 3)).copy_from(&c);
+    expected.view_mut((0, 3), (3, 2)).copy_from(&c.transpose());
+    assert_matrix_eq!(DMatrix::from(system.matrix()), expected);
+    // Only the nonzeros of the blocks are stored
+    assert_eq!(system.matrix().nnz(), 7 + 2 * 3);
+
+    let f = DVector::from_column_slice(&[1.0, 2.0, 3.0]);
+    let g = DVector::from_column_slice(&[4.0, 5.0]);
+    let rhs = system.assemble_rhs(&f, &g);
+    assert_eq!(rhs.as_slice(), &[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let solution = solve_dense(&system, &rhs);
+    let (u, lambda) = system.split_solution(&solution);
+    assert_eq!(system.primal_part(&solution), u);
+    assert_eq!(system.multiplier_part(&solution), lambda);
+    assert_matrix_eq!(&c * u, g, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(&a * u + c.transpose() * lambda, f, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn saddle_point_system_enforces_dirichlet_and_mean_value_constraints() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u0 = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u0)
+        .build();
+    let a = CsrAssembler::default().assemble(&assembler).unwrap();
+    let n = a.nrows();
+
+    // Dirichlet conditions reproducing the linear solution u = 1 + x + 2y must give the same
+    // result as elimination
+    let u_exact = |x: f64, y: f64| 1.0 + x + 2.0 * y;
+    let boundary_nodes = mesh.find_boundary_vertices();
+    let boundary_values: Vec<_> = boundary_nodes
+        .iter()
+        .map(|&node| u_exact(mesh.vertices()[node].x, mesh.vertices()[node].y))
+        .collect();
+    let conditions = DirichletConditions::try_from_nodes_and_values(
+        n,
+        1,
+        NodeIndex::from_raw_slice(&boundary_nodes),
+        &boundary_values,
+    )
+    .unwrap();
+    let c = conditions.constraint_operator();
+    assert_eq!(c.nrows(), boundary_nodes.len());
+
+    let system = SaddlePointSystem::assemble(&a, &c).unwrap();
+    let b = DVector::zeros(n);
+    let g = DVector::from_column_slice(conditions.values());
+    let solution = solve_dense(&system, &system.assemble_rhs(&b, &g));
+    let expected = DVector::from_iterator(n, mesh.vertices().iter().map(|v| u_exact(v.x, v.y)));
+    assert_matrix_eq!(system.primal_part(&solution), expected, comp = abs, tol = 1e-12);
+
+    let reduced = conditions.eliminate(&a, &b).unwrap();
+    let reduced_solution = DMatrix::from(reduced.matrix())
+        .lu()
+        .solve(reduced.rhs())
+        .unwrap();
+    assert_matrix_eq!(
+        system.primal_part(&solution),
+        reduced.recover_solution(&reduced_solution),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // The pure Neumann problem is singular, but becomes well-posed with a zero mean constraint
+    let c = CsrMatrix::from(&DMatrix::repeat(1, n, 1.0));
+    let system = SaddlePointSystem::assemble(&a, &c).unwrap();
+    let f = DVector::from_fn(n, |i, _| if i == 0 { 1.0 } else { -1.0 / (n - 1) as f64 });
+    let solution = solve_dense(&system, &system.assemble_rhs(&f, &DVector::zeros(1)));
+    let u = system.primal_part(&solution);
+    assert!(u.sum().abs() < 1e-12);
+    assert!(system.multiplier_part(&solution)[0].abs() < 1e-12);
+    assert_matrix_eq!(&a * u, f, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn saddle_point_system_rejects_incompatible_blocks() {
+    let a = CsrMatrix::<f64>::identity(3);
+    let c = CsrMatrix::<f64>::identity(2);
+    assert!(SaddlePointSystem::assemble(&a, &c).is_err());
+
+    let rectangular = CsrMatrix::from(&DMatrix::<f64>::zeros(3, 2));
+    assert!(SaddlePointSystem::assemble(&rectangular, &c).is_err());
+}
@@ -0,0 +1,184 @@
+use fenris::assembly::global::{color_nodes, CsrAssembler, CsrParAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementSourceAssemblerBuilder, QuadratureTable,
+    SourceFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
+use fenris::assembly::stats::{inverse_mapping_profile, ElementProfile, InstrumentedAssembler};
+use fenris::connectivity::Connectivity;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::nalgebra::{DVector, Point2, Vector1, U1, U2};
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+use std::time::Duration;
+
+struct UnitSource;
+
+impl Operator<f64, U2> for UnitSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for UnitSource {
+    fn evaluate(&self, _x: &Point2<f64>, _data: &()) -> Vector1<f64> {
+        Vector1::new(1.0)
+    }
+}
+
+#[test]
+fn instrumented_assembler_records_matrix_assembly_stats() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let num_cells = mesh.connectivity().len();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let u = DVector::zeros(mesh.vertices().len());
+    let element_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let expected = CsrAssembler::default()
+        .assemble(&element_assembler)
+        .unwrap();
+
+    let instrumented = InstrumentedAssembler::new(&element_assembler).with_quadrature_table(&qtable);
+    let matrix = instrumented
+        .record_assembly(|assembler| CsrAssembler::default().assemble(assembler))
+        .unwrap();
+    assert_matrix_eq!(matrix, expected);
+
+    let num_points = qtable.element_quadrature_size(0);
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_assemblies, 1);
+    assert_eq!(stats.num_elements, num_cells);
+    assert_eq!(stats.num_quadrature_points, num_cells * num_points);
+    // Each 3x3 element matrix is contracted with 2-dimensional gradients at every point
+    assert_eq!(stats.estimated_flops, (num_cells * num_points * 2 * 2 * 9) as u64);
+    assert_eq!(
+        stats.max_element_working_set_bytes,
+        8 * (9 + 3 * num_points) + 3 * std::mem::size_of::<usize>()
+    );
+    assert_eq!(stats.output_entries, expected.nnz());
+    assert!(stats.total_time >= stats.element_time);
+    assert_eq!(stats.global_time(), stats.total_time - stats.element_time);
+
+    // Parallel assembly accumulates into the same counters
+    let colors = color_nodes(&mesh);
+    instrumented
+        .record_assembly(|assembler| CsrParAssembler::default().assemble(&colors, assembler))
+        .unwrap();
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_assemblies, 2);
+    assert_eq!(stats.num_elements, 2 * num_cells);
+
+    instrumented.reset_stats();
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_elements, 0);
+    assert_eq!(stats.element_time, Duration::ZERO);
+    assert_eq!(stats.estimated_element_flops_per_second(), None);
+
+    // Element kernels invoked outside of global assembly are also recorded
+    instrumented.assemble_element_matrix(0).unwrap();
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_assemblies, 0);
+    assert_eq!(stats.num_elements, 1);
+}
+
+#[test]
+fn instrumented_assembler_records_vector_assembly_stats() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(1).unwrap());
+    let element_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+
+    // Without a quadrature table, only timings and element counts are recorded
+    let instrumented = InstrumentedAssembler::new(&element_assembler);
+    let vector = instrumented
+        .record_assembly(|assembler| VectorAssembler::default().assemble_vector(assembler))
+        .unwrap();
+    assert_eq!(vector.len(), mesh.vertices().len());
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_elements, mesh.connectivity().len());
+    assert_eq!(stats.num_quadrature_points, 0);
+    assert_eq!(stats.estimated_flops, 0);
+    assert_eq!(stats.output_entries, mesh.vertices().len());
+
+    // Failed assemblies are timed, but do not record an output
+    let result: eyre::Result<DVector<f64>> = instrumented.record_assembly(|_| Err(eyre::eyre!("failure")));
+    assert!(result.is_err());
+    let stats = instrumented.stats();
+    assert_eq!(stats.num_assemblies, 2);
+    assert_eq!(stats.output_entries, mesh.vertices().len());
+}
+
+#[test]
+fn instrumented_assembler_records_element_profile() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let num_cells = mesh.connectivity().len();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(1).unwrap());
+    let element_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+
+    let instrumented = InstrumentedAssembler::new(&element_assembler);
+    assert_eq!(instrumented.element_time_profile(), None);
+
+    let instrumented = instrumented.with_element_profiling();
+    instrumented
+        .record_assembly(|assembler| VectorAssembler::default().assemble_vector(assembler))
+        .unwrap();
+    let profile = instrumented.element_time_profile().unwrap();
+    assert_eq!(profile.num_elements(), num_cells);
+    let total: f64 = profile.values().iter().sum();
+    let element_time = instrumented.stats().element_time.as_secs_f64();
+    assert!((total - element_time).abs() <= 1e-9 * num_cells as f64);
+
+    instrumented.reset_stats();
+    let profile = instrumented.element_time_profile().unwrap();
+    assert!(profile.values().iter().all(|&t| t == 0.0));
+}
+
+#[test]
+fn element_profile_flags_outliers() {
+    let profile = ElementProfile::from_values(vec![1.0, 2.0, 1.5, 10.0, f64::NAN]);
+    assert_eq!(profile.median(), Some(1.75));
+    assert_eq!(profile.outliers(3.0), vec![3, 4]);
+    assert_eq!(profile.outlier_indicator(3.0), vec![0.0, 0.0, 0.0, 1.0, 1.0]);
+    assert_eq!(profile.outliers(100.0), vec![4]);
+
+    let profile = ElementProfile::from_values(vec![f64::NAN]);
+    assert_eq!(profile.median(), None);
+    assert_eq!(profile.outliers(2.0), vec![0]);
+}
+
+#[test]
+fn inverse_mapping_profile_flags_distorted_elements() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    // Move the upper right corner of the first cell (node 5) close to the opposite corner, which
+    // makes the bilinear map of the first cell strongly non-affine
+    let num_cells = mesh.connectivity().len();
+    let distorted_cell = mesh
+        .connectivity()
+        .iter()
+        .position(|conn| conn.vertex_indices().contains(&5) && conn.vertex_indices().contains(&0))
+        .unwrap();
+    mesh.vertices_mut()[5] = Point2::new(0.05, 0.05);
+
+    let points = quadrature::tensor::quadrilateral_gauss::<f64>(2).1;
+    let profile = inverse_mapping_profile(&mesh, &points);
+    assert_eq!(profile.num_elements(), num_cells);
+    let outliers = profile.outliers(2.0);
+    assert!(outliers.contains(&distorted_cell));
+    // Cells that do not share the distorted vertex are parallelograms, for which Newton
+    // converges in a single iteration
+    for (cell_idx, conn) in mesh.connectivity().iter().enumerate() {
+        if !conn.vertex_indices().contains(&5) {
+            assert!(profile.values()[cell_idx] <= 1.0);
+        }
+    }
+}
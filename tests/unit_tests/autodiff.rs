@@ -0,0 +1,54 @@
+use fenris::autodiff::{AutoDiffScalar, Dual};
+use fenris::nalgebra::{ComplexField, RealField};
+use matrixcompare::assert_scalar_eq;
+
+#[test]
+fn dual_numbers_compute_derivatives() {
+    let x = 0.7;
+    let f = |x: Dual<f64>| x.sin() * x.exp() + x.sqrt() / x.powi(2) - x.atan2(Dual::constant(2.0));
+    let df = x.cos() * x.exp() + x.sin() * x.exp() - 1.5 * x.powf(-2.5) - 2.0 / (4.0 + x * x);
+    let y = f(Dual::variable(x));
+    assert_scalar_eq!(
+        y.value(),
+        x.sin() * x.exp() + x.sqrt() / x.powi(2) - x.atan2(2.0),
+        comp = float
+    );
+    assert_scalar_eq!(y.derivative(), df, comp = abs, tol = 1e-12);
+
+    // Derivatives of powers with respect to both base and exponent
+    let base = Dual::new(1.5, 1.0);
+    let exponent = Dual::new(2.5, 2.0);
+    let y = base.powf(exponent);
+    let expected = 2.5 * 1.5f64.powf(1.5) + 1.5f64.powf(2.5) * 1.5f64.ln() * 2.0;
+    assert_scalar_eq!(y.derivative(), expected, comp = abs, tol = 1e-12);
+
+    // Branches depend only on the value
+    let y = RealField::max(Dual::new(1.0, 5.0), Dual::new(2.0, -3.0));
+    assert_eq!(y, Dual::new(2.0, -3.0));
+    assert_eq!(Dual::new(-2.0, 1.0).abs(), Dual::new(2.0, -1.0));
+}
+
+#[test]
+fn nested_dual_numbers_compute_second_derivatives() {
+    // f(x) = x^3 ln(x), f''(x) = 6 x ln(x) + 5 x
+    let x = 1.3;
+    let variable = Dual::new(Dual::variable(x), Dual::constant(1.0));
+    let y = variable.powi(3) * variable.ln();
+    assert_scalar_eq!(y.value().value(), x.powi(3) * x.ln(), comp = float);
+    assert_scalar_eq!(
+        y.value().derivative(),
+        3.0 * x * x * x.ln() + x * x,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        y.derivative().derivative(),
+        6.0 * x * x.ln() + 5.0 * x,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let constant = <Dual<Dual<f64>> as AutoDiffScalar<f64>>::from_primal(x);
+    assert_eq!(AutoDiffScalar::<f64>::primal(&constant), x);
+    assert_eq!(constant.derivative(), Dual::constant(0.0));
+}
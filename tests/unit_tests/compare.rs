@@ -0,0 +1,116 @@
+use fenris::assembly::local::{QuadratureTable, UniformQuadratureTable};
+use fenris::compare::{compare_fields, compare_meshes, FieldComparisonTolerances};
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::{QuadMesh2d, TriangleMesh2d};
+use fenris::nalgebra::{DVector, Point2, Vector2, U1, U2};
+use fenris::quadrature;
+use fenris::space::SpatiallyIndexed;
+use matrixcompare::assert_scalar_eq;
+
+fn linear_scalar_field(x: &Point2<f64>) -> f64 {
+    1.0 + 2.0 * x.x - 3.0 * x.y
+}
+
+fn interpolate_scalar_field(vertices: &[Point2<f64>], f: impl Fn(&Point2<f64>) -> f64) -> DVector<f64> {
+    DVector::from_iterator(vertices.len(), vertices.iter().map(f))
+}
+
+#[test]
+fn compare_fields_linear_field_on_different_meshes_agrees() {
+    // A linear field is exactly representable on both meshes, so the difference should vanish
+    let mesh_a: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(7);
+    let mesh_b: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(5);
+    let u_a = interpolate_scalar_field(mesh_a.vertices(), linear_scalar_field);
+    let u_b = interpolate_scalar_field(mesh_b.vertices(), linear_scalar_field);
+
+    let space_b = SpatiallyIndexed::from_space(mesh_b);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let comparison =
+        compare_fields::<_, U1, _, _, _>(&mesh_a, (&u_a).into(), &space_b, (&u_b).into(), &qtable).unwrap();
+
+    assert_eq!(
+        comparison.num_points,
+        mesh_a.connectivity().len() * qtable.element_quadrature_size(0)
+    );
+    assert_scalar_eq!(comparison.l2_difference, 0.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(comparison.max_difference, 0.0, comp = abs, tol = 1e-12);
+    assert!(comparison.l2_reference_norm > 0.0);
+
+    let tolerances = FieldComparisonTolerances::default()
+        .with_l2_abs(1e-10)
+        .with_l2_rel(1e-10)
+        .with_max_abs(1e-10);
+    assert!(comparison.is_within(&tolerances));
+    assert!(comparison.check(&tolerances).is_ok());
+}
+
+#[test]
+fn compare_fields_constant_offset() {
+    let mesh_a: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let mesh_b: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let offset = Vector2::new(0.3, -0.4);
+    let field = |x: &Point2<f64>| Vector2::new(x.x + x.y, 2.0 * x.x);
+    let u_a = DVector::from_iterator(
+        2 * mesh_a.vertices().len(),
+        mesh_a.vertices().iter().flat_map(|x| field(x).data.0[0]),
+    );
+    let u_b = DVector::from_iterator(
+        2 * mesh_b.vertices().len(),
+        mesh_b
+            .vertices()
+            .iter()
+            .flat_map(|x| (field(x) + offset).data.0[0]),
+    );
+
+    let space_b = SpatiallyIndexed::from_space(mesh_b);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let comparison =
+        compare_fields::<_, U2, _, _, _>(&mesh_a, (&u_a).into(), &space_b, (&u_b).into(), &qtable).unwrap();
+
+    // The domain has unit area, so the L2 difference is equal to the magnitude of the offset
+    assert_scalar_eq!(comparison.l2_difference, offset.norm(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(comparison.max_difference, offset.norm(), comp = abs, tol = 1e-12);
+
+    let tolerances = FieldComparisonTolerances::default().with_max_abs(0.4);
+    assert!(!comparison.is_within(&tolerances));
+    assert!(comparison.check(&tolerances).is_err());
+    let tolerances = FieldComparisonTolerances::default().with_l2_abs(0.6);
+    assert!(comparison.is_within(&tolerances));
+}
+
+#[test]
+fn compare_fields_rejects_inconsistent_weights() {
+    let mesh_a: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(2);
+    let mesh_b: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(2);
+    let u_a = DVector::zeros(mesh_a.vertices().len());
+    let u_b = DVector::zeros(mesh_b.vertices().len() + 1);
+    let space_b = SpatiallyIndexed::from_space(mesh_b);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(1).unwrap());
+    let result = compare_fields::<_, U1, _, _, _>(&mesh_a, (&u_a).into(), &space_b, (&u_b).into(), &qtable);
+    assert!(result.is_err());
+}
+
+#[test]
+fn compare_meshes_basic() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let mut perturbed = mesh.clone();
+    perturbed.vertices_mut()[2].x += 1e-3;
+
+    let comparison = compare_meshes(&mesh, &mesh);
+    assert!(comparison.same_connectivity);
+    assert_eq!(comparison.max_vertex_distance, Some(0.0));
+    assert!(comparison.is_within(0.0));
+
+    let comparison = compare_meshes(&mesh, &perturbed);
+    assert!(comparison.same_connectivity);
+    assert_scalar_eq!(comparison.max_vertex_distance.unwrap(), 1e-3, comp = abs, tol = 1e-14);
+    assert!(comparison.is_within(1e-2));
+    assert!(!comparison.is_within(1e-4));
+
+    let other: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let comparison = compare_meshes(&mesh, &other);
+    assert!(!comparison.same_vertex_count);
+    assert!(!comparison.same_connectivity);
+    assert_eq!(comparison.max_vertex_distance, None);
+    assert!(!comparison.is_within(1.0));
+}
@@ -0,0 +1,159 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::connectivity::{Segment2d2Connectivity, Tri6d3Connectivity};
+use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::mesh::{Mesh2d, Mesh3d, Tet10Mesh, Tet4Mesh, TriangleMesh3d};
+use fenris::nalgebra::{Matrix3, Point2, Point3, Rotation3, Vector2, Vector3};
+use fenris::quadrature;
+use fenris::space::{compute_nodal_normals, minimal_rotation, DirectorField, NormalAveraging};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+const ALL_AVERAGING: [NormalAveraging; 3] = [
+    NormalAveraging::Uniform,
+    NormalAveraging::AreaWeighted,
+    NormalAveraging::Consistent,
+];
+
+/// A regular polygon with `n` vertices on the circle of radius `r`, oriented counter-clockwise.
+fn regular_polygon(n: usize, r: f64) -> Mesh2d<f64, Segment2d2Connectivity> {
+    let vertices = (0..n)
+        .map(|i| {
+            let theta = 2.0 * PI * i as f64 / n as f64;
+            Point2::new(r * theta.cos(), r * theta.sin())
+        })
+        .collect();
+    let connectivity = (0..n)
+        .map(|i| Segment2d2Connectivity([i, (i + 1) % n]))
+        .collect();
+    Mesh2d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+/// A triangulated sphere of radius `r` centered at the origin, obtained by projecting the
+/// boundary of a tetrahedral box mesh onto the sphere.
+fn sphere(r: f64) -> TriangleMesh3d<f64> {
+    let box_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(4);
+    let mut surface = box_mesh.extract_surface_mesh();
+    for v in surface.vertices_mut() {
+        let x = v.coords - Vector3::repeat(0.5);
+        *v = Point3::from(x.normalize() * r);
+    }
+    surface
+}
+
+fn quadratic_sphere(r: f64) -> Mesh3d<f64, Tri6d3Connectivity> {
+    let box_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(4);
+    let mut surface = Tet10Mesh::from(&box_mesh)
+        .extract_boundary_mesh()
+        .into_mesh();
+    for v in surface.vertices_mut() {
+        let x = v.coords - Vector3::repeat(0.5);
+        *v = Point3::from(x.normalize() * r);
+    }
+    surface
+}
+
+#[test]
+fn nodal_normals_of_regular_polygon_are_exact() {
+    // The polygon is oriented counter-clockwise, so the normals point outwards
+    let mesh = regular_polygon(10, 2.0);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+    for averaging in ALL_AVERAGING {
+        let normals = compute_nodal_normals(&mesh, &qtable, averaging).unwrap();
+        for (x, n) in mesh.vertices().iter().zip(&normals) {
+            let expected: Vector2<f64> = x.coords.normalize();
+            assert_matrix_eq!(n, expected, comp = abs, tol = 1e-12);
+        }
+    }
+}
+
+#[test]
+fn nodal_normals_of_sphere_approximate_exact_normals() {
+    let mesh = sphere(1.5);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    for averaging in ALL_AVERAGING {
+        let normals = compute_nodal_normals(&mesh, &qtable, averaging).unwrap();
+        for (x, n) in mesh.vertices().iter().zip(&normals) {
+            assert_scalar_eq!(n.norm(), 1.0, comp = abs, tol = 1e-12);
+            // The angle between the approximate and the exact normal is small
+            assert!(n.dot(&x.coords.normalize()) > 0.99);
+        }
+    }
+
+    // The vertices of quadratic triangles have vanishing basis function integrals, so consistent
+    // averaging is not applicable
+    let mesh = quadratic_sphere(1.5);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(4).unwrap());
+    for averaging in [NormalAveraging::Uniform, NormalAveraging::AreaWeighted] {
+        let normals = compute_nodal_normals(&mesh, &qtable, averaging).unwrap();
+        for (x, n) in mesh.vertices().iter().zip(&normals) {
+            assert!(n.dot(&x.coords.normalize()) > 0.99);
+        }
+    }
+}
+
+#[test]
+fn minimal_rotation_properties() {
+    let a = Vector3::new(1.0, 2.0, -0.5).normalize();
+    let b = Vector3::new(-0.3, 0.4, 1.0).normalize();
+    let r = minimal_rotation(&a, &b).unwrap();
+    assert_matrix_eq!(r * a, b, comp = abs, tol = 1e-14);
+    assert_matrix_eq!(r.transpose() * r, Matrix3::identity(), comp = abs, tol = 1e-14);
+    assert_scalar_eq!(r.determinant(), 1.0, comp = abs, tol = 1e-14);
+    // Vectors orthogonal to both a and b are unchanged
+    let c = a.cross(&b);
+    assert_matrix_eq!(r * c, c, comp = abs, tol = 1e-14);
+
+    assert_matrix_eq!(
+        minimal_rotation(&a, &a).unwrap(),
+        Matrix3::identity(),
+        comp = abs,
+        tol = 1e-14
+    );
+    assert!(minimal_rotation(&a, &(-a)).is_none());
+}
+
+#[test]
+fn director_field_follows_surface_deformation() {
+    let mesh = sphere(1.0);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let mut field = DirectorField::from_space(&mesh, &qtable, NormalAveraging::AreaWeighted).unwrap();
+    assert_eq!(field.num_nodes(), mesh.vertices().len());
+    assert_eq!(field.directors(), field.normals());
+
+    // Tilt the directors of half the nodes away from the normals
+    let tilt = Rotation3::from_euler_angles(0.1, -0.05, 0.2).into_inner();
+    let rotations: Vec<_> = (0..field.num_nodes())
+        .map(|i| if i % 2 == 0 { tilt } else { Matrix3::identity() })
+        .collect();
+    field.rotate_directors(&rotations).unwrap();
+    let initial_field = field.clone();
+    assert!(field.rotate_directors(&rotations[1..]).is_err());
+
+    // Deform the sphere into a rotated ellipsoid
+    let q = Rotation3::from_euler_angles(0.4, 0.3, -0.2);
+    let mut deformed = mesh.clone();
+    for v in deformed.vertices_mut() {
+        *v = q * Point3::new(1.2 * v.x, 0.9 * v.y, v.z);
+    }
+    field.update_from_space(&deformed, &qtable).unwrap();
+
+    let expected_normals = compute_nodal_normals(&deformed, &qtable, NormalAveraging::AreaWeighted).unwrap();
+    assert_eq!(field.normals(), expected_normals.as_slice());
+    for i in 0..field.num_nodes() {
+        let (d0, n0) = (&initial_field.directors()[i], &initial_field.normals()[i]);
+        let (d, n) = (&field.directors()[i], &field.normals()[i]);
+        assert_scalar_eq!(d.norm(), 1.0, comp = abs, tol = 1e-12);
+        // The angle between director and normal is preserved
+        assert_scalar_eq!(d.dot(n), d0.dot(n0), comp = abs, tol = 1e-12);
+        if i % 2 == 1 {
+            assert_matrix_eq!(d, n, comp = abs, tol = 1e-12);
+        }
+    }
+
+    // The deformed space must have the same nodes as the field
+    let before_failed_update = field.clone();
+    assert!(field
+        .update_from_space(&quadratic_sphere(1.0), &qtable)
+        .is_err());
+    assert_eq!(field, before_failed_update);
+}
@@ -0,0 +1,56 @@
+use fenris::index::NodeIndex;
+use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
+use fenris::nalgebra::{point, DVector};
+use fenris::space::{DiscontinuousSpace, FiniteElementConnectivity, FiniteElementSpace};
+use matrixcompare::assert_matrix_eq;
+
+#[test]
+fn discontinuous_space_has_element_local_nodes() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let num_cells = mesh.connectivity().len();
+    let space = DiscontinuousSpace::from_space(mesh.clone());
+    assert_eq!(space.num_elements(), num_cells);
+    assert_eq!(space.num_nodes(), 3 * num_cells);
+
+    let mut nodes = [NodeIndex::default(); 3];
+    for element_index in 0..space.num_elements() {
+        assert_eq!(
+            space.element_nodes(element_index),
+            3 * element_index..3 * element_index + 3
+        );
+        space.populate_element_nodes(&mut nodes, element_index.into());
+        assert_eq!(
+            NodeIndex::as_raw_slice(&nodes),
+            space.element_nodes(element_index).collect::<Vec<_>>()
+        );
+
+        let xi = point![-0.2, 0.1];
+        assert_eq!(
+            space.map_element_reference_coords(element_index, &xi),
+            mesh.map_element_reference_coords(element_index, &xi)
+        );
+        assert_eq!(
+            space.is_element_affine(element_index),
+            mesh.is_element_affine(element_index)
+        );
+    }
+}
+
+#[test]
+fn discontinuous_space_maps_continuous_vectors() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let space = DiscontinuousSpace::from_space(mesh.clone());
+    let solution_dim = 2;
+    let u = DVector::from_fn(solution_dim * mesh.vertices().len(), |i, _| i as f64);
+    let u_dg = space.map_continuous_vector(&u, solution_dim);
+    assert_eq!(u_dg.len(), solution_dim * space.num_nodes());
+
+    for (element_index, conn) in mesh.connectivity().iter().enumerate() {
+        for (dg_node, &node) in space.element_nodes(element_index).zip(&conn.0) {
+            assert_matrix_eq!(
+                u_dg.rows(solution_dim * dg_node, solution_dim),
+                u.rows(solution_dim * node, solution_dim)
+            );
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use fenris::element::{
-    map_physical_coordinates, project_physical_coordinates, ClosestPoint, ClosestPointInElement, ElementConnectivity,
-    FiniteElement, FixedNodesReferenceFiniteElement, Hex20Element, Hex27Element, Hex8Element, Quad4d2Element,
-    Quad9d2Element, Segment2d2Element, Tet10Element, Tet20Element, Tet4Element, Tri3d2Element, Tri6d2Element,
+    assert_element_invariants, map_physical_coordinates, map_quadrature_to_element, project_physical_coordinates,
+    ArcSegment2dElement, CircularArc2d, ClosestPoint, ClosestPointInElement, CurvedQuad4d2Element, CurvedTri3d2Element,
+    ElementArchetype, ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement, Hex20Element, Hex27Element,
+    Hex8Element, LagrangeNodes, LagrangeSimplexBasis, LagrangeSimplexElement, LagrangeTetrahedronElement,
+    LagrangeTriangleElement, Quad4d2Element, Quad4d3Element, Quad9d2Element, ReferenceFiniteElement, Segment2d1Element,
+    Segment2d2Element, Segment3d2Element, SurfaceFiniteElement, Tet10Element, Tet20Element, Tet4Element, Tri3d2Element,
+    Tri3d3Element, Tri6d2Element, Tri6d3Element,
 };
 use fenris::error::estimate_element_L2_error;
 use fenris::geometry::proptest::{clockwise_triangle2d_strategy_f64, nondegenerate_convex_quad2d_strategy_f64};
@@ -13,12 +17,14 @@ use fenris::nalgebra::DVector;
 use fenris::quadrature;
 use fenris::util::proptest::point2_f64_strategy;
 use fenris_optimize::calculus::{approximate_jacobian, VectorFunctionBuilder};
+use itertools::izip;
 use matrixcompare::{assert_matrix_eq, assert_scalar_eq, prop_assert_matrix_eq};
 use nalgebra::{
-    point, DVectorView, DimName, Dyn, MatrixView, OMatrix, OPoint, Point1, Point2, Point3, Vector1, Vector2, Vector3,
-    U1, U10, U2, U20, U27, U3, U4, U6, U8, U9,
+    point, DVectorView, DimName, Dyn, Matrix2, MatrixView, MatrixViewMut, OMatrix, OPoint, Point1, Point2, Point3,
+    Vector1, Vector2, Vector3, U1, U10, U2, U20, U27, U3, U4, U6, U8, U9,
 };
 use proptest::prelude::*;
+use std::f64::consts::{FRAC_PI_2, PI, SQRT_2};
 use util::assert_approx_matrix_eq;
 
 #[test]
@@ -68,6 +74,44 @@ fn map_reference_coords_edge2d() {
         .relative_eq(&(0.25 * a.coords + 0.75 * b.coords), 1e-10, 1e-10));
 }
 
+#[test]
+fn quadratic_surface_elements_interpolate_vertices() {
+    let vertices: [Point2<f64>; 3] = [Point2::new(5.0, 3.0), Point2::new(7.0, 4.5), Point2::new(10.0, 4.0)];
+    let segment = Segment3d2Element::from_vertices(vertices);
+    for (xi, x) in [-1.0, 0.0, 1.0].into_iter().zip(&vertices) {
+        let x_mapped = segment.map_reference_coords(&Point1::new(xi));
+        assert_matrix_eq!(x_mapped.coords, x.coords, comp = abs, tol = 1e-12);
+    }
+    let n = segment.normal(&Point1::new(0.0));
+    let t = segment.reference_jacobian(&Point1::new(0.0));
+    assert_scalar_eq!(n.norm(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(n.dot(&t), 0.0, comp = abs, tol = 1e-12);
+
+    let vertices = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.5),
+        Point3::new(0.0, 2.0, 1.0),
+        Point3::new(1.0, 0.1, 0.2),
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(0.0, 1.0, 0.3),
+    ];
+    let triangle = Tri6d3Element::from_vertices(vertices);
+    let reference_vertices = *Tri6d2Element::<f64>::reference().vertices();
+    for (xi, x) in reference_vertices.iter().zip(&vertices) {
+        assert_matrix_eq!(
+            triangle.map_reference_coords(xi).coords,
+            x.coords,
+            comp = abs,
+            tol = 1e-12
+        );
+    }
+    let xi = Point2::new(-0.2, -0.3);
+    let n = triangle.normal(&xi);
+    let j = triangle.reference_jacobian(&xi);
+    assert_scalar_eq!(n.norm(), 1.0, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(j.transpose() * n, Vector2::zeros(), comp = abs, tol = 1e-12);
+}
+
 #[test]
 fn map_physical_coords_quad2d() {
     let vertices = [
@@ -202,6 +246,34 @@ fn tet20_lagrange_property() {
     }
 }
 
+#[test]
+fn simplex_elements_are_affine() {
+    assert!(Tri3d2Element::<f64>::reference().is_affine());
+    assert!(Tri6d2Element::<f64>::reference().is_affine());
+    assert!(Tet4Element::<f64>::reference().is_affine());
+    assert!(Tet10Element::<f64>::reference().is_affine());
+    assert!(Tet20Element::<f64>::reference().is_affine());
+
+    assert!(!Quad4d2Element::<f64>::reference().is_affine());
+    assert!(!Quad9d2Element::<f64>::reference().is_affine());
+    assert!(!Hex8Element::<f64>::reference().is_affine());
+    assert!(!Hex27Element::<f64>::reference().is_affine());
+
+    // The constant Jacobian of an affine element agrees with the Jacobian at any point
+    let tet = Tet10Element::from(&Tet4Element::from_vertices([
+        Point3::new(0.5, 0.1, 0.2),
+        Point3::new(2.0, 0.3, 0.1),
+        Point3::new(0.7, 1.5, 0.4),
+        Point3::new(0.2, 0.6, 1.8),
+    ]));
+    let jacobian = tet.constant_reference_jacobian().unwrap();
+    let xi = Point3::new(-0.5, -0.2, 0.1);
+    assert_approx_matrix_eq!(jacobian, tet.reference_jacobian(&xi), abstol = 1e-14);
+    assert!(Quad4d2Element::<f64>::reference()
+        .constant_reference_jacobian()
+        .is_none());
+}
+
 #[test]
 fn hex8_lagrange_property() {
     // We expect that N_i(x_j) = delta_ij
@@ -1054,3 +1126,337 @@ fn tri3d2_closest_point_boundary_points() {
         }
     }
 }
+
+#[test]
+fn all_elements_satisfy_element_invariants() {
+    assert_element_invariants::<Segment2d1Element<f64>>();
+    assert_element_invariants::<Segment2d2Element<f64>>();
+    assert_element_invariants::<Segment3d2Element<f64>>();
+    assert_element_invariants::<Tri3d2Element<f64>>();
+    assert_element_invariants::<Tri6d2Element<f64>>();
+    assert_element_invariants::<Tri3d3Element<f64>>();
+    assert_element_invariants::<Tri6d3Element<f64>>();
+    assert_element_invariants::<Quad4d2Element<f64>>();
+    assert_element_invariants::<Quad9d2Element<f64>>();
+    assert_element_invariants::<Quad4d3Element<f64>>();
+    assert_element_invariants::<Tet4Element<f64>>();
+    assert_element_invariants::<Tet10Element<f64>>();
+    assert_element_invariants::<Tet20Element<f64>>();
+    assert_element_invariants::<Hex8Element<f64>>();
+    assert_element_invariants::<Hex20Element<f64>>();
+    assert_element_invariants::<Hex27Element<f64>>();
+    assert_element_invariants::<LagrangeSimplexElement<f64, U1, 4>>();
+    assert_element_invariants::<LagrangeTriangleElement<f64, 1>>();
+    assert_element_invariants::<LagrangeTriangleElement<f64, 2>>();
+    assert_element_invariants::<LagrangeTriangleElement<f64, 5>>();
+    assert_element_invariants::<LagrangeTetrahedronElement<f64, 1>>();
+    assert_element_invariants::<LagrangeTetrahedronElement<f64, 3>>();
+    assert_element_invariants::<LagrangeTetrahedronElement<f64, 4>>();
+}
+
+/// A triangle element whose basis gradients are deliberately wrong.
+#[derive(Debug)]
+struct InconsistentGradientsTriangle(Tri3d2Element<f64>);
+
+impl ReferenceFiniteElement<f64> for InconsistentGradientsTriangle {
+    type ReferenceDim = U2;
+
+    fn num_nodes(&self) -> usize {
+        3
+    }
+
+    fn populate_basis(&self, basis_values: &mut [f64], reference_coords: &Point2<f64>) {
+        self.0.populate_basis(basis_values, reference_coords)
+    }
+
+    fn populate_basis_gradients(
+        &self,
+        mut basis_gradients: MatrixViewMut<f64, U2, Dyn>,
+        reference_coords: &Point2<f64>,
+    ) {
+        self.0
+            .populate_basis_gradients(basis_gradients.columns_mut(0, 3), reference_coords);
+        basis_gradients.swap_columns(1, 2);
+    }
+}
+
+impl FiniteElement<f64> for InconsistentGradientsTriangle {
+    type GeometryDim = U2;
+
+    fn reference_jacobian(&self, reference_coords: &Point2<f64>) -> Matrix2<f64> {
+        self.0.reference_jacobian(reference_coords)
+    }
+
+    fn map_reference_coords(&self, reference_coords: &Point2<f64>) -> Point2<f64> {
+        self.0.map_reference_coords(reference_coords)
+    }
+
+    fn diameter(&self) -> f64 {
+        self.0.diameter()
+    }
+}
+
+impl ElementArchetype<f64> for InconsistentGradientsTriangle {
+    fn reference_nodes() -> Vec<Point2<f64>> {
+        Tri3d2Element::reference_nodes()
+    }
+
+    fn archetype() -> Self {
+        Self(Tri3d2Element::archetype())
+    }
+}
+
+#[test]
+#[should_panic(expected = "finite differences")]
+fn element_invariants_detect_inconsistent_gradients() {
+    assert_element_invariants::<InconsistentGradientsTriangle>();
+}
+
+#[test]
+fn lagrange_simplex_elements_match_hand_written_elements() {
+    let xi = point![-0.3, 0.1];
+    let vertices = vec![point![1.0, 0.5], point![3.0, 1.0], point![1.5, 2.5]];
+    let linear = LagrangeTriangleElement::<f64, 1>::from_vertices(vertices.clone());
+    let tri3 = Tri3d2Element::from_vertices([vertices[0], vertices[1], vertices[2]]);
+    let mut basis = vec![0.0; 3];
+    linear.populate_basis(&mut basis, &xi);
+    assert_matrix_eq!(
+        DVector::from_vec(basis),
+        tri3.evaluate_basis(&xi).transpose(),
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_matrix_eq!(
+        linear.reference_jacobian(&xi),
+        tri3.reference_jacobian(&xi),
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_matrix_eq!(
+        linear.map_reference_coords(&xi).coords,
+        tri3.map_reference_coords(&xi).coords,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // Quadratic elements have the same basis functions as Tri6 and Tet10 up to the node ordering
+    let quadratic = LagrangeTriangleElement::<f64, 2>::from_vertices(vertices.clone());
+    let tri6 = Tri6d2Element::from(&tri3);
+    let tri6_basis = tri6.evaluate_basis(&xi);
+    let mut basis = vec![0.0; 6];
+    quadratic.populate_basis(&mut basis, &xi);
+    for (node, value) in izip!(quadratic.nodes(), basis) {
+        let tri6_node = tri6
+            .vertices()
+            .iter()
+            .position(|v| (v - node).norm() < 1e-12)
+            .unwrap();
+        assert_scalar_eq!(value, tri6_basis[tri6_node], comp = abs, tol = 1e-13);
+    }
+
+    let xi = point![-0.3, 0.1, -0.4];
+    let tet4 = Tet4Element::<f64>::reference();
+    let tet10 = Tet10Element::from(&tet4);
+    let quadratic = LagrangeTetrahedronElement::<f64, 2>::from_vertices(tet4.vertices().to_vec());
+    let tet10_basis = tet10.evaluate_basis(&xi);
+    let mut basis = vec![0.0; 10];
+    quadratic.populate_basis(&mut basis, &xi);
+    for (node, value) in izip!(quadratic.nodes(), basis) {
+        let tet10_node = tet10
+            .vertices()
+            .iter()
+            .position(|v| (v - node).norm() < 1e-12)
+            .unwrap();
+        assert_scalar_eq!(value, tet10_basis[tet10_node], comp = abs, tol = 1e-13);
+    }
+}
+
+#[test]
+fn lagrange_simplex_basis_node_counts_and_ordering() {
+    let basis = LagrangeSimplexBasis::<f64, U2, 4>::equispaced();
+    assert_eq!(basis.num_nodes(), 15);
+    let basis = LagrangeSimplexBasis::<f64, U3, 4>::equispaced();
+    assert_eq!(basis.num_nodes(), 35);
+
+    // Vertices first, then the nodes on the edge between vertex 0 and 1, from vertex 0 to 1
+    let nodes = basis.reference_nodes();
+    let reference_vertices = Tet4Element::<f64>::reference().vertices().to_vec();
+    assert_eq!(&nodes[..4], reference_vertices.as_slice());
+    assert_eq!(
+        &nodes[4..7],
+        &[
+            point![-0.5, -1.0, -1.0],
+            point![0.0, -1.0, -1.0],
+            point![0.5, -1.0, -1.0]
+        ]
+    );
+}
+
+#[test]
+fn warped_lagrange_simplex_basis_interpolates_polynomials_and_is_better_conditioned() {
+    let equispaced = LagrangeSimplexBasis::<f64, U2, 7>::equispaced();
+    let warped = LagrangeSimplexBasis::<f64, U2, 7>::warped();
+    assert_eq!(warped.nodes(), LagrangeNodes::Warped);
+
+    // Nodes on the edges are Gauss-Lobatto points
+    let (_, gll_points) = quadrature::univariate::try_gauss_lobatto::<f64>(8).unwrap();
+    for (node, gll_point) in izip!(&warped.reference_nodes()[3..9], &gll_points[1..7]) {
+        assert_scalar_eq!(node.x, gll_point.x, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(node.y, -1.0, comp = abs, tol = 1e-12);
+    }
+
+    // Interpolation of a polynomial of degree 7 is exact
+    let f = |x: &Point2<f64>| x.x.powi(4) * x.y.powi(3) - 2.0 * x.x.powi(7) + x.y;
+    let sample_points: Vec<Point2<f64>> = (0..=20)
+        .flat_map(|i| (0..=20 - i).map(move |j| point![-1.0 + 0.1 * i as f64, -1.0 + 0.1 * j as f64]))
+        .collect();
+    let mut lebesgue_constants = Vec::new();
+    for basis in [&equispaced, &warped] {
+        let weights: Vec<f64> = basis.reference_nodes().iter().map(f).collect();
+        let mut values = vec![0.0; basis.num_nodes()];
+        let mut lebesgue_constant = 0.0f64;
+        for x in &sample_points {
+            basis.populate_basis(&mut values, x);
+            let interpolated: f64 = izip!(&values, &weights).map(|(phi, w)| phi * w).sum();
+            assert_scalar_eq!(interpolated, f(x), comp = abs, tol = 1e-9);
+            lebesgue_constant = lebesgue_constant.max(values.iter().map(|phi| phi.abs()).sum());
+        }
+        lebesgue_constants.push(lebesgue_constant);
+    }
+    assert!(lebesgue_constants[1] < lebesgue_constants[0]);
+}
+
+/// Checks the reference Jacobian of an element against central finite differences.
+fn assert_jacobian_matches_finite_differences<E>(element: &E, points: &[Point2<f64>])
+where
+    E: FiniteElement<f64, GeometryDim = U2, ReferenceDim = U2>,
+{
+    let h = 1e-6;
+    for xi in points {
+        let fd = Matrix2::from_fn(|i, j| {
+            let mut dxi = Vector2::zeros();
+            dxi[j] = h;
+            let forward = element.map_reference_coords(&(xi + dxi));
+            let backward = element.map_reference_coords(&(xi - dxi));
+            (forward[i] - backward[i]) / (2.0 * h)
+        });
+        assert_matrix_eq!(element.reference_jacobian(xi), fd, comp = abs, tol = 1e-8);
+    }
+}
+
+#[test]
+fn circular_arc_from_end_points_and_center() {
+    let arc = CircularArc2d::from_end_points_and_center(point![3.0, 1.0], point![1.0, 3.0], point![1.0, 1.0]);
+    assert_scalar_eq!(arc.radius(), 2.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(arc.sweep_angle(), FRAC_PI_2, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(arc.length(), PI, comp = abs, tol = 1e-14);
+    assert_matrix_eq!(arc.start().coords, Vector2::new(3.0, 1.0), comp = abs, tol = 1e-14);
+    assert_matrix_eq!(arc.end().coords, Vector2::new(1.0, 3.0), comp = abs, tol = 1e-14);
+    let midpoint = point![1.0 + SQRT_2, 1.0 + SQRT_2];
+    assert_matrix_eq!(arc.point(0.0).coords, midpoint.coords, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(arc.diameter(), 2.0 * SQRT_2, comp = abs, tol = 1e-14);
+
+    // The shorter arc is clockwise when the end points are swapped
+    let reversed = CircularArc2d::from_end_points_and_center(point![1.0, 3.0], point![3.0, 1.0], point![1.0, 1.0]);
+    assert_scalar_eq!(reversed.sweep_angle(), -FRAC_PI_2, comp = abs, tol = 1e-14);
+    assert_matrix_eq!(reversed.point(0.0).coords, midpoint.coords, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn arc_segment_element_integrates_exactly_over_arc() {
+    let arc = CircularArc2d::from_center_and_angles(point![0.0, 0.0], 2.0, 0.0, FRAC_PI_2);
+    let element = ArcSegment2dElement::from_arc(arc);
+    assert_matrix_eq!(
+        element.vertices()[1].coords,
+        Vector2::new(0.0, 2.0),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    let (weights, points) = map_quadrature_to_element(&element, &quadrature::univariate::gauss(10));
+    let length: f64 = weights.iter().sum();
+    let first_moment: f64 = izip!(&weights, &points).map(|(w, x)| w * x.x).sum();
+    assert_scalar_eq!(length, PI, comp = abs, tol = 1e-13);
+    assert_scalar_eq!(first_moment, 4.0, comp = abs, tol = 1e-13);
+    assert!(points.iter().all(|x| (x.coords.norm() - 2.0).abs() < 1e-14));
+
+    // The normal points away from the center of a counter-clockwise arc
+    let xi = Point1::new(0.3);
+    let x = element.map_reference_coords(&xi);
+    assert_matrix_eq!(element.normal(&xi), x.coords / 2.0, comp = abs, tol = 1e-14);
+    assert!(!element.is_affine());
+}
+
+#[test]
+fn curved_quad_element_represents_annulus_sector_exactly() {
+    let vertices = [point![1.0, 0.0], point![2.0, 0.0], point![0.0, 2.0], point![0.0, 1.0]];
+    let center = point![0.0, 0.0];
+    let outer = CircularArc2d::from_end_points_and_center(vertices[1], vertices[2], center);
+    // The inner arc is given in the opposite orientation of the edge
+    let inner = CircularArc2d::from_end_points_and_center(vertices[0], vertices[3], center);
+    let element = CurvedQuad4d2Element::from_vertices(vertices)
+        .with_arc_edge(1, outer)
+        .with_arc_edge(3, inner);
+
+    for (xi, x) in izip!(Quad4d2Element::<f64>::reference().vertices(), &vertices) {
+        assert_matrix_eq!(
+            element.map_reference_coords(xi).coords,
+            x.coords,
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+    for s in [-0.7, 0.1, 0.5] {
+        assert_scalar_eq!(
+            element.map_reference_coords(&point![1.0, s]).coords.norm(),
+            2.0,
+            comp = abs,
+            tol = 1e-14
+        );
+        assert_scalar_eq!(
+            element.map_reference_coords(&point![-1.0, s]).coords.norm(),
+            1.0,
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+    assert_jacobian_matches_finite_differences(&element, &[point![0.0, 0.0], point![0.3, -0.6], point![-0.8, 0.9]]);
+
+    let (weights, points) = map_quadrature_to_element(&element, &quadrature::tensor::quadrilateral_gauss(10));
+    let area: f64 = weights.iter().sum();
+    let second_moment: f64 = izip!(&weights, &points).map(|(w, x)| w * x.x * x.x).sum();
+    assert_scalar_eq!(area, 3.0 * PI / 4.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(second_moment, 15.0 * PI / 16.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(element.diameter(), 2.0 * SQRT_2, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn curved_triangle_element_represents_quarter_disk_exactly() {
+    let vertices = [point![0.0, 0.0], point![1.0, 0.0], point![0.0, 1.0]];
+    let arc = CircularArc2d::from_center_and_angles(point![0.0, 0.0], 1.0, 0.0, FRAC_PI_2);
+    let straight = CurvedTri3d2Element::from_vertices(vertices);
+    assert!(straight.is_affine());
+    let element = straight.with_arc_edge(1, arc);
+    assert!(!element.is_affine());
+
+    for (xi, x) in izip!(Tri3d2Element::<f64>::reference().vertices(), &vertices) {
+        assert_matrix_eq!(
+            element.map_reference_coords(xi).coords,
+            x.coords,
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+    for s in [-0.7, 0.1, 0.5] {
+        // Points on the curved edge (xi + eta = 0) lie on the unit circle
+        let x = element.map_reference_coords(&point![s, -s]);
+        assert_scalar_eq!(x.coords.norm(), 1.0, comp = abs, tol = 1e-14);
+    }
+    assert_jacobian_matches_finite_differences(&element, &[point![-0.5, -0.5], point![0.2, -0.6], point![-0.9, 0.6]]);
+
+    let (weights, points) = map_quadrature_to_element(&element, &quadrature::total_order::triangle(20).unwrap());
+    let area: f64 = weights.iter().sum();
+    let second_moment: f64 = izip!(&weights, &points).map(|(w, x)| w * x.x * x.x).sum();
+    assert_scalar_eq!(area, PI / 4.0, comp = abs, tol = 1e-10);
+    assert_scalar_eq!(second_moment, PI / 16.0, comp = abs, tol = 1e-10);
+}
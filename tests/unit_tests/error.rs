@@ -1,19 +1,44 @@
-use fenris::assembly::global::gather_global_to_local;
-use fenris::assembly::local::GeneralQuadratureTable;
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, gather_global_to_local, CsrAssembler,
+    VectorAssembler,
+};
+use fenris::assembly::goal::evaluate_linear_functional;
+use fenris::assembly::local::{
+    ElementEllipticAssemblerBuilder, ElementSourceAssemblerBuilder, GeneralQuadratureTable, SourceFunction,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
 use fenris::connectivity::Connectivity;
-use fenris::element::{ElementConnectivity, Tet20Element, Tet4Element, VolumetricFiniteElement};
+use fenris::element::{
+    ElementConnectivity, FiniteElement, LagrangeNodes, Tet20Element, Tet4Element, VolumetricFiniteElement,
+};
 use fenris::error::{
-    estimate_H1_seminorm_error, estimate_L2_error, estimate_element_H1_seminorm_error,
+    assemble_nested_prolongation, estimate_H1_error, estimate_H1_seminorm_error, estimate_Hdiv_error,
+    estimate_L2_error, estimate_dwr_error_indicators, estimate_element_H1_error, estimate_element_H1_seminorm_error,
     estimate_element_H1_seminorm_error_squared, estimate_element_L2_error, estimate_element_L2_error_squared,
+    estimate_residual_error_indicators, estimate_two_grid_error_indicators, estimate_zz_error_indicators,
+    fit_algebraic_convergence, fit_exponential_convergence, mark_dorfler, par_estimate_H1_error,
+    par_estimate_H1_error_squared_chunked, par_estimate_H1_seminorm_error,
+    par_estimate_H1_seminorm_error_squared_chunked, par_estimate_Hdiv_error, par_estimate_L2_error,
+    par_estimate_L2_error_squared_chunked, recover_nodal_field, recover_nodal_fluxes, recover_nodal_gradients,
+    ConvergenceSample, LagrangeOrderSweep, PRefinementStudy, TwoGridSystem, MAX_LAGRANGE_SWEEP_ORDER,
 };
 use fenris::integrate::IntegrationWorkspace;
-use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::refinement::refine_marked_cells;
+use fenris::mesh::{Mesh, Tri6Mesh2d, TriangleMesh2d};
 use fenris::nalgebra::coordinates::XYZ;
-use fenris::nalgebra::{DVector, DVectorView, OVector, Point3, Vector1, Vector2, U3};
+use fenris::nalgebra::{DMatrix, DVector, DVectorView, OVector, Point2, Point3, Vector1, Vector2, U1, U2, U3};
+use fenris::nalgebra_sparse::CsrMatrix;
 use fenris::quadrature;
-use fenris::quadrature::{Quadrature, QuadraturePair3d};
+use fenris::quadrature::registry::{QuadratureRegistry, ReferenceShape};
+use fenris::quadrature::{Quadrature, QuadraturePair2d, QuadraturePair3d};
+use fenris::space::{FiniteElementConnectivity, LagrangeSimplexSpace};
 use fenris::util::NestedVec;
-use matrixcompare::assert_scalar_eq;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 use nalgebra::{Matrix3x2, Vector3};
 use std::ops::Deref;
 use util::flatten_vertically;
@@ -291,6 +316,186 @@ fn test_estimate_H1_seminorm_error_on_mesh() {
     );
 }
 
+#[test]
+#[allow(non_snake_case)]
+fn test_element_H1_error_vector() {
+    // The full H1 norm combines the L2 norm and the H1 seminorm
+    let element = arbitrary_tet20_element();
+    let u_h_element = flatten_vertically(&element.vertices().iter().map(u2_vector).collect::<Vec<_>>()).unwrap();
+    let (weights, points) = quadrature::total_order::tetrahedron(10).unwrap();
+    let mut workspace = IntegrationWorkspace::default();
+    let u_h_element = DVectorView::from(&u_h_element);
+
+    let H1_error = estimate_element_H1_error(
+        &element,
+        &u1_vector,
+        &u1_vector_grad,
+        u_h_element,
+        &weights,
+        &points,
+        &mut workspace,
+    );
+    let L2_error_squared =
+        estimate_element_L2_error_squared(&element, &u1_vector, u_h_element, &weights, &points, &mut workspace);
+    let H1_seminorm_error_squared = estimate_element_H1_seminorm_error_squared(
+        &element,
+        &u1_vector_grad,
+        u_h_element,
+        &weights,
+        &points,
+        &mut workspace,
+    );
+
+    assert!(H1_error > 0.0);
+    assert_scalar_eq!(
+        H1_error,
+        (L2_error_squared + H1_seminorm_error_squared).sqrt(),
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_estimate_H1_error_on_mesh() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::hexahedron_gauss(4));
+    let g = |x: &Point3<f64>| {
+        let &XYZ { x, y, z } = x.deref();
+        Vector2::new(3.0 * x + 2.0 * y * z.powi(3), 4.0 * x.powi(2) + 2.0 * y + z)
+    };
+    let u_h = flatten_vertically(&mesh.vertices().iter().map(g).collect::<Vec<_>>()).unwrap();
+
+    let L2_error = estimate_L2_error(&mesh, &u_vector, &u_h, &qtable).unwrap();
+    let H1_seminorm_error = estimate_H1_seminorm_error(&mesh, &u_vector_grad, &u_h, &qtable).unwrap();
+    let H1_error = estimate_H1_error(&mesh, &u_vector, &u_vector_grad, &u_h, &qtable).unwrap();
+    assert_scalar_eq!(
+        H1_error,
+        (L2_error.powi(2) + H1_seminorm_error.powi(2)).sqrt(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // The parallel versions must agree with the sequential versions
+    let par_L2_error = par_estimate_L2_error(&mesh, &u_vector, &u_h, &qtable).unwrap();
+    let par_H1_seminorm_error = par_estimate_H1_seminorm_error(&mesh, &u_vector_grad, &u_h, &qtable).unwrap();
+    let par_H1_error = par_estimate_H1_error(&mesh, &u_vector, &u_vector_grad, &u_h, &qtable).unwrap();
+    assert_scalar_eq!(par_L2_error, L2_error, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(par_H1_seminorm_error, H1_seminorm_error, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(par_H1_error, H1_error, comp = abs, tol = 1e-12);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_chunked_error_estimates_agree_with_parallel_estimates() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::hexahedron_gauss(4));
+    let g = |x: &Point3<f64>| {
+        let &XYZ { x, y, z } = x.deref();
+        Vector2::new(3.0 * x + 2.0 * y * z.powi(3), 4.0 * x.powi(2) + 2.0 * y + z)
+    };
+    let u_h = flatten_vertically(&mesh.vertices().iter().map(g).collect::<Vec<_>>()).unwrap();
+
+    // Store the solution as the second of two time steps in a contiguous buffer, as it might
+    // e.g. be stored in a memory-mapped file
+    let n = u_h.len();
+    let history: Vec<f64> = DVector::zeros(n)
+        .iter()
+        .chain(u_h.iter())
+        .copied()
+        .collect();
+    let u_h_view = DVectorView::from_slice(&history[n..], n);
+
+    let L2_error = par_estimate_L2_error(&mesh, &u_vector, &u_h, &qtable).unwrap();
+    let H1_seminorm_error = par_estimate_H1_seminorm_error(&mesh, &u_vector_grad, &u_h, &qtable).unwrap();
+    let H1_error = par_estimate_H1_error(&mesh, &u_vector, &u_vector_grad, &u_h, &qtable).unwrap();
+
+    let num_elements = mesh.connectivity().len();
+    for chunk_size in [1, 4, 100] {
+        let L2_error2 =
+            par_estimate_L2_error_squared_chunked(&mesh, &u_vector, u_h_view, &qtable, chunk_size, None).unwrap();
+        assert_scalar_eq!(L2_error2.sqrt(), L2_error, comp = abs, tol = 1e-12);
+
+        let mut element_errors2 = vec![0.0; num_elements];
+        let H1_seminorm_error2 = par_estimate_H1_seminorm_error_squared_chunked(
+            &mesh,
+            &u_vector_grad,
+            u_h_view,
+            &qtable,
+            chunk_size,
+            Some(&mut element_errors2),
+        )
+        .unwrap();
+        assert_scalar_eq!(H1_seminorm_error2.sqrt(), H1_seminorm_error, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(
+            element_errors2.iter().sum::<f64>(),
+            H1_seminorm_error2,
+            comp = abs,
+            tol = 1e-12
+        );
+        for (i, conn) in mesh.connectivity().iter().enumerate() {
+            let element = conn.element(mesh.vertices()).unwrap();
+            let u_element = DVector::from_iterator(
+                2 * conn.vertex_indices().len(),
+                conn.vertex_indices()
+                    .iter()
+                    .flat_map(|&v| [u_h[2 * v], u_h[2 * v + 1]]),
+            );
+            let (weights, points) = quadrature::tensor::hexahedron_gauss(4);
+            let expected = estimate_element_H1_seminorm_error_squared(
+                &element,
+                &u_vector_grad,
+                DVectorView::from(&u_element),
+                &weights,
+                &points,
+                &mut IntegrationWorkspace::default(),
+            );
+            assert_scalar_eq!(element_errors2[i], expected, comp = abs, tol = 1e-12);
+        }
+
+        let H1_error2 = par_estimate_H1_error_squared_chunked(
+            &mesh,
+            &u_vector,
+            &u_vector_grad,
+            u_h_view,
+            &qtable,
+            chunk_size,
+            None,
+        )
+        .unwrap();
+        assert_scalar_eq!(H1_error2.sqrt(), H1_error, comp = abs, tol = 1e-12);
+    }
+
+    // The per-element output must have one entry per element
+    let mut element_errors2 = vec![0.0; num_elements - 1];
+    assert!(
+        par_estimate_L2_error_squared_chunked(&mesh, &u_vector, u_h_view, &qtable, 4, Some(&mut element_errors2))
+            .is_err()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_estimate_Hdiv_error_on_mesh() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(4).unwrap());
+    let u = |x: &Point2<f64>| Vector2::new(x.x, x.y);
+    let u_div = |_: &Point2<f64>| 2.0;
+
+    // For u_h = 0, the squared error is the integral of x^2 + y^2 + 2^2 over the unit square
+    let u_h = DVector::zeros(2 * mesh.vertices().len());
+    let expected = (2.0 / 3.0 + 4.0f64).sqrt();
+    let error = estimate_Hdiv_error(&mesh, &u, &u_div, &u_h, &qtable).unwrap();
+    let par_error = par_estimate_Hdiv_error(&mesh, &u, &u_div, &u_h, &qtable).unwrap();
+    assert_scalar_eq!(error, expected, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(par_error, expected, comp = abs, tol = 1e-12);
+
+    // Linear fields are represented exactly
+    let u_h = flatten_vertically(&mesh.vertices().iter().map(u).collect::<Vec<_>>()).unwrap();
+    let error = estimate_Hdiv_error(&mesh, &u, &u_div, &u_h, &qtable).unwrap();
+    assert_scalar_eq!(error, 0.0, comp = abs, tol = 1e-12);
+}
+
 /// An arbitrary multi-variate scalar function used in tests.
 fn u1_scalar(x: &Point3<f64>) -> f64 {
     let &XYZ { x, y, z } = x.deref();
@@ -414,3 +619,555 @@ fn u2_vector_grad(x: &Point3<f64>) -> Matrix3x2<f64> {
 fn u_vector_grad(x: &Point3<f64>) -> Matrix3x2<f64> {
     u1_vector_grad(x) - u2_vector_grad(x)
 }
+
+struct SineSource;
+
+impl Operator<f64, U2> for SineSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for SineSource {
+    fn evaluate(&self, x: &Point2<f64>, _data: &()) -> Vector1<f64> {
+        let pi = std::f64::consts::PI;
+        Vector1::new(2.0 * pi * pi * (pi * x.x).sin() * (pi * x.y).sin())
+    }
+}
+
+/// Assembles the Poisson system with homogeneous Dirichlet boundary conditions.
+fn assemble_poisson_system<C>(
+    mesh: &Mesh<f64, U2, C>,
+    quadrature: QuadraturePair2d<f64>,
+) -> (CsrMatrix<f64>, DVector<f64>)
+where
+    C: ElementConnectivity<f64, GeometryDim = U2, ReferenceDim = U2>,
+{
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature);
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let source_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&SineSource)
+        .build();
+    let mut a = CsrAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+    let mut b = VectorAssembler::default()
+        .assemble_vector(&source_assembler)
+        .unwrap();
+    let boundary_nodes = mesh.find_boundary_vertices();
+    apply_homogeneous_dirichlet_bc_csr(&mut a, &boundary_nodes, 1);
+    apply_homogeneous_dirichlet_bc_rhs(&mut b, &boundary_nodes, 1);
+    (a, b)
+}
+
+fn solve_dense(a: &CsrMatrix<f64>, b: &DVector<f64>) -> DVector<f64> {
+    DMatrix::from(a).lu().solve(b).unwrap()
+}
+
+#[test]
+fn nested_prolongation_reproduces_linear_field() {
+    let coarse: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let fine = Tri6Mesh2d::from(coarse.clone());
+    let identity: Vec<_> = (0..coarse.connectivity().len()).collect();
+    let prolongation = assemble_nested_prolongation(&coarse, &fine, &identity, 2).unwrap();
+
+    let field = |x: &Point2<f64>| Vector2::new(1.0 + 2.0 * x.x - x.y, 3.0 * x.y);
+    let flatten = |vertices: &[Point2<f64>]| {
+        DVector::from_iterator(2 * vertices.len(), vertices.iter().flat_map(|x| field(x).data.0[0]))
+    };
+    let u_coarse = flatten(coarse.vertices());
+    let u_fine = flatten(fine.vertices());
+    assert_matrix_eq!(&prolongation * &u_coarse, u_fine, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn two_grid_error_indicators_for_poisson() {
+    let coarse: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let fine = Tri6Mesh2d::from(coarse.clone());
+    let identity: Vec<_> = (0..coarse.connectivity().len()).collect();
+    let (a_coarse, b_coarse) = assemble_poisson_system(&coarse, quadrature::total_order::triangle(4).unwrap());
+    let (a_fine, b_fine) = assemble_poisson_system(&fine, quadrature::total_order::triangle(4).unwrap());
+    let u_coarse = solve_dense(&a_coarse, &b_coarse);
+    let prolongation = assemble_nested_prolongation(&coarse, &fine, &identity, 1).unwrap();
+
+    let indicators = estimate_two_grid_error_indicators(
+        &coarse,
+        &fine,
+        &identity,
+        1,
+        TwoGridSystem {
+            fine_matrix: &a_fine,
+            fine_rhs: (&b_fine).into(),
+            prolongation: &prolongation,
+        },
+        (&u_coarse).into(),
+    )
+    .unwrap();
+    assert_eq!(indicators.len(), coarse.connectivity().len());
+    assert!(indicators.iter().all(|&eta| eta >= 0.0));
+
+    // The estimate should be comparable to the true energy error
+    let pi = std::f64::consts::PI;
+    let u_grad = |x: &Point2<f64>| {
+        Vector2::new(
+            pi * (pi * x.x).cos() * (pi * x.y).sin(),
+            pi * (pi * x.x).sin() * (pi * x.y).cos(),
+        )
+    };
+    let error_qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(6).unwrap());
+    let true_error = estimate_H1_seminorm_error(&coarse, &u_grad, &u_coarse, &error_qtable).unwrap();
+    let estimate = indicators.iter().map(|eta| eta * eta).sum::<f64>().sqrt();
+    let effectivity = estimate / true_error;
+    assert!(effectivity > 0.5 && effectivity < 3.0, "effectivity: {}", effectivity);
+
+    // Solving the fine problem exactly leaves no defect, so all indicators must vanish
+    let u_fine = solve_dense(&a_fine, &b_fine);
+    let fine_identity: Vec<_> = (0..fine.connectivity().len()).collect();
+    let fine_prolongation = CsrMatrix::identity(fine.vertices().len());
+    let indicators = estimate_two_grid_error_indicators(
+        &fine,
+        &fine,
+        &fine_identity,
+        1,
+        TwoGridSystem {
+            fine_matrix: &a_fine,
+            fine_rhs: (&b_fine).into(),
+            prolongation: &fine_prolongation,
+        },
+        (&u_fine).into(),
+    )
+    .unwrap();
+    assert!(indicators.iter().all(|&eta| eta < 1e-10));
+}
+
+struct ZeroSource;
+
+impl Operator<f64, U2> for ZeroSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for ZeroSource {
+    fn evaluate(&self, _x: &Point2<f64>, _data: &()) -> Vector1<f64> {
+        Vector1::zeros()
+    }
+}
+
+#[test]
+fn residual_error_indicators_vanish_for_linear_solution() {
+    let u_linear = |x: &Point2<f64>| 1.0 + 2.0 * x.x - 3.0 * x.y;
+    let tri_mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let tri6_mesh = Tri6Mesh2d::from(tri_mesh.clone());
+    let face_quadrature = quadrature::univariate::gauss(2);
+
+    let u = DVector::from_iterator(tri_mesh.vertices().len(), tri_mesh.vertices().iter().map(u_linear));
+    let indicators = estimate_residual_error_indicators(
+        &tri_mesh,
+        &LaplaceOperator,
+        &(),
+        &ZeroSource,
+        (&u).into(),
+        &quadrature::total_order::triangle(2).unwrap(),
+        &face_quadrature,
+    )
+    .unwrap();
+    assert_eq!(indicators.len(), tri_mesh.connectivity().len());
+    assert!(indicators.iter().all(|&eta| eta < 1e-12));
+
+    let u = DVector::from_iterator(quad_mesh.vertices().len(), quad_mesh.vertices().iter().map(u_linear));
+    let indicators = estimate_residual_error_indicators(
+        &quad_mesh,
+        &LaplaceOperator,
+        &(),
+        &ZeroSource,
+        (&u).into(),
+        &quadrature::tensor::quadrilateral_gauss(2),
+        &face_quadrature,
+    )
+    .unwrap();
+    assert!(indicators.iter().all(|&eta| eta < 1e-12));
+
+    // The divergence of the flux of quadratic elements is computed with finite differences
+    let u = DVector::from_iterator(tri6_mesh.vertices().len(), tri6_mesh.vertices().iter().map(u_linear));
+    let indicators = estimate_residual_error_indicators(
+        &tri6_mesh,
+        &LaplaceOperator,
+        &(),
+        &ZeroSource,
+        (&u).into(),
+        &quadrature::total_order::triangle(2).unwrap(),
+        &face_quadrature,
+    )
+    .unwrap();
+    assert!(indicators.iter().all(|&eta| eta < 1e-8));
+}
+
+#[test]
+fn residual_error_indicators_for_poisson() {
+    let pi = std::f64::consts::PI;
+    let u_grad = |x: &Point2<f64>| {
+        Vector2::new(
+            pi * (pi * x.x).cos() * (pi * x.y).sin(),
+            pi * (pi * x.x).sin() * (pi * x.y).cos(),
+        )
+    };
+    let error_qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(6).unwrap());
+
+    let mut estimates = Vec::new();
+    for resolution in [4, 8] {
+        let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(resolution);
+        let (a, b) = assemble_poisson_system(&mesh, quadrature::total_order::triangle(4).unwrap());
+        let u = solve_dense(&a, &b);
+        let indicators = estimate_residual_error_indicators(
+            &mesh,
+            &LaplaceOperator,
+            &(),
+            &SineSource,
+            (&u).into(),
+            &quadrature::total_order::triangle(4).unwrap(),
+            &quadrature::univariate::gauss(2),
+        )
+        .unwrap();
+        assert_eq!(indicators.len(), mesh.connectivity().len());
+        assert!(indicators.iter().all(|&eta| eta > 0.0));
+
+        // The estimate is an upper bound of the true energy error up to a moderate constant
+        let true_error = estimate_H1_seminorm_error(&mesh, &u_grad, &u, &error_qtable).unwrap();
+        let estimate = indicators.iter().map(|eta| eta * eta).sum::<f64>().sqrt();
+        let effectivity = estimate / true_error;
+        assert!(effectivity > 1.0 && effectivity < 10.0, "effectivity: {}", effectivity);
+        estimates.push(estimate);
+    }
+
+    // The estimate converges with the same first order rate as the energy error
+    let rate = (estimates[0] / estimates[1]).log2();
+    assert!((rate - 1.0).abs() < 0.2, "rate: {}", rate);
+}
+
+#[test]
+fn dorfler_marking_selects_largest_indicators() {
+    let indicators = [0.1, 3.0, 0.5, 2.0, 0.0, 1.0];
+    // Squared indicators sum to 14.26, of which 9 belong to the largest indicator
+    assert_eq!(mark_dorfler(&indicators, 0.0), Vec::<usize>::new());
+    assert_eq!(mark_dorfler(&indicators, 0.5), vec![1]);
+    assert_eq!(mark_dorfler(&indicators, 0.7), vec![1, 3]);
+    assert_eq!(mark_dorfler(&indicators, 0.95), vec![1, 3, 5]);
+    assert_eq!(mark_dorfler(&indicators, 1.0), vec![1, 3, 5, 2, 0]);
+    assert_eq!(mark_dorfler(&[0.0, 0.0], 1.0), Vec::<usize>::new());
+}
+
+#[test]
+fn residual_error_indicators_drive_local_refinement() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let (a, b) = assemble_poisson_system(&mesh, quadrature::total_order::triangle(4).unwrap());
+    let u = solve_dense(&a, &b);
+    let indicators = estimate_residual_error_indicators(
+        &mesh,
+        &LaplaceOperator,
+        &(),
+        &SineSource,
+        (&u).into(),
+        &quadrature::total_order::triangle(4).unwrap(),
+        &quadrature::univariate::gauss(2),
+    )
+    .unwrap();
+
+    let marked = mark_dorfler(&indicators, 0.3);
+    assert!(!marked.is_empty() && marked.len() < mesh.connectivity().len());
+    let refinement = refine_marked_cells(&mesh, &marked).unwrap();
+    assert!(refinement.mesh().connectivity().len() > mesh.connectivity().len());
+    for &cell in &marked {
+        assert!(refinement.children(cell).len() >= 2);
+    }
+}
+
+#[test]
+fn patch_recovery_reproduces_linear_fields() {
+    let field = |x: &Point2<f64>| Vector2::new(1.0 + 2.0 * x.x - x.y, 3.0 * x.y - 0.5 * x.x);
+    let tri_mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let tri_qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(1).unwrap());
+    let quad_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    // With one point per triangle, the patches of corner nodes must be enlarged
+    let recovered = recover_nodal_field(&tri_mesh, &tri_qtable, 2, |element_index, xi| {
+        let element = tri_mesh.connectivity()[element_index]
+            .element(tri_mesh.vertices())
+            .unwrap();
+        Ok(DVector::from_column_slice(
+            field(&element.map_reference_coords(xi)).as_slice(),
+        ))
+    })
+    .unwrap();
+    let expected = flatten_vertically(&tri_mesh.vertices().iter().map(field).collect::<Vec<_>>()).unwrap();
+    assert_matrix_eq!(recovered, expected, comp = abs, tol = 1e-12);
+
+    // The gradient of a linear solution is recovered exactly, so that the indicators vanish
+    let u_linear = |x: &Point2<f64>| Vector2::new(2.0 * x.x - x.y, 0.5 + x.x + 3.0 * x.y);
+    let gradient = [2.0, -1.0, 1.0, 3.0];
+    for (recovered, indicators, num_vertices) in [
+        {
+            let u = flatten_vertically(&tri_mesh.vertices().iter().map(u_linear).collect::<Vec<_>>()).unwrap();
+            (
+                recover_nodal_gradients(&tri_mesh, &tri_qtable, &u, 2).unwrap(),
+                estimate_zz_error_indicators(&tri_mesh, &tri_qtable, &u, 2).unwrap(),
+                tri_mesh.vertices().len(),
+            )
+        },
+        {
+            let u = flatten_vertically(
+                &quad_mesh
+                    .vertices()
+                    .iter()
+                    .map(u_linear)
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+            (
+                recover_nodal_gradients(&quad_mesh, &quad_qtable, &u, 2).unwrap(),
+                estimate_zz_error_indicators(&quad_mesh, &quad_qtable, &u, 2).unwrap(),
+                quad_mesh.vertices().len(),
+            )
+        },
+    ] {
+        let expected = DVector::from_iterator(
+            4 * num_vertices,
+            gradient.iter().copied().cycle().take(4 * num_vertices),
+        );
+        assert_matrix_eq!(recovered, expected, comp = abs, tol = 1e-12);
+        assert!(indicators.iter().all(|&eta| eta < 1e-12));
+    }
+}
+
+#[test]
+fn zz_error_indicators_for_poisson() {
+    let pi = std::f64::consts::PI;
+    let u_grad = |x: &Point2<f64>| {
+        Vector2::new(
+            pi * (pi * x.x).cos() * (pi * x.y).sin(),
+            pi * (pi * x.x).sin() * (pi * x.y).cos(),
+        )
+    };
+    let error_qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(6).unwrap());
+    // The quadrature must be exact for the quadratic integrand of the indicators
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(8);
+    let (a, b) = assemble_poisson_system(&mesh, quadrature::total_order::triangle(4).unwrap());
+    let u = solve_dense(&a, &b);
+    let indicators = estimate_zz_error_indicators(&mesh, &qtable, &u, 1).unwrap();
+    assert_eq!(indicators.len(), mesh.connectivity().len());
+
+    // The ZZ estimate is asymptotically exact for smooth solutions on uniform meshes
+    let true_error = estimate_H1_seminorm_error(&mesh, &u_grad, &u, &error_qtable).unwrap();
+    let estimate = indicators.iter().map(|eta| eta * eta).sum::<f64>().sqrt();
+    let effectivity = estimate / true_error;
+    assert!(effectivity > 0.9 && effectivity < 1.1, "effectivity: {}", effectivity);
+
+    // For the Laplace operator, the flux is the gradient
+    let gradients = recover_nodal_gradients(&mesh, &qtable, &u, 1).unwrap();
+    let fluxes = recover_nodal_fluxes(&mesh, &qtable, &LaplaceOperator, &(), &u).unwrap();
+    assert_matrix_eq!(gradients, fluxes, comp = abs, tol = 1e-14);
+
+    // The interpolated recovered gradient is more accurate than the discrete gradient
+    let recovered_error = estimate_L2_error(&mesh, &u_grad, &gradients, &error_qtable).unwrap();
+    assert!(
+        recovered_error < 0.5 * true_error,
+        "recovered error: {}, discrete error: {}",
+        recovered_error,
+        true_error,
+    );
+}
+
+struct UnitSource;
+
+impl Operator<f64, U2> for UnitSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for UnitSource {
+    fn evaluate(&self, _x: &Point2<f64>, _data: &()) -> Vector1<f64> {
+        Vector1::new(1.0)
+    }
+}
+
+#[test]
+fn dwr_error_indicators_for_mean_value_goal() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(8);
+    let dual_mesh = Tri6Mesh2d::from(mesh.clone());
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(4).unwrap());
+    let (a, b) = assemble_poisson_system(&mesh, quadrature::total_order::triangle(4).unwrap());
+    let u = solve_dense(&a, &b);
+
+    // The goal J(u) = int u dx is represented by the unit source on either mesh
+    let goal = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+    let dual_goal = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&dual_mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&UnitSource)
+        .build();
+    let (a_dual, _) = assemble_poisson_system(&dual_mesh, quadrature::total_order::triangle(4).unwrap());
+    let mut j_dual = VectorAssembler::default()
+        .assemble_vector(&dual_goal)
+        .unwrap();
+    apply_homogeneous_dirichlet_bc_rhs(&mut j_dual, &dual_mesh.find_boundary_vertices(), 1);
+    // The Laplace operator is symmetric, so the dual matrix coincides with the primal matrix
+    let z = solve_dense(&a_dual, &j_dual);
+
+    let contributions = estimate_dwr_error_indicators(
+        &mesh,
+        &LaplaceOperator,
+        &(),
+        &SineSource,
+        (&u).into(),
+        &dual_mesh,
+        (&z).into(),
+        &quadrature::total_order::triangle(4).unwrap(),
+        &quadrature::univariate::gauss(3),
+    )
+    .unwrap();
+    assert_eq!(contributions.len(), mesh.connectivity().len());
+
+    let pi = std::f64::consts::PI;
+    let true_error = 4.0 / (pi * pi) - evaluate_linear_functional(&goal, &u).unwrap();
+    let estimate: f64 = contributions.iter().sum();
+    let effectivity = estimate / true_error;
+    assert!(effectivity > 0.95 && effectivity < 1.05, "effectivity: {}", effectivity);
+
+    // The dual weight vanishes when the dual solution lies in the primal space
+    let contributions = estimate_dwr_error_indicators(
+        &mesh,
+        &LaplaceOperator,
+        &(),
+        &SineSource,
+        (&u).into(),
+        &mesh,
+        (&u).into(),
+        &quadrature::total_order::triangle(4).unwrap(),
+        &quadrature::univariate::gauss(3),
+    )
+    .unwrap();
+    assert!(contributions.iter().all(|&eta| eta.abs() < 1e-12));
+}
+
+#[test]
+fn lagrange_simplex_space_shares_nodes_between_elements() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(2);
+    let num_vertices = mesh.vertices().len();
+    let num_edges = 16;
+    let num_triangles = mesh.connectivity().len();
+
+    let space = LagrangeSimplexSpace::<f64, U2, 4>::from_triangle_mesh(&mesh, LagrangeNodes::Warped);
+    // Each edge has 3 interior nodes, and each triangle has 3 interior nodes
+    assert_eq!(space.num_nodes(), num_vertices + 3 * num_edges + 3 * num_triangles);
+    assert_eq!(space.num_elements(), num_triangles);
+    assert_eq!(&space.nodes()[..num_vertices], mesh.vertices());
+
+    // The space is continuous, so interpolating a polynomial of the same order is exact
+    let f = |x: &Point2<f64>| x.x.powi(4) - 2.0 * x.x * x.y.powi(3) + x.y;
+    let u = DVector::from_iterator(space.num_nodes(), space.nodes().iter().map(f));
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(10).unwrap());
+    let u_exact = |x: &Point2<f64>| Vector1::new(f(x));
+    let error = estimate_L2_error(&space, &u_exact, &u, &qtable).unwrap();
+    assert!(error < 1e-12, "error: {}", error);
+}
+
+/// Solves the Poisson problem with the sine solution on a fixed mesh.
+struct PoissonOrderSweep {
+    mesh: TriangleMesh2d<f64>,
+}
+
+impl LagrangeOrderSweep<f64> for PoissonOrderSweep {
+    fn solve<const ORDER: usize>(&mut self) -> eyre::Result<ConvergenceSample<f64>> {
+        let space = LagrangeSimplexSpace::<f64, U2, ORDER>::from_triangle_mesh(&self.mesh, LagrangeNodes::Warped);
+        let rule = QuadratureRegistry::new().rule_for_shape(ReferenceShape::Triangle, 2 * ORDER + 2)?;
+        let qtable = UniformQuadratureTable::from_quadrature(rule);
+        let u = DVector::zeros(space.num_nodes());
+        let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&space)
+            .with_operator(&LaplaceOperator)
+            .with_quadrature_table(&qtable)
+            .with_u(&u)
+            .build();
+        let source_assembler = ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(&space)
+            .with_quadrature_table(&qtable)
+            .with_source(&SineSource)
+            .build();
+        let mut a = CsrAssembler::default().assemble(&laplace_assembler)?;
+        let mut b = VectorAssembler::default().assemble_vector(&source_assembler)?;
+        let on_boundary = |x: f64| x.abs() < 1e-12 || (x - 1.0).abs() < 1e-12;
+        let boundary_nodes: Vec<_> = (0..space.num_nodes())
+            .filter(|&i| on_boundary(space.nodes()[i].x) || on_boundary(space.nodes()[i].y))
+            .collect();
+        apply_homogeneous_dirichlet_bc_csr(&mut a, &boundary_nodes, 1);
+        apply_homogeneous_dirichlet_bc_rhs(&mut b, &boundary_nodes, 1);
+        let u_h = solve_dense(&a, &b);
+
+        let pi = std::f64::consts::PI;
+        let u_exact = |x: &Point2<f64>| Vector1::new((pi * x.x).sin() * (pi * x.y).sin());
+        let error = estimate_L2_error(&space, &u_exact, &u_h, &qtable)?;
+        Ok(ConvergenceSample {
+            num_dofs: space.num_nodes(),
+            error,
+        })
+    }
+}
+
+#[test]
+fn p_refinement_study_converges_exponentially_for_poisson() {
+    let mut sweep = PoissonOrderSweep {
+        mesh: create_unit_square_uniform_tri_mesh_2d(2),
+    };
+    let study = PRefinementStudy::run_lagrange(1..=6, &mut sweep).unwrap();
+    assert_eq!(study.orders(), &[1, 2, 3, 4, 5, 6]);
+    let errors = study.errors();
+    assert!(errors.windows(2).all(|e| e[1] < e[0]), "errors: {:?}", errors);
+    assert!(errors[5] < 1e-5, "errors: {:?}", errors);
+
+    // Exponential convergence: the rate does not deteriorate with increasing order
+    let rates = study.observed_rates();
+    assert_eq!(rates.len(), 5);
+    assert!(rates.iter().all(|&r| r > 1.0), "rates: {:?}", rates);
+    let fit = study.fit_in_order().unwrap();
+    assert!(fit.rate() > 1.5, "fit: {:?}", fit);
+    assert!(study.fit_in_dofs(2).unwrap().rate() > 0.0);
+
+    // Orders are limited by the compile-time dispatch
+    assert!(PRefinementStudy::run_lagrange([0], &mut sweep).is_err());
+    assert!(PRefinementStudy::run_lagrange([MAX_LAGRANGE_SWEEP_ORDER + 1], &mut sweep).is_err());
+}
+
+#[test]
+fn convergence_fits_recover_synthetic_rates() {
+    let orders = [1.0, 2.0, 3.0, 4.0];
+    let errors = orders.map(|p: f64| 3.0 * (-1.5 * p).exp());
+    let fit = fit_exponential_convergence(&orders, &errors).unwrap();
+    assert_scalar_eq!(fit.rate(), 1.5, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(fit.coefficient(), 3.0, comp = abs, tol = 1e-12);
+
+    let resolutions = [0.5, 0.25, 0.125];
+    let errors = resolutions.map(|h: f64| 0.5 * h.powi(2));
+    let fit = fit_algebraic_convergence(&resolutions, &errors).unwrap();
+    assert_scalar_eq!(fit.rate(), 2.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(fit.coefficient(), 0.5, comp = abs, tol = 1e-12);
+
+    assert!(fit_exponential_convergence(&[1.0], &[1.0]).is_err());
+    assert!(fit_exponential_convergence(&[1.0, 2.0], &[1.0]).is_err());
+    assert!(fit_exponential_convergence(&[1.0, 2.0], &[1.0, 0.0]).is_err());
+    assert!(fit_exponential_convergence(&[1.0, 1.0], &[1.0, 0.5]).is_err());
+    assert!(fit_algebraic_convergence(&[0.0, 1.0], &[1.0, 0.5]).is_err());
+}
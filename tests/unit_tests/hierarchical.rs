@@ -0,0 +1,275 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    Density, ElementMassAssembler, ElementSourceAssemblerBuilder, SourceFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::Operator;
+use fenris::element::{
+    map_physical_coordinates, FiniteElement, HierarchicalBasis, HierarchicalElement, Quad4d2Element,
+    ReferenceFiniteElement,
+};
+use fenris::index::NodeIndex;
+use fenris::linear_solver::{ConjugateGradientSolver, LinearSolver};
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::nalgebra::{point, DVector, DefaultAllocator, DimMin, Dyn, OMatrix, OPoint, Point2, Vector1, U1, U2, U3};
+use fenris::quadrature;
+use fenris::space::{FiniteElementConnectivity, FiniteElementSpace, HierarchicalSpace};
+use fenris::SmallDim;
+use itertools::Itertools;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn evaluate<D>(space: &HierarchicalSpace<f64, D>, element: usize, xi: &OPoint<f64, D>, u: &DVector<f64>) -> f64
+where
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let n = space.element_node_count(element.into());
+    let mut nodes = vec![0; n];
+    let mut basis = vec![0.0; n];
+    space.populate_element_nodes(NodeIndex::from_raw_slice_mut(&mut nodes), element.into());
+    space.populate_element_basis(element, &mut basis, xi);
+    nodes.iter().zip(&basis).map(|(&i, phi)| u[i] * phi).sum()
+}
+
+fn pseudo_random_weights(n: usize) -> DVector<f64> {
+    DVector::from_fn(n, |i, _| ((i as f64 + 1.0) * 1.618).sin())
+}
+
+/// Checks that the function is the same on both sides of all shared entities, sampling
+/// each element at points on the boundary of the reference element.
+fn assert_continuous<D>(space: &HierarchicalSpace<f64, D>, cells: &[Vec<usize>], u: &DVector<f64>)
+where
+    D: SmallDim + DimMin<D, Output = D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let d = D::dim();
+    let samples = [-1.0, -0.7, -0.2, 0.1, 0.55, 1.0];
+    let mut num_checked = 0;
+    for (a, b) in (0..cells.len()).tuple_combinations() {
+        let shared = cells[a].iter().filter(|v| cells[b].contains(v)).count();
+        if shared < 2 {
+            continue;
+        }
+        for xi in (0..d)
+            .map(|_| samples.iter().copied())
+            .multi_cartesian_product()
+        {
+            // Only points on the boundary of the reference element
+            if !xi.iter().any(|x: &f64| x.abs() == 1.0) {
+                continue;
+            }
+            let xi = OPoint::<f64, D>::from_slice(&xi);
+            let x = space.element(a).map_reference_coords(&xi);
+            let xi_b = map_physical_coordinates(space.element(b), &x).unwrap();
+            let x_b = space.element(b).map_reference_coords(&xi_b);
+            let in_b = xi_b.iter().all(|x| x.abs() <= 1.0 + 1e-10) && (x_b - &x).norm() < 1e-10;
+            if in_b {
+                assert_scalar_eq!(
+                    evaluate(space, a, &xi, u),
+                    evaluate(space, b, &xi_b, u),
+                    comp = abs,
+                    tol = 1e-9
+                );
+                num_checked += 1;
+            }
+        }
+    }
+    assert!(num_checked > 0);
+}
+
+#[test]
+fn hierarchical_basis_has_correct_modes_and_gradients() {
+    for order in 1..=5 {
+        let basis2 = HierarchicalBasis::<f64, U2>::uniform(order);
+        assert_eq!(basis2.num_nodes(), (order + 1).pow(2));
+        let basis3 = HierarchicalBasis::<f64, U3>::uniform(order);
+        assert_eq!(basis3.num_nodes(), (order + 1).pow(3));
+    }
+
+    // The vertex modes are the bilinear basis functions
+    let basis = HierarchicalBasis::<f64, U2>::uniform(4);
+    let quad = Quad4d2Element::reference();
+    let xi = point![0.3, -0.6];
+    let mut values = vec![0.0; basis.num_nodes()];
+    basis.populate_basis(&mut values, &xi);
+    let mut bilinear = vec![0.0; 4];
+    quad.populate_basis(&mut bilinear, &xi);
+    assert_matrix_eq!(
+        DVector::from_column_slice(&values[..4]),
+        DVector::from_vec(bilinear),
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // Non-vertex modes vanish at the vertices
+    for v in [
+        point![-1.0, -1.0],
+        point![1.0, -1.0],
+        point![1.0, 1.0],
+        point![-1.0, 1.0],
+    ] {
+        basis.populate_basis(&mut values, &v);
+        assert!(values[4..].iter().all(|phi: &f64| phi.abs() < 1e-14));
+    }
+
+    // Gradients match finite differences
+    let basis = HierarchicalBasis::<f64, U3>::uniform(4);
+    let xi = point![0.3, -0.6, 0.45];
+    let n = basis.num_nodes();
+    let mut gradients = OMatrix::<f64, U3, Dyn>::zeros(n);
+    basis.populate_basis_gradients(gradients.as_view_mut(), &xi);
+    let h = 1e-6;
+    for d in 0..3 {
+        let (mut plus, mut minus) = (vec![0.0; n], vec![0.0; n]);
+        let mut xi_plus = xi;
+        xi_plus[d] += h;
+        let mut xi_minus = xi;
+        xi_minus[d] -= h;
+        basis.populate_basis(&mut plus, &xi_plus);
+        basis.populate_basis(&mut minus, &xi_minus);
+        for i in 0..n {
+            assert_scalar_eq!(
+                gradients[(d, i)],
+                (plus[i] - minus[i]) / (2.0 * h),
+                comp = abs,
+                tol = 1e-6
+            );
+        }
+    }
+}
+
+#[test]
+fn hierarchical_basis_is_nested() {
+    let low = HierarchicalBasis::<f64, U3>::uniform(2);
+    let high = HierarchicalBasis::<f64, U3>::uniform(4);
+    let xi = point![0.1, 0.7, -0.35];
+    let (mut low_values, mut high_values) = (vec![0.0; low.num_nodes()], vec![0.0; high.num_nodes()]);
+    low.populate_basis(&mut low_values, &xi);
+    high.populate_basis(&mut high_values, &xi);
+    for (mode, value) in low.modes().iter().zip(&low_values) {
+        let index = high.modes().iter().position(|m| m == mode).unwrap();
+        assert_eq!(high_values[index], *value);
+    }
+
+    let element = HierarchicalElement::from_vertices(
+        vec![point![0.0, 0.0], point![2.0, 0.0], point![2.5, 1.0], point![0.0, 1.0]],
+        3,
+    );
+    assert_matrix_eq!(
+        element.map_reference_coords(&point![1.0, 1.0]).coords,
+        point![2.5, 1.0].coords
+    );
+    assert_scalar_eq!(
+        element.diameter(),
+        (2.5f64.powi(2) + 1.0).sqrt(),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+#[test]
+fn hierarchical_space_is_continuous_with_mixed_orders_2d() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    // Distort the mesh so that the elements are not parallelograms
+    mesh.vertices_mut()[5].x += 0.05;
+    mesh.vertices_mut()[6].y -= 0.04;
+    let orders = vec![1, 2, 3, 4, 5, 2, 3, 1, 4];
+    let space = HierarchicalSpace::from_quad_mesh(&mesh, orders);
+    let cells: Vec<Vec<usize>> = mesh.connectivity().iter().map(|c| c.0.to_vec()).collect();
+    let u = pseudo_random_weights(space.num_nodes());
+    assert_continuous(&space, &cells, &u);
+}
+
+#[test]
+fn hierarchical_space_is_continuous_with_mixed_orders_3d() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    let orders = vec![1, 2, 3, 4, 3, 2, 4, 3];
+    let space = HierarchicalSpace::from_hex_mesh(&mesh, orders);
+    let cells: Vec<Vec<usize>> = mesh.connectivity().iter().map(|c| c.0.to_vec()).collect();
+    let u = pseudo_random_weights(space.num_nodes());
+    assert_continuous(&space, &cells, &u);
+}
+
+struct PolynomialSource;
+
+fn polynomial(x: &Point2<f64>) -> f64 {
+    1.0 - x.x * x.y + x.x.powi(3) * x.y.powi(2) + 2.0 * x.y.powi(3)
+}
+
+impl Operator<f64, U2> for PolynomialSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for PolynomialSource {
+    fn evaluate(&self, coords: &Point2<f64>, _data: &Self::Parameters) -> Vector1<f64> {
+        Vector1::new(polynomial(coords))
+    }
+}
+
+#[test]
+fn hierarchical_space_l2_projection_reproduces_polynomials() {
+    // The polynomial lies in Q3, so its L2 projection onto the space of order 3 is exact
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let space = HierarchicalSpace::from_quad_mesh(&mesh, vec![3; 4]);
+    let quadrature = quadrature::tensor::quadrilateral_gauss(5);
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature.clone(), Density(1.0));
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature);
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&space)
+        .with_quadrature_table(&mass_table);
+    let mass = CsrAssembler::default().assemble(&mass_assembler).unwrap();
+    let source_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&space)
+        .with_quadrature_table(&qtable)
+        .with_source(&PolynomialSource)
+        .build();
+    let rhs = VectorAssembler::default()
+        .assemble_vector(&source_assembler)
+        .unwrap();
+    let mut u = DVector::zeros(space.num_nodes());
+    ConjugateGradientSolver::with_tolerance(1e-14)
+        .solve(&mass, u.as_view_mut(), rhs.as_view())
+        .unwrap();
+
+    // Vertex coefficients are point values
+    for (v, x) in mesh.vertices().iter().enumerate() {
+        assert_scalar_eq!(u[v], polynomial(x), comp = abs, tol = 1e-10);
+    }
+    for element in 0..space.num_elements() {
+        for xi in [point![0.3, -0.2], point![-0.9, 0.75]] {
+            let x = space.map_element_reference_coords(element, &xi);
+            assert_scalar_eq!(
+                evaluate(&space, element, &xi, &u),
+                polynomial(&x),
+                comp = abs,
+                tol = 1e-10
+            );
+        }
+    }
+}
+
+#[test]
+fn hierarchical_space_transfer_after_p_refinement_is_exact() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    let coarse = HierarchicalSpace::from_hex_mesh(&mesh, vec![1, 2, 2, 3, 1, 2, 3, 2]);
+    let fine = coarse.with_element_orders(coarse.element_orders().iter().map(|p| p + 1).collect());
+    assert!(fine.num_nodes() > coarse.num_nodes());
+
+    let u_coarse = pseudo_random_weights(coarse.num_nodes());
+    let u_fine = fine.transfer_weights(&coarse, u_coarse.as_view(), 1);
+    for element in 0..coarse.num_elements() {
+        for xi in [point![0.3, -0.2, 0.9], point![-1.0, 0.75, 0.1], point![1.0, 1.0, -1.0]] {
+            assert_scalar_eq!(
+                evaluate(&fine, element, &xi, &u_fine),
+                evaluate(&coarse, element, &xi, &u_coarse),
+                comp = abs,
+                tol = 1e-12
+            );
+        }
+    }
+
+    // Transferring back recovers the original coefficients
+    let u_back = coarse.transfer_weights(&fine, u_fine.as_view(), 1);
+    assert_eq!(u_back, u_coarse);
+}
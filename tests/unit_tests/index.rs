@@ -0,0 +1,69 @@
+use fenris::index::{node_dofs, DofIndex, ElementIndex, FaceIndex, NodeIndex, TypedIndex};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::Point2;
+use fenris::space::FiniteElementConnectivity;
+
+#[test]
+fn node_and_dof_indices_convert_consistently() {
+    let node = NodeIndex::new(5);
+    assert_eq!(node.dof(2, 0), DofIndex::new(10));
+    assert_eq!(node.dof(2, 1), DofIndex::new(11));
+    assert_eq!(node.dofs(3).collect::<Vec<_>>(), [15, 16, 17].map(DofIndex::new));
+    for dof in node.dofs(3) {
+        assert_eq!(dof.node_and_component(3).0, node);
+    }
+    assert_eq!(DofIndex::new(16).node_and_component(3), (node, 1));
+    assert_eq!(
+        node_dofs(&[NodeIndex::new(1), NodeIndex::new(0)], 2),
+        [2, 3, 0, 1].map(DofIndex::new)
+    );
+
+    assert_eq!(usize::from(ElementIndex::from(3)), 3);
+    assert_eq!(FaceIndex::new(7).to_string(), "7");
+    assert_eq!(
+        ElementIndex::range(3).collect::<Vec<_>>(),
+        [0, 1, 2].map(ElementIndex::new)
+    );
+    assert_eq!(ElementIndex::range(3).next_back(), Some(ElementIndex::new(2)));
+}
+
+#[test]
+#[should_panic]
+fn node_dof_panics_for_invalid_component() {
+    NodeIndex::new(0).dof(2, 2);
+}
+
+#[test]
+fn typed_indices_convert_slices() {
+    let raw = [3, 1, 4];
+    let nodes = NodeIndex::from_raw_slice(&raw);
+    assert_eq!(nodes, [3, 1, 4].map(NodeIndex::new));
+    assert_eq!(NodeIndex::as_raw_slice(nodes), &raw);
+
+    let mut buffer = [0; 2];
+    NodeIndex::from_raw_slice_mut(&mut buffer).copy_from_slice(&[5, 9].map(NodeIndex::new));
+    assert_eq!(buffer, [5, 9]);
+
+    let serialized = serde_json::to_string(&nodes).unwrap();
+    assert_eq!(serialized, "[3,1,4]");
+}
+
+#[test]
+fn space_provides_typed_element_nodes_and_dofs() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let element = ElementIndex::new(3);
+    let nodes = mesh.element_node_indices(element);
+    let raw_nodes = mesh.connectivity()[3].0;
+    assert_eq!(NodeIndex::as_raw_slice(&nodes), &raw_nodes);
+
+    let dofs = mesh.element_dof_indices(element, 2);
+    assert_eq!(dofs.len(), 8);
+    for (i, node) in nodes.iter().enumerate() {
+        assert_eq!(dofs[2 * i], node.dof(2, 0));
+        assert_eq!(dofs[2 * i + 1], node.dof(2, 1));
+    }
+
+    // Vertices of the mesh are accessed with the raw node index
+    let vertex: &Point2<f64> = &mesh.vertices()[nodes[0].index()];
+    assert_eq!(vertex, &mesh.vertices()[raw_nodes[0]]);
+}
@@ -1 +1,4 @@
 mod msh;
+mod partitioned;
+mod quadrature;
+mod vtk;
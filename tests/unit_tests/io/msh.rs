@@ -1,11 +1,13 @@
 use crate::export_mesh_vtk;
 use fenris::connectivity::{
-    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
 };
-use fenris::io::msh::load_msh_from_file;
+use fenris::element::{ElementConnectivity, Hex20Element};
+use fenris::io::msh::{load_msh_from_file, load_msh_with_physical_groups_from_file, MshPhysicalName};
 use insta::assert_debug_snapshot;
-use nalgebra::{U2, U3};
+use itertools::izip;
+use nalgebra::{Point3, Vector3, U2, U3};
 
 #[test]
 fn load_msh_sphere_tet4_large() -> eyre::Result<()> {
@@ -163,3 +165,69 @@ fn load_msh_cube_hex27() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn load_msh_cube_hex20() -> eyre::Result<()> {
+    let mesh = load_msh_from_file::<f64, U3, Hex20Connectivity, _>("assets/meshes/cube_hex20_1.msh")?;
+
+    assert_eq!(mesh.vertices().len(), 20);
+    assert_eq!(mesh.connectivity().len(), 1);
+
+    // Gmsh uses the same node ordering as fenris, so the nodes of the element should
+    // coincide with the nodes of the reference element mapped to the unit cube
+    let element = mesh.connectivity()[0].element(mesh.vertices()).unwrap();
+    for (x, xi) in izip!(element.vertices(), Hex20Element::<f64>::reference().vertices()) {
+        let expected = Point3::from((xi.coords + Vector3::repeat(1.0)) / 2.0);
+        assert_eq!(x, &expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn load_msh_with_physical_groups_square_tri3d2() -> eyre::Result<()> {
+    let labeled_mesh = load_msh_with_physical_groups_from_file::<f64, U2, Tri3d2Connectivity, _>(
+        "assets/meshes/square_tri3_2_physical.msh",
+    )?;
+
+    // The last cell belongs to a surface that is not part of any physical group
+    assert_eq!(labeled_mesh.mesh.vertices().len(), 6);
+    assert_eq!(labeled_mesh.mesh.connectivity().len(), 5);
+    assert_eq!(labeled_mesh.cell_labels, vec![Some(5), Some(5), Some(7), Some(7), None]);
+
+    assert_eq!(
+        labeled_mesh.physical_names,
+        vec![
+            MshPhysicalName {
+                dim: 2,
+                tag: 5,
+                name: "left".to_string()
+            },
+            MshPhysicalName {
+                dim: 2,
+                tag: 7,
+                name: "right".to_string()
+            },
+        ]
+    );
+    assert_eq!(labeled_mesh.physical_tag_by_name(2, "right"), Some(7));
+    assert_eq!(labeled_mesh.physical_tag_by_name(1, "right"), None);
+    assert_eq!(labeled_mesh.cells_with_label(5), vec![0, 1]);
+    assert_eq!(labeled_mesh.cells_with_label(7), vec![2, 3]);
+
+    // Loading without physical groups gives the same mesh
+    let mesh = load_msh_from_file::<f64, U2, Tri3d2Connectivity, _>("assets/meshes/square_tri3_2_physical.msh")?;
+    assert_eq!(mesh.vertices(), labeled_mesh.mesh.vertices());
+    assert_eq!(mesh.connectivity(), labeled_mesh.mesh.connectivity());
+
+    Ok(())
+}
+
+#[test]
+fn load_msh_with_physical_groups_without_groups() -> eyre::Result<()> {
+    let labeled_mesh =
+        load_msh_with_physical_groups_from_file::<f64, U2, Tri3d2Connectivity, _>("assets/meshes/square_tri3_4.msh")?;
+    assert_eq!(labeled_mesh.cell_labels, vec![None; 4]);
+    assert!(labeled_mesh.physical_names.is_empty());
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use super::vtk::{data_array_offset, read_uncompressed_f64_array, split_vtu};
+use fenris::io::PartitionedFieldExporter;
+use fenris::mesh::partition::partition_elements_by_coordinate_bisection;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use std::convert::TryInto;
+use std::path::Path;
+
+/// Returns the value of the given attribute in the first line of `xml` containing `line_pattern`.
+fn xml_attribute<'a>(xml: &'a str, line_pattern: &str, attribute: &str) -> &'a str {
+    let line = xml
+        .lines()
+        .find(|line| line.contains(line_pattern))
+        .unwrap_or_else(|| panic!("No line containing {line_pattern}"));
+    let value = line
+        .split(&format!("{attribute}=\""))
+        .nth(1)
+        .unwrap_or_else(|| panic!("No attribute {attribute}"));
+    &value[..value.find('"').unwrap()]
+}
+
+/// Reads the raw f64 data item with the given name from an XDMF piece written by fenris.
+fn read_xdmf_f64_data_item(piece_path: &Path, xml: &str, name: &str, len: usize) -> Vec<f64> {
+    let data_item_line = xml
+        .lines()
+        .skip_while(|line| !line.contains(&format!("Name=\"{name}\"")))
+        .nth(1)
+        .unwrap();
+    assert!(data_item_line.contains(r#"NumberType="Float" Precision="8""#));
+    let seek: usize = xml_attribute(data_item_line, "<DataItem", "Seek")
+        .parse()
+        .unwrap();
+    let bytes = std::fs::read(piece_path.with_extension("bin")).unwrap();
+    bytes[seek..seek + 8 * len]
+        .chunks_exact(8)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn partitioned_test_problem() -> (QuadMesh2d<f64>, Vec<usize>, Vec<f64>, Vec<f64>) {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let partition = partition_elements_by_coordinate_bisection(&mesh, 4);
+    // The point field reproduces the vertex coordinates, so that it can be checked against the points of each piece
+    let coords: Vec<f64> = mesh.vertices().iter().flat_map(|v| [v.x, v.y]).collect();
+    let element_indices: Vec<f64> = (0..mesh.connectivity().len()).map(|i| i as f64).collect();
+    (mesh, partition, coords, element_indices)
+}
+
+#[test]
+fn partitioned_field_exporter_writes_pvtu_pieces() {
+    let (mesh, partition, coords, element_indices) = partitioned_test_problem();
+    let dir = std::env::temp_dir().join("fenris_partitioned_pvtu");
+    let _ = std::fs::remove_dir_all(&dir);
+    PartitionedFieldExporter::new(&mesh, &partition)
+        .with_point_field("coords", &coords, 2)
+        .with_cell_field("element", &element_indices)
+        .write_pvtu(dir.join("solution.pvtu"))
+        .unwrap();
+
+    let index = std::fs::read_to_string(dir.join("solution.pvtu")).unwrap();
+    assert!(index.contains(r#"type="PUnstructuredGrid""#));
+    assert!(index.contains(r#"<PDataArray type="Float64" Name="coords" NumberOfComponents="2"/>"#));
+    assert!(index.contains(r#"<PDataArray type="Float64" Name="element" NumberOfComponents="1"/>"#));
+    assert!(index.contains(r#"<PDataArray type="Float64" Name="Points" NumberOfComponents="3"/>"#));
+
+    let mut all_elements = Vec::new();
+    for part in 0..4 {
+        let source = format!("solution_{part}.vtu");
+        assert!(index.contains(&format!(r#"<Piece Source="{source}"/>"#)));
+        let bytes = std::fs::read(dir.join(&source)).unwrap();
+        let (header, data) = split_vtu(&bytes);
+
+        let elements = read_uncompressed_f64_array(data, data_array_offset(header, "element"));
+        assert!(elements
+            .iter()
+            .all(|&element| partition[element as usize] == part));
+        all_elements.extend(elements);
+
+        let points = read_uncompressed_f64_array(data, data_array_offset(header, "Points"));
+        let piece_coords = read_uncompressed_f64_array(data, data_array_offset(header, "coords"));
+        let expected_coords: Vec<f64> = points.chunks_exact(3).flat_map(|p| [p[0], p[1]]).collect();
+        assert_eq!(piece_coords, expected_coords);
+    }
+    all_elements.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(all_elements, element_indices);
+}
+
+#[test]
+fn partitioned_field_exporter_writes_xdmf_pieces() {
+    let (mesh, partition, coords, element_indices) = partitioned_test_problem();
+    let dir = std::env::temp_dir().join("fenris_partitioned_xdmf");
+    let _ = std::fs::remove_dir_all(&dir);
+    PartitionedFieldExporter::new(&mesh, &partition)
+        .with_point_field("coords", &coords, 2)
+        .with_cell_field("element", &element_indices)
+        .write_xdmf(dir.join("solution.xmf"))
+        .unwrap();
+
+    let index = std::fs::read_to_string(dir.join("solution.xmf")).unwrap();
+    assert!(index.contains(r#"CollectionType="Spatial""#));
+
+    let mut total_elements = 0;
+    for part in 0..4 {
+        let source = format!("solution_{part}.xmf");
+        assert!(index.contains(&format!(r#"<xi:include href="{source}""#)));
+        let piece_path = dir.join(&source);
+        let xml = std::fs::read_to_string(&piece_path).unwrap();
+        assert_eq!(xml_attribute(&xml, "<Topology", "TopologyType"), "Quadrilateral");
+        let num_elements: usize = xml_attribute(&xml, "<Topology", "NumberOfElements")
+            .parse()
+            .unwrap();
+        assert_eq!(xml_attribute(&xml, r#"Name="coords""#, "AttributeType"), "Matrix");
+        assert_eq!(xml_attribute(&xml, r#"Name="element""#, "Center"), "Cell");
+
+        let elements = read_xdmf_f64_data_item(&piece_path, &xml, "element", num_elements);
+        assert!(elements
+            .iter()
+            .all(|&element| partition[element as usize] == part));
+        total_elements += num_elements;
+    }
+    assert_eq!(total_elements, mesh.connectivity().len());
+}
+
+#[test]
+fn partitioned_field_exporter_skips_empty_partitions_and_rejects_invalid_input() {
+    let (mesh, partition, coords, _) = partitioned_test_problem();
+    // Move all elements of partition 2 to partition 4, leaving partition 2 empty
+    let sparse_partition: Vec<usize> = partition
+        .iter()
+        .map(|&part| if part == 2 { 4 } else { part })
+        .collect();
+    let dir = std::env::temp_dir().join("fenris_partitioned_empty");
+    let _ = std::fs::remove_dir_all(&dir);
+    let exporter = PartitionedFieldExporter::new(&mesh, &sparse_partition);
+    assert_eq!(exporter.num_partitions(), 5);
+    let pieces = exporter.try_build_pieces().unwrap();
+    let parts: Vec<_> = pieces.iter().map(|(part, _)| *part).collect();
+    assert_eq!(parts, vec![0, 1, 3, 4]);
+    exporter.write_pvtu(dir.join("solution.pvtu")).unwrap();
+    assert!(!dir.join("solution_2.vtu").exists());
+    assert!(dir.join("solution_4.vtu").exists());
+
+    let path = dir.join("invalid.pvtu");
+    let exporter = PartitionedFieldExporter::new(&mesh, &partition[1..]);
+    assert!(exporter.write_pvtu(&path).is_err());
+    let exporter = PartitionedFieldExporter::new(&mesh, &partition).with_point_field("coords", &coords[1..], 2);
+    assert!(exporter.write_pvtu(&path).is_err());
+    assert!(!path.exists());
+}
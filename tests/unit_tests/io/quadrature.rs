@@ -0,0 +1,148 @@
+use fenris::assembly::local::{CompactQuadratureTable, QuadratureTable, UniformQuadratureTable};
+use fenris::io::quadrature::{QuadratureRuleRecord, QuadratureTableFile};
+use fenris::nalgebra::{Point2, U2};
+use fenris::quadrature;
+use fenris::util::NestedVec;
+
+fn element_quadrature(table: &impl QuadratureTable<f64, U2>, element_index: usize) -> (Vec<Point2<f64>>, Vec<f64>) {
+    let n = table.element_quadrature_size(element_index);
+    let mut points = vec![Point2::origin(); n];
+    let mut weights = vec![0.0; n];
+    table.populate_element_quadrature(element_index, &mut points, &mut weights);
+    (points, weights)
+}
+
+fn example_compact_table() -> CompactQuadratureTable<f64, U2> {
+    let (w1, p1) = quadrature::tensor::quadrilateral_gauss(1);
+    let (w2, p2) = quadrature::tensor::quadrilateral_gauss(2);
+    let mut points = NestedVec::new();
+    points.push(&p1);
+    points.push(&p2);
+    let mut weights = NestedVec::new();
+    weights.push(&w1);
+    weights.push(&w2);
+    CompactQuadratureTable::from_points_weights_and_map(points, weights, vec![0, 1, 1, 0])
+}
+
+#[test]
+fn quadrature_table_file_json_example_is_parsed() {
+    let json = r#"{
+        "dim": 2,
+        "rules": [
+            { "points": [[0.0, 0.0]], "weights": [4.0] },
+            { "points": [[-0.5, 0.0], [0.5, 0.0]], "weights": [2.0, 2.0] }
+        ],
+        "element_rules": [0, 1, 0]
+    }"#;
+    let file = QuadratureTableFile::read_json(json.as_bytes()).unwrap();
+    assert!(!file.is_uniform());
+    let table: CompactQuadratureTable<f64, U2> = file.to_compact_table(3).unwrap();
+    assert_eq!(element_quadrature(&table, 0), (vec![Point2::new(0.0, 0.0)], vec![4.0]));
+    assert_eq!(
+        element_quadrature(&table, 1),
+        (vec![Point2::new(-0.5, 0.0), Point2::new(0.5, 0.0)], vec![2.0, 2.0])
+    );
+    assert_eq!(element_quadrature(&table, 2), element_quadrature(&table, 0));
+
+    assert!(file.to_compact_table::<f64, U2>(4).is_err());
+    assert!(file.to_uniform_table::<f64, U2>().is_err());
+}
+
+#[test]
+fn quadrature_table_file_roundtrips_per_element_tables() {
+    let table = example_compact_table();
+    let file = QuadratureTableFile::per_element_from_table(&table, 4);
+
+    let mut json = Vec::new();
+    file.write_json(&mut json).unwrap();
+    assert_eq!(QuadratureTableFile::read_json(json.as_slice()).unwrap(), file);
+
+    let mut binary = Vec::new();
+    file.write_binary(&mut binary).unwrap();
+    assert_eq!(QuadratureTableFile::read_binary(binary.as_slice()).unwrap(), file);
+
+    let imported: CompactQuadratureTable<f64, U2> = file.to_compact_table(4).unwrap();
+    for element_index in 0..4 {
+        assert_eq!(
+            element_quadrature(&imported, element_index),
+            element_quadrature(&table, element_index)
+        );
+    }
+}
+
+#[test]
+fn quadrature_table_file_roundtrips_uniform_tables_through_files() {
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss::<f64>(3));
+    let file = QuadratureTableFile::uniform_from_table(&table);
+    assert!(file.is_uniform());
+
+    let dir = std::env::temp_dir().join("fenris_quadrature_table_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["table.json", "table.bin"] {
+        let path = dir.join(name);
+        file.save(&path).unwrap();
+        let loaded = QuadratureTableFile::load(&path).unwrap();
+        assert_eq!(loaded, file);
+        assert_eq!(loaded.to_uniform_table::<f64, U2>().unwrap(), table);
+        let compact: CompactQuadratureTable<f64, U2> = loaded.to_compact_table(2).unwrap();
+        assert_eq!(element_quadrature(&compact, 1), element_quadrature(&table, 1));
+    }
+    // JSON files are human-readable
+    let json = std::fs::read_to_string(dir.join("table.json")).unwrap();
+    assert!(json.contains("\"weights\""));
+    assert!(!json.contains("element_rules"));
+}
+
+#[test]
+fn quadrature_table_file_rejects_invalid_tables() {
+    let rule = QuadratureRuleRecord {
+        points: vec![vec![0.0, 0.0]],
+        weights: vec![4.0],
+    };
+    let valid = QuadratureTableFile {
+        dim: 2,
+        rules: vec![rule.clone()],
+        element_rules: Some(vec![0, 0]),
+    };
+    assert!(valid.validate().is_ok());
+    // Dimension mismatch with the requested table
+    assert!(valid
+        .to_compact_table::<f64, fenris::nalgebra::U3>(2)
+        .is_err());
+
+    let invalid_tables = [
+        QuadratureTableFile {
+            element_rules: Some(vec![0, 1]),
+            ..valid.clone()
+        },
+        QuadratureTableFile {
+            rules: vec![QuadratureRuleRecord {
+                weights: vec![1.0, 2.0],
+                ..rule.clone()
+            }],
+            ..valid.clone()
+        },
+        QuadratureTableFile {
+            dim: 3,
+            ..valid.clone()
+        },
+        QuadratureTableFile {
+            rules: vec![rule.clone(), rule.clone()],
+            element_rules: None,
+            ..valid.clone()
+        },
+    ];
+    for table in &invalid_tables {
+        assert!(table.validate().is_err());
+        assert!(table.write_binary(Vec::new()).is_err());
+        assert!(table.write_json(Vec::new()).is_err());
+        let json = serde_json::to_string(table).unwrap();
+        assert!(QuadratureTableFile::read_json(json.as_bytes()).is_err());
+    }
+
+    assert!(QuadratureTableFile::read_binary(&b"NOTATABLE"[..]).is_err());
+    let mut truncated = Vec::new();
+    valid.write_binary(&mut truncated).unwrap();
+    truncated.pop();
+    assert!(QuadratureTableFile::read_binary(truncated.as_slice()).is_err());
+}
@@ -0,0 +1,229 @@
+use fenris::io::vtk::{write_vtu_to, FiniteElementMeshDataSetBuilder, VtuCompression};
+use fenris::io::FieldExporter;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::vtkio::model::{Attribute, DataSet};
+use std::convert::TryInto;
+
+/// Splits a VTU file into its XML header and the raw appended data.
+pub(super) fn split_vtu(bytes: &[u8]) -> (&str, &[u8]) {
+    let marker = b"<AppendedData encoding=\"raw\">";
+    let marker_pos = bytes
+        .windows(marker.len())
+        .position(|window| window == marker)
+        .expect("VTU file must contain raw appended data");
+    let data_start = marker_pos + bytes[marker_pos..].iter().position(|&b| b == b'_').unwrap() + 1;
+    let header = std::str::from_utf8(&bytes[..marker_pos]).unwrap();
+    (header, &bytes[data_start..])
+}
+
+/// Returns the offset of the data array with the given name in the appended data.
+pub(super) fn data_array_offset(header: &str, name: &str) -> usize {
+    let line = header
+        .lines()
+        .find(|line| line.contains("<DataArray") && line.contains(&format!("Name=\"{name}\"")))
+        .unwrap_or_else(|| panic!("No data array named {name}"));
+    let offset = line.split("offset=\"").nth(1).unwrap();
+    offset[..offset.find('"').unwrap()].parse().unwrap()
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+pub(super) fn read_uncompressed_f64_array(data: &[u8], offset: usize) -> Vec<f64> {
+    let num_bytes = read_u64(data, offset) as usize;
+    data[offset + 8..offset + 8 + num_bytes]
+        .chunks_exact(8)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn write_vtu_uncompressed_preserves_data_exactly() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let num_points = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    // Values that cannot be represented exactly in short decimal notation
+    let point_data: Vec<f64> = (0..5 * num_points)
+        .map(|i| (i as f64 + 1.0) / 3.0 + std::f64::consts::PI)
+        .collect();
+    let cell_data: Vec<f64> = (0..num_cells).map(|i| 1.0 / (i as f64 + 7.0)).collect();
+
+    let dataset = FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_point_scalar_attributes("five_components", 5, &point_data)
+        .with_cell_scalar_attributes("cell_values", 1, &cell_data)
+        .try_build()
+        .unwrap();
+    let mut bytes = Vec::new();
+    write_vtu_to(&mut bytes, &dataset, VtuCompression::None).unwrap();
+    let (header, data) = split_vtu(&bytes);
+
+    assert!(header.contains(r#"type="UnstructuredGrid""#));
+    assert!(!header.contains("compressor="));
+    assert!(header.contains(&format!(
+        r#"<Piece NumberOfPoints="{num_points}" NumberOfCells="{num_cells}">"#
+    )));
+    assert!(header.contains(r#"Name="five_components" NumberOfComponents="5""#));
+
+    let points = read_uncompressed_f64_array(data, data_array_offset(header, "Points"));
+    let expected_points: Vec<f64> = mesh
+        .vertices()
+        .iter()
+        .flat_map(|v| [v.x, v.y, 0.0])
+        .collect();
+    assert_eq!(points, expected_points);
+
+    let offset = data_array_offset(header, "five_components");
+    assert_eq!(read_uncompressed_f64_array(data, offset), point_data);
+    let offset = data_array_offset(header, "cell_values");
+    assert_eq!(read_uncompressed_f64_array(data, offset), cell_data);
+
+    // Connectivity is stored as 64-bit integers, offsets point one past the last index of each cell
+    let offset = data_array_offset(header, "offsets");
+    assert_eq!(read_u64(data, offset), 8 * num_cells as u64);
+    let cell_offsets: Vec<u64> = (0..num_cells)
+        .map(|i| read_u64(data, offset + 8 * (i + 1)))
+        .collect();
+    let expected_offsets: Vec<u64> = (1..=num_cells as u64).map(|i| 4 * i).collect();
+    assert_eq!(cell_offsets, expected_offsets);
+
+    let offset = data_array_offset(header, "types");
+    assert_eq!(read_u64(data, offset), num_cells as u64);
+    // VTK_QUAD = 9
+    assert!(data[offset + 8..offset + 8 + num_cells]
+        .iter()
+        .all(|&cell_type| cell_type == 9));
+}
+
+#[test]
+fn write_vtu_compressed_has_vtk_block_headers() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(10);
+    let num_points = mesh.vertices().len();
+    let point_data = vec![1.0; 2 * num_points];
+    let dataset = FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_point_scalar_attributes("constant", 2, &point_data)
+        .try_build()
+        .unwrap();
+
+    let mut uncompressed = Vec::new();
+    write_vtu_to(&mut uncompressed, &dataset, VtuCompression::None).unwrap();
+
+    for (compression, compressor_name) in [
+        (VtuCompression::ZLib { level: 6 }, "vtkZLibDataCompressor"),
+        (VtuCompression::Lz4, "vtkLZ4DataCompressor"),
+        (VtuCompression::Lzma { level: 6 }, "vtkLZMADataCompressor"),
+    ] {
+        let mut bytes = Vec::new();
+        write_vtu_to(&mut bytes, &dataset, compression).unwrap();
+        assert!(bytes.len() < uncompressed.len());
+
+        let (header, data) = split_vtu(&bytes);
+        assert!(header.contains(&format!(r#"compressor="{compressor_name}""#)));
+
+        // Each array is stored in a single block: [1][uncompressed size][0][compressed size][data]
+        let offset = data_array_offset(header, "constant");
+        assert_eq!(read_u64(data, offset), 1);
+        assert_eq!(read_u64(data, offset + 8), 8 * 2 * num_points as u64);
+        assert_eq!(read_u64(data, offset + 16), 0);
+        let compressed_size = read_u64(data, offset + 24) as usize;
+        let next_offset = data_array_offset(header, "Points");
+        assert_eq!(offset + 32 + compressed_size, next_offset);
+    }
+}
+
+#[test]
+fn field_exporter_attaches_fields() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_points = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    let u = vec![1.0; 2 * num_points];
+    let s = vec![2.0; num_cells];
+    let tensor = vec![3.0; 4 * num_cells];
+
+    let dataset = FieldExporter::new(&mesh)
+        .with_point_field("displacement", &u, 2)
+        .with_cell_field("von_mises", &s)
+        .with_cell_field("stress", &tensor)
+        .try_build()
+        .unwrap();
+
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } => pieces[0].load_piece_data(None).unwrap(),
+        _ => panic!("Expected unstructured grid"),
+    };
+    let summarize = |attributes: &[Attribute]| -> Vec<(String, usize, usize)> {
+        attributes
+            .iter()
+            .map(|attribute| match attribute {
+                Attribute::DataArray(array) => (array.name.clone(), array.num_comp(), array.data.len()),
+                _ => panic!("Expected data array"),
+            })
+            .collect()
+    };
+    assert_eq!(
+        summarize(&piece.data.point),
+        vec![("displacement".to_string(), 2, 2 * num_points)]
+    );
+    assert_eq!(
+        summarize(&piece.data.cell),
+        vec![
+            ("von_mises".to_string(), 1, num_cells),
+            ("stress".to_string(), 4, 4 * num_cells)
+        ]
+    );
+}
+
+#[test]
+fn field_exporter_rejects_inconsistent_fields() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_points = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+
+    let exporter = FieldExporter::new(&mesh).with_point_field("u", &vec![0.0; 2 * num_points + 1], 2);
+    assert!(exporter.try_build().is_err());
+
+    let exporter = FieldExporter::new(&mesh).with_point_field("u", &vec![0.0; num_points], 0);
+    assert!(exporter.try_build().is_err());
+
+    let exporter = FieldExporter::new(&mesh).with_cell_field("s", &vec![0.0; num_cells + 1]);
+    assert!(exporter.try_build().is_err());
+
+    // Errors must not be lost when valid fields are added afterwards
+    let exporter = FieldExporter::new(&mesh)
+        .with_cell_field("s", &[0.0; 0])
+        .with_point_field("u", &vec![0.0; num_points], 1);
+    assert!(exporter.try_build().is_err());
+    let path = std::env::temp_dir().join("fenris_field_exporter_invalid.vtu");
+    assert!(exporter.export(&path).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn field_exporter_checks_piecewise_constant_fields() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_cells = mesh.connectivity().len();
+
+    let dataset = FieldExporter::new(&mesh)
+        .with_piecewise_constant_field("density", &vec![1.0; 2 * num_cells], 2)
+        .try_build()
+        .unwrap();
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } => pieces[0].load_piece_data(None).unwrap(),
+        _ => panic!("Expected unstructured grid"),
+    };
+    match &piece.data.cell[..] {
+        [Attribute::DataArray(array)] => {
+            assert_eq!(array.name, "density");
+            assert_eq!(array.num_comp(), 2);
+            assert_eq!(array.data.len(), 2 * num_cells);
+        }
+        _ => panic!("Expected a single cell data array"),
+    }
+
+    // The number of components is explicit, so a field with the wrong layout must be rejected
+    let exporter = FieldExporter::new(&mesh).with_piecewise_constant_field("density", &vec![1.0; 2 * num_cells], 4);
+    assert!(exporter.try_build().is_err());
+    let exporter = FieldExporter::new(&mesh).with_piecewise_constant_field("density", &[0.0; 0], 0);
+    assert!(exporter.try_build().is_err());
+}
@@ -0,0 +1,82 @@
+use fenris::assembly::local::{GeneralQuadratureTable, UniformQuadratureTable};
+use fenris::memory::{format_bytes, HeapSizeEstimate, MemoryReport};
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::nalgebra::{DMatrix, DVector, Point2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris::space::SpatiallyIndexed;
+use fenris::util::NestedVec;
+use std::mem::{size_of, size_of_val};
+
+#[test]
+fn heap_size_estimates_of_basic_data_structures() {
+    let vector = DVector::<f64>::zeros(10);
+    assert_eq!(vector.heap_size_estimate(), 80);
+    let matrix = DMatrix::<f32>::zeros(3, 4);
+    assert_eq!(matrix.heap_size_estimate(), 48);
+
+    let csr = CsrMatrix::<f64>::identity(5);
+    assert_eq!(csr.heap_size_estimate(), (6 + 5) * size_of::<usize>() + 5 * 8);
+
+    let mut nested = NestedVec::new();
+    nested.push(&[1u32, 2, 3]);
+    nested.push(&[4u32]);
+    assert_eq!(nested.heap_size_estimate(), 4 * 4 + 4 * size_of::<usize>());
+}
+
+#[test]
+fn heap_size_estimates_of_meshes_and_tables() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let num_cells = mesh.connectivity().len();
+    let minimal_size = size_of_val(mesh.vertices()) + size_of_val(mesh.connectivity());
+    let size = mesh.heap_size_estimate();
+    assert!(size >= minimal_size);
+
+    // Sets contribute to the size of the mesh
+    mesh.sets_mut()
+        .insert_node_set("boundary", (0..10).collect());
+    assert!(mesh.heap_size_estimate() >= size + 10 * size_of::<usize>());
+
+    // A spatial index adds to the size of the underlying space
+    let tri_mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let indexed = SpatiallyIndexed::from_space(tri_mesh.clone());
+    assert!(indexed.heap_size_estimate() > tri_mesh.heap_size_estimate());
+
+    // A general table stores a rule per element, a uniform table only a single rule
+    let (weights, points) = quadrature::tensor::quadrilateral_gauss::<f64>(2);
+    let uniform = UniformQuadratureTable::from_points_and_weights(points.clone(), weights.clone());
+    let mut table_points = NestedVec::new();
+    let mut table_weights = NestedVec::new();
+    for _ in 0..num_cells {
+        table_points.push(&points);
+        table_weights.push(&weights);
+    }
+    let general = GeneralQuadratureTable::from_points_and_weights(table_points, table_weights);
+    assert!(uniform.heap_size_estimate() >= 4 * (size_of::<Point2<f64>>() + 8));
+    assert!(general.heap_size_estimate() >= num_cells * uniform.heap_size_estimate());
+}
+
+#[test]
+fn memory_report_summarizes_entries() {
+    let vector = DVector::<f64>::zeros(256);
+    let matrix = DMatrix::<f64>::zeros(128, 1024);
+    let report = MemoryReport::new()
+        .with_entry("solution", &vector)
+        .with_entry("dense matrix", &matrix)
+        .with_bytes("other", 100);
+    assert_eq!(report.entries().len(), 3);
+    assert_eq!(report.entries()[1], ("dense matrix".to_string(), 1024 * 1024));
+    assert_eq!(report.total_bytes(), 2048 + 1024 * 1024 + 100);
+
+    let output = report.to_string();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("solution") && lines[0].contains("2.00 KiB"));
+    assert!(lines[1].contains("1.00 MiB") && lines[1].contains("99.8%"));
+    assert!(lines[2].contains("100 B"));
+    assert!(lines[3].starts_with("total"));
+
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1536), "1.50 KiB");
+    assert_eq!(format_bytes(3 << 30), "3.00 GiB");
+}
@@ -14,8 +14,21 @@ use proptest::collection::vec;
 use proptest::prelude::*;
 use std::cmp::max;
 
+mod adjacency;
+mod boundary;
+mod editor;
+mod features;
+mod halo;
+mod inflation;
+mod partition;
 mod procedural;
 mod refinement;
+mod selection;
+mod sets;
+mod split;
+mod structured;
+mod submesh;
+mod supermesh;
 
 #[test]
 fn quad4_find_boundary_faces() {
@@ -0,0 +1,117 @@
+use fenris::connectivity::{Connectivity, Tri3d2Connectivity};
+use fenris::mesh::adjacency::MeshConnectivity;
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::sets::CellFace;
+use fenris::mesh::split::find_interior_faces;
+use fenris::mesh::TriangleMesh2d;
+use nalgebra::point;
+
+#[test]
+fn adjacency_of_quad_mesh() {
+    // Cells are numbered row by row, starting at the top left:
+    //  0 1
+    //  2 3
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let connectivity = MeshConnectivity::from_mesh(&mesh).unwrap();
+    assert_eq!(connectivity.num_vertices(), 9);
+    assert_eq!(connectivity.num_cells(), 4);
+    assert_eq!(connectivity.num_faces(), 12);
+
+    let center = mesh
+        .vertices()
+        .iter()
+        .position(|x| x == &point![0.5, 0.5])
+        .unwrap();
+    assert_eq!(connectivity.vertex_cells(center), &[0, 1, 2, 3]);
+    assert_eq!(connectivity.vertex_cells(0), &[0]);
+
+    assert_eq!(connectivity.cell_neighbors(0), &[1, 2]);
+    assert_eq!(connectivity.cell_neighbors(1), &[0, 3]);
+    assert_eq!(connectivity.cell_neighbors(3), &[1, 2]);
+
+    assert_eq!(connectivity.interior_faces().count(), 4);
+    assert_eq!(connectivity.boundary_faces().count(), 8);
+    assert_eq!(
+        connectivity.interior_faces().collect::<Vec<_>>(),
+        find_interior_faces(&mesh)
+    );
+}
+
+#[test]
+fn adjacency_of_tet_mesh_is_consistent() {
+    let mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    let connectivity = MeshConnectivity::from_mesh(&mesh).unwrap();
+
+    let mut boundary_faces: Vec<_> = connectivity.boundary_faces().collect();
+    boundary_faces.sort_unstable();
+    let mut expected_boundary_faces: Vec<_> = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(_, cell_index, local_index)| CellFace {
+            cell_index,
+            local_index,
+        })
+        .collect();
+    expected_boundary_faces.sort_unstable();
+    assert_eq!(boundary_faces, expected_boundary_faces);
+
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        let faces = connectivity.cell_faces(cell_index);
+        assert_eq!(faces.len(), cell.num_faces());
+        for (local_index, &face_index) in faces.iter().enumerate() {
+            let cell_face = CellFace {
+                cell_index,
+                local_index,
+            };
+            let face = connectivity.face(face_index);
+            assert!(face.first == cell_face || face.second == Some(cell_face));
+            match connectivity.opposite_face(cell_face) {
+                Some(opposite) => {
+                    assert!(!face.is_boundary());
+                    assert_eq!(connectivity.opposite_face(opposite), Some(cell_face));
+                    assert_eq!(
+                        connectivity.cell_faces(opposite.cell_index)[opposite.local_index],
+                        face_index
+                    );
+                    assert!(connectivity
+                        .cell_neighbors(cell_index)
+                        .contains(&opposite.cell_index));
+                }
+                None => assert!(face.is_boundary()),
+            }
+        }
+        for &vertex_index in cell.vertex_indices() {
+            assert!(connectivity
+                .vertex_cells(vertex_index)
+                .contains(&cell_index));
+        }
+    }
+
+    let num_cell_faces: usize = mesh
+        .connectivity()
+        .iter()
+        .map(|cell| cell.num_faces())
+        .sum();
+    assert_eq!(
+        2 * connectivity.interior_faces().count() + connectivity.boundary_faces().count(),
+        num_cell_faces
+    );
+}
+
+#[test]
+fn adjacency_rejects_non_manifold_faces() {
+    let vertices = vec![
+        point![0.0, 0.0],
+        point![1.0, 0.0],
+        point![0.5, 1.0],
+        point![0.5, -1.0],
+        point![0.5, 2.0],
+    ];
+    let connectivity = vec![
+        Tri3d2Connectivity([0, 1, 2]),
+        Tri3d2Connectivity([1, 0, 3]),
+        Tri3d2Connectivity([0, 1, 4]),
+    ];
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(vertices, connectivity);
+    assert!(MeshConnectivity::from_mesh(&mesh).is_err());
+}
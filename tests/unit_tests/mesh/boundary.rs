@@ -0,0 +1,130 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{
+    ElementConnectivityAssembler, ElementTractionAssemblerBuilder, TractionFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::Operator;
+use fenris::connectivity::Connectivity;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::{Tet10Mesh, Tet4Mesh, Tri6Mesh2d, TriangleMesh2d};
+use fenris::nalgebra::{DVector, OPoint, OVector, Point2, Point3, Vector2, Vector3, U2, U3};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// Traction equal to the outward unit normal.
+struct NormalTraction;
+
+impl Operator<f64, U2> for NormalTraction {
+    type SolutionDim = U2;
+    type Parameters = ();
+}
+
+impl Operator<f64, U3> for NormalTraction {
+    type SolutionDim = U3;
+    type Parameters = ();
+}
+
+impl TractionFunction<f64, U2> for NormalTraction {
+    fn evaluate(&self, _coords: &OPoint<f64, U2>, normal: &OVector<f64, U2>, _data: &()) -> Vector2<f64> {
+        *normal
+    }
+}
+
+impl TractionFunction<f64, U3> for NormalTraction {
+    fn evaluate(&self, _coords: &OPoint<f64, U3>, normal: &OVector<f64, U3>, _data: &()) -> Vector3<f64> {
+        *normal
+    }
+}
+
+#[test]
+fn extract_boundary_mesh_tet10() {
+    let tet4_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let mesh = Tet10Mesh::from(&tet4_mesh);
+    let boundary = mesh.extract_boundary_mesh();
+
+    assert_eq!(boundary.num_parent_vertices(), mesh.vertices().len());
+    assert_eq!(boundary.parent_vertices(), mesh.find_boundary_vertices());
+    assert_eq!(boundary.mesh(), &mesh.extract_surface_mesh());
+    for (i, &parent_vertex) in boundary.parent_vertices().iter().enumerate() {
+        assert_eq!(boundary.mesh().vertices()[i], mesh.vertices()[parent_vertex]);
+    }
+    for (face_index, face) in boundary.mesh().connectivity().iter().enumerate() {
+        let parent_cell = &mesh.connectivity()[boundary.parent_cells()[face_index]];
+        let parent_face = parent_cell
+            .get_face_connectivity(boundary.parent_local_faces()[face_index])
+            .unwrap();
+        let mapped_face: Vec<_> = face
+            .vertex_indices()
+            .iter()
+            .map(|&i| boundary.parent_vertices()[i])
+            .collect();
+        assert_eq!(mapped_face, parent_face.vertex_indices());
+    }
+
+    // The boundary mesh can be exported for skin visualization
+    assert!(FiniteElementMeshDataSetBuilder::from_mesh(boundary.mesh())
+        .try_build()
+        .is_ok());
+
+    // The boundary mesh is a quadratic surface finite element space. With t = n, the traction
+    // vector f mapped to the parent space satisfies
+    //  u . f = int_{dΩ} u . n ds = int_Ω div u dx
+    // for any u that is exactly represented by the parent space. For the unit box and
+    // u = (x^2, yz, 1 - y^2) we have div u = 2x + z and hence u . f = 3 / 2.
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(4).unwrap());
+    let assembler = ElementTractionAssemblerBuilder::new()
+        .with_finite_element_space(boundary.mesh())
+        .with_traction(&NormalTraction)
+        .with_quadrature_table(&qtable)
+        .build()
+        .map_element_nodes(boundary.num_parent_vertices(), |i| boundary.parent_vertices()[i]);
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    let u_fn = |p: &Point3<f64>| Vector3::new(p.x * p.x, p.y * p.z, 1.0 - p.y * p.y);
+    let u = DVector::from_iterator(f.len(), mesh.vertices().iter().flat_map(|p| u_fn(p).data.0[0]));
+    assert_scalar_eq!(u.dot(&f), 1.5, comp = abs, tol = 1e-12);
+
+    // Assembling on the boundary space and adding to the parent vector gives the same result
+    let f_boundary = VectorAssembler::default()
+        .assemble_vector(
+            &ElementTractionAssemblerBuilder::new()
+                .with_finite_element_space(boundary.mesh())
+                .with_traction(&NormalTraction)
+                .with_quadrature_table(&qtable)
+                .build(),
+        )
+        .unwrap();
+    let mut f_parent = DVector::zeros(f.len());
+    boundary.add_to_parent(&f_boundary, &mut f_parent, 3);
+    assert_matrix_eq!(f_parent, f, comp = abs, tol = 1e-14);
+    assert_eq!(boundary.gather_from_parent(&f_parent, 3), f_boundary);
+}
+
+#[test]
+fn extract_boundary_mesh_tri6_2d() {
+    let tri3_mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(3);
+    let mesh = Tri6Mesh2d::from(tri3_mesh);
+    let boundary = mesh.extract_boundary_mesh();
+    assert_eq!(boundary.parent_vertices(), mesh.find_boundary_vertices());
+
+    // u = (x^2, y^2) has div u = 2x + 2y and hence int_Ω div u dx = 2
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(3));
+    let assembler = ElementTractionAssemblerBuilder::new()
+        .with_finite_element_space(boundary.mesh())
+        .with_traction(&NormalTraction)
+        .with_quadrature_table(&qtable)
+        .build();
+    let f_boundary = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    let u_fn = |p: &Point2<f64>| Vector2::new(p.x * p.x, p.y * p.y);
+    let u = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|p| u_fn(p).data.0[0]),
+    );
+    let u_boundary = boundary.gather_from_parent(&u, 2);
+    assert_scalar_eq!(u_boundary.dot(&f_boundary), 2.0, comp = abs, tol = 1e-12);
+}
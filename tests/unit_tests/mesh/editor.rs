@@ -0,0 +1,95 @@
+use fenris::connectivity::Quad4d2Connectivity;
+use fenris::geometry::AxisAlignedBoundingBox;
+use fenris::mesh::editor::MeshEditor;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::{point, Point2, Vector2};
+
+#[test]
+fn mesh_editor_invalidates_derived_data() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let mut editor = MeshEditor::from_mesh(mesh);
+    assert!(!editor.has_cached_adjacency());
+    assert_eq!(editor.adjacency().unwrap().num_cells(), 9);
+    assert_eq!(editor.boundary_vertices().len(), 12);
+    let query = AxisAlignedBoundingBox::new(point![0.1, 0.1], point![0.2, 0.2]);
+    assert_eq!(editor.cells_intersecting(&query).len(), 1);
+    assert!(editor.has_cached_adjacency());
+    assert!(editor.has_cached_spatial_index());
+
+    // Moving vertices only invalidates the spatial index
+    editor.transform_vertices(|v| *v += Vector2::new(1.0, 0.0));
+    assert_eq!(editor.topology_revision(), 0);
+    assert_eq!(editor.geometry_revision(), 1);
+    assert!(editor.has_cached_adjacency());
+    assert!(!editor.has_cached_spatial_index());
+    assert!(editor.cells_intersecting(&query).is_empty());
+    let shifted_query = AxisAlignedBoundingBox::new(point![1.1, 0.1], point![1.2, 0.2]);
+    assert_eq!(editor.cells_intersecting(&shifted_query).len(), 1);
+
+    // Adding a cell that is attached to the right boundary invalidates everything
+    let a = editor.add_vertex(Point2::new(2.5, 0.0));
+    let b = editor.add_vertex(Point2::new(2.5, 1.0 / 3.0));
+    assert!(!editor.has_cached_adjacency());
+    let cell = editor.add_cell(Quad4d2Connectivity([3, a, b, 7])).unwrap();
+    assert_eq!(cell, 9);
+    assert_eq!(editor.topology_revision(), 3);
+    let adjacency = editor.adjacency().unwrap();
+    assert_eq!(adjacency.num_vertices(), 18);
+    assert_eq!(adjacency.cell_neighbors(cell), &[2]);
+    assert_eq!(editor.boundary_faces().len(), 12 + 2);
+    let far_query = AxisAlignedBoundingBox::new(point![2.4, 0.0], point![2.5, 0.1]);
+    assert_eq!(editor.cells_intersecting(&far_query), vec![cell]);
+
+    // Replacing the cell with one that is detached from the rest of the mesh
+    let c = editor.add_vertex(Point2::new(3.0, 0.0));
+    let d = editor.add_vertex(Point2::new(3.0, 1.0 / 3.0));
+    let previous = editor
+        .replace_cell(cell, Quad4d2Connectivity([a, c, d, b]))
+        .unwrap();
+    assert_eq!(previous, Quad4d2Connectivity([3, a, b, 7]));
+    assert!(editor.adjacency().unwrap().cell_neighbors(cell).is_empty());
+    assert_eq!(editor.boundary_faces().len(), 12 + 4);
+}
+
+#[test]
+fn mesh_editor_removal_matches_cell_extraction() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    mesh.tag_cells("left", |x| x.x < 0.5);
+    mesh.tag_nodes("bottom", |x| x.y < 1e-12);
+    let removed = [0, 4, 5];
+    let kept: Vec<_> = (0..9).filter(|i| !removed.contains(i)).collect();
+    let expected = mesh.keep_cells(&kept);
+
+    let mut editor = MeshEditor::from(mesh.clone());
+    assert_eq!(editor.remove_cells(&removed).unwrap(), kept);
+    assert_eq!(editor.num_vertices(), mesh.vertices().len());
+    let kept_vertices = editor.remove_unreferenced_vertices();
+    assert_eq!(kept_vertices.len(), expected.vertices().len());
+    assert!(!kept_vertices.contains(&0));
+    for (new_index, &old_index) in kept_vertices.iter().enumerate() {
+        assert_eq!(expected.vertices()[new_index], mesh.vertices()[old_index]);
+    }
+    // No vertices are unreferenced anymore, so nothing changes
+    let revision = editor.topology_revision();
+    assert_eq!(editor.remove_unreferenced_vertices().len(), kept_vertices.len());
+    assert_eq!(editor.topology_revision(), revision);
+
+    assert_eq!(editor.into_mesh(), expected);
+}
+
+#[test]
+fn mesh_editor_rejects_invalid_edits() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(1);
+    let mut editor = MeshEditor::from_mesh(mesh.clone());
+    assert!(editor.add_cell(Quad4d2Connectivity([0, 1, 2, 4])).is_err());
+    assert!(editor
+        .replace_cell(1, Quad4d2Connectivity([0, 1, 2, 3]))
+        .is_err());
+    assert!(editor
+        .replace_cell(0, Quad4d2Connectivity([0, 1, 2, 5]))
+        .is_err());
+    assert!(editor.set_vertex(4, Point2::origin()).is_err());
+    assert!(editor.remove_cells(&[0, 1]).is_err());
+    assert_eq!(editor.geometry_revision(), 0);
+    assert_eq!(editor.mesh(), &mesh);
+}
@@ -0,0 +1,94 @@
+use fenris::connectivity::Tri3d3Connectivity;
+use fenris::mesh::features::{find_feature_corners, find_feature_edges};
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::TriangleMesh3d;
+use nalgebra::point;
+use std::f64::consts::PI;
+
+fn unit_square_surface(cells_per_dim: usize) -> TriangleMesh3d<f64> {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(cells_per_dim);
+    let vertices = mesh
+        .vertices()
+        .iter()
+        .map(|v| point![v.x, v.y, 0.0])
+        .collect();
+    let connectivity = mesh
+        .connectivity()
+        .iter()
+        .map(|conn| Tri3d3Connectivity(conn.0))
+        .collect();
+    TriangleMesh3d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+fn is_on_unit_cube_edge(x: &nalgebra::Point3<f64>) -> bool {
+    let on_face = |c: f64| c.abs() < 1e-12 || (c - 1.0).abs() < 1e-12;
+    x.iter().filter(|&&c| on_face(c)).count() >= 2
+}
+
+#[test]
+fn feature_edges_and_corners_of_box_surface() {
+    let m = 2;
+    let mut surface = create_unit_box_uniform_tet_mesh_3d::<f64>(m).extract_surface_mesh();
+    let angle = PI / 6.0;
+
+    let edges = surface.tag_feature_edges("edges", angle).to_vec();
+    assert_eq!(edges.len(), 12 * m);
+    for [a, b] in &edges {
+        assert!(a < b);
+        assert!(is_on_unit_cube_edge(&surface.vertices()[*a]));
+        assert!(is_on_unit_cube_edge(&surface.vertices()[*b]));
+    }
+
+    let corners = surface.tag_feature_corners("corners", angle).to_vec();
+    assert_eq!(corners.len(), 8);
+    for &corner in &corners {
+        assert!(surface.vertices()[corner]
+            .iter()
+            .all(|&c| c.abs() < 1e-12 || (c - 1.0).abs() < 1e-12));
+    }
+
+    let sets = surface.sets();
+    assert_eq!(sets.edge_set("edges").unwrap(), edges.as_slice());
+    assert_eq!(sets.node_set("corners").unwrap(), corners.as_slice());
+    assert_eq!(sets.edge_set_nodes("edges").unwrap().len(), 8 + 12 * (m - 1));
+
+    // The dihedral angles of the box are all 90 degrees
+    assert!(find_feature_edges(&surface, 0.51 * PI).is_empty());
+}
+
+#[test]
+fn feature_edges_of_open_surface() {
+    let m = 3;
+    let mut surface = unit_square_surface(m);
+    // Flip the orientation of one triangle, which must not introduce feature edges
+    surface = {
+        let mut connectivity = surface.connectivity().to_vec();
+        connectivity[0].0.swap(1, 2);
+        TriangleMesh3d::from_vertices_and_connectivity(surface.vertices().to_vec(), connectivity)
+    };
+
+    // Boundary edges are always feature edges
+    let edges = find_feature_edges(&surface, PI);
+    assert_eq!(edges.len(), 4 * m);
+
+    // The boundary curve only has kinks at the corners of the square
+    let corners = find_feature_corners(&surface, &edges, PI / 4.0);
+    let corner_positions: Vec<_> = corners.iter().map(|&i| surface.vertices()[i]).collect();
+    assert_eq!(corners.len(), 4);
+    for x in corner_positions {
+        assert!((x.x == 0.0 || x.x == 1.0) && (x.y == 0.0 || x.y == 1.0));
+    }
+    assert!(find_feature_corners(&surface, &edges, PI).is_empty());
+
+    // Folding the square along the line x = 1/3 introduces a feature edge along the fold
+    surface.transform_vertices(|v| {
+        if v.x > 1.0 / 3.0 + 1e-12 {
+            v.z = v.x - 1.0 / 3.0;
+        }
+    });
+    let edges = find_feature_edges(&surface, PI / 8.0);
+    assert_eq!(edges.len(), 5 * m);
+    assert_eq!(find_feature_edges(&surface, PI / 3.0).len(), 4 * m);
+    // The end points of the fold are now junctions of three feature edges
+    assert_eq!(find_feature_corners(&surface, &edges, PI / 8.0).len(), 6);
+}
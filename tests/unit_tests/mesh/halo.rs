@@ -0,0 +1,132 @@
+use fenris::connectivity::Connectivity;
+use fenris::mesh::halo::{ChannelTransport, NodeHalo};
+use fenris::mesh::partition::partition_elements_by_coordinate_bisection;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::DVector;
+
+#[test]
+fn node_halos_assign_each_node_to_a_single_owner() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let partition = partition_elements_by_coordinate_bisection(&mesh, 4);
+    let halos = NodeHalo::from_element_partition(&mesh, &partition).unwrap();
+    assert_eq!(halos.len(), 4);
+
+    let mut owners = vec![None; mesh.vertices().len()];
+    for halo in &halos {
+        for &node in halo.owned_nodes() {
+            assert_eq!(owners[node], None, "Node {node} has several owners");
+            owners[node] = Some(halo.partition());
+        }
+    }
+    assert!(owners.iter().all(Option::is_some));
+
+    for halo in &halos {
+        // Ghosts are owned by partitions with a smaller index
+        for &node in halo.ghost_nodes() {
+            assert!(owners[node].unwrap() < halo.partition());
+        }
+
+        // Send and receive lists of neighboring partitions must refer to the same global nodes
+        for neighbor in halo.neighbors() {
+            let other = &halos[neighbor.partition()];
+            let other_neighbor = other
+                .neighbors()
+                .iter()
+                .find(|n| n.partition() == halo.partition())
+                .unwrap();
+            let to_global = |halo: &NodeHalo, nodes: &[usize]| -> Vec<usize> {
+                nodes.iter().map(|&i| halo.global_nodes()[i]).collect()
+            };
+            assert_eq!(
+                to_global(halo, neighbor.send_nodes()),
+                to_global(other, other_neighbor.receive_nodes())
+            );
+            assert!(neighbor
+                .send_nodes()
+                .iter()
+                .all(|&i| i < halo.num_owned_nodes()));
+        }
+
+        let local_mesh = halo.extract_local_mesh(&mesh);
+        assert_eq!(local_mesh.vertices().len(), halo.num_local_nodes());
+        for (local_conn, &element) in local_mesh.connectivity().iter().zip(halo.elements()) {
+            for (&local, &global) in local_conn
+                .vertex_indices()
+                .iter()
+                .zip(mesh.connectivity()[element].vertex_indices())
+            {
+                assert_eq!(halo.global_nodes()[local], global);
+                assert_eq!(local_mesh.vertices()[local], mesh.vertices()[global]);
+            }
+        }
+    }
+
+    assert!(NodeHalo::from_element_partition(&mesh, &partition[1..]).is_err());
+}
+
+#[test]
+fn halo_exchange_and_accumulation_across_threads() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(5);
+    let num_nodes = mesh.vertices().len();
+    let partition = partition_elements_by_coordinate_bisection(&mesh, 3);
+    let halos = NodeHalo::from_element_partition(&mesh, &partition).unwrap();
+    let transports = ChannelTransport::create_group(halos.len());
+
+    // A vector field with two components, and the number of elements adjacent to each node
+    let u_global = DVector::from_fn(2 * num_nodes, |i, _| i as f64 + 0.5);
+    let mut valence_global = DVector::<f64>::zeros(num_nodes);
+    for conn in mesh.connectivity() {
+        for &node in conn.vertex_indices() {
+            valence_global[node] += 1.0;
+        }
+    }
+
+    let mut u_result = DVector::zeros(2 * num_nodes);
+    let mut valence_result = DVector::zeros(num_nodes);
+    let locals: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = halos
+            .iter()
+            .zip(transports)
+            .map(|(halo, mut transport)| {
+                let (mesh, u_global) = (&mesh, &u_global);
+                scope.spawn(move || {
+                    // Only owned values are known initially
+                    let mut u = halo.gather_from_global(u_global, 2);
+                    let num_owned = 2 * halo.num_owned_nodes();
+                    u.rows_mut(num_owned, u.len() - num_owned).fill(-1.0);
+                    halo.exchange(&mut transport, &mut u, 2).unwrap();
+
+                    let local_mesh = halo.extract_local_mesh(mesh);
+                    let mut valence = DVector::zeros(halo.num_local_nodes());
+                    for conn in local_mesh.connectivity() {
+                        for &node in conn.vertex_indices() {
+                            valence[node] += 1.0;
+                        }
+                    }
+                    halo.accumulate(&mut transport, &mut valence, 1).unwrap();
+                    halo.exchange(&mut transport, &mut valence, 1).unwrap();
+                    (u, valence)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    for (halo, (u, valence)) in halos.iter().zip(&locals) {
+        assert_eq!(u, &halo.gather_from_global(&u_global, 2));
+        assert_eq!(valence, &halo.gather_from_global(&valence_global, 1));
+        halo.scatter_owned_to_global(u, &mut u_result, 2);
+        halo.scatter_owned_to_global(valence, &mut valence_result, 1);
+    }
+    assert_eq!(u_result, u_global);
+    assert_eq!(valence_result, valence_global);
+
+    // A transport must belong to the same partition as the halo
+    let mut transports = ChannelTransport::create_group(halos.len());
+    let mut u = halos[0].gather_from_global(&u_global, 2);
+    assert!(halos[0].exchange(&mut transports[1], &mut u, 2).is_err());
+}
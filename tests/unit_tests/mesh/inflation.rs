@@ -0,0 +1,103 @@
+use fenris::connectivity::{Connectivity, Tri3d3Connectivity};
+use fenris::mesh::inflation::{boundary_layer_thicknesses, inflate_boundary_layers};
+use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
+use fenris::mesh::{Tet4Mesh, TriangleMesh3d};
+use matrixcompare::assert_scalar_eq;
+use nalgebra::{point, Matrix3};
+
+/// Returns the unit square in the xy-plane, with triangles oriented such that the normal
+/// points in the positive z-direction.
+fn unit_square_surface(cells_per_dim: usize) -> TriangleMesh3d<f64> {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(cells_per_dim);
+    let vertices = mesh
+        .vertices()
+        .iter()
+        .map(|v| point![v.x, v.y, 0.0])
+        .collect();
+    let connectivity = mesh
+        .connectivity()
+        .iter()
+        .map(|conn| Tri3d3Connectivity(conn.0))
+        .collect();
+    TriangleMesh3d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+fn signed_tet_volumes(mesh: &Tet4Mesh<f64>) -> Vec<f64> {
+    mesh.connectivity()
+        .iter()
+        .map(|conn| {
+            let [a, b, c, d] = conn.0.map(|i| mesh.vertices()[i]);
+            Matrix3::from_columns(&[b - a, c - a, d - a]).determinant() / 6.0
+        })
+        .collect()
+}
+
+#[test]
+fn boundary_layer_thicknesses_grow_geometrically() {
+    let thicknesses = boundary_layer_thicknesses(0.1, 2.0, 4);
+    assert_eq!(thicknesses, vec![0.1, 0.2, 0.4, 0.8]);
+    assert!(boundary_layer_thicknesses(0.1, 2.0, 0).is_empty());
+}
+
+#[test]
+fn inflate_boundary_layers_from_flat_surface() {
+    let m = 3;
+    let surface = unit_square_surface(m);
+    // Make sure that the surface normal points in the positive z-direction
+    let [a, b, c] = surface.connectivity()[0].0.map(|i| surface.vertices()[i]);
+    assert!((b - a).cross(&(c - a)).z > 0.0);
+
+    let num_layers = 4;
+    let mesh = inflate_boundary_layers(&surface, 0.01, 1.5, num_layers);
+    let n = surface.vertices().len();
+    assert_eq!(mesh.vertices().len(), (num_layers + 1) * n);
+    assert_eq!(mesh.connectivity().len(), 3 * num_layers * surface.connectivity().len());
+
+    // Vertices of each layer are offset along the normal
+    let mut offset = 0.0;
+    let thicknesses = boundary_layer_thicknesses(0.01, 1.5, num_layers);
+    for layer in 0..=num_layers {
+        for i in 0..n {
+            let v = mesh.vertices()[layer * n + i];
+            let x = surface.vertices()[i];
+            assert_scalar_eq!(v.x, x.x, comp = abs, tol = 1e-14);
+            assert_scalar_eq!(v.y, x.y, comp = abs, tol = 1e-14);
+            assert_scalar_eq!(v.z, offset, comp = abs, tol = 1e-14);
+        }
+        offset += thicknesses.get(layer).copied().unwrap_or(0.0);
+    }
+
+    let volumes = signed_tet_volumes(&mesh);
+    assert!(volumes.iter().all(|&volume| volume > 0.0));
+    let total_thickness: f64 = thicknesses.iter().sum();
+    assert_scalar_eq!(volumes.iter().sum::<f64>(), total_thickness, comp = abs, tol = 1e-14);
+
+    // The mesh is conforming if every interior face is shared by exactly two tetrahedra, so that
+    // the boundary consists of the bottom and top surfaces and the sides of the layers
+    let num_boundary_faces = mesh.find_boundary_faces().len();
+    assert_eq!(num_boundary_faces, 2 * (2 * m * m) + 4 * m * num_layers * 2);
+}
+
+#[test]
+fn inflate_boundary_layers_follows_surface_orientation() {
+    let mut surface = unit_square_surface(2);
+    let connectivity: Vec<_> = surface
+        .connectivity()
+        .iter()
+        .map(|Tri3d3Connectivity([a, b, c])| Tri3d3Connectivity([*a, *c, *b]))
+        .collect();
+    surface = TriangleMesh3d::from_vertices_and_connectivity(surface.vertices().to_vec(), connectivity);
+
+    let mesh = inflate_boundary_layers(&surface, 0.1, 1.0, 2);
+    assert!(mesh
+        .vertices()
+        .iter()
+        .all(|v| v.z <= 0.0 && v.z >= -0.2 - 1e-14));
+    assert!(signed_tet_volumes(&mesh).iter().all(|&volume| volume > 0.0));
+    let vertex_count: usize = mesh
+        .connectivity()
+        .iter()
+        .map(|conn| conn.vertex_indices().len())
+        .sum();
+    assert_eq!(vertex_count, 4 * mesh.connectivity().len());
+}
@@ -0,0 +1,46 @@
+use fenris::mesh::partition::partition_elements_by_coordinate_bisection;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+
+#[test]
+fn coordinate_bisection_partitions_unit_square_into_quadrants() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let partition = partition_elements_by_coordinate_bisection(&mesh, 4);
+    assert_eq!(partition.len(), 16);
+    for part in 0..4 {
+        let elements: Vec<_> = (0..16).filter(|&e| partition[e] == part).collect();
+        assert_eq!(elements.len(), 4);
+
+        // Each part is a 2x2 block of elements, i.e. its vertices span half the square
+        let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+        for &element in &elements {
+            for &v in &mesh.connectivity()[element].0 {
+                let p = mesh.vertices()[v];
+                for i in 0..2 {
+                    min[i] = min[i].min(p[i]);
+                    max[i] = max[i].max(p[i]);
+                }
+            }
+        }
+        assert_eq!([max[0] - min[0], max[1] - min[1]], [0.5, 0.5]);
+    }
+}
+
+#[test]
+fn coordinate_bisection_balances_part_sizes() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(5);
+    for num_parts in 1..=7 {
+        let partition = partition_elements_by_coordinate_bisection(&mesh, num_parts);
+        let mut sizes = vec![0; num_parts];
+        for &part in &partition {
+            sizes[part] += 1;
+        }
+        let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+        assert!(max - min <= 2, "Unbalanced part sizes {sizes:?}");
+        assert_eq!(partition, partition_elements_by_coordinate_bisection(&mesh, num_parts));
+    }
+
+    // More parts than elements
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+    assert_eq!(partition_elements_by_coordinate_bisection(&mesh, 3).len(), 1);
+}
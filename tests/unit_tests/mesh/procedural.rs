@@ -1,18 +1,30 @@
+use fenris::allocators::DimAllocator;
 use fenris::assembly::global::assemble_scalar;
-use fenris::connectivity::Connectivity;
+use fenris::connectivity::{CellConnectivity, Connectivity};
+use fenris::connectivity::{
+    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity, Tri6d2Connectivity,
+};
 use fenris::element::{ElementConnectivity, FiniteElement, SurfaceFiniteElement};
 use fenris::integrate::{dependency::NoDeps, FnFunction, UFunction};
 use fenris::integrate::{integrate_over_element, volume_form, ElementIntegralAssemblerBuilder};
 use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
-use fenris::mesh::procedural::{create_rectangular_uniform_hex_mesh, create_rectangular_uniform_tet_mesh};
+use fenris::mesh::procedural::{
+    create_annulus_mesh_2d, create_cylinder_shell_mesh_3d, create_rectangular_uniform_hex_mesh,
+    create_rectangular_uniform_tet10_mesh_3d, create_rectangular_uniform_tet_mesh,
+    create_rectangular_uniform_tet_mesh_3d, create_spherical_shell_mesh_3d, CubeTetSplit,
+};
+use fenris::mesh::Mesh;
 use fenris::quadrature::CanonicalMassQuadrature;
 use fenris::quadrature::Quadrature;
 use fenris::util::global_vector_from_point_fn;
+use fenris::SmallDim;
 use fenris_geometry::AxisAlignedBoundingBox3d;
 use matrixcompare::prop_assert_scalar_eq;
 use nalgebra::coordinates::XYZ;
-use nalgebra::{dvector, vector, Point3, Vector1, Vector3, Vector4, U1};
+use nalgebra::{dvector, vector, DefaultAllocator, OPoint, Point2, Point3, Vector1, Vector3, Vector4, U1, U3};
 use proptest::prelude::*;
+use std::f64::consts::PI;
 use std::path::PathBuf;
 
 #[test]
@@ -29,6 +41,221 @@ fn rectangular_uniform_tet_mesh_basics() {
     }
 }
 
+#[test]
+fn rectangular_uniform_tet_mesh_3d_is_conforming_and_positively_oriented() {
+    let extents = vector![2.0, 1.0, 0.5];
+    let resolutions = [3, 2, 2];
+    let num_cubes = 12;
+    // Each face of the box is split into two triangles per grid square
+    let num_boundary_faces = 2 * 2 * (3 * 2 + 2 * 2 + 3 * 2);
+    for (split, tets_per_cube) in [(CubeTetSplit::Six, 6), (CubeTetSplit::Five, 5)] {
+        let mesh = create_rectangular_uniform_tet_mesh_3d(&extents, resolutions, split);
+        assert_eq!(mesh.vertices().len(), 4 * 3 * 3);
+        assert_eq!(mesh.connectivity().len(), tets_per_cube * num_cubes);
+
+        let aabb = AxisAlignedBoundingBox3d::from_points(mesh.vertices()).unwrap();
+        assert_eq!(aabb.min(), &Point3::origin());
+        assert_eq!(aabb.max(), &Point3::from(extents));
+
+        // Non-matching face diagonals of neighboring cubes would show up as boundary faces
+        assert_eq!(mesh.find_boundary_faces().len(), num_boundary_faces);
+
+        let mut volume = 0.0f64;
+        for connectivity in mesh.connectivity() {
+            let element = connectivity.element(mesh.vertices()).unwrap();
+            let j_det = element.reference_jacobian(&Point3::origin()).determinant();
+            assert!(j_det > 0.0, "element is inverted");
+            // The reference tetrahedron has volume 4 / 3
+            volume += 4.0 / 3.0 * j_det;
+        }
+        assert!((volume - 1.0).abs() < 1e-12);
+
+        let tet10_mesh = create_rectangular_uniform_tet10_mesh_3d(&extents, resolutions, split);
+        assert_eq!(tet10_mesh.connectivity().len(), mesh.connectivity().len());
+        assert_eq!(tet10_mesh.find_boundary_faces().len(), num_boundary_faces);
+        let (_, points) = fenris::quadrature::total_order::tetrahedron::<f64>(2).unwrap();
+        for connectivity in tet10_mesh.connectivity() {
+            let element = connectivity.element(tet10_mesh.vertices()).unwrap();
+            for xi in &points {
+                assert!(element.reference_jacobian(xi).determinant() > 0.0);
+            }
+        }
+    }
+
+    let empty = create_rectangular_uniform_tet_mesh_3d(&extents, [2, 0, 1], CubeTetSplit::Five);
+    assert!(empty.vertices().is_empty());
+    assert!(empty.connectivity().is_empty());
+}
+
+/// Computes the volume of the mesh, checking that the Jacobian determinant is positive at
+/// every quadrature point.
+fn checked_mesh_volume<D, C>(mesh: &Mesh<f64, D, C>, (weights, points): &(Vec<f64>, Vec<OPoint<f64, D>>)) -> f64
+where
+    D: SmallDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let mut volume = 0.0;
+    for connectivity in mesh.connectivity() {
+        let element = connectivity.element(mesh.vertices()).unwrap();
+        for (w, xi) in weights.iter().zip(points) {
+            let j_det = element.reference_jacobian(xi).determinant();
+            assert!(j_det > 0.0, "element is inverted");
+            volume += w * j_det;
+        }
+    }
+    volume
+}
+
+/// Checks that all nodes on the boundary of the mesh have one of the given distances to the
+/// center, as computed by `radius`.
+fn assert_boundary_nodes_on_radii<D, C>(mesh: &Mesh<f64, D, C>, radius: impl Fn(&OPoint<f64, D>) -> f64, radii: &[f64])
+where
+    D: SmallDim,
+    C: CellConnectivity<f64, D>,
+    C::FaceConnectivity: Connectivity,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    for (face, _, _) in mesh.find_boundary_faces() {
+        for &v in face.vertex_indices() {
+            let r = radius(&mesh.vertices()[v]);
+            let on_any_radius = radii
+                .iter()
+                .any(|r_expected| (r - r_expected).abs() < 1e-12);
+            assert!(
+                on_any_radius,
+                "boundary node with radius {r} is not on a boundary surface"
+            );
+        }
+    }
+}
+
+fn boundary_face_nodes<C>(mesh: &Mesh<f64, U3, C>) -> Vec<Vec<usize>>
+where
+    C: CellConnectivity<f64, U3>,
+    C::FaceConnectivity: Connectivity,
+{
+    mesh.find_boundary_faces()
+        .into_iter()
+        .map(|(face, _, _)| face.vertex_indices().to_vec())
+        .collect()
+}
+
+#[test]
+fn annulus_mesh_2d_is_conforming_with_nodes_on_boundary() {
+    let (r_inner, r_outer) = (1.0, 2.0);
+    let exact_area = PI * (r_outer * r_outer - r_inner * r_inner);
+    let quad_quadrature = fenris::quadrature::tensor::quadrilateral_gauss(3);
+    let tri_quadrature = fenris::quadrature::total_order::triangle(4).unwrap();
+    let radius = |v: &Point2<f64>| v.coords.norm();
+    // Only the inner and outer circles are part of the boundary
+    let num_boundary_faces = 2 * 16;
+
+    let quad4_mesh = create_annulus_mesh_2d::<f64, Quad4d2Connectivity>(r_inner, r_outer, 2, 16);
+    assert_eq!(quad4_mesh.vertices().len(), 3 * 16);
+    assert_eq!(quad4_mesh.connectivity().len(), 2 * 16);
+    assert_eq!(quad4_mesh.find_boundary_faces().len(), num_boundary_faces);
+    assert_boundary_nodes_on_radii(&quad4_mesh, radius, &[r_inner, r_outer]);
+    let quad4_area = checked_mesh_volume(&quad4_mesh, &quad_quadrature);
+
+    let quad9_mesh = create_annulus_mesh_2d::<f64, Quad9d2Connectivity>(r_inner, r_outer, 2, 16);
+    assert_eq!(quad9_mesh.vertices().len(), 5 * 32);
+    assert_eq!(quad9_mesh.find_boundary_faces().len(), num_boundary_faces);
+    assert_boundary_nodes_on_radii(&quad9_mesh, radius, &[r_inner, r_outer]);
+    checked_mesh_volume(&quad9_mesh, &quad_quadrature);
+
+    let tri3_mesh = create_annulus_mesh_2d::<f64, Tri3d2Connectivity>(r_inner, r_outer, 2, 16);
+    assert_eq!(tri3_mesh.vertices().len(), 3 * 16);
+    assert_eq!(tri3_mesh.find_boundary_faces().len(), num_boundary_faces);
+    let tri3_area = checked_mesh_volume(&tri3_mesh, &tri_quadrature);
+
+    let tri6_mesh = create_annulus_mesh_2d::<f64, Tri6d2Connectivity>(r_inner, r_outer, 2, 16);
+    assert_eq!(tri6_mesh.vertices().len(), 5 * 32);
+    assert_eq!(tri6_mesh.find_boundary_faces().len(), num_boundary_faces);
+    assert_boundary_nodes_on_radii(&tri6_mesh, radius, &[r_inner, r_outer]);
+    checked_mesh_volume(&tri6_mesh, &tri_quadrature);
+
+    // The area of the inscribed polygons converges to the area of the annulus
+    assert!((quad4_area - tri3_area).abs() < 1e-12);
+    assert!(quad4_area < exact_area && (exact_area - quad4_area) / exact_area < 3e-2);
+
+    let empty = create_annulus_mesh_2d::<f64, Quad9d2Connectivity>(r_inner, r_outer, 0, 16);
+    assert!(empty.vertices().is_empty());
+    assert!(empty.connectivity().is_empty());
+}
+
+#[test]
+fn cylinder_shell_mesh_3d_is_conforming_with_nodes_on_boundary() {
+    let (r_inner, r_outer, height) = (1.0, 1.5, 2.0);
+    let exact_volume = PI * (r_outer * r_outer - r_inner * r_inner) * height;
+    let [nr, nt, nz] = [2, 12, 3];
+    let hex_quadrature = fenris::quadrature::tensor::hexahedron_gauss(2);
+    let tet_quadrature = fenris::quadrature::total_order::tetrahedron(2).unwrap();
+    let num_boundary_quads = 2 * nt * nz + 2 * nr * nt;
+    // Nodes on the curved boundaries must lie on the cylinders, nodes on the top and
+    // bottom must lie on the planes
+    let check_boundary_nodes = |vertices: &[Point3<f64>], faces: Vec<Vec<usize>>| {
+        for face in faces {
+            let on_curved = face.iter().all(|&v| {
+                let r = vertices[v].xy().coords.norm();
+                (r - r_inner).abs() < 1e-12 || (r - r_outer).abs() < 1e-12
+            });
+            let on_planar = face.iter().all(|&v| vertices[v].z.abs() < 1e-12)
+                || face.iter().all(|&v| (vertices[v].z - height).abs() < 1e-12);
+            assert!(on_curved || on_planar);
+        }
+    };
+
+    let hex8_mesh = create_cylinder_shell_mesh_3d::<f64, Hex8Connectivity>(r_inner, r_outer, height, nr, nt, nz);
+    assert_eq!(hex8_mesh.vertices().len(), (nr + 1) * nt * (nz + 1));
+    assert_eq!(hex8_mesh.connectivity().len(), nr * nt * nz);
+    assert_eq!(hex8_mesh.find_boundary_faces().len(), num_boundary_quads);
+    let hex8_volume = checked_mesh_volume(&hex8_mesh, &hex_quadrature);
+
+    let hex27_mesh = create_cylinder_shell_mesh_3d::<f64, Hex27Connectivity>(r_inner, r_outer, height, nr, nt, nz);
+    assert_eq!(hex27_mesh.vertices().len(), (2 * nr + 1) * 2 * nt * (2 * nz + 1));
+    check_boundary_nodes(hex27_mesh.vertices(), boundary_face_nodes(&hex27_mesh));
+    checked_mesh_volume(&hex27_mesh, &hex_quadrature);
+
+    let tet4_mesh = create_cylinder_shell_mesh_3d::<f64, Tet4Connectivity>(r_inner, r_outer, height, nr, nt, nz);
+    assert_eq!(tet4_mesh.vertices().len(), hex8_mesh.vertices().len());
+    assert_eq!(tet4_mesh.connectivity().len(), 6 * nr * nt * nz);
+    assert_eq!(tet4_mesh.find_boundary_faces().len(), 2 * num_boundary_quads);
+    let tet4_volume = checked_mesh_volume(&tet4_mesh, &tet_quadrature);
+
+    let tet10_mesh = create_cylinder_shell_mesh_3d::<f64, Tet10Connectivity>(r_inner, r_outer, height, nr, nt, nz);
+    assert_eq!(tet10_mesh.find_boundary_faces().len(), 2 * num_boundary_quads);
+    check_boundary_nodes(tet10_mesh.vertices(), boundary_face_nodes(&tet10_mesh));
+    checked_mesh_volume(&tet10_mesh, &tet_quadrature);
+
+    assert!((hex8_volume - tet4_volume).abs() < 1e-12);
+    assert!(hex8_volume < exact_volume && (exact_volume - hex8_volume) / exact_volume < 5e-2);
+}
+
+#[test]
+fn spherical_shell_mesh_3d_is_conforming_with_nodes_on_boundary() {
+    let (r_inner, r_outer) = (0.5f64, 1.0f64);
+    let exact_volume = 4.0 / 3.0 * PI * (r_outer.powi(3) - r_inner.powi(3));
+    let (nr, n) = (2, 3);
+    let quadrature = fenris::quadrature::tensor::hexahedron_gauss(2);
+    let radius = |v: &Point3<f64>| v.coords.norm();
+
+    let hex8_mesh = create_spherical_shell_mesh_3d::<f64, Hex8Connectivity>(r_inner, r_outer, nr, n);
+    // The cubed sphere has 6 n^2 + 2 vertices on each spherical surface
+    assert_eq!(hex8_mesh.vertices().len(), (nr + 1) * (6 * n * n + 2));
+    assert_eq!(hex8_mesh.connectivity().len(), 6 * n * n * nr);
+    assert_eq!(hex8_mesh.find_boundary_faces().len(), 2 * 6 * n * n);
+    assert_boundary_nodes_on_radii(&hex8_mesh, radius, &[r_inner, r_outer]);
+    let hex8_volume = checked_mesh_volume(&hex8_mesh, &quadrature);
+    assert!(hex8_volume < exact_volume && (exact_volume - hex8_volume) / exact_volume < 0.15);
+
+    let hex27_mesh = create_spherical_shell_mesh_3d::<f64, Hex27Connectivity>(r_inner, r_outer, nr, n);
+    assert_eq!(hex27_mesh.vertices().len(), (2 * nr + 1) * (6 * 4 * n * n + 2));
+    assert_eq!(hex27_mesh.find_boundary_faces().len(), 2 * 6 * n * n);
+    assert_boundary_nodes_on_radii(&hex27_mesh, radius, &[r_inner, r_outer]);
+    checked_mesh_volume(&hex27_mesh, &quadrature);
+}
+
 fn empty_tet_mesh_params() -> impl Strategy<Value = [usize; 4]> {
     let strategy = prop_oneof![Just(0), 0usize..3];
     [strategy.clone(), strategy.clone(), strategy.clone(), strategy]
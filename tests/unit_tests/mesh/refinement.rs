@@ -1,9 +1,15 @@
 use crate::export_mesh_vtk;
-use fenris::connectivity::Tri3d2Connectivity;
-use fenris::mesh::refinement::{refine_uniformly, refine_uniformly_repeat};
-use fenris::mesh::Mesh;
+use fenris::connectivity::{Connectivity, Tet4Connectivity, Tri3d2Connectivity};
+use fenris::error::assemble_nested_prolongation;
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::refinement::{
+    refine_marked_cells, refine_uniformly, refine_uniformly_repeat, refine_uniformly_with_prolongation,
+    UniformRefinementHierarchy,
+};
+use fenris::mesh::{Mesh, Mesh2d, Mesh3d};
 use insta::assert_debug_snapshot;
-use nalgebra::point;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use nalgebra::{point, DVector, Point2, Point3};
 
 #[test]
 fn uniform_refinement_tri3d2() {
@@ -38,3 +44,208 @@ fn uniform_refinement_tri3d2() {
     assert_debug_snapshot!(refined1);
     assert_debug_snapshot!(refined2);
 }
+
+#[test]
+fn uniform_refinement_prolongation_tri3d2() {
+    let mesh: Mesh2d<f64, Tri3d2Connectivity> = create_unit_square_uniform_tri_mesh_2d(3);
+    let (refined, prolongation) = refine_uniformly_with_prolongation(&mesh, 2);
+    assert_eq!(refined, refine_uniformly(&mesh));
+    assert_eq!(prolongation.nrows(), 2 * refined.vertices().len());
+    assert_eq!(prolongation.ncols(), 2 * mesh.vertices().len());
+
+    // Each coarse cell is split into four consecutive children, so the prolongation must agree
+    // with the one obtained by locating the fine vertices in the coarse mesh
+    let fine_to_coarse: Vec<usize> = (0..refined.connectivity().len()).map(|i| i / 4).collect();
+    let expected = assemble_nested_prolongation(&mesh, &refined, &fine_to_coarse, 2).unwrap();
+    assert_matrix_eq!(prolongation, expected, comp = abs, tol = 1e-14);
+
+    let linear = |p: &Point2<f64>| [2.0 * p.x - p.y, 1.0 + 3.0 * p.y];
+    let values = DVector::from_iterator(2 * mesh.vertices().len(), mesh.vertices().iter().flat_map(linear));
+    let expected_fine =
+        DVector::from_iterator(2 * refined.vertices().len(), refined.vertices().iter().flat_map(linear));
+    assert_matrix_eq!(&prolongation * &values, expected_fine, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn uniform_refinement_hierarchy_tri3d2() {
+    let mesh: Mesh2d<f64, Tri3d2Connectivity> = create_unit_square_uniform_tri_mesh_2d(2);
+    let hierarchy = UniformRefinementHierarchy::new(mesh.clone(), 2);
+    assert_eq!(hierarchy.num_levels(), 3);
+    assert_eq!(hierarchy.mesh(0), &mesh);
+    assert_eq!(hierarchy.finest_mesh(), &refine_uniformly_repeat(&mesh, 2));
+
+    for level in 0..2 {
+        let coarse = hierarchy.mesh(level);
+        let fine = hierarchy.mesh(level + 1);
+        let (_, expected) = refine_uniformly_with_prolongation(coarse, 3);
+        let prolongation = hierarchy.prolongation(level, 3);
+        assert_eq!(prolongation, expected);
+        assert_eq!(hierarchy.restriction(level, 3), prolongation.transpose());
+        assert_eq!(hierarchy.prolongation(level, 1).nrows(), fine.vertices().len());
+    }
+
+    // Constants are preserved across all levels
+    let ones = DVector::repeat(mesh.vertices().len(), 1.0);
+    let fine_values = hierarchy.prolongation(1, 1) * (hierarchy.prolongation(0, 1) * ones);
+    assert_matrix_eq!(
+        fine_values,
+        DVector::repeat(hierarchy.finest_mesh().vertices().len(), 1.0),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+fn triangle_signed_area(mesh: &Mesh2d<f64, Tri3d2Connectivity>, cell: &Tri3d2Connectivity) -> f64 {
+    let [a, b, c] = cell.0.map(|i| mesh.vertices()[i]);
+    0.5 * (b - a).perp(&(c - a))
+}
+
+fn triangle_min_angle(mesh: &Mesh2d<f64, Tri3d2Connectivity>, cell: &Tri3d2Connectivity) -> f64 {
+    let [a, b, c] = cell.0.map(|i| mesh.vertices()[i]);
+    let angle = |p: Point2<f64>, q: Point2<f64>, r: Point2<f64>| (q - p).angle(&(r - p));
+    angle(a, b, c).min(angle(b, c, a)).min(angle(c, a, b))
+}
+
+fn tet_signed_volume(mesh: &Mesh3d<f64, Tet4Connectivity>, cell: &Tet4Connectivity) -> f64 {
+    let [a, b, c, d] = cell.0.map(|i| mesh.vertices()[i]);
+    (b - a).cross(&(c - a)).dot(&(d - a)) / 6.0
+}
+
+/// The total length of the boundary faces, which exceeds the perimeter of the domain if the
+/// mesh has hanging nodes.
+fn boundary_length(mesh: &Mesh2d<f64, Tri3d2Connectivity>) -> f64 {
+    mesh.find_boundary_faces()
+        .iter()
+        .map(|(face, _, _)| {
+            let [a, b] = face.vertex_indices() else { unreachable!() };
+            (mesh.vertices()[*a] - mesh.vertices()[*b]).norm()
+        })
+        .sum()
+}
+
+fn boundary_area(mesh: &Mesh3d<f64, Tet4Connectivity>) -> f64 {
+    mesh.find_boundary_faces()
+        .iter()
+        .map(|(face, _, _)| {
+            let [a, b, c]: [Point3<f64>; 3] = face.0.map(|i| mesh.vertices()[i]);
+            0.5 * (b - a).cross(&(c - a)).norm()
+        })
+        .sum()
+}
+
+#[test]
+fn refine_marked_cells_tri3d2_is_conforming() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let refinement = refine_marked_cells(&mesh, &[0, 5, 5, 17]).unwrap();
+    let refined = refinement.mesh();
+
+    assert_eq!(refinement.num_parent_cells(), mesh.connectivity().len());
+    assert_eq!(refinement.num_parent_vertices(), mesh.vertices().len());
+    assert_eq!(&refined.vertices()[..mesh.vertices().len()], mesh.vertices());
+    assert_eq!(
+        refined.vertices().len(),
+        mesh.vertices().len() + refinement.midpoint_edges().len()
+    );
+    assert_scalar_eq!(boundary_length(refined), 4.0, comp = abs, tol = 1e-12);
+
+    // Children are contiguous, cover their parent and preserve orientation
+    assert_eq!(refinement.cell_parents().len(), refined.connectivity().len());
+    for (parent_index, parent) in mesh.connectivity().iter().enumerate() {
+        let children = refinement.children(parent_index);
+        assert!(children
+            .clone()
+            .all(|child| refinement.cell_parents()[child] == parent_index));
+        let child_area: f64 = children
+            .clone()
+            .map(|child| triangle_signed_area(refined, &refined.connectivity()[child]))
+            .sum();
+        assert!(children
+            .clone()
+            .all(|child| triangle_signed_area(refined, &refined.connectivity()[child]) > 0.0));
+        assert_scalar_eq!(child_area, triangle_signed_area(&mesh, parent), comp = abs, tol = 1e-14);
+        if [0, 5, 17].contains(&parent_index) {
+            assert!(children.len() >= 2);
+        }
+    }
+
+    // Linear functions are transferred exactly, cell values are copied to children
+    let linear = |p: &Point2<f64>| [2.0 * p.x - p.y, 1.0 + 3.0 * p.y];
+    let values = DVector::from_iterator(2 * mesh.vertices().len(), mesh.vertices().iter().flat_map(linear));
+    let interpolated = refinement.interpolate_vertex_values(&values, 2);
+    for (i, vertex) in refined.vertices().iter().enumerate() {
+        let [u0, u1] = linear(vertex);
+        assert_scalar_eq!(interpolated[2 * i], u0, comp = abs, tol = 1e-14);
+        assert_scalar_eq!(interpolated[2 * i + 1], u1, comp = abs, tol = 1e-14);
+    }
+    let cell_values: Vec<usize> = (0..mesh.connectivity().len()).collect();
+    assert_eq!(refinement.transfer_cell_values(&cell_values), refinement.cell_parents());
+    let prolongation = refinement.prolongation(2);
+    assert_eq!(prolongation.nrows(), 2 * refined.vertices().len());
+    assert_eq!(prolongation.ncols(), 2 * mesh.vertices().len());
+    assert_matrix_eq!(&prolongation * &values, interpolated, comp = abs, tol = 1e-14);
+    assert_eq!(refinement.restriction(2), prolongation.transpose());
+
+    let unrefined = refine_marked_cells(&mesh, &[]).unwrap();
+    assert_eq!(unrefined.mesh(), &mesh);
+    assert!(refine_marked_cells(&mesh, &[mesh.connectivity().len()]).is_err());
+}
+
+#[test]
+fn refine_marked_cells_tri3d2_preserves_quality() {
+    let mut mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let initial_min_angle = mesh
+        .connectivity()
+        .iter()
+        .map(|cell| triangle_min_angle(&mesh, cell))
+        .fold(f64::INFINITY, f64::min);
+
+    // Repeatedly refine the cells touching the origin to obtain a strongly graded mesh
+    for _ in 0..12 {
+        let marked: Vec<usize> = (0..mesh.connectivity().len())
+            .filter(|&i| mesh.connectivity()[i].0.contains(&0))
+            .collect();
+        let num_cells = mesh.connectivity().len();
+        mesh = refine_marked_cells(&mesh, &marked).unwrap().into_mesh();
+        assert!(mesh.connectivity().len() > num_cells);
+    }
+
+    let min_angle = mesh
+        .connectivity()
+        .iter()
+        .map(|cell| triangle_min_angle(&mesh, cell))
+        .fold(f64::INFINITY, f64::min);
+    assert!(min_angle >= 0.5 * initial_min_angle - 1e-12);
+    assert_scalar_eq!(boundary_length(&mesh), 4.0, comp = abs, tol = 1e-12);
+    let smallest_area = mesh
+        .connectivity()
+        .iter()
+        .map(|cell| triangle_signed_area(&mesh, cell))
+        .fold(f64::INFINITY, f64::min);
+    assert!(smallest_area > 0.0 && smallest_area < 1e-4);
+}
+
+#[test]
+fn refine_marked_cells_tet4_is_conforming() {
+    let mut mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    for round in 0..4 {
+        let marked: Vec<usize> = (0..mesh.connectivity().len())
+            .filter(|&i| i % (round + 3) == 0)
+            .collect();
+        let refinement = refine_marked_cells(&mesh, &marked).unwrap();
+        let refined = refinement.mesh();
+        for (parent_index, parent) in mesh.connectivity().iter().enumerate() {
+            let children = refinement.children(parent_index);
+            let child_volume: f64 = children
+                .clone()
+                .map(|child| tet_signed_volume(refined, &refined.connectivity()[child]))
+                .sum();
+            assert_scalar_eq!(child_volume, tet_signed_volume(&mesh, parent), comp = abs, tol = 1e-14);
+            let parent_sign = tet_signed_volume(&mesh, parent).signum();
+            assert!(children
+                .clone()
+                .all(|child| { tet_signed_volume(refined, &refined.connectivity()[child]).signum() == parent_sign }));
+        }
+        assert_scalar_eq!(boundary_area(refined), 6.0, comp = abs, tol = 1e-12);
+        mesh = refinement.into_mesh();
+    }
+}
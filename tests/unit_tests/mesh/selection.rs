@@ -0,0 +1,101 @@
+use fenris::geometry::AxisAlignedBoundingBox;
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::selection::Region;
+use matrixcompare::assert_scalar_eq;
+use nalgebra::{point, vector, U2};
+
+#[test]
+fn primitive_region_signed_distances() {
+    let ball = Region::ball(point![1.0, 1.0], 0.5);
+    assert_scalar_eq!(ball.signed_distance(&point![1.0, 1.0]), -0.5, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(ball.signed_distance(&point![2.0, 1.0]), 0.5, comp = abs, tol = 1e-14);
+
+    let aabb = Region::aabb(AxisAlignedBoundingBox::new(point![0.0, 0.0], point![2.0, 1.0]));
+    assert_scalar_eq!(aabb.signed_distance(&point![1.0, 0.5]), -0.5, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(aabb.signed_distance(&point![1.8, 0.5]), -0.2, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(aabb.signed_distance(&point![3.0, 0.5]), 1.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(aabb.signed_distance(&point![5.0, 5.0]), 5.0, comp = abs, tol = 1e-14);
+    assert!(aabb.contains(&point![2.0, 1.0]));
+
+    let plane = Region::plane(point![0.0, 1.0], vector![0.0, 3.0], 0.1);
+    assert!(plane.contains(&point![10.0, 1.05]));
+    assert!(!plane.contains(&point![10.0, 0.85]));
+
+    let half_space = Region::half_space(point![1.0, 0.0], vector![1.0, 1.0]);
+    assert!(half_space.contains(&point![0.0, 0.0]));
+    assert!(half_space.contains(&point![0.5, 0.5]));
+    assert!(!half_space.contains(&point![1.0, 0.5]));
+
+    assert!(Region::<f64, U2>::everywhere().contains(&point![1e10, -1e10]));
+    assert!(!Region::<f64, U2>::nowhere().contains(&point![0.0, 0.0]));
+}
+
+#[test]
+fn region_set_operations() {
+    let a = Region::coordinate_below(0, 1.0);
+    let b = Region::coordinate_above(0, 0.0);
+    let inside = point![0.5, 7.0];
+    let left = point![-0.5, 7.0];
+    let right = point![1.5, 7.0];
+
+    let intersection = a.clone() & b.clone();
+    assert!(intersection.contains(&inside));
+    assert!(!intersection.contains(&left) && !intersection.contains(&right));
+
+    let union = a.clone() | b.clone();
+    assert!(union.contains(&inside) && union.contains(&left) && union.contains(&right));
+
+    let difference = a.clone() - b.clone();
+    assert!(difference.contains(&left));
+    assert!(!difference.contains(&inside) && !difference.contains(&right));
+
+    let complement = !a;
+    assert!(complement.contains(&right));
+    assert!(!complement.contains(&left));
+
+    let predicate = Region::from_predicate(|x| x[1] > 5.0).inflated(100.0);
+    assert!(predicate.contains(&inside));
+    assert!(!predicate.contains(&point![0.0, 4.0]));
+
+    let inflated = Region::ball(point![0.0, 0.0], 1.0).inflated(0.5);
+    assert!(inflated.contains(&point![1.4, 0.0]));
+    assert!(!inflated.contains(&point![1.6, 0.0]));
+}
+
+#[test]
+fn select_and_tag_mesh_regions() {
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+
+    let bottom = Region::coordinate_equals(1, 0.0, 1e-12);
+    let nodes = bottom.select_nodes(&mesh);
+    assert_eq!(nodes.len(), 5);
+    assert!(nodes.iter().all(|&i| mesh.vertices()[i].y == 0.0));
+
+    let lower_left = Region::aabb(AxisAlignedBoundingBox::new(point![0.0, 0.0], point![0.5, 0.5]));
+    assert_eq!(lower_left.select_cells(&mesh).len(), 4);
+    assert_eq!(lower_left.select_cells_fully_inside(&mesh).len(), 4);
+    let enlarged = lower_left.clone().inflated(0.1);
+    assert_eq!(enlarged.select_cells(&mesh).len(), 4);
+    assert_eq!(enlarged.select_cells_fully_inside(&mesh).len(), 4);
+    assert_eq!(enlarged.clone().inflated(0.2).select_cells(&mesh).len(), 9);
+
+    mesh.tag_nodes("bottom", bottom.predicate());
+    mesh.tag_cells("lower_left", lower_left.predicate());
+    assert_eq!(mesh.sets().node_set("bottom").unwrap(), nodes.as_slice());
+    assert_eq!(
+        mesh.sets().cell_set("lower_left").unwrap(),
+        lower_left.select_cells(&mesh).as_slice()
+    );
+}
+
+#[test]
+fn select_nodes_on_box_faces_in_3d() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    let tol = 1e-12;
+    let faces = (0..3)
+        .flat_map(|axis| [0.0, 1.0].map(|value| Region::coordinate_equals(axis, value, tol)))
+        .fold(Region::nowhere(), |region, face| region | face);
+    // All nodes except for the center node are on the boundary
+    assert_eq!(faces.select_nodes(&mesh).len(), 26);
+    assert_eq!((!faces).inflated(-2.0 * tol).select_nodes(&mesh).len(), 1);
+}
@@ -0,0 +1,81 @@
+use fenris::assembly::bc::DirichletConditions;
+use fenris::index::DofIndex;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::reorder::reorder_mesh_par;
+use fenris::mesh::sets::CellFace;
+use fenris::mesh::QuadMesh2d;
+
+#[test]
+fn tag_nodes_cells_and_boundary_faces() {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    // Vertices are numbered row by row, starting at the top left corner
+    let left_wall = mesh.tag_nodes("left_wall", |x| x[0] < 1e-12).to_vec();
+    assert_eq!(left_wall, vec![0, 3, 6]);
+    let upper_half = mesh.tag_cells("upper_half", |x| x[1] > 0.5).to_vec();
+    assert_eq!(upper_half, vec![0, 1]);
+    let top = mesh
+        .tag_boundary_faces("top", |x| x[1] > 1.0 - 1e-12)
+        .to_vec();
+    assert_eq!(top.len(), 2);
+    assert!(top.iter().all(|face| face.cell_index < 2));
+    assert_eq!(mesh.face_set_nodes("top").unwrap(), vec![0, 1, 2]);
+
+    assert!(mesh.sets().node_set("top").is_none());
+    assert!(mesh.face_set_nodes("left_wall").is_none());
+    assert_eq!(mesh.sets().node_set_names().collect::<Vec<_>>(), vec!["left_wall"]);
+
+    // Tagging again with the same name replaces the set
+    mesh.tag_nodes("left_wall", |x| x[0] < 1e-12 && x[1] > 0.25);
+    assert_eq!(mesh.sets().node_set("left_wall").unwrap(), &[0, 3]);
+}
+
+#[test]
+fn sets_are_relabeled_when_keeping_cells() {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    mesh.tag_nodes("right_wall", |x| x[0] > 1.0 - 1e-12);
+    mesh.tag_cells("right_half", |x| x[0] > 0.5);
+    mesh.tag_boundary_faces("right", |x| x[0] > 1.0 - 1e-12);
+
+    // Keep the two cells on the right, in reversed order
+    let submesh = mesh.keep_cells(&[3, 1]);
+    assert_eq!(submesh.sets().cell_set("right_half").unwrap(), &[0, 1]);
+    let right_nodes = submesh.sets().node_set("right_wall").unwrap().to_vec();
+    assert_eq!(right_nodes.len(), 3);
+    assert!(right_nodes
+        .iter()
+        .all(|&i| (submesh.vertices()[i].x - 1.0).abs() < 1e-12));
+    assert_eq!(submesh.face_set_nodes("right").unwrap(), right_nodes);
+
+    // Keeping only cells on the left removes everything on the right wall
+    let submesh = mesh.keep_cells(&[0, 2]);
+    assert!(submesh.sets().node_set("right_wall").unwrap().is_empty());
+    assert!(submesh.sets().face_set("right").unwrap().is_empty());
+}
+
+#[test]
+fn sets_are_relabeled_when_reordering() {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    mesh.tag_nodes("bottom_wall", |x| x[1] < 1e-12);
+    mesh.tag_boundary_faces("bottom", |x| x[1] < 1e-12);
+
+    let reordered = reorder_mesh_par(&mesh).apply(&mesh);
+    let bottom_nodes = reordered.sets().node_set("bottom_wall").unwrap();
+    assert_eq!(bottom_nodes.len(), 4);
+    assert!(bottom_nodes
+        .iter()
+        .all(|&i| reordered.vertices()[i].y.abs() < 1e-12));
+    assert_eq!(reordered.face_set_nodes("bottom").unwrap(), bottom_nodes);
+    let faces: &[CellFace] = reordered.sets().face_set("bottom").unwrap();
+    assert_eq!(faces.len(), 3);
+}
+
+#[test]
+fn dirichlet_conditions_from_named_node_set() {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    mesh.tag_nodes("left_wall", |x| x[0] < 1e-12);
+    let n = mesh.vertices().len();
+
+    let bc = DirichletConditions::<f64>::try_homogeneous_node_set(n, 2, mesh.sets(), "left_wall").unwrap();
+    assert_eq!(bc.constrained_dofs(), &[0, 1, 6, 7, 12, 13].map(DofIndex::new));
+    assert!(DirichletConditions::<f64>::try_homogeneous_node_set(n, 2, mesh.sets(), "right_wall").is_err());
+}
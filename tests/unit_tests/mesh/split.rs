@@ -0,0 +1,230 @@
+use fenris::connectivity::{Connectivity, Quad4d2Connectivity, Segment2d2Connectivity};
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::split::{
+    find_interface_faces, find_interior_faces, find_interior_faces_where, split_mesh_along_faces, InteriorFace,
+};
+use fenris::mesh::{QuadMesh2d, Tet10Mesh, Tet4Mesh};
+use itertools::Itertools;
+use nalgebra::{Point2, Point3};
+use std::collections::BTreeSet;
+
+fn cell_vertex_set<C: Connectivity>(cells: impl IntoIterator<Item = C>) -> BTreeSet<usize> {
+    cells
+        .into_iter()
+        .flat_map(|cell| cell.vertex_indices().to_vec())
+        .collect()
+}
+
+fn is_on_line_y(y: f64) -> impl Fn(&[Point2<f64>]) -> bool {
+    move |vertices| vertices.iter().all(|v| (v.y - y).abs() < 1e-12)
+}
+
+#[test]
+fn find_interior_faces_of_quad_mesh() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let faces = find_interior_faces(&mesh);
+    // 2 * 3 interior edges in each direction
+    assert_eq!(faces.len(), 12);
+    for face in &faces {
+        assert!(face.cells[0] < face.cells[1]);
+        let [a, b] = [0, 1].map(|side| {
+            mesh.connectivity()[face.cells[side]]
+                .get_face_connectivity(face.local_faces[side])
+                .unwrap()
+        });
+        assert_eq!(
+            a.vertex_indices().iter().sorted().collect_vec(),
+            b.vertex_indices().iter().sorted().collect_vec()
+        );
+    }
+
+    let horizontal = find_interior_faces_where(&mesh, is_on_line_y(1.0 / 3.0));
+    assert_eq!(horizontal.len(), 3);
+    assert!(horizontal.iter().all(|face| faces.contains(face)));
+}
+
+#[test]
+fn split_quad_mesh_along_line_separates_mesh() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let faces = find_interior_faces_where(&mesh, is_on_line_y(0.5));
+    assert_eq!(faces.len(), 2);
+    let split = split_mesh_along_faces(&mesh, &faces).unwrap();
+
+    // All three vertices on the line are duplicated
+    assert_eq!(split.num_parent_vertices(), 9);
+    assert_eq!(split.num_duplicated_vertices(), 3);
+    assert_eq!(split.mesh().vertices().len(), 12);
+    assert_eq!(split.mesh().connectivity().len(), 4);
+    assert_eq!(&split.parent_vertices()[..9], (0..9).collect_vec().as_slice());
+    for (v, &parent) in split.mesh().vertices().iter().zip(split.parent_vertices()) {
+        assert_eq!(v, &mesh.vertices()[parent]);
+    }
+
+    // The cells below and above the line no longer share vertices, but cells on the same side
+    // remain connected
+    let centroid_y = |cell: &Quad4d2Connectivity| {
+        cell.vertex_indices()
+            .iter()
+            .map(|&v| split.mesh().vertices()[v].y)
+            .sum::<f64>()
+            / 4.0
+    };
+    let (below, above): (Vec<_>, Vec<_>) = split
+        .mesh()
+        .connectivity()
+        .iter()
+        .cloned()
+        .partition(|cell| centroid_y(cell) < 0.5);
+    let below_vertices = cell_vertex_set(below);
+    let above_vertices = cell_vertex_set(above);
+    assert!(below_vertices.is_disjoint(&above_vertices));
+    assert_eq!(below_vertices.len(), 6);
+    assert_eq!(above_vertices.len(), 6);
+
+    // The two sides of each interface face pair up through their parent vertices
+    assert_eq!(split.interface_faces(), faces.as_slice());
+    for i in 0..split.interface_faces().len() {
+        let [a, b] = split.interface_face_connectivities(i);
+        assert!(cell_vertex_set([a]).is_disjoint(&cell_vertex_set([b])));
+        let parents = |face: &Segment2d2Connectivity| {
+            face.vertex_indices()
+                .iter()
+                .map(|&v| split.parent_vertices()[v])
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(parents(&a), parents(&b));
+    }
+}
+
+#[test]
+fn split_quad_mesh_along_crack_keeps_crack_tip() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let faces = find_interior_faces_where(&mesh, |vertices| {
+        is_on_line_y(0.5)(vertices) && vertices.iter().all(|v| v.x <= 0.5 + 1e-12)
+    });
+    assert_eq!(faces.len(), 2);
+    let split = split_mesh_along_faces(&mesh, &faces).unwrap();
+
+    // The vertices at x = 0 and x = 0.25 are duplicated, but not the crack tip at x = 0.5
+    let duplicated: Vec<_> = split.parent_vertices()[split.num_parent_vertices()..]
+        .iter()
+        .map(|&v| mesh.vertices()[v])
+        .sorted_by(|a, b| a.x.partial_cmp(&b.x).unwrap())
+        .collect();
+    assert_eq!(duplicated, vec![Point2::new(0.0, 0.5), Point2::new(0.25, 0.5)]);
+
+    // Splitting along the same faces with cells in reverse order and duplicates gives the same result
+    let reversed: Vec<_> = faces
+        .iter()
+        .map(|face| InteriorFace {
+            cells: [face.cells[1], face.cells[0]],
+            local_faces: [face.local_faces[1], face.local_faces[0]],
+        })
+        .chain(faces.iter().copied())
+        .collect();
+    assert_eq!(split_mesh_along_faces(&mesh, &reversed).unwrap(), split);
+}
+
+#[test]
+fn split_quad_mesh_along_crossing_lines_disconnects_all_cells() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let faces = find_interior_faces(&mesh);
+    let split = split_mesh_along_faces(&mesh, &faces).unwrap();
+    assert_eq!(split.mesh().vertices().len(), 16);
+    // The center vertex has a copy for each cell
+    assert_eq!(split.parent_vertices().iter().filter(|&&v| v == 4).count(), 4);
+    let all_vertices = split
+        .mesh()
+        .connectivity()
+        .iter()
+        .flat_map(|cell| cell.vertex_indices().to_vec())
+        .collect_vec();
+    assert!(all_vertices.iter().all_unique());
+}
+
+fn assert_split_along_plane_z(mesh_vertices: &[Point3<f64>], split_vertices: &[usize], num_parent: usize) {
+    // Exactly the vertices in the plane z = 0.5 are duplicated once
+    let expected = mesh_vertices
+        .iter()
+        .positions(|v| (v.z - 0.5).abs() < 1e-12)
+        .collect_vec();
+    let duplicated = split_vertices[num_parent..]
+        .iter()
+        .copied()
+        .sorted()
+        .collect_vec();
+    assert_eq!(duplicated, expected);
+}
+
+#[test]
+fn split_tet_meshes_along_label_interface() {
+    let tet4 = Tet4Mesh::from(&create_unit_box_uniform_hex_mesh_3d::<f64>(2));
+    let tet10 = Tet10Mesh::from(&tet4);
+    let labels: Vec<_> = tet4
+        .connectivity()
+        .iter()
+        .map(|cell| {
+            let z: f64 = cell
+                .vertex_indices()
+                .iter()
+                .map(|&v| tet4.vertices()[v].z)
+                .sum();
+            z / 4.0 < 0.5
+        })
+        .collect();
+
+    let faces = find_interface_faces(&tet4, &labels);
+    assert!(!faces.is_empty());
+    let split4 = split_mesh_along_faces(&tet4, &faces).unwrap();
+    assert_split_along_plane_z(tet4.vertices(), split4.parent_vertices(), split4.num_parent_vertices());
+
+    let faces = find_interface_faces(&tet10, &labels);
+    let split10 = split_mesh_along_faces(&tet10, &faces).unwrap();
+    assert_split_along_plane_z(
+        tet10.vertices(),
+        split10.parent_vertices(),
+        split10.num_parent_vertices(),
+    );
+
+    // The regions with different labels no longer share vertices
+    let region_vertices = |label: bool| {
+        cell_vertex_set(
+            split10
+                .mesh()
+                .connectivity()
+                .iter()
+                .zip(&labels)
+                .filter(|&(_, &l)| l == label)
+                .map(|(cell, _)| *cell),
+        )
+    };
+    assert!(region_vertices(true).is_disjoint(&region_vertices(false)));
+}
+
+#[test]
+fn split_mesh_rejects_invalid_faces() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let face = find_interior_faces(&mesh)[0];
+    let invalid_faces = [
+        InteriorFace {
+            cells: [face.cells[0], 10],
+            ..face
+        },
+        InteriorFace {
+            local_faces: [face.local_faces[0], (face.local_faces[1] + 1) % 4],
+            ..face
+        },
+        InteriorFace {
+            cells: [face.cells[0], face.cells[0]],
+            local_faces: [face.local_faces[0], face.local_faces[0]],
+        },
+        InteriorFace {
+            local_faces: [face.local_faces[0], 4],
+            ..face
+        },
+    ];
+    for invalid_face in invalid_faces {
+        assert!(split_mesh_along_faces(&mesh, &[face, invalid_face]).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::Connectivity;
+use fenris::element::ClosestPoint;
+use fenris::geometry::AxisAlignedBoundingBox;
+use fenris::index::NodeIndex;
+use fenris::mesh::structured::StructuredQuadGrid2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix2x4, Point2, Vector2};
+use fenris::quadrature;
+use fenris::space::{ClosestPointInElementInSpace, FindClosestElement, FiniteElementConnectivity, FiniteElementSpace};
+use matrixcompare::assert_matrix_eq;
+
+fn grid() -> StructuredQuadGrid2d<f64> {
+    StructuredQuadGrid2d::new(Point2::new(-1.0, 2.0), Vector2::new(0.5, 0.25), 3, 2)
+}
+
+#[test]
+fn structured_grid_indexing() {
+    let grid = grid();
+    assert_eq!(grid.num_cells(), 6);
+    assert_eq!(grid.num_vertices(), 12);
+    assert_eq!(grid.vertex(0, 0), Point2::new(-1.0, 2.0));
+    assert_eq!(grid.vertex(3, 2), Point2::new(0.5, 2.5));
+    assert_eq!(grid.bounds().max(), &Point2::new(0.5, 2.5));
+
+    assert_eq!(grid.vertex_index(2, 1), 6);
+    assert_eq!(grid.cell_index(2, 1), 5);
+    assert_eq!(grid.cell_vertex_indices(2, 1), [6, 7, 11, 10]);
+    for v in 0..grid.num_vertices() {
+        let (i, j) = grid.vertex_ij(v);
+        assert_eq!(grid.vertex_index(i, j), v);
+    }
+    for cell in 0..grid.num_cells() {
+        let (i, j) = grid.cell_ij(cell);
+        assert_eq!(grid.cell_index(i, j), cell);
+    }
+
+    let from_bounds = StructuredQuadGrid2d::from_bounds(
+        &AxisAlignedBoundingBox::new(Point2::new(-1.0, 2.0), Point2::new(0.5, 2.5)),
+        3,
+        2,
+    );
+    assert_eq!(from_bounds, grid);
+
+    let empty = StructuredQuadGrid2d::new(Point2::origin(), Vector2::new(1.0, 1.0), 0, 4);
+    assert_eq!(empty.num_cells(), 0);
+    assert_eq!(empty.num_vertices(), 0);
+    assert_eq!(empty.to_mesh().vertices().len(), 0);
+    assert!(empty
+        .find_closest_element_and_reference_coords(&Point2::origin())
+        .is_none());
+}
+
+#[test]
+fn structured_grid_agrees_with_converted_mesh() {
+    let grid = grid();
+    let mesh = QuadMesh2d::from(&grid);
+    assert_eq!(mesh.vertices().len(), grid.num_nodes());
+    assert_eq!(mesh.connectivity().len(), grid.num_elements());
+
+    let xi = Point2::new(0.3, -0.7);
+    for cell in 0..grid.num_cells() {
+        let mut nodes = [NodeIndex::default(); 4];
+        grid.populate_element_nodes(&mut nodes, cell.into());
+        assert_eq!(
+            NodeIndex::as_raw_slice(&nodes),
+            mesh.connectivity()[cell].vertex_indices()
+        );
+
+        let mut basis_grid = [0.0; 4];
+        let mut basis_mesh = [0.0; 4];
+        grid.populate_element_basis(cell, &mut basis_grid, &xi);
+        mesh.populate_element_basis(cell, &mut basis_mesh, &xi);
+        assert_eq!(basis_grid, basis_mesh);
+
+        let mut gradients_grid = Matrix2x4::zeros();
+        let mut gradients_mesh = Matrix2x4::zeros();
+        grid.populate_element_gradients(cell, (&mut gradients_grid).into(), &xi);
+        mesh.populate_element_gradients(cell, (&mut gradients_mesh).into(), &xi);
+        assert_eq!(gradients_grid, gradients_mesh);
+
+        assert_matrix_eq!(
+            grid.element_reference_jacobian(cell, &xi),
+            mesh.element_reference_jacobian(cell, &xi),
+            comp = abs,
+            tol = 1e-14
+        );
+        assert_matrix_eq!(
+            grid.map_element_reference_coords(cell, &xi).coords,
+            mesh.map_element_reference_coords(cell, &xi).coords,
+            comp = abs,
+            tol = 1e-14
+        );
+        assert!((grid.diameter(cell) - mesh.diameter(cell)).abs() < 1e-14);
+        assert!(grid.element_constant_reference_jacobian(cell).is_some());
+    }
+
+    // Assembly on the grid and on the mesh gives the same results
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::from_fn(grid.num_nodes(), |i, _| (i as f64).sin());
+    let grid_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&grid)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let mesh_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let a_grid = CsrAssembler::default().assemble(&grid_assembler).unwrap();
+    let a_mesh = CsrAssembler::default().assemble(&mesh_assembler).unwrap();
+    assert_matrix_eq!(DMatrix::from(&a_grid), DMatrix::from(&a_mesh), comp = abs, tol = 1e-12);
+    let f_grid = VectorAssembler::default()
+        .assemble_vector(&grid_assembler)
+        .unwrap();
+    let f_mesh = VectorAssembler::default()
+        .assemble_vector(&mesh_assembler)
+        .unwrap();
+    assert_matrix_eq!(f_grid, f_mesh, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn structured_grid_point_location() {
+    let grid = grid();
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(-0.9, 2.1)), Some((0, 0)));
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(-0.25, 2.3)), Some((1, 1)));
+    // Points on interior cell boundaries belong to the cell with the larger index, points on the
+    // upper boundary to the last cell
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(-0.5, 2.25)), Some((1, 1)));
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(0.5, 2.5)), Some((2, 1)));
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(0.6, 2.3)), None);
+    assert_eq!(grid.find_cell_containing_point(&Point2::new(-0.5, 1.9)), None);
+
+    let p = Point2::new(-0.125, 2.3);
+    let (cell, xi) = grid.find_closest_element_and_reference_coords(&p).unwrap();
+    assert_eq!(grid.cell_ij(cell), (1, 1));
+    assert_matrix_eq!(
+        grid.map_element_reference_coords(cell, &xi).coords,
+        p.coords,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert!(matches!(
+        grid.closest_point_in_element(cell, &p),
+        ClosestPoint::InElement(_)
+    ));
+
+    // Points outside the grid are projected onto the closest cell
+    let outside = Point2::new(1.0, 1.0);
+    let (cell, xi) = grid
+        .find_closest_element_and_reference_coords(&outside)
+        .unwrap();
+    assert_eq!(grid.cell_ij(cell), (2, 0));
+    assert_eq!(xi, Point2::new(1.0, -1.0));
+    assert_eq!(
+        grid.closest_point_in_element(cell, &outside),
+        ClosestPoint::ClosestPoint(Point2::new(1.0, -1.0))
+    );
+}
@@ -0,0 +1,87 @@
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use nalgebra::DVector;
+
+#[test]
+fn extract_submesh_with_mapping() {
+    // 3x3 quads, with the lower right 2x2 block selected (out of order)
+    let mut mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    mesh.tag_nodes("bottom", |x| x[1] < 1e-12);
+    mesh.tag_cells("left", |x| x[0] < 1.0 / 3.0);
+    let cells = [8, 4, 5, 7];
+    let (submesh, mapping) = mesh.extract_submesh(&cells).unwrap();
+
+    assert_eq!(submesh.connectivity().len(), 4);
+    assert_eq!(submesh.vertices().len(), 9);
+    assert_eq!(mapping.global_cells(), &cells);
+    assert_eq!(mapping.num_local_nodes(), 9);
+    assert_eq!(mapping.num_local_cells(), 4);
+    assert!(mapping.global_nodes().windows(2).all(|w| w[0] < w[1]));
+
+    for (local_cell, &global_cell) in cells.iter().enumerate() {
+        assert_eq!(mapping.local_cell(global_cell), Some(local_cell));
+        let local_conn = &submesh.connectivity()[local_cell];
+        let global_conn = &mesh.connectivity()[global_cell];
+        for (&local_node, &global_node) in local_conn.0.iter().zip(&global_conn.0) {
+            assert_eq!(mapping.global_nodes()[local_node], global_node);
+            assert_eq!(mapping.local_node(global_node), Some(local_node));
+            assert_eq!(submesh.vertices()[local_node], mesh.vertices()[global_node]);
+        }
+    }
+    assert_eq!(mapping.local_cell(0), None);
+    assert_eq!(mapping.local_node(0), None);
+
+    // Sets are restricted to the submesh
+    let bottom_nodes: Vec<_> = submesh
+        .sets()
+        .node_set("bottom")
+        .unwrap()
+        .iter()
+        .map(|&i| submesh.vertices()[i])
+        .collect();
+    assert_eq!(bottom_nodes.len(), 3);
+    assert!(bottom_nodes.iter().all(|x| x.y == 0.0));
+    assert!(submesh.sets().cell_set("left").unwrap().is_empty());
+
+    // keep_cells gives the same mesh
+    assert_eq!(mesh.keep_cells(&cells), submesh);
+
+    // Transfer of nodal vectors
+    let solution_dim = 2;
+    let u_global = DVector::from_fn(solution_dim * mesh.vertices().len(), |i, _| i as f64);
+    let u_local = mapping.gather_from_global(&u_global, solution_dim);
+    assert_eq!(u_local.len(), solution_dim * 9);
+    for (local_node, &global_node) in mapping.global_nodes().iter().enumerate() {
+        for i in 0..solution_dim {
+            assert_eq!(
+                u_local[solution_dim * local_node + i],
+                u_global[solution_dim * global_node + i]
+            );
+        }
+    }
+    let mut v_global = DVector::zeros(u_global.len());
+    mapping.scatter_to_global(&u_local, &mut v_global, solution_dim);
+    for (node, x) in mesh.vertices().iter().enumerate() {
+        let expected = if mapping.local_node(node).is_some() {
+            u_global.rows(2 * node, 2).clone_owned()
+        } else {
+            DVector::zeros(2)
+        };
+        assert_eq!(v_global.rows(2 * node, 2), expected, "node at {x}");
+        assert_eq!(
+            mapping.local_node(node).is_some(),
+            x.x > 1.0 / 3.0 - 1e-12 && x.y < 2.0 / 3.0 + 1e-12
+        );
+    }
+}
+
+#[test]
+fn extract_submesh_rejects_invalid_cells() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    assert!(mesh.extract_submesh(&[0, 4]).is_err());
+    assert!(mesh.extract_submesh(&[1, 2, 1]).is_err());
+
+    let (submesh, mapping) = mesh.extract_submesh(&[]).unwrap();
+    assert!(submesh.vertices().is_empty());
+    assert!(submesh.connectivity().is_empty());
+    assert!(mapping.global_nodes().is_empty());
+}
@@ -0,0 +1,108 @@
+use fenris::connectivity::CellConnectivity;
+use fenris::geometry::{ConvexPolygon, Triangle};
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::supermesh::Supermesh2d;
+use fenris::nalgebra::Point2;
+use matrixcompare::assert_scalar_eq;
+
+fn centroid(polygon: &ConvexPolygon<f64>) -> Point2<f64> {
+    let n = polygon.vertices().len() as f64;
+    Point2::from(
+        polygon
+            .vertices()
+            .iter()
+            .map(|v| v.coords)
+            .sum::<fenris::nalgebra::Vector2<f64>>()
+            / n,
+    )
+}
+
+#[test]
+fn supermesh_of_identical_meshes_reproduces_cells() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let supermesh = Supermesh2d::from_meshes(&mesh, &mesh).unwrap();
+    // Neighboring cells only share edges, so only the cell itself is intersected
+    assert_eq!(supermesh.num_cells(), mesh.connectivity().len());
+    for (i, cell) in supermesh.cells().iter().enumerate() {
+        assert_eq!((cell.first_cell, cell.second_cell), (i, i));
+        let triangle = mesh.connectivity()[i].cell(mesh.vertices()).unwrap();
+        assert_scalar_eq!(cell.area(), triangle.area(), comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn supermesh_of_non_matching_meshes_partitions_both_meshes() {
+    let first = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let mut second = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    // Perturb the interior vertices of the second mesh
+    for v in second.vertices_mut() {
+        if v.x > 1e-12 && v.x < 1.0 - 1e-12 && v.y > 1e-12 && v.y < 1.0 - 1e-12 {
+            v.x += 0.03 * (7.0 * v.y).sin();
+            v.y += 0.02 * (5.0 * v.x).cos();
+        }
+    }
+
+    let supermesh = Supermesh2d::from_meshes(&first, &second).unwrap();
+    assert_scalar_eq!(supermesh.area(), 1.0, comp = abs, tol = 1e-12);
+
+    // Supermesh cells partition every cell of both meshes
+    let mut first_areas = vec![0.0; first.connectivity().len()];
+    let mut second_areas = vec![0.0; second.connectivity().len()];
+    for cell in supermesh.cells() {
+        first_areas[cell.first_cell] += cell.area();
+        second_areas[cell.second_cell] += cell.area();
+
+        // Each supermesh cell is contained in its parents
+        let x = centroid(&cell.polygon);
+        let first_parent: ConvexPolygon<f64> = first.connectivity()[cell.first_cell]
+            .cell(first.vertices())
+            .unwrap()
+            .into();
+        let second_parent: ConvexPolygon<f64> = second.connectivity()[cell.second_cell]
+            .cell(second.vertices())
+            .unwrap()
+            .into();
+        assert!(first_parent.contains_point(&x));
+        assert!(second_parent.contains_point(&x));
+    }
+    for (conn, area) in first.connectivity().iter().zip(first_areas) {
+        assert_scalar_eq!(
+            area,
+            conn.cell(first.vertices()).unwrap().area(),
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+    for (conn, area) in second.connectivity().iter().zip(second_areas) {
+        assert_scalar_eq!(
+            area,
+            conn.cell(second.vertices()).unwrap().area(),
+            comp = abs,
+            tol = 1e-14
+        );
+    }
+
+    let (triangles, parents) = supermesh.triangulate();
+    assert_eq!(triangles.connectivity().len(), parents.len());
+    let mut triangle_areas = vec![0.0; supermesh.num_cells()];
+    for (conn, &parent) in triangles.connectivity().iter().zip(&parents) {
+        let triangle: Triangle<f64, _> = conn.cell(triangles.vertices()).unwrap();
+        assert!(triangle.signed_area() > 0.0);
+        triangle_areas[parent] += triangle.area();
+    }
+    for (cell, area) in supermesh.cells().iter().zip(triangle_areas) {
+        assert_scalar_eq!(area, cell.area(), comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn supermesh_of_partially_overlapping_triangle_and_quad_meshes() {
+    let first = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let mut second = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    second
+        .vertices_mut()
+        .iter_mut()
+        .for_each(|v| *v += fenris::nalgebra::vector![0.5, 0.25]);
+    let supermesh = Supermesh2d::from_meshes(&first, &second).unwrap();
+    assert_scalar_eq!(supermesh.area(), 0.5 * 0.75, comp = abs, tol = 1e-12);
+}
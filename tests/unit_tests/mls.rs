@@ -0,0 +1,113 @@
+use fenris::nalgebra::{point, vector, Point2, Point3, Vector1, Vector2};
+use fenris::space::{MlsBasis, MlsWeight, MovingLeastSquares};
+use matrixcompare::assert_scalar_eq;
+
+/// Slightly perturbed grid points in the unit square, so that the point cloud is not structured.
+fn scattered_points_2d(samples_per_dim: usize) -> Vec<Point2<f64>> {
+    let h = 1.0 / (samples_per_dim - 1) as f64;
+    (0..samples_per_dim)
+        .flat_map(|i| {
+            (0..samples_per_dim).map(move |j| {
+                let perturbation = vector![(3.0 * i as f64 + j as f64).sin(), (i as f64 - 2.0 * j as f64).cos()];
+                point![i as f64 * h, j as f64 * h] + 0.2 * h * perturbation
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn mls_shape_functions_form_partition_of_unity() {
+    let points = scattered_points_2d(8);
+    let weights = [
+        MlsWeight::Wendland,
+        MlsWeight::CubicSpline,
+        MlsWeight::Gaussian { shape_parameter: 0.4 },
+    ];
+    for basis in [MlsBasis::Constant, MlsBasis::Linear, MlsBasis::Quadratic] {
+        for weight in weights {
+            let mls = MovingLeastSquares::from_points(points.clone(), 0.45)
+                .with_basis(basis)
+                .with_weight(weight);
+            for x in [point![0.5, 0.5], point![0.13, 0.87], point![0.0, 1.0]] {
+                let phi = mls.shape_functions(&x).unwrap();
+                assert!(!phi.is_empty());
+                let sum: f64 = phi.iter().map(|(_, phi_i)| phi_i).sum();
+                assert_scalar_eq!(sum, 1.0, comp = abs, tol = 1e-12);
+                for (i, _) in phi {
+                    assert!((points[i] - x).norm() <= 0.45);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mls_reproduces_polynomials_in_basis() {
+    let points = scattered_points_2d(10);
+    let linear = |x: &Point2<f64>| vector![1.0 + 2.0 * x.x - 3.0 * x.y, -x.x + 0.5];
+    let quadratic = |x: &Point2<f64>| Vector1::new(1.0 - x.x * x.y + 2.0 * x.x * x.x - x.y + 0.5 * x.y * x.y);
+    let linear_values: Vec<Vector2<f64>> = points.iter().map(linear).collect();
+    let quadratic_values: Vec<Vector1<f64>> = points.iter().map(quadratic).collect();
+    let eval_points = vec![
+        point![0.5, 0.5],
+        point![0.21, 0.77],
+        point![0.95, 0.02],
+        point![1.0, 1.0],
+    ];
+
+    let mls = MovingLeastSquares::from_points(points.clone(), 0.35);
+    let approx = mls
+        .evaluate_at_points(&eval_points, &linear_values)
+        .unwrap();
+    for (x, u) in eval_points.iter().zip(&approx) {
+        assert!((u - linear(x)).norm() < 1e-12);
+    }
+
+    let mls = mls.with_basis(MlsBasis::Quadratic);
+    let approx = mls
+        .evaluate_at_points(&eval_points, &quadratic_values)
+        .unwrap();
+    for (x, u) in eval_points.iter().zip(&approx) {
+        assert_scalar_eq!(u.x, quadratic(x).x, comp = abs, tol = 1e-12);
+    }
+
+    // Constant basis (Shepard interpolation) only reproduces constants
+    let mls = mls.with_basis(MlsBasis::Constant);
+    let constant_values = vec![Vector1::new(2.5); points.len()];
+    let u = mls.evaluate(&point![0.3, 0.6], &constant_values).unwrap();
+    assert_scalar_eq!(u.x, 2.5, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn mls_reproduces_linear_field_in_3d() {
+    let n = 5;
+    let h = 1.0 / (n - 1) as f64;
+    let points: Vec<Point3<f64>> = (0..n * n * n)
+        .map(|idx| {
+            let (i, j, k) = (idx / (n * n), (idx / n) % n, idx % n);
+            point![i as f64 * h, j as f64 * h, k as f64 * h] + 0.1 * h * vector![(idx as f64).sin(), 0.0, 0.0]
+        })
+        .collect();
+    let field = |x: &Point3<f64>| Vector1::new(x.x - 2.0 * x.y + 3.0 * x.z);
+    let values: Vec<_> = points.iter().map(field).collect();
+    let mls = MovingLeastSquares::from_points(points, 0.6).with_weight(MlsWeight::CubicSpline);
+    let x = point![0.3, 0.4, 0.7];
+    let u = mls.evaluate(&x, &values).unwrap();
+    assert_scalar_eq!(u.x, field(&x).x, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn mls_fails_without_enough_neighbors() {
+    let points = scattered_points_2d(6);
+    let values = vec![Vector1::new(1.0); points.len()];
+    let mls = MovingLeastSquares::from_points(points.clone(), 0.3);
+
+    // No neighbors at all
+    assert!(mls.evaluate(&point![3.0, 3.0], &values).is_err());
+    // Collinear neighbors do not determine a linear fit
+    let collinear = vec![point![0.0, 0.0], point![0.1, 0.0], point![0.2, 0.0]];
+    let mls_collinear = MovingLeastSquares::from_points(collinear, 0.5);
+    assert!(mls_collinear.shape_functions(&point![0.1, 0.0]).is_err());
+    // Mismatched number of values
+    assert!(mls.evaluate(&point![0.5, 0.5], &values[1..]).is_err());
+}
@@ -1,10 +1,27 @@
+mod affine_cache;
 mod assembly;
+mod autodiff;
 mod basis;
+mod compare;
+mod directors;
+mod discontinuous;
 mod element;
 mod error;
 mod fe_mesh;
+mod hierarchical;
+mod index;
 mod io;
+mod memory;
 mod mesh;
+mod mls;
+mod model;
+mod moving_mesh;
+mod piecewise_constant;
 mod quadrature;
+mod remap;
 mod reorder;
+mod scattered;
+mod solver;
 mod spatially_indexed;
+mod timestepping;
+mod transfer;
@@ -0,0 +1,6 @@
+mod fct;
+mod inversion;
+mod limiter;
+mod newton;
+mod phase_field;
+mod stokes;
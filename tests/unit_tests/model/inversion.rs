@@ -0,0 +1,147 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::model::inversion::{InversionRecovery, RecoveryOutcome};
+use fenris::nalgebra::{DVector, Vector2, U2};
+use fenris::quadrature;
+use fenris::quadrature::QuadraturePair2d;
+
+fn setup() -> (QuadMesh2d<f64>, UniformQuadratureTable<f64, U2>) {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let quadrature: QuadraturePair2d<f64> = quadrature::tensor::quadrilateral_gauss(2);
+    (mesh, UniformQuadratureTable::from_quadrature(quadrature))
+}
+
+fn displacement(mesh: &QuadMesh2d<f64>, f: impl Fn(&Vector2<f64>) -> Vector2<f64>) -> DVector<f64> {
+    let mut u = DVector::zeros(2 * mesh.vertices().len());
+    for (i, v) in mesh.vertices().iter().enumerate() {
+        u.fixed_rows_mut::<2>(2 * i).copy_from(&f(&v.coords));
+    }
+    u
+}
+
+fn boundary_nodes(mesh: &QuadMesh2d<f64>) -> Vec<usize> {
+    let on_boundary = |x: f64| x.abs() < 1e-12 || (x - 1.0).abs() < 1e-12;
+    (0..mesh.vertices().len())
+        .filter(|&i| {
+            let v = &mesh.vertices()[i];
+            on_boundary(v.x) || on_boundary(v.y)
+        })
+        .collect()
+}
+
+#[test]
+fn check_reports_volume_ratio_of_deformation() {
+    let (mesh, qtable) = setup();
+    let recovery = InversionRecovery::new();
+
+    // Uniform stretch with F = diag(2, 0.75)
+    let u = displacement(&mesh, |x| Vector2::new(x.x, -0.25 * x.y));
+    let report = recovery.check(&mesh, &u, &qtable).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.num_elements(), 9);
+    assert!((report.min_volume_ratio().unwrap() - 1.5).abs() < 1e-12);
+
+    // The threshold also flags nearly degenerate elements
+    let report = recovery
+        .clone()
+        .with_min_volume_ratio(1.6)
+        .check(&mesh, &u, &qtable)
+        .unwrap();
+    assert_eq!(report.inverted_elements().len(), 9);
+
+    // Moving an interior node beyond its neighbor inverts the elements around it
+    let mut u = DVector::zeros(2 * mesh.vertices().len());
+    u[2 * 5] = 0.5;
+    let report = recovery.check(&mesh, &u, &qtable).unwrap();
+    assert!(!report.is_valid());
+    assert!(report.min_volume_ratio().unwrap() < 0.0);
+    assert!(report
+        .inverted_element_indices()
+        .all(|element| mesh.connectivity()[element].0.contains(&5)));
+    assert!(report
+        .inverted_elements()
+        .iter()
+        .all(|element| element.min_volume_ratio <= 0.0));
+
+    assert!(recovery.check(&mesh, &DVector::zeros(3), &qtable).is_err());
+}
+
+#[test]
+fn recover_untangles_local_inversion() {
+    let (mesh, qtable) = setup();
+    let recovery = InversionRecovery::new().with_fixed_nodes(boundary_nodes(&mesh));
+    let u_previous = DVector::zeros(2 * mesh.vertices().len());
+
+    let mut u_valid = displacement(&mesh, |x| 0.1 * x);
+    let expected = u_valid.clone();
+    let outcome = recovery
+        .recover(&mesh, &u_previous, &mut u_valid, &qtable)
+        .unwrap();
+    assert_eq!(outcome, RecoveryOutcome::Valid);
+    assert_eq!(u_valid, expected);
+
+    let mut u_new = DVector::zeros(2 * mesh.vertices().len());
+    u_new[2 * 5] = 0.5;
+    let outcome = recovery
+        .recover(&mesh, &u_previous, &mut u_new, &qtable)
+        .unwrap();
+    assert!(outcome.is_accepted());
+    match outcome {
+        RecoveryOutcome::Untangled {
+            inverted_elements,
+            num_iterations,
+            num_moved_nodes,
+        } => {
+            assert!(!inverted_elements.is_empty());
+            assert_eq!(num_iterations, 1);
+            // All interior nodes belong to the inverted elements around node 5
+            assert_eq!(num_moved_nodes, 4);
+        }
+        _ => panic!("Expected untangling"),
+    }
+    assert!(recovery.check(&mesh, &u_new, &qtable).unwrap().is_valid());
+    // Boundary nodes were not moved
+    for node in boundary_nodes(&mesh) {
+        assert_eq!(u_new.fixed_rows::<2>(2 * node).norm(), 0.0);
+    }
+}
+
+#[test]
+fn recover_rejects_global_inversion_with_step_fraction() {
+    let (mesh, qtable) = setup();
+    let recovery = InversionRecovery::new().with_fixed_nodes(boundary_nodes(&mesh));
+    let u_previous = DVector::zeros(2 * mesh.vertices().len());
+
+    // F = diag(1 - 2 α, 1) along the update, which inverts all elements for α ≥ 1/2
+    let u_inverted = displacement(&mesh, |x| Vector2::new(-2.0 * x.x, 0.0));
+    let mut u_new = u_inverted.clone();
+    let outcome = recovery
+        .recover(&mesh, &u_previous, &mut u_new, &qtable)
+        .unwrap();
+    assert!(!outcome.is_accepted());
+    match outcome {
+        RecoveryOutcome::Rejected {
+            report,
+            max_step_fraction,
+        } => {
+            assert_eq!(report.inverted_elements().len(), 9);
+            assert!(max_step_fraction < 0.5);
+            assert!(max_step_fraction >= 0.5 - 2.0f64.powi(-10));
+        }
+        _ => panic!("Expected rejection"),
+    }
+    assert_eq!(u_new, u_previous);
+
+    let fraction = recovery
+        .clone()
+        .with_max_bisection_steps(20)
+        .max_step_fraction(&mesh, &u_previous, &u_inverted, &qtable)
+        .unwrap();
+    assert!((fraction - 0.5).abs() < 1e-6);
+
+    // The previous displacement must be valid
+    assert!(recovery
+        .max_step_fraction(&mesh, &u_inverted, &u_previous, &qtable)
+        .is_err());
+}
@@ -0,0 +1,220 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::model::limiter::{clip_and_scale, BarthJespersenLimiter, BoundConstrainedProjection};
+use fenris::nalgebra::{DMatrix, DVector, Vector2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn barth_jespersen_limiter_enforces_neighbor_bounds() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let limiter = BarthJespersenLimiter::from_mesh(&mesh);
+    assert_eq!(limiter.num_cells(), 32);
+    // Interior triangles have three face neighbors, corner triangles at least one
+    assert!((0..32).all(|i| (1..=3).contains(&limiter.cell_neighbors(i).len())));
+
+    // A linear field with strongly overshooting gradients and a constant region
+    let means: Vec<f64> = limiter.centroids().iter().map(|x| x.x.min(0.5)).collect();
+    let original_gradients = vec![Vector2::new(4.0, 0.0); 32];
+    let mut gradients = original_gradients.clone();
+    let coefficients = limiter.limit_gradients(&means, &mut gradients).unwrap();
+    let (lower, upper) = limiter.compute_local_bounds(&means).unwrap();
+
+    for (cell_index, cell) in mesh.connectivity().iter().enumerate() {
+        let alpha = coefficients[cell_index];
+        assert!((0.0..=1.0).contains(&alpha));
+        assert_eq!(gradients[cell_index], original_gradients[cell_index] * alpha);
+        for &v in &cell.0 {
+            let offset = mesh.vertices()[v] - limiter.centroids()[cell_index];
+            let value = means[cell_index] + gradients[cell_index].dot(&offset);
+            assert!(value >= lower[cell_index] - 1e-14);
+            assert!(value <= upper[cell_index] + 1e-14);
+        }
+        // Cells in the constant region have constant neighborhoods, so their gradient vanishes
+        if lower[cell_index] == upper[cell_index] {
+            assert_eq!(alpha, 0.0);
+        }
+    }
+    assert!(coefficients.iter().any(|&alpha| alpha > 0.0 && alpha < 1.0));
+
+    // Zero gradients are never limited
+    let coefficients = limiter
+        .compute_coefficients(&means, &vec![Vector2::zeros(); 32])
+        .unwrap();
+    assert!(coefficients.iter().all(|&alpha| alpha == 1.0));
+    assert!(limiter.compute_local_bounds(&means[1..]).is_err());
+    assert!(limiter
+        .compute_coefficients(&means, &original_gradients[1..])
+        .is_err());
+}
+
+#[test]
+fn barth_jespersen_limiter_preserves_linear_fields_in_interior() {
+    // On a uniform quad mesh, the vertex values of a linear field lie between the means of the
+    // face neighbors only for fields aligned with the mesh, which are therefore not limited in
+    // interior cells
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let limiter = BarthJespersenLimiter::from_mesh(&mesh);
+    let gradient = Vector2::new(2.0, 0.0);
+    let means: Vec<f64> = limiter
+        .centroids()
+        .iter()
+        .map(|x| gradient.dot(&x.coords))
+        .collect();
+    let coefficients = limiter
+        .compute_coefficients(&means, &vec![gradient; 16])
+        .unwrap();
+    for (cell_index, centroid) in limiter.centroids().iter().enumerate() {
+        if centroid.x > 0.25 && centroid.x < 0.75 {
+            assert_scalar_eq!(coefficients[cell_index], 1.0, comp = abs, tol = 1e-14);
+        } else {
+            assert_scalar_eq!(coefficients[cell_index], 0.0, comp = abs, tol = 1e-14);
+        }
+    }
+}
+
+#[test]
+fn clip_and_scale_enforces_bounds_and_conserves_mass() {
+    let masses = [1.0, 2.0, 1.0, 0.5, 1.5];
+    let lower = [0.0; 5];
+    let upper = [1.0; 5];
+    let mass = |u: &[f64]| u.iter().zip(&masses).map(|(u, m)| u * m).sum::<f64>();
+
+    // Overshoot is redistributed to the nodes below the upper bound
+    let mut values = [1.4, 0.2, 0.5, -0.1, 0.9];
+    let initial_mass = mass(&values);
+    clip_and_scale(&mut values, &masses, &lower, &upper).unwrap();
+    assert_scalar_eq!(mass(&values), initial_mass, comp = abs, tol = 1e-14);
+    assert!(values.iter().all(|&u| (0.0..=1.0).contains(&u)));
+    assert_eq!(values[0], 1.0);
+
+    // Undershoot is taken from the nodes above the lower bound
+    let mut values = [-0.6, 0.2, 0.5, 0.3, 0.9];
+    let initial_mass = mass(&values);
+    clip_and_scale(&mut values, &masses, &lower, &upper).unwrap();
+    assert_scalar_eq!(mass(&values), initial_mass, comp = abs, tol = 1e-14);
+    assert!(values.iter().all(|&u| (0.0..=1.0).contains(&u)));
+    assert_eq!(values[0], 0.0);
+
+    // Values within bounds are unchanged
+    let mut values = [0.1, 0.2, 0.3, 0.4, 0.5];
+    clip_and_scale(&mut values, &masses, &lower, &upper).unwrap();
+    assert_eq!(values, [0.1, 0.2, 0.3, 0.4, 0.5]);
+
+    // Infeasible total mass and invalid input are rejected without modifying the values
+    let mut values = [1.5, 1.2, 1.0, 1.0, 1.0];
+    assert!(clip_and_scale(&mut values, &masses, &lower, &upper).is_err());
+    assert_eq!(values, [1.5, 1.2, 1.0, 1.0, 1.0]);
+    assert!(clip_and_scale(&mut values, &masses[1..], &lower, &upper).is_err());
+    assert!(clip_and_scale(&mut values, &masses, &upper, &lower).is_err());
+}
+
+/// Checks the KKT conditions of the bound-constrained projection, where the multiplier of the
+/// mass constraint is estimated from a free weight.
+fn assert_projection_optimality(
+    mass_matrix: &CsrMatrix<f64>,
+    u: &DVector<f64>,
+    u0: &DVector<f64>,
+    lower: &[f64],
+    upper: &[f64],
+    conserve_mass: bool,
+) {
+    let tol = 1e-9;
+    let n = u.len();
+    let mut gradient = mass_matrix * (u - u0);
+    let is_free = |i: usize| u[i] > lower[i] + tol && u[i] < upper[i] - tol;
+    if conserve_mass {
+        let row_sums = mass_matrix * DVector::repeat(n, 1.0);
+        let i = (0..n).find(|&i| is_free(i)).unwrap();
+        let multiplier = gradient[i] / row_sums[i];
+        gradient -= row_sums * multiplier;
+    }
+    for i in 0..n {
+        assert!(u[i] >= lower[i] && u[i] <= upper[i]);
+        if is_free(i) {
+            assert_scalar_eq!(gradient[i], 0.0, comp = abs, tol = tol);
+        } else if u[i] <= lower[i] + tol {
+            assert!(gradient[i] >= -tol);
+        } else {
+            assert!(gradient[i] <= tol);
+        }
+    }
+}
+
+#[test]
+fn bound_constrained_projection_with_diagonal_mass_matrix_clips_values() {
+    let mass_matrix = CsrMatrix::from(&DMatrix::from_diagonal(&DVector::from_vec(vec![1.0, 2.0, 0.5, 3.0])));
+    let mut u = DVector::from_vec(vec![-0.5, 0.3, 1.7, 0.9]);
+    let (lower, upper) = ([0.0; 4], [1.0; 4]);
+    BoundConstrainedProjection::new()
+        .project(&mass_matrix, u.as_view_mut(), &lower, &upper)
+        .unwrap();
+    assert_matrix_eq!(u, DVector::from_vec(vec![0.0, 0.3, 1.0, 0.9]), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn bound_constrained_projection_of_oscillatory_field_is_optimal() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(8);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::total_order::triangle(2).unwrap(),
+        Density(1.0),
+    );
+    let assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&qtable);
+    let mass_matrix = CsrAssembler::default().assemble(&assembler).unwrap();
+
+    // A smoothed step with over- and undershoots, as produced by a high-order transport scheme
+    let u0 = DVector::from_iterator(
+        mesh.vertices().len(),
+        mesh.vertices()
+            .iter()
+            .map(|x| if x.x < 0.5 { 1.0 } else { 0.0 } + 0.2 * (20.0 * x.x).sin() * (7.0 * x.y).cos()),
+    );
+    let n = u0.len();
+    let (lower, upper) = (vec![0.0; n], vec![1.0; n]);
+    let ones = DVector::repeat(n, 1.0);
+    let mass = |u: &DVector<f64>| ones.dot(&(&mass_matrix * u));
+
+    for conserve_mass in [false, true] {
+        let mut u = u0.clone();
+        let projection = BoundConstrainedProjection::new().with_mass_conservation(conserve_mass);
+        let iterations = projection
+            .project(&mass_matrix, u.as_view_mut(), &lower, &upper)
+            .unwrap();
+        assert!(iterations > 1);
+        assert_projection_optimality(&mass_matrix, &u, &u0, &lower, &upper, conserve_mass);
+        if conserve_mass {
+            assert_scalar_eq!(mass(&u), mass(&u0), comp = abs, tol = 1e-12);
+        }
+    }
+
+    // A field that satisfies the bounds is not modified
+    let mut u = u0.map(|u| u.clamp(0.0, 1.0));
+    let u_feasible = u.clone();
+    BoundConstrainedProjection::new()
+        .with_mass_conservation(true)
+        .project(&mass_matrix, u.as_view_mut(), &lower, &upper)
+        .unwrap();
+    assert_matrix_eq!(u, u_feasible, comp = abs, tol = 1e-14);
+
+    // Infeasible mass, invalid input and lack of convergence are rejected without modifying the weights
+    let mut u = u0.add_scalar(1.0);
+    let projection = BoundConstrainedProjection::new().with_mass_conservation(true);
+    assert!(projection
+        .project(&mass_matrix, u.as_view_mut(), &lower, &upper)
+        .is_err());
+    assert!(projection
+        .project(&mass_matrix, u.as_view_mut(), &upper, &lower)
+        .is_err());
+    assert!(projection
+        .project(&mass_matrix, u.as_view_mut(), &lower[1..], &upper)
+        .is_err());
+    assert!(BoundConstrainedProjection::new()
+        .with_max_iterations(1)
+        .project(&mass_matrix, u.as_view_mut(), &lower, &upper)
+        .is_err());
+    assert_eq!(u, u0.add_scalar(1.0));
+}
@@ -0,0 +1,172 @@
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, VectorAssembler,
+};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::solid::{LameParameters, NeoHookeanOperator};
+use fenris::linear_solver::DenseLuSolver;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::model::newton::{BacktrackingLineSearch, ConvergenceReason, NewtonSolver};
+use fenris::nalgebra::{dvector, DMatrix, DVector, DVectorView, DVectorViewMut};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::nonlinear::{NonlinearSystem, NonlinearSystemFn};
+use fenris::quadrature;
+use matrixcompare::assert_scalar_eq;
+
+/// The system r(u) = atan(u), for which Newton's method diverges without globalization if the
+/// initial guess is sufficiently far from the root at zero.
+fn arctan_system() -> impl NonlinearSystem<f64> {
+    NonlinearSystemFn::new(
+        |mut r: DVectorViewMut<f64>, u: DVectorView<f64>| {
+            r[0] = u[0].atan();
+            Ok(())
+        },
+        |u: DVectorView<f64>| Ok(CsrMatrix::from(&DMatrix::from_element(1, 1, 1.0 / (1.0 + u[0] * u[0])))),
+    )
+}
+
+#[test]
+fn newton_converges_quadratically() {
+    let mut system = arctan_system();
+    let mut u = dvector![0.5];
+    let report = NewtonSolver::new()
+        .with_residual_tolerance(1e-14, 0.0)
+        .solve(&mut system, &mut u, &mut DenseLuSolver)
+        .unwrap();
+    assert_eq!(report.converged_by, ConvergenceReason::ResidualNorm);
+    assert_scalar_eq!(report.initial_residual_norm, 0.5f64.atan());
+    assert!(report.final_residual_norm() <= 1e-14);
+    assert!(u[0].abs() <= 1e-14);
+    // For atan, the error e satisfies e_{k + 1} ~ -(2/3) e_k^3
+    let norms: Vec<_> = report
+        .iterations
+        .iter()
+        .map(|iteration| iteration.residual_norm)
+        .collect();
+    for pair in norms.windows(2).filter(|pair| pair[1] > 1e-15) {
+        assert!(pair[1] <= pair[0].powi(2));
+    }
+    assert!(report
+        .iterations
+        .iter()
+        .all(|iteration| iteration.step_length == 1.0));
+}
+
+#[test]
+fn newton_line_search_globalizes_convergence() {
+    let initial_guess = dvector![3.0];
+
+    // Without line search, the iterates diverge
+    let mut u = initial_guess.clone();
+    let result = NewtonSolver::new()
+        .with_max_iterations(5)
+        .solve(&mut arctan_system(), &mut u, &mut DenseLuSolver);
+    assert!(result.is_err());
+    assert!(u[0].abs() > 3.0);
+
+    let mut u = initial_guess;
+    let report = NewtonSolver::new()
+        .with_line_search(BacktrackingLineSearch::default())
+        .solve(&mut arctan_system(), &mut u, &mut DenseLuSolver)
+        .unwrap();
+    assert!(u[0].abs() <= 1e-12);
+    assert!(report.iterations[0].step_length < 1.0);
+    assert_eq!(report.iterations.last().unwrap().step_length, 1.0);
+    for iteration in &report.iterations {
+        assert!(iteration.increment_norm > 0.0);
+    }
+}
+
+#[test]
+fn newton_increment_criterion() {
+    let mut u = dvector![0.5];
+    let report = NewtonSolver::new()
+        .with_residual_tolerance(1e-300, 0.0)
+        .with_increment_tolerance(1e-2)
+        .solve(&mut arctan_system(), &mut u, &mut DenseLuSolver)
+        .unwrap();
+    assert_eq!(report.converged_by, ConvergenceReason::IncrementNorm);
+    assert!(report.iterations.last().unwrap().increment_norm <= 1e-2);
+    assert!(report.final_residual_norm() > 1e-300);
+    assert!(u[0].abs() <= 1e-9);
+
+    // A solution that is exact from the start requires no iterations
+    let mut u = dvector![0.0];
+    let report = NewtonSolver::new()
+        .solve(&mut arctan_system(), &mut u, &mut DenseLuSolver)
+        .unwrap();
+    assert_eq!(report.num_iterations(), 0);
+    assert_eq!(report.converged_by, ConvergenceReason::ResidualNorm);
+}
+
+#[test]
+fn newton_solves_hyperelastic_stretch() {
+    // Stretch a unit square of Neo-Hookean material by clamping the left edge and prescribing
+    // a displacement of the right edge
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let lame = LameParameters::from_young_poisson(1e3, 0.3);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), lame);
+    let dirichlet_nodes: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.x == 0.0 || v.x == 1.0)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut u = DVector::zeros(2 * mesh.vertices().len());
+    for &node in &dirichlet_nodes {
+        u[2 * node] = 0.5 * mesh.vertices()[node].x;
+    }
+
+    let assemble_residual = |u: DVectorView<f64>| {
+        let assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&mesh)
+            .with_operator(&NeoHookeanOperator)
+            .with_quadrature_table(&qtable)
+            .with_u(u)
+            .build();
+        let mut r = VectorAssembler::default().assemble_vector(&assembler)?;
+        apply_homogeneous_dirichlet_bc_rhs(&mut r, &dirichlet_nodes, 2);
+        Ok::<_, eyre::Report>(r)
+    };
+    let mut system = NonlinearSystemFn::new(
+        |mut output: DVectorViewMut<f64>, u: DVectorView<f64>| {
+            output.copy_from(&assemble_residual(u)?);
+            Ok(())
+        },
+        |u: DVectorView<f64>| {
+            let assembler = ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&mesh)
+                .with_operator(&NeoHookeanOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(u)
+                .build();
+            let mut jacobian = CsrAssembler::default().assemble(&assembler)?;
+            apply_homogeneous_dirichlet_bc_csr(&mut jacobian, &dirichlet_nodes, 2);
+            Ok(jacobian)
+        },
+    );
+
+    let u_initial = u.clone();
+    let report = NewtonSolver::new()
+        .with_line_search(BacktrackingLineSearch::default())
+        .solve(&mut system, &mut u, &mut DenseLuSolver)
+        .unwrap();
+    assert!(report.num_iterations() > 1);
+    assert!(report.final_residual_norm() <= 1e-10 * report.initial_residual_norm);
+    assert!(assemble_residual(DVectorView::from(&u)).unwrap().norm() <= 1e-10 * report.initial_residual_norm);
+
+    // The prescribed displacements are unchanged, and the material contracts laterally
+    for &node in &dirichlet_nodes {
+        assert_eq!(u[2 * node], u_initial[2 * node]);
+        assert_eq!(u[2 * node + 1], u_initial[2 * node + 1]);
+    }
+    let top_center = mesh
+        .vertices()
+        .iter()
+        .position(|v| v.x == 0.5 && v.y == 1.0)
+        .unwrap();
+    assert!(u[2 * top_center + 1] < 0.0);
+}
@@ -0,0 +1,109 @@
+use fenris::assembly::bc::DirichletConditions;
+use fenris::assembly::operators::solid::LameParameters;
+use fenris::assembly::operators::{EnergySplit, PhaseFieldModel};
+use fenris::index::DofIndex;
+use fenris::linear_solver::DenseLuSolver;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::model::phase_field::{PhaseFieldFractureSolver, PhaseFieldMaterial, StaggeredSettings};
+use fenris::quadrature;
+use matrixcompare::assert_scalar_eq;
+
+fn material() -> PhaseFieldMaterial<f64> {
+    PhaseFieldMaterial {
+        lame: LameParameters::from_young_poisson(10.0, 0.3),
+        fracture_toughness: 1.0,
+        length_scale: 0.5,
+        residual_stiffness: 1e-6,
+    }
+}
+
+/// Conditions for a bar in uniaxial tension with the given stretch: the left and right edges
+/// are displaced horizontally, and the bottom edge is fixed vertically.
+fn uniaxial_tension(mesh: &QuadMesh2d<f64>, stretch: f64) -> DirichletConditions<f64> {
+    let mut dofs = Vec::new();
+    let mut values = Vec::new();
+    for (i, v) in mesh.vertices().iter().enumerate() {
+        if v.x == 0.0 || v.x == 1.0 {
+            dofs.push(DofIndex::new(2 * i));
+            values.push(stretch * v.x);
+        }
+        if v.y == 0.0 {
+            dofs.push(DofIndex::new(2 * i + 1));
+            values.push(0.0);
+        }
+    }
+    DirichletConditions::try_from_dofs_and_values(2 * mesh.vertices().len(), &dofs, &values).unwrap()
+}
+
+/// The strain energy density of a uniaxial stress state in plane strain.
+fn uniaxial_energy_density(lame: &LameParameters<f64>, stretch: f64) -> f64 {
+    let LameParameters { mu, lambda } = *lame;
+    let eps_xx = stretch;
+    let eps_yy = -lambda / (lambda + 2.0 * mu) * stretch;
+    mu * (eps_xx * eps_xx + eps_yy * eps_yy) + 0.5 * lambda * (eps_xx + eps_yy).powi(2)
+}
+
+#[test]
+fn staggered_solver_reproduces_homogeneous_at2_solution() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let material = material();
+    let mut solver = PhaseFieldFractureSolver::new(&mesh, quadrature::tensor::quadrilateral_gauss(2), material)
+        .with_staggered_settings(StaggeredSettings {
+            tolerance: 1e-12,
+            max_iterations: 10,
+        });
+
+    let stretch = 0.2;
+    let info = solver
+        .step(&uniaxial_tension(&mesh, stretch), &mut DenseLuSolver)
+        .unwrap();
+    assert!(info.staggered_iterations >= 2);
+    assert!(info.newton_iterations >= 1);
+
+    // The strain is homogeneous and independent of the (homogeneous) phase-field, so the history
+    // field is the uniaxial energy density and the phase-field is the minimizer of
+    // (1 - d)^2 H + G_c / (2 l) d^2
+    let h = uniaxial_energy_density(&material.lame, stretch);
+    for &history in solver.history() {
+        assert_scalar_eq!(history, h, comp = abs, tol = 1e-10);
+    }
+    let g_c_over_l = material.fracture_toughness / material.length_scale;
+    let expected_phase_field = 2.0 * h / (g_c_over_l + 2.0 * h);
+    for &d in solver.phase_field().iter() {
+        assert_scalar_eq!(d, expected_phase_field, comp = abs, tol = 1e-10);
+    }
+    for (i, v) in mesh.vertices().iter().enumerate() {
+        assert_scalar_eq!(solver.displacement()[2 * i], stretch * v.x, comp = abs, tol = 1e-10);
+    }
+}
+
+#[test]
+fn staggered_solver_enforces_irreversibility() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let mut solver = PhaseFieldFractureSolver::new(&mesh, quadrature::tensor::quadrilateral_gauss(2), material())
+        .with_model(PhaseFieldModel::At1)
+        .with_energy_split(EnergySplit::VolumetricDeviatoric);
+
+    // AT1 has an elastic stage without damage for small loads
+    solver
+        .step(&uniaxial_tension(&mesh, 0.05), &mut DenseLuSolver)
+        .unwrap();
+    assert_eq!(solver.phase_field().max(), 0.0);
+
+    solver
+        .step(&uniaxial_tension(&mesh, 0.4), &mut DenseLuSolver)
+        .unwrap();
+    let loaded_phase_field = solver.phase_field().clone();
+    let loaded_history = solver.history().to_vec();
+    assert!(loaded_phase_field.min() > 0.0);
+    assert!(loaded_phase_field.max() < 1.0);
+
+    // Unloading neither heals the crack nor reduces the history field
+    solver
+        .step(&uniaxial_tension(&mesh, 0.0), &mut DenseLuSolver)
+        .unwrap();
+    assert_eq!(solver.phase_field(), &loaded_phase_field);
+    assert_eq!(solver.history(), loaded_history.as_slice());
+    assert!(solver.displacement().amax() <= 1e-12);
+}
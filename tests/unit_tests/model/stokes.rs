@@ -0,0 +1,338 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::bc::DirichletConditions;
+use fenris::assembly::local::SourceFunction;
+use fenris::assembly::operators::Operator;
+use fenris::index::{DofIndex, ElementIndex, NodeIndex};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::model::stokes::{MiniStokesAssembler, StokesAssembler};
+use fenris::nalgebra::allocator::Allocator;
+use fenris::nalgebra::{
+    DMatrix, DVector, DefaultAllocator, DimName, OPoint, OVector, Point2, Vector2, Vector3, U2, U3,
+};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris::space::{
+    BubbleEnrichedSpace, FiniteElementConnectivity, FiniteElementSpace, TaylorHoodHexMesh, TaylorHoodMesh,
+    TaylorHoodQuadMesh2d, TaylorHoodTetMesh, TaylorHoodTriMesh2d,
+};
+use fenris::SmallDim;
+use matrixcompare::assert_scalar_eq;
+
+struct ConstantForce<D: SmallDim>(OVector<f64, D>)
+where
+    DefaultAllocator: BiDimAllocator<f64, D, D>;
+
+impl<D: SmallDim> Operator<f64, D> for ConstantForce<D>
+where
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    type SolutionDim = D;
+    type Parameters = ();
+}
+
+impl<D: SmallDim> SourceFunction<f64, D> for ConstantForce<D>
+where
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    fn evaluate(&self, _coords: &OPoint<f64, D>, _data: &()) -> OVector<f64, D> {
+        self.0.clone()
+    }
+}
+
+fn is_on_unit_box_boundary<D: DimName>(x: &OPoint<f64, D>) -> bool
+where
+    DefaultAllocator: Allocator<f64, D>,
+{
+    x.iter().any(|&x_i| x_i == 0.0 || x_i == 1.0)
+}
+
+fn solve_dense(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> DVector<f64> {
+    DMatrix::from(matrix).lu().solve(rhs).unwrap()
+}
+
+/// Solves the Stokes system with the exact velocity prescribed on the boundary and the exact
+/// pressure prescribed at the first pressure node, and checks that the exact solution is
+/// reproduced at the nodes.
+fn assert_reproduces_exact_solution<D: DimName>(
+    mut matrix: CsrMatrix<f64>,
+    mut rhs: DVector<f64>,
+    velocity_vertices: &[OPoint<f64, D>],
+    pressure_vertices: &[OPoint<f64, D>],
+    u_exact: impl Fn(&OPoint<f64, D>) -> OVector<f64, D>,
+    p_exact: impl Fn(&OPoint<f64, D>) -> f64,
+) where
+    DefaultAllocator: Allocator<f64, D>,
+{
+    let d = D::dim();
+    let num_velocity_dofs = d * velocity_vertices.len();
+    let mut dofs = Vec::new();
+    let mut values = Vec::new();
+    for (i, x) in velocity_vertices.iter().enumerate() {
+        if is_on_unit_box_boundary(x) {
+            dofs.extend((0..d).map(|j| DofIndex::new(d * i + j)));
+            values.extend(u_exact(x).iter());
+        }
+    }
+    dofs.push(DofIndex::new(num_velocity_dofs));
+    values.push(p_exact(&pressure_vertices[0]));
+    let bc = DirichletConditions::try_from_dofs_and_values(rhs.len(), &dofs, &values).unwrap();
+    bc.apply_row_replacement(&mut matrix, &mut rhs).unwrap();
+
+    let solution = solve_dense(&matrix, &rhs);
+    for (i, x) in velocity_vertices.iter().enumerate() {
+        for (j, u_j) in u_exact(x).iter().enumerate() {
+            assert_scalar_eq!(solution[d * i + j], *u_j, comp = abs, tol = 1e-10);
+        }
+    }
+    for (i, x) in pressure_vertices.iter().enumerate() {
+        assert_scalar_eq!(solution[num_velocity_dofs + i], p_exact(x), comp = abs, tol = 1e-9);
+    }
+}
+
+#[test]
+fn taylor_hood_meshes_have_expected_layouts() {
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let taylor_hood = TaylorHoodQuadMesh2d::from(quad_mesh.clone());
+    assert_eq!(taylor_hood.pressure_mesh(), &quad_mesh);
+    assert_eq!(taylor_hood.velocity_mesh().num_elements(), 4);
+    assert_eq!(taylor_hood.velocity_mesh().vertices().len(), 25);
+    let layout = taylor_hood.layout();
+    assert_eq!(layout.num_blocks(), 2);
+    assert_eq!(layout.block_size(TaylorHoodQuadMesh2d::<f64>::VELOCITY_BLOCK), 50);
+    assert_eq!(layout.block_size(TaylorHoodQuadMesh2d::<f64>::PRESSURE_BLOCK), 9);
+
+    let taylor_hood = TaylorHoodTriMesh2d::from(create_unit_square_uniform_tri_mesh_2d::<f64>(2));
+    assert_eq!(taylor_hood.velocity_mesh().num_elements(), 8);
+    assert_eq!(taylor_hood.layout().num_dofs(), 2 * 25 + 9);
+
+    let taylor_hood = TaylorHoodHexMesh::from(create_unit_box_uniform_hex_mesh_3d::<f64>(2));
+    assert_eq!(taylor_hood.layout().num_dofs(), 3 * 125 + 27);
+
+    let taylor_hood = TaylorHoodTetMesh::from(create_unit_box_uniform_tet_mesh_3d::<f64>(1));
+    assert_eq!(
+        taylor_hood.velocity_mesh().num_elements(),
+        taylor_hood.pressure_mesh().num_elements()
+    );
+    assert_eq!(
+        taylor_hood.layout().block_size(1),
+        taylor_hood.pressure_mesh().vertices().len()
+    );
+
+    // Meshes with different elements are rejected
+    let result = TaylorHoodMesh::try_from_meshes(
+        TaylorHoodQuadMesh2d::from(create_unit_square_uniform_quad_mesh_2d::<f64>(2))
+            .velocity_mesh()
+            .clone(),
+        create_unit_square_uniform_quad_mesh_2d::<f64>(3),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn stokes_assembler_reproduces_poiseuille_flow_2d() {
+    // The quadratic velocity u = (y (1 - y), 0) and linear pressure p = 2 mu (1 - x) satisfy the
+    // Stokes equations without body force, and are therefore reproduced by Taylor-Hood elements
+    let mu = 0.5;
+    let u_exact = |x: &Point2<f64>| Vector2::new(x.y * (1.0 - x.y), 0.0);
+    let p_exact = |x: &Point2<f64>| 2.0 * mu * (1.0 - x.x);
+    let no_force = ConstantForce(Vector2::zeros());
+
+    let quad = TaylorHoodQuadMesh2d::from(create_unit_square_uniform_quad_mesh_2d(3));
+    let assembler =
+        StokesAssembler::from_taylor_hood_mesh(&quad, quadrature::tensor::quadrilateral_gauss(3)).with_viscosity(mu);
+    let matrix = assembler.assemble_matrix().unwrap();
+    let rhs = assembler.assemble_rhs(&no_force).unwrap();
+    assert_eq!(rhs.amax(), 0.0);
+    assert_eq!(matrix.nrows(), assembler.layout().num_dofs());
+    let dense = DMatrix::from(&matrix);
+    assert_eq!(dense, dense.transpose());
+    assert_reproduces_exact_solution::<U2>(
+        matrix,
+        rhs,
+        quad.velocity_mesh().vertices(),
+        quad.pressure_mesh().vertices(),
+        u_exact,
+        p_exact,
+    );
+
+    let tri = TaylorHoodTriMesh2d::from(create_unit_square_uniform_tri_mesh_2d(3));
+    let assembler =
+        StokesAssembler::from_taylor_hood_mesh(&tri, quadrature::total_order::triangle(2).unwrap()).with_viscosity(mu);
+    assert_reproduces_exact_solution::<U2>(
+        assembler.assemble_matrix().unwrap(),
+        assembler.assemble_rhs(&no_force).unwrap(),
+        tri.velocity_mesh().vertices(),
+        tri.pressure_mesh().vertices(),
+        u_exact,
+        p_exact,
+    );
+}
+
+#[test]
+fn stokes_assembler_reproduces_hydrostatic_pressure_3d() {
+    // A constant body force f = (0, 0, -g) is balanced by the pressure p = g (1 - z) at rest
+    let g = 3.0;
+    let force = ConstantForce(Vector3::new(0.0, 0.0, -g));
+    let u_exact = |_: &OPoint<f64, U3>| Vector3::zeros();
+    let p_exact = |x: &OPoint<f64, U3>| g * (1.0 - x.z);
+
+    let tet = TaylorHoodTetMesh::from(create_unit_box_uniform_tet_mesh_3d(1));
+    let assembler = StokesAssembler::from_taylor_hood_mesh(&tet, quadrature::total_order::tetrahedron(2).unwrap())
+        .with_viscosity(2.0);
+    assert_reproduces_exact_solution::<U3>(
+        assembler.assemble_matrix().unwrap(),
+        assembler.assemble_rhs(&force).unwrap(),
+        tet.velocity_mesh().vertices(),
+        tet.pressure_mesh().vertices(),
+        u_exact,
+        p_exact,
+    );
+}
+
+#[test]
+fn bubble_enriched_space_appends_element_bubbles() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let space = BubbleEnrichedSpace::from_space(mesh.clone());
+    assert_eq!(space.num_elements(), 8);
+    assert_eq!(space.num_nodes(), 9 + 8);
+    assert_eq!(space.element_node_count(ElementIndex::new(3)), 4);
+    let mut nodes = [NodeIndex::default(); 4];
+    space.populate_element_nodes(&mut nodes, ElementIndex::new(3));
+    assert_eq!(NodeIndex::as_raw_slice(&nodes[..3]), mesh.connectivity()[3].0);
+    assert_eq!(nodes[3].index(), space.bubble_node(3));
+    assert_eq!(space.bubble_node(3), 12);
+
+    // The bubble vanishes at the vertices and is one at the barycenter
+    let mut basis = [0.0; 4];
+    space.populate_element_basis(3, &mut basis, &Point2::new(-1.0, -1.0));
+    assert_eq!(basis, [1.0, 0.0, 0.0, 0.0]);
+    space.populate_element_basis(3, &mut basis, &Point2::new(-1.0 / 3.0, -1.0 / 3.0));
+    assert_scalar_eq!(basis[3], 1.0, comp = abs, tol = 1e-14);
+
+    // Compare gradients with finite differences
+    let xi = Point2::new(-0.4, -0.2);
+    let mut gradients = DMatrix::zeros(2, 4);
+    space.populate_element_gradients(3, gradients.as_view_mut(), &xi);
+    let h = 1e-6;
+    for k in 0..2 {
+        let (mut basis_plus, mut basis_minus) = ([0.0; 4], [0.0; 4]);
+        let mut xi_plus = xi;
+        let mut xi_minus = xi;
+        xi_plus[k] += h;
+        xi_minus[k] -= h;
+        space.populate_element_basis(3, &mut basis_plus, &xi_plus);
+        space.populate_element_basis(3, &mut basis_minus, &xi_minus);
+        for i in 0..4 {
+            let fd = (basis_plus[i] - basis_minus[i]) / (2.0 * h);
+            assert_scalar_eq!(gradients[(k, i)], fd, comp = abs, tol = 1e-8);
+        }
+    }
+}
+
+#[test]
+fn mini_stokes_assembler_reproduces_linear_solutions() {
+    // The linear velocity u = (x, -y) and pressure p = 1 - x satisfy the Stokes equations with
+    // the constant body force f = (-1, 0), and lie in the MINI spaces (with vanishing bubbles)
+    let u_exact = |x: &Point2<f64>| Vector2::new(x.x, -x.y);
+    let p_exact = |x: &Point2<f64>| 1.0 - x.x;
+    let force = ConstantForce(Vector2::new(-1.0, 0.0));
+
+    let mesh = create_unit_square_uniform_tri_mesh_2d(3);
+    let space = BubbleEnrichedSpace::from_space(mesh.clone());
+    let assembler = MiniStokesAssembler::new(&space, quadrature::total_order::triangle(4).unwrap()).with_viscosity(0.5);
+    let matrix = assembler.assemble_matrix().unwrap();
+    assert_eq!(matrix.nrows(), assembler.layout().num_dofs());
+    assert_eq!(assembler.layout().num_dofs(), 3 * 16);
+    let dense = DMatrix::from(&matrix);
+    assert_scalar_eq!((&dense - dense.transpose()).amax(), 0.0, comp = abs, tol = 1e-12);
+    assert_reproduces_exact_solution::<U2>(
+        matrix,
+        assembler.assemble_rhs(&force).unwrap(),
+        mesh.vertices(),
+        mesh.vertices(),
+        u_exact,
+        p_exact,
+    );
+
+    // Hydrostatic pressure in 3D
+    let g = 3.0;
+    let force = ConstantForce(Vector3::new(0.0, 0.0, -g));
+    let mesh = create_unit_box_uniform_tet_mesh_3d(1);
+    let space = BubbleEnrichedSpace::from_space(mesh.clone());
+    let assembler =
+        MiniStokesAssembler::new(&space, quadrature::total_order::tetrahedron(6).unwrap()).with_viscosity(2.0);
+    assert_reproduces_exact_solution::<U3>(
+        assembler.assemble_matrix().unwrap(),
+        assembler.assemble_rhs(&force).unwrap(),
+        mesh.vertices(),
+        mesh.vertices(),
+        |_| Vector3::zeros(),
+        |x| g * (1.0 - x.z),
+    );
+}
+
+#[test]
+fn mini_stokes_assembler_condensation_matches_uncondensed_system() {
+    let force = ConstantForce(Vector2::new(1.0, -2.0));
+    let mesh = create_unit_square_uniform_tri_mesh_2d(3);
+    let space = BubbleEnrichedSpace::from_space(mesh.clone());
+    let quadrature = quadrature::total_order::triangle(4).unwrap();
+    let num_vertices = mesh.vertices().len();
+    let num_velocity_nodes = space.num_nodes();
+
+    // Driven cavity: unit tangential velocity on the top boundary, zero elsewhere
+    let boundary_velocity = |x: &Point2<f64>| if x.y == 1.0 { 1.0 } else { 0.0 };
+    let constrained_dofs = |pressure_offset: usize| {
+        let mut dofs = Vec::new();
+        let mut values = Vec::new();
+        for (i, x) in mesh.vertices().iter().enumerate() {
+            if is_on_unit_box_boundary(x) {
+                dofs.extend(NodeIndex::new(i).dofs(2));
+                values.extend([boundary_velocity(x), 0.0]);
+            }
+        }
+        dofs.push(DofIndex::new(pressure_offset));
+        values.push(0.0);
+        (dofs, values)
+    };
+    let solve = |mut matrix: CsrMatrix<f64>, mut rhs: DVector<f64>, pressure_offset: usize| {
+        let (dofs, values) = constrained_dofs(pressure_offset);
+        let bc = DirichletConditions::try_from_dofs_and_values(rhs.len(), &dofs, &values).unwrap();
+        bc.apply_row_replacement(&mut matrix, &mut rhs).unwrap();
+        solve_dense(&matrix, &rhs)
+    };
+
+    let full_assembler = StokesAssembler::new(&space, &mesh, quadrature.clone()).with_viscosity(0.3);
+    let full_solution = solve(
+        full_assembler.assemble_matrix().unwrap(),
+        full_assembler.assemble_rhs(&force).unwrap(),
+        2 * num_velocity_nodes,
+    );
+
+    let assembler = MiniStokesAssembler::new(&space, quadrature).with_viscosity(0.3);
+    let condensed_solution = solve(
+        assembler.assemble_matrix().unwrap(),
+        assembler.assemble_rhs(&force).unwrap(),
+        2 * num_vertices,
+    );
+    let velocity = assembler
+        .recover_velocity(&force, &condensed_solution)
+        .unwrap();
+
+    assert_eq!(velocity.len(), 2 * num_velocity_nodes);
+    let full_velocity = full_solution.rows(0, 2 * num_velocity_nodes);
+    assert_scalar_eq!((&velocity - full_velocity).amax(), 0.0, comp = abs, tol = 1e-10);
+    // The bubbles are active for this flow
+    assert!(
+        velocity
+            .rows(2 * num_vertices, 2 * mesh.connectivity().len())
+            .amax()
+            > 1e-3
+    );
+    let full_pressure = full_solution.rows(2 * num_velocity_nodes, num_vertices);
+    let pressure = condensed_solution.rows(2 * num_vertices, num_vertices);
+    assert_scalar_eq!((pressure - full_pressure).amax(), 0.0, comp = abs, tol = 1e-9);
+}
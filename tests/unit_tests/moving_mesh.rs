@@ -0,0 +1,143 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    ElementConvectionAssembler, ElementSourceAssemblerBuilder, SourceFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::Operator;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix2, OPoint, Point2, Vector1, Vector2, U1, U2};
+use fenris::quadrature;
+use fenris::space::{FiniteElementConnectivity, MovingMeshSpace};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// The affine motion x = X + t (b + A X).
+fn affine_motion(x: &Point2<f64>, t: f64) -> Point2<f64> {
+    let a = Matrix2::new(0.2, 0.1, -0.1, 0.3);
+    let b = Vector2::new(1.0, -0.5);
+    x + t * (b + a * x.coords)
+}
+
+fn affine_velocity(x: &Point2<f64>) -> Vector2<f64> {
+    let a = Matrix2::new(0.2, 0.1, -0.1, 0.3);
+    let b = Vector2::new(1.0, -0.5);
+    b + a * x.coords
+}
+
+#[test]
+fn moving_mesh_space_prescribed_motion() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let mut space = MovingMeshSpace::from_mesh(mesh.clone()).with_time(1.0);
+    assert_eq!(space.num_nodes(), mesh.vertices().len());
+    assert!(space.mesh_velocity().iter().all(|&w| w == 0.0));
+
+    space.move_with(affine_motion, 1.5).unwrap();
+    assert_scalar_eq!(space.time(), 1.5);
+    assert_scalar_eq!(space.time_step(), 0.5);
+    assert_eq!(space.reference_vertices(), mesh.vertices());
+    // The motion was prescribed relative to the reference configuration, so the previous
+    // configuration is the initial mesh and not the motion at t = 1
+    assert_eq!(space.previous_vertices(), mesh.vertices());
+
+    space.move_with(affine_motion, 2.0).unwrap();
+    let d = 2;
+    for (i, x_ref) in space.reference_vertices().iter().enumerate() {
+        assert_matrix_eq!(
+            space.previous_vertices()[i].coords,
+            affine_motion(x_ref, 1.5).coords,
+            comp = abs,
+            tol = 1e-14
+        );
+        assert_matrix_eq!(
+            space.mesh().vertices()[i].coords,
+            affine_motion(x_ref, 2.0).coords,
+            comp = abs,
+            tol = 1e-14
+        );
+        // For motions that are linear in time, the discrete mesh velocity is exact
+        let w = space.mesh_velocity().rows(d * i, d);
+        assert_matrix_eq!(w, affine_velocity(x_ref), comp = abs, tol = 1e-12);
+    }
+
+    let midpoint_mesh = space.intermediate_mesh(0.5);
+    for (x_mid, x_ref) in midpoint_mesh
+        .vertices()
+        .iter()
+        .zip(space.reference_vertices())
+    {
+        assert_matrix_eq!(x_mid.coords, affine_motion(x_ref, 1.75).coords, comp = abs, tol = 1e-14);
+    }
+    assert_eq!(space.previous_mesh().vertices(), space.previous_vertices());
+
+    // Points are mapped between configurations through their reference coordinates, which for an
+    // affine motion coincides with the motion itself
+    let x_ref = Point2::new(0.3, 0.2);
+    let x_prev = affine_motion(&x_ref, 1.5);
+    let x = space.map_previous_to_current(0, &x_prev).unwrap();
+    assert_matrix_eq!(x.coords, affine_motion(&x_ref, 2.0).coords, comp = abs, tol = 1e-10);
+
+    // Invalid moves leave the space unchanged
+    assert!(space.move_with(affine_motion, 2.0).is_err());
+    assert!(space.move_vertices(vec![Point2::origin(); 3], 0.1).is_err());
+    assert_scalar_eq!(space.time(), 2.0);
+}
+
+struct UnitSource;
+
+impl Operator<f64, U2> for UnitSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U2> for UnitSource {
+    fn evaluate(&self, _coords: &OPoint<f64, U2>, _data: &Self::Parameters) -> Vector1<f64> {
+        Vector1::new(1.0)
+    }
+}
+
+#[test]
+fn moving_mesh_space_satisfies_discrete_geometric_conservation_law() {
+    // The change in area of the domain over a time step must equal the integral of the
+    // divergence of the mesh velocity over time. For affine motions in 2D the area is quadratic
+    // in time, so that this holds exactly when evaluating the integral in the midpoint configuration:
+    //  |Ω^{n+1}| - |Ω^n| = dt int_{Ω^{n+1/2}} div w dx
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let mut space = MovingMeshSpace::from_mesh(mesh);
+    space.move_with(affine_motion, 0.4).unwrap();
+    space.move_with(affine_motion, 0.7).unwrap();
+    let dt = space.time_step();
+
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+    let area = |mesh: &QuadMesh2d<f64>| {
+        let assembler = ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(mesh)
+            .with_source(&UnitSource)
+            .with_quadrature_table(&qtable)
+            .build();
+        VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap()
+            .sum()
+    };
+    let area_change = area(space.mesh()) - area(&space.previous_mesh());
+
+    // With the convection matrix C_i for the unit velocity e_i, we have 1^T C_i u = int du/dx_i dx,
+    // so that int div w dx = 1^T C_x w_x + 1^T C_y w_y
+    let midpoint_mesh = space.intermediate_mesh(0.5);
+    let n = midpoint_mesh.vertices().len();
+    let ones = DVector::repeat(n, 1.0);
+    let w = space.mesh_velocity();
+    let div_w_integral: f64 = (0..2)
+        .map(|i| {
+            let unit_velocity = DVector::from_fn(2 * n, |j, _| if j % 2 == i { 1.0 } else { 0.0 });
+            let assembler = ElementConvectionAssembler::with_solution_dim(1)
+                .with_space(&midpoint_mesh)
+                .with_quadrature_table(&qtable)
+                .with_velocity(&unit_velocity);
+            let c = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+            let w_i = DVector::from_iterator(n, w.iter().skip(i).step_by(2).copied());
+            ones.dot(&(c * w_i))
+        })
+        .sum();
+
+    assert_scalar_eq!(area_change, dt * div_w_integral, comp = abs, tol = 1e-12);
+}
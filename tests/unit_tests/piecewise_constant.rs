@@ -0,0 +1,82 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::index::NodeIndex;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::nalgebra::{point, DVector};
+use fenris::quadrature;
+use fenris::space::{FiniteElementConnectivity, FiniteElementSpace, PiecewiseConstantSpace};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn piecewise_constant_space_has_one_node_per_element() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let space = PiecewiseConstantSpace::from_space(mesh.clone());
+    assert_eq!(space.num_elements(), mesh.connectivity().len());
+    assert_eq!(space.num_nodes(), space.num_elements());
+
+    let xi = point![-0.2, 0.1];
+    let mut node = [NodeIndex::default()];
+    let mut basis = [0.0];
+    for element_index in 0..space.num_elements() {
+        assert_eq!(space.element_node_count(element_index.into()), 1);
+        space.populate_element_nodes(&mut node, element_index.into());
+        assert_eq!(node, [NodeIndex::new(element_index)]);
+        space.populate_element_basis(element_index, &mut basis, &xi);
+        assert_eq!(basis, [1.0]);
+        assert_eq!(
+            space.map_element_reference_coords(element_index, &xi),
+            mesh.map_element_reference_coords(element_index, &xi)
+        );
+    }
+}
+
+#[test]
+fn piecewise_constant_mass_matrix_is_diagonal_with_element_measures() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let space = PiecewiseConstantSpace::from_space(mesh);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::total_order::triangle(2).unwrap(),
+        Density(1.0),
+    );
+
+    let measures = space.element_measures(&qtable);
+    assert_scalar_eq!(measures.sum(), 1.0, comp = abs, tol = 1e-12);
+    for &measure in measures.iter() {
+        assert_scalar_eq!(measure, 1.0 / 18.0, comp = abs, tol = 1e-12);
+    }
+
+    // The generic mass assembler must agree with the explicit diagonal mass matrix
+    let solution_dim = 2;
+    let mass = space.assemble_mass_matrix(&qtable, solution_dim);
+    let assembler = ElementMassAssembler::with_solution_dim(solution_dim)
+        .with_space(&space)
+        .with_quadrature_table(&qtable);
+    let expected = CsrAssembler::default().assemble(&assembler).unwrap();
+    assert_eq!(mass.nnz(), solution_dim * space.num_nodes());
+    assert_matrix_eq!(mass, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn piecewise_constant_projection_computes_element_averages() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let space = PiecewiseConstantSpace::from_space(mesh.clone());
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    // The average of a linear function over a parallelogram is its value at the centroid
+    let u = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices()
+            .iter()
+            .flat_map(|x| [x.x + 2.0 * x.y, 3.0 - x.x]),
+    );
+    let u_p0 = space.project_from_space(&qtable, &u, 2).unwrap();
+    assert_eq!(u_p0.len(), 2 * space.num_nodes());
+    for element_index in 0..space.num_elements() {
+        let x = space.map_element_reference_coords(element_index, &point![0.0, 0.0]);
+        assert_scalar_eq!(u_p0[2 * element_index], x.x + 2.0 * x.y, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(u_p0[2 * element_index + 1], 3.0 - x.x, comp = abs, tol = 1e-12);
+    }
+
+    let result = space.project_l2(&qtable, 2, |_, _| Ok(DVector::zeros(1)));
+    assert!(result.is_err());
+}
@@ -0,0 +1,96 @@
+use fenris::nalgebra::{Point1, Point2, Point3};
+use fenris::quadrature::adaptive::AdaptiveSubdivision;
+use fenris::quadrature::tensor;
+use matrixcompare::assert_scalar_eq;
+use std::f64::consts::PI;
+
+#[test]
+fn adaptive_quadrature_integrates_polynomials_without_refinement() {
+    let adaptive = AdaptiveSubdivision::new().with_strength(3);
+
+    let result = adaptive.integrate_segment(|x: &Point1<f64>| x.x.powi(3) + x.x.powi(2));
+    assert!(result.converged());
+    assert_scalar_eq!(result.integral(), 2.0 / 3.0, comp = abs, tol = 1e-14);
+    // The comparison rule is applied to the segment, and the base rule to its two children
+    assert_eq!(result.num_evaluations(), 3 + 2 * 2);
+    assert_eq!(result.quadrature().0.len(), 2 * 2);
+
+    let result = adaptive
+        .integrate_tetrahedron(|x: &Point3<f64>| x.x * x.y + 1.0)
+        .unwrap();
+    assert!(result.converged());
+    assert_scalar_eq!(result.integral(), 4.0 / 3.0 + 4.0 / 15.0, comp = abs, tol = 1e-13);
+}
+
+#[test]
+fn adaptive_quadrature_resolves_discontinuous_integrands() {
+    let indicator = |x: &Point2<f64>| if x.coords.norm() < 1.0 { 1.0 } else { 0.0 };
+    let result = AdaptiveSubdivision::new()
+        .with_absolute_tolerance(1e-2)
+        .integrate_quadrilateral(indicator);
+    assert!(result.converged());
+    assert!(result.error_estimate() <= 1e-2);
+    assert_scalar_eq!(result.integral(), PI, comp = abs, tol = 1e-2);
+
+    // The adapted rule reproduces the integral
+    let (weights, points) = result.quadrature();
+    let integral: f64 = weights
+        .iter()
+        .zip(points)
+        .map(|(w, x)| w * indicator(x))
+        .sum();
+    assert_scalar_eq!(integral, result.integral(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(weights.iter().sum::<f64>(), 4.0, comp = abs, tol = 1e-12);
+
+    // A fixed rule with the same number of points is much less accurate
+    let n = (weights.len() as f64).sqrt() as usize;
+    let (fixed_weights, fixed_points) = tensor::quadrilateral_gauss::<f64>(n);
+    let fixed_integral: f64 = fixed_weights
+        .iter()
+        .zip(&fixed_points)
+        .map(|(w, x)| w * indicator(x))
+        .sum();
+    assert!((fixed_integral - PI).abs() > 5.0 * (result.integral() - PI).abs());
+
+    // The region x < 0.3 of the reference triangle has area 1.755
+    let result = AdaptiveSubdivision::new()
+        .with_absolute_tolerance(1e-3)
+        .with_max_levels(16)
+        .integrate_triangle(|x: &Point2<f64>| if x.x < 0.3 { 1.0 } else { 0.0 })
+        .unwrap();
+    assert!(result.converged());
+    assert_scalar_eq!(result.integral(), 1.755, comp = abs, tol = 1e-3);
+
+    // The region x < 0.2 of the reference tetrahedron has volume 4/3 * (1 - 0.4^3)
+    let result = AdaptiveSubdivision::new()
+        .with_absolute_tolerance(1e-2)
+        .integrate_tetrahedron(|x: &Point3<f64>| if x.x < 0.2 { 1.0 } else { 0.0 })
+        .unwrap();
+    assert!(result.converged());
+    assert_scalar_eq!(result.integral(), 4.0 / 3.0 * 0.936, comp = abs, tol = 1e-2);
+}
+
+#[test]
+fn adaptive_quadrature_resolves_sharply_varying_integrands() {
+    // The integral of exp(-a |x|^2) over [-1, 1]^3 is (sqrt(pi / a) * erf(sqrt(a)))^3, where
+    // erf(sqrt(a)) = 1 to machine precision for a = 50
+    let a = 50.0;
+    let expected = (PI / a).sqrt().powi(3);
+    let result = AdaptiveSubdivision::new()
+        .with_strength(7)
+        .with_absolute_tolerance(0.0)
+        .with_relative_tolerance(1e-6)
+        .integrate_hexahedron(|x: &Point3<f64>| (-a * x.coords.norm_squared()).exp());
+    assert!(result.converged());
+    assert_scalar_eq!(result.integral(), expected, comp = abs, tol = 1e-6 * expected);
+}
+
+#[test]
+fn adaptive_quadrature_reports_unresolved_integrals() {
+    let result = AdaptiveSubdivision::new()
+        .with_max_levels(2)
+        .integrate_quadrilateral(|x: &Point2<f64>| if x.coords.norm() < 1.0 { 1.0 } else { 0.0 });
+    assert!(!result.converged());
+    assert!(result.error_estimate() > AdaptiveSubdivision::new().absolute_tolerance());
+    assert_scalar_eq!(result.integral(), PI, comp = abs, tol = 0.5);
+}
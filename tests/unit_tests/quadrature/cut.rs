@@ -0,0 +1,188 @@
+use fenris::nalgebra::{Point2, Point3, Vector2};
+use fenris::quadrature::cut::{moment_fit, LevelSetSubdivision};
+use fenris::quadrature::{tensor, Quadrature};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+fn total_weight<Q: Quadrature<f64, D>, D: fenris::nalgebra::DimName>(quadrature: &Q) -> f64
+where
+    fenris::nalgebra::DefaultAllocator: fenris::nalgebra::allocator::Allocator<f64, D>,
+{
+    quadrature.weights().iter().sum()
+}
+
+#[test]
+fn cut_triangle_by_straight_line_is_exact() {
+    // The line x = 0 splits the reference triangle into a quadrilateral and a triangle
+    let cut = LevelSetSubdivision::new()
+        .with_max_refinement_levels(0)
+        .cut_triangle(|p: &Point2<f64>| p.x)
+        .unwrap();
+    assert!(cut.is_cut());
+    assert_scalar_eq!(total_weight(cut.negative()), 1.5, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(total_weight(cut.positive()), 0.5, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(total_weight(cut.interface()), 1.0, comp = abs, tol = 1e-14);
+
+    // Quadratic polynomials are integrated exactly with the default strength
+    let f = |p: &Point2<f64>| p.x * p.x + p.x * p.y;
+    assert_scalar_eq!(cut.positive().integrate(f), -1.0 / 24.0, comp = abs, tol = 1e-14);
+    // The interface is the segment from (0, -1) to (0, 0)
+    assert_scalar_eq!(cut.interface().integrate(|p| p.y), -0.5, comp = abs, tol = 1e-14);
+    assert!(cut.interface().points().iter().all(|p| p.x.abs() < 1e-14));
+    for normal in cut.interface_normals() {
+        assert_matrix_eq!(normal, Vector2::new(1.0, 0.0), comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn uncut_elements_produce_full_rules() {
+    let cut = LevelSetSubdivision::new()
+        .cut_quadrilateral(|p: &Point2<f64>| p.x + 3.0)
+        .unwrap();
+    assert!(!cut.is_cut());
+    assert!(cut.negative().weights().is_empty());
+    assert_scalar_eq!(total_weight(cut.positive()), 4.0, comp = abs, tol = 1e-14);
+    assert!(cut.interface_normals().is_empty());
+
+    let cut = LevelSetSubdivision::new()
+        .cut_tetrahedron(|_: &Point3<f64>| -1.0)
+        .unwrap();
+    assert!(cut.positive().weights().is_empty());
+    assert_scalar_eq!(total_weight(cut.negative()), 4.0 / 3.0, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn cut_tetrahedron_by_planes_is_exact() {
+    let subdivision = LevelSetSubdivision::new().with_max_refinement_levels(0);
+
+    // A single vertex is separated from the others. The cut-off tetrahedron at the origin vertex
+    // is scaled by 1/2 in each direction
+    let cut = subdivision
+        .cut_tetrahedron(|p: &Point3<f64>| p.x + p.y + p.z + 2.0)
+        .unwrap();
+    assert_scalar_eq!(total_weight(cut.negative()), 1.0 / 6.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        total_weight(cut.positive()),
+        4.0 / 3.0 - 1.0 / 6.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    // Equilateral triangle with side length sqrt(2)
+    assert_scalar_eq!(
+        total_weight(cut.interface()),
+        3.0f64.sqrt() / 2.0,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // Two vertices on each side. The part with z < 0 of the tetrahedron {x, y, z >= -1,
+    // x + y + z <= -1} is the full tetrahedron minus the part above z = 0, which is empty here,
+    // so use z = -1/2 instead
+    let cut = subdivision
+        .cut_tetrahedron(|p: &Point3<f64>| p.x + 0.5)
+        .unwrap();
+    // The part with x >= -1/2 is a tetrahedron scaled by 3/4
+    let positive_volume = 4.0 / 3.0 * 0.75f64.powi(3);
+    assert_scalar_eq!(total_weight(cut.positive()), positive_volume, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(
+        total_weight(cut.negative()),
+        4.0 / 3.0 - positive_volume,
+        comp = abs,
+        tol = 1e-14
+    );
+    // The interface is a right triangle with legs of length 3/2
+    assert_scalar_eq!(total_weight(cut.interface()), 9.0 / 8.0, comp = abs, tol = 1e-14);
+    assert_eq!(cut.interface_normals().len(), cut.interface().weights().len());
+
+    let cut = subdivision
+        .cut_tetrahedron(|p: &Point3<f64>| p.x - p.y)
+        .unwrap();
+    // The plane x = y passes through two vertices and splits the tetrahedron symmetrically
+    assert_scalar_eq!(total_weight(cut.negative()), 2.0 / 3.0, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(total_weight(cut.positive()), 2.0 / 3.0, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn cut_quadrilateral_by_circle_converges() {
+    let r = 0.6;
+    let circle = |p: &Point2<f64>| p.coords.norm() - r;
+    let mut errors = Vec::new();
+    for levels in [3, 4, 5] {
+        let cut = LevelSetSubdivision::new()
+            .with_max_refinement_levels(levels)
+            .cut_quadrilateral(circle)
+            .unwrap();
+        let area = total_weight(cut.negative());
+        assert_scalar_eq!(area + total_weight(cut.positive()), 4.0, comp = abs, tol = 1e-13);
+        errors.push((area - PI * r * r).abs());
+
+        let length = total_weight(cut.interface());
+        assert_scalar_eq!(length, 2.0 * PI * r, comp = abs, tol = 5e-2);
+        for (normal, p) in cut.interface_normals().iter().zip(cut.interface().points()) {
+            assert!(normal.dot(&p.coords.normalize()) > 0.95);
+        }
+    }
+    // Second-order convergence in the size of the leaf triangles
+    assert!(errors[1] < 0.3 * errors[0]);
+    assert!(errors[2] < 0.3 * errors[1]);
+    assert!(errors[2] < 3e-3);
+}
+
+#[test]
+fn cut_hexahedron_by_sphere_approximates_ball() {
+    let r = 0.7;
+    let cut = LevelSetSubdivision::new()
+        .with_max_refinement_levels(4)
+        .cut_hexahedron(|p: &Point3<f64>| p.coords.norm() - r)
+        .unwrap();
+    assert_scalar_eq!(
+        total_weight(cut.negative()) + total_weight(cut.positive()),
+        8.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    // The leaf tetrahedra are inscribed in the sphere, so the errors are relatively large
+    assert_scalar_eq!(
+        total_weight(cut.negative()),
+        4.0 / 3.0 * PI * r.powi(3),
+        comp = abs,
+        tol = 3e-2
+    );
+    assert_scalar_eq!(total_weight(cut.interface()), 4.0 * PI * r * r, comp = abs, tol = 6e-2);
+    // Second moment of the ball
+    let expected = 4.0 / 15.0 * PI * r.powi(5);
+    assert_scalar_eq!(
+        cut.negative().integrate(|p| p.x * p.x),
+        expected,
+        comp = abs,
+        tol = 1e-2
+    );
+}
+
+#[test]
+fn moment_fitted_rule_reproduces_moments_of_cut_rule() {
+    let cut = LevelSetSubdivision::new()
+        .with_max_refinement_levels(4)
+        .cut_quadrilateral(|p: &Point2<f64>| p.x + 0.5 * p.y * p.y - 0.2)
+        .unwrap();
+    let (_, points) = tensor::quadrilateral_gauss::<f64>(3);
+    let fitted = moment_fit(&points, cut.negative(), 2).unwrap();
+    assert_eq!(fitted.points(), points.as_slice());
+
+    for f in [
+        |_: &Point2<f64>| 1.0,
+        |p: &Point2<f64>| p.x,
+        |p: &Point2<f64>| p.x * p.y,
+        |p: &Point2<f64>| p.y * p.y - p.x,
+    ] {
+        assert_scalar_eq!(
+            fitted.integrate(f),
+            cut.negative().integrate(f),
+            comp = abs,
+            tol = 1e-12
+        );
+    }
+
+    // A single point cannot reproduce the linear moments
+    assert!(moment_fit(&points[..1], cut.negative(), 1).is_err());
+}
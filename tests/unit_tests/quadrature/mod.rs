@@ -1,9 +1,19 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{Hex27Mesh, Quad9Mesh2d};
+use fenris::quadrature::tensor::{try_hexahedron_gauss_lobatto, try_quadrilateral_gauss_lobatto};
+use fenris::quadrature::total_order::{tetrahedron_from_family, triangle_from_family, SimplexRuleFamily};
 use fenris::quadrature::univariate::gauss;
 use fenris::quadrature::{OwnedQuadratureParts, Quadrature};
 use itertools::izip;
+use matrixcompare::assert_scalar_eq;
 use nalgebra::Point1;
 
+mod adaptive;
 mod canonical;
+mod cut;
+mod registry;
 mod subdivide;
 
 #[test]
@@ -21,3 +31,58 @@ fn quadrature_iter() {
 
     assert_eq!(quadrature_iter_collected, quadrature_izip_collected);
 }
+
+#[test]
+fn simplex_rule_families_agree_on_polynomial_integrals() {
+    let f2 = |p: &nalgebra::Point2<f64>| p.x.powi(4) * p.y.powi(3) - p.y;
+    let f3 = |p: &nalgebra::Point3<f64>| p.x.powi(2) * p.y * p.z.powi(4) + p.z;
+    for strength in [7, 8] {
+        let polyquad = triangle_from_family::<f64>(SimplexRuleFamily::Polyquad, strength).unwrap();
+        let gm = triangle_from_family::<f64>(SimplexRuleFamily::GrundmannMoeller, strength).unwrap();
+        assert_scalar_eq!(polyquad.integrate(f2), gm.integrate(f2), comp = abs, tol = 1e-13);
+
+        let polyquad = tetrahedron_from_family::<f64>(SimplexRuleFamily::Polyquad, strength).unwrap();
+        let gm = tetrahedron_from_family::<f64>(SimplexRuleFamily::GrundmannMoeller, strength).unwrap();
+        assert_scalar_eq!(polyquad.integrate(f3), gm.integrate(f3), comp = abs, tol = 1e-13);
+    }
+
+    // Only the Grundmann-Möller family is available beyond the tabulated rules
+    assert!(triangle_from_family::<f64>(SimplexRuleFamily::Polyquad, 30).is_err());
+    let (weights, _) = triangle_from_family::<f64>(SimplexRuleFamily::GrundmannMoeller, 30).unwrap();
+    assert_scalar_eq!(weights.iter().sum::<f64>(), 2.0, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn gauss_lobatto_mass_matrices_are_diagonal_for_matching_lagrange_elements() {
+    let quad9_mesh = Quad9Mesh2d::from(create_unit_square_uniform_quad_mesh_2d::<f64>(2));
+    let hex27_mesh = Hex27Mesh::from(&create_unit_box_uniform_hex_mesh_3d::<f64>(2));
+    let quad_rule = try_quadrilateral_gauss_lobatto::<f64>(3).unwrap();
+    let hex_rule = try_hexahedron_gauss_lobatto::<f64>(3).unwrap();
+    assert!(try_quadrilateral_gauss_lobatto::<f64>(1).is_none());
+
+    let quad_table = UniformQuadratureTable::from_quadrature_and_uniform_data(quad_rule, Density(1.0));
+    let quad_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&quad9_mesh)
+        .with_quadrature_table(&quad_table);
+    let quad_mass = CsrAssembler::default().assemble(&quad_assembler).unwrap();
+
+    let hex_table = UniformQuadratureTable::from_quadrature_and_uniform_data(hex_rule, Density(1.0));
+    let hex_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&hex27_mesh)
+        .with_quadrature_table(&hex_table);
+    let hex_mass = CsrAssembler::default().assemble(&hex_assembler).unwrap();
+
+    for mass in [quad_mass, hex_mass] {
+        let mut total_mass = 0.0;
+        for (i, j, &m_ij) in mass.triplet_iter() {
+            if i == j {
+                assert!(m_ij > 0.0);
+                total_mass += m_ij;
+            } else {
+                assert_scalar_eq!(m_ij, 0.0, comp = abs, tol = 1e-15);
+            }
+        }
+        // The domains have unit measure
+        assert_scalar_eq!(total_mass, 1.0, comp = abs, tol = 1e-13);
+    }
+}
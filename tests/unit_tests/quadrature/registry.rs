@@ -0,0 +1,146 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::local::QuadratureTable;
+use fenris::connectivity::{
+    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Segment2d2Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity, Tri6d3Connectivity,
+};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::Mesh;
+use fenris::quadrature::registry::{BoxRuleFamily, ElementReferenceShape, QuadratureRegistry, ReferenceShape};
+use fenris::quadrature::total_order::SimplexRuleFamily;
+use fenris::quadrature::Quadrature;
+use fenris::space::FiniteElementSpace;
+use fenris::SmallDim;
+use matrixcompare::assert_scalar_eq;
+use nalgebra::{DefaultAllocator, OPoint, Point1, Point2, Point3, U2, U3};
+
+#[test]
+fn registry_rules_have_requested_strength() {
+    let f1 = |p: &Point1<f64>, k: usize| (1.0 + p.x).powi(k as i32);
+    let f2 = |p: &Point2<f64>, k: usize| (1.0 + 0.5 * p.x - 0.3 * p.y).powi(k as i32);
+    let f3 = |p: &Point3<f64>, k: usize| (1.0 + 0.5 * p.x - 0.3 * p.y + 0.2 * p.z).powi(k as i32);
+
+    let tensor = QuadratureRegistry::new();
+    let total_order = QuadratureRegistry::new().with_box_family(BoxRuleFamily::TotalOrder);
+    for registry in [tensor, total_order] {
+        for strength in 0..=8 {
+            // Rules of higher strength serve as reference
+            let exact = tensor
+                .rule::<f64, Segment2d2Connectivity>(20)
+                .unwrap()
+                .integrate(|p| f1(p, strength));
+            let approx = registry
+                .rule::<f64, Segment2d2Connectivity>(strength)
+                .unwrap()
+                .integrate(|p| f1(p, strength));
+            assert_scalar_eq!(approx, exact, comp = abs, tol = 1e-12 * exact.abs());
+
+            for shape in [ReferenceShape::Triangle, ReferenceShape::Quadrilateral] {
+                let rule = registry.rule_for_shape::<f64, U2>(shape, strength).unwrap();
+                let reference = tensor.rule_for_shape::<f64, U2>(shape, 12).unwrap();
+                let exact = reference.integrate(|p| f2(p, strength));
+                assert_scalar_eq!(rule.integrate(|p| f2(p, strength)), exact, comp = abs, tol = 1e-12);
+            }
+
+            for shape in [ReferenceShape::Tetrahedron, ReferenceShape::Hexahedron] {
+                let rule = registry.rule_for_shape::<f64, U3>(shape, strength).unwrap();
+                let reference = tensor.rule_for_shape::<f64, U3>(shape, 10).unwrap();
+                let exact = reference.integrate(|p| f3(p, strength));
+                assert_scalar_eq!(rule.integrate(|p| f3(p, strength)), exact, comp = abs, tol = 1e-12);
+            }
+        }
+    }
+
+    // Tensor-product Gauss rules are selected by default, with the minimal number of points
+    assert_eq!(tensor.rule::<f64, Hex27Connectivity>(5).unwrap().0.len(), 27);
+    assert_eq!(tensor.rule::<f64, Quad4d2Connectivity>(2).unwrap().0.len(), 4);
+    // Surface elements use rules for their reference shape
+    assert_eq!(
+        tensor.rule::<f64, Tri6d3Connectivity>(4).unwrap(),
+        tensor.rule::<f64, Tri3d2Connectivity>(4).unwrap()
+    );
+    assert_eq!(
+        <Tet4Connectivity as ElementReferenceShape<f64>>::REFERENCE_SHAPE,
+        ReferenceShape::Tetrahedron
+    );
+}
+
+#[test]
+fn registry_reports_unavailable_rules() {
+    let registry = QuadratureRegistry::new();
+    assert!(registry.rule::<f64, Tri3d2Connectivity>(40).is_err());
+    let registry = registry.with_simplex_family(SimplexRuleFamily::GrundmannMoeller);
+    assert_eq!(registry.simplex_family(), SimplexRuleFamily::GrundmannMoeller);
+    let (weights, _) = registry.rule::<f64, Tri3d2Connectivity>(40).unwrap();
+    assert_scalar_eq!(weights.iter().sum::<f64>(), 2.0, comp = abs, tol = 1e-8);
+
+    let registry = registry.with_box_family(BoxRuleFamily::TotalOrder);
+    assert!(registry.rule::<f64, Hex8Connectivity>(100).is_err());
+}
+
+/// Integrates a function over a mesh without knowledge of the element type.
+fn integrate_over_mesh<D, C>(mesh: &Mesh<f64, D, C>, strength: usize, f: impl Fn(&OPoint<f64, D>) -> f64) -> f64
+where
+    D: SmallDim,
+    C: ElementReferenceShape<f64, GeometryDim = D, ReferenceDim = D>,
+    Mesh<f64, D, C>: FiniteElementSpace<f64, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let (weights, points) = QuadratureRegistry::new().rule::<f64, C>(strength).unwrap();
+    let mut integral = 0.0;
+    for element_index in 0..mesh.connectivity().len() {
+        for (w, xi) in weights.iter().zip(&points) {
+            let x = mesh.map_element_reference_coords(element_index, xi);
+            let j_det = mesh
+                .element_reference_jacobian(element_index, xi)
+                .determinant();
+            integral += w * j_det.abs() * f(&x);
+        }
+    }
+    integral
+}
+
+#[test]
+fn registry_enables_element_agnostic_integration() {
+    // The integral of x^3 y^2 over the unit square is 1/12
+    let f2 = |x: &Point2<f64>| x.x.powi(3) * x.y.powi(2);
+    let tri_mesh = create_unit_square_uniform_tri_mesh_2d(2);
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    assert_scalar_eq!(
+        integrate_over_mesh(&tri_mesh, 5, f2),
+        1.0 / 12.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(
+        integrate_over_mesh(&quad_mesh, 5, f2),
+        1.0 / 12.0,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    // The integral of x^2 y z^3 over the unit cube is 1/24
+    let f3 = |x: &Point3<f64>| x.x.powi(2) * x.y * x.z.powi(3);
+    let tet_mesh = create_unit_box_uniform_tet_mesh_3d(2);
+    let hex_mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    assert_scalar_eq!(
+        integrate_over_mesh(&tet_mesh, 6, f3),
+        1.0 / 24.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_scalar_eq!(
+        integrate_over_mesh(&hex_mesh, 6, f3),
+        1.0 / 24.0,
+        comp = abs,
+        tol = 1e-14
+    );
+
+    let table = QuadratureRegistry::new()
+        .table_for_mesh(&hex_mesh, 3)
+        .unwrap();
+    assert_eq!(table.element_quadrature_size(0), 8);
+}
@@ -0,0 +1,129 @@
+use fenris::linear_solver::DenseLuSolver;
+use fenris::mesh::procedural::{
+    create_rectangular_uniform_quad_mesh_2d, create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d,
+    create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::nalgebra::{vector, DVector, Point2, Point3, Vector2};
+use fenris::quadrature;
+use fenris::space::{mesh_intersection_quadrature, ConservativeRemap};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn mesh_intersection_quadrature_covers_intersected_cells() {
+    let source = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let target = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let simplex_quadrature = quadrature::total_order::triangle(2).unwrap();
+    let intersections = mesh_intersection_quadrature(&source, &target, &simplex_quadrature).unwrap();
+
+    // Each target cell is exactly covered by its intersections with source cells
+    let mut target_areas = vec![0.0; target.connectivity().len()];
+    let mut source_areas = vec![0.0; source.connectivity().len()];
+    for intersection in &intersections {
+        let area: f64 = intersection.quadrature.0.iter().sum();
+        target_areas[intersection.target_cell] += area;
+        source_areas[intersection.source_cell] += area;
+    }
+    for area in target_areas {
+        assert_scalar_eq!(area, 1.0 / 16.0, comp = abs, tol = 1e-14);
+    }
+    for area in source_areas {
+        assert_scalar_eq!(area, 1.0 / 18.0, comp = abs, tol = 1e-14);
+    }
+
+    // Integrate x * y exactly over the square
+    let integral: f64 = intersections
+        .iter()
+        .flat_map(|intersection| {
+            let (weights, points) = &intersection.quadrature;
+            weights.iter().zip(points).map(|(w, x)| w * x.x * x.y)
+        })
+        .sum();
+    assert_scalar_eq!(integral, 0.25, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn conservative_remap_2d_reproduces_linear_fields_and_conserves_integral() {
+    let source = create_unit_square_uniform_tri_mesh_2d::<f64>(5);
+    let target = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let simplex_quadrature = quadrature::total_order::triangle(4).unwrap();
+    let mut remap = ConservativeRemap::from_meshes(&source, &target, &simplex_quadrature).unwrap();
+
+    let linear = |x: &Point2<f64>| vector![1.0 + 2.0 * x.x - x.y, 3.0 * x.y];
+    let interleave = |vertices: &[Point2<f64>], f: &dyn Fn(&Point2<f64>) -> Vector2<f64>| {
+        DVector::from_iterator(2 * vertices.len(), vertices.iter().flat_map(|v| f(v).data.0[0]))
+    };
+    let u_source = interleave(source.vertices(), &linear);
+    let u_target = remap.remap(u_source.as_view(), 2).unwrap();
+    assert_matrix_eq!(u_target, interleave(target.vertices(), &linear), comp = abs, tol = 1e-9);
+
+    // A nonlinear field is not reproduced, but its integral is preserved
+    let nonlinear = |x: &Point2<f64>| vector![(3.0 * x.x).sin() * x.y, x.x * x.x];
+    let u_source = interleave(source.vertices(), &nonlinear);
+    let mut remap = remap.with_solver(DenseLuSolver);
+    let u_target = remap.remap(u_source.as_view(), 2).unwrap();
+
+    let source_mass = ConservativeRemap::from_meshes(&source, &source, &simplex_quadrature)
+        .unwrap()
+        .target_mass_matrix()
+        .clone();
+    let ones_source = DVector::repeat(source.vertices().len(), 1.0);
+    let ones_target = DVector::repeat(target.vertices().len(), 1.0);
+    for k in 0..2 {
+        let source_component =
+            DVector::from_iterator(source.vertices().len(), u_source.iter().skip(k).step_by(2).copied());
+        let target_component =
+            DVector::from_iterator(target.vertices().len(), u_target.iter().skip(k).step_by(2).copied());
+        let source_integral = ones_source.dot(&(&source_mass * &source_component));
+        let target_integral = ones_target.dot(&(remap.target_mass_matrix() * &target_component));
+        assert_scalar_eq!(source_integral, target_integral, comp = abs, tol = 1e-12);
+    }
+
+    assert!(remap.remap(u_source.rows(0, 4), 2).is_err());
+}
+
+#[test]
+fn conservative_remap_2d_with_partial_overlap() {
+    // The target mesh extends beyond the source mesh, where the source field is zero
+    let source = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let target = create_rectangular_uniform_quad_mesh_2d::<f64>(0.5, 3, 2, 1, &vector![0.25, 1.0]);
+    let simplex_quadrature = quadrature::total_order::triangle(4).unwrap();
+    let mut remap = ConservativeRemap::from_meshes(&source, &target, &simplex_quadrature).unwrap();
+
+    let u_source = DVector::repeat(source.vertices().len(), 2.0);
+    let u_target = remap.remap(u_source.as_view(), 1).unwrap();
+    let ones_target = DVector::repeat(target.vertices().len(), 1.0);
+    let target_integral = ones_target.dot(&(remap.target_mass_matrix() * &u_target));
+    // The target mesh covers [0.25, 1.75] x [0, 1], so the overlap is [0.25, 1] x [0, 1]
+    assert_scalar_eq!(target_integral, 2.0 * 0.75, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn conservative_remap_3d_reproduces_linear_fields() {
+    let simplex_quadrature = quadrature::total_order::tetrahedron(2).unwrap();
+    let linear = |x: &Point3<f64>| 1.0 + x.x - 2.0 * x.y + 0.5 * x.z;
+    let nodal_values = |vertices: &[Point3<f64>]| DVector::from_iterator(vertices.len(), vertices.iter().map(linear));
+
+    let source = create_unit_box_uniform_tet_mesh_3d::<f64>(3);
+    let target = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    let intersections = mesh_intersection_quadrature(&source, &target, &simplex_quadrature).unwrap();
+    let volume: f64 = intersections
+        .iter()
+        .flat_map(|intersection| &intersection.quadrature.0)
+        .sum();
+    assert_scalar_eq!(volume, 1.0, comp = abs, tol = 1e-12);
+
+    let mut remap = ConservativeRemap::from_meshes(&source, &target, &simplex_quadrature).unwrap();
+    let u_target = remap
+        .remap(nodal_values(source.vertices()).as_view(), 1)
+        .unwrap();
+    assert_matrix_eq!(u_target, nodal_values(target.vertices()), comp = abs, tol = 1e-9);
+
+    // Remap from a hex mesh, whose trilinear basis contains the linear field
+    let hex_source = create_unit_box_uniform_hex_mesh_3d::<f64>(3);
+    let simplex_quadrature = quadrature::total_order::tetrahedron(5).unwrap();
+    let mut remap = ConservativeRemap::from_meshes(&hex_source, &target, &simplex_quadrature).unwrap();
+    let u_target = remap
+        .remap(nodal_values(hex_source.vertices()).as_view(), 1)
+        .unwrap();
+    assert_matrix_eq!(u_target, nodal_values(target.vertices()), comp = abs, tol = 1e-9);
+}
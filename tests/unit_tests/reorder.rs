@@ -1,6 +1,15 @@
-use fenris::mesh::reorder::{cuthill_mckee, reverse_cuthill_mckee};
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::reorder::{
+    bandwidth, cuthill_mckee, reorder_mesh, reorder_mesh_par, reorder_mesh_with, reverse_cuthill_mckee, Permutation,
+};
+use fenris::mesh::QuadMesh2d;
 use fenris::nalgebra_sparse::CsrMatrix;
-use nalgebra::DMatrix;
+use fenris::quadrature;
+use matrixcompare::assert_matrix_eq;
+use nalgebra::{DMatrix, DVector};
 
 #[test]
 fn cuthill_mckee_basic_examples() {
@@ -28,3 +37,79 @@ fn cuthill_mckee_basic_examples() {
 
     // TODO: Property-based tests
 }
+
+/// Returns a quad mesh whose vertices are numbered in a scattered order.
+fn scrambled_quad_mesh() -> QuadMesh2d<f64> {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(10);
+    let n = mesh.vertices().len();
+    // 121 vertices, and 37 is coprime to 121
+    let scramble = Permutation::from_vec((0..n).map(|i| (37 * i) % n).collect()).unwrap();
+    reorder_mesh_with(&mesh, |_| scramble).apply(&mesh)
+}
+
+#[test]
+fn reorder_mesh_reduces_bandwidth() {
+    let mesh = scrambled_quad_mesh();
+    let assembler = CsrAssembler::<f64>::default();
+    let original_bandwidth = bandwidth(&assembler.assemble_pattern(&mesh));
+
+    let permutation = reorder_mesh(&mesh);
+    assert_eq!(
+        permutation.vertex_permutation(),
+        reorder_mesh_par(&mesh).vertex_permutation()
+    );
+    let reordered = permutation.apply(&mesh);
+    let reordered_bandwidth = bandwidth(&assembler.assemble_pattern(&reordered));
+    // For an n x n grid, the optimal bandwidth is roughly n
+    assert!(reordered_bandwidth <= 2 * 11);
+    assert!(reordered_bandwidth < original_bandwidth);
+
+    // A custom ordering can be provided
+    let identity = reorder_mesh_with(&mesh, |pattern| {
+        Permutation::from_vec((0..pattern.major_dim()).collect()).unwrap()
+    });
+    assert_eq!(identity.apply(&mesh).vertices(), mesh.vertices());
+}
+
+#[test]
+fn reorder_mesh_nodal_vectors_can_be_restored() {
+    let mesh = scrambled_quad_mesh();
+    let permutation = reorder_mesh(&mesh);
+    let reordered = permutation.apply(&mesh);
+
+    let u = DVector::from_fn(2 * mesh.vertices().len(), |i, _| i as f64);
+    let u_reordered = permutation.permute_nodal_vector(&u, 2);
+    for (new_index, v) in reordered.vertices().iter().enumerate() {
+        let old_index = permutation.vertex_permutation().source_index(new_index);
+        assert_eq!(v, &mesh.vertices()[old_index]);
+        assert_eq!(u_reordered[2 * new_index], u[2 * old_index]);
+        assert_eq!(u_reordered[2 * new_index + 1], u[2 * old_index + 1]);
+    }
+    assert_eq!(permutation.restore_nodal_vector(&u_reordered, 2), u);
+
+    // A system assembled on the reordered mesh is equivalent to the original system
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let laplace_matrix = |mesh: &QuadMesh2d<f64>| {
+        let u = DVector::zeros(mesh.vertices().len());
+        let element_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(mesh)
+            .with_operator(&LaplaceOperator)
+            .with_quadrature_table(&qtable)
+            .with_u(&u)
+            .build();
+        CsrAssembler::default()
+            .assemble(&element_assembler)
+            .unwrap()
+    };
+    let a = laplace_matrix(&mesh);
+    let a_reordered = laplace_matrix(&reordered);
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let a_u = &a * &u;
+    let a_u_reordered = &a_reordered * permutation.permute_nodal_vector(&u, 1);
+    assert_matrix_eq!(
+        permutation.restore_nodal_vector(&a_u_reordered, 1),
+        a_u,
+        comp = abs,
+        tol = 1e-12
+    );
+}
@@ -0,0 +1,119 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::linear_solver::DenseLuSolver;
+use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
+use fenris::nalgebra::{point, vector, DVector, Point2, Vector1, Vector2};
+use fenris::quadrature;
+use fenris::space::{assemble_evaluation_matrix, interpolate_at_points, ScatteredDataFit, SpatiallyIndexed};
+use matrixcompare::assert_matrix_eq;
+
+fn sample_points(samples_per_dim: usize) -> Vec<Point2<f64>> {
+    let h = 1.0 / samples_per_dim as f64;
+    (0..samples_per_dim)
+        .flat_map(|i| (0..samples_per_dim).map(move |j| point![(i as f64 + 0.5) * h, (j as f64 + 0.5) * h]))
+        .collect()
+}
+
+#[test]
+fn evaluation_matrix_interpolates_at_points() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(3);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+    let mut points = sample_points(5);
+    // Points outside the domain are evaluated in the closest element
+    points.push(point![1.2, 0.3]);
+
+    let e = assemble_evaluation_matrix(&space, &points);
+    assert_eq!((e.nrows(), e.ncols()), (points.len(), mesh.vertices().len()));
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let mut expected = vec![Vector1::zeros(); points.len()];
+    interpolate_at_points(&space, &points, u.as_view(), &mut expected);
+    let expected = DVector::from_iterator(points.len(), expected.iter().map(|v| v.x));
+    assert_matrix_eq!(&e * &u, expected, comp = abs, tol = 1e-14);
+
+    // Partition of unity
+    let ones = DVector::repeat(mesh.vertices().len(), 1.0);
+    assert_matrix_eq!(&e * &ones, DVector::repeat(points.len(), 1.0), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn scattered_data_fit_reproduces_fields_in_space() {
+    // A linear vector field lies in the space, so the unregularized fit reproduces it exactly
+    // as long as the samples determine all weights
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+    let field = |x: &Point2<f64>| vector![1.0 + 2.0 * x.x - x.y, 3.0 * x.y];
+    let points = sample_points(8);
+    let values: Vec<Vector2<f64>> = points.iter().map(field).collect();
+
+    let weights = ScatteredDataFit::with_regularization(0.0)
+        .fit(&space, &points, &values)
+        .unwrap();
+    let expected = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|v| field(v).data.0[0]),
+    );
+    assert_matrix_eq!(weights, expected, comp = abs, tol = 1e-9);
+
+    let weights_lu = ScatteredDataFit::with_regularization(0.0)
+        .with_solver(DenseLuSolver)
+        .fit(&space, &points, &values)
+        .unwrap();
+    assert_matrix_eq!(weights_lu, expected, comp = abs, tol = 1e-12);
+
+    assert!(ScatteredDataFit::with_regularization(0.0)
+        .fit(&space, &points[..3], &values)
+        .is_err());
+}
+
+#[test]
+fn scattered_data_fit_with_laplace_regularization_fills_in_missing_data() {
+    // Only a few samples in the lower left corner, which do not determine all weights. With
+    // gradient regularization, constant data is nevertheless reproduced everywhere
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(4);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+    let points = vec![point![0.1, 0.1], point![0.2, 0.05], point![0.05, 0.2]];
+    let values = vec![Vector1::new(2.5); points.len()];
+
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let stiffness = CsrAssembler::default().assemble(&assembler).unwrap();
+
+    let weights = ScatteredDataFit::with_regularization(1e-3)
+        .with_regularization_matrix(&stiffness)
+        .fit(&space, &points, &values)
+        .unwrap();
+    assert_matrix_eq!(
+        weights,
+        DVector::repeat(mesh.vertices().len(), 2.5),
+        comp = abs,
+        tol = 1e-9
+    );
+
+    // Tikhonov regularization instead pulls the weights of unsampled nodes to zero
+    let weights = ScatteredDataFit::with_regularization(1e-3)
+        .fit(&space, &points, &values)
+        .unwrap();
+    assert!(weights.iter().any(|w| w.abs() < 1e-12));
+
+    let wrong_size = CsrAssembler::default()
+        .assemble(
+            &ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&create_unit_square_uniform_tri_mesh_2d::<f64>(1))
+                .with_operator(&LaplaceOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&DVector::zeros(4))
+                .build(),
+        )
+        .unwrap();
+    assert!(ScatteredDataFit::with_regularization(1.0)
+        .with_regularization_matrix(&wrong_size)
+        .fit(&space, &points, &values)
+        .is_err());
+}
@@ -0,0 +1,557 @@
+use fenris::assembly::global::{apply_homogeneous_dirichlet_bc_csr, CsrAssembler};
+use fenris::assembly::local::{ElementEllipticAssemblerBuilder, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::assembly::patch::PatchTopology;
+use fenris::mesh::partition::partition_elements_by_coordinate_bisection;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::refinement::UniformRefinementHierarchy;
+use fenris::mesh::{QuadMesh2d, TriangleMesh2d};
+use fenris::nalgebra::{DMatrix, DVector};
+use fenris::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use fenris::quadrature;
+use fenris::solver::chebyshev::{estimate_largest_eigenvalue, inverse_diagonal, ChebyshevSmoother};
+use fenris::solver::multigrid::GeometricMultigrid;
+use fenris::solver::schwarz::{overlapping_subdomain_elements, AdditiveSchwarzPreconditioner};
+use fenris::solver::smoothers::{
+    vertex_patch_blocks, BlockJacobiSmoother, GaussSeidelSmoother, JacobiSmoother, Smoother, VankaSmoother,
+};
+use fenris_sparse::cg::{ConjugateGradient, IdentityOperator, LinearOperator, RelativeResidualCriterion};
+use matrixcompare::assert_matrix_eq;
+
+/// Assembles the Laplace matrix with homogeneous Dirichlet boundary conditions.
+fn assemble_laplace_matrix(mesh: &QuadMesh2d<f64>) -> CsrMatrix<f64> {
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let mut a = CsrAssembler::default().assemble(&assembler).unwrap();
+    apply_homogeneous_dirichlet_bc_csr(&mut a, &mesh.find_boundary_vertices(), 1);
+    a
+}
+
+/// Assembles the Laplace matrix with homogeneous Dirichlet boundary conditions on a triangle mesh.
+fn assemble_triangle_laplace_matrix(mesh: &TriangleMesh2d<f64>) -> CsrMatrix<f64> {
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(1).unwrap());
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let mut a = CsrAssembler::default().assemble(&assembler).unwrap();
+    apply_homogeneous_dirichlet_bc_csr(&mut a, &mesh.find_boundary_vertices(), 1);
+    a
+}
+
+fn residual_norm(a: &CsrMatrix<f64>, x: &DVector<f64>, b: &DVector<f64>) -> f64 {
+    (b - a * x).norm()
+}
+
+#[test]
+fn block_jacobi_with_singleton_blocks_is_point_jacobi() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::from_fn(n, |i, _| (i as f64).sin());
+    let x0 = DVector::from_fn(n, |i, _| (i as f64).cos());
+
+    let blocks = (0..n).map(|i| vec![i]).collect();
+    let smoother = BlockJacobiSmoother::from_blocks(&a, blocks)
+        .unwrap()
+        .with_damping(0.8);
+    let mut x = x0.clone();
+    smoother.smooth(&a, (&mut x).into(), (&b).into());
+
+    let dense = DMatrix::from(&a);
+    let r = &b - &dense * &x0;
+    let expected = DVector::from_fn(n, |i, _| x0[i] + 0.8 * r[i] / dense[(i, i)]);
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn single_block_smoothers_solve_exactly() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::from_fn(n, |i, _| 1.0 + i as f64);
+    let expected = DMatrix::from(&a).lu().solve(&b).unwrap();
+
+    let jacobi = BlockJacobiSmoother::from_blocks(&a, vec![(0..n).collect()]).unwrap();
+    let mut x = DVector::zeros(n);
+    jacobi.smooth(&a, (&mut x).into(), (&b).into());
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+
+    let vanka = VankaSmoother::from_blocks(&a, vec![(0..n).rev().collect()]).unwrap();
+    let mut x = DVector::zeros(n);
+    vanka.smooth(&a, (&mut x).into(), (&b).into());
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn patch_smoothers_converge_for_poisson() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(6);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::from_fn(n, |i, _| ((i % 5) as f64) - 2.0);
+    let topology = PatchTopology::from_space(&mesh);
+    let blocks = vertex_patch_blocks(&topology, 1, |_| true);
+    assert!(blocks.iter().any(|block| block.len() > 1));
+
+    let vanka = VankaSmoother::from_blocks(&a, blocks.clone())
+        .unwrap()
+        .with_symmetric_sweeps(true);
+    let mut x = DVector::zeros(n);
+    let initial_residual = residual_norm(&a, &x, &b);
+    for _ in 0..100 {
+        vanka.smooth(&a, (&mut x).into(), (&b).into());
+    }
+    assert!(residual_norm(&a, &x, &b) < 1e-8 * initial_residual);
+
+    // Each degree of freedom belongs to at most 4 vertex patches
+    let jacobi = BlockJacobiSmoother::from_blocks(&a, blocks)
+        .unwrap()
+        .with_damping(0.25);
+    let mut x = DVector::zeros(n);
+    let mut previous_residual = residual_norm(&a, &x, &b);
+    for _ in 0..20 {
+        jacobi.smooth(&a, (&mut x).into(), (&b).into());
+        let residual = residual_norm(&a, &x, &b);
+        assert!(residual < previous_residual);
+        previous_residual = residual;
+    }
+
+    // Block Jacobi (without damping) is a symmetric preconditioner for CG
+    let mut x = DVector::zeros(n);
+    let output = ConjugateGradient::new()
+        .with_operator(&a)
+        .with_preconditioner(&jacobi)
+        .with_stopping_criterion(RelativeResidualCriterion::new(1e-10))
+        .solve_with_guess(&b, &mut x)
+        .unwrap();
+    assert!(output.num_iterations <= n);
+    assert!(residual_norm(&a, &x, &b) < 1e-8 * initial_residual);
+}
+
+#[test]
+fn vanka_converges_for_saddle_point_system() {
+    // Poisson problem with one averaging constraint per quadrant of the unit square,
+    // enforced with a Lagrange multiplier (playing the role of a pressure)
+    let mesh = create_unit_square_uniform_quad_mesh_2d(6);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let boundary = mesh.find_boundary_vertices();
+    let quadrant = |i: usize| {
+        let v = mesh.vertices()[i];
+        usize::from(v.x >= 0.5) + 2 * usize::from(v.y >= 0.5)
+    };
+
+    let mut coo = CooMatrix::new(n + 4, n + 4);
+    for (i, j, &v) in a.triplet_iter() {
+        coo.push(i, j, v);
+    }
+    let mut quadrant_nodes = vec![Vec::new(); 4];
+    for i in (0..n).filter(|i| !boundary.contains(i)) {
+        quadrant_nodes[quadrant(i)].push(i);
+        coo.push(n + quadrant(i), i, 1.0);
+        coo.push(i, n + quadrant(i), 1.0);
+    }
+    let k = CsrMatrix::from(&coo);
+    let rhs = DVector::from_fn(n + 4, |i, _| if i < n { 1.0 } else { 0.1 * i as f64 });
+
+    // Each block consists of the nodes in a quadrant together with its multiplier
+    let mut blocks: Vec<Vec<usize>> = quadrant_nodes
+        .into_iter()
+        .enumerate()
+        .map(|(q, mut nodes)| {
+            nodes.push(n + q);
+            nodes
+        })
+        .collect();
+    blocks.extend(boundary.iter().map(|&i| vec![i]));
+
+    // A block consisting only of a multiplier has a zero local matrix
+    assert!(VankaSmoother::from_blocks(&k, vec![vec![n]]).is_err());
+
+    let vanka = VankaSmoother::from_blocks(&k, blocks).unwrap();
+    let mut x = DVector::zeros(n + 4);
+    let initial_residual = residual_norm(&k, &x, &rhs);
+    for _ in 0..200 {
+        vanka.smooth(&k, (&mut x).into(), (&rhs).into());
+    }
+    assert!(residual_norm(&k, &x, &rhs) < 1e-8 * initial_residual);
+}
+
+#[test]
+fn block_smoothers_reject_invalid_blocks() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(2);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    assert!(BlockJacobiSmoother::from_blocks(&a, vec![vec![0, n]]).is_err());
+    assert!(BlockJacobiSmoother::from_blocks(&a, vec![vec![0, 1, 0]]).is_err());
+    assert!(VankaSmoother::from_blocks(&a, vec![vec![n]]).is_err());
+}
+
+#[test]
+fn power_iteration_estimates_largest_eigenvalue_of_1d_laplacian() {
+    let n = 20;
+    let mut coo = CooMatrix::new(n, n);
+    for i in 0..n {
+        coo.push(i, i, 4.0);
+        if i + 1 < n {
+            coo.push(i, i + 1, -2.0);
+            coo.push(i + 1, i, -2.0);
+        }
+    }
+    let a = CsrMatrix::from(&coo);
+    let d_inv = inverse_diagonal(&a).unwrap();
+    assert_matrix_eq!(d_inv, DVector::repeat(n, 0.25));
+
+    // The eigenvalues of D^{-1} A are 1 - cos(k pi / (n + 1)) for k = 1, ..., n
+    let exact = 1.0 + (std::f64::consts::PI / (n as f64 + 1.0)).cos();
+    let estimate = estimate_largest_eigenvalue(&a, d_inv.as_view(), 200).unwrap();
+    assert!(estimate <= exact + 1e-12);
+    assert!(estimate > 0.99 * exact);
+
+    let smoother = ChebyshevSmoother::from_matrix(&a).unwrap();
+    assert!(smoother.max_eigenvalue() > exact);
+    assert!(smoother.max_eigenvalue() < 1.1 * exact);
+}
+
+#[test]
+fn chebyshev_smoother_reduces_energy_error_for_poisson() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(8);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let x_exact = DVector::from_fn(n, |i, _| (0.7 * i as f64).sin());
+    let b = &a * &x_exact;
+    let energy_error = |x: &DVector<f64>| {
+        let e = x - &x_exact;
+        e.dot(&(&a * &e)).sqrt()
+    };
+
+    for degree in 1..=4 {
+        let smoother = ChebyshevSmoother::from_matrix(&a)
+            .unwrap()
+            .with_degree(degree);
+        let mut x = DVector::zeros(n);
+        let initial_error = energy_error(&x);
+        let mut previous_error = initial_error;
+        for _ in 0..10 {
+            smoother.smooth(&a, (&mut x).into(), (&b).into());
+            let error = energy_error(&x);
+            assert!(error < previous_error);
+            previous_error = error;
+        }
+        assert!(previous_error < 0.5 * initial_error);
+    }
+
+    // The condition number of D^{-1} A is about 13 for this mesh, so the default smoothing
+    // range covers the whole spectrum and the smoother converges quickly
+    let smoother = ChebyshevSmoother::from_matrix(&a).unwrap().with_degree(8);
+    let mut x = DVector::zeros(n);
+    for _ in 0..10 {
+        smoother.smooth(&a, (&mut x).into(), (&b).into());
+    }
+    assert!(energy_error(&x) < 1e-6 * energy_error(&DVector::zeros(n)));
+
+    // The matrix-free variant gives the same result
+    let dense = DMatrix::from(&a);
+    let x0 = DVector::from_fn(n, |i, _| (i as f64).cos());
+    let mut x_csr = x0.clone();
+    smoother.smooth(&a, (&mut x_csr).into(), (&b).into());
+    let mut x_operator = x0;
+    smoother
+        .smooth_with_operator(&dense, (&mut x_operator).into(), (&b).into())
+        .unwrap();
+    assert_matrix_eq!(x_csr, x_operator, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn chebyshev_smoother_rejects_invalid_input() {
+    let a = CsrMatrix::from(&DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 1.0, 0.0]));
+    assert!(ChebyshevSmoother::from_matrix(&a).is_err());
+
+    let a = CsrMatrix::from(&DMatrix::from_row_slice(1, 2, &[1.0, 1.0]));
+    assert!(inverse_diagonal(&a).is_err());
+
+    let a = CsrMatrix::<f64>::identity(3);
+    assert!(estimate_largest_eigenvalue(&a, DVector::repeat(3, 1.0).as_view(), 0).is_err());
+}
+
+#[test]
+fn overlapping_subdomains_grow_by_element_layers() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let topology = PatchTopology::from_space(&mesh);
+    // Left and right halves of the square
+    let partition: Vec<_> = (0..16)
+        .map(|element| usize::from(element % 4 >= 2))
+        .collect();
+
+    let subdomains = overlapping_subdomain_elements(&topology, &partition, 0).unwrap();
+    assert_eq!(subdomains[0], vec![0, 1, 4, 5, 8, 9, 12, 13]);
+    assert_eq!(subdomains[1], vec![2, 3, 6, 7, 10, 11, 14, 15]);
+
+    let subdomains = overlapping_subdomain_elements(&topology, &partition, 1).unwrap();
+    assert_eq!(subdomains[0], vec![0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14]);
+    assert_eq!(subdomains[1], vec![1, 2, 3, 5, 6, 7, 9, 10, 11, 13, 14, 15]);
+
+    let subdomains = overlapping_subdomain_elements(&topology, &partition, 2).unwrap();
+    assert_eq!(subdomains[0], (0..16).collect::<Vec<_>>());
+
+    assert!(overlapping_subdomain_elements(&topology, &partition[1..], 1).is_err());
+}
+
+#[test]
+fn additive_schwarz_with_single_subdomain_solves_exactly() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::from_fn(n, |i, _| 1.0 + i as f64);
+    let expected = DMatrix::from(&a).lu().solve(&b).unwrap();
+
+    let topology = PatchTopology::from_space(&mesh);
+    let partition = vec![0; topology.num_elements()];
+    let preconditioner =
+        AdditiveSchwarzPreconditioner::from_element_partition(&a, &topology, &partition, 1, 0).unwrap();
+    assert_eq!(preconditioner.num_subdomains(), 1);
+    let mut x = DVector::zeros(n);
+    preconditioner.apply((&mut x).into(), (&b).into()).unwrap();
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-10);
+}
+
+/// Computes the condition number of the preconditioned matrix M^{-1} A for SPD A and M^{-1}.
+fn preconditioned_condition_number(a: &CsrMatrix<f64>, preconditioner: &impl LinearOperator<f64>) -> f64 {
+    // With A = L L^T, the eigenvalues of M^{-1} A are the eigenvalues of the symmetric matrix L^T M^{-1} L
+    let l = DMatrix::from(a).cholesky().unwrap().unpack();
+    let n = a.nrows();
+    let mut m_inv_l = DMatrix::zeros(n, n);
+    for j in 0..n {
+        let mut y = DVector::zeros(n);
+        preconditioner.apply((&mut y).into(), l.column(j)).unwrap();
+        m_inv_l.set_column(j, &y);
+    }
+    let b = l.transpose() * m_inv_l;
+    let eigenvalues = (0.5 * (&b + b.transpose())).symmetric_eigenvalues();
+    eigenvalues.max() / eigenvalues.min()
+}
+
+#[test]
+fn additive_schwarz_preconditioners_accelerate_cg() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(16);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::repeat(n, 1.0);
+    let topology = PatchTopology::from_space(&mesh);
+    let partition = partition_elements_by_coordinate_bisection(&mesh, 16);
+
+    let one_level = AdditiveSchwarzPreconditioner::from_element_partition(&a, &topology, &partition, 1, 1).unwrap();
+    assert_eq!(one_level.num_subdomains(), 16);
+    assert!(!one_level.has_coarse_space());
+    let two_level = one_level.clone().with_nicolaides_coarse_space(&a).unwrap();
+    assert!(two_level.has_coarse_space());
+
+    let solve = |preconditioner: &dyn LinearOperator<f64>| {
+        let mut x = DVector::zeros(n);
+        let output = ConjugateGradient::new()
+            .with_operator(&a)
+            .with_preconditioner(preconditioner)
+            .with_stopping_criterion(RelativeResidualCriterion::new(1e-10))
+            .solve_with_guess(&b, &mut x)
+            .unwrap();
+        assert!(residual_norm(&a, &x, &b) < 1e-8 * b.norm());
+        output.num_iterations
+    };
+    let iterations = solve(&IdentityOperator);
+    assert!(solve(&one_level) < iterations);
+    assert!(solve(&two_level) < iterations);
+
+    // The coarse space improves the conditioning, as does additional overlap
+    let kappa_one_level = preconditioned_condition_number(&a, &one_level);
+    assert!(preconditioned_condition_number(&a, &two_level) < 0.8 * kappa_one_level);
+    let more_overlap = AdditiveSchwarzPreconditioner::from_element_partition(&a, &topology, &partition, 1, 2).unwrap();
+    assert!(preconditioned_condition_number(&a, &more_overlap) < kappa_one_level);
+}
+
+#[test]
+fn additive_schwarz_rejects_invalid_input() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(2);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let topology = PatchTopology::from_space(&mesh);
+
+    assert!(AdditiveSchwarzPreconditioner::from_subdomain_dofs(&a, vec![vec![0, n]], 1).is_err());
+    assert!(AdditiveSchwarzPreconditioner::from_subdomain_dofs(&a, vec![vec![0, 0]], 1).is_err());
+    assert!(AdditiveSchwarzPreconditioner::from_element_partition(&a, &topology, &[0; 4], 2, 0).is_err());
+
+    // Indefinite local matrix
+    let indefinite = CsrMatrix::from(&DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]));
+    assert!(AdditiveSchwarzPreconditioner::from_subdomain_dofs(&indefinite, vec![vec![0, 1]], 1).is_err());
+
+    let preconditioner = AdditiveSchwarzPreconditioner::from_subdomain_dofs(&a, vec![(0..n).collect()], 1).unwrap();
+    let prolongation = CsrMatrix::from(&DMatrix::<f64>::zeros(n + 1, 1));
+    assert!(preconditioner
+        .clone()
+        .with_coarse_space(&a, prolongation)
+        .is_err());
+    // A zero coarse space gives a singular coarse matrix
+    let prolongation = CsrMatrix::from(&DMatrix::<f64>::zeros(n, 1));
+    assert!(preconditioner.with_coarse_space(&a, prolongation).is_err());
+}
+
+#[test]
+fn point_smoothers_converge_for_poisson() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+    let a = assemble_laplace_matrix(&mesh);
+    let n = a.nrows();
+    let b = DVector::from_fn(n, |i, _| (i as f64).sin());
+    let x0 = DVector::from_fn(n, |i, _| (i as f64).cos());
+
+    // Point Jacobi agrees with block Jacobi with singleton blocks
+    let jacobi = JacobiSmoother::from_matrix(&a).unwrap().with_damping(0.8);
+    let block_jacobi = BlockJacobiSmoother::from_blocks(&a, (0..n).map(|i| vec![i]).collect())
+        .unwrap()
+        .with_damping(0.8);
+    let mut x = x0.clone();
+    let mut expected = x0.clone();
+    jacobi.smooth(&a, (&mut x).into(), (&b).into());
+    block_jacobi.smooth(&a, (&mut expected).into(), (&b).into());
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+
+    // Gauss-Seidel agrees with Vanka with singleton blocks, also for the adjoint sweep
+    let gauss_seidel = GaussSeidelSmoother::from_matrix(&a).unwrap();
+    let vanka = VankaSmoother::from_blocks(&a, (0..n).map(|i| vec![i]).collect()).unwrap();
+    let mut x = x0.clone();
+    let mut expected = x0.clone();
+    gauss_seidel.smooth_adjoint(&a, (&mut x).into(), (&b).into());
+    vanka.smooth_adjoint(&a, (&mut expected).into(), (&b).into());
+    assert_matrix_eq!(x, expected, comp = abs, tol = 1e-12);
+
+    let symmetric = gauss_seidel.with_symmetric_sweeps(true);
+    let mut x = DVector::zeros(n);
+    let initial_residual = residual_norm(&a, &x, &b);
+    for _ in 0..100 {
+        symmetric.smooth(&a, (&mut x).into(), (&b).into());
+    }
+    assert!(residual_norm(&a, &x, &b) < 1e-8 * initial_residual);
+}
+
+#[test]
+fn multigrid_converges_independently_of_mesh_size() {
+    let coarse_mesh = create_unit_square_uniform_tri_mesh_2d(2);
+    let cycles_for_levels = |num_refinements: usize| {
+        let hierarchy = UniformRefinementHierarchy::new(coarse_mesh.clone(), num_refinements);
+        let a = assemble_triangle_laplace_matrix(hierarchy.finest_mesh());
+        let n = a.nrows();
+        let b = DVector::repeat(n, 1.0);
+
+        let jacobi = GeometricMultigrid::from_hierarchy(a.clone(), &hierarchy, 1, |a| {
+            Ok(JacobiSmoother::from_matrix(a)?.with_damping(2.0 / 3.0))
+        })
+        .unwrap()
+        .with_smoothing_steps(2, 2);
+        assert_eq!(jacobi.num_levels(), num_refinements + 1);
+        assert_eq!(jacobi.num_dofs(), n);
+        assert_eq!(jacobi.level_matrix(0).nrows(), coarse_mesh.vertices().len());
+        let gauss_seidel =
+            GeometricMultigrid::from_hierarchy(a.clone(), &hierarchy, 1, GaussSeidelSmoother::from_matrix).unwrap();
+        let chebyshev =
+            GeometricMultigrid::from_hierarchy(a.clone(), &hierarchy, 1, ChebyshevSmoother::from_matrix).unwrap();
+
+        let mut cycles = Vec::new();
+        for solver in [&jacobi as &dyn LinearOperator<f64>, &gauss_seidel, &chebyshev] {
+            // Use the preconditioner as a standalone solver: x <- x + M^{-1} (b - A x)
+            let mut x = DVector::zeros(n);
+            let mut num_cycles = 0;
+            while residual_norm(&a, &x, &b) > 1e-8 * b.norm() {
+                let mut correction = DVector::zeros(n);
+                solver
+                    .apply((&mut correction).into(), (&(&b - &a * &x)).into())
+                    .unwrap();
+                x += correction;
+                num_cycles += 1;
+                assert!(num_cycles <= 30);
+            }
+            cycles.push(num_cycles);
+        }
+
+        let mut x = DVector::zeros(n);
+        let num_cycles = gauss_seidel
+            .solve((&mut x).into(), (&b).into(), 1e-8, 30)
+            .unwrap();
+        assert_eq!(num_cycles, cycles[1]);
+        assert!(residual_norm(&a, &x, &b) <= 1e-8 * b.norm());
+        cycles
+    };
+
+    let cycles_coarse = cycles_for_levels(3);
+    let cycles_fine = cycles_for_levels(4);
+    for (&coarse, &fine) in cycles_coarse.iter().zip(&cycles_fine) {
+        assert!(fine <= coarse + 1, "{cycles_coarse:?} vs {cycles_fine:?}");
+    }
+}
+
+#[test]
+fn multigrid_preconditioner_accelerates_cg() {
+    let hierarchy = UniformRefinementHierarchy::new(create_unit_square_uniform_tri_mesh_2d(2), 4);
+    let a = assemble_triangle_laplace_matrix(hierarchy.finest_mesh());
+    let n = a.nrows();
+    let b = DVector::repeat(n, 1.0);
+    let multigrid =
+        GeometricMultigrid::from_hierarchy(a.clone(), &hierarchy, 1, ChebyshevSmoother::from_matrix).unwrap();
+
+    let solve = |preconditioner: &dyn LinearOperator<f64>| {
+        let mut x = DVector::zeros(n);
+        let output = ConjugateGradient::new()
+            .with_operator(&a)
+            .with_preconditioner(preconditioner)
+            .with_stopping_criterion(RelativeResidualCriterion::new(1e-10))
+            .solve_with_guess(&b, &mut x)
+            .unwrap();
+        assert!(residual_norm(&a, &x, &b) < 1e-8 * b.norm());
+        output.num_iterations
+    };
+    let iterations = solve(&IdentityOperator);
+    let preconditioned_iterations = solve(&multigrid);
+    assert!(preconditioned_iterations < iterations / 4);
+    assert!(preconditioned_iterations < iterations / 4);
+}
+
+#[test]
+fn multigrid_rejects_invalid_input() {
+    let hierarchy = UniformRefinementHierarchy::new(create_unit_square_uniform_tri_mesh_2d(2), 1);
+    let coarse = assemble_triangle_laplace_matrix(hierarchy.mesh(0));
+    let fine = assemble_triangle_laplace_matrix(hierarchy.mesh(1));
+    let prolongation = hierarchy.prolongation(0, 1);
+
+    // Prolongation does not map to the finest level
+    assert!(
+        GeometricMultigrid::from_prolongations(coarse, vec![prolongation.clone()], JacobiSmoother::from_matrix)
+            .is_err()
+    );
+    // Smoother creation fails
+    let failing = |_: &CsrMatrix<f64>| -> eyre::Result<JacobiSmoother<f64>> { Err(eyre::eyre!("No smoother")) };
+    assert!(GeometricMultigrid::from_prolongations(fine.clone(), vec![prolongation.clone()], failing).is_err());
+    // A single level is solved directly
+    let direct = GeometricMultigrid::from_prolongations(fine.clone(), vec![], JacobiSmoother::from_matrix).unwrap();
+    assert_eq!(direct.num_levels(), 1);
+    let b = DVector::repeat(fine.nrows(), 1.0);
+    let mut x = DVector::zeros(fine.nrows());
+    assert_eq!(
+        direct
+            .solve((&mut x).into(), (&b).into(), 1e-10, 1)
+            .unwrap(),
+        1
+    );
+    // Not converging within the cycle limit
+    let two_level =
+        GeometricMultigrid::from_prolongations(fine, vec![prolongation], JacobiSmoother::from_matrix).unwrap();
+    let mut x = DVector::zeros(b.len());
+    assert!(two_level
+        .solve((&mut x).into(), (&b).into(), 1e-14, 1)
+        .is_err());
+}
@@ -1,9 +1,13 @@
 use fenris::element::{ElementConnectivity, FiniteElement};
 use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
 use fenris::mesh::TriangleMesh2d;
-use fenris::space::{FindClosestElement, FiniteElementSpace, SpatiallyIndexed};
+use fenris::space::{
+    interpolate_at_points, par_interpolate_at_points, FindClosestElement, FiniteElementSpace, OutsidePointPolicy,
+    SpatiallyIndexed,
+};
+use itertools::izip;
 use matrixcompare::assert_matrix_eq;
-use nalgebra::Point2;
+use nalgebra::{DVector, DVectorView, Matrix2, Point2, Vector2, U2};
 
 #[test]
 fn spatially_indexed_closest_element_at_interfaces() {
@@ -31,3 +35,68 @@ fn spatially_indexed_closest_element_at_interfaces() {
         }
     }
 }
+
+#[test]
+fn par_interpolate_at_points_reproduces_linear_field() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let space = SpatiallyIndexed::from_space(mesh);
+    // u(x) = A x + b is reproduced exactly by linear elements
+    let a = Matrix2::new(1.0, -2.0, 0.5, 3.0);
+    let b = Vector2::new(0.25, -1.0);
+    let u_exact = |x: &Point2<f64>| a * x.coords + b;
+    let u = DVector::from_iterator(
+        2 * space.space().vertices().len(),
+        space
+            .space()
+            .vertices()
+            .iter()
+            .flat_map(|x| u_exact(x).iter().copied().collect::<Vec<_>>()),
+    );
+
+    let points: Vec<_> = (0..50)
+        .map(|i| Point2::new((0.37 * i as f64).fract(), (0.61 * i as f64 + 0.1).fract()))
+        .collect();
+    let results = par_interpolate_at_points::<_, U2, _>(&space, &points, &u, OutsidePointPolicy::Error).unwrap();
+    let mut sequential = vec![Vector2::zeros(); points.len()];
+    interpolate_at_points(&space, &points, DVectorView::from(&u), &mut sequential);
+    for (point, result, expected) in izip!(&points, &results, &sequential) {
+        let result = result.as_ref().unwrap();
+        assert!(result.is_inside);
+        assert_matrix_eq!(result.value, u_exact(point), comp = abs, tol = 1e-12);
+        assert_matrix_eq!(result.value, expected, comp = abs, tol = 1e-12);
+        // The gradient is stored as ∇u with one column per component, i.e. A^T
+        assert_matrix_eq!(result.gradient, a.transpose(), comp = abs, tol = 1e-12);
+        assert_matrix_eq!(
+            space
+                .map_element_reference_coords(result.element_index, &result.reference_coords)
+                .coords,
+            point.coords,
+            comp = abs,
+            tol = 1e-12
+        );
+    }
+
+    // Points outside the mesh
+    let outside = [Point2::new(0.5, 0.5), Point2::new(1.5, 0.25)];
+    let nearest =
+        par_interpolate_at_points::<_, U2, _>(&space, &outside, &u, OutsidePointPolicy::NearestElement).unwrap();
+    assert!(nearest[0].as_ref().unwrap().is_inside);
+    let extrapolated = nearest[1].as_ref().unwrap();
+    assert!(!extrapolated.is_inside);
+    assert_matrix_eq!(
+        extrapolated.value,
+        u_exact(&Point2::new(1.0, 0.25)),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let skipped = par_interpolate_at_points::<_, U2, _>(&space, &outside, &u, OutsidePointPolicy::Skip).unwrap();
+    assert!(skipped[0].is_some());
+    assert!(skipped[1].is_none());
+
+    let err = par_interpolate_at_points::<_, U2, _>(&space, &outside, &u, OutsidePointPolicy::Error).unwrap_err();
+    assert!(err.to_string().contains("Point 1"));
+
+    let u_wrong = DVector::zeros(u.len() - 1);
+    assert!(par_interpolate_at_points::<_, U2, _>(&space, &points, &u_wrong, OutsidePointPolicy::default()).is_err());
+}
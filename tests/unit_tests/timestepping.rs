@@ -0,0 +1,264 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, UniformQuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::linear_solver::{ConjugateGradientSolver, DenseLuSolver};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{dvector, DMatrix, DVector, DVectorView, DVectorViewMut};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris::timestepping::{
+    FirstOrderState, FirstOrderSystem, GeneralizedAlpha, LinearFirstOrderSystem, LinearSecondOrderSystem, Newmark,
+    SecondOrderState, ThetaMethod,
+};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn csr(matrix: DMatrix<f64>) -> CsrMatrix<f64> {
+    CsrMatrix::from(&matrix)
+}
+
+fn scalar(value: f64) -> CsrMatrix<f64> {
+    csr(DMatrix::from_element(1, 1, value))
+}
+
+/// Integrates the system from t = 0 to t = t_end with `num_steps` steps of the theta method.
+fn integrate_first_order(
+    method: &ThetaMethod<f64>,
+    system: &mut impl FirstOrderSystem<f64>,
+    u0: DVector<f64>,
+    t_end: f64,
+    num_steps: usize,
+) -> FirstOrderState<f64> {
+    let mut state = FirstOrderState { t: 0.0, u: u0 };
+    let dt = t_end / num_steps as f64;
+    for _ in 0..num_steps {
+        method
+            .step(system, &mut state, dt, &mut DenseLuSolver)
+            .unwrap();
+    }
+    state
+}
+
+#[test]
+fn theta_method_amplification_factors() {
+    let (lambda, dt) = (3.0, 0.1);
+    let (m, k) = (scalar(2.0), scalar(2.0 * lambda));
+    let mut system = LinearFirstOrderSystem::new(&m, &k);
+    for (method, factor) in [
+        (ThetaMethod::backward_euler(), 1.0 / (1.0 + lambda * dt)),
+        (
+            ThetaMethod::trapezoidal(),
+            (1.0 - 0.5 * lambda * dt) / (1.0 + 0.5 * lambda * dt),
+        ),
+    ] {
+        let state = integrate_first_order(&method, &mut system, dvector![1.0], 5.0 * dt, 5);
+        assert_scalar_eq!(state.t, 0.5, comp = abs, tol = 1e-14);
+        assert_scalar_eq!(state.u[0], factor.powi(5), comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn theta_method_convergence_with_load() {
+    // u' + u = cos(t), u(0) = 0 has the solution u = (cos t + sin t - exp(-t)) / 2
+    let exact = |t: f64| 0.5 * (t.cos() + t.sin() - (-t).exp());
+    let (m, k) = (scalar(1.0), scalar(1.0));
+    let mut system = LinearFirstOrderSystem::new(&m, &k).with_load(|mut f: DVectorViewMut<f64>, t: f64| {
+        f[0] += t.cos();
+    });
+    let error = |method: &ThetaMethod<f64>, num_steps, system: &mut _| {
+        let state = integrate_first_order(method, system, dvector![0.0], 2.0, num_steps);
+        (state.u[0] - exact(2.0)).abs()
+    };
+    for (method, expected_order) in [(ThetaMethod::backward_euler(), 1), (ThetaMethod::trapezoidal(), 2)] {
+        let ratio = error(&method, 40, &mut system) / error(&method, 80, &mut system);
+        assert_scalar_eq!(ratio.log2(), expected_order as f64, comp = abs, tol = 0.05);
+    }
+}
+
+/// The first-order system u' + u^3 = 0.
+struct CubicDecay {
+    mass: CsrMatrix<f64>,
+}
+
+impl FirstOrderSystem<f64> for CubicDecay {
+    fn mass_matrix(&self) -> &CsrMatrix<f64> {
+        &self.mass
+    }
+
+    fn compute_residual(&mut self, mut output: DVectorViewMut<f64>, _t: f64, u: DVectorView<f64>) -> eyre::Result<()> {
+        output[0] = u[0].powi(3);
+        Ok(())
+    }
+
+    fn compute_jacobian(&mut self, _t: f64, u: DVectorView<f64>) -> eyre::Result<CsrMatrix<f64>> {
+        Ok(scalar(3.0 * u[0].powi(2)))
+    }
+}
+
+#[test]
+fn theta_method_solves_nonlinear_systems() {
+    // u' = -u^3, u(0) = 1 has the solution u = 1 / sqrt(1 + 2t)
+    let mut system = CubicDecay { mass: scalar(1.0) };
+    let mut state = FirstOrderState {
+        t: 0.0,
+        u: dvector![1.0],
+    };
+    let method = ThetaMethod::trapezoidal();
+    let dt = 0.01;
+    for _ in 0..100 {
+        let info = method
+            .step(&mut system, &mut state, dt, &mut DenseLuSolver)
+            .unwrap();
+        assert!(info.newton_iterations > 1);
+    }
+    assert_scalar_eq!(state.u[0], 1.0 / 3.0f64.sqrt(), comp = abs, tol = 1e-5);
+}
+
+#[test]
+fn initial_acceleration_satisfies_equations_of_motion() {
+    let m = csr(DMatrix::from_row_slice(2, 2, &[2.0, 1.0, 1.0, 2.0]));
+    let k = csr(DMatrix::from_row_slice(2, 2, &[3.0, -1.0, -1.0, 3.0]));
+    let c = csr(DMatrix::from_row_slice(2, 2, &[0.5, 0.0, 0.0, 0.25]));
+    let mut system = LinearSecondOrderSystem::new(&m, &k)
+        .with_damping(&c)
+        .with_load(|mut f: DVectorViewMut<f64>, t: f64| f[1] += 1.0 + t);
+    let (u, v) = (dvector![1.0, -2.0], dvector![0.5, 0.5]);
+    let state =
+        SecondOrderState::from_initial_conditions(&mut system, &mut DenseLuSolver, 1.0, u.clone(), v.clone()).unwrap();
+    let residual = &m * &state.a + &c * &v + &k * &u - dvector![0.0, 2.0];
+    assert_matrix_eq!(residual, DVector::zeros(2), comp = abs, tol = 1e-14);
+}
+
+/// Integrates the undamped oscillator u'' + omega^2 u = 0 with u(0) = 1, v(0) = 0 and returns
+/// the final state.
+fn integrate_oscillator(
+    step: impl Fn(&mut LinearSecondOrderSystem<f64>, &mut SecondOrderState<f64>, f64),
+    omega: f64,
+    t_end: f64,
+    num_steps: usize,
+) -> SecondOrderState<f64> {
+    let (m, k) = (scalar(1.0), scalar(omega * omega));
+    let mut system = LinearSecondOrderSystem::new(&m, &k);
+    let mut state =
+        SecondOrderState::from_initial_conditions(&mut system, &mut DenseLuSolver, 0.0, dvector![1.0], dvector![0.0])
+            .unwrap();
+    let dt = t_end / num_steps as f64;
+    for _ in 0..num_steps {
+        step(&mut system, &mut state, dt);
+    }
+    state
+}
+
+fn oscillator_energy(state: &SecondOrderState<f64>, omega: f64) -> f64 {
+    0.5 * state.v[0].powi(2) + 0.5 * omega.powi(2) * state.u[0].powi(2)
+}
+
+#[test]
+fn newmark_average_acceleration_conserves_energy_and_converges() {
+    let method = Newmark::average_acceleration();
+    assert_eq!((method.beta(), method.gamma()), (0.25, 0.5));
+    let step = |system: &mut LinearSecondOrderSystem<f64>, state: &mut SecondOrderState<f64>, dt: f64| {
+        method.step(system, state, dt, &mut DenseLuSolver).unwrap();
+    };
+    let omega = 2.0;
+    let state = integrate_oscillator(step, omega, 3.0, 50);
+    assert_scalar_eq!(state.t, 3.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(
+        oscillator_energy(&state, omega),
+        0.5 * omega * omega,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let error = |num_steps| (integrate_oscillator(step, omega, 3.0, num_steps).u[0] - (omega * 3.0).cos()).abs();
+    assert_scalar_eq!((error(100) / error(200)).log2(), 2.0, comp = abs, tol = 0.05);
+}
+
+#[test]
+fn generalized_alpha_converges_and_damps_high_frequencies() {
+    // Without dissipation, generalized-alpha reduces to the average acceleration method
+    let method = GeneralizedAlpha::from_spectral_radius(1.0);
+    assert_scalar_eq!(method.alpha_m(), 0.5, comp = abs, tol = 1e-15);
+    assert_scalar_eq!(method.alpha_f(), 0.5, comp = abs, tol = 1e-15);
+    assert_scalar_eq!(method.beta(), 0.25, comp = abs, tol = 1e-15);
+    assert_scalar_eq!(method.gamma(), 0.5, comp = abs, tol = 1e-15);
+
+    let omega = 2.0;
+    let newmark_step = |system: &mut LinearSecondOrderSystem<f64>, state: &mut SecondOrderState<f64>, dt: f64| {
+        Newmark::average_acceleration()
+            .step(system, state, dt, &mut DenseLuSolver)
+            .unwrap();
+    };
+    let alpha_step = |rho| {
+        move |system: &mut LinearSecondOrderSystem<f64>, state: &mut SecondOrderState<f64>, dt: f64| {
+            GeneralizedAlpha::from_spectral_radius(rho)
+                .step(system, state, dt, &mut DenseLuSolver)
+                .unwrap();
+        }
+    };
+    let expected = integrate_oscillator(newmark_step, omega, 3.0, 50);
+    let state = integrate_oscillator(alpha_step(1.0), omega, 3.0, 50);
+    assert_matrix_eq!(state.u, expected.u, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(state.v, expected.v, comp = abs, tol = 1e-12);
+
+    // Second order convergence with dissipation
+    let error =
+        |num_steps| (integrate_oscillator(alpha_step(0.5), omega, 3.0, num_steps).u[0] - (omega * 3.0).cos()).abs();
+    assert_scalar_eq!((error(200) / error(400)).log2(), 2.0, comp = abs, tol = 0.1);
+
+    // Highly under-resolved oscillations are damped, but only with dissipation
+    let omega = 1e4;
+    let undamped = integrate_oscillator(alpha_step(1.0), omega, 1.0, 10);
+    let initial_energy = 0.5 * omega * omega;
+    let relative_energy = |state| oscillator_energy(state, omega) / initial_energy;
+    assert_scalar_eq!(relative_energy(&undamped), 1.0, comp = abs, tol = 1e-8);
+    let damped = integrate_oscillator(alpha_step(0.0), omega, 1.0, 10);
+    assert!(relative_energy(&damped) < 1e-6);
+}
+
+#[test]
+fn heat_equation_with_assembled_matrices() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(8);
+    let gauss = quadrature::tensor::quadrilateral_gauss(2);
+    let mass_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss.clone(), Density(1.0));
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_table);
+    let m = CsrAssembler::default().assemble(&mass_assembler).unwrap();
+    let u0 = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(|p| p.x * (1.0 - p.y)));
+    let laplace_table = UniformQuadratureTable::from_quadrature_and_uniform_data(gauss, ());
+    let zero = DVector::zeros(u0.len());
+    let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&laplace_table)
+        .with_u(&zero)
+        .build();
+    let k = CsrAssembler::default()
+        .assemble(&stiffness_assembler)
+        .unwrap();
+    let mut system = LinearFirstOrderSystem::new(&m, &k);
+
+    let method = ThetaMethod::trapezoidal();
+    let mut lu_state = FirstOrderState { t: 0.0, u: u0.clone() };
+    let mut cg_state = lu_state.clone();
+    let mut cg = ConjugateGradientSolver::with_tolerance(1e-12);
+    for _ in 0..10 {
+        method
+            .step(&mut system, &mut lu_state, 0.01, &mut DenseLuSolver)
+            .unwrap();
+        method
+            .step(&mut system, &mut cg_state, 0.01, &mut cg)
+            .unwrap();
+    }
+    assert_matrix_eq!(cg_state.u, lu_state.u, comp = abs, tol = 1e-9);
+
+    // With homogeneous Neumann boundary conditions, the total heat is conserved
+    let ones = DVector::repeat(u0.len(), 1.0);
+    assert_scalar_eq!(
+        ones.dot(&(&m * &lu_state.u)),
+        ones.dot(&(&m * &u0)),
+        comp = abs,
+        tol = 1e-12
+    );
+}
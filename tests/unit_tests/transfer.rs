@@ -0,0 +1,83 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::nalgebra::{DVector, Point2};
+use fenris::quadrature;
+use fenris::space::{ConservativeRemap, FieldTransfer, SpatiallyIndexed, TransferMethod};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn linear_vector_field(x: &Point2<f64>) -> [f64; 2] {
+    [2.0 * x.x - x.y + 0.5, 0.25 * x.x + 3.0 * x.y]
+}
+
+fn nodal_weights(vertices: &[Point2<f64>], f: impl Fn(&Point2<f64>) -> [f64; 2]) -> DVector<f64> {
+    DVector::from_iterator(2 * vertices.len(), vertices.iter().flat_map(f))
+}
+
+#[test]
+fn field_transfer_interpolation_reproduces_linear_fields() {
+    let source = SpatiallyIndexed::from_space(create_unit_square_uniform_tri_mesh_2d::<f64>(5));
+    let target = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+
+    let mut transfer = FieldTransfer::interpolation(&source, target.vertices());
+    assert_eq!(transfer.method(), TransferMethod::Interpolation);
+    assert_eq!(transfer.num_source_nodes(), source.space().vertices().len());
+    assert_eq!(transfer.num_target_nodes(), target.vertices().len());
+
+    let u_source = nodal_weights(source.space().vertices(), linear_vector_field);
+    let u_target = transfer.transfer(&u_source, 2).unwrap();
+    let u_expected = nodal_weights(target.vertices(), linear_vector_field);
+    assert_matrix_eq!(u_target, u_expected, comp = abs, tol = 1e-12);
+
+    assert!(transfer.transfer(&u_source, 1).is_err());
+}
+
+#[test]
+fn field_transfer_l2_projection_reproduces_linear_fields() {
+    let source = SpatiallyIndexed::from_space(create_unit_square_uniform_tri_mesh_2d::<f64>(5));
+    let target = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    let mut transfer = FieldTransfer::l2_projection(&source, &target, &qtable).unwrap();
+    assert_eq!(transfer.method(), TransferMethod::L2Projection);
+    let u_source = nodal_weights(source.space().vertices(), linear_vector_field);
+    let u_target = transfer.transfer(&u_source, 2).unwrap();
+    let u_expected = nodal_weights(target.vertices(), linear_vector_field);
+    assert_matrix_eq!(u_target, u_expected, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn field_transfer_l2_projection_approximates_conservative_remap() {
+    let source_mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(7);
+    let target = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+    let f = |x: &Point2<f64>| [(3.0 * x.x).sin() * x.y, x.x * x.x * x.y];
+    let u_source = nodal_weights(source_mesh.vertices(), f);
+
+    // Exact integration over the intersections of source and target cells
+    let simplex_quadrature = quadrature::total_order::triangle(2).unwrap();
+    let remap = ConservativeRemap::from_meshes(&source_mesh, &target, &simplex_quadrature).unwrap();
+    let mut exact_transfer = FieldTransfer::from(remap.clone());
+    let u_exact = exact_transfer.transfer(&u_source, 2).unwrap();
+    assert_matrix_eq!(
+        u_exact,
+        remap.clone().remap(u_source.as_view(), 2).unwrap(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // Approximate integration with a high-order target quadrature
+    let source = SpatiallyIndexed::from_space(source_mesh);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(8));
+    let mut transfer = FieldTransfer::l2_projection(&source, &target, &qtable).unwrap();
+    let u_target = transfer.transfer(&u_source, 2).unwrap();
+    assert_matrix_eq!(u_target, u_exact, comp = abs, tol = 1e-2);
+
+    // Both transfers approximately preserve the integral of the field
+    let mass = remap.target_mass_matrix();
+    let integral = |u: &DVector<f64>, k: usize| -> f64 {
+        let component = DVector::from_iterator(target.vertices().len(), u.iter().skip(k).step_by(2).copied());
+        (mass * component).sum()
+    };
+    for k in 0..2 {
+        assert_scalar_eq!(integral(&u_target, k), integral(&u_exact, k), comp = abs, tol = 1e-3);
+    }
+}